@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use ambient_network::{
+    admin::{AdminCommand, AdminResponse},
+    thumbnail::ThumbnailOptions,
+};
+use anyhow::Context;
+use clap::Subcommand;
+
+use crate::server::ADMIN_INTERFACE_PORT;
+
+#[derive(Subcommand, Clone)]
+pub enum AdminCli {
+    /// List the connected players
+    Players,
+    /// Disconnect a player
+    Kick { user_id: String },
+    /// Disconnect a player, and refuse their connections until they're unbanned or the server restarts
+    Ban { user_id: String },
+    /// Allow a banned player to connect again
+    Unban { user_id: String },
+    /// Send a message to every world instance, as an `ADMIN_MESSAGE` event
+    Broadcast { message: String },
+    /// List the world instances
+    Instances,
+    /// List the loaded packages
+    Packages,
+    /// Load a built package into the main instance; the path is on the server's machine
+    Load { path: PathBuf },
+    /// Unload a package from the main instance
+    Unload { id: String },
+    /// Render the main instance to a PNG on the server's machine, for the project's thumbnail
+    Thumbnail {
+        /// The id of the camera entity to render from; defaults to the active global camera
+        #[arg(long)]
+        camera: Option<String>,
+        #[arg(long, default_value_t = 512)]
+        width: u32,
+        #[arg(long, default_value_t = 512)]
+        height: u32,
+        /// Where to save the thumbnail on the server's machine; defaults to `thumbnail.png` in the server's build directory
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+}
+impl AdminCli {
+    fn command(&self) -> AdminCommand {
+        match self.clone() {
+            AdminCli::Players => AdminCommand::ListPlayers,
+            AdminCli::Kick { user_id } => AdminCommand::Kick { user_id },
+            AdminCli::Ban { user_id } => AdminCommand::Ban { user_id },
+            AdminCli::Unban { user_id } => AdminCommand::Unban { user_id },
+            AdminCli::Broadcast { message } => AdminCommand::Broadcast { message },
+            AdminCli::Instances => AdminCommand::ListInstances,
+            AdminCli::Packages => AdminCommand::ListPackages,
+            AdminCli::Load { path } => {
+                // Relative paths are most likely relative to where this is run, which is usually the server's machine
+                let path = std::fs::canonicalize(&path).unwrap_or(path);
+                AdminCommand::LoadPackage { path: path.to_string_lossy().to_string() }
+            }
+            AdminCli::Unload { id } => AdminCommand::UnloadPackage { id },
+            AdminCli::Thumbnail { camera, width, height, path } => AdminCommand::RenderThumbnail(ThumbnailOptions {
+                camera,
+                width,
+                height,
+                // Like for `load`, relative paths are made relative to where this is run
+                path: path.map(|path| std::env::current_dir().map_or(path.clone(), |dir| dir.join(&path)).to_string_lossy().to_string()),
+            }),
+        }
+    }
+}
+
+/// Sends the command to the server at `host`, and prints the result
+pub async fn run(host: &str, token: &str, command: &AdminCli) -> anyhow::Result<()> {
+    let host = if host.contains(':') { host.to_string() } else { format!("{host}:{ADMIN_INTERFACE_PORT}") };
+    let result: Result<AdminResponse, String> = reqwest::Client::new()
+        .post(format!("http://{host}/admin"))
+        .bearer_auth(token)
+        .json(&command.command())
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach the server at {host}"))?
+        .json()
+        .await
+        .context("Invalid response from the server; is the admin token set?")?;
+
+    match result.map_err(anyhow::Error::msg)? {
+        AdminResponse::Done => println!("Done"),
+        AdminResponse::Players(players) => {
+            for player in players {
+                println!("{} (instance {})", player.user_id, player.instance);
+            }
+        }
+        AdminResponse::Instances(instances) => {
+            for instance in instances {
+                println!("{}: {} players, {} entities", instance.id, instance.players, instance.entities);
+            }
+        }
+        AdminResponse::Packages(packages) => {
+            for package in packages {
+                println!("{package}");
+            }
+        }
+        AdminResponse::Thumbnail(path) => println!("Saved the thumbnail to {path}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use std::{
+    fmt::Display,
+    net::{TcpListener, UdpSocket},
+    path::Path,
+};
+
+use ambient_app::gpu_adapter_options;
+use ambient_gpu::gpu::Gpu;
+use ambient_rustc::Rust;
+use ambient_settings::Settings;
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    download_asset::AssetsCacheDir,
+    to_byte_unit,
+};
+use walkdir::WalkDir;
+
+use crate::server::{HTTP_INTERFACE_PORT, QUIC_INTERFACE_PORT};
+
+/// Above this size, clearing the asset cache is suggested
+const LARGE_CACHE_SIZE: u64 = 10 * 1024 * 1024 * 1024;
+
+/// Prints the result of each check as it's made, with how to fix the problems found
+#[derive(Default)]
+struct Report {
+    warnings: usize,
+    errors: usize,
+}
+impl Report {
+    fn ok(&mut self, check: &str, message: impl Display) {
+        println!("[ok]    {check}: {message}");
+    }
+    fn warn(&mut self, check: &str, message: impl Display, fix: impl Display) {
+        self.warnings += 1;
+        println!("[warn]  {check}: {message}\n        fix: {fix}");
+    }
+    fn error(&mut self, check: &str, message: impl Display, fix: impl Display) {
+        self.errors += 1;
+        println!("[error] {check}: {message}\n        fix: {fix}");
+    }
+}
+
+/// Checks that everything Ambient needs is installed and working, and prints how to fix what isn't
+pub fn run(settings: &Settings, assets: &AssetCache) -> anyhow::Result<()> {
+    let mut report = Report::default();
+    check_settings(&mut report);
+    check_rust(&mut report);
+    check_gpu(&mut report, settings);
+    check_network(&mut report);
+    check_asset_cache(&mut report, assets);
+
+    println!();
+    anyhow::ensure!(report.errors == 0, "Found {} errors and {} warnings", report.errors, report.warnings);
+    println!("No errors found ({} warnings)", report.warnings);
+    Ok(())
+}
+
+fn check_settings(report: &mut Report) {
+    let path = Settings::path().map_or_else(|| "the settings".to_string(), |path| format!("{path:?}"));
+    match Settings::load() {
+        Ok(_) => report.ok("settings", format!("loaded {path}")),
+        Err(err) => report.warn("settings", format!("{err:#}; the defaults are used instead"), format!("Fix or delete {path}")),
+    }
+}
+
+fn check_rust(report: &mut Report) {
+    let status = Rust::get_installation_status();
+    match &status.rustup_version {
+        Some(version) => report.ok("rustup", version),
+        None => report.error("rustup", "not installed; it's needed to build Rust modules", "Install it from https://rustup.rs/"),
+    }
+    match &status.rustc_version {
+        Some(version) if status.rustc_supported => report.ok("rustc", version),
+        Some(version) => report.error(
+            "rustc",
+            format!("{version} is older than the minimum supported version, {}", status.minimum_rustc_version),
+            "Run `rustup update stable`",
+        ),
+        None => report.error("rustc", "not installed", "Run `rustup toolchain install stable`"),
+    }
+    if status.has_wasm32_wasi {
+        report.ok("wasm32-wasi", "installed");
+    } else {
+        report.error(
+            "wasm32-wasi",
+            "not installed for the stable toolchain; it's the target Rust modules are built for",
+            "Run `rustup target add --toolchain stable wasm32-wasi`",
+        );
+    }
+}
+
+fn check_gpu(report: &mut Report, settings: &Settings) {
+    let adapters = Gpu::available_adapters(wgpu::Backends::all());
+    let names = adapters.iter().map(|adapter| format!("{} ({:?})", adapter.name, adapter.backend)).collect::<Vec<_>>().join(", ");
+    if adapters.is_empty() {
+        report.error(
+            "gpu",
+            "no adapters found",
+            "Install the drivers of your GPU. Without one, install a software renderer, like Mesa's Vulkan drivers on Linux",
+        );
+        return;
+    } else if adapters.iter().all(|adapter| adapter.device_type == wgpu::DeviceType::Cpu) {
+        report.warn("gpu", format!("only software adapters found, which are slow: {names}"), "Install the drivers of your GPU");
+    } else {
+        report.ok("gpu", names);
+    }
+
+    let options = gpu_adapter_options(&settings.gpu);
+    if let Some(backends) = options.backends {
+        if !adapters.iter().any(|adapter| backends.contains(adapter.backend.into())) {
+            report.warn(
+                "gpu backend",
+                format!("no adapter supports the backend chosen in the settings, {:?}", settings.gpu.backend),
+                "Set `backend` to `auto` in the `gpu` settings, or run with `--gpu-backend auto`",
+            );
+        }
+    }
+    if let Some(name) = &options.adapter_name {
+        if !adapters.iter().any(|adapter| adapter.name.to_lowercase().contains(&name.to_lowercase())) {
+            report.warn(
+                "gpu adapter",
+                format!("no adapter is named {name:?}, as chosen in the settings"),
+                "Change `adapter_name` in the `gpu` settings to (part of) one of the names above, or remove it",
+            );
+        }
+    }
+}
+
+fn check_network(report: &mut Report) {
+    // Servers use the first free port in this range
+    let ports = QUIC_INTERFACE_PORT..(QUIC_INTERFACE_PORT + 10);
+    match ports.clone().find(|port| UdpSocket::bind(("0.0.0.0", *port)).is_ok()) {
+        Some(port) => report.ok("server port", format!("UDP port {port} is free")),
+        None => report.error(
+            "server port",
+            format!("UDP ports {} to {} are all in use", ports.start, ports.end - 1),
+            "Stop the other Ambient servers running on this machine",
+        ),
+    }
+    match TcpListener::bind(("0.0.0.0", HTTP_INTERFACE_PORT)) {
+        Ok(_) => report.ok("http port", format!("TCP port {HTTP_INTERFACE_PORT} is free")),
+        Err(err) => report.warn(
+            "http port",
+            format!("can't listen on TCP port {HTTP_INTERFACE_PORT} ({err}); servers serve their assets from it"),
+            format!("Stop the program using port {HTTP_INTERFACE_PORT}, which is likely another Ambient server"),
+        ),
+    }
+}
+
+fn check_asset_cache(report: &mut Report, assets: &AssetCache) {
+    let dir = AssetsCacheDir.get(assets);
+    if !dir.exists() {
+        report.ok("asset cache", format!("{dir:?} is empty"));
+        return;
+    }
+
+    let probe = dir.join(".doctor");
+    if let Err(err) = std::fs::write(&probe, []).and_then(|_| std::fs::remove_file(&probe)) {
+        report.error("asset cache", format!("can't write to {dir:?}: {err}"), format!("Fix the permissions of {dir:?}, or delete it"));
+        return;
+    }
+
+    let mut size = 0;
+    let mut corrupt = 0;
+    for entry in WalkDir::new(&dir).into_iter().filter_map(Result::ok).filter(|entry| entry.file_type().is_file()) {
+        size += entry.metadata().map_or(0, |metadata| metadata.len());
+        if entry.path().parent().and_then(Path::file_name).map_or(false, |parent| parent == "json_cache")
+            && std::fs::read(entry.path()).ok().and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok()).is_none()
+        {
+            corrupt += 1;
+        }
+    }
+    if corrupt > 0 {
+        report.warn("asset cache", format!("{corrupt} cached files are corrupt"), format!("Delete {:?}", dir.join("json_cache")));
+    } else if size > LARGE_CACHE_SIZE {
+        report.warn(
+            "asset cache",
+            format!("{dir:?} takes up {}", to_byte_unit(size)),
+            format!("Delete {dir:?}; the assets are downloaded again when they're needed"),
+        );
+    } else {
+        report.ok("asset cache", format!("{dir:?} takes up {}", to_byte_unit(size)));
+    }
+}
@@ -1,7 +1,11 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
+use ambient_core::determinism::Determinism;
+use ambient_settings::GpuBackend;
 use clap::{Args, Parser};
 
+pub mod admin;
+pub mod doctor;
 pub mod new_project;
 
 #[derive(Parser, Clone)]
@@ -14,6 +18,15 @@ pub enum Cli {
         project_args: ProjectCli,
         #[arg(short, long)]
         name: Option<String>,
+        /// The id of the project. Defaults to the name in snake_case
+        #[arg(long)]
+        id: Option<String>,
+        /// The template to create the project from: `empty`, `fps`, `ui`, `multiplayer`, or the URL of a git repository
+        #[arg(short, long, default_value = "empty")]
+        template: new_project::Template,
+        /// Prompt for the name, id and template of the project
+        #[arg(short, long)]
+        interactive: bool,
     },
     /// Builds and runs the project locally
     Run {
@@ -23,6 +36,8 @@ pub enum Cli {
         host_args: HostCli,
         #[command(flatten)]
         run_args: RunCli,
+        #[command(flatten)]
+        determinism_args: DeterminismCli,
     },
     /// Builds the project
     Build {
@@ -35,6 +50,8 @@ pub enum Cli {
         project_args: ProjectCli,
         #[command(flatten)]
         host_args: HostCli,
+        #[command(flatten)]
+        determinism_args: DeterminismCli,
     },
     /// View an asset
     View {
@@ -47,9 +64,36 @@ pub enum Cli {
     Join {
         #[command(flatten)]
         run_args: RunCli,
+        #[command(flatten)]
+        determinism_args: DeterminismCli,
         /// The server to connect to; defaults to localhost
         host: Option<String>,
     },
+    /// Run an admin command on a server that was started with an admin token
+    Admin {
+        /// The server to send the command to; defaults to localhost
+        #[arg(long, default_value = "localhost")]
+        host: String,
+        /// The server's admin token. Can also be set through the `AMBIENT_ADMIN_TOKEN` environment variable
+        #[arg(long)]
+        token: Option<String>,
+        #[command(subcommand)]
+        command: admin::AdminCli,
+    },
+    /// Checks that everything Ambient needs is installed and working, and suggests how to fix what isn't
+    Doctor,
+    /// Writes a JSON description of the components, concepts, types and settings of the project, for external tools
+    Schema {
+        #[command(flatten)]
+        project_args: ProjectCli,
+        /// Where to write the schema; defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Write a TypeScript module with the types of the components and a client for the web client's embedding API,
+        /// instead of JSON
+        #[arg(long)]
+        typescript: bool,
+    },
     /// Updates all WASM APIs with the core primitive components (not for users)
     #[cfg(not(feature = "production"))]
     #[command(hide = true)]
@@ -65,13 +109,29 @@ pub struct RunCli {
     #[arg(long)]
     pub headless: bool,
 
-    /// Take a screenshot after N seconds, compare it to the existing one and then exit with an exit code of 1 if they are different
+    /// Take a screenshot after N seconds, compare it and the checkpoints captured before with `golden_image_checkpoint` events to the existing ones, and then exit with an exit code of 1 if they are different. The metrics used can be configured in `golden_images.json`, and a report is written to `golden_image_report`
     #[arg(long)]
     pub screenshot_test: Option<f32>,
 
     /// The user ID to join this server with
     #[clap(short, long)]
     pub user_id: Option<String>,
+
+    /// A token (like a JWT or an OAuth access token) proving who you are, for servers that authenticate players. Can also be set through the `AMBIENT_AUTH_TOKEN` environment variable
+    #[arg(long)]
+    pub auth_token: Option<String>,
+
+    /// Join as a spectator, without a player character. The camera flies with WASD and follows the players with the number keys
+    #[arg(long)]
+    pub spectator: bool,
+
+    /// The GPU to render with: `discrete`, `integrated`, or (part of) the name of a specific adapter. Overrides the settings file
+    #[arg(long)]
+    pub gpu: Option<String>,
+
+    /// The graphics backend to use: `auto`, `vulkan`, `metal`, `dx12` or `gl`. Overrides the settings file
+    #[arg(long)]
+    pub gpu_backend: Option<GpuBackend>,
 }
 #[derive(Args, Clone)]
 pub struct ProjectCli {
@@ -81,6 +141,10 @@ pub struct ProjectCli {
     /// Build all the assets with full optimization; this will make debugging more difficult
     #[arg(short, long)]
     pub release: bool,
+
+    /// Rebuild all the assets, even those that haven't changed since the last build
+    #[arg(long)]
+    pub force: bool,
 }
 #[derive(Args, Clone)]
 pub struct HostCli {
@@ -89,6 +153,71 @@ pub struct HostCli {
     /// Defaults to localhost
     #[arg(long)]
     pub public_host: Option<String>,
+
+    /// Serve server metrics in the Prometheus format at `http://<host>:<port>/metrics`, for monitoring
+    #[arg(long)]
+    pub metrics_port: Option<u16>,
+
+    /// Enable the admin commands (see `ambient admin`), which must be sent with this token. Can also be set through the `AMBIENT_ADMIN_TOKEN` environment variable
+    #[arg(long)]
+    pub admin_token: Option<String>,
+
+    /// Where to listen for admin commands; defaults to `127.0.0.1:8998`. The token is sent in plain text, so only listen on a
+    /// public address behind a TLS proxy
+    #[arg(long)]
+    pub admin_address: Option<SocketAddr>,
+
+    /// Authenticate players with JSON Web Tokens signed with this HS256 secret. Can also be set through the `AMBIENT_AUTH_JWT_SECRET` environment variable
+    #[arg(long)]
+    pub auth_jwt_secret: Option<String>,
+
+    /// Authenticate players with JSON Web Tokens signed with the RS256 private key matching this PEM public key
+    #[arg(long)]
+    pub auth_jwt_public_key: Option<PathBuf>,
+
+    /// Only accept JSON Web Tokens from this issuer
+    #[arg(long)]
+    pub auth_jwt_issuer: Option<String>,
+
+    /// Only accept JSON Web Tokens for this audience
+    #[arg(long)]
+    pub auth_jwt_audience: Option<String>,
+
+    /// Authenticate players with OAuth access tokens, which are checked with this token introspection endpoint
+    #[arg(long)]
+    pub auth_oauth_introspection_url: Option<String>,
+
+    /// Refuse players that can't be authenticated; otherwise, they join as guests
+    #[arg(long)]
+    pub require_auth: bool,
+
+    /// Warn about the entities made from a concept (see the `concept` component) which lack some of its components. Can also be enabled through the `AMBIENT_VALIDATE_CONCEPTS` environment variable
+    #[arg(long)]
+    pub validate_concepts: bool,
+
+    /// Simulation frames per second. Overrides `tick-rate` in the `[server]` section of the project's manifest
+    #[arg(long)]
+    pub tick_rate: Option<f32>,
+
+    /// World diffs sent to the clients per second, which can't be more than the tick rate. Overrides `replication-rate` in the `[server]` section of the project's manifest
+    #[arg(long)]
+    pub replication_rate: Option<f32>,
+}
+#[derive(Args, Clone)]
+pub struct DeterminismCli {
+    /// Run deterministically, for tests and replays: entity IDs and the `rng` resource are seeded with this, time starts at 0 and advances by a fixed timestep every frame, and instances are stepped in a stable order
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// The timestep in seconds when running with `--seed`; defaults to 1/60
+    #[arg(long, requires = "seed")]
+    pub fixed_timestep: Option<f32>,
+}
+impl DeterminismCli {
+    pub fn determinism(&self) -> Option<Determinism> {
+        let seed = self.seed?;
+        Some(Determinism { seed, timestep: self.fixed_timestep.unwrap_or(Determinism::DEFAULT_TIMESTEP) })
+    }
 }
 
 impl Cli {
@@ -101,6 +230,9 @@ impl Cli {
             Cli::Serve { .. } => None,
             Cli::View { .. } => None,
             Cli::Join { run_args, .. } => Some(run_args),
+            Cli::Admin { .. } => None,
+            Cli::Doctor => None,
+            Cli::Schema { .. } => None,
             #[cfg(not(feature = "production"))]
             Cli::UpdateInterfaceComponents => None,
         }
@@ -114,6 +246,9 @@ impl Cli {
             Cli::Serve { project_args, .. } => Some(project_args),
             Cli::View { project_args, .. } => Some(project_args),
             Cli::Join { .. } => None,
+            Cli::Admin { .. } => None,
+            Cli::Doctor => None,
+            Cli::Schema { project_args, .. } => Some(project_args),
             #[cfg(not(feature = "production"))]
             Cli::UpdateInterfaceComponents => None,
         }
@@ -127,8 +262,20 @@ impl Cli {
             Cli::Serve { host_args, .. } => Some(host_args),
             Cli::View { .. } => None,
             Cli::Join { .. } => None,
+            Cli::Admin { .. } => None,
+            Cli::Doctor => None,
+            Cli::Schema { .. } => None,
             #[cfg(not(feature = "production"))]
             Cli::UpdateInterfaceComponents => None,
         }
     }
+    /// Extract the deterministic mode, if it's enabled
+    pub fn determinism(&self) -> Option<Determinism> {
+        match self {
+            Cli::Run { determinism_args, .. } => determinism_args.determinism(),
+            Cli::Serve { determinism_args, .. } => determinism_args.determinism(),
+            Cli::Join { determinism_args, .. } => determinism_args.determinism(),
+            _ => None,
+        }
+    }
 }
@@ -1,114 +1,256 @@
-use std::path::Path;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use ambient_project::Identifier;
 use anyhow::Context;
 use convert_case::Casing;
+use walkdir::WalkDir;
+
+/// The templates that are built into Ambient
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinTemplate {
+    /// A camera looking at a quad
+    Empty,
+    /// A first-person controller walking around some crates
+    Fps,
+    /// A clientside UI with a button and a counter
+    Ui,
+    /// A cube for every player, which the player moves around
+    Multiplayer,
+}
+impl BuiltinTemplate {
+    const ALL: [Self; 4] = [Self::Empty, Self::Fps, Self::Ui, Self::Multiplayer];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Fps => "fps",
+            Self::Ui => "ui",
+            Self::Multiplayer => "multiplayer",
+        }
+    }
+
+    fn ambient_toml(self) -> &'static str {
+        match self {
+            Self::Fps => include_str!("new_project_templates/fps/ambient.toml"),
+            _ => include_str!("new_project_template/ambient.toml"),
+        }
+    }
+
+    /// The crates the template needs besides `ambient_api`, as their directory in `crates` and
+    /// their features
+    fn dependencies(self) -> &'static [(&'static str, &'static [&'static str])] {
+        match self {
+            Self::Ui => &[("element", &["guest"]), ("guest_bridge", &["guest"]), ("ui_components", &["guest"])],
+            _ => &[],
+        }
+    }
+
+    /// The files of the template, besides the manifests
+    fn files(self) -> Vec<(&'static Path, &'static [u8])> {
+        macro_rules! include_template {
+            ($path:expr) => {
+                (Path::new($path), include_bytes!(concat!("new_project_template/", $path)).as_slice())
+            };
+            ($template:expr, $path:expr) => {
+                (Path::new($path), include_bytes!(concat!("new_project_templates/", $template, "/", $path)).as_slice())
+            };
+        }
+
+        let mut files = vec![
+            // root
+            include_template!(".gitignore"),
+            include_template!("rust-toolchain.toml"),
+            // .cargo
+            include_template!(".cargo/config.toml"),
+            // .vscode
+            include_template!(".vscode/settings.json"),
+        ];
+        match self {
+            Self::Empty => files.extend([include_template!("src/client.rs"), include_template!("src/server.rs")]),
+            Self::Fps => files.extend([
+                include_template!("src/client.rs"),
+                include_template!("fps", "src/server.rs"),
+                include_template!("fps", "assets/Cube.glb"),
+                include_template!("fps", "assets/pipeline.json"),
+            ]),
+            Self::Ui => files.extend([include_template!("ui", "src/client.rs"), include_template!("ui", "src/server.rs")]),
+            Self::Multiplayer => files.extend([include_template!("src/client.rs"), include_template!("multiplayer", "src/server.rs")]),
+        }
+        files
+    }
+}
+
+/// Where the files of a new project come from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Template {
+    Builtin(BuiltinTemplate),
+    /// A git repository, which is cloned. `{{id}}` and `{{name}}` are replaced in its files
+    Git(String),
+}
+impl std::str::FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(template: &str) -> anyhow::Result<Self> {
+        if let Some(builtin) = BuiltinTemplate::ALL.into_iter().find(|t| t.name() == template) {
+            Ok(Self::Builtin(builtin))
+        } else if template.contains("://") || template.starts_with("git@") {
+            Ok(Self::Git(template.to_string()))
+        } else {
+            anyhow::bail!(
+                "unknown template {template:?}; expected one of {} or the URL of a git repository",
+                BuiltinTemplate::ALL.map(|t| t.name()).join(", ")
+            )
+        }
+    }
+}
+
+pub(crate) struct NewProjectOptions<'a> {
+    pub name: Option<&'a str>,
+    pub id: Option<&'a str>,
+    pub template: Template,
+    /// Prompt for the name, id and template, defaulting to the options above
+    pub interactive: bool,
+}
+
+pub(crate) fn new_project(project_path: &Path, options: NewProjectOptions) -> anyhow::Result<()> {
+    let NewProjectOptions { name, id, mut template, interactive } = options;
 
-pub(crate) fn new_project(project_path: &Path, name: Option<&str>) -> anyhow::Result<()> {
     // Build the identifier.
-    let project_path = if let Some(name) = name {
-        project_path.join(name)
-    } else {
-        project_path.to_owned()
-    };
+    let project_path = if let Some(name) = name { project_path.join(name) } else { project_path.to_owned() };
 
-    let name = project_path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .context("project path has no terminating segment")?;
+    let mut name = project_path.file_name().and_then(|s| s.to_str()).context("project path has no terminating segment")?.to_string();
 
     if project_path.is_dir() && std::fs::read_dir(&project_path)?.next().is_some() {
         anyhow::bail!("project path {project_path:?} is not empty");
     }
 
-    let id = name.to_case(convert_case::Case::Snake);
+    let default_id = |name: &str| id.map_or_else(|| name.to_case(convert_case::Case::Snake), str::to_string);
+    let mut id = default_id(&name);
+    if interactive {
+        name = prompt("Name", &name)?;
+        id = prompt("Id", &default_id(&name))?;
+        template = loop {
+            let names = BuiltinTemplate::ALL.map(|t| t.name()).join(", ");
+            let default = match &template {
+                Template::Builtin(template) => template.name(),
+                Template::Git(url) => url.as_str(),
+            };
+            match prompt(&format!("Template ({names} or a git URL)"), default)?.parse() {
+                Ok(template) => break template,
+                Err(err) => eprintln!("{err}"),
+            }
+        };
+    }
     let id = Identifier::new(id).map_err(anyhow::Error::msg)?;
 
-    // Create the folders.
-    let dot_cargo_path = project_path.join(".cargo");
-    let dot_vscode_path = project_path.join(".vscode");
-    let src_path = project_path.join("src");
+    match template {
+        Template::Builtin(template) => write_builtin(&project_path, &id, &name, template)?,
+        Template::Git(url) => clone_git(&project_path, &id, &name, &url)?,
+    }
+
+    log::info!("Project \"{name}\" with id `{id}` created at {project_path:?}");
+
+    Ok(())
+}
 
-    std::fs::create_dir_all(&project_path).context("Failed to create project directory")?;
-    std::fs::create_dir_all(&dot_cargo_path).context("Failed to create .cargo directory")?;
-    std::fs::create_dir_all(&dot_vscode_path).context("Failed to create .vscode directory")?;
-    std::fs::create_dir_all(&src_path).context("Failed to create src directory")?;
+fn write_builtin(project_path: &Path, id: &Identifier, name: &str, template: BuiltinTemplate) -> anyhow::Result<()> {
+    std::fs::create_dir_all(project_path).context("Failed to create project directory")?;
 
     // Write the files to disk.
-    let ambient_toml = include_str!("new_project_template/ambient.toml")
-        .replace("{{id}}", id.as_ref())
-        .replace("{{name}}", name);
+    let ambient_toml = template.ambient_toml().replace("{{id}}", id.as_ref()).replace("{{name}}", name);
 
     let cargo_toml = {
         // Special-case creating an example in guest/rust/examples so that it "Just Works".
         let segments = project_path.iter().collect::<Vec<_>>();
-        let (replacement, in_ambient_examples) = match segments
-            .windows(3)
-            .position(|w| w == ["guest", "rust", "examples"])
-        {
-            Some(i) => {
-                let number_of_parents = segments.len() - i - 2;
-                (
-                    format!(
-                        r#"ambient_api = {{ path = "{}api" }}"#,
-                        "../".repeat(number_of_parents)
-                    ),
-                    true,
-                )
+        let number_of_parents = segments.windows(3).position(|w| w == ["guest", "rust", "examples"]).map(|i| segments.len() - i - 2);
+        let ambient_api = match number_of_parents {
+            Some(number_of_parents) => {
+                format!(r#"ambient_api = {{ path = "{}api" }}"#, "../".repeat(number_of_parents))
             }
-            None => (
-                format!("ambient_api = \"{}\"", env!("CARGO_PKG_VERSION")),
-                false,
-            ),
+            None => format!("ambient_api = \"{}\"", env!("CARGO_PKG_VERSION")),
         };
+        let dependencies = std::iter::once(ambient_api)
+            .chain(template.dependencies().iter().map(|(krate, features)| {
+                let source = match number_of_parents {
+                    Some(number_of_parents) => format!(r#"path = "{}../../crates/{krate}""#, "../".repeat(number_of_parents)),
+                    None => format!(r#"git = "{}", tag = "v{}""#, env!("CARGO_PKG_REPOSITORY"), env!("CARGO_PKG_VERSION")),
+                };
+                let features = features.iter().map(|f| format!("{f:?}")).collect::<Vec<_>>();
+                format!("ambient_{krate} = {{ {source}, default-features = false, features = [{}] }}", features.join(", "))
+            }))
+            .collect::<Vec<_>>()
+            .join("\n");
 
         let mut template_cargo_toml = include_str!("new_project_template/Cargo.toml")
             .replace("{{id}}", id.as_ref())
-            .replace(
-                "ambient_api = { path = \"../../../../guest/rust/api\" }",
-                &replacement,
-            );
-
-        if in_ambient_examples {
-            template_cargo_toml = template_cargo_toml.replace(
-                r#"version = "0.0.1""#,
-                "rust-version = {workspace = true}\nversion = {workspace = true}",
-            )
+            .replace("ambient_api = { path = \"../../../../guest/rust/api\" }", &dependencies);
+
+        if number_of_parents.is_some() {
+            template_cargo_toml =
+                template_cargo_toml.replace(r#"version = "0.0.1""#, "rust-version = {workspace = true}\nversion = {workspace = true}")
         }
 
         template_cargo_toml
     };
 
-    macro_rules! include_template {
-        ($path:expr) => {
-            (
-                Path::new($path),
-                include_str!(concat!("new_project_template/", $path)),
-            )
-        };
-    }
-
-    let files_to_write = &[
-        // root
-        (Path::new("ambient.toml"), ambient_toml.as_str()),
-        (Path::new("Cargo.toml"), cargo_toml.as_str()),
-        include_template!(".gitignore"),
-        include_template!("rust-toolchain.toml"),
-        // .cargo
-        include_template!(".cargo/config.toml"),
-        // .vscode
-        include_template!(".vscode/settings.json"),
-        // src
-        include_template!("src/client.rs"),
-        include_template!("src/server.rs"),
-    ];
+    let files_to_write = [(Path::new("ambient.toml"), ambient_toml.as_bytes()), (Path::new("Cargo.toml"), cargo_toml.as_bytes())]
+        .into_iter()
+        .chain(template.files());
 
     for (filename, contents) in files_to_write {
-        std::fs::write(project_path.join(filename), contents)
-            .with_context(|| format!("failed to create {filename:?}"))?;
+        let path = project_path.join(filename);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {parent:?}"))?;
+        }
+        std::fs::write(path, contents).with_context(|| format!("failed to create {filename:?}"))?;
     }
 
-    log::info!("Project \"{name}\" with id `{id}` created at {project_path:?}");
+    Ok(())
+}
+
+fn clone_git(project_path: &Path, id: &Identifier, name: &str, url: &str) -> anyhow::Result<()> {
+    log::info!("Cloning the template from {url}");
+    let status = Command::new("git")
+        .args(["clone", "--depth", "1", url])
+        .arg(project_path)
+        .status()
+        .context("Failed to run git; is it installed?")?;
+    anyhow::ensure!(status.success(), "Failed to clone {url}");
+
+    // The project starts its own history
+    std::fs::remove_dir_all(project_path.join(".git")).context("Failed to remove .git")?;
+    anyhow::ensure!(project_path.join("ambient.toml").exists(), "The template has no ambient.toml at its root");
+
+    let files: Vec<PathBuf> = WalkDir::new(project_path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .collect();
+    for path in files {
+        // Binary files, like assets, are left as they are
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if contents.contains("{{id}}") || contents.contains("{{name}}") {
+            let contents = contents.replace("{{id}}", id.as_ref()).replace("{{name}}", name);
+            std::fs::write(&path, contents).with_context(|| format!("failed to write {path:?}"))?;
+        }
+    }
 
     Ok(())
 }
+
+/// Asks for a value on stdin, returning `default` if the answer is empty
+fn prompt(question: &str, default: &str) -> anyhow::Result<String> {
+    print!("{question} [{default}]: ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).context("Failed to read the answer")?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
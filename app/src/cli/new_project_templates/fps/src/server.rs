@@ -0,0 +1,122 @@
+use ambient_api::{
+    components::core::{
+        app::main_scene,
+        camera::aspect_ratio_from_window,
+        physics::{
+            box_collider, character_controller_height, character_controller_radius,
+            physics_controlled, plane_collider,
+        },
+        player::{player, user_id},
+        prefab::prefab_from_url,
+        primitives::quad,
+        rendering::color,
+        transform::{lookat_center, rotation, scale, translation},
+    },
+    concepts::{make_perspective_infinite_reverse_camera, make_transformable},
+    player::KeyCode,
+    prelude::*,
+};
+
+use components::{player_camera_ref, player_pitch};
+
+/// How high the camera is above the center of the player
+const EYE_HEIGHT: f32 = 0.8;
+const SPEED: f32 = 0.1;
+const MOUSE_SENSITIVITY: f32 = 0.01;
+
+#[main]
+pub async fn main() -> EventResult {
+    Entity::new()
+        .with_merge(make_transformable())
+        .with_default(quad())
+        .with(scale(), Vec3::ONE * 50.)
+        .with(color(), vec4(0.3, 0.3, 0.3, 1.))
+        .with_default(plane_collider())
+        .spawn();
+
+    // Some crates to walk around, loaded from the assets folder
+    for i in 0..5 {
+        let angle = i as f32 / 5. * std::f32::consts::TAU;
+        Entity::new()
+            .with_merge(make_transformable())
+            .with(prefab_from_url(), asset::url("assets/Cube.glb").unwrap())
+            .with(box_collider(), Vec3::ONE * 2.)
+            .with(
+                translation(),
+                vec3(angle.cos(), angle.sin(), 0.) * 10. + Vec3::Z,
+            )
+            .spawn();
+    }
+
+    spawn_query((player(), user_id())).bind(move |players| {
+        for (id, (_, user)) in players {
+            let camera = Entity::new()
+                .with_merge(make_perspective_infinite_reverse_camera())
+                .with(aspect_ratio_from_window(), EntityId::resources())
+                .with_default(main_scene())
+                .with(user_id(), user)
+                .with(translation(), Vec3::Z * 2.)
+                .with(lookat_center(), vec3(1., 0., 2.))
+                .spawn();
+
+            entity::add_components(
+                id,
+                Entity::new()
+                    .with_merge(make_transformable())
+                    .with(translation(), Vec3::Z * 2.)
+                    .with(player_camera_ref(), camera)
+                    .with(player_pitch(), 0.)
+                    .with(character_controller_height(), 2.)
+                    .with(character_controller_radius(), 0.5)
+                    .with_default(physics_controlled()),
+            );
+        }
+    });
+
+    query((player(), player_camera_ref()))
+        .build()
+        .each_frame(move |players| {
+            for (player_id, (_, camera_id)) in players {
+                let Some((delta, pressed)) = player::get_raw_input_delta(player_id) else {
+                    continue;
+                };
+
+                entity::mutate_component(player_id, rotation(), |rotation| {
+                    *rotation *= Quat::from_rotation_z(delta.mouse_position.x * MOUSE_SENSITIVITY)
+                });
+                entity::mutate_component(player_id, player_pitch(), |pitch| {
+                    *pitch = (*pitch - delta.mouse_position.y * MOUSE_SENSITIVITY).clamp(-1.5, 1.5)
+                });
+
+                let rotation = entity::get_component(player_id, rotation()).unwrap();
+                let forward = rotation * Vec3::X;
+                let right = rotation * Vec3::Y;
+                let mut displace = Vec3::ZERO;
+                if pressed.keys.contains(&KeyCode::W) {
+                    displace += forward;
+                }
+                if pressed.keys.contains(&KeyCode::S) {
+                    displace -= forward;
+                }
+                if pressed.keys.contains(&KeyCode::A) {
+                    displace -= right;
+                }
+                if pressed.keys.contains(&KeyCode::D) {
+                    displace += right;
+                }
+                displace = displace.normalize_or_zero() * SPEED;
+                // Gravity
+                displace.z = -0.1;
+                physics::move_character(player_id, displace, 0.01, frametime());
+
+                let pitch = entity::get_component(player_id, player_pitch()).unwrap();
+                let eye =
+                    entity::get_component(player_id, translation()).unwrap() + Vec3::Z * EYE_HEIGHT;
+                let look = rotation * Quat::from_rotation_y(-pitch) * Vec3::X;
+                entity::set_component(camera_id, translation(), eye);
+                entity::set_component(camera_id, lookat_center(), eye + look);
+            }
+        });
+
+    EventOk
+}
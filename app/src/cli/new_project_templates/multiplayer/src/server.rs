@@ -0,0 +1,85 @@
+use ambient_api::{
+    components::core::{
+        app::main_scene,
+        camera::aspect_ratio_from_window,
+        player::player,
+        primitives::{cube, quad},
+        rendering::color,
+        transform::{lookat_center, scale, translation},
+    },
+    concepts::{make_perspective_infinite_reverse_camera, make_transformable},
+    player::KeyCode,
+    prelude::*,
+};
+
+const SPEED: f32 = 0.1;
+
+#[main]
+pub async fn main() -> EventResult {
+    Entity::new()
+        .with_merge(make_perspective_infinite_reverse_camera())
+        .with(aspect_ratio_from_window(), EntityId::resources())
+        .with_default(main_scene())
+        .with(translation(), vec3(10., 10., 10.))
+        .with(lookat_center(), vec3(0., 0., 0.))
+        .spawn();
+
+    Entity::new()
+        .with_merge(make_transformable())
+        .with_default(quad())
+        .with(scale(), Vec3::ONE * 20.)
+        .spawn();
+
+    // Every player that joins is a randomly colored cube. The cube's components are added to the
+    // player entity, so the cube disappears when the player leaves.
+    spawn_query(player()).bind(move |players| {
+        for (id, _) in players {
+            entity::add_components(
+                id,
+                Entity::new()
+                    .with_merge(make_transformable())
+                    .with_default(cube())
+                    .with(translation(), vec3(0., 0., 0.5))
+                    .with(color(), rand::random::<Vec3>().extend(1.0)),
+            );
+            println!("A player joined");
+        }
+    });
+
+    despawn_query(player()).bind(move |players| {
+        for _ in players {
+            println!("A player left");
+        }
+    });
+
+    // The server owns the world, so the clients only send their input, and the server moves
+    // their cubes.
+    query(player()).build().each_frame(move |players| {
+        for (id, _) in players {
+            let Some(input) = player::get_raw_input(id) else {
+                continue;
+            };
+
+            let mut direction = Vec3::ZERO;
+            if input.keys.contains(&KeyCode::W) {
+                direction -= Vec3::X;
+            }
+            if input.keys.contains(&KeyCode::S) {
+                direction += Vec3::X;
+            }
+            if input.keys.contains(&KeyCode::A) {
+                direction += Vec3::Y;
+            }
+            if input.keys.contains(&KeyCode::D) {
+                direction -= Vec3::Y;
+            }
+            if direction != Vec3::ZERO {
+                entity::mutate_component(id, translation(), |translation| {
+                    *translation += direction.normalize() * SPEED
+                });
+            }
+        }
+    });
+
+    EventOk
+}
@@ -0,0 +1,35 @@
+use ambient_api::prelude::*;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_guest_bridge::components::{layout::space_between_items, transform::translation};
+use ambient_ui_components::{
+    button::{Button, ButtonStyle},
+    default_theme::STREET,
+    layout::FlowColumn,
+    setup_ui_camera,
+    text::Text,
+    UIExt,
+};
+
+#[element_component]
+fn App(hooks: &mut Hooks) -> Element {
+    let (count, set_count) = hooks.use_state(0);
+
+    FlowColumn(vec![
+        Text::el(format!("You've clicked {count} times")),
+        Button::new("Click me", move |_| set_count(count + 1))
+            .style(ButtonStyle::Primary)
+            .el(),
+    ])
+    .el()
+    .set(space_between_items(), STREET)
+    .with_padding_even(STREET)
+    .set(translation(), vec3(100., 100., 0.))
+}
+
+#[main]
+pub async fn main() -> EventResult {
+    setup_ui_camera();
+    App.el().spawn_interactive();
+
+    EventOk
+}
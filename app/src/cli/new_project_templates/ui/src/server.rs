@@ -0,0 +1,6 @@
+use ambient_api::prelude::*;
+
+#[main]
+pub async fn main() -> EventResult {
+    EventOk
+}
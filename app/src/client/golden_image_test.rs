@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::exit,
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_core::{name, runtime};
+use ambient_ecs::{world_events, FnSystem, SystemGroup, World, WorldEventReader};
+use ambient_network::capture::capture;
+use image::{Rgba, RgbaImage};
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+/// The event client-side modules send to capture a checkpoint of the test. The name of the checkpoint is its `name` component
+pub const CHECKPOINT_EVENT: &str = "golden_image_checkpoint";
+/// The checkpoint captured at the end of the test
+const FINAL_CHECKPOINT: &str = "screenshot";
+/// The file in the project directory with the [GoldenImageConfig] of the test
+const CONFIG_FILE: &str = "golden_images.json";
+/// The directory in the project directory the report is written to
+const REPORT_DIR: &str = "golden_image_report";
+
+/// How a captured image is compared to its golden image
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum ImageMetric {
+    /// Fails if any channel of any pixel differs by more than `epsilon`
+    Exact { epsilon: u8 },
+    /// Compares the perceived brightness and color of the pixels, and fails if more than `max_differing_pixels` (a fraction of
+    /// the image) differ by more than `threshold`, from 0 (any difference) to 1 (black and white)
+    Perceptual { threshold: f32, max_differing_pixels: f32 },
+}
+impl Default for ImageMetric {
+    fn default() -> Self {
+        Self::Exact { epsilon: 3 }
+    }
+}
+impl ImageMetric {
+    fn differs(&self, a: &Rgba<u8>, b: &Rgba<u8>) -> bool {
+        match *self {
+            ImageMetric::Exact { epsilon } => a.0.iter().zip(b.0.iter()).any(|(a, b)| a.abs_diff(*b) > epsilon),
+            ImageMetric::Perceptual { threshold, .. } => perceptual_delta(a, b) > threshold * threshold,
+        }
+    }
+    fn max_differing_pixels(&self) -> f32 {
+        match *self {
+            ImageMetric::Exact { .. } => 0.,
+            ImageMetric::Perceptual { max_differing_pixels, .. } => max_differing_pixels,
+        }
+    }
+}
+
+/// The squared difference of the colors in the YIQ color space, from 0 to 1, as in pixelmatch
+fn perceptual_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    fn yiq(color: &Rgba<u8>) -> (f32, f32, f32) {
+        // Blended with white, so that transparent pixels compare as they're displayed
+        let alpha = color[3] as f32 / 255.;
+        let [r, g, b] = [0, 1, 2].map(|i| 255. + (color[i] as f32 - 255.) * alpha);
+        (
+            r * 0.29889531 + g * 0.58662247 + b * 0.11448223,
+            r * 0.59597799 - g * 0.2741761 - b * 0.32180189,
+            r * 0.21147017 - g * 0.52261711 + b * 0.31114694,
+        )
+    }
+    const MAX_DELTA: f32 = 35215.;
+    let (a, b) = (yiq(a), yiq(b));
+    (0.5053 * (a.0 - b.0).powi(2) + 0.299 * (a.1 - b.1).powi(2) + 0.1957 * (a.2 - b.2).powi(2)) / MAX_DELTA
+}
+
+/// The metrics of a test, read from `golden_images.json` in the project directory
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GoldenImageConfig {
+    /// The metric of the checkpoints which aren't in `checkpoints`
+    #[serde(default)]
+    pub default: ImageMetric,
+    #[serde(default)]
+    pub checkpoints: HashMap<String, ImageMetric>,
+}
+impl GoldenImageConfig {
+    fn load(project_path: &Path) -> anyhow::Result<Self> {
+        let path = project_path.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+    fn metric(&self, checkpoint: &str) -> ImageMetric {
+        self.checkpoints.get(checkpoint).copied().unwrap_or(self.default)
+    }
+}
+
+struct Comparison {
+    checkpoint: String,
+    /// The fraction of the pixels which differ, or None if there was no golden image
+    differing_pixels: Option<f32>,
+    passed: bool,
+}
+
+/// Captures the game at the checkpoints client-side modules send with [CHECKPOINT_EVENT], and after `seconds`, then compares
+/// them to the golden images in the project directory and exits with an exit code of 1 if any of them differ.
+///
+/// The golden images of the checkpoints that don't have any are saved, so deleting a golden image updates it on the next run.
+/// A report with the captured images and their differences is written to `golden_image_report/index.html`.
+#[derive(Debug, Clone)]
+pub struct GoldenImageTest {
+    project_path: PathBuf,
+    seconds: f32,
+    config: Arc<GoldenImageConfig>,
+    captured: Arc<Mutex<Vec<(String, RgbaImage)>>>,
+}
+impl GoldenImageTest {
+    pub fn new(project_path: PathBuf, seconds: f32) -> Self {
+        let config = GoldenImageConfig::load(&project_path).unwrap_or_else(|err| {
+            log::error!("Failed to load {CONFIG_FILE}: {err:?}");
+            exit(1);
+        });
+        Self { project_path, seconds, config: Arc::new(config), captured: Default::default() }
+    }
+
+    /// Captures the checkpoints sent by client-side modules
+    pub fn systems(&self) -> SystemGroup {
+        let mut reader = WorldEventReader::new();
+        let captured = self.captured.clone();
+        SystemGroup::new(
+            "golden_image_test",
+            vec![Box::new(FnSystem::new(move |world, _| {
+                let checkpoints = reader
+                    .iter(world.resource(world_events()))
+                    .filter(|(_, (event_name, _))| event_name == CHECKPOINT_EVENT)
+                    .filter_map(|(_, (_, event))| event.get_ref(name()).cloned())
+                    .collect::<Vec<_>>();
+                for checkpoint in checkpoints {
+                    log::info!("Capturing checkpoint {checkpoint:?}");
+                    let captured = captured.clone();
+                    world.resource(capture()).capture_frame(Box::new(move |image| captured.lock().push((checkpoint, image))));
+                }
+            }))],
+        )
+    }
+
+    /// Captures the final checkpoint after `seconds`, and then compares the checkpoints and exits
+    pub fn start(&self, world: &World) {
+        let test = self.clone();
+        let capture = world.resource(capture()).clone();
+        world.resource(runtime()).spawn(async move {
+            tokio::time::sleep(Duration::from_secs_f32(test.seconds)).await;
+            capture.capture_frame(Box::new(move |image| {
+                test.captured.lock().push((FINAL_CHECKPOINT.to_string(), image));
+                let passed = test.finish();
+                exit(if passed { 0 } else { 1 });
+            }));
+        });
+    }
+
+    fn golden_image_path(&self, checkpoint: &str) -> PathBuf {
+        if checkpoint == FINAL_CHECKPOINT {
+            self.project_path.join("screenshot.png")
+        } else {
+            self.project_path.join(format!("screenshot_{checkpoint}.png"))
+        }
+    }
+
+    /// Returns whether all the checkpoints match their golden images
+    fn finish(&self) -> bool {
+        let report_dir = self.project_path.join(REPORT_DIR);
+        if let Err(err) = std::fs::create_dir_all(&report_dir) {
+            log::error!("Failed to create {report_dir:?}: {err}");
+        }
+
+        let mut comparisons = Vec::new();
+        for (checkpoint, actual) in std::mem::take(&mut *self.captured.lock()) {
+            if comparisons.iter().any(|comparison: &Comparison| comparison.checkpoint == checkpoint) {
+                log::warn!("Checkpoint {checkpoint:?} was captured more than once; only the first capture is compared");
+                continue;
+            }
+            let comparison = self.compare(&report_dir, &checkpoint, &actual);
+            match comparison.differing_pixels {
+                Some(differing) if comparison.passed => {
+                    log::info!("Checkpoint {checkpoint:?} passed ({:.4}% of pixels differ)", differing * 100.)
+                }
+                Some(differing) => log::info!("Checkpoint {checkpoint:?} differs ({:.4}% of pixels differ)", differing * 100.),
+                None => log::info!("Checkpoint {checkpoint:?} had no golden image to compare to; saved it"),
+            }
+            comparisons.push(comparison);
+        }
+
+        let report = report_dir.join("index.html");
+        match std::fs::write(&report, html_report(&comparisons)) {
+            Ok(()) => log::info!("Wrote the report to {report:?}"),
+            Err(err) => log::error!("Failed to write the report to {report:?}: {err}"),
+        }
+        comparisons.iter().all(|comparison| comparison.passed)
+    }
+
+    fn compare(&self, report_dir: &Path, checkpoint: &str, actual: &RgbaImage) -> Comparison {
+        save(actual, &report_dir.join(format!("{checkpoint}_actual.png")));
+        let golden_path = self.golden_image_path(checkpoint);
+        let Ok(golden) = image::open(&golden_path) else {
+            save(actual, &golden_path);
+            return Comparison { checkpoint: checkpoint.to_string(), differing_pixels: None, passed: false };
+        };
+        let golden = golden.into_rgba8();
+        save(&golden, &report_dir.join(format!("{checkpoint}_golden.png")));
+
+        if golden.dimensions() != actual.dimensions() {
+            log::info!("Checkpoint {checkpoint:?} is {:?}, but its golden image is {:?}", actual.dimensions(), golden.dimensions());
+            save(actual, &report_dir.join(format!("{checkpoint}_diff.png")));
+            return Comparison { checkpoint: checkpoint.to_string(), differing_pixels: Some(1.), passed: false };
+        }
+
+        // The differing pixels are red, over a faded copy of the image
+        let metric = self.config.metric(checkpoint);
+        let mut diff = RgbaImage::new(actual.width(), actual.height());
+        let mut differing = 0;
+        for ((a, b), out) in golden.pixels().zip(actual.pixels()).zip(diff.pixels_mut()) {
+            *out = if metric.differs(a, b) {
+                differing += 1;
+                Rgba([255, 0, 0, 255])
+            } else {
+                let luma = (b[0] as u32 * 299 + b[1] as u32 * 587 + b[2] as u32 * 114) / 1000;
+                let faded = (255 - (255 - luma) / 4) as u8;
+                Rgba([faded, faded, faded, 255])
+            };
+        }
+        save(&diff, &report_dir.join(format!("{checkpoint}_diff.png")));
+
+        let differing_pixels = differing as f32 / (actual.width() * actual.height()).max(1) as f32;
+        Comparison {
+            checkpoint: checkpoint.to_string(),
+            differing_pixels: Some(differing_pixels),
+            passed: differing_pixels <= metric.max_differing_pixels(),
+        }
+    }
+}
+
+fn save(image: &RgbaImage, path: &Path) {
+    if let Err(err) = image.save(path) {
+        log::error!("Failed to save {path:?}: {err}");
+    }
+}
+
+fn html_report(comparisons: &[Comparison]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Golden image test</title>\n<style>\n\
+         body { font-family: sans-serif; }\nimg { max-width: 400px; }\n.passed { color: green; }\n.failed { color: red; }\n\
+         </style>\n</head>\n<body>\n<h1>Golden image test</h1>\n<table>\n<tr><th>Checkpoint</th><th>Result</th><th>Golden</th><th>Actual</th><th>Difference</th></tr>\n",
+    );
+    for Comparison { checkpoint, differing_pixels, passed } in comparisons {
+        let (class, result) = match differing_pixels {
+            Some(differing) => (if *passed { "passed" } else { "failed" }, format!("{:.4}% of pixels differ", differing * 100.)),
+            None => ("failed", "No golden image".to_string()),
+        };
+        let image = |kind: &str| match (kind, differing_pixels) {
+            ("actual", _) | (_, Some(_)) => format!("<a href=\"{checkpoint}_{kind}.png\"><img src=\"{checkpoint}_{kind}.png\"></a>"),
+            _ => String::new(),
+        };
+        writeln!(
+            html,
+            "<tr><td>{checkpoint}</td><td class=\"{class}\">{result}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            image("golden"),
+            image("actual"),
+            image("diff")
+        )
+        .unwrap();
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,51 @@
+use ambient_core::loading::{custom_loading_screen, load_status, LoadStatus, ModuleLoadStage};
+use ambient_ecs::query;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::client::GameClient;
+use ambient_ui::{
+    layout::{height, width},
+    Centered, FlowColumn, FlowRow, PageScreen, Rectangle, StylesExt, Text, Throbber, UIExt,
+};
+use glam::vec4;
+
+const BAR_WIDTH: f32 = 300.;
+
+/// What's loading, for the detail line of the loading screen
+fn describe(status: &LoadStatus) -> String {
+    let module = status.modules.iter().find(|(_, stage)| !stage.is_done());
+    match module {
+        Some((name, ModuleLoadStage::Compiling)) => format!("Compiling {name}"),
+        Some((name, _)) => format!("Downloading {name}"),
+        None => format!("Loading {} assets", status.loading_assets.len()),
+    }
+}
+
+/// Shows how much of the game has loaded until it's done, unless a package shows its own loading screen
+#[element_component]
+pub fn LoadingScreen(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (status, set_status) = hooks.use_state(None::<LoadStatus>);
+    hooks.use_frame({
+        let status = status.clone();
+        move |_| {
+            let game_state = game_client.game_state.lock();
+            let world = &game_state.world;
+            let custom = query(custom_loading_screen()).iter(world, None).next().is_some();
+            let new_status = world.resource_opt(load_status()).filter(|status| !custom && !status.is_loaded()).cloned();
+            if new_status != status {
+                set_status(new_status);
+            }
+        }
+    });
+
+    let Some(status) = status else { return Element::new() };
+    let progress = status.progress();
+    PageScreen::el([Centered::el([FlowColumn::el([
+        FlowRow::el([Text::el("Loading").header_style(), Throbber.el()]),
+        FlowRow::el([
+            Rectangle.el().set(width(), BAR_WIDTH * progress).set(height(), 4.).with_background(vec4(1., 1., 1., 0.9)),
+            Rectangle.el().set(width(), BAR_WIDTH * (1. - progress)).set(height(), 4.).with_background(vec4(1., 1., 1., 0.2)),
+        ]),
+        Text::el(describe(&status)).small_style(),
+    ])])])
+}
@@ -1,39 +1,88 @@
-use std::{net::SocketAddr, path::PathBuf, process::exit, sync::Arc, time::Duration};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
 use ambient_app::{window_title, AppBuilder};
+use ambient_audio::{AudioMixer, AudioStream};
 use ambient_cameras::UICamera;
-use ambient_core::{camera::active_camera, runtime};
-use ambient_debugger::Debugger;
-use ambient_ecs::{Entity, SystemGroup, World};
-use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_core::{camera::active_camera, determinism::Determinism, gpu};
+use ambient_debugger::{Debugger, LogConsole};
+use ambient_ecs::{Entity, SystemGroup};
+use ambient_ecs_editor::ECSInspector;
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
 use ambient_network::{
+    capture::{capture, RecordingSettings},
     client::{GameClient, GameClientNetworkStats, GameClientRenderTarget, GameClientServerStats, GameClientView, UseOnce},
     events::ServerEventRegistry,
 };
-use ambient_renderer::RenderTarget;
+use ambient_settings::{PanelDock, PanelLayout, Settings};
 use ambient_std::{asset_cache::AssetCache, cb, friendly_id};
-use ambient_ui::{use_window_physical_resolution, Dock, FocusRoot, StylesExt, Text, WindowSized};
+use ambient_ui::{
+    command_modifier, use_window_physical_resolution, Dock, FocusRoot, Hotkey, Panel, PanelManager, StylesExt, Text, WindowSized,
+};
+use ambient_window_types::{ModifiersState, VirtualKeyCode};
 use glam::uvec2;
 
 use crate::{cli::RunCli, shared};
+use golden_image_test::GoldenImageTest;
+use loading_screen::LoadingScreen;
+use settings::SettingsPanel;
+use spectator::SpectatorCamera;
 
+mod golden_image_test;
+mod loading_screen;
+mod settings;
+mod spectator;
 mod wasm;
 
 /// Construct an app and enter the main client view
-pub async fn run(assets: AssetCache, server_addr: SocketAddr, run: &RunCli, project_path: Option<PathBuf>) {
+pub async fn run(
+    assets: AssetCache,
+    server_addr: SocketAddr,
+    run: &RunCli,
+    project_path: Option<PathBuf>,
+    determinism: Option<Determinism>,
+) {
     let user_id = run.user_id.clone().unwrap_or_else(|| format!("user_{}", friendly_id()));
+    let auth_token = run.auth_token.clone().or_else(|| std::env::var("AMBIENT_AUTH_TOKEN").ok());
     let headless = if run.headless { Some(uvec2(400, 400)) } else { None };
 
     let is_debug = std::env::var("AMBIENT_DEBUGGER").is_ok() || run.debugger;
 
+    let mut settings = Settings::load_or_default();
+    if let Some(gpu) = &run.gpu {
+        settings.gpu.apply_cli(gpu);
+    }
+    if let Some(backend) = run.gpu_backend {
+        settings.gpu.backend = backend;
+    }
+
+    // Sounds are only heard while the stream is alive, so it's kept until the client exits
+    let audio_stream = if run.headless {
+        None
+    } else {
+        AudioStream::new().map_err(|err| log::warn!("Failed to open the audio output, sounds won't be played: {err:?}")).ok()
+    };
+    let audio_mixer = audio_stream.as_ref().map(|stream| stream.mixer().clone());
+
     AppBuilder::new()
         .ui_renderer(true)
         .with_asset_cache(assets)
+        .with_settings(settings)
         .headless(headless)
         .run(move |app, _runtime| {
-            MainApp { server_addr, user_id, show_debug: is_debug, screenshot_test: run.screenshot_test, project_path }
-                .el()
-                .spawn_interactive(&mut app.world);
+            crate::crash_reporter::set_gpu_adapter(format!("{:?}", app.world.resource(gpu()).adapter.get_info()));
+            MainApp {
+                server_addr,
+                user_id,
+                auth_token,
+                spectator: run.spectator,
+                show_debug: is_debug,
+                screenshot_test: run.screenshot_test,
+                project_path,
+                determinism,
+                audio_mixer,
+            }
+            .el()
+            .spawn_interactive(&mut app.world);
         })
         .await;
 }
@@ -44,8 +93,12 @@ fn MainApp(
     server_addr: SocketAddr,
     project_path: Option<PathBuf>,
     user_id: String,
+    auth_token: Option<String>,
+    spectator: bool,
     show_debug: bool,
     screenshot_test: Option<f32>,
+    determinism: Option<Determinism>,
+    audio_mixer: Option<AudioMixer>,
 ) -> Element {
     let resolution = use_window_physical_resolution(hooks);
 
@@ -54,86 +107,147 @@ fn MainApp(
 
     *hooks.world.resource_mut(window_title()) = "Ambient".to_string();
 
+    let (golden_image_test, _) =
+        hooks.use_state_with(|_| screenshot_test.map(|seconds| GoldenImageTest::new(project_path.unwrap_or_default(), seconds)));
+    let golden_image_test_systems = golden_image_test.clone();
+
     FocusRoot::el([
         UICamera.el().set(active_camera(), 0.),
         shared::player::PlayerRawInputHandler.el(),
+        shared::player::PlayerGamepadRumble.el(),
+        shared::player::LocalPlayersHandler.el(),
         shared::player::PlayerDataUpload.el(),
         WindowSized::el([GameClientView {
             server_addr,
             user_id,
+            auth_token,
+            spectator,
             resolution,
             on_disconnect: cb(move || {}),
-            init_world: cb(UseOnce::new(Box::new(move |world, render_target| {
+            init_world: cb(UseOnce::new(Box::new(move |world, _render_target| {
+                if let Some(determinism) = determinism {
+                    determinism.apply(world);
+                }
                 wasm::initialize(world).unwrap();
+                if let Some(audio_mixer) = audio_mixer {
+                    if let Err(err) = ambient_world_audio::systems::setup_audio(world, audio_mixer) {
+                        log::error!("Failed to set up audio: {err:?}");
+                    }
+                }
 
-                world.add_resource(ambient_network::events::event_registry(), Arc::new(ServerEventRegistry::new()));
-                if let Some(seconds) = screenshot_test {
-                    run_screenshot_test(world, render_target, project_path, seconds);
+                let event_registry = ServerEventRegistry::new();
+                ambient_world_audio::register_client_events(&event_registry);
+                world.add_resource(ambient_network::events::event_registry(), Arc::new(event_registry));
+                if let Some(golden_image_test) = &golden_image_test {
+                    golden_image_test.start(world);
                 }
             }))),
             on_loaded: cb(move |_game_state, _game_client| Ok(Box::new(|| {}))),
             error_view: cb(move |error| Dock(vec![Text::el("Error").header_style(), Text::el(error)]).el()),
             on_network_stats: cb(move |stats| update_network_stats(stats)),
             on_server_stats: cb(move |stats| update_server_stats(stats)),
-            systems_and_resources: cb(|| (systems(), Entity::new())),
+            systems_and_resources: cb(move || {
+                let mut systems = systems();
+                if let Some(golden_image_test) = &golden_image_test_systems {
+                    systems.add(Box::new(golden_image_test.systems()));
+                }
+                (systems, Entity::new())
+            }),
             create_rpc_registry: cb(shared::create_rpc_registry),
             on_in_entities: None,
-            ui: GameView { show_debug }.el(),
+            ui: Group(vec![GameView { show_debug }.el(), SpectatorCamera.el(), LoadingScreen.el()]).el(),
         }
         .el()]),
     ])
 }
 
-fn run_screenshot_test(world: &World, render_target: Arc<RenderTarget>, project_path: Option<PathBuf>, seconds: f32) {
-    world.resource(runtime()).spawn(async move {
-        tokio::time::sleep(Duration::from_secs_f32(seconds)).await;
-        let screenshot = project_path.unwrap_or(PathBuf::new()).join("screenshot.png");
-        log::info!("Loading screenshot from {:?}", screenshot);
-        let old = image::open(&screenshot);
-        log::info!("Saving screenshot to {:?}", screenshot);
-        let new = render_target.color_buffer.reader().read_image().await.unwrap().into_rgba8();
-        log::info!("Screenshot saved");
-        new.save(screenshot).unwrap();
-        let epsilon = 3;
-        if let Ok(old) = old {
-            log::info!("Comparing screenshots");
-            let old = old.into_rgba8();
-            for (a, b) in old.pixels().zip(new.pixels()) {
-                if (a[0]).abs_diff(b[0]) > epsilon
-                    || (a[1]).abs_diff(b[1]) > epsilon
-                    || (a[2]).abs_diff(b[2]) > epsilon
-                    || (a[3]).abs_diff(b[3]) > epsilon
-                {
-                    log::info!("Screenshots differ");
-                    exit(1);
-                }
-            }
-            log::info!("Screenshots are identical");
-            exit(0);
-        } else {
-            log::info!("No old screenshot to compare to");
-            exit(1);
-        }
-    });
-}
-
 #[element_component]
 fn GameView(hooks: &mut Hooks, show_debug: bool) -> Element {
     let (state, _) = hooks.consume_context::<GameClient>().unwrap();
     let (render_target, _) = hooks.consume_context::<GameClientRenderTarget>().unwrap();
+    let (show_inspector, set_show_inspector) = hooks.use_state(false);
+    let (show_log_console, set_show_log_console) = hooks.use_state(false);
+    let (show_settings, set_show_settings) = hooks.use_state(false);
 
-    if show_debug {
-        Debugger {
-            get_state: cb(move |cb| {
-                let mut game_state = state.game_state.lock();
-                let game_state = &mut *game_state;
-                cb(&mut game_state.renderer, &render_target.0, &mut game_state.world);
-            }),
-        }
-        .el()
-    } else {
-        Element::new()
+    let capture = state.game_state.lock().world.resource(capture()).clone();
+
+    let mut panels = Vec::new();
+    if show_inspector {
+        let state = state.clone();
+        panels.push(
+            Panel::new("inspector", "Inspector", ECSInspector { get_world: cb(move |cb| cb(&state.game_state.lock().world)) }.el())
+                .default_layout(PanelLayout { dock: PanelDock::Right, ..Default::default() }),
+        );
+    }
+    if show_log_console {
+        panels.push(Panel::new("log_console", "Log console", LogConsole.el()).default_layout(PanelLayout {
+            dock: PanelDock::Bottom,
+            height: 250.,
+            ..Default::default()
+        }));
+    }
+    if show_settings {
+        panels.push(Panel::new("settings", "Settings", SettingsPanel.el()));
+    }
+    let content = PanelManager {
+        panels,
+        content: if show_debug {
+            Debugger {
+                get_state: cb({
+                    let state = state.clone();
+                    move |cb| {
+                        let mut game_state = state.game_state.lock();
+                        let game_state = &mut *game_state;
+                        cb(&mut game_state.renderer, &render_target.0, &mut game_state.world);
+                    }
+                }),
+                show_inspector,
+                set_show_inspector: set_show_inspector.clone(),
+            }
+            .el()
+        } else {
+            Element::new()
+        },
+    }
+    .el();
+
+    // The inspector, the log console and the settings are available in all clients, not only when the debugger is enabled
+    let content =
+        Hotkey::new(VirtualKeyCode::F9, move |_| set_show_inspector(!show_inspector), content).hotkey_modifier(ModifiersState::SHIFT).el();
+    let content = Hotkey::new(VirtualKeyCode::F12, move |_| set_show_log_console(!show_log_console), content)
+        .hotkey_modifier(ModifiersState::SHIFT)
+        .el();
+    let content =
+        Hotkey::new(VirtualKeyCode::Comma, move |_| set_show_settings(!show_settings), content).hotkey_modifier(command_modifier()).el();
+
+    // F2 saves a screenshot of the game, and Shift+F2 starts and stops recording it.
+    // The hotkeys are invoked both when they're pressed and released, so they only act on presses
+    let content = Hotkey {
+        on_is_pressed_changed: Some(cb({
+            let capture = capture.clone();
+            move |pressed| {
+                if pressed {
+                    capture.screenshot(None).unwrap();
+                }
+            }
+        })),
+        ..Hotkey::new(VirtualKeyCode::F2, |_| {}, content)
+    }
+    .el();
+    Hotkey {
+        on_is_pressed_changed: Some(cb(move |pressed| {
+            if !pressed {
+                return;
+            }
+            if capture.is_recording() {
+                capture.stop_recording();
+            } else {
+                capture.start_recording(RecordingSettings::default()).unwrap();
+            }
+        })),
+        ..Hotkey::new(VirtualKeyCode::F2, |_| {}, content).hotkey_modifier(ModifiersState::SHIFT)
     }
+    .el()
 }
 
 fn systems() -> SystemGroup {
@@ -141,10 +255,15 @@ fn systems() -> SystemGroup {
         "client",
         vec![
             Box::new(ambient_decals::client_systems()),
+            Box::new(ambient_prefab::prefetch::systems()),
             Box::new(ambient_primitives::systems()),
             Box::new(ambient_sky::systems()),
             Box::new(ambient_water::systems()),
+            Box::new(ambient_weather::systems()),
             Box::new(ambient_physics::client_systems()),
+            Box::new(ambient_network::time_control::client_systems()),
+            Box::new(ambient_world_audio::systems::client_systems()),
+            Box::new(ambient_core::loading::systems()),
             Box::new(wasm::systems()),
         ],
     )
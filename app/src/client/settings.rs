@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_network::client::GameClient;
+use ambient_settings::{
+    package_settings_schema, settings, update_settings, PackageSetting, PackageSettingsSchema, RenderSettings, SettingValue, Settings,
+};
+use ambient_std::{cb, color::Color};
+use ambient_ui::{
+    fit_horizontal, space_between_items, width, Button, ButtonStyle, Checkbox, Editor, EditorOpts, Fit, FlowColumn, FlowRow, IntegerSlider,
+    Separator, Slider, StylesExt, Text, UIExt, STREET,
+};
+use itertools::Itertools;
+
+const RESOLUTION_SCALES: [f32; 5] = [0.5, 0.75, 1., 1.5, 2.];
+
+fn setting_editor(setting: &PackageSetting, value: SettingValue, on_change: impl Fn(SettingValue) + Sync + Send + 'static) -> Element {
+    match (value, setting.min, setting.max) {
+        (SettingValue::Bool(value), _, _) => Checkbox::new(value, move |value| on_change(SettingValue::Bool(value))).el(),
+        (SettingValue::I32(value), Some(min), Some(max)) => IntegerSlider {
+            value,
+            on_change: Some(cb(move |value: i32| on_change(SettingValue::I32(value)))),
+            min: min as i32,
+            max: max as i32,
+            width: 200.,
+            logarithmic: false,
+            suffix: None,
+        }
+        .el(),
+        (SettingValue::I32(value), _, _) => value.editor(cb(move |value: i32| on_change(SettingValue::I32(value))), EditorOpts::default()),
+        (SettingValue::F32(value), Some(min), Some(max)) => Slider {
+            value,
+            on_change: Some(cb(move |value: f32| on_change(SettingValue::F32(value)))),
+            min: min as f32,
+            max: max as f32,
+            width: 200.,
+            logarithmic: false,
+            round: Some(2),
+            suffix: None,
+        }
+        .el(),
+        (SettingValue::F32(value), _, _) => value.editor(cb(move |value: f32| on_change(SettingValue::F32(value))), EditorOpts::default()),
+        (SettingValue::String(value), _, _) => {
+            value.editor(cb(move |value: String| on_change(SettingValue::String(value))), EditorOpts::default())
+        }
+    }
+}
+
+/// Shows the render settings and the settings declared by the project, generated from their schemas.
+///
+/// Changes are applied immediately through [update_settings], and saved to the settings file.
+#[element_component]
+pub fn SettingsPanel(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (current, set_current) = hooks.use_state_with(|world| world.resource(settings()).clone());
+    let (schemas, set_schemas) = hooks.use_state(Vec::<PackageSettingsSchema>::new());
+
+    let prev_schemas = hooks.use_ref_with(|_| Vec::<PackageSettingsSchema>::new());
+    hooks.use_interval_deps(Duration::from_secs(1), true, (), move |_| {
+        let schemas = game_client.game_state.lock().world.synced_resource(package_settings_schema()).cloned().unwrap_or_default();
+        let mut prev_schemas = prev_schemas.lock();
+        if *prev_schemas != schemas {
+            *prev_schemas = schemas.clone();
+            set_schemas(schemas);
+        }
+    });
+    hooks.use_effect(current.clone(), |world, current| {
+        if world.resource(settings()) != current {
+            update_settings(world, current.clone());
+            // Only what can be changed here is saved; the other settings in the world may have been overridden from the command line
+            let mut saved = Settings::load_or_default();
+            saved.render = current.render.clone();
+            saved.packages = current.packages.clone();
+            if let Err(err) = saved.save() {
+                log::warn!("Failed to save settings: {err:?}");
+            }
+        }
+        Box::new(|_| {})
+    });
+
+    let set_render = {
+        let current = current.clone();
+        let set_current = set_current.clone();
+        move |change: &dyn Fn(&mut RenderSettings)| {
+            let mut settings = current.clone();
+            change(&mut settings.render);
+            set_current(settings);
+        }
+    };
+    let row = |label: &str, description: &str, editor: Element| {
+        FlowRow::el([
+            FlowColumn::el([Text::el(label), if description.is_empty() { Element::new() } else { Text::el(description).small_style() }])
+                .set(width(), 250.),
+            editor,
+        ])
+        .set(space_between_items(), STREET)
+    };
+
+    let mut sections = vec![
+        Text::el("Rendering").section_style(),
+        row(
+            "Anti-aliasing (MSAA)",
+            "The number of samples per pixel",
+            FlowRow::el(
+                RenderSettings::SUPPORTED_MSAA_SAMPLES
+                    .iter()
+                    .map(|&samples| {
+                        let set_render = set_render.clone();
+                        Button::new(if samples == 1 { "Off".to_string() } else { format!("{samples}x") }, move |_| {
+                            set_render(&|render| render.msaa_samples = samples)
+                        })
+                        .toggled(current.render.msaa_samples() == samples)
+                        .style(ButtonStyle::Flat)
+                        .el()
+                    })
+                    .collect_vec(),
+            ),
+        ),
+        row(
+            "Resolution scale",
+            "The resolution the 3D scene is rendered at, relative to the window",
+            FlowRow::el(
+                RESOLUTION_SCALES
+                    .iter()
+                    .map(|&scale| {
+                        let set_render = set_render.clone();
+                        Button::new(format!("{}%", (scale * 100.) as u32), move |_| set_render(&|render| render.resolution_scale = scale))
+                            .toggled(current.render.resolution_scale() == scale)
+                            .style(ButtonStyle::Flat)
+                            .el()
+                    })
+                    .collect_vec(),
+            ),
+        ),
+        row(
+            "Pixel-accurate picking",
+            "Picks what's under the cursor on the GPU, rather than by bounding boxes",
+            Checkbox::new(current.render.gpu_picking, {
+                let set_render = set_render.clone();
+                move |value| set_render(&move |render| render.gpu_picking = value)
+            })
+            .el(),
+        ),
+    ];
+    for schema in schemas.iter().filter(|schema| !schema.settings.is_empty()) {
+        sections.push(Separator { vertical: false }.el());
+        sections.push(Text::el(&schema.name).section_style());
+        for setting in &schema.settings {
+            let value = current.package_value(&schema.package, setting);
+            let set_value = {
+                let current = current.clone();
+                let set_current = set_current.clone();
+                let package = schema.package.clone();
+                let setting = setting.clone();
+                move |value: SettingValue| {
+                    let mut settings = current.clone();
+                    settings.set_package_value(&package, &setting.key, &setting.clamp(value));
+                    set_current(settings);
+                }
+            };
+            sections.push(row(
+                &setting.name,
+                &setting.description,
+                FlowRow::el([
+                    setting_editor(setting, value.clone(), set_value.clone()),
+                    if value != setting.default {
+                        let default = setting.default.clone();
+                        Button::new("\u{f0e2}", move |_| set_value(default.clone()))
+                            .tooltip("Reset to default")
+                            .style(ButtonStyle::Flat)
+                            .el()
+                    } else {
+                        Element::new()
+                    },
+                ])
+                .set(space_between_items(), STREET),
+            ));
+        }
+    }
+
+    FlowColumn::el(sections)
+        .set(space_between_items(), STREET)
+        .with_background(Color::rgba(0., 0., 0., 0.8).into())
+        .set(fit_horizontal(), Fit::Parent)
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashSet, f32::consts::FRAC_PI_2, str::FromStr};
+
+use ambient_core::{
+    camera::{
+        active_camera, aspect_ratio, aspect_ratio_from_window, fovy, near, perspective_infinite_reverse, projection, projection_view,
+    },
+    main_scene,
+    player::{get_spectator_by_user_id, player, user_id},
+    transform::{inv_local_to_world, local_to_world, lookat_center, lookat_up, translation},
+};
+use ambient_ecs::{query, Entity, EntityId, World};
+use ambient_element::{element_component, Element, Hooks};
+use ambient_event_types::{WINDOW_KEYBOARD_INPUT, WINDOW_MOUSE_INPUT, WINDOW_MOUSE_MOTION};
+use ambient_input::{event_keyboard_input, event_mouse_input, event_mouse_motion, keycode, mouse_button};
+use ambient_network::client::GameClient;
+use ambient_sys::time::Instant;
+use ambient_window_types::{MouseButton, VirtualKeyCode};
+use glam::{vec3, Vec2, Vec3};
+
+/// In meters per second
+const FLY_SPEED: f32 = 10.;
+/// In radians per pixel
+const LOOK_SPEED: f32 = 0.005;
+/// How far behind the player the camera is when following them, in meters
+const FOLLOW_DISTANCE: f32 = 8.;
+const FOLLOW_KEYS: [VirtualKeyCode; 9] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+];
+
+#[derive(Debug, Default)]
+struct SpectatorInput {
+    keys: HashSet<VirtualKeyCode>,
+    /// The keys pressed since the last frame
+    pressed: Vec<VirtualKeyCode>,
+    is_looking: bool,
+    mouse_delta: Vec2,
+}
+
+#[derive(Debug)]
+struct SpectatorState {
+    camera: Option<EntityId>,
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+    /// The user id of the player being followed, if any
+    following: Option<String>,
+    last_frame: Instant,
+}
+
+fn direction(yaw: f32, pitch: f32) -> Vec3 {
+    vec3(pitch.cos() * yaw.cos(), pitch.cos() * yaw.sin(), pitch.sin())
+}
+
+fn camera_entity_data(user: &str, position: Vec3) -> Entity {
+    Entity::new()
+        .with_default(local_to_world())
+        .with_default(inv_local_to_world())
+        .with(near(), 0.1)
+        .with(fovy(), 1.0)
+        .with(perspective_infinite_reverse(), ())
+        .with(aspect_ratio(), 1.)
+        .with(aspect_ratio_from_window(), EntityId::resources())
+        .with_default(projection())
+        .with_default(projection_view())
+        .with(translation(), position)
+        .with(lookat_center(), position + Vec3::X)
+        .with(lookat_up(), Vec3::Z)
+        .with_default(main_scene())
+        // Above the cameras that the game might give the spectator
+        .with(active_camera(), 1000.)
+        .with(user_id(), user.to_string())
+}
+
+/// Where the player with `user` is; their player entity is usually separate from their body, so this is the position of
+/// any of their entities which isn't a camera
+fn player_position(world: &World, user: &str) -> Option<Vec3> {
+    query((user_id(), translation())).excl(projection()).iter(world, None).find(|(_, (id, _))| *id == user).map(|(_, (_, pos))| *pos)
+}
+
+/// Controls the camera of the local user if they joined as a spectator.
+///
+/// It flies with WASD, and Q and E to go down and up, and looks around while the right mouse button is held.
+/// The number keys follow the players, in the order of their user ids, Tab follows the next player, and Escape goes
+/// back to flying.
+#[element_component]
+pub fn SpectatorCamera(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let input = hooks.use_ref_with(|_| SpectatorInput::default());
+    let state = hooks.use_ref_with(|_| SpectatorState {
+        camera: None,
+        position: vec3(-10., -10., 10.),
+        yaw: FRAC_PI_2 / 2.,
+        pitch: -0.5,
+        following: None,
+        last_frame: Instant::now(),
+    });
+
+    hooks.use_multi_event(&[WINDOW_KEYBOARD_INPUT, WINDOW_MOUSE_INPUT, WINDOW_MOUSE_MOTION], {
+        let input = input.clone();
+        move |_world, event| {
+            let mut input = input.lock();
+            if let Some(pressed) = event.get(event_keyboard_input()) {
+                let Some(keycode) = event.get_ref(keycode()).and_then(|keycode| VirtualKeyCode::from_str(keycode).ok()) else { return };
+                if pressed {
+                    input.keys.insert(keycode);
+                    input.pressed.push(keycode);
+                } else {
+                    input.keys.remove(&keycode);
+                }
+            } else if let Some(pressed) = event.get(event_mouse_input()) {
+                if event.get(mouse_button()).map(MouseButton::from) == Some(MouseButton::Right) {
+                    input.is_looking = pressed;
+                }
+            } else if let Some(delta) = event.get(event_mouse_motion()) {
+                if input.is_looking {
+                    input.mouse_delta += delta;
+                }
+            }
+        }
+    });
+
+    hooks.use_frame(move |_| {
+        let mut game_state = game_client.game_state.lock();
+        let world = &mut game_state.world;
+        let mut state = state.lock();
+        let dt = state.last_frame.elapsed().as_secs_f32();
+        state.last_frame = Instant::now();
+
+        if get_spectator_by_user_id(world, &game_client.user_id).is_none() {
+            if let Some(camera) = state.camera.take() {
+                world.despawn(camera);
+            }
+            return;
+        }
+        let camera = match state.camera {
+            Some(camera) if world.exists(camera) => camera,
+            _ => {
+                let camera = camera_entity_data(&game_client.user_id, state.position).spawn(world);
+                state.camera = Some(camera);
+                camera
+            }
+        };
+
+        let (keys, pressed, mouse_delta) = {
+            let mut input = input.lock();
+            (input.keys.clone(), std::mem::take(&mut input.pressed), std::mem::take(&mut input.mouse_delta))
+        };
+        let mut players = query(user_id()).incl(player()).iter(world, None).map(|(_, id)| id.clone()).collect::<Vec<_>>();
+        players.sort();
+        for key in pressed {
+            if let Some(index) = FOLLOW_KEYS.iter().position(|follow_key| *follow_key == key) {
+                if let Some(player) = players.get(index) {
+                    state.following = Some(player.clone());
+                }
+            } else if key == VirtualKeyCode::Tab && !players.is_empty() {
+                let next = match &state.following {
+                    Some(following) => players.iter().position(|player| player == following).map_or(0, |index| (index + 1) % players.len()),
+                    None => 0,
+                };
+                state.following = Some(players[next].clone());
+            } else if key == VirtualKeyCode::Escape {
+                state.following = None;
+            }
+        }
+
+        state.yaw -= mouse_delta.x * LOOK_SPEED;
+        state.pitch = (state.pitch - mouse_delta.y * LOOK_SPEED).clamp(-FRAC_PI_2 + 0.01, FRAC_PI_2 - 0.01);
+        let direction = direction(state.yaw, state.pitch);
+
+        let target = state.following.as_ref().and_then(|following| player_position(world, following));
+        if let Some(target) = target {
+            state.position = target - direction * FOLLOW_DISTANCE;
+        } else {
+            // Also if the followed player left
+            state.following = None;
+            let right = direction.cross(Vec3::Z).normalize_or_zero();
+            let axes = [
+                (VirtualKeyCode::W, direction),
+                (VirtualKeyCode::S, -direction),
+                (VirtualKeyCode::D, right),
+                (VirtualKeyCode::A, -right),
+                (VirtualKeyCode::E, Vec3::Z),
+                (VirtualKeyCode::Q, -Vec3::Z),
+            ];
+            let velocity = axes.iter().filter(|(key, _)| keys.contains(key)).map(|(_, axis)| *axis).sum::<Vec3>();
+            state.position += velocity * FLY_SPEED * dt;
+        }
+
+        world.set(camera, translation(), state.position).ok();
+        world.set(camera, lookat_center(), target.unwrap_or(state.position + direction)).ok();
+    });
+
+    Element::new()
+}
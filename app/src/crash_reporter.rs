@@ -0,0 +1,233 @@
+//! Opt-in crash reporting.
+//!
+//! When enabled (through the `crash_reports` settings or the `AMBIENT_CRASH_REPORTS` environment variable), panics and
+//! native crashes write a [CrashReport] to the crash directory. On the next launch, the user is offered to upload any
+//! pending reports to the configured endpoint.
+
+use std::{
+    backtrace::Backtrace,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ambient_core::logging::LogBuffer;
+use ambient_settings::CrashReportSettings;
+use anyhow::Context;
+use parking_lot::{const_mutex, Mutex};
+use serde::{Deserialize, Serialize};
+
+/// The number of log lines kept around to be included in crash reports
+const MAX_LOG_LINES: usize = 200;
+
+/// What is known about the running process, collected as it starts up
+struct CrashContext {
+    gpu_adapter: Option<String>,
+    packages: Vec<String>,
+}
+
+static CONTEXT: Mutex<CrashContext> = const_mutex(CrashContext { gpu_adapter: None, packages: Vec::new() });
+
+pub fn set_gpu_adapter(adapter: String) {
+    CONTEXT.lock().gpu_adapter = Some(adapter);
+}
+pub fn set_packages(packages: Vec<String>) {
+    CONTEXT.lock().packages = packages;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub ambient_version: String,
+    pub os: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+    pub gpu_adapter: Option<String>,
+    pub packages: Vec<String>,
+    pub recent_log: Vec<String>,
+    /// The file name of the minidump next to this report, for native crashes
+    pub minidump: Option<String>,
+}
+impl CrashReport {
+    fn new(message: String, location: Option<String>, backtrace: Option<String>, minidump: Option<String>) -> Self {
+        // Don't block forever if the crash happened while the context or the log was locked
+        let (gpu_adapter, packages) = match CONTEXT.try_lock() {
+            Some(context) => (context.gpu_adapter.clone(), context.packages.clone()),
+            None => Default::default(),
+        };
+        let recent_log = LogBuffer::global()
+            .try_recent(MAX_LOG_LINES)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|record| format!("{} [{} {}] {}", record.time.format("%H:%M:%S%.3f"), record.level, record.target, record.message))
+            .collect();
+        Self {
+            ambient_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+            message,
+            location,
+            thread: std::thread::current().name().map(|name| name.to_string()),
+            backtrace,
+            gpu_adapter,
+            packages,
+            recent_log,
+            minidump,
+        }
+    }
+    fn write(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("crash-{}-{}.json", self.timestamp, std::process::id()));
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// Where crash reports are written to
+pub fn crash_dir() -> Option<PathBuf> {
+    directories_next::ProjectDirs::from("com", "Ambient", "Ambient").map(|dirs| dirs.data_local_dir().join("crashes"))
+}
+
+fn is_enabled(settings: &CrashReportSettings) -> bool {
+    settings.enabled || std::env::var("AMBIENT_CRASH_REPORTS").is_ok()
+}
+
+/// Keeps the native crash handler installed; crashes are only caught while this is alive
+pub struct CrashReporter {
+    #[cfg(not(target_os = "unknown"))]
+    _native: Option<breakpad_handler::BreakpadHandler>,
+}
+
+/// Installs the panic hook and native crash handler, if crash reporting is enabled
+pub fn install(settings: &CrashReportSettings) -> Option<CrashReporter> {
+    if !is_enabled(settings) {
+        return None;
+    }
+    let dir = crash_dir()?;
+
+    let prev_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new({
+        let dir = dir.clone();
+        move |info| {
+            let message = match (info.payload().downcast_ref::<&str>(), info.payload().downcast_ref::<String>()) {
+                (Some(message), _) => message.to_string(),
+                (_, Some(message)) => message.clone(),
+                _ => "Box<dyn Any>".to_string(),
+            };
+            let location = info.location().map(|location| location.to_string());
+            let report = CrashReport::new(message, location, Some(Backtrace::force_capture().to_string()), None);
+            match report.write(&dir) {
+                Ok(path) => eprintln!("A crash report was written to {path:?}"),
+                Err(err) => eprintln!("Failed to write crash report: {err:?}"),
+            }
+            prev_hook(info);
+        }
+    }));
+
+    // Segfaults, aborts and other native crashes write a minidump, which the report refers to. The backtrace can be
+    // recovered from the minidump when it's symbolicated.
+    #[cfg(not(target_os = "unknown"))]
+    let native = {
+        let _ = std::fs::create_dir_all(&dir);
+        let report_dir = dir.clone();
+        breakpad_handler::BreakpadHandler::attach(
+            &dir,
+            breakpad_handler::InstallOptions::ExceptionHandler,
+            Box::new(move |minidump_path: PathBuf| {
+                let minidump = minidump_path.file_name().map(|name| name.to_string_lossy().to_string());
+                let _ = CrashReport::new("Native crash".to_string(), None, None, minidump).write(&report_dir);
+            }),
+        )
+        .map_err(|err| log::warn!("Failed to install native crash handler: {err:?}"))
+        .ok()
+    };
+
+    log::info!("Crash reporting is enabled; reports are written to {dir:?}");
+    Some(CrashReporter {
+        #[cfg(not(target_os = "unknown"))]
+        _native: native,
+    })
+}
+
+fn pending_reports(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut reports = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+        .collect::<Vec<_>>();
+    reports.sort();
+    reports
+}
+
+/// Moves a report and its minidump out of the way, so that it isn't offered again
+fn archive_report(dir: &Path, path: &Path, report: Option<&CrashReport>) -> anyhow::Result<()> {
+    let archive = dir.join("archive");
+    std::fs::create_dir_all(&archive)?;
+    if let Some(minidump) = report.and_then(|report| report.minidump.as_ref()) {
+        let _ = std::fs::rename(dir.join(minidump), archive.join(minidump));
+    }
+    std::fs::rename(path, archive.join(path.file_name().context("Report has no file name")?))?;
+    Ok(())
+}
+
+fn upload_report(client: &reqwest::blocking::Client, endpoint: &str, dir: &Path, report: &CrashReport) -> anyhow::Result<()> {
+    let minidump = match &report.minidump {
+        Some(minidump) => Some(base64::encode(std::fs::read(dir.join(minidump))?)),
+        None => None,
+    };
+    client
+        .post(endpoint)
+        .json(&serde_json::json!({ "report": report, "minidump": minidump }))
+        .send()
+        .context("Failed to send crash report")?
+        .error_for_status()?;
+    Ok(())
+}
+
+fn ask(question: &str) -> bool {
+    print!("{question} [y/N] ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Offers to upload the reports left behind by previous crashes. Reports are archived once they have been offered,
+/// whether they were uploaded or not.
+pub fn offer_upload(settings: &CrashReportSettings) {
+    if !is_enabled(settings) {
+        return;
+    }
+    let Some(dir) = crash_dir() else { return };
+    let reports = pending_reports(&dir);
+    if reports.is_empty() {
+        return;
+    }
+    let Some(endpoint) = &settings.upload_endpoint else {
+        log::info!("Ambient crashed previously; {} crash report(s) can be found in {dir:?}", reports.len());
+        return;
+    };
+    let upload =
+        settings.auto_upload || ask(&format!("Ambient crashed previously. Upload {} crash report(s) to {endpoint}?", reports.len()));
+
+    let client = reqwest::blocking::Client::new();
+    for path in reports {
+        let report = std::fs::read(&path).ok().and_then(|data| serde_json::from_slice::<CrashReport>(&data).ok());
+        if upload {
+            let Some(report) = &report else {
+                log::warn!("Skipping unreadable crash report {path:?}");
+                continue;
+            };
+            if let Err(err) = upload_report(&client, endpoint, &dir, report) {
+                log::warn!("Failed to upload crash report {path:?}: {err:?}");
+                continue;
+            }
+        }
+        if let Err(err) = archive_report(&dir, &path, report.as_ref()) {
+            log::warn!("Failed to archive crash report {path:?}: {err:?}");
+        }
+    }
+}
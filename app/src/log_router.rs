@@ -0,0 +1,73 @@
+//! Routes log events through the [LogBuffer]: its filter decides what is logged, and everything that is logged is also kept
+//! in the buffer, so that it can be shown in the log console and included in crash reports.
+
+use ambient_core::logging::LogBuffer;
+use tracing::Level;
+
+fn to_level(level: log::Level) -> Level {
+    match level {
+        log::Level::Error => Level::ERROR,
+        log::Level::Warn => Level::WARN,
+        log::Level::Info => Level::INFO,
+        log::Level::Debug => Level::DEBUG,
+        log::Level::Trace => Level::TRACE,
+    }
+}
+
+/// Forwards the `log` records that pass the filter to the terminal logger
+pub struct LogRouter(pub env_logger::Logger);
+impl log::Log for LogRouter {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        LogBuffer::global().enabled(metadata.target(), to_level(metadata.level()))
+    }
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        LogBuffer::global().push(to_level(record.level()), record.target(), record.args().to_string());
+        self.0.log(record);
+    }
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+/// Filters `tracing` events like [LogRouter::enabled]. The max level hint lets `tracing` skip the levels that the filter
+/// leaves out entirely; it's queried again when the filter changes.
+#[cfg(feature = "tracing")]
+pub struct LogBufferFilter;
+#[cfg(feature = "tracing")]
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for LogBufferFilter {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) -> bool {
+        LogBuffer::global().enabled(metadata.target(), *metadata.level())
+    }
+    fn max_level_hint(&self) -> Option<tracing::metadata::LevelFilter> {
+        Some(LogBuffer::global().filter().max_level())
+    }
+}
+
+/// The `tracing` equivalent of [LogRouter]; the filtering is done by [LogBufferFilter]
+#[cfg(feature = "tracing")]
+pub struct LogBufferLayer;
+#[cfg(feature = "tracing")]
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+        struct Visitor(String);
+        impl tracing::field::Visit for Visitor {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                if !self.0.is_empty() {
+                    self.0.push(' ');
+                }
+                if field.name() == "message" {
+                    self.0 += &format!("{value:?}");
+                } else {
+                    self.0 += &format!("{}={value:?}", field.name());
+                }
+            }
+        }
+        let metadata = event.metadata();
+        let mut visitor = Visitor(String::new());
+        event.record(&mut visitor);
+        LogBuffer::global().push(*metadata.level(), metadata.target(), visitor.0);
+    }
+}
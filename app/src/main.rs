@@ -6,16 +6,20 @@ use clap::Parser;
 
 mod cli;
 mod client;
+mod crash_reporter;
+mod log_router;
 mod server;
 mod shared;
 
+use ambient_core::logging::{LogBuffer, LogFilter};
 use ambient_physics::physx::PhysicsKey;
+use ambient_settings::Settings;
 use anyhow::Context;
 use cli::Cli;
 use log::LevelFilter;
 use server::QUIC_INTERFACE_PORT;
 
-fn setup_logging() -> anyhow::Result<()> {
+fn setup_logging(settings_filter: &str) -> anyhow::Result<()> {
     const MODULES: &[(LevelFilter, &[&str])] = &[
         (
             LevelFilter::Error,
@@ -41,66 +45,58 @@ fn setup_logging() -> anyhow::Result<()> {
         ),
     ];
 
-    // Initialize the logger and lower the log level for modules we don't need to hear from by default.
-    #[cfg(not(feature = "tracing"))]
-    {
-        let mut builder = env_logger::builder();
-        builder.filter_level(LevelFilter::Info);
-
-        for (level, modules) in MODULES {
-            for module in *modules {
-                builder.filter_module(module, *level);
-            }
+    // Lower the log level for modules we don't need to hear from by default. The filter from the settings is applied on top
+    // of that, and `RUST_LOG` on top of both.
+    let mut filter = LogFilter::default();
+    for (level, modules) in MODULES {
+        for module in *modules {
+            filter.extend(&format!("{module}={level}"))?;
         }
+    }
+    let settings_result = filter.extend(settings_filter);
+    let env_result = filter.extend(&std::env::var("RUST_LOG").unwrap_or_default());
+    LogBuffer::global().set_filter(filter);
 
-        builder.parse_default_env().try_init()?;
-
-        Ok(())
+    // Initialize the logger; all filtering is done by the log buffer, so that it can be changed at runtime. The buffer also
+    // sets the max log level from the filter.
+    #[cfg(not(feature = "tracing"))]
+    {
+        let logger = env_logger::builder().filter_level(LevelFilter::Trace).build();
+        log::set_boxed_logger(Box::new(log_router::LogRouter(logger)))?;
     }
 
     #[cfg(feature = "tracing")]
     {
-        use tracing::metadata::Level;
-        use tracing_log::AsTrace;
         use tracing_subscriber::prelude::*;
-        use tracing_subscriber::{registry, EnvFilter};
-
-        let mut filter = tracing_subscriber::filter::Targets::new().with_default(tracing::metadata::LevelFilter::DEBUG);
-        for (level, modules) in MODULES {
-            for &module in *modules {
-                filter = filter.with_target(module, level.as_trace());
-            }
-        }
-
-        // BLOCKING: pending https://github.com/tokio-rs/tracing/issues/2507
-        // let modules: Vec<_> = MODULES.iter().flat_map(|&(level, modules)| modules.iter().map(move |&v| format!("{v}={level}"))).collect();
-
-        // eprintln!("{modules:#?}");
-        // let mut filter = tracing_subscriber::filter::EnvFilter::builder().with_default_directive(Level::INFO.into()).from_env_lossy();
-
-        // for module in modules {
-        //     filter = filter.add_directive(module.parse().unwrap());
-        // }
-
-        // let mut filter = std::env::var("RUST_LOG").unwrap_or_default().parse::<tracing_subscriber::filter::Targets>().unwrap_or_default();
-        // filter.extend(MODULES.iter().flat_map(|&(level, modules)| modules.iter().map(move |&v| (v, level.as_trace()))));
-
-        let env_filter = EnvFilter::builder().with_default_directive(Level::INFO.into()).from_env_lossy();
+        use tracing_subscriber::registry;
 
         registry()
-            .with(filter)
-            .with(env_filter)
+            .with(log_router::LogBufferFilter)
+            .with(log_router::LogBufferLayer)
             //
             .with(tracing_tree::HierarchicalLayer::new(4).with_indent_lines(true).with_verbose_entry(true).with_verbose_exit(true))
             // .with(tracing_subscriber::fmt::Layer::new().pretty())
             .try_init()?;
+    }
 
-        Ok(())
+    if let Err(err) = settings_result {
+        log::warn!("Invalid log filter in settings: {err:?}");
+    }
+    if let Err(err) = env_result {
+        log::warn!("Invalid RUST_LOG: {err:?}");
     }
+    Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
-    setup_logging()?;
+    // The settings are loaded before the logger is set up, so any errors are logged afterwards
+    let settings = Settings::load();
+    setup_logging(settings.as_ref().map(|settings| settings.logging.filter.as_str()).unwrap_or_default())?;
+    let settings = settings.unwrap_or_else(|err| {
+        log::warn!("{err:?}; using default settings");
+        Settings::default()
+    });
+    let _crash_reporter = crash_reporter::install(&settings.crash_reports);
 
     shared::components::init()?;
     let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
@@ -109,6 +105,9 @@ fn main() -> anyhow::Result<()> {
     AssetsCacheOnDisk.insert(&assets, false); // Disable disk caching for now; see https://github.com/AmbientRun/Ambient/issues/81
 
     let cli = Cli::parse();
+    if cli.run().map(|run| !run.headless).unwrap_or(false) {
+        crash_reporter::offer_upload(&settings.crash_reports);
+    }
 
     let current_dir = std::env::current_dir()?;
     let project_path = cli.project().and_then(|p| p.path.clone()).unwrap_or_else(|| current_dir.clone());
@@ -120,13 +119,44 @@ fn main() -> anyhow::Result<()> {
     }
 
     // If new: create project, immediately exit
-    if let Cli::New { name, .. } = &cli {
-        if let Err(err) = cli::new_project::new_project(&project_path, name.as_deref()) {
+    if let Cli::New { name, id, template, interactive, .. } = &cli {
+        let options = cli::new_project::NewProjectOptions {
+            name: name.as_deref(),
+            id: id.as_deref(),
+            template: template.clone(),
+            interactive: *interactive,
+        };
+        if let Err(err) = cli::new_project::new_project(&project_path, options) {
             eprintln!("Failed to create project: {err:?}");
         }
         return Ok(());
     }
 
+    // If admin: send the command to the server, immediately exit
+    if let Cli::Admin { host, token, command } = &cli {
+        let token = token.clone().or_else(|| std::env::var("AMBIENT_ADMIN_TOKEN").ok()).context("No admin token was given")?;
+        return runtime.block_on(cli::admin::run(host, &token, command));
+    }
+
+    // If doctor: check the environment, immediately exit
+    if let Cli::Doctor = &cli {
+        return cli::doctor::run(&settings, &assets);
+    }
+
+    // If schema: describe the project, immediately exit
+    if let Cli::Schema { output, typescript, .. } = &cli {
+        let manifest = ambient_project::Manifest::parse(
+            &std::fs::read_to_string(project_path.join("ambient.toml")).context("No project manifest was found. Please create one.")?,
+        )?;
+        let schema = manifest.schema(false);
+        let schema = if *typescript { schema.typescript() } else { serde_json::to_string_pretty(&schema)? };
+        match output {
+            Some(output) => std::fs::write(output, schema).with_context(|| format!("Failed to write the schema to {output:?}"))?,
+            None => println!("{schema}"),
+        }
+        return Ok(());
+    }
+
     // If UIC: write components to disk, immediately exit
     #[cfg(not(feature = "production"))]
     if let Cli::UpdateInterfaceComponents = cli {
@@ -156,6 +186,7 @@ fn main() -> anyhow::Result<()> {
         .transpose()?;
 
     if let Some(manifest) = manifest.as_ref() {
+        crash_reporter::set_packages(vec![format!("{} {}", manifest.project.id, manifest.project.version)]);
         let project_name = manifest.project.name.as_deref().unwrap_or("project");
         log::info!("Building {}", project_name);
         runtime.block_on(ambient_build::build(
@@ -164,6 +195,7 @@ fn main() -> anyhow::Result<()> {
             project_path.clone(),
             manifest,
             cli.project().map(|p| p.release).unwrap_or(false),
+            cli.project().map(|p| p.force).unwrap_or(false),
         ));
         log::info!("Done building {}", project_name);
     }
@@ -184,7 +216,7 @@ fn main() -> anyhow::Result<()> {
             format!("127.0.0.1:{QUIC_INTERFACE_PORT}").parse()?
         }
     } else {
-        let port = server::start(&runtime, assets.clone(), cli.clone(), project_path, manifest.as_ref().expect("no manifest"));
+        let port = server::start(&runtime, assets.clone(), cli.clone(), project_path, manifest.as_ref().expect("no manifest"))?;
         format!("127.0.0.1:{port}").parse()?
     };
 
@@ -192,7 +224,7 @@ fn main() -> anyhow::Result<()> {
     let handle = runtime.handle().clone();
     if let Some(run) = cli.run() {
         // If we have run parameters, start a client and join a server
-        runtime.block_on(client::run(assets, server_addr, run, cli.project().and_then(|p| p.path.clone())));
+        runtime.block_on(client::run(assets, server_addr, run, cli.project().and_then(|p| p.path.clone()), cli.determinism()));
     } else {
         // Otherwise, wait for the Ctrl+C signal
         handle.block_on(async move {
@@ -3,34 +3,50 @@ use std::{
     net::SocketAddr,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
-use ambient_core::{app_start_time, asset_cache, dtime, no_sync, project_name, time};
+use ambient_core::{
+    app_start_time, asset_cache,
+    determinism::{rng, WorldRng},
+    dtime, no_sync, project_name, time,
+};
 use ambient_ecs::{
-    dont_store, world_events, ComponentDesc, ComponentRegistry, Entity, Networked, SystemGroup, World, WorldEventsSystem,
-    WorldStreamCompEvent,
+    concept_requirements, dont_store, world_events, ComponentDesc, ComponentRegistry, Entity, Networked, SystemGroup, World,
+    WorldEventsSystem, WorldStreamCompEvent,
 };
 use ambient_network::{
-    bi_stream_handlers, datagram_handlers, persistent_resources,
+    admin::{AdminCommand, AdminHandle},
+    auth::{Authentication, JwtVerifier, OAuthIntrospectionVerifier, TokenVerifier},
+    bi_stream_handlers, datagram_handlers,
+    metrics::{timed_system, ServerMetrics},
+    persistent_resources,
     server::{ForkingEvent, GameServer, ShutdownEvent},
-    synced_resources, uni_stream_handlers,
+    storage::WorldStorage,
+    synced_resources, uni_stream_handlers, ServerWorldExt,
 };
-use ambient_prefab::PrefabFromUrl;
+use ambient_prefab::{scene::Scene, PrefabFromUrl};
+use ambient_settings::{package_settings_schema, PackageSettingsSchema};
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKeyExt, SyncAssetKeyExt},
-    asset_url::{AbsAssetUrl, ServerBaseUrlKey},
+    asset_url::{AbsAssetUrl, ServerBaseUrlKey, ServerContentDirKey},
 };
 use ambient_sys::{task::RuntimeHandle, time::SystemTime};
+use ambient_text::font_preloads;
 use anyhow::Context;
 use axum::{
-    http::{Method, StatusCode},
+    extract::State,
+    http::{header, HeaderMap, Method, StatusCode},
     response::IntoResponse,
-    routing::{get, get_service},
-    Router,
+    routing::{get, get_service, post},
+    Json, Router,
 };
 use tower_http::{cors::CorsLayer, services::ServeDir};
 
-use crate::{cli::Cli, shared};
+use crate::{
+    cli::{Cli, HostCli},
+    shared,
+};
 
 pub mod wasm;
 
@@ -40,9 +56,9 @@ pub fn start(
     cli: Cli,
     project_path: PathBuf,
     manifest: &ambient_project::Manifest,
-) -> u16 {
+) -> anyhow::Result<u16> {
     log::info!("Creating server");
-    let server = runtime.block_on(async move {
+    let mut server = runtime.block_on(async move {
         GameServer::new_with_port_in_range(QUIC_INTERFACE_PORT..(QUIC_INTERFACE_PORT + 10), false)
             .await
             .context("failed to create game server with port in range")
@@ -50,6 +66,24 @@ pub fn start(
     });
     let port = server.port;
 
+    let storage = &manifest.storage;
+    if storage.enabled {
+        server.storage = Some(WorldStorage {
+            path: project_path.join(&storage.path),
+            autosave_interval: Duration::from_secs_f32(storage.autosave_interval.max(1.)),
+            backups: storage.backups,
+        });
+    }
+    let tick_rate = cli.host().and_then(|h| h.tick_rate).unwrap_or(manifest.server.tick_rate);
+    let replication_rate = cli.host().and_then(|h| h.replication_rate).or(manifest.server.replication_rate).unwrap_or(tick_rate);
+    assert!(tick_rate > 0. && replication_rate > 0., "The tick and replication rates must be positive");
+    server.tick_interval = Duration::from_secs_f32(1. / tick_rate);
+    server.replication_interval = Duration::from_secs_f32(1. / replication_rate);
+    log::info!("Simulating at {tick_rate} Hz, and replicating at {} Hz", replication_rate.min(tick_rate));
+    if let Some(host) = cli.host() {
+        server.authentication = authentication(host).context("Invalid authentication settings")?;
+    }
+
     let public_host = cli
         .host()
         .and_then(|h| h.public_host.clone())
@@ -57,27 +91,94 @@ pub fn start(
         .unwrap_or("localhost".to_string());
     log::info!("Created server, running at {public_host}:{port}");
     ServerBaseUrlKey.insert(&assets, AbsAssetUrl::parse(format!("http://{public_host}:{HTTP_INTERFACE_PORT}/content/")).unwrap());
+    ServerContentDirKey.insert(&assets, Some(project_path.join("build")));
 
+    let admin_token = cli.host().and_then(|h| h.admin_token.clone()).or_else(|| std::env::var("AMBIENT_ADMIN_TOKEN").ok());
     start_http_interface(runtime, &project_path);
+    if let Some(admin_token) = admin_token {
+        let admin_address = cli.host().and_then(|h| h.admin_address).unwrap_or(SocketAddr::from(([127, 0, 0, 1], ADMIN_INTERFACE_PORT)));
+        start_admin_interface(runtime, admin_address, admin_token, server.admin());
+    }
+    if let Some(metrics_port) = cli.host().and_then(|h| h.metrics_port) {
+        start_metrics_interface(runtime, metrics_port, server.metrics.clone());
+    }
 
     ComponentRegistry::get_mut().add_external(manifest.all_defined_components(false).unwrap());
+    let validate_concepts =
+        cli.host().map_or(false, |h| h.validate_concepts) || std::env::var("AMBIENT_VALIDATE_CONCEPTS").map_or(false, |v| v != "0");
 
     let manifest = manifest.clone();
+    let storage = server.storage.clone();
     runtime.spawn(async move {
         let mut server_world = World::new_with_config("server", true);
         server_world.init_shape_change_tracking();
 
         server_world.add_components(server_world.resource_entity(), create_resources(assets.clone())).unwrap();
+        if let Some(determinism) = cli.determinism() {
+            log::info!("Running deterministically with the seed {}", determinism.seed);
+            determinism.apply(&mut server_world);
+        }
 
         // Keep track of the project name
         let name = manifest.project.name.clone().unwrap_or_else(|| "Ambient".into());
         server_world.add_components(server_world.resource_entity(), Entity::new().with(project_name(), name)).unwrap();
 
-        Entity::new().with(synced_resources(), ()).with(dont_store(), ()).spawn(&mut server_world);
-        // Note: this should not be reset every time the server is created. Remove this when it becomes possible to load/save worlds.
-        Entity::new().with(persistent_resources(), ()).spawn(&mut server_world);
+        // Let the clients know which settings the project has, so that they can show them in their settings UI
+        let settings_schema = match PackageSettingsSchema::from_manifest(&manifest) {
+            Ok(schema) => vec![schema],
+            Err(err) => {
+                log::warn!("Invalid settings in the project manifest: {err:?}");
+                vec![]
+            }
+        };
+        // The clients load the fonts to preload when they join, rather than when the first text uses them
+        let base_url = ServerBaseUrlKey.get(&assets);
+        let font_preloads = manifest
+            .fonts
+            .values()
+            .filter(|font| font.preload)
+            .filter_map(|font| base_url.join(format!("assets/{}", font.path.to_string_lossy().replace('\\', "/"))).ok())
+            .map(|url| url.to_string())
+            .collect();
+        Entity::new()
+            .with(synced_resources(), ())
+            .with(dont_store(), ())
+            .with(package_settings_schema(), settings_schema)
+            .with(font_preloads(), font_preloads)
+            .spawn(&mut server_world);
+
+        if let Some(storage) = &storage {
+            match storage.load(&mut server_world) {
+                Ok(true) => {}
+                Ok(false) => log::info!("No saved world was found at {:?}; starting a new one", storage.path),
+                Err(err) => log::error!("Failed to load the saved world: {err:?}"),
+            }
+        }
+        if server_world.persisted_resource_entity().is_none() {
+            Entity::new().with(persistent_resources(), ()).spawn(&mut server_world);
+        }
+
+        if validate_concepts {
+            log::info!("Validating the entities made from concepts");
+            let concepts = shared::components::concepts()
+                .iter()
+                .map(|concept| concept.requirements())
+                .chain(manifest.all_defined_concepts(false))
+                .map(|concept| (concept.id.clone(), concept))
+                .collect();
+            server_world.add_resource(concept_requirements(), concepts);
+        }
 
         wasm::initialize(&mut server_world, project_path.clone(), &manifest).unwrap();
+        ambient_lua::initialize(&mut server_world, &project_path, &manifest);
+
+        let base_url = ServerBaseUrlKey.get(&assets);
+        for path in &manifest.server.scenes {
+            match Scene::load(&project_path.join(path)).and_then(|scene| scene.spawn(&mut server_world, &base_url)) {
+                Ok(ids) => log::info!("Spawned {} entities from the scene {path:?}", ids.len()),
+                Err(err) => log::error!("Failed to spawn the scene {path:?}: {err:?}"),
+            }
+        }
 
         if let Cli::View { asset_path, .. } = cli.clone() {
             let asset_path = AbsAssetUrl::from_file_path(project_path.join("build").join(asset_path).join("prefabs/main.json"));
@@ -90,33 +191,71 @@ pub fn start(
             .run(server_world, Arc::new(systems), Arc::new(on_forking_systems), Arc::new(on_shutdown_systems), Arc::new(is_sync_component))
             .await;
     });
-    port
+    Ok(port)
+}
+
+/// How players are authenticated, if they are
+fn authentication(host: &HostCli) -> anyhow::Result<Option<Authentication>> {
+    let jwt_secret = host.auth_jwt_secret.clone().or_else(|| std::env::var("AMBIENT_AUTH_JWT_SECRET").ok());
+    let verifier: Arc<dyn TokenVerifier> = if let Some(url) = &host.auth_oauth_introspection_url {
+        Arc::new(OAuthIntrospectionVerifier::new(url.clone(), None))
+    } else {
+        let mut verifier = match (&jwt_secret, &host.auth_jwt_public_key) {
+            (Some(secret), _) => JwtVerifier::hs256(secret.as_bytes()),
+            (None, Some(path)) => JwtVerifier::rs256(&std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?)?,
+            (None, None) => {
+                anyhow::ensure!(!host.require_auth, "Authentication is required, but no way to authenticate players was given");
+                return Ok(None);
+            }
+        };
+        if let Some(issuer) = &host.auth_jwt_issuer {
+            verifier = verifier.with_issuer(issuer);
+        }
+        if let Some(audience) = &host.auth_jwt_audience {
+            verifier = verifier.with_audience(audience);
+        }
+        Arc::new(verifier)
+    };
+    Ok(Some(Authentication { verifier, required: host.require_auth }))
 }
 
 fn systems(_world: &mut World) -> SystemGroup {
     SystemGroup::new(
         "server",
         vec![
-            ambient_physics::run_simulation_system(),
+            timed_system("physics_simulation", ambient_physics::run_simulation_system()),
             // Can happen *during* the physics step
-            Box::new(ambient_core::async_ecs::async_ecs_systems()),
-            Box::new(ambient_prefab::systems()),
+            timed_system("async_ecs", Box::new(ambient_core::async_ecs::async_ecs_systems())),
+            timed_system("prefab", Box::new(ambient_prefab::systems())),
+            timed_system("streaming", Box::new(ambient_prefab::streaming::systems())),
             // Happens after the physics step
-            ambient_physics::fetch_simulation_system(),
-            Box::new(ambient_physics::physx::sync_ecs_physics()),
-            Box::new(ambient_core::transform::TransformSystem::new()),
-            ambient_core::remove_at_time_system(),
-            Box::new(WorldEventsSystem),
-            Box::new(ambient_core::camera::camera_systems()),
-            Box::new(ambient_physics::server_systems()),
-            Box::new(shared::player::server_systems()),
-            Box::new(wasm::systems()),
-            Box::new(shared::player::server_systems_final()),
+            timed_system("physics_fetch", ambient_physics::fetch_simulation_system()),
+            timed_system("physics_sync", Box::new(ambient_physics::physx::sync_ecs_physics())),
+            timed_system("transform", Box::new(ambient_core::transform::TransformSystem::new())),
+            timed_system("remove_at_time", ambient_core::remove_at_time_system()),
+            timed_system("world_events", Box::new(WorldEventsSystem)),
+            timed_system("camera", Box::new(ambient_core::camera::camera_systems())),
+            timed_system("time_of_day", Box::new(ambient_sky::server_systems())),
+            timed_system("physics_server", Box::new(ambient_physics::server_systems())),
+            timed_system("player", Box::new(shared::player::server_systems())),
+            timed_system("wasm", Box::new(wasm::systems())),
+            timed_system("lua", Box::new(ambient_lua::systems())),
+            timed_system("concept_validation", ambient_ecs::concept_validation_system()),
+            timed_system("player_final", Box::new(shared::player::server_systems_final())),
+            timed_system("time_control", Box::new(ambient_network::time_control::server_systems())),
         ],
     )
 }
 fn on_forking_systems() -> SystemGroup<ForkingEvent> {
-    SystemGroup::new("on_forking_systems", vec![Box::new(ambient_physics::on_forking_systems()), Box::new(wasm::on_forking_systems())])
+    SystemGroup::new(
+        "on_forking_systems",
+        vec![
+            Box::new(ambient_physics::on_forking_systems()),
+            Box::new(wasm::on_forking_systems()),
+            Box::new(ambient_lua::on_forking_systems()),
+            Box::new(ambient_editor::annotations::on_forking_systems()),
+        ],
+    )
 }
 fn on_shutdown_systems() -> SystemGroup<ShutdownEvent> {
     SystemGroup::new("on_shutdown_systems", vec![Box::new(ambient_physics::on_shutdown_systems()), Box::new(wasm::on_shutdown_systems())])
@@ -138,6 +277,7 @@ fn create_resources(assets: AssetCache) -> Entity {
     server_resources.set(time(), now);
     server_resources.set(app_start_time(), now);
     server_resources.set(dtime(), 1. / 60.);
+    server_resources.set(rng(), WorldRng::from_entropy());
 
     let mut bistream_handlers = HashMap::new();
     ambient_network::register_rpc_bi_stream_handler(&mut bistream_handlers, shared::create_rpc_registry());
@@ -155,6 +295,7 @@ fn create_resources(assets: AssetCache) -> Entity {
 
 pub const HTTP_INTERFACE_PORT: u16 = 8999;
 pub const QUIC_INTERFACE_PORT: u16 = 9000;
+pub const ADMIN_INTERFACE_PORT: u16 = 8998;
 
 fn start_http_interface(runtime: &tokio::runtime::Runtime, project_path: &Path) {
     let router = Router::new()
@@ -168,6 +309,89 @@ fn start_http_interface(runtime: &tokio::runtime::Runtime, project_path: &Path)
     });
 }
 
+/// Serves the admin commands on their own listener, as the token is sent in plain text; by default it only listens on localhost
+fn start_admin_interface(runtime: &tokio::runtime::Runtime, addr: SocketAddr, token: String, handle: AdminHandle) {
+    let router = Router::new().route("/admin", post(handle_admin_command)).with_state(Arc::new((token, handle)));
+
+    log::info!("Accepting admin commands at http://{addr}/admin");
+    runtime.spawn(async move {
+        axum::Server::bind(&addr).serve(router.into_make_service()).await.unwrap();
+    });
+}
+
+fn start_metrics_interface(runtime: &tokio::runtime::Runtime, port: u16, metrics: ServerMetrics) {
+    let router = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], metrics.render()) }
+        }),
+    );
+
+    log::info!("Serving metrics at http://0.0.0.0:{port}/metrics");
+    runtime.spawn(async move {
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+        axum::Server::bind(&addr).serve(router.into_make_service()).await.unwrap();
+    });
+}
+
+/// Runs an admin command; the request must have the admin token as its bearer token
+async fn handle_admin_command(
+    State(admin): State<Arc<(String, AdminHandle)>>,
+    headers: HeaderMap,
+    Json(command): Json<AdminCommand>,
+) -> impl IntoResponse {
+    let (token, handle) = &*admin;
+    if !is_authorized(&headers, token) {
+        return (StatusCode::UNAUTHORIZED, Json(Err("Invalid admin token".to_string())));
+    }
+    (StatusCode::OK, Json(handle.run(command).await))
+}
+
+/// Whether the request has `token` as its bearer token
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| constant_time_eq(value.as_bytes(), token.as_bytes()))
+        .unwrap_or(false)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
 async fn handle_error(_err: std::io::Error) -> impl IntoResponse {
     (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong...")
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        headers
+    }
+
+    #[test]
+    fn admin_commands_need_the_bearer_token() {
+        assert!(is_authorized(&headers("Bearer secret"), "secret"));
+        assert!(!is_authorized(&headers("Bearer secre"), "secret"));
+        assert!(!is_authorized(&headers("Bearer secrets"), "secret"));
+        assert!(!is_authorized(&headers("secret"), "secret"));
+        assert!(!is_authorized(&HeaderMap::new(), "secret"));
+    }
+
+    #[test]
+    fn constant_time_comparison() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokem"));
+        assert!(!constant_time_eq(b"token", b"tok"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
@@ -1,14 +1,21 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use ambient_core::asset_cache;
-use ambient_ecs::{EntityId, SystemGroup, World};
+use ambient_ecs::{dont_store, query, ComponentRegistry, EntityId, SystemGroup, World};
+use ambient_network::admin::{package_handlers, PackageHandlers};
 use ambient_project::Identifier;
 use ambient_std::{
     asset_cache::SyncAssetKeyExt,
-    asset_url::{AssetUrl, ServerBaseUrlKey},
+    asset_url::{AssetUrl, ServerBaseUrlKey, ServerContentDirKey},
 };
 pub use ambient_wasm::server::{on_forking_systems, on_shutdown_systems};
-use ambient_wasm::shared::{client_bytecode_from_url, get_module_name, module_bytecode, spawn_module, MessageType, ModuleBytecode};
+use ambient_wasm::shared::{
+    client_bytecode_from_url, despawn_module, get_module_name, module, module_bytecode, module_package, spawn_module, MessageType,
+    ModuleBytecode,
+};
 use anyhow::Context;
 
 pub fn systems() -> SystemGroup {
@@ -29,8 +36,20 @@ pub fn initialize(world: &mut World, project_path: PathBuf, manifest: &ambient_p
     });
 
     ambient_wasm::server::initialize(world, messenger)?;
+    world.add_resource(
+        package_handlers(),
+        PackageHandlers { list: Arc::new(list_packages), load: Arc::new(load_package), unload: Arc::new(unload_package) },
+    );
+
+    spawn_package_modules(world, &project_path.join("build"), manifest)
+}
+
+/// Spawns the modules of a package that was built to `build_dir`
+fn spawn_package_modules(world: &mut World, build_dir: &Path, manifest: &ambient_project::Manifest) -> anyhow::Result<()> {
+    // Clientside modules are downloaded by the clients, so they have to be in the directory the server serves
+    let content_dir = ServerContentDirKey.get(world.resource(asset_cache()));
+    let relative_build_dir = content_dir.as_ref().and_then(|dir| build_dir.strip_prefix(dir).ok()).map(|dir| dir.to_path_buf());
 
-    let build_dir = project_path.join("build");
     for target in ["client", "server"] {
         let wasm_component_paths: Vec<PathBuf> = std::fs::read_dir(build_dir.join(target))
             .ok()
@@ -48,13 +67,19 @@ pub fn initialize(world: &mut World, project_path: PathBuf, manifest: &ambient_p
                 Identifier::new(format!("{}_{}", manifest.project.id, filename_identifier)).map_err(anyhow::Error::msg)?
             };
 
-            let description = manifest.project.description.clone().unwrap_or_default();
-            let description = if is_sole_module { description } else { format!("{description} ({filename_identifier})") };
+            if target == "client" && relative_build_dir.is_none() {
+                log::warn!("Not loading the clientside module {path:?}, as it's not in the directory served to clients");
+                continue;
+            }
 
+            let description = module_description(manifest, is_sole_module, &filename_identifier);
             let id = spawn_module(world, &name, description, true)?;
+            // The modules are spawned from the build every time the server starts, so they're not saved with the world
+            world.add_component(id, dont_store(), ())?;
+            world.add_component(id, module_package(), manifest.project.id.to_string())?;
 
-            if target == "client" {
-                let relative_path = path.strip_prefix(&build_dir)?;
+            if let (true, Some(relative_build_dir)) = (target == "client", &relative_build_dir) {
+                let relative_path = relative_build_dir.join(path.strip_prefix(build_dir)?);
 
                 let base_url = ServerBaseUrlKey.get(world.resource(asset_cache()));
                 let bytecode_url = AssetUrl::parse(&relative_path.to_string_lossy())?.resolve(&base_url)?.to_string();
@@ -69,3 +94,83 @@ pub fn initialize(world: &mut World, project_path: PathBuf, manifest: &ambient_p
 
     Ok(())
 }
+
+fn module_description(manifest: &ambient_project::Manifest, is_sole_module: bool, filename_identifier: &Identifier) -> String {
+    let description = manifest.project.description.clone().unwrap_or_default();
+    if is_sole_module {
+        description
+    } else {
+        format!("{description} ({filename_identifier})")
+    }
+}
+
+/// The modules of the package `id`
+fn package_modules(world: &World, id: &str) -> Vec<EntityId> {
+    query((module(), module_package()))
+        .iter(world, None)
+        .filter(|(_, (_, package))| *package == id)
+        .map(|(module_id, _)| module_id)
+        .collect()
+}
+
+/// The ids of the loaded packages
+fn list_packages(world: &World) -> Vec<String> {
+    let mut packages = query((module(), module_package())).iter(world, None).map(|(_, (_, package))| package.clone()).collect::<Vec<_>>();
+    packages.sort();
+    packages.dedup();
+    packages
+}
+
+/// Loads the package at `path`, which must have been built already
+fn load_package(world: &mut World, path: &str) -> anyhow::Result<String> {
+    let path = Path::new(path);
+    let manifest = ambient_project::Manifest::parse(
+        &std::fs::read_to_string(path.join("ambient.toml")).with_context(|| format!("No package manifest was found in {path:?}"))?,
+    )?;
+    let id = manifest.project.id.to_string();
+    anyhow::ensure!(package_modules(world, &id).is_empty(), "The package {id} is already loaded");
+
+    ComponentRegistry::get_mut().add_external(manifest.all_defined_components(false).map_err(anyhow::Error::msg)?);
+    spawn_package_modules(world, &path.join("build"), &manifest)?;
+    Ok(id)
+}
+
+fn unload_package(world: &mut World, id: &str) -> anyhow::Result<()> {
+    let modules = package_modules(world, id);
+    anyhow::ensure!(!modules.is_empty(), "The package {id} is not loaded");
+    for module_id in modules {
+        despawn_module(world, module_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::Entity;
+
+    use super::*;
+
+    fn spawn_package_module(world: &mut World, package: &str) -> EntityId {
+        Entity::new().with(module(), ()).with(module_package(), package.to_string()).spawn(world)
+    }
+
+    #[test]
+    fn package_modules_match_the_package_id() {
+        ambient_ecs::init_components();
+        ambient_wasm::shared::init_components();
+        let mut world = World::new("package_modules");
+        let game_client = spawn_package_module(&mut world, "game");
+        let game_server = spawn_package_module(&mut world, "game");
+        // Its modules are named like `game_ui`, but it's a different package
+        let ui = spawn_package_module(&mut world, "game_ui");
+
+        let mut modules = package_modules(&world, "game");
+        modules.sort();
+        let mut expected = vec![game_client, game_server];
+        expected.sort();
+        assert_eq!(modules, expected);
+        assert_eq!(package_modules(&world, "game_ui"), vec![ui]);
+        assert!(package_modules(&world, "gam").is_empty());
+        assert_eq!(list_packages(&world), vec!["game".to_string(), "game_ui".to_string()]);
+    }
+}
@@ -28,6 +28,8 @@ fn make_components() -> toml_edit::Table {
     let namespaces = [
         ("core", "Core", "Contains all core components for the Ambient Runtime."),
         ("core::app", "App", "High-level state relevant to the application (including the in-development Editor)."),
+        ("core::animation", "Animation", "Animation graphs, their parameters and their state, root motion and inverse kinematics."),
+        ("core::audio", "Audio", "Spatial audio emitters and their parameters."),
         ("core::camera", "Camera", "Camera matrices, types, parameters, and more."),
         ("core::ecs", "Entity Component System", "Core components for the ECS and entities."),
         ("core::game_objects", "Game Objects", "Pre-defined game objects that implement specific behaviours."),
@@ -43,6 +45,8 @@ fn make_components() -> toml_edit::Table {
             "Components that create primitive (in the geometric sense) objects from their attached entities.",
         ),
         ("core::rendering", "Rendering", "Rendering-related state, including global rendering parameters and per-entity state."),
+        ("core::settings", "Settings", "The settings that changed in a `core/settings_changed` event."),
+        ("core::streaming", "Streaming", "Streaming of large worlds, whose cells are loaded around players."),
         (
             "core::transform",
             "Transform",
@@ -51,6 +55,8 @@ fn make_components() -> toml_edit::Table {
         ("core::layout", "Layout", "Layout components such as flow, margins etc."),
         ("core::rect", "Rect", "Rounded corners rectangle rendering components, with an optional border."),
         ("core::text", "Text", "Text rendering."),
+        ("core::ui", "UI", "The UI theme, made of named style tokens, the transitions of UI elements, and minimaps."),
+        ("core::weather", "Weather", "The weather of the world: rain, snow, wind and their ambience."),
     ];
 
     for (path, name, description) in namespaces {
@@ -100,10 +106,19 @@ fn make_component_table(component: &ambient_ecs::PrimitiveComponent) -> Option<t
     if let Some(default) = get_toml_default_for_primitive_component(component) {
         table.insert("default", default);
     }
-    table.insert("attributes", {
-        let attrs = ExternalComponentAttributes::from_existing_component(desc);
-        value(toml_edit::Array::from_iter(attrs.flags.iter()))
-    });
+    let attrs = ExternalComponentAttributes::from_existing_component(desc);
+    if let Some(unit) = &attrs.unit {
+        table.insert("unit", value(unit.as_str()));
+    }
+    if let Some(range) = attrs.range {
+        if range.min.is_finite() {
+            table.insert("min", value(range.min));
+        }
+        if range.max.is_finite() {
+            table.insert("max", value(range.max));
+        }
+    }
+    table.insert("attributes", value(toml_edit::Array::from_iter(attrs.flags.iter())));
 
     Some(table)
 }
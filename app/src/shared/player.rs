@@ -1,7 +1,7 @@
-use std::{io::Write, str::FromStr, sync::Arc};
+use std::{collections::HashSet, io::Write, str::FromStr, sync::Arc};
 
 use ambient_core::{
-    player::{get_player_by_user_id, player},
+    player::{get_player_by_user_id, local_player_user_id, local_players, player},
     runtime,
     window::{cursor_position, window_logical_size, window_physical_size},
 };
@@ -9,15 +9,21 @@ use ambient_ecs::{query, query_mut, Entity, SystemGroup, WorldDiff};
 use ambient_element::{element_component, Element, Hooks};
 use ambient_event_types::{WINDOW_FOCUSED, WINDOW_KEYBOARD_INPUT, WINDOW_MOUSE_INPUT, WINDOW_MOUSE_MOTION, WINDOW_MOUSE_WHEEL};
 use ambient_input::{
-    event_focus_change, event_keyboard_input, event_mouse_input, event_mouse_motion, event_mouse_wheel, event_mouse_wheel_pixels, keycode,
-    mouse_button, player_prev_raw_input, player_raw_input, PlayerRawInput,
+    event_focus_change, event_keyboard_input, event_mouse_input, event_mouse_motion, event_mouse_wheel, event_mouse_wheel_pixels,
+    gamepad::{gamepad_rumble, gamepads, local_player_gamepads, GamepadButton},
+    keycode, mouse_button, player_prev_raw_input, player_raw_input, PlayerRawInput, LOCAL_PLAYER_INPUT_DATAGRAM_ID,
+    PLAYER_INPUT_DATAGRAM_ID,
+};
+use ambient_network::{
+    client::game_client,
+    local_players::MAX_LOCAL_PLAYERS,
+    log_network_result,
+    rpc::{rpc_join_local_player, rpc_leave_local_player, rpc_world_diff},
+    DatagramHandlers,
 };
-use ambient_network::{client::game_client, log_network_result, rpc::rpc_world_diff, DatagramHandlers};
 use ambient_std::unwrap_log_err;
 use ambient_window_types::VirtualKeyCode;
-use byteorder::{BigEndian, WriteBytesExt};
-
-const PLAYER_INPUT_DATAGRAM_ID: u32 = 5;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
 pub fn register_datagram_handler(handlers: &mut DatagramHandlers) {
     handlers.insert(
@@ -32,6 +38,22 @@ pub fn register_datagram_handler(handlers: &mut DatagramHandlers) {
             }
         }),
     );
+    handlers.insert(
+        LOCAL_PLAYER_INPUT_DATAGRAM_ID,
+        Arc::new(|state, _assets, user_id, data| {
+            let mut data = &data[..];
+            let index = unwrap_log_err!(data.read_u32::<BigEndian>());
+            let input: PlayerRawInput = unwrap_log_err!(bincode::deserialize(data));
+            let mut state = state.lock();
+            // The local player's user id is derived from the sender's, so clients can only send input to their own
+            let local_user_id = local_player_user_id(user_id, index);
+            if let Some(world) = state.get_player_world_mut(user_id) {
+                if let Some(player_id) = get_player_by_user_id(world, &local_user_id) {
+                    world.set(player_id, player_raw_input(), input).ok();
+                }
+            }
+        }),
+    );
 }
 
 pub fn server_systems() -> SystemGroup {
@@ -132,6 +154,10 @@ pub fn PlayerRawInputHandler(hooks: &mut Hooks) -> Element {
             let runtime = world.resource(runtime()).clone();
             let input = input.clone();
             let cursor_position = *world.resource(cursor_position());
+            let mut gamepads = world.resource_opt(gamepads()).map(|gamepads| gamepads.states()).unwrap_or_default();
+            if let Some(assigned) = world.resource_opt(local_player_gamepads()) {
+                gamepads.retain(|id, _| !assigned.values().any(|gamepad| gamepad == id));
+            }
 
             runtime.spawn(async move {
                 let mut data = Vec::new();
@@ -140,6 +166,7 @@ pub fn PlayerRawInputHandler(hooks: &mut Hooks) -> Element {
                 let msg = {
                     let mut input = input.lock();
                     input.cursor_position = cursor_position;
+                    input.gamepads = gamepads;
                     bincode::serialize(&*input).unwrap()
                 };
                 data.write_all(&msg).unwrap();
@@ -150,3 +177,95 @@ pub fn PlayerRawInputHandler(hooks: &mut Hooks) -> Element {
 
     Element::new()
 }
+
+/// Lets other players join this client for splitscreen: pressing Start and Select together on a gamepad which isn't
+/// assigned to a local player joins one with it, and pressing them again leaves. Sends the input of each local player's
+/// gamepad to the server.
+#[element_component]
+pub fn LocalPlayersHandler(hooks: &mut Hooks) -> Element {
+    // The gamepads which were pressing Start and Select last frame, so that holding them only joins or leaves once
+    let held = hooks.use_ref_with(|_| HashSet::<u32>::new());
+    hooks.use_frame(move |world| {
+        let Some(Some(gc)) = world.resource_opt(game_client()).cloned() else { return };
+        let runtime = world.resource(runtime()).clone();
+        let states = world.resource_opt(gamepads()).map(|gamepads| gamepads.states()).unwrap_or_default();
+        let mut assigned = world.resource_opt(local_player_gamepads()).cloned().unwrap_or_default();
+        let was_assigned = assigned.values().copied().collect::<HashSet<_>>();
+
+        let pressing = states
+            .iter()
+            .filter(|(_, state)| state.buttons.contains(&GamepadButton::Start) && state.buttons.contains(&GamepadButton::Select))
+            .map(|(id, _)| *id)
+            .collect::<HashSet<_>>();
+        let mut held = held.lock();
+        let pressed = pressing.difference(&held).copied().collect::<HashSet<_>>();
+        *held = pressing;
+
+        let mut changed = false;
+        // Local players also leave when their gamepad is disconnected
+        for (index, gamepad) in assigned.clone() {
+            if !states.contains_key(&gamepad) || pressed.contains(&gamepad) {
+                assigned.remove(&index);
+                changed = true;
+                let gc = gc.clone();
+                runtime.spawn(async move {
+                    log_network_result!(gc.rpc(rpc_leave_local_player, index).await);
+                });
+            }
+        }
+        for gamepad in pressed.into_iter().filter(|gamepad| !was_assigned.contains(gamepad)) {
+            let Some(index) = (1..MAX_LOCAL_PLAYERS).find(|index| !assigned.contains_key(index)) else { break };
+            assigned.insert(index, gamepad);
+            changed = true;
+            let gc = gc.clone();
+            runtime.spawn(async move {
+                log_network_result!(gc.rpc(rpc_join_local_player, index).await);
+            });
+        }
+        if changed {
+            let user_ids = assigned.keys().map(|index| local_player_user_id(&gc.user_id, *index)).collect();
+            gc.game_state.lock().world.add_resource(local_players(), user_ids);
+            world.add_resource(local_player_gamepads(), assigned.clone());
+        }
+
+        for (index, gamepad) in assigned {
+            let Some(state) = states.get(&gamepad).cloned() else { continue };
+            let gc = gc.clone();
+            runtime.spawn(async move {
+                let mut data = Vec::new();
+                data.write_u32::<BigEndian>(LOCAL_PLAYER_INPUT_DATAGRAM_ID).unwrap();
+                data.write_u32::<BigEndian>(index).unwrap();
+                let input = PlayerRawInput { gamepads: [(gamepad, state)].into(), ..Default::default() };
+                data.write_all(&bincode::serialize(&input).unwrap()).unwrap();
+                gc.connection.send_datagram(data.into()).ok();
+            });
+        }
+    });
+    Element::new()
+}
+
+/// Plays the rumbles the server requests for the local player on their gamepads
+#[element_component]
+pub fn PlayerGamepadRumble(hooks: &mut Hooks) -> Element {
+    // The sequence of the last rumble that was seen; the rumble the player already had when joining isn't played
+    let last_sequence = hooks.use_ref_with(|_| None::<u64>);
+    hooks.use_frame(move |world| {
+        let Some(Some(gc)) = world.resource_opt(game_client()).cloned() else { return };
+        let rumble = {
+            let state = gc.game_state.lock();
+            let Some(player_id) = get_player_by_user_id(&state.world, &gc.user_id) else { return };
+            state.world.get_cloned(player_id, gamepad_rumble()).ok()
+        };
+        let sequence = rumble.as_ref().map(|rumble| rumble.sequence).unwrap_or_default();
+        let mut last_sequence = last_sequence.lock();
+        if let (Some(last), Some(rumble)) = (*last_sequence, &rumble) {
+            if last != sequence {
+                if let Some(gamepads) = world.resource_opt(gamepads()) {
+                    gamepads.rumble(rumble);
+                }
+            }
+        }
+        *last_sequence = Some(sequence);
+    });
+    Element::new()
+}
@@ -0,0 +1,461 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ambient_core::{asset_cache, dtime};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Store, SystemGroup, World};
+use ambient_model::model_from_url;
+use ambient_std::asset_url::{AnimationAssetType, TypedAssetUrl};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::{animation_errors, animation_retargeting, AnimationAction, AnimationActionTime, AnimationClipRef, AnimationRetargeting};
+
+components!("animation", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Animation graph"],
+        Description["The animation graph of this entity, as JSON: its states, which play a clip or blend several depending on parameters, and the transitions between them.\nTakes precedence over the animation controller."]
+    ]
+    animation_graph: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Animation graph parameter names"],
+        Description["The names of the parameters of the animation graph, whose values are in `animation_graph_parameter_values`."]
+    ]
+    animation_graph_parameter_names: Vec<String>,
+    @[
+        Debuggable, Networked, Store,
+        Name["Animation graph parameter values"],
+        Description["The values of the parameters named in `animation_graph_parameter_names`. Booleans are 0 or 1."]
+    ]
+    animation_graph_parameter_values: Vec<f32>,
+    @[
+        Debuggable,
+        Name["Animation graph state"],
+        Description["The state the animation graph is in, or is transitioning to."]
+    ]
+    animation_graph_state: String,
+    @[Debuggable]
+    animation_graph_runtime: AnimationGraphRuntime,
+});
+
+/// A state machine of animations, deserialized from the `animation_graph` component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationGraph {
+    pub states: Vec<AnimationState>,
+    #[serde(default)]
+    pub transitions: Vec<AnimationTransition>,
+    /// Defaults to the first state
+    #[serde(default)]
+    pub initial_state: Option<String>,
+}
+impl AnimationGraph {
+    fn state_index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationState {
+    pub name: String,
+    pub motion: AnimationMotion,
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+    /// Whether the root motion of the clips is taken out of them, if the entity has `root_motion`
+    #[serde(default = "crate::default_root_motion")]
+    pub root_motion: bool,
+}
+fn default_speed() -> f32 {
+    1.
+}
+fn default_looping() -> bool {
+    true
+}
+
+/// What a state plays
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnimationMotion {
+    Clip {
+        clip: TypedAssetUrl<AnimationAssetType>,
+    },
+    /// Blends the two clips whose values surround the value of `parameter`
+    Blend1d {
+        parameter: String,
+        points: Vec<BlendPoint1d>,
+    },
+    /// Blends the clips by the inverse of the distance between their positions and the values of `x` and `y`
+    Blend2d {
+        x: String,
+        y: String,
+        points: Vec<BlendPoint2d>,
+    },
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlendPoint1d {
+    pub value: f32,
+    pub clip: TypedAssetUrl<AnimationAssetType>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlendPoint2d {
+    pub position: Vec2,
+    pub clip: TypedAssetUrl<AnimationAssetType>,
+}
+impl AnimationMotion {
+    /// The clips of the motion and their weights, which add up to 1
+    fn clips(&self, parameters: &Parameters) -> Vec<(TypedAssetUrl<AnimationAssetType>, f32)> {
+        match self {
+            AnimationMotion::Clip { clip } => vec![(clip.clone(), 1.)],
+            AnimationMotion::Blend1d { parameter, points } => {
+                let value = parameters.get(parameter);
+                let mut points = points.iter().collect::<Vec<_>>();
+                points.sort_by(|a, b| a.value.total_cmp(&b.value));
+                let (Some(first), Some(last)) = (points.first(), points.last()) else { return vec![] };
+                // NaN would be outside of every pair of points
+                if !value.is_finite() || value <= first.value {
+                    return vec![(first.clip.clone(), 1.)];
+                }
+                if value >= last.value {
+                    return vec![(last.clip.clone(), 1.)];
+                }
+                let (a, b) = points.windows(2).map(|pair| (pair[0], pair[1])).find(|(_, b)| value < b.value).unwrap();
+                let p = (value - a.value) / (b.value - a.value).max(f32::EPSILON);
+                vec![(a.clip.clone(), 1. - p), (b.clip.clone(), p)]
+            }
+            AnimationMotion::Blend2d { x, y, points } => {
+                let position = Vec2::new(parameters.get(x), parameters.get(y));
+                if let Some(point) = points.iter().find(|point| point.position.distance_squared(position) < 1e-6) {
+                    return vec![(point.clip.clone(), 1.)];
+                }
+                let weights = points.iter().map(|point| 1. / point.position.distance_squared(position)).collect::<Vec<_>>();
+                let total = weights.iter().sum::<f32>();
+                points.iter().zip(weights).map(|(point, weight)| (point.clip.clone(), weight / total)).collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationTransition {
+    /// The state this transition leaves, or any state if None
+    #[serde(default)]
+    pub from: Option<String>,
+    pub to: String,
+    /// All of them must be true for the transition to happen
+    #[serde(default)]
+    pub conditions: Vec<AnimationCondition>,
+    /// How long the states are crossfaded, in seconds
+    #[serde(default)]
+    pub duration: f32,
+    /// If set, the transition only happens once the state has played this many times its length, e.g. 1 at the end of
+    /// its first loop
+    #[serde(default)]
+    pub exit_time: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationCondition {
+    pub parameter: String,
+    pub comparison: AnimationComparison,
+    #[serde(default)]
+    pub value: f32,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimationComparison {
+    Greater,
+    Less,
+    Equal,
+    NotEqual,
+}
+impl AnimationCondition {
+    fn is_met(&self, parameters: &Parameters) -> bool {
+        let value = parameters.get(&self.parameter);
+        match self.comparison {
+            AnimationComparison::Greater => value > self.value,
+            AnimationComparison::Less => value < self.value,
+            AnimationComparison::Equal => value == self.value,
+            AnimationComparison::NotEqual => value != self.value,
+        }
+    }
+}
+
+struct Parameters(HashMap<String, f32>);
+impl Parameters {
+    fn from_entity(world: &World, id: EntityId) -> Self {
+        let names = world.get_ref(id, animation_graph_parameter_names()).map(|names| names.as_slice()).unwrap_or_default();
+        let values = world.get_ref(id, animation_graph_parameter_values()).map(|values| values.as_slice()).unwrap_or_default();
+        Self(names.iter().cloned().zip(values.iter().copied()).collect())
+    }
+    /// Parameters which aren't set are 0
+    fn get(&self, name: &str) -> f32 {
+        self.0.get(name).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveState {
+    index: usize,
+    /// How many times the state has played its length
+    progress: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Crossfade {
+    from: ActiveState,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// The state of the animation graph of an entity, which is evaluated locally
+#[derive(Debug, Clone)]
+pub struct AnimationGraphRuntime {
+    graph: Arc<AnimationGraph>,
+    current: ActiveState,
+    crossfade: Option<Crossfade>,
+    /// The actions the graph plays this frame, blended like those of an animation controller
+    pub actions: Vec<AnimationAction>,
+}
+impl AnimationGraphRuntime {
+    fn new(graph: AnimationGraph) -> anyhow::Result<Self> {
+        anyhow::ensure!(!graph.states.is_empty(), "The animation graph has no states");
+        for transition in &graph.transitions {
+            for name in transition.from.iter().chain([&transition.to]) {
+                anyhow::ensure!(graph.state_index(name).is_some(), "A transition refers to {name:?}, which isn't a state");
+            }
+        }
+        let index = match &graph.initial_state {
+            Some(name) => graph.state_index(name).ok_or_else(|| anyhow::anyhow!("No state named {name:?}"))?,
+            None => 0,
+        };
+        Ok(Self { graph: Arc::new(graph), current: ActiveState { index, progress: 0. }, crossfade: None, actions: vec![] })
+    }
+
+    fn update_transitions(&mut self, parameters: &Parameters) {
+        let current = &self.graph.states[self.current.index];
+        let transition = self.graph.transitions.iter().find(|transition| {
+            let from_current = match &transition.from {
+                Some(from) => *from == current.name,
+                // Transitions from any state don't restart the state they go to
+                None => transition.to != current.name,
+            };
+            from_current
+                && transition.exit_time.map_or(true, |exit_time| self.current.progress >= exit_time)
+                && transition.conditions.iter().all(|condition| condition.is_met(parameters))
+        });
+        let Some(transition) = transition else { return };
+        let index = self.graph.state_index(&transition.to).unwrap();
+        self.crossfade = if transition.duration > 0. {
+            Some(Crossfade { from: self.current, elapsed: 0., duration: transition.duration })
+        } else {
+            None
+        };
+        self.current = ActiveState { index, progress: 0. };
+    }
+
+    /// Advances the states by `dtime`, and returns the actions they play, or None if their clips are still loading
+    fn update_actions(&mut self, world: &World, id: EntityId, parameters: &Parameters, dtime: f32) -> Option<Vec<AnimationAction>> {
+        let mut actions = Vec::new();
+        let fade = self.crossfade.as_ref().map_or(1., |crossfade| (crossfade.elapsed / crossfade.duration).min(1.));
+        let graph = self.graph.clone();
+        let mut active = vec![(&mut self.current, fade)];
+        if let Some(crossfade) = &mut self.crossfade {
+            crossfade.elapsed += dtime;
+            active.push((&mut crossfade.from, 1. - fade));
+        }
+        for (state, weight) in active {
+            let definition = &graph.states[state.index];
+            let clips = definition.motion.clips(parameters);
+            // The clips are played at the same relative time, so that e.g. the steps of walk and run cycles are in sync
+            let mut duration = 0.;
+            for (clip, clip_weight) in &clips {
+                duration += clip_duration(world, id, clip)? * clip_weight;
+            }
+            if duration > 0. {
+                state.progress += dtime * definition.speed / duration;
+            }
+            let percentage = if definition.looping { state.progress } else { state.progress.min(1.) };
+            actions.extend(clips.into_iter().map(|(clip, clip_weight)| AnimationAction {
+                clip: AnimationClipRef::FromModelAsset(clip),
+                time: AnimationActionTime::Percentage { percentage },
+                looping: definition.looping,
+                weight: weight * clip_weight,
+                root_motion: definition.root_motion,
+            }));
+        }
+        if self.crossfade.as_ref().map_or(false, |crossfade| crossfade.elapsed >= crossfade.duration) {
+            self.crossfade = None;
+        }
+        Some(actions)
+    }
+}
+
+fn clip_duration(world: &World, id: EntityId, clip: &TypedAssetUrl<AnimationAssetType>) -> Option<f32> {
+    let assets = world.resource(asset_cache()).clone();
+    let retarget = world.get(id, animation_retargeting()).unwrap_or(AnimationRetargeting::None);
+    let model = world.get_ref(id, model_from_url()).ok().and_then(|def| TypedAssetUrl::parse(def).ok());
+    match AnimationClipRef::FromModelAsset(clip.clone()).get_clip(assets, retarget, model)? {
+        Ok(clip) => Some(clip.duration()),
+        // The error is reported when the clip is played
+        Err(_) => Some(0.),
+    }
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "animation_graph",
+        vec![
+            query(animation_graph().changed()).to_system(|q, world, qs, _| {
+                for (id, json) in q.collect_cloned(world, qs) {
+                    match serde_json::from_str(&json).map_err(anyhow::Error::from).and_then(AnimationGraphRuntime::new) {
+                        Ok(runtime) => {
+                            let state = runtime.graph.states[runtime.current.index].name.clone();
+                            world.add_component(id, animation_graph_runtime(), runtime).unwrap();
+                            world.add_component(id, animation_graph_state(), state).unwrap();
+                            world.remove_component(id, animation_errors()).unwrap();
+                        }
+                        Err(err) => {
+                            world.remove_component(id, animation_graph_runtime()).unwrap();
+                            world.add_component(id, animation_errors(), format!("Invalid animation graph: {err:#}")).unwrap();
+                        }
+                    }
+                }
+            }),
+            query(()).incl(animation_graph_runtime()).excl(animation_graph()).to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    world.remove_components(id, vec![animation_graph_runtime().desc(), animation_graph_state().desc()]).unwrap();
+                }
+            }),
+            query(animation_graph_runtime()).to_system(|q, world, qs, _| {
+                let dtime = *world.resource(dtime());
+                for (id, mut runtime) in q.collect_cloned(world, qs) {
+                    let parameters = Parameters::from_entity(world, id);
+                    let previous_state = runtime.current.index;
+                    runtime.update_transitions(&parameters);
+                    if let Some(actions) = runtime.update_actions(world, id, &parameters, dtime) {
+                        runtime.actions = actions;
+                    }
+                    if runtime.current.index != previous_state {
+                        let state = runtime.graph.states[runtime.current.index].name.clone();
+                        world.set(id, animation_graph_state(), state).unwrap();
+                    }
+                    world.set(id, animation_graph_runtime(), runtime).unwrap();
+                }
+            }),
+        ],
+    )
+}
+
+#[test]
+fn test_blend_1d() {
+    let motion: AnimationMotion = serde_json::from_str(
+        r#"{ "type": "blend1d", "parameter": "speed", "points": [
+            { "value": 0, "clip": "idle.anim" }, { "value": 2, "clip": "walk.anim" }, { "value": 6, "clip": "run.anim" }
+        ] }"#,
+    )
+    .unwrap();
+    let weights = |speed: f32| {
+        let parameters = Parameters([("speed".to_string(), speed)].into_iter().collect());
+        motion.clips(&parameters).into_iter().map(|(clip, weight)| (clip.0.to_string(), weight)).collect::<Vec<_>>()
+    };
+    assert_eq!(weights(-1.), vec![("idle.anim".to_string(), 1.)]);
+    assert_eq!(weights(3.), vec![("walk.anim".to_string(), 0.75), ("run.anim".to_string(), 0.25)]);
+    assert_eq!(weights(10.), vec![("run.anim".to_string(), 1.)]);
+    assert_eq!(weights(2.), vec![("walk.anim".to_string(), 1.), ("run.anim".to_string(), 0.)]);
+    assert_eq!(weights(f32::NAN), vec![("idle.anim".to_string(), 1.)]);
+    assert_eq!(weights(f32::INFINITY), vec![("idle.anim".to_string(), 1.)]);
+}
+
+#[test]
+fn test_blend_1d_unsorted_and_empty() {
+    let parameters = Parameters([("speed".to_string(), 1.)].into_iter().collect());
+    let motion: AnimationMotion = serde_json::from_str(
+        r#"{ "type": "blend1d", "parameter": "speed", "points": [ { "value": 2, "clip": "walk.anim" }, { "value": 0, "clip": "idle.anim" } ] }"#,
+    )
+    .unwrap();
+    let weights = motion.clips(&parameters).into_iter().map(|(clip, weight)| (clip.0.to_string(), weight)).collect::<Vec<_>>();
+    assert_eq!(weights, vec![("idle.anim".to_string(), 0.5), ("walk.anim".to_string(), 0.5)]);
+
+    let motion = AnimationMotion::Blend1d { parameter: "speed".to_string(), points: vec![] };
+    assert!(motion.clips(&parameters).is_empty());
+}
+
+#[test]
+fn test_blend_2d() {
+    let motion: AnimationMotion = serde_json::from_str(
+        r#"{ "type": "blend2d", "x": "x", "y": "y", "points": [
+            { "position": [-1, 0], "clip": "left.anim" }, { "position": [1, 0], "clip": "right.anim" }
+        ] }"#,
+    )
+    .unwrap();
+    let weights = |x: f32| {
+        let parameters = Parameters([("x".to_string(), x)].into_iter().collect());
+        motion.clips(&parameters).into_iter().map(|(clip, weight)| (clip.0.to_string(), weight)).collect::<Vec<_>>()
+    };
+    assert_eq!(weights(1.), vec![("right.anim".to_string(), 1.)]);
+    assert_eq!(weights(0.), vec![("left.anim".to_string(), 0.5), ("right.anim".to_string(), 0.5)]);
+    let weights = weights(0.5);
+    assert!(weights[1].1 > weights[0].1);
+    assert!((weights[0].1 + weights[1].1 - 1.).abs() < 1e-6);
+}
+
+#[test]
+fn test_transitions() {
+    let graph: AnimationGraph = serde_json::from_str(
+        r#"{
+            "states": [
+                { "name": "idle", "motion": { "type": "clip", "clip": "idle.anim" } },
+                { "name": "walk", "motion": { "type": "clip", "clip": "walk.anim" } },
+                { "name": "jump", "motion": { "type": "clip", "clip": "jump.anim" }, "looping": false }
+            ],
+            "transitions": [
+                { "from": "idle", "to": "walk", "conditions": [{ "parameter": "speed", "comparison": "greater", "value": 0.1 }], "duration": 0.2 },
+                { "from": "walk", "to": "idle", "conditions": [{ "parameter": "speed", "comparison": "less", "value": 0.1 }] },
+                { "to": "jump", "conditions": [{ "parameter": "jumping", "comparison": "equal", "value": 1 }] },
+                { "from": "jump", "to": "idle", "exit_time": 1 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let parameters =
+        |speed: f32, jumping: f32| Parameters([("speed".to_string(), speed), ("jumping".to_string(), jumping)].into_iter().collect());
+    let mut runtime = AnimationGraphRuntime::new(graph).unwrap();
+    let state = |runtime: &AnimationGraphRuntime| runtime.graph.states[runtime.current.index].name.clone();
+    assert_eq!(state(&runtime), "idle");
+
+    runtime.update_transitions(&parameters(0., 0.));
+    assert_eq!(state(&runtime), "idle");
+
+    runtime.update_transitions(&parameters(1., 0.));
+    assert_eq!(state(&runtime), "walk");
+    let crossfade = runtime.crossfade.as_ref().unwrap();
+    assert_eq!((crossfade.from.index, crossfade.duration), (0, 0.2));
+
+    runtime.update_transitions(&parameters(0., 0.));
+    assert_eq!(state(&runtime), "idle");
+    assert!(runtime.crossfade.is_none());
+
+    // Transitions from any state go to a state once, and the exit time holds the state until it has played
+    runtime.update_transitions(&parameters(0., 1.));
+    assert_eq!(state(&runtime), "jump");
+    runtime.current.progress = 0.5;
+    runtime.update_transitions(&parameters(0., 1.));
+    assert_eq!((state(&runtime), runtime.current.progress), ("jump".to_string(), 0.5));
+    runtime.current.progress = 1.;
+    runtime.update_transitions(&parameters(0., 0.));
+    assert_eq!(state(&runtime), "idle");
+}
+
+#[test]
+fn test_invalid_graphs() {
+    let graph = |json: &str| AnimationGraphRuntime::new(serde_json::from_str(json).unwrap());
+    assert!(graph(r#"{ "states": [] }"#).is_err());
+    assert!(graph(
+        r#"{ "states": [{ "name": "idle", "motion": { "type": "clip", "clip": "idle.anim" } }], "transitions": [{ "to": "run" }] }"#
+    )
+    .is_err());
+    assert!(
+        graph(r#"{ "states": [{ "name": "idle", "motion": { "type": "clip", "clip": "idle.anim" } }], "initial_state": "run" }"#).is_err()
+    );
+}
@@ -0,0 +1,198 @@
+use ambient_core::{
+    hierarchy::parent,
+    transform::{fbx_complex_transform, get_world_position, get_world_transform, local_to_parent, rotation, scale, translation},
+};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Store, SystemGroup, World};
+use ambient_model::animation_binder;
+use glam::{Mat4, Quat, Vec3};
+
+components!("animation", {
+    @[
+        Debuggable, Networked, Store,
+        Name["IK chain"],
+        Description["The bind ids of the bones of the inverse kinematics chain of this entity, from its root to the bone that reaches for `ik_target`; each bone must be the parent of the next.\nChains of three bones, like legs and arms, are solved analytically, and others with FABRIK."]
+    ]
+    ik_chain: Vec<String>,
+    @[
+        Debuggable, Networked, Store,
+        Name["IK model"],
+        Description["The animated model whose bones `ik_chain` refers to."]
+    ]
+    ik_model: EntityId,
+    @[
+        Debuggable, Networked, Store,
+        Name["IK target"],
+        Description["The entity the last bone of `ik_chain` reaches for, e.g. a point on the terrain for a foot or an object for a hand."]
+    ]
+    ik_target: EntityId,
+    @[
+        Debuggable, Networked, Store,
+        Name["IK pole target"],
+        Description["The entity the middle bone of a chain of three bones bends towards, e.g. in front of the knee. Defaults to the way the animation bends it."]
+    ]
+    ik_pole_target: EntityId,
+    @[
+        Debuggable, Networked, Store,
+        Name["IK weight"],
+        Description["How much the inverse kinematics override the animation, from 0 to 1. Defaults to 1."]
+    ]
+    ik_weight: f32,
+});
+
+const FABRIK_ITERATIONS: usize = 10;
+const FABRIK_TOLERANCE: f32 = 1e-3;
+
+/// Solves the inverse kinematics chains after the animations are evaluated
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "animation_ik",
+        vec![query((ik_chain(), ik_model(), ik_target())).to_system(|q, world, qs, _| {
+            let mut rotations = Vec::new();
+            for (id, (chain, &model, &target)) in q.iter(world, qs) {
+                let weight = world.get(id, ik_weight()).unwrap_or(1.).clamp(0., 1.);
+                let Ok(binder) = world.get_ref(model, animation_binder()) else { continue };
+                let Some(bones) = chain.iter().map(|bone| binder.get(bone).copied()).collect::<Option<Vec<_>>>() else { continue };
+                let Ok(target) = get_world_position(world, target) else { continue };
+                let pole = world.get(id, ik_pole_target()).ok().and_then(|pole| get_world_position(world, pole).ok());
+                if weight > 0. && bones.len() >= 2 {
+                    rotations.extend(solve_chain(world, model, &bones, target, pole, weight));
+                }
+            }
+            for (bone, value) in rotations {
+                world.add_component(bone, rotation(), value).unwrap();
+            }
+        })],
+    )
+}
+
+/// The local rotations of `bones` which make the last one reach `target`
+fn solve_chain(world: &World, model: EntityId, bones: &[EntityId], target: Vec3, pole: Option<Vec3>, weight: f32) -> Vec<(EntityId, Quat)> {
+    let transforms = bones.iter().map(|&bone| animated_world_transform(world, bone, model)).collect::<Vec<_>>();
+    let mut positions = transforms.iter().map(|transform| transform.transform_point3(Vec3::ZERO)).collect::<Vec<_>>();
+    let mut rotations = transforms.iter().map(|transform| transform.to_scale_rotation_translation().1).collect::<Vec<_>>();
+    let parent_rotation =
+        world.get(bones[0], parent()).map(|parent| animated_world_transform(world, parent, model).to_scale_rotation_translation().1);
+    let solved = match positions[..] {
+        [a, b, c] => solve_two_bone([a, b, c], target, pole).to_vec(),
+        _ => solve_fabrik(&positions, target),
+    };
+
+    // Rotates the bones one after the other, so that each of them points at the solved position of the next; the last
+    // bone keeps the rotation the animation gave it
+    for i in 0..bones.len() - 1 {
+        let from = (positions[i + 1] - positions[i]).normalize_or_zero();
+        let to = (solved[i + 1] - solved[i]).normalize_or_zero();
+        if from == Vec3::ZERO || to == Vec3::ZERO {
+            continue;
+        }
+        let delta = Quat::IDENTITY.slerp(Quat::from_rotation_arc(from, to), weight);
+        for j in i + 1..positions.len() {
+            positions[j] = positions[i] + delta * (positions[j] - positions[i]);
+        }
+        for rotation in &mut rotations[i..bones.len() - 1] {
+            *rotation = delta * *rotation;
+        }
+    }
+
+    let mut parent_rotation = parent_rotation.unwrap_or(Quat::IDENTITY);
+    bones
+        .iter()
+        .zip(rotations)
+        .map(|(&bone, rotation)| {
+            let local = parent_rotation.inverse() * rotation;
+            parent_rotation = rotation;
+            (bone, local.normalize())
+        })
+        .collect()
+}
+
+/// The transform of `id` in the world, from the transforms the animations have just set rather than the `local_to_world`
+/// of the previous frame, which lags behind them
+pub(crate) fn animated_world_transform(world: &World, id: EntityId, model: EntityId) -> Mat4 {
+    if id == model {
+        return get_world_transform(world, id).unwrap_or(Mat4::IDENTITY);
+    }
+    match world.get(id, parent()) {
+        Ok(parent) => animated_world_transform(world, parent, model) * animated_local_transform(world, id),
+        Err(_) => get_world_transform(world, id).unwrap_or(Mat4::IDENTITY),
+    }
+}
+
+/// The transform of `id` relative to its parent, from the transforms the animations have just set
+pub(crate) fn animated_local_transform(world: &World, id: EntityId) -> Mat4 {
+    if world.has_component(id, fbx_complex_transform()) {
+        world.get(id, local_to_parent()).unwrap_or(Mat4::IDENTITY)
+    } else {
+        Mat4::from_scale_rotation_translation(
+            world.get(id, scale()).unwrap_or(Vec3::ONE),
+            world.get(id, rotation()).unwrap_or(Quat::IDENTITY),
+            world.get(id, translation()).unwrap_or(Vec3::ZERO),
+        )
+    }
+}
+
+/// Solves a chain of three joints analytically: the middle joint bends towards `pole`, or the way it already bends
+pub fn solve_two_bone([a, b, c]: [Vec3; 3], target: Vec3, pole: Option<Vec3>) -> [Vec3; 3] {
+    let (upper, lower) = (a.distance(b), b.distance(c));
+    let direction = (target - a).normalize_or_zero();
+    if direction == Vec3::ZERO {
+        return [a, b, c];
+    }
+    let distance = a.distance(target).clamp((upper - lower).abs() + 1e-4, upper + lower - 1e-4);
+    let bend = pole.unwrap_or(b) - a;
+    let bend = (bend - direction * bend.dot(direction)).try_normalize().unwrap_or_else(|| direction.any_orthonormal_vector());
+    // The middle joint is where the spheres around the root and the target, with the lengths of the bones as radii, meet
+    let along = (upper * upper - lower * lower + distance * distance) / (2. * distance);
+    let height = (upper * upper - along * along).max(0.).sqrt();
+    [a, a + direction * along + bend * height, a + direction * distance]
+}
+
+/// Solves a chain of any number of joints with FABRIK, which moves the joints back and forth along the chain until the
+/// last one reaches `target`
+pub fn solve_fabrik(joints: &[Vec3], target: Vec3) -> Vec<Vec3> {
+    let lengths = joints.windows(2).map(|pair| pair[0].distance(pair[1])).collect::<Vec<_>>();
+    let mut joints = joints.to_vec();
+    let root = joints[0];
+    let last = joints.len() - 1;
+    if root.distance(target) >= lengths.iter().sum::<f32>() {
+        // Out of reach, so the chain is stretched towards the target
+        for i in 0..last {
+            joints[i + 1] = joints[i] + (target - joints[i]).normalize_or_zero() * lengths[i];
+        }
+        return joints;
+    }
+    for _ in 0..FABRIK_ITERATIONS {
+        joints[last] = target;
+        for i in (0..last).rev() {
+            joints[i] = joints[i + 1] + (joints[i] - joints[i + 1]).normalize_or_zero() * lengths[i];
+        }
+        joints[0] = root;
+        for i in 0..last {
+            joints[i + 1] = joints[i] + (joints[i + 1] - joints[i]).normalize_or_zero() * lengths[i];
+        }
+        if joints[last].distance(target) < FABRIK_TOLERANCE {
+            break;
+        }
+    }
+    joints
+}
+
+#[test]
+fn test_two_bone() {
+    let [a, b, c] = solve_two_bone([Vec3::ZERO, Vec3::new(0., 0., -1.), Vec3::new(0., 0., -2.)], Vec3::new(0., 0., -1.), Some(Vec3::X));
+    assert!(c.distance(Vec3::new(0., 0., -1.)) < 1e-3);
+    assert!((a.distance(b) - 1.).abs() < 1e-3 && (b.distance(c) - 1.).abs() < 1e-3);
+    assert!(b.x > 0.);
+}
+
+#[test]
+fn test_fabrik() {
+    let joints = (0..4).map(|i| Vec3::new(i as f32, 0., 0.)).collect::<Vec<_>>();
+    let target = Vec3::new(1., 1.5, 0.);
+    let solved = solve_fabrik(&joints, target);
+    assert_eq!(solved[0], Vec3::ZERO);
+    assert!(solved[3].distance(target) < 1e-2);
+    for pair in solved.windows(2) {
+        assert!((pair[0].distance(pair[1]) - 1.).abs() < 1e-3);
+    }
+}
@@ -1,5 +1,5 @@
-use ambient_core::{asset_cache, hierarchy::children, time};
-use ambient_ecs::{components, query, Debuggable, EntityId, MakeDefault, Networked, Store, SystemGroup};
+use ambient_core::{asset_cache, game_time, hierarchy::children, time};
+use ambient_ecs::{components, query, Debuggable, EntityId, MakeDefault, Networked, Store, SystemGroup, World};
 use ambient_model::{animation_binder, model, model_from_url, ModelFromUrl};
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKeyExt},
@@ -12,11 +12,16 @@ use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
+pub mod graph;
+pub mod ik;
+pub mod ragdoll;
 mod resources;
 mod retargeting;
+mod root_motion;
 
 pub use resources::*;
 pub use retargeting::*;
+pub use root_motion::*;
 
 components!("animation", {
     @[Debuggable, Networked, Store]
@@ -32,6 +37,10 @@ components!("animation", {
     copy_animation_controller_to_children: (),
     @[Debuggable, Networked, Store]
     animation_errors: String,
+    /// How far `game_time` was behind `time`, in seconds, when the `animation_controller` was last changed. The `Offset`
+    /// actions, which start at a `time`, advance with the `game_time` from then on, so that they slow down and pause with it
+    @[Debuggable]
+    animation_time_lag: f32,
 
     /// This is a shorthand for working directly with the animation_controller
     @[MakeDefault,  Debuggable, Networked, Store]
@@ -99,6 +108,12 @@ pub struct AnimationAction {
     pub time: AnimationActionTime,
     pub looping: bool,
     pub weight: f32,
+    /// Whether the root motion of the clip is taken out of it, if the entity has `root_motion`
+    #[serde(default = "default_root_motion")]
+    pub root_motion: bool,
+}
+fn default_root_motion() -> bool {
+    true
 }
 impl AnimationAction {
     fn time(&self, time: Duration, clip: &AnimationClip) -> f32 {
@@ -138,6 +153,7 @@ impl AnimationController {
                 time: AnimationActionTime::Offset { start_time: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap(), speed },
                 looping: true,
                 weight: 1.0,
+                root_motion: true,
             }],
             apply_base_pose: true,
         }
@@ -151,6 +167,13 @@ struct AnimationBlendOutput {
     weight: f32,
 }
 
+fn game_time_of(world: &World) -> Duration {
+    world.resource_opt(game_time()).copied().unwrap_or_else(|| *world.resource(time()))
+}
+fn time_lag(time: Duration, game_time: Duration) -> f32 {
+    (time.as_secs_f64() - game_time.as_secs_f64()) as f32
+}
+
 pub fn animation_systems() -> SystemGroup {
     SystemGroup::new(
         "animation_systems",
@@ -170,8 +193,10 @@ pub fn animation_systems() -> SystemGroup {
                 },
             ),
             query(animation_controller().changed()).optional_changed(model()).to_system(|q, world, qs, _| {
+                let lag = time_lag(*world.resource(time()), game_time_of(world));
                 for (id, ctrlr) in q.collect_cloned(world, qs) {
                     world.remove_component(id, animation_errors()).unwrap();
+                    world.add_component(id, animation_time_lag(), lag).unwrap();
                     if ctrlr.apply_base_pose {
                         if let Some(action) = ctrlr.actions.get(0) {
                             if let AnimationClipRef::FromModelAsset(def) = &action.clip {
@@ -197,16 +222,34 @@ pub fn animation_systems() -> SystemGroup {
                     }
                 }
             }),
-            query((animation_controller(), animation_binder())).excl(animation_errors()).to_system(|q, world, qs, _| {
+            Box::new(graph::systems()),
+            query(animation_binder()).excl(animation_errors()).to_system(|q, world, qs, _| {
                 let assets = world.resource(asset_cache()).clone();
                 let time = *world.resource(time());
+                let game_time = game_time_of(world);
                 let mut outputs: HashMap<String, AnimationBlendOutput> = HashMap::new();
                 let mut in_error = Vec::new();
-                for (id, (controller, binder)) in q.iter(world, qs) {
+                let mut root_motions = Vec::new();
+                for (id, binder) in q.iter(world, qs) {
+                    // The animation graph takes precedence over the controller
+                    let actions = if let Ok(runtime) = world.get_ref(id, graph::animation_graph_runtime()) {
+                        &runtime.actions
+                    } else if let Ok(controller) = world.get_ref(id, animation_controller()) {
+                        &controller.actions
+                    } else {
+                        continue;
+                    };
                     let retaget = world.get(id, animation_retargeting()).unwrap_or(AnimationRetargeting::None);
                     let model = world.get_ref(id, model_from_url()).ok().and_then(|def| TypedAssetUrl::parse(def).ok());
+                    let lag = world.get(id, animation_time_lag()).unwrap_or_else(|_| time_lag(time, game_time));
+                    let time = if lag >= 0. {
+                        game_time + Duration::from_secs_f32(lag)
+                    } else {
+                        game_time.saturating_sub(Duration::from_secs_f32(-lag))
+                    };
+                    let mut root_motion = RootMotion::new(world, id, binder, actions);
                     // Calc
-                    for action in controller.actions.iter() {
+                    for action in actions.iter() {
                         match action.clip.get_clip(assets.clone(), retaget, model.clone()) {
                             Some(Err(err)) => {
                                 in_error.push((id, err));
@@ -215,7 +258,10 @@ pub fn animation_systems() -> SystemGroup {
                             Some(Ok(clip)) => {
                                 let anim_time = action.time(time, &clip);
                                 for track in clip.tracks.iter() {
-                                    let value = AnimationTrackInterpolator::new().value(track, anim_time);
+                                    let mut value = AnimationTrackInterpolator::new().value(track, anim_time);
+                                    if let Some(root_motion) = &mut root_motion {
+                                        value = root_motion.extract(action, &clip, track, anim_time, value);
+                                    }
                                     let key = format!(
                                         "{}_{:?}_{}_{:?}",
                                         id,
@@ -253,6 +299,7 @@ pub fn animation_systems() -> SystemGroup {
                             None => {}
                         }
                     }
+                    root_motions.extend(root_motion.map(|root_motion| (id, root_motion)));
                 }
 
                 // Apply
@@ -275,14 +322,27 @@ pub fn animation_systems() -> SystemGroup {
                         }
                     }
                 }
+                for (id, root_motion) in root_motions {
+                    root_motion.apply(world, id);
+                }
                 for (id, err) in in_error {
                     world.add_component(id, animation_errors(), err).unwrap();
                 }
             }),
+            Box::new(ik::systems()),
+            Box::new(ragdoll::systems()),
         ],
     )
 }
 
+pub fn init_all_components() {
+    init_components();
+    graph::init_components();
+    ik::init_components();
+    ragdoll::init_components();
+    root_motion::init_components();
+}
+
 pub fn animation_bind_id_from_name(name: &str) -> String {
     let name = if let Some((_a, b)) = name.split_once(':') { b.to_string() } else { name.to_string() };
     fn normalize_name(value: &str) -> String {
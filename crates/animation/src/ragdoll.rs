@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use ambient_core::{
+    hierarchy::parent,
+    transform::{rotation, translation},
+};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Store, SystemGroup, World};
+use ambient_model::animation_binder;
+use glam::{Mat4, Quat, Vec3};
+
+use crate::ik::{animated_local_transform, animated_world_transform};
+
+components!("animation", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Ragdoll weight"],
+        Description["The active ragdoll weight: how much the pose of this model follows its ragdoll rather than its animation, from 0 to 1.\nThe server blends it towards the `ragdoll_target_weight`."]
+    ]
+    ragdoll_weight: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Ragdoll model"],
+        Description["The model whose ragdoll this physics body is a part of."]
+    ]
+    ragdoll_model: EntityId,
+    @[
+        Debuggable, Networked, Store,
+        Name["Ragdoll bone"],
+        Description["The bind id of the bone of the `ragdoll_model` which this physics body moves."]
+    ]
+    ragdoll_bone: String,
+});
+
+/// Blends the animated poses of the models with the bodies of their ragdolls, after the animations and the inverse
+/// kinematics are evaluated
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "animation_ragdoll",
+        vec![query((ragdoll_model(), ragdoll_bone(), translation(), rotation())).to_system(|q, world, qs, _| {
+            let mut bodies = HashMap::<EntityId, HashMap<EntityId, (Vec3, Quat)>>::new();
+            for (_, (&model, bone, &position, &rotation)) in q.iter(world, qs) {
+                let Ok(binder) = world.get_ref(model, animation_binder()) else { continue };
+                let Some(&bone) = binder.get(bone) else { continue };
+                bodies.entry(model).or_default().insert(bone, (position, rotation));
+            }
+            let mut poses = Vec::new();
+            for (model, bodies) in bodies {
+                let weight = world.get(model, ragdoll_weight()).unwrap_or(0.).clamp(0., 1.);
+                if weight > 0. {
+                    poses.extend(blend_pose(world, model, &bodies, weight));
+                }
+            }
+            for (bone, position, value) in poses {
+                if let Some(position) = position {
+                    world.add_component(bone, translation(), position).unwrap();
+                }
+                world.add_component(bone, rotation(), value).unwrap();
+            }
+        })],
+    )
+}
+
+/// The local poses of the bones which have a body, blended from their animated poses towards the poses of the bodies.
+/// Only the bones at the root of the ragdoll are moved; the others just rotate, as the joints keep them together
+fn blend_pose(
+    world: &World,
+    model: EntityId,
+    bodies: &HashMap<EntityId, (Vec3, Quat)>,
+    weight: f32,
+) -> Vec<(EntityId, Option<Vec3>, Quat)> {
+    let mut blended = HashMap::new();
+    bodies
+        .keys()
+        .map(|&bone| {
+            let transform = blended_world_transform(world, model, bone, bodies, weight, &mut blended);
+            let parent_transform = match world.get(bone, parent()) {
+                Ok(parent) if bone != model => blended_world_transform(world, model, parent, bodies, weight, &mut blended),
+                _ => Mat4::IDENTITY,
+            };
+            let (_, rotation, position) = (parent_transform.inverse() * transform).to_scale_rotation_translation();
+            let is_root = !ancestors(world, bone, model).any(|ancestor| bodies.contains_key(&ancestor));
+            (bone, is_root.then_some(position), rotation.normalize())
+        })
+        .collect()
+}
+
+fn blended_world_transform(
+    world: &World,
+    model: EntityId,
+    id: EntityId,
+    bodies: &HashMap<EntityId, (Vec3, Quat)>,
+    weight: f32,
+    blended: &mut HashMap<EntityId, Mat4>,
+) -> Mat4 {
+    if let Some(transform) = blended.get(&id) {
+        return *transform;
+    }
+    let transform = if let Some(&(position, rotation)) = bodies.get(&id) {
+        let (scale, animated_rotation, animated_position) = animated_world_transform(world, id, model).to_scale_rotation_translation();
+        Mat4::from_scale_rotation_translation(scale, animated_rotation.slerp(rotation, weight), animated_position.lerp(position, weight))
+    } else {
+        match world.get(id, parent()) {
+            Ok(parent) if id != model => {
+                blended_world_transform(world, model, parent, bodies, weight, blended) * animated_local_transform(world, id)
+            }
+            _ => animated_world_transform(world, id, model),
+        }
+    };
+    blended.insert(id, transform);
+    transform
+}
+
+fn ancestors(world: &World, id: EntityId, model: EntityId) -> impl Iterator<Item = EntityId> + '_ {
+    std::iter::successors(world.get(id, parent()).ok(), move |&id| world.get(id, parent()).ok()).take_while(move |&id| id != model)
+}
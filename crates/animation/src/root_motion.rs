@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use ambient_core::{
+    hierarchy::parent,
+    transform::{local_to_world, rotation, translation},
+};
+use ambient_ecs::{components, Debuggable, Description, EntityId, Name, Networked, Store, World};
+use glam::{Mat4, Quat, Vec3};
+
+use crate::{AnimationAction, AnimationClip, AnimationOutput, AnimationTrack, AnimationTrackInterpolator};
+
+components!("animation", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Root motion"],
+        Description["If attached, the horizontal movement and turning of the root bone are taken out of the animations of this entity, and written to `root_motion_translation_delta` and `root_motion_rotation_delta` every frame instead.\nClips can opt out with their root motion flag."]
+    ]
+    root_motion: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Root motion bone"],
+        Description["The bind id of the bone the root motion is taken from. Defaults to `Hips`."]
+    ]
+    root_motion_bone: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Apply root motion"],
+        Description["If attached, the root motion of this entity is added to its `translation` and `rotation` where it's animated.\nOtherwise, it's left for e.g. a character controller to use."]
+    ]
+    apply_root_motion: (),
+    @[
+        Debuggable,
+        Name["Root motion translation delta"],
+        Description["How far the root bone moved horizontally this frame, in world space, if this entity has `root_motion`."]
+    ]
+    root_motion_translation_delta: Vec3,
+    @[
+        Debuggable,
+        Name["Root motion rotation delta"],
+        Description["How far the root bone turned around the Z axis this frame, in radians, if this entity has `root_motion`."]
+    ]
+    root_motion_rotation_delta: f32,
+    /// The time each clip was sampled at on the previous frame, by clip id
+    @[Debuggable]
+    root_motion_clip_times: HashMap<String, f32>,
+});
+
+const DEFAULT_ROOT_BONE: &str = "Hips";
+
+/// The root motion of an entity, taken out of the pose of its root bone while its actions are evaluated
+pub(crate) struct RootMotion {
+    bone: String,
+    /// The transform of the parent of the root bone, which its animated translation and rotation are relative to
+    parent_transform: Mat4,
+    parent_rotation: Quat,
+    total_weight: f32,
+    previous_times: HashMap<String, f32>,
+    times: HashMap<String, f32>,
+    translation: Vec3,
+    rotation: f32,
+}
+impl RootMotion {
+    /// Returns None if `id` doesn't have `root_motion`
+    pub(crate) fn new(world: &World, id: EntityId, binder: &HashMap<String, EntityId>, actions: &[AnimationAction]) -> Option<Self> {
+        if !world.has_component(id, root_motion()) {
+            return None;
+        }
+        let bone = world.get_ref(id, root_motion_bone()).cloned().unwrap_or_else(|_| DEFAULT_ROOT_BONE.to_string());
+        let parent_transform = binder
+            .get(&bone)
+            .and_then(|bone| world.get(*bone, parent()).ok())
+            .and_then(|parent| world.get(parent, local_to_world()).ok())
+            .unwrap_or(Mat4::IDENTITY);
+        let (_, parent_rotation, _) = parent_transform.to_scale_rotation_translation();
+        Some(Self {
+            bone,
+            parent_transform,
+            parent_rotation,
+            total_weight: actions.iter().map(|action| action.weight).sum(),
+            previous_times: world.get_ref(id, root_motion_clip_times()).cloned().unwrap_or_default(),
+            times: HashMap::new(),
+            translation: Vec3::ZERO,
+            rotation: 0.,
+        })
+    }
+
+    /// Takes the root motion out of `value`, the value of `track` at `anim_time`, and accumulates it
+    pub(crate) fn extract(
+        &mut self,
+        action: &AnimationAction,
+        clip: &AnimationClip,
+        track: &AnimationTrack,
+        anim_time: f32,
+        value: AnimationOutput,
+    ) -> AnimationOutput {
+        if !action.root_motion || track.target.bind_id() != Some(self.bone.as_str()) {
+            return value;
+        }
+        self.times.insert(clip.id.clone(), anim_time);
+        let previous = self.previous_times.get(&clip.id).copied();
+        // Looping actions are sampled from 0, see AnimationAction::time
+        let (start, end) = if action.looping { (0., clip.duration()) } else { (clip.start, clip.end) };
+        let looped = |previous: f32| action.looping && anim_time < previous;
+        let weight = if self.total_weight > 0. { action.weight / self.total_weight } else { 0. };
+        let (parent_transform, parent_rotation) = (self.parent_transform, self.parent_rotation);
+
+        match value {
+            AnimationOutput::Vec3 { component, value } if track.outputs.component() == translation() => {
+                // In world space, so that the height of the bone, which isn't root motion, can be kept
+                let at = |time: f32| match AnimationTrackInterpolator::new().value(track, time) {
+                    AnimationOutput::Vec3 { value, .. } => parent_transform.transform_vector3(value),
+                    _ => unreachable!(),
+                };
+                if let Some(previous) = previous {
+                    let delta = if looped(previous) {
+                        (at(end) - at(previous)) + (at(anim_time) - at(start))
+                    } else {
+                        at(anim_time) - at(previous)
+                    };
+                    self.translation += Vec3::new(delta.x, delta.y, 0.) * weight;
+                }
+                let height = (parent_transform.transform_vector3(value) - at(start)).z;
+                let stripped = at(start) + Vec3::Z * height;
+                AnimationOutput::Vec3 { component, value: parent_transform.inverse().transform_vector3(stripped) }
+            }
+            AnimationOutput::Quat { component, value } if track.outputs.component() == rotation() => {
+                let at = |time: f32| match AnimationTrackInterpolator::new().value(track, time) {
+                    AnimationOutput::Quat { value, .. } => yaw(parent_rotation * value),
+                    _ => unreachable!(),
+                };
+                if let Some(previous) = previous {
+                    let delta = if looped(previous) {
+                        wrap_angle(at(end) - at(previous)) + wrap_angle(at(anim_time) - at(start))
+                    } else {
+                        wrap_angle(at(anim_time) - at(previous))
+                    };
+                    self.rotation += delta * weight;
+                }
+                let world_rotation = parent_rotation * value;
+                let stripped = Quat::from_rotation_z(at(start) - yaw(world_rotation)) * world_rotation;
+                AnimationOutput::Quat { component, value: parent_rotation.inverse() * stripped }
+            }
+            value => value,
+        }
+    }
+
+    /// Writes the root motion to `id`, and applies it if `id` has `apply_root_motion`
+    pub(crate) fn apply(self, world: &mut World, id: EntityId) {
+        world.add_component(id, root_motion_translation_delta(), self.translation).unwrap();
+        world.add_component(id, root_motion_rotation_delta(), self.rotation).unwrap();
+        world.add_component(id, root_motion_clip_times(), self.times).unwrap();
+        if world.has_component(id, apply_root_motion()) {
+            if let Ok(value) = world.get_mut(id, translation()) {
+                *value += self.translation;
+            }
+            if let Ok(value) = world.get_mut(id, rotation()) {
+                *value = Quat::from_rotation_z(self.rotation) * *value;
+            }
+        }
+    }
+}
+
+/// The angle of `rotation` around the Z axis
+fn yaw(rotation: Quat) -> f32 {
+    2. * rotation.z.atan2(rotation.w)
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
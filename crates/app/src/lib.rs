@@ -22,10 +22,11 @@ use ambient_ecs::{
 use ambient_element::ambient_system;
 use ambient_gizmos::{gizmos, Gizmos};
 use ambient_gpu::{
-    gpu::{Gpu, GpuKey},
+    gpu::{Gpu, GpuAdapterOptions, GpuKey},
     mesh_buffer::MeshBufferKey,
 };
 use ambient_renderer::lod::lod_system;
+use ambient_settings::{settings, GpuBackend, GpuPowerPreference, GpuSettings, Settings};
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
     fps_counter::{FpsCounter, FpsSample},
@@ -58,11 +59,12 @@ pub fn init_all_components() {
     ambient_ecs::init_components();
     ambient_core::init_all_components();
     ambient_element::init_components();
-    ambient_animation::init_components();
+    ambient_animation::init_all_components();
     ambient_gizmos::init_components();
     ambient_cameras::init_all_components();
     init_components();
     ambient_renderer::init_all_components();
+    ambient_settings::init_components();
     ambient_ui::init_all_components();
     ambient_input::init_all_components();
     ambient_model::init_components();
@@ -104,6 +106,7 @@ pub fn world_instance_systems(full: bool) -> SystemGroup {
             Box::new(ambient_renderer::skinning::skinning_systems()),
             Box::new(bounding_systems()),
             Box::new(camera_systems()),
+            Box::new(ambient_gizmos::systems()),
         ],
     )
 }
@@ -130,6 +133,12 @@ impl AppResources {
             window_scale_factor: *world.resource(ambient_core::window::window_scale_factor()),
         }
     }
+    /// For worlds without a window, which render to a target of `size`
+    pub fn headless(assets: AssetCache, gpu: Arc<Gpu>, runtime: RuntimeHandle, size: UVec2) -> Self {
+        // Nothing listens to the window controls
+        let (ctl_tx, _) = flume::unbounded();
+        Self { assets, gpu, runtime, ctl_tx, window_physical_size: size, window_logical_size: size, window_scale_factor: 1. }
+    }
 }
 
 pub fn world_instance_resources(resources: AppResources) -> Entity {
@@ -147,6 +156,7 @@ pub fn world_instance_resources(resources: AppResources) -> Entity {
         .with(ambient_core::app_start_time(), current_time)
         .with(ambient_core::time(), current_time)
         .with(ambient_core::dtime(), 0.)
+        .with(ambient_core::determinism::rng(), ambient_core::determinism::WorldRng::from_entropy())
         .with(gpu_world(), GpuWorld::new_arced(resources.assets))
         .with_merge(ambient_input::picking::resources())
         .with_merge(ambient_core::async_ecs::async_ecs_resources())
@@ -154,6 +164,25 @@ pub fn world_instance_resources(resources: AppResources) -> Entity {
         .with(ambient_core::window::window_logical_size(), resources.window_logical_size)
         .with(ambient_core::window::window_scale_factor(), resources.window_scale_factor)
         .with(ambient_core::window::window_ctl(), resources.ctl_tx)
+        .with(ambient_core::logging::log_buffer(), ambient_core::logging::LogBuffer::global().clone())
+}
+
+/// How the [Gpu] is created with the user's GPU settings
+pub fn gpu_adapter_options(settings: &GpuSettings) -> GpuAdapterOptions {
+    GpuAdapterOptions {
+        backends: match settings.backend {
+            GpuBackend::Auto => None,
+            GpuBackend::Vulkan => Some(wgpu::Backends::VULKAN),
+            GpuBackend::Metal => Some(wgpu::Backends::METAL),
+            GpuBackend::Dx12 => Some(wgpu::Backends::DX12),
+            GpuBackend::Gl => Some(wgpu::Backends::GL),
+        },
+        power_preference: match settings.power_preference {
+            GpuPowerPreference::Discrete => wgpu::PowerPreference::HighPerformance,
+            GpuPowerPreference::Integrated => wgpu::PowerPreference::LowPower,
+        },
+        adapter_name: settings.adapter_name.clone(),
+    }
 }
 
 pub fn get_time_since_app_start(world: &World) -> Duration {
@@ -168,6 +197,7 @@ pub struct AppBuilder {
     pub main_renderer: bool,
     pub examples_systems: bool,
     pub headless: Option<UVec2>,
+    pub settings: Option<Settings>,
 }
 
 pub trait AsyncInit<'a> {
@@ -197,6 +227,7 @@ impl AppBuilder {
             main_renderer: true,
             examples_systems: false,
             headless: None,
+            settings: None,
         }
     }
     pub fn simple() -> Self {
@@ -243,16 +274,28 @@ impl AppBuilder {
         self
     }
 
+    /// Use these settings instead of the ones loaded from the user's settings file
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
     pub async fn build(self) -> anyhow::Result<App> {
         crate::init_all_components();
         let (window, event_loop) = if self.headless.is_some() {
             (None, None)
         } else {
             let event_loop = self.event_loop.unwrap_or_else(EventLoop::new);
-            let window = self.window_builder.unwrap_or_default();
+            // The window is shown once the accessibility adapter is set up
+            let window = self.window_builder.unwrap_or_default().with_visible(false);
             let window = Arc::new(window.build(&event_loop).unwrap());
             (Some(window), Some(event_loop))
         };
+        #[cfg(not(target_os = "unknown"))]
+        let accessibility = window.as_ref().map(|window| ambient_ui::accessibility::Accessibility::new(window));
+        if let Some(window) = &window {
+            window.set_visible(true);
+        }
 
         #[cfg(target_os = "unknown")]
         // Insert a canvas element for the window to attach to
@@ -290,7 +333,8 @@ impl AppBuilder {
         let assets = self.asset_cache.unwrap_or_else(|| AssetCache::new(runtime.clone()));
 
         let mut world = World::new("main_app");
-        let gpu = Arc::new(Gpu::with_config(window.as_deref(), true).await);
+        let settings = self.settings.unwrap_or_else(Settings::load_or_default);
+        let gpu = Arc::new(Gpu::with_config(window.as_deref(), true, &gpu_adapter_options(&settings.gpu)).await);
 
         tracing::debug!("Inserting runtime");
         RuntimeKey.insert(&assets, runtime.clone());
@@ -313,6 +357,11 @@ impl AppBuilder {
         let resources = world_instance_resources(app_resources);
 
         world.add_components(world.resource_entity(), resources).unwrap();
+        world.add_resource(self::settings(), settings);
+        world.add_resource(ambient_input::files::files(), ambient_input::files::Files::new());
+        if window.is_some() {
+            world.add_resource(ambient_input::gamepad::gamepads(), ambient_input::gamepad::Gamepads::new());
+        }
         tracing::debug!("Setup renderers");
         if self.ui_renderer || self.main_renderer {
             // let _span = info_span!("setup_renderers").entered();
@@ -348,6 +397,8 @@ impl AppBuilder {
             gpu_world_sync_systems: gpu_world_sync_systems(),
             window_event_systems,
             event_loop,
+            #[cfg(not(target_os = "unknown"))]
+            accessibility,
 
             fps: FpsCounter::new(),
             #[cfg(feature = "profile")]
@@ -394,6 +445,8 @@ pub struct App {
     pub runtime: RuntimeHandle,
     pub window: Option<Arc<Window>>,
     event_loop: Option<EventLoop<()>>,
+    #[cfg(not(target_os = "unknown"))]
+    accessibility: Option<ambient_ui::accessibility::Accessibility>,
     fps: FpsCounter,
     #[cfg(feature = "profile")]
     _puffin: puffin_http::Server,
@@ -492,6 +545,10 @@ impl App {
         let gpu_world_sync_systems = &mut self.gpu_world_sync_systems;
         world.resource(gpu()).device.poll(wgpu::Maintain::Poll);
 
+        #[cfg(not(target_os = "unknown"))]
+        if let (Event::WindowEvent { event, .. }, Some(accessibility), Some(window)) = (event, &self.accessibility, &self.window) {
+            accessibility.on_window_event(window, event);
+        }
         self.window_event_systems.run(world, event);
         match event {
             Event::MainEventsCleared => {
@@ -514,6 +571,22 @@ impl App {
                                 window.set_cursor_icon(icon);
                             }
                         }
+                        WindowCtl::SetSize(size) => {
+                            if let Some(window) = &self.window {
+                                window.set_inner_size(winit::dpi::LogicalSize::new(size.x, size.y));
+                            }
+                        }
+                        WindowCtl::SetVisible(visible) => {
+                            if let Some(window) = &self.window {
+                                window.set_visible(visible);
+                                // winit doesn't hide the canvas on the web
+                                #[cfg(target_os = "unknown")]
+                                {
+                                    use winit::platform::web::WindowExtWebSys;
+                                    window.canvas().style().set_property("display", if visible { "" } else { "none" }).ok();
+                                }
+                            }
+                        }
                     }
                 }
 
@@ -525,6 +598,10 @@ impl App {
                     systems.run(world, &FrameEvent);
                     gpu_world_sync_systems.run(world, &GpuWorldSyncEvent);
                 }
+                #[cfg(not(target_os = "unknown"))]
+                if let Some(accessibility) = &mut self.accessibility {
+                    accessibility.update(world);
+                }
 
                 if let Some(fps) = self.fps.frame_next() {
                     world.set(world.resource_entity(), self::fps_stats(), fps.clone()).unwrap();
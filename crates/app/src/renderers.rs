@@ -9,8 +9,9 @@ use ambient_gpu::{
     texture::{Texture, TextureView},
 };
 use ambient_renderer::{renderer_stats, RenderTarget, Renderer, RendererConfig, RendererTarget};
+use ambient_settings::settings;
 use ambient_std::{asset_cache::SyncAssetKeyExt, color::Color};
-use ambient_ui::app_background_color;
+use ambient_ui::theme::{tokens, Theme};
 use glam::{uvec2, UVec2};
 use parking_lot::Mutex;
 use winit::{
@@ -85,10 +86,17 @@ impl ExamplesRender {
                 let mut renderer = Renderer::new(
                     world,
                     world.resource(asset_cache()).clone(),
-                    RendererConfig { scene: main_scene(), shadows: true, ..Default::default() },
+                    RendererConfig {
+                        scene: main_scene(),
+                        shadows: true,
+                        gpu_picking: world.resource(settings()).render.gpu_picking,
+                        reflection_probes: true,
+                        ..Default::default()
+                    }
+                    .with_render_settings(&world.resource(settings()).render),
                 );
                 tracing::debug!("Creating gizmo renderer");
-                renderer.post_transparent = Some(Box::new(GizmoRenderer::new(&assets)));
+                renderer.graph.add_pass(GizmoRenderer::render_pass_desc(), Box::new(GizmoRenderer::new(&assets))).unwrap();
                 Some(renderer)
             } else {
                 None
@@ -174,7 +182,7 @@ impl System for ExamplesRender {
                 &mut encoder,
                 &mut post_submit,
                 RendererTarget::Target(&self.render_target),
-                if self.main.is_some() { None } else { Some(app_background_color()) },
+                if self.main.is_some() { None } else { Some(Theme::from_world(world).color(tokens::APP_BACKGROUND)) },
             );
         }
         if let Some(surface) = &self.gpu.surface {
@@ -261,7 +269,7 @@ impl UIRender {
             world.resource(asset_cache()).clone(),
             RendererConfig { scene: ui_scene(), shadows: false, ..Default::default() },
         );
-        ui_renderer.post_transparent = Some(Box::new(GizmoRenderer::new(&assets)));
+        ui_renderer.graph.add_pass(GizmoRenderer::render_pass_desc(), Box::new(GizmoRenderer::new(&assets))).unwrap();
         Self {
             ui_renderer,
             depth_buffer_view: Arc::new(depth_buffer.create_view(&Default::default())),
@@ -312,7 +320,7 @@ impl UIRender {
                 size: wgpu::Extent3d { width: window_size.x, height: window_size.y, depth_or_array_layers: 1 },
                 normals: &self.normals_view,
             },
-            Some(app_background_color()),
+            Some(Theme::from_world(world).color(tokens::APP_BACKGROUND)),
         );
         {
             profiling::scope!("Submit");
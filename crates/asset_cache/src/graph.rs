@@ -0,0 +1,207 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_sys::time::Instant;
+
+use crate::{AssetHolder, AssetKey, AssetsTimeline};
+
+/// Tracks which referrers (usually entities) use which assets, and which assets were loaded by which other assets.
+///
+/// A referenced asset is kept alive by the graph, instead of by its keepalive, until its last referrer releases it and a
+/// grace period has passed. This means that assets that are no longer used, and the GPU memory they hold, are freed shortly
+/// after the last entity using them is despawned, while an asset that is quickly used again (e.g. when respawning) doesn't have to be reloaded.
+pub struct AssetGraph {
+    grace_period: Duration,
+    held: HashMap<AssetKey, HeldAsset>,
+    referrers: HashMap<String, HashSet<AssetKey>>,
+    /// The assets that each asset loaded while it was loading
+    dependencies: HashMap<AssetKey, HashSet<AssetKey>>,
+    /// Gets a strong reference to a loaded dependency, if it's still alive
+    upgraders: HashMap<AssetKey, Upgrader>,
+}
+
+pub(crate) type Upgrader = Arc<dyn Fn() -> Option<Arc<dyn AssetHolder>> + Sync + Send>;
+
+struct HeldAsset {
+    strong: Arc<dyn AssetHolder>,
+    referrers: HashSet<String>,
+    unreferenced_since: Option<Instant>,
+}
+
+/// The memory used by an asset, as shown in the debugger
+#[derive(Debug, Clone)]
+pub struct AssetMemory {
+    pub key: AssetKey,
+    pub long_name: String,
+    pub referrers: usize,
+    pub cpu_size: u64,
+    pub gpu_size: u64,
+    /// The sizes of this asset and of all the assets it loaded, counting each only once
+    pub total_cpu_size: u64,
+    pub total_gpu_size: u64,
+}
+
+impl AssetGraph {
+    pub(crate) fn new() -> Self {
+        Self {
+            grace_period: Duration::from_secs(10),
+            held: HashMap::new(),
+            referrers: HashMap::new(),
+            dependencies: HashMap::new(),
+            upgraders: HashMap::new(),
+        }
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+    /// How long an asset is kept after its last referrer releases it
+    pub fn set_grace_period(&mut self, grace_period: Duration) {
+        self.grace_period = grace_period;
+    }
+
+    /// References `key`, and every asset it loaded (such as the meshes and textures of a model) that is still alive
+    pub(crate) fn reference(&mut self, referrer: String, key: AssetKey, strong: Arc<dyn AssetHolder>) {
+        let mut visited = HashSet::new();
+        let mut stack = self.dependencies.get(&key).into_iter().flatten().cloned().collect::<Vec<_>>();
+        self.reference_one(&referrer, key.clone(), || Some(strong));
+        visited.insert(key);
+        while let Some(key) = stack.pop() {
+            if !visited.insert(key.clone()) {
+                continue;
+            }
+            let Some(upgrader) = self.upgraders.get(&key).cloned() else { continue };
+            if self.reference_one(&referrer, key.clone(), &*upgrader) {
+                stack.extend(self.dependencies.get(&key).into_iter().flatten().cloned());
+            }
+        }
+    }
+
+    fn reference_one(&mut self, referrer: &str, key: AssetKey, strong: impl FnOnce() -> Option<Arc<dyn AssetHolder>>) -> bool {
+        let held = match self.held.entry(key.clone()) {
+            Entry::Occupied(slot) => slot.into_mut(),
+            Entry::Vacant(slot) => match strong() {
+                Some(strong) => slot.insert(HeldAsset { strong, referrers: HashSet::new(), unreferenced_since: None }),
+                None => return false,
+            },
+        };
+        held.referrers.insert(referrer.to_string());
+        held.unreferenced_since = None;
+        self.referrers.entry(referrer.to_string()).or_default().insert(key);
+        true
+    }
+
+    /// Releases all the assets referenced by `referrer`
+    pub fn release(&mut self, referrer: &str) {
+        let Some(keys) = self.referrers.remove(referrer) else { return };
+        for key in keys {
+            self.release_key(referrer, &key);
+        }
+    }
+
+    /// Releases a single asset referenced by `referrer`
+    pub fn release_asset(&mut self, referrer: &str, key: &str) {
+        let key = AssetKey::new(key);
+        if let Some(keys) = self.referrers.get_mut(referrer) {
+            keys.remove(&key);
+            if keys.is_empty() {
+                self.referrers.remove(referrer);
+            }
+        }
+        self.release_key(referrer, &key);
+    }
+
+    fn release_key(&mut self, referrer: &str, key: &AssetKey) {
+        if let Some(held) = self.held.get_mut(key) {
+            held.referrers.remove(referrer);
+            if held.referrers.is_empty() {
+                held.unreferenced_since = Some(Instant::now());
+            }
+        }
+    }
+
+    pub(crate) fn add_dependency(&mut self, parent: &AssetKey, child: &AssetKey, upgrader: Upgrader) {
+        self.dependencies.entry(parent.clone()).or_default().insert(child.clone());
+        self.upgraders.insert(child.clone(), upgrader);
+    }
+
+    /// Whether the asset is referenced, or was released less than a grace period ago
+    pub(crate) fn is_held(&self, key: &AssetKey) -> bool {
+        self.held.contains_key(key)
+    }
+
+    /// Drops the assets whose grace period has ended, and returns their keys
+    pub(crate) fn collect_unreferenced(&mut self) -> Vec<AssetKey> {
+        let grace_period = self.grace_period;
+        let expired = self
+            .held
+            .iter()
+            .filter(|(_, held)| held.unreferenced_since.map_or(false, |since| since.elapsed() >= grace_period))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in &expired {
+            if let Some(held) = self.held.remove(key) {
+                tracing::debug!("Releasing unreferenced asset {key:?}");
+                drop(held.strong);
+            }
+        }
+        expired
+    }
+
+    /// The number of referrers of an asset
+    pub fn referrer_count(&self, key: &str) -> usize {
+        self.held.get(&AssetKey::new(key)).map_or(0, |held| held.referrers.len())
+    }
+    /// The assets referenced by `referrer`
+    pub fn referenced_by(&self, referrer: &str) -> Vec<AssetKey> {
+        self.referrers.get(referrer).map(|keys| keys.iter().cloned().collect()).unwrap_or_default()
+    }
+    /// The assets loaded by an asset while it was loading
+    pub fn dependencies(&self, key: &str) -> Vec<AssetKey> {
+        self.dependencies.get(&AssetKey::new(key)).map(|keys| keys.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// The memory used by each alive asset, including what the assets it loaded use, sorted by GPU memory use, largest first
+    pub fn memory_report(&self, timeline: &AssetsTimeline) -> Vec<AssetMemory> {
+        let mut report = timeline
+            .assets
+            .iter()
+            .filter(|(_, asset)| asset.is_alive)
+            .map(|(key, asset)| {
+                let mut visited = HashSet::new();
+                let mut stack = vec![key.clone()];
+                let (mut total_cpu_size, mut total_gpu_size) = (0, 0);
+                while let Some(key) = stack.pop() {
+                    if !visited.insert(key.clone()) {
+                        continue;
+                    }
+                    if let Some(asset) = timeline.assets.get(&key).filter(|asset| asset.is_alive) {
+                        total_cpu_size += asset.cpu_size.unwrap_or_default();
+                        total_gpu_size += asset.gpu_size.unwrap_or_default();
+                    }
+                    stack.extend(self.dependencies.get(&key).into_iter().flatten().cloned());
+                }
+                AssetMemory {
+                    key: key.clone(),
+                    long_name: asset.long_name.clone(),
+                    referrers: self.held.get(key).map_or(0, |held| held.referrers.len()),
+                    cpu_size: asset.cpu_size.unwrap_or_default(),
+                    gpu_size: asset.gpu_size.unwrap_or_default(),
+                    total_cpu_size,
+                    total_gpu_size,
+                }
+            })
+            .collect::<Vec<_>>();
+        report.sort_by(|a, b| b.total_gpu_size.cmp(&a.total_gpu_size).then(b.total_cpu_size.cmp(&a.total_cpu_size)));
+        report
+    }
+}
+
+impl std::fmt::Debug for AssetGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetGraph").field("held", &self.held.len()).field("referrers", &self.referrers.len()).finish_non_exhaustive()
+    }
+}
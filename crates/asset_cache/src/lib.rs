@@ -1,4 +1,5 @@
 mod background;
+pub mod graph;
 
 use std::{
     any::Any,
@@ -20,6 +21,7 @@ use futures::{
     future::{pending, BoxFuture, Shared, WeakShared},
     Future, FutureExt,
 };
+use graph::AssetGraph;
 use parking_lot::Mutex;
 use pin_project::{pin_project, pinned_drop};
 use serde::{Deserialize, Serialize};
@@ -162,6 +164,8 @@ pub struct AssetCache {
     async_cache: Arc<Mutex<HashMap<AssetKey, AsyncAssetLoc>>>,
     sync: Arc<Mutex<HashMap<AssetKey, SyncAssetLoc>>>,
     pub timeline: Arc<Mutex<AssetsTimeline>>,
+    /// Which entities reference which assets; see [`AssetGraph`]
+    pub graph: Arc<Mutex<AssetGraph>>,
     runtime: RuntimeHandle,
     max_keepalive: Option<Duration>,
     /// stack is used for nested asset loading, to visualize for the timeline who loaded what
@@ -178,6 +182,7 @@ impl AssetCache {
             async_cache: Arc::new(Mutex::new(HashMap::new())),
             sync: Arc::new(Mutex::new(HashMap::new())),
             timeline: Arc::new(Mutex::new(AssetsTimeline::new())),
+            graph: Arc::new(Mutex::new(AssetGraph::new())),
             runtime: runtime.clone(),
             max_keepalive,
             stack: Vec::new(),
@@ -238,7 +243,23 @@ impl AssetCache {
         cache.insert(key.clone(), SyncAssetLoc { _key: key, content: Arc::new(Mutex::new(Some(Arc::new(asset) as Arc<dyn AssetHolder>))) });
     }
 
+    /// Keeps `asset` alive for as long as `referrer` (usually an entity id) references it; see [`AssetGraph`].
+    /// The asset's keepalive is stopped, so it's released a grace period after its last referrer releases it.
+    pub fn reference<T: Asset + Clone + Sync + Send + 'static>(
+        &self,
+        referrer: impl Into<String>,
+        key: &impl AsyncAssetKeyExt<T>,
+        asset: &T,
+    ) {
+        let key = AssetKey::new(key.key());
+        self.graph.lock().reference(referrer.into(), key.clone(), Arc::new(asset.clone()));
+        if let Some(loc) = self.async_cache.lock().get_mut(&key) {
+            loc.keepalive_task = None;
+        }
+    }
+
     fn clean_up_dropped(&self) {
+        self.graph.lock().collect_unreferenced();
         let mut async_ = self.async_cache.lock();
         for (key, asset) in &mut *async_ {
             let state = asset.state();
@@ -383,6 +404,22 @@ impl AssetCache {
             }
         };
 
+        {
+            let mut graph = self.graph.lock();
+            if let Some(parent) = self.stack.last() {
+                let weak = T::to_weak(&value);
+                graph.add_dependency(
+                    parent,
+                    &asset_key,
+                    Arc::new(move || T::from_weak(&weak).map(|value| Arc::new(value) as Arc<dyn AssetHolder>)),
+                );
+            }
+            // Referenced assets are kept alive by the graph instead
+            if graph.is_held(&asset_key) {
+                return value;
+            }
+        }
+
         let mut cache = self.async_cache.lock();
         let loc = cache.get_mut(&asset_key).expect("Asset loc was removed during loading");
 
@@ -877,6 +914,54 @@ mod test {
             assert_eq!(val, 3);
         }
     }
+
+    #[tokio::test]
+    async fn referenced_asset_released_after_grace_period() {
+        #[derive(Debug, Clone)]
+        struct MeshKey;
+        #[async_trait]
+        impl AsyncAssetKey<Arc<String>> for MeshKey {
+            async fn load(self, _assets: AssetCache) -> Arc<String> {
+                Arc::new("mesh".to_string())
+            }
+            fn keepalive(&self) -> AssetKeepalive {
+                AssetKeepalive::None
+            }
+        }
+        #[derive(Debug, Clone)]
+        struct ModelKey;
+        #[async_trait]
+        impl AsyncAssetKey<Arc<Arc<String>>> for ModelKey {
+            async fn load(self, assets: AssetCache) -> Arc<Arc<String>> {
+                Arc::new(MeshKey.get(&assets).await)
+            }
+            fn keepalive(&self) -> AssetKeepalive {
+                AssetKeepalive::None
+            }
+        }
+
+        let assets = AssetCache::new(runtime::Handle::current());
+        assets.graph.lock().set_grace_period(Duration::from_millis(10));
+
+        let model = ModelKey.get(&assets).await;
+        assets.reference("entity", &ModelKey, &model);
+        drop(model);
+
+        // The model and the mesh it loaded are both kept alive by the reference
+        assets.clean_up_dropped();
+        assert!(ModelKey.is_loaded(&assets).is_some());
+        assert!(MeshKey.is_loaded(&assets).is_some());
+        assert_eq!(assets.graph.lock().referrer_count(&MeshKey.key()), 1);
+
+        assets.graph.lock().release("entity");
+        assets.clean_up_dropped();
+        assert!(ModelKey.is_loaded(&assets).is_some());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assets.clean_up_dropped();
+        assert!(ModelKey.is_loaded(&assets).is_none());
+        assert!(MeshKey.is_loaded(&assets).is_none());
+    }
 }
 
 struct KeepaliveGuard {
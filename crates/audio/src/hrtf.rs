@@ -43,7 +43,7 @@ use crate::{
     barycentric::{Barycentric3, Triangle},
     dynamic_delay::DynamicDelay,
     streaming_source::StreamingSource,
-    Attenuation, Frame, SampleRate, Source, Uniform, SPEED_OF_SOUND,
+    AudioEmitter, Frame, SampleRate, Source, Uniform, SPEED_OF_SOUND,
 };
 
 #[derive(Copy, PartialEq, Eq, Debug, Clone, Index)]
@@ -529,7 +529,7 @@ impl HrtfContext {
     /// Construct a new HrtfContext from a source position in listener space.
     ///
     /// The offsets of the ears is given in listener local space
-    pub fn new(to_source: Vec3, ear_offsets: Vec3, attn: Attenuation, amplitude: f32) -> Self {
+    pub fn new(to_source: Vec3, ear_offsets: Vec3, emitter: &AudioEmitter) -> Self {
         let rel_left = to_source + ear_offsets;
         let rel_right = to_source - ear_offsets;
 
@@ -537,9 +537,15 @@ impl HrtfContext {
 
         // dbg!(rel_left, rel_right, dist);
 
-        let vol = vec2(attn.attenuate(dist.x), attn.attenuate(dist.y)) * amplitude;
+        let vol = vec2(emitter.gain(dist.x), emitter.gain(dist.y));
 
-        let delay = dist / SPEED_OF_SOUND;
+        // The delay changing with the distance is what shifts the pitch, so without doppler only
+        // the difference between the ears is kept
+        let delay = if emitter.doppler {
+            dist / SPEED_OF_SOUND
+        } else {
+            (dist - dist.min_element()) / SPEED_OF_SOUND
+        };
 
         Self {
             to_source,
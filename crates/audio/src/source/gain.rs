@@ -1,6 +1,6 @@
 use std::fmt::Debug;
 
-use crate::Source;
+use crate::{value::Value, Source};
 
 #[derive(Debug, Clone)]
 pub struct Gain<S> {
@@ -30,3 +30,47 @@ where
         self.source.sample_count()
     }
 }
+
+/// A gain which can change while the source plays, e.g. to fade it in and out. Changes are
+/// smoothed over a few milliseconds to avoid clicks
+#[derive(Debug, Clone)]
+pub struct DynamicGain<S, V> {
+    source: S,
+    gain: V,
+    current: f32,
+}
+
+impl<S, V> DynamicGain<S, V>
+where
+    S: Source,
+    V: for<'x> Value<'x, Item = f32>,
+{
+    pub fn new(source: S, gain: V) -> Self {
+        let current = *gain.get();
+        Self {
+            source,
+            gain,
+            current,
+        }
+    }
+}
+
+impl<S, V> Source for DynamicGain<S, V>
+where
+    S: Source,
+    V: for<'x> Value<'x, Item = f32>,
+{
+    fn next_sample(&mut self) -> Option<crate::Frame> {
+        let target = *self.gain.get();
+        self.current += (target - self.current) * 0.005;
+        Some(self.source.next_sample()? * self.current)
+    }
+
+    fn sample_rate(&self) -> crate::SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        self.source.sample_count()
+    }
+}
@@ -153,6 +153,14 @@ pub trait Source: Send {
         Gain::new(self, gain)
     }
 
+    fn dynamic_gain<V>(self, gain: V) -> DynamicGain<Self, V>
+    where
+        Self: Sized,
+        V: for<'x> Value<'x, Item = f32>,
+    {
+        DynamicGain::new(self, gain)
+    }
+
     fn spatial<L, P>(self, hrtf_lib: &HrtfLib, listener: L, params: P) -> Spatial<Self, L, P>
     where
         Self: Sized,
@@ -83,12 +83,7 @@ where
 
         let to_source = (rel).clamp_length_max(MAX_SPEED) + prev_to_source;
 
-        HrtfContext::new(
-            to_source,
-            listener.ear_distance / 2.0,
-            emitter.attenuation,
-            emitter.amplitude,
-        )
+        HrtfContext::new(to_source, listener.ear_distance / 2.0, emitter)
     }
 }
 
@@ -23,6 +23,13 @@ pub struct AudioEmitter {
     pub amplitude: f32,
     pub pos: Vec3,
     pub attenuation: Attenuation,
+    /// The distance within which the emitter is heard at full volume. The attenuation is applied
+    /// to the distance beyond it.
+    pub min_distance: f32,
+    /// The distance beyond which the emitter can't be heard
+    pub max_distance: f32,
+    /// Whether the pitch changes as the emitter moves towards or away from the listener
+    pub doppler: bool,
 }
 
 impl Default for AudioEmitter {
@@ -31,10 +38,26 @@ impl Default for AudioEmitter {
             amplitude: 1.0,
             pos: Default::default(),
             attenuation: Default::default(),
+            min_distance: 0.0,
+            max_distance: f32::INFINITY,
+            doppler: true,
         }
     }
 }
 
+impl AudioEmitter {
+    /// The volume of the emitter heard at `dist`
+    pub fn gain(&self, dist: f32) -> f32 {
+        if dist >= self.max_distance {
+            return 0.0;
+        }
+
+        self.attenuation
+            .attenuate((dist - self.min_distance).max(0.0))
+            * self.amplitude
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct AudioListener {
     /// The position of the right ear
@@ -75,6 +98,9 @@ pub enum Attenuation {
     /// e^(-ax)
     #[editor(slider, min = -10.0, max = 10.0)]
     Exponential(f32),
+    /// 1 - ax
+    #[editor(slider, min = 0.0, max = 1.0)]
+    Linear(f32),
     /// e^(-(density*x)^gradient)
     #[editor(slider, min = -10.0, max = 10.0)]
     Smoothstep { density: f32, gradient: f32 },
@@ -103,6 +129,7 @@ impl Attenuation {
         match *self {
             Attenuation::InverseQuadratic(a) => 1.0 / (a * dist * dist),
             Attenuation::Exponential(a) => E.powf(-a * dist),
+            Attenuation::Linear(a) => 1.0 - a * dist,
             Attenuation::Smoothstep { density, gradient } => {
                 E.powf(-(density * dist).powf(gradient))
             }
@@ -119,6 +146,7 @@ impl Attenuation {
         let x = match self {
             Self::InverseQuadratic(a) => (1.0 / (amp * a)).sqrt(),
             Self::Exponential(a) => -amp.ln() / a,
+            Self::Linear(a) => (1.0 - amp) / a,
             Self::Smoothstep { density, gradient } => (-amp.ln()).powf(1.0 / gradient) / density,
             Self::InversePoly {
                 quad,
@@ -137,3 +165,46 @@ impl Attenuation {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linear_attenuation() {
+        let attenuation = Attenuation::Linear(0.25);
+        assert_eq!(attenuation.attenuate(0.0), 1.0);
+        assert_eq!(attenuation.attenuate(2.0), 0.5);
+        assert_eq!(attenuation.attenuate(4.0), 0.0);
+        // Clamped to 0..=1 outside of the line
+        assert_eq!(attenuation.attenuate(8.0), 0.0);
+        assert_eq!(attenuation.attenuate(-4.0), 1.0);
+        assert_eq!(attenuation.inverse(0.5), 2.0);
+    }
+
+    #[test]
+    fn emitter_gain_distances() {
+        let emitter = AudioEmitter {
+            amplitude: 0.5,
+            attenuation: Attenuation::Linear(0.25),
+            min_distance: 2.0,
+            max_distance: 5.0,
+            ..Default::default()
+        };
+        // Full volume within the min distance
+        assert_eq!(emitter.gain(0.0), 0.5);
+        assert_eq!(emitter.gain(2.0), 0.5);
+        // The attenuation starts at the min distance
+        assert_eq!(emitter.gain(4.0), 0.25);
+        // Silent from the max distance, even if the attenuation hasn't reached 0 yet
+        assert_eq!(emitter.gain(5.0), 0.0);
+        assert_eq!(emitter.gain(100.0), 0.0);
+
+        let unbounded = AudioEmitter {
+            attenuation: Attenuation::Linear(0.25),
+            ..Default::default()
+        };
+        assert_eq!(unbounded.gain(2.0), 0.5);
+        assert_eq!(unbounded.gain(1000.0), 0.0);
+    }
+}
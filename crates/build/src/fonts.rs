@@ -0,0 +1,238 @@
+//! Copies the fonts declared in the manifest to the build, subsetting them to their unicode ranges.
+//!
+//! Subsetting keeps the glyph ids of the font, so that its other tables don't have to be rewritten; the outlines of the
+//! glyphs which aren't in the ranges are removed, which is where the size of a font goes.
+use std::{collections::HashMap, ops::RangeInclusive, path::Path};
+
+use ambient_project::{Font, Identifier};
+use anyhow::Context;
+
+pub async fn build_fonts(assets_path: &Path, build_path: &Path, fonts: &HashMap<Identifier, Font>) {
+    for (id, font) in fonts {
+        if let Err(err) = build_font(assets_path, build_path, font).await {
+            log::error!("Failed to build the font {id}: {err:?}");
+        }
+    }
+}
+
+async fn build_font(assets_path: &Path, build_path: &Path, font: &Font) -> anyhow::Result<()> {
+    let data = tokio::fs::read(assets_path.join(&font.path)).await.with_context(|| format!("Failed to read {:?}", font.path))?;
+    let data = if font.ranges.is_empty() {
+        data
+    } else {
+        let ranges = font.ranges.iter().map(|range| range.0.clone()).collect::<Vec<_>>();
+        match subset_font(&data, &ranges) {
+            Ok(subset) => {
+                log::info!("Subset {:?} from {} to {} bytes", font.path, data.len(), subset.len());
+                subset
+            }
+            Err(err) => {
+                log::warn!("Failed to subset {:?}, so all of its glyphs are kept: {err:?}", font.path);
+                data
+            }
+        }
+    };
+    let out_path = build_path.join("assets").join(&font.path);
+    if let Some(parent) = out_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&out_path, data).await?;
+    Ok(())
+}
+
+/// Removes the outlines of the glyphs of `font` which aren't used by the code points in `ranges`. Only fonts with
+/// TrueType outlines are supported.
+pub fn subset_font(font: &[u8], ranges: &[RangeInclusive<u32>]) -> anyhow::Result<Vec<u8>> {
+    let sfnt_version = read_u32(font, 0)?;
+    if sfnt_version == u32::from_be_bytes(*b"ttcf") {
+        anyhow::bail!("Font collections can't be subset");
+    }
+    let tables = parse_tables(font)?;
+    let table = |tag: &[u8; 4]| tables.iter().find(|(t, _)| t == tag).map(|(_, data)| *data);
+    let (Some(head), Some(maxp), Some(loca), Some(glyf)) = (table(b"head"), table(b"maxp"), table(b"loca"), table(b"glyf")) else {
+        anyhow::bail!("Only fonts with TrueType outlines can be subset");
+    };
+
+    let num_glyphs = read_u16(maxp, 4)? as usize;
+    let long_offsets = read_u16(head, 50)? != 0;
+    let offsets = (0..=num_glyphs)
+        .map(|i| {
+            if long_offsets {
+                read_u32(loca, i * 4).map(|offset| offset as usize)
+            } else {
+                read_u16(loca, i * 2).map(|offset| offset as usize * 2)
+            }
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let glyph = |id: usize| glyf.get(offsets[id]..offsets[id + 1]).context("A glyph of the font is out of bounds");
+
+    let face = ttf_parser::Face::parse(font, 0)?;
+    // The .notdef glyph is always kept, and so are the glyphs that the kept composite glyphs are made of
+    let mut pending = vec![0];
+    pending.extend(
+        ranges.iter().flat_map(|range| range.clone()).filter_map(char::from_u32).filter_map(|c| face.glyph_index(c)).map(|id| id.0),
+    );
+    let mut keep = vec![false; num_glyphs];
+    while let Some(id) = pending.pop() {
+        let id = id as usize;
+        if id >= num_glyphs || keep[id] {
+            continue;
+        }
+        keep[id] = true;
+        pending.extend(glyph_components(glyph(id)?)?);
+    }
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((num_glyphs + 1) * 4);
+    for (id, &keep) in keep.iter().enumerate() {
+        new_loca.extend((new_glyf.len() as u32).to_be_bytes());
+        if keep {
+            new_glyf.extend_from_slice(glyph(id)?);
+            pad(&mut new_glyf);
+        }
+    }
+    new_loca.extend((new_glyf.len() as u32).to_be_bytes());
+    let mut new_head = head.to_vec();
+    // The checksum adjustment is set once the font is written, and the new loca has long offsets
+    new_head[8..12].fill(0);
+    new_head[50..52].copy_from_slice(&1u16.to_be_bytes());
+
+    let tables = tables
+        .iter()
+        // The signature wouldn't match anymore
+        .filter(|(tag, _)| tag != b"DSIG")
+        .map(|(tag, data)| {
+            let data = match tag {
+                b"head" => new_head.clone(),
+                b"loca" => new_loca.clone(),
+                b"glyf" => new_glyf.clone(),
+                _ => data.to_vec(),
+            };
+            (*tag, data)
+        })
+        .collect();
+    Ok(write_font(sfnt_version, tables))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> anyhow::Result<u16> {
+    let bytes = data.get(offset..offset + 2).context("Unexpected end of font")?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> anyhow::Result<u32> {
+    let bytes = data.get(offset..offset + 4).context("Unexpected end of font")?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn parse_tables(font: &[u8]) -> anyhow::Result<Vec<([u8; 4], &[u8])>> {
+    let num_tables = read_u16(font, 4)? as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = 12 + i * 16;
+            let tag: [u8; 4] = font.get(record..record + 4).context("Unexpected end of font")?.try_into()?;
+            let offset = read_u32(font, record + 8)? as usize;
+            let length = read_u32(font, record + 12)? as usize;
+            let data = font.get(offset..offset + length).context("A table of the font is out of bounds")?;
+            Ok((tag, data))
+        })
+        .collect()
+}
+
+/// The glyphs that a composite glyph is made of; none if it's a simple glyph
+fn glyph_components(glyph: &[u8]) -> anyhow::Result<Vec<u16>> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+    if glyph.is_empty() || read_u16(glyph, 0)? as i16 >= 0 {
+        return Ok(Vec::new());
+    }
+    let mut components = Vec::new();
+    // After the number of contours and the bounding box
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)?);
+        offset += 4 + if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        offset += if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
+
+/// Tables are aligned to four bytes
+fn pad(data: &mut Vec<u8>) {
+    data.resize((data.len() + 3) & !3, 0);
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn write_font(sfnt_version: u32, mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.leading_zeros() as u16;
+    let search_range = (1 << entry_selector) * 16;
+
+    let mut font = Vec::new();
+    font.extend(sfnt_version.to_be_bytes());
+    font.extend(num_tables.to_be_bytes());
+    font.extend(search_range.to_be_bytes());
+    font.extend(entry_selector.to_be_bytes());
+    font.extend((num_tables * 16 - search_range).to_be_bytes());
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend(tag);
+        font.extend(checksum(data).to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in &tables {
+        font.extend(data);
+        pad(&mut font);
+    }
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsets_to_ranges() {
+        let font = include_bytes!("../../../assets/fonts/Ubuntu/Ubuntu Nerd Font Complete.ttf");
+        let subset = subset_font(font, &[0x20..=0x7e]).unwrap();
+        assert!(subset.len() < font.len() / 2);
+
+        let face = ttf_parser::Face::parse(&subset, 0).unwrap();
+        let bounds = |c: char| face.glyph_bounding_box(face.glyph_index(c).unwrap());
+        assert!(bounds('A').is_some());
+        assert!(bounds('é').is_none());
+        assert_eq!(checksum(&subset), 0xB1B0AFBA);
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use ambient_std::{asset_url::AbsAssetUrl, download_asset::BuildMetadata};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+/// Bumped whenever the output of the pipelines changes for the same input, so that everything gets rebuilt
+const BUILD_CACHE_VERSION: u32 = 1;
+
+/// What each `pipeline.json` was built from and what it output the last time the assets were built.
+/// Stored next to the built assets, so that only the pipelines with changed inputs are processed again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildCache {
+    version: u32,
+    ambient_version: String,
+    /// By the path of the `pipeline.json`, relative to the assets directory
+    pipelines: HashMap<String, PipelineBuild>,
+}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PipelineBuild {
+    /// The hash of the inputs; `None` if the last build of the pipeline failed
+    hash: Option<String>,
+    /// The files that were output, relative to the output directory
+    outputs: Vec<String>,
+}
+impl BuildCache {
+    pub fn path(build_path: &Path) -> PathBuf {
+        build_path.join("build_cache.json")
+    }
+    /// Loads the cache, or returns an empty one if there isn't one or it's from another version of Ambient
+    pub fn load(build_path: &Path) -> Self {
+        let cache = std::fs::read(Self::path(build_path)).ok().and_then(|data| serde_json::from_slice::<Self>(&data).ok());
+        match cache {
+            Some(cache) if cache.version == BUILD_CACHE_VERSION && cache.ambient_version == env!("CARGO_PKG_VERSION") => cache,
+            _ => Self::default(),
+        }
+    }
+    pub fn save(&self, build_path: &Path) -> anyhow::Result<()> {
+        let cache = Self {
+            version: BUILD_CACHE_VERSION,
+            ambient_version: env!("CARGO_PKG_VERSION").to_string(),
+            pipelines: self.pipelines.clone(),
+        };
+        std::fs::write(Self::path(build_path), serde_json::to_vec_pretty(&cache)?)?;
+        Ok(())
+    }
+
+    /// Whether `pipeline` can be skipped: its inputs hash to `hash`, like they did last time, and its outputs are all still there
+    pub fn is_up_to_date(&self, pipeline: &str, hash: &str, out_path: &Path) -> bool {
+        self.pipelines
+            .get(pipeline)
+            .map_or(false, |build| build.hash.as_deref() == Some(hash) && build.outputs.iter().all(|output| out_path.join(output).exists()))
+    }
+
+    /// Records the outputs of the pipelines that were processed, and removes the outputs that are no longer produced
+    /// by any pipeline (including those of pipelines that were deleted). `processed` maps the processed pipelines to the hashes
+    /// of their inputs, or to `None` if they failed.
+    pub fn update(&mut self, out_path: &Path, pipelines: &[String], processed: HashMap<String, Option<String>>, outputs: HashSet<String>) {
+        let mut new_outputs: HashMap<String, Vec<String>> = HashMap::new();
+        for output in outputs {
+            if let Some(pipeline) = owning_pipeline(pipelines, &output) {
+                new_outputs.entry(pipeline.to_string()).or_default().push(output);
+            }
+        }
+
+        let mut orphans = Vec::new();
+        self.pipelines.retain(|pipeline, build| {
+            let keep = pipelines.contains(pipeline);
+            if !keep {
+                orphans.extend(build.outputs.drain(..));
+            }
+            keep
+        });
+        for (pipeline, hash) in processed {
+            let mut outputs = new_outputs.remove(&pipeline).unwrap_or_default();
+            outputs.sort();
+            let old = self.pipelines.insert(pipeline, PipelineBuild { hash, outputs: outputs.clone() });
+            if let Some(old) = old {
+                orphans.extend(old.outputs.into_iter().filter(|output| !outputs.contains(output)));
+            }
+        }
+
+        for orphan in orphans {
+            let path = out_path.join(&orphan);
+            if path.exists() {
+                log::info!("Removing orphaned asset {orphan}");
+                if let Err(err) = std::fs::remove_file(&path) {
+                    log::warn!("Failed to remove {path:?}: {err}");
+                }
+            }
+        }
+    }
+}
+
+/// The directory of a `pipeline.json`, relative to the assets directory, with a trailing slash unless it's the root
+fn pipeline_directory(pipeline: &str) -> &str {
+    pipeline.rsplit_once('/').map(|(dir, _)| &pipeline[..dir.len() + 1]).unwrap_or("")
+}
+
+/// The pipeline an output belongs to: the one in the deepest directory containing it, as that's where pipelines write their outputs
+fn owning_pipeline<'a>(pipelines: &'a [String], output: &str) -> Option<&'a str> {
+    pipelines
+        .iter()
+        .map(|x| x.as_str())
+        .filter(|pipeline| output.starts_with(pipeline_directory(pipeline)))
+        .max_by_key(|pipeline| pipeline_directory(pipeline).len())
+}
+
+/// Hashes the `pipeline.json` at `pipeline` and all the files in its directory, which are the inputs the pipelines in it can use
+pub async fn hash_pipeline_inputs(files: &[AbsAssetUrl], in_root: &AbsAssetUrl, pipeline: &AbsAssetUrl) -> anyhow::Result<String> {
+    let directory = pipeline.join(".")?;
+    let mut inputs = files.iter().filter(|file| file.path().starts_with(directory.path())).collect::<Vec<_>>();
+    inputs.sort_by_key(|file| file.path().to_string());
+
+    let mut hasher = Sha256::new();
+    for input in inputs {
+        let path = input.to_file_path()?.ok_or_else(|| anyhow::anyhow!("{input} is not a local file"))?;
+        hasher.update(in_root.relative_path(input.path()).as_str().as_bytes());
+        hasher.update([0]);
+        hasher.update(tokio::fs::read(&path).await?);
+        hasher.update([0]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Writes the [BuildMetadata] for the files in `build_path`, which clients use to verify the assets they download, and to find
+/// the `mirrors` they can be downloaded from. The hashes of the files that haven't been modified since the metadata was last written are reused.
+pub fn write_build_metadata(build_path: &Path, mirrors: &[String]) -> anyhow::Result<()> {
+    let metadata_path = build_path.join(BuildMetadata::FILE_NAME);
+    let previous_time = std::fs::metadata(&metadata_path).and_then(|x| x.modified()).ok();
+    let previous = std::fs::read(&metadata_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<BuildMetadata>(&data).ok())
+        .filter(|_| previous_time.is_some())
+        .unwrap_or_default();
+
+    let mirrors = mirrors
+        .iter()
+        .map(|mirror| AbsAssetUrl::parse(mirror).with_context(|| format!("Invalid mirror url: {mirror}")))
+        .collect::<anyhow::Result<_>>()?;
+    let mut metadata = BuildMetadata { mirrors, ..Default::default() };
+    for entry in WalkDir::new(build_path.join("assets")).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let path = entry.path().strip_prefix(build_path)?.to_string_lossy().replace('\\', "/");
+        let modified = entry.metadata()?.modified()?;
+        let hash = match previous.asset_hashes.get(&path) {
+            Some(hash) if previous_time.map_or(false, |time| modified <= time) => hash.clone(),
+            _ => hex::encode(Sha256::digest(std::fs::read(entry.path())?)),
+        };
+        metadata.asset_hashes.insert(path, hash);
+    }
+    std::fs::write(metadata_path, serde_json::to_vec_pretty(&metadata)?)?;
+    Ok(())
+}
@@ -1,6 +1,10 @@
 use std::{
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use ambient_asset_cache::{AssetCache, SyncAssetKeyExt};
@@ -8,10 +12,14 @@ use ambient_physics::physx::{Physics, PhysicsKey};
 use ambient_project::Manifest as ProjectManifest;
 use ambient_std::asset_url::AbsAssetUrl;
 use futures::FutureExt;
+use incremental::BuildCache;
 use itertools::Itertools;
+use parking_lot::Mutex;
 use pipelines::{FileCollection, ProcessCtx, ProcessCtxKey};
 use walkdir::WalkDir;
 
+pub mod fonts;
+pub mod incremental;
 pub mod pipelines;
 
 /// This takes the path to an Ambient project and builds it. An Ambient project is expected to
@@ -21,7 +29,9 @@ pub mod pipelines;
 /// src/**  This is where you store Rust source files
 /// build  This is the output directory, and is created when building
 /// ambient.toml  This is a metadata file to describe the project
-pub async fn build(physics: Physics, _assets: &AssetCache, path: PathBuf, manifest: &ProjectManifest, optimize: bool) {
+///
+/// Only the pipelines whose inputs have changed since the last build are processed, unless `force` is set.
+pub async fn build(physics: Physics, _assets: &AssetCache, path: PathBuf, manifest: &ProjectManifest, optimize: bool, force: bool) {
     log::info!(
         "Building project `{}` ({})",
         manifest.project.id,
@@ -34,30 +44,63 @@ pub async fn build(physics: Physics, _assets: &AssetCache, path: PathBuf, manife
     let assets_path = path.join("assets");
 
     std::fs::create_dir_all(&build_path).unwrap();
-    build_assets(physics, &assets_path, &build_path).await;
+    build_assets(physics, &assets_path, &build_path, force).await;
+    fonts::build_fonts(&assets_path, &build_path, &manifest.fonts).await;
+    if let Err(err) = incremental::write_build_metadata(&build_path, &manifest.build.mirrors) {
+        log::warn!("Failed to write the build metadata: {err:?}");
+    }
     build_rust_if_available(&path, manifest, &build_path, optimize).await.unwrap();
 }
 
-async fn build_assets(physics: Physics, assets_path: &Path, build_path: &Path) {
+async fn build_assets(physics: Physics, assets_path: &Path, build_path: &Path, force: bool) {
     let files = WalkDir::new(assets_path)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.metadata().map(|x| x.is_file()).unwrap_or(false))
         .map(|x| AbsAssetUrl::from_file_path(x.into_path()))
         .collect_vec();
+    let in_root = AbsAssetUrl::from_directory_path(assets_path);
+    let out_path = build_path.join("assets");
+
+    let mut cache = BuildCache::load(build_path);
+    let mut pipelines = Vec::new();
+    let mut changed = HashMap::new();
+    for file in files.iter().filter(|file| file.path().ends_with("pipeline.json")) {
+        let pipeline = in_root.relative_path(file.path()).to_string();
+        let hash = match incremental::hash_pipeline_inputs(&files, &in_root, file).await {
+            Ok(hash) => Some(hash),
+            Err(err) => {
+                log::warn!("Failed to hash the inputs of {pipeline}: {err:?}");
+                None
+            }
+        };
+        let up_to_date = !force && hash.as_ref().map_or(false, |hash| cache.is_up_to_date(&pipeline, hash, &out_path));
+        if up_to_date {
+            log::info!("Skipping {pipeline}, as it hasn't changed since the last build");
+        } else {
+            changed.insert(file.clone(), (pipeline.clone(), hash));
+        }
+        pipelines.push(pipeline);
+    }
+
+    let outputs = Arc::new(Mutex::new(HashSet::new()));
+    let errors = Arc::new(AtomicUsize::new(0));
     let assets = AssetCache::new_with_config(tokio::runtime::Handle::current(), None);
     PhysicsKey.insert(&assets, physics);
     let ctx = ProcessCtx {
         assets: assets.clone(),
         files: FileCollection(Arc::new(files)),
-        in_root: AbsAssetUrl::from_directory_path(assets_path),
-        out_root: AbsAssetUrl::from_directory_path(build_path.join("assets")),
+        pipeline_files: Some(changed.keys().cloned().collect()),
+        in_root,
+        out_root: AbsAssetUrl::from_directory_path(&out_path),
         input_file_filter: None,
         package_name: "".to_string(),
         write_file: Arc::new({
-            let build_path = build_path.to_owned();
+            let out_path = out_path.clone();
+            let outputs = outputs.clone();
             move |path, contents| {
-                let path = build_path.join("assets").join(path);
+                outputs.lock().insert(path.clone());
+                let path = out_path.join(path);
                 async move {
                     std::fs::create_dir_all(path.parent().unwrap()).unwrap();
                     tokio::fs::write(&path, contents).await.unwrap();
@@ -70,13 +113,27 @@ async fn build_assets(physics: Physics, assets_path: &Path, build_path: &Path) {
             log::info!("{}", msg);
             async {}.boxed()
         }),
-        on_error: Arc::new(|err| {
-            log::error!("{:?}", err);
-            async {}.boxed()
+        on_error: Arc::new({
+            let errors = errors.clone();
+            move |err| {
+                log::error!("{:?}", err);
+                errors.fetch_add(1, Ordering::SeqCst);
+                async {}.boxed()
+            }
         }),
     };
     ProcessCtxKey.insert(&ctx.assets, ctx.clone());
     pipelines::process_pipelines(&ctx).await;
+
+    // Errors can't be traced back to the pipeline that caused them, so if there were any, all the processed pipelines are
+    // processed again next time
+    let failed = errors.load(Ordering::SeqCst) > 0;
+    let processed = changed.into_values().map(|(pipeline, hash)| (pipeline, if failed { None } else { hash })).collect();
+    let outputs = std::mem::take(&mut *outputs.lock());
+    cache.update(&out_path, &pipelines, processed, outputs);
+    if let Err(err) = cache.save(build_path) {
+        log::warn!("Failed to save the build cache: {err:?}");
+    }
 }
 
 async fn build_rust_if_available(project_path: &Path, manifest: &ProjectManifest, build_path: &Path, optimize: bool) -> anyhow::Result<()> {
@@ -101,7 +158,9 @@ async fn build_rust_if_available(project_path: &Path, manifest: &ProjectManifest
     let rustc = ambient_rustc::Rust::get_system_installation().await?;
 
     for feature in &manifest.build.rust.feature_multibuild {
-        let Some(wasm_bytecode) = rustc.build(project_path, manifest.project.id.as_ref(), optimize, &[feature])? else { continue; };
+        let Some(wasm_bytecode) = rustc.build(project_path, manifest.project.id.as_ref(), optimize, &[feature])? else {
+            continue;
+        };
         let component_bytecode = ambient_wasm::shared::build::componentize(&wasm_bytecode)?;
 
         let output_path = build_path.join(feature);
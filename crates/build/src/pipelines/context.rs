@@ -41,9 +41,13 @@ impl PipelineCtx {
         }
     }
 
-    pub async fn write_model_crate(&self, model_crate: &ModelCrate, path: &RelativePath) -> TypedAssetUrl<ModelCrateAssetType> {
-        join_all(model_crate.to_items().iter().map(|item| self.write_file(path.join(&item.path), (*item.data).clone()))).await;
-        self.out_root().push(path).unwrap().as_directory().into()
+    pub async fn write_model_crate(
+        &self,
+        model_crate: &ModelCrate,
+        path: &RelativePath,
+    ) -> anyhow::Result<TypedAssetUrl<ModelCrateAssetType>> {
+        join_all(model_crate.to_items()?.iter().map(|item| self.write_file(path.join(&item.path), (*item.data).clone()))).await;
+        Ok(self.out_root().push(path).unwrap().as_directory().into())
     }
     pub async fn write_file(&self, path: impl AsRef<str>, content: Vec<u8>) -> AbsAssetUrl {
         (self.process_ctx.write_file)(self.root_path.join(path.as_ref()).to_string(), content).await
@@ -24,6 +24,7 @@ use serde::{Deserialize, Serialize};
 use super::{
     context::PipelineCtx,
     out_asset::{asset_id_from_url, OutAsset, OutAssetContent, OutAssetPreview},
+    texture_compression::TextureCompression,
     ProcessCtxKey,
 };
 use crate::pipelines::download_image;
@@ -91,7 +92,8 @@ pub async fn pipeline(ctx: &PipelineCtx, config: MaterialsPipeline) -> Vec<OutAs
                         .with(collider(), ambient_physics::collider::ColliderDef::Box { size: Vec3::ONE, center: Vec3::ZERO })
                         .with(collider_type(), ambient_physics::collider::ColliderType::Picking),
                 );
-                let model_url = ctx.write_model_crate(&model_crate, &model_path).await;
+                // The decal crate only holds a prefab, which always serializes
+                let model_url = ctx.write_model_crate(&model_crate, &model_path).await.expect("Failed to write the decal");
                 res.push(OutAsset {
                     id: asset_id_from_url(&out_model_url),
                     type_: AssetType::Prefab,
@@ -126,6 +128,8 @@ pub struct PipelinePbrMaterial {
     pub normalmap: Option<AssetUrl>,
     /// The metallic roughness map of this material.
     pub metallic_roughness: Option<AssetUrl>,
+    /// The emissive map of this material. It is multiplied by the emissive factor, which defaults to white when the map is specified.
+    pub emissive: Option<AssetUrl>,
 
     /// The color that this material should be multiplied by. Defaults to white for PBR.
     pub base_color_factor: Option<Vec4>,
@@ -193,6 +197,7 @@ impl PipelinePbrMaterial {
             } else {
                 None
             },
+            emissive: pipe_image(&self.emissive).await?,
 
             base_color_factor: self.base_color_factor,
             emissive_factor: self.emissive_factor,
@@ -245,13 +250,14 @@ pub struct PipeImage {
     second_source: Option<AbsAssetUrl>,
     transform: Option<Box<dyn ImageTransformer>>,
     cap_texture_sizes: Option<ModelTextureSize>,
+    compression: Option<TextureCompression>,
 }
 impl PipeImage {
     pub fn resolve(ctx: &PipelineCtx, source: AbsAssetUrl) -> Self {
-        Self::new(ctx.get_downloadable_url(&source).unwrap().clone())
+        Self::new(ctx.get_downloadable_url(&source).unwrap().clone()).compression(ctx.pipeline.texture_compression)
     }
     pub fn new(source: AbsAssetUrl) -> Self {
-        PipeImage { source, second_source: None, transform: None, cap_texture_sizes: None }
+        PipeImage { source, second_source: None, transform: None, cap_texture_sizes: None, compression: None }
     }
     pub fn transform<F: Fn(&mut RgbaImage, Option<&RgbaImage>) + Sync + Send + 'static>(
         mut self,
@@ -265,6 +271,10 @@ impl PipeImage {
         self.cap_texture_sizes = cap_texture_sizes;
         self
     }
+    pub fn compression(mut self, compression: Option<TextureCompression>) -> Self {
+        self.compression = compression;
+        self
+    }
 }
 #[async_trait]
 impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
@@ -275,7 +285,8 @@ impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
             .await
             .with_context(|| format!("Failed to download image {}", self.source))?)
         .clone();
-        let mut extension = "png".to_string();
+        let format_extension = if self.compression.is_some() { "ktx2" } else { "png" };
+        let mut extension = format_extension.to_string();
         let second_image = if let Some(second_source) = &self.second_source {
             Some(
                 ImageFromUrl { url: second_source.clone() }
@@ -287,18 +298,24 @@ impl AsyncAssetKey<AssetResult<Arc<AbsAssetUrl>>> for PipeImage {
             None
         };
         let path = ctx.in_root.relative_path(self.source.path());
-        let mut data = Cursor::new(Vec::new());
-        tokio::task::block_in_place(|| {
+        let data = tokio::task::block_in_place(|| -> anyhow::Result<Vec<u8>> {
             if let Some(transform) = &self.transform {
                 transform.transform(&mut image, second_image.as_deref());
-                extension = format!("{}.png", transform.name());
+                extension = format!("{}.{format_extension}", transform.name());
             }
             if let Some(size) = self.cap_texture_sizes {
                 cap_texture_size(&mut image, size.size());
             }
-            image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
-        });
-        Ok(Arc::new((ctx.write_file)(path.with_extension(extension).to_string(), data.into_inner()).await))
+            match &self.compression {
+                Some(compression) => compression.compress(&image).with_context(|| format!("Failed to compress image {}", self.source)),
+                None => {
+                    let mut data = Cursor::new(Vec::new());
+                    image.write_to(&mut data, ImageOutputFormat::Png).unwrap();
+                    Ok(data.into_inner())
+                }
+            }
+        })?;
+        Ok(Arc::new((ctx.write_file)(path.with_extension(extension).to_string(), data).await))
     }
 }
 
@@ -1,6 +1,7 @@
 use std::{collections::HashSet, sync::Arc};
 
 use ambient_asset_cache::SyncAssetKey;
+use ambient_gpu::ktx2::{self, Ktx2Texture};
 use ambient_std::{asset_cache::AssetCache, asset_url::AbsAssetUrl};
 use anyhow::Context;
 use context::PipelineCtx;
@@ -9,13 +10,15 @@ use image::ImageFormat;
 use out_asset::{OutAsset, OutAssetContent, OutAssetPreview};
 use serde::{Deserialize, Serialize};
 
-use self::{materials::MaterialsPipeline, models::ModelsPipeline};
+use self::{materials::MaterialsPipeline, models::ModelsPipeline, texture_compression::TextureCompression};
 
 pub mod audio;
 pub mod context;
 pub mod materials;
 pub mod models;
 pub mod out_asset;
+pub mod streaming;
+pub mod texture_compression;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -29,6 +32,9 @@ pub enum PipelineConfig {
     /// The audio asset pipeline.
     /// Will import supported audio file formats and produce Ogg Vorbis files to be used by the runtime.
     Audio,
+    /// The world streaming pipeline.
+    /// Will split the entities of `.world.json` files by their `streaming_cell` into cells, which are loaded around players.
+    Streaming,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +53,10 @@ pub struct Pipeline {
     /// Categories to apply to the output resources.
     #[serde(default)]
     pub categories: Vec<Vec<String>>,
+    /// If specified, the textures output by this pipeline are transcoded to BasisU textures in KTX2 containers.
+    /// These are much smaller to download and to keep in GPU memory, and are transcoded to a format the GPU supports when loaded.
+    #[serde(default)]
+    pub texture_compression: Option<TextureCompression>,
 }
 impl Pipeline {
     pub async fn process(&self, ctx: PipelineCtx) -> Vec<OutAsset> {
@@ -54,6 +64,7 @@ impl Pipeline {
             PipelineConfig::Models(config) => models::pipeline(&ctx, config.clone()).await,
             PipelineConfig::Materials(config) => materials::pipeline(&ctx, config.clone()).await,
             PipelineConfig::Audio => audio::pipeline(&ctx).await,
+            PipelineConfig::Streaming => streaming::pipeline(&ctx).await,
         };
         for asset in &mut assets {
             asset.tags.extend(self.tags.clone());
@@ -87,7 +98,8 @@ pub async fn process_pipelines(ctx: &ProcessCtx) -> Vec<OutAsset> {
 
     futures::stream::iter(ctx.files.0.iter())
         .filter_map(|file| async move {
-            let pipelines: PipelineOneOrMany = if file.0.path().ends_with("pipeline.json") {
+            let should_process = ctx.pipeline_files.as_ref().map_or(true, |pipeline_files| pipeline_files.contains(file));
+            let pipelines: PipelineOneOrMany = if file.0.path().ends_with("pipeline.json") && should_process {
                 file.download_json(&ctx.assets).await.unwrap()
             } else {
                 return None;
@@ -127,6 +139,8 @@ impl SyncAssetKey<ProcessCtx> for ProcessCtxKey {}
 pub struct ProcessCtx {
     pub assets: AssetCache,
     pub files: FileCollection,
+    /// The `pipeline.json` files to process; all of them are if this is `None`
+    pub pipeline_files: Option<HashSet<AbsAssetUrl>>,
     pub input_file_filter: Option<String>,
     pub package_name: String,
     pub in_root: AbsAssetUrl,
@@ -155,6 +169,9 @@ impl FileCollection {
 
 pub async fn download_image(assets: &AssetCache, url: &AbsAssetUrl) -> anyhow::Result<image::DynamicImage> {
     let data = url.download_bytes(assets).await?;
+    if ktx2::is_ktx2(&data) {
+        return Ok(image::DynamicImage::ImageRgba8(Ktx2Texture::parse(&data)?.to_rgba8_image()?));
+    }
     if let Some(format) = url.extension().as_ref().and_then(ImageFormat::from_extension) {
         Ok(image::load_from_memory_with_format(&data, format).with_context(|| format!("Failed to load image {url}"))?)
     } else {
@@ -2,7 +2,9 @@ use std::{path::PathBuf, sync::Arc};
 
 use ambient_core::hierarchy::children;
 use ambient_ecs::Entity;
-use ambient_model_import::{model_crate::ModelCrate, MaterialFilter, ModelTextureSize, ModelTransform, TextureResolver};
+use ambient_model_import::{
+    mesh_optimization::MeshOptimization, model_crate::ModelCrate, MaterialFilter, ModelTextureSize, ModelTransform, TextureResolver,
+};
 use ambient_physics::collider::{collider_type, ColliderType};
 use ambient_std::asset_url::AssetType;
 use futures::FutureExt;
@@ -65,8 +67,16 @@ pub struct ModelsPipeline {
     /// If a collider is present, this controls how it will interact with other colliders.
     #[serde(default)]
     collider_type: ColliderType,
+    /// If specified, the model is pre-fractured: the children of the node with this name are split off into chunks with
+    /// colliders of their own, which the prefab breaks into when it's destroyed. The children of a chunk are the smaller
+    /// chunks it breaks into.
+    #[serde(default)]
+    destructible_chunks: Option<String>,
     /// Whether or not this mesh should have its texture sizes capped.
     cap_texture_sizes: Option<ModelTextureSize>,
+    /// If specified, the meshes are optimized for rendering, and can have levels of detail generated for them.
+    #[serde(default)]
+    optimize_meshes: Option<MeshOptimization>,
     /// Treats all assets in the pipeline as variations, and outputs a single asset which is a collection of all assets.
     /// Most useful for grass and other entities whose individual identity is not important.
     #[serde(default)]
@@ -112,6 +122,15 @@ impl ModelsPipeline {
         if let Some(max_size) = self.cap_texture_sizes {
             model_crate.cap_texture_sizes(max_size.size());
         }
+        if let Some(optimization) = &self.optimize_meshes {
+            model_crate.optimize_meshes(optimization);
+        }
+        if let Some(compression) = ctx.pipeline.texture_compression {
+            model_crate.set_image_format("ktx2", Arc::new(move |image| compression.compress(image)));
+        }
+        if let Some(chunks_node) = &self.destructible_chunks {
+            model_crate.create_destructible_chunks(&ctx.process_ctx.assets, chunks_node)?;
+        }
         model_crate.finalize_model();
         match self.collider {
             Collider::None => {}
@@ -59,7 +59,7 @@ pub async fn pipeline(ctx: &PipelineCtx, config: ModelsPipeline) -> Vec<OutAsset
                     let out_model_path = ctx.in_root().relative_path(file.path()).join(i.to_string());
                     config.apply(&ctx, &mut asset_crate, &out_model_path).await?;
 
-                    let model_crate_url = ctx.write_model_crate(&asset_crate, &out_model_path).await;
+                    let model_crate_url = ctx.write_model_crate(&asset_crate, &out_model_path).await?;
 
                     res.push(OutAsset {
                         id: id.clone(),
@@ -126,7 +126,13 @@ pub async fn object_pipelines_from_quixel_json(
         async move {
             let pattern = format!("{}**/*{}", in_root_url.as_directory().path(), ending);
             let file = ctx.files.find_file_res(&pattern)?.clone();
-            Ok(AssetUrl::from(PipeImage::new(file).cap_texture_size(config.cap_texture_sizes).get(ctx.assets()).await?))
+            Ok(AssetUrl::from(
+                PipeImage::new(file)
+                    .cap_texture_size(config.cap_texture_sizes)
+                    .compression(ctx.pipeline.texture_compression)
+                    .get(ctx.assets())
+                    .await?,
+            ))
         }
         .boxed()
     };
@@ -246,6 +252,7 @@ pub async fn object_pipelines_from_quixel_json(
                             PipeImage::new(ctx.get_downloadable_url(&in_root_url.push(ending).unwrap()).unwrap().clone())
                                 .transform("mr", |img, _| rougness_to_mr(img))
                                 .cap_texture_size(config.cap_texture_sizes)
+                                .compression(ctx.pipeline.texture_compression)
                                 .get(ctx.assets())
                                 .await?,
                         )))
@@ -31,7 +31,7 @@ pub async fn pipeline(ctx: &PipelineCtx, config: ModelsPipeline) -> Vec<OutAsset
                 let out_model_path = ctx.in_root().relative_path(file.path());
                 config.apply(&ctx, &mut model_crate, &out_model_path).await?;
 
-                let model_crate_url = ctx.write_model_crate(&model_crate, &out_model_path).await;
+                let model_crate_url = ctx.write_model_crate(&model_crate, &out_model_path).await?;
 
                 if config.output_prefabs {
                     res.push(OutAsset {
@@ -20,6 +20,7 @@ use ambient_std::{
     asset_cache::AssetCache,
     asset_url::{AbsAssetUrl, AssetType, ModelCrateAssetType, TypedAssetUrl},
 };
+use ambient_unity_parser as unity_parser;
 use anyhow::Context;
 use async_recursion::async_recursion;
 use futures::{future::join_all, FutureExt};
@@ -28,7 +29,6 @@ use image::ImageOutputFormat;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
-use ambient_unity_parser as unity_parser;
 use unity_parser::{parse_unity_yaml, prefab::PrefabObject, UnityRef};
 use yaml_rust::Yaml;
 
@@ -107,7 +107,7 @@ pub async fn pipeline(ctx: &PipelineCtx, use_prefabs: bool, config: ModelsPipeli
                     .await?;
                     config.apply(&ctx, &mut asset_crate, &out_model_path).await?;
 
-                    let model_crate_url = ctx.write_model_crate(&asset_crate, &out_model_path).await;
+                    let model_crate_url = ctx.write_model_crate(&asset_crate, &out_model_path).await?;
                     res.push(OutAsset {
                         id: asset_id_from_url(&file),
                         type_: AssetType::Prefab,
@@ -158,7 +158,7 @@ pub async fn pipeline(ctx: &PipelineCtx, use_prefabs: bool, config: ModelsPipeli
 
                     config.apply(&ctx, &mut asset_crate, &out_path).await?;
 
-                    let model_crate_url = ctx.write_model_crate(&asset_crate, &out_path).await;
+                    let model_crate_url = ctx.write_model_crate(&asset_crate, &out_path).await?;
                     res.push(OutAsset {
                         id: asset_id_from_url(&file),
                         type_: AssetType::Prefab,
@@ -322,6 +322,7 @@ impl UnityMaterials {
                 base_color: base_color.map(|x| x.into()),
                 normalmap: normalmap.map(|x| x.into()),
                 metallic_roughness: metallic_roughness.map(|x| x.into()),
+                emissive: None,
                 opacity: None,
                 base_color_factor: None,
                 emissive_factor: None,
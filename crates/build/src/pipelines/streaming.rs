@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use ambient_core::transform::translation;
+use ambient_ecs::{EntityId, World};
+use ambient_editor::annotations::annotation;
+use ambient_prefab::streaming::{streaming_cell, StreamingCell, StreamingIndex, STREAMING_INDEX_FILE};
+use ambient_std::asset_url::AssetType;
+use anyhow::Context;
+use relative_path::RelativePathBuf;
+
+use super::{
+    context::PipelineCtx,
+    out_asset::{asset_id_from_url, OutAsset, OutAssetContent, OutAssetPreview},
+};
+
+/// Worlds to split into streaming cells end with this
+pub const WORLD_EXTENSION: &str = ".world.json";
+
+/// Splits each `.world.json` file into a prefab per [streaming_cell] and a prefab for the entities without one, and writes
+/// the [StreamingIndex] of the world next to them. `level.world.json` is written to the `level.world` directory, which is
+/// what `streaming_world_from_url` is set to
+pub async fn pipeline(ctx: &PipelineCtx) -> Vec<OutAsset> {
+    ctx.process_files(
+        |file| file.path().as_str().ends_with(WORLD_EXTENSION),
+        |ctx, file| async move {
+            let data = file.download_bytes(ctx.assets()).await?;
+            let source = World::from_slice(&data).with_context(|| format!("Failed to deserialize the world {file}"))?;
+
+            // Each cell is written with the ids its entities have in the source world, so that the ids entities refer to each
+            // other with, e.g. in their hierarchy, stay the same
+            let mut cells = BTreeMap::<Option<String>, Vec<EntityId>>::new();
+            for (id, _) in source.entities() {
+                // Annotations are only for the editor, so they are left out of the build
+                if id != source.resource_entity() && !source.has_component(id, annotation()) {
+                    cells.entry(source.get_cloned(id, streaming_cell()).ok()).or_default().push(id);
+                }
+            }
+            let write_cell = |ids: &[EntityId]| -> anyhow::Result<Vec<u8>> {
+                let mut world = World::new_with_config("streaming_cell", false);
+                for &id in std::iter::once(&source.resource_entity()).chain(ids) {
+                    world.spawn_with_id(id, source.clone_entity(id)?);
+                }
+                Ok(serde_json::to_vec(&world)?)
+            };
+
+            let rel_path = ctx.in_root().relative_path(file.path());
+            let dir = RelativePathBuf::from(rel_path.as_str().strip_suffix(".json").unwrap());
+            let mut index = StreamingIndex::default();
+            for (cell, ids) in &cells {
+                let data = write_cell(ids)?;
+                match cell {
+                    None => {
+                        ctx.write_file(dir.join("main.json"), data).await;
+                        index.main = Some("main.json".to_string());
+                    }
+                    Some(cell) => {
+                        let url = format!("cells/{}.json", index.cells.len());
+                        ctx.write_file(dir.join(&url), data).await;
+                        let positions = ids.iter().filter_map(|&id| source.get(id, translation()).ok()).collect::<Vec<_>>();
+                        let (center, radius) = StreamingCell::bounds(&positions);
+                        index.cells.push(StreamingCell { id: cell.clone(), url, center, radius });
+                    }
+                }
+            }
+            let index_url = ctx.write_file(dir.join(STREAMING_INDEX_FILE), serde_json::to_vec_pretty(&index)?).await;
+
+            Ok(vec![OutAsset {
+                id: asset_id_from_url(&file),
+                type_: AssetType::StreamingWorld,
+                hidden: false,
+                name: file.path().file_name().unwrap().to_string(),
+                tags: Vec::new(),
+                categories: Default::default(),
+                preview: OutAssetPreview::None,
+                content: OutAssetContent::Content(index_url),
+                source: Some(file.clone()),
+            }])
+        },
+    )
+    .await
+}
@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use ambient_gpu::ktx2::Ktx2Texture;
+use anyhow::{anyhow, ensure, Context};
+use basis_universal::{BasisTextureFormat, ColorSpace, Compressor, CompressorParams, UASTC_QUALITY_DEFAULT};
+use byteorder::{ByteOrder, LittleEndian};
+use image::{imageops::FilterType, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// Transcodes textures to UASTC (BasisU) in KTX2 containers, supercompressed with Zstandard.
+///
+/// When loaded, they're transcoded to a compressed format the GPU supports (BC7, ASTC or ETC2), which takes a quarter of
+/// the memory of uncompressed textures.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextureCompression {
+    /// The UASTC quality level, from 0 (fastest) to 4 (best quality).
+    pub quality: u32,
+    /// If set, rate-distortion optimization is used to make the textures compress better, at some cost of quality.
+    /// Lower values give better quality; 1.0 is a good starting point.
+    pub rdo_quality: Option<f32>,
+    /// The Zstandard compression level used for the texture data.
+    pub zstd_level: i32,
+}
+impl Default for TextureCompression {
+    fn default() -> Self {
+        Self { quality: UASTC_QUALITY_DEFAULT, rdo_quality: None, zstd_level: 18 }
+    }
+}
+impl TextureCompression {
+    /// Compresses `image` and its mipmaps, returning the KTX2 file
+    pub fn compress(&self, image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(basis_universal::encoder_init);
+
+        // UASTC is transcoded to formats made of 4x4 blocks, which the GPU only takes for sizes that are multiples of 4
+        let (width, height) = block_aligned_size(image.width(), image.height());
+        let image = if (width, height) == image.dimensions() {
+            Cow::Borrowed(image)
+        } else {
+            Cow::Owned(image::imageops::resize(image, width, height, FilterType::CatmullRom))
+        };
+
+        let mut params = CompressorParams::new();
+        params.set_basis_format(BasisTextureFormat::UASTC4x4);
+        params.set_uastc_quality_level(self.quality);
+        params.set_rdo_uastc(self.rdo_quality);
+        params.set_generate_mipmaps(true);
+        // The pipelines don't know whether textures hold colors or data such as normals, so the error is measured linearly
+        params.set_color_space(ColorSpace::Linear);
+        params.source_image_mut(0).init(image.as_raw(), image.width(), image.height(), 4);
+
+        let threads = std::thread::available_parallelism().map(|x| x.get() as u32).unwrap_or(1);
+        let mut compressor = Compressor::new(threads);
+        // SAFETY: the params are fully initialized and outlive the compression
+        unsafe {
+            ensure!(compressor.init(&params), "Failed to initialize the texture compressor");
+            compressor.process().map_err(|err| anyhow!("Failed to compress texture: {err:?}"))?;
+        }
+
+        let texture = Ktx2Texture {
+            width: image.width(),
+            height: image.height(),
+            srgb: false,
+            has_alpha: image.pixels().any(|pixel| pixel.0[3] < 255),
+            levels: uastc_levels_from_basis(compressor.basis_file())?,
+        };
+        texture.to_bytes(Some(self.zstd_level))
+    }
+}
+
+/// Rounds the size up to multiples of 4
+fn block_aligned_size(width: u32, height: u32) -> (u32, u32) {
+    ((width.max(1) + 3) / 4 * 4, (height.max(1) + 3) / 4 * 4)
+}
+
+/// Extracts the UASTC blocks of each mip level from a `.basis` file with a single UASTC image
+fn uastc_levels_from_basis(data: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+    const HEADER_SIZE: usize = 77;
+    const SLICE_DESC_SIZE: usize = 23;
+    const TEX_FORMAT_UASTC: u8 = 1;
+    let read = |offset: usize, bytes: usize| LittleEndian::read_uint(&data[offset..offset + bytes], bytes) as usize;
+
+    ensure!(data.len() >= HEADER_SIZE && &data[0..2] == b"sB", "Not a basis file");
+    ensure!(data[20] == TEX_FORMAT_UASTC, "Expected a UASTC basis file");
+    let total_slices = read(14, 3);
+    let slice_desc_offset = read(65, 4);
+    ensure!(data.len() >= slice_desc_offset + total_slices * SLICE_DESC_SIZE, "Truncated basis file");
+
+    let mut slices = (0..total_slices)
+        .map(|slice| {
+            let desc = slice_desc_offset + slice * SLICE_DESC_SIZE;
+            let (level, offset, size) = (data[desc + 3], read(desc + 13, 4), read(desc + 17, 4));
+            let slice_data = data.get(offset..offset + size).context("Truncated basis slice")?;
+            Ok((level, slice_data.to_vec()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    slices.sort_by_key(|(level, _)| *level);
+    Ok(slices.into_iter().map(|(_, data)| data).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_aligned_to_blocks() {
+        assert_eq!(block_aligned_size(256, 128), (256, 128));
+        assert_eq!(block_aligned_size(250, 3), (252, 4));
+        assert_eq!(block_aligned_size(1, 0), (4, 4));
+    }
+}
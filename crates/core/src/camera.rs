@@ -1,16 +1,18 @@
 use ambient_ecs::{
-    components, query, query_mut, Component, Concept, Debuggable, Description, ECSError, Entity, EntityId, Name, Networked, RefConcept,
-    Store, SystemGroup, World,
+    components, query, query_mut, Component, Concept, Debuggable, Description, ECSError, Entity, EntityId, FnSystem, Name, Networked,
+    RefConcept, Store, SystemGroup, World,
 };
 use ambient_std::{
     math::Line,
     shapes::{BoundingBox, Plane, Ray, AABB},
 };
-use glam::{vec3, Mat4, Vec2, Vec3, Vec3Swizzles};
+use glam::{vec3, vec4, Mat4, Vec2, Vec3, Vec3Swizzles, Vec4};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 
 use crate::{
+    main_scene,
+    player::{local_players, local_user_id, user_id},
     transform::{inv_local_to_world, local_to_world},
     window::{window_logical_size, window_physical_size},
 };
@@ -133,6 +135,30 @@ components!("camera", {
         Description["If attached, this camera will see/render fog."]
     ]
     fog: (),
+    @[
+        Networked, Store, Debuggable,
+        Name["Exposure"],
+        Description["The exposure of this camera, as an EV100 value (e.g. 15 for a sunny day, 9 for an overcast day, 5 for a lit room).\nUse it when the scene is lit with physical light units; without it, the lighting is displayed as is."]
+    ]
+    exposure: f32,
+    @[
+        Networked, Store, Debuggable,
+        Name["Camera viewport"],
+        Description["If attached, this camera is rendered into this region of the window, on top of the active camera, instead of competing to be the active camera.\nThe region is `(x, y, width, height)`, as fractions of the window size from its top-left corner."]
+    ]
+    camera_viewport: glam::Vec4,
+    @[
+        Networked, Store, Debuggable,
+        Name["Camera texture size"],
+        Description["If attached, this camera is rendered into an offscreen texture of this size, instead of competing to be the active camera.\nThe texture can be displayed with `render_target_from_camera`."]
+    ]
+    camera_texture_size: glam::UVec2,
+    @[
+        Networked, Store, Debuggable,
+        Name["Camera layers"],
+        Description["A mask of the rendering layers this camera sees; only entities whose `render_layers` intersect it are rendered. Defaults to all layers."]
+    ]
+    camera_layers: u32,
 
     // Shadows
     @[
@@ -141,6 +167,10 @@ components!("camera", {
         Description["The far plane for the shadow camera, measured in meters."]
     ]
     shadows_far: f32,
+
+    /// Set on the cameras that were given a `camera_viewport` for splitscreen, so that it's removed when they no longer need one
+    @[Debuggable]
+    splitscreen_viewport: (),
 });
 
 pub fn concepts() -> Vec<Concept> {
@@ -206,6 +236,7 @@ pub fn camera_systems() -> SystemGroup {
     SystemGroup::new(
         "camera_systems",
         vec![
+            Box::new(FnSystem::new(|world, _| update_splitscreen(world))),
             query((aspect_ratio_from_window(), aspect_ratio())).to_system(|q, world, qs, _| {
                 for (id, (window, old_ratio)) in q.collect_cloned(world, qs) {
                     let window_size = world.get(window, window_physical_size()).unwrap_or_default();
@@ -213,12 +244,22 @@ pub fn camera_systems() -> SystemGroup {
                         continue;
                     }
 
-                    let aspect_ratio = window_size.x as f32 / window_size.y as f32;
+                    let mut aspect_ratio = window_size.x as f32 / window_size.y as f32;
+                    if let Ok(viewport) = world.get(id, camera_viewport()) {
+                        aspect_ratio *= viewport.z / viewport.w.max(f32::EPSILON);
+                    }
                     if aspect_ratio != old_ratio {
                         world.set(id, self::aspect_ratio(), aspect_ratio).unwrap();
                     }
                 }
             }),
+            query((camera_texture_size().changed(), aspect_ratio())).to_system(|q, world, qs, _| {
+                for (id, (size, _)) in q.collect_cloned(world, qs) {
+                    if size.x > 0 && size.y > 0 {
+                        world.set(id, aspect_ratio(), size.x as f32 / size.y as f32).unwrap();
+                    }
+                }
+            }),
             query_mut((projection(),), (near(), fovy(), aspect_ratio())).incl(perspective_infinite_reverse()).to_system(
                 |q, world, qs, _| {
                     for (_, (projection,), (&near, &fovy, &aspect_ratio)) in q.iter(world, qs) {
@@ -288,6 +329,52 @@ pub fn camera_systems() -> SystemGroup {
     )
 }
 
+/// The viewports of `count` players sharing a window, in a grid that's filled row by row; two players are side by side
+pub fn splitscreen_viewports(count: usize) -> Vec<Vec4> {
+    let count = count.max(1);
+    let columns = (count as f32).sqrt().ceil() as usize;
+    let rows = (count + columns - 1) / columns;
+    let size = Vec2::new(1. / columns as f32, 1. / rows as f32);
+    (0..count).map(|i| vec4((i % columns) as f32 * size.x, (i / columns) as f32 * size.y, size.x, size.y)).collect()
+}
+
+/// Splits the window between the cameras of the local player and the `local_players` of this client, if there are any
+fn update_splitscreen(world: &mut World) {
+    let users = match (world.resource_opt(local_user_id()), world.resource_opt(local_players())) {
+        (Some(local_user_id), Some(local_players)) if !local_players.is_empty() => {
+            std::iter::once(local_user_id).chain(local_players).cloned().collect_vec()
+        }
+        _ => Vec::new(),
+    };
+
+    let mut split = Vec::new();
+    for (user, viewport) in users.iter().zip(splitscreen_viewports(users.len())) {
+        // Cameras which were given a viewport by something else, or which render to a texture, are left alone
+        let camera = query((main_scene(), active_camera(), user_id()))
+            .excl(camera_texture_size())
+            .iter(world, None)
+            .filter(|(id, (_, _, camera_user))| {
+                *camera_user == user && (!world.has_component(*id, camera_viewport()) || world.has_component(*id, splitscreen_viewport()))
+            })
+            .max_by_key(|(_, (_, active, _))| OrderedFloat(**active))
+            .map(|(id, _)| id);
+        if let Some(camera) = camera {
+            split.push((camera, viewport));
+        }
+    }
+
+    for (id, _) in query(splitscreen_viewport()).collect_cloned(world, None) {
+        if !split.iter().any(|(camera, _)| *camera == id) {
+            world.remove_components(id, vec![camera_viewport().into(), splitscreen_viewport().into()]).ok();
+        }
+    }
+    for (camera, viewport) in split {
+        if world.get(camera, camera_viewport()) != Ok(viewport) {
+            world.add_components(camera, Entity::new().with(camera_viewport(), viewport).with(splitscreen_viewport(), ())).ok();
+        }
+    }
+}
+
 /// Ambient uses a left handed reverse-z NDC. This function will produce a correct perspective matrix for that
 pub fn perspective_reverse(fov_y_radians: f32, aspect_ratio: f32, z_near: f32, z_far: f32) -> Mat4 {
     // far and near and swapped on purpose
@@ -309,26 +396,25 @@ pub fn screen_ray(world: &World, camera: EntityId, mouse_origin: Vec2) -> Result
     Ok(Ray::new(camera_mouse_origin, camera_mouse_dir))
 }
 
+/// Cameras with a `camera_viewport` or `camera_texture_size` are rendered separately, and are never the active camera
 pub fn get_active_camera(world: &World, scene: Component<()>, user_id: Option<&String>) -> Option<EntityId> {
     query((scene, active_camera()))
+        .excl(camera_viewport())
+        .excl(camera_texture_size())
         .iter(world, None)
-        .filter(|(id, _)| {
-            if let Some(user_id) = &user_id {
-                if let Ok(cam_user_id) = world.get_ref(*id, crate::player::user_id()) {
-                    cam_user_id == *user_id
-                } else {
-                    // The camera is considered global, as it doesn't have a user_id attached
-                    true
-                }
-            } else {
-                // No user_id was supplied, so all cameras are considered
-                true
-            }
-        })
+        .filter(|(id, _)| is_camera_of_user(world, *id, user_id))
         .max_by_key(|(_, (_, x))| OrderedFloat(**x))
         .map(|(id, _)| id)
 }
 
+/// Whether the camera can be used by `user_id`. Cameras without a `user_id` are global, and all cameras are considered if no `user_id` is supplied
+pub fn is_camera_of_user(world: &World, camera: EntityId, user_id: Option<&String>) -> bool {
+    match (user_id, world.get_ref(camera, crate::player::user_id())) {
+        (Some(user_id), Ok(cam_user_id)) => cam_user_id == user_id,
+        _ => true,
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Projection {
     Orthographic { rect: OrthographicRect, near: f32, far: f32 },
@@ -341,8 +427,11 @@ impl Projection {
         if let Ok(rect) = world.get(entity, orthographic_rect()) {
             Self::Orthographic { rect, near: world.get(entity, near()).unwrap_or(-1.), far: world.get(entity, far()).unwrap_or(1.) }
         } else {
-            let window_size = world.resource(window_physical_size());
-            let aspect_ratio = window_size.x as f32 / window_size.y as f32;
+            // Cameras which don't follow the window, e.g. the ones rendering to a texture, have their own aspect ratio
+            let aspect_ratio = world.get(entity, aspect_ratio()).unwrap_or_else(|_| {
+                let window_size = world.resource(window_physical_size());
+                window_size.x as f32 / window_size.y as f32
+            });
             Self::PerspectiveInfiniteReverse {
                 fovy: world.get(entity, fovy()).unwrap_or(1.),
                 aspect_ratio,
@@ -690,3 +779,11 @@ fn test_frustum_reverse_z() {
     assert!(frustum.right.distance(Vec3::Z * 100.) < 0.);
     assert!(frustum.top.distance(Vec3::Z * 100.) < 0.);
 }
+
+#[test]
+fn test_splitscreen_viewports() {
+    assert_eq!(splitscreen_viewports(1), vec![vec4(0., 0., 1., 1.)]);
+    assert_eq!(splitscreen_viewports(2), vec![vec4(0., 0., 0.5, 1.), vec4(0.5, 0., 0.5, 1.)]);
+    assert_eq!(splitscreen_viewports(3)[2], vec4(0., 0.5, 0.5, 0.5));
+    assert_eq!(splitscreen_viewports(4)[3], vec4(0.5, 0.5, 0.5, 0.5));
+}
@@ -0,0 +1,100 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use ambient_ecs::{components, Debuggable, Description, Name, Resource, World};
+use parking_lot::Mutex;
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+
+components!("app", {
+    @[
+        Resource, Debuggable,
+        Name["Fixed timestep"],
+        Description["If set, the `time` advances by this many seconds every frame instead of following the clock, and `dtime` is this."]
+    ]
+    fixed_timestep: f32,
+    @[
+        Resource, Debuggable,
+        Name["Random number generator"],
+        Description["The random number generator the systems of this world should use, so that it can be seeded."]
+    ]
+    rng: WorldRng,
+    @[Resource, Debuggable]
+    determinism: Determinism,
+});
+
+/// A random number generator shared by the systems of a world, through the `rng` resource.
+///
+/// It's seeded by [Determinism], and from the OS otherwise.
+#[derive(Clone)]
+pub struct WorldRng(Arc<Mutex<Pcg64>>);
+impl WorldRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self(Arc::new(Mutex::new(Pcg64::seed_from_u64(seed))))
+    }
+    pub fn from_entropy() -> Self {
+        Self(Arc::new(Mutex::new(Pcg64::from_entropy())))
+    }
+}
+impl Debug for WorldRng {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorldRng").finish()
+    }
+}
+impl RngCore for WorldRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.lock().next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0.lock().next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.lock().fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.lock().try_fill_bytes(dest)
+    }
+}
+
+/// Makes the simulation of worlds reproducible, for tests and replays: given the same inputs, they produce the same
+/// entities, with the same ids, on every run and platform.
+///
+/// Time starts at 0 and advances by a fixed timestep every frame, the entity ids and the `rng` resource are seeded, and
+/// the server steps its instances in a stable order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Determinism {
+    pub seed: u64,
+    /// In seconds
+    pub timestep: f32,
+}
+impl Determinism {
+    pub const DEFAULT_TIMESTEP: f32 = 1. / 60.;
+
+    pub fn new(seed: u64) -> Self {
+        Self { seed, timestep: Self::DEFAULT_TIMESTEP }
+    }
+
+    /// Seeds the entity ids of `world`, and replaces its time and `rng` resources
+    pub fn apply(&self, world: &mut World) {
+        self.reseed(world);
+        world.add_resource(crate::time(), Duration::ZERO);
+        world.add_resource(crate::game_time(), Duration::ZERO);
+        world.add_resource(crate::app_start_time(), Duration::ZERO);
+        world.add_resource(crate::dtime(), self.timestep);
+    }
+
+    /// Seeds the entity ids and the `rng` resource of `world`, without resetting its time
+    pub fn reseed(&self, world: &mut World) {
+        world.seed_entity_ids(self.seed);
+        world.add_resource(determinism(), *self);
+        world.add_resource(fixed_timestep(), self.timestep);
+        world.add_resource(rng(), WorldRng::from_seed(self.seed));
+    }
+
+    /// The determinism of a world forked from this one, which is named `name`; its entity ids and random numbers differ
+    /// from the original's, but are still reproducible
+    pub fn fork(&self, name: &str) -> Self {
+        // FNV-1a, which unlike the std hashers is stable across versions and platforms
+        let hash = name.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+        Self { seed: self.seed ^ hash, ..*self }
+    }
+}
@@ -0,0 +1,9 @@
+//! Messages between the web client and the page it's embedded in
+use ambient_ecs::{components, Debuggable, Description, Name};
+
+components!("embed", {
+    @[Debuggable, Name["Embed message name"], Description["The name of a message from or to the page embedding the web client."]]
+    embed_message_name: String,
+    @[Debuggable, Name["Embed message data"], Description["The data of a message from or to the page embedding the web client, as JSON."]]
+    embed_message_data: String,
+});
@@ -1,4 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 use ambient_ecs::{components, ArchetypeId, Resource, System, World};
 use ambient_gpu::{
@@ -60,11 +66,20 @@ impl SyncAssetKey<ShaderModule> for GpuWorldShaderModuleKey {
     }
 }
 
+/// What was written to a [GpuWorld] during a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuUploadStats {
+    pub writes: u64,
+    pub bytes: u64,
+}
+
 pub struct GpuWorld {
     gpu: Arc<Gpu>,
     layout_buffer: TypedBuffer<i32>,
     buffers: Vec<GpuComponentsBuffer>,
     assets: AssetCache,
+    upload_writes: AtomicU64,
+    upload_bytes: AtomicU64,
 }
 impl GpuWorld {
     pub fn new_arced(assets: AssetCache) -> Arc<Mutex<Self>> {
@@ -87,15 +102,29 @@ impl GpuWorld {
             buffers: config.buffers.iter().map(|buffer| GpuComponentsBuffer::new(gpu.clone(), buffer.clone())).collect(),
             assets,
             gpu,
+            upload_writes: Default::default(),
+            upload_bytes: Default::default(),
         }
     }
+    /// Starts a new frame of [GpuUploadStats], and lays out the buffers for the archetypes of `world`
     pub fn update(&mut self, world: &World) {
+        self.upload_writes.store(0, Ordering::Relaxed);
+        self.upload_bytes.store(0, Ordering::Relaxed);
         self.layout_buffer.write(0, &[world.archetypes().len() as i32]);
         for buf in self.buffers.iter_mut() {
             let layout_offset = buf.config.layout_offset(world.archetypes().len());
             buf.update(world, &mut self.layout_buffer, layout_offset as u64);
         }
     }
+    /// Counts a write of `bytes` by a sync system
+    pub fn record_upload(&self, bytes: usize) {
+        self.upload_writes.fetch_add(1, Ordering::Relaxed);
+        self.upload_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+    /// What the sync systems wrote since the last [Self::update]
+    pub fn upload_stats(&self) -> GpuUploadStats {
+        GpuUploadStats { writes: self.upload_writes.load(Ordering::Relaxed), bytes: self.upload_bytes.load(Ordering::Relaxed) }
+    }
     pub fn create_bind_group(&self, read_only: bool) -> wgpu::BindGroup {
         let layout = GpuWorldShaderModuleKey { read_only }.get(&self.assets).idents[0].as_bind_group().unwrap().get(&self.assets);
         let mut buffers = vec![wgpu::BindGroupEntry { binding: 0, resource: self.layout_buffer.buffer().as_entire_binding() }];
@@ -176,7 +205,10 @@ impl GpuComponentsBuffer {
 pub struct GpuWorldUpdate;
 impl System<GpuWorldSyncEvent> for GpuWorldUpdate {
     fn run(&mut self, world: &mut World, _event: &GpuWorldSyncEvent) {
-        profiling::scope!("GpuWorldUpdate.run");
-        world.resource_mut(gpu_world()).clone().lock().update(world);
+        let gpu_world = world.resource(gpu_world()).clone();
+        let mut gpu_world = gpu_world.lock();
+        let uploaded = gpu_world.upload_stats();
+        profiling::scope!("GpuWorldUpdate.run", &format!("{} bytes in {} writes last frame", uploaded.bytes, uploaded.writes));
+        gpu_world.update(world);
     }
 }
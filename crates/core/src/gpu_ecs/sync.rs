@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range};
 
 use ambient_ecs::{Archetype, ArchetypeFilter, Component, ComponentDesc, ComponentValue, EntityId, System, World};
 use ambient_std::sparse_vec::SparseVec;
@@ -30,12 +30,61 @@ impl ArchChangeDetection {
     }
 }
 
+/// Dirty spans closer than this many entities are written together, as each write has more overhead than a few unchanged entities
+const MAX_DIRTY_GAP: usize = 16;
+
+struct SyncedArchetype {
+    version: u64,
+    layout_version: u64,
+    ids: Vec<EntityId>,
+}
+
+/// Tracks which entities of each archetype changed since they were last synced, so that only those spans are written
+#[derive(Default)]
+struct DirtyRanges {
+    synced: SparseVec<SyncedArchetype>,
+}
+impl DirtyRanges {
+    /// The ranges of entity indices of `arch` to write: the ones whose `component` was written, and the ones that were
+    /// moved, as entities keep their version when they're moved
+    fn take(&mut self, world: &World, arch: &Archetype, component: ComponentDesc, layout_version: u64) -> Vec<Range<usize>> {
+        let count = arch.entity_count();
+        let ranges = match self.synced.get(arch.id) {
+            Some(synced) if synced.layout_version == layout_version => {
+                let moved = (0..count).filter(|&index| synced.ids.get(index) != Some(&arch.get_entity_id_from_index(index)));
+                let indices = moved.chain(arch.get_component_changed_indices(component, synced.version)).sorted().dedup();
+                to_ranges(indices, MAX_DIRTY_GAP)
+            }
+            _ => vec![0..count],
+        };
+        let synced = self.synced.get_mut_or_insert_with(arch.id, || SyncedArchetype { version: 0, layout_version, ids: Vec::new() });
+        synced.version = world.version();
+        synced.layout_version = layout_version;
+        synced.ids.clear();
+        synced.ids.extend((0..count).map(|index| arch.get_entity_id_from_index(index)));
+        ranges
+    }
+}
+
+/// Joins sorted `indices` into ranges, including gaps of up to `max_gap`
+fn to_ranges(indices: impl IntoIterator<Item = usize>, max_gap: usize) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for index in indices {
+        match ranges.last_mut() {
+            Some(range) if index <= range.end + max_gap => range.end = range.end.max(index + 1),
+            _ => ranges.push(index..index + 1),
+        }
+    }
+    ranges
+}
+
 pub struct ComponentToGpuSystem<T: ComponentValue + bytemuck::Pod> {
     format: GpuComponentFormat,
     source_archetypes: ArchetypeFilter,
     source_component: Component<T>,
     destination_component: GpuComponentId,
     changed: ArchChangeDetection,
+    dirty: DirtyRanges,
 }
 impl<T: ComponentValue + bytemuck::Pod> ComponentToGpuSystem<T> {
     pub fn new(format: GpuComponentFormat, source_component: Component<T>, destination_component: GpuComponentId) -> Self {
@@ -46,6 +95,7 @@ impl<T: ComponentValue + bytemuck::Pod> ComponentToGpuSystem<T> {
             source_archetypes: ArchetypeFilter::new().incl(source_component),
             destination_component,
             changed: ArchChangeDetection::new(),
+            dirty: DirtyRanges::default(),
         }
     }
     pub fn with_arch_filter(mut self, arch_filter: ArchetypeFilter) -> Self {
@@ -62,7 +112,11 @@ impl<T: ComponentValue + bytemuck::Pod> System<GpuWorldSyncEvent> for ComponentT
             if let Some((gpu_buff, offset, layout_version)) = gpu_world.get_buffer(self.format, self.destination_component, arch.id) {
                 if self.changed.changed(arch, self.source_component, layout_version) {
                     let buf = arch.get_component_buffer(self.source_component).unwrap();
-                    gpu.queue.write_buffer(gpu_buff, offset, bytemuck::cast_slice(&buf.data));
+                    for range in self.dirty.take(world, arch, self.source_component.desc(), layout_version) {
+                        let data = bytemuck::cast_slice(&buf.data[range.clone()]);
+                        gpu.queue.write_buffer(gpu_buff, offset + range.start as u64 * self.format.size(), data);
+                        gpu_world.record_upload(data.len());
+                    }
                 }
             }
         }
@@ -107,7 +161,9 @@ impl<A: ComponentValue, B: bytemuck::Pod> System<GpuWorldSyncEvent> for MappedCo
                         .enumerate()
                         .map(&|(index, value)| (self.map)(world, arch.get_entity_id_from_index(index), value))
                         .collect_vec();
-                    gpu.queue.write_buffer(gpu_buff, offset, bytemuck::cast_slice(&data));
+                    let data = bytemuck::cast_slice(&data);
+                    gpu.queue.write_buffer(gpu_buff, offset, data);
+                    gpu_world.record_upload(data.len());
                 }
             }
         }
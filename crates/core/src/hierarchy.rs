@@ -31,6 +31,20 @@ pub fn despawn_children_recursive(world: &mut World, entity: EntityId) {
         }
     }
 }
+/// All descendants of `entity`, depth first
+pub fn descendants(world: &World, entity: EntityId) -> Vec<EntityId> {
+    let mut res = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = world.get_ref(entity, children()).cloned().unwrap_or_default();
+    stack.reverse();
+    while let Some(id) = stack.pop() {
+        if visited.insert(id) {
+            res.push(id);
+            stack.extend(world.get_ref(id, children()).cloned().unwrap_or_default().into_iter().rev());
+        }
+    }
+    res
+}
 pub fn add_child(world: &mut World, id: EntityId, child_id: EntityId) -> Result<(), ECSError> {
     if world.has_component(id, children()) {
         world.get_mut(id, children())?.push(child_id);
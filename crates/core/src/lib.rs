@@ -16,10 +16,18 @@ use winit::window::Window;
 pub mod async_ecs;
 pub mod bounding;
 pub mod camera;
+pub mod determinism;
+pub mod embed;
 pub mod gpu_ecs;
 pub mod hierarchy;
+pub mod loading;
+pub mod logging;
+pub mod minimap;
 pub mod player;
+pub mod theme;
 pub mod transform;
+pub mod transition;
+pub mod weather;
 pub mod window;
 
 components!("app", {
@@ -44,6 +52,12 @@ components!("app", {
         Description["If attached, this entity belongs to the UI scene."]
     ]
     ui_scene: (),
+    @[
+        Debuggable,
+        Name["UI culled"],
+        Description["Whether this UI element is entirely outside of the window, or of the scroll area it's in, so it's not rendered."]
+    ]
+    ui_culled: bool,
     @[Resource]
     asset_cache: AssetCache,
     @[
@@ -83,6 +97,33 @@ components!("app", {
     time: Duration,
     @[Resource, Debuggable, Name["Delta Time"], Description["How long the previous tick took in seconds.\nAlso known as frametime."]]
     dtime: f32,
+    @[
+        Resource, Debuggable,
+        Name["Unscaled delta time"],
+        Description["How long the previous tick took in seconds, ignoring `time_scale` and `paused`."]
+    ]
+    unscaled_dtime: f32,
+    @[
+        Resource, Debuggable, Networked,
+        Name["Time scale"],
+        Description["How fast the game runs: `dtime`, and thus animations and physics, are multiplied by this, so that e.g. 0.5 is slow motion.\nDefaults to 1."]
+    ]
+    time_scale: f32,
+    @[
+        Resource, Debuggable, Networked,
+        Name["Paused"],
+        Description["If true, `dtime` is 0, which stops animations and physics. See `step_frames`."]
+    ]
+    paused: bool,
+    @[
+        Resource, Debuggable, Networked,
+        Name["Step frames"],
+        Description["While `paused`, the number of frames that still advance; each of them decrements this."]
+    ]
+    step_frames: u32,
+    /// The sum of the `dtime`s, which unlike `time` stops while paused
+    @[Resource, Debuggable]
+    game_time: Duration,
     @[Resource, Debuggable]
     app_start_time: Duration,
     @[Resource, Debuggable]
@@ -106,11 +147,19 @@ pub fn init_all_components() {
     init_components();
     player::init_components();
     window::init_components();
+    theme::init_components();
     hierarchy::init_components();
+    loading::init_components();
+    logging::init_components();
+    minimap::init_components();
     async_ecs::init_components();
     gpu_ecs::init_components();
     camera::init_components();
+    determinism::init_components();
+    embed::init_components();
     transform::init_components();
+    transition::init_components();
+    weather::init_components();
     transform::init_gpu_components();
     bounding::init_components();
     bounding::init_gpu_components();
@@ -170,15 +219,36 @@ impl TimeResourcesSystem {
 }
 impl System for TimeResourcesSystem {
     fn run(&mut self, world: &mut World, _event: &FrameEvent) {
-        let dtime = self.frame_time.elapsed().as_secs_f32();
+        let (time, dtime) = if let Some(&timestep) = world.resource_opt(determinism::fixed_timestep()) {
+            (*world.resource(self::time()) + Duration::from_secs_f32(timestep), timestep)
+        } else {
+            let dtime = self.frame_time.elapsed().as_secs_f32();
+            (SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap(), dtime)
+        };
         self.frame_time = Instant::now();
-        let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
         world.set(world.resource_entity(), self::time(), time).unwrap();
-        world.set(world.resource_entity(), self::dtime(), dtime).unwrap();
+        update_dtime(world, dtime);
         world.set(world.resource_entity(), frame_index(), world.resource(frame_index()) + 1).unwrap();
     }
 }
 
+/// Sets `dtime` for a frame that took `unscaled_dtime` seconds, scaled by `time_scale` and stopped while `paused` (except
+/// for the frames requested with `step_frames`), and advances `game_time` by it
+pub fn update_dtime(world: &mut World, unscaled_dtime: f32) {
+    let mut scale = world.resource_opt(self::time_scale()).copied().unwrap_or(1.).max(0.);
+    if world.resource_opt(self::paused()).copied().unwrap_or(false) {
+        match world.resource_opt(self::step_frames()).copied().unwrap_or(0) {
+            0 => scale = 0.,
+            frames => world.add_resource(self::step_frames(), frames - 1),
+        }
+    }
+    let dtime = unscaled_dtime * scale;
+    let game_time = world.resource_opt(self::game_time()).copied().unwrap_or_else(|| *world.resource(self::time()));
+    world.add_resource(self::dtime(), dtime);
+    world.add_resource(self::unscaled_dtime(), unscaled_dtime);
+    world.add_resource(self::game_time(), game_time + Duration::from_secs_f32(dtime));
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
 pub enum GameMode {
     #[default]
@@ -0,0 +1,100 @@
+//! How much of the game a client has loaded: its client-side modules, which are downloaded and then compiled, and the
+//! assets which are loading
+use std::{collections::BTreeMap, time::Duration};
+
+use ambient_ecs::{
+    components, query, world_events, Debuggable, Description, Entity, FnSystem, Name, Networked, Resource, Store, SystemGroup,
+};
+use ambient_event_types::LOAD_PROGRESS;
+use ambient_sys::time::Instant;
+
+use crate::{asset_cache, name};
+
+components!("app", {
+    @[Debuggable, Networked, Store, Name["Custom loading screen"], Description["If an entity has this, the client doesn't show its default loading screen, so that a package can show its own using `load_progress`."]]
+    custom_loading_screen: (),
+    @[Debuggable, Resource, Name["Load progress"], Description["How much of the game this client has loaded, from 0 to 1, counting its client-side modules and the assets which are loading.\nSent with the `LOAD_PROGRESS` event when it changes."]]
+    load_progress: f32,
+    @[Debuggable, Resource]
+    load_status: LoadStatus,
+    /// Set on each client-side module as it loads
+    @[Debuggable]
+    module_load_stage: ModuleLoadStage,
+});
+
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleLoadStage {
+    Downloading,
+    Compiling,
+    Loaded,
+    Failed(String),
+}
+impl ModuleLoadStage {
+    /// Failed modules are done, as they won't load any further
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Loaded | Self::Failed(_))
+    }
+}
+
+/// What the client is loading
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadStatus {
+    /// The stage of each client-side module, by its name
+    pub modules: BTreeMap<String, ModuleLoadStage>,
+    /// The names of the assets which are loading
+    pub loading_assets: Vec<String>,
+    pub loaded_assets: usize,
+}
+impl LoadStatus {
+    pub fn progress(&self) -> f32 {
+        let done = self.modules.values().filter(|stage| stage.is_done()).count() + self.loaded_assets;
+        let total = self.modules.len() + self.loaded_assets + self.loading_assets.len();
+        if total == 0 {
+            1.
+        } else {
+            done as f32 / total as f32
+        }
+    }
+    pub fn is_loaded(&self) -> bool {
+        self.loading_assets.is_empty() && self.modules.values().all(|stage| stage.is_done())
+    }
+}
+
+/// Updates the `load_status` and `load_progress` of the world, and sends the `LOAD_PROGRESS` event when the progress changes
+pub fn systems() -> SystemGroup {
+    let mut last_update: Option<Instant> = None;
+    SystemGroup::new(
+        "core/loading",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            // The timeline of the asset cache is shared with its loads, so it isn't read every frame
+            if last_update.map_or(false, |last_update| last_update.elapsed() < UPDATE_INTERVAL) {
+                return;
+            }
+            last_update = Some(Instant::now());
+
+            let modules = query(module_load_stage())
+                .iter(world, None)
+                .map(|(id, stage)| (world.get_cloned(id, name()).unwrap_or_else(|_| id.to_string()), stage.clone()))
+                .collect();
+            let (loading_assets, loaded_assets) = {
+                let timeline = world.resource(asset_cache()).timeline.lock();
+                let loading = timeline.assets.values().filter(|asset| asset.is_loading()).map(|asset| asset.long_name.clone()).collect();
+                let loaded =
+                    timeline.assets.values().filter(|asset| asset.lifetimes.iter().any(|lifetime| lifetime.end_load.is_some())).count();
+                (loading, loaded)
+            };
+            let status = LoadStatus { modules, loading_assets, loaded_assets };
+
+            let progress = status.progress();
+            if world.resource_opt(load_progress()) != Some(&progress) {
+                world.add_resource(load_progress(), progress);
+                world.resource_mut(world_events()).add_event((LOAD_PROGRESS.to_string(), Entity::new().with(load_progress(), progress)));
+            }
+            if world.resource_opt(load_status()) != Some(&status) {
+                world.add_resource(load_status(), status);
+            }
+        }))],
+    )
+}
@@ -0,0 +1,216 @@
+use std::{collections::VecDeque, fmt, str::FromStr, sync::Arc};
+
+use ambient_ecs::{components, Resource};
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use tracing::{metadata::LevelFilter, Level};
+
+components!("app", {
+    /// The most recent log records of this process; see [LogBuffer::global]
+    @[Resource]
+    log_buffer: LogBuffer,
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// Increases by one for every record pushed to the buffer, so that readers can tell which records they have already seen
+    pub index: u64,
+    pub time: DateTime<Utc>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// A `RUST_LOG`-style filter, like `info,ambient_gpu=warn,wgpu_core=off`.
+///
+/// A target also applies to its submodules; the most specific target wins. A target without a level enables everything for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    pub default: LevelFilter,
+    pub targets: Vec<(String, LevelFilter)>,
+}
+impl LogFilter {
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| {
+                target == prefix || target.strip_prefix(prefix.as_str()).map(|rest| rest.starts_with("::")).unwrap_or(false)
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        level <= self.level(target)
+    }
+    /// The most verbose level that any target is logged at
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets.iter().map(|(_, level)| *level).fold(self.default, Ord::max)
+    }
+    /// Adds the directives of `filter`; they take precedence over the existing ones
+    pub fn extend(&mut self, filter: &str) -> anyhow::Result<()> {
+        for directive in filter.split(',').map(|directive| directive.trim()).filter(|directive| !directive.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => self.set_target(target.trim(), parse_level(level)?),
+                None => match parse_level(directive) {
+                    Ok(level) => self.default = level,
+                    Err(_) => self.set_target(directive, LevelFilter::TRACE),
+                },
+            }
+        }
+        Ok(())
+    }
+    fn set_target(&mut self, target: &str, level: LevelFilter) {
+        self.targets.retain(|(prefix, _)| prefix != target);
+        self.targets.push((target.to_string(), level));
+    }
+}
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self { default: LevelFilter::INFO, targets: Vec::new() }
+    }
+}
+impl FromStr for LogFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self::default();
+        filter.extend(s)?;
+        Ok(filter)
+    }
+}
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default.to_string().to_lowercase())?;
+        for (target, level) in &self.targets {
+            write!(f, ",{target}={}", level.to_string().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+fn to_log_level_filter(level: LevelFilter) -> log::LevelFilter {
+    match level.into_level() {
+        None => log::LevelFilter::Off,
+        Some(Level::ERROR) => log::LevelFilter::Error,
+        Some(Level::WARN) => log::LevelFilter::Warn,
+        Some(Level::INFO) => log::LevelFilter::Info,
+        Some(Level::DEBUG) => log::LevelFilter::Debug,
+        Some(Level::TRACE) => log::LevelFilter::Trace,
+    }
+}
+
+fn parse_level(level: &str) -> anyhow::Result<LevelFilter> {
+    level.trim().parse().map_err(|_| anyhow::anyhow!("Unknown log level {level:?}; expected one of off, error, warn, info, debug or trace"))
+}
+
+#[derive(Debug)]
+struct LogBufferState {
+    records: VecDeque<LogRecord>,
+    next_index: u64,
+}
+
+/// A bounded buffer of the most recent log records, along with the filter that decides which records are logged at all.
+///
+/// The filter can be changed at runtime. Whatever routes log events into the buffer is expected to also apply the filter to
+/// the other outputs, such as the terminal.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    capacity: usize,
+    state: Arc<Mutex<LogBufferState>>,
+    /// Checked for every log event, so it's swapped atomically rather than kept behind the mutex
+    filter: Arc<ArcSwap<LogFilter>>,
+}
+impl LogBuffer {
+    pub const DEFAULT_CAPACITY: usize = 5000;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Arc::new(Mutex::new(LogBufferState { records: VecDeque::new(), next_index: 0 })),
+            filter: Arc::new(ArcSwap::from_pointee(LogFilter::default())),
+        }
+    }
+    /// The buffer the process' logger writes to
+    pub fn global() -> &'static LogBuffer {
+        lazy_static! {
+            static ref GLOBAL: LogBuffer = LogBuffer::new(LogBuffer::DEFAULT_CAPACITY);
+        }
+        &GLOBAL
+    }
+
+    pub fn filter(&self) -> LogFilter {
+        LogFilter::clone(&self.filter.load())
+    }
+    /// Replaces the filter, and lowers or raises the max levels of `log` and `tracing` to the most verbose level it allows, so
+    /// that the events it filters out are skipped as early as possible
+    pub fn set_filter(&self, filter: LogFilter) {
+        log::set_max_level(to_log_level_filter(filter.max_level()));
+        self.filter.store(Arc::new(filter));
+        // Makes `tracing` query the max level hints of the subscriber again
+        tracing::callsite::rebuild_interest_cache();
+    }
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        self.filter.load().enabled(target, level)
+    }
+
+    pub fn push(&self, level: Level, target: &str, message: String) {
+        let mut state = self.state.lock();
+        let index = state.next_index;
+        state.next_index += 1;
+        if state.records.len() >= self.capacity {
+            state.records.pop_front();
+        }
+        state.records.push_back(LogRecord { index, time: Utc::now(), level, target: target.to_string(), message });
+    }
+    /// The records with an index of `index` or higher that are still in the buffer
+    pub fn records_since(&self, index: u64) -> Vec<LogRecord> {
+        let state = self.state.lock();
+        let start = state.records.partition_point(|record| record.index < index);
+        state.records.range(start..).cloned().collect()
+    }
+    /// The `count` most recent records
+    pub fn recent(&self, count: usize) -> Vec<LogRecord> {
+        let state = self.state.lock();
+        state.records.range(state.records.len().saturating_sub(count)..).cloned().collect()
+    }
+    /// Like [LogBuffer::recent], but doesn't wait if the buffer is in use; for when the logger itself may be stuck
+    pub fn try_recent(&self, count: usize) -> Option<Vec<LogRecord>> {
+        let state = self.state.try_lock()?;
+        Some(state.records.range(state.records.len().saturating_sub(count)..).cloned().collect())
+    }
+    pub fn clear(&self) {
+        self.state.lock().records.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_targets() {
+        let filter: LogFilter = "warn,ambient_gpu=debug,ambient_gpu::shader=off,naga".parse().unwrap();
+        assert!(!filter.enabled("ambient_core", Level::INFO));
+        assert!(filter.enabled("ambient_gpu::gpu", Level::DEBUG));
+        assert!(!filter.enabled("ambient_gpu::shader", Level::ERROR));
+        assert!(!filter.enabled("ambient_gpux", Level::INFO));
+        assert!(filter.enabled("naga", Level::TRACE));
+        assert_eq!(filter.to_string().parse::<LogFilter>().unwrap(), filter);
+        assert!("info,ambient_gpu=loud".parse::<LogFilter>().is_err());
+        assert_eq!(filter.max_level(), LevelFilter::TRACE);
+        assert_eq!("warn,naga=off".parse::<LogFilter>().unwrap().max_level(), LevelFilter::WARN);
+    }
+
+    #[test]
+    fn buffer_is_bounded() {
+        let buffer = LogBuffer::new(2);
+        for i in 0..3 {
+            buffer.push(Level::INFO, "test", i.to_string());
+        }
+        assert_eq!(buffer.records_since(0).iter().map(|record| record.message.as_str()).collect::<Vec<_>>(), ["1", "2"]);
+        assert_eq!(buffer.records_since(2).len(), 1);
+        assert_eq!(buffer.recent(1)[0].index, 2);
+    }
+}
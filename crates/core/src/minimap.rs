@@ -0,0 +1,41 @@
+use ambient_ecs::{components, Debuggable, Description, Name, Networked, Store};
+use glam::{Vec2, Vec4};
+
+components!("ui", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Minimap"],
+        Description["This UI element is a minimap: the entities with a `minimap_icon` are shown on it as seen from above, with the world's x axis to the right and its y axis up.\nIts icons are positioned by the minimap, so it shouldn't have a layout."]
+    ]
+    minimap: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Minimap center"],
+        Description["The world position `(x, y)` shown at the center of this minimap, which pans it. Defaults to the origin."]
+    ]
+    minimap_center: Vec2,
+    @[
+        Debuggable, Networked, Store,
+        Name["Minimap zoom"],
+        Description["The pixels of this minimap per world unit. Defaults to 1."]
+    ]
+    minimap_zoom: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Minimap rotation"],
+        Description["The direction shown up this minimap, as an angle in radians counterclockwise from the world's y axis, e.g. the heading of the player for a radar. Defaults to 0."]
+    ]
+    minimap_rotation: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Minimap icon"],
+        Description["If attached, this entity is shown on every `minimap` as a dot of this color, at its `translation`."]
+    ]
+    minimap_icon: Vec4,
+    @[
+        Debuggable, Networked, Store,
+        Name["Minimap icon size"],
+        Description["The diameter of the `minimap_icon` of this entity, in pixels. Defaults to 8."]
+    ]
+    minimap_icon_size: f32,
+});
@@ -13,14 +13,52 @@ components!("player", {
         Description["An identifier attached to all things owned by a user, and supplied by the user.\nThis can be attached to more than just the player; by convention, it is also attached to related entities, including their camera and body."]
     ]
     user_id: String,
+    @[
+        Networked, Store, Debuggable,
+        Name["Display name"],
+        Description["The name of the player, as given by the identity provider the server authenticated them with."]
+    ]
+    display_name: String,
+    @[
+        Networked, Store, Debuggable,
+        Name["Is authenticated"],
+        Description["If attached, the server verified the identity of this player, and their `user_id` is the one given by the identity provider."]
+    ]
+    is_authenticated: (),
+    @[
+        Networked, Store, Debuggable,
+        Name["Spectator"],
+        Description["If attached, this user is spectating: they're connected and receive the world, but they aren't a player and have no character.\nThe entity has the `user_id` of the spectator, and their client controls their camera."]
+    ]
+    spectator: (),
     @[
         Networked, Store, Resource, Debuggable,
         Name["Local user ID"],
         Description["The user ID of the local player."]
     ]
     local_user_id: String,
+    @[
+        Networked, Store, Debuggable,
+        Name["Local player owner"],
+        Description["If attached, this player plays on the same client as the player with this user ID, sharing its connection and window (splitscreen)."]
+    ]
+    local_player_owner: String,
+    @[
+        Debuggable, Resource,
+        Name["Local players"],
+        Description["The user IDs of the other players playing on this client, in the order they joined.\nThe window is split between their cameras and the local player's."]
+    ]
+    local_players: Vec<String>,
 });
 
+/// The user ID of the `index`th extra player on the client of `owner`
+pub fn local_player_user_id(owner: &str, index: u32) -> String {
+    format!("{owner}#{index}")
+}
+
 pub fn get_player_by_user_id(world: &World, user_id: &str) -> Option<EntityId> {
     query(self::user_id()).incl(player()).iter(world, None).find_map(|(id, uid)| if uid == user_id { Some(id) } else { None })
 }
+pub fn get_spectator_by_user_id(world: &World, user_id: &str) -> Option<EntityId> {
+    query(self::user_id()).incl(spectator()).iter(world, None).find_map(|(id, uid)| if uid == user_id { Some(id) } else { None })
+}
@@ -0,0 +1,35 @@
+use ambient_ecs::{components, Debuggable, Description, Name, Resource};
+use glam::Vec4;
+
+components!("ui", {
+    @[
+        Resource, Debuggable,
+        Name["Theme name"],
+        Description["The name of the active UI theme, e.g. `dark` or `light`.\nThe UI restyles itself when it changes, so it must be set after the tokens."]
+    ]
+    theme_name: String,
+    @[
+        Resource, Debuggable,
+        Name["Theme color tokens"],
+        Description["The names of the color tokens of the active UI theme, e.g. `primary` or `text`. Their values are in `theme_color_values`."]
+    ]
+    theme_color_tokens: Vec<String>,
+    @[
+        Resource, Debuggable,
+        Name["Theme color values"],
+        Description["The RGBA colors of the tokens in `theme_color_tokens`, in the same order."]
+    ]
+    theme_color_values: Vec<Vec4>,
+    @[
+        Resource, Debuggable,
+        Name["Theme size tokens"],
+        Description["The names of the size tokens of the active UI theme, e.g. font sizes, spacing and rounding. Their values are in `theme_size_values`."]
+    ]
+    theme_size_tokens: Vec<String>,
+    @[
+        Resource, Debuggable,
+        Name["Theme size values"],
+        Description["The sizes of the tokens in `theme_size_tokens`, in the same order."]
+    ]
+    theme_size_values: Vec<f32>,
+});
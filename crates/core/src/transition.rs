@@ -0,0 +1,17 @@
+use ambient_ecs::{components, Debuggable, Description, Name, Networked, Store};
+use glam::Vec4;
+
+components!("ui", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Transition duration"],
+        Description["When the `translation`, `width`, `height`, `background_color` or `color` of this UI element changes, e.g. because of its state or its layout, it animates to the new value over this many seconds instead of jumping to it."]
+    ]
+    transition_duration: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Transition curve"],
+        Description["The easing of the transitions of this UI element, as the control points `(x1, y1, x2, y2)` of a cubic Bézier curve from (0, 0) to (1, 1), like CSS's `cubic-bezier`.\nLinear if absent."]
+    ]
+    transition_curve: Vec4,
+});
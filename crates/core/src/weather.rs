@@ -0,0 +1,54 @@
+use ambient_ecs::{components, Debuggable, Description, Name, Networked, Resource, Store};
+use glam::Vec3;
+
+components!("weather", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Rain"],
+        Description["How hard it rains, from 0 (not at all) to 1 (a downpour).\nSet on the synchronized resources, it applies to the whole world."]
+    ]
+    rain: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Snow"],
+        Description["How hard it snows, from 0 (not at all) to 1 (a blizzard).\nSet on the synchronized resources, it applies to the whole world."]
+    ]
+    snow: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Wind"],
+        Description["The velocity of the wind, in metres per second. It blows the rain and the snow, and sways the vegetation.\nSet on the synchronized resources, it applies to the whole world."]
+    ]
+    wind: Vec3,
+    @[
+        Debuggable, Networked, Store,
+        Name["Clear ambience"],
+        Description["The URL of a sound looped while the weather is calm, which fades out as rain, snow or wind pick up.\nSet on the synchronized resources."]
+    ]
+    clear_ambience: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Rain ambience"],
+        Description["The URL of a sound looped while it rains, which fades in with the `rain`.\nSet on the synchronized resources."]
+    ]
+    rain_ambience: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Wind ambience"],
+        Description["The URL of a sound looped while the wind blows, which fades in with the speed of the `wind`.\nSet on the synchronized resources."]
+    ]
+    wind_ambience: String,
+
+    /// The weather on this client, which eases towards the one set by the server
+    @[Debuggable, Resource]
+    weather: Weather,
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Weather {
+    pub rain: f32,
+    pub snow: f32,
+    pub wind: Vec3,
+    /// How wet the surfaces are, from 0 to 1. It rises while it rains, and falls as they dry
+    pub wetness: f32,
+}
@@ -53,4 +53,7 @@ pub enum WindowCtl {
     GrabCursor(CursorGrabMode),
     SetCursorIcon(CursorIcon),
     ShowCursor(bool),
+    /// Resizes the window to this logical size
+    SetSize(UVec2),
+    SetVisible(bool),
 }
@@ -142,6 +142,26 @@ async fn gpu_ecs() {
     test.assert_gpu_cpu_components_eq(b, tomato()).await;
 }
 
+#[tokio::test]
+async fn partial_updates() {
+    let mut test = TestCommon::new().await;
+
+    let ids = (0..100).map(|i| Entity::new().with(carrot(), Vec4::splat(i as f32)).spawn(&mut test.world)).collect::<Vec<_>>();
+    test.update();
+    test.world.set(ids[50], carrot(), Vec4::splat(-1.)).unwrap();
+    test.update();
+    // Only the changed entity is written
+    assert_eq!(test.gpu_world.lock().upload_stats().bytes, std::mem::size_of::<Vec4>() as u64);
+    test.assert_gpu_cpu_components_eq(ids[50], carrot()).await;
+
+    // The last entity is moved into the place of the despawned one
+    test.world.despawn(ids[10]);
+    test.update();
+    for &id in [&ids[..10], &ids[11..]].concat().iter() {
+        test.assert_gpu_cpu_components_eq(id, carrot()).await;
+    }
+}
+
 #[tokio::test]
 async fn gpu_update_with_gpu_run() {
     let mut test = TestCommon::new().await;
@@ -1,27 +1,33 @@
-use std::{num::NonZeroU32, sync::Arc};
+use std::{collections::VecDeque, num::NonZeroU32, sync::Arc, time::Duration};
 
 use ambient_core::{
     asset_cache,
     bounding::world_bounding_sphere,
     camera::shadow_cameras_from_world,
+    gpu,
     hierarchy::{dump_world_hierarchy, dump_world_hierarchy_to_tmp_file},
-    main_scene,
+    main_scene, paused,
     player::local_user_id,
-    runtime,
+    runtime, step_frames,
 };
 use ambient_ecs::{query, World};
 use ambient_ecs_editor::ECSEditor;
 use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
 use ambient_gizmos::{gizmos, GizmoPrimitive};
-use ambient_network::client::{GameClient, GameRpcArgs};
-use ambient_renderer::{RenderTarget, Renderer};
+use ambient_gpu::{multi_buffer::MultiBuffer, texture::Texture, typed_buffer::UntypedBuffer};
+use ambient_network::client::{GameClient, GameClientNetworkStats, GameRpcArgs};
+use ambient_renderer::{GpuTimings, RenderTarget, Renderer};
 use ambient_rpc::RpcRegistry;
-use ambient_std::{asset_cache::SyncAssetKeyExt, cb, color::Color, download_asset::AssetsCacheDir, line_hash, Cb};
+use ambient_std::{asset_cache::SyncAssetKeyExt, cb, color::Color, download_asset::AssetsCacheDir, line_hash, to_byte_unit, Cb};
 use ambient_ui::{
-    fit_horizontal, height, space_between_items, width, Button, ButtonStyle, Dropdown, Fit, FlowColumn, FlowRow, Image, UIExt,
+    fit_horizontal, height, space_between_items, width, Button, ButtonStyle, Dropdown, Fit, FlowColumn, FlowRow, Image, Plot, PlotSeries,
+    StylesExt, Text, UIBase, UIExt,
 };
 use ambient_window_types::{ModifiersState, VirtualKeyCode};
-use glam::Vec3;
+use glam::{vec2, vec4, Vec3, Vec4};
+
+mod log_console;
+pub use log_console::*;
 
 type GetDebuggerState = Cb<dyn Fn(&mut dyn FnMut(&mut Renderer, &RenderTarget, &mut World)) + Sync + Send>;
 
@@ -33,15 +39,50 @@ pub async fn rpc_dump_world_hierarchy(args: GameRpcArgs, _: ()) -> Option<String
     Some(String::from_utf8(res).unwrap())
 }
 
+/// Pauses or resumes the player's instance
+pub async fn rpc_set_paused(args: GameRpcArgs, is_paused: bool) -> Option<()> {
+    let mut state = args.state.lock();
+    let world = state.get_player_world_mut(&args.user_id)?;
+    world.add_resource(paused(), is_paused);
+    Some(())
+}
+
+/// Advances the player's instance by one frame while it's paused
+pub async fn rpc_step_frame(args: GameRpcArgs, _: ()) -> Option<()> {
+    let mut state = args.state.lock();
+    let world = state.get_player_world_mut(&args.user_id)?;
+    step_frame(world);
+    Some(())
+}
+
+fn step_frame(world: &mut World) {
+    let frames = world.resource_opt(step_frames()).copied().unwrap_or(0);
+    world.add_resource(step_frames(), frames + 1);
+}
+
 pub fn register_rpcs(reg: &mut RpcRegistry<GameRpcArgs>) {
     reg.register(rpc_dump_world_hierarchy);
+    reg.register(rpc_set_paused);
+    reg.register(rpc_step_frame);
 }
 
 #[element_component]
-pub fn Debugger(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
+pub fn Debugger(
+    hooks: &mut Hooks,
+    get_state: GetDebuggerState,
+    /// Whether the ECS inspector panel, which the client shows with Shift+F9, is open
+    show_inspector: bool,
+    set_show_inspector: Cb<dyn Fn(bool) + Sync + Send>,
+) -> Element {
     let (show_shadows, set_show_shadows) = hooks.use_state(false);
     let (show_ecs, set_show_ecs) = hooks.use_state(false);
+    let (show_gpu_timings, set_show_gpu_timings) = hooks.use_state(false);
+    let (show_network_stats, set_show_network_stats) = hooks.use_state(false);
+    let (show_asset_memory, set_show_asset_memory) = hooks.use_state(false);
+    let (show_gizmo_categories, set_show_gizmo_categories) = hooks.use_state(false);
+    let (is_paused, set_paused) = hooks.use_state(false);
     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let adapter = hooks.world.resource(gpu()).adapter_info();
     FlowColumn::el([
         FlowRow(vec![
             Button::new("Show entities", {
@@ -74,7 +115,7 @@ pub fn Debugger(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
             .style(ButtonStyle::Flat)
             .el(),
             Button::new("Dump Server World", {
-                let game_client = game_client;
+                let game_client = game_client.clone();
                 move |world| {
                     let game_client = game_client.clone();
                     let cache_dir = AssetsCacheDir.get(world.resource(asset_cache()));
@@ -107,6 +148,10 @@ pub fn Debugger(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
             .hotkey(VirtualKeyCode::F5)
             .style(ButtonStyle::Flat)
             .el(),
+            Button::new("Show Inspector", move |_| set_show_inspector(!show_inspector))
+                .toggled(show_inspector)
+                .style(ButtonStyle::Flat)
+                .el(),
             Button::new("Show Shadow Frustums", {
                 let get_state = get_state.clone();
                 move |_| {
@@ -164,11 +209,73 @@ pub fn Debugger(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
             .hotkey(VirtualKeyCode::F8)
             .style(ButtonStyle::Flat)
             .el(),
+            Button::new("Show GPU Timings", move |_| set_show_gpu_timings(!show_gpu_timings))
+                .toggled(show_gpu_timings)
+                .hotkey_modifier(ModifiersState::SHIFT)
+                .hotkey(VirtualKeyCode::F10)
+                .style(ButtonStyle::Flat)
+                .el(),
+            Button::new("Show Network Stats", move |_| set_show_network_stats(!show_network_stats))
+                .toggled(show_network_stats)
+                .hotkey_modifier(ModifiersState::SHIFT)
+                .hotkey(VirtualKeyCode::F11)
+                .style(ButtonStyle::Flat)
+                .el(),
+            Button::new("Show Asset Memory", move |_| set_show_asset_memory(!show_asset_memory))
+                .toggled(show_asset_memory)
+                .style(ButtonStyle::Flat)
+                .el(),
+            Button::new("Show Gizmo Categories", move |_| set_show_gizmo_categories(!show_gizmo_categories))
+                .toggled(show_gizmo_categories)
+                .style(ButtonStyle::Flat)
+                .el(),
+            Button::new("Pause", {
+                let game_client = game_client.clone();
+                let get_state = get_state.clone();
+                move |world| {
+                    let is_paused = !is_paused;
+                    get_state(&mut |_, _, world| world.add_resource(paused(), is_paused));
+                    let game_client = game_client.clone();
+                    world.resource(runtime()).spawn(async move {
+                        if let Err(err) = game_client.rpc(rpc_set_paused, is_paused).await {
+                            log::error!("Failed to pause the server: {err:?}");
+                        }
+                    });
+                    set_paused(is_paused);
+                }
+            })
+            .toggled(is_paused)
+            .hotkey_modifier(ModifiersState::SHIFT)
+            .hotkey(VirtualKeyCode::Pause)
+            .style(ButtonStyle::Flat)
+            .el(),
+            Button::new("Step Frame", {
+                let get_state = get_state.clone();
+                move |world| {
+                    get_state(&mut |_, _, world| step_frame(world));
+                    let game_client = game_client.clone();
+                    world.resource(runtime()).spawn(async move {
+                        if let Err(err) = game_client.rpc(rpc_step_frame, ()).await {
+                            log::error!("Failed to step the server: {err:?}");
+                        }
+                    });
+                }
+            })
+            .disabled(!is_paused)
+            .hotkey_modifier(ModifiersState::SHIFT)
+            .hotkey(VirtualKeyCode::F9)
+            .style(ButtonStyle::Flat)
+            .el(),
             ShaderDebug { get_state: get_state.clone() }.el(),
+            Text::el(format!("GPU: {} ({:?}, {:?})", adapter.name, adapter.backend, adapter.device_type)).small_style(),
         ])
         .el()
         .set(space_between_items(), 5.),
         if show_shadows { ShadowMapsViz { get_state: get_state.clone() }.el() } else { Element::new() },
+        if show_gpu_timings { GpuTimingsViz { get_state: get_state.clone() }.el() } else { Element::new() },
+        if show_network_stats { NetworkStatsViz.el() } else { Element::new() },
+        if show_asset_memory { AssetMemoryViz.el() } else { Element::new() },
+        if show_gizmo_categories { GizmoCategoriesViz { get_state: get_state.clone() }.el() } else { Element::new() },
         if show_ecs {
             ECSEditor { get_world: cb(move |res| get_state(&mut move |_, _, world| res(world))), on_change: cb(|_, _| {}) }
                 .el()
@@ -181,6 +288,217 @@ pub fn Debugger(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
     .set(fit_horizontal(), Fit::Parent)
 }
 
+/// The number of frames shown in the GPU timings history chart
+const GPU_TIMINGS_HISTORY: usize = 120;
+/// The upper bound of the history chart, in milliseconds
+const GPU_TIMINGS_CHART_MS: f32 = 33.;
+
+#[element_component]
+fn GpuTimingsViz(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
+    let (timings, set_timings) = hooks.use_state(None::<GpuTimings>);
+    let (history, set_history) = hooks.use_state(VecDeque::<f32>::new());
+    // The interval outlives this render, so the history is accumulated here rather than in the captured state
+    let history_ref = hooks.use_ref_with(|_| VecDeque::<f32>::new());
+    hooks.use_interval_deps(Duration::from_millis(100), true, (), move |_| {
+        let mut timings = None;
+        get_state(&mut |renderer, _, _| timings = renderer.gpu_timings());
+        if let Some(timings) = &timings {
+            let mut history = history_ref.lock();
+            history.push_back(timings.total_ms());
+            if history.len() > GPU_TIMINGS_HISTORY {
+                history.pop_front();
+            }
+            set_history(history.clone());
+        }
+        set_timings(timings);
+    });
+
+    let vram = Texture::total_bytes_used() + UntypedBuffer::total_bytes_used() as u64 + MultiBuffer::total_bytes_used() as u64;
+    let Some(timings) = timings else {
+        return Text::el("GPU timings are not available on this adapter (TIMESTAMP_QUERY is not supported)");
+    };
+    let total = timings.total_ms();
+    FlowRow::el([
+        FlowColumn::el(
+            std::iter::once(Text::el(format!("Total: {total:.2} ms, VRAM: ~{} MB", vram / 1_000_000)))
+                .chain(timings.passes.iter().map(|(name, ms)| {
+                    FlowRow::el([
+                        Text::el(format!("{name}: {ms:.2} ms")).set(width(), 150.),
+                        UIBase
+                            .el()
+                            .set(width(), 200. * ms / total.max(0.001))
+                            .set(height(), 10.)
+                            .with_background(Color::rgb(0.3, 0.7, 1.).into()),
+                    ])
+                    .set(space_between_items(), 5.)
+                }))
+                .collect::<Vec<_>>(),
+        ),
+        Plot {
+            series: vec![PlotSeries::from_values("Total (ms)", vec4(0.3, 0.7, 1., 1.), history.iter().copied())],
+            size: vec2(300., 100.),
+            x_range: Some((0., GPU_TIMINGS_HISTORY as f32)),
+            y_range: Some((0., GPU_TIMINGS_CHART_MS)),
+        }
+        .el(),
+    ])
+    .set(space_between_items(), 20.)
+}
+
+/// The number of network stats samples shown in the history charts
+const NETWORK_STATS_HISTORY: usize = 60;
+/// The number of components listed as the top bandwidth consumers
+const NETWORK_STATS_TOP_COMPONENTS: usize = 10;
+
+#[element_component]
+fn NetworkStatsViz(hooks: &mut Hooks) -> Element {
+    let stats = hooks.consume_context::<GameClientNetworkStats>().map(|(stats, _)| stats).unwrap_or_default();
+    let (history, set_history) = hooks.use_state(VecDeque::<GameClientNetworkStats>::new());
+    hooks.use_effect(stats.clone(), {
+        let history = history.clone();
+        move |_, stats| {
+            let mut history = history;
+            history.push_back(stats.clone());
+            if history.len() > NETWORK_STATS_HISTORY {
+                history.pop_front();
+            }
+            set_history(history);
+            Box::new(|_| {})
+        }
+    });
+
+    let chart = |label: String, series: &[(&str, Vec4, fn(&GameClientNetworkStats) -> f32)]| {
+        FlowColumn::el([
+            Text::el(label).small_style(),
+            Plot {
+                series: series
+                    .iter()
+                    .map(|&(name, color, value)| PlotSeries::from_values(name, color, history.iter().map(value)))
+                    .collect(),
+                size: vec2(200., 60.),
+                x_range: Some((0., NETWORK_STATS_HISTORY as f32)),
+                y_range: None,
+            }
+            .el(),
+        ])
+    };
+
+    FlowRow::el([
+        FlowColumn::el(
+            std::iter::once(Text::el("Top bandwidth consumers"))
+                .chain(
+                    stats
+                        .component_bandwidth
+                        .iter()
+                        .take(NETWORK_STATS_TOP_COMPONENTS)
+                        .map(|(path, bytes)| Text::el(format!("{path}: {}/s", to_byte_unit(*bytes))).small_style()),
+                )
+                .collect::<Vec<_>>(),
+        ),
+        chart(format!("RTT: {} ms", stats.latency_ms), &[("RTT (ms)", vec4(1., 1., 0., 1.), |stats| stats.latency_ms as f32)]),
+        chart(
+            format!("Packet loss: {:.1}%", stats.packet_loss * 100.),
+            &[("Packet loss (%)", vec4(1., 0.3, 0.3, 1.), |stats| stats.packet_loss * 100.)],
+        ),
+        chart(
+            format!("Datagrams: {}/s out, {}/s in", stats.datagrams_sent, stats.datagrams_received),
+            &[
+                ("Out", vec4(1., 0.6, 0.2, 1.), |stats| stats.datagrams_sent as f32),
+                ("In", vec4(0.3, 1., 0.3, 1.), |stats| stats.datagrams_received as f32),
+            ],
+        ),
+        chart(
+            format!("Bandwidth: {}/s out, {}/s in", to_byte_unit(stats.bytes_sent), to_byte_unit(stats.bytes_received)),
+            &[
+                ("Out (B/s)", vec4(1., 0.6, 0.2, 1.), |stats| stats.bytes_sent as f32),
+                ("In (B/s)", vec4(0.3, 0.7, 1., 1.), |stats| stats.bytes_received as f32),
+            ],
+        ),
+    ])
+    .set(space_between_items(), 20.)
+}
+
+/// The number of assets listed in the asset memory view
+const ASSET_MEMORY_TOP_ASSETS: usize = 20;
+
+#[element_component]
+fn AssetMemoryViz(hooks: &mut Hooks) -> Element {
+    let assets = hooks.world.resource(asset_cache()).clone();
+    let (report, set_report) = hooks.use_state(Vec::new());
+    hooks.use_interval_deps(Duration::from_millis(500), true, (), move |_| {
+        let report = assets.graph.lock().memory_report(&assets.timeline.lock());
+        set_report(report);
+    });
+
+    let total_gpu_size = report.iter().map(|asset| asset.gpu_size).sum::<u64>();
+    let total_cpu_size = report.iter().map(|asset| asset.cpu_size).sum::<u64>();
+    FlowColumn::el(
+        std::iter::once(Text::el(format!(
+            "{} assets loaded, GPU: {}, CPU: {}",
+            report.len(),
+            to_byte_unit(total_gpu_size),
+            to_byte_unit(total_cpu_size)
+        )))
+        .chain(report.iter().take(ASSET_MEMORY_TOP_ASSETS).map(|asset| {
+            Text::el(format!(
+                "{}: GPU {} ({} with dependencies), CPU {}, {} referrers",
+                asset.key.as_str(),
+                to_byte_unit(asset.gpu_size),
+                to_byte_unit(asset.total_gpu_size),
+                to_byte_unit(asset.cpu_size),
+                asset.referrers
+            ))
+            .small_style()
+        }))
+        .collect::<Vec<_>>(),
+    )
+}
+
+/// Toggles the categories of the timed gizmos, e.g. to only show the debug draws of one system
+#[element_component]
+fn GizmoCategoriesViz(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
+    let (categories, set_categories) = hooks.use_state(Vec::<(String, bool)>::new());
+    let refresh = {
+        let get_state = get_state.clone();
+        move || {
+            let mut categories = Vec::new();
+            get_state(&mut |_, _, world| {
+                let gizmos = world.resource(gizmos());
+                categories =
+                    gizmos.categories().into_iter().map(|category| (category.clone(), gizmos.is_category_visible(&category))).collect();
+            });
+            set_categories(categories);
+        }
+    };
+    hooks.use_interval_deps(Duration::from_millis(500), true, (), {
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+
+    if categories.is_empty() {
+        return Text::el("No gizmos have been drawn with a category yet").small_style();
+    }
+    FlowRow::el(
+        categories
+            .into_iter()
+            .map(|(category, visible)| {
+                Button::new(category.clone(), {
+                    let get_state = get_state.clone();
+                    let refresh = refresh.clone();
+                    move |_| {
+                        get_state(&mut |_, _, world| world.resource(gizmos()).set_category_visible(&category, !visible));
+                        refresh();
+                    }
+                })
+                .toggled(visible)
+                .style(ButtonStyle::Flat)
+                .el()
+            })
+            .collect::<Vec<_>>(),
+    )
+    .set(space_between_items(), 5.)
+}
+
 #[element_component]
 fn ShadowMapsViz(hooks: &mut Hooks, get_state: GetDebuggerState) -> Element {
     let (shadow_cascades, _) = hooks.use_state_with(|_| {
@@ -211,7 +529,7 @@ fn ShadowMapViz(hooks: &mut Hooks, get_state: GetDebuggerState, cascade: u32) ->
         });
         tex.unwrap()
     });
-    Image { texture }.el().set(width(), 200.).set(height(), 200.)
+    Image::new(texture).el().set(width(), 200.).set(height(), 200.)
 }
 
 #[element_component]
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use ambient_core::logging::{log_buffer, LogFilter, LogRecord};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_renderer::color;
+use ambient_settings::{settings, Settings};
+use ambient_std::color::Color;
+use ambient_ui::{
+    fit_horizontal, height, space_between_items, width, Button, ButtonStyle, Fit, FlowColumn, FlowRow, ScrollArea, StylesExt, Text,
+    TextEditor, UIExt, STREET,
+};
+use glam::vec4;
+use itertools::Itertools;
+use tracing::Level;
+
+/// The number of records the console keeps around
+const MAX_RECORDS: usize = 1000;
+/// The number of matching records that are shown at once
+const MAX_SHOWN_RECORDS: usize = 200;
+
+const LEVELS: [Level; 5] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE];
+
+/// Shows the most recent log records, filtered by level and target, and lets the process' log filter be changed at runtime.
+/// A changed filter is saved to the settings.
+#[element_component]
+pub fn LogConsole(hooks: &mut Hooks) -> Element {
+    let buffer = hooks.world.resource(log_buffer()).clone();
+    let (records, set_records) = hooks.use_state(Vec::<LogRecord>::new());
+    let (max_level, set_max_level) = hooks.use_state(Level::INFO);
+    let (target, set_target) = hooks.use_state(String::new());
+    let (filter, set_filter) = hooks.use_state_with(|world| world.resource(log_buffer()).filter().to_string());
+    let (filter_error, set_filter_error) = hooks.use_state(None::<String>);
+
+    // The records read so far, and the index of the next one to read
+    let read = hooks.use_ref_with(|_| (Vec::<LogRecord>::new(), 0_u64));
+    hooks.use_interval_deps(Duration::from_millis(250), true, (), {
+        let buffer = buffer.clone();
+        let read = read.clone();
+        let set_records = set_records.clone();
+        move |_| {
+            let mut read = read.lock();
+            let (records, next_index) = &mut *read;
+            let new_records = buffer.records_since(*next_index);
+            let Some(last) = new_records.last() else { return };
+            *next_index = last.index + 1;
+            records.extend(new_records);
+            records.drain(..records.len().saturating_sub(MAX_RECORDS));
+            set_records(records.clone());
+        }
+    });
+
+    let target_search = target.to_lowercase();
+    let shown = records
+        .iter()
+        .filter(|record| record.level <= max_level && (target_search.is_empty() || record.target.to_lowercase().contains(&target_search)))
+        .collect_vec();
+    let rows = shown
+        .iter()
+        .skip(shown.len().saturating_sub(MAX_SHOWN_RECORDS))
+        .map(|record| {
+            let level_color = match record.level {
+                Level::ERROR => vec4(1., 0.3, 0.3, 1.),
+                Level::WARN => vec4(1., 1., 0., 1.),
+                Level::INFO => vec4(0.5, 1., 0.5, 1.),
+                _ => vec4(0.6, 0.6, 0.6, 1.),
+            };
+            FlowRow::el([
+                Text::el(record.time.format("%H:%M:%S%.3f").to_string()).small_style(),
+                Text::el(record.level.to_string()).set(color(), level_color).set(width(), 50.),
+                Text::el(&record.target).set(color(), vec4(0.6, 0.6, 1., 1.)),
+                Text::el(&record.message),
+            ])
+            .set(space_between_items(), STREET)
+        })
+        .collect_vec();
+
+    FlowColumn::el([
+        FlowRow::el(
+            LEVELS
+                .iter()
+                .map(|&level| {
+                    Button::new(level.to_string(), {
+                        let set_max_level = set_max_level.clone();
+                        move |_| set_max_level(level)
+                    })
+                    .toggled(level == max_level)
+                    .tooltip(format!("Show {level} and more severe records"))
+                    .style(ButtonStyle::Flat)
+                    .el()
+                })
+                .chain([
+                    TextEditor::new(target, set_target).placeholder(Some("Target")).el().set(width(), 200.),
+                    Button::new("Clear", move |_| {
+                        buffer.clear();
+                        read.lock().0.clear();
+                        set_records(Vec::new());
+                    })
+                    .style(ButtonStyle::Flat)
+                    .el(),
+                    Text::el(format!("{} of {} records shown", rows.len(), records.len())).small_style(),
+                ])
+                .collect_vec(),
+        )
+        .set(space_between_items(), STREET),
+        FlowRow::el([
+            TextEditor::new(filter.clone(), set_filter)
+                .placeholder(Some("Log filter, like info,ambient_gpu=debug"))
+                .el()
+                .set(width(), 400.),
+            Button::new("Apply filter", move |world| match filter.parse::<LogFilter>() {
+                Ok(filter) => {
+                    world.resource(log_buffer()).set_filter(filter.clone());
+                    if let Some(settings) = world.resource_mut_opt(settings()) {
+                        settings.logging.filter = filter.to_string();
+                    }
+                    // Only the filter is changed on disk; the other settings in the world may have been overridden from the command line
+                    let mut saved = Settings::load_or_default();
+                    saved.logging.filter = filter.to_string();
+                    if let Err(err) = saved.save() {
+                        log::warn!("Failed to save the log filter: {err:?}");
+                    }
+                    set_filter_error(None);
+                }
+                Err(err) => set_filter_error(Some(err.to_string())),
+            })
+            .style(ButtonStyle::Flat)
+            .el(),
+            match filter_error {
+                Some(err) => Text::el(err).set(color(), vec4(1., 0.3, 0.3, 1.)),
+                None => Element::new(),
+            },
+        ])
+        .set(space_between_items(), STREET),
+        ScrollArea(FlowColumn::el(rows)).el().set(height(), 300.),
+    ])
+    .set(space_between_items(), STREET)
+    .with_background(Color::rgba(0., 0., 0., 0.8).into())
+    .set(fit_horizontal(), Fit::Parent)
+}
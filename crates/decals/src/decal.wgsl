@@ -137,3 +137,14 @@ fn fs_outlines_main(in: VertexOutput, @builtin(front_facing) is_front: bool) ->
     }
     return get_outline(in.instance_index);
 }
+
+@fragment
+fn fs_picking_main(in: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<u32> {
+    let decal = get_decal(in);
+    var material = get_material(decal.material_in);
+
+    if (material.opacity < material.alpha_cutoff) {
+        discard;
+    }
+    return get_picking_id(in.instance_index, in.world_position.xyz);
+}
@@ -57,6 +57,7 @@ impl SyncAssetKey<Arc<RendererShader>> for DecalShaderKey {
             fs_shadow_main: "fs_shadow_main".to_string(),
             fs_forward_main: if self.lit { "fs_forward_lit_main".to_string() } else { "fs_forward_unlit_main".to_string() },
             fs_outline_main: "fs_outlines_main".to_string(),
+            fs_picking_main: "fs_picking_main".to_string(),
             transparent: true,
             double_sided: true,
             depth_write_enabled: false,
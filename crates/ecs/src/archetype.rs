@@ -291,6 +291,13 @@ impl Archetype {
     pub fn get_component_max_content_version(&self, component: ComponentDesc) -> Option<u64> {
         self.components.get(component.index() as _).map(|arch_comp| arch_comp.max_content_version.0.load(Ordering::Acquire))
     }
+    /// The indices of the entities whose `component` was written after the world was at `version`. Entities that were
+    /// moved into the archetype keep the version they were written at.
+    pub fn get_component_changed_indices(&self, component: ComponentDesc, version: u64) -> Vec<usize> {
+        let Some(arch_comp) = self.components.get(component.index() as _) else { return Vec::new() };
+        let content_versions = arch_comp.content_versions.borrow();
+        content_versions.iter().enumerate().filter(|(_, &content_version)| content_version > version).map(|(index, _)| index).collect()
+    }
     /// Data version is like get_component_content_version except it always updates
     pub fn get_component_data_version(&self, component: ComponentDesc) -> Option<u64> {
         self.components.get(component.index() as _).map(|arch_comp| arch_comp.data_version.0.load(Ordering::Acquire))
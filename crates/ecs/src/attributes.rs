@@ -243,6 +243,41 @@ impl<T: ComponentValue> AttributeConstructor<T, &str> for Description {
     }
 }
 
+/// The unit of the value, for display. (e.g. "m/s" or "radians")
+#[derive(Clone)]
+pub struct Unit(pub String);
+impl ComponentAttribute for Unit {}
+impl<T: ComponentValue> AttributeConstructor<T, &str> for Unit {
+    fn construct(store: &mut AttributeStore, value: &str) {
+        store.set(Self(value.to_string()))
+    }
+}
+
+/// The range that the value is expected to be in, so that editors can clamp it. Applies to each element of vectors and
+/// lists. Either bound can be infinite.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    pub min: f64,
+    pub max: f64,
+}
+impl ValueRange {
+    pub fn new(min: Option<f64>, max: Option<f64>) -> Self {
+        Self { min: min.unwrap_or(f64::NEG_INFINITY), max: max.unwrap_or(f64::INFINITY) }
+    }
+    pub fn contains(&self, value: f64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+    pub fn clamp(&self, value: f64) -> f64 {
+        value.max(self.min).min(self.max)
+    }
+}
+impl ComponentAttribute for ValueRange {}
+impl<T: ComponentValue> AttributeConstructor<T, (f64, f64)> for ValueRange {
+    fn construct(store: &mut AttributeStore, (min, max): (f64, f64)) {
+        store.set(Self { min, max })
+    }
+}
+
 /// Indicates that this component was externally added.
 #[derive(Clone)]
 pub struct External;
@@ -16,7 +16,7 @@ use serde::{
 
 use crate::{
     component_traits::IComponentBuffer, with_component_registry, AttributeGuard, AttributeStoreGuard, AttributeStoreGuardMut,
-    ComponentAttribute, ComponentEntry, ComponentPath, ComponentVTable, Debuggable, Description, Name, Serializable,
+    ComponentAttribute, ComponentEntry, ComponentPath, ComponentVTable, Debuggable, Description, Name, Serializable, Unit, ValueRange,
 };
 
 pub trait ComponentValueBase: Send + Sync + Downcast + 'static {
@@ -207,6 +207,16 @@ impl ComponentDesc {
         Some(self.attribute::<Description>()?.0.clone())
     }
 
+    /// The unit of the value, if available. Corresponds to the [Unit] attribute.
+    pub fn unit(&self) -> Option<String> {
+        Some(self.attribute::<Unit>()?.0.clone())
+    }
+
+    /// The range the value is expected to be in, if available. Corresponds to the [ValueRange] attribute.
+    pub fn value_range(&self) -> Option<ValueRange> {
+        self.attribute::<ValueRange>().map(|range| *range)
+    }
+
     pub fn type_name(&self) -> &'static str {
         (self.vtable.get_type_name)()
     }
@@ -43,6 +43,10 @@ pub struct ExternalComponentDesc {
 pub struct ExternalComponentAttributes {
     pub name: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub range: Option<ValueRange>,
     pub flags: ExternalComponentFlagAttributes,
 }
 impl ExternalComponentAttributes {
@@ -50,6 +54,8 @@ impl ExternalComponentAttributes {
         Self {
             name: desc.attribute::<Name>().map(|n| n.0.clone()),
             description: desc.attribute::<Description>().map(|n| n.0.clone()),
+            unit: desc.unit(),
+            range: desc.value_range(),
             flags: ExternalComponentFlagAttributes::from_existing_component(desc),
         }
     }
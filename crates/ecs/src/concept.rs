@@ -1,9 +1,13 @@
-//! At present, an entirely informational mechanism for describing groups of components that, when used together,
-//! result in some behaviour.
+//! A mechanism for describing groups of components that, when used together, result in some behaviour. Concepts aren't
+//! enforced, but the entities which declare the [concept](crate::concept()) they were made from can be validated against it.
 //!
 //! See the project manifest documentation for more information.
 
-use crate::Entity;
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::{concept, concept_requirements, query, ComponentRegistry, DynSystem, Entity, EntityId, World};
 
 #[derive(Clone)]
 pub struct Concept {
@@ -32,3 +36,68 @@ impl<'a> RefConcept<'a> {
         }
     }
 }
+impl Concept {
+    pub fn requirements(&self) -> ConceptRequirements {
+        ConceptRequirements {
+            id: self.id.clone(),
+            extends: self.extends.clone(),
+            components: self.data.iter().map(|entry| entry.desc().path()).collect(),
+        }
+    }
+}
+
+/// The components that the entities made from a concept must have, without their default values
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConceptRequirements {
+    pub id: String,
+    pub extends: Vec<String>,
+    /// The paths of the components of the concept itself; those of the concepts it extends aren't repeated
+    pub components: Vec<String>,
+}
+
+/// The paths of the components of `concept` (including those of the concepts it extends) that `id` lacks.
+/// Components which aren't registered are reported as missing
+pub fn missing_concept_components(
+    world: &World,
+    id: EntityId,
+    concept: &str,
+    concepts: &HashMap<String, ConceptRequirements>,
+) -> Result<Vec<String>, UnknownConcept> {
+    let mut missing = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![concept.to_string()];
+    while let Some(concept) = pending.pop() {
+        if !visited.insert(concept.clone()) {
+            continue;
+        }
+        let requirements = concepts.get(&concept).ok_or_else(|| UnknownConcept(concept.clone()))?;
+        missing.extend(
+            requirements
+                .components
+                .iter()
+                .filter(|path| !ComponentRegistry::get().get_by_path(path).map_or(false, |desc| world.has_component(id, desc)))
+                .cloned(),
+        );
+        pending.extend(requirements.extends.iter().cloned());
+    }
+    Ok(missing)
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("There is no concept `{0}`")]
+pub struct UnknownConcept(pub String);
+
+/// Warns about the spawned entities which lack some of the components of the [concept](crate::concept()) they were made
+/// from. Only runs when the [concept_requirements] resource is present, as it's opt-in
+pub fn concept_validation_system() -> DynSystem {
+    query(concept()).spawned().to_system(|q, world, qs, _| {
+        let Some(concepts) = world.resource_opt(concept_requirements()) else { return };
+        for (id, concept) in q.collect_cloned(world, qs) {
+            match missing_concept_components(world, id, &concept, concepts) {
+                Ok(missing) if missing.is_empty() => {}
+                Ok(missing) => log::warn!("Entity {id} is a `{concept}`, but lacks {}", missing.join(", ")),
+                Err(err) => log::warn!("Entity {id}: {err}"),
+            }
+        }
+    })
+}
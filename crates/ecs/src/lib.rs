@@ -21,6 +21,8 @@ use parking_lot::Mutex;
 /// Expose to macros
 #[doc(hidden)]
 pub use paste;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -97,6 +99,17 @@ components!("ecs", {
         Description["Indicates that this entity shouldn't be despawned when the module that spawned it unloads."]
     ]
     dont_despawn_on_unload: (),
+    @[
+        Networked, Store, Debuggable,
+        Name["Concept"],
+        Description["The concept this entity was made from, e.g. `perspective_camera`. Entities with this are checked for the components of the concept when concept validation is enabled."]
+    ]
+    concept: String,
+    @[
+        Resource,
+        Description["The concepts that entities are validated against, by id. Concept validation is enabled when this is present."]
+    ]
+    concept_requirements: HashMap<String, ConceptRequirements>,
     @[
         Resource,
         Description["A global general event queue for this ecs World. Can be used to dispatch or listen to any kinds of events."]
@@ -115,6 +128,8 @@ pub struct World {
     /// Used for reset_events. Prevents change events in queries when you use reset_events
     ignore_query_inits: bool,
     query_ticker: CloneableAtomicU64,
+    /// When set, the ids of the spawned entities are drawn from this instead of being random; see [World::seed_entity_ids]
+    entity_id_generator: Option<Pcg64>,
 }
 impl World {
     pub fn new(name: &'static str) -> Self {
@@ -133,6 +148,7 @@ impl World {
             shape_change_events: None,
             ignore_query_inits: false,
             query_ticker: CloneableAtomicU64::new(0),
+            entity_id_generator: None,
         };
         if resources {
             world.spawn_with_id(EntityId::resources(), Entity::new());
@@ -170,7 +186,7 @@ impl World {
     }
 
     pub fn batch_spawn(&mut self, entity_data: Entity, count: usize) -> Vec<EntityId> {
-        let ids = (0..count).map(|_| EntityId::new()).collect_vec();
+        let ids = (0..count).map(|_| self.new_entity_id()).collect_vec();
         for id in &ids {
             self.locs.insert(*id, EntityLocation::empty());
         }
@@ -178,6 +194,18 @@ impl World {
         ids
     }
 
+    /// Makes the ids of the entities spawned from now on only depend on `seed` and on the order they're spawned in, instead of
+    /// being random, so that the same world is produced on every run and platform
+    pub fn seed_entity_ids(&mut self, seed: u64) {
+        self.entity_id_generator = Some(Pcg64::seed_from_u64(seed));
+    }
+    fn new_entity_id(&mut self) -> EntityId {
+        match &mut self.entity_id_generator {
+            Some(generator) => EntityId(generator.gen()),
+            None => EntityId::new(),
+        }
+    }
+
     /// Returns false if the id already exists
     pub fn spawn_with_id(&mut self, entity_id: EntityId, entity_data: Entity) -> bool {
         if let std::collections::hash_map::Entry::Vacant(e) = self.locs.entry(entity_id) {
@@ -524,7 +552,8 @@ impl World {
         }
         old_to_new_ids.into_values().collect()
     }
-    fn version(&self) -> u64 {
+    /// Incremented by every change to the world, so that the content versions of components can be compared with it
+    pub fn version(&self) -> u64 {
         self.version.0.load(Ordering::Relaxed)
     }
     fn inc_version(&self) -> u64 {
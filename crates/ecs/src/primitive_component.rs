@@ -6,7 +6,7 @@ use paste::paste;
 
 use crate::{
     AttributeConstructor, AttributeStore, ComponentDesc, ComponentRegistry, ComponentVTable, Description, EntityId,
-    ExternalComponentAttributes, Name,
+    ExternalComponentAttributes, Name, Unit, ValueRange,
 };
 
 /// A mapping from enum names to Rust types. Instantiate this with a macro that takes `$(($value:ident, $type:ty)),*`.
@@ -44,6 +44,12 @@ macro_rules! build_attribute_registration {
         if let Some(description) = $attributes.description {
             <Description as AttributeConstructor<$type, _>>::construct(&mut $store, &description);
         }
+        if let Some(unit) = $attributes.unit {
+            <Unit as AttributeConstructor<$type, _>>::construct(&mut $store, &unit);
+        }
+        if let Some(range) = $attributes.range {
+            <ValueRange as AttributeConstructor<$type, _>>::construct(&mut $store, (range.min, range.max));
+        }
         $attributes.flags.construct_for_store::<$type>(&mut $store);
 
         static VTABLE: &ComponentVTable<$type> = &ComponentVTable::construct_external();
@@ -222,3 +222,15 @@ fn can_add_a_resource() {
     init();
     World::new("can_add_a_resource").add_resource(a_resource(), ());
 }
+
+#[test]
+fn seeded_entity_ids() {
+    init();
+    let spawn = |seed| {
+        let mut world = World::new("seeded_entity_ids");
+        world.seed_entity_ids(seed);
+        (0..3).map(|_| world.spawn(Entity::new().with(a(), 1.))).collect_vec()
+    };
+    assert_eq!(spawn(42), spawn(42));
+    assert_ne!(spawn(42), spawn(43));
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use ambient_core::name;
+use ambient_ecs::{ComponentDesc, ComponentEntry, EntityId, World};
+use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_renderer::color;
+use ambient_std::{cb, color::Color, Cb};
+use ambient_ui::{
+    fit_horizontal, height, space_between_items, width, Button, ButtonStyle, Fit, FlowColumn, FlowRow, ScrollArea, StylesExt, Text,
+    TextEditor, Tooltip, UIExt, STREET,
+};
+use glam::{vec4, Vec2, Vec3, Vec4};
+use itertools::Itertools;
+
+/// The maximum number of entities listed at once; narrow the search to find the others
+const MAX_LISTED_ENTITIES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+struct ArchetypeSummary {
+    components: Vec<String>,
+    entities: Vec<EntityId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct EntitySummary {
+    id: EntityId,
+    name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct InspectorState {
+    archetypes: Vec<ArchetypeSummary>,
+    entities: Vec<EntitySummary>,
+    total_entities: usize,
+    /// The components of the inspected entity, and their values
+    inspected: Option<Vec<InspectedComponent>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct InspectedComponent {
+    path: String,
+    value: String,
+    description: Option<String>,
+    unit: Option<String>,
+    /// Whether the value is outside of the range declared for the component
+    out_of_range: bool,
+}
+impl InspectedComponent {
+    fn new(entry: &ComponentEntry) -> Self {
+        let desc = entry.desc();
+        let out_of_range = desc.value_range().map_or(false, |range| numeric_values(entry).iter().any(|&value| !range.contains(value)));
+        Self {
+            path: desc.path(),
+            value: format!("{:?}", entry.as_debug()),
+            description: desc.description().filter(|description| !description.is_empty()),
+            unit: desc.unit(),
+            out_of_range,
+        }
+    }
+}
+
+/// The numbers in the value of `entry`, if it's a number or a vector of them
+fn numeric_values(entry: &ComponentEntry) -> Vec<f64> {
+    if let Some(value) = entry.try_downcast_ref::<f32>() {
+        vec![*value as f64]
+    } else if let Some(value) = entry.try_downcast_ref::<f64>() {
+        vec![*value]
+    } else if let Some(value) = entry.try_downcast_ref::<i32>() {
+        vec![*value as f64]
+    } else if let Some(value) = entry.try_downcast_ref::<u32>() {
+        vec![*value as f64]
+    } else if let Some(value) = entry.try_downcast_ref::<Vec2>() {
+        value.to_array().map(f64::from).to_vec()
+    } else if let Some(value) = entry.try_downcast_ref::<Vec3>() {
+        value.to_array().map(f64::from).to_vec()
+    } else if let Some(value) = entry.try_downcast_ref::<Vec4>() {
+        value.to_array().map(f64::from).to_vec()
+    } else if let Some(values) = entry.try_downcast_ref::<Vec<f32>>() {
+        values.iter().map(|&value| value as f64).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+fn component_paths(components: Vec<ComponentDesc>) -> Vec<String> {
+    components.into_iter().map(|c| c.path()).sorted().collect()
+}
+
+fn read_state(world: &World, search: &str, archetype: Option<usize>, inspected: Option<EntityId>) -> InspectorState {
+    let search = search.to_lowercase();
+    let archetypes = world
+        .archetypes()
+        .iter()
+        .filter(|arch| arch.entity_count() > 0)
+        .filter_map(|arch| {
+            let entities = (0..arch.entity_count()).map(|i| arch.get_entity_id_from_index(i)).collect_vec();
+            let components = component_paths(world.get_components(entities[0]).ok()?);
+            Some(ArchetypeSummary { components, entities })
+        })
+        .sorted_by_key(|arch| std::cmp::Reverse(arch.entities.len()))
+        .collect_vec();
+    let candidates = match archetype.and_then(|i| archetypes.get(i)) {
+        Some(arch) => arch.entities.clone(),
+        None => archetypes.iter().flat_map(|arch| arch.entities.iter().copied()).collect(),
+    };
+    let matching = candidates
+        .into_iter()
+        .filter(|&id| {
+            search.is_empty()
+                || id.to_string().to_lowercase().contains(&search)
+                || world.get_ref(id, name()).map(|name| name.to_lowercase().contains(&search)).unwrap_or(false)
+                || world.get_components(id).map(|comps| comps.iter().any(|c| c.path().contains(&search))).unwrap_or(false)
+        })
+        .collect_vec();
+    InspectorState {
+        total_entities: matching.len(),
+        entities: matching
+            .into_iter()
+            .take(MAX_LISTED_ENTITIES)
+            .map(|id| EntitySummary { id, name: world.get_ref(id, name()).ok().cloned() })
+            .collect(),
+        inspected: inspected
+            .and_then(|id| world.clone_entity(id).ok())
+            .map(|data| data.iter().map(InspectedComponent::new).sorted_by(|a, b| a.path.cmp(&b.path)).collect()),
+        archetypes,
+    }
+}
+
+/// A read-only, live view of a world: its archetypes, its entities and their component values.
+///
+/// Unlike the editor, this only needs access to the world, so it can be used in any client.
+#[derive(Debug, Clone)]
+pub struct ECSInspector {
+    pub get_world: Cb<dyn Fn(Cb<dyn Fn(&World) + Sync + Send>) + Sync + Send>,
+}
+impl ElementComponent for ECSInspector {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let Self { get_world } = *self;
+        let (search, set_search) = hooks.use_state(String::new());
+        let (archetype, set_archetype) = hooks.use_state(None::<usize>);
+        let (inspected, set_inspected) = hooks.use_state(None::<EntityId>);
+        let (state, set_state) = hooks.use_state(InspectorState::default());
+
+        let prev_state = hooks.use_ref_with(|_| InspectorState::default());
+        hooks.use_interval_deps(
+            Duration::from_millis(250),
+            true,
+            (search.clone(), archetype, inspected),
+            move |(search, archetype, inspected)| {
+                let (search, archetype, inspected) = (search.clone(), *archetype, *inspected);
+                let prev_state = prev_state.clone();
+                let set_state = set_state.clone();
+                get_world(cb(move |world| {
+                    let state = read_state(world, &search, archetype, inspected);
+                    let mut prev_state = prev_state.lock();
+                    if *prev_state != state {
+                        *prev_state = state.clone();
+                        set_state(state);
+                    }
+                }));
+            },
+        );
+
+        let archetypes = FlowColumn::el(
+            state
+                .archetypes
+                .iter()
+                .enumerate()
+                .map(|(i, arch)| {
+                    Button::new(format!("{} × {}", arch.entities.len(), arch.components.join(", ")), {
+                        let set_archetype = set_archetype.clone();
+                        move |_| set_archetype(if archetype == Some(i) { None } else { Some(i) })
+                    })
+                    .toggled(archetype == Some(i))
+                    .style(ButtonStyle::Flat)
+                    .el()
+                })
+                .collect_vec(),
+        );
+        let entities = FlowColumn::el(
+            state
+                .entities
+                .iter()
+                .map(|entity| {
+                    let label = match &entity.name {
+                        Some(name) => format!("{} {name}", entity.id),
+                        None => entity.id.to_string(),
+                    };
+                    let id = entity.id;
+                    Button::new(label, {
+                        let set_inspected = set_inspected.clone();
+                        move |_| set_inspected(Some(id))
+                    })
+                    .toggled(inspected == Some(id))
+                    .style(ButtonStyle::Flat)
+                    .el()
+                })
+                .collect_vec(),
+        );
+        let components = match (inspected, state.inspected) {
+            (Some(id), Some(components)) => FlowColumn::el(
+                std::iter::once(Text::el(id.to_string()).header_style())
+                    .chain(components.into_iter().map(|component| {
+                        let label = Text::el(format!("{}:", component.path)).set(color(), vec4(1., 1., 0., 1.));
+                        let label = match component.description {
+                            Some(description) => Tooltip { inner: label, tooltip: Text::el(description) }.el(),
+                            None => label,
+                        };
+                        let value = match &component.unit {
+                            Some(unit) => format!("{} {unit}", component.value),
+                            None => component.value,
+                        };
+                        let value = if component.out_of_range {
+                            Text::el(format!("{value} (out of range)")).set(color(), vec4(1., 0.3, 0.3, 1.))
+                        } else {
+                            Text::el(value)
+                        };
+                        FlowRow::el([label, value]).set(space_between_items(), STREET)
+                    }))
+                    .collect_vec(),
+            ),
+            (Some(id), None) => Text::el(format!("{id} no longer exists")),
+            (None, _) => Text::el("Select an entity to inspect it"),
+        };
+
+        FlowColumn::el([
+            FlowRow::el([
+                TextEditor::new(search, set_search).placeholder(Some("Search by id, name or component")).el().set(width(), 300.),
+                Text::el(format!(
+                    "{} of {} entities shown, {} archetypes",
+                    state.entities.len(),
+                    state.total_entities,
+                    state.archetypes.len()
+                )),
+            ])
+            .set(space_between_items(), STREET),
+            FlowRow::el([
+                ScrollArea(archetypes).el().set(width(), 300.).set(height(), 400.),
+                ScrollArea(entities).el().set(width(), 250.).set(height(), 400.),
+                ScrollArea(components).el().set(height(), 400.),
+            ])
+            .set(space_between_items(), STREET),
+        ])
+        .set(space_between_items(), STREET)
+        .with_background(Color::rgba(0., 0., 0., 0.8).into())
+        .set(fit_horizontal(), Fit::Parent)
+    }
+}
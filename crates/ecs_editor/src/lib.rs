@@ -8,6 +8,9 @@ use ambient_ui::{fit_horizontal, space_between_items, Button, ButtonStyle, Fit,
 use glam::{vec4, Vec4};
 use itertools::Itertools;
 
+mod inspector;
+pub use inspector::*;
+
 #[derive(Debug, Clone)]
 pub struct ECSEditor {
     pub get_world: Cb<dyn Fn(Cb<dyn Fn(&World) + Sync + Send>) + Sync + Send>,
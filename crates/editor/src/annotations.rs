@@ -0,0 +1,42 @@
+use ambient_core::{game_mode, transform::translation, GameMode};
+use ambient_ecs::{components, dont_store, query, Debuggable, Entity, FnSystem, Networked, SystemGroup};
+use ambient_network::{server::ForkingEvent, ServerWorldExt};
+use glam::Vec3;
+
+components!("editor", {
+    /// A note left in the scene for other level designers. The text is shown at the entity's `translation`,
+    /// with an arrow pointing to the `annotation_target`.
+    ///
+    /// Annotations are saved with the scene, but not with the world, and are removed when it's played or built.
+    @[Debuggable, Networked]
+    annotation: String,
+    /// The world space point an annotation points at
+    @[Debuggable, Networked]
+    annotation_target: Vec3,
+});
+
+/// The height above its target that an annotation's text is placed at
+pub const ANNOTATION_HEIGHT: f32 = 2.;
+
+pub fn annotation_entity(text: String, target: Vec3) -> Entity {
+    Entity::new()
+        .with(annotation(), text)
+        .with(annotation_target(), target)
+        .with(translation(), target + Vec3::Z * ANNOTATION_HEIGHT)
+        .with(dont_store(), ())
+}
+
+/// Removes the annotations from instances that are forked to be played
+pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
+    SystemGroup::new(
+        "editor/on_forking_systems",
+        vec![Box::new(FnSystem::new(|world, _| {
+            if world.synced_resource(game_mode()) != Some(&GameMode::Play) {
+                return;
+            }
+            for (id, _) in query(()).incl(annotation()).collect_cloned(world, None) {
+                world.despawn(id);
+            }
+        }))],
+    )
+}
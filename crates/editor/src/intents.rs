@@ -1,9 +1,11 @@
 use ambient_core::player::get_player_by_user_id;
 use ambient_core::{
-    self, selectable, snap_to_ground,
-    transform::{get_world_transform, rotation, scale, translation},
+    self,
+    hierarchy::{add_child, children, descendants, parent},
+    main_scene, name, selectable, snap_to_ground,
+    transform::{get_world_transform, local_to_parent, rotation, scale, translation},
 };
-use ambient_ecs::{components, Entity, EntityId, World};
+use ambient_ecs::{components, query, Entity, EntityId, World};
 use ambient_intent::{use_old_state, IntentContext, IntentRegistry};
 use ambient_physics::{collider::collider_shapes_convex, main_physics_scene, physx::rigid_actor, PxShapeUserData};
 use anyhow::Context;
@@ -16,7 +18,10 @@ use ordered_float::OrderedFloat;
 use physxx::{PxActor, PxQueryFilterData, PxRaycastCallback, PxTransform, PxUserData};
 use serde::{Deserialize, Serialize};
 
-use crate::{selection, ui::entity_editor::EntityComponentChange, Selection};
+use crate::{
+    editor_hidden, editor_layer, editor_locked, layer, presence::transform_lock_holder, selection,
+    ui::entity_editor::EntityComponentChange, Selection,
+};
 use ambient_prefab::prefab_from_url;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -67,8 +72,41 @@ components!("editor", {
     intent_delete_undo: (World, Selection),
     intent_component_change: (EntityId, EntityComponentChange),
     intent_component_change_undo: (EntityId, EntityComponentChange),
+    intent_reparent: IntentReparent,
+    intent_reparent_undo: Vec<(EntityId, Option<EntityId>)>,
+    /// Hides or shows entities (and their children) in the editor
+    intent_set_hidden: (Vec<EntityId>, bool),
+    intent_set_hidden_undo: Vec<(EntityId, bool)>,
+    /// Locks entities, which stops them from being selected in the viewport
+    intent_set_locked: (Vec<EntityId>, bool),
+    intent_set_locked_undo: Vec<(EntityId, bool)>,
+    /// Creates a new, empty layer with the given id and name
+    intent_create_layer: (EntityId, String),
+    intent_create_layer_undo: EntityId,
+    /// Moves entities to a layer, or removes them from their layer if None
+    intent_set_layer: (Vec<EntityId>, Option<EntityId>),
+    intent_set_layer_undo: Vec<LayerMembership>,
+    /// Hides or shows a layer along with all of its entities
+    intent_set_layer_hidden: (EntityId, bool),
+    /// Locks or unlocks a layer along with all of its entities
+    intent_set_layer_locked: (EntityId, bool),
 });
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LayerMembership {
+    pub id: EntityId,
+    pub layer: Option<EntityId>,
+    pub hidden: bool,
+    pub locked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntentReparent {
+    pub entities: Vec<EntityId>,
+    /// Moves the entities to the root of the scene if None
+    pub parent: Option<EntityId>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct IntentTransform {
     pub entities: Vec<EntityId>,
@@ -238,6 +276,14 @@ fn resolve_clipping(world: &mut World, entities: &[EntityId], ids: &[EntityId],
         .max_by_key(|v| ordered_float::NotNan::new(-v.dot(dir)).unwrap())
 }
 
+/// Fails if another user is transforming one of `entities`, see [crate::presence]
+fn ensure_not_transformed_by_others(world: &World, user_id: &str, entities: &[EntityId]) -> anyhow::Result<()> {
+    match transform_lock_holder(world, user_id, entities) {
+        Some(holder) => anyhow::bail!("{holder} is transforming these entities"),
+        None => Ok(()),
+    }
+}
+
 pub fn register_intents(reg: &mut IntentRegistry) {
     reg.register(
         intent_place_ray(),
@@ -245,6 +291,7 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         |ctx, IntentPlaceRay { targets, ray, snap }| {
             profiling::scope!("handle_intent_move");
             let world = ctx.world;
+            ensure_not_transformed_by_others(world, ctx.user_id, &targets)?;
 
             enum SurfaceOffset {
                 Keep { _normal: Vec3 },
@@ -342,6 +389,7 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         |ctx, IntentTranslate { targets, position }| {
             profiling::scope!("handle_intent_move");
             let world = ctx.world;
+            ensure_not_transformed_by_others(world, ctx.user_id, &targets)?;
 
             enum SurfaceOffset {
                 Keep { _normal: Vec3 },
@@ -395,6 +443,7 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         intent_set_transform_undo(),
         |ctx, intent| {
             let world = ctx.world;
+            ensure_not_transformed_by_others(world, ctx.user_id, &intent.entities)?;
             intent
                 .entities
                 .iter()
@@ -617,6 +666,189 @@ pub fn register_intents(reg: &mut IntentRegistry) {
         },
         use_old_state,
     );
+    reg.register(
+        intent_reparent(),
+        intent_reparent_undo(),
+        |ctx, IntentReparent { entities, parent: new_parent }| {
+            let world = ctx.world;
+            // Nothing is changed unless every entity can be moved
+            for &id in &entities {
+                check_reparent(world, id, new_parent)?;
+            }
+            entities
+                .into_iter()
+                .map(|id| {
+                    let old_parent = world.get(id, parent()).ok();
+                    reparent(world, id, new_parent)?;
+                    Ok((id, old_parent))
+                })
+                .collect()
+        },
+        |ctx, old_parents| {
+            let world = ctx.world;
+            for &(id, old_parent) in &old_parents {
+                check_reparent(world, id, old_parent)?;
+            }
+            for (id, old_parent) in old_parents.into_iter().rev() {
+                reparent(world, id, old_parent)?;
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_set_hidden(),
+        intent_set_hidden_undo(),
+        |ctx, (entities, hidden)| {
+            let world = ctx.world;
+            Ok(entities
+                .into_iter()
+                .map(|id| {
+                    let was_hidden = world.has_component(id, editor_hidden());
+                    set_hidden(world, id, hidden);
+                    (id, was_hidden)
+                })
+                .collect())
+        },
+        |ctx, old| {
+            let world = ctx.world;
+            for (id, hidden) in old {
+                set_hidden(world, id, hidden);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_set_locked(),
+        intent_set_locked_undo(),
+        |ctx, (entities, locked)| {
+            let world = ctx.world;
+            Ok(entities
+                .into_iter()
+                .map(|id| {
+                    let was_locked = world.has_component(id, editor_locked());
+                    set_locked(world, id, locked);
+                    (id, was_locked)
+                })
+                .collect())
+        },
+        |ctx, old| {
+            let world = ctx.world;
+            for (id, locked) in old {
+                set_locked(world, id, locked);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_create_layer(),
+        intent_create_layer_undo(),
+        |ctx, (id, layer_name)| {
+            ctx.world.spawn_with_id(id, Entity::new().with(name(), layer_name).with_default(editor_layer()));
+            Ok(id)
+        },
+        |ctx, id| {
+            // Entities may have been moved to the layer since, e.g. by other users, and shouldn't point at a despawned layer
+            for member in layer_members(ctx.world, id) {
+                set_layer(ctx.world, member, None);
+            }
+            ctx.world.despawn(id);
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_set_layer(),
+        intent_set_layer_undo(),
+        |ctx, (entities, new_layer)| {
+            let world = ctx.world;
+            if let Some(new_layer) = new_layer {
+                anyhow::ensure!(world.has_component(new_layer, editor_layer()), "{new_layer} is not a layer");
+            }
+            let layer_hidden = new_layer.map(|id| world.has_component(id, editor_hidden())).unwrap_or(false);
+            let layer_locked = new_layer.map(|id| world.has_component(id, editor_locked())).unwrap_or(false);
+            Ok(entities
+                .into_iter()
+                .map(|id| {
+                    let old = LayerMembership {
+                        id,
+                        layer: world.get(id, layer()).ok(),
+                        hidden: world.has_component(id, editor_hidden()),
+                        locked: world.has_component(id, editor_locked()),
+                    };
+                    set_layer(world, id, new_layer);
+                    // Entities take on the state of the layer they're moved to, unless neither layer is hidden or locked
+                    let old_layer_hidden = old.layer.map(|id| world.has_component(id, editor_hidden())).unwrap_or(false);
+                    let old_layer_locked = old.layer.map(|id| world.has_component(id, editor_locked())).unwrap_or(false);
+                    if layer_hidden || old_layer_hidden {
+                        set_hidden(world, id, layer_hidden);
+                    }
+                    if layer_locked || old_layer_locked {
+                        set_locked(world, id, layer_locked);
+                    }
+                    old
+                })
+                .collect())
+        },
+        |ctx, old| {
+            let world = ctx.world;
+            for LayerMembership { id, layer, hidden, locked } in old.into_iter().rev() {
+                set_layer(world, id, layer);
+                set_hidden(world, id, hidden);
+                set_locked(world, id, locked);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_set_layer_hidden(),
+        intent_set_hidden_undo(),
+        |ctx, (id, hidden)| {
+            let world = ctx.world;
+            Ok(std::iter::once(id)
+                .chain(layer_members(world, id))
+                .map(|id| {
+                    let was_hidden = world.has_component(id, editor_hidden());
+                    set_hidden(world, id, hidden);
+                    (id, was_hidden)
+                })
+                .collect())
+        },
+        |ctx, old| {
+            let world = ctx.world;
+            for (id, hidden) in old {
+                set_hidden(world, id, hidden);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
+    reg.register(
+        intent_set_layer_locked(),
+        intent_set_locked_undo(),
+        |ctx, (id, locked)| {
+            let world = ctx.world;
+            Ok(std::iter::once(id)
+                .chain(layer_members(world, id))
+                .map(|id| {
+                    let was_locked = world.has_component(id, editor_locked());
+                    set_locked(world, id, locked);
+                    (id, was_locked)
+                })
+                .collect())
+        },
+        |ctx, old| {
+            let world = ctx.world;
+            for (id, locked) in old {
+                set_locked(world, id, locked);
+            }
+            Ok(())
+        },
+        use_old_state,
+    );
 
     ambient_terrain::intents::register_intents(reg);
     // Box::new(common_intent_systems()),
@@ -683,3 +915,86 @@ fn set_snap_to_ground(world: &mut World, id: EntityId, height: f32) {
     // Modify the transformed z value
     world.add_component(id, snap_to_ground(), height).expect("Invalid entity");
 }
+
+/// Moves `id` to `new_parent`, while keeping its world transform
+/// Fails if `id` can't be made a child of `new_parent`, or a root if it's `None`
+fn check_reparent(world: &World, id: EntityId, new_parent: Option<EntityId>) -> anyhow::Result<()> {
+    anyhow::ensure!(world.exists(id), "The entity {id} doesn't exist");
+    get_world_transform(world, id).with_context(|| format!("{id} has no transform"))?;
+    if let Some(new_parent) = new_parent {
+        anyhow::ensure!(world.exists(new_parent), "The parent {new_parent} doesn't exist");
+        get_world_transform(world, new_parent).with_context(|| format!("The parent {new_parent} has no transform"))?;
+        let mut ancestor = Some(new_parent);
+        while let Some(current) = ancestor {
+            anyhow::ensure!(current != id, "Can't make {id} a child of its own descendant {new_parent}");
+            ancestor = world.get(current, parent()).ok();
+        }
+    }
+    Ok(())
+}
+
+fn reparent(world: &mut World, id: EntityId, new_parent: Option<EntityId>) -> anyhow::Result<()> {
+    let transform = get_world_transform(world, id).context("No transform")?;
+    if let Ok(old_parent) = world.get(id, parent()) {
+        if let Ok(siblings) = world.get_mut(old_parent, children()) {
+            siblings.retain(|&child| child != id);
+        }
+    }
+    let local_transform = match new_parent {
+        Some(new_parent) => {
+            let parent_transform = get_world_transform(world, new_parent).context("No parent transform")?;
+            add_child(world, new_parent, id)?;
+            world.add_component(id, parent(), new_parent)?;
+            world.add_component(id, local_to_parent(), Mat4::IDENTITY)?;
+            parent_transform.inverse() * transform
+        }
+        None => {
+            world.remove_components(id, vec![parent().into(), local_to_parent().into()])?;
+            transform
+        }
+    };
+    let (scl, rot, pos) = local_transform.to_scale_rotation_translation();
+    world.add_component(id, translation(), pos)?;
+    world.add_component(id, rotation(), rot)?;
+    world.add_component(id, scale(), scl)?;
+    Ok(())
+}
+
+/// Hiding removes the entity and its descendants from the main scene; [editor_hidden] remembers which of them were in it
+fn set_hidden(world: &mut World, id: EntityId, hidden: bool) {
+    if hidden == world.has_component(id, editor_hidden()) {
+        return;
+    }
+    for id in std::iter::once(id).chain(descendants(world, id)) {
+        if hidden {
+            let in_main_scene = world.has_component(id, main_scene());
+            world.remove_component(id, main_scene()).ok();
+            world.add_component(id, editor_hidden(), in_main_scene).ok();
+        } else if let Ok(in_main_scene) = world.get(id, editor_hidden()) {
+            world.remove_component(id, editor_hidden()).ok();
+            if in_main_scene {
+                world.add_component(id, main_scene(), ()).ok();
+            }
+        }
+    }
+}
+
+fn set_locked(world: &mut World, id: EntityId, locked: bool) {
+    if locked {
+        world.add_component(id, editor_locked(), ()).ok();
+    } else {
+        world.remove_component(id, editor_locked()).ok();
+    }
+}
+
+fn set_layer(world: &mut World, id: EntityId, new_layer: Option<EntityId>) {
+    match new_layer {
+        Some(new_layer) => world.add_component(id, layer(), new_layer).ok(),
+        None => world.remove_component(id, layer()).ok(),
+    };
+}
+
+/// Returns the entities that belong to `layer_id`
+pub fn layer_members(world: &World, layer_id: EntityId) -> Vec<EntityId> {
+    query(layer()).iter(world, None).filter(|(_, &layer)| layer == layer_id).map(|(id, _)| id).collect()
+}
@@ -1,20 +1,41 @@
-use ambient_ecs::{components, EntityId};
+use ambient_ecs::{components, Debuggable, EntityId, Networked, Resource, Store};
 use std::iter::Cloned;
 
 #[macro_use]
 extern crate closure;
+pub mod annotations;
 pub mod intents;
+pub mod presence;
 pub mod rpc;
 pub mod ui;
 
 components!("editor", {
     selection: Selection,
     prev_selection: Selection,
+    /// The url of the prefab that is being edited in this instance
+    editing_prefab: String,
+    /// Set on entities hidden in the editor. The value is whether the entity was part of the main scene before it was hidden.
+    @[Debuggable, Networked, Store]
+    editor_hidden: bool,
+    /// Locked entities can't be selected in the viewport, but can still be selected in the hierarchy
+    @[Debuggable, Networked, Store]
+    editor_locked: (),
+    /// Marks an entity as an editor layer. Layers group entities so that they can be hidden or locked together.
+    @[Debuggable, Networked, Store]
+    editor_layer: (),
+    /// The editor layer this entity belongs to
+    @[Debuggable, Networked, Store]
+    layer: EntityId,
+    /// A snapshot of the main instance, taken when the first player starts play-testing, see [rpc::rpc_snapshot_main_instance]
+    @[Resource]
+    play_snapshot: rpc::PlaySnapshot,
 });
 
 pub fn init_all_components() {
     init_components();
+    annotations::init_components();
     intents::init_components();
+    presence::init_components();
 }
 
 pub const GRID_SIZE: f32 = 1.0;
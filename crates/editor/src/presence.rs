@@ -0,0 +1,93 @@
+//! Presence lets the users editing the same world see each other: each client sets its [editor_camera] on its player
+//! entity, next to its `selection`, and both are replicated to the other clients, which draw them in the user's
+//! [presence_color] with their name.
+//!
+//! An entity being transformed by a user is locked with [editor_transformed_by] until the transform is confirmed or
+//! cancelled, and the transform intents of the other users fail in the meantime.
+use ambient_core::player::{player, user_id};
+use ambient_ecs::{components, query, Debuggable, EntityId, Networked, SystemGroup, World};
+use ambient_std::color::Color;
+use glam::{vec3, Mat4, Vec3};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+components!("editor", {
+    /// The camera of an editor user, set on their player entity by their client
+    @[Debuggable, Networked]
+    editor_camera: EditorCamera,
+    /// The id of the user transforming this entity. Only they can transform it until they're done, see [lock_transform]
+    @[Debuggable, Networked]
+    editor_transformed_by: String,
+});
+
+/// How far from the camera the frustums of other users are drawn
+pub const PRESENCE_FRUSTUM_DEPTH: f32 = 1.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct EditorCamera {
+    /// The `local_to_world` of the camera
+    pub transform: Mat4,
+    pub fovy: f32,
+    pub aspect_ratio: f32,
+}
+impl EditorCamera {
+    pub fn position(&self) -> Vec3 {
+        self.transform.transform_point3(Vec3::ZERO)
+    }
+
+    /// The corners of the frustum at `depth` in front of the camera, in world space
+    pub fn frustum_corners(&self, depth: f32) -> [Vec3; 4] {
+        let y = depth * (self.fovy / 2.).tan();
+        let x = y * self.aspect_ratio;
+        [vec3(-x, -y, depth), vec3(x, -y, depth), vec3(x, y, depth), vec3(-x, y, depth)].map(|p| self.transform.transform_point3(p))
+    }
+}
+
+/// The color the other users see `user_id` in. It's derived from the id so that every client picks the same one
+pub fn presence_color(user_id: &str) -> Color {
+    // FNV-1a, which unlike the std hasher is stable across builds
+    let hash = user_id.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    Color::hsl((hash % 360) as f32, 0.8, 0.6)
+}
+
+/// The user other than `user_id` who is transforming one of `entities`, if there is one
+pub fn transform_lock_holder(world: &World, user_id: &str, entities: &[EntityId]) -> Option<String> {
+    entities.iter().filter_map(|&id| world.get_ref(id, editor_transformed_by()).ok()).find(|holder| *holder != user_id).cloned()
+}
+
+/// Locks `entities` for `user_id` to transform. Fails with the id of the user holding the lock if another user is
+/// transforming one of them
+pub fn lock_transform(world: &mut World, user_id: &str, entities: &[EntityId]) -> Result<(), String> {
+    if let Some(holder) = transform_lock_holder(world, user_id, entities) {
+        return Err(holder);
+    }
+    for &id in entities {
+        world.add_component(id, editor_transformed_by(), user_id.to_string()).ok();
+    }
+    Ok(())
+}
+
+/// Releases the locks `user_id` holds on `entities`
+pub fn unlock_transform(world: &mut World, user_id: &str, entities: &[EntityId]) {
+    for &id in entities {
+        if world.get_ref(id, editor_transformed_by()).map_or(false, |holder| holder == user_id) {
+            world.remove_component(id, editor_transformed_by()).ok();
+        }
+    }
+}
+
+/// Releases the locks of the users who leave the world
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "editor/presence",
+        vec![query(user_id()).incl(player()).despawned().to_system(|q, world, qs, _| {
+            for (_, user) in q.collect_cloned(world, qs) {
+                let locked =
+                    query(editor_transformed_by()).iter(world, None).filter(|(_, holder)| **holder == user).map(|(id, _)| id).collect_vec();
+                for id in locked {
+                    world.remove_component(id, editor_transformed_by()).ok();
+                }
+            }
+        })],
+    )
+}
@@ -1,20 +1,43 @@
-use ambient_ecs::{ArchetypeFilter, Entity, EntityId};
-use ambient_intent::server_push_intent;
-use ambient_network::client::GameRpcArgs;
+use ambient_core::{asset_cache, player::user_id};
+use std::{
+    collections::HashSet,
+    path::{Component, Path},
+};
+
+use ambient_ecs::{dont_store, query, ArchetypeFilter, ComponentDesc, Entity, EntityId, Store, System, World, WorldChange, WorldDiff};
+use ambient_intent::{intent, intent_touched, server_push_intent};
+use ambient_network::{
+    client::GameRpcArgs,
+    persistent_resources,
+    server::{ForkingEvent, WorldInstance, MAIN_INSTANCE_ID},
+    storage::{deserialize_world, serialize_world},
+    synced_resources, ServerWorldExt,
+};
 use ambient_physics::visualization::{visualize_collider, visualizing};
 use ambient_physics::{
     helpers::{convert_rigid_dynamic_to_static, convert_rigid_static_to_dynamic, unweld_multi, weld_multi},
-    intersection::{intersect_frustum, raycast_filtered, rpc_pick, RaycastFilter},
+    intersection::{intersect_frustum, raycast, rpc_pick},
 };
+use ambient_prefab::{export_prefab, load_prefab_for_editing, save_prefab, scene::Scene};
 use ambient_rpc::RpcRegistry;
-use ambient_std::{shapes::Ray, unwrap_log_err};
+use ambient_std::{
+    asset_cache::SyncAssetKeyExt,
+    asset_url::{AbsAssetUrl, ServerContentDirKey},
+    shapes::Ray,
+    unwrap_log_err,
+};
 use anyhow::Context;
 use bitflags::bitflags;
 use glam::{vec3, Vec3};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::intents::{intent_select, SelectMode};
-use crate::Selection;
+use crate::{
+    editing_prefab, editor_hidden, editor_locked, play_snapshot,
+    presence::{lock_transform, unlock_transform},
+    Selection,
+};
 use ambient_core::selectable;
 
 bitflags! {
@@ -47,26 +70,31 @@ pub fn register_rpcs(reg: &mut RpcRegistry<GameRpcArgs>) {
     reg.register(rpc_toggle_visualize_colliders);
     // reg.register(rpc_save);
     reg.register(rpc_spawn);
+    reg.register(rpc_lock_transform);
+    reg.register(rpc_unlock_transform);
+    reg.register(rpc_open_prefab);
+    reg.register(rpc_save_prefab);
+    reg.register(rpc_close_prefab);
+    reg.register(rpc_save_scene);
+    reg.register(rpc_snapshot_main_instance);
+    reg.register(rpc_restore_main_instance);
     // reg.register(rpc_teleport_player);
 }
 
-pub async fn rpc_select(args: GameRpcArgs, (method, mode): (SelectMethod, SelectMode)) {
+pub async fn rpc_select(args: GameRpcArgs, (method, mode, filter): (SelectMethod, SelectMode, SelectFilter)) {
     let entities = {
         let mut state = args.state.lock();
         let world = unwrap_log_err!(state.get_player_world_mut(&args.user_id).context("No player world"));
         match method {
             SelectMethod::Frustum(frustum) => {
-                intersect_frustum(world, &frustum).into_iter().filter(|id| world.has_component(*id, selectable())).collect()
+                intersect_frustum(world, &frustum).into_iter().filter(|id| filter.matches(world, *id)).collect()
             }
-            SelectMethod::Ray(ray) => {
-                if let Some((entity, _)) = raycast_filtered(
-                    world,
-                    RaycastFilter { entities: Some(ArchetypeFilter::new().incl(selectable())), collider_type: None },
-                    ray,
-                ) {
-                    Selection::new([entity])
-                } else {
+            SelectMethod::Ray { ray, depth } => {
+                let hits = pick_list(world, ray, &filter);
+                if hits.is_empty() {
                     Default::default()
+                } else {
+                    Selection::new([hits[depth % hits.len()]])
                 }
             }
             SelectMethod::Manual(ids) => ids,
@@ -77,6 +105,11 @@ pub async fn rpc_select(args: GameRpcArgs, (method, mode): (SelectMethod, Select
     server_push_intent(args.state, intent_select(), (entities, mode), args.user_id.clone(), Some(collapse_id)).await;
 }
 
+/// Returns all the selectable entities hit by the ray, sorted front to back
+pub fn pick_list(world: &World, ray: Ray, filter: &SelectFilter) -> Vec<EntityId> {
+    raycast(world, ray).into_iter().map(|(id, _)| id).unique().filter(|id| filter.matches(world, *id)).collect_vec()
+}
+
 pub async fn rpc_weld(args: GameRpcArgs, entities: Vec<EntityId>) {
     let mut state = args.state.lock();
     let world = unwrap_log_err!(state.get_player_world_mut(&args.user_id).context("No player world"));
@@ -152,12 +185,230 @@ pub async fn rpc_toggle_visualize_colliders(args: GameRpcArgs, entities: Vec<Ent
 //     };
 //     log_warning!(save_world(stored_map, map_path).await);
 // }
+/// Locks `entities` while the player transforms them, so that other users can't transform them at the same time. Fails
+/// with the id of the user transforming them if they're already locked
+pub async fn rpc_lock_transform(args: GameRpcArgs, entities: Vec<EntityId>) -> Result<(), String> {
+    let mut state = args.state.lock();
+    let world = state.get_player_world_mut(&args.user_id).ok_or("No player world")?;
+    lock_transform(world, &args.user_id, &entities)
+}
+/// Releases the locks taken by [rpc_lock_transform]
+pub async fn rpc_unlock_transform(args: GameRpcArgs, entities: Vec<EntityId>) {
+    let mut state = args.state.lock();
+    let world = unwrap_log_err!(state.get_player_world_mut(&args.user_id).context("No player world"));
+    unlock_transform(world, &args.user_id, &entities);
+}
 pub async fn rpc_spawn(args: GameRpcArgs, entity_data: Entity) -> Option<EntityId> {
     let mut state = args.state.lock();
     let world = state.get_player_world_mut(&args.user_id)?;
     Some(entity_data.spawn(world))
 }
 
+/// Opens a prefab for editing in its own instance, which only contains the prefab's entities (and the resources of the
+/// player's current instance). Returns the id of the instance, which the player then needs to join.
+pub async fn rpc_open_prefab(args: GameRpcArgs, url: String) -> Result<String, String> {
+    let instance_id = format!("prefab:{url}");
+    let assets = {
+        let state = args.state.lock();
+        if state.instances.contains_key(&instance_id) {
+            return Ok(instance_id);
+        }
+        state.get_player_world(&args.user_id).ok_or("No player world")?.resource(asset_cache()).clone()
+    };
+    let url = AbsAssetUrl::parse(&url).map_err(|err| format!("{err:?}"))?;
+    // Only the prefabs this server serves can be saved back, and files elsewhere on the server mustn't be read
+    if url.to_local_path(&assets).map_err(|err| format!("{err:?}"))?.is_none() {
+        return Err(format!("Can't edit prefab {url}; it's not served from this server's content"));
+    }
+    let prefab = load_prefab_for_editing(&assets, &url).await.map_err(|err| format!("{err:?}"))?;
+
+    let mut state = args.state.lock();
+    let instance = state.get_player_world_instance(&args.user_id).ok_or("No player world")?;
+    let mut world = instance.world.clone();
+    let world_stream = instance.world_stream.clone();
+
+    let resource_entities = [Some(world.resource_entity()), world.synced_resource_entity(), world.persisted_resource_entity()]
+        .into_iter()
+        .flatten()
+        .collect_vec();
+    for (id, _) in query(()).iter(&world, None).filter(|(id, _)| !resource_entities.contains(id)).collect_vec() {
+        world.despawn(id);
+    }
+    prefab.spawn_into_world(&mut world, None);
+    world.add_resource(editing_prefab(), url.to_string());
+
+    let mut on_forking = (state.create_on_forking_systems)();
+    on_forking.run(&mut world, &ForkingEvent);
+    world.reset_events();
+
+    let systems = (state.create_server_systems)(&mut world);
+    state.insert_instance(instance_id.clone(), WorldInstance::new(world, world_stream, systems));
+    Ok(instance_id)
+}
+
+/// Saves the prefab that is being edited in the player's current instance back to its url
+pub async fn rpc_save_prefab(args: GameRpcArgs, _: ()) -> Result<(), String> {
+    let (assets, url, prefab) = {
+        let state = args.state.lock();
+        let world = state.get_player_world(&args.user_id).ok_or("No player world")?;
+        let url = world.resource_opt(editing_prefab()).ok_or("Not editing a prefab")?;
+        let url = AbsAssetUrl::parse(url).map_err(|err| format!("{err:?}"))?;
+        let prefab = export_prefab(world, &level_entities(world)).map_err(|err| format!("{err:?}"))?;
+        (world.resource(asset_cache()).clone(), url, prefab)
+    };
+    save_prefab(&assets, &url, &prefab).await.map_err(|err| format!("{err:?}"))
+}
+
+/// Saves the entities of the player's current instance as a scene at `path`, relative to the project, so that the level
+/// can be kept in version control and spawned by the server when it starts
+pub async fn rpc_save_scene(args: GameRpcArgs, path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    if path.is_absolute() || path.components().any(|component| matches!(component, Component::ParentDir)) {
+        return Err(format!("The scene {path:?} must be inside the project"));
+    }
+    let (project_path, scene) = {
+        let state = args.state.lock();
+        let world = state.get_player_world(&args.user_id).ok_or("No player world")?;
+        // The content is served from the build directory of the project
+        let project_path = ServerContentDirKey
+            .get(world.resource(asset_cache()))
+            .and_then(|content_dir| content_dir.parent().map(Path::to_path_buf))
+            .ok_or("The project isn't served from this machine")?;
+        let scene = Scene::from_world(world, &level_entities(world)).map_err(|err| format!("{err:?}"))?;
+        (project_path, scene)
+    };
+    scene.save(&project_path.join(path)).map_err(|err| format!("{err:?}"))
+}
+
+/// The entities which make up the level, rather than the players and the resources
+fn level_entities(world: &World) -> Vec<EntityId> {
+    query(())
+        .excl(user_id())
+        .excl(synced_resources())
+        .excl(persistent_resources())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .filter(|&id| id != world.resource_entity())
+        .collect_vec()
+}
+
+/// Removes a prefab editing instance once all players have left it
+pub async fn rpc_close_prefab(args: GameRpcArgs, instance_id: String) {
+    let mut state = args.state.lock();
+    let Some(instance) = state.instances.get(&instance_id) else { return };
+    if instance.world.resource_opt(editing_prefab()).is_some() && instance.player_count() == 0 {
+        state.instances.remove(&instance_id);
+    }
+}
+
+/// The main instance as it was before play-testing, see [rpc_snapshot_main_instance]
+#[derive(Clone)]
+pub struct PlaySnapshot {
+    /// The stored entities and resources of the main instance, in the [serialize_world] format
+    data: Vec<u8>,
+    /// The version of the main instance when the snapshot was taken. Components written after it have changed since.
+    version: u64,
+    /// The users that are play-testing; the snapshot is restored when the last of them stops
+    testers: HashSet<String>,
+}
+
+/// Snapshots the main instance before play-testing. If there already is a snapshot (e.g. because another player is
+/// play-testing), it's kept and the player is added to its testers.
+pub async fn rpc_snapshot_main_instance(args: GameRpcArgs, _: ()) {
+    let mut guard = args.state.lock();
+    let state = &mut *guard;
+    let Some(instance) = state.instances.get_mut(MAIN_INSTANCE_ID) else { return };
+    if let Some(snapshot) = instance.world.resource_mut_opt(play_snapshot()) {
+        snapshot.testers.retain(|tester| state.players.contains_key(tester));
+        snapshot.testers.insert(args.user_id);
+        return;
+    }
+    let data = unwrap_log_err!(serialize_world(&instance.world));
+    let snapshot = PlaySnapshot { data, version: instance.world.version(), testers: HashSet::from([args.user_id]) };
+    instance.world.add_resource(play_snapshot(), snapshot);
+    // Edits made in the editor while play-testing are kept when the snapshot is restored
+    instance.world.add_resource(intent_touched(), HashSet::new());
+}
+
+/// Restores the main instance to the snapshot taken by [rpc_snapshot_main_instance] once the last player stops
+/// play-testing. Only the stored components of non-player entities are restored; runtime state is rebuilt by the server
+/// systems. Entities that were changed by editor intents while play-testing are left as they are. Returns true if the
+/// snapshot was restored.
+pub async fn rpc_restore_main_instance(args: GameRpcArgs, _: ()) -> bool {
+    let mut guard = args.state.lock();
+    let state = &mut *guard;
+    let Some(instance) = state.instances.get_mut(MAIN_INSTANCE_ID) else { return false };
+    let Some(snapshot) = instance.world.resource_mut_opt(play_snapshot()) else { return false };
+    snapshot.testers.remove(&args.user_id);
+    snapshot.testers.retain(|tester| state.players.contains_key(tester));
+    if !snapshot.testers.is_empty() {
+        return false;
+    }
+
+    let snapshot = instance.world.resource(play_snapshot()).clone();
+    let edited = instance.world.resource_opt(intent_touched()).cloned().unwrap_or_default();
+    let resource_entity = instance.world.resource_entity();
+    instance.world.remove_component(resource_entity, play_snapshot()).ok();
+    instance.world.remove_component(resource_entity, intent_touched()).ok();
+
+    let mut snapshot_world = World::new("play_snapshot");
+    unwrap_log_err!(deserialize_world(&snapshot.data, &mut snapshot_world));
+    let diff = play_snapshot_diff(&instance.world, &snapshot_world, snapshot.version, &edited);
+    log::info!("Restoring main instance after play-testing: {diff}");
+    diff.apply(&mut instance.world, Entity::new(), false);
+    true
+}
+
+/// The changes that take the stored components of `world` back to `snapshot`, which was taken at `version`. Players,
+/// intents and the `edited` entities are left out.
+fn play_snapshot_diff(world: &World, snapshot: &World, version: u64, edited: &HashSet<EntityId>) -> WorldDiff {
+    let filter = ArchetypeFilter::new().excl(user_id()).excl(intent()).excl(dont_store());
+    let stored =
+        |world: &World, id| world.get_components(id).unwrap().into_iter().filter(|desc| desc.has_attribute::<Store>()).collect_vec();
+    let changed = |desc: &ComponentDesc, id| world.get_component_content_version(id, desc.index()).unwrap() > version;
+
+    let mut diff = WorldDiff::new();
+    for (id, _) in query(()).filter(&filter).iter(world, None) {
+        if edited.contains(&id) {
+            continue;
+        }
+        let components = stored(world, id);
+        if !snapshot.exists(id) {
+            // Entities spawned while play-testing are despawned; older ones only lose their new stored components
+            if world.get_components(id).unwrap().iter().all(|desc| changed(desc, id)) {
+                diff = diff.despawn(vec![id]);
+            } else {
+                let added = components.into_iter().filter(|desc| changed(desc, id)).collect_vec();
+                if !added.is_empty() {
+                    diff = diff.remove_components_raw(id, added);
+                }
+            }
+            continue;
+        }
+        let mut removed = Vec::new();
+        for desc in components.iter().filter(|desc| changed(desc, id)) {
+            match snapshot.get_entry(id, *desc) {
+                Ok(entry) => diff = diff.set_entry(id, entry),
+                Err(_) => removed.push(*desc),
+            }
+        }
+        if !removed.is_empty() {
+            diff = diff.remove_components_raw(id, removed);
+        }
+        for desc in stored(snapshot, id) {
+            if !world.has_component(id, desc) {
+                diff = diff.add_entry(id, snapshot.get_entry(id, desc).unwrap());
+            }
+        }
+    }
+    for (id, _) in query(()).filter(&filter).iter(snapshot, None) {
+        if !world.exists(id) && !edited.contains(&id) {
+            diff.changes.push(WorldChange::Spawn(Some(id), snapshot.clone_entity(id).unwrap()));
+        }
+    }
+    diff
+}
+
 // pub async fn rpc_teleport_player(args: GameRpcArgs, position: Vec3) -> Result<(), ECSError> {
 //     let mut state = args.state.lock();
 //     let world = state.get_player_world_mut(&args.user_id).ok_or_else(|| ECSError::NoSuchEntity { entity_id: EntityId::null() })?;
@@ -178,6 +429,29 @@ pub async fn rpc_spawn(args: GameRpcArgs, entity_data: Entity) -> Option<EntityI
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum SelectMethod {
     Frustum([Vec3; 8]),
-    Ray(Ray),
+    /// Selects the entity hit by the ray. When several entities overlap, `depth` picks which one, counted front to back
+    /// (wrapping around), so that repeated clicks can cycle through them.
+    Ray {
+        ray: Ray,
+        depth: usize,
+    },
     Manual(Selection),
 }
+
+/// Restricts which entities a selection can include. Only `selectable` entities that are neither hidden nor locked are ever selected.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SelectFilter {
+    /// If set, only entities matching this filter (for instance, all entities with the components of a concept) are selected
+    pub entities: Option<ArchetypeFilter>,
+}
+impl SelectFilter {
+    pub fn new(entities: ArchetypeFilter) -> Self {
+        Self { entities: Some(entities) }
+    }
+    pub fn matches(&self, world: &World, id: EntityId) -> bool {
+        world.has_component(id, selectable())
+            && !world.has_component(id, editor_hidden())
+            && !world.has_component(id, editor_locked())
+            && self.entities.as_ref().map(|f| f.matches_entity(world, id)).unwrap_or(true)
+    }
+}
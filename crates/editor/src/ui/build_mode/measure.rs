@@ -0,0 +1,87 @@
+use ambient_core::{
+    runtime,
+    window::{cursor_position, window_logical_size, window_scale_factor},
+};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks, Setter};
+use ambient_network::{client::GameClient, log_network_result, unwrap_log_network_err};
+use ambient_physics::intersection::{rpc_pick, RaycastFilter};
+use ambient_std::{cb, math::interpolate, shapes::Ray};
+use ambient_ui::{Button, EditorPrompt, UIBase, UIExt};
+use ambient_window_types::{MouseButton, VirtualKeyCode};
+use glam::{vec2, Vec2, Vec3};
+
+use crate::{annotations::annotation_entity, rpc::rpc_spawn, ui::world_overlay::Measurement};
+
+async fn pick_point(game_client: &GameClient, ray: Ray) -> Option<Vec3> {
+    let hit = unwrap_log_network_err!(game_client.rpc(rpc_pick, (ray, RaycastFilter { entities: None, collider_type: None })).await);
+    hit.map(|(_, dist)| ray.origin + ray.dir * dist)
+}
+
+/// Replaces the selection area while measuring; each click picks a point, and every third click starts a new measurement
+#[element_component]
+pub fn MeasureArea(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (measurement, set_measurement) = hooks.consume_context::<Measurement>().unwrap();
+
+    hooks.use_spawn({
+        let set_measurement = set_measurement.clone();
+        move |_| Box::new(move |_| set_measurement(Measurement::default()))
+    });
+
+    UIBase
+        .el()
+        .with_clickarea()
+        .on_mouse_down(move |world, _, button| {
+            if button != MouseButton::Left {
+                return;
+            }
+            let scl = *world.resource(window_scale_factor()) as f32;
+            let screen_size = world.resource(window_logical_size()).as_vec2();
+            let mouse_pos = *world.resource(cursor_position()) / scl;
+            let ray = {
+                let state = game_client.game_state.lock();
+                state.screen_ray(interpolate(mouse_pos, Vec2::ZERO, screen_size, vec2(-1., 1.), vec2(1., -1.)))
+            };
+            let game_client = game_client.clone();
+            let set_measurement = set_measurement.clone();
+            let measurement = measurement.clone();
+            world.resource(runtime()).spawn(async move {
+                let Some(point) = pick_point(&game_client, ray).await else { return };
+                set_measurement(Measurement(match measurement.0[..] {
+                    [start] => vec![start, point],
+                    _ => vec![point],
+                }));
+            });
+        })
+        .el()
+}
+
+/// Asks for a text, and then adds an annotation pointing at whatever is in the center of the screen
+#[element_component]
+pub fn AnnotateButton(hooks: &mut Hooks, set_screen: Setter<Option<Element>>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    Button::new("\u{f249}", move |_| {
+        let game_client = game_client.clone();
+        set_screen(Some(
+            EditorPrompt {
+                title: "Annotation".to_string(),
+                value: String::new(),
+                set_screen: set_screen.clone(),
+                on_ok: cb(move |world, text| {
+                    let game_client = game_client.clone();
+                    let ray = game_client.game_state.lock().center_screen_ray();
+                    world.resource(runtime()).spawn(async move {
+                        let Some(target) = pick_point(&game_client, ray).await else { return };
+                        log_network_result!(game_client.rpc(rpc_spawn, annotation_entity(text, target)).await);
+                    });
+                }),
+                on_cancel: Some(cb(|_| {})),
+                validator: Some(cb(|text: &String| !text.is_empty())),
+            }
+            .el(),
+        ));
+    })
+    .tooltip("Add annotation at the center of the screen")
+    .hotkey(VirtualKeyCode::N)
+    .el()
+}
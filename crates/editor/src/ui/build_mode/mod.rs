@@ -25,9 +25,13 @@ use ambient_ui::{
 use ambient_window_types::VirtualKeyCode;
 use tokio::time::sleep;
 
-use super::{terrain_mode::GenerateTerrainButton, EditorPlayerInputHandler, EditorPrefs};
+use super::{
+    hierarchy::HierarchyPanel, layers::LayersPanel, presence::use_transform_lock, terrain_mode::GenerateTerrainButton,
+    undo::use_local_undo, EditorPlayerInputHandler, EditorPrefs,
+};
 use crate::{
     intents::{intent_delete, intent_duplicate, intent_spawn_object, IntentDuplicate, IntentSpawnObject, SelectMode},
+    rpc::SelectFilter,
     ui::use_player_selection,
     Selection, GRID_SIZE,
 };
@@ -35,11 +39,13 @@ use crate::{
 mod entity_browser;
 mod grid_material;
 mod guide;
+mod measure;
 mod select_area;
 mod selection_panel;
 mod transform;
 
 use guide::*;
+use measure::*;
 use select_area::*;
 use selection_panel::*;
 use transform::*;
@@ -129,8 +135,13 @@ impl ElementComponent for EditorBuildMode {
         // tracing::info!("Drawing EditorBuildMode: {selection:?}");
 
         let set_select_mode = hooks.provide_context(|| SelectMode::Set);
+        hooks.provide_context(SelectFilter::default);
         let set_srt_mode = hooks.provide_context(|| None as Option<TransformMode>);
         let (screen, set_screen) = hooks.use_state(None);
+        let (measuring, set_measuring) = hooks.use_state(false);
+        let (show_hierarchy, set_show_hierarchy) = hooks.use_state(false);
+        let (hierarchy_right, set_hierarchy_right) = hooks.use_state(false);
+        let (show_layers, set_show_layers) = hooks.use_state(false);
 
         let targets = hooks.use_ref_with::<Arc<[EntityId]>>(|_| Arc::from([]));
         let rerender = hooks.use_rerender_signal();
@@ -187,6 +198,28 @@ impl ElementComponent for EditorBuildMode {
         Dock(vec![
             EditorPlayerInputHandler.el(),
             ScreenContainer(screen).el(),
+            if show_hierarchy {
+                HierarchyPanel { dock_right: hierarchy_right, set_dock_right: set_hierarchy_right }
+                    .el()
+                    .set(width(), 300.)
+                    .set(docking(), if hierarchy_right { Docking::Right } else { Docking::Left })
+                    .floating_panel()
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
+            if show_layers {
+                LayersPanel
+                    .el()
+                    .set(width(), 300.)
+                    .set(docking(), Docking::Left)
+                    .floating_panel()
+                    .set(margin(), Borders::even(STREET))
+                    .set(padding(), Borders::even(STREET))
+            } else {
+                Element::new()
+            },
             if !selection.is_empty() {
                 SelectionPanel { selection: selection.clone(), set_selection: set_selection.clone() }
                     .el()
@@ -261,6 +294,19 @@ impl ElementComponent for EditorBuildMode {
                     })
                     .tooltip("Browse entities")
                     .el(),
+                    Button::new("\u{f0e8}", move |_| set_show_hierarchy(!show_hierarchy))
+                        .tooltip("Hierarchy")
+                        .toggled(show_hierarchy)
+                        .el(),
+                    Button::new("\u{f5fd}", move |_| set_show_layers(!show_layers)).tooltip("Layers").toggled(show_layers).el(),
+                    SelectFilterDropdown.el(),
+                    Separator { vertical: true }.el(),
+                    Button::new("\u{f545}", move |_| set_measuring(!measuring))
+                        .tooltip("Measure distance")
+                        .hotkey(VirtualKeyCode::M)
+                        .toggled(measuring)
+                        .el(),
+                    AnnotateButton { set_screen: set_screen.clone() }.el(),
                 ];
                 if !selection.is_empty() {
                     items.extend([
@@ -315,7 +361,7 @@ impl ElementComponent for EditorBuildMode {
                 .set(padding(), Borders::even(STREET)),
             GenerateTerrainButton.el()
                 .set(margin(), Borders::even(STREET)),
-            SelectArea.el(),
+            if measuring { MeasureArea.el() } else { SelectArea.el() },
         ])
             .el()
     }
@@ -344,9 +390,13 @@ impl ElementComponent for TransformControls {
         let (srt_mode, set_srt_mode) = hooks.consume_context::<Option<TransformMode>>().unwrap();
 
         let (prefs, set_prefs) = hooks.consume_context::<EditorPrefs>().unwrap();
-        let set = set_prefs.clone();
-        let set_snap_mode = move |snap| (set)(EditorPrefs { snap, ..prefs });
-        let set_global_coordinates = move |use_global| (set_prefs)(EditorPrefs { use_global_coordinates: use_global, ..prefs });
+        let local_undo = use_local_undo(hooks);
+        let set_snap_mode = closure!(clone local_undo, clone set_prefs, |snap| {
+            local_undo.set("Snap to grid", set_prefs.clone(), prefs, EditorPrefs { snap, ..prefs })
+        });
+        let set_global_coordinates = move |use_global| {
+            local_undo.set("Align to world space", set_prefs.clone(), prefs, EditorPrefs { use_global_coordinates: use_global, ..prefs })
+        };
 
         let mode_button = |mode, icon, hotkey| {
             Button::new(
@@ -390,6 +440,16 @@ impl ElementComponent for TransformControls {
             mode_button(TransformMode::Place, "", VirtualKeyCode::Key4).el(),
         ];
 
+        // Another user may already be transforming the targets, in which case the transform is cancelled
+        let lock_targets = (srt_mode.is_some() && !targets.is_empty()).then(|| targets.clone());
+        use_transform_lock(hooks, lock_targets, {
+            let set_srt_mode = set_srt_mode.clone();
+            cb(move |holder| {
+                log::warn!("{holder} is already transforming the selection");
+                set_srt_mode(None);
+            })
+        });
+
         let on_click: Cb<dyn Fn(MouseButton) + Sync + Send> = cb({
             let set_srt_mode = set_srt_mode.clone();
             move |_| {
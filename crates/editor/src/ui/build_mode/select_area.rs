@@ -4,19 +4,24 @@ use ambient_core::{
     window::cursor_position,
     window::{window_logical_size, window_scale_factor},
 };
+use ambient_decals::decal;
+use ambient_ecs::ArchetypeFilter;
 use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
 use ambient_input::{event_mouse_input, event_mouse_motion};
+use ambient_model::model_from_url;
 use ambient_network::{client::GameClient, log_network_result};
-use ambient_std::{color::Color, math::interpolate};
+use ambient_physics::physx::rigid_dynamic;
+use ambient_std::{cb, color::Color, math::interpolate};
 use ambient_ui::{
     layout::{height, width},
-    UIBase, UIExt,
+    DropdownSelect, Text, UIBase, UIExt,
 };
 use glam::{vec2, vec3, Vec2, Vec3Swizzles};
 
 use crate::{
     intents::SelectMode,
-    rpc::{rpc_select, SelectMethod},
+    rpc::{rpc_select, SelectFilter, SelectMethod},
+    ui::undo::use_local_undo,
 };
 use ambient_event_types::{WINDOW_MOUSE_INPUT, WINDOW_MOUSE_MOTION};
 
@@ -30,19 +35,27 @@ impl ElementComponent for SelectArea {
         let (mouse_pos, set_mouse_pos) = hooks.use_state(Vec2::ZERO);
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let (select_mode, _) = hooks.consume_context::<SelectMode>().unwrap();
+        let (select_filter, _) = hooks.consume_context::<SelectFilter>().unwrap();
         let is_clicking = hooks.use_ref_with(|_| false);
+        // The position and depth of the last click; clicking the same spot again selects the next entity behind it
+        let last_pick = hooks.use_ref_with::<Option<(Vec2, usize)>>(|_| None);
 
         let client = game_client.clone();
         hooks.use_spawn(move |_| {
             Box::new(move |w| {
                 w.resource(runtime()).spawn(async move {
-                    log_network_result!(client.rpc(rpc_select, (SelectMethod::Manual(Default::default()), SelectMode::Clear)).await);
+                    log_network_result!(
+                        client
+                            .rpc(rpc_select, (SelectMethod::Manual(Default::default()), SelectMode::Clear, SelectFilter::default()))
+                            .await
+                    );
                 });
             })
         });
         hooks.use_multi_event(&[WINDOW_MOUSE_MOTION, WINDOW_MOUSE_INPUT], {
             let set_dragging = set_dragging.clone();
             let is_clicking = is_clicking.clone();
+            let select_filter = select_filter.clone();
             move |world, event| {
                 let scl = *world.resource(window_scale_factor()) as f32;
                 if let Some(position) = event.get(event_mouse_motion()) {
@@ -87,9 +100,13 @@ impl ElementComponent for SelectArea {
                                         get_corner(vec2(max_x, max_y), 0.001),
                                     ]
                                 };
+                                let select_filter = select_filter.clone();
                                 world.resource(runtime()).clone().spawn(async move {
-                                    log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Frustum(frustum), select_mode)).await);
+                                    log_network_result!(
+                                        game_client.rpc(rpc_select, (SelectMethod::Frustum(frustum), select_mode, select_filter)).await
+                                    );
                                 });
+                                *last_pick.lock() = None;
                                 return;
                             }
                         }
@@ -100,9 +117,22 @@ impl ElementComponent for SelectArea {
                             state.screen_ray(p)
                         };
 
+                        let depth = {
+                            let mut last_pick = last_pick.lock();
+                            let depth = match *last_pick {
+                                Some((pos, depth)) if pos.distance(mouse_pos) < 3. => depth + 1,
+                                _ => 0,
+                            };
+                            *last_pick = Some((mouse_pos, depth));
+                            depth
+                        };
+
                         let game_client = game_client.clone();
+                        let select_filter = select_filter.clone();
                         world.resource(runtime()).clone().spawn(async move {
-                            log_network_result!(game_client.rpc(rpc_select, (SelectMethod::Ray(ray), select_mode)).await);
+                            log_network_result!(
+                                game_client.rpc(rpc_select, (SelectMethod::Ray { ray, depth }, select_mode, select_filter)).await
+                            );
                         });
                     }
                 }
@@ -132,7 +162,14 @@ impl ElementComponent for SelectArea {
                 let max_y = dragging.y.max(mouse_pos.y);
                 UIBase
                     .el()
-                    .with_background(Color::rgba(0., 0., 1., 0.3).into())
+                    .with_background(
+                        match select_mode {
+                            SelectMode::Add => Color::rgba(0., 1., 0., 0.3),
+                            SelectMode::Remove => Color::rgba(1., 0., 0., 0.3),
+                            _ => Color::rgba(0., 0., 1., 0.3),
+                        }
+                        .into(),
+                    )
                     .set(translation(), vec3(min_x, min_y, -0.05) - area_offset.extend(0.))
                     .set(width(), max_x - min_x)
                     .set(height(), max_y - min_y)
@@ -141,3 +178,42 @@ impl ElementComponent for SelectArea {
             }])
     }
 }
+
+fn select_filter_presets() -> Vec<(&'static str, SelectFilter)> {
+    vec![
+        ("All", SelectFilter::default()),
+        ("Models", SelectFilter::new(ArchetypeFilter::new().incl(model_from_url()))),
+        ("Decals", SelectFilter::new(ArchetypeFilter::new().incl(decal()))),
+        ("Dynamic physics", SelectFilter::new(ArchetypeFilter::new().incl(rigid_dynamic()))),
+    ]
+}
+
+#[derive(Debug, Clone)]
+/// Restricts the [SelectArea] to only select some kinds of entities
+pub struct SelectFilterDropdown;
+impl ElementComponent for SelectFilterDropdown {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let (preset, set_preset) = hooks.use_state(0);
+        let (select_filter, set_select_filter) = hooks.consume_context::<SelectFilter>().unwrap();
+        let local_undo = use_local_undo(hooks);
+        let presets = select_filter_presets();
+        DropdownSelect {
+            content: Text::el(format!("\u{f0b0} {}", presets[preset].0)),
+            on_select: cb(move |index| {
+                let (set_preset, set_select_filter) = (set_preset.clone(), set_select_filter.clone());
+                let select = move |index: usize, filter: SelectFilter| {
+                    set_preset(index);
+                    set_select_filter(filter);
+                };
+                let (prev_filter, filter) = (select_filter.clone(), select_filter_presets().swap_remove(index).1);
+                select(index, filter.clone());
+                local_undo.push("Selection filter", closure!(clone select, || select(preset, prev_filter.clone())), move || {
+                    select(index, filter.clone())
+                });
+            }),
+            items: presets.iter().map(|(name, _)| Text::el(*name)).collect(),
+            inline: false,
+        }
+        .el()
+    }
+}
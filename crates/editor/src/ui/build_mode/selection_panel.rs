@@ -3,11 +3,15 @@ use ambient_network::{client::GameClient, log_network_result};
 use ambient_std::Cb;
 use ambient_ui::{
     layout::{fit_horizontal, fit_vertical, space_between_items, Fit},
-    Button, FlowColumn, Text, UIExt, STREET,
+    Button, FlowColumn, StylesExt, Text, UIExt, STREET,
 };
 
 use super::super::entity_editor::EntityEditor;
-use crate::{rpc::rpc_toggle_visualize_colliders, ui::EditorSettings, Selection};
+use crate::{
+    rpc::rpc_toggle_visualize_colliders,
+    ui::{presence::use_selection_conflicts, EditorSettings},
+    Selection,
+};
 
 #[derive(Debug, Clone)]
 pub struct SelectionPanel {
@@ -21,8 +25,10 @@ impl ElementComponent for SelectionPanel {
         let Self { selection, set_selection: _ } = *self;
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let (settings, _) = hooks.consume_context::<EditorSettings>().unwrap();
+        let conflicts = use_selection_conflicts(hooks, selection.clone());
 
         FlowColumn(vec![
+            FlowColumn(conflicts.into_iter().map(|conflict| Text::el(conflict).error_text_style()).collect()).el(),
             #[allow(clippy::comparison_chain)]
             if selection.len() == 1 {
                 let _state = game_client.game_state.lock();
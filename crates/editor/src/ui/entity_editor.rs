@@ -2,25 +2,29 @@ use std::{sync::Arc, time::Duration};
 
 use ambient_animation::{animation_errors, animation_retargeting, loop_animation};
 use ambient_core::{
-    name, runtime, snap_to_ground, tags,
+    asset_cache, name, runtime, snap_to_ground, tags,
     transform::{scale, translation},
 };
 use ambient_decals::decal;
 use ambient_ecs::{
-    with_component_registry, Component, ComponentDesc, ComponentEntry, ComponentValue, Entity, EntityId, PrimitiveComponentType, World,
+    primitive_component_definitions, with_component_registry, Component, ComponentDesc, ComponentEntry, ComponentValue, Entity, EntityId,
+    PrimitiveComponentType, World,
 };
 use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
 use ambient_intent::client_push_intent;
 use ambient_network::{client::GameClient, hooks::use_remote_component};
 use ambient_physics::collider::{character_controller_height, character_controller_radius, collider, collider_type, mass};
-use ambient_std::{cb, Cb};
+use ambient_std::{
+    asset_url::{select_asset, AssetType, AssetUrl},
+    cb, Cb,
+};
 use ambient_ui::{
     align_horizontal, align_vertical,
     layout::{fit_horizontal, margin, Borders, Fit},
     space_between_items, Align, Button, ButtonStyle, DropdownSelect, Editor, EditorPrompt, FlowColumn, FlowRow, ScreenContainer, StylesExt,
     Text, STREET,
 };
-use glam::{Vec2, Vec3, Vec4};
+use glam::{Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +36,8 @@ use crate::intents::intent_component_change;
 pub fn EntityEditor(hooks: &mut Hooks, entity_id: EntityId) -> Element {
     // tracing::info!("Drawing EntityEditor");
     let (entity, set_entity) = hooks.use_state(None);
+    // The entity as it was when it was first inspected, which components can be reverted to
+    let original = hooks.use_ref_with::<Option<(EntityId, Entity)>>(|_| None);
     hooks.provide_context(|| EditingEntityContext(entity_id));
     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
 
@@ -39,16 +45,21 @@ pub fn EntityEditor(hooks: &mut Hooks, entity_id: EntityId) -> Element {
         Duration::from_millis(100),
         false,
         entity_id,
-        closure!(clone set_entity, clone game_client, |&entity_id| {
+        closure!(clone set_entity, clone game_client, clone original, |&entity_id| {
             profiling::scope!("EntityEditor::update_entity_data");
             let game_state = game_client.game_state.lock();
             if let Ok(data) = game_state.world.clone_entity(entity_id) {
+                let mut original = original.lock();
+                if original.as_ref().map(|(id, _)| *id) != Some(entity_id) {
+                    *original = Some((entity_id, data.clone()));
+                }
                 set_entity(Some(data));
             } else {
                 set_entity(None);
             }
         }),
     );
+    let original = original.lock().as_ref().filter(|(id, _)| *id == entity_id).map(|(_, data)| data.clone());
 
     let name = use_remote_component(hooks, entity_id, name()).unwrap_or(format!("Entity {entity_id}"));
     let runtime = hooks.world.resource(runtime()).clone();
@@ -60,6 +71,7 @@ pub fn EntityEditor(hooks: &mut Hooks, entity_id: EntityId) -> Element {
             if let Some(mass) = entity.get(mass()) { Text::el(format!("{mass} kg")).small_style() } else { Element::new() },
             EntityComponentsEditor {
                 value: entity,
+                original,
                 on_change: cb(move |change| {
                     runtime.spawn(client_push_intent(game_client.clone(), intent_component_change(), (entity_id, change), None, None));
                 }),
@@ -121,65 +133,237 @@ impl EntityComponentChange {
     }
 }
 
-#[tracing::instrument(level = "info", skip_all)]
-#[profiling::function]
-#[element_component]
-fn EntityComponentsEditor(_hooks: &mut Hooks, value: Entity, on_change: Cb<dyn Fn(EntityComponentChange) + Sync + Send>) -> Element {
-    let mut missing_components = Vec::new();
-    fn reg_component<T: ComponentValue + Editor + std::fmt::Debug + Clone + Sync + Send + 'static>(
-        entity: &Entity,
-        on_change: Cb<dyn Fn(EntityComponentChange) + Sync + Send>,
-        missing_components: &mut Vec<(String, Arc<dyn Fn() + Sync + Send>)>,
-        display_name: &str,
-        short: bool,
-        component: Component<T>,
-        on_create: impl Fn() -> T + Sync + Send + 'static,
-    ) -> Option<(String, Element)> {
-        let value = entity.get_ref(component).cloned();
-        if let Some(value) = value {
-            Some((
-                display_name.to_string(),
-                ComponentEditor {
-                    value,
-                    component,
-                    display_name: display_name.to_string(),
-                    inline: short,
-                    on_change: cb(closure!(clone on_change, |value| on_change(EntityComponentChange::Change(value)))),
-                    on_remove: cb(move || on_change(EntityComponentChange::Remove(component.into()))),
+/// Live validation of component values in the inspector; invalid values are not sent to the server
+pub trait ValidateComponent {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+macro_rules! impl_validate_finite {
+    ($($type:ty),*) => {
+        $(impl ValidateComponent for $type {
+            fn validate(&self) -> Result<(), String> {
+                if self.is_finite() {
+                    Ok(())
+                } else {
+                    Err("Value must be finite".to_string())
                 }
-                .el(),
-            ))
+            }
+        })*
+    };
+}
+impl_validate_finite!(f32, f64, Vec2, Vec3, Vec4, Mat4);
+impl ValidateComponent for Quat {
+    fn validate(&self) -> Result<(), String> {
+        if !self.is_finite() {
+            Err("Value must be finite".to_string())
+        } else if !self.is_normalized() {
+            Err("Rotation must be normalized".to_string())
         } else {
-            missing_components.push((
-                display_name.to_string(),
-                Arc::new(move || on_change(EntityComponentChange::Add(ComponentEntry::new(component, on_create())))),
-            ));
-            None
+            Ok(())
         }
     }
+}
+impl ValidateComponent for () {}
+impl ValidateComponent for bool {}
+impl ValidateComponent for EntityId {}
+impl ValidateComponent for i32 {}
+impl ValidateComponent for String {}
+impl ValidateComponent for u32 {}
+impl ValidateComponent for u64 {}
+impl ValidateComponent for UVec2 {}
+impl ValidateComponent for UVec3 {}
+impl ValidateComponent for UVec4 {}
+impl<T: ValidateComponent> ValidateComponent for Vec<T> {
+    fn validate(&self) -> Result<(), String> {
+        self.iter().enumerate().try_for_each(|(i, v)| v.validate().map_err(|err| format!("Item {i}: {err}")))
+    }
+}
+impl<T: ValidateComponent> ValidateComponent for Option<T> {
+    fn validate(&self) -> Result<(), String> {
+        self.as_ref().map(|v| v.validate()).unwrap_or(Ok(()))
+    }
+}
+
+type Validator<T> = Cb<dyn Fn(&T) -> Result<(), String> + Sync + Send>;
+/// Lets the user pick a new value, e.g. from the asset browser, and passes it to the callback
+type Picker<T> = Cb<dyn Fn(&mut World, Cb<dyn Fn(T) + Sync + Send>) + Sync + Send>;
+
+fn positive(value: &f32) -> Result<(), String> {
+    value.validate()?;
+    if *value > 0. {
+        Ok(())
+    } else {
+        Err("Value must be greater than zero".to_string())
+    }
+}
+
+type RegisterArgs<'a> =
+    (&'a Entity, Option<&'a Entity>, Cb<dyn Fn(EntityComponentChange) + Sync + Send>, &'a mut Vec<(String, Arc<dyn Fn() + Sync + Send>)>);
+
+fn reg_component<T: ComponentValue + Editor + std::fmt::Debug + Clone + Sync + Send + 'static>(
+    (entity, original, on_change, missing_components): RegisterArgs,
+    display_name: &str,
+    short: bool,
+    component: Component<T>,
+    validator: Option<Validator<T>>,
+    picker: Option<Picker<T>>,
+    on_create: impl Fn() -> T + Sync + Send + 'static,
+) -> Option<(String, Element)> {
+    let value = entity.get_ref(component).cloned();
+    if let Some(value) = value {
+        // Debug output is the only comparison available for all component types
+        let original = original.and_then(|o| o.get_ref(component).cloned()).filter(|o| format!("{o:?}") != format!("{value:?}"));
+        Some((
+            display_name.to_string(),
+            ComponentEditor {
+                value,
+                original,
+                component,
+                display_name: display_name.to_string(),
+                inline: short,
+                validator,
+                picker,
+                on_change: cb(closure!(clone on_change, |value| on_change(EntityComponentChange::Change(value)))),
+                on_remove: cb(move || on_change(EntityComponentChange::Remove(component.into()))),
+            }
+            .el(),
+        ))
+    } else {
+        missing_components.push((
+            display_name.to_string(),
+            Arc::new(move || on_change(EntityComponentChange::Add(ComponentEntry::new(component, on_create())))),
+        ));
+        None
+    }
+}
+
+fn register_dynamic_component<
+    T: ComponentValue + Editor + ValidateComponent + std::fmt::Debug + Clone + Sync + Send + Default + 'static,
+>(
+    args: RegisterArgs,
+    display_name: &str,
+    short: bool,
+    desc: ComponentDesc,
+) -> Option<(String, Element)> {
+    reg_component(args, display_name, short, Component::<T>::new(desc), Some(cb(T::validate)), None, Default::default)
+}
+
+/// The type of asset that a string component is the url of, going by its path
+fn asset_url_type(path: &str) -> Option<AssetType> {
+    Some(match path.rsplit("::").next()? {
+        "model_from_url" => AssetType::Model,
+        "prefab_from_url" => AssetType::Prefab,
+        "collider_from_url" => AssetType::Collider,
+        "pbr_material_from_url" => AssetType::Material,
+        "environment_map_from_url" => AssetType::Image,
+        "streaming_world_from_url" => AssetType::StreamingWorld,
+        _ => return None,
+    })
+}
+
+/// Creates an editor for a string component that is an asset url, which can be picked from the asset browser
+fn register_asset_url_component(
+    args: RegisterArgs,
+    display_name: &str,
+    desc: ComponentDesc,
+    asset_type: AssetType,
+) -> Option<(String, Element)> {
+    let validator: Validator<String> = cb(|url: &String| {
+        if url.is_empty() {
+            return Ok(());
+        }
+        AssetUrl::parse(url).map(|_| ()).map_err(|err| format!("Invalid url: {err}"))
+    });
+    let picker: Picker<String> = cb(move |world: &mut World, on_change: Cb<dyn Fn(String) + Sync + Send>| {
+        select_asset(world.resource(asset_cache()), asset_type, move |url| {
+            if let Some(url) = url.random() {
+                on_change(url.clone());
+            }
+        });
+    });
+    reg_component(args, display_name, true, Component::<String>::new(desc), Some(validator), Some(picker), Default::default)
+}
+
+macro_rules! make_register_primitive_component {
+    ($(($value:ident, $type:ty)),*) => {
+        paste::paste! {
+            /// Creates an editor for a component of any of the primitive types
+            fn register_primitive_component(
+                ty: PrimitiveComponentType,
+                args: RegisterArgs,
+                display_name: &str,
+                desc: ComponentDesc,
+            ) -> Option<(String, Element)> {
+                match ty {
+                    $(
+                    PrimitiveComponentType::$value => register_dynamic_component::<$type>(args, display_name, true, desc),
+                    PrimitiveComponentType::[<Vec $value>] => register_dynamic_component::<Vec<$type>>(args, display_name, false, desc),
+                    PrimitiveComponentType::[<Option $value>] => register_dynamic_component::<Option<$type>>(args, display_name, true, desc),
+                    )*
+                }
+            }
+        }
+    };
+}
+primitive_component_definitions!(make_register_primitive_component);
+
+#[tracing::instrument(level = "info", skip_all)]
+#[profiling::function]
+#[element_component]
+fn EntityComponentsEditor(
+    _hooks: &mut Hooks,
+    value: Entity,
+    original: Option<Entity>,
+    on_change: Cb<dyn Fn(EntityComponentChange) + Sync + Send>,
+) -> Element {
+    let mut missing_components = Vec::new();
 
     macro_rules! reg_default_component {
         ($name:expr, $short:expr, $component:expr) => {
-            reg_component(&value, on_change.clone(), &mut missing_components, $name, $short, $component, Default::default)
+            reg_default_component!($name, $short, $component, None)
+        };
+        ($name:expr, $short:expr, $component:expr, $validator:expr) => {
+            reg_component(
+                (&value, original.as_ref(), on_change.clone(), &mut missing_components),
+                $name,
+                $short,
+                $component,
+                $validator,
+                None,
+                Default::default,
+            )
         };
     }
 
     let mut component_editors = [
         reg_default_component!("Name", true, name()),
         reg_default_component!("Tags", false, tags()),
-        reg_default_component!("Translation", true, translation()),
-        reg_default_component!("Scale", true, scale()),
+        reg_default_component!("Translation", true, translation(), Some(cb(Vec3::validate))),
+        reg_default_component!(
+            "Scale",
+            true,
+            scale(),
+            Some(cb(|scale: &Vec3| {
+                scale.validate()?;
+                if scale.cmpeq(Vec3::ZERO).any() {
+                    Err("Scale can't be zero".to_string())
+                } else {
+                    Ok(())
+                }
+            }))
+        ),
         // reg_default_component!("Model", false, model_def()),
-        // reg_default_component!("Decal", false, decal()),
-        reg_default_component!("Character collider radius", false, character_controller_radius()),
-        reg_default_component!("Character collider height", false, character_controller_height()),
+        reg_default_component!("Decal", false, decal()),
+        reg_default_component!("Character collider radius", false, character_controller_radius(), Some(cb(positive))),
+        reg_default_component!("Character collider height", false, character_controller_height(), Some(cb(positive))),
         // reg_default_component!("Collider", false, collider()),
         reg_default_component!("Collider type", true, collider_type()),
-        reg_default_component!("Mass", true, mass()),
+        reg_default_component!("Mass", true, mass(), Some(cb(positive))),
         reg_default_component!("Audio Emitter", false, ambient_world_audio::audio_emitter()),
         // reg_default_component!("Loop animation", true, loop_animation()),
         reg_default_component!("Animation retargeting", true, animation_retargeting()),
-        reg_default_component!("Snap to ground", true, snap_to_ground()),
+        reg_default_component!("Snap to ground", true, snap_to_ground(), Some(cb(f32::validate))),
     ]
     .into_iter()
     .flatten()
@@ -187,41 +371,15 @@ fn EntityComponentsEditor(_hooks: &mut Hooks, value: Entity, on_change: Cb<dyn F
 
     with_component_registry(|cr| {
         profiling::scope!("setup_component_editors");
-        fn register_dynamic_component<T: ComponentValue + Editor + std::fmt::Debug + Clone + Sync + Send + Default + 'static>(
-            (entity, on_change, missing_components): (
-                &Entity,
-                Cb<dyn Fn(EntityComponentChange) + Sync + Send>,
-                &mut Vec<(String, Arc<dyn Fn() + Sync + Send>)>,
-            ),
-            display_name: &str,
-            desc: ComponentDesc,
-        ) -> Option<(String, Element)> {
-            reg_component(entity, on_change, missing_components, display_name, true, Component::<T>::new(desc), Default::default)
-        }
-
         for (comp, desc) in cr.all_external() {
             let display_name = desc.name().unwrap_or_else(|| desc.path());
-
-            let t = (&value, on_change.clone(), &mut missing_components);
-
-            let element = match comp.ty {
-                PrimitiveComponentType::Empty => register_dynamic_component::<()>(t, &display_name, desc),
-                PrimitiveComponentType::Bool => register_dynamic_component::<bool>(t, &display_name, desc),
-                // ExternalEcsComponent::EntityId => register_dynamic_component(t, &display_name, desc),
-                PrimitiveComponentType::F32 => register_dynamic_component::<f32>(t, &display_name, desc),
-                // ExternalEcsComponent::F64 => register_dynamic_component(t, &display_name, desc),
-                // ExternalEcsComponent::Mat4 => register_dynamic_component(t, &display_name, desc),
-                PrimitiveComponentType::I32 => register_dynamic_component::<i32>(t, &display_name, desc),
-                // ExternalEcsComponent::Quat => register_dynamic_component(t, &display_name, desc),
-                PrimitiveComponentType::String => register_dynamic_component::<String>(t, &display_name, desc),
-                PrimitiveComponentType::U32 => register_dynamic_component::<u32>(t, &display_name, desc),
-                PrimitiveComponentType::U64 => register_dynamic_component::<u64>(t, &display_name, desc),
-                PrimitiveComponentType::Vec2 => register_dynamic_component::<Vec2>(t, &display_name, desc),
-                PrimitiveComponentType::Vec3 => register_dynamic_component::<Vec3>(t, &display_name, desc),
-                PrimitiveComponentType::Vec4 => register_dynamic_component::<Vec4>(t, &display_name, desc),
-                _ => None,
+            let args = (&value, original.as_ref(), on_change.clone(), &mut missing_components);
+            let element = match asset_url_type(&comp.path) {
+                Some(asset_type) if comp.ty == PrimitiveComponentType::String => {
+                    register_asset_url_component(args, &display_name, desc, asset_type)
+                }
+                _ => register_primitive_component(comp.ty, args, &display_name, desc),
             };
-
             if let Some(element) = element {
                 component_editors.push(element);
             }
@@ -253,7 +411,7 @@ fn EntityComponentsEditor(_hooks: &mut Hooks, value: Entity, on_change: Cb<dyn F
                     Button::new(
                         FlowRow::el([Text::el(format!("Animation errors:\n{}", anim_error.split(": ").join(":\n"))).error_text_style()]),
                         move |_| {
-                            arboard::Clipboard::new().unwrap().set_text(anim_error.clone()).ok();
+                            ambient_sys::clipboard::set_text(&anim_error);
                         },
                     )
                     .style(ButtonStyle::Flat)
@@ -273,12 +431,17 @@ fn ComponentEditor<T: ComponentValue + Editor + std::fmt::Debug + Clone + Sync +
     hooks: &mut Hooks,
     component: Component<T>,
     value: T,
+    /// The value when the entity was first inspected, if it differs from the current value
+    original: Option<T>,
     display_name: String,
     inline: bool,
+    validator: Option<Validator<T>>,
+    picker: Option<Picker<T>>,
     on_change: Cb<dyn Fn(ComponentEntry) + Sync + Send>,
     on_remove: Cb<dyn Fn() + Sync + Send>,
 ) -> Element {
     let (screen, set_screen) = hooks.use_state(None);
+    let (error, set_error) = hooks.use_state::<Option<String>>(None);
     let remove = Button::new("\u{f6bf}", move |_| {
         on_remove();
     })
@@ -286,46 +449,78 @@ fn ComponentEditor<T: ComponentValue + Editor + std::fmt::Debug + Clone + Sync +
     .tooltip("Delete")
     .el()
     .set(margin(), Borders::right(STREET));
-
-    FlowRow(vec![
-        ScreenContainer(screen).el(),
-        remove,
-        Text::el(&display_name).set(margin(), Borders::right(STREET)),
-        FlowRow(vec![if inline {
-            T::editor(
-                value,
-                cb(move |new_value| {
-                    on_change(ComponentEntry::new(component, new_value));
-                }),
-                Default::default(),
-            )
-        } else {
-            Button::new("\u{fb4e} Edit", move |_| {
-                set_screen(Some(
-                    EditorPrompt {
-                        title: display_name.clone(),
-                        value: value.clone(),
-                        set_screen: set_screen.clone(),
-                        on_ok: cb({
-                            let on_change = on_change.clone();
-                            move |_, new_value| {
-                                on_change(ComponentEntry::new(component, new_value));
-                            }
-                        }),
-                        on_cancel: Some(cb(|_| {})),
-                        validator: None,
-                    }
-                    .el(),
-                ));
-            })
+    let revert = if let Some(original) = original {
+        let on_change = on_change.clone();
+        let set_error = set_error.clone();
+        Button::new("\u{f0e2}", move |_| {
+            set_error(None);
+            on_change(ComponentEntry::new(component, original.clone()));
+        })
+        .style(ButtonStyle::Flat)
+        .tooltip("Revert")
+        .el()
+        .set(margin(), Borders::right(STREET))
+    } else {
+        Element::new()
+    };
+    let on_change = cb({
+        let validator = validator.clone();
+        move |new_value: T| match validator.as_ref().map(|v| v(&new_value)).unwrap_or(Ok(())) {
+            Ok(()) => {
+                set_error(None);
+                on_change(ComponentEntry::new(component, new_value));
+            }
+            Err(err) => set_error(Some(err)),
+        }
+    });
+    let browse = if let Some(picker) = picker {
+        let on_change = on_change.clone();
+        Button::new("\u{f74e} Browse", move |world| picker(world, on_change.clone()))
             .style(ButtonStyle::Flat)
             .el()
-        }])
+            .set(margin(), Borders::left(STREET))
+    } else {
+        Element::new()
+    };
+
+    FlowColumn::el([
+        FlowRow(vec![
+            ScreenContainer(screen).el(),
+            remove,
+            revert,
+            Text::el(&display_name).set(margin(), Borders::right(STREET)),
+            FlowRow(vec![if inline {
+                FlowRow::el([T::editor(value, on_change, Default::default()), browse])
+            } else {
+                Button::new("\u{fb4e} Edit", move |_| {
+                    set_screen(Some(
+                        EditorPrompt {
+                            title: display_name.clone(),
+                            value: value.clone(),
+                            set_screen: set_screen.clone(),
+                            on_ok: cb({
+                                let on_change = on_change.clone();
+                                move |_, new_value| on_change(new_value)
+                            }),
+                            on_cancel: Some(cb(|_| {})),
+                            validator: validator
+                                .clone()
+                                .map(|v| cb(move |value: &T| v(value).is_ok()) as Cb<dyn Fn(&T) -> bool + Sync + Send>),
+                        }
+                        .el(),
+                    ));
+                })
+                .style(ButtonStyle::Flat)
+                .el()
+            }])
+            .el()
+            .set(align_horizontal(), Align::End)
+            .set(fit_horizontal(), Fit::Parent),
+        ])
         .el()
-        .set(align_horizontal(), Align::End)
+        .set(align_vertical(), Align::Center)
         .set(fit_horizontal(), Fit::Parent),
+        if let Some(error) = error { Text::el(error).error_text_style() } else { Element::new() },
     ])
-    .el()
-    .set(align_vertical(), Align::Center)
     .set(fit_horizontal(), Fit::Parent)
 }
@@ -0,0 +1,271 @@
+use std::{collections::HashMap, time::Duration};
+
+use ambient_core::{
+    hierarchy::{children, parent},
+    name, runtime, selectable,
+};
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks, Setter};
+use ambient_event_types::WINDOW_MOUSE_INPUT;
+use ambient_input::event_mouse_input;
+use ambient_intent::client_push_intent;
+use ambient_network::{client::GameClient, is_remote_entity, log_network_result};
+use ambient_std::color::Color;
+use ambient_ui::{
+    margin, space_between_items, Borders, Button, ButtonStyle, FlowColumn, FlowRow, ScrollArea, Separator, Text, TextEditor, UIExt, STREET,
+};
+use ambient_window_types::MouseButton;
+use itertools::Itertools;
+
+use crate::{
+    editor_hidden, editor_locked,
+    intents::{intent_reparent, intent_set_hidden, intent_set_locked, IntentReparent, SelectMode},
+    rpc::{rpc_select, SelectFilter, SelectMethod},
+    Selection,
+};
+
+use super::use_player_selection;
+
+#[derive(Debug, Clone, PartialEq)]
+struct HierarchyNode {
+    name: String,
+    children: Vec<EntityId>,
+    hidden: bool,
+    locked: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct HierarchyTree {
+    roots: Vec<EntityId>,
+    nodes: HashMap<EntityId, HierarchyNode>,
+}
+
+fn read_tree(game_client: &GameClient) -> HierarchyTree {
+    let state = game_client.game_state.lock();
+    let world = &state.world;
+    let mut nodes = HashMap::new();
+    let mut stack = query(selectable()).incl(is_remote_entity()).iter(world, None).map(|(id, _)| id).collect_vec();
+    while let Some(id) = stack.pop() {
+        if nodes.contains_key(&id) || !world.exists(id) {
+            continue;
+        }
+        let children = world.get_ref(id, children()).cloned().unwrap_or_default();
+        stack.extend(children.iter().copied());
+        nodes.insert(
+            id,
+            HierarchyNode {
+                name: world.get_ref(id, name()).cloned().unwrap_or_else(|_| id.to_string()),
+                children,
+                hidden: world.has_component(id, editor_hidden()),
+                locked: world.has_component(id, editor_locked()),
+            },
+        );
+    }
+    let roots = nodes
+        .keys()
+        .filter(|&&id| world.get(id, parent()).map(|parent| !nodes.contains_key(&parent)).unwrap_or(true))
+        .copied()
+        .sorted_by_key(|id| nodes[id].name.clone())
+        .collect();
+    HierarchyTree { roots, nodes }
+}
+
+/// Shows the parent/children tree of the scene. Entities can be dragged onto each other to reparent them.
+#[element_component]
+pub fn HierarchyPanel(hooks: &mut Hooks, dock_right: bool, set_dock_right: Setter<bool>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (select_mode, _) = hooks.consume_context::<SelectMode>().unwrap();
+    let (selection, _) = use_player_selection(hooks);
+    let (tree, set_tree) = hooks.use_state(HierarchyTree::default());
+    let (search, set_search) = hooks.use_state(String::new());
+    let (expanded, set_expanded) = hooks.use_state(Vec::<EntityId>::new());
+    let (dragging, set_dragging) = hooks.use_state::<Option<EntityId>>(None);
+
+    let prev_tree = hooks.use_ref_with(|_| HierarchyTree::default());
+    hooks.use_interval_deps(Duration::from_millis(500), true, (), {
+        let game_client = game_client.clone();
+        move |_| {
+            let tree = read_tree(&game_client);
+            let mut prev_tree = prev_tree.lock();
+            if *prev_tree != tree {
+                *prev_tree = tree.clone();
+                set_tree(tree);
+            }
+        }
+    });
+    hooks.use_event(WINDOW_MOUSE_INPUT, {
+        let set_dragging = set_dragging.clone();
+        move |_, event| {
+            if event.get(event_mouse_input()) == Some(false) {
+                set_dragging(None);
+            }
+        }
+    });
+
+    let push_reparent = {
+        let game_client = game_client.clone();
+        let selection = selection.clone();
+        move |world: &mut World, dragged: EntityId, parent: Option<EntityId>| {
+            // Dragging one of the selected entities moves all of them
+            let entities = if selection.contains(&dragged) { selection.entities.clone() } else { vec![dragged] };
+            if parent.map(|parent| entities.contains(&parent)).unwrap_or(false) {
+                return;
+            }
+            world.resource(runtime()).spawn(client_push_intent(
+                game_client.clone(),
+                intent_reparent(),
+                IntentReparent { entities, parent },
+                None,
+                None,
+            ));
+        }
+    };
+
+    let row = |id: EntityId, depth: usize| -> Element {
+        let node = &tree.nodes[&id];
+        let is_expanded = expanded.contains(&id);
+        let is_selected = selection.contains(&id);
+        FlowRow::el([
+            if node.children.is_empty() {
+                Text::el("").set(margin(), Borders::left(20.))
+            } else {
+                Button::new(if is_expanded { "\u{f0d7}" } else { "\u{f0da}" }, {
+                    let expanded = expanded.clone();
+                    let set_expanded = set_expanded.clone();
+                    move |_| {
+                        let mut expanded = expanded.clone();
+                        if let Some(index) = expanded.iter().position(|&x| x == id) {
+                            expanded.remove(index);
+                        } else {
+                            expanded.push(id);
+                        }
+                        set_expanded(expanded);
+                    }
+                })
+                .style(ButtonStyle::Flat)
+                .el()
+            },
+            Text::el(&node.name)
+                .with_background(
+                    if is_selected {
+                        Color::rgba(0.2, 0.4, 1., 0.6)
+                    } else if dragging.is_some() && dragging != Some(id) {
+                        Color::rgba(1., 1., 1., 0.05)
+                    } else {
+                        Color::rgba(0., 0., 0., 0.)
+                    }
+                    .into(),
+                )
+                .with_clickarea()
+                .on_mouse_down({
+                    let game_client = game_client.clone();
+                    let set_dragging = set_dragging.clone();
+                    move |world, _, button| {
+                        if button != MouseButton::Left {
+                            return;
+                        }
+                        set_dragging(Some(id));
+                        let game_client = game_client.clone();
+                        world.resource(runtime()).spawn(async move {
+                            log_network_result!(
+                                game_client
+                                    .rpc(rpc_select, (SelectMethod::Manual(Selection::new([id])), select_mode, SelectFilter::default()))
+                                    .await
+                            );
+                        });
+                    }
+                })
+                .on_mouse_up({
+                    let push_reparent = push_reparent.clone();
+                    move |world, _, button| {
+                        if let (Some(dragged), MouseButton::Left) = (dragging, button) {
+                            if dragged != id {
+                                push_reparent(world, dragged, Some(id));
+                            }
+                        }
+                    }
+                })
+                .el(),
+            Button::new(if node.hidden { "\u{f070}" } else { "\u{f06e}" }, {
+                let game_client = game_client.clone();
+                let hidden = node.hidden;
+                move |world| {
+                    world.resource(runtime()).spawn(client_push_intent(
+                        game_client.clone(),
+                        intent_set_hidden(),
+                        (vec![id], !hidden),
+                        None,
+                        None,
+                    ));
+                }
+            })
+            .tooltip(if node.hidden { "Show" } else { "Hide" })
+            .style(ButtonStyle::Flat)
+            .el(),
+            Button::new(if node.locked { "\u{f023}" } else { "\u{f09c}" }, {
+                let game_client = game_client.clone();
+                let locked = node.locked;
+                move |world| {
+                    world.resource(runtime()).spawn(client_push_intent(
+                        game_client.clone(),
+                        intent_set_locked(),
+                        (vec![id], !locked),
+                        None,
+                        None,
+                    ));
+                }
+            })
+            .tooltip(if node.locked { "Unlock" } else { "Lock" })
+            .style(ButtonStyle::Flat)
+            .el(),
+        ])
+        .set(margin(), Borders::left(depth as f32 * 16.))
+    };
+
+    let mut rows = Vec::new();
+    if search.is_empty() {
+        let mut stack = tree.roots.iter().rev().map(|&id| (id, 0)).collect_vec();
+        while let Some((id, depth)) = stack.pop() {
+            rows.push(row(id, depth));
+            if expanded.contains(&id) {
+                stack.extend(tree.nodes[&id].children.iter().rev().filter(|id| tree.nodes.contains_key(id)).map(|&id| (id, depth + 1)));
+            }
+        }
+    } else {
+        let search = search.to_lowercase();
+        rows.extend(
+            tree.nodes
+                .iter()
+                .filter(|(_, node)| node.name.to_lowercase().contains(&search))
+                .sorted_by_key(|(_, node)| node.name.clone())
+                .map(|(&id, _)| row(id, 0)),
+        );
+    }
+
+    FlowColumn::el([
+        FlowRow::el([
+            Text::el("Hierarchy"),
+            Button::new(if dock_right { "\u{f053}" } else { "\u{f054}" }, move |_| set_dock_right(!dock_right))
+                .tooltip("Move panel")
+                .style(ButtonStyle::Flat)
+                .el(),
+        ])
+        .set(space_between_items(), STREET),
+        TextEditor::new(search, set_search).placeholder(Some("Search")).el(),
+        Separator { vertical: false }.el(),
+        if dragging.is_some() {
+            Text::el("Drop here to move to the root")
+                .with_clickarea()
+                .on_mouse_up(move |world, _, _| {
+                    if let Some(dragged) = dragging {
+                        push_reparent(world, dragged, None);
+                    }
+                })
+                .el()
+        } else {
+            Element::new()
+        },
+        ScrollArea(FlowColumn(rows).el()).el(),
+    ])
+    .set(space_between_items(), 4.)
+}
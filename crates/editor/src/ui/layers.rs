@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use ambient_core::{name, runtime};
+use ambient_ecs::{query, EntityId, World};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_intent::client_push_intent;
+use ambient_network::{client::GameClient, is_remote_entity};
+use ambient_ui::{space_between_items, Button, ButtonStyle, FlowColumn, FlowRow, Separator, Text, TextEditor, STREET};
+use itertools::Itertools;
+
+use crate::{
+    editor_hidden, editor_layer, editor_locked,
+    intents::{intent_create_layer, intent_set_layer, intent_set_layer_hidden, intent_set_layer_locked},
+    layer,
+};
+
+use super::use_player_selection;
+
+#[derive(Debug, Clone, PartialEq)]
+struct LayerInfo {
+    id: EntityId,
+    name: String,
+    members: usize,
+    hidden: bool,
+    locked: bool,
+}
+
+fn read_layers(game_client: &GameClient) -> Vec<LayerInfo> {
+    let state = game_client.game_state.lock();
+    let world = &state.world;
+    let members = query(layer()).iter(world, None).map(|(_, &layer)| layer).counts();
+    query(name())
+        .incl(editor_layer())
+        .incl(is_remote_entity())
+        .iter(world, None)
+        .map(|(id, name)| LayerInfo {
+            id,
+            name: name.clone(),
+            members: members.get(&id).copied().unwrap_or_default(),
+            hidden: world.has_component(id, editor_hidden()),
+            locked: world.has_component(id, editor_locked()),
+        })
+        .sorted_by_key(|layer| layer.name.clone())
+        .collect()
+}
+
+/// Lists the layers of the scene, and lets the user create new ones, move the selection to them, and hide or lock them
+#[element_component]
+pub fn LayersPanel(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (selection, _) = use_player_selection(hooks);
+    let (layers, set_layers) = hooks.use_state(Vec::<LayerInfo>::new());
+    let (new_layer_name, set_new_layer_name) = hooks.use_state(String::new());
+
+    let prev_layers = hooks.use_ref_with(|_| Vec::<LayerInfo>::new());
+    hooks.use_interval_deps(Duration::from_millis(500), true, (), {
+        let game_client = game_client.clone();
+        move |_| {
+            let layers = read_layers(&game_client);
+            let mut prev_layers = prev_layers.lock();
+            if *prev_layers != layers {
+                *prev_layers = layers.clone();
+                set_layers(layers);
+            }
+        }
+    });
+
+    let set_selection_layer = {
+        let game_client = game_client.clone();
+        let selection = selection.clone();
+        move |world: &mut World, layer: Option<EntityId>| {
+            world.resource(runtime()).spawn(client_push_intent(
+                game_client.clone(),
+                intent_set_layer(),
+                (selection.entities.clone(), layer),
+                None,
+                None,
+            ));
+        }
+    };
+
+    FlowColumn::el([
+        Text::el("Layers"),
+        FlowRow::el([
+            TextEditor::new(new_layer_name.clone(), set_new_layer_name.clone()).placeholder(Some("New layer")).el(),
+            Button::new("\u{f067}", {
+                let game_client = game_client.clone();
+                move |world| {
+                    world.resource(runtime()).spawn(client_push_intent(
+                        game_client.clone(),
+                        intent_create_layer(),
+                        (EntityId::new(), new_layer_name.clone()),
+                        None,
+                        None,
+                    ));
+                    set_new_layer_name(String::new());
+                }
+            })
+            .tooltip("Create layer")
+            .disabled(new_layer_name.is_empty())
+            .el(),
+        ])
+        .set(space_between_items(), STREET),
+        Separator { vertical: false }.el(),
+        FlowColumn::el(
+            layers
+                .into_iter()
+                .map(|info| {
+                    FlowRow::el([
+                        Button::new(if info.hidden { "\u{f070}" } else { "\u{f06e}" }, {
+                            let game_client = game_client.clone();
+                            move |world| {
+                                world.resource(runtime()).spawn(client_push_intent(
+                                    game_client.clone(),
+                                    intent_set_layer_hidden(),
+                                    (info.id, !info.hidden),
+                                    None,
+                                    None,
+                                ));
+                            }
+                        })
+                        .tooltip(if info.hidden { "Show layer" } else { "Hide layer" })
+                        .style(ButtonStyle::Flat)
+                        .el(),
+                        Button::new(if info.locked { "\u{f023}" } else { "\u{f09c}" }, {
+                            let game_client = game_client.clone();
+                            move |world| {
+                                world.resource(runtime()).spawn(client_push_intent(
+                                    game_client.clone(),
+                                    intent_set_layer_locked(),
+                                    (info.id, !info.locked),
+                                    None,
+                                    None,
+                                ));
+                            }
+                        })
+                        .tooltip(if info.locked { "Unlock layer" } else { "Lock layer" })
+                        .style(ButtonStyle::Flat)
+                        .el(),
+                        Text::el(format!("{} ({})", info.name, info.members)),
+                        Button::new("\u{f0ab}", {
+                            let set_selection_layer = set_selection_layer.clone();
+                            move |world| set_selection_layer(world, Some(info.id))
+                        })
+                        .tooltip("Move the selection to this layer")
+                        .style(ButtonStyle::Flat)
+                        .disabled(selection.is_empty())
+                        .el(),
+                    ])
+                    .set(space_between_items(), 4.)
+                })
+                .collect_vec(),
+        ),
+        Button::new("Remove selection from its layer", move |world| set_selection_layer(world, None))
+            .style(ButtonStyle::Flat)
+            .disabled(selection.is_empty())
+            .el(),
+    ])
+    .set(space_between_items(), 4.)
+}
@@ -2,14 +2,20 @@ use std::{collections::HashMap, fmt::Debug, io::Cursor};
 
 mod build_mode;
 pub mod entity_editor;
+mod hierarchy;
+mod layers;
+mod presence;
 mod terrain_mode;
+mod undo;
+mod world_overlay;
 
-use ambient_core::{game_mode, runtime, transform::translation, GameMode};
+use ambient_core::{asset_cache, game_mode, runtime, transform::translation, GameMode};
 use ambient_ecs::{Entity, EntityId};
 use ambient_element::{element_component, Element, ElementComponent, ElementComponentExt, Group, Hooks, Setter};
-use ambient_intent::{rpc_redo, rpc_undo_head, IntentHistoryVisualizer};
-use ambient_naturals::{get_default_natural_layers, natural_layers, NaturalLayer, NaturalsPreset};
+use ambient_intent::IntentHistoryVisualizer;
+use ambient_naturals::{get_default_natural_layers, natural_layers, natural_scatter_preview, NaturalLayer, NaturalsPreset};
 use ambient_network::{
+    capture::capture,
     client::GameClient,
     hooks::{use_remote_persisted_resource, use_remote_player_component},
     log_network_result,
@@ -18,16 +24,21 @@ use ambient_network::{
     unwrap_log_network_err,
 };
 use ambient_physics::make_physics_static;
-use ambient_std::{cb, color::Color, Cb};
+use ambient_std::{
+    asset_url::{select_asset, AssetType},
+    cb,
+    color::Color,
+    Cb,
+};
 use ambient_terrain::{
-    brushes::{Brush, BrushShape, BrushSize, BrushSmoothness, BrushStrength, HydraulicErosionConfig},
+    brushes::{Brush, BrushShape, BrushSize, BrushSmoothness, BrushStrength, HydraulicErosionConfig, TerrainStamp},
     terrain_material_def, TerrainMaterialDef,
 };
 use ambient_ui::{
-    command_modifier, height,
+    align_vertical, command_modifier, height,
     layout::{docking, space_between_items, width, Borders, Docking},
-    margin, use_window_logical_resolution, Button, Editor, FlowColumn, FlowRow, FontAwesomeIcon, Hotkey, Rectangle, ScreenContainer,
-    ScrollArea, Separator, StylesExt, Text, UIExt, WindowSized, STREET,
+    margin, use_window_logical_resolution, Align, Button, ButtonStyle, Editor, FlowColumn, FlowRow, FontAwesomeIcon, Hotkey, Rectangle,
+    ScreenContainer, ScrollArea, Separator, StylesExt, Text, UIExt, WindowSized, STREET,
 };
 use ambient_window_types::{ModifiersState, VirtualKeyCode};
 use build_mode::*;
@@ -35,8 +46,13 @@ use glam::{vec3, Vec3};
 use image::{DynamicImage, ImageOutputFormat, RgbImage};
 use itertools::Itertools;
 use terrain_mode::*;
+use undo::*;
+use world_overlay::*;
 
-use crate::{selection, Selection};
+use crate::{
+    rpc::{rpc_close_prefab, rpc_open_prefab, rpc_restore_main_instance, rpc_save_prefab, rpc_snapshot_main_instance},
+    selection, Selection,
+};
 use serde::{de::DeserializeOwned, Serialize};
 
 pub fn use_player_selection(hooks: &mut Hooks) -> (Selection, Setter<Selection>) {
@@ -79,11 +95,19 @@ pub struct EditorSettings {
 #[derive(Debug, Clone)]
 pub struct EditingEntityContext(pub EntityId);
 
+/// A prefab opened in isolation, see [crate::rpc::rpc_open_prefab]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditingPrefab {
+    pub url: String,
+    pub instance_id: String,
+}
+
 const PLAY_INSTANCE_ID: &str = "play";
 
 #[element_component]
 pub fn EditorUI(hooks: &mut Hooks) -> Element {
     let (editor_mode, set_editor_mode) = hooks.use_state(EditorMode::Build);
+    let (prefab, set_prefab) = hooks.use_state::<Option<EditingPrefab>>(None);
 
     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
     let (hide_ui, set_hide_ui) = hooks.use_state(false);
@@ -91,6 +115,10 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
     let (screen, _set_screen) = hooks.use_state(None);
 
     hooks.provide_context(EditorPrefs::default);
+    hooks.provide_context(LocalUndoStack::default);
+    hooks.provide_context(Measurement::default);
+    let local_undo = use_local_undo(hooks);
+    presence::use_share_camera(hooks);
 
     hooks.provide_context(|| Brush::Raise);
     hooks.provide_context(|| 0u32);
@@ -99,30 +127,46 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
     hooks.provide_context(|| BrushShape::Circle);
     hooks.provide_context(|| BrushSmoothness(1.));
     hooks.provide_context(HydraulicErosionConfig::default);
+    hooks.provide_context(|| None::<TerrainStamp>);
 
-    hooks.use_effect(editor_mode, {
+    hooks.use_effect((editor_mode, prefab.clone()), {
         let game_client = game_client.clone();
+        let prefab = prefab.clone();
         move |world, _| {
-            world.resource(runtime()).spawn(async move {
-                if editor_mode == EditorMode::Experience {
-                    let id = unwrap_log_network_err!(
-                        game_client
-                            .rpc(
-                                rpc_fork_instance,
-                                RpcForkInstance {
-                                    resources: Entity::new().with(make_physics_static(), false),
-                                    synced_res: Entity::new().with(game_mode(), GameMode::Play),
-                                    id: Some(PLAY_INSTANCE_ID.to_string())
-                                }
-                            )
-                            .await
-                    );
-                    log_network_result!(game_client.rpc(rpc_join_instance, id).await);
-                } else {
-                    log_network_result!(game_client.rpc(rpc_join_instance, MAIN_INSTANCE_ID.to_string()).await);
+            let play_testing = prefab.is_none() && editor_mode == EditorMode::Experience;
+            world.resource(runtime()).spawn({
+                let game_client = game_client.clone();
+                async move {
+                    if let Some(prefab) = prefab {
+                        log_network_result!(game_client.rpc(rpc_join_instance, prefab.instance_id).await);
+                    } else if editor_mode == EditorMode::Experience {
+                        log_network_result!(game_client.rpc(rpc_snapshot_main_instance, ()).await);
+                        let id = unwrap_log_network_err!(
+                            game_client
+                                .rpc(
+                                    rpc_fork_instance,
+                                    RpcForkInstance {
+                                        resources: Entity::new().with(make_physics_static(), false),
+                                        synced_res: Entity::new().with(game_mode(), GameMode::Play),
+                                        id: Some(PLAY_INSTANCE_ID.to_string())
+                                    }
+                                )
+                                .await
+                        );
+                        log_network_result!(game_client.rpc(rpc_join_instance, id).await);
+                    } else {
+                        log_network_result!(game_client.rpc(rpc_join_instance, MAIN_INSTANCE_ID.to_string()).await);
+                    }
                 }
             });
-            Box::new(|_| {})
+            Box::new(move |world| {
+                // Undo whatever happened to the main instance while play-testing
+                if play_testing {
+                    world.resource(runtime()).spawn(async move {
+                        log_network_result!(game_client.rpc(rpc_restore_main_instance, ()).await);
+                    });
+                }
+            })
         }
     });
 
@@ -133,6 +177,7 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
 
     Group(vec![
         Crosshair.el(),
+        WorldOverlay { show_annotations: editor_mode != EditorMode::Experience }.el(),
         WindowSized(vec![
             ScreenContainer(screen).el(),
             FlowColumn::el([FlowRow::el([
@@ -185,40 +230,67 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
                 .tooltip("Ground materials")
                 .el(),
                 Separator { vertical: true }.el(),
+                Button::new(FontAwesomeIcon::el(0xf1b2, true), {
+                    let game_client = game_client.clone();
+                    let set_prefab = set_prefab.clone();
+                    let set_editor_mode = set_editor_mode.clone();
+                    move |world| {
+                        let game_client = game_client.clone();
+                        let set_prefab = set_prefab.clone();
+                        let set_editor_mode = set_editor_mode.clone();
+                        let runtime = world.resource(runtime()).clone();
+                        select_asset(world.resource(asset_cache()), AssetType::Prefab, move |url| {
+                            let Some(url) = url.random().cloned() else { return };
+                            runtime.spawn(async move {
+                                let result = game_client.rpc(rpc_open_prefab, url.clone()).await;
+                                let instance_id = unwrap_log_network_err!(result);
+                                match instance_id {
+                                    Ok(instance_id) => {
+                                        set_editor_mode(EditorMode::Build);
+                                        set_prefab(Some(EditingPrefab { url, instance_id }));
+                                    }
+                                    Err(err) => log::error!("Failed to open prefab: {err}"),
+                                }
+                            });
+                        });
+                    }
+                })
+                .tooltip("Edit prefab")
+                .el(),
+                Separator { vertical: true }.el(),
                 Button::new(FontAwesomeIcon::el(0xf815, true), closure!(clone set_hide_ui, |_| set_hide_ui(true)))
                     .hotkey(VirtualKeyCode::P)
                     .hotkey_modifier(command_modifier())
                     .tooltip("Hide UI")
                     .el(),
-                // UploadThumbnailButton.el(),
+                ThumbnailButton.el(),
                 Button::new_async(FontAwesomeIcon::el(0xf2ea, true), {
                     let game_client = game_client.clone();
-                    move || {
-                        let game_client = game_client.clone();
-                        async move {
-                            game_client.rpc(rpc_undo_head, ()).await.ok();
-                        }
-                    }
+                    let local_undo = local_undo.clone();
+                    move || undo(game_client.clone(), local_undo.clone())
                 })
                 .hotkey(VirtualKeyCode::Z)
                 .hotkey_modifier(command_modifier())
                 .tooltip("Undo")
                 .el(),
-                Button::new_async(FontAwesomeIcon::el(0xf2f9, true), move || {
-                    let game_client = game_client.clone();
-                    async move {
-                        game_client.rpc(rpc_redo, ()).await.ok();
-                    }
-                })
-                .hotkey(VirtualKeyCode::Z)
-                .hotkey_modifier(command_modifier() | ModifiersState::SHIFT)
-                .tooltip("Redo")
-                .el(),
+                Button::new_async(FontAwesomeIcon::el(0xf2f9, true), move || redo(game_client.clone(), local_undo.clone()))
+                    .hotkey(VirtualKeyCode::Z)
+                    .hotkey_modifier(command_modifier() | ModifiersState::SHIFT)
+                    .tooltip("Redo")
+                    .el(),
                 ServerInstancesInfo.el(),
             ])
             .floating_panel()
             .keyboard()
             .set(margin(), Borders::even(STREET).set_bottom(0.))]),
+            if let Some(prefab) = prefab {
+                PrefabBreadcrumb { prefab, set_prefab }
+                    .el()
+                    .set(margin(), Borders::even(STREET).set_bottom(0.))
+                    .set(docking(), Docking::Top)
+            } else {
+                Element::new()
+            },
             if user_settings.debug_intents {
                 IntentHistoryVisualizer.el().set(margin(), Borders::even(STREET)).set(docking(), Docking::Top)
             } else {
@@ -238,6 +310,54 @@ pub fn EditorUI(hooks: &mut Hooks) -> Element {
     .el()
 }
 
+/// Shows which prefab is being edited, and lets the user save it or go back to the main scene
+#[element_component]
+fn PrefabBreadcrumb(hooks: &mut Hooks, prefab: EditingPrefab, set_prefab: Setter<Option<EditingPrefab>>) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (status, set_status) = hooks.use_state::<Option<String>>(None);
+    let name = prefab.url.trim_end_matches("/prefabs/main.json").rsplit('/').next().unwrap_or(&prefab.url).to_string();
+
+    FlowRow::el([
+        Button::new("Main scene", {
+            let game_client = game_client.clone();
+            let instance_id = prefab.instance_id.clone();
+            move |world| {
+                let game_client = game_client.clone();
+                let instance_id = instance_id.clone();
+                set_prefab(None);
+                world.resource(runtime()).spawn(async move {
+                    log_network_result!(game_client.rpc(rpc_join_instance, MAIN_INSTANCE_ID.to_string()).await);
+                    log_network_result!(game_client.rpc(rpc_close_prefab, instance_id).await);
+                });
+            }
+        })
+        .style(ButtonStyle::Flat)
+        .el(),
+        Text::el("\u{f105}"),
+        Text::el(name),
+        Button::new_async("\u{f0c7} Save", move || {
+            let game_client = game_client.clone();
+            let set_status = set_status.clone();
+            async move {
+                let result = game_client.rpc(rpc_save_prefab, ()).await;
+                set_status(Some(match result {
+                    Ok(Ok(())) => "Saved".to_string(),
+                    Ok(Err(err)) => format!("Failed to save: {err}"),
+                    Err(err) => format!("Failed to save: {err:?}"),
+                }));
+            }
+        })
+        .hotkey(VirtualKeyCode::S)
+        .hotkey_modifier(command_modifier())
+        .el(),
+        if let Some(status) = status { Text::el(status).small_style() } else { Element::new() },
+    ])
+    .set(space_between_items(), STREET)
+    .set(align_vertical(), Align::Center)
+    .floating_panel()
+    .keyboard()
+}
+
 #[element_component]
 fn ServerInstancesInfo(hooks: &mut Hooks) -> Element {
     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
@@ -351,12 +471,23 @@ fn NaturalLayersEditor(hooks: &mut Hooks) -> Element {
     let (value, set_value) = use_remote_persisted_resource(hooks, natural_layers());
     let value = value.unwrap_or_else(|| get_default_natural_layers(NaturalsPreset::Mountains));
     let set_value = cb(move |value| set_value(Some(value)));
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (preview, set_preview) = hooks.use_state(true);
+    hooks.use_effect(preview, move |_, &preview| {
+        game_client.game_state.lock().world.add_resource(natural_scatter_preview(), preview);
+        Box::new(|_| {})
+    });
     FlowColumn::el([
         EditorPlayerInputHandler.el(),
         ScrollArea::el(
             FlowColumn::el([
                 FlowRow::el([
                     CopyPasteButtons { value: value.clone(), on_change: set_value.clone() }.el().set(margin(), Borders::bottom(STREET)),
+                    Button::new("Preview scatter", move |_| set_preview(!preview))
+                        .toggled(preview)
+                        .tooltip("Show the Poisson-disk layers on the terrain")
+                        .el()
+                        .set(margin(), Borders::bottom(STREET)),
                     // SelectAndDownloadJsonAssetButton2::<Vec<NaturalLayer>> {
                     //     asset_type: AssetType::Biomes,
                     //     on_select_file: Cb::new({
@@ -484,41 +615,42 @@ pub fn EditorPlayerMovementHandler(_hooks: &mut Hooks, _flag_as_updated: Cb<dyn
     Element::new()
 }
 
-// #[element_component]
-// fn UploadThumbnailButton(hooks: &mut Hooks) -> Element {
-//     let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
-//     let (world_instance_config, _) = hooks.consume_context::<Option<WorldInstanceConfig>>().unwrap();
-//     let (render_target, _) = hooks.consume_context::<GameClientRenderTarget>().unwrap();
-//     Button::new_async("\u{f030}", move || {
-//         let game_client = game_client.clone();
-//         let reader = render_target.0.color_buffer.reader();
-//         let map_url = world_instance_config.as_ref().unwrap().map_url.clone();
-//         async move {
-//             let screenshot = reader.read_image().await.unwrap().into_rgb8();
-//             let aspect = 720. / 1280.;
-//             let expected_height = screenshot.width() as f32 * aspect;
-//             let cropped = if (screenshot.height() as f32) < expected_height {
-//                 // keep height, crop width
-//                 let new_width = screenshot.height() as f32 / aspect;
-//                 let diff = screenshot.width() as f32 - new_width;
-//                 image::imageops::crop_imm(&screenshot, (diff / 2.) as u32, 0, new_width as u32, screenshot.height())
-//             } else {
-//                 // keep width, crop height
-//                 let diff = screenshot.height() as f32 - expected_height;
-//                 image::imageops::crop_imm(&screenshot, 0, (diff / 2.) as u32, screenshot.width(), expected_height as u32)
-//             }
-//             .to_image();
-//             let thumbnail = image::imageops::resize(&cropped, 640, 360, image::imageops::FilterType::CatmullRom);
-
-//             // log_network_result!(game_client.rpc(rpc_upload_thumbnail, (map_url, image_to_png(thumbnail))).await);
-//             // original: &image_to_png(screenshot)
-//         }
-//     })
-//     .tooltip("Upload thumbnail")
-//     .el()
-// }
-
-fn _image_to_png(image: RgbImage) -> Vec<u8> {
+/// Saves a 640x360 thumbnail of the game view as `thumbnail.png`
+#[element_component]
+fn ThumbnailButton(hooks: &mut Hooks) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    Button::new(FontAwesomeIcon::el(0xf030, true), move |_| {
+        let capture = game_client.game_state.lock().world.resource(capture()).clone();
+        capture.capture_frame(Box::new(|screenshot| {
+            let screenshot = DynamicImage::ImageRgba8(screenshot).into_rgb8();
+            let aspect = 720. / 1280.;
+            let expected_height = screenshot.width() as f32 * aspect;
+            let cropped = if (screenshot.height() as f32) < expected_height {
+                // keep height, crop width
+                let new_width = screenshot.height() as f32 / aspect;
+                let diff = screenshot.width() as f32 - new_width;
+                image::imageops::crop_imm(&screenshot, (diff / 2.) as u32, 0, new_width as u32, screenshot.height())
+            } else {
+                // keep width, crop height
+                let diff = screenshot.height() as f32 - expected_height;
+                image::imageops::crop_imm(&screenshot, 0, (diff / 2.) as u32, screenshot.width(), expected_height as u32)
+            }
+            .to_image();
+            let thumbnail = image::imageops::resize(&cropped, 640, 360, image::imageops::FilterType::CatmullRom);
+            ambient_sys::task::spawn(async move {
+                let path = std::path::Path::new("thumbnail.png");
+                match ambient_sys::export::export_file(path, image_to_png(thumbnail)).await {
+                    Ok(()) => log::info!("Saved thumbnail to {path:?}"),
+                    Err(err) => log::error!("Failed to save thumbnail: {err}"),
+                }
+            });
+        }));
+    })
+    .tooltip("Save thumbnail")
+    .el()
+}
+
+fn image_to_png(image: RgbImage) -> Vec<u8> {
     let image = DynamicImage::ImageRgb8(image);
     let mut buff = Cursor::new(Vec::new());
     image.write_to(&mut buff, ImageOutputFormat::Png).unwrap();
@@ -550,11 +682,11 @@ impl<T: Serialize + DeserializeOwned + Send + Sync + std::fmt::Debug + Clone + '
         let Self { value, on_change } = *self;
         FlowRow(vec![
             Button::new("Copy", move |_| {
-                arboard::Clipboard::new().unwrap().set_text(serde_json::to_string_pretty(&value).unwrap()).ok();
+                ambient_sys::clipboard::set_text(&serde_json::to_string_pretty(&value).unwrap());
             })
             .el(),
             Button::new("Paste", move |_| {
-                if let Ok(paste) = arboard::Clipboard::new().unwrap().get_text() {
+                if let Some(paste) = ambient_sys::clipboard::get_text() {
                     on_change(serde_json::from_str(&paste).unwrap());
                 }
             })
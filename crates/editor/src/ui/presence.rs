@@ -0,0 +1,147 @@
+use std::{sync::Arc, time::Duration};
+
+use ambient_core::{
+    bounding::world_bounding_sphere,
+    camera::{aspect_ratio, fovy, get_active_camera},
+    main_scene, name,
+    player::{player, user_id},
+    runtime,
+    transform::local_to_world,
+};
+use ambient_ecs::{query, EntityId, WorldDiff};
+use ambient_element::Hooks;
+use ambient_gizmos::{GizmoPrimitive, GizmoScope};
+use ambient_network::{
+    client::GameClient, client_game_state::ClientGameState, hooks::use_player_id, log_network_result, rpc::rpc_world_diff,
+};
+use ambient_std::Cb;
+use glam::{Vec2, Vec3};
+
+use super::world_overlay::WorldLabel;
+use crate::{
+    presence::{editor_camera, editor_transformed_by, presence_color, EditorCamera, PRESENCE_FRUSTUM_DEPTH},
+    rpc::{rpc_lock_transform, rpc_unlock_transform},
+    selection, Selection,
+};
+
+/// How often the camera is sent to the other users
+const CAMERA_SHARE_INTERVAL: Duration = Duration::from_millis(100);
+const LINE_RADIUS: f32 = 0.02;
+
+/// Shares the camera of this client with the other users, by setting its [editor_camera] whenever it moves
+pub fn use_share_camera(hooks: &mut Hooks) {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let runtime = hooks.world.resource(runtime()).clone();
+    let player_id = use_player_id(hooks);
+    let mut shared = None;
+    hooks.use_interval_deps(CAMERA_SHARE_INTERVAL, false, player_id, move |&player_id| {
+        let Some(player_id) = player_id else { return };
+        let camera = {
+            let state = game_client.game_state.lock();
+            let Some(camera) = get_active_camera(&state.world, main_scene(), Some(&game_client.user_id)) else { return };
+            EditorCamera {
+                transform: state.world.get(camera, local_to_world()).unwrap_or_default(),
+                fovy: state.world.get(camera, fovy()).unwrap_or(1.),
+                aspect_ratio: state.world.get(camera, aspect_ratio()).unwrap_or(1.),
+            }
+        };
+        if shared == Some(camera) {
+            return;
+        }
+        shared = Some(camera);
+        let game_client = game_client.clone();
+        runtime.spawn(async move {
+            log_network_result!(game_client.rpc(rpc_world_diff, WorldDiff::new().set(player_id, editor_camera(), camera)).await);
+        });
+    });
+}
+
+/// Locks `targets` for as long as they're being transformed, see [crate::presence]. If another user is already
+/// transforming one of them, `on_conflict` is called with their id
+pub fn use_transform_lock(hooks: &mut Hooks, targets: Option<Arc<[EntityId]>>, on_conflict: Cb<dyn Fn(String) + Sync + Send>) {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    hooks.use_effect(targets, move |world, targets| {
+        let Some(targets) = targets.as_ref().map(|targets| targets.to_vec()) else { return Box::new(|_| {}) };
+        world.resource(runtime()).spawn({
+            let game_client = game_client.clone();
+            let targets = targets.clone();
+            async move {
+                if let Ok(Err(holder)) = game_client.rpc(rpc_lock_transform, targets).await {
+                    on_conflict(holder);
+                }
+            }
+        });
+        Box::new(move |world| {
+            world.resource(runtime()).spawn(async move {
+                log_network_result!(game_client.rpc(rpc_unlock_transform, targets).await);
+            });
+        })
+    });
+}
+
+/// Describes how the selection of this client conflicts with the other users: the entities they're transforming, and
+/// the ones they have selected as well
+pub fn use_selection_conflicts(hooks: &mut Hooks, own_selection: Selection) -> Vec<String> {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (conflicts, set_conflicts) = hooks.use_state(Vec::<String>::new());
+    let prev_conflicts = hooks.use_ref_with(|_| Vec::<String>::new());
+    hooks.use_frame(move |_| {
+        let state = game_client.game_state.lock();
+        let world = &state.world;
+        let mut new_conflicts = Vec::new();
+        for id in &own_selection {
+            if let Ok(holder) = world.get_ref(id, editor_transformed_by()) {
+                if *holder != game_client.user_id {
+                    let entity = world.get_ref(id, name()).cloned().unwrap_or_else(|_| id.to_string());
+                    new_conflicts.push(format!("{holder} is moving {entity}"));
+                }
+            }
+        }
+        for (_, (user, other_selection)) in query((user_id(), selection())).incl(player()).iter(world, None) {
+            let shared = other_selection.iter().filter(|id| own_selection.contains(id)).count();
+            if *user != game_client.user_id && shared > 0 {
+                new_conflicts.push(format!("{user} also has {shared} of these selected"));
+            }
+        }
+
+        let mut prev_conflicts = prev_conflicts.lock();
+        if *prev_conflicts != new_conflicts {
+            *prev_conflicts = new_conflicts.clone();
+            set_conflicts(new_conflicts);
+        }
+    });
+    conflicts
+}
+
+/// Draws the cameras and the selections of the other users, with their names above their cameras
+pub(super) fn draw_presence(
+    state: &ClientGameState,
+    own_user_id: &str,
+    scope: &mut GizmoScope,
+    to_screen: impl Fn(Vec3) -> Option<Vec2>,
+    labels: &mut Vec<WorldLabel>,
+) {
+    let world = &state.world;
+    for (id, user) in query(user_id()).incl(player()).iter(world, None) {
+        if user == own_user_id {
+            continue;
+        }
+        let color = presence_color(user);
+        if let Ok(camera) = world.get(id, editor_camera()) {
+            let origin = camera.position();
+            let corners = camera.frustum_corners(PRESENCE_FRUSTUM_DEPTH);
+            for (i, &corner) in corners.iter().enumerate() {
+                scope.draw(GizmoPrimitive::line(origin, corner, LINE_RADIUS).with_color(color.into()));
+                scope.draw(GizmoPrimitive::line(corner, corners[(i + 1) % corners.len()], LINE_RADIUS).with_color(color.into()));
+            }
+            if let Some(position) = to_screen(origin) {
+                labels.push(WorldLabel { position, text: user.clone(), background: color });
+            }
+        }
+        for selected in world.get_ref(id, selection()).into_iter().flatten() {
+            if let Ok(bounds) = world.get(selected, world_bounding_sphere()) {
+                scope.draw(GizmoPrimitive::torus(bounds.center, bounds.radius, LINE_RADIUS).with_color(color.into()));
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use ambient_core::{
     asset_cache, runtime,
@@ -23,10 +23,12 @@ use ambient_primitives::Cube;
 use ambient_renderer::{color, material, renderer_shader, Material, MaterialShader, SharedMaterial, MATERIAL_BIND_GROUP};
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt},
+    asset_url::{select_asset, AssetType},
     cb, friendly_id,
 };
 use ambient_terrain::{
-    brushes::{Brush, BrushShape, BrushSize, BrushSmoothness, BrushStrength, HydraulicErosionConfig, TerrainBrushStroke},
+    brushes::{Brush, BrushShape, BrushSize, BrushSmoothness, BrushStrength, HydraulicErosionConfig, TerrainBrushStroke, TerrainStamp},
+    heightmap::{terrain_bounds, Heightmap},
     intent_terrain_stroke, terrain_world_cell,
 };
 use ambient_ui::{
@@ -50,12 +52,13 @@ pub struct TerrainRaycastPicker {
     pub brush_shape: BrushShape,
     pub brush_smoothness: BrushSmoothness,
     pub erosion_config: HydraulicErosionConfig,
+    pub stamp: Option<TerrainStamp>,
 }
 impl ElementComponent for TerrainRaycastPicker {
     fn render(self: Box<Self>, hooks: &mut ambient_element::Hooks) -> Element {
         let action_button = ambient_window_types::MouseButton::Left;
 
-        let Self { filter, layer, brush, brush_size, brush_strength, brush_smoothness, brush_shape, erosion_config } = *self;
+        let Self { filter, layer, brush, brush_size, brush_strength, brush_smoothness, brush_shape, erosion_config, stamp } = *self;
         let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
         let (target_position, set_target_position) = hooks.use_state(None);
         let (mouseover, set_mouseover) = hooks.use_state(false);
@@ -136,6 +139,7 @@ impl ElementComponent for TerrainRaycastPicker {
                     let center = target_position.xy();
 
                     let erosion = erosion_config.clone();
+                    let stamp = stamp.clone();
                     let game_client = game_client.clone();
                     world.resource(runtime()).spawn({
                         client_push_intent(
@@ -151,6 +155,7 @@ impl ElementComponent for TerrainRaycastPicker {
                                 brush_shape,
                                 start_position,
                                 erosion,
+                                stamp,
                             },
                             None,
                             None,
@@ -292,6 +297,8 @@ impl ElementComponent for EditorTerrainMode {
         let (brush_shape, set_brush_shape) = hooks.consume_context::<BrushShape>().unwrap();
         let (brush_smoothness, set_brush_smoothness) = hooks.consume_context::<BrushSmoothness>().unwrap();
         let (erosion_config, _set_erosion_config) = hooks.consume_context::<HydraulicErosionConfig>().unwrap();
+        let (stamp, set_stamp) = hooks.consume_context::<Option<TerrainStamp>>().unwrap();
+        let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
 
         let mut items = vec![
             EditorPlayerInputHandler.el(),
@@ -315,6 +322,10 @@ impl ElementComponent for EditorTerrainMode {
                 .hotkey(VirtualKeyCode::Key5)
                 .tooltip("Thermal Erosion")
                 .el(),
+            Button::new_value(FontAwesomeIcon::el(0xf03e, true), brush, set_brush.clone(), Brush::Stamp)
+                .hotkey(VirtualKeyCode::Key6)
+                .tooltip("Stamp")
+                .el(),
             Separator { vertical: true }.el(),
             FlowRow(vec![
                 Text::el("Size"),
@@ -333,7 +344,58 @@ impl ElementComponent for EditorTerrainMode {
             .el()
             .set(space_between_items(), STREET),
         ];
-        if let Brush::Raise | Brush::Lower | Brush::Flatten = brush {
+        if brush == Brush::Stamp {
+            items.push(Separator { vertical: true }.el());
+            items.push(
+                Button::new(stamp.as_ref().map_or("Pick heightmap".to_string(), |stamp| stamp.url.clone()), {
+                    let set_stamp = set_stamp.clone();
+                    move |world| {
+                        let set_stamp = set_stamp.clone();
+                        select_asset(world.resource(asset_cache()), AssetType::Image, move |url| {
+                            if let Some(url) = url.random().cloned() {
+                                set_stamp(Some(TerrainStamp::new(url)));
+                            }
+                        });
+                    }
+                })
+                .tooltip("The heightmap to stamp")
+                .el(),
+            );
+            if let Some(stamp) = stamp.clone() {
+                items.push(
+                    FlowRow(vec![
+                        Text::el("Rotation"),
+                        Slider {
+                            value: stamp.rotation.to_degrees(),
+                            on_change: Some(cb(closure!(clone stamp, clone set_stamp, |value: f32| {
+                                set_stamp(Some(TerrainStamp { rotation: value.to_radians(), ..stamp.clone() }))
+                            }))),
+                            min: -180.,
+                            max: 180.,
+                            width: 200.0,
+                            logarithmic: false,
+                            round: Some(0),
+                            suffix: Some("°"),
+                        }
+                        .el(),
+                    ])
+                    .el()
+                    .set(space_between_items(), STREET),
+                );
+                items.push(
+                    Button::new(
+                        "Replace",
+                        closure!(clone stamp, clone set_stamp, |_| {
+                            set_stamp(Some(TerrainStamp { replace: !stamp.replace, ..stamp.clone() }))
+                        }),
+                    )
+                    .toggled(stamp.replace)
+                    .tooltip("Set the height of the terrain to the stamp, instead of adding the stamp to it")
+                    .el(),
+                );
+            }
+        }
+        if let Brush::Raise | Brush::Lower | Brush::Flatten | Brush::Stamp = brush {
             items.push(
                 FlowRow(vec![
                     Text::el("Strength"),
@@ -399,6 +461,23 @@ impl ElementComponent for EditorTerrainMode {
                 );
             }
         }
+        items.push(Separator { vertical: true }.el());
+        items.push(
+            Button::new(FontAwesomeIcon::el(0xf56e, true), move |_| {
+                let state = game_client.game_state.lock();
+                let Some((min, max)) = terrain_bounds(&state.world) else {
+                    tracing::warn!("There is no terrain to export");
+                    return;
+                };
+                let path = PathBuf::from(format!("heightmaps/terrain_{}.exr", chrono::Utc::now().format("%Y%m%d_%H%M%S")));
+                match Heightmap::from_terrain(&state.world, min, max).save(&path) {
+                    Ok(()) => tracing::info!("Exported the terrain heightmap to {path:?}"),
+                    Err(err) => tracing::error!("Failed to export the terrain heightmap: {err:?}"),
+                }
+            })
+            .tooltip("Export heightmap")
+            .el(),
+        );
 
         WindowSized(vec![
             FlowColumn::el([FlowRow(items).el().floating_panel().keyboard().set(margin(), Borders::even(STREET))]),
@@ -415,6 +494,7 @@ impl ElementComponent for EditorTerrainMode {
                     brush_smoothness,
                     brush_shape,
                     erosion_config,
+                    stamp,
                 }
                 .el()
                 .vec_of(),
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use ambient_ecs::query;
+use ambient_element::{Hooks, Setter};
+use ambient_intent::{intent_reverted, intent_timestamp, intent_user_id, rpc_redo, rpc_undo_head};
+use ambient_network::client::GameClient;
+use ambient_std::{cb, Cb};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+/// An undoable operation which only changes client side state (e.g. editor preferences), and
+/// thus can't be expressed as an intent
+#[derive(Clone, Debug)]
+struct LocalUndoOp {
+    name: String,
+    timestamp: DateTime<Utc>,
+    undo: Cb<dyn Fn() + Sync + Send>,
+    redo: Cb<dyn Fn() + Sync + Send>,
+}
+
+#[derive(Debug, Default)]
+struct LocalUndoStackInner {
+    undo: Vec<LocalUndoOp>,
+    redo: Vec<LocalUndoOp>,
+}
+
+/// The undo stack for client side operations. This is merged with the intent history of the server
+/// by [undo] and [redo], so that Ctrl+Z always undoes the most recent change, wherever it happened.
+#[derive(Debug, Clone, Default)]
+pub struct LocalUndoStack(Arc<Mutex<LocalUndoStackInner>>);
+impl LocalUndoStack {
+    /// Records an operation which has already been applied
+    pub fn push(&self, name: impl Into<String>, undo: impl Fn() + Sync + Send + 'static, redo: impl Fn() + Sync + Send + 'static) {
+        let mut inner = self.0.lock();
+        inner.redo.clear();
+        inner.undo.push(LocalUndoOp { name: name.into(), timestamp: Utc::now(), undo: cb(undo), redo: cb(redo) });
+    }
+    /// Changes a value through `setter`, recording the change so that it can be undone
+    pub fn set<T: Clone + Send + Sync + 'static>(&self, name: impl Into<String>, setter: Setter<T>, prev: T, value: T) {
+        setter(value.clone());
+        self.push(name, closure!(clone setter, || setter(prev.clone())), move || setter(value.clone()));
+    }
+    fn undo_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.0.lock().undo.last().map(|op| op.timestamp)
+    }
+    fn redo_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.0.lock().redo.last().map(|op| op.timestamp)
+    }
+    fn undo(&self) {
+        let op = {
+            let mut inner = self.0.lock();
+            let Some(op) = inner.undo.pop() else { return };
+            inner.redo.push(op.clone());
+            op
+        };
+        tracing::info!("Undoing local operation: {}", op.name);
+        (op.undo)();
+    }
+    fn redo(&self) {
+        let op = {
+            let mut inner = self.0.lock();
+            let Some(op) = inner.redo.pop() else { return };
+            inner.undo.push(op.clone());
+            op
+        };
+        tracing::info!("Redoing local operation: {}", op.name);
+        (op.redo)();
+    }
+    fn clear_redo(&self) {
+        self.0.lock().redo.clear();
+    }
+}
+
+pub fn use_local_undo(hooks: &mut Hooks) -> LocalUndoStack {
+    hooks.consume_context::<LocalUndoStack>().expect("No LocalUndoStack in context").0
+}
+
+/// Returns the timestamps of the newest non-reverted and the oldest reverted intent of this user, as replicated to the client
+fn intent_timestamps(game_client: &GameClient) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+    let state = game_client.game_state.lock();
+    let (mut head, mut tail_reverted) = (None, None);
+    for (id, (user_id, &timestamp)) in query((intent_user_id(), intent_timestamp())).iter(&state.world, None) {
+        if user_id != &game_client.user_id {
+            continue;
+        }
+        if state.world.has_component(id, intent_reverted()) {
+            tail_reverted = Some(tail_reverted.map_or(timestamp, |t: DateTime<Utc>| t.min(timestamp)));
+        } else {
+            head = Some(head.map_or(timestamp, |t: DateTime<Utc>| t.max(timestamp)));
+        }
+    }
+    (head, tail_reverted)
+}
+
+/// Undoes the most recent operation, which is either a local operation or an intent
+pub async fn undo(game_client: GameClient, local: LocalUndoStack) {
+    let (intent_head, _) = intent_timestamps(&game_client);
+    match (local.undo_timestamp(), intent_head) {
+        (Some(local_head), intent_head) if intent_head.map_or(true, |intent_head| local_head > intent_head) => local.undo(),
+        (_, Some(_)) => {
+            game_client.rpc(rpc_undo_head, ()).await.ok();
+        }
+        _ => {}
+    }
+}
+
+/// Redoes the most recently undone operation, which is either a local operation or an intent.
+///
+/// Undone operations that are older than the most recent change are no longer redoable.
+pub async fn redo(game_client: GameClient, local: LocalUndoStack) {
+    let (intent_head, intent_tail) = intent_timestamps(&game_client);
+    let mut local_tail = local.redo_timestamp();
+    if let (Some(tail), Some(head)) = (local_tail, intent_head) {
+        if tail < head {
+            local.clear_redo();
+            local_tail = None;
+        }
+    }
+    let intent_tail = intent_tail.filter(|&tail| local.undo_timestamp().map_or(true, |head| tail > head));
+    match (local_tail, intent_tail) {
+        (Some(local_tail), intent_tail) if intent_tail.map_or(true, |intent_tail| local_tail < intent_tail) => local.redo(),
+        (_, Some(_)) => {
+            game_client.rpc(rpc_redo, ()).await.ok();
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,98 @@
+use ambient_core::{transform::translation, window::window_scale_factor};
+use ambient_ecs::query;
+use ambient_element::{element_component, Element, ElementComponentExt, Group, Hooks};
+use ambient_gizmos::{gizmos, GizmoPrimitive};
+use ambient_network::client::GameClient;
+use ambient_std::{color::Color, line_hash};
+use ambient_ui::{padding, Borders, Text, UIExt};
+use glam::{vec3, Vec2, Vec3};
+
+use super::presence::draw_presence;
+use crate::annotations::{annotation, annotation_target};
+
+const LINE_RADIUS: f32 = 0.03;
+const POINT_RADIUS: f32 = 0.1;
+const LABEL_BACKGROUND: Color = Color::rgba(0., 0., 0., 0.6);
+
+/// The points picked with the measure tool. The distance is shown once there are two of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Measurement(pub Vec<Vec3>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct WorldLabel {
+    pub position: Vec2,
+    pub text: String,
+    pub background: Color,
+}
+
+fn gizmo_scope() -> u64 {
+    line_hash!("editor/world_overlay")
+}
+
+/// Draws the current measurement, the annotations in the scene and the other users, with their text always facing the
+/// camera
+#[element_component]
+pub fn WorldOverlay(hooks: &mut Hooks, show_annotations: bool) -> Element {
+    let (game_client, _) = hooks.consume_context::<GameClient>().unwrap();
+    let (measurement, _) = hooks.consume_context::<Measurement>().unwrap();
+    let (labels, set_labels) = hooks.use_state(Vec::<WorldLabel>::new());
+    let prev_labels = hooks.use_ref_with(|_| Vec::<WorldLabel>::new());
+
+    hooks.use_spawn({
+        let game_state = game_client.game_state.clone();
+        move |_| {
+            Box::new(move |_| {
+                game_state.lock().world.resource(gizmos()).scope(gizmo_scope());
+            })
+        }
+    });
+    hooks.use_frame(move |world| {
+        let scale_factor = *world.resource(window_scale_factor()) as f32;
+        let state = game_client.game_state.lock();
+        let Some(proj_view) = state.proj_view() else { return };
+        // Points behind the camera don't have a meaningful screen position
+        let to_screen = |p: Vec3| ((proj_view * p.extend(1.)).w > 0.).then(|| state.world_to_screen_space(p) / scale_factor);
+
+        let mut new_labels = Vec::new();
+        let mut scope = state.world.resource(gizmos()).scope(gizmo_scope());
+        for &point in &measurement.0 {
+            scope.draw(GizmoPrimitive::sphere(point, POINT_RADIUS).with_color(Color::rgb(1., 0.8, 0.).into()));
+        }
+        if let [a, b] = measurement.0[..] {
+            scope.draw(GizmoPrimitive::line(a, b, LINE_RADIUS).with_color(Color::rgb(1., 0.8, 0.).into()));
+            if let Some(position) = to_screen((a + b) / 2.) {
+                new_labels.push(WorldLabel { position, text: format!("{:.2} m", a.distance(b)), background: LABEL_BACKGROUND });
+            }
+        }
+        if show_annotations {
+            for (_, (text, &position, &target)) in query((annotation(), translation(), annotation_target())).iter(&state.world, None) {
+                scope.draw(GizmoPrimitive::line(position, target, LINE_RADIUS));
+                scope.draw(GizmoPrimitive::sphere(target, POINT_RADIUS));
+                if let Some(position) = to_screen(position) {
+                    new_labels.push(WorldLabel { position, text: text.clone(), background: LABEL_BACKGROUND });
+                }
+            }
+        }
+        draw_presence(&state, &game_client.user_id, &mut scope, to_screen, &mut new_labels);
+        drop(scope);
+
+        let mut prev_labels = prev_labels.lock();
+        if *prev_labels != new_labels {
+            *prev_labels = new_labels.clone();
+            set_labels(new_labels);
+        }
+    });
+
+    Group(
+        labels
+            .into_iter()
+            .map(|label| {
+                Text::el(label.text)
+                    .with_background(label.background.into())
+                    .set(padding(), Borders::even(4.))
+                    .set(translation(), vec3(label.position.x, label.position.y, -0.02))
+            })
+            .collect(),
+    )
+    .el()
+}
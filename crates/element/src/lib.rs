@@ -98,7 +98,8 @@ impl Element {
         self.config.components.set(component, value);
         self
     }
-    pub fn set_with<T: ComponentValue + Sync + Send + 'static, F: Fn(&World) -> T + ComponentValue + Sync + Send + 'static>(
+    /// Sets the component to the value computed from the world each time the element is rendered
+    pub fn set_with<T: ComponentValue + Sync + Send + 'static, F: Fn(&World) -> T + Sync + Send + 'static>(
         mut self,
         component: Component<T>,
         value: F,
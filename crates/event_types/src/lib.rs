@@ -24,3 +24,36 @@ pub const WINDOW_MOUSE_INPUT: &str = "core/window_mouse_input";
 pub const WINDOW_MOUSE_WHEEL: &str = "core/window_mouse_wheel";
 /// The mouse cursor was moved
 pub const WINDOW_MOUSE_MOTION: &str = "core/window_mouse_motion";
+/// A finger touched, moved on, or was lifted from the screen
+pub const WINDOW_TOUCH: &str = "core/window_touch";
+/// A file was dropped into the window
+pub const WINDOW_FILE_DROPPED: &str = "core/window_file_dropped";
+/// A file was picked in a file dialog
+pub const FILE_OPENED: &str = "core/file_opened";
+/// A UI entity gained or lost keyboard focus
+pub const UI_FOCUS_CHANGED: &str = "core/ui_focus_changed";
+/// A UI entity was activated from the keyboard or by assistive technologies
+pub const UI_ACTIVATED: &str = "core/ui_activated";
+/// A gamepad was connected or disconnected
+pub const GAMEPAD_CONNECTED: &str = "core/gamepad_connected";
+/// A gamepad button was pressed or released
+pub const GAMEPAD_BUTTON: &str = "core/gamepad_button";
+/// A gamepad stick or trigger was moved
+pub const GAMEPAD_AXIS: &str = "core/gamepad_axis";
+/// A server admin broadcast a message
+pub const ADMIN_MESSAGE: &str = "core/admin_message";
+/// A client setting was changed; see `ambient_settings::update_settings`. Components will contain the `setting_key`, and the
+/// `setting_package` and `setting_value` for the settings declared by packages
+pub const SETTINGS_CHANGED: &str = "core/settings_changed";
+/// The `load_progress` of the client changed. Components will contain the `load_progress`
+pub const LOAD_PROGRESS: &str = "core/load_progress";
+/// A message from the page embedding the web client. Components will contain the `embed_message_name` and `embed_message_data`
+pub const EMBED_HOST_MESSAGE: &str = "core/embed_host_message";
+/// Add this event to send a message to the page embedding the web client, with the `embed_message_name` and `embed_message_data` components
+pub const EMBED_CLIENT_MESSAGE: &str = "core/embed_client_message";
+/// Send this event on the server to blend the model of the `id` entity into its ragdoll. It can also contain the `ragdoll_target_weight`,
+/// which defaults to 1, and the `ragdoll_blend_time`. Unlike the core events, modules can send it
+pub const ENABLE_RAGDOLL: &str = "physics/enable_ragdoll";
+/// Send this event on the server to break the `id` entity into its `destructible_chunks`. It can also contain the
+/// `destruction_impulse` of the hit, and its `destruction_position`
+pub const DESTROY: &str = "physics/destroy";
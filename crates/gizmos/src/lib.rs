@@ -1,16 +1,31 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
 use ambient_ecs::{components, Resource};
 use glam::{Mat4, Vec2};
 
 pub mod render;
+mod text;
 mod traits;
 use ambient_std::math::Line;
-use dashmap::{mapref::one::RefMut, DashMap};
+use dashmap::{
+    mapref::{multiple::RefMulti, one::RefMut},
+    DashMap, DashSet,
+};
 use glam::Vec3;
+pub use text::systems;
 pub use traits::*;
 
 components!("gizmos", {
     @[Resource]
     gizmos: Gizmos,
+    /// The id of the debug text this entity displays, see [Gizmos::text_for]
+    gizmo_text: u64,
 });
 
 #[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -115,11 +130,15 @@ impl Cuboid {
 #[derive(Debug, Clone)]
 pub struct Gizmos {
     scopes: DashMap<u64, GizmoScope>,
+    /// The draws which persist across frames until they expire, by category
+    timed: DashMap<String, Vec<TimedGizmo>>,
+    hidden_categories: DashSet<String>,
+    next_text_id: Arc<AtomicU64>,
 }
 
 impl Gizmos {
     pub fn new() -> Self {
-        Self { scopes: DashMap::new() }
+        Self { scopes: DashMap::new(), timed: DashMap::new(), hidden_categories: DashSet::new(), next_text_id: Arc::new(AtomicU64::new(0)) }
     }
 
     pub fn scope(&self, scope: u64) -> RefMut<u64, GizmoScope> {
@@ -136,6 +155,84 @@ impl Gizmos {
         f(&mut scope);
         self
     }
+
+    /// Draws `gizmo` until `duration` has passed in `game_time`, so it stays while the game is paused, or for a single
+    /// frame if it's zero. Unlike scopes, the draws of a category accumulate, and it can be hidden from the debugger
+    pub fn draw_for(&self, category: &str, duration: Duration, gizmo: impl Gizmo) -> &Self {
+        let primitives = gizmo.into_gizmo_primitives().into_iter().collect();
+        self.push_timed(category, TimedGizmo { duration, expires_at: None, primitives, texts: Vec::new() });
+        self
+    }
+
+    /// Draws `text` at `position`, facing the camera at a constant size on screen, like [Self::draw_for]
+    pub fn text_for(&self, category: &str, duration: Duration, position: Vec3, text: impl Into<String>, color: Vec3) -> &Self {
+        let id = self.next_text_id.fetch_add(1, Ordering::SeqCst);
+        let text = GizmoText { position, text: text.into(), color };
+        self.push_timed(category, TimedGizmo { duration, expires_at: None, primitives: Vec::new(), texts: vec![(id, text)] });
+        self
+    }
+
+    fn push_timed(&self, category: &str, gizmo: TimedGizmo) {
+        self.timed.entry(category.to_string()).or_default().push(gizmo);
+    }
+
+    /// The categories which have been drawn to, sorted; they're kept after their draws expire
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories = self.timed.iter().map(|timed| timed.key().clone()).collect::<Vec<_>>();
+        categories.sort();
+        categories
+    }
+
+    pub fn is_category_visible(&self, category: &str) -> bool {
+        !self.hidden_categories.contains(category)
+    }
+
+    pub fn set_category_visible(&self, category: &str, visible: bool) {
+        if visible {
+            self.hidden_categories.remove(category);
+        } else {
+            self.hidden_categories.insert(category.to_string());
+        }
+    }
+
+    /// The primitives of the timed draws of the visible categories
+    pub fn visible_timed_primitives(&self) -> Vec<GizmoPrimitive> {
+        self.visible_timed().flat_map(|timed| timed.iter().flat_map(|gizmo| gizmo.primitives.clone()).collect::<Vec<_>>()).collect()
+    }
+
+    fn visible_timed_texts(&self) -> Vec<(u64, GizmoText)> {
+        self.visible_timed().flat_map(|timed| timed.iter().flat_map(|gizmo| gizmo.texts.clone()).collect::<Vec<_>>()).collect()
+    }
+
+    fn visible_timed(&self) -> impl Iterator<Item = RefMulti<'_, String, Vec<TimedGizmo>>> + '_ {
+        self.timed.iter().filter(|timed| self.is_category_visible(timed.key()))
+    }
+
+    /// Starts the countdowns of the new timed draws, and removes the expired ones
+    fn update_timed(&self, now: Duration) {
+        for mut timed in self.timed.iter_mut() {
+            timed.retain_mut(|gizmo| {
+                let expires_at = *gizmo.expires_at.get_or_insert(now + gizmo.duration);
+                expires_at >= now
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TimedGizmo {
+    duration: Duration,
+    /// Set on the first frame the draw is updated, so that it's drawn for at least one frame
+    expires_at: Option<Duration>,
+    primitives: Vec<GizmoPrimitive>,
+    texts: Vec<(u64, GizmoText)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GizmoText {
+    pub position: Vec3,
+    pub text: String,
+    pub color: Vec3,
 }
 
 impl Default for Gizmos {
@@ -1,15 +1,14 @@
 use std::{fmt::Debug, sync::Arc};
 
 use ambient_core::{asset_cache, camera::Camera, main_scene, player::local_user_id};
-use ambient_ecs::World;
 use ambient_gpu::{
     gpu::{Gpu, GpuKey},
-    mesh_buffer::{GpuMesh, MeshBuffer},
+    mesh_buffer::GpuMesh,
     shader_module::{BindGroupDesc, GraphicsPipeline, GraphicsPipelineInfo, Shader, ShaderModule},
     typed_buffer::TypedBuffer,
 };
 use ambient_meshes::QuadMeshKey;
-use ambient_renderer::{get_overlay_modules, get_resources_module, RendererTarget, SubRenderer};
+use ambient_renderer::{get_overlay_modules, get_resources_module, RenderContext, RenderHook, RenderPassDesc, RenderResource, SubRenderer};
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
     include_file,
@@ -17,7 +16,7 @@ use ambient_std::{
 use bytemuck::{Pod, Zeroable};
 use glam::{vec2, Mat4, Quat, Vec2, Vec3};
 use once_cell::sync::OnceCell;
-use wgpu::{BindGroup, BindGroupEntry, BindGroupLayoutEntry, BlendState, BufferUsages, ColorTargetState, ColorWrites, ShaderStages};
+use wgpu::{BindGroupEntry, BindGroupLayoutEntry, BlendState, BufferUsages, ColorTargetState, ColorWrites, ShaderStages};
 
 use super::{gizmos, GizmoPrimitive};
 
@@ -42,17 +41,16 @@ impl GizmoRenderer {
 
         Self { gpu, quad: QuadMeshKey.get(assets), pipeline: OnceCell::new(), buffer, primitives: Vec::new() }
     }
+    pub fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("gizmos", RenderHook::PostTransparent).reads(RenderResource::Depth).writes(RenderResource::Color)
+    }
 }
 impl SubRenderer for GizmoRenderer {
     #[profiling::function]
-    fn render<'a>(
-        &'a mut self,
-        world: &World,
-        mesh_buffer: &MeshBuffer,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &RendererTarget,
-        binds: &[(&str, &'a BindGroup)],
-    ) {
+    fn render(&mut self, context: &mut RenderContext) {
+        let Some(scene) = context.scene else { return };
+        let (world, encoder, target, mesh_buffer, binds) =
+            (&*context.world, &mut *context.encoder, &context.target, scene.mesh_buffer, scene.binds());
         let gizmos = world.resource(gizmos());
         let camera = Camera::get_active(world, main_scene(), world.resource_opt(local_user_id())).unwrap_or_default();
         let primitives = &mut self.primitives;
@@ -114,6 +112,7 @@ impl SubRenderer for GizmoRenderer {
         gizmos.scopes().for_each(|scope| {
             primitives.extend(scope.primitives.iter().map(|v| Gizmo::from_primitive(v, camera.position())));
         });
+        primitives.extend(gizmos.visible_timed_primitives().iter().map(|v| Gizmo::from_primitive(v, camera.position())));
 
         if primitives.is_empty() {
             return;
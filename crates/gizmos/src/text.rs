@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use ambient_core::{
+    camera::Camera,
+    game_time, main_scene,
+    player::local_user_id,
+    time,
+    transform::{local_to_world, mesh_to_local, mesh_to_world, rotation, scale, translation},
+};
+use ambient_ecs::{query, Entity, FnSystem, SystemGroup};
+use ambient_renderer::{color, double_sided};
+use ambient_text::text;
+use glam::{Quat, Vec3};
+
+use crate::{gizmo_text, gizmos};
+
+/// How large the debug texts are per unit of distance to the camera, which keeps them at a constant size on screen
+const TEXT_SCALE_PER_DISTANCE: f32 = 0.002;
+
+/// Expires the timed draws, and spawns and despawns the entities of the debug texts
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "gizmos",
+        vec![Box::new(FnSystem::new(|world, _| {
+            let now = world.resource_opt(game_time()).copied().unwrap_or_else(|| *world.resource(time()));
+            let Some(gizmos) = world.resource_opt(gizmos()) else { return };
+            gizmos.update_timed(now);
+            let texts = gizmos.visible_timed_texts();
+
+            let mut spawned = HashMap::new();
+            for (id, text_id) in query(gizmo_text()).iter(world, None).map(|(id, &text_id)| (id, text_id)).collect::<Vec<_>>() {
+                if texts.iter().any(|(id, _)| *id == text_id) {
+                    spawned.insert(text_id, id);
+                } else {
+                    world.despawn(id);
+                }
+            }
+
+            // The texts are laid out like UI, with Y down, so they're flipped to face the camera upright
+            let camera = Camera::get_active(world, main_scene(), world.resource_opt(local_user_id()));
+            let (camera_position, camera_rotation) = camera.map_or((Vec3::ZERO, Quat::IDENTITY), |camera| {
+                let (_, rotation, position) = camera.view.inverse().to_scale_rotation_translation();
+                (position, rotation)
+            });
+            let text_rotation = camera_rotation * Quat::from_rotation_x(std::f32::consts::PI);
+            for (text_id, gizmo) in texts {
+                let text_scale = Vec3::splat(gizmo.position.distance(camera_position) * TEXT_SCALE_PER_DISTANCE);
+                match spawned.get(&text_id) {
+                    Some(&id) => {
+                        world.set(id, translation(), gizmo.position).ok();
+                        world.set(id, rotation(), text_rotation).ok();
+                        world.set(id, scale(), text_scale).ok();
+                    }
+                    None => {
+                        Entity::new()
+                            .with(gizmo_text(), text_id)
+                            .with(text(), gizmo.text)
+                            .with(color(), gizmo.color.extend(1.))
+                            .with(double_sided(), true)
+                            .with(translation(), gizmo.position)
+                            .with(rotation(), text_rotation)
+                            .with(scale(), text_scale)
+                            .with_default(local_to_world())
+                            .with_default(mesh_to_local())
+                            .with_default(mesh_to_world())
+                            .with_default(main_scene())
+                            .spawn(world);
+                    }
+                }
+            }
+        }))],
+    )
+}
@@ -1,6 +1,7 @@
 use std::{borrow::Cow, sync::Arc};
 
 use ambient_std::asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt};
+use glam::Vec4;
 use wgpu::{BindGroupLayoutDescriptor, BindGroupLayoutEntry, PipelineLayoutDescriptor, ShaderStages, TextureSampleType};
 
 use super::gpu::{Gpu, GpuKey};
@@ -84,6 +85,26 @@ impl Blitter {
         Self { pipeline, sampler, gpu }
     }
     pub fn run(&self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::TextureView, target: &wgpu::TextureView) {
+        self.run_with(encoder, source, target, wgpu::LoadOp::Clear(wgpu::Color::WHITE), None);
+    }
+    /// Draws `source` into the `(x, y, width, height)` region of `target`, in pixels, keeping the rest of `target` as is
+    pub fn run_to_viewport(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        viewport: Vec4,
+    ) {
+        self.run_with(encoder, source, target, wgpu::LoadOp::Load, Some(viewport));
+    }
+    fn run_with(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+        viewport: Option<Vec4>,
+    ) {
         let bind_group_layout = self.pipeline.get_bind_group_layout(0);
 
         let bind_group = self.gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -100,11 +121,14 @@ impl Blitter {
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: target,
                 resolve_target: None,
-                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::WHITE), store: true },
+                ops: wgpu::Operations { load, store: true },
             })],
             depth_stencil_attachment: None,
         });
         rpass.set_pipeline(&self.pipeline);
+        if let Some(viewport) = viewport {
+            rpass.set_viewport(viewport.x, viewport.y, viewport.z, viewport.w, 0., 1.);
+        }
         rpass.set_bind_group(0, &bind_group, &[]);
         rpass.draw(0..4, 0..1);
     }
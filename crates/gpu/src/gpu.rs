@@ -11,6 +11,16 @@ pub const DEFAULT_SAMPLE_COUNT: u32 = 1;
 // #[cfg(not(debug_assertions))]
 // pub const DEFAULT_SAMPLE_COUNT: u32 = 4;
 
+/// Controls which backend and adapter the [Gpu] is created with.
+#[derive(Debug, Clone, Default)]
+pub struct GpuAdapterOptions {
+    /// Restrict the backends to choose an adapter from. If no adapter is found, the platform default is used instead.
+    pub backends: Option<wgpu::Backends>,
+    pub power_preference: wgpu::PowerPreference,
+    /// Prefer the first adapter whose name contains this (case-insensitive)
+    pub adapter_name: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct GpuKey;
 impl SyncAssetKey<Arc<Gpu>> for GpuKey {}
@@ -28,10 +38,10 @@ pub struct Gpu {
 }
 impl Gpu {
     pub async fn new(window: Option<&Window>) -> Self {
-        Self::with_config(window, false).await
+        Self::with_config(window, false, &Default::default()).await
     }
     #[tracing::instrument(level = "info")]
-    pub async fn with_config(window: Option<&Window>, will_be_polled: bool) -> Self {
+    pub async fn with_config(window: Option<&Window>, will_be_polled: bool, options: &GpuAdapterOptions) -> Self {
         // From: https://github.com/KhronosGroup/Vulkan-Loader/issues/552
         #[cfg(not(target_os = "unknown"))]
         {
@@ -48,27 +58,26 @@ impl Gpu {
         #[cfg(target_os = "unknown")]
         let backend = wgpu::Backends::all();
 
-        let instance = wgpu::Instance::new(backend);
-        let surface = window.map(|window| unsafe { instance.create_surface(window) });
-        #[cfg(not(target_os = "unknown"))]
-        {
-            tracing::debug!("Available adapters:");
-            for adapter in instance.enumerate_adapters(wgpu::Backends::PRIMARY) {
-                tracing::debug!("Adapter: {:?}", adapter.get_info());
+        let mut selected = None;
+        if let Some(backends) = options.backends {
+            selected = Self::request_adapter(window, backends, options, false).await;
+            if selected.is_none() {
+                tracing::warn!("No GPU adapter found for backends {backends:?}; falling back to {backend:?}");
             }
         }
+        let (_instance, surface, adapter) = match selected {
+            Some(selected) => selected,
+            None => match Self::request_adapter(window, backend, options, false).await {
+                Some(selected) => selected,
+                None => {
+                    // Headless machines, like servers and CI runners, usually don't have a GPU
+                    tracing::warn!("No GPU adapter found; falling back to a software adapter");
+                    Self::request_adapter(window, backend, options, true).await.expect("Failed to find an appropriate adapter")
+                }
+            },
+        };
 
-        tracing::debug!("Requesting adapter");
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: surface.as_ref(),
-                force_fallback_adapter: false,
-            })
-            .await
-            .expect("Failed to find an appropriate adapter");
-
-        tracing::debug!("Using gpu adapter: {:?}", adapter.get_info());
+        tracing::info!("Using GPU adapter: {:?}", adapter.get_info());
         tracing::debug!("Adapter features:\n{:#?}", adapter.features());
         let adapter_limits = adapter.limits();
         tracing::debug!("Adapter limits:\n{:#?}", adapter_limits);
@@ -77,6 +86,14 @@ impl Gpu {
         let features = wgpu::Features::empty();
         #[cfg(not(target_os = "macos"))]
         let features = wgpu::Features::MULTI_DRAW_INDIRECT | wgpu::Features::MULTI_DRAW_INDIRECT_COUNT;
+        // Only used for profiling, so it's fine if it's not available
+        let features = features | (adapter.features() & wgpu::Features::TIMESTAMP_QUERY);
+        // KTX2 textures are transcoded to whichever of these the adapter supports
+        let features = features
+            | (adapter.features()
+                & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                    | wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR
+                    | wgpu::Features::TEXTURE_COMPRESSION_ETC2));
 
         let (device, queue) = adapter
             .request_device(
@@ -118,6 +135,53 @@ impl Gpu {
         Self { device, surface, queue, swapchain_format, swapchain_mode, adapter, will_be_polled }
     }
 
+    async fn request_adapter(
+        window: Option<&Window>,
+        backends: wgpu::Backends,
+        options: &GpuAdapterOptions,
+        force_fallback_adapter: bool,
+    ) -> Option<(wgpu::Instance, Option<wgpu::Surface>, wgpu::Adapter)> {
+        let instance = wgpu::Instance::new(backends);
+        let surface = window.map(|window| unsafe { instance.create_surface(window) });
+
+        #[cfg(not(target_os = "unknown"))]
+        {
+            tracing::debug!("Available adapters:");
+            let adapters = instance
+                .enumerate_adapters(backends)
+                .filter(|adapter| surface.as_ref().map(|surface| adapter.is_surface_supported(surface)).unwrap_or(true))
+                .inspect(|adapter| tracing::debug!("Adapter: {:?}", adapter.get_info()))
+                .collect::<Vec<_>>();
+            if let Some(name) = &options.adapter_name {
+                let name = name.to_lowercase();
+                if let Some(adapter) = adapters.into_iter().find(|adapter| adapter.get_info().name.to_lowercase().contains(&name)) {
+                    return Some((instance, surface, adapter));
+                }
+                tracing::warn!("No GPU adapter named {name:?} found; using the preferred adapter instead");
+            }
+        }
+
+        tracing::debug!("Requesting adapter");
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: options.power_preference,
+                compatible_surface: surface.as_ref(),
+                force_fallback_adapter,
+            })
+            .await?;
+        Some((instance, surface, adapter))
+    }
+
+    /// The adapters available for `backends`, for diagnostics
+    #[cfg(not(target_os = "unknown"))]
+    pub fn available_adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+        wgpu::Instance::new(backends).enumerate_adapters(backends).map(|adapter| adapter.get_info()).collect()
+    }
+
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
     pub fn resize(&self, size: winit::dpi::PhysicalSize<u32>) {
         if let Some(surface) = &self.surface {
             if size.width > 0 && size.height > 0 {
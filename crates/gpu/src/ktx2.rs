@@ -0,0 +1,326 @@
+//! Reading and writing of KTX2 textures holding UASTC (BasisU) data, supercompressed with Zstandard.
+//!
+//! UASTC is a universal format; it's transcoded on load to a compressed format the GPU supports (see [TranscodeTarget]).
+
+use anyhow::{bail, ensure, Context};
+use byteorder::{ByteOrder, LittleEndian};
+
+pub const KTX2_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+const COLOR_MODEL_UASTC: u8 = 166;
+const CHANNEL_UASTC_RGB: u8 = 0;
+const CHANNEL_UASTC_RGBA: u8 = 3;
+const TRANSFER_LINEAR: u8 = 1;
+const TRANSFER_SRGB: u8 = 2;
+/// UASTC blocks are 4x4 pixels, 16 bytes each
+pub const UASTC_BLOCK_SIZE: usize = 16;
+
+const HEADER_SIZE: usize = 80;
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+const DFD_SIZE: usize = 44;
+
+pub fn is_ktx2(data: &[u8]) -> bool {
+    data.starts_with(&KTX2_IDENTIFIER)
+}
+
+/// A 2D texture of UASTC blocks, with one entry in `levels` per mip level (the largest first)
+#[derive(Debug, Clone)]
+pub struct Ktx2Texture {
+    pub width: u32,
+    pub height: u32,
+    pub srgb: bool,
+    pub has_alpha: bool,
+    pub levels: Vec<Vec<u8>>,
+}
+impl Ktx2Texture {
+    pub fn level_size(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+    pub fn level_blocks(&self, level: usize) -> (u32, u32) {
+        let (width, height) = self.level_size(level);
+        ((width + 3) / 4, (height + 3) / 4)
+    }
+
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        ensure!(is_ktx2(data), "Not a KTX2 file");
+        ensure!(data.len() >= HEADER_SIZE, "Truncated KTX2 header");
+        let u32_at = |offset: usize| LittleEndian::read_u32(&data[offset..offset + 4]);
+        let u64_at = |offset: usize| LittleEndian::read_u64(&data[offset..offset + 8]) as usize;
+
+        let vk_format = u32_at(12);
+        let width = u32_at(20);
+        let height = u32_at(24);
+        let depth = u32_at(28);
+        let layers = u32_at(32);
+        let faces = u32_at(36);
+        let level_count = u32_at(40).max(1) as usize;
+        let supercompression = u32_at(44);
+        ensure!(vk_format == 0, "Only BasisU KTX2 textures are supported, got VkFormat {vk_format}");
+        ensure!(depth == 0 && layers == 0 && faces == 1, "Only 2D KTX2 textures are supported");
+
+        let dfd_offset = u32_at(48) as usize;
+        let dfd = data.get(dfd_offset..dfd_offset + DFD_SIZE).context("Truncated KTX2 data format descriptor")?;
+        ensure!(dfd[12] == COLOR_MODEL_UASTC, "Only UASTC KTX2 textures are supported, got color model {}", dfd[12]);
+        let srgb = dfd[14] == TRANSFER_SRGB;
+        let has_alpha = dfd[31] & 0xF == CHANNEL_UASTC_RGBA;
+
+        let levels = (0..level_count)
+            .map(|level| {
+                let entry = HEADER_SIZE + level * LEVEL_INDEX_ENTRY_SIZE;
+                ensure!(data.len() >= entry + LEVEL_INDEX_ENTRY_SIZE, "Truncated KTX2 level index");
+                let (offset, length, uncompressed_length) = (u64_at(entry), u64_at(entry + 8), u64_at(entry + 16));
+                let level_data = data.get(offset..offset + length).with_context(|| format!("Truncated KTX2 level {level}"))?;
+                match supercompression {
+                    SUPERCOMPRESSION_NONE => Ok(level_data.to_vec()),
+                    SUPERCOMPRESSION_ZSTD => zstd_decompress(level_data, uncompressed_length),
+                    other => bail!("Unsupported KTX2 supercompression scheme {other}"),
+                }
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let texture = Self { width, height, srgb, has_alpha, levels };
+        for (level, data) in texture.levels.iter().enumerate() {
+            let (blocks_x, blocks_y) = texture.level_blocks(level);
+            ensure!(data.len() == (blocks_x * blocks_y) as usize * UASTC_BLOCK_SIZE, "KTX2 level {level} has the wrong size");
+        }
+        Ok(texture)
+    }
+
+    /// Writes the texture as a KTX2 file, supercompressing the levels with Zstandard at `zstd_level` if it's set
+    pub fn to_bytes(&self, zstd_level: Option<i32>) -> anyhow::Result<Vec<u8>> {
+        let levels = self
+            .levels
+            .iter()
+            .map(|level| match zstd_level {
+                Some(zstd_level) => Ok((zstd_compress(level, zstd_level)?, level.len())),
+                None => Ok((level.clone(), level.len())),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let dfd_offset = HEADER_SIZE + levels.len() * LEVEL_INDEX_ENTRY_SIZE;
+        let mut out = Vec::new();
+        out.extend_from_slice(&KTX2_IDENTIFIER);
+        for value in [
+            0,
+            1,
+            self.width,
+            self.height,
+            0,
+            0,
+            1,
+            levels.len() as u32,
+            if zstd_level.is_some() { SUPERCOMPRESSION_ZSTD } else { SUPERCOMPRESSION_NONE },
+            dfd_offset as u32,
+            DFD_SIZE as u32,
+            0,
+            0,
+        ] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        // No supercompression global data
+        out.extend_from_slice(&[0; 16]);
+
+        // The levels are stored smallest first, but indexed largest first
+        let level_index_offset = out.len();
+        out.resize(dfd_offset, 0);
+        out.extend_from_slice(&self.data_format_descriptor(zstd_level.is_some()));
+        for (level, (data, uncompressed_length)) in levels.iter().enumerate().rev() {
+            if zstd_level.is_none() {
+                out.resize((out.len() + UASTC_BLOCK_SIZE - 1) / UASTC_BLOCK_SIZE * UASTC_BLOCK_SIZE, 0);
+            }
+            let entry = level_index_offset + level * LEVEL_INDEX_ENTRY_SIZE;
+            LittleEndian::write_u64(&mut out[entry..entry + 8], out.len() as u64);
+            LittleEndian::write_u64(&mut out[entry + 8..entry + 16], data.len() as u64);
+            LittleEndian::write_u64(&mut out[entry + 16..entry + 24], *uncompressed_length as u64);
+            out.extend_from_slice(data);
+        }
+        Ok(out)
+    }
+
+    fn data_format_descriptor(&self, supercompressed: bool) -> [u8; DFD_SIZE] {
+        let mut dfd = [0; DFD_SIZE];
+        LittleEndian::write_u32(&mut dfd[0..4], DFD_SIZE as u32);
+        // Vendor and descriptor type are both 0 (Khronos, basic), version 2, block size
+        LittleEndian::write_u16(&mut dfd[8..10], 2);
+        LittleEndian::write_u16(&mut dfd[10..12], DFD_SIZE as u16 - 4);
+        dfd[12] = COLOR_MODEL_UASTC;
+        dfd[13] = 1; // BT.709 primaries
+        dfd[14] = if self.srgb { TRANSFER_SRGB } else { TRANSFER_LINEAR };
+        dfd[16] = 3; // 4x4 blocks, stored as size - 1
+        dfd[17] = 3;
+        // Supercompressed textures don't have a fixed number of bytes per block
+        dfd[20] = if supercompressed { 0 } else { UASTC_BLOCK_SIZE as u8 };
+        // The single sample covers the whole 128 bit block
+        dfd[30] = 127;
+        dfd[31] = if self.has_alpha { CHANNEL_UASTC_RGBA } else { CHANNEL_UASTC_RGB };
+        LittleEndian::write_u32(&mut dfd[40..44], u32::MAX);
+        dfd
+    }
+
+    /// The best target for this texture on a device with `features`. The compressed formats are made of 4x4 blocks, which
+    /// the GPU only takes for sizes that are multiples of 4, so other textures are transcoded to [TranscodeTarget::Rgba8]
+    pub fn transcode_target(&self, features: wgpu::Features) -> TranscodeTarget {
+        if self.width % 4 == 0 && self.height % 4 == 0 {
+            TranscodeTarget::for_features(features)
+        } else {
+            TranscodeTarget::Rgba8
+        }
+    }
+
+    /// Transcodes every level to `target`, returning the levels' data one after the other
+    #[cfg(not(target_os = "unknown"))]
+    pub fn transcode(&self, target: TranscodeTarget) -> anyhow::Result<Vec<u8>> {
+        use basis_universal::{DecodeFlags, LowLevelUastcTranscoder, SliceParametersUastc, TranscoderBlockFormat};
+
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(basis_universal::transcoder_init);
+
+        let format = match target {
+            TranscodeTarget::Bc7 => TranscoderBlockFormat::BC7,
+            TranscodeTarget::Astc => TranscoderBlockFormat::ASTC_4x4,
+            TranscodeTarget::Etc2 => TranscoderBlockFormat::ETC2_RGBA,
+            TranscodeTarget::Rgba8 => TranscoderBlockFormat::RGBA32,
+        };
+        let transcoder = LowLevelUastcTranscoder::new();
+        let mut out = Vec::new();
+        for (level, data) in self.levels.iter().enumerate() {
+            let (width, height) = self.level_size(level);
+            let (num_blocks_x, num_blocks_y) = self.level_blocks(level);
+            let params = SliceParametersUastc {
+                num_blocks_x,
+                num_blocks_y,
+                has_alpha: self.has_alpha,
+                original_width: width,
+                original_height: height,
+            };
+            let transcoded = transcoder
+                .transcode_slice(data, params, DecodeFlags::HIGH_QUALITY, format)
+                .map_err(|err| anyhow::anyhow!("Failed to transcode level {level}: {err:?}"))?;
+            out.extend(transcoded);
+        }
+        Ok(out)
+    }
+    #[cfg(target_os = "unknown")]
+    pub fn transcode(&self, _target: TranscodeTarget) -> anyhow::Result<Vec<u8>> {
+        bail!("KTX2 textures are not supported on the web yet")
+    }
+
+    /// Transcodes the largest level to an RGBA8 image, for when the pixels are needed on the CPU
+    pub fn to_rgba8_image(&self) -> anyhow::Result<image::RgbaImage> {
+        let first_level = Self { levels: self.levels[..1].to_vec(), ..self.clone() };
+        let data = first_level.transcode(TranscodeTarget::Rgba8)?;
+        image::RgbaImage::from_raw(self.width, self.height, data).context("Transcoded image has the wrong size")
+    }
+}
+
+/// The GPU format a [Ktx2Texture] is transcoded to when it's loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscodeTarget {
+    Bc7,
+    Astc,
+    Etc2,
+    /// Uncompressed, for devices that don't support any of the compressed formats
+    Rgba8,
+}
+impl TranscodeTarget {
+    /// Picks the best target the device supports; BC7 on desktop, ASTC or ETC2 on mobile
+    pub fn for_features(features: wgpu::Features) -> Self {
+        if features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC) {
+            Self::Bc7
+        } else if features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR) {
+            Self::Astc
+        } else if features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2) {
+            Self::Etc2
+        } else {
+            Self::Rgba8
+        }
+    }
+    pub fn texture_format(&self, srgb: bool) -> wgpu::TextureFormat {
+        use wgpu::TextureFormat::*;
+        match (self, srgb) {
+            (Self::Bc7, true) => Bc7RgbaUnormSrgb,
+            (Self::Bc7, false) => Bc7RgbaUnorm,
+            (Self::Astc, true) => Astc4x4RgbaUnormSrgb,
+            (Self::Astc, false) => Astc4x4RgbaUnorm,
+            (Self::Etc2, true) => Etc2Rgba8UnormSrgb,
+            (Self::Etc2, false) => Etc2Rgba8Unorm,
+            (Self::Rgba8, true) => Rgba8UnormSrgb,
+            (Self::Rgba8, false) => Rgba8Unorm,
+        }
+    }
+}
+
+#[cfg(not(target_os = "unknown"))]
+fn zstd_compress(data: &[u8], level: i32) -> anyhow::Result<Vec<u8>> {
+    Ok(zstd::bulk::compress(data, level)?)
+}
+#[cfg(not(target_os = "unknown"))]
+fn zstd_decompress(data: &[u8], uncompressed_length: usize) -> anyhow::Result<Vec<u8>> {
+    zstd::bulk::decompress(data, uncompressed_length).context("Failed to decompress KTX2 level")
+}
+#[cfg(target_os = "unknown")]
+fn zstd_compress(_data: &[u8], _level: i32) -> anyhow::Result<Vec<u8>> {
+    bail!("Zstandard is not supported on the web yet")
+}
+#[cfg(target_os = "unknown")]
+fn zstd_decompress(_data: &[u8], _uncompressed_length: usize) -> anyhow::Result<Vec<u8>> {
+    bail!("Zstandard is not supported on the web yet")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture(width: u32, height: u32) -> Ktx2Texture {
+        let mut texture = Ktx2Texture { width, height, srgb: true, has_alpha: true, levels: Vec::new() };
+        let level_count = width.max(height).ilog2() as usize + 1;
+        texture.levels = (0..level_count)
+            .map(|level| {
+                let (blocks_x, blocks_y) = texture.level_blocks(level);
+                (0..blocks_x * blocks_y * UASTC_BLOCK_SIZE as u32).map(|i| (i as usize * 7 + level) as u8).collect()
+            })
+            .collect();
+        texture
+    }
+
+    fn assert_round_trips(texture: &Ktx2Texture, zstd_level: Option<i32>) {
+        let bytes = texture.to_bytes(zstd_level).unwrap();
+        assert!(is_ktx2(&bytes));
+        let parsed = Ktx2Texture::parse(&bytes).unwrap();
+        assert_eq!(
+            (parsed.width, parsed.height, parsed.srgb, parsed.has_alpha),
+            (texture.width, texture.height, texture.srgb, texture.has_alpha)
+        );
+        assert_eq!(parsed.levels, texture.levels);
+    }
+
+    #[test]
+    fn round_trip() {
+        let texture = texture(16, 8);
+        assert_eq!(texture.levels.len(), 5);
+        assert_round_trips(&texture, None);
+        assert_round_trips(&Ktx2Texture { srgb: false, has_alpha: false, ..texture }, None);
+    }
+
+    #[test]
+    fn round_trip_supercompressed() {
+        assert_round_trips(&texture(32, 32), Some(3));
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        let bytes = texture(8, 8).to_bytes(None).unwrap();
+        assert!(Ktx2Texture::parse(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Ktx2Texture::parse(&bytes[..HEADER_SIZE - 1]).is_err());
+        assert!(Ktx2Texture::parse(b"not a texture").is_err());
+    }
+
+    #[test]
+    fn unaligned_textures_are_not_block_compressed() {
+        let features = wgpu::Features::TEXTURE_COMPRESSION_BC;
+        assert_eq!(texture(16, 8).transcode_target(features), TranscodeTarget::Bc7);
+        assert_eq!(texture(18, 8).transcode_target(features), TranscodeTarget::Rgba8);
+        assert_eq!(texture(16, 8).transcode_target(wgpu::Features::empty()), TranscodeTarget::Rgba8);
+    }
+}
@@ -13,7 +13,7 @@ use ambient_std::{
     mesh::Mesh,
 };
 use async_trait::async_trait;
-use glam::{UVec4, Vec2, Vec4};
+use glam::{UVec4, Vec2, Vec3, Vec4};
 use itertools::Itertools;
 use parking_lot::Mutex;
 use wgpu::RenderPass;
@@ -353,6 +353,40 @@ impl MeshBuffer {
         self.gpu.queue.submit(Some(encoder.finish()));
         MESHES_TOTAL_SIZE.store(self.size() as usize, Ordering::SeqCst);
     }
+    /// Overwrites the vertices of `mesh` from `offset` in place, e.g. to animate procedural meshes without reallocating them.
+    /// Empty attributes are left as they are; the others must fit in the vertices `mesh` was created with
+    pub fn write_vertices(
+        &mut self,
+        mesh: &GpuMesh,
+        offset: u64,
+        positions: &[Vec3],
+        normals: &[Vec3],
+        texcoords: &[Vec2],
+    ) -> anyhow::Result<()> {
+        let Some(internal) = self.meshes[mesh.index as usize].as_ref() else { anyhow::bail!("Mesh {} was removed", mesh.index) };
+        for (name, len, count) in [
+            ("positions", positions.len(), internal.position_count),
+            ("normals", normals.len(), internal.normal_count),
+            ("texcoords", texcoords.len(), internal.texcoord0_count),
+        ] {
+            if len > 0 && offset + len as u64 > count {
+                anyhow::bail!("Writing {len} {name} at {offset} overflows the {count} {name} of mesh {:?}", mesh.name);
+            }
+        }
+        let metadata = internal.metadata;
+        if !positions.is_empty() {
+            self.position_buffer
+                .front
+                .write(metadata.position_offset as u64 + offset, &positions.iter().map(|p| p.extend(0.)).collect_vec());
+        }
+        if !normals.is_empty() {
+            self.normal_buffer.front.write(metadata.normal_offset as u64 + offset, &normals.iter().map(|p| p.extend(0.)).collect_vec());
+        }
+        if !texcoords.is_empty() {
+            self.texcoord0_buffer.front.write(metadata.texcoord0_offset as u64 + offset, texcoords);
+        }
+        Ok(())
+    }
     pub fn get_mesh_metadata(&self, mesh: &GpuMesh) -> &MeshMetadata {
         &self.meshes[mesh.index as usize].as_ref().unwrap().metadata
     }
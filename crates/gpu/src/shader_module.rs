@@ -299,7 +299,7 @@ impl Shader {
             },
             fragment: Some(wgpu::FragmentState { module: self.module(), entry_point: info.fs_main, targets: info.targets }),
             depth_stencil: info.depth,
-            multisample: wgpu::MultisampleState { count: DEFAULT_SAMPLE_COUNT, mask: !0, alpha_to_coverage_enabled: false },
+            multisample: wgpu::MultisampleState { count: info.sample_count, mask: !0, alpha_to_coverage_enabled: false },
             multiview: None,
         });
 
@@ -333,6 +333,7 @@ pub struct GraphicsPipelineInfo<'a> {
     pub front_face: wgpu::FrontFace,
     pub cull_mode: Option<wgpu::Face>,
     pub topology: wgpu::PrimitiveTopology,
+    pub sample_count: u32,
 }
 
 impl<'a> Default for GraphicsPipelineInfo<'a> {
@@ -345,6 +346,7 @@ impl<'a> Default for GraphicsPipelineInfo<'a> {
             front_face: wgpu::FrontFace::Cw,
             cull_mode: None,
             topology: wgpu::PrimitiveTopology::TriangleList,
+            sample_count: DEFAULT_SAMPLE_COUNT,
         }
     }
 }
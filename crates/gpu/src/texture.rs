@@ -12,7 +12,7 @@ use std::{
 use ambient_std::asset_cache::{AssetCache, SyncAssetKeyExt};
 use bytemuck::Pod;
 use byteorder::{ByteOrder, LittleEndian};
-use glam::{uvec2, UVec4, Vec4};
+use glam::{uvec2, UVec2, UVec4, Vec4};
 use image::{io::Reader as ImageReader, DynamicImage, Rgba, RgbaImage};
 use itertools::Itertools;
 use ndarray::{s, Array, Array2, Array4, Dimension};
@@ -22,6 +22,7 @@ use wgpu::util::DeviceExt;
 use super::{
     fill::FillerKey,
     gpu::{Gpu, GpuKey},
+    ktx2::Ktx2Texture,
     mipmap::generate_mipmaps,
 };
 
@@ -51,14 +52,18 @@ impl Texture {
     }
 
     fn size_in_bytes_from_desc(descriptor: &wgpu::TextureDescriptor) -> u64 {
-        let mut mip_size = (descriptor.size.width as u64 * descriptor.size.height as u64 * descriptor.size.depth_or_array_layers as u64)
-            * descriptor.format.describe().block_size as u64;
-        let mut size_in_bytes = mip_size;
-        for _ in 1..descriptor.mip_level_count {
-            mip_size /= 2;
-            size_in_bytes += mip_size;
-        }
-        size_in_bytes
+        let info = descriptor.format.describe();
+        let (block_width, block_height) = (info.block_dimensions.0 as u64, info.block_dimensions.1 as u64);
+        (0..descriptor.mip_level_count)
+            .map(|level| {
+                let width = (descriptor.size.width as u64 >> level).max(1);
+                let height = (descriptor.size.height as u64 >> level).max(1);
+                ((width + block_width - 1) / block_width)
+                    * ((height + block_height - 1) / block_height)
+                    * descriptor.size.depth_or_array_layers as u64
+                    * info.block_size as u64
+            })
+            .sum()
     }
 
     pub fn new(gpu: Arc<Gpu>, descriptor: &wgpu::TextureDescriptor) -> Self {
@@ -132,6 +137,25 @@ impl Texture {
         gpu.queue.submit(Some(encoder.finish()));
         texture
     }
+    /// Creates a texture from a KTX2 texture, transcoded to a format the GPU supports.
+    /// `srgb` picks between the sRGB and linear variants of that format.
+    pub fn from_ktx2(gpu: Arc<Gpu>, texture: &Ktx2Texture, srgb: bool, label: wgpu::Label) -> anyhow::Result<Self> {
+        let target = texture.transcode_target(gpu.device.features());
+        let data = texture.transcode(target)?;
+        Ok(Self::new_with_data(
+            gpu,
+            &wgpu::TextureDescriptor {
+                size: wgpu::Extent3d { width: texture.width, height: texture.height, depth_or_array_layers: 1 },
+                mip_level_count: texture.levels.len() as u32,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: target.texture_format(srgb),
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                label,
+            },
+            &data,
+        ))
+    }
     pub fn from_image(gpu: Arc<Gpu>, image: DynamicImage, format: wgpu::TextureFormat, label: wgpu::Label) -> Self {
         let img = image.into_rgba8();
 
@@ -257,6 +281,25 @@ impl Texture {
         );
     }
 
+    /// Writes `data`, tightly packed rows of pixels, to the region of the first mip level at `origin` of `size`
+    pub fn write_region(&self, origin: UVec2, size: UVec2, data: &[u8]) {
+        self.gpu.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.handle,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: origin.x, y: origin.y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(size.x * self.format.describe().block_size as u32),
+                rows_per_image: NonZeroU32::new(size.y),
+            },
+            wgpu::Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 },
+        );
+    }
+
     pub fn reader(&self) -> TextureReader {
         let mut encoder = self.gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
         let reader = self.reader_with_encoder(&mut encoder);
@@ -1,7 +1,7 @@
 use std::{borrow::Cow, fmt, io::Cursor, sync::Arc};
 
 use ambient_std::{
-    asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt},
+    asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKeyExt},
     asset_url::AbsAssetUrl,
     download_asset::{AssetError, AssetResult, BytesFromUrl},
     CowStr,
@@ -11,7 +11,11 @@ use async_trait::async_trait;
 use futures::future::join_all;
 use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
 
-use crate::texture::Texture;
+use crate::{
+    gpu::GpuKey,
+    ktx2::{self, Ktx2Texture},
+    texture::Texture,
+};
 
 #[derive(Debug, Clone)]
 pub struct ImageFromUrl {
@@ -40,6 +44,9 @@ async fn image_from_url(assets: AssetCache, url: AbsAssetUrl) -> Result<DynamicI
 
     let extension = url.extension().context("No extension")?;
     Ok(task::block_in_place(move || -> anyhow::Result<DynamicImage> {
+        if ktx2::is_ktx2(&data) {
+            return Ok(DynamicImage::ImageRgba8(Ktx2Texture::parse(&data)?.to_rgba8_image()?));
+        }
         let format = ImageFormat::from_extension(extension).context("Invalid extension")?;
         Ok(image::io::Reader::with_format(Cursor::new(&*data), format).decode()?)
     })
@@ -59,6 +66,14 @@ impl AsyncAssetKey<Result<Arc<Texture>, AssetError>> for TextureFromUrl {
     }
     #[tracing::instrument(level = "info", name = "texture_from_url")]
     async fn load(self, assets: AssetCache) -> Result<Arc<Texture>, AssetError> {
+        if self.url.extension_is("ktx2") {
+            let data = BytesFromUrl::new(self.url.clone(), true).get(&assets).await?;
+            return task::block_in_place(|| {
+                let texture = Ktx2Texture::parse(&data).with_context(|| format!("Failed to load KTX2 texture {}", self.url))?;
+                let srgb = self.format.describe().srgb;
+                Ok(Arc::new(Texture::from_ktx2(GpuKey.get(&assets), &texture, srgb, Some(&self.url.to_string()))?))
+            });
+        }
         let image = image_from_url(assets.clone(), self.url.clone()).await?;
         task::block_in_place(|| Ok(Arc::new(Texture::from_image_mipmapped(assets, image, self.format, Some(&self.url.to_string())))))
     }
@@ -103,6 +118,10 @@ impl AsyncAssetKey<Result<Arc<Texture>, AssetError>> for TextureFromBytes {
     }
     async fn load(self, assets: AssetCache) -> Result<Arc<Texture>, AssetError> {
         let texture = task::spawn_blocking(move || -> anyhow::Result<Arc<Texture>> {
+            if ktx2::is_ktx2(&self.bytes) {
+                let texture = Ktx2Texture::parse(&self.bytes)?;
+                return Ok(Arc::new(Texture::from_ktx2(GpuKey.get(&assets), &texture, true, self.label.as_deref())?));
+            }
             let image = image::load_from_memory(&self.bytes[..]).context("Failed to load image from bytes")?;
             Ok(Arc::new(Texture::from_image_mipmapped(assets, image, wgpu::TextureFormat::Rgba8UnormSrgb, self.label.as_deref())))
         })
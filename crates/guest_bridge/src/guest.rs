@@ -56,6 +56,12 @@ pub mod ecs {
         pub fn resource<T: ComponentValue + SupportedValueGet>(&self, component: Component<T>) -> T {
             ambient_api::entity::get_component(ambient_api::entity::resources(), component).unwrap()
         }
+        pub fn resource_opt<T: ComponentValue + SupportedValueGet>(&self, component: Component<T>) -> Option<T> {
+            ambient_api::entity::get_component(ambient_api::entity::resources(), component)
+        }
+        pub fn add_resource<T: ComponentValue>(&self, component: Component<T>, value: T) {
+            ambient_api::entity::add_component(ambient_api::entity::resources(), component, value);
+        }
     }
     #[derive(Debug)]
     pub enum ECSError {
@@ -82,7 +88,19 @@ pub mod window {
     pub fn set_cursor(_world: &crate::ecs::World, _cursor: CursorIcon) {
         // TODO: Once we have client side scripting this needs to be hooked up to that
     }
+    #[cfg(feature = "client")]
+    pub fn get_clipboard() -> Option<String> {
+        ambient_api::window::get_clipboard()
+    }
+    #[cfg(feature = "client")]
+    pub fn set_clipboard(text: &str) {
+        ambient_api::window::set_clipboard(text);
+    }
+    /// Server modules have no clipboard
+    #[cfg(not(feature = "client"))]
     pub fn get_clipboard() -> Option<String> {
         None
     }
+    #[cfg(not(feature = "client"))]
+    pub fn set_clipboard(_text: &str) {}
 }
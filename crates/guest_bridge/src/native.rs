@@ -38,14 +38,24 @@ pub mod components {
     }
     pub mod input {
         pub use ambient_input::{
+            accessibility::{accessibility_label, accessibility_role, event_ui_activate, event_ui_focus, focus_order},
             event_focus_change, event_keyboard_input, event_mouse_input, event_mouse_motion, event_mouse_wheel, event_mouse_wheel_pixels,
-            event_received_character, keyboard_modifiers, keycode, mouse_button,
+            event_received_character, event_touch,
+            files::{event_file, file_handle},
+            keyboard_modifiers, keycode, mouse_button,
             picking::{mouse_over, mouse_pickable_max, mouse_pickable_min},
+            touch_capture, touch_id, touch_phase,
         };
     }
     pub mod player {
         pub use ambient_core::player::{local_user_id, player, user_id};
     }
+    pub mod ui {
+        pub use ambient_core::{
+            theme::{theme_color_tokens, theme_color_values, theme_name, theme_size_tokens, theme_size_values},
+            transition::{transition_curve, transition_duration},
+        };
+    }
 }
 
 pub fn run_async(world: &ecs::World, future: impl Future<Output = ()> + Send + 'static) {
@@ -64,13 +74,9 @@ pub mod window {
         world.resource(window_ctl()).send(WindowCtl::SetCursorIcon(cursor.into())).ok();
     }
     pub fn get_clipboard() -> Option<String> {
-        #[cfg(not(target_os = "unknown"))]
-        {
-            return arboard::Clipboard::new().ok().and_then(|mut x| x.get_text().ok());
-        }
-        #[cfg(target_os = "unknown")]
-        {
-            return None;
-        }
+        ambient_sys::clipboard::get_text()
+    }
+    pub fn set_clipboard(text: &str) {
+        ambient_sys::clipboard::set_text(text);
     }
 }
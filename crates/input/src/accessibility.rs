@@ -0,0 +1,41 @@
+use ambient_ecs::{components, world_events, Debuggable, Description, Entity, EntityId, Name, Networked, Resource, Store, World};
+
+components!("input", {
+    @[Debuggable, Networked, Store, Name["Accessibility role"], Description["What this UI entity is to assistive technologies like screen readers, like `button`, `checkbox`, `text_input` or `label`."]]
+    accessibility_role: String,
+    @[Debuggable, Networked, Store, Name["Accessibility label"], Description["What this UI entity is called by assistive technologies like screen readers.\nIf not set, the text of its first descendant with text is used."]]
+    accessibility_label: String,
+    @[Debuggable, Networked, Store, Name["Focus order"], Description["This UI entity can be focused with Tab and Shift-Tab. Entities with a lower order are focused first; entities with the same order are focused from the top left."]]
+    focus_order: i32,
+
+    @[Debuggable, Networked, Store, Name["Event UI focus"], Description["The UI entity with keyboard focus changed. The value is the entity, or null if no entity has focus."]]
+    event_ui_focus: EntityId,
+    @[Debuggable, Networked, Store, Name["Event UI activate"], Description["The UI entity was activated from the keyboard or by assistive technologies, like a click."]]
+    event_ui_activate: EntityId,
+
+    @[Debuggable, Resource]
+    ui_focus: EntityId,
+});
+
+/// The UI entity with keyboard focus, if any
+pub fn get_ui_focus(world: &World) -> Option<EntityId> {
+    world.resource_opt(ui_focus()).copied().filter(|id| !id.is_null())
+}
+
+/// Gives `id` keyboard focus, or clears it if `id` is null
+pub fn set_ui_focus(world: &mut World, id: EntityId) {
+    if get_ui_focus(world).unwrap_or_else(EntityId::null) == id {
+        return;
+    }
+    world.add_resource(ui_focus(), id);
+    world
+        .resource_mut(world_events())
+        .add_event((ambient_event_types::UI_FOCUS_CHANGED.to_string(), Entity::new().with(event_ui_focus(), id)));
+}
+
+/// Activates `id`, like when it's clicked
+pub fn activate_ui(world: &mut World, id: EntityId) {
+    world
+        .resource_mut(world_events())
+        .add_event((ambient_event_types::UI_ACTIVATED.to_string(), Entity::new().with(event_ui_activate(), id)));
+}
@@ -0,0 +1,80 @@
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
+
+use ambient_core::async_ecs::async_run;
+use ambient_ecs::{components, world_events, Debuggable, Description, Entity, Name, Networked, Resource, Store, World};
+use parking_lot::Mutex;
+
+components!("input", {
+    @[Debuggable, Networked, Store, Name["Event file"], Description["A file was dropped into the window, or opened with the file dialog. The value is the name of the file.\nWill also contain a `file_handle` component."]]
+    event_file: String,
+    @[Debuggable, Networked, Store, Name["File handle"], Description["The handle to the contents of a file that was dropped into the window, or opened with the file dialog."]]
+    file_handle: u64,
+
+    @[Resource]
+    files: Files,
+});
+
+/// How many files are kept around for their handles to be read from
+const MAX_FILES: usize = 16;
+
+#[derive(Debug, Default)]
+struct FilesState {
+    next_handle: u64,
+    files: VecDeque<(u64, Arc<Vec<u8>>)>,
+}
+
+/// The contents of the files that were dropped into the window or opened with the file dialog, by their handle.
+///
+/// Only the most recent files are kept, so their contents should be read when their event is received.
+#[derive(Debug, Clone, Default)]
+pub struct Files(Arc<Mutex<FilesState>>);
+impl Files {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn insert(&self, bytes: Vec<u8>) -> u64 {
+        let mut state = self.0.lock();
+        let handle = state.next_handle;
+        state.next_handle += 1;
+        state.files.push_back((handle, Arc::new(bytes)));
+        if state.files.len() > MAX_FILES {
+            state.files.pop_front();
+        }
+        handle
+    }
+    pub fn get(&self, handle: u64) -> Option<Arc<Vec<u8>>> {
+        self.0.lock().files.iter().find(|(h, _)| *h == handle).map(|(_, bytes)| bytes.clone())
+    }
+}
+
+/// Stores `bytes` and emits the `event_name` event for them
+pub fn emit_file(world: &mut World, event_name: &str, name: String, bytes: Vec<u8>) {
+    let Some(files) = world.resource_opt(files()).cloned() else { return };
+    let handle = files.insert(bytes);
+    world
+        .resource_mut(world_events())
+        .add_event((event_name.to_string(), Entity::new().with(event_file(), name).with(file_handle(), handle)));
+}
+
+pub(crate) fn load_dropped_file(world: &World, path: PathBuf) {
+    let async_run = world.resource(async_run()).clone();
+    ambient_sys::task::spawn_local(move || async move {
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        match ambient_sys::fs::read(&path).await {
+            Ok(bytes) => async_run.run(move |world| emit_file(world, ambient_event_types::WINDOW_FILE_DROPPED, name, bytes)),
+            Err(err) => tracing::warn!("Failed to read dropped file {path:?}: {err}"),
+        }
+    });
+}
+
+/// Shows a dialog for the user to pick a file, and emits a `FILE_OPENED` event with it if they do.
+///
+/// See [ambient_sys::file_dialog::open_file] for `filters`.
+pub fn open_file_dialog(world: &World, filters: Vec<(String, Vec<String>)>) {
+    let async_run = world.resource(async_run()).clone();
+    ambient_sys::task::spawn_local(move || async move {
+        if let Some(file) = ambient_sys::file_dialog::open_file(&filters).await {
+            async_run.run(move |world| emit_file(world, ambient_event_types::FILE_OPENED, file.name, file.bytes));
+        }
+    });
+}
@@ -0,0 +1,318 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    str::FromStr,
+    sync::Arc,
+};
+
+use ambient_ecs::{components, Debuggable, Description, Entity, Name, Networked, Resource, Store};
+use glam::Vec2;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(not(target_os = "unknown"), path = "native.rs")]
+#[cfg_attr(target_os = "unknown", path = "wasm.rs")]
+mod backend;
+
+components!("input", {
+    @[Debuggable, Networked, Store, Name["Event gamepad connected"], Description["A gamepad was connected (true) or disconnected (false). Will also contain a `gamepad_id` component, and a `gamepad_name` component when connected."]]
+    event_gamepad_connected: bool,
+    @[Debuggable, Networked, Store, Name["Event gamepad button"], Description["A gamepad button was pressed (true) or released (false). Will also contain `gamepad_id` and `gamepad_button` components."]]
+    event_gamepad_button: bool,
+    @[Debuggable, Networked, Store, Name["Event gamepad axis"], Description["A gamepad axis moved. The value is its new position: -1 to 1 for sticks, with up being positive, and 0 to 1 for triggers.\nWill also contain `gamepad_id` and `gamepad_axis` components."]]
+    event_gamepad_axis: f32,
+
+    @[Debuggable, Networked, Store, Name["Gamepad ID"], Description["The gamepad an event is from. IDs may be reused after a gamepad is disconnected."]]
+    gamepad_id: u32,
+    @[Debuggable, Networked, Store, Name["Gamepad name"], Description["The name of a gamepad, as reported by the platform."]]
+    gamepad_name: String,
+    @[Debuggable, Networked, Store, Name["Gamepad button"], Description["The gamepad button of an event, like `south` or `dpad_up`."]]
+    gamepad_button: String,
+    @[Debuggable, Networked, Store, Name["Gamepad axis"], Description["The gamepad axis of an event, like `left_stick_x` or `right_trigger`."]]
+    gamepad_axis: String,
+
+    /// The most recent rumble requested for a player; set on the player entity, and played by their client
+    @[Debuggable, Networked]
+    gamepad_rumble: GamepadRumble,
+    @[Resource]
+    gamepads: Gamepads,
+    /// The gamepads of the other players on this client, by the index of the player; the local player uses the rest
+    @[Debuggable, Resource]
+    local_player_gamepads: BTreeMap<u32, u32>,
+});
+
+/// The buttons of a gamepad, named after their position on a standard layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GamepadButton {
+    /// The bottom face button; A on Xbox controllers, Cross on PlayStation controllers
+    South,
+    /// The right face button
+    East,
+    /// The top face button
+    North,
+    /// The left face button
+    West,
+    LeftBumper,
+    RightBumper,
+    /// The left trigger, when pressed far enough; see [GamepadAxis::LeftTrigger] for how far it's pressed
+    LeftTrigger,
+    /// The right trigger, when pressed far enough; see [GamepadAxis::RightTrigger] for how far it's pressed
+    RightTrigger,
+    Select,
+    Start,
+    /// The button in the middle, like the Xbox or PS button
+    Mode,
+    /// Pressing down the left stick
+    LeftStick,
+    /// Pressing down the right stick
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 17] = [
+        Self::South,
+        Self::East,
+        Self::North,
+        Self::West,
+        Self::LeftBumper,
+        Self::RightBumper,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+        Self::Select,
+        Self::Start,
+        Self::Mode,
+        Self::LeftStick,
+        Self::RightStick,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::South => "south",
+            Self::East => "east",
+            Self::North => "north",
+            Self::West => "west",
+            Self::LeftBumper => "left_bumper",
+            Self::RightBumper => "right_bumper",
+            Self::LeftTrigger => "left_trigger",
+            Self::RightTrigger => "right_trigger",
+            Self::Select => "select",
+            Self::Start => "start",
+            Self::Mode => "mode",
+            Self::LeftStick => "left_stick",
+            Self::RightStick => "right_stick",
+            Self::DPadUp => "dpad_up",
+            Self::DPadDown => "dpad_down",
+            Self::DPadLeft => "dpad_left",
+            Self::DPadRight => "dpad_right",
+        }
+    }
+}
+impl fmt::Display for GamepadButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl FromStr for GamepadButton {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|button| button.as_str() == s).ok_or_else(|| anyhow::anyhow!("Unknown gamepad button {s:?}"))
+    }
+}
+
+/// The analog inputs of a gamepad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+impl GamepadAxis {
+    pub const ALL: [GamepadAxis; 6] =
+        [Self::LeftStickX, Self::LeftStickY, Self::RightStickX, Self::RightStickY, Self::LeftTrigger, Self::RightTrigger];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LeftStickX => "left_stick_x",
+            Self::LeftStickY => "left_stick_y",
+            Self::RightStickX => "right_stick_x",
+            Self::RightStickY => "right_stick_y",
+            Self::LeftTrigger => "left_trigger",
+            Self::RightTrigger => "right_trigger",
+        }
+    }
+}
+impl fmt::Display for GamepadAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl FromStr for GamepadAxis {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|axis| axis.as_str() == s).ok_or_else(|| anyhow::anyhow!("Unknown gamepad axis {s:?}"))
+    }
+}
+
+/// The current state of a connected gamepad
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadState {
+    pub name: String,
+    pub buttons: HashSet<GamepadButton>,
+    pub left_stick: Vec2,
+    pub right_stick: Vec2,
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+}
+impl GamepadState {
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        match axis {
+            GamepadAxis::LeftStickX => self.left_stick.x,
+            GamepadAxis::LeftStickY => self.left_stick.y,
+            GamepadAxis::RightStickX => self.right_stick.x,
+            GamepadAxis::RightStickY => self.right_stick.y,
+            GamepadAxis::LeftTrigger => self.left_trigger,
+            GamepadAxis::RightTrigger => self.right_trigger,
+        }
+    }
+    fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        match axis {
+            GamepadAxis::LeftStickX => self.left_stick.x = value,
+            GamepadAxis::LeftStickY => self.left_stick.y = value,
+            GamepadAxis::RightStickX => self.right_stick.x = value,
+            GamepadAxis::RightStickY => self.right_stick.y = value,
+            GamepadAxis::LeftTrigger => self.left_trigger = value,
+            GamepadAxis::RightTrigger => self.right_trigger = value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadEvent {
+    Connected { id: u32, name: String },
+    Disconnected { id: u32 },
+    Button { id: u32, button: GamepadButton, pressed: bool },
+    Axis { id: u32, axis: GamepadAxis, value: f32 },
+}
+impl GamepadEvent {
+    /// The name and data of the world event for this event
+    pub fn to_world_event(&self) -> (String, Entity) {
+        match self {
+            Self::Connected { id, name } => (
+                ambient_event_types::GAMEPAD_CONNECTED.to_string(),
+                Entity::new().with(event_gamepad_connected(), true).with(gamepad_id(), *id).with(gamepad_name(), name.clone()),
+            ),
+            Self::Disconnected { id } => (
+                ambient_event_types::GAMEPAD_CONNECTED.to_string(),
+                Entity::new().with(event_gamepad_connected(), false).with(gamepad_id(), *id),
+            ),
+            Self::Button { id, button, pressed } => (
+                ambient_event_types::GAMEPAD_BUTTON.to_string(),
+                Entity::new().with(event_gamepad_button(), *pressed).with(gamepad_id(), *id).with(gamepad_button(), button.to_string()),
+            ),
+            Self::Axis { id, axis, value } => (
+                ambient_event_types::GAMEPAD_AXIS.to_string(),
+                Entity::new().with(event_gamepad_axis(), *value).with(gamepad_id(), *id).with(gamepad_axis(), axis.to_string()),
+            ),
+        }
+    }
+}
+
+/// A request to rumble a gamepad
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadRumble {
+    /// Increases with every request, so that a repeated request can be told apart from the previous one
+    pub sequence: u64,
+    /// The gamepad to rumble, or all of them if None
+    pub gamepad: Option<u32>,
+    /// The strength of the low-frequency motor, from 0 to 1
+    pub strong: f32,
+    /// The strength of the high-frequency motor, from 0 to 1
+    pub weak: f32,
+    /// In seconds
+    pub duration: f32,
+}
+
+/// The ID of the gamepad that on-screen touch controls report as; see [Gamepads::push_event]
+pub const VIRTUAL_GAMEPAD_ID: u32 = u32::MAX;
+
+#[derive(Debug)]
+struct GamepadsState {
+    backend: backend::Backend,
+    states: BTreeMap<u32, GamepadState>,
+    /// Events that didn't come from the backend, to be returned by the next poll
+    pending: Vec<GamepadEvent>,
+}
+
+/// The gamepads connected to this machine. Polled by the input systems, which turn the changes into world events.
+#[derive(Clone)]
+pub struct Gamepads(Arc<Mutex<GamepadsState>>);
+impl Gamepads {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(GamepadsState { backend: backend::Backend::new(), states: BTreeMap::new(), pending: Vec::new() })))
+    }
+    /// Returns the changes since the last poll, and applies them to [Gamepads::states]
+    pub fn poll(&self) -> Vec<GamepadEvent> {
+        let mut state = self.0.lock();
+        let mut events = std::mem::take(&mut state.pending);
+        events.extend(state.backend.poll());
+        for event in &events {
+            match event {
+                GamepadEvent::Connected { id, name } => {
+                    state.states.insert(*id, GamepadState { name: name.clone(), ..Default::default() });
+                }
+                GamepadEvent::Disconnected { id } => {
+                    state.states.remove(id);
+                }
+                GamepadEvent::Button { id, button, pressed } => {
+                    if let Some(gamepad) = state.states.get_mut(id) {
+                        if *pressed {
+                            gamepad.buttons.insert(*button);
+                        } else {
+                            gamepad.buttons.remove(button);
+                        }
+                    }
+                }
+                GamepadEvent::Axis { id, axis, value } => {
+                    if let Some(gamepad) = state.states.get_mut(id) {
+                        gamepad.set_axis(*axis, *value);
+                    }
+                }
+            }
+        }
+        events
+    }
+    /// Reports an event for a gamepad that isn't a physical device, like on-screen touch controls.
+    /// It's handled like the backend's events on the next poll.
+    pub fn push_event(&self, event: GamepadEvent) {
+        self.0.lock().pending.push(event);
+    }
+    pub fn states(&self) -> BTreeMap<u32, GamepadState> {
+        self.0.lock().states.clone()
+    }
+    /// Rumbles the requested gamepads, if they support it
+    pub fn rumble(&self, rumble: &GamepadRumble) {
+        self.0.lock().backend.rumble(rumble);
+    }
+}
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl fmt::Debug for Gamepads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gamepads").field("states", &self.0.lock().states).finish()
+    }
+}
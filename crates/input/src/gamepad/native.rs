@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use flume::{Receiver, Sender, TryRecvError};
+use gilrs::{
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks},
+    Axis, Button, EventType, GamepadId, Gilrs,
+};
+
+use super::{GamepadAxis, GamepadButton, GamepadEvent, GamepadRumble};
+
+/// gilrs is polled on its own thread, as it isn't `Sync` on all platforms
+#[derive(Debug)]
+pub struct Backend {
+    events: Receiver<GamepadEvent>,
+    rumbles: Sender<GamepadRumble>,
+}
+impl Backend {
+    pub fn new() -> Self {
+        let (events_tx, events) = flume::unbounded();
+        let (rumbles, rumbles_rx) = flume::unbounded();
+        if let Err(err) = std::thread::Builder::new().name("gamepads".to_string()).spawn(move || run(events_tx, rumbles_rx)) {
+            tracing::warn!("Failed to start the gamepad thread: {err:?}");
+        }
+        Self { events, rumbles }
+    }
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        self.events.try_iter().collect()
+    }
+    pub fn rumble(&mut self, rumble: &GamepadRumble) {
+        self.rumbles.send(rumble.clone()).ok();
+    }
+}
+
+fn run(events: Sender<GamepadEvent>, rumbles: Receiver<GamepadRumble>) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(err) => {
+            tracing::warn!("Gamepads are not available: {err}");
+            return;
+        }
+    };
+    let mut ids = HashMap::<u32, GamepadId>::new();
+    // gilrs only reports connections that happen after it was created
+    for (id, gamepad) in gilrs.gamepads() {
+        ids.insert(usize::from(id) as u32, id);
+        events.send(GamepadEvent::Connected { id: usize::from(id) as u32, name: gamepad.name().to_string() }).ok();
+    }
+    // Effects stop playing when they are dropped
+    let mut effects = Vec::<(Instant, Effect)>::new();
+    loop {
+        loop {
+            match rumbles.try_recv() {
+                Ok(rumble) => {
+                    let targets = match rumble.gamepad {
+                        Some(gamepad) => ids.get(&gamepad).copied().into_iter().collect(),
+                        None => ids.values().copied().collect::<Vec<_>>(),
+                    };
+                    let targets = targets.into_iter().filter(|id| gilrs.gamepad(*id).is_ff_supported()).collect::<Vec<_>>();
+                    if targets.is_empty() {
+                        continue;
+                    }
+                    match play_rumble(&mut gilrs, &targets, &rumble) {
+                        Ok(effect) => effects.push((Instant::now() + Duration::from_secs_f32(rumble.duration.max(0.)), effect)),
+                        Err(err) => tracing::warn!("Failed to rumble gamepad: {err}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        let now = Instant::now();
+        effects.retain(|(end, _)| *end > now);
+
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            let gamepad = usize::from(id) as u32;
+            let event = match event {
+                EventType::Connected => {
+                    ids.insert(gamepad, id);
+                    Some(GamepadEvent::Connected { id: gamepad, name: gilrs.gamepad(id).name().to_string() })
+                }
+                EventType::Disconnected => {
+                    ids.remove(&gamepad);
+                    Some(GamepadEvent::Disconnected { id: gamepad })
+                }
+                EventType::ButtonPressed(button, _) => {
+                    to_button(button).map(|button| GamepadEvent::Button { id: gamepad, button, pressed: true })
+                }
+                EventType::ButtonReleased(button, _) => {
+                    to_button(button).map(|button| GamepadEvent::Button { id: gamepad, button, pressed: false })
+                }
+                // The analog value of the triggers is reported as a button change
+                EventType::ButtonChanged(Button::LeftTrigger2, value, _) => {
+                    Some(GamepadEvent::Axis { id: gamepad, axis: GamepadAxis::LeftTrigger, value })
+                }
+                EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+                    Some(GamepadEvent::Axis { id: gamepad, axis: GamepadAxis::RightTrigger, value })
+                }
+                EventType::AxisChanged(axis, value, _) => to_axis(axis).map(|axis| GamepadEvent::Axis { id: gamepad, axis, value }),
+                _ => None,
+            };
+            if let Some(event) = event {
+                if events.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(4));
+    }
+}
+
+fn play_rumble(gilrs: &mut Gilrs, targets: &[GamepadId], rumble: &GamepadRumble) -> Result<Effect, gilrs::ff::Error> {
+    let scheduling = Replay { play_for: Ticks::from_ms((rumble.duration.max(0.) * 1000.) as u32), ..Default::default() };
+    let magnitude = |strength: f32| (strength.clamp(0., 1.) * u16::MAX as f32) as u16;
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect { kind: BaseEffectType::Strong { magnitude: magnitude(rumble.strong) }, scheduling, ..Default::default() })
+        .add_effect(BaseEffect { kind: BaseEffectType::Weak { magnitude: magnitude(rumble.weak) }, scheduling, ..Default::default() })
+        .gamepads(targets)
+        .finish(gilrs)?;
+    effect.play()?;
+    Ok(effect)
+}
+
+fn to_button(button: Button) -> Option<GamepadButton> {
+    Some(match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+fn to_axis(axis: Axis) -> Option<GamepadAxis> {
+    Some(match axis {
+        Axis::LeftStickX => GamepadAxis::LeftStickX,
+        Axis::LeftStickY => GamepadAxis::LeftStickY,
+        Axis::RightStickX => GamepadAxis::RightStickX,
+        Axis::RightStickY => GamepadAxis::RightStickY,
+        _ => return None,
+    })
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use wasm_bindgen::JsCast;
+
+use super::{GamepadAxis, GamepadButton, GamepadEvent, GamepadRumble};
+
+/// The buttons of the standard gamepad mapping, in the order the browser reports them
+const STANDARD_BUTTONS: [GamepadButton; 17] = [
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::West,
+    GamepadButton::North,
+    GamepadButton::LeftBumper,
+    GamepadButton::RightBumper,
+    GamepadButton::LeftTrigger,
+    GamepadButton::RightTrigger,
+    GamepadButton::Select,
+    GamepadButton::Start,
+    GamepadButton::LeftStick,
+    GamepadButton::RightStick,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+    GamepadButton::Mode,
+];
+/// The axes of the standard gamepad mapping, in the order the browser reports them
+const STANDARD_AXES: [GamepadAxis; 4] =
+    [GamepadAxis::LeftStickX, GamepadAxis::LeftStickY, GamepadAxis::RightStickX, GamepadAxis::RightStickY];
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Snapshot {
+    buttons: BTreeMap<GamepadButton, bool>,
+    axes: BTreeMap<GamepadAxis, f32>,
+}
+impl Snapshot {
+    fn read(gamepad: &web_sys::Gamepad) -> Self {
+        let buttons = gamepad.buttons().iter().map(|button| button.unchecked_into::<web_sys::GamepadButton>()).collect::<Vec<_>>();
+        let axes = gamepad.axes().iter().map(|axis| axis.as_f64().unwrap_or_default() as f32).collect::<Vec<_>>();
+        let mut snapshot = Self {
+            buttons: STANDARD_BUTTONS.iter().zip(&buttons).map(|(&standard, button)| (standard, button.pressed())).collect(),
+            axes: STANDARD_AXES
+                .iter()
+                .zip(&axes)
+                // The browser reports down as positive; it's flipped to match native
+                .map(|(&axis, &value)| {
+                    (axis, if matches!(axis, GamepadAxis::LeftStickY | GamepadAxis::RightStickY) { -value } else { value })
+                })
+                .collect(),
+        };
+        // The triggers are analog buttons; how far they're pressed is reported as an axis, like on native
+        for (index, axis) in [(6, GamepadAxis::LeftTrigger), (7, GamepadAxis::RightTrigger)] {
+            if let Some(button) = buttons.get(index) {
+                snapshot.axes.insert(axis, button.value() as f32);
+            }
+        }
+        snapshot
+    }
+}
+
+/// The Gamepad API has no events for button presses, so the gamepads are compared with how they were at the previous poll
+#[derive(Debug, Default)]
+pub struct Backend {
+    previous: BTreeMap<u32, Snapshot>,
+}
+impl Backend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        let mut events = Vec::new();
+        let mut current = BTreeMap::new();
+        for gamepad in connected_gamepads() {
+            let id = gamepad.index();
+            let snapshot = Snapshot::read(&gamepad);
+            let previous = match self.previous.remove(&id) {
+                Some(previous) => previous,
+                None => {
+                    events.push(GamepadEvent::Connected { id, name: gamepad.id() });
+                    Snapshot::default()
+                }
+            };
+            for (&button, &pressed) in &snapshot.buttons {
+                if pressed != previous.buttons.get(&button).copied().unwrap_or_default() {
+                    events.push(GamepadEvent::Button { id, button, pressed });
+                }
+            }
+            for (&axis, &value) in &snapshot.axes {
+                if value != previous.axes.get(&axis).copied().unwrap_or_default() {
+                    events.push(GamepadEvent::Axis { id, axis, value });
+                }
+            }
+            current.insert(id, snapshot);
+        }
+        for id in self.previous.keys() {
+            events.push(GamepadEvent::Disconnected { id: *id });
+        }
+        self.previous = current;
+        events
+    }
+    pub fn rumble(&mut self, rumble: &GamepadRumble) {
+        for gamepad in connected_gamepads().filter(|gamepad| rumble.gamepad.map(|id| id == gamepad.index()).unwrap_or(true)) {
+            for actuator in gamepad.haptic_actuators().iter() {
+                let actuator = actuator.unchecked_into::<web_sys::GamepadHapticActuator>();
+                // Browsers only expose a single motor, so the stronger of the two is used
+                actuator.pulse(rumble.strong.max(rumble.weak).clamp(0., 1.) as f64, (rumble.duration.max(0.) * 1000.) as f64).ok();
+            }
+        }
+    }
+}
+
+fn connected_gamepads() -> impl Iterator<Item = web_sys::Gamepad> {
+    web_sys::window()
+        .and_then(|window| window.navigator().get_gamepads().ok())
+        .map(|gamepads| gamepads.iter().collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|gamepad| gamepad.dyn_into::<web_sys::Gamepad>().ok())
+        .filter(|gamepad| gamepad.connected() && gamepad.mapping() == web_sys::GamepadMappingType::Standard)
+}
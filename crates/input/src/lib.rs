@@ -1,13 +1,25 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
-use ambient_ecs::{components, world_events, Debuggable, Description, Entity, Name, Networked, Store, System, SystemGroup};
+use ambient_core::{transform::local_to_world, window::cursor_position};
+use ambient_ecs::{components, query, world_events, Debuggable, Description, Entity, Name, Networked, Store, System, SystemGroup, World};
 use glam::{vec2, Vec2};
 use serde::{Deserialize, Serialize};
 use winit::event::ModifiersState;
-pub use winit::event::{DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+pub use winit::event::{
+    DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, Touch, TouchPhase, VirtualKeyCode, WindowEvent,
+};
 
+pub mod accessibility;
+pub mod files;
+pub mod gamepad;
 pub mod picking;
 
+/// The id of the datagram handler the clients send their [PlayerRawInput] to
+pub const PLAYER_INPUT_DATAGRAM_ID: u32 = 5;
+/// The id of the datagram handler the clients send the [PlayerRawInput] of their local players to, after the index of the
+/// local player
+pub const LOCAL_PLAYER_INPUT_DATAGRAM_ID: u32 = 6;
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct PlayerRawInput {
     pub keys: HashSet<ambient_window_types::VirtualKeyCode>,
@@ -16,6 +28,8 @@ pub struct PlayerRawInput {
     pub cursor_position: Vec2,
     pub mouse_wheel: f32,
     pub mouse_buttons: HashSet<ambient_window_types::MouseButton>,
+    /// The connected gamepads, by their id
+    pub gamepads: BTreeMap<u32, gamepad::GamepadState>,
 }
 
 components!("input", {
@@ -34,6 +48,8 @@ components!("input", {
     event_modifiers_change: ModifiersState,
     @[Debuggable, Networked, Store, Name["Event focus change"], Description["The window was focused or list its focus."]]
     event_focus_change: bool,
+    @[Debuggable, Networked, Store, Name["Event touch"], Description["A finger touched, moved on, or was lifted from the screen. The value is its position in screen-space.\nWill also contain `touch_id` and `touch_phase` components."]]
+    event_touch: Vec2,
 
     @[Debuggable, Networked, Store, Name["Keycode"], Description["Keycode when a keyboard key was pressed."]]
     keycode: String,
@@ -41,6 +57,12 @@ components!("input", {
     keyboard_modifiers: u32,
     @[Debuggable, Networked, Store, Name["Mouse button"], Description["The mouse button. 0=left, 1=right, 2=middle."]]
     mouse_button: u32,
+    @[Debuggable, Networked, Store, Name["Touch ID"], Description["Identifies a finger for as long as it touches the screen."]]
+    touch_id: u64,
+    @[Debuggable, Networked, Store, Name["Touch phase"], Description["The phase of a touch: `started`, `moved`, `ended` or `cancelled`."]]
+    touch_phase: String,
+    @[Debuggable, Networked, Store, Name["Touch capture"], Description["Touches that start within this UI entity, from its position to its position plus this size, are handled by it, and do not act as the mouse."]]
+    touch_capture: Vec2,
 
     @[Debuggable]
     player_raw_input: PlayerRawInput,
@@ -50,6 +72,9 @@ components!("input", {
 
 pub fn init_all_components() {
     picking::init_components();
+    accessibility::init_components();
+    files::init_components();
+    gamepad::init_components();
     init_components();
 }
 
@@ -61,12 +86,62 @@ pub fn event_systems() -> SystemGroup<Event<'static, ()>> {
 pub struct InputSystem {
     modifiers: ModifiersState,
     is_focused: bool,
+    /// The touch that acts as the mouse, and its last position
+    primary_touch: Option<(u64, Vec2)>,
 }
 
 impl InputSystem {
     pub fn new() -> Self {
-        Self { modifiers: ModifiersState::empty(), is_focused: true }
+        Self { modifiers: ModifiersState::empty(), is_focused: true, primary_touch: None }
     }
+
+    /// The first finger that touches the screen outside of any `touch_capture` area acts as the left mouse button,
+    /// so that UI and games made for the mouse can be used on touch screens
+    fn emulate_mouse(&mut self, world: &mut World, touch: &Touch, position: Vec2) {
+        match touch.phase {
+            TouchPhase::Started => {
+                if self.primary_touch.is_some() || is_touch_captured(world, position) {
+                    return;
+                }
+                self.primary_touch = Some((touch.id, position));
+                world.set(world.resource_entity(), cursor_position(), position).ok();
+                world.resource_mut(world_events()).add_event(mouse_input_event(true, MouseButton::Left));
+            }
+            TouchPhase::Moved => {
+                let Some((id, last_position)) = &mut self.primary_touch else { return };
+                if *id != touch.id {
+                    return;
+                }
+                let delta = position - *last_position;
+                *last_position = position;
+                world.set(world.resource_entity(), cursor_position(), position).ok();
+                world
+                    .resource_mut(world_events())
+                    .add_event((ambient_event_types::WINDOW_MOUSE_MOTION.to_string(), Entity::new().with(event_mouse_motion(), delta)));
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if self.primary_touch.map(|(id, _)| id) != Some(touch.id) {
+                    return;
+                }
+                self.primary_touch = None;
+                world.resource_mut(world_events()).add_event(mouse_input_event(false, MouseButton::Left));
+            }
+        }
+    }
+}
+
+fn is_touch_captured(world: &World, position: Vec2) -> bool {
+    query((touch_capture(), local_to_world())).iter(world, None).any(|(_, (size, transform))| {
+        let min = transform.to_scale_rotation_translation().2.truncate();
+        position.cmpge(min).all() && position.cmplt(min + *size).all()
+    })
+}
+
+fn mouse_input_event(pressed: bool, button: MouseButton) -> (String, Entity) {
+    (
+        ambient_event_types::WINDOW_MOUSE_INPUT.to_string(),
+        Entity::new().with(event_mouse_input(), pressed).with(mouse_button(), ambient_window_types::MouseButton::from(button).into()),
+    )
 }
 
 impl System<Event<'static, ()>> for InputSystem {
@@ -111,20 +186,32 @@ impl System<Event<'static, ()>> for InputSystem {
                 }
 
                 WindowEvent::MouseInput { state, button, .. } => {
+                    world.resource_mut(world_events()).add_event(mouse_input_event(
+                        match state {
+                            ElementState::Pressed => true,
+                            ElementState::Released => false,
+                        },
+                        *button,
+                    ));
+                }
+
+                WindowEvent::Touch(touch) => {
+                    let position = vec2(touch.location.x as f32, touch.location.y as f32);
+                    let phase = match touch.phase {
+                        TouchPhase::Started => "started",
+                        TouchPhase::Moved => "moved",
+                        TouchPhase::Ended => "ended",
+                        TouchPhase::Cancelled => "cancelled",
+                    };
                     world.resource_mut(world_events()).add_event((
-                        ambient_event_types::WINDOW_MOUSE_INPUT.to_string(),
-                        Entity::new()
-                            .with(
-                                event_mouse_input(),
-                                match state {
-                                    ElementState::Pressed => true,
-                                    ElementState::Released => false,
-                                },
-                            )
-                            .with(mouse_button(), ambient_window_types::MouseButton::from(*button).into()),
+                        ambient_event_types::WINDOW_TOUCH.to_string(),
+                        Entity::new().with(event_touch(), position).with(touch_id(), touch.id).with(touch_phase(), phase.to_string()),
                     ));
+                    self.emulate_mouse(world, touch, position);
                 }
 
+                WindowEvent::DroppedFile(path) => files::load_dropped_file(world, path.clone()),
+
                 WindowEvent::MouseWheel { delta, .. } => {
                     world.resource_mut(world_events()).add_event((
                         ambient_event_types::WINDOW_MOUSE_WHEEL.to_string(),
@@ -143,6 +230,14 @@ impl System<Event<'static, ()>> for InputSystem {
                 _ => {}
             },
 
+            Event::MainEventsCleared => {
+                if let Some(gamepads) = world.resource_opt(gamepad::gamepads()).cloned() {
+                    for event in gamepads.poll() {
+                        world.resource_mut(world_events()).add_event(event.to_world_event());
+                    }
+                }
+            }
+
             Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
                 world.resource_mut(world_events()).add_event((
                     ambient_event_types::WINDOW_MOUSE_MOTION.to_string(),
@@ -12,6 +12,9 @@ use glam::{Vec2, Vec3};
 components!("input", {
     @[MaybeResource, Debuggable]
     picker_intersecting: Option<PickerIntersection>,
+    /// The entity under the cursor according to the GPU picking pass of the renderer, if it's enabled
+    @[MaybeResource, Debuggable]
+    gpu_picker_intersecting: Option<PickerIntersection>,
 
     @[Debuggable, Networked, Store, Name["Mouse pickable min"], Description["This entity can be clicked by the mouse, and this component defines the min AABB bound of the click area."]]
     mouse_pickable_min: Vec3,
@@ -70,6 +73,10 @@ pub fn frame_systems() -> SystemGroup {
                             }
                         }
                     }
+                    // The UI is picked by its click areas, and the scene by the GPU picking pass of the renderer if it's enabled
+                    if intersecting.is_none() {
+                        intersecting = world.resource_opt(gpu_picker_intersecting()).copied().flatten();
+                    }
                     let prev_intersecting_entity = prev_intersecting.map(|x| x.entity);
                     let intersecting_entity = intersecting.map(|x| x.entity);
                     if prev_intersecting_entity != intersecting_entity {
@@ -1,7 +1,7 @@
 pub mod logic;
 mod registry;
 
-use std::{fmt::Debug, sync::Arc};
+use std::{collections::HashSet, fmt::Debug, sync::Arc};
 
 use ambient_ecs::{
     components, index_system, query, ArchetypeFilter, Component, ComponentValue, Debuggable, Entity, EntityId, Index, IndexColumns,
@@ -46,6 +46,9 @@ components!("intent", {
     intent_success: (),
     @[Debuggable, Networked, Store]
     intent_no_state: (),
+    /// While this resource is set, the entities changed by applying or reverting intents are added to it
+    @[Debuggable, Resource]
+    intent_touched: HashSet<EntityId>,
 
     @[Debuggable, Resource]
     intent_registry: Arc<IntentRegistry>,
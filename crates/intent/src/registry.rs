@@ -1,6 +1,10 @@
-use std::{collections::HashMap, fmt::Debug, marker::Send};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    marker::Send,
+};
 
-use ambient_ecs::{ArchetypeFilter, Component, ComponentValue, Entity, EntityId, IndexExt, SystemGroup, World};
+use ambient_ecs::{query, ArchetypeFilter, Component, ComponentValue, Entity, EntityId, IndexExt, SystemGroup, World};
 use ambient_network::{
     assert_networked,
     server::{ServerState, SharedServerState},
@@ -11,7 +15,7 @@ use tracing::info_span;
 
 use crate::{
     common_intent_systems, intent, intent_applied, intent_failed, intent_id, intent_id_index, intent_index, intent_index_applied,
-    intent_index_reverted, intent_reverted, intent_success, logic::get_head_applied_intent,
+    intent_index_reverted, intent_reverted, intent_success, intent_touched, logic::get_head_applied_intent,
 };
 
 pub struct IntentContext<'a> {
@@ -25,6 +29,33 @@ impl<'a> IntentContext<'a> {
     }
 }
 
+/// Finds the entities changed by an intent handler, when [intent_touched] is set
+struct TouchedEntities {
+    version: u64,
+    archetypes: HashMap<EntityId, usize>,
+}
+impl TouchedEntities {
+    fn before(world: &World) -> Option<Self> {
+        world.resource_opt(intent_touched())?;
+        let archetypes = query(()).iter(world, None).map(|(id, _)| (id, world.entity_loc(id).unwrap().archetype)).collect();
+        Some(Self { version: world.version(), archetypes })
+    }
+    /// Entities that were despawned, spawned, had components added or removed, or were written to since [TouchedEntities::before]
+    fn record(self, world: &mut World) {
+        let despawned = self.archetypes.keys().filter(|&&id| !world.exists(id)).copied();
+        let changed = query(()).iter(world, None).map(|(id, _)| id).filter(|&id| {
+            self.archetypes.get(&id) != Some(&world.entity_loc(id).unwrap().archetype)
+                || world
+                    .get_components(id)
+                    .unwrap()
+                    .into_iter()
+                    .any(|desc| world.get_component_content_version(id, desc.index() as _).unwrap() > self.version)
+        });
+        let touched: HashSet<EntityId> = despawned.chain(changed).collect();
+        world.resource_mut(intent_touched()).extend(touched);
+    }
+}
+
 pub trait IntentFn<'a, Arg, R>: 'static + Send + Sync {
     type Future: 'a + Future<Output = anyhow::Result<R>> + Send;
     fn call(&self, ctx: &'a mut IntentContext<'_>, arg: Arg) -> Self::Future;
@@ -101,7 +132,11 @@ where
         if let Some(head) = head {
             assert!(ctx.world.exists(head), "Head does not exist");
         }
+        let touched = TouchedEntities::before(ctx.world);
         let result = (self.apply)(IntentContext { world: ctx.world, user_id: ctx.user_id }, arg);
+        if let Some(touched) = touched {
+            touched.record(ctx.world);
+        }
         let world = &mut ctx.world;
         if world.has_component(id, intent_applied()) {
             panic!("Intent applied twice");
@@ -164,11 +199,15 @@ where
         let world = &mut ctx.world;
 
         // Undoing a failed intent is always a success
+        let touched = TouchedEntities::before(world);
         let result = if let Ok(revert_state) = world.get_cloned(id, self.intent_revert) {
             (self.revert)(IntentContext { world: ctx.world, user_id: ctx.user_id }, revert_state)
         } else {
             Ok(())
         };
+        if let Some(touched) = touched {
+            touched.record(ctx.world);
+        }
 
         let name = &self.name;
         let world = &mut ctx.world;
@@ -350,4 +389,34 @@ mod test {
             |_, old_state, new_arg, _| (*old_state, *new_arg),
         );
     }
+    #[test]
+    fn touched_entities() {
+        components!("intent", {
+            @[Store]
+            intent_test_value: u32,
+        });
+
+        init_components();
+        crate::init_components();
+        let mut world = World::new("touched_test");
+        let kept = Entity::new().with(intent_test_value(), 1).spawn(&mut world);
+        let written = Entity::new().with(intent_test_value(), 1).spawn(&mut world);
+        let removed = Entity::new().with(intent_test_value(), 1).spawn(&mut world);
+        let despawned = Entity::new().with(intent_test_value(), 1).spawn(&mut world);
+
+        // Nothing is recorded unless asked for
+        assert!(TouchedEntities::before(&world).is_none());
+        world.add_resource(intent_touched(), HashSet::new());
+
+        let touched = TouchedEntities::before(&world).unwrap();
+        world.set(written, intent_test_value(), 2).unwrap();
+        world.remove_component(removed, intent_test_value()).unwrap();
+        world.despawn(despawned).unwrap();
+        let spawned = Entity::new().with(intent_test_value(), 1).spawn(&mut world);
+        touched.record(&mut world);
+
+        let touched = world.resource(intent_touched());
+        assert_eq!(touched, &HashSet::from([written, removed, despawned, spawned]));
+        assert!(!touched.contains(&kept));
+    }
 }
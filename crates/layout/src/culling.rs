@@ -0,0 +1,100 @@
+//! Culls the UI elements which are entirely outside of the window, or of the scroll area they're in, so that large panels
+//! only pay for what's on screen. Culled elements aren't rendered, and aren't laid out until they come back into view
+use ambient_core::{
+    hierarchy::{children, parent},
+    main_scene,
+    transform::local_to_world,
+    ui_culled,
+    window::window_logical_size,
+};
+use ambient_ecs::{components, query, Debuggable, Description, DynSystem, EntityId, Name, Networked, Store, World};
+use glam::{vec3, UVec2, Vec2, Vec3};
+
+use crate::{guest_api::layout_width_to_children, height, layout, width};
+
+components!("layout", {
+    @[Debuggable, Networked, Store, Name["UI always layout"], Description["Lay out this UI element even when it's culled, e.g. because the size of its parent depends on it."]]
+    ui_always_layout: (),
+});
+
+/// A screen space rectangle, which is empty if `min` is greater than `max`
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    min: Vec2,
+    max: Vec2,
+}
+impl Rect {
+    /// The rect of a UI element, from its transform and size
+    fn of(world: &World, id: EntityId) -> Option<Self> {
+        let transform = world.get(id, local_to_world()).ok()?;
+        let size = vec3(world.get(id, width()).ok()?, world.get(id, height()).ok()?, 0.);
+        let a = transform.transform_point3(Vec3::ZERO).truncate();
+        let b = transform.transform_point3(size).truncate();
+        Some(Self { min: a.min(b), max: a.max(b) })
+    }
+    fn union(self, other: Self) -> Self {
+        Self { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+    fn intersection(self, other: Self) -> Self {
+        Self { min: self.min.max(other.min), max: self.max.min(other.max) }
+    }
+    fn intersects(&self, other: &Self) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+}
+
+/// Culls `id` and its descendants against `viewport`, and returns the bounds of the subtree
+fn cull_subtree(world: &mut World, id: EntityId, viewport: Rect) -> Option<Rect> {
+    let rect = Rect::of(world, id);
+    // Scroll areas only show their children within their own rect, once they've been given a height
+    let children_viewport = match rect {
+        Some(rect) if world.has_component(id, layout_width_to_children()) && rect.max.y > rect.min.y => viewport.intersection(rect),
+        _ => viewport,
+    };
+    let mut bounds = rect;
+    for child in world.get_ref(id, children()).cloned().unwrap_or_default() {
+        if let Some(child_bounds) = cull_subtree(world, child, children_viewport) {
+            bounds = Some(bounds.map_or(child_bounds, |bounds| bounds.union(child_bounds)));
+        }
+    }
+
+    if rect.is_some() {
+        // Children may be outside of their parent, so the whole subtree has to be out of view
+        let culled = !bounds.map_or(false, |bounds| bounds.intersects(&viewport));
+        if world.get(id, ui_culled()).unwrap_or(false) != culled {
+            world.add_component(id, ui_culled(), culled).ok();
+            // Layout was skipped while it was culled
+            if !culled {
+                world.get_mut(id, layout()).ok();
+            }
+        }
+    }
+    bounds
+}
+
+fn cull_ui(world: &mut World, window_size: UVec2) {
+    let viewport = Rect { min: Vec2::ZERO, max: window_size.as_vec2() };
+    let roots = query((width(), height(), local_to_world()))
+        .excl(main_scene())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .filter(|&id| world.get(id, parent()).map_or(true, |parent| !world.has_component(parent, width())))
+        .collect::<Vec<_>>();
+    for root in roots {
+        cull_subtree(world, root, viewport);
+    }
+}
+
+/// Culls the UI again whenever an element moves or is resized, or the window is resized
+pub(crate) fn culling_system() -> DynSystem {
+    let mut culled_window_size = None;
+    query((local_to_world().changed(), width().changed(), height().changed())).to_system(move |q, world, qs, _| {
+        let Some(&window_size) = world.resource_opt(window_logical_size()) else { return };
+        let changed = q.iter(world, qs).count() > 0;
+        if !changed && culled_window_size == Some(window_size) {
+            return;
+        }
+        culled_window_size = Some(window_size);
+        cull_ui(world, window_size);
+    })
+}
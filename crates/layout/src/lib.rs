@@ -3,6 +3,7 @@ use ambient_core::{
     gpu_ecs::{ComponentToGpuSystem, GpuComponentFormat, GpuWorldSyncEvent},
     hierarchy::{children, parent},
     transform::{local_to_parent, local_to_world, mesh_to_local, translation},
+    ui_culled,
 };
 use ambient_ecs::{components, query, query_mut, Debuggable, Description, DynSystem, EntityId, Name, Networked, Store, SystemGroup, World};
 use ambient_input::picking::mouse_pickable;
@@ -10,8 +11,11 @@ use glam::{vec2, vec3, vec4, Mat4, Vec2, Vec4};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+mod culling;
 pub mod guest_api;
 
+pub use culling::ui_always_layout;
+
 components!("layout", {
     @[Debuggable, Networked, Store, Name["Layout"], Description["The layout to apply to this entity's children."]]
     layout: Layout,
@@ -48,6 +52,7 @@ gpu_components! {
 
 pub fn init_all_components() {
     init_components();
+    culling::init_components();
     guest_api::init_components();
 }
 
@@ -166,6 +171,7 @@ pub fn layout_systems() -> SystemGroup {
         "layout",
         vec![
             Box::new(guest_api::systems()),
+            culling::culling_system(),
             // For all "normal" components, i.e. non-layout components
             query((width().changed(),)).excl(layout()).to_system(|q, world, qs, _| {
                 for (id, _) in q.collect_cloned(world, qs) {
@@ -183,6 +189,9 @@ pub fn layout_systems() -> SystemGroup {
                     for _ in 0..100 {
                         let mut changed = false;
                         for (id, (_, _, children, layout)) in q.collect_cloned(world, Some(qs)) {
+                            if world.get(id, ui_culled()).unwrap_or(false) && !world.has_component(id, ui_always_layout()) {
+                                continue;
+                            }
                             // dump_world_hierarchy_to_tmp_file(world);
                             changed = true;
                             match layout {
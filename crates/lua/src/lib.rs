@@ -0,0 +1,342 @@
+//! Runs the Lua scripts of a project on the server, as a lighter alternative to WASM modules for quick iteration. Scripts
+//! aren't built: they're loaded from the project, and reloaded when they're saved.
+//!
+//! Scripts have a global `ambient` table with the same subset of the host API as modules:
+//!
+//! - `ambient.spawn(components)` spawns an entity and returns its id; `components` is a table of values by component path
+//! - `ambient.despawn(id)` and `ambient.exists(id)`
+//! - `ambient.get(id, path)`, `ambient.set(id, path, value)`, `ambient.has(id, path)` and `ambient.remove(id, path)` for
+//!   components, whose values are in their JSON form, e.g. `{1, 2, 3}` for a `Vec3`
+//! - `ambient.on(name, handler)` calls `handler(data)` for each event of that name, like `core/frame`
+//! - `ambient.send(name, data)` sends an event to the modules and scripts
+//! - `ambient.time()` is the time since the server started, in seconds
+//!
+//! The entities a script spawns are despawned when it's reloaded.
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use ambient_core::{app_start_time, name, time};
+use ambient_ecs::{
+    components, query, world_events, ComponentDesc, ComponentRegistry, Debuggable, Description, Entity, EntityId, FnSystem, Serializable,
+    SystemGroup, World, WorldEventReader,
+};
+use ambient_event_types::FRAME;
+use ambient_network::server::ForkingEvent;
+use itertools::Itertools;
+use mlua::{Function, Lua, LuaSerdeExt, Table, Value};
+use parking_lot::Mutex;
+
+components!("lua", {
+    @[Debuggable, Description["The path of a Lua script that the server runs. It's reloaded when the file changes."]]
+    lua_script: PathBuf,
+    lua_script_state: LuaScriptState,
+});
+
+/// How often the scripts are checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+/// The name of the table of event handlers in the registry of each script
+const HANDLERS: &str = "ambient_handlers";
+
+#[derive(Clone)]
+pub struct LuaScriptState {
+    lua: Arc<Mutex<Lua>>,
+    /// When the script file was last changed, when it was loaded
+    modified: Option<SystemTime>,
+    spawned: Arc<Mutex<Vec<EntityId>>>,
+}
+impl std::fmt::Debug for LuaScriptState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LuaScriptState").field("modified", &self.modified).finish_non_exhaustive()
+    }
+}
+
+/// Spawns the Lua scripts of the project; they're loaded by [systems]
+pub fn initialize(world: &mut World, project_path: &Path, manifest: &ambient_project::Manifest) {
+    for path in &manifest.lua.server {
+        let script_name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        Entity::new()
+            .with(name(), format!("{}_{script_name}", manifest.project.id))
+            .with(lua_script(), project_path.join(path))
+            .spawn(world);
+    }
+}
+
+pub fn systems() -> SystemGroup {
+    let mut last_reload_check: Option<Instant> = None;
+    let mut events_reader = WorldEventReader::new();
+    SystemGroup::new(
+        "lua",
+        vec![
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("Lua script reloads");
+                if last_reload_check.map_or(false, |check| check.elapsed() < RELOAD_INTERVAL) {
+                    return;
+                }
+                last_reload_check = Some(Instant::now());
+
+                for (id, path) in query(lua_script()).collect_cloned(world, None) {
+                    let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+                    let loaded = world.get_ref(id, lua_script_state()).ok().map(|state| state.modified);
+                    if loaded != Some(modified) {
+                        load(world, id, &path, modified);
+                    }
+                }
+            })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("Lua script events");
+                let events = events_reader.iter(world.resource(world_events())).map(|(_, event)| event.clone()).collect_vec();
+                for (event_name, data) in events {
+                    run_all(world, &event_name, &data);
+                }
+                run_all(world, FRAME, &Entity::new());
+            })),
+        ],
+    )
+}
+
+pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
+    SystemGroup::new(
+        "lua/on_forking_systems",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            // The forked world starts with fresh scripts, like the modules
+            reload_all(world);
+        }))],
+    )
+}
+
+/// Reloads all the scripts, e.g. when the world is forked
+pub fn reload_all(world: &mut World) {
+    for (id, path) in query(lua_script()).collect_cloned(world, None) {
+        let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        load(world, id, &path, modified);
+    }
+}
+
+fn script_name(world: &World, id: EntityId) -> String {
+    world.get_cloned(id, name()).unwrap_or_else(|_| id.to_string())
+}
+
+/// Logs like the messenger of the WASM modules, so that both can be read the same way
+fn log_message(script_name: &str, level: log::Level, prefix: &str, message: &str) {
+    log::log!(level, "[{script_name}] {prefix}: {message}");
+}
+
+fn load(world: &mut World, id: EntityId, path: &Path, modified: Option<SystemTime>) {
+    let script_name = script_name(world, id);
+
+    // The entities of the previous version of the script are replaced by those of the new one. The list is shared with the
+    // worlds forked from this one, so it's copied rather than drained
+    if let Ok(state) = world.get_cloned(id, lua_script_state()) {
+        let spawned = state.spawned.lock().clone();
+        for spawned in spawned {
+            world.despawn(spawned);
+        }
+        world.remove_component(id, lua_script_state()).ok();
+    }
+
+    let lua = Lua::new();
+    let spawned = Arc::new(Mutex::new(Vec::new()));
+    let result = std::fs::read_to_string(path).map_err(mlua::Error::external).and_then(|source| {
+        register_globals(&lua, &script_name)?;
+        let chunk = lua.load(&source).set_name(&path.to_string_lossy())?;
+        with_api(&lua, world, &mut spawned.lock(), || chunk.exec())
+    });
+    match result {
+        Ok(()) => log_message(&script_name, log::Level::Info, "info", "Loaded"),
+        Err(err) => log_message(&script_name, log::Level::Error, "error", &format!("Failed to load {path:?}: {err}")),
+    }
+
+    // A script that failed to load is kept, so that it's only tried again once it changes
+    world.add_component(id, lua_script_state(), LuaScriptState { lua: Arc::new(Mutex::new(lua)), modified, spawned }).ok();
+}
+
+/// The parts of the API that don't use the world
+fn register_globals(lua: &Lua, script_name: &str) -> mlua::Result<()> {
+    lua.set_named_registry_value(HANDLERS, lua.create_table()?)?;
+
+    let api = lua.create_table()?;
+    api.set(
+        "on",
+        lua.create_function(|lua, (event_name, handler): (String, Function)| {
+            let handlers: Table = lua.named_registry_value(HANDLERS)?;
+            let event_handlers = match handlers.get::<_, Option<Table>>(event_name.as_str())? {
+                Some(event_handlers) => event_handlers,
+                None => {
+                    let event_handlers = lua.create_table()?;
+                    handlers.set(event_name.as_str(), event_handlers.clone())?;
+                    event_handlers
+                }
+            };
+            event_handlers.set(event_handlers.raw_len() + 1, handler)
+        })?,
+    )?;
+    lua.globals().set("ambient", api)?;
+
+    let script_name = script_name.to_string();
+    lua.globals().set(
+        "print",
+        lua.create_function(move |lua, values: mlua::Variadic<Value>| {
+            let tostring: Function = lua.globals().get("tostring")?;
+            let message = values.into_iter().map(|value| tostring.call::<_, String>(value)).collect::<mlua::Result<Vec<_>>>()?.join("\t");
+            log_message(&script_name, log::Level::Info, "stdout", &message);
+            Ok(())
+        })?,
+    )
+}
+
+/// Runs `run` with the parts of the API that use the world, which are only valid while it runs
+fn with_api<R: 'static>(
+    lua: &Lua,
+    world: &mut World,
+    spawned: &mut Vec<EntityId>,
+    run: impl FnOnce() -> mlua::Result<R>,
+) -> mlua::Result<R> {
+    let world = RefCell::new(world);
+    let spawned = RefCell::new(spawned);
+    lua.scope(|scope| {
+        let api: Table = lua.globals().get("ambient")?;
+        api.set(
+            "spawn",
+            scope.create_function(|lua, components: Value| {
+                let entity: Entity = serde_json::from_value(lua.from_value(components)?).map_err(mlua::Error::external)?;
+                let id = entity.spawn(&mut world.borrow_mut());
+                spawned.borrow_mut().push(id);
+                Ok(id.to_string())
+            })?,
+        )?;
+        api.set(
+            "despawn",
+            scope.create_function(|_, id: String| {
+                let id = parse_id(&id)?;
+                spawned.borrow_mut().retain(|spawned| *spawned != id);
+                Ok(world.borrow_mut().despawn(id).is_some())
+            })?,
+        )?;
+        api.set("exists", scope.create_function(|_, id: String| Ok(world.borrow().exists(parse_id(&id)?)))?)?;
+        api.set(
+            "get",
+            scope.create_function(|lua, (id, path): (String, String)| {
+                let (id, desc) = (parse_id(&id)?, component_desc(&path)?);
+                let Ok(entry) = world.borrow().get_entry(id, desc) else { return Ok(Value::Nil) };
+                let json: serde_json::Value =
+                    serde_json::from_str(&desc.to_json(&entry).map_err(mlua::Error::external)?).map_err(mlua::Error::external)?;
+                lua.to_value(&json)
+            })?,
+        )?;
+        api.set(
+            "set",
+            scope.create_function(|lua, (id, path, value): (String, String, Value)| {
+                let (id, desc) = (parse_id(&id)?, component_desc(&path)?);
+                let json: serde_json::Value = lua.from_value(value)?;
+                let entry = desc.from_json(&json.to_string()).map_err(mlua::Error::external)?;
+                world.borrow_mut().add_entry(id, entry).map_err(|err| mlua::Error::external(format!("{err:?}")))
+            })?,
+        )?;
+        api.set(
+            "has",
+            scope.create_function(|_, (id, path): (String, String)| {
+                Ok(world.borrow().has_component(parse_id(&id)?, component_desc(&path)?))
+            })?,
+        )?;
+        api.set(
+            "remove",
+            scope.create_function(|_, (id, path): (String, String)| {
+                world
+                    .borrow_mut()
+                    .remove_component(parse_id(&id)?, component_desc(&path)?)
+                    .map_err(|err| mlua::Error::external(format!("{err:?}")))
+            })?,
+        )?;
+        api.set(
+            "send",
+            scope.create_function(|lua, (event_name, data): (String, Option<Value>)| {
+                let data: Entity = match data {
+                    Some(data) => serde_json::from_value(lua.from_value(data)?).map_err(mlua::Error::external)?,
+                    None => Entity::new(),
+                };
+                world.borrow_mut().resource_mut(world_events()).add_event((event_name, data));
+                Ok(())
+            })?,
+        )?;
+        api.set(
+            "time",
+            scope.create_function(|_, ()| {
+                let world = world.borrow();
+                Ok((*world.resource(time()) - *world.resource(app_start_time())).as_secs_f64())
+            })?,
+        )?;
+
+        run()
+    })
+}
+
+/// Calls the handlers of the event in every script
+fn run_all(world: &mut World, event_name: &str, data: &Entity) {
+    for (id, state) in query(lua_script_state()).collect_cloned(world, None) {
+        let lua = state.lua.lock();
+        let handlers =
+            lua.named_registry_value::<_, Table>(HANDLERS).and_then(|handlers| handlers.get::<_, Option<Table>>(event_name)).ok().flatten();
+        let Some(handlers) = handlers else { continue };
+
+        let result = serde_json::to_value(data).map_err(mlua::Error::external).and_then(|data| {
+            let data = lua.to_value(&data)?;
+            with_api(&lua, world, &mut state.spawned.lock(), || {
+                for handler in handlers.sequence_values::<Function>() {
+                    handler?.call::<_, ()>(data.clone())?;
+                }
+                Ok(())
+            })
+        });
+        if let Err(err) = result {
+            log_message(&script_name(world, id), log::Level::Error, "error", &format!("Runtime error in {event_name}: {err}"));
+        }
+    }
+}
+
+fn parse_id(id: &str) -> mlua::Result<EntityId> {
+    id.parse().map_err(|_| mlua::Error::external(format!("Invalid entity id {id:?}")))
+}
+
+fn component_desc(path: &str) -> mlua::Result<ComponentDesc> {
+    let desc = ComponentRegistry::get().get_by_path(path).ok_or_else(|| mlua::Error::external(format!("Unknown component {path:?}")))?;
+    if !desc.has_attribute::<Serializable>() {
+        return Err(mlua::Error::external(format!("The component {path:?} can't be read or written from Lua")));
+    }
+    Ok(desc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloading_a_fork_keeps_the_spawned_entities_of_the_original() {
+        ambient_ecs::init_components();
+        ambient_core::init_all_components();
+        init_components();
+        let dir = std::env::temp_dir().join(format!("ambient_lua_fork_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("spawner.lua");
+        std::fs::write(&path, r#"ambient.spawn({ ["core::app::name"] = "spawned" })"#).unwrap();
+
+        let mut world = World::new("test");
+        Entity::new().with(lua_script(), path).spawn(&mut world);
+        reload_all(&mut world);
+        let spawned = |world: &World| query(name()).iter(world, None).filter(|(_, name)| name.as_str() == "spawned").count();
+        assert_eq!(spawned(&world), 1);
+
+        // The fork starts its scripts over, like the forking systems do
+        let mut fork = world.clone();
+        reload_all(&mut fork);
+        assert_eq!(spawned(&fork), 1);
+
+        // The original still replaces the entities it spawned when it's reloaded
+        reload_all(&mut world);
+        assert_eq!(spawned(&world), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
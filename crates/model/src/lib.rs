@@ -127,8 +127,9 @@ async fn internal_spawn_models_from_defs(
                 Err(e) => return (ids, Err(e)),
             };
         };
-        match ModelFromUrl(url).get(assets).await.context("Failed to load model") {
-            Ok(v) => (ids, Ok(v)),
+        let key = ModelFromUrl(url);
+        match key.get(assets).await.context("Failed to load model") {
+            Ok(v) => (ids, Ok((key, v))),
             Err(e) => (ids, Err(e)),
         }
     });
@@ -136,6 +137,7 @@ async fn internal_spawn_models_from_defs(
     let mut iter = futures::stream::iter(iter).buffer_unordered(4);
     while let Some((mut ids, model)) = iter.next().await {
         let cube_fail = cube_fail.clone();
+        let assets = assets.clone();
         async_run.run(move |world| {
             // Remove the models which still exist
             ids.retain(|id| world.exists(*id));
@@ -146,8 +148,11 @@ async fn internal_spawn_models_from_defs(
             let len = ids.len();
 
             match model {
-                Ok(model) => {
+                Ok((key, model)) => {
                     tracing::info!("Spawning model: {:?} for {ids:?}", model.name());
+                    for id in &ids {
+                        assets.reference(id.to_string(), &key, &Ok::<_, AssetError>(model.clone()));
+                    }
                     model.batch_spawn(
                         world,
                         &ModelSpawnOpts {
@@ -232,6 +237,7 @@ fn remove_model(world: &mut World, entity: EntityId) {
     ];
     components.retain(|&comp| world.has_component_ref(entity, comp));
     world.remove_components(entity, components).ok();
+    world.resource(asset_cache()).graph.lock().release(&entity.to_string());
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
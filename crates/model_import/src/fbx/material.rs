@@ -150,6 +150,7 @@ impl FbxMaterial {
                     (asset_crate.images.insert(format!("{}-mr", x.0.id), img), x.1)
                 })
                 .map(img_to_asset),
+            emissive: None,
             double_sided: None,
             transparent: Some(false),
             alpha_cutoff: Some(0.5),
@@ -193,6 +193,7 @@ pub async fn import(import: &GltfImport, asset_crate: &mut ModelCrate) -> anyhow
                 .metallic_roughness_texture()
                 .and_then(|x| images.get(x.texture().index()))
                 .map(|x| dotdot_path(x).into()),
+            emissive: mat.emissive_texture().and_then(|x| images.get(x.texture().index())).map(|x| dotdot_path(x).into()),
             double_sided: Some(mat.double_sided()),
             opacity: None,
         };
@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 pub mod assimp;
 pub mod fbx;
 pub mod gltf;
+pub mod mesh_optimization;
 pub mod model_crate;
 
 pub type TextureResolver = Arc<dyn Fn(String) -> futures::future::BoxFuture<'static, Option<RgbaImage>> + Sync + Send>;
@@ -0,0 +1,249 @@
+use ambient_ecs::query;
+use ambient_model::{pbr_renderer_primitives_from_url, PbrRenderPrimitiveFromUrl};
+use ambient_renderer::lod::{gpu_lod, lod_cutoffs, LodCutoffs};
+use ambient_std::mesh::Mesh;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::{dotdot_path, model_crate::ModelCrate};
+
+/// Optimizes meshes for rendering with meshoptimizer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshOptimization {
+    /// Reorder triangles so that the GPU's vertex cache is used better. On by default.
+    pub vertex_cache: bool,
+    /// Reorder triangles to reduce overdraw, allowing the vertex cache efficiency to get worse by at most this factor
+    /// (i.e. 1.05 allows it to be 5% worse). Defaults to 1.05; set it to `null` to turn it off.
+    pub overdraw_threshold: Option<f32>,
+    /// If specified, generate simplified versions of the meshes, which are switched to as they get smaller on screen.
+    pub lods: Option<LodChain>,
+}
+impl Default for MeshOptimization {
+    fn default() -> Self {
+        Self { vertex_cache: true, overdraw_threshold: Some(1.05), lods: None }
+    }
+}
+
+/// The levels of detail to generate for each mesh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LodChain {
+    /// The smallest size on screen (the radius of the mesh's bounding sphere, relative to the screen) at which the original mesh is
+    /// used; below it, the first level is used.
+    pub min_screen_size: f32,
+    /// The levels, from the most detailed to the least.
+    pub levels: Vec<LodLevel>,
+}
+impl Default for LodChain {
+    fn default() -> Self {
+        Self {
+            min_screen_size: 0.25,
+            levels: vec![
+                LodLevel { ratio: 0.5, max_error: 0.01, min_screen_size: 0.1 },
+                LodLevel { ratio: 0.25, max_error: 0.02, min_screen_size: 0.04 },
+                LodLevel { ratio: 0.1, max_error: 0.05, min_screen_size: 0. },
+            ],
+        }
+    }
+}
+
+/// A level of detail.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LodLevel {
+    /// The fraction of the original mesh's triangles to aim for.
+    pub ratio: f32,
+    /// The largest error the simplification is allowed to make, relative to the size of the mesh. Simplification stops before
+    /// reaching `ratio` if it would exceed this.
+    pub max_error: f32,
+    /// The smallest size on screen at which this level is used; below it, the next level is, or the mesh is hidden if this is the last one.
+    #[serde(default)]
+    pub min_screen_size: f32,
+}
+
+impl ModelCrate {
+    /// Optimizes all the meshes, and generates levels of detail for the nodes that don't already have them
+    pub fn optimize_meshes(&mut self, config: &MeshOptimization) {
+        for mesh in self.meshes.content.values_mut() {
+            optimize_mesh(mesh, config);
+        }
+        if let Some(lods) = &config.lods {
+            self.generate_lods(lods, config);
+        }
+    }
+
+    fn generate_lods(&mut self, lods: &LodChain, config: &MeshOptimization) {
+        let cutoffs =
+            std::iter::once(lods.min_screen_size).chain(lods.levels.iter().map(|level| level.min_screen_size)).collect::<Vec<_>>();
+        let nodes = query(pbr_renderer_primitives_from_url()).excl(lod_cutoffs()).collect_cloned(self.model_world(), None);
+        for (id, primitives) in nodes {
+            let mut lod_primitives = primitives.clone();
+            for (lod, level) in lods.levels.iter().enumerate() {
+                let lod = lod + 1;
+                for primitive in primitives.iter().filter(|primitive| primitive.lod == 0) {
+                    let Some(mesh_id) = self.meshes.loc.id_from_path(primitive.mesh.path()) else { continue };
+                    let Some(mesh) = self.meshes.content.get(&mesh_id) else { continue };
+                    let mut simplified = simplify_mesh(mesh, level);
+                    optimize_mesh(&mut simplified, &MeshOptimization { lods: None, ..config.clone() });
+                    let path = self.meshes.insert(format!("{mesh_id}_lod{lod}"), simplified).path;
+                    lod_primitives.push(PbrRenderPrimitiveFromUrl {
+                        mesh: dotdot_path(path).into(),
+                        material: primitive.material.clone(),
+                        lod,
+                    });
+                }
+            }
+            let world = self.model_world_mut();
+            world.set(id, pbr_renderer_primitives_from_url(), lod_primitives).unwrap();
+            world.add_component(id, lod_cutoffs(), LodCutoffs::new(&cutoffs)).unwrap();
+            world.add_component(id, gpu_lod(), ()).unwrap();
+        }
+    }
+}
+
+fn vertex_count(mesh: &Mesh) -> usize {
+    mesh.positions.as_ref().map(|x| x.len()).unwrap_or_default()
+}
+
+/// Reorders the triangles and vertices of `mesh` for rendering
+pub fn optimize_mesh(mesh: &mut Mesh, config: &MeshOptimization) {
+    if mesh.positions.is_none() || mesh.indices.as_ref().map_or(true, |indices| indices.is_empty()) {
+        return;
+    }
+
+    let mut indices = mesh.indices.clone().unwrap();
+    let count = vertex_count(mesh);
+    if config.vertex_cache {
+        indices = meshopt::optimize_vertex_cache(&indices, count);
+    }
+    if let Some(threshold) = config.overdraw_threshold {
+        let positions = mesh.positions.as_ref().unwrap();
+        if let Ok(adapter) = meshopt::VertexDataAdapter::new(meshopt::typed_to_bytes(positions), std::mem::size_of::<Vec3>(), 0) {
+            meshopt::optimize_overdraw_in_place(&mut indices, &adapter, threshold);
+        }
+    }
+    mesh.indices = Some(indices);
+
+    // Order the vertices by first use, which also drops the unused ones
+    let remap = meshopt::optimize_vertex_fetch_remap(mesh.indices.as_ref().unwrap(), vertex_count(mesh));
+    remap_mesh(mesh, &remap);
+}
+
+/// Returns a copy of `mesh` with fewer triangles; the vertices are shared with the original until the mesh is optimized
+pub fn simplify_mesh(mesh: &Mesh, level: &LodLevel) -> Mesh {
+    let mut simplified = mesh.clone();
+    let (Some(positions), Some(indices)) = (&mesh.positions, &mesh.indices) else { return simplified };
+    let Ok(adapter) = meshopt::VertexDataAdapter::new(meshopt::typed_to_bytes(positions), std::mem::size_of::<Vec3>(), 0) else {
+        return simplified;
+    };
+    let target_count = ((indices.len() as f32 * level.ratio) as usize / 3 * 3).max(3);
+    simplified.indices = Some(meshopt::simplify(indices, &adapter, target_count, level.max_error));
+    simplified.name = format!("{}_lod", mesh.name);
+    simplified
+}
+
+/// Moves each vertex to the index `remap` gives for it, dropping those mapped to `u32::MAX`
+fn remap_mesh(mesh: &mut Mesh, remap: &[u32]) {
+    fn apply<T: Copy + Default>(values: &[T], remap: &[u32], count: usize) -> Vec<T> {
+        let mut out = vec![T::default(); count];
+        for (value, &to) in values.iter().zip(remap) {
+            if to != u32::MAX {
+                out[to as usize] = *value;
+            }
+        }
+        out
+    }
+    let count = remap.iter().filter(|&&to| to != u32::MAX).max().map(|&max| max as usize + 1).unwrap_or_default();
+    macro_rules! remap_attribute {
+        ($attribute:expr) => {
+            if let Some(values) = &mut $attribute {
+                *values = apply(values, remap, count);
+            }
+        };
+    }
+    remap_attribute!(mesh.positions);
+    remap_attribute!(mesh.colors);
+    remap_attribute!(mesh.normals);
+    remap_attribute!(mesh.tangents);
+    remap_attribute!(mesh.joint_indices);
+    remap_attribute!(mesh.joint_weights);
+    for texcoords in &mut mesh.texcoords {
+        *texcoords = apply(texcoords, remap, count);
+    }
+    if let Some(indices) = &mut mesh.indices {
+        indices.iter_mut().for_each(|index| *index = remap[*index as usize]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{vec2, vec3};
+
+    use super::*;
+
+    fn quad() -> Mesh {
+        Mesh {
+            name: "quad".to_string(),
+            positions: Some(vec![vec3(0., 0., 0.), vec3(1., 0., 0.), vec3(1., 1., 0.), vec3(0., 1., 0.), vec3(5., 5., 5.)]),
+            colors: None,
+            normals: Some(vec![Vec3::Z; 5]),
+            tangents: None,
+            texcoords: vec![vec![vec2(0., 0.), vec2(1., 0.), vec2(1., 1.), vec2(0., 1.), vec2(0.5, 0.5)]],
+            joint_indices: None,
+            joint_weights: None,
+            // The last vertex isn't used by any triangle
+            indices: Some(vec![2, 3, 0, 0, 1, 2]),
+        }
+    }
+
+    fn triangles(mesh: &Mesh) -> Vec<[Vec3; 3]> {
+        let positions = mesh.positions.as_ref().unwrap();
+        let mut triangles = mesh
+            .indices
+            .as_ref()
+            .unwrap()
+            .chunks(3)
+            .map(|triangle| {
+                // Rotate each triangle to start from its smallest corner, which keeps its winding
+                let mut corners = [positions[triangle[0] as usize], positions[triangle[1] as usize], positions[triangle[2] as usize]];
+                let first = (0..3).min_by(|&a, &b| corners[a].to_array().partial_cmp(&corners[b].to_array()).unwrap()).unwrap();
+                corners.rotate_left(first);
+                corners
+            })
+            .collect::<Vec<_>>();
+        triangles.sort_by(|a, b| {
+            a[0].to_array().partial_cmp(&b[0].to_array()).unwrap().then(a[1].to_array().partial_cmp(&b[1].to_array()).unwrap())
+        });
+        triangles
+    }
+
+    #[test]
+    fn remap_moves_and_drops_vertices() {
+        let mut mesh = quad();
+        remap_mesh(&mut mesh, &[1, 0, 3, 2, u32::MAX]);
+        assert_eq!(mesh.positions.as_ref().unwrap(), &vec![vec3(1., 0., 0.), vec3(0., 0., 0.), vec3(0., 1., 0.), vec3(1., 1., 0.)]);
+        assert_eq!(mesh.texcoords[0], vec![vec2(1., 0.), vec2(0., 0.), vec2(0., 1.), vec2(1., 1.)]);
+        assert_eq!(mesh.normals.as_ref().unwrap().len(), 4);
+        assert_eq!(mesh.indices.as_ref().unwrap(), &vec![3, 2, 1, 1, 0, 3]);
+    }
+
+    #[test]
+    fn optimizing_keeps_the_triangles() {
+        let original = quad();
+        let mut mesh = original.clone();
+        optimize_mesh(&mut mesh, &MeshOptimization::default());
+        assert_eq!(vertex_count(&mesh), 4, "The unused vertex is dropped");
+        assert_eq!(mesh.normals.as_ref().unwrap().len(), 4);
+        assert_eq!(mesh.texcoords[0].len(), 4);
+        assert_eq!(triangles(&mesh), triangles(&original));
+        // The vertices are ordered by first use
+        assert_eq!(mesh.indices.as_ref().unwrap()[..3], [0, 1, 2]);
+    }
+
+    #[test]
+    fn meshes_without_triangles_are_left_alone() {
+        let mut mesh = Mesh { indices: Some(Vec::new()), ..quad() };
+        optimize_mesh(&mut mesh, &MeshOptimization::default());
+        assert_eq!(vertex_count(&mesh), 5);
+    }
+}
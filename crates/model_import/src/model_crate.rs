@@ -3,7 +3,7 @@ use std::{collections::HashMap, io::Cursor, path::PathBuf, sync::Arc};
 use ambient_animation::{animation_bind_id_from_name, AnimationClip};
 use ambient_core::{
     bounding::local_bounding_aabb,
-    hierarchy::children,
+    hierarchy::{children, parent},
     name,
     transform::{local_to_parent, local_to_world, mesh_to_local, TransformSystem},
 };
@@ -13,6 +13,7 @@ use ambient_model::{
 };
 use ambient_physics::{
     collider::{character_controller_height, character_controller_radius, collider, ColliderDef, ColliderFromUrls},
+    destruction::{destructible_chunks, DestructibleChunk},
     mesh::PhysxGeometryFromUrl,
     physx::PhysicsKey,
 };
@@ -23,7 +24,7 @@ use ambient_renderer::{
 };
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
-    asset_url::AbsAssetUrl,
+    asset_url::{AbsAssetUrl, AssetUrl},
     download_asset::AssetsCacheDir,
     mesh::Mesh,
     shapes::AABB,
@@ -45,6 +46,7 @@ pub struct AssetLoc {
     pub path: RelativePathBuf,
 }
 
+#[derive(Clone)]
 pub struct AssetMapLoc {
     store: String,
     extension: String,
@@ -70,11 +72,15 @@ impl AssetMapLoc {
 pub struct AssetMap<T> {
     pub loc: AssetMapLoc,
     pub content: HashMap<String, T>,
-    pub serialize: fn(&T) -> Vec<u8>,
+    pub serialize: Arc<dyn Fn(&T) -> anyhow::Result<Vec<u8>> + Sync + Send>,
 }
 impl<T: Send + 'static> AssetMap<T> {
-    fn new(store: &str, extension: &str, serialize: fn(&T) -> Vec<u8>) -> Self {
-        Self { loc: AssetMapLoc { store: store.to_string(), extension: extension.into() }, content: Default::default(), serialize }
+    fn new(store: &str, extension: &str, serialize: fn(&T) -> anyhow::Result<Vec<u8>>) -> Self {
+        Self {
+            loc: AssetMapLoc { store: store.to_string(), extension: extension.into() },
+            content: Default::default(),
+            serialize: Arc::new(serialize),
+        }
     }
 
     pub fn get_by_path(&self, path: impl Into<RelativePathBuf>) -> Option<&T> {
@@ -85,8 +91,15 @@ impl<T: Send + 'static> AssetMap<T> {
         self.content.insert(id.clone(), content);
         AssetLoc { path: self.loc.path(&id), id }
     }
-    pub fn to_items(&self) -> Vec<AssetItem> {
-        self.content.iter().map(|(id, content)| AssetItem { path: self.loc.path(id), data: Arc::new((self.serialize)(content)) }).collect()
+    pub fn to_items(&self) -> anyhow::Result<Vec<AssetItem>> {
+        self.content
+            .iter()
+            .map(|(id, content)| {
+                let path = self.loc.path(id);
+                let data = (self.serialize)(content).with_context(|| format!("Failed to serialize {path}"))?;
+                Ok(AssetItem { path, data: Arc::new(data) })
+            })
+            .collect()
     }
 }
 
@@ -104,19 +117,19 @@ pub struct ModelCrate {
 impl ModelCrate {
     pub fn new() -> Self {
         Self {
-            models: AssetMap::new("models", "json", |v| serde_json::to_vec(v).unwrap()),
-            prefabs: AssetMap::new("prefabs", "json", |v| serde_json::to_vec(v).unwrap()),
-            meshes: AssetMap::new("meshes", "mesh", |v| bincode::serialize(v).unwrap()),
-            animations: AssetMap::new("animations", "anim", |v| bincode::serialize(v).unwrap()),
+            models: AssetMap::new("models", "json", |v| Ok(serde_json::to_vec(v)?)),
+            prefabs: AssetMap::new("prefabs", "json", |v| Ok(serde_json::to_vec(v)?)),
+            meshes: AssetMap::new("meshes", "mesh", |v| Ok(bincode::serialize(v)?)),
+            animations: AssetMap::new("animations", "anim", |v| Ok(bincode::serialize(v)?)),
             images: AssetMap::new("images", "png", |v| {
                 let mut data = Cursor::new(Vec::new());
-                v.write_to(&mut data, ImageOutputFormat::Png).unwrap();
-                data.into_inner()
+                v.write_to(&mut data, ImageOutputFormat::Png)?;
+                Ok(data.into_inner())
             }),
-            materials: AssetMap::new("materials", "json", |v| serde_json::to_vec(v).unwrap()),
-            px_triangle_meshes: AssetMap::new("px_triangle_meshes", "pxtm", |v| v.clone()),
-            px_convex_meshes: AssetMap::new("px_convex_meshes", "pxcm", |v| v.clone()),
-            colliders: AssetMap::new("colliders", "json", |v| serde_json::to_vec(v).unwrap()),
+            materials: AssetMap::new("materials", "json", |v| Ok(serde_json::to_vec(v)?)),
+            px_triangle_meshes: AssetMap::new("px_triangle_meshes", "pxtm", |v| Ok(v.clone())),
+            px_convex_meshes: AssetMap::new("px_convex_meshes", "pxcm", |v| Ok(v.clone())),
+            colliders: AssetMap::new("colliders", "json", |v| Ok(serde_json::to_vec(v)?)),
         }
     }
     pub async fn local_import(assets: &AssetCache, url: &AbsAssetUrl, normalize: bool, force_assimp: bool) -> anyhow::Result<Model> {
@@ -143,30 +156,30 @@ impl ModelCrate {
         model.model_mut().update_model_aabb();
         model.produce_local_model(assets, cache_path).await
     }
-    pub async fn write_to_fs(&self, path: &PathBuf) {
-        for item in self.to_items() {
+    pub async fn write_to_fs(&self, path: &PathBuf) -> anyhow::Result<()> {
+        for item in self.to_items()? {
             let item_path = item.path.to_path(path);
             std::fs::create_dir_all(item_path.parent().unwrap())
-                .context(format!("Failed to create dir: {:?}", item_path.parent().unwrap()))
-                .unwrap();
-            tokio::fs::write(&item_path, &*item.data).await.context(format!("Failed to write file: {item_path:?}")).unwrap();
+                .context(format!("Failed to create dir: {:?}", item_path.parent().unwrap()))?;
+            tokio::fs::write(&item_path, &*item.data).await.context(format!("Failed to write file: {item_path:?}"))?;
         }
+        Ok(())
     }
-    pub fn to_items(&self) -> Vec<AssetItem> {
-        [
-            self.models.to_items().into_iter(),
-            self.prefabs.to_items().into_iter(),
-            self.meshes.to_items().into_iter(),
-            self.animations.to_items().into_iter(),
-            self.images.to_items().into_iter(),
-            self.materials.to_items().into_iter(),
-            self.px_triangle_meshes.to_items().into_iter(),
-            self.px_convex_meshes.to_items().into_iter(),
-            self.colliders.to_items().into_iter(),
+    pub fn to_items(&self) -> anyhow::Result<Vec<AssetItem>> {
+        Ok([
+            self.models.to_items()?,
+            self.prefabs.to_items()?,
+            self.meshes.to_items()?,
+            self.animations.to_items()?,
+            self.images.to_items()?,
+            self.materials.to_items()?,
+            self.px_triangle_meshes.to_items()?,
+            self.px_convex_meshes.to_items()?,
+            self.colliders.to_items()?,
         ]
         .into_iter()
         .flatten()
-        .collect_vec()
+        .collect_vec())
     }
     pub const MAIN: &str = "main";
     pub fn model(&self) -> &Model {
@@ -189,7 +202,7 @@ impl ModelCrate {
     }
 
     pub async fn produce_local_model_url(&self, path: PathBuf) -> anyhow::Result<PathBuf> {
-        self.write_to_fs(&path).await;
+        self.write_to_fs(&path).await?;
         let model_id = self.models.content.keys().next().unwrap();
         Ok(self.models.loc.path(model_id).to_path(path))
     }
@@ -418,6 +431,30 @@ impl ModelCrate {
             }
         }
     }
+    /// Writes the images in another format, such as compressed textures, and points the materials to them
+    pub fn set_image_format(
+        &mut self,
+        extension: &str,
+        serialize: Arc<dyn Fn(&image::RgbaImage) -> anyhow::Result<Vec<u8>> + Sync + Send>,
+    ) {
+        let old_loc = self.images.loc.clone();
+        self.images.loc.extension = extension.to_string();
+        self.images.serialize = serialize;
+        let images = &self.images;
+        let retarget = |url: &mut Option<AssetUrl>| {
+            if let Some(AssetUrl::Relative(path)) = url {
+                if old_loc.id_from_path(path.clone()).map(|id| images.content.contains_key(&id)).unwrap_or(false) {
+                    *path = path.with_extension(extension);
+                }
+            }
+        };
+        for material in self.materials.content.values_mut() {
+            retarget(&mut material.base_color);
+            retarget(&mut material.opacity);
+            retarget(&mut material.normalmap);
+            retarget(&mut material.metallic_roughness);
+        }
+    }
     pub fn cap_texture_sizes(&mut self, max_size: u32) {
         for image in self.images.content.values_mut() {
             cap_texture_size(image, max_size);
@@ -463,6 +500,19 @@ impl ModelCrate {
     }
     pub fn create_collider_from_model(&mut self, assets: &AssetCache, flip_normals: bool, reverse_indices: bool) -> anyhow::Result<()> {
         self.update_transforms();
+        let obj_collider = self.create_collider(assets, ModelCrate::MAIN, flip_normals, reverse_indices);
+        let prefab = self.prefab_world_mut();
+        prefab
+            .add_component(
+                prefab.resource(children())[0],
+                collider(),
+                ColliderDef::Asset { collider: dotdot_path(obj_collider.path).into() },
+            )
+            .unwrap();
+        Ok(())
+    }
+    /// Cooks the meshes of the model `model_id`, which has to have its transforms updated, into a collider with the same id
+    fn create_collider(&mut self, assets: &AssetCache, model_id: &str, flip_normals: bool, reverse_indices: bool) -> AssetLoc {
         let physics = PhysicsKey.get(assets);
         let create_triangle_mesh = |asset_crate: &mut ModelCrate, id: &str| -> bool {
             if asset_crate.px_triangle_meshes.content.contains_key(id) {
@@ -509,18 +559,21 @@ impl ModelCrate {
         };
         let mut convex = Vec::new();
         let mut triangle = Vec::new();
-        let world_transform = self.model().get_transform().unwrap_or_default();
-        let entities = {
-            let world = self.model_world();
-            query(pbr_renderer_primitives_from_url()).collect_cloned(world, None)
-        };
-        for (id, prims) in entities {
-            let ltw = self.model_world().get(id, local_to_world()).unwrap_or_default();
+        let model = &self.models.content[model_id];
+        let world_transform = model.get_transform().unwrap_or_default();
+        let entities = query(pbr_renderer_primitives_from_url())
+            .iter(&model.0, None)
+            .map(|(id, prims)| {
+                let ltw = model.0.get(id, local_to_world()).unwrap_or_default();
+                let mtl = model.0.get(id, mesh_to_local()).unwrap_or_default();
+                (ltw * mtl, prims.clone())
+            })
+            .collect_vec();
+        for (mesh_to_model, prims) in entities {
             if let Some(max_lod) = prims.iter().map(|x| x.lod).max() {
-                let mtl = self.model_world().get(id, mesh_to_local()).unwrap_or_default();
                 // Only use the "max" lod for colliders
                 for primitive in prims.into_iter().filter(|x| x.lod == max_lod) {
-                    let transform = world_transform * ltw * mtl;
+                    let transform = world_transform * mesh_to_model;
                     let (scale, rot, pos) = transform.to_scale_rotation_translation();
                     let mesh_id = self.meshes.loc.id_from_path(primitive.mesh.path()).unwrap();
                     if create_triangle_mesh(self, &mesh_id) {
@@ -537,17 +590,85 @@ impl ModelCrate {
                 }
             }
         }
-        let obj_collider = self.colliders.insert(ModelCrate::MAIN.to_string(), ColliderFromUrls { convex, concave: triangle });
-        let prefab = self.prefab_world_mut();
-        prefab
-            .add_component(
-                prefab.resource(children())[0],
-                collider(),
-                ColliderDef::Asset { collider: dotdot_path(obj_collider.path).into() },
-            )
-            .unwrap();
+        self.colliders.insert(model_id, ColliderFromUrls { convex, concave: triangle })
+    }
+    /// Splits the children of the node named `chunks_node` off into models and colliders of their own, which the prefab
+    /// breaks into when it's destroyed. The children of a chunk are the smaller chunks it breaks into, so the model of a
+    /// chunk only has the meshes of its own node
+    pub fn create_destructible_chunks(&mut self, assets: &AssetCache, chunks_node: &str) -> anyhow::Result<()> {
+        self.update_transforms();
+        self.update_node_primitive_aabbs_from_cpu_meshes();
+        let world = self.model_world();
+        let root = query(name())
+            .iter(world, None)
+            .find(|(_, name)| *name == chunks_node)
+            .map(|(id, _)| id)
+            .with_context(|| format!("The model has no {chunks_node:?} node to take the destructible chunks from"))?;
+        let nodes = self.model().roots().into_iter().flat_map(|node| descendants(world, node)).collect_vec();
+        let chunk_nodes = world.get_ref(root, children()).cloned().unwrap_or_default();
+        let mut count = 0;
+        let chunks = chunk_nodes.into_iter().map(|node| self.create_destructible_chunk(assets, node, &nodes, &mut count)).collect_vec();
+
+        // The intact model is what's left without the chunks
+        let world = self.model_world_mut();
+        match world.get(root, parent()) {
+            Ok(parent) => world.get_mut(parent, children()).unwrap().retain(|&child| child != root),
+            Err(_) => world.resource_mut(children()).retain(|&child| child != root),
+        }
+        for node in descendants(world, root) {
+            world.despawn(node);
+        }
+        self.add_component_to_prefab(destructible_chunks(), chunks);
         Ok(())
     }
+    fn create_destructible_chunk(
+        &mut self,
+        assets: &AssetCache,
+        node: EntityId,
+        nodes: &[EntityId],
+        count: &mut usize,
+    ) -> DestructibleChunk {
+        let id = format!("chunk_{count}");
+        *count += 1;
+        let model = self.model();
+        let model_transform = model.get_transform().unwrap_or_default();
+        let transform = model_transform * model.0.get(node, local_to_world()).unwrap_or_default();
+        let parent_transform = model.0.get(node, parent()).and_then(|parent| model.0.get(parent, local_to_world())).unwrap_or_default();
+        let child_chunks = model.0.get_ref(node, children()).cloned().unwrap_or_default();
+
+        let mut chunk = model.clone();
+        for &other in nodes.iter().filter(|&&other| other != node) {
+            chunk.0.despawn(other);
+        }
+        chunk.0.remove_component(node, parent()).ok();
+        chunk.0.add_component(node, children(), Vec::new()).unwrap();
+        chunk.0.add_resource(children(), vec![node]);
+        // The chunk is spawned at its own pose, so its model only keeps what's left of the transforms above it
+        chunk.0.add_resource(local_to_parent(), transform.inverse() * model_transform * parent_transform);
+        TransformSystem::new().run(&mut chunk.0, &FrameEvent);
+        chunk.update_model_aabb();
+        let center = transform.transform_point3(chunk.aabb().unwrap_or(AABB::ZERO).center());
+        let model_loc = self.models.insert(&id, chunk);
+        let collider_loc = self.create_collider(assets, &id, false, true);
+        self.models.content.get_mut(&id).unwrap().remove_non_storage_matrices();
+
+        let children = child_chunks.into_iter().map(|child| self.create_destructible_chunk(assets, child, nodes, count)).collect();
+        DestructibleChunk {
+            model: dotdot_path(model_loc.path).into(),
+            collider: ColliderDef::Asset { collider: dotdot_path(collider_loc.path).into() },
+            transform,
+            center,
+            children,
+        }
+    }
+}
+
+fn descendants(world: &World, node: EntityId) -> Vec<EntityId> {
+    let mut nodes = vec![node];
+    for &child in world.get_ref(node, children()).into_iter().flatten() {
+        nodes.extend(descendants(world, child));
+    }
+    nodes
 }
 pub struct AssetItem {
     pub path: RelativePathBuf,
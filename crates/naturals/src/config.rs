@@ -43,6 +43,18 @@ pub struct NaturalLayer {
     // #[editor(slider, min = 0.05, max = 5.)]
     pub grid_size: f32,
     pub elements: Vec<NaturalElement>,
+    #[serde(default)]
+    pub placement: NaturalPlacement,
+}
+
+/// How the instances of a [NaturalLayer] are placed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NaturalPlacement {
+    /// On a jittered grid of `grid_size`, on the GPU
+    #[default]
+    Grid,
+    /// At least `grid_size` apart, see [crate::scatter]
+    PoissonDisk,
 }
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NaturalElement {
@@ -95,10 +107,21 @@ pub struct NaturalElement {
     pub steepness: NaturalCurve,
     #[serde(default)]
     pub cluster_noise: NaturalCurve,
+    /// The fraction of the points of a [NaturalPlacement::PoissonDisk] layer this element can be placed on
+    #[serde(default = "density_default")]
+    // #[editor(slider, min = 0., max = 1.)]
+    pub density: f32,
+    /// How much the element sways in the wind, see [wind_sway](ambient_renderer::wind_sway); 0 for rocks and the like
+    #[serde(default)]
+    // #[editor(slider, min = 0., max = 3.)]
+    pub wind_sway: f32,
 }
 fn cluster_noise_scale_default() -> f32 {
     1.
 }
+fn density_default() -> f32 {
+    1.
+}
 impl Default for NaturalElement {
     fn default() -> Self {
         Self {
@@ -123,6 +146,8 @@ impl Default for NaturalElement {
             water_depth: Default::default(),
             steepness: Default::default(),
             cluster_noise: Default::default(),
+            density: 1.,
+            wind_sway: 0.,
         }
     }
 }
@@ -173,6 +198,27 @@ impl Default for NaturalCurve {
         Self::Constant { value: 1. }
     }
 }
+impl NaturalCurve {
+    /// The value of the curve at `x`, as computed by the naturals shader
+    pub fn sample(&self, x: f32) -> f32 {
+        fn mix(y0: f32, y1: f32, t: f32) -> f32 {
+            y0 + (y1 - y0) * t
+        }
+        match *self {
+            Self::Constant { value } => value,
+            Self::Interpolate { x0, x1, y0, y1 } => mix(y0, y1, (x - x0) / (x1 - x0)),
+            Self::InterpolateClamped { x0, x1, y0, y1 } => mix(y0, y1, ((x - x0) / (x1 - x0)).clamp(0., 1.)),
+            Self::SmoothStep { x0, x1, y0, y1 } => {
+                let t = ((x - x0) / (x1 - x0)).clamp(0., 1.);
+                mix(y0, y1, t * t * (3. - 2. * t))
+            }
+            Self::BellCurve { center, width, y0, y1 } => {
+                let k = x - center;
+                mix(y0, y1, (-k * k / (2. * width * width)).exp())
+            }
+        }
+    }
+}
 
 pub enum NaturalsPreset {
     Mountains,
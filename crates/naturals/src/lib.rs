@@ -11,7 +11,7 @@ use ambient_core::{
 };
 use ambient_ecs::{components, query, Entity, EntityId, FnSystem, SystemGroup};
 use ambient_model::{Model, ModelFromUrl, ModelSpawnOpts, ModelSpawnRoot};
-use ambient_renderer::color;
+use ambient_renderer::{color, wind_sway};
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKeyExt, SyncAssetKey, SyncAssetKeyExt},
     log_result,
@@ -23,6 +23,7 @@ use rand::prelude::SliceRandom;
 
 mod compute;
 mod config;
+pub mod scatter;
 use ambient_network::ServerWorldExt;
 pub use compute::*;
 pub use config::*;
@@ -36,6 +37,12 @@ components!("game_objects", {
     natural_layers_in_progress: usize,
     terrain_cell_nature_version: i32,
     terrain_cell_nature_conf_hash: u64,
+    /// The instances scattered on a terrain cell by the Poisson-disk layers, see [scatter]
+    natural_scatter_entities: Vec<EntityId>,
+    /// The hash of the layers and the terrain a cell was last scattered with
+    natural_scatter_hash: u64,
+    /// Whether this client scatters the Poisson-disk layers; on when missing. The editor toggles it to preview them
+    natural_scatter_preview: bool,
 });
 
 pub fn init_world_resources() -> Entity {
@@ -140,17 +147,9 @@ impl From<NaturalCurve> for NaturalCurveWGSL {
     }
 }
 
-async fn update_natural_layer(
-    assets: AssetCache,
-    async_run: AsyncRun,
-    terrain_state: TerrainState,
-    terrain_cell_id: EntityId,
-    terrain_cell_position: Vec3,
-    layer: NaturalLayer,
-) {
-    // Flatten elements so that there's one element per model
-    let elements: Vec<(NaturalElement, BoxModelKey)> = layer
-        .elements
+/// Flattens the enabled elements so that there's one element per model
+fn flatten_elements(elements: Vec<NaturalElement>) -> Vec<(NaturalElement, BoxModelKey)> {
+    elements
         .into_iter()
         .filter(|el| el.enabled)
         .flat_map(|element| {
@@ -169,7 +168,18 @@ async fn update_natural_layer(
                 })
                 .collect_vec()
         })
-        .collect_vec();
+        .collect_vec()
+}
+
+async fn update_natural_layer(
+    assets: AssetCache,
+    async_run: AsyncRun,
+    terrain_state: TerrainState,
+    terrain_cell_id: EntityId,
+    terrain_cell_position: Vec3,
+    layer: NaturalLayer,
+) {
+    let elements = flatten_elements(layer.elements);
     if elements.is_empty() {
         return;
     }
@@ -210,6 +220,7 @@ async fn update_natural_layer(
                                         root_components: Entity::new()
                                             .with(natural_model(), model.clone())
                                             .with(color(), element.color.into())
+                                            .with(wind_sway(), element.wind_sway)
                                             .with_default(local_to_world()),
                                         animatable: Some(false),
                                         ..Default::default()
@@ -275,6 +286,7 @@ pub fn client_systems() -> SystemGroup {
                     .unwrap_or_else(|| get_default_natural_layers(NaturalsPreset::Mountains));
                 let layers = layers
                     .into_iter()
+                    .filter(|layer| layer.placement == NaturalPlacement::Grid)
                     .filter_map(|layer| {
                         let res = NaturalLayer { elements: layer.elements.into_iter().filter(|e| e.enabled).collect_vec(), ..layer };
                         if !res.elements.is_empty() {
@@ -351,6 +363,7 @@ pub fn client_systems() -> SystemGroup {
                     world.despawn(id);
                 }
             })),
+            Box::new(scatter::systems()),
         ],
     )
 }
@@ -0,0 +1,283 @@
+//! Scatters the [NaturalPlacement::PoissonDisk] layers over the terrain on the CPU, with their instances at least
+//! `grid_size` apart. Each terrain cell is a chunk which is scattered on its own, and its instances are spawned and
+//! despawned along with it as the terrain streams in and out.
+//!
+//! The placement only depends on the position and the layer, so chunks line up at their borders and every client
+//! scatters the same instances.
+use std::f32::consts::{PI, SQRT_2};
+
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    runtime,
+    transform::{local_to_world, translation},
+};
+use ambient_ecs::{query, Entity, FnSystem, SystemGroup};
+use ambient_model::{ModelSpawnOpts, ModelSpawnRoot};
+use ambient_network::ServerWorldExt;
+use ambient_renderer::{color, wind_sway};
+use ambient_terrain::{
+    terrain_cell_version, terrain_hole_mask, terrain_state_cpu, TerrainHoleMask, TerrainLayers, TerrainStateCpu, TERRAIN_BASE,
+};
+use glam::{ivec2, vec2, EulerRot, IVec2, Mat4, Quat, Vec2, Vec3, Vec3Swizzles};
+use itertools::Itertools;
+
+use crate::{
+    calculate_hash, flatten_elements, get_default_natural_layers, natural_layers, natural_model, natural_scatter_entities,
+    natural_scatter_hash, natural_scatter_preview, NaturalElement, NaturalLayer, NaturalPlacement, NaturalsPreset,
+};
+
+/// An instance of a model of a layer, relative to its terrain cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScatterInstance {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub scale: f32,
+    /// The index of the model in the flattened elements of the layer
+    pub model: usize,
+}
+impl ScatterInstance {
+    pub fn transform(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(Vec3::ONE * self.scale, self.rotation, self.position)
+    }
+}
+
+/// A random number from 0 to 1 which only depends on its inputs, so that every client and chunk agrees on it
+fn hash01(seed: u64, cell: IVec2, channel: u64) -> f32 {
+    // SplitMix64
+    let mut z =
+        seed.wrapping_add((cell.x as u32 as u64) | ((cell.y as u32 as u64) << 32)).wrapping_mul(0x9e3779b97f4a7c15).wrapping_add(channel);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Places points from `min` to `max` which are at least `radius` apart. Each cell of a grid of `radius / sqrt(2)` has
+/// one candidate point, which is kept unless a neighbouring candidate with a higher priority is too close. As the
+/// candidates only depend on their cell, the points of adjacent regions line up. Returns the grid cell of each point
+/// along with it, to derive its other random values from
+pub fn poisson_disk_points(seed: u64, min: Vec2, max: Vec2, radius: f32) -> Vec<(IVec2, Vec2)> {
+    let grid = radius / SQRT_2;
+    let candidate = |cell: IVec2| (cell.as_vec2() + vec2(hash01(seed, cell, 0), hash01(seed, cell, 1))) * grid;
+    let priority = |cell: IVec2| (hash01(seed, cell, 2), cell.x, cell.y);
+    // Candidates further than this many cells away are always further than `radius` away
+    let reach = SQRT_2.ceil() as i32;
+
+    let min_cell = (min / grid).floor().as_ivec2();
+    let max_cell = (max / grid).ceil().as_ivec2();
+    (min_cell.y..max_cell.y)
+        .cartesian_product(min_cell.x..max_cell.x)
+        .map(|(y, x)| ivec2(x, y))
+        .filter_map(|cell| {
+            let point = candidate(cell);
+            if point.cmplt(min).any() || point.cmpge(max).any() {
+                return None;
+            }
+            let blocked = (-reach..=reach).cartesian_product(-reach..=reach).filter(|&offset| offset != (0, 0)).any(|(x, y)| {
+                let other = cell + ivec2(x, y);
+                priority(other) > priority(cell) && candidate(other).distance_squared(point) < radius * radius
+            });
+            (!blocked).then_some((cell, point))
+        })
+        .collect()
+}
+
+/// The heights of the rock and soil layers and the normal of a terrain cell at `offset`
+fn sample_terrain(state: &TerrainStateCpu, offset: Vec2) -> (f32, f32, Vec3) {
+    let size = state.heightmap.shape()[1];
+    let texel = (offset / state.size.size_in_meters() * (size - 1) as f32).clamp(Vec2::ZERO, Vec2::splat((size - 1) as f32));
+    let (x0, y0) = (texel.x.floor() as usize, texel.y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(size - 1), (y0 + 1).min(size - 1));
+    let t = texel.fract();
+    let bilinear = |layer: usize| {
+        let height = |x: usize, y: usize| state.heightmap[[layer, y, x]];
+        let bottom = height(x0, y0) + (height(x1, y0) - height(x0, y0)) * t.x;
+        let top = height(x0, y1) + (height(x1, y1) - height(x0, y1)) * t.x;
+        bottom + (top - bottom) * t.y
+    };
+    let (x, y) = (texel.x.round() as usize, texel.y.round() as usize);
+    let normal = Vec3::new(state.normalmap[[y, x, 0]], state.normalmap[[y, x, 1]], state.normalmap[[y, x, 2]]).normalize_or_zero();
+    (bilinear(TerrainLayers::Rock as usize), bilinear(TerrainLayers::Soil as usize), normal)
+}
+
+fn element_probability(element: &NaturalElement, rock: f32, soil: f32, normal: Vec3) -> f32 {
+    let height_over_ocean = rock + soil + TERRAIN_BASE;
+    let steepness = normal.z.clamp(-1., 1.).acos().to_degrees();
+    element.density.clamp(0., 1.)
+        * element.soil_depth.sample(soil).clamp(0., 1.)
+        * element.elevation.sample(height_over_ocean).clamp(0., 1.)
+        * element.water_depth.sample((-height_over_ocean).max(0.)).clamp(0., 1.)
+        * element.steepness.sample(steepness).clamp(0., 1.)
+}
+
+/// Scatters `elements`, flattened to one per model, over a terrain cell at `cell_position`. Points over holes are
+/// skipped, as they're in caves. The cluster noise of the elements isn't used
+pub fn scatter_cell(
+    seed: u64,
+    grid_size: f32,
+    elements: &[NaturalElement],
+    cell_position: Vec2,
+    state: &TerrainStateCpu,
+    holes: Option<&TerrainHoleMask>,
+) -> Vec<ScatterInstance> {
+    if elements.is_empty() {
+        return Vec::new();
+    }
+    let size = state.size.size_in_meters();
+    let mut probabilities = vec![0.; elements.len()];
+    poisson_disk_points(seed, cell_position, cell_position + size, grid_size)
+        .into_iter()
+        .filter_map(|(cell, point)| {
+            let offset = point - cell_position;
+            if holes.map_or(false, |holes| holes.is_hole_at_world_offset(offset)) {
+                return None;
+            }
+            let (rock, soil, normal) = sample_terrain(state, offset);
+            for (probability, element) in probabilities.iter_mut().zip(elements) {
+                *probability = element_probability(element, rock, soil, normal);
+            }
+            let total = probabilities.iter().sum::<f32>();
+            if total <= 0. {
+                return None;
+            }
+
+            // Pick an element in proportion to its probability, and keep it with that probability
+            let pick = hash01(seed, cell, 3) * total;
+            let model = probabilities
+                .iter()
+                .scan(0., |cumulative, probability| {
+                    *cumulative += probability;
+                    Some(*cumulative)
+                })
+                .position(|cumulative| pick <= cumulative)
+                .unwrap_or(elements.len() - 1);
+            if probabilities[model] <= hash01(seed, cell, 4) {
+                return None;
+            }
+
+            let element = &elements[model];
+            let jitter = |channel| (2. * hash01(seed, cell, channel) - 1.) * PI;
+            let rotation = Quat::from_euler(
+                EulerRot::XYZ,
+                -normal.y.asin() * (1. - element.rotation_straightness) + jitter(5) * element.rotation_xy_jitter + element.rotation_x,
+                normal.x.asin() * (1. - element.rotation_straightness) + jitter(6) * element.rotation_xy_jitter + element.rotation_y,
+                normal.y.atan2(normal.x) + jitter(7) * element.rotation_z_jitter + element.rotation_z,
+            );
+            let scale = element.scale_min + (element.scale_max - element.scale_min) * hash01(seed, cell, 8).powf(element.scale_power);
+            let position = offset.extend(rock + soil) + normal * element.position_normal_offset + Vec3::Z * element.position_z_offset;
+            Some(ScatterInstance { position, rotation, scale, model })
+        })
+        .collect()
+}
+
+/// The enabled Poisson-disk layers, with their enabled elements
+fn scatter_layers(layers: Vec<NaturalLayer>) -> Vec<NaturalLayer> {
+    layers
+        .into_iter()
+        .filter(|layer| layer.placement == NaturalPlacement::PoissonDisk && layer.grid_size > 0.05)
+        .map(|layer| NaturalLayer { elements: layer.elements.into_iter().filter(|el| el.enabled).collect_vec(), ..layer })
+        .filter(|layer| !layer.elements.is_empty())
+        .collect_vec()
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "dims/naturals/scatter",
+        vec![
+            Box::new(FnSystem::new(|world, _| {
+                let preview = world.resource_opt(natural_scatter_preview()).copied().unwrap_or(true);
+                let layers = if preview {
+                    scatter_layers(
+                        world
+                            .persisted_resource(natural_layers())
+                            .cloned()
+                            .unwrap_or_else(|| get_default_natural_layers(NaturalsPreset::Mountains)),
+                    )
+                } else {
+                    Vec::new()
+                };
+                let layers_hash = calculate_hash(&format!("{layers:?}"));
+
+                let cells = query((terrain_state_cpu(), translation(), terrain_cell_version()))
+                    .iter(world, None)
+                    .map(|(id, (state, position, version))| {
+                        let holes = world.get_ref(id, terrain_hole_mask()).ok().cloned();
+                        let hash = calculate_hash(&(layers_hash, *version, holes.as_ref().map(|holes| holes.bits.clone())));
+                        (id, state.clone(), *position, holes, hash)
+                    })
+                    .filter(|(id, _, _, _, hash)| world.get(*id, natural_scatter_hash()).ok() != Some(*hash))
+                    .collect_vec();
+
+                for (cell_id, state, cell_position, holes, hash) in cells {
+                    world.add_component(cell_id, natural_scatter_hash(), hash).ok();
+                    let layers = layers.clone();
+                    let assets = world.resource(asset_cache()).clone();
+                    let async_run = world.resource(async_run()).clone();
+                    world.resource(runtime()).spawn(async move {
+                        let mut spawns = Vec::new();
+                        for (index, layer) in layers.into_iter().enumerate() {
+                            let (elements, models): (Vec<_>, Vec<_>) = flatten_elements(layer.elements).into_iter().unzip();
+                            let instances = scatter_cell(
+                                calculate_hash(&index),
+                                layer.grid_size,
+                                &elements,
+                                cell_position.xy(),
+                                &state,
+                                holes.as_ref(),
+                            );
+                            for (model_index, (element, model)) in elements.into_iter().zip(models).enumerate() {
+                                let instances = instances.iter().filter(|instance| instance.model == model_index).copied().collect_vec();
+                                if instances.is_empty() {
+                                    continue;
+                                }
+                                match model.get(&assets).await {
+                                    Ok(model) => spawns.push((element, model, instances)),
+                                    Err(err) => tracing::warn!("Failed to load asset {}: {:#}", model.key(), err),
+                                }
+                            }
+                        }
+                        async_run.run(move |world| {
+                            // The cell was despawned or scattered again in the meantime
+                            if world.get(cell_id, natural_scatter_hash()).ok() != Some(hash) {
+                                return;
+                            }
+                            for id in world.get_cloned(cell_id, natural_scatter_entities()).unwrap_or_default() {
+                                world.despawn(id);
+                            }
+                            let mut entities = Vec::new();
+                            for (element, model, instances) in spawns {
+                                let ids = model.batch_spawn(
+                                    world,
+                                    &ModelSpawnOpts {
+                                        root: ModelSpawnRoot::Spawn,
+                                        root_components: Entity::new()
+                                            .with(natural_model(), model.clone())
+                                            .with(color(), element.color.into())
+                                            .with(wind_sway(), element.wind_sway)
+                                            .with_default(local_to_world()),
+                                        animatable: Some(false),
+                                        ..Default::default()
+                                    },
+                                    instances.len(),
+                                );
+                                for (id, instance) in ids.iter().zip(instances) {
+                                    world.set(*id, local_to_world(), Mat4::from_translation(cell_position) * instance.transform()).ok();
+                                }
+                                entities.extend(ids);
+                            }
+                            world.add_component(cell_id, natural_scatter_entities(), entities).ok();
+                        });
+                    });
+                }
+            })),
+            query(natural_scatter_entities()).despawned().to_system(|q, world, qs, _| {
+                for (_, entities) in q.collect_cloned(world, qs) {
+                    for id in entities {
+                        world.despawn(id);
+                    }
+                }
+            }),
+        ],
+    )
+}
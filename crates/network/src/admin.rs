@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use ambient_ecs::{components, world_events, Debuggable, Description, Entity, Name, Networked, Resource, Store, World};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    server::{ServerState, MAIN_INSTANCE_ID},
+    thumbnail::ThumbnailOptions,
+};
+
+components!("network", {
+    @[Debuggable, Networked, Store, Name["Admin message"], Description["A message broadcast by a server admin. Sent with the `ADMIN_MESSAGE` event."]]
+    admin_message: String,
+
+    @[Resource]
+    package_handlers: PackageHandlers,
+});
+
+/// A command sent to the server by an admin; see [AdminHandle]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum AdminCommand {
+    ListPlayers,
+    /// Disconnects the player; they can reconnect
+    Kick {
+        user_id: String,
+    },
+    /// Disconnects the player, and refuses their connections until they're unbanned or the server restarts
+    Ban {
+        user_id: String,
+    },
+    Unban {
+        user_id: String,
+    },
+    /// Sends an `ADMIN_MESSAGE` event to every instance
+    Broadcast {
+        message: String,
+    },
+    ListInstances,
+    ListPackages,
+    /// Loads the modules of the built package at `path` into the main instance
+    LoadPackage {
+        path: String,
+    },
+    /// Unloads the modules of the package with the id `id` from the main instance
+    UnloadPackage {
+        id: String,
+    },
+    /// Renders the main instance to a PNG on the server's machine, with a software adapter if it has no GPU
+    RenderThumbnail(ThumbnailOptions),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub user_id: String,
+    pub instance: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceInfo {
+    pub id: String,
+    pub players: usize,
+    pub entities: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", content = "value", rename_all = "snake_case")]
+pub enum AdminResponse {
+    Done,
+    Players(Vec<PlayerInfo>),
+    Instances(Vec<InstanceInfo>),
+    Packages(Vec<String>),
+    /// The path the thumbnail was saved to
+    Thumbnail(String),
+}
+
+/// How the server loads and unloads packages at runtime; these depend on the WASM runtime, so they are provided by the app
+#[derive(Clone)]
+pub struct PackageHandlers {
+    pub list: Arc<dyn Fn(&World) -> Vec<String> + Sync + Send>,
+    /// Returns the id of the loaded package
+    pub load: Arc<dyn Fn(&mut World, &str) -> anyhow::Result<String> + Sync + Send>,
+    pub unload: Arc<dyn Fn(&mut World, &str) -> anyhow::Result<()> + Sync + Send>,
+}
+impl std::fmt::Debug for PackageHandlers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackageHandlers").finish_non_exhaustive()
+    }
+}
+
+pub type AdminResult = Result<AdminResponse, String>;
+
+/// Sends [AdminCommand]s to a running [crate::server::GameServer]. Commands are run between simulation frames.
+#[derive(Debug, Clone)]
+pub struct AdminHandle(pub(crate) flume::Sender<(AdminCommand, flume::Sender<AdminResult>)>);
+impl AdminHandle {
+    pub async fn run(&self, command: AdminCommand) -> AdminResult {
+        let (tx, rx) = flume::bounded(1);
+        self.0.send_async((command, tx)).await.map_err(|_| "The server is not running".to_string())?;
+        rx.recv_async().await.map_err(|_| "The server stopped before running the command".to_string())?
+    }
+}
+
+impl ServerState {
+    pub fn is_banned(&self, user_id: &str) -> bool {
+        self.banned.contains(user_id)
+    }
+
+    /// Disconnects the player; returns false if they're not connected
+    pub fn kick_player(&mut self, user_id: &str) -> bool {
+        let Some(player) = self.players.get(user_id) else { return false };
+        // Aborting the connection task drops the connection, which despawns the player
+        if let Some(handle) = player.abort_handle.get() {
+            handle.abort();
+        }
+        true
+    }
+
+    pub fn handle_admin_command(&mut self, command: AdminCommand) -> AdminResult {
+        log::info!("Running admin command: {command:?}");
+        match command {
+            AdminCommand::ListPlayers => {
+                let mut players = self
+                    .players
+                    .iter()
+                    .map(|(user_id, player)| PlayerInfo { user_id: user_id.clone(), instance: player.instance.clone() })
+                    .collect::<Vec<_>>();
+                players.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+                Ok(AdminResponse::Players(players))
+            }
+            AdminCommand::Kick { user_id } => {
+                if !self.kick_player(&user_id) {
+                    return Err(format!("No player with the user id {user_id:?} is connected"));
+                }
+                Ok(AdminResponse::Done)
+            }
+            AdminCommand::Ban { user_id } => {
+                self.kick_player(&user_id);
+                self.banned.insert(user_id);
+                Ok(AdminResponse::Done)
+            }
+            AdminCommand::Unban { user_id } => {
+                if !self.banned.remove(&user_id) {
+                    return Err(format!("{user_id:?} is not banned"));
+                }
+                Ok(AdminResponse::Done)
+            }
+            AdminCommand::Broadcast { message } => {
+                for instance in self.instances.values_mut() {
+                    instance
+                        .world
+                        .resource_mut(world_events())
+                        .add_event((ambient_event_types::ADMIN_MESSAGE.to_string(), Entity::new().with(admin_message(), message.clone())));
+                }
+                Ok(AdminResponse::Done)
+            }
+            AdminCommand::ListInstances => {
+                let mut instances = self
+                    .instances
+                    .iter()
+                    .map(|(id, instance)| InstanceInfo { id: id.clone(), players: instance.player_count(), entities: instance.world.len() })
+                    .collect::<Vec<_>>();
+                instances.sort_by(|a, b| a.id.cmp(&b.id));
+                Ok(AdminResponse::Instances(instances))
+            }
+            AdminCommand::ListPackages => {
+                let (world, handlers) = self.main_package_handlers()?;
+                Ok(AdminResponse::Packages((handlers.list)(world)))
+            }
+            AdminCommand::LoadPackage { path } => {
+                let (world, handlers) = self.main_package_handlers()?;
+                let id = (handlers.load)(world, &path).map_err(|err| format!("{err:?}"))?;
+                log::info!("Loaded package {id} from {path}");
+                Ok(AdminResponse::Done)
+            }
+            AdminCommand::UnloadPackage { id } => {
+                let (world, handlers) = self.main_package_handlers()?;
+                (handlers.unload)(world, &id).map_err(|err| format!("{err:?}"))?;
+                log::info!("Unloaded package {id}");
+                Ok(AdminResponse::Done)
+            }
+            // Rendering takes too long to block the server, so the server loop runs it with `thumbnail::render_thumbnail`
+            AdminCommand::RenderThumbnail(_) => Err("Thumbnails can't be rendered synchronously".to_string()),
+        }
+    }
+
+    fn main_package_handlers(&mut self) -> Result<(&mut World, PackageHandlers), String> {
+        let world = &mut self.instances.get_mut(MAIN_INSTANCE_ID).ok_or("The main instance is not running")?.world;
+        let handlers = world.resource_opt(package_handlers()).cloned().ok_or("This server can't load packages")?;
+        Ok((world, handlers))
+    }
+}
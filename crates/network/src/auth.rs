@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use anyhow::{ensure, Context};
+use async_trait::async_trait;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+/// Who a player is, as verified by a [TokenVerifier]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedIdentity {
+    pub user_id: String,
+    pub display_name: Option<String>,
+}
+
+/// Verifies the token a player connects with
+#[async_trait]
+pub trait TokenVerifier: Send + Sync {
+    async fn verify(&self, token: &str) -> anyhow::Result<VerifiedIdentity>;
+}
+
+/// The claims of an identity token that are used; `sub` is the user id
+#[derive(Debug, Deserialize)]
+struct IdentityClaims {
+    sub: String,
+    name: Option<String>,
+    preferred_username: Option<String>,
+}
+
+/// Verifies JSON Web Tokens signed with a shared secret (HS256) or an RSA key pair (RS256)
+pub struct JwtVerifier {
+    key: DecodingKey,
+    validation: Validation,
+}
+impl JwtVerifier {
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self { key: DecodingKey::from_secret(secret), validation: Validation::new(Algorithm::HS256) }
+    }
+    pub fn rs256(public_key_pem: &[u8]) -> anyhow::Result<Self> {
+        Ok(Self {
+            key: DecodingKey::from_rsa_pem(public_key_pem).context("Invalid RSA public key")?,
+            validation: Validation::new(Algorithm::RS256),
+        })
+    }
+    /// Only accept tokens issued by `issuer`
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.validation.set_issuer(&[issuer]);
+        self
+    }
+    /// Only accept tokens meant for `audience`
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.validation.set_audience(&[audience]);
+        self
+    }
+}
+#[async_trait]
+impl TokenVerifier for JwtVerifier {
+    async fn verify(&self, token: &str) -> anyhow::Result<VerifiedIdentity> {
+        let claims = jsonwebtoken::decode::<IdentityClaims>(token, &self.key, &self.validation)?.claims;
+        Ok(VerifiedIdentity { user_id: claims.sub, display_name: claims.name.or(claims.preferred_username) })
+    }
+}
+
+/// Verifies OAuth 2.0 access tokens with the identity provider's token introspection endpoint (RFC 7662)
+pub struct OAuthIntrospectionVerifier {
+    pub url: String,
+    /// The credentials the server authenticates to the endpoint with, if it requires them
+    pub client_credentials: Option<(String, String)>,
+    client: reqwest::Client,
+}
+impl OAuthIntrospectionVerifier {
+    pub fn new(url: String, client_credentials: Option<(String, String)>) -> Self {
+        Self { url, client_credentials, client: reqwest::Client::new() }
+    }
+}
+#[derive(Debug, Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    sub: Option<String>,
+    username: Option<String>,
+    name: Option<String>,
+}
+#[async_trait]
+impl TokenVerifier for OAuthIntrospectionVerifier {
+    async fn verify(&self, token: &str) -> anyhow::Result<VerifiedIdentity> {
+        let mut request = self.client.post(&self.url).form(&[("token", token)]);
+        if let Some((id, secret)) = &self.client_credentials {
+            request = request.basic_auth(id, Some(secret));
+        }
+        let response: IntrospectionResponse =
+            request.send().await?.error_for_status()?.json().await.context("Invalid response from the introspection endpoint")?;
+        ensure!(response.active, "The token is not active");
+        let user_id = response.sub.or_else(|| response.username.clone()).context("The token has no subject")?;
+        Ok(VerifiedIdentity { user_id, display_name: response.name.or(response.username) })
+    }
+}
+
+/// How a [crate::server::GameServer] authenticates the players that connect to it
+#[derive(Clone)]
+pub struct Authentication {
+    pub verifier: Arc<dyn TokenVerifier>,
+    /// Refuse players that don't have a token. Otherwise, they join as guests; their user ids are prefixed with `guest_`
+    /// so that they can't pass for authenticated players.
+    pub required: bool,
+}
+impl std::fmt::Debug for Authentication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authentication").field("required", &self.required).finish_non_exhaustive()
+    }
+}
+
+/// The identity a player joins with
+#[derive(Debug, Clone)]
+pub(crate) struct PlayerIdentity {
+    pub user_id: String,
+    pub display_name: Option<String>,
+    pub authenticated: bool,
+}
+
+/// Works out who the player that said they're `user_id` is. Players with tokens that can't be verified are always refused.
+pub(crate) async fn authenticate(
+    authentication: Option<&Authentication>,
+    user_id: String,
+    token: Option<&str>,
+) -> anyhow::Result<PlayerIdentity> {
+    let Some(authentication) = authentication else {
+        return Ok(PlayerIdentity { user_id, display_name: None, authenticated: false });
+    };
+    match token {
+        Some(token) => {
+            let identity = authentication.verifier.verify(token).await.context("The token could not be verified")?;
+            Ok(PlayerIdentity { user_id: identity.user_id, display_name: identity.display_name, authenticated: true })
+        }
+        None => {
+            ensure!(!authentication.required, "This server requires players to sign in");
+            Ok(PlayerIdentity { user_id: format!("guest_{user_id}"), display_name: None, authenticated: false })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{EncodingKey, Header};
+
+    use super::*;
+
+    const SECRET: &[u8] = b"secret";
+
+    fn authentication(required: bool) -> Authentication {
+        Authentication { verifier: Arc::new(JwtVerifier::hs256(SECRET)), required }
+    }
+    fn token(sub: &str, secret: &[u8]) -> String {
+        #[derive(serde::Serialize)]
+        struct Claims<'a> {
+            sub: &'a str,
+            name: &'a str,
+            exp: u64,
+        }
+        let claims = Claims { sub, name: "Player One", exp: u32::MAX as u64 };
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn no_authentication_keeps_requested_id() {
+        let identity = authenticate(None, "player".to_string(), None).await.unwrap();
+        assert_eq!(identity.user_id, "player");
+        assert!(!identity.authenticated);
+    }
+
+    #[tokio::test]
+    async fn verified_token_replaces_requested_id() {
+        let token = token("user_1", SECRET);
+        let identity = authenticate(Some(&authentication(true)), "player".to_string(), Some(&token)).await.unwrap();
+        assert_eq!(identity.user_id, "user_1");
+        assert_eq!(identity.display_name.as_deref(), Some("Player One"));
+        assert!(identity.authenticated);
+    }
+
+    #[tokio::test]
+    async fn required_refuses_players_without_token() {
+        assert!(authenticate(Some(&authentication(true)), "player".to_string(), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn guests_are_prefixed() {
+        let identity = authenticate(Some(&authentication(false)), "player".to_string(), None).await.unwrap();
+        assert_eq!(identity.user_id, "guest_player");
+        assert!(!identity.authenticated);
+    }
+
+    #[tokio::test]
+    async fn invalid_tokens_are_refused() {
+        let forged = token("user_1", b"not the secret");
+        assert!(authenticate(Some(&authentication(false)), "player".to_string(), Some(&forged)).await.is_err());
+        assert!(authenticate(Some(&authentication(false)), "player".to_string(), Some("not a token")).await.is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_ecs::{components, Resource};
+use ambient_renderer::RenderTarget;
+use ambient_sys::time::{Instant, SystemTime};
+use futures::{future::BoxFuture, FutureExt};
+use image::{DynamicImage, RgbaImage};
+use parking_lot::Mutex;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+components!("capture", {
+    @[Resource]
+    capture: Capture,
+});
+
+/// Where screenshots are saved on native; on the web, they are downloaded instead
+pub const SCREENSHOTS_DIR: &str = "screenshots";
+/// Where recordings are saved
+pub const RECORDINGS_DIR: &str = "recordings";
+
+pub type OnFrameCaptured = Box<dyn FnOnce(RgbaImage) + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct RecordingSettings {
+    /// The name of the video, or of the directory of the frames if ffmpeg isn't available. Defaults to a timestamp
+    pub name: Option<String>,
+    pub fps: f32,
+    /// Stops the recording after this long, or when [Capture::stop_recording] is called if None
+    pub duration: Option<Duration>,
+}
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self { name: None, fps: 30., duration: None }
+    }
+}
+
+type FrameFuture = BoxFuture<'static, Option<DynamicImage>>;
+
+struct Recording {
+    settings: RecordingSettings,
+    started: Instant,
+    frames: usize,
+    /// The frames in the order they were rendered, with how many times each is repeated to keep up with the fps
+    sender: flume::Sender<(FrameFuture, usize)>,
+}
+
+#[derive(Default)]
+struct CaptureState {
+    pending_frames: Vec<OnFrameCaptured>,
+    recording: Option<Recording>,
+}
+
+/// Captures the frames rendered by the game client, for screenshots and recordings
+#[derive(Clone, Default)]
+pub struct Capture(Arc<Mutex<CaptureState>>);
+impl std::fmt::Debug for Capture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Capture").field("recording", &self.is_recording()).finish()
+    }
+}
+impl Capture {
+    /// Calls `on_captured` with the next rendered frame
+    pub fn capture_frame(&self, on_captured: OnFrameCaptured) {
+        self.0.lock().pending_frames.push(on_captured);
+    }
+    /// Saves the next rendered frame as a PNG in [SCREENSHOTS_DIR], named `name` or after the current time
+    ///
+    /// Fails if `name` isn't a plain file name, as it may come from a module sent by the server
+    pub fn screenshot(&self, name: Option<String>) -> anyhow::Result<()> {
+        let name = validate_name(name.unwrap_or_else(|| timestamp("screenshot")))?;
+        let path = PathBuf::from(SCREENSHOTS_DIR).join(format!("{name}.png"));
+        self.capture_frame(Box::new(move |image| {
+            ambient_sys::task::spawn(async move {
+                let mut png = std::io::Cursor::new(Vec::new());
+                if let Err(err) = image.write_to(&mut png, image::ImageOutputFormat::Png) {
+                    log::error!("Failed to encode screenshot: {err}");
+                    return;
+                }
+                match ambient_sys::export::export_file(&path, png.into_inner()).await {
+                    Ok(()) => log::info!("Saved screenshot to {path:?}"),
+                    Err(err) => log::error!("Failed to save screenshot to {path:?}: {err}"),
+                }
+            });
+        }));
+        Ok(())
+    }
+    /// Starts recording the rendered frames into [RECORDINGS_DIR], replacing the current recording if there is one.
+    ///
+    /// The frames are piped to `ffmpeg` to encode an mp4 video if it's installed, and saved as a sequence of PNGs otherwise.
+    /// Fails if the name isn't a plain file name, as it may come from a module sent by the server.
+    pub fn start_recording(&self, settings: RecordingSettings) -> anyhow::Result<()> {
+        let name = validate_name(settings.name.clone().unwrap_or_else(|| timestamp("recording")))?;
+        let (sender, receiver) = flume::unbounded();
+        ambient_sys::task::spawn(write_recording(PathBuf::from(RECORDINGS_DIR).join(name), settings.fps, receiver));
+        log::info!("Started recording at {} fps", settings.fps);
+        self.0.lock().recording = Some(Recording { settings, started: Instant::now(), frames: 0, sender });
+        Ok(())
+    }
+    pub fn stop_recording(&self) {
+        if self.0.lock().recording.take().is_some() {
+            log::info!("Stopped recording");
+        }
+    }
+    pub fn is_recording(&self) -> bool {
+        self.0.lock().recording.is_some()
+    }
+
+    /// Copies `target` in `encoder` if the frame is needed for a screenshot or a recording
+    pub fn on_frame(
+        &self,
+        target: &RenderTarget,
+        encoder: &mut wgpu::CommandEncoder,
+        post_submit: &mut Vec<Box<dyn FnOnce() + Send + Send>>,
+    ) {
+        let mut state = self.0.lock();
+        let pending_frames = std::mem::take(&mut state.pending_frames);
+
+        let mut repeat = 0;
+        if let Some(recording) = &mut state.recording {
+            let elapsed = recording.started.elapsed();
+            if recording.settings.duration.map_or(false, |duration| elapsed > duration) {
+                state.recording = None;
+                log::info!("Finished recording");
+            } else {
+                // The frames which should have been captured by now, so that the recording plays at its fps even if the game doesn't
+                let frames = (elapsed.as_secs_f32() * recording.settings.fps) as usize + 1;
+                repeat = frames.saturating_sub(recording.frames);
+                recording.frames = frames;
+            }
+        }
+        if pending_frames.is_empty() && repeat == 0 {
+            return;
+        }
+
+        let reader = target.color_buffer.reader_with_encoder(encoder);
+        let (image_sender, image_receiver) = futures::channel::oneshot::channel();
+        if repeat > 0 {
+            if let Some(recording) = &state.recording {
+                recording.sender.send((image_receiver.map(|image| image.ok().flatten()).boxed(), repeat)).ok();
+            }
+        }
+        post_submit.push(Box::new(move || {
+            ambient_sys::task::spawn(async move {
+                let image = reader.read_image().await;
+                if let Some(image) = &image {
+                    let rgba = image.to_rgba8();
+                    for on_captured in pending_frames {
+                        on_captured(rgba.clone());
+                    }
+                }
+                image_sender.send(image).ok();
+            });
+        }));
+    }
+}
+
+fn timestamp(prefix: &str) -> String {
+    format!("{prefix}_{}", SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_millis())
+}
+
+/// Checks that `name` is a single plain file name, so that the capture can't be written outside of its directory
+fn validate_name(name: String) -> anyhow::Result<String> {
+    let mut components = Path::new(&name).components();
+    anyhow::ensure!(
+        matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) && !name.contains(['/', '\\']),
+        "Invalid capture name {name:?}; it must be a plain file name"
+    );
+    Ok(name)
+}
+
+async fn write_recording(path: PathBuf, fps: f32, frames: flume::Receiver<(FrameFuture, usize)>) {
+    let mut size = None;
+    let mut ffmpeg = None;
+    let mut index = 0;
+    while let Ok((frame, repeat)) = frames.recv_async().await {
+        let Some(image) = frame.await else { continue };
+        let mut image = image.into_rgba8();
+        let (width, height) = *size.get_or_insert(image.dimensions());
+        if image.dimensions() != (width, height) {
+            image = image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle);
+        }
+
+        if index == 0 {
+            ffmpeg = spawn_ffmpeg(&path, width, height, fps);
+        }
+        for _ in 0..repeat {
+            if let Some(stdin) = ffmpeg.as_mut().and_then(|ffmpeg| ffmpeg.stdin.as_mut()) {
+                if let Err(err) = stdin.write_all(image.as_raw()).await {
+                    log::error!("Failed to write to ffmpeg: {err}");
+                    return;
+                }
+            } else {
+                let frame_path = path.join(format!("frame_{index:05}.png"));
+                if let Err(err) = image.save(&frame_path) {
+                    log::error!("Failed to save frame to {frame_path:?}: {err}");
+                    return;
+                }
+            }
+            index += 1;
+        }
+    }
+
+    if let Some(mut ffmpeg) = ffmpeg {
+        // Closing stdin ends the video
+        drop(ffmpeg.stdin.take());
+        match ffmpeg.wait().await {
+            Ok(status) if status.success() => log::info!("Saved recording to {:?}", path.with_extension("mp4")),
+            Ok(status) => log::error!("ffmpeg failed to encode the recording: {status}"),
+            Err(err) => log::error!("ffmpeg failed to encode the recording: {err}"),
+        }
+    } else if index > 0 {
+        log::info!("Saved recording to {path:?}");
+    }
+}
+
+/// Starts `ffmpeg` to encode raw RGBA frames from its stdin into an mp4 video, or creates the directory for the frames if
+/// it can't be started
+fn spawn_ffmpeg(path: &std::path::Path, width: u32, height: u32, fps: f32) -> Option<tokio::process::Child> {
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-f", "rawvideo", "-pix_fmt", "rgba", "-s", &format!("{width}x{height}"), "-r", &fps.to_string(), "-i", "-"])
+        // H.264 needs even dimensions
+        .args(["-vf", "scale=trunc(iw/2)*2:trunc(ih/2)*2", "-pix_fmt", "yuv420p"])
+        .arg(path.with_extension("mp4"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match result {
+        Ok(ffmpeg) => Some(ffmpeg),
+        Err(err) => {
+            log::warn!("Failed to start ffmpeg ({err}), saving the recording as a sequence of PNGs instead");
+            if let Err(err) = std::fs::create_dir_all(path) {
+                log::error!("Failed to create {path:?}: {err}");
+            }
+            None
+        }
+    }
+}
@@ -14,9 +14,10 @@ use ambient_core::{
 };
 use ambient_ecs::{components, query, world_events, Entity, EntityId, Resource, SystemGroup, World, WorldDiff, WorldEventReader};
 use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
+use ambient_event_types::SETTINGS_CHANGED;
 use ambient_renderer::RenderTarget;
 use ambient_rpc::RpcRegistry;
-use ambient_std::{cb, fps_counter::FpsSample, log_result, to_byte_unit, CallbackFn, Cb};
+use ambient_std::{cb, download_asset::load_build_metadata, fps_counter::FpsSample, log_result, to_byte_unit, CallbackFn, Cb};
 use ambient_ui::{Button, Centered, FlowColumn, FlowRow, Image, Text, Throbber};
 use anyhow::Context;
 use futures::{io::BufReader, AsyncBufReadExt, AsyncReadExt, Future, StreamExt};
@@ -32,7 +33,7 @@ use crate::{
     is_remote_entity, log_network_result,
     protocol::{ClientInfo, ClientProtocol},
     rpc_request,
-    server::{ServerInfo, SharedServerState},
+    server::{ServerInfo, ServerStatsSample, SharedServerState},
     NetworkError,
 };
 
@@ -140,6 +141,10 @@ pub type InitCallback = Box<dyn FnOnce(&mut World, Arc<RenderTarget>) + Send + S
 pub struct GameClientView {
     pub server_addr: SocketAddr,
     pub user_id: String,
+    /// Sent to servers that authenticate players
+    pub auth_token: Option<String>,
+    /// Join without a player character, to watch; see `spectator`
+    pub spectator: bool,
     pub resolution: UVec2,
     pub systems_and_resources: Cb<dyn Fn() -> (SystemGroup, Entity) + Sync + Send>,
     pub init_world: Cb<UseOnce<InitCallback>>,
@@ -158,6 +163,8 @@ impl Clone for GameClientView {
         Self {
             server_addr: self.server_addr,
             user_id: self.user_id.clone(),
+            auth_token: self.auth_token.clone(),
+            spectator: self.spectator,
             resolution: self.resolution,
             systems_and_resources: self.systems_and_resources.clone(),
             init_world: self.init_world.clone(),
@@ -178,6 +185,8 @@ impl ElementComponent for GameClientView {
         let Self {
             server_addr,
             user_id,
+            auth_token,
+            spectator,
             resolution,
             init_world,
             error_view,
@@ -227,9 +236,14 @@ impl ElementComponent for GameClientView {
 
                 mirror_window_components(app_world, &mut game_state.world);
                 // Pipe events from app world to game world
+                let mut settings_changed = false;
                 for (_, event) in world_event_reader.lock().iter(app_world.resource(world_events())) {
+                    settings_changed |= event.0 == SETTINGS_CHANGED;
                     game_state.world.resource_mut(world_events()).add_event(event.clone());
                 }
+                if settings_changed {
+                    game_state.apply_settings(app_world);
+                }
 
                 game_state.on_frame(&render_target);
             });
@@ -262,6 +276,16 @@ impl ElementComponent for GameClientView {
                     let mut on_init = {
                         let game_state = game_state.clone();
                         move |conn, client_info: ClientInfo, server_info: ServerInfo| {
+                            if let Some(base_url) = server_info.content_base_url {
+                                let assets = assets.clone();
+                                tokio::spawn(async move {
+                                    if let Err(err) = load_build_metadata(&assets, &base_url).await {
+                                        log::warn!("Failed to load the build metadata, downloaded assets won't be verified: {err:?}");
+                                    }
+                                });
+                            }
+
+                            game_state.lock().set_user_id(client_info.user_id.clone());
                             let game_client =
                                 GameClient::new(conn, Arc::new(create_rpc_registry()), game_state.clone(), client_info.user_id);
 
@@ -295,6 +319,8 @@ impl ElementComponent for GameClientView {
                         set_connection_status,
                         server_addr,
                         user_id,
+                        auth_token,
+                        spectator,
                         on_init: &mut on_init,
                         on_diff: &mut on_diff,
                         on_server_stats: &mut on_server_stats,
@@ -342,7 +368,7 @@ impl ElementComponent for GameClientView {
             hooks.provide_context(|| GameClientRenderTarget(render_target.clone()));
             hooks.world.add_resource(self::game_client(), Some(game_client.clone()));
 
-            Image { texture: Some(Arc::new(render_target.color_buffer.create_view(&Default::default()))) }.el().children(vec![ui])
+            Image::new(Some(Arc::new(render_target.color_buffer.create_view(&Default::default())))).el().children(vec![ui])
         } else {
             Centered(vec![FlowColumn::el([
                 FlowRow::el([Text::el(connection_status), Throbber.el()]),
@@ -357,6 +383,8 @@ struct ClientInstance<'a> {
     set_connection_status: CallbackFn<String>,
     server_addr: SocketAddr,
     user_id: String,
+    auth_token: Option<String>,
+    spectator: bool,
 
     /// Called when the client connected and received the world.
     on_init: &'a mut (dyn FnMut(Connection, ClientInfo, ServerInfo) -> anyhow::Result<Box<dyn FnOnce() + Sync + Send>> + Send + Sync),
@@ -388,11 +416,12 @@ impl<'a> ClientInstance<'a> {
         (self.set_connection_status)("Waiting for server to respond".to_string());
 
         // Set up the protocol.
-        let mut protocol = ClientProtocol::new(conn, self.user_id.clone()).await?;
+        let mut protocol = ClientProtocol::new(conn, self.user_id.clone(), self.auth_token.clone(), self.spectator).await?;
 
         let stats_interval = 5;
         let mut stats_timer = tokio::time::interval(Duration::from_secs_f32(stats_interval as f32));
         let mut prev_stats = protocol.connection().stats();
+        let mut component_bandwidth = Vec::new();
 
         // The first WorldDiff initializes the world, so wait for that until we say things are "ready"
         (self.set_connection_status)("Receiving world".to_string());
@@ -414,17 +443,26 @@ impl<'a> ClientInstance<'a> {
                 }
                 _ = stats_timer.tick() => {
                     let stats = protocol.connection().stats();
+                    let sent_packets = stats.path.sent_packets - prev_stats.path.sent_packets;
+                    let lost_packets = stats.path.lost_packets - prev_stats.path.lost_packets;
 
                     (self.on_client_stats)(GameClientNetworkStats {
                         latency_ms: protocol.connection().rtt().as_millis() as u64,
                         bytes_sent: (stats.udp_tx.bytes - prev_stats.udp_tx.bytes) / stats_interval,
                         bytes_received: (stats.udp_rx.bytes - prev_stats.udp_rx.bytes) / stats_interval,
+                        datagrams_sent: (stats.udp_tx.datagrams - prev_stats.udp_tx.datagrams) / stats_interval,
+                        datagrams_received: (stats.udp_rx.datagrams - prev_stats.udp_rx.datagrams) / stats_interval,
+                        packet_loss: if sent_packets > 0 { lost_packets as f32 / sent_packets as f32 } else { 0. },
+                        component_bandwidth: component_bandwidth.clone(),
                     });
 
                     prev_stats = stats;
                 }
-                Ok(stats) = protocol.stat_stream.next() => {
-                    (self.on_server_stats)(GameClientServerStats(stats));
+                Ok(stats) = protocol.stat_stream.next::<ServerStatsSample>() => {
+                    let duration = stats.fps.duration.as_secs_f64().max(f64::EPSILON);
+                    component_bandwidth =
+                        stats.diff_bytes_by_component.into_iter().map(|(path, bytes)| (path, (bytes as f64 / duration) as u64)).collect();
+                    (self.on_server_stats)(GameClientServerStats(stats.fps));
                 }
                 Some(Ok(msg)) = protocol.conn.uni_streams.next() => {
                     let mut reader = BufReader::new(msg);
@@ -444,11 +482,17 @@ impl<'a> ClientInstance<'a> {
 }
 
 /// Set up and manage a connection to the server
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct GameClientNetworkStats {
     pub latency_ms: u64,
     pub bytes_sent: u64,
     pub bytes_received: u64,
+    pub datagrams_sent: u64,
+    pub datagrams_received: u64,
+    /// The fraction of the packets sent by the client that were lost
+    pub packet_loss: f32,
+    /// The bytes per second of world diffs received, per component path, ordered by the most bandwidth used first
+    pub component_bandwidth: Vec<(String, u64)>,
 }
 
 impl Display for GameClientNetworkStats {
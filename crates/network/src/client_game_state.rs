@@ -12,7 +12,9 @@ use ambient_core::{
 use ambient_ecs::{components, query, Entity, FrameEvent, System, SystemGroup, World};
 use ambient_gizmos::render::GizmoRenderer;
 use ambient_gpu::gpu::GpuKey;
-use ambient_renderer::{RenderTarget, Renderer, RendererConfig, RendererTarget};
+use ambient_input::files::files;
+use ambient_renderer::{cameras::CameraRenderers, RenderTarget, Renderer, RendererConfig, RendererTarget};
+use ambient_settings::{settings, RenderSettings};
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
     color::Color,
@@ -21,6 +23,8 @@ use ambient_std::{
 };
 use glam::{vec2, Mat4, Vec2, Vec3, Vec3Swizzles};
 
+use crate::capture::{capture, Capture};
+
 use ambient_core::player::{player, user_id};
 
 components!("rendering", {
@@ -36,6 +40,8 @@ pub struct ClientGameState {
     gpu_world_sync_systems: SystemGroup<GpuWorldSyncEvent>,
     pub renderer: Renderer,
     pub ui_renderer: Renderer,
+    camera_renderers: CameraRenderers,
+    render_settings: RenderSettings,
     assets: AssetCache,
     user_id: String,
 }
@@ -56,16 +62,25 @@ impl ClientGameState {
         client_resources: Entity,
     ) -> Self {
         let mut game_world = World::new("client_game_world");
-        let local_resources = world_instance_resources(AppResources::from_world(world))
+        let mut local_resources = world_instance_resources(AppResources::from_world(world))
             .with(ambient_core::player::local_user_id(), player_id.clone())
             .with(game_screen_render_target(), render_target)
+            .with(capture(), Capture::default())
             .with_merge(client_resources);
+        // Shared with the app world, so that the files of the file events piped from it can be read
+        if let Some(shared_files) = world.resource_opt(files()).cloned() {
+            local_resources.set(files(), shared_files);
+        }
+        // Copied from the app world, so that packages can read their settings
+        if let Some(app_settings) = world.resource_opt(settings()).cloned() {
+            local_resources.set(settings(), app_settings);
+        }
         game_world.add_components(game_world.resource_entity(), local_resources).unwrap();
 
         let systems = SystemGroup::new("game", vec![Box::new(client_systems), Box::new(world_instance_systems(true))]);
-        let mut renderer =
-            Renderer::new(world, assets.clone(), RendererConfig { scene: main_scene(), shadows: true, ..Default::default() });
-        renderer.post_transparent = Some(Box::new(GizmoRenderer::new(&assets)));
+        let render_settings = world.resource_opt(settings()).map(|settings| settings.render.clone()).unwrap_or_default();
+        let renderer = Self::create_renderer(world, &assets, &render_settings);
+        let camera_renderers = CameraRenderers::new(&assets, Self::renderer_config(&render_settings));
 
         let ui_renderer = Renderer::new(world, assets.clone(), RendererConfig { scene: ui_scene(), shadows: false, ..Default::default() });
 
@@ -76,10 +91,43 @@ impl ClientGameState {
             gpu_world_sync_systems: gpu_world_sync_systems(),
             renderer,
             ui_renderer,
+            camera_renderers,
+            render_settings,
             assets,
             user_id: player_id,
         }
     }
+    fn renderer_config(render_settings: &RenderSettings) -> RendererConfig {
+        RendererConfig { scene: main_scene(), shadows: true, ..Default::default() }.with_render_settings(render_settings)
+    }
+    fn create_renderer(world: &mut World, assets: &AssetCache, render_settings: &RenderSettings) -> Renderer {
+        // Only the main renderer picks, as the cursor isn't over the targets of the camera renderers, and only it captures the
+        // reflection probes, which the camera renderers can do without
+        let config =
+            RendererConfig { gpu_picking: render_settings.gpu_picking, reflection_probes: true, ..Self::renderer_config(render_settings) };
+        let mut renderer = Renderer::new(world, assets.clone(), config);
+        renderer.graph.add_pass(GizmoRenderer::render_pass_desc(), Box::new(GizmoRenderer::new(assets))).unwrap();
+        renderer
+    }
+    /// Switches the local player to the user id the server accepted the client as, which differs from the one it asked
+    /// for if the server authenticates players
+    pub fn set_user_id(&mut self, user_id: String) {
+        self.world.add_resource(ambient_core::player::local_user_id(), user_id.clone());
+        self.user_id = user_id;
+    }
+    /// Copies the settings of the app world into the game world, and recreates the renderer if the render settings have changed
+    /// since it was created
+    pub fn apply_settings(&mut self, world: &mut World) {
+        if let Some(app_settings) = world.resource_opt(settings()).cloned() {
+            self.world.add_resource(settings(), app_settings);
+        }
+        let render_settings = world.resource_opt(settings()).map(|settings| settings.render.clone()).unwrap_or_default();
+        if render_settings != self.render_settings {
+            self.renderer = Self::create_renderer(world, &self.assets, &render_settings);
+            self.camera_renderers = CameraRenderers::new(&self.assets, Self::renderer_config(&render_settings));
+            self.render_settings = render_settings;
+        }
+    }
     #[profiling::function]
     pub fn on_frame(&mut self, target: &RenderTarget) {
         self.world.next_frame();
@@ -90,6 +138,7 @@ impl ClientGameState {
         let gpu = GpuKey.get(&self.assets);
         let mut encoder = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("GameState.render") });
         let mut post_submit = Vec::new();
+        self.camera_renderers.render_textures(&mut self.world, &mut encoder, &mut post_submit);
         self.renderer.render(
             &mut self.world,
             &mut encoder,
@@ -97,7 +146,9 @@ impl ClientGameState {
             RendererTarget::Target(target),
             Some(Color::rgba(0., 0., 0., 1.)),
         );
+        self.camera_renderers.render_viewports(&mut self.world, &mut encoder, &mut post_submit, target);
         self.ui_renderer.render(&mut self.world, &mut encoder, &mut post_submit, RendererTarget::Target(target), None);
+        self.world.resource(capture()).on_frame(target, &mut encoder, &mut post_submit);
         gpu.queue.submit(Some(encoder.finish()));
         for action in post_submit {
             action();
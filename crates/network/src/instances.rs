@@ -0,0 +1,366 @@
+use std::{sync::Arc, time::Duration};
+
+use ambient_core::{
+    determinism::determinism,
+    player::{display_name, is_authenticated, spectator, user_id},
+};
+use ambient_ecs::{
+    components, query, world_events, Debuggable, Description, Entity, Name, Networked, Resource, Store, System, World, WorldDiff,
+};
+use anyhow::{bail, ensure, Context};
+use parking_lot::Mutex;
+
+use crate::{
+    server::{
+        create_player_entity_data, create_spectator_entity_data, player_entity_stream, player_event_stream, player_stats_stream,
+        ForkingEvent, ServerState, WorldInstance, MAIN_INSTANCE_ID,
+    },
+    ServerWorldExt,
+};
+
+components!("network", {
+    @[Debuggable, Resource]
+    instance_id: String,
+    @[Resource]
+    instance_requests: InstanceRequests,
+
+    @[Debuggable, Networked, Store, Name["Source instance"], Description["The id of the world instance a message was sent from. Added to the data of events sent with `instance::send`."]]
+    source_instance: String,
+});
+
+/// A change to the server's instances, requested from inside one of them; see [request_instance_change]
+#[derive(Debug, Clone)]
+pub enum InstanceRequest {
+    /// Creates an instance by forking `source`, without its players
+    Create {
+        id: String,
+        source: String,
+        tick_rate: Option<f32>,
+    },
+    /// Moves the instance's players to the main instance, and removes it
+    Destroy {
+        id: String,
+    },
+    TransferPlayer {
+        user_id: String,
+        instance: String,
+    },
+    /// Sends the `name` event with `data` to the `target` instance
+    Message {
+        source: String,
+        target: String,
+        name: String,
+        data: Entity,
+    },
+}
+
+#[derive(Debug, Default)]
+struct InstanceRequestsState {
+    ids: Vec<String>,
+    requests: Vec<InstanceRequest>,
+}
+
+/// Shared by every instance of a server. Requests are handled after all instances have been stepped.
+#[derive(Debug, Clone, Default)]
+pub struct InstanceRequests(Arc<Mutex<InstanceRequestsState>>);
+impl InstanceRequests {
+    pub fn push(&self, request: InstanceRequest) {
+        self.0.lock().requests.push(request);
+    }
+    /// The ids of the instances, as of the end of the last server tick
+    pub fn ids(&self) -> Vec<String> {
+        self.0.lock().ids.clone()
+    }
+    fn take(&self) -> Vec<InstanceRequest> {
+        std::mem::take(&mut self.0.lock().requests)
+    }
+    fn set_ids(&self, mut ids: Vec<String>) {
+        ids.sort();
+        self.0.lock().ids = ids;
+    }
+}
+
+/// The id of the instance `world` is, or the main instance if it's not a server instance
+pub fn get_instance_id(world: &World) -> String {
+    world.resource_opt(instance_id()).cloned().unwrap_or_else(|| MAIN_INSTANCE_ID.to_string())
+}
+
+/// Requests a change to the server's instances; it's applied at the end of the current server tick
+pub fn request_instance_change(world: &World, request: InstanceRequest) -> anyhow::Result<()> {
+    world.resource_opt(instance_requests()).context("This world is not a server instance")?.push(request);
+    Ok(())
+}
+
+/// Sends the `name` event with `data` to the `target` instance; it's received there with a `source_instance` component
+pub fn send_instance_message(world: &World, target: String, name: String, data: Entity) -> anyhow::Result<()> {
+    request_instance_change(world, InstanceRequest::Message { source: get_instance_id(world), target, name, data })
+}
+
+impl ServerState {
+    /// Adds `instance`, replacing the instance with the same id if there is one
+    pub fn insert_instance(&mut self, id: String, mut instance: WorldInstance) {
+        instance.world.add_resource(instance_id(), id.clone());
+        instance.world.add_resource(instance_requests(), self.instance_requests.clone());
+        self.instances.insert(id, instance);
+    }
+
+    /// Creates the `id` instance by cloning the `source` instance without its players, and adding `resources` and
+    /// `synced_resources` to it
+    pub fn fork_instance(&mut self, source: &str, id: String, resources: Entity, synced_resources: Entity) -> anyhow::Result<()> {
+        ensure!(!self.instances.contains_key(&id), "An instance with the id {id:?} already exists");
+        let from = self.instances.get(source).with_context(|| format!("No instance with the id {source:?}"))?;
+        let mut world = from.world.clone();
+        let world_stream = from.world_stream.clone();
+
+        for (id, _) in query(user_id()).collect_cloned(&world, None) {
+            world.despawn(id);
+        }
+        world.add_components(world.resource_entity(), resources.with_merge(ambient_core::async_ecs::async_ecs_resources()))?;
+        if let Some(synced_resource_entity) = world.synced_resource_entity() {
+            world.add_components(synced_resource_entity, synced_resources)?;
+        }
+
+        let mut on_forking = (self.create_on_forking_systems)();
+        on_forking.run(&mut world, &ForkingEvent);
+
+        world.reset_events();
+        if let Some(determinism) = world.resource_opt(determinism()).copied() {
+            determinism.fork(&id).reseed(&mut world);
+        }
+
+        let systems = (self.create_server_systems)(&mut world);
+        self.insert_instance(id, WorldInstance::new(world, world_stream, systems));
+        Ok(())
+    }
+
+    /// Creates an instance that stays around when it has no players. See [ServerState::fork_instance].
+    pub fn create_instance(&mut self, id: String, source: &str, tick_rate: Option<f32>) -> anyhow::Result<()> {
+        if let Some(tick_rate) = tick_rate {
+            ensure!(tick_rate > 0., "The tick rate must be positive");
+        }
+        self.fork_instance(source, id.clone(), Entity::new(), Entity::new())?;
+        let instance = self.instances.get_mut(&id).unwrap();
+        instance.remove_when_empty = false;
+        instance.tick_interval = tick_rate.map(|tick_rate| Duration::from_secs_f32(1. / tick_rate));
+        log::info!("Created instance {id}");
+        Ok(())
+    }
+
+    /// Moves the players of the instance to the main instance, and removes it
+    pub fn destroy_instance(&mut self, id: &str) -> anyhow::Result<()> {
+        ensure!(id != MAIN_INSTANCE_ID, "The main instance can't be destroyed");
+        ensure!(self.instances.contains_key(id), "No instance with the id {id:?}");
+        let players =
+            self.players.iter().filter(|(_, player)| player.instance == id).map(|(user_id, _)| user_id.clone()).collect::<Vec<_>>();
+        for user_id in players {
+            self.transfer_player(&user_id, MAIN_INSTANCE_ID)?;
+        }
+        if self.instances.contains_key(id) {
+            self.remove_instance(id);
+        }
+        log::info!("Destroyed instance {id}");
+        Ok(())
+    }
+
+    /// Moves the player to the `new_instance_id` instance, and sends them the difference between the two
+    pub fn transfer_player(&mut self, user_id: &str, new_instance_id: &str) -> anyhow::Result<()> {
+        let old_instance_id =
+            self.players.get(user_id).with_context(|| format!("No player with the user id {user_id:?}"))?.instance.clone();
+        if old_instance_id == new_instance_id {
+            return Ok(());
+        }
+
+        ensure!(self.instances.contains_key(new_instance_id), "No instance with the id {new_instance_id:?}");
+        // The player's local players go with them, before the old instance's players are counted
+        self.transfer_local_players(user_id, &old_instance_id, new_instance_id);
+
+        let instances = &mut self.instances;
+
+        // Borrow the new world mutably to broadcast its diffs.
+        instances.get_mut(new_instance_id).with_context(|| format!("No instance with the id {new_instance_id:?}"))?.broadcast_diffs();
+
+        // Borrow both worlds immutably to extract the old world's player count and the diff between the two, and
+        // to broadcast the latest diffs for the new instance.
+        let (old_player_count, remove_when_empty, diff) = {
+            let (old_instance, new_instance) = instances.get(&old_instance_id).zip(instances.get(new_instance_id)).unwrap();
+            (
+                old_instance.player_count(),
+                old_instance.remove_when_empty,
+                WorldDiff::from_a_to_b(old_instance.world_stream.filter().clone(), &old_instance.world, &new_instance.world),
+            )
+        };
+
+        // Borrow the old world mutably to remove the player and their streams. Everything else on the player entity is
+        // left behind, except for their identity.
+        let (entities_tx, events_tx, stats_tx, identity, is_spectator) = {
+            let Some(mut ed) = instances.get_mut(&old_instance_id).unwrap().despawn_player(user_id) else {
+                bail!("The player {user_id:?} is not in their instance");
+            };
+            (
+                ed.remove_self(player_entity_stream()).unwrap(),
+                ed.remove_self(player_event_stream()).unwrap(),
+                ed.remove_self(player_stats_stream()).unwrap(),
+                Entity::new()
+                    .with_opt(display_name(), ed.remove_self(display_name()))
+                    .with_opt(is_authenticated(), ed.remove_self(is_authenticated())),
+                ed.contains(spectator()),
+            )
+        };
+
+        // Borrow the new world mutably to spawn the player in with their old streams.
+        let create_entity_data = if is_spectator { create_spectator_entity_data } else { create_player_entity_data };
+        instances
+            .get_mut(new_instance_id)
+            .unwrap()
+            .spawn_player(create_entity_data(user_id, entities_tx.clone(), events_tx, stats_tx).with_merge(identity));
+        self.players.get_mut(user_id).unwrap().instance = new_instance_id.to_string();
+
+        let msg = bincode::serialize(&diff).unwrap();
+        entities_tx.send(msg).ok();
+
+        // Remove old instance
+        if old_player_count == 1 && remove_when_empty && old_instance_id != MAIN_INSTANCE_ID {
+            self.remove_instance(&old_instance_id);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn process_instance_requests(&mut self) {
+        for request in self.instance_requests.take() {
+            let result = match request {
+                InstanceRequest::Create { id, source, tick_rate } => self.create_instance(id, &source, tick_rate),
+                InstanceRequest::Destroy { id } => self.destroy_instance(&id),
+                InstanceRequest::TransferPlayer { user_id, instance } => self.transfer_player(&user_id, &instance),
+                InstanceRequest::Message { source, target, name, data } => match self.instances.get_mut(&target) {
+                    Some(instance) => {
+                        instance.world.resource_mut(world_events()).add_event((name, data.with(source_instance(), source)));
+                        Ok(())
+                    }
+                    None => Err(anyhow::anyhow!("No instance with the id {target:?} to send {name:?} to")),
+                },
+            };
+            if let Err(err) = result {
+                log::warn!("Failed to change instances: {err:?}");
+            }
+        }
+        self.instance_requests.set_ids(self.instances.keys().cloned().collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_core::name;
+    use ambient_ecs::EntityId;
+    use flume::Receiver;
+
+    use super::*;
+    use crate::server::Player;
+
+    fn init() {
+        ambient_ecs::init_components();
+        ambient_core::init_all_components();
+        crate::init_all_components();
+    }
+
+    /// Spawns a player in `instance`, and returns the receiver of the diffs sent to them
+    fn join(state: &mut ServerState, user_id: &str, instance: &str) -> Receiver<Vec<u8>> {
+        let (entities_tx, entities_rx) = flume::unbounded();
+        let (events_tx, _) = flume::unbounded();
+        let (stats_tx, _) = flume::unbounded();
+        state.instances.get_mut(instance).unwrap().spawn_player(create_player_entity_data(user_id, entities_tx, events_tx, stats_tx));
+        state.players.insert(user_id.to_string(), Player::new_local(instance.to_string()));
+        entities_rx
+    }
+
+    fn user_ids(state: &ServerState, instance: &str) -> Vec<String> {
+        let mut ids = query(user_id()).iter(&state.instances[instance].world, None).map(|(_, id)| id.clone()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    fn spawn_named(state: &mut ServerState, instance: &str, value: &str) -> EntityId {
+        Entity::new().with(name(), value.to_string()).spawn(&mut state.instances.get_mut(instance).unwrap().world)
+    }
+
+    #[test]
+    fn create_instance_forks_without_players() {
+        init();
+        let mut state = ServerState::new_local();
+        let _rx = join(&mut state, "a", MAIN_INSTANCE_ID);
+        let id = spawn_named(&mut state, MAIN_INSTANCE_ID, "tree");
+
+        state.create_instance("arena".to_string(), MAIN_INSTANCE_ID, Some(4.)).unwrap();
+        let arena = &state.instances["arena"];
+        assert_eq!(arena.world.get_ref(id, name()).unwrap(), "tree");
+        assert_eq!(get_instance_id(&arena.world), "arena");
+        assert!(!arena.remove_when_empty);
+        assert_eq!(arena.tick_interval, Some(Duration::from_millis(250)));
+        assert!(user_ids(&state, "arena").is_empty());
+        assert_eq!(user_ids(&state, MAIN_INSTANCE_ID), vec!["a"]);
+
+        assert!(state.create_instance("arena".to_string(), MAIN_INSTANCE_ID, None).is_err());
+        assert!(state.create_instance("other".to_string(), "missing", None).is_err());
+        assert!(state.create_instance("other".to_string(), MAIN_INSTANCE_ID, Some(0.)).is_err());
+        assert!(!state.instances.contains_key("other"));
+    }
+
+    #[test]
+    fn transfer_player_moves_the_player_and_sends_the_diff() {
+        init();
+        let mut state = ServerState::new_local();
+        let rx = join(&mut state, "a", MAIN_INSTANCE_ID);
+        state.create_instance("arena".to_string(), MAIN_INSTANCE_ID, None).unwrap();
+        spawn_named(&mut state, "arena", "only in the arena");
+
+        state.transfer_player("a", "arena").unwrap();
+        assert_eq!(state.players["a"].instance, "arena");
+        assert!(user_ids(&state, MAIN_INSTANCE_ID).is_empty());
+        assert_eq!(user_ids(&state, "arena"), vec!["a"]);
+        // The player is sent the difference between the two worlds
+        let _: WorldDiff = bincode::deserialize(&rx.try_recv().unwrap()).unwrap();
+
+        // Transferring to the same instance does nothing
+        state.transfer_player("a", "arena").unwrap();
+        assert!(rx.try_recv().is_err());
+
+        assert!(state.transfer_player("a", "missing").is_err());
+        assert!(state.transfer_player("b", MAIN_INSTANCE_ID).is_err());
+        assert_eq!(user_ids(&state, "arena"), vec!["a"]);
+    }
+
+    #[test]
+    fn forked_instances_are_removed_when_empty() {
+        init();
+        let mut state = ServerState::new_local();
+        let _rx = join(&mut state, "a", MAIN_INSTANCE_ID);
+        state.fork_instance(MAIN_INSTANCE_ID, "temporary".to_string(), Entity::new(), Entity::new()).unwrap();
+        state.create_instance("arena".to_string(), MAIN_INSTANCE_ID, None).unwrap();
+
+        state.transfer_player("a", "temporary").unwrap();
+        state.transfer_player("a", "arena").unwrap();
+        assert!(!state.instances.contains_key("temporary"));
+        // Created instances stay around
+        state.transfer_player("a", MAIN_INSTANCE_ID).unwrap();
+        assert!(state.instances.contains_key("arena"));
+    }
+
+    #[test]
+    fn destroy_instance_moves_players_to_main() {
+        init();
+        let mut state = ServerState::new_local();
+        let _a = join(&mut state, "a", MAIN_INSTANCE_ID);
+        let _b = join(&mut state, "b", MAIN_INSTANCE_ID);
+        state.create_instance("arena".to_string(), MAIN_INSTANCE_ID, None).unwrap();
+        state.transfer_player("a", "arena").unwrap();
+        state.transfer_player("b", "arena").unwrap();
+
+        state.destroy_instance("arena").unwrap();
+        assert!(!state.instances.contains_key("arena"));
+        assert_eq!(user_ids(&state, MAIN_INSTANCE_ID), vec!["a", "b"]);
+        assert!(state.players.values().all(|player| player.instance == MAIN_INSTANCE_ID));
+
+        assert!(state.destroy_instance("arena").is_err());
+        assert!(state.destroy_instance(MAIN_INSTANCE_ID).is_err());
+        assert!(state.instances.contains_key(MAIN_INSTANCE_ID));
+    }
+}
@@ -25,15 +25,26 @@ use server::SharedServerState;
 use thiserror::Error;
 use tokio::io::AsyncWriteExt;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+use validation::ClientMessage;
 
 pub type AsyncMutex<T> = tokio::sync::Mutex<T>;
+pub mod admin;
+pub mod auth;
+pub mod capture;
 pub mod client;
 pub mod client_game_state;
 pub mod events;
 pub mod hooks;
+pub mod instances;
+pub mod local_players;
+pub mod metrics;
 pub mod protocol;
 pub mod rpc;
 pub mod server;
+pub mod storage;
+pub mod thumbnail;
+pub mod time_control;
+pub mod validation;
 
 components!("network", {
     @[Resource]
@@ -45,7 +56,7 @@ components!("network", {
 
     /// Works like `world.resource_entity` for server worlds, except it's also persisted to disk, and synchronized to clients
     @[
-        Debuggable, Networked,
+        Debuggable, Networked, Store,
         Name["Persistent resources"],
         Description["If attached, this entity contains global resources that are persisted to disk and synchronized to clients."]
     ]
@@ -68,10 +79,15 @@ components!("network", {
 
 pub fn init_all_components() {
     init_components();
+    admin::init_components();
     client::init_components();
     events::init_components();
+    instances::init_components();
+    metrics::init_components();
     server::init_components();
+    validation::init_components();
     client_game_state::init_components();
+    capture::init_components();
 }
 
 pub trait ServerWorldExt {
@@ -165,6 +181,10 @@ pub fn register_rpc_bi_stream_handler(handlers: &mut BiStreamHandlers, rpc_regis
             tokio::spawn(async move {
                 let try_block = || async {
                     let req = recv.read_to_end(100_000_000).await?;
+                    let name = ambient_rpc::req_name(&req).unwrap_or_default().to_string();
+                    if !state.lock().validate_client_message(&user_id, ClientMessage::Rpc(name), &req) {
+                        return Ok(());
+                    }
                     let args = GameRpcArgs { state, user_id: user_id.to_string() };
                     let resp = rpc_registry.run_req(args, &req).await?;
                     send.write_all(&resp).await?;
@@ -195,6 +215,10 @@ pub enum NetworkError {
     WriteError(#[from] quinn::WriteError),
     #[error(transparent)]
     RpcError(#[from] RpcError),
+    #[error("The player is banned from this server")]
+    Banned,
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(String),
 }
 
 impl NetworkError {
@@ -0,0 +1,80 @@
+//! Extra players on one client, for splitscreen. They're players of their own, with the user id given by
+//! [local_player_user_id], but they have no connection: they're joined, left and sent input through their owner's.
+use ambient_core::player::{display_name, get_player_by_user_id, local_player_owner, local_player_user_id, player, user_id};
+use ambient_ecs::{dont_store, query, Entity, World};
+use anyhow::{ensure, Context};
+
+use crate::server::ServerState;
+
+/// How many players can play on one client, including its own
+pub const MAX_LOCAL_PLAYERS: u32 = 4;
+
+/// The user ids of the local players of `owner` in `world`
+pub fn get_local_players(world: &World, owner: &str) -> Vec<String> {
+    query((user_id(), local_player_owner()))
+        .incl(player())
+        .iter(world, None)
+        .filter(|(_, (_, o))| *o == owner)
+        .map(|(_, (id, _))| id.clone())
+        .collect()
+}
+
+fn local_player_entity_data(owner: &str, id: &str) -> Entity {
+    Entity::new()
+        .with(player(), ())
+        .with(user_id(), id.to_string())
+        .with(local_player_owner(), owner.to_string())
+        .with_default(dont_store())
+}
+
+impl ServerState {
+    /// Spawns the `index`th local player of `owner` in their instance, and returns its user id
+    pub fn join_local_player(&mut self, owner: &str, index: u32) -> anyhow::Result<String> {
+        ensure!(index > 0 && index < MAX_LOCAL_PLAYERS, "The index of a local player must be between 1 and {}", MAX_LOCAL_PLAYERS - 1);
+        let instance = self.get_player_world_instance_mut(owner).with_context(|| format!("No player with the user id {owner:?}"))?;
+        let id = local_player_user_id(owner, index);
+        ensure!(get_player_by_user_id(&instance.world, &id).is_none(), "{id:?} has already joined");
+
+        let owner_name =
+            get_player_by_user_id(&instance.world, owner).and_then(|owner| instance.world.get_cloned(owner, display_name()).ok());
+        instance.spawn_player(
+            local_player_entity_data(owner, &id).with_opt(display_name(), owner_name.map(|name| format!("{name} ({})", index + 1))),
+        );
+        log::info!("[{owner}] Local player {id} joined");
+        Ok(id)
+    }
+
+    /// Despawns the `index`th local player of `owner`
+    pub fn leave_local_player(&mut self, owner: &str, index: u32) -> anyhow::Result<()> {
+        let instance = self.get_player_world_instance_mut(owner).with_context(|| format!("No player with the user id {owner:?}"))?;
+        let id = local_player_user_id(owner, index);
+        instance.despawn_player(&id).with_context(|| format!("{id:?} hasn't joined"))?;
+        log::info!("[{owner}] Local player {id} left");
+        Ok(())
+    }
+
+    /// Moves the local players of `owner` from the `from` instance to the `to` instance. Like their owner, only their
+    /// identity is kept.
+    pub(crate) fn transfer_local_players(&mut self, owner: &str, from: &str, to: &str) {
+        let Some(from) = self.instances.get_mut(from) else { return };
+        let players = get_local_players(&from.world, owner)
+            .into_iter()
+            .filter_map(|id| {
+                let ed = from.despawn_player(&id)?;
+                Some((id, ed.get_cloned(display_name())))
+            })
+            .collect::<Vec<_>>();
+        let Some(to) = self.instances.get_mut(to) else { return };
+        for (id, name) in players {
+            to.spawn_player(local_player_entity_data(owner, &id).with_opt(display_name(), name));
+        }
+    }
+
+    /// Despawns the local players of `owner` from their instance
+    pub(crate) fn despawn_local_players(&mut self, owner: &str) {
+        let Some(instance) = self.get_player_world_instance_mut(owner) else { return };
+        for id in get_local_players(&instance.world, owner) {
+            instance.despawn_player(&id);
+        }
+    }
+}
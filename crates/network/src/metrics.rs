@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Arc, time::Duration};
+
+use ambient_ecs::{components, DynSystem, FrameEvent, Resource, System, World};
+use ambient_std::fps_counter::FpsSample;
+use ambient_sys::time::Instant;
+use parking_lot::Mutex;
+
+components!("network", {
+    @[Resource]
+    server_metrics: ServerMetrics,
+});
+
+#[derive(Debug, Default)]
+struct InstanceMetrics {
+    entities: usize,
+    players: usize,
+    tick_rate: f32,
+}
+
+#[derive(Debug, Default)]
+struct SystemMetrics {
+    count: u64,
+    total: Duration,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    frames: u64,
+    frame_time_total: Duration,
+    last_sample: Option<FpsSample>,
+    target_tick_rate: f32,
+    target_replication_rate: f32,
+    replications: u64,
+    last_replication_sample: Option<FpsSample>,
+    instances: BTreeMap<String, InstanceMetrics>,
+    systems: BTreeMap<&'static str, SystemMetrics>,
+    bytes_sent: BTreeMap<&'static str, u64>,
+    bytes_received: BTreeMap<&'static str, u64>,
+}
+
+/// Counters describing a running server, which can be rendered in the Prometheus text format with [ServerMetrics::render].
+///
+/// Cloning it gives a handle to the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct ServerMetrics(Arc<Mutex<MetricsState>>);
+impl ServerMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn record_frame(&self, frame_time: Duration, sample: Option<FpsSample>) {
+        let mut state = self.0.lock();
+        state.frames += 1;
+        state.frame_time_total += frame_time;
+        if sample.is_some() {
+            state.last_sample = sample;
+        }
+    }
+    /// Sets the rates the server is configured to simulate and send world diffs at, in frames per second
+    pub fn set_target_rates(&self, tick_rate: f32, replication_rate: f32) {
+        let mut state = self.0.lock();
+        state.target_tick_rate = tick_rate;
+        state.target_replication_rate = replication_rate;
+    }
+    pub fn record_replication(&self, sample: Option<FpsSample>) {
+        let mut state = self.0.lock();
+        state.replications += 1;
+        if sample.is_some() {
+            state.last_replication_sample = sample;
+        }
+    }
+    pub fn record_system(&self, name: &'static str, time: Duration) {
+        let mut state = self.0.lock();
+        let system = state.systems.entry(name).or_default();
+        system.count += 1;
+        system.total += time;
+    }
+    /// `tick_rate` is how many times per second the instance is stepped
+    pub fn record_instance(&self, instance_id: &str, entities: usize, players: usize, tick_rate: f32) {
+        self.0.lock().instances.insert(instance_id.to_string(), InstanceMetrics { entities, players, tick_rate });
+    }
+    /// Forgets the instances that are not in `instance_ids`
+    pub fn retain_instances<'a>(&self, instance_ids: impl IntoIterator<Item = &'a String>) {
+        let ids = instance_ids.into_iter().collect::<Vec<_>>();
+        self.0.lock().instances.retain(|id, _| ids.contains(&id));
+    }
+    /// `kind` is what the bytes were, like `diff` or `datagram`
+    pub fn add_bytes_sent(&self, kind: &'static str, bytes: usize) {
+        *self.0.lock().bytes_sent.entry(kind).or_default() += bytes as u64;
+    }
+    /// `kind` is what the bytes were, like `diff` or `datagram`
+    pub fn add_bytes_received(&self, kind: &'static str, bytes: usize) {
+        *self.0.lock().bytes_received.entry(kind).or_default() += bytes as u64;
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let state = self.0.lock();
+        let mut out = String::new();
+
+        metric_header(&mut out, "ambient_server_frames_total", "counter", "Number of simulation frames run");
+        writeln!(out, "ambient_server_frames_total {}", state.frames).unwrap();
+        metric_header(&mut out, "ambient_server_frame_seconds_total", "counter", "Time spent running simulation frames");
+        writeln!(out, "ambient_server_frame_seconds_total {}", state.frame_time_total.as_secs_f64()).unwrap();
+        if let Some(sample) = &state.last_sample {
+            metric_header(&mut out, "ambient_server_fps", "gauge", "Simulation frames per second, over the last sample");
+            writeln!(out, "ambient_server_fps {}", sample.fps()).unwrap();
+            metric_header(&mut out, "ambient_server_frame_time_seconds", "gauge", "Average active time per frame, over the last sample");
+            writeln!(out, "ambient_server_frame_time_seconds {}", sample.active_time.as_secs_f64() / sample.n_frames.max(1) as f64)
+                .unwrap();
+            metric_header(&mut out, "ambient_server_slowest_frame_seconds", "gauge", "Slowest frame, over the last sample");
+            writeln!(out, "ambient_server_slowest_frame_seconds {}", sample.slowest_frame.as_secs_f64()).unwrap();
+        }
+
+        metric_header(&mut out, "ambient_server_target_tick_rate", "gauge", "Simulation frames per second the server is configured to run");
+        writeln!(out, "ambient_server_target_tick_rate {}", state.target_tick_rate).unwrap();
+        metric_header(
+            &mut out,
+            "ambient_server_target_replication_rate",
+            "gauge",
+            "World diff broadcasts per second the server is configured to send",
+        );
+        writeln!(out, "ambient_server_target_replication_rate {}", state.target_replication_rate).unwrap();
+        metric_header(&mut out, "ambient_server_replications_total", "counter", "Number of times the world diffs were sent to the clients");
+        writeln!(out, "ambient_server_replications_total {}", state.replications).unwrap();
+        if let Some(sample) = &state.last_replication_sample {
+            metric_header(&mut out, "ambient_server_replication_rate", "gauge", "World diff broadcasts per second, over the last sample");
+            writeln!(out, "ambient_server_replication_rate {}", sample.fps()).unwrap();
+        }
+
+        metric_header(&mut out, "ambient_server_instances", "gauge", "Number of world instances");
+        writeln!(out, "ambient_server_instances {}", state.instances.len()).unwrap();
+        metric_header(&mut out, "ambient_server_entities", "gauge", "Number of entities, per world instance");
+        for (id, instance) in &state.instances {
+            writeln!(out, "ambient_server_entities{{instance=\"{}\"}} {}", escape(id), instance.entities).unwrap();
+        }
+        metric_header(&mut out, "ambient_server_players", "gauge", "Number of connected players, per world instance");
+        for (id, instance) in &state.instances {
+            writeln!(out, "ambient_server_players{{instance=\"{}\"}} {}", escape(id), instance.players).unwrap();
+        }
+        metric_header(&mut out, "ambient_server_instance_tick_rate", "gauge", "Simulation frames per second, per world instance");
+        for (id, instance) in &state.instances {
+            writeln!(out, "ambient_server_instance_tick_rate{{instance=\"{}\"}} {}", escape(id), instance.tick_rate).unwrap();
+        }
+
+        metric_header(&mut out, "ambient_server_system_runs_total", "counter", "Number of times each server system has run");
+        for (name, system) in &state.systems {
+            writeln!(out, "ambient_server_system_runs_total{{system=\"{}\"}} {}", escape(name), system.count).unwrap();
+        }
+        metric_header(&mut out, "ambient_server_system_seconds_total", "counter", "Time spent running each server system");
+        for (name, system) in &state.systems {
+            writeln!(out, "ambient_server_system_seconds_total{{system=\"{}\"}} {}", escape(name), system.total.as_secs_f64()).unwrap();
+        }
+
+        metric_header(&mut out, "ambient_server_sent_bytes_total", "counter", "Bytes sent to clients");
+        for (kind, bytes) in &state.bytes_sent {
+            writeln!(out, "ambient_server_sent_bytes_total{{kind=\"{kind}\"}} {bytes}").unwrap();
+        }
+        metric_header(&mut out, "ambient_server_received_bytes_total", "counter", "Bytes received from clients");
+        for (kind, bytes) in &state.bytes_received {
+            writeln!(out, "ambient_server_received_bytes_total{{kind=\"{kind}\"}} {bytes}").unwrap();
+        }
+        out
+    }
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} {kind}").unwrap();
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Wraps `system` so that its run time is recorded in the world's [server_metrics], if it has them
+pub fn timed_system(name: &'static str, system: DynSystem) -> DynSystem {
+    Box::new(TimedSystem { name, system })
+}
+
+#[derive(Debug)]
+struct TimedSystem {
+    name: &'static str,
+    system: DynSystem,
+}
+impl System for TimedSystem {
+    fn run(&mut self, world: &mut World, event: &FrameEvent) {
+        let start = Instant::now();
+        self.system.run(world, event);
+        if let Some(metrics) = world.resource_opt(server_metrics()) {
+            metrics.record_system(self.name, start.elapsed());
+        }
+    }
+}
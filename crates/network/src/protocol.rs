@@ -3,7 +3,12 @@ use anyhow::{Context, Result};
 use futures::{io::BufReader, StreamExt};
 use quinn::{NewConnection, RecvStream};
 
-use crate::{next_bincode_bi_stream, open_bincode_bi_stream, server::ServerInfo, IncomingStream, NetworkError, OutgoingStream};
+use crate::{
+    auth::{authenticate, Authentication},
+    next_bincode_bi_stream, open_bincode_bi_stream,
+    server::ServerInfo,
+    IncomingStream, NetworkError, OutgoingStream,
+};
 
 #[derive(Debug)]
 pub struct ClientProtocol {
@@ -16,15 +21,15 @@ pub struct ClientProtocol {
 }
 
 impl ClientProtocol {
-    pub async fn new(mut conn: NewConnection, player_id: String) -> Result<Self> {
+    pub async fn new(mut conn: NewConnection, player_id: String, auth_token: Option<String>, spectator: bool) -> Result<Self> {
         // Say who we are
-        // The server will respond appropriately and return things such as
-        // username (TODO)
+        // The server will verify the token, if it authenticates players, and respond with who we are to it
         let (mut tx, mut rx) = open_bincode_bi_stream(&conn.connection).await?;
-        tx.send(&player_id).await?;
+        tx.send(&ClientHello { user_id: player_id, auth_token, spectator }).await?;
 
         // The server will acknowledge and send the credentials back
-        let client_info: ClientInfo = rx.next().await?;
+        let client_info: Result<ClientInfo, String> = rx.next().await?;
+        let client_info = client_info.map_err(NetworkError::Unauthenticated)?;
         ComponentRegistry::get_mut().add_external(client_info.external_components.clone());
 
         let server_info: ServerInfo = rx.next().await?;
@@ -75,20 +80,39 @@ pub struct ServerProtocol {
 }
 
 impl ServerProtocol {
-    pub async fn new(mut conn: NewConnection, server_info: ServerInfo) -> Result<Self, NetworkError> {
+    pub async fn new(
+        mut conn: NewConnection,
+        server_info: ServerInfo,
+        authentication: Option<&Authentication>,
+    ) -> Result<Self, NetworkError> {
         // The client now sends the player id
         let (mut tx, mut rx) = next_bincode_bi_stream(&mut conn).await?;
 
-        let user_id: String = rx.next().await?;
+        let hello: ClientHello = rx.next().await?;
 
-        log::debug!("Received handshake from {user_id:?}");
+        log::debug!("Received handshake from {:?}", hello.user_id);
+
+        let identity = match authenticate(authentication, hello.user_id, hello.auth_token.as_deref()).await {
+            Ok(identity) => identity,
+            Err(err) => {
+                let reason = format!("{err:#}");
+                tx.send(&Err::<ClientInfo, _>(reason.clone())).await?;
+                return Err(NetworkError::Unauthenticated(reason));
+            }
+        };
 
         let external_components = ComponentRegistry::get().all_external().map(|x| x.0).collect();
 
         // Respond
-        let client_info = ClientInfo { user_id, external_components };
+        let client_info = ClientInfo {
+            user_id: identity.user_id,
+            display_name: identity.display_name,
+            authenticated: identity.authenticated,
+            spectator: hello.spectator,
+            external_components,
+        };
         log::debug!("Responding with {client_info:?}");
-        tx.send(&client_info).await?;
+        tx.send(&Ok::<_, String>(&client_info)).await?;
 
         // Send the project name to the client so it can title its window correctly
         tx.send(&server_info).await?;
@@ -112,15 +136,36 @@ impl ServerProtocol {
     }
 }
 
-/// Contains things such as username (TODO) and user_id
+/// The first message a client sends
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct ClientHello {
+    user_id: String,
+    /// A token proving who the player is, for servers that authenticate players
+    auth_token: Option<String>,
+    /// Join without a player character, to watch
+    spectator: bool,
+}
+
+/// Who the server has accepted the client as
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct ClientInfo {
+    /// Replaced by the verified user id if the server authenticates players
     pub user_id: String,
+    pub display_name: Option<String>,
+    /// If the server verified the player's identity
+    pub authenticated: bool,
+    /// If the client joined as a spectator, instead of as a player
+    pub spectator: bool,
     pub external_components: Vec<ExternalComponentDesc>,
 }
 
 impl std::fmt::Debug for ClientInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ClientInfo").field("user_id", &self.user_id).finish_non_exhaustive()
+        f.debug_struct("ClientInfo")
+            .field("user_id", &self.user_id)
+            .field("display_name", &self.display_name)
+            .field("authenticated", &self.authenticated)
+            .field("spectator", &self.spectator)
+            .finish_non_exhaustive()
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     ops::Range,
     sync::Arc,
@@ -7,16 +7,19 @@ use std::{
 };
 
 use ambient_core::{
-    asset_cache, no_sync,
-    player::{get_player_by_user_id, player},
+    asset_cache,
+    determinism::fixed_timestep,
+    no_sync,
+    player::{display_name, get_player_by_user_id, get_spectator_by_user_id, is_authenticated, player, spectator},
     project_name,
 };
 use ambient_ecs::{
-    components, dont_store, query, ArchetypeFilter, ComponentDesc, Entity, EntityId, FrameEvent, System, SystemGroup, World, WorldStream,
-    WorldStreamCompEvent, WorldStreamFilter,
+    components, dont_store, query, ArchetypeFilter, ComponentDesc, ComponentEntry, Entity, EntityId, FrameEvent, Resource, Serializable,
+    System, SystemGroup, World, WorldChange, WorldDiff, WorldStream, WorldStreamCompEvent, WorldStreamFilter,
 };
 use ambient_std::{
-    asset_cache::AssetCache,
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    asset_url::{AbsAssetUrl, ServerBaseUrlKey},
     fps_counter::{FpsCounter, FpsSample},
     friendly_id, log_result,
 };
@@ -25,28 +28,80 @@ use anyhow::bail;
 use bytes::Bytes;
 use flume::Sender;
 use futures::StreamExt;
+use itertools::Itertools;
 use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
 use quinn::{Endpoint, Incoming, NewConnection, RecvStream, SendStream};
 use serde::{Deserialize, Serialize};
 use tokio::{
     io::AsyncReadExt,
-    time::{interval, MissedTickBehavior},
+    time::{interval, interval_at, MissedTickBehavior},
 };
 use tracing::{debug_span, Instrument};
 
 use crate::{
+    admin::{AdminCommand, AdminHandle, AdminResult},
+    auth::Authentication,
     bi_stream_handlers, create_server, datagram_handlers,
+    instances::InstanceRequests,
+    metrics::{server_metrics, ServerMetrics},
     protocol::{ClientInfo, ServerProtocol},
-    uni_stream_handlers, NetworkError,
+    storage::WorldStorage,
+    thumbnail, uni_stream_handlers,
+    validation::{client_validators, ClientMessage, ClientValidators},
+    NetworkError,
 };
 
 components!("network", {
     player_entity_stream: Sender<Vec<u8>>,
     player_event_stream: Sender<Vec<u8>>,
-    player_stats_stream: Sender<FpsSample>,
+    player_stats_stream: Sender<ServerStatsSample>,
+    /// Which entities are replicated to this player. All of them are, if not attached
+    player_replication_filter: ReplicationFilter,
+
+    /// How often the server steps its instances
+    @[Resource]
+    server_tick_interval: Duration,
 });
 
+/// The stats which are periodically sent from the server to each client
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerStatsSample {
+    pub fps: FpsSample,
+    /// The number of bytes of world diffs that were sent to the client during the sample, per component path
+    pub diff_bytes_by_component: Vec<(String, u64)>,
+}
+
+/// Decides which entities are replicated to a player, see [player_replication_filter]. Replace the filter, rather than
+/// change what it captures, for the entities it now shows or hides to be sent or despawned
+#[derive(Clone)]
+pub struct ReplicationFilter(Arc<dyn Fn(&World, EntityId) -> bool + Sync + Send>);
+impl ReplicationFilter {
+    pub fn new(filter: impl Fn(&World, EntityId) -> bool + Sync + Send + 'static) -> Self {
+        Self(Arc::new(filter))
+    }
+    /// Replicates every entity
+    pub fn all() -> Self {
+        Self::new(|_, _| true)
+    }
+    pub fn is_replicated(&self, world: &World, id: EntityId) -> bool {
+        (self.0)(world, id)
+    }
+}
+impl std::fmt::Debug for ReplicationFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplicationFilter").finish_non_exhaustive()
+    }
+}
+
+/// What was replicated to a player with a [player_replication_filter]
+struct PlayerReplication {
+    /// The filter `hidden` was last updated with
+    filter: ReplicationFilter,
+    /// The entities the player doesn't have
+    hidden: HashSet<EntityId>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ForkingEvent;
 
@@ -60,13 +115,23 @@ pub struct WorldInstance {
     pub world: World,
     pub world_stream: WorldStream,
     pub systems: SystemGroup,
+    /// The number of bytes of world diffs broadcast per component path, since the last [ServerStatsSample]
+    pub diff_bytes_by_component: HashMap<String, u64>,
+    /// How often the instance is stepped, or as often as the server if `None`. Instances can't be stepped more often than the
+    /// server's tick rate
+    pub tick_interval: Option<Duration>,
+    last_tick: Option<Duration>,
+    /// Remove this instance when its last player leaves it
+    pub remove_when_empty: bool,
+    /// By player entity, for the players who have, or had, a [player_replication_filter]
+    replication: HashMap<EntityId, PlayerReplication>,
 }
 
 pub fn create_player_entity_data(
     user_id: &str,
     entities_tx: Sender<Vec<u8>>,
     events_tx: Sender<Vec<u8>>,
-    stats_tx: Sender<FpsSample>,
+    stats_tx: Sender<ServerStatsSample>,
 ) -> Entity {
     Entity::new()
         .with(ambient_core::player::player(), ())
@@ -77,40 +142,228 @@ pub fn create_player_entity_data(
         .with_default(dont_store())
 }
 
+/// Like [create_player_entity_data], but for a spectator, who receives the world without being a player
+pub fn create_spectator_entity_data(
+    user_id: &str,
+    entities_tx: Sender<Vec<u8>>,
+    events_tx: Sender<Vec<u8>>,
+    stats_tx: Sender<ServerStatsSample>,
+) -> Entity {
+    let mut ed = create_player_entity_data(user_id, entities_tx, events_tx, stats_tx);
+    ed.remove_self(player());
+    ed.with(spectator(), ())
+}
+
+/// The part of `diff` which is replicated to a player with `filter`, followed by the spawns and despawns of the entities the
+/// filter now shows or hides
+fn player_diff(
+    world: &World,
+    stream_filter: &WorldStreamFilter,
+    state: &mut PlayerReplication,
+    filter: &ReplicationFilter,
+    diff: &WorldDiff,
+) -> WorldDiff {
+    let hidden = &mut state.hidden;
+    let is_replicated = |id| world.exists(id) && filter.is_replicated(world, id);
+    let spawn = |id| WorldChange::Spawn(Some(id), stream_filter.read_entity_components(world, id).into());
+    let mut changes = Vec::new();
+    for change in &diff.changes {
+        match change {
+            WorldChange::Despawn(id) => {
+                if !hidden.remove(id) {
+                    changes.push(change.clone());
+                }
+            }
+            WorldChange::Spawn(Some(id), _) => {
+                if is_replicated(*id) {
+                    changes.push(change.clone());
+                } else {
+                    hidden.insert(*id);
+                }
+            }
+            WorldChange::Spawn(None, _) => changes.push(change.clone()),
+            WorldChange::AddComponents(id, _) | WorldChange::RemoveComponents(id, _) | WorldChange::Set(id, _) => {
+                match (hidden.contains(id), is_replicated(*id)) {
+                    (false, true) => changes.push(change.clone()),
+                    (false, false) => {
+                        hidden.insert(*id);
+                        changes.push(WorldChange::Despawn(*id));
+                    }
+                    (true, true) => {
+                        hidden.remove(id);
+                        changes.push(spawn(*id));
+                    }
+                    (true, false) => {}
+                }
+            }
+        }
+    }
+    if !Arc::ptr_eq(&state.filter.0, &filter.0) {
+        for id in stream_filter.all_entities(world) {
+            match (hidden.contains(&id), filter.is_replicated(world, id)) {
+                (false, false) => {
+                    hidden.insert(id);
+                    changes.push(WorldChange::Despawn(id));
+                }
+                (true, true) => {
+                    hidden.remove(&id);
+                    changes.push(spawn(id));
+                }
+                _ => {}
+            }
+        }
+        state.filter = filter.clone();
+    }
+    WorldDiff { changes }
+}
+
+/// The entity of the player or spectator with `user_id`
+pub fn get_connected_user_entity(world: &World, user_id: &str) -> Option<EntityId> {
+    get_player_by_user_id(world, user_id).or_else(|| get_spectator_by_user_id(world, user_id))
+}
+
+/// The components describing who the player is, as verified when they connected
+fn identity_entity_data(client: &ClientInfo) -> Entity {
+    Entity::new().with_opt(display_name(), client.display_name.clone()).with_opt(is_authenticated(), client.authenticated.then_some(()))
+}
+
 impl WorldInstance {
+    pub fn new(world: World, world_stream: WorldStream, systems: SystemGroup) -> Self {
+        Self {
+            world,
+            world_stream,
+            systems,
+            diff_bytes_by_component: Default::default(),
+            tick_interval: None,
+            last_tick: None,
+            remove_when_empty: true,
+            replication: Default::default(),
+        }
+    }
     /// Create server side player entity
     pub fn spawn_player(&mut self, ed: Entity) -> EntityId {
         ed.spawn(&mut self.world)
     }
+    /// Despawns the player or spectator with `user_id`
     pub fn despawn_player(&mut self, user_id: &str) -> Option<Entity> {
-        self.world.despawn(get_player_by_user_id(&self.world, user_id)?)
+        self.world.despawn(get_connected_user_entity(&self.world, user_id)?)
     }
     pub fn broadcast_diffs(&mut self) {
         let diff = self.world_stream.next_diff(&self.world);
-        if diff.is_empty() {
+        let world = &self.world;
+        self.replication.retain(|&id, _| world.has_component(id, player_entity_stream()));
+        if diff.is_empty() && self.replication.is_empty() && query(()).incl(player_replication_filter()).iter(world, None).next().is_none()
+        {
             return;
         }
-        let msg = bincode::serialize(&diff).unwrap();
+        let msg = (!diff.is_empty()).then(|| bincode::serialize(&diff).unwrap());
+        if msg.is_some() && self.player_count() > 0 {
+            add_diff_bytes_by_component(&diff, &mut self.diff_bytes_by_component);
+        }
 
         profiling::scope!("Send MsgEntities");
-        for (_, (entity_stream,)) in query((player_entity_stream(),)).iter(&self.world, None) {
-            let msg = msg.clone();
+        let metrics = self.world.resource_opt(server_metrics());
+        for (id, (entity_stream,)) in query((player_entity_stream(),)).iter(&self.world, None) {
+            let filter = self.world.get_ref(id, player_replication_filter()).ok();
+            let msg = if filter.is_none() && !self.replication.contains_key(&id) {
+                msg.clone()
+            } else {
+                // The client got every entity when it connected
+                let state = self
+                    .replication
+                    .entry(id)
+                    .or_insert_with(|| PlayerReplication { filter: ReplicationFilter::all(), hidden: HashSet::new() });
+                let diff = player_diff(
+                    &self.world,
+                    self.world_stream.filter(),
+                    state,
+                    &filter.cloned().unwrap_or_else(ReplicationFilter::all),
+                    &diff,
+                );
+                if filter.is_none() {
+                    self.replication.remove(&id);
+                }
+                (!diff.is_empty()).then(|| bincode::serialize(&diff).unwrap())
+            };
+            let Some(msg) = msg else { continue };
+            if let Some(metrics) = metrics {
+                metrics.add_bytes_sent("diff", msg.len());
+            }
             if let Err(_err) = entity_stream.send(msg) {
                 log::warn!("Failed to broadcast diff to player");
             }
         }
     }
+    /// Forgets what was replicated to `player`, who was just sent the whole world again
+    pub fn reset_replication(&mut self, player: EntityId) {
+        self.replication.remove(&player);
+    }
     pub fn player_count(&self) -> usize {
         query((player(),)).iter(&self.world, None).count()
     }
+    /// Sends the stats to all players in this instance, and starts a new sample
+    pub fn broadcast_stats(&mut self, fps: FpsSample) {
+        let mut diff_bytes_by_component = self.diff_bytes_by_component.drain().collect::<Vec<_>>();
+        diff_bytes_by_component.sort_by(|a, b| b.1.cmp(&a.1));
+        let sample = ServerStatsSample { fps, diff_bytes_by_component };
+        for (_, (stream,)) in query((player_stats_stream(),)).iter(&self.world, None) {
+            stream.send(sample.clone()).ok();
+        }
+    }
+    fn server_interval(&self) -> Duration {
+        self.world.resource_opt(server_tick_interval()).copied().unwrap_or(DEFAULT_SERVER_TICK_INTERVAL)
+    }
+    /// How often the instance is actually stepped, given the server's tick rate
+    pub fn effective_tick_interval(&self) -> Duration {
+        let server_interval = self.server_interval();
+        self.tick_interval.map_or(server_interval, |interval| interval.max(server_interval))
+    }
+    /// Runs the systems, unless the instance was stepped less than `tick_interval` ago
     pub fn step(&mut self, time: Duration) {
+        let server_interval = self.server_interval();
+        let tick_interval = self.effective_tick_interval();
+        if let Some(last_tick) = self.last_tick.filter(|_| tick_interval > server_interval) {
+            // Leave some leeway for the jitter of the server's own interval
+            if time.saturating_sub(last_tick) + server_interval / 2 < tick_interval {
+                return;
+            }
+        }
+        self.last_tick = Some(time);
+        let (time, dtime) = if let Some(&timestep) = self.world.resource_opt(fixed_timestep()) {
+            (*self.world.resource(ambient_core::time()) + Duration::from_secs_f32(timestep), timestep)
+        } else {
+            (time, tick_interval.as_secs_f32())
+        };
         self.world.set(self.world.resource_entity(), ambient_core::time(), time).unwrap();
+        ambient_core::update_dtime(&mut self.world, dtime);
         self.systems.run(&mut self.world, &FrameEvent);
         self.world.next_frame();
     }
 }
 
+fn add_diff_bytes_by_component(diff: &WorldDiff, bytes_by_component: &mut HashMap<String, u64>) {
+    profiling::scope!("add_diff_bytes_by_component");
+    let mut add = |entry: &ComponentEntry| {
+        if entry.has_attribute::<Serializable>() {
+            *bytes_by_component.entry(entry.desc().path()).or_default() += bincode::serialized_size(entry).unwrap_or_default();
+        }
+    };
+    for change in &diff.changes {
+        match change {
+            WorldChange::Spawn(_, data) | WorldChange::AddComponents(_, data) => data.iter().for_each(&mut add),
+            WorldChange::Set(_, entry) => add(entry),
+            WorldChange::Despawn(_) | WorldChange::RemoveComponents(_, _) => {}
+        }
+    }
+}
+
 pub const MAIN_INSTANCE_ID: &str = "main";
+/// How often the server steps its instances, unless it's configured otherwise
+pub const DEFAULT_SERVER_TICK_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 60);
+/// The changes to a world are only kept for [FramedEvents::HISTORY_SIZE] frames, so the diffs must be sent well within that
+///
+/// [FramedEvents::HISTORY_SIZE]: ambient_ecs::FramedEvents::HISTORY_SIZE
+const MAX_TICKS_PER_REPLICATION: u32 = 50;
 
 pub struct Player {
     pub instance: String,
@@ -132,6 +385,10 @@ pub type SharedServerState = Arc<Mutex<ServerState>>;
 pub struct ServerState {
     pub instances: HashMap<String, WorldInstance>,
     pub players: HashMap<String, Player>,
+    /// The user ids that can't connect
+    pub banned: HashSet<String>,
+    /// Shared with every instance, so that they can manage and message each other
+    pub instance_requests: InstanceRequests,
     pub create_server_systems: Arc<dyn Fn(&mut World) -> SystemGroup + Sync + Send>,
     pub create_on_forking_systems: Arc<dyn Fn() -> SystemGroup<ForkingEvent> + Sync + Send>,
     pub create_shutdown_systems: Arc<dyn Fn() -> SystemGroup<ShutdownEvent> + Sync + Send>,
@@ -142,14 +399,12 @@ impl ServerState {
         Self {
             instances: [(
                 MAIN_INSTANCE_ID.to_string(),
-                WorldInstance {
-                    world: World::new("main_server"),
-                    world_stream: WorldStream::new(world_stream_filter),
-                    systems: SystemGroup::new("", vec![]),
-                },
+                WorldInstance::new(World::new("main_server"), WorldStream::new(world_stream_filter), SystemGroup::new("", vec![])),
             )]
             .into(),
             players: Default::default(),
+            banned: Default::default(),
+            instance_requests: Default::default(),
             create_server_systems: Arc::new(|_| SystemGroup::new("", vec![])),
             create_on_forking_systems: Arc::new(|| SystemGroup::new("", vec![])),
             create_shutdown_systems: Arc::new(|| SystemGroup::new("", vec![])),
@@ -161,14 +416,28 @@ impl ServerState {
         create_on_forking_systems: Arc<dyn Fn() -> SystemGroup<ForkingEvent> + Sync + Send>,
         create_shutdown_systems: Arc<dyn Fn() -> SystemGroup<ShutdownEvent> + Sync + Send>,
     ) -> Self {
-        Self { instances, players: Default::default(), create_server_systems, create_on_forking_systems, create_shutdown_systems }
+        let mut state = Self {
+            instances: Default::default(),
+            players: Default::default(),
+            banned: Default::default(),
+            instance_requests: Default::default(),
+            create_server_systems,
+            create_on_forking_systems,
+            create_shutdown_systems,
+        };
+        for (id, instance) in instances {
+            state.insert_instance(id, instance);
+        }
+        state
     }
 
     pub fn step(&mut self) {
         let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
-        for instance in self.instances.values_mut() {
-            instance.step(time);
+        // In a stable order, so that runs are reproducible with a seed
+        for id in self.instances.keys().cloned().sorted() {
+            self.instances.get_mut(&id).unwrap().step(time);
         }
+        self.process_instance_requests();
     }
     pub fn broadcast_diffs(&mut self) {
         for instance in self.instances.values_mut() {
@@ -178,6 +447,14 @@ impl ServerState {
     pub fn player_count(&self) -> usize {
         self.instances.values().map(|i| i.player_count()).sum()
     }
+    /// Updates `metrics` with the current entity and player counts of each instance
+    pub fn record_metrics(&self, metrics: &ServerMetrics) {
+        for (id, instance) in &self.instances {
+            let tick_rate = 1. / instance.effective_tick_interval().as_secs_f32();
+            metrics.record_instance(id, instance.world.len(), instance.player_count(), tick_rate);
+        }
+        metrics.retain_instances(self.instances.keys());
+    }
     pub fn get_player_world_instance_mut(&mut self, user_id: &str) -> Option<&mut WorldInstance> {
         self.players.get(user_id).and_then(|player| self.instances.get_mut(&player.instance))
     }
@@ -205,6 +482,21 @@ pub struct GameServer {
     pub port: u16,
     /// Shuts down the server if there are no players
     pub use_inactivity_shutdown: bool,
+    /// Updated while the server runs; also added as a resource to the main instance's world
+    pub metrics: ServerMetrics,
+    /// If set, the main instance is saved periodically and when the server shuts down
+    pub storage: Option<WorldStorage>,
+    /// If set, connecting players are authenticated with it
+    pub authentication: Option<Authentication>,
+    /// Checks what clients send; also added as a resource to the main instance's world
+    pub validators: ClientValidators,
+    /// How often the instances are stepped
+    pub tick_interval: Duration,
+    /// How often the world diffs are sent to the clients. It's clamped to be at least `tick_interval`, and the diffs are sent
+    /// right after the instances are stepped when they're equal
+    pub replication_interval: Duration,
+    admin_tx: flume::Sender<(AdminCommand, flume::Sender<AdminResult>)>,
+    admin_rx: flume::Receiver<(AdminCommand, flume::Sender<AdminResult>)>,
 }
 impl GameServer {
     pub async fn new_with_port(port: u16, use_inactivity_shutdown: bool) -> anyhow::Result<Self> {
@@ -213,7 +505,21 @@ impl GameServer {
         let (endpoint, incoming) = create_server(server_addr)?;
 
         log::debug!("GameServer listening on port {}", port);
-        Ok(Self { _endpoint: endpoint, incoming, port, use_inactivity_shutdown })
+        let (admin_tx, admin_rx) = flume::unbounded();
+        Ok(Self {
+            _endpoint: endpoint,
+            incoming,
+            port,
+            use_inactivity_shutdown,
+            metrics: ServerMetrics::new(),
+            storage: None,
+            authentication: None,
+            validators: ClientValidators::default(),
+            tick_interval: DEFAULT_SERVER_TICK_INTERVAL,
+            replication_interval: DEFAULT_SERVER_TICK_INTERVAL,
+            admin_tx,
+            admin_rx,
+        })
     }
     pub async fn new_with_port_in_range(port_range: Range<u16>, use_inactivity_shutdown: bool) -> anyhow::Result<Self> {
         for port in port_range {
@@ -228,6 +534,10 @@ impl GameServer {
         }
         bail!("Failed to create server")
     }
+    /// Can be used to run admin commands on the server once it's running
+    pub fn admin(&self) -> AdminHandle {
+        AdminHandle(self.admin_tx.clone())
+    }
     #[tracing::instrument(skip_all)]
     pub async fn run(
         self,
@@ -237,18 +547,20 @@ impl GameServer {
         create_shutdown_systems: Arc<dyn Fn() -> SystemGroup<ShutdownEvent> + Sync + Send>,
         is_sync_component: Arc<dyn Fn(ComponentDesc, WorldStreamCompEvent) -> bool + Sync + Send>,
     ) -> SharedServerState {
-        let Self { mut incoming, .. } = self;
+        let Self { mut incoming, admin_rx, storage, authentication, tick_interval, .. } = self;
+        let replication_interval = self.replication_interval.clamp(tick_interval, tick_interval * MAX_TICKS_PER_REPLICATION);
+        let metrics = self.metrics.clone();
+        metrics.set_target_rates(1. / tick_interval.as_secs_f32(), 1. / replication_interval.as_secs_f32());
+        world.add_resource(server_metrics(), metrics.clone());
+        world.add_resource(server_tick_interval(), tick_interval);
+        world.add_resource(client_validators(), self.validators.clone());
         let assets = world.resource(asset_cache()).clone();
         let world_stream_filter = WorldStreamFilter::new(ArchetypeFilter::new().excl(no_sync()), is_sync_component);
         let state = Arc::new(Mutex::new(ServerState::new(
-            [(
-                MAIN_INSTANCE_ID.to_string(),
-                WorldInstance {
-                    systems: create_server_systems(&mut world),
-                    world,
-                    world_stream: WorldStream::new(world_stream_filter.clone()),
-                },
-            )]
+            [(MAIN_INSTANCE_ID.to_string(), {
+                let systems = create_server_systems(&mut world);
+                WorldInstance::new(world, WorldStream::new(world_stream_filter.clone()), systems)
+            })]
             .into_iter()
             .collect(),
             create_server_systems,
@@ -257,12 +569,21 @@ impl GameServer {
         )));
 
         let mut fps_counter = FpsCounter::new();
-        let mut sim_interval = interval(Duration::from_secs_f32(1. / 60.));
+        let mut sim_interval = interval(tick_interval);
         sim_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
+        let decoupled_replication = replication_interval != tick_interval;
+        let mut replication_counter = FpsCounter::new();
+        let mut replication_ticker = interval(replication_interval);
+        replication_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         let mut inactivity_interval = interval(Duration::from_secs_f32(5.));
         let mut last_active = ambient_sys::time::Instant::now();
 
+        let autosave_period = storage.as_ref().map(|storage| storage.autosave_interval).unwrap_or(Duration::from_secs(60));
+        let mut autosave_interval = interval_at(tokio::time::Instant::now() + autosave_period, autosave_period);
+        autosave_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         loop {
             tracing::debug_span!("Listening for incoming connections");
             tokio::select! {
@@ -279,7 +600,14 @@ impl GameServer {
 
 
                     log::debug!("Accepted connection");
-                    run_connection(conn, state.clone(), world_stream_filter.clone(), assets.clone());
+                    run_connection(
+                        conn,
+                        state.clone(),
+                        world_stream_filter.clone(),
+                        assets.clone(),
+                        metrics.clone(),
+                        authentication.clone(),
+                    );
                 }
                 _ = sim_interval.tick() => {
                     fps_counter.frame_start();
@@ -287,17 +615,48 @@ impl GameServer {
                     tokio::task::block_in_place(|| {
                         profiling::finish_frame!();
                         profiling::scope!("sim_tick");
+                        let frame_start = Instant::now();
                         state.step();
-                        state.broadcast_diffs();
-                        if let Some(sample) = fps_counter.frame_end() {
-                            for instance in state.instances.values() {
-                                for (_, (stream,)) in query((player_stats_stream(),)).iter(&instance.world, None) {
-                                    stream.send(sample.clone()).ok();
-                                }
+                        if !decoupled_replication {
+                            replicate(&mut state, &mut replication_counter, &metrics);
+                        }
+                        let sample = fps_counter.frame_end();
+                        metrics.record_frame(frame_start.elapsed(), sample.clone());
+                        state.record_metrics(&metrics);
+                        if let Some(sample) = sample {
+                            for instance in state.instances.values_mut() {
+                                instance.broadcast_stats(sample.clone());
                             }
                         }
                     });
                 }
+                _ = replication_ticker.tick(), if decoupled_replication => {
+                    let mut state = state.lock();
+                    tokio::task::block_in_place(|| {
+                        profiling::scope!("replication_tick");
+                        replicate(&mut state, &mut replication_counter, &metrics);
+                    });
+                }
+                Ok((command, result_tx)) = admin_rx.recv_async() => {
+                    if let AdminCommand::RenderThumbnail(options) = command {
+                        let state = state.clone();
+                        tokio::spawn(async move {
+                            result_tx.send(thumbnail::render_thumbnail(state, options).await).ok();
+                        });
+                    } else {
+                        let result = tokio::task::block_in_place(|| state.lock().handle_admin_command(command));
+                        result_tx.send(result).ok();
+                    }
+                }
+                _ = autosave_interval.tick(), if storage.is_some() => {
+                    let storage = storage.clone().unwrap();
+                    let data = tokio::task::block_in_place(|| state.lock().serialize_main_instance(&storage));
+                    // Writing can be slow, so it's done outside of the simulation loop
+                    tokio::task::spawn_blocking(move || match data.and_then(|data| storage.write(&data)) {
+                        Ok(()) => log::debug!("Saved the world to {:?}", storage.path),
+                        Err(err) => log::error!("Failed to save the world: {err:?}"),
+                    });
+                }
                 _ = inactivity_interval.tick(), if self.use_inactivity_shutdown => {
                     if state.lock().player_count() == 0 {
                         if Instant::now().duration_since(last_active).as_secs_f32() > 2. * 60. {
@@ -317,6 +676,12 @@ impl GameServer {
         log::debug!("[{}] GameServer shutting down", self.port);
         {
             let mut state = state.lock();
+            if let Some(storage) = &storage {
+                match state.serialize_main_instance(storage).and_then(|data| storage.write(&data)) {
+                    Ok(()) => log::info!("Saved the world to {:?}", storage.path),
+                    Err(err) => log::error!("Failed to save the world: {err:?}"),
+                }
+            }
             let create_shutdown_systems = state.create_shutdown_systems.clone();
             for instance in state.instances.values_mut() {
                 let mut sys = (create_shutdown_systems)();
@@ -330,7 +695,21 @@ impl GameServer {
 
 /// Setup the protocol and enter the update loop for a new connected client
 #[tracing::instrument(skip_all)]
-fn run_connection(connection: NewConnection, state: SharedServerState, world_stream_filter: WorldStreamFilter, assets: AssetCache) {
+/// Sends the world diffs of all instances to their players
+fn replicate(state: &mut ServerState, counter: &mut FpsCounter, metrics: &ServerMetrics) {
+    counter.frame_start();
+    state.broadcast_diffs();
+    metrics.record_replication(counter.frame_end());
+}
+
+fn run_connection(
+    connection: NewConnection,
+    state: SharedServerState,
+    world_stream_filter: WorldStreamFilter,
+    assets: AssetCache,
+    metrics: ServerMetrics,
+    authentication: Option<Authentication>,
+) {
     let connection_id = friendly_id();
     let handle = Arc::new(OnceCell::new());
     handle
@@ -345,6 +724,9 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                     let user_id = &client.user_id;
                     log::debug!("[{}] Locking world", user_id);
                     let mut state = state.lock();
+                    if state.is_banned(user_id) {
+                        return Err(NetworkError::Banned);
+                    }
                     // If there's an old player
                     let reconnecting = if let Some(player) = state.players.get_mut(user_id) {
                         if let Some(handle) = player.abort_handle.get() {
@@ -380,15 +762,25 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                     log::debug!("[{}] Init diff sent", user_id);
 
                     if !reconnecting {
-                        instance.spawn_player(create_player_entity_data(user_id, diffs_tx.clone(), events_tx.clone(), stats_tx.clone()));
-                        log::info!("[{}] Player spawned", user_id);
+                        let create_entity_data = if client.spectator { create_spectator_entity_data } else { create_player_entity_data };
+                        instance.spawn_player(
+                            create_entity_data(user_id, diffs_tx.clone(), events_tx.clone(), stats_tx.clone())
+                                .with_merge(identity_entity_data(&client)),
+                        );
+                        if client.spectator {
+                            log::info!("[{}] Spectator joined", user_id);
+                        } else {
+                            log::info!("[{}] Player spawned", user_id);
+                        }
                     } else {
-                        let entity = get_player_by_user_id(&instance.world, user_id).unwrap();
+                        let entity = get_connected_user_entity(&instance.world, user_id).unwrap();
                         instance.world.set(entity, player_entity_stream(), diffs_tx.clone()).unwrap();
                         instance.world.set(entity, player_stats_stream(), stats_tx.clone()).unwrap();
                         instance.world.set(entity, player_event_stream(), events_tx.clone()).unwrap();
+                        instance.reset_replication(entity);
                         log::info!("[{}] Player reconnected", user_id);
                     }
+                    Ok(())
                 };
 
                 let on_disconnect = |user_id: &Option<String>| {
@@ -399,6 +791,7 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                             log::info!("[{}] Disconnected (reconnection)", user_id);
                             return;
                         }
+                        state.despawn_local_players(user_id);
                         if let Some(player) = state.players.remove(user_id) {
                             state.instances.get_mut(&player.instance).unwrap().despawn_player(user_id);
                         }
@@ -410,7 +803,7 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                 let on_bi_stream = |user_id: &String, handler_id, tx, rx| {
                     let _span = debug_span!("on_bi_stream").entered();
                     let handler = {
-                        let state = state.lock();
+                        let mut state = state.lock();
                         let world = match state.get_player_world(user_id) {
                             Some(world) => world,
                             None => {
@@ -419,7 +812,11 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                             }
                         };
 
-                        world.resource(bi_stream_handlers()).get(&handler_id).cloned()
+                        let handler = world.resource(bi_stream_handlers()).get(&handler_id).cloned();
+                        if !state.validate_client_message(user_id, ClientMessage::BiStream(handler_id), &[]) {
+                            return;
+                        }
+                        handler
                     };
                     if let Some(handler) = handler {
                         handler(state.clone(), assets.clone(), user_id, tx, rx);
@@ -431,7 +828,7 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                 let on_uni_stream = |user_id: &String, handler_id, rx| {
                     let _span = debug_span!("on_uni_stream").entered();
                     let handler = {
-                        let state = state.lock();
+                        let mut state = state.lock();
                         let world = match state.get_player_world(user_id) {
                             Some(world) => world,
                             None => {
@@ -440,7 +837,11 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                             }
                         };
 
-                        world.resource(uni_stream_handlers()).get(&handler_id).cloned()
+                        let handler = world.resource(uni_stream_handlers()).get(&handler_id).cloned();
+                        if !state.validate_client_message(user_id, ClientMessage::UniStream(handler_id), &[]) {
+                            return;
+                        }
+                        handler
                     };
                     if let Some(handler) = handler {
                         handler(state.clone(), assets.clone(), user_id, rx);
@@ -454,7 +855,7 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                     let handler_id = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
                     let state = state.clone();
                     let handler = {
-                        let state = state.lock();
+                        let mut state = state.lock();
                         let world = match state.get_player_world(user_id) {
                             Some(world) => world,
                             None => {
@@ -462,7 +863,11 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                                 return;
                             }
                         };
-                        world.resource(datagram_handlers()).get(&handler_id).cloned()
+                        let handler = world.resource(datagram_handlers()).get(&handler_id).cloned();
+                        if !state.validate_client_message(user_id, ClientMessage::Datagram(handler_id), &data) {
+                            return;
+                        }
+                        handler
                     };
                     match handler {
                         Some(handler) => {
@@ -484,13 +889,18 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                     on_datagram: &on_datagram,
                     on_disconnect: &on_disconnect,
                     user_id: None,
+                    metrics,
+                    authentication,
                 };
 
                 let server_info = {
                     let state = state.lock();
                     let instance = state.instances.get(MAIN_INSTANCE_ID).unwrap();
                     let world = &instance.world;
-                    ServerInfo { project_name: world.resource(project_name()).clone() }
+                    ServerInfo {
+                        project_name: world.resource(project_name()).clone(),
+                        content_base_url: Some(ServerBaseUrlKey.get(world.resource(asset_cache()))),
+                    }
                 };
 
                 match client.run(connection, server_info).await {
@@ -498,6 +908,12 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
                     Err(err) if err.is_closed() => {
                         log::info!("Connection closed by client");
                     }
+                    Err(NetworkError::Banned) => {
+                        log::info!("Refused connection from banned player");
+                    }
+                    Err(NetworkError::Unauthenticated(reason)) => {
+                        log::info!("Refused connection from unauthenticated player: {reason}");
+                    }
                     Err(err) if err.is_end_of_stream() => {
                         log::warn!("Stream was closed prematurely");
                     }
@@ -516,15 +932,17 @@ fn run_connection(connection: NewConnection, state: SharedServerState, world_str
 /// Manages the server side client communication
 struct ClientInstance<'a> {
     diffs_rx: flume::Receiver<Vec<u8>>,
-    stats_rx: flume::Receiver<FpsSample>,
+    stats_rx: flume::Receiver<ServerStatsSample>,
     events_rx: flume::Receiver<Vec<u8>>,
 
-    on_init: &'a (dyn Fn(ClientInfo) + Send + Sync),
+    on_init: &'a (dyn Fn(ClientInfo) -> Result<(), NetworkError> + Send + Sync),
     on_datagram: &'a (dyn Fn(&String, Bytes) + Send + Sync),
     on_bi_stream: &'a (dyn Fn(&String, u32, SendStream, RecvStream) + Send + Sync),
     on_uni_stream: &'a (dyn Fn(&String, u32, RecvStream) + Send + Sync),
     on_disconnect: &'a (dyn Fn(&Option<String>) + Send + Sync),
     user_id: Option<String>,
+    metrics: ServerMetrics,
+    authentication: Option<Authentication>,
 }
 
 impl<'a> Drop for ClientInstance<'a> {
@@ -540,16 +958,14 @@ impl<'a> ClientInstance<'a> {
     #[tracing::instrument(skip_all)]
     pub async fn run(mut self, conn: NewConnection, server_info: ServerInfo) -> Result<(), NetworkError> {
         log::debug!("Connecting to client");
-        let mut proto = ServerProtocol::new(conn, server_info).await?;
+        let mut proto = ServerProtocol::new(conn, server_info, self.authentication.as_ref()).await?;
 
         log::debug!("Client loop starting");
         let mut entities_rx = self.diffs_rx.stream();
         let mut stats_rx = self.stats_rx.stream();
         let mut events_rx = self.events_rx.stream();
 
-        tokio::task::block_in_place(|| {
-            (self.on_init)(proto.client_info().clone());
-        });
+        tokio::task::block_in_place(|| (self.on_init)(proto.client_info().clone()))?;
         let user_id = proto.client_info().user_id.clone();
         self.user_id = Some(user_id.clone());
 
@@ -568,10 +984,12 @@ impl<'a> ClientInstance<'a> {
                     let span =tracing::debug_span!("server_event");
                     let mut stream = proto.connection().open_uni().instrument(span).await?;
 
+                    self.metrics.add_bytes_sent("event", msg.len());
                     stream.write(&msg).await?;
                 }
                 Some(Ok(datagram)) = proto.conn.datagrams.next() => {
                     let _span =tracing::debug_span!("datagram").entered();
+                    self.metrics.add_bytes_received("datagram", datagram.len());
                     tokio::task::block_in_place(|| (self.on_datagram)(&user_id, datagram))
                 }
                 Some(Ok((tx, mut rx))) = proto.conn.bi_streams.next() => {
@@ -600,10 +1018,65 @@ impl<'a> ClientInstance<'a> {
 pub struct ServerInfo {
     /// The name of the project. Used by the client to figure out what to title its window. Defaults to "Ambient".
     pub project_name: String,
+    /// The url the server's built assets are served from. The client loads the build metadata from it to verify the assets it downloads.
+    #[serde(default)]
+    pub content_base_url: Option<AbsAssetUrl>,
 }
 
 impl Default for ServerInfo {
     fn default() -> Self {
-        Self { project_name: "Ambient".into() }
+        Self { project_name: "Ambient".into(), content_base_url: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::components;
+
+    use super::*;
+
+    components!("test", {
+        replication_test_value: u32,
+    });
+
+    fn despawned(diff: &WorldDiff) -> Vec<EntityId> {
+        diff.changes.iter().filter_map(|change| if let WorldChange::Despawn(id) = change { Some(*id) } else { None }).collect()
+    }
+    fn spawned(diff: &WorldDiff) -> Vec<EntityId> {
+        diff.changes.iter().filter_map(|change| if let WorldChange::Spawn(Some(id), _) = change { Some(*id) } else { None }).collect()
+    }
+
+    #[test]
+    fn player_diff_hides_and_shows_entities() {
+        ambient_ecs::init_components();
+        init_components();
+        let mut world = World::new("player_diff");
+        let shown = Entity::new().with(replication_test_value(), 1).spawn(&mut world);
+        let hidden = Entity::new().with(replication_test_value(), 2).spawn(&mut world);
+        let stream_filter = WorldStreamFilter::default();
+        let mut state = PlayerReplication { filter: ReplicationFilter::all(), hidden: HashSet::new() };
+
+        let filter = ReplicationFilter::new(move |_, id| id != hidden);
+        let diff = player_diff(&world, &stream_filter, &mut state, &filter, &WorldDiff::new());
+        assert_eq!(despawned(&diff), vec![hidden]);
+
+        world.set(shown, replication_test_value(), 3).unwrap();
+        world.set(hidden, replication_test_value(), 4).unwrap();
+        let changes = WorldDiff::new().set(shown, replication_test_value(), 3).set(hidden, replication_test_value(), 4);
+        let diff = player_diff(&world, &stream_filter, &mut state, &filter, &changes);
+        assert_eq!(diff.changes.len(), 1);
+        assert!(matches!(diff.changes[0], WorldChange::Set(id, _) if id == shown));
+
+        world.despawn(hidden);
+        let diff = player_diff(&world, &stream_filter, &mut state, &filter, &WorldDiff::new().despawn(vec![hidden]));
+        assert!(diff.is_empty());
+
+        let hidden = Entity::new().with(replication_test_value(), 5).spawn(&mut world);
+        let filter = ReplicationFilter::new(move |_, id| id != hidden);
+        let diff = player_diff(&world, &stream_filter, &mut state, &filter, &WorldDiff::new());
+        assert_eq!(despawned(&diff), vec![hidden]);
+        let diff = player_diff(&world, &stream_filter, &mut state, &ReplicationFilter::all(), &WorldDiff::new());
+        assert_eq!(spawned(&diff), vec![hidden]);
+        assert!(state.hidden.is_empty());
     }
 }
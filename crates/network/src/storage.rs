@@ -0,0 +1,236 @@
+use std::{path::PathBuf, time::Duration};
+
+use ambient_ecs::{dont_store, query, Entity, EntityId, Store, World};
+use anyhow::{ensure, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::server::{ServerState, MAIN_INSTANCE_ID};
+
+/// Bumped whenever [WorldSnapshot] changes in an incompatible way
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// The binary format a world is saved in. Only the components with the [Store] attribute are kept.
+#[derive(Serialize, Deserialize)]
+struct WorldSnapshot {
+    version: u32,
+    entities: Vec<(EntityId, Entity)>,
+}
+impl WorldSnapshot {
+    fn from_world(world: &World) -> Self {
+        let entities = query(())
+            .excl(dont_store())
+            .iter(world, None)
+            .filter_map(|(id, _)| {
+                let mut entity = world.clone_entity(id).ok()?;
+                entity.filter(&|desc| desc.has_attribute::<Store>());
+                (!entity.is_empty()).then_some((id, entity))
+            })
+            .collect();
+        Self { version: SNAPSHOT_VERSION, entities }
+    }
+    fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        let snapshot: WorldSnapshot = bincode::deserialize(data)?;
+        ensure!(snapshot.version == SNAPSHOT_VERSION, "Unsupported save version {}", snapshot.version);
+        Ok(snapshot)
+    }
+    /// Adds the entities to `world`, merging into the ones that already exist (such as the resource entity)
+    fn spawn_into(self, world: &mut World) -> anyhow::Result<usize> {
+        let count = self.entities.len();
+        for (id, entity) in self.entities {
+            if world.exists(id) {
+                world.add_components(id, entity)?;
+            } else {
+                world.spawn_with_id(id, entity);
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// Serializes the stored entities and resources of `world` in the same format as the saves of a [WorldStorage]
+pub fn serialize_world(world: &World) -> anyhow::Result<Vec<u8>> {
+    Ok(bincode::serialize(&WorldSnapshot::from_world(world))?)
+}
+
+/// Reads `data` written by [serialize_world] into `world`, returning the number of entities read
+pub fn deserialize_world(data: &[u8], world: &mut World) -> anyhow::Result<usize> {
+    WorldSnapshot::from_bytes(data)?.spawn_into(world)
+}
+
+/// Where and how often the main instance of a [crate::server::GameServer] is saved
+#[derive(Debug, Clone)]
+pub struct WorldStorage {
+    pub path: PathBuf,
+    pub autosave_interval: Duration,
+    /// The number of previous saves to keep, as `<path>.1` (the most recent), `<path>.2` and so on
+    pub backups: usize,
+}
+impl WorldStorage {
+    /// `0` is the current save
+    fn save_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.path.clone()
+        } else {
+            PathBuf::from(format!("{}.{index}", self.path.display()))
+        }
+    }
+
+    /// Serializes the stored entities and resources of `world`, to be written with [WorldStorage::write]
+    pub fn serialize(&self, world: &World) -> anyhow::Result<Vec<u8>> {
+        serialize_world(world)
+    }
+
+    /// Replaces the current save with `data`, moving the previous saves down one step
+    pub fn write(&self, data: &[u8]) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {parent:?}"))?;
+        }
+        // Write to a temporary file first, so that a crash while saving doesn't leave a broken save behind
+        let tmp_path = PathBuf::from(format!("{}.tmp", self.path.display()));
+        std::fs::write(&tmp_path, data).with_context(|| format!("Failed to write {tmp_path:?}"))?;
+
+        for index in (0..self.backups).rev() {
+            let from = self.save_path(index);
+            if from.exists() {
+                std::fs::rename(&from, self.save_path(index + 1)).with_context(|| format!("Failed to rotate {from:?}"))?;
+            }
+        }
+        std::fs::rename(&tmp_path, &self.path).with_context(|| format!("Failed to move the save to {:?}", self.path))?;
+        Ok(())
+    }
+
+    pub fn save(&self, world: &World) -> anyhow::Result<()> {
+        self.write(&self.serialize(world)?)
+    }
+
+    /// Restores the most recent save that can be read into `world`, falling back to the older saves if it can't be.
+    /// Returns false if there was nothing to restore.
+    pub fn load(&self, world: &mut World) -> anyhow::Result<bool> {
+        for index in 0..=self.backups {
+            let path = self.save_path(index);
+            if !path.exists() {
+                continue;
+            }
+            let snapshot = std::fs::read(&path).context("Failed to read the save").and_then(|data| WorldSnapshot::from_bytes(&data));
+            match snapshot {
+                Ok(snapshot) => {
+                    let count = snapshot.spawn_into(world)?;
+                    log::info!("Loaded {count} entities from {path:?}");
+                    return Ok(true);
+                }
+                Err(err) => log::warn!("Failed to load the world from {path:?}: {err:?}"),
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl ServerState {
+    /// Serializes the main instance with [WorldStorage::serialize]
+    pub fn serialize_main_instance(&self, storage: &WorldStorage) -> anyhow::Result<Vec<u8>> {
+        let instance = self.instances.get(MAIN_INSTANCE_ID).context("The main instance is not running")?;
+        storage.serialize(&instance.world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::{components, Serializable};
+
+    use super::*;
+
+    components!("test", {
+        @[Serializable, Store]
+        storage_test_value: u32,
+        @[Serializable]
+        storage_test_unstored: u32,
+    });
+
+    fn init() {
+        ambient_ecs::init_components();
+        init_components();
+    }
+
+    /// A storage in an empty temporary directory
+    fn storage(name: &str, backups: usize) -> WorldStorage {
+        let dir = std::env::temp_dir().join(format!("ambient_storage_{name}_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        WorldStorage { path: dir.join("world.bin"), autosave_interval: Duration::from_secs(60), backups }
+    }
+
+    fn read(storage: &WorldStorage, index: usize) -> Option<String> {
+        std::fs::read_to_string(storage.save_path(index)).ok()
+    }
+
+    #[test]
+    fn write_rotates_backups() {
+        let storage = storage("rotate", 2);
+        for data in ["1", "2", "3", "4"] {
+            storage.write(data.as_bytes()).unwrap();
+        }
+        assert_eq!(read(&storage, 0).as_deref(), Some("4"));
+        assert_eq!(read(&storage, 1).as_deref(), Some("3"));
+        assert_eq!(read(&storage, 2).as_deref(), Some("2"));
+        assert_eq!(read(&storage, 3), None);
+        std::fs::remove_dir_all(storage.path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn write_replaces_leftover_tmp_file() {
+        let storage = storage("tmp", 0);
+        let tmp_path = PathBuf::from(format!("{}.tmp", storage.path.display()));
+        std::fs::create_dir_all(storage.path.parent().unwrap()).unwrap();
+        std::fs::write(&tmp_path, "interrupted save").unwrap();
+
+        storage.write(b"save").unwrap();
+        assert_eq!(read(&storage, 0).as_deref(), Some("save"));
+        assert_eq!(read(&storage, 1), None);
+        assert!(!tmp_path.exists());
+        std::fs::remove_dir_all(storage.path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_restores_stored_components() {
+        init();
+        let storage = storage("load", 1);
+        let mut world = World::new("test");
+        assert!(!storage.load(&mut world).unwrap());
+
+        let id = Entity::new().with(storage_test_value(), 1).with(storage_test_unstored(), 2).spawn(&mut world);
+        let unstored = Entity::new().with(storage_test_unstored(), 3).spawn(&mut world);
+        storage.save(&world).unwrap();
+
+        let mut loaded = World::new("test");
+        assert!(storage.load(&mut loaded).unwrap());
+        assert_eq!(loaded.get(id, storage_test_value()).unwrap(), 1);
+        assert!(!loaded.has_component(id, storage_test_unstored()));
+        assert!(!loaded.exists(unstored));
+        std::fs::remove_dir_all(storage.path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_falls_back_to_backups() {
+        init();
+        let storage = storage("fallback", 2);
+        let mut world = World::new("test");
+        let id = Entity::new().with(storage_test_value(), 1).spawn(&mut world);
+        storage.save(&world).unwrap();
+        storage.write(b"broken").unwrap();
+
+        let mut loaded = World::new("test");
+        assert!(storage.load(&mut loaded).unwrap());
+        assert_eq!(loaded.get(id, storage_test_value()).unwrap(), 1);
+
+        // Saves of another version are skipped too
+        let snapshot = WorldSnapshot { version: SNAPSHOT_VERSION + 1, entities: vec![] };
+        storage.write(&bincode::serialize(&snapshot).unwrap()).unwrap();
+        let mut loaded = World::new("test");
+        assert!(storage.load(&mut loaded).unwrap());
+        assert_eq!(loaded.get(id, storage_test_value()).unwrap(), 1);
+
+        // Nothing can be loaded once the good save has been rotated out
+        storage.write(b"broken").unwrap();
+        assert!(!storage.load(&mut World::new("test")).unwrap());
+        std::fs::remove_dir_all(storage.path.parent().unwrap()).unwrap();
+    }
+}
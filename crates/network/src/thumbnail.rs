@@ -0,0 +1,201 @@
+//! Renders thumbnails of the main instance on the server, without a window. The server's machine usually doesn't have a
+//! GPU, in which case a software adapter is used.
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use ambient_app::{world_instance_resources, AppResources, MeshBufferUpdate};
+use ambient_core::{
+    asset_cache,
+    camera::{
+        active_camera, aspect_ratio, aspect_ratio_from_window, fovy, get_active_camera, near, perspective_infinite_reverse, projection,
+        projection_view,
+    },
+    loading::load_status,
+    main_scene,
+    player::user_id,
+    transform::{inv_local_to_world, local_to_world, lookat_center, lookat_up, translation},
+    RuntimeKey,
+};
+use ambient_ecs::{Entity, EntityId, SystemGroup, World, WorldDiff};
+use ambient_gpu::gpu::{Gpu, GpuKey};
+use ambient_renderer::RenderTarget;
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    asset_url::{ServerBaseUrlKey, ServerContentDirKey},
+};
+use ambient_sys::{task::RuntimeHandle, time::Instant};
+use anyhow::{ensure, Context};
+use glam::{uvec2, vec3, UVec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    admin::{AdminResponse, AdminResult},
+    capture::capture,
+    client_game_state::ClientGameState,
+    server::{ServerState, SharedServerState, MAIN_INSTANCE_ID},
+};
+
+/// The name of the thumbnail in the server's content directory, which is served with the rest of the content
+pub const THUMBNAIL_FILE: &str = "thumbnail.png";
+/// How long to wait for the assets of the world to load before rendering it anyway
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// The frames to render before the thumbnail, so that the loads have started and the transforms have been updated
+const MIN_FRAMES: usize = 10;
+/// The user id of the renderer, so that only the global cameras are used by default
+const THUMBNAIL_USER_ID: &str = "thumbnail";
+
+fn default_size() -> u32 {
+    512
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailOptions {
+    /// The id of the camera entity to render from. Defaults to the active global camera of the main instance, or to a camera
+    /// looking at the origin if there's none
+    #[serde(default)]
+    pub camera: Option<String>,
+    #[serde(default = "default_size")]
+    pub width: u32,
+    #[serde(default = "default_size")]
+    pub height: u32,
+    /// Where to save the PNG on the server's machine. Defaults to [THUMBNAIL_FILE] in the server's content directory
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// The main instance, copied between server frames so that it can be rendered without holding up the server
+pub struct ThumbnailSnapshot {
+    diff: WorldDiff,
+    server_assets: AssetCache,
+    camera: Option<EntityId>,
+    size: UVec2,
+    path: PathBuf,
+}
+
+impl ServerState {
+    pub fn snapshot_for_thumbnail(&self, options: ThumbnailOptions) -> anyhow::Result<ThumbnailSnapshot> {
+        ensure!(options.width > 0 && options.height > 0, "The size of the thumbnail must be positive");
+        let instance = self.instances.get(MAIN_INSTANCE_ID).context("There is no main instance")?;
+        let camera = match options.camera {
+            Some(camera) => {
+                let camera = camera.parse::<EntityId>().with_context(|| format!("Invalid entity id {camera:?}"))?;
+                ensure!(instance.world.exists(camera), "The camera {camera} doesn't exist");
+                Some(camera)
+            }
+            None => None,
+        };
+        let server_assets = instance.world.resource(asset_cache()).clone();
+        let path = match options.path {
+            Some(path) => PathBuf::from(path),
+            None => ServerContentDirKey
+                .get(&server_assets)
+                .context("The server has no content directory; a path is required")?
+                .join(THUMBNAIL_FILE),
+        };
+        Ok(ThumbnailSnapshot {
+            diff: instance.world_stream.filter().initial_diff(&instance.world),
+            server_assets,
+            camera,
+            size: uvec2(options.width, options.height),
+            path,
+        })
+    }
+}
+
+impl ThumbnailSnapshot {
+    /// Renders the snapshot and saves it as a PNG, once its assets have loaded. This blocks until it's done, so it should be
+    /// run on a blocking thread.
+    pub fn render(self, runtime: RuntimeHandle) -> anyhow::Result<PathBuf> {
+        let Self { diff, server_assets, camera, size, path } = self;
+        let gpu = Arc::new(futures::executor::block_on(Gpu::with_config(None, false, &Default::default())));
+
+        // The assets are loaded from the server like a client would
+        let assets = AssetCache::new(runtime.clone());
+        RuntimeKey.insert(&assets, runtime.clone());
+        GpuKey.insert(&assets, gpu.clone());
+        ServerBaseUrlKey.insert(&assets, ServerBaseUrlKey.get(&server_assets));
+        ServerContentDirKey.insert(&assets, ServerContentDirKey.get(&server_assets));
+
+        let mut app_world = World::new("thumbnail_app");
+        let resources = world_instance_resources(AppResources::headless(assets.clone(), gpu.clone(), runtime, size));
+        app_world.add_components(app_world.resource_entity(), resources).unwrap();
+        let render_target = Arc::new(RenderTarget::new(gpu, size, None));
+        let systems = SystemGroup::new("thumbnail", vec![Box::new(MeshBufferUpdate), Box::new(ambient_core::loading::systems())]);
+        let mut game_state =
+            ClientGameState::new(&mut app_world, assets, THUMBNAIL_USER_ID.to_string(), render_target.clone(), systems, Entity::new());
+        diff.apply(&mut game_state.world, Entity::new(), false);
+        use_camera(&mut game_state.world, camera);
+
+        let started = Instant::now();
+        let mut frames = 0;
+        loop {
+            game_state.on_frame(&render_target);
+            frames += 1;
+            let loaded = game_state.world.resource_opt(load_status()).map_or(false, |status| status.is_loaded());
+            if frames >= MIN_FRAMES && loaded {
+                break;
+            }
+            if started.elapsed() > LOAD_TIMEOUT {
+                log::warn!("The world didn't finish loading in {LOAD_TIMEOUT:?}; rendering the thumbnail anyway");
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(16));
+        }
+
+        let (image_tx, image_rx) = flume::bounded(1);
+        game_state.world.resource(capture()).capture_frame(Box::new(move |image| {
+            image_tx.send(image).ok();
+        }));
+        game_state.on_frame(&render_target);
+        let image = image_rx.recv_timeout(Duration::from_secs(10)).context("Failed to read back the rendered frame")?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {parent:?}"))?;
+        }
+        image.save(&path).with_context(|| format!("Failed to save the thumbnail to {path:?}"))?;
+        Ok(path)
+    }
+}
+
+/// Makes `camera` the active camera of the renderer, or spawns a camera looking at the origin if it's `None` and the world
+/// has no global camera
+fn use_camera(world: &mut World, camera: Option<EntityId>) {
+    match camera {
+        Some(camera) => {
+            // Cameras of players are only used by their player's renderer
+            world.remove_component(camera, user_id()).ok();
+            world.add_component(camera, active_camera(), f32::MAX).ok();
+        }
+        None if get_active_camera(world, main_scene(), Some(&THUMBNAIL_USER_ID.to_string())).is_some() => {}
+        None => {
+            Entity::new()
+                .with_default(local_to_world())
+                .with_default(inv_local_to_world())
+                .with(near(), 0.1)
+                .with(fovy(), 1.0)
+                .with(perspective_infinite_reverse(), ())
+                .with(aspect_ratio(), 1.)
+                .with(aspect_ratio_from_window(), EntityId::resources())
+                .with_default(projection())
+                .with_default(projection_view())
+                .with(translation(), vec3(5., 5., 4.))
+                .with(lookat_center(), Vec3::ZERO)
+                .with(lookat_up(), Vec3::Z)
+                .with_default(main_scene())
+                .with(active_camera(), 0.)
+                .spawn(world);
+        }
+    }
+}
+
+/// Snapshots the main instance, and renders it on a blocking thread
+pub(crate) async fn render_thumbnail(state: SharedServerState, options: ThumbnailOptions) -> AdminResult {
+    log::info!("Rendering a thumbnail: {options:?}");
+    let snapshot = state.lock().snapshot_for_thumbnail(options).map_err(|err| format!("{err:?}"))?;
+    let runtime = RuntimeHandle::current();
+    let path = tokio::task::spawn_blocking(move || snapshot.render(runtime))
+        .await
+        .map_err(|_| "The renderer crashed; is there a GPU or a software adapter available?".to_string())?
+        .map_err(|err| format!("{err:?}"))?;
+    log::info!("Saved the thumbnail to {path:?}");
+    Ok(AdminResponse::Thumbnail(path.to_string_lossy().to_string()))
+}
@@ -0,0 +1,41 @@
+use ambient_core::{paused, time_scale};
+use ambient_ecs::{FnSystem, SystemGroup};
+
+use crate::ServerWorldExt;
+
+/// Copies the `time_scale` and `paused` resources of the server into its synced resources, so that the clients' animations
+/// slow down and pause with the server
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "time_control",
+        vec![Box::new(FnSystem::new(|world, _| {
+            let Some(id) = world.synced_resource_entity() else { return };
+            let scale = world.resource_opt(time_scale()).copied().unwrap_or(1.);
+            let is_paused = world.resource_opt(paused()).copied().unwrap_or(false);
+            if world.get(id, time_scale()).ok() != Some(scale) {
+                world.add_component(id, time_scale(), scale).unwrap();
+            }
+            if world.get(id, paused()).ok() != Some(is_paused) {
+                world.add_component(id, paused(), is_paused).unwrap();
+            }
+        }))],
+    )
+}
+
+/// Applies the `time_scale` and `paused` of the server to the client's world when they change, while letting client-side
+/// code change them in between
+pub fn client_systems() -> SystemGroup {
+    let mut last = None;
+    SystemGroup::new(
+        "time_control",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            let Some(id) = world.synced_resource_entity() else { return };
+            let (Ok(scale), Ok(is_paused)) = (world.get(id, time_scale()), world.get(id, paused())) else { return };
+            if last != Some((scale, is_paused)) {
+                last = Some((scale, is_paused));
+                world.add_resource(time_scale(), scale);
+                world.add_resource(paused(), is_paused);
+            }
+        }))],
+    )
+}
@@ -0,0 +1,309 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ambient_core::player::get_spectator_by_user_id;
+use ambient_ecs::{components, Component, ComponentDesc, ComponentEntry, Entity, EntityId, Resource, World, WorldChange, WorldDiff};
+use ambient_sys::time::Instant;
+use glam::Vec3;
+use parking_lot::Mutex;
+
+use crate::server::{server_tick_interval, ServerState, DEFAULT_SERVER_TICK_INTERVAL};
+
+components!("network", {
+    @[Resource]
+    client_validators: ClientValidators,
+});
+
+/// Checks a change a client made to a component of an entity. The value is `None` if the component is being removed.
+pub type ComponentValidator = Arc<dyn Fn(&World, &str, EntityId, Option<&ComponentEntry>) -> Result<(), String> + Sync + Send>;
+/// Checks a message sent by a client. Streams are checked before they're read, so only datagrams and RPCs have data.
+pub type MessageValidator = Arc<dyn Fn(&World, &str, &[u8]) -> Result<(), String> + Sync + Send>;
+
+/// A player's violation count goes down by one every this long, so that only violations made in quick succession get them
+/// kicked
+pub const VIOLATION_DECAY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A kind of message sent by clients, by handler id, or by name for RPCs
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientMessage {
+    Datagram(u32),
+    UniStream(u32),
+    BiStream(u32),
+    /// A call to the RPC with this name; see [ambient_rpc::rpc_name]. RPCs are also checked as a [ClientMessage::BiStream]
+    /// to [crate::RPC_STREAM_ID] before they're read.
+    Rpc(String),
+}
+
+#[derive(Default)]
+struct ValidatorsState {
+    components: HashMap<ComponentDesc, Vec<ComponentValidator>>,
+    messages: HashMap<ClientMessage, Vec<MessageValidator>>,
+    kick_after: Option<u32>,
+    /// The decaying violation count of each player, and when it was last updated
+    violations: HashMap<String, (f32, Instant)>,
+}
+
+/// Checks the changes and messages that clients send, before they reach the world.
+///
+/// Added as a resource to every instance of a [crate::server::GameServer]; cloning it gives a handle to the same validators.
+#[derive(Clone, Default)]
+pub struct ClientValidators(Arc<Mutex<ValidatorsState>>);
+impl std::fmt::Debug for ClientValidators {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientValidators").finish_non_exhaustive()
+    }
+}
+impl ClientValidators {
+    pub fn add_component_validator(&self, component: impl Into<ComponentDesc>, validator: ComponentValidator) {
+        self.0.lock().components.entry(component.into()).or_default().push(validator);
+    }
+    pub fn add_message_validator(&self, message: ClientMessage, validator: MessageValidator) {
+        self.0.lock().messages.entry(message).or_default().push(validator);
+    }
+    /// Kick players once they've made this many violations. If not set, violations are only logged.
+    pub fn set_kick_after(&self, violations: Option<u32>) {
+        self.0.lock().kick_after = violations;
+    }
+
+    pub fn validate_message(&self, world: &World, user_id: &str, message: ClientMessage, data: &[u8]) -> Result<(), String> {
+        let validators = self.0.lock().messages.get(&message).cloned().unwrap_or_default();
+        validators.iter().try_for_each(|validator| validator(world, user_id, data)).map_err(|err| format!("{message:?}: {err}"))
+    }
+
+    fn validate_component(
+        &self,
+        world: &World,
+        user_id: &str,
+        id: EntityId,
+        desc: ComponentDesc,
+        value: Option<&ComponentEntry>,
+    ) -> Result<(), String> {
+        let validators = self.0.lock().components.get(&desc).cloned().unwrap_or_default();
+        validators.iter().try_for_each(|validator| validator(world, user_id, id, value)).map_err(|err| format!("{}: {err}", desc.path()))
+    }
+
+    /// Removes the changes of `diff` that fail validation, and returns why they did
+    pub fn validate_diff(&self, world: &World, user_id: &str, diff: WorldDiff) -> (WorldDiff, Vec<String>) {
+        let mut violations = Vec::new();
+        let mut check = |id: EntityId, desc: ComponentDesc, value: Option<&ComponentEntry>| match self
+            .validate_component(world, user_id, id, desc, value)
+        {
+            Ok(()) => true,
+            Err(err) => {
+                violations.push(err);
+                false
+            }
+        };
+
+        let changes = diff
+            .changes
+            .into_iter()
+            .filter_map(|change| match change {
+                WorldChange::Spawn(id, mut entity) => {
+                    let entity_id = id.unwrap_or_default();
+                    for entry in entity.iter().cloned().collect::<Vec<_>>() {
+                        if !check(entity_id, entry.desc(), Some(&entry)) {
+                            entity.remove_raw(entry.desc());
+                        }
+                    }
+                    Some(WorldChange::Spawn(id, entity))
+                }
+                WorldChange::AddComponents(id, mut entity) => {
+                    for entry in entity.iter().cloned().collect::<Vec<_>>() {
+                        if !check(id, entry.desc(), Some(&entry)) {
+                            entity.remove_raw(entry.desc());
+                        }
+                    }
+                    Some(WorldChange::AddComponents(id, entity))
+                }
+                WorldChange::RemoveComponents(id, components) => {
+                    let components = components.into_iter().filter(|&desc| check(id, desc, None)).collect();
+                    Some(WorldChange::RemoveComponents(id, components))
+                }
+                WorldChange::Set(id, entry) => check(id, entry.desc(), Some(&entry)).then_some(WorldChange::Set(id, entry)),
+                // Despawning removes all the components, so it's only allowed if they can all be removed
+                WorldChange::Despawn(id) => {
+                    let components = world.get_components(id).unwrap_or_default();
+                    components.into_iter().all(|desc| check(id, desc, None)).then_some(WorldChange::Despawn(id))
+                }
+            })
+            .collect();
+        (WorldDiff { changes }, violations)
+    }
+
+    /// Counts a violation of the player, and returns true if they should be kicked for it. Their count starts over once they
+    /// are
+    fn count_violation(&self, user_id: &str) -> bool {
+        let mut state = self.0.lock();
+        let now = Instant::now();
+        let (count, last) = state.violations.entry(user_id.to_string()).or_insert((0., now));
+        *count = decay_violations(*count, now.duration_since(*last)) + 1.;
+        *last = now;
+        let count = *count;
+        let kick = state.kick_after.map(|kick_after| count >= kick_after as f32).unwrap_or(false);
+        if kick {
+            state.violations.remove(user_id);
+        }
+        kick
+    }
+}
+
+/// The violation count `count` has decayed to after `elapsed`
+fn decay_violations(count: f32, elapsed: Duration) -> f32 {
+    (count - elapsed.as_secs_f32() / VIOLATION_DECAY_INTERVAL.as_secs_f32()).max(0.)
+}
+
+impl ServerState {
+    /// Validates a message from the player; if it doesn't pass, the violation is handled and false is returned
+    pub fn validate_client_message(&mut self, user_id: &str, message: ClientMessage, data: &[u8]) -> bool {
+        let Some(world) = self.get_player_world(user_id) else { return false };
+        let Some(validators) = world.resource_opt(client_validators()).cloned() else { return true };
+        match validators.validate_message(world, user_id, message, data) {
+            Ok(()) => true,
+            Err(violation) => {
+                self.handle_violations(&validators, user_id, vec![violation]);
+                false
+            }
+        }
+    }
+
+    /// Applies the changes the player made to their world, except for the ones that fail validation
+    pub fn apply_client_diff(&mut self, user_id: &str, diff: WorldDiff) {
+        let Some(world) = self.get_player_world(user_id) else { return };
+        // Spectators only watch
+        if get_spectator_by_user_id(world, user_id).is_some() {
+            log::warn!("[{user_id}] Rejected a change from a spectator");
+            return;
+        }
+        let (diff, violations, validators) = match world.resource_opt(client_validators()).cloned() {
+            Some(validators) => {
+                let (diff, violations) = validators.validate_diff(world, user_id, diff);
+                (diff, violations, Some(validators))
+            }
+            None => (diff, Vec::new(), None),
+        };
+        if let Some(world) = self.get_player_world_mut(user_id) {
+            diff.apply(world, Entity::new(), false);
+        }
+        if let Some(validators) = validators {
+            self.handle_violations(&validators, user_id, violations);
+        }
+    }
+
+    fn handle_violations(&mut self, validators: &ClientValidators, user_id: &str, violations: Vec<String>) {
+        let mut kick = false;
+        for violation in violations {
+            log::warn!("[{user_id}] Rejected a change from the client: {violation}");
+            kick |= validators.count_violation(user_id);
+        }
+        if kick {
+            log::warn!("[{user_id}] Kicking the player for too many violations");
+            self.kick_player(user_id);
+        }
+    }
+}
+
+/// Rejects all client changes to the component
+pub fn read_only_validator() -> ComponentValidator {
+    Arc::new(|_, _, _, _| Err("The component can't be changed by clients".to_string()))
+}
+
+/// Rejects client changes to `component` that move it faster than `max_speed` units per second
+pub fn max_speed_validator(component: Component<Vec3>, max_speed: f32) -> ComponentValidator {
+    let last_changes = Mutex::new(HashMap::<EntityId, Instant>::new());
+    Arc::new(move |world, _, id, value| {
+        let (Some(new), Ok(old)) = (value.and_then(|value| value.try_downcast_ref::<Vec3>()), world.get(id, component)) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let mut last_changes = last_changes.lock();
+        // Changes can't be applied more often than the server ticks, so changes closer than that are counted as a tick apart
+        let tick_interval = world.resource_opt(server_tick_interval()).copied().unwrap_or(DEFAULT_SERVER_TICK_INTERVAL);
+        let elapsed = last_changes.get(&id).map(|last| now.duration_since(*last)).unwrap_or(tick_interval).max(tick_interval);
+        let speed = old.distance(*new) / elapsed.as_secs_f32();
+        if speed > max_speed {
+            return Err(format!("Moved at {speed} units per second, faster than {max_speed}"));
+        }
+        last_changes.insert(id, now);
+        Ok(())
+    })
+}
+
+/// Rejects messages from players that send more than `max_per_second` of them, allowing bursts of up to a second's worth
+pub fn rate_limit_validator(max_per_second: f32) -> MessageValidator {
+    let budgets = Mutex::new(HashMap::<String, (f32, Instant)>::new());
+    Arc::new(move |_, user_id, _| {
+        let now = Instant::now();
+        let capacity = max_per_second.max(1.);
+        let mut budgets = budgets.lock();
+        let (budget, last) = budgets.entry(user_id.to_string()).or_insert((capacity, now));
+        *budget = (*budget + now.duration_since(*last).as_secs_f32() * max_per_second).min(capacity);
+        *last = now;
+        if *budget < 1. {
+            return Err(format!("Sent more than {max_per_second} messages per second"));
+        }
+        *budget -= 1.;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    components!("test", {
+        health: f32,
+        label: String,
+    });
+
+    #[test]
+    fn validate_diff_rejects_read_only_changes() {
+        init_components();
+        let mut world = World::new("validate_diff");
+        let guarded = Entity::new().with(health(), 100.).with(label(), "guarded".to_string()).spawn(&mut world);
+        let unguarded = Entity::new().with(label(), "unguarded".to_string()).spawn(&mut world);
+        let validators = ClientValidators::default();
+        validators.add_component_validator(health(), read_only_validator());
+
+        let diff = WorldDiff::new()
+            .set(guarded, health(), 0.)
+            .set(guarded, label(), "renamed".to_string())
+            .remove_component(guarded, health().desc())
+            .despawn(vec![guarded, unguarded]);
+        let (diff, violations) = validators.validate_diff(&world, "player", diff);
+
+        assert_eq!(violations.len(), 3);
+        assert_eq!(diff.changes.len(), 3);
+        assert!(matches!(&diff.changes[0], WorldChange::Set(id, entry) if *id == guarded && entry.desc() == label().desc()));
+        assert!(matches!(&diff.changes[1], WorldChange::RemoveComponents(id, components) if *id == guarded && components.is_empty()));
+        assert!(matches!(&diff.changes[2], WorldChange::Despawn(id) if *id == unguarded));
+    }
+
+    #[test]
+    fn violations_decay_and_reset_on_kick() {
+        let validators = ClientValidators::default();
+        validators.set_kick_after(Some(2));
+        assert!(!validators.count_violation("a"));
+        assert!(!validators.count_violation("b"));
+        assert!(validators.count_violation("a"));
+        assert!(!validators.count_violation("a"));
+
+        assert_eq!(decay_violations(3., VIOLATION_DECAY_INTERVAL * 2), 1.);
+        assert_eq!(decay_violations(1., VIOLATION_DECAY_INTERVAL * 2), 0.);
+    }
+
+    #[test]
+    fn rate_limit_validator_limits_each_player() {
+        let world = World::new("rate_limit_validator");
+        let validator = rate_limit_validator(2.);
+        assert!(validator(&world, "a", &[]).is_ok());
+        assert!(validator(&world, "a", &[]).is_ok());
+        assert!(validator(&world, "a", &[]).is_err());
+        assert!(validator(&world, "b", &[]).is_ok());
+
+        let validators = ClientValidators::default();
+        validators.add_message_validator(ClientMessage::Rpc("limited".to_string()), rate_limit_validator(1.));
+        assert!(validators.validate_message(&world, "a", ClientMessage::Rpc("limited".to_string()), &[]).is_ok());
+        assert!(validators.validate_message(&world, "a", ClientMessage::Rpc("limited".to_string()), &[]).is_err());
+        assert!(validators.validate_message(&world, "a", ClientMessage::Rpc("other".to_string()), &[]).is_ok());
+    }
+}
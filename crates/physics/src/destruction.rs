@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use ambient_core::{
+    remove_at_time, time,
+    transform::{get_world_transform, local_to_world, rotation, scale, translation},
+};
+use ambient_ecs::{
+    components, id, query, world_events, Debuggable, Description, Entity, EntityId, FnSystem, Name, Networked, Resource, Store,
+    SystemGroup, World, WorldEventReader,
+};
+use ambient_event_types::DESTROY;
+use ambient_model::model_from_url;
+use ambient_std::asset_url::{AbsAssetUrl, AssetUrl};
+use anyhow::Context;
+use glam::{Mat4, Vec3};
+use itertools::Itertools;
+use physxx::{PxBase, PxForceMode, PxRigidBody};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collider::{collider, density, dynamic, ColliderDef},
+    physx::{linear_velocity, rigid_actor},
+};
+
+components!("physics", {
+    /// The pre-fractured chunks this entity breaks into when it's destroyed; see [DestructibleChunk]
+    @[Debuggable, Networked, Store]
+    destructible_chunks: Vec<DestructibleChunk>,
+    @[
+        Debuggable, Networked, Store,
+        Name["Destruction chunk lifetime"],
+        Description["How many seconds the chunks of this entity last after it's destroyed. Defaults to 10."]
+    ]
+    destruction_chunk_lifetime: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Destruction impulse"],
+        Description["The impulse of the hit which destroys an entity, sent with the `DESTROY` event.\nEach chunk gets a share of it which falls off with its distance from the `destruction_position`."]
+    ]
+    destruction_impulse: Vec3,
+    @[
+        Debuggable, Networked, Store,
+        Name["Destruction position"],
+        Description["Where the hit which destroys an entity lands, sent with the `DESTROY` event. Defaults to the position of the entity."]
+    ]
+    destruction_position: Vec3,
+    @[
+        Debuggable, Networked, Store, Resource,
+        Name["Max destruction chunks"],
+        Description["The most chunks of destroyed entities there can be at once. Defaults to 256.\nThe chunks near the hit break into their smaller chunks only as far as this allows, and the oldest chunks are removed to make room for new ones."]
+    ]
+    max_destruction_chunks: u32,
+    /// This entity is a chunk of a destroyed entity
+    @[Debuggable]
+    destruction_chunk: (),
+    /// The impulse, and where it's applied, to give this chunk once its rigid body is created
+    @[Debuggable]
+    destruction_pending_impulse: (Vec3, Vec3),
+});
+
+const DEFAULT_CHUNK_LIFETIME: f32 = 10.;
+const DEFAULT_MAX_CHUNKS: u32 = 256;
+
+/// A piece of a pre-fractured model, generated by the models pipeline from the chunk nodes of the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DestructibleChunk {
+    /// The url of the model of this chunk
+    pub model: String,
+    pub collider: ColliderDef,
+    /// The pose of the chunk relative to the intact entity
+    pub transform: Mat4,
+    /// The center of the chunk relative to the intact entity
+    pub center: Vec3,
+    /// The smaller chunks this chunk breaks into, which together make up the same piece
+    #[serde(default)]
+    pub children: Vec<DestructibleChunk>,
+}
+impl DestructibleChunk {
+    pub fn resolve(&mut self, base_url: &AbsAssetUrl) -> anyhow::Result<()> {
+        self.model = AssetUrl::parse(&self.model).context("Invalid chunk model url")?.resolve(base_url)?.into();
+        self.collider.resolve(base_url)?;
+        for child in &mut self.children {
+            child.resolve(base_url)?;
+        }
+        Ok(())
+    }
+}
+
+/// Picks the chunks to spawn. Starting from the largest chunks, the chunks nearest to the hit break into their smaller
+/// chunks, for as long as that keeps the count within the budget; the rest stay merged
+fn select_chunks(chunks: &[DestructibleChunk], hit: Vec3, budget: usize) -> Vec<&DestructibleChunk> {
+    let mut selected = chunks.iter().collect_vec();
+    loop {
+        let next = selected
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| !chunk.children.is_empty())
+            .min_by(|(_, a), (_, b)| a.center.distance_squared(hit).total_cmp(&b.center.distance_squared(hit)))
+            .map(|(i, chunk)| (i, *chunk));
+        let Some((i, chunk)) = next else { break };
+        if selected.len() - 1 + chunk.children.len() > budget {
+            break;
+        }
+        selected.splice(i..i + 1, chunk.children.iter());
+    }
+    selected
+}
+
+/// Swaps the entity `id` for the chunks it breaks into, which are thrown by the `impulse` of a hit at `position`. Does
+/// nothing if the entity isn't destructible
+pub fn destroy(world: &mut World, id: EntityId, impulse: Vec3, position: Option<Vec3>) {
+    let Ok(chunks) = world.get_cloned(id, destructible_chunks()) else { return };
+    let transform = get_world_transform(world, id).unwrap_or(Mat4::IDENTITY);
+    let position = position.unwrap_or_else(|| transform.transform_point3(Vec3::ZERO));
+    let max_chunks = world.get(world.resource_entity(), max_destruction_chunks()).unwrap_or(DEFAULT_MAX_CHUNKS) as usize;
+    let live_chunks = query(()).incl(destruction_chunk()).iter(world, None).count();
+    let chunks = select_chunks(&chunks, transform.inverse().transform_point3(position), max_chunks.saturating_sub(live_chunks));
+
+    let lifetime = world.get(id, destruction_chunk_lifetime()).unwrap_or(DEFAULT_CHUNK_LIFETIME).max(0.);
+    let remove_at = *world.resource(time()) + Duration::from_secs_f32(lifetime);
+    let velocity = world.get(id, linear_velocity()).unwrap_or_default();
+    let chunk_density = world.get(id, density()).ok();
+    for chunk in chunks {
+        let (chunk_scale, chunk_rotation, chunk_translation) = (transform * chunk.transform).to_scale_rotation_translation();
+        // The chunks nearest to the hit are thrown the hardest
+        let falloff = 1. / (1. + transform.transform_point3(chunk.center).distance_squared(position));
+        let mut entity = Entity::new()
+            .with(translation(), chunk_translation)
+            .with(rotation(), chunk_rotation)
+            .with(scale(), chunk_scale)
+            .with_default(local_to_world())
+            .with(model_from_url(), chunk.model.clone())
+            .with(collider(), chunk.collider.clone())
+            .with(dynamic(), true)
+            .with(linear_velocity(), velocity)
+            .with(remove_at_time(), remove_at)
+            .with_default(destruction_chunk())
+            .with(destruction_pending_impulse(), (impulse * falloff, position));
+        if let Some(chunk_density) = chunk_density {
+            entity.set(density(), chunk_density);
+        }
+        entity.spawn(world);
+    }
+    world.despawn(id);
+    remove_oldest_chunks(world, max_chunks);
+}
+
+fn remove_oldest_chunks(world: &mut World, max_chunks: usize) {
+    let chunks = query(remove_at_time()).incl(destruction_chunk()).iter(world, None).map(|(id, &remove_at)| (id, remove_at)).collect_vec();
+    if chunks.len() <= max_chunks {
+        return;
+    }
+    let count = chunks.len() - max_chunks;
+    for (id, _) in chunks.into_iter().sorted_by_key(|(_, remove_at)| *remove_at).take(count) {
+        world.despawn(id);
+    }
+}
+
+pub fn server_systems() -> SystemGroup {
+    let mut events = WorldEventReader::new();
+    SystemGroup::new(
+        "destruction",
+        vec![
+            Box::new(FnSystem::new(move |world, _| {
+                let destroy_events = events
+                    .iter(world.resource(world_events()))
+                    .filter(|(_, (name, _))| name == DESTROY)
+                    .filter_map(|(_, (_, event))| {
+                        Some((event.get(id())?, event.get(destruction_impulse()).unwrap_or_default(), event.get(destruction_position())))
+                    })
+                    .collect_vec();
+                for (id, impulse, position) in destroy_events {
+                    if world.exists(id) {
+                        destroy(world, id, impulse, position);
+                    }
+                }
+            })),
+            // The rigid bodies of the chunks are created once their colliders are loaded
+            query((rigid_actor(), destruction_pending_impulse())).to_system(|q, world, qs, _| {
+                for (id, (actor, (impulse, position))) in q.collect_cloned(world, qs) {
+                    if let Some(actor) = actor.to_rigid_dynamic() {
+                        actor.add_force_at_pos(impulse, position, Some(PxForceMode::Impulse), Some(true));
+                    }
+                    world.remove_component(id, destruction_pending_impulse()).unwrap();
+                }
+            }),
+        ],
+    )
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use ambient_core::asset_cache;
+use ambient_core::{asset_cache, dtime, time_scale};
 use ambient_ecs::{
     components, query, Debuggable, Description, DynSystem, Entity, EntityId, FnSystem, Name, Networked, Resource, Store, SystemGroup, World,
 };
@@ -12,7 +12,7 @@ use helpers::release_px_scene;
 use parking_lot::Mutex;
 use physx::{
     actor_aggregate, articulation_cache, articulation_link, articulation_reduce_coordinate, character_controller, fixed_joint,
-    physics_shape, revolute_joint, rigid_actor, rigid_dynamic, rigid_static,
+    physics_shape, revolute_joint, rigid_actor, rigid_dynamic, rigid_static, spherical_joint,
 };
 use physxx::{
     AsPxActor, PxContactPairHeader, PxControllerManagerRef, PxMaterial, PxPvdSceneFlag, PxRigidActor, PxRigidActorRef, PxSceneDesc,
@@ -23,10 +23,12 @@ use serde::{Deserialize, Serialize};
 use crate::physx::PhysicsKey;
 
 pub mod collider;
+pub mod destruction;
 pub mod helpers;
 pub mod intersection;
 pub mod mesh;
 pub mod physx;
+pub mod ragdoll;
 pub mod rc_asset;
 pub mod visualization;
 
@@ -79,6 +81,8 @@ pub fn init_all_components() {
     init_components();
     physx::init_components();
     collider::init_components();
+    destruction::init_components();
+    ragdoll::init_components();
     visualization::init_components();
 }
 
@@ -223,6 +227,8 @@ pub fn server_systems() -> SystemGroup {
                 }
             }),
             Box::new(collider::server_systems()),
+            Box::new(ragdoll::server_systems()),
+            Box::new(destruction::server_systems()),
             Box::new(visualization::server_systems()),
         ],
     )
@@ -232,14 +238,19 @@ pub fn client_systems() -> SystemGroup {
     SystemGroup::new("physics", vec![Box::new(visualization::client_systems())])
 }
 
-/// Starts the physx simulation step concurrently.
+/// Starts the physx simulation step concurrently, scaled by the `time_scale`. It's skipped when the game is paused,
+/// i.e. when the `dtime` is 0.
 ///
 /// Results will be available after [`fetch_simulation_system`]
 pub fn run_simulation_system() -> DynSystem {
     Box::new(FnSystem::new(|world, _| {
         profiling::scope!("run_simulation_system");
+        if *world.resource(dtime()) <= 0. {
+            return;
+        }
+        let scale = world.resource_opt(time_scale()).copied().unwrap_or(1.);
         let scene = world.resource(main_physics_scene());
-        scene.simulate(1. / 60.);
+        scene.simulate(scale / 60.);
     }))
 }
 
@@ -252,6 +263,10 @@ pub fn fetch_simulation_system() -> DynSystem {
 
         world.resource(collisions()).lock().clear();
         world.resource_mut(collider_loads()).clear();
+        if *world.resource(dtime()) <= 0. {
+            // The simulation wasn't started
+            return;
+        }
         let scene = world.resource(main_physics_scene());
         // Ensure the previous simulation has completed
         scene.fetch_results(true);
@@ -287,6 +302,9 @@ pub fn on_forking_systems() -> SystemGroup<ForkingEvent> {
             for (id, _) in query(()).incl(revolute_joint()).collect_cloned(world, None) {
                 world.remove_component(id, revolute_joint()).unwrap();
             }
+            for (id, _) in query(()).incl(spherical_joint()).collect_cloned(world, None) {
+                world.remove_component(id, spherical_joint()).unwrap();
+            }
             for (id, _) in query(()).incl(articulation_reduce_coordinate()).collect_cloned(world, None) {
                 world.remove_component(id, articulation_reduce_coordinate()).unwrap();
             }
@@ -27,6 +27,7 @@ components!("physics", {
     physics_shape: PxShape,
     fixed_joint: PxFixedJointRef,
     revolute_joint: PxRevoluteJointRef,
+    spherical_joint: PxSphericalJointRef,
     articulation_reduce_coordinate: PxArticulationRef,
     articulation_link: PxArticulationLinkRef,
     articulation_cache: Option<PxArticulationCacheRef>,
@@ -0,0 +1,513 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    sync::Arc,
+};
+
+use ambient_animation::ragdoll::{ragdoll_bone, ragdoll_model, ragdoll_weight};
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    dtime,
+    hierarchy::children,
+    runtime,
+    transform::{get_world_transform, rotation, scale, translation},
+};
+use ambient_ecs::{
+    components, id, query, world_events, Debuggable, Description, Entity, EntityId, FnSystem, Name, Networked, Store, SystemGroup, World,
+    WorldEventReader,
+};
+use ambient_event_types::ENABLE_RAGDOLL;
+use ambient_model::{animation_bind_id, model_from_url, Model};
+use ambient_std::{
+    asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
+    download_asset::{AssetError, BytesFromUrl},
+};
+use async_trait::async_trait;
+use glam::{Mat4, Quat, Vec3};
+use itertools::Itertools;
+use physxx::{
+    AsPxActor, AsPxRigidActor, PxAggregateRef, PxCapsuleGeometry, PxJoint, PxJointLimitCone, PxRigidActor, PxRigidBody, PxRigidDynamicRef,
+    PxShape, PxSphericalJointFlag, PxSphericalJointRef, PxTransform, PxUserData,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collider::mass,
+    main_physics_scene,
+    physx::{actor_aggregate, linear_velocity, physics, physics_controlled, physics_shape, rigid_actor, rigid_dynamic, spherical_joint},
+    PxActorUserData, PxShapeUserData, PxWoodMaterialKey,
+};
+
+components!("physics", {
+    /// How the ragdoll of this model is generated from its skeleton; see [RagdollBuilder]
+    @[Debuggable, Networked, Store]
+    ragdoll_builder: RagdollBuilder,
+    @[
+        Debuggable, Networked, Store,
+        Name["Ragdoll target weight"],
+        Description["The `ragdoll_weight` this model blends towards, over the `ragdoll_blend_time`. The ragdoll is built from the model's skeleton when this is first above 0, and removed once the model has blended back to 0.\nSet by the `ENABLE_RAGDOLL` event."]
+    ]
+    ragdoll_target_weight: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Ragdoll blend time"],
+        Description["How many seconds the `ragdoll_weight` of this model takes to blend between 0 and 1. Defaults to 0.2."]
+    ]
+    ragdoll_blend_time: f32,
+    /// The physics bodies of the ragdoll of this model
+    @[Debuggable]
+    ragdoll_bodies: Vec<EntityId>,
+    /// The ragdoll of this model is being built, or failed to be
+    @[Debuggable]
+    ragdoll_loading: (),
+});
+
+const DEFAULT_BLEND_TIME: f32 = 0.2;
+/// The bones which get a body by default, if the model has them
+const HUMANOID_BONES: &[&str] = &[
+    "Hips",
+    "Spine",
+    "Spine2",
+    "Head",
+    "LeftArm",
+    "LeftForeArm",
+    "RightArm",
+    "RightForeArm",
+    "LeftUpLeg",
+    "LeftLeg",
+    "RightUpLeg",
+    "RightLeg",
+];
+/// The length of the bodies of the bones without children to reach to
+const LEAF_LENGTH: f32 = 0.1;
+const MIN_LENGTH: f32 = 0.01;
+const JOINT_CONTACT_DISTANCE: f32 = 0.05;
+
+/// Generates the capsule colliders and joints of a ragdoll from the skeleton of a model. Each body reaches from its bone
+/// to the bones which have the next bodies, and is jointed to the body of the nearest bone above it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RagdollBuilder {
+    /// The bind ids of the bones which get a body. Defaults to the main bones of a humanoid, or else to all of the bones
+    /// of the model's skin
+    pub bones: Option<Vec<String>>,
+    /// The radius of the capsules, relative to the lengths of their bones
+    pub radius: f32,
+    /// The total mass of the ragdoll, in kilograms, which is shared by the bodies by their volumes
+    pub mass: f32,
+    /// How far, in radians, the bodies can swing away from the rest pose around their joints
+    pub swing_limit: f32,
+    /// Overrides the settings of single bones, by their bind ids
+    pub overrides: HashMap<String, RagdollBoneOverride>,
+}
+impl Default for RagdollBuilder {
+    fn default() -> Self {
+        Self {
+            bones: None,
+            radius: 0.25,
+            mass: 70.,
+            swing_limit: PI / 4.,
+            overrides: HashMap::from([
+                ("Hips".to_string(), RagdollBoneOverride { end: Some("Spine".to_string()), ..Default::default() }),
+                ("Spine2".to_string(), RagdollBoneOverride { end: Some("Neck".to_string()), ..Default::default() }),
+            ]),
+        }
+    }
+}
+
+/// The settings of a single bone of a [RagdollBuilder]. The lengths are in the units of the model
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RagdollBoneOverride {
+    /// The bind id of the bone the capsule reaches to, instead of the bones with the next bodies
+    pub end: Option<String>,
+    pub length: Option<f32>,
+    pub radius: Option<f32>,
+    /// The mass of the body, in kilograms, instead of its share of the total
+    pub mass: Option<f32>,
+    pub swing_limit: Option<f32>,
+}
+
+impl RagdollBuilder {
+    pub fn with_bones(mut self, bones: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.bones = Some(bones.into_iter().map(|bone| bone.into()).collect());
+        self
+    }
+    pub fn with_override(mut self, bone: impl Into<String>, bone_override: RagdollBoneOverride) -> Self {
+        self.overrides.insert(bone.into(), bone_override);
+        self
+    }
+
+    /// The ragdoll of `model`, in its rest pose, when it's placed at `transform`
+    pub fn build(&self, model: &Model, transform: Mat4) -> anyhow::Result<Ragdoll> {
+        let skeleton = Skeleton::new(model, transform);
+        let bones = self.bone_nodes(model, &skeleton);
+        anyhow::ensure!(!bones.is_empty(), "The model has no bones to build a ragdoll of");
+        let is_body = bones.iter().copied().collect::<HashSet<_>>();
+        let body_parent = |node: EntityId| skeleton.ancestors(node).find(|ancestor| is_body.contains(ancestor));
+        let bones = bones.into_iter().sorted_by_key(|&bone| skeleton.ancestors(bone).count()).collect_vec();
+        let scale = transform.to_scale_rotation_translation().0.max_element();
+
+        let mut bodies = Vec::<RagdollBody>::new();
+        for &bone in &bones {
+            let bind_id = skeleton.bind_ids[&bone].clone();
+            let settings = self.overrides.get(&bind_id).cloned().unwrap_or_default();
+            let (_, rotation, position) = skeleton.transforms[&bone].to_scale_rotation_translation();
+            let parent = body_parent(bone).map(|parent| bones.iter().position(|&bone| bone == parent).unwrap());
+            let body_children =
+                bones.iter().filter(|&&child| body_parent(child) == Some(bone)).map(|&child| skeleton.position(child)).collect_vec();
+
+            let end = settings
+                .end
+                .as_ref()
+                .and_then(|end| skeleton.by_bind_id(end))
+                .map(|end| skeleton.position(end))
+                .or_else(|| average(&body_children))
+                .or_else(|| average(&skeleton.children(bone).map(|child| skeleton.position(child)).collect_vec()))
+                .filter(|end| end.distance(position) > MIN_LENGTH * scale);
+            let (axis, length) = match end {
+                Some(end) => ((end - position).normalize(), end.distance(position)),
+                // Bones without anything to reach to continue the way their parent points
+                None => {
+                    let from = parent.map(|parent| bodies[parent].position).unwrap_or(position - Vec3::Z);
+                    ((position - from).try_normalize().unwrap_or(Vec3::Z), LEAF_LENGTH * scale)
+                }
+            };
+            let length = settings.length.map(|length| length * scale).unwrap_or(length);
+            // The bodies with several bodies below them, like the hips, are as wide as their children are spread out
+            let spread = body_children
+                .iter()
+                .map(|child| {
+                    let offset = *child - position;
+                    (offset - axis * offset.dot(axis)).length()
+                })
+                .fold(0., f32::max);
+            let radius = settings.radius.map(|radius| radius * scale).unwrap_or((self.radius * length).max(spread));
+
+            bodies.push(RagdollBody {
+                bone: bind_id,
+                parent,
+                position,
+                rotation,
+                capsule_center: rotation.inverse() * axis * length / 2.,
+                capsule_rotation: rotation.inverse() * Quat::from_rotation_arc(Vec3::X, axis),
+                radius,
+                half_height: (length / 2. - radius).max(0.),
+                mass: settings.mass.unwrap_or(0.),
+                swing_limit: settings.swing_limit.unwrap_or(self.swing_limit).clamp(0.01, PI - 0.01),
+            });
+        }
+
+        // The bodies without a mass of their own share the rest of it by their volumes
+        let shared = (0..bodies.len()).filter(|&i| self.overrides.get(&bodies[i].bone).and_then(|o| o.mass).is_none()).collect_vec();
+        let overridden_mass = bodies.iter().map(|body| body.mass).sum::<f32>();
+        let volume = shared.iter().map(|&i| bodies[i].volume()).sum::<f32>();
+        for i in shared {
+            bodies[i].mass = (self.mass - overridden_mass).max(0.) * bodies[i].volume() / volume.max(f32::EPSILON);
+        }
+        Ok(Ragdoll { bodies })
+    }
+
+    fn bone_nodes(&self, model: &Model, skeleton: &Skeleton) -> Vec<EntityId> {
+        let find = |bones: &mut dyn Iterator<Item = &str>| bones.filter_map(|bone| skeleton.by_bind_id(bone)).collect_vec();
+        match &self.bones {
+            Some(bones) => find(&mut bones.iter().map(|bone| bone.as_str())),
+            None => {
+                let humanoid = find(&mut HUMANOID_BONES.iter().copied());
+                if humanoid.len() >= 2 {
+                    return humanoid;
+                }
+                model
+                    .skins()
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|skin| skin.joints.iter().copied())
+                    .filter(|joint| skeleton.bind_ids.contains_key(joint))
+                    .unique()
+                    .collect()
+            }
+        }
+    }
+}
+
+fn average(points: &[Vec3]) -> Option<Vec3> {
+    (!points.is_empty()).then(|| points.iter().sum::<Vec3>() / points.len() as f32)
+}
+
+/// The nodes of a model in its rest pose
+struct Skeleton {
+    transforms: HashMap<EntityId, Mat4>,
+    parents: HashMap<EntityId, EntityId>,
+    bind_ids: HashMap<EntityId, String>,
+    model: Model,
+}
+impl Skeleton {
+    fn new(model: &Model, transform: Mat4) -> Self {
+        let mut transforms = HashMap::new();
+        let mut parents = HashMap::new();
+        let root_transform = transform * model.get_transform().unwrap_or(Mat4::IDENTITY);
+        let mut stack = model.roots().into_iter().map(|root| (root, root_transform)).collect_vec();
+        while let Some((node, parent_transform)) = stack.pop() {
+            let transform = parent_transform
+                * Mat4::from_scale_rotation_translation(
+                    model.0.get(node, scale()).unwrap_or(Vec3::ONE),
+                    model.0.get(node, rotation()).unwrap_or(Quat::IDENTITY),
+                    model.0.get(node, translation()).unwrap_or(Vec3::ZERO),
+                );
+            for &child in model.0.get_ref(node, children()).into_iter().flatten() {
+                parents.insert(child, node);
+                stack.push((child, transform));
+            }
+            transforms.insert(node, transform);
+        }
+        let bind_ids = query(animation_bind_id())
+            .iter(&model.0, None)
+            .filter(|(node, _)| transforms.contains_key(node))
+            .map(|(node, bind_id)| (node, bind_id.clone()))
+            .collect();
+        Self { transforms, parents, bind_ids, model: model.clone() }
+    }
+    fn by_bind_id(&self, bind_id: &str) -> Option<EntityId> {
+        self.bind_ids.iter().find(|(_, id)| *id == bind_id).map(|(&node, _)| node)
+    }
+    fn position(&self, node: EntityId) -> Vec3 {
+        self.transforms[&node].transform_point3(Vec3::ZERO)
+    }
+    fn ancestors(&self, node: EntityId) -> impl Iterator<Item = EntityId> + '_ {
+        std::iter::successors(self.parents.get(&node).copied(), |node| self.parents.get(node).copied())
+    }
+    fn children(&self, node: EntityId) -> impl Iterator<Item = EntityId> + '_ {
+        self.model.0.get_ref(node, children()).into_iter().flatten().copied()
+    }
+}
+
+/// The bodies of a ragdoll, generated by a [RagdollBuilder]
+#[derive(Debug, Clone)]
+pub struct Ragdoll {
+    /// The bodies, with the parents before their children
+    pub bodies: Vec<RagdollBody>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RagdollBody {
+    /// The bind id of the bone this body moves
+    pub bone: String,
+    /// The index of the body this one is jointed to
+    pub parent: Option<usize>,
+    /// The pose of the bone in the world, which is the pose of the body
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// The pose of the capsule relative to the bone. The capsule is along its X axis
+    pub capsule_center: Vec3,
+    pub capsule_rotation: Quat,
+    pub radius: f32,
+    pub half_height: f32,
+    pub mass: f32,
+    pub swing_limit: f32,
+}
+impl RagdollBody {
+    fn volume(&self) -> f32 {
+        PI * self.radius * self.radius * (2. * self.half_height + 4. / 3. * self.radius)
+    }
+}
+
+/// The model, without loading its meshes, which the server can't
+#[derive(Debug, Clone)]
+struct ModelSkeletonFromUrl(AbsAssetUrl);
+#[async_trait]
+impl AsyncAssetKey<Result<Arc<Model>, AssetError>> for ModelSkeletonFromUrl {
+    async fn load(self, assets: AssetCache) -> Result<Arc<Model>, AssetError> {
+        let data = BytesFromUrl::new(self.0.clone(), true).get(&assets).await?;
+        Ok(Arc::new(Model::from_slice(&data)?))
+    }
+}
+
+/// Spawns the bodies and joints of `ragdoll` for the model `id`
+pub fn spawn_ragdoll(world: &mut World, id: EntityId, ragdoll: &Ragdoll) {
+    let physics = world.resource(physics()).clone();
+    let material = PxWoodMaterialKey.get(world.resource(asset_cache()));
+    let velocity = world.get(id, linear_velocity()).unwrap_or_default();
+    // The bodies overlap at their joints, so they don't collide with each other
+    let mut aggregate = PxAggregateRef::new(&physics.physics, ragdoll.bodies.len() as u32, false);
+
+    let mut bodies: Vec<(EntityId, PxRigidDynamicRef)> = Vec::new();
+    for body in &ragdoll.bodies {
+        let actor = PxRigidDynamicRef::new(physics.physics, &PxTransform::new(body.position, body.rotation));
+        let shape = PxShape::new(physics.physics, &PxCapsuleGeometry::new(body.radius, body.half_height), &[&material], Some(true), None);
+        shape.set_local_pose(&PxTransform::new(body.capsule_center, body.capsule_rotation));
+        let body_id = Entity::new()
+            .with(translation(), body.position)
+            .with(rotation(), body.rotation)
+            .with(ragdoll_model(), id)
+            .with(ragdoll_bone(), body.bone.clone())
+            .with_default(physics_controlled())
+            .spawn(world);
+        shape.set_user_data(PxShapeUserData {
+            entity: body_id,
+            density: body.mass / body.volume().max(f32::EPSILON),
+            base_pose: Mat4::from_rotation_translation(body.capsule_rotation, body.capsule_center),
+        });
+        actor.attach_shape(&shape);
+        actor.update_mass_and_inertia(vec![body.mass / body.volume().max(f32::EPSILON)], None, None);
+        actor.set_linear_velocity(velocity, true);
+        actor.as_actor().set_user_data(PxActorUserData { serialize: false });
+        aggregate.add_actor(&actor);
+        world
+            .add_components(
+                body_id,
+                Entity::new()
+                    .with(physics_shape(), shape)
+                    .with(rigid_actor(), actor.as_rigid_actor())
+                    .with(rigid_dynamic(), actor)
+                    .with(mass(), body.mass),
+            )
+            .unwrap();
+        bodies.push((body_id, actor));
+    }
+
+    for (body, &(body_id, actor)) in ragdoll.bodies.iter().zip(&bodies) {
+        let Some(parent_index) = body.parent else { continue };
+        let parent = &ragdoll.bodies[parent_index];
+        // The joint is at the bone, with its X axis, around which the swing is limited, along the capsule
+        let frame = body.rotation * body.capsule_rotation;
+        let joint = PxSphericalJointRef::new(
+            physics.physics,
+            Some(bodies[parent_index].1.as_rigid_actor()),
+            &PxTransform::new(parent.rotation.inverse() * (body.position - parent.position), parent.rotation.inverse() * frame),
+            Some(actor.as_rigid_actor()),
+            &PxTransform::new(Vec3::ZERO, body.capsule_rotation),
+        );
+        joint.set_limit_cone(&PxJointLimitCone::new(body.swing_limit, body.swing_limit, JOINT_CONTACT_DISTANCE));
+        joint.set_spherical_joint_flag(PxSphericalJointFlag::LIMIT_ENABLED, true);
+        world.add_component(body_id, spherical_joint(), joint).unwrap();
+    }
+
+    world.resource(main_physics_scene()).add_aggregate(&aggregate);
+    world
+        .add_components(
+            id,
+            Entity::new()
+                .with(ragdoll_bodies(), bodies.into_iter().map(|(body_id, _)| body_id).collect())
+                .with(actor_aggregate(), aggregate),
+        )
+        .unwrap();
+}
+
+/// Despawns the bodies of the ragdoll of the model `id`
+pub fn remove_ragdoll(world: &mut World, id: EntityId) {
+    let bodies = world.get_cloned(id, ragdoll_bodies()).unwrap_or_default();
+    world.remove_component(id, ragdoll_bodies()).ok();
+    if let Ok(mut aggregate) = world.get(id, actor_aggregate()) {
+        aggregate.release();
+        world.remove_component(id, actor_aggregate()).unwrap();
+    }
+    despawn_bodies(world, &bodies);
+}
+
+fn despawn_bodies(world: &mut World, bodies: &[EntityId]) {
+    // The joints are released before the bodies they join
+    for &body in bodies {
+        if let Ok(joint) = world.get(body, spherical_joint()) {
+            joint.release();
+            world.remove_component(body, spherical_joint()).unwrap();
+        }
+    }
+    for &body in bodies {
+        world.despawn(body);
+    }
+}
+
+pub fn server_systems() -> SystemGroup {
+    let mut events = WorldEventReader::new();
+    SystemGroup::new(
+        "ragdoll",
+        vec![
+            Box::new(FnSystem::new(move |world, _| {
+                let enable = events
+                    .iter(world.resource(world_events()))
+                    .filter(|(_, (name, _))| name == ENABLE_RAGDOLL)
+                    .filter_map(|(_, (_, event))| {
+                        Some((event.get(id())?, event.get(ragdoll_target_weight()).unwrap_or(1.), event.get(ragdoll_blend_time())))
+                    })
+                    .collect_vec();
+                for (id, target_weight, blend_time) in enable {
+                    if !world.exists(id) {
+                        continue;
+                    }
+                    world.add_component(id, ragdoll_target_weight(), target_weight.clamp(0., 1.)).unwrap();
+                    if let Some(blend_time) = blend_time {
+                        world.add_component(id, ragdoll_blend_time(), blend_time).unwrap();
+                    }
+                }
+            })),
+            query((ragdoll_target_weight(), model_from_url())).excl(ragdoll_bodies()).excl(ragdoll_loading()).to_system(
+                |q, world, qs, _| {
+                    let models =
+                        q.iter(world, qs).filter(|(_, (&weight, _))| weight > 0.).map(|(id, (_, url))| (id, url.clone())).collect_vec();
+                    if models.is_empty() {
+                        return;
+                    }
+                    let assets = world.resource(asset_cache()).clone();
+                    let runtime = world.resource(runtime()).clone();
+                    let async_run = world.resource(async_run()).clone();
+                    for (id, url) in models {
+                        world.add_component(id, ragdoll_loading(), ()).unwrap();
+                        let builder = world.get_cloned(id, ragdoll_builder()).unwrap_or_default();
+                        let assets = assets.clone();
+                        let async_run = async_run.clone();
+                        runtime.spawn(async move {
+                            let model = match AbsAssetUrl::parse(&url) {
+                                Ok(url) => ModelSkeletonFromUrl(url).get(&assets).await.map_err(|err| anyhow::anyhow!("{err:#}")),
+                                Err(err) => Err(err),
+                            };
+                            async_run.run(move |world| {
+                                if !world.exists(id) {
+                                    return;
+                                }
+                                let transform = get_world_transform(world, id).unwrap_or(Mat4::IDENTITY);
+                                match model.and_then(|model| builder.build(&model, transform)) {
+                                    Ok(ragdoll) => {
+                                        spawn_ragdoll(world, id, &ragdoll);
+                                        world.remove_component(id, ragdoll_loading()).unwrap();
+                                    }
+                                    // The model keeps its `ragdoll_loading`, so that it isn't tried again
+                                    Err(err) => tracing::warn!("Failed to build the ragdoll of {id}: {err:#}"),
+                                }
+                            });
+                        });
+                    }
+                },
+            ),
+            query(ragdoll_target_weight()).to_system(|q, world, qs, _| {
+                let dtime = *world.resource(dtime());
+                for (id, target_weight) in q.collect_cloned(world, qs) {
+                    let has_bodies = world.has_component(id, ragdoll_bodies());
+                    // The model only blends into its ragdoll once the bodies are there
+                    let target_weight = if has_bodies { target_weight } else { 0. };
+                    let weight = world.get(id, ragdoll_weight()).unwrap_or(0.);
+                    let blend_time = world.get(id, ragdoll_blend_time()).unwrap_or(DEFAULT_BLEND_TIME);
+                    let step = if blend_time > 0. { dtime / blend_time } else { 1. };
+                    let new_weight =
+                        if target_weight > weight { (weight + step).min(target_weight) } else { (weight - step).max(target_weight) };
+                    if new_weight != weight || !world.has_component(id, ragdoll_weight()) {
+                        world.add_component(id, ragdoll_weight(), new_weight).unwrap();
+                    }
+                    if has_bodies && new_weight <= 0. && target_weight <= 0. {
+                        remove_ragdoll(world, id);
+                    }
+                }
+            }),
+            query((ragdoll_bodies(), actor_aggregate())).despawned().to_system(|q, world, qs, _| {
+                for (_, (bodies, mut aggregate)) in q.collect_cloned(world, qs) {
+                    aggregate.release();
+                    despawn_bodies(world, &bodies);
+                }
+            }),
+            query(spherical_joint()).despawned().to_system(|q, world, qs, _| {
+                for (_, joint) in q.collect_cloned(world, qs) {
+                    joint.release();
+                }
+            }),
+        ],
+    )
+}
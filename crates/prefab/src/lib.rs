@@ -1,21 +1,31 @@
 use std::{collections::HashMap, sync::Arc};
 
-use ambient_core::{asset_cache, async_ecs::async_run, hierarchy::children, runtime};
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    hierarchy::{children, parent},
+    runtime,
+};
 use ambient_decals::decal;
 use ambient_ecs::{
-    components, query, query_mut, Debuggable, Description, DeserWorldWithWarnings, EntityId, Name, Networked, Store, SystemGroup, World,
+    components, query, query_mut, Debuggable, Description, DeserWorldWithWarnings, EntityId, Name, Networked, Resource, Store, SystemGroup,
+    World,
 };
 use ambient_model::model_from_url;
-use ambient_physics::collider::collider;
+use ambient_physics::{collider::collider, destruction::destructible_chunks};
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt},
-    asset_url::AssetUrl,
+    asset_url::{AbsAssetUrl, AssetUrl},
     download_asset::{AssetError, BytesFromUrl},
     unwrap_log_err,
 };
 use anyhow::Context;
 use async_trait::async_trait;
 
+pub mod prefetch;
+pub mod scene;
+pub mod streaming;
+
 components!("prefab", {
     @[
         Debuggable, Networked, Store,
@@ -29,6 +39,9 @@ components!("prefab", {
         Description["If attached, this entity was built from a prefab that has finished spawning."]
     ]
     spawned: (),
+
+    @[Resource]
+    prefetches: prefetch::Prefetches,
 });
 
 pub fn systems() -> SystemGroup {
@@ -70,18 +83,69 @@ impl AsyncAssetKey<Result<Arc<World>, AssetError>> for PrefabFromUrl {
     async fn load(self, assets: AssetCache) -> Result<Arc<World>, AssetError> {
         let obj_url = self.0.abs().context(format!("PrefabFromUrl got relative url: {}", self.0))?;
         let data = BytesFromUrl::new(obj_url.clone(), true).get(&assets).await?;
-        let DeserWorldWithWarnings { mut world, warnings } = tokio::task::block_in_place(|| serde_json::from_slice(&data))
-            .with_context(|| format!("Failed to deserialize object2 from url {obj_url}"))?;
-        warnings.log_warnings();
-        for (_id, (url,), _) in query_mut((model_from_url(),), ()).iter(&mut world, None) {
-            *url = AssetUrl::parse(&url).context("Invalid model url")?.resolve(&obj_url).context("Failed to resolve model url")?.into();
-        }
-        for (_id, (def,), _) in query_mut((collider(),), ()).iter(&mut world, None) {
-            def.resolve(&obj_url).context("Failed to resolve collider")?;
+        Ok(Arc::new(parse_prefab(&data, &obj_url)?))
+    }
+}
+
+fn parse_prefab(data: &[u8], obj_url: &AbsAssetUrl) -> anyhow::Result<World> {
+    let DeserWorldWithWarnings { mut world, warnings } = tokio::task::block_in_place(|| serde_json::from_slice(data))
+        .with_context(|| format!("Failed to deserialize object2 from url {obj_url}"))?;
+    warnings.log_warnings();
+    for (_id, (url,), _) in query_mut((model_from_url(),), ()).iter(&mut world, None) {
+        *url = AssetUrl::parse(&url).context("Invalid model url")?.resolve(obj_url).context("Failed to resolve model url")?.into();
+    }
+    for (_id, (def,), _) in query_mut((collider(),), ()).iter(&mut world, None) {
+        def.resolve(obj_url).context("Failed to resolve collider")?;
+    }
+    for (_id, (chunks,), _) in query_mut((destructible_chunks(),), ()).iter(&mut world, None) {
+        for chunk in chunks.iter_mut() {
+            chunk.resolve(obj_url).context("Failed to resolve destructible chunk")?;
         }
-        for (_id, (def,), _) in query_mut((decal(),), ()).iter(&mut world, None) {
-            *def = def.resolve(&obj_url).context("Failed to resolve decal")?.into();
+    }
+    for (_id, (def,), _) in query_mut((decal(),), ()).iter(&mut world, None) {
+        *def = def.resolve(obj_url).context("Failed to resolve decal")?.into();
+    }
+    Ok(world)
+}
+
+/// Loads a prefab for editing. Prefabs in the content directory are read directly, so that edits saved with [save_prefab] are picked up
+/// immediately rather than the cached version being returned.
+pub async fn load_prefab_for_editing(assets: &AssetCache, url: &AbsAssetUrl) -> anyhow::Result<World> {
+    match url.to_local_path(assets)? {
+        Some(path) => {
+            let data = tokio::fs::read(&path).await.with_context(|| format!("Failed to read prefab from {path:?}"))?;
+            parse_prefab(&data, url)
         }
-        Ok(Arc::new(world))
+        None => Ok(PrefabFromUrl(url.clone().into()).get(assets).await?.as_ref().clone()),
+    }
+}
+
+/// Exports the given entities into a prefab world, which can be saved with [save_prefab].
+///
+/// The entities keep their ids, so references between them (such as the hierarchy) are preserved. The entities
+/// which don't have a parent among the exported entities become the roots of the prefab.
+pub fn export_prefab(world: &World, entities: &[EntityId]) -> anyhow::Result<World> {
+    let mut prefab = World::new("prefab");
+    for &id in entities {
+        prefab.spawn_with_id(id, world.clone_entity(id)?.serializable());
+    }
+    let roots = entities
+        .iter()
+        .copied()
+        .filter(|&id| prefab.get(id, parent()).map(|parent| !entities.contains(&parent)).unwrap_or(true))
+        .collect::<Vec<_>>();
+    prefab.add_resource(children(), roots);
+    Ok(prefab)
+}
+
+/// Writes a prefab back to the location it is served from. Only prefabs in the content directory of this server can be saved.
+pub async fn save_prefab(assets: &AssetCache, url: &AbsAssetUrl, prefab: &World) -> anyhow::Result<()> {
+    let path =
+        url.to_writable_local_path(assets)?.with_context(|| format!("Can't save prefab {url}; it's not served from this machine"))?;
+    let data = serde_json::to_vec_pretty(prefab)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
     }
+    tokio::fs::write(&path, data).await.with_context(|| format!("Failed to write prefab to {path:?}"))?;
+    Ok(())
 }
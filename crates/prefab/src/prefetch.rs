@@ -0,0 +1,188 @@
+//! Prefetching loads assets into the asset cache ahead of their use, so that gameplay code can hide the hitch of loading
+//! e.g. the level behind a teleport or the props of a cutscene.
+//!
+//! A prefetch downloads and decodes its asset and, for prefabs and models, uploads their meshes and textures to the GPU.
+//! The loaded asset is kept in the cache until the prefetch is cancelled, or until [PREFETCH_KEEPALIVE] has passed.
+use std::{any::Any, collections::HashMap, fmt, sync::Arc, time::Duration};
+
+use ambient_core::{asset_cache, runtime, time};
+use ambient_ecs::{query, FnSystem, SystemGroup, World};
+use ambient_model::{model_from_url, ModelFromUrl};
+use ambient_std::{
+    asset_cache::{AssetCache, AsyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
+    download_asset::BytesFromUrl,
+};
+use ambient_sys::task::ChildTask;
+use futures::future::try_join_all;
+use parking_lot::Mutex;
+
+use crate::{prefetches, PrefabFromUrl};
+
+/// How many prefetches below [PrefetchPriority::High] load at the same time
+pub const MAX_CONCURRENT_PREFETCHES: usize = 4;
+/// How long a loaded prefetch keeps its asset in the cache if it isn't cancelled before
+pub const PREFETCH_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// The order in which queued prefetches start. High priority prefetches start immediately, regardless of how many
+/// others are loading
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum PrefetchPriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefetchStatus {
+    Queued,
+    Loading,
+    Loaded,
+    Failed,
+}
+
+type Asset = Arc<dyn Any + Send + Sync>;
+
+enum PrefetchState {
+    Queued,
+    Loading,
+    /// The asset is held on to so that the cache keeps it
+    Loaded(Asset),
+    Failed,
+}
+
+struct Prefetch {
+    url: AbsAssetUrl,
+    priority: PrefetchPriority,
+    state: Arc<Mutex<PrefetchState>>,
+    /// Aborts the load when the prefetch is cancelled
+    task: Option<ChildTask<()>>,
+    loaded_at: Option<Duration>,
+}
+
+/// The prefetches of this world, by handle
+#[derive(Clone, Default)]
+pub struct Prefetches {
+    prefetches: Arc<Mutex<HashMap<u64, Prefetch>>>,
+    next_handle: u64,
+}
+impl fmt::Debug for Prefetches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prefetches").field("count", &self.prefetches.lock().len()).finish()
+    }
+}
+
+fn prefetches_mut(world: &mut World) -> &mut Prefetches {
+    if world.resource_opt(prefetches()).is_none() {
+        world.add_resource(prefetches(), Prefetches::default());
+    }
+    world.resource_mut(prefetches())
+}
+
+/// Queues `url` to be loaded into the asset cache, and returns the handle of the prefetch. Prefabs are loaded along
+/// with their models; a URL ending in the extension of a model file, like `Cube.glb`, is the prefab the pipeline built
+/// from it, as with `prefab_from_url`
+pub fn prefetch_asset(world: &mut World, url: AbsAssetUrl, priority: PrefetchPriority) -> u64 {
+    let prefetches = prefetches_mut(world);
+    let handle = prefetches.next_handle;
+    prefetches.next_handle += 1;
+    prefetches
+        .prefetches
+        .lock()
+        .insert(handle, Prefetch { url, priority, state: Arc::new(Mutex::new(PrefetchState::Queued)), task: None, loaded_at: None });
+    handle
+}
+
+/// Stops the prefetch with `handle` if it's still loading, and releases its asset. Anything already using the asset
+/// keeps it loaded
+pub fn cancel_prefetch(world: &mut World, handle: u64) {
+    prefetches_mut(world).prefetches.lock().remove(&handle);
+}
+
+/// The status of the prefetch with `handle`, or `None` if it was cancelled or has expired
+pub fn prefetch_status(world: &World, handle: u64) -> Option<PrefetchStatus> {
+    let prefetches = world.resource_opt(prefetches())?.prefetches.lock();
+    let state = prefetches.get(&handle)?.state.lock();
+    Some(match &*state {
+        PrefetchState::Queued => PrefetchStatus::Queued,
+        PrefetchState::Loading => PrefetchStatus::Loading,
+        PrefetchState::Loaded(_) => PrefetchStatus::Loaded,
+        PrefetchState::Failed => PrefetchStatus::Failed,
+    })
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new("prefab/prefetch", vec![Box::new(FnSystem::new(|world, _| update_prefetches(world)))])
+}
+
+/// Starts queued prefetches in order of priority, and expires the ones which have been loaded for long enough
+fn update_prefetches(world: &mut World) {
+    let Some(prefetches) = world.resource_opt(prefetches()).map(|p| p.prefetches.clone()) else { return };
+    let assets = world.resource(asset_cache()).clone();
+    let runtime = world.resource(runtime()).clone();
+    let now = *world.resource(time());
+
+    let mut prefetches = prefetches.lock();
+    prefetches.retain(|_, prefetch| {
+        if matches!(*prefetch.state.lock(), PrefetchState::Loaded(_)) {
+            prefetch.task = None;
+            let loaded_at = *prefetch.loaded_at.get_or_insert(now);
+            return now - loaded_at < PREFETCH_KEEPALIVE;
+        }
+        true
+    });
+
+    let mut loading = prefetches.values().filter(|prefetch| matches!(*prefetch.state.lock(), PrefetchState::Loading)).count();
+    let mut queued = prefetches
+        .iter_mut()
+        .filter(|(_, prefetch)| matches!(*prefetch.state.lock(), PrefetchState::Queued))
+        .map(|(handle, prefetch)| ((prefetch.priority, *handle), prefetch))
+        .collect::<Vec<_>>();
+    queued.sort_by_key(|(order, _)| *order);
+    for ((priority, _), prefetch) in queued {
+        if priority != PrefetchPriority::High && loading >= MAX_CONCURRENT_PREFETCHES {
+            break;
+        }
+        loading += 1;
+        *prefetch.state.lock() = PrefetchState::Loading;
+        let state = prefetch.state.clone();
+        let assets = assets.clone();
+        let url = prefetch.url.clone();
+        prefetch.task = Some(
+            runtime
+                .spawn(async move {
+                    let loaded = match load(&assets, url.clone()).await {
+                        Ok(asset) => PrefetchState::Loaded(asset),
+                        Err(err) => {
+                            log::warn!("Failed to prefetch {url}: {err:?}");
+                            PrefetchState::Failed
+                        }
+                    };
+                    *state.lock() = loaded;
+                })
+                .into(),
+        );
+    }
+}
+
+async fn load(assets: &AssetCache, url: AbsAssetUrl) -> anyhow::Result<Asset> {
+    let is_json_in = |dir: &str| url.extension_is("json") && url.path().as_str().contains(&format!("/{dir}/"));
+    let (is_prefab, is_model) = (is_json_in("prefabs"), is_json_in("models"));
+    if is_prefab {
+        load_prefab(assets, url).await
+    } else if is_model {
+        Ok(ModelFromUrl(url.into()).get(assets).await?)
+    } else if matches!(url.extension().as_deref(), Some("glb" | "gltf" | "fbx" | "obj")) {
+        load_prefab(assets, url.push("prefabs/main.json")?).await
+    } else {
+        Ok(BytesFromUrl::new(url, true).get(assets).await?)
+    }
+}
+
+async fn load_prefab(assets: &AssetCache, url: AbsAssetUrl) -> anyhow::Result<Asset> {
+    let prefab = PrefabFromUrl(url.into()).get(assets).await?;
+    let models = query(model_from_url()).iter(&prefab, None).map(|(_, url)| ModelFromUrl::new(url)).collect::<Result<Vec<_>, _>>()?;
+    let models = try_join_all(models.into_iter().map(|model| model.get(assets))).await?;
+    Ok(Arc::new((prefab, models)))
+}
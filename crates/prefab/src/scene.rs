@@ -0,0 +1,133 @@
+//! Scenes are human-editable files listing entities by their components, and the prefabs they're built from. Unlike
+//! prefabs, which are dumps of a world, they're meant to be written by hand as well as by the editor, and to be kept in
+//! version control as the level content of a project.
+//!
+//! A scene is either a `.scene.toml` or a `.scene.json` file:
+//!
+//! ```toml
+//! [[entities]]
+//! prefab = "assets/Door.glb"
+//!
+//! [entities.components]
+//! "core::app::name" = "Front door"
+//! "core::transform::translation" = [0.0, 2.0, 0.0]
+//! ```
+//!
+//! Components without a value, like `core::player::player`, are written as `{}` in TOML, which has no null.
+use std::path::Path;
+
+use ambient_ecs::{Entity, EntityId, World};
+use ambient_std::asset_url::AbsAssetUrl;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::prefab_from_url;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scene {
+    #[serde(default)]
+    pub entities: Vec<SceneEntity>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneEntity {
+    /// Lets the components of other entities of the scene, like `parent`, refer to this one. The entities get new ids when
+    /// the scene is spawned, and these references are updated to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<EntityId>,
+    /// The prefab the entity is built from, as with `prefab_from_url`. Relative URLs are resolved against the URL the
+    /// scene is spawned with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefab: Option<String>,
+    /// The components of the entity, by their path. They're added to, or replace, the components of the prefab
+    #[serde(default)]
+    pub components: Entity,
+}
+
+impl Scene {
+    /// Reads a scene from a `.scene.toml` or `.scene.json` file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(path).with_context(|| format!("Failed to read the scene {path:?}"))?;
+        let scene = if is_toml(path) { Self::from_toml(&data) } else { serde_json::from_str(&data).map_err(Into::into) };
+        scene.with_context(|| format!("Failed to parse the scene {path:?}"))
+    }
+
+    /// Writes the scene to a `.scene.toml` or `.scene.json` file
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let data = if is_toml(path) { self.to_toml()? } else { serde_json::to_string_pretty(self)? };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data).with_context(|| format!("Failed to write the scene to {path:?}"))
+    }
+
+    pub fn from_toml(toml: &str) -> anyhow::Result<Self> {
+        let mut scene = serde_json::to_value(toml::from_str::<toml::Value>(toml)?)?;
+        for_each_component(&mut scene, |value| {
+            if value.as_object().map_or(false, |value| value.is_empty()) {
+                *value = serde_json::Value::Null;
+            }
+        });
+        Ok(serde_json::from_value(scene)?)
+    }
+
+    pub fn to_toml(&self) -> anyhow::Result<String> {
+        let mut scene = serde_json::to_value(self)?;
+        for_each_component(&mut scene, |value| {
+            if value.is_null() {
+                *value = serde_json::Value::Object(Default::default());
+            }
+        });
+        Ok(toml::to_string_pretty(&toml::Value::try_from(scene)?)?)
+    }
+
+    /// Builds a scene from the stored components of `entities`. An entity with a `prefab_from_url` is written as built from
+    /// that prefab
+    pub fn from_world(world: &World, entities: &[EntityId]) -> anyhow::Result<Self> {
+        let entities = entities
+            .iter()
+            .map(|&id| {
+                let mut components = world.clone_entity(id)?.serializable();
+                let prefab = components.get_cloned(prefab_from_url());
+                components.remove_self(prefab_from_url());
+                Ok(SceneEntity { id: Some(id), prefab, components })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { entities })
+    }
+
+    /// Spawns the entities of the scene into `world`, resolving their prefabs against `base_url`, and returns their ids
+    pub fn spawn(&self, world: &mut World, base_url: &AbsAssetUrl) -> anyhow::Result<Vec<EntityId>> {
+        let mut scene = World::new("scene");
+        for entity in &self.entities {
+            let mut components = entity.components.clone();
+            if let Some(prefab) = &entity.prefab {
+                let url = base_url.resolve(prefab).with_context(|| format!("Invalid prefab url {prefab:?}"))?;
+                components.set(prefab_from_url(), url.to_string());
+            }
+            match entity.id {
+                Some(id) => {
+                    anyhow::ensure!(scene.spawn_with_id(id, components), "The id {id} is used by more than one entity");
+                }
+                None => {
+                    scene.spawn(components);
+                }
+            }
+        }
+        Ok(scene.spawn_into_world(world, None))
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "toml")
+}
+
+/// Calls `f` with the value of each component of each entity of a serialized scene
+fn for_each_component(scene: &mut serde_json::Value, mut f: impl FnMut(&mut serde_json::Value)) {
+    let Some(entities) = scene.get_mut("entities").and_then(|entities| entities.as_array_mut()) else { return };
+    for entity in entities {
+        if let Some(components) = entity.get_mut("components").and_then(|components| components.as_object_mut()) {
+            components.values_mut().for_each(&mut f);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Streaming splits a large world into cells, which are loaded when a player, or another [streaming_observer], comes close
+//! to them and unloaded once they leave, so that only the parts of the world around the players are kept in memory.
+//!
+//! The `Streaming` build pipeline splits the entities of a `.world.json` file by their [streaming_cell] into one prefab per
+//! cell, and writes a [StreamingIndex] with the bounds of each cell next to them. Cells are loaded on the server, and each
+//! player is only replicated the cells around them.
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use ambient_core::{asset_cache, async_ecs::async_run, player::user_id, runtime, transform::translation};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Store, SystemGroup, World};
+use ambient_network::server::{player_entity_stream, player_replication_filter, ReplicationFilter};
+use ambient_std::{asset_cache::AsyncAssetKeyExt, asset_url::AbsAssetUrl, download_asset::BytesFromUrl, unwrap_log_err};
+use ambient_sys::time::Instant;
+use anyhow::Context;
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::PrefabFromUrl;
+
+components!("streaming", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Streaming cell"],
+        Description["The cell of a streamed world this entity belongs to. The entities of a cell are loaded and unloaded together, depending on how close players are to them.\nSet on the entities of a `.world.json` file, which the `Streaming` pipeline splits into cells."]
+    ]
+    streaming_cell: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Streaming world from URL"],
+        Description["Streams the world the `Streaming` pipeline built at this URL, e.g. `assets/level.world`, loading its cells around players.\nThe entities of the world which don't belong to a cell are loaded immediately."]
+    ]
+    streaming_world_from_url: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Streaming radius"],
+        Description["How close, in meters, a player has to be to a cell of the world streamed by this entity for the cell to be loaded. Defaults to 100."]
+    ]
+    streaming_radius: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Streaming observer"],
+        Description["If attached, the cells of streamed worlds are loaded around this entity, as they are around players."]
+    ]
+    streaming_observer: (),
+
+    @[
+        Debuggable,
+        Name["Streaming state"],
+        Description["The index of the world streamed by this entity, and the entities of its loaded cells."]
+    ]
+    streaming_state: StreamingState,
+});
+
+/// How close an observer has to be to a cell for it to be loaded, if the world has no [streaming_radius]
+pub const DEFAULT_STREAMING_RADIUS: f32 = 100.;
+/// Cells are unloaded once every observer is further from them than this many times the radius, so that an observer at
+/// the edge of the radius doesn't load and unload a cell over and over
+pub const UNLOAD_RADIUS_FACTOR: f32 = 1.25;
+/// How long to wait before loading a cell again after its first failed load. The delay doubles with every failure, up to
+/// [MAX_RETRY_DELAY]
+pub const RETRY_DELAY: Duration = Duration::from_secs(1);
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// The file the `Streaming` pipeline writes the [StreamingIndex] of a world to, in the directory of the world
+pub const STREAMING_INDEX_FILE: &str = "index.json";
+
+/// Describes the cells of a streamed world
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamingIndex {
+    /// The prefab with the entities which don't belong to a cell, relative to the index
+    pub main: Option<String>,
+    pub cells: Vec<StreamingCell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamingCell {
+    pub id: String,
+    /// The prefab with the entities of the cell, relative to the index
+    pub url: String,
+    /// The center of the sphere around the translations of the cell's entities
+    pub center: Vec3,
+    pub radius: f32,
+}
+impl StreamingCell {
+    /// The sphere around `positions`, as a center and a radius
+    pub fn bounds(positions: &[Vec3]) -> (Vec3, f32) {
+        if positions.is_empty() {
+            return (Vec3::ZERO, 0.);
+        }
+        let min = positions.iter().copied().fold(Vec3::splat(f32::INFINITY), Vec3::min);
+        let max = positions.iter().copied().fold(Vec3::splat(f32::NEG_INFINITY), Vec3::max);
+        let center = (min + max) / 2.;
+        (center, positions.iter().map(|p| p.distance(center)).fold(0., f32::max))
+    }
+
+    /// The distance from `position` to the bounds of the cell
+    pub fn distance(&self, position: Vec3) -> f32 {
+        (position.distance(self.center) - self.radius).max(0.)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamingState {
+    index_url: AbsAssetUrl,
+    index: Arc<StreamingIndex>,
+    /// The entities which don't belong to a cell
+    main: Vec<EntityId>,
+    /// The cells which are loading, loaded or failed to load, by their position in the index
+    cells: HashMap<usize, CellState>,
+    /// The cells close enough to each user to be replicated to them, by user id
+    visible: HashMap<String, HashSet<usize>>,
+    /// Whether a cell was loaded or unloaded since the [player_replication_filter]s were last updated
+    filters_outdated: bool,
+}
+impl StreamingState {
+    pub fn index(&self) -> &StreamingIndex {
+        &self.index
+    }
+    /// The ids of the cells which are loaded
+    pub fn loaded_cells(&self) -> impl Iterator<Item = &str> + '_ {
+        self.cells.iter().filter(|(_, state)| matches!(state, CellState::Loaded(_))).map(|(i, _)| self.index.cells[*i].id.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CellState {
+    /// After `attempts` failed loads
+    Loading {
+        attempts: u32,
+    },
+    Loaded(Vec<EntityId>),
+    /// Treated as unloaded, but not loaded again before `retry_at`
+    Failed {
+        attempts: u32,
+        retry_at: Instant,
+    },
+}
+
+/// How long to wait before loading a cell again after `attempts` failed loads
+fn retry_delay(attempts: u32) -> Duration {
+    RETRY_DELAY.saturating_mul(1 << attempts.saturating_sub(1).min(16)).min(MAX_RETRY_DELAY)
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "streaming",
+        vec![
+            query(streaming_world_from_url()).spawned().to_system(|q, world, qs, _| {
+                for (id, url) in q.collect_cloned(world, qs) {
+                    load_index(world, id, url);
+                }
+            }),
+            query(streaming_state()).despawned().to_system(|q, world, qs, _| {
+                for (_, state) in q.collect_cloned(world, qs) {
+                    for cell in state.cells.into_values() {
+                        if let CellState::Loaded(ids) = cell {
+                            despawn_all(world, ids);
+                        }
+                    }
+                    despawn_all(world, state.main);
+                }
+            }),
+            query(()).incl(streaming_state()).to_system(|q, world, qs, _| {
+                let observers = observers(world);
+                let ids = q.collect_ids(world, qs);
+                let mut filters_outdated = false;
+                for &id in &ids {
+                    filters_outdated |= update_cells(world, id, &observers);
+                }
+                let unfiltered =
+                    query(()).incl(player_entity_stream()).excl(player_replication_filter()).iter(world, None).next().is_some();
+                if filters_outdated || (unfiltered && !ids.is_empty()) {
+                    update_replication_filters(world, &ids);
+                }
+            }),
+        ],
+    )
+}
+
+fn load_index(world: &mut World, id: EntityId, url: String) {
+    let url = match AbsAssetUrl::parse(&url) {
+        Ok(url) if url.path().as_str().ends_with(STREAMING_INDEX_FILE) => url,
+        Ok(url) => unwrap_log_err!(url.push(STREAMING_INDEX_FILE)),
+        Err(err) => {
+            log::warn!("Failed to parse streaming_world_from_url url: {err:?}");
+            return;
+        }
+    };
+    let assets = world.resource(asset_cache()).clone();
+    let async_run = world.resource(async_run()).clone();
+    world.resource(runtime()).spawn(async move {
+        let index = unwrap_log_err!(BytesFromUrl::new(url.clone(), true).get(&assets).await);
+        let index: StreamingIndex =
+            unwrap_log_err!(serde_json::from_slice(&index).with_context(|| format!("Invalid streaming index at {url}")));
+        let main = match &index.main {
+            Some(main) => {
+                let main = unwrap_log_err!(url.resolve(main));
+                Some(unwrap_log_err!(PrefabFromUrl(main.into()).get(&assets).await))
+            }
+            None => None,
+        };
+        async_run.run(move |world| {
+            if !world.exists(id) {
+                return;
+            }
+            let main = main.map(|main| main.spawn_into_world(world, None)).unwrap_or_default();
+            let state = StreamingState {
+                index_url: url,
+                index: Arc::new(index),
+                main,
+                cells: HashMap::new(),
+                visible: HashMap::new(),
+                filters_outdated: false,
+            };
+            world.add_component(id, streaming_state(), state).unwrap();
+        });
+    });
+}
+
+/// The positions cells are loaded around, by the user they belong to. A user is observing from each entity with their
+/// [user_id] and a translation, such as their player, body and camera; a [streaming_observer] without one doesn't belong to
+/// any user
+fn observers(world: &World) -> HashMap<Option<String>, Vec<Vec3>> {
+    let mut observers = HashMap::<_, Vec<_>>::new();
+    for (_, (user_id, &position)) in query((user_id(), translation())).iter(world, None) {
+        observers.entry(Some(user_id.clone())).or_default().push(position);
+    }
+    for (_, &position) in query(translation()).incl(streaming_observer()).excl(user_id()).iter(world, None) {
+        observers.entry(None).or_default().push(position);
+    }
+    observers
+}
+
+/// Loads the cells of the world streamed by `id` which an observer is close to, and unloads the ones no observer is close to.
+/// Returns whether the [player_replication_filter]s need to be updated
+fn update_cells(world: &mut World, id: EntityId, observers: &HashMap<Option<String>, Vec<Vec3>>) -> bool {
+    let radius = world.get(id, streaming_radius()).unwrap_or(DEFAULT_STREAMING_RADIUS);
+    let state = world.get_mut(id, streaming_state()).unwrap();
+    let now = Instant::now();
+    let distance =
+        |cell: &StreamingCell, positions: &[Vec3]| positions.iter().map(|&position| cell.distance(position)).fold(f32::INFINITY, f32::min);
+    let positions = observers.values().flatten().copied().collect::<Vec<_>>();
+    let mut to_load = Vec::new();
+    let mut to_unload = Vec::new();
+    for (i, cell) in state.index.cells.iter().enumerate() {
+        let distance = distance(cell, &positions);
+        match state.cells.get(&i) {
+            None if distance <= radius => to_load.push((i, 0)),
+            Some(&CellState::Failed { attempts, retry_at }) if distance <= radius && retry_at <= now => to_load.push((i, attempts)),
+            Some(_) if distance > radius * UNLOAD_RADIUS_FACTOR => to_unload.push(i),
+            _ => {}
+        }
+    }
+
+    // Like loading, a cell stops being replicated to a user once they're further from it than the unload radius
+    let visible = observers
+        .iter()
+        .filter_map(|(user_id, positions)| {
+            let user_id = user_id.as_ref()?;
+            let previous = state.visible.get(user_id);
+            let cells = state.index.cells.iter().enumerate().filter(|(i, cell)| {
+                let distance = distance(*cell, positions);
+                distance <= radius || (distance <= radius * UNLOAD_RADIUS_FACTOR && previous.map_or(false, |previous| previous.contains(i)))
+            });
+            Some((user_id.clone(), cells.map(|(i, _)| i).collect()))
+        })
+        .collect::<HashMap<_, HashSet<_>>>();
+    let filters_outdated = std::mem::take(&mut state.filters_outdated) || visible != state.visible || !to_unload.is_empty();
+    state.visible = visible;
+
+    let to_despawn = to_unload.into_iter().filter_map(|i| match state.cells.remove(&i) {
+        Some(CellState::Loaded(ids)) => Some(ids),
+        // The load is discarded when it finishes
+        _ => None,
+    });
+    let to_despawn = to_despawn.flatten().collect::<Vec<_>>();
+    let to_load = to_load
+        .into_iter()
+        .filter_map(|(i, attempts)| match state.index_url.resolve(&state.index.cells[i].url) {
+            Ok(url) => {
+                state.cells.insert(i, CellState::Loading { attempts });
+                Some((i, url))
+            }
+            Err(err) => {
+                let attempts = attempts + 1;
+                log::warn!("Invalid url of streaming cell {}: {err:?}", state.index.cells[i].id);
+                state.cells.insert(i, CellState::Failed { attempts, retry_at: now + retry_delay(attempts) });
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    despawn_all(world, to_despawn);
+
+    for (i, url) in to_load {
+        let assets = world.resource(asset_cache()).clone();
+        let async_run = world.resource(async_run()).clone();
+        world.resource(runtime()).spawn(async move {
+            let cell = PrefabFromUrl(url.clone().into()).get(&assets).await;
+            async_run.run(move |world| {
+                // The cell may have been unloaded while it was loading
+                let Ok(state) = world.get_mut(id, streaming_state()) else { return };
+                let Some(&CellState::Loading { attempts }) = state.cells.get(&i) else { return };
+                match cell {
+                    Ok(cell) => {
+                        let ids = cell.spawn_into_world(world, None);
+                        let state = world.get_mut(id, streaming_state()).unwrap();
+                        state.cells.insert(i, CellState::Loaded(ids));
+                        state.filters_outdated = true;
+                    }
+                    Err(err) => {
+                        let attempts = attempts + 1;
+                        let delay = retry_delay(attempts);
+                        log::warn!("Failed to load streaming cell {url}, retrying in {delay:?}: {err:?}");
+                        state.cells.insert(i, CellState::Failed { attempts, retry_at: Instant::now() + delay });
+                    }
+                }
+            });
+        });
+    }
+    filters_outdated
+}
+
+/// Sets the [player_replication_filter] of every player, and spectator, so that the loaded cells of the worlds streamed by
+/// `ids` are only replicated to the users close to them
+fn update_replication_filters(world: &mut World, ids: &[EntityId]) {
+    let players = query(user_id()).incl(player_entity_stream()).collect_cloned(world, None);
+    for (player_id, user_id) in players {
+        let mut hidden = HashSet::new();
+        for &id in ids {
+            let state = world.get_ref(id, streaming_state()).unwrap();
+            let visible = state.visible.get(&user_id);
+            for (i, cell) in &state.cells {
+                if let CellState::Loaded(ids) = cell {
+                    if !visible.map_or(false, |visible| visible.contains(i)) {
+                        hidden.extend(ids.iter().copied());
+                    }
+                }
+            }
+        }
+        world.add_component(player_id, player_replication_filter(), ReplicationFilter::new(move |_, id| !hidden.contains(&id))).unwrap();
+    }
+}
+
+fn despawn_all(world: &mut World, ids: Vec<EntityId>) {
+    for id in ids {
+        world.despawn(id);
+    }
+}
@@ -25,6 +25,9 @@ use ambient_std::{
 };
 use glam::{vec3, Mat4, Quat, Vec3, Vec4};
 
+pub mod procedural;
+pub mod spline;
+
 components!("primitives", {
     @[
         Networked, Store, Debuggable,
@@ -170,6 +173,8 @@ pub fn systems() -> SystemGroup {
                     extend(world, id, data);
                 }
             }),
+            Box::new(spline::systems()),
+            Box::new(procedural::systems()),
         ],
     )
 }
@@ -0,0 +1,220 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use ambient_core::{
+    asset_cache,
+    bounding::{local_bounding_aabb, world_bounding_aabb, world_bounding_sphere},
+    main_scene, mesh,
+    transform::{local_to_world, mesh_to_world, translation},
+    ui_scene,
+};
+use ambient_ecs::{components, query, Debuggable, Description, Entity, FnSystem, Name, Resource, SystemGroup, World};
+use ambient_gpu::mesh_buffer::{GpuMesh, MeshBufferKey};
+use ambient_renderer::{
+    color, gpu_primitives, material,
+    materials::flat_material::{get_flat_shader, FlatMaterialKey},
+    primitives, renderer_shader,
+};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    cb,
+    mesh::Mesh,
+    shapes::AABB,
+};
+use glam::{Vec2, Vec3, Vec4};
+
+use crate::extend;
+
+components!("primitives", {
+    @[
+        Debuggable,
+        Name["Procedural mesh"],
+        Description["If attached, this entity is rendered with the procedural mesh with this handle, which was created at runtime on this client."]
+    ]
+    procedural_mesh: u64,
+    @[Resource]
+    procedural_meshes: ProceduralMeshes,
+});
+
+#[derive(Debug, Clone)]
+struct ProceduralMesh {
+    /// Kept to update the bounds on partial updates
+    mesh: Mesh,
+    gpu_mesh: Arc<GpuMesh>,
+}
+
+/// The meshes created at runtime, e.g. by modules, by handle
+#[derive(Debug, Clone, Default)]
+pub struct ProceduralMeshes {
+    meshes: HashMap<u64, ProceduralMesh>,
+    next_handle: u64,
+    /// The handles of the meshes whose entities must be updated
+    changed: HashSet<u64>,
+}
+
+fn meshes_mut(world: &mut World) -> &mut ProceduralMeshes {
+    if world.resource_opt(procedural_meshes()).is_none() {
+        world.add_resource(procedural_meshes(), ProceduralMeshes::default());
+    }
+    world.resource_mut(procedural_meshes())
+}
+
+/// Builds a mesh from its attributes; the normals are computed from the triangles if there are none
+pub fn mesh_from_attributes(positions: Vec<Vec3>, normals: Vec<Vec3>, texcoords: Vec<Vec2>, indices: Vec<u32>) -> anyhow::Result<Mesh> {
+    if let Some(index) = indices.iter().find(|&&index| index as usize >= positions.len()) {
+        anyhow::bail!("Index {index} is out of bounds of the {} positions", positions.len());
+    }
+    if !normals.is_empty() && normals.len() != positions.len() {
+        anyhow::bail!("There are {} normals for {} positions", normals.len(), positions.len());
+    }
+    if !texcoords.is_empty() && texcoords.len() != positions.len() {
+        anyhow::bail!("There are {} texcoords for {} positions", texcoords.len(), positions.len());
+    }
+    let normals = if normals.is_empty() { smooth_normals(&positions, &indices) } else { normals };
+    let mut mesh = Mesh {
+        name: "Procedural mesh".to_string(),
+        positions: Some(positions),
+        normals: Some(normals),
+        texcoords: if texcoords.is_empty() { vec![] } else { vec![texcoords] },
+        indices: Some(indices),
+        ..Default::default()
+    };
+    mesh.try_ensure_tangents();
+    Ok(mesh)
+}
+
+fn smooth_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+        // Weighted by the area of the triangle
+        let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+    normals.into_iter().map(|normal| normal.normalize_or_zero()).collect()
+}
+
+/// Uploads `mesh`, and returns the handle entities can render it with through `procedural_mesh`
+pub fn create_procedural_mesh(world: &mut World, mesh: Mesh) -> u64 {
+    let gpu_mesh = GpuMesh::from_mesh(world.resource(asset_cache()).clone(), &mesh);
+    let meshes = meshes_mut(world);
+    let handle = meshes.next_handle;
+    meshes.next_handle += 1;
+    meshes.meshes.insert(handle, ProceduralMesh { mesh, gpu_mesh });
+    handle
+}
+
+/// Replaces the mesh with `handle`, which can change its number of vertices and indices
+pub fn update_procedural_mesh(world: &mut World, handle: u64, mesh: Mesh) -> anyhow::Result<()> {
+    let gpu_mesh = GpuMesh::from_mesh(world.resource(asset_cache()).clone(), &mesh);
+    let meshes = meshes_mut(world);
+    let Some(procedural) = meshes.meshes.get_mut(&handle) else { anyhow::bail!("There's no procedural mesh with handle {handle}") };
+    *procedural = ProceduralMesh { mesh, gpu_mesh };
+    meshes.changed.insert(handle);
+    Ok(())
+}
+
+/// Overwrites vertices of the mesh with `handle` from `offset`, without reallocating it; empty attributes are left as they are
+pub fn update_procedural_mesh_vertices(
+    world: &mut World,
+    handle: u64,
+    offset: u32,
+    positions: &[Vec3],
+    normals: &[Vec3],
+    texcoords: &[Vec2],
+) -> anyhow::Result<()> {
+    let assets = world.resource(asset_cache()).clone();
+    let meshes = meshes_mut(world);
+    let Some(procedural) = meshes.meshes.get_mut(&handle) else { anyhow::bail!("There's no procedural mesh with handle {handle}") };
+    MeshBufferKey.get(&assets).lock().write_vertices(&procedural.gpu_mesh, offset as u64, positions, normals, texcoords)?;
+
+    let offset = offset as usize;
+    let mesh = &mut procedural.mesh;
+    if let Some(old) = mesh.positions.as_mut() {
+        old[offset..offset + positions.len()].copy_from_slice(positions);
+    }
+    if let Some(old) = mesh.normals.as_mut() {
+        old[offset..offset + normals.len()].copy_from_slice(normals);
+    }
+    if let Some(old) = mesh.texcoords.get_mut(0) {
+        old[offset..offset + texcoords.len()].copy_from_slice(texcoords);
+    }
+    if !positions.is_empty() {
+        meshes.changed.insert(handle);
+    }
+    Ok(())
+}
+
+/// Forgets the mesh with `handle`; it's freed once no entity renders it anymore
+pub fn destroy_procedural_mesh(world: &mut World, handle: u64) {
+    meshes_mut(world).meshes.remove(&handle);
+}
+
+fn procedural_mesh_data(assets: &AssetCache, procedural: &ProceduralMesh) -> Entity {
+    let aabb = procedural.mesh.aabb().unwrap_or(AABB { min: Vec3::ZERO, max: Vec3::ZERO });
+    Entity::new()
+        .with(mesh(), procedural.gpu_mesh.clone())
+        .with_default(local_to_world())
+        .with_default(mesh_to_world())
+        .with_default(translation())
+        .with(renderer_shader(), cb(get_flat_shader))
+        .with(material(), FlatMaterialKey::white().get(assets))
+        .with(primitives(), vec![])
+        .with_default(gpu_primitives())
+        .with(color(), Vec4::ONE)
+        .with(main_scene(), ())
+        .with(local_bounding_aabb(), aabb)
+        .with(world_bounding_sphere(), aabb.to_sphere())
+        .with(world_bounding_aabb(), aabb)
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "procedural_meshes",
+        vec![
+            query(procedural_mesh().changed()).to_system(|q, world, qs, _| {
+                for (id, handle) in q.collect_cloned(world, qs) {
+                    let Some(procedural) = world.resource_opt(procedural_meshes()).and_then(|meshes| meshes.meshes.get(&handle)) else {
+                        continue;
+                    };
+                    let mut data = procedural_mesh_data(world.resource(asset_cache()), procedural);
+                    // UI elements, e.g. plots, are only rendered in the UI
+                    if world.has_component(id, ui_scene()) {
+                        data.remove_self(main_scene());
+                    }
+                    // The entity may already render another mesh
+                    world.set(id, mesh(), data.get_cloned(mesh()).unwrap()).ok();
+                    world.set(id, local_bounding_aabb(), data.get(local_bounding_aabb()).unwrap()).ok();
+                    extend(world, id, data);
+                }
+            }),
+            // Applies the updated meshes to the entities which render them
+            Box::new(FnSystem::new(|world, _| {
+                let Some(meshes) = world.resource_mut_opt(procedural_meshes()) else { return };
+                if meshes.changed.is_empty() {
+                    return;
+                }
+                let changed = std::mem::take(&mut meshes.changed);
+                let updated = changed
+                    .into_iter()
+                    .filter_map(|handle| {
+                        meshes.meshes.get(&handle).map(|procedural| (handle, (procedural.gpu_mesh.clone(), procedural.mesh.aabb())))
+                    })
+                    .collect::<HashMap<_, _>>();
+                for (id, handle) in query(procedural_mesh()).iter(world, None).map(|(id, &handle)| (id, handle)).collect::<Vec<_>>() {
+                    let Some((gpu_mesh, aabb)) = updated.get(&handle) else { continue };
+                    if let Some(aabb) = aabb {
+                        world.set(id, local_bounding_aabb(), *aabb).ok();
+                    }
+                    if !world.get_ref(id, mesh()).map_or(false, |mesh| Arc::ptr_eq(mesh, gpu_mesh)) {
+                        world.set(id, mesh(), gpu_mesh.clone()).ok();
+                    }
+                }
+            })),
+        ],
+    )
+}
@@ -0,0 +1,292 @@
+//! Splines are smooth curves through control points, e.g. for roads and rivers. An entity with [spline_points] is
+//! rendered with a strip along its spline, and the terrain can be flattened to match it.
+use ambient_core::transform::translation;
+use ambient_ecs::{
+    components, query, Component, ComponentValue, Debuggable, DefaultValue, Description, DynSystem, EntityId, Name, Networked, Store,
+    SystemGroup, World,
+};
+use ambient_renderer::color;
+use ambient_std::mesh::Mesh;
+use glam::{vec2, vec4, Vec2, Vec3, Vec3Swizzles, Vec4};
+
+use crate::procedural::{create_procedural_mesh, destroy_procedural_mesh, mesh_from_attributes, procedural_mesh, update_procedural_mesh};
+
+components!("primitives", {
+    @[
+        Networked, Store, Debuggable,
+        Name["Spline points"],
+        Description["The control points of a spline, in world space. The spline passes through all of them.\nIf attached, the entity is rendered with a road or a river along the spline; see the other `spline_*` components."]
+    ]
+    spline_points: Vec<Vec3>,
+    @[
+        Networked, Store, Debuggable,
+        Name["Spline closed"],
+        Description["If true, the spline loops from its last control point back to its first."]
+    ]
+    spline_closed: bool,
+    @[
+        Networked, Store, DefaultValue<_>[4.], Debuggable,
+        Name["Spline width"],
+        Description["The width of the road or river along a spline, in metres."]
+    ]
+    spline_width: f32,
+    @[
+        Networked, Store, Debuggable,
+        Name["Spline kind"],
+        Description["What runs along a spline: `Road` (the default), which lies on the spline, or `River`, whose surface is the spline and whose bed is carved below it."]
+    ]
+    spline_kind: String,
+    @[
+        Networked, Store, Debuggable,
+        Name["Spline deform terrain"],
+        Description["If attached to a spline, the terrain is flattened to the road, or carved out for the river, whenever the spline's points change."]
+    ]
+    spline_deform_terrain: (),
+});
+
+/// The distance between the rows of vertices of the spline meshes
+const MESH_SPACING: f32 = 1.;
+/// How far above the spline a road is rendered, to keep it from flickering with the terrain
+const ROAD_OFFSET: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplineKind {
+    #[default]
+    Road,
+    River,
+}
+impl SplineKind {
+    pub fn parse(kind: &str) -> Self {
+        match kind {
+            "River" => Self::River,
+            _ => Self::Road,
+        }
+    }
+
+    /// How deep the terrain is carved below the spline
+    pub fn depth(&self, width: f32) -> f32 {
+        match self {
+            Self::Road => 0.,
+            Self::River => (width * 0.2).clamp(0.5, 3.),
+        }
+    }
+
+    fn color(&self) -> Vec4 {
+        match self {
+            Self::Road => vec4(0.35, 0.33, 0.3, 1.),
+            Self::River => vec4(0.15, 0.35, 0.5, 1.),
+        }
+    }
+}
+
+/// A point along a spline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplineSample {
+    pub position: Vec3,
+    /// The normalized direction of the spline
+    pub tangent: Vec3,
+    /// The distance along the spline from its start
+    pub distance: f32,
+}
+
+/// A Catmull-Rom spline, which goes through all its points. It's parametrized by `t`, which goes from 0 at the first point to
+/// [Spline::segments] at the last one; guests evaluate splines the same way
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Spline {
+    pub points: Vec<Vec3>,
+    pub closed: bool,
+}
+impl Spline {
+    pub fn new(points: Vec<Vec3>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    pub fn from_world(world: &World, id: EntityId) -> Option<Self> {
+        let points = world.get_cloned(id, spline_points()).ok()?;
+        Some(Self::new(points, world.get(id, spline_closed()).unwrap_or(false)))
+    }
+
+    /// The number of curves between the points
+    pub fn segments(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n - 1,
+        }
+    }
+
+    fn point(&self, index: isize) -> Vec3 {
+        let n = self.points.len() as isize;
+        if self.closed {
+            self.points[index.rem_euclid(n) as usize]
+        } else {
+            self.points[index.clamp(0, n - 1) as usize]
+        }
+    }
+
+    /// The segment `t` is on, and how far along it
+    fn segment(&self, t: f32) -> (isize, f32) {
+        let segments = self.segments();
+        let t = if self.closed { t.rem_euclid(segments as f32) } else { t.clamp(0., segments as f32) };
+        let index = (t.floor() as usize).min(segments - 1);
+        (index as isize, t - index as f32)
+    }
+
+    fn coefficients(&self, index: isize) -> [Vec3; 4] {
+        let [p0, p1, p2, p3] = [index - 1, index, index + 1, index + 2].map(|i| self.point(i));
+        [2. * p1, p2 - p0, 2. * p0 - 5. * p1 + 4. * p2 - p3, -p0 + 3. * p1 - 3. * p2 + p3]
+    }
+
+    pub fn evaluate(&self, t: f32) -> Vec3 {
+        match self.points.len() {
+            0 => Vec3::ZERO,
+            1 => self.points[0],
+            _ => {
+                let (index, u) = self.segment(t);
+                let [a, b, c, d] = self.coefficients(index);
+                0.5 * (a + b * u + c * u * u + d * u * u * u)
+            }
+        }
+    }
+
+    /// The normalized direction of the spline at `t`
+    pub fn tangent(&self, t: f32) -> Vec3 {
+        if self.points.len() < 2 {
+            return Vec3::X;
+        }
+        let (index, u) = self.segment(t);
+        let [_, b, c, d] = self.coefficients(index);
+        let tangent = 0.5 * (b + 2. * c * u + 3. * d * u * u);
+        tangent.try_normalize().unwrap_or_else(|| (self.point(index + 1) - self.point(index)).normalize_or_zero())
+    }
+
+    /// Samples the spline about every `spacing` metres, from its start to its end
+    pub fn sample(&self, spacing: f32) -> Vec<SplineSample> {
+        let mut samples: Vec<SplineSample> = Vec::new();
+        let mut distance = 0.;
+        for index in 0..self.segments() {
+            let chord = self.point(index as isize + 1).distance(self.point(index as isize));
+            let steps = ((chord / spacing.max(0.01)).ceil() as usize).max(1);
+            let first = if index == 0 { 0 } else { 1 };
+            for step in first..=steps {
+                let t = index as f32 + step as f32 / steps as f32;
+                let position = self.evaluate(t);
+                if let Some(last) = samples.last() {
+                    distance += position.distance(last.position);
+                }
+                samples.push(SplineSample { position, tangent: self.tangent(t), distance });
+            }
+        }
+        samples
+    }
+
+    pub fn length(&self) -> f32 {
+        self.sample(MESH_SPACING).last().map_or(0., |sample| sample.distance)
+    }
+
+    /// The parameter `t` of the point of the spline closest to `point`, and that point
+    pub fn closest(&self, point: Vec3) -> (f32, Vec3) {
+        if self.points.len() < 2 {
+            return (0., self.evaluate(0.));
+        }
+        const STEPS: usize = 16;
+        let distance = |t: f32| self.evaluate(t).distance_squared(point);
+        let coarse = (0..=self.segments() * STEPS)
+            .map(|i| i as f32 / STEPS as f32)
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+            .unwrap_or_default();
+        // Refines it with a ternary search around the closest sample
+        let (mut min, mut max) = (coarse - 1. / STEPS as f32, coarse + 1. / STEPS as f32);
+        if !self.closed {
+            (min, max) = (min.max(0.), max.min(self.segments() as f32));
+        }
+        for _ in 0..20 {
+            let (a, b) = (min + (max - min) / 3., max - (max - min) / 3.);
+            if distance(a) < distance(b) {
+                max = b;
+            } else {
+                min = a;
+            }
+        }
+        let t = (min + max) / 2.;
+        let t = if self.closed { t.rem_euclid(self.segments() as f32) } else { t };
+        (t, self.evaluate(t))
+    }
+}
+/// A flat strip of `width` along the spline, relative to `origin`
+pub fn spline_mesh(spline: &Spline, width: f32, kind: SplineKind, origin: Vec3) -> anyhow::Result<Mesh> {
+    let samples = spline.sample(MESH_SPACING);
+    let offset = match kind {
+        SplineKind::Road => ROAD_OFFSET,
+        SplineKind::River => 0.,
+    };
+    let mut positions = Vec::with_capacity(samples.len() * 2);
+    let mut texcoords = Vec::with_capacity(samples.len() * 2);
+    for sample in &samples {
+        let side = sample.tangent.xy().perp().try_normalize().unwrap_or(Vec2::Y).extend(0.) * width / 2.;
+        let center = sample.position - origin + Vec3::Z * offset;
+        positions.extend([center + side, center - side]);
+        let v = sample.distance / width.max(0.01);
+        texcoords.extend([vec2(0., v), vec2(1., v)]);
+    }
+    let indices = (0..samples.len().saturating_sub(1) as u32)
+        .flat_map(|i| {
+            let [left, right, next_left, next_right] = [i * 2, i * 2 + 1, i * 2 + 2, i * 2 + 3];
+            [left, right, next_left, right, next_right, next_left]
+        })
+        .collect();
+    let normals = vec![Vec3::Z; positions.len()];
+    mesh_from_attributes(positions, normals, texcoords, indices)
+}
+
+fn update_spline_mesh(world: &mut World, id: EntityId) {
+    let Some(spline) = Spline::from_world(world, id) else { return };
+    let width = world.get(id, spline_width()).unwrap_or(4.);
+    let kind = world.get_ref(id, spline_kind()).map(|kind| SplineKind::parse(kind)).unwrap_or_default();
+    let origin = world.get(id, translation()).unwrap_or_default();
+    let mesh = match spline_mesh(&spline, width, kind, origin) {
+        Ok(mesh) => mesh,
+        Err(err) => {
+            log::warn!("Failed to build the mesh of spline {id}: {err:?}");
+            return;
+        }
+    };
+    if !world.has_component(id, color()) {
+        world.add_component(id, color(), kind.color()).ok();
+    }
+    match world.get(id, procedural_mesh()) {
+        Ok(handle) => {
+            update_procedural_mesh(world, handle, mesh).ok();
+        }
+        Err(_) => {
+            let handle = create_procedural_mesh(world, mesh);
+            world.add_component(id, procedural_mesh(), handle).ok();
+        }
+    }
+}
+
+/// Rebuilds the mesh of the splines whose `component` changed
+fn on_changed<T: ComponentValue>(component: Component<T>) -> DynSystem {
+    query(component.changed()).incl(spline_points()).to_system(|q, world, qs, _| {
+        for (id, _) in q.collect_cloned(world, qs) {
+            update_spline_mesh(world, id);
+        }
+    })
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "primitives/splines",
+        vec![
+            on_changed(spline_points()),
+            on_changed(spline_closed()),
+            on_changed(spline_width()),
+            on_changed(spline_kind()),
+            query(procedural_mesh()).incl(spline_points()).despawned().to_system(|q, world, qs, _| {
+                for (_, handle) in q.collect_cloned(world, qs) {
+                    destroy_procedural_mesh(world, handle);
+                }
+            }),
+        ],
+    )
+}
@@ -1,12 +1,21 @@
-use std::{collections::HashMap, fmt::Display, num::NonZeroUsize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    path::PathBuf,
+};
 
 use ambient_ecs::{
-    components, Debuggable, ExternalComponentAttributes, ExternalComponentDesc, ExternalComponentFlagAttributes, Networked,
-    PrimitiveComponentType, Store,
+    components, ConceptRequirements, Debuggable, ExternalComponentAttributes, ExternalComponentDesc, ExternalComponentFlagAttributes,
+    Networked, PrimitiveComponentType, Store, ValueRange,
 };
 use serde::{de::Visitor, Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod schema;
+pub mod typescript;
+
 #[cfg(test)]
 mod tests;
 
@@ -24,6 +33,19 @@ pub struct Manifest {
     pub components: HashMap<IdentifierPathBuf, NamespaceOrComponent>,
     #[serde(default)]
     pub concepts: HashMap<IdentifierPathBuf, NamespaceOrConcept>,
+    #[serde(default)]
+    pub settings: HashMap<Identifier, Setting>,
+    #[serde(default)]
+    pub storage: Storage,
+    #[serde(default)]
+    pub server: Server,
+    #[serde(default)]
+    pub fonts: HashMap<Identifier, Font>,
+    /// The struct types that components can be of, by their PascalCase name
+    #[serde(default)]
+    pub types: HashMap<String, StructType>,
+    #[serde(default)]
+    pub lua: Lua,
 }
 impl Manifest {
     pub fn parse(manifest: &str) -> Result<Self, toml::de::Error> {
@@ -43,6 +65,7 @@ impl Manifest {
                 NamespaceOrComponent::Other(c) => Some((id, c)),
                 NamespaceOrComponent::Namespace(_) => None,
             })
+            .flat_map(|(id, component)| self.expand_struct_component(id, component))
             .map(|(id, component)| {
                 let full_path = IdentifierPathBuf(project_path.iter().chain(id.0.iter()).cloned().collect());
                 Ok(ExternalComponentDesc {
@@ -51,12 +74,72 @@ impl Manifest {
                     attributes: ExternalComponentAttributes {
                         name: Some(component.name.clone()),
                         description: Some(component.description.clone()),
+                        unit: component.unit.clone(),
+                        range: (component.min.is_some() || component.max.is_some()).then(|| ValueRange::new(component.min, component.max)),
                         flags: ExternalComponentFlagAttributes::from_iter(component.attributes.iter().map(|s| s.as_str())),
                     },
                 })
             })
             .collect::<Result<Vec<_>, _>>()
     }
+
+    /// A component of a struct type is stored as one component per field, under the path of the component; other
+    /// components are left as they are
+    fn expand_struct_component(&self, id: &IdentifierPathBuf, component: &Component) -> Vec<(IdentifierPathBuf, Component)> {
+        let struct_type = match &component.type_ {
+            ComponentType::String(ty) => self.types.get(ty),
+            ComponentType::ContainerType { .. } => None,
+        };
+        let Some(struct_type) = struct_type else {
+            return vec![(id.clone(), component.clone())];
+        };
+        struct_type
+            .fields
+            .iter()
+            .map(|(field, ty)| {
+                let path = IdentifierPathBuf(id.0.iter().chain(std::iter::once(field)).cloned().collect());
+                let field_component = Component {
+                    name: format!("{}: {field}", component.name),
+                    description: format!("The `{field}` of the *{}*.", struct_type.name),
+                    type_: ty.clone(),
+                    attributes: component.attributes.clone(),
+                    unit: None,
+                    min: None,
+                    max: None,
+                };
+                (path, field_component)
+            })
+            .collect()
+    }
+
+    /// The components that the entities made from the concepts of this manifest must have. The concepts keep their ids
+    /// from the manifest, and the components defined in the manifest are given their full path
+    pub fn all_defined_concepts(&self, global_namespace: bool) -> Vec<ConceptRequirements> {
+        let project_path: Vec<_> = if global_namespace {
+            vec![]
+        } else {
+            self.project.organization.iter().chain(std::iter::once(&self.project.id)).cloned().collect()
+        };
+        let full_component_path = |path: &IdentifierPathBuf| match self.components.get(path) {
+            Some(NamespaceOrComponent::Other(_)) => {
+                IdentifierPathBuf(project_path.iter().chain(path.0.iter()).cloned().collect()).to_string()
+            }
+            _ => path.to_string(),
+        };
+
+        self.concepts
+            .iter()
+            .filter_map(|(id, concept)| match concept {
+                NamespaceOrConcept::Other(c) => Some((id, c)),
+                NamespaceOrConcept::Namespace(_) => None,
+            })
+            .map(|(id, concept)| ConceptRequirements {
+                id: id.to_string(),
+                extends: concept.extends.iter().map(|path| path.to_string()).collect(),
+                components: concept.components.keys().map(full_component_path).collect(),
+            })
+            .collect()
+    }
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq)]
@@ -74,6 +157,9 @@ pub struct Project {
 pub struct Build {
     #[serde(default)]
     pub rust: BuildRust,
+    /// Other base urls the built project is served from (e.g. a CDN), which clients fall back to when downloading assets
+    #[serde(default)]
+    pub mirrors: Vec<String>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq)]
@@ -87,6 +173,87 @@ impl Default for BuildRust {
     }
 }
 
+/// How the server persists its world between runs. When enabled, the entities and resources with `Store` components are
+/// restored when the server starts, and saved periodically and when it shuts down.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Storage {
+    pub enabled: bool,
+    /// Where the world is saved, relative to the project
+    pub path: PathBuf,
+    /// Seconds between saves
+    pub autosave_interval: f32,
+    /// The number of previous saves to keep
+    pub backups: usize,
+}
+impl Default for Storage {
+    fn default() -> Self {
+        Self { enabled: false, path: PathBuf::from("data/world.bin"), autosave_interval: 60., backups: 3 }
+    }
+}
+
+/// How often the server simulates and replicates its world, and what it spawns when it starts. The rates can be overridden
+/// from the command line.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Server {
+    /// Simulation frames per second
+    pub tick_rate: f32,
+    /// World diffs sent to the clients per second; defaults to the tick rate, and can't be higher than it
+    pub replication_rate: Option<f32>,
+    /// The `.scene.toml` or `.scene.json` files the server spawns when it starts, relative to the project
+    pub scenes: Vec<PathBuf>,
+}
+impl Default for Server {
+    fn default() -> Self {
+        Self { tick_rate: 60., replication_rate: None, scenes: Vec::new() }
+    }
+}
+
+/// Lua scripts that the server runs alongside the project's WASM modules. They're not built; the server loads them from the
+/// project and reloads them when they change, which makes them quicker to iterate on.
+#[derive(Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Lua {
+    /// The scripts, relative to the project
+    #[serde(default)]
+    pub server: Vec<PathBuf>,
+}
+
+/// A font that the project uses. It's copied to the build, keeping only the glyphs in `ranges` if there are any, and
+/// clients load the fonts to `preload` when they join, so that the first text using them doesn't have to wait for them.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Font {
+    /// The font file, relative to the project's `assets` directory
+    pub path: PathBuf,
+    #[serde(default)]
+    pub ranges: Vec<UnicodeRange>,
+    #[serde(default = "default_preload")]
+    pub preload: bool,
+}
+fn default_preload() -> bool {
+    true
+}
+
+/// A range of unicode code points, written like `U+0041` or `U+0020-007E`
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct UnicodeRange(pub RangeInclusive<u32>);
+impl TryFrom<String> for UnicodeRange {
+    type Error = &'static str;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let range = value.strip_prefix("U+").ok_or("unicode range must start with `U+`")?;
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let parse = |code_point: &str| u32::from_str_radix(code_point, 16).map_err(|_| "invalid code point in unicode range");
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err("unicode range must not end before it starts");
+        }
+        Ok(Self(start..=end))
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct Namespace {
     pub name: String,
@@ -126,6 +293,15 @@ pub struct Component {
     pub type_: ComponentType,
     #[serde(default)]
     pub attributes: Vec<String>,
+    /// The unit of the value, shown next to it in the inspector
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// The lowest value editors allow
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// The highest value editors allow
+    #[serde(default)]
+    pub max: Option<f64>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq)]
@@ -160,6 +336,15 @@ impl TryFrom<&ComponentType> for PrimitiveComponentType {
     }
 }
 
+/// A struct type that components can be of. As the runtime only stores primitive values, a component of a struct type is
+/// stored as one component per field, e.g. `stats::health` and `stats::name` for a component `stats`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct StructType {
+    pub name: String,
+    pub description: String,
+    pub fields: BTreeMap<Identifier, ComponentType>,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct Concept {
     pub name: String,
@@ -169,6 +354,29 @@ pub struct Concept {
     pub components: HashMap<IdentifierPathBuf, toml::Value>,
 }
 
+/// A setting that the project exposes to its players. These are shown in the client's settings UI, and stored in their settings file
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Setting {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type")]
+    pub type_: SettingType,
+    pub default: toml::Value,
+    /// The lower bound of numeric settings
+    pub min: Option<f64>,
+    /// The upper bound of numeric settings
+    pub max: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingType {
+    Bool,
+    I32,
+    F32,
+    String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct IdentifierPathBuf(Vec<Identifier>);
 impl IdentifierPathBuf {
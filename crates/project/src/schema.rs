@@ -0,0 +1,213 @@
+//! A description of everything a project defines, for tools that don't parse manifests themselves, like editors, clients
+//! in other languages and docs generators. It serializes to JSON with its items sorted by path, so that exports of the same
+//! manifest are identical.
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{ComponentType, Identifier, IdentifierPathBuf, Manifest, NamespaceOr, SettingType, Version};
+
+/// Bumped whenever a field is removed or changes meaning; tools should check it before reading the rest
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Schema {
+    pub schema_version: u32,
+    pub project: ProjectSchema,
+    pub namespaces: Vec<NamespaceSchema>,
+    pub types: Vec<StructTypeSchema>,
+    pub components: Vec<ComponentSchema>,
+    pub concepts: Vec<ConceptSchema>,
+    pub settings: Vec<SettingSchema>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ProjectSchema {
+    pub id: Identifier,
+    pub name: Option<String>,
+    pub version: Version,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub organization: Option<Identifier>,
+}
+
+/// A documented namespace of components or concepts
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct NamespaceSchema {
+    /// The full path of the namespace
+    pub path: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct StructTypeSchema {
+    /// The PascalCase name components refer to the type by
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// The type of each field, by its name
+    pub fields: BTreeMap<Identifier, String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ComponentSchema {
+    /// The full path of the component, including the organization and id of the project
+    pub path: String,
+    pub name: String,
+    pub description: String,
+    /// A primitive type like `F32` or `Vec<String>`, or the id of one of the struct types. A component of a struct type is
+    /// stored as one component per field, at `<path>::<field>`
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub attributes: Vec<String>,
+    pub unit: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ConceptSchema {
+    /// The path of the concept in the manifest
+    pub path: String,
+    pub name: String,
+    pub description: String,
+    pub extends: Vec<String>,
+    /// The default value of each component of the concept, by the full path of the component
+    pub components: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SettingSchema {
+    pub id: Identifier,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "type")]
+    pub type_: SettingType,
+    pub default: toml::Value,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Manifest {
+    /// Resolves the manifest into a [Schema]. The components defined in the manifest are given their full path, as in
+    /// [Manifest::all_defined_components]
+    pub fn schema(&self, global_namespace: bool) -> Schema {
+        let project_path: Vec<_> = if global_namespace {
+            vec![]
+        } else {
+            self.project.organization.iter().chain(std::iter::once(&self.project.id)).cloned().collect()
+        };
+        let full_path = |path: &IdentifierPathBuf| IdentifierPathBuf(project_path.iter().chain(path.0.iter()).cloned().collect());
+        let full_component_path = |path: &IdentifierPathBuf| match self.components.get(path) {
+            Some(NamespaceOr::Other(_)) => full_path(path).to_string(),
+            _ => path.to_string(),
+        };
+
+        let mut namespaces = self
+            .components
+            .iter()
+            .filter_map(|(path, namespace)| match namespace {
+                NamespaceOr::Namespace(namespace) => Some((full_path(path).to_string(), namespace)),
+                NamespaceOr::Other(_) => None,
+            })
+            .chain(self.concepts.iter().filter_map(|(path, namespace)| match namespace {
+                NamespaceOr::Namespace(namespace) => Some((path.to_string(), namespace)),
+                NamespaceOr::Other(_) => None,
+            }))
+            .map(|(path, namespace)| NamespaceSchema { path, name: namespace.name.clone(), description: namespace.description.clone() })
+            .collect::<Vec<_>>();
+        namespaces.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut types = self
+            .types
+            .iter()
+            .map(|(id, ty)| StructTypeSchema {
+                id: id.clone(),
+                name: ty.name.clone(),
+                description: ty.description.clone(),
+                fields: ty.fields.iter().map(|(field, ty)| (field.clone(), ty.to_string())).collect(),
+            })
+            .collect::<Vec<_>>();
+        types.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut components = self
+            .components
+            .iter()
+            .filter_map(|(path, component)| match component {
+                NamespaceOr::Other(component) => Some((path, component)),
+                NamespaceOr::Namespace(_) => None,
+            })
+            .map(|(path, component)| ComponentSchema {
+                path: full_path(path).to_string(),
+                name: component.name.clone(),
+                description: component.description.clone(),
+                type_: component.type_.to_string(),
+                attributes: component.attributes.clone(),
+                unit: component.unit.clone(),
+                min: component.min,
+                max: component.max,
+            })
+            .collect::<Vec<_>>();
+        components.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut concepts = self
+            .concepts
+            .iter()
+            .filter_map(|(path, concept)| match concept {
+                NamespaceOr::Other(concept) => Some((path, concept)),
+                NamespaceOr::Namespace(_) => None,
+            })
+            .map(|(path, concept)| ConceptSchema {
+                path: path.to_string(),
+                name: concept.name.clone(),
+                description: concept.description.clone(),
+                extends: concept.extends.iter().map(|path| path.to_string()).collect(),
+                components: concept.components.iter().map(|(path, value)| (full_component_path(path), value.clone())).collect(),
+            })
+            .collect::<Vec<_>>();
+        concepts.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut settings = self
+            .settings
+            .iter()
+            .map(|(id, setting)| SettingSchema {
+                id: id.clone(),
+                name: setting.name.clone(),
+                description: setting.description.clone(),
+                type_: setting.type_,
+                default: setting.default.clone(),
+                min: setting.min,
+                max: setting.max,
+            })
+            .collect::<Vec<_>>();
+        settings.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Schema {
+            schema_version: SCHEMA_VERSION,
+            project: ProjectSchema {
+                id: self.project.id.clone(),
+                name: self.project.name.clone(),
+                version: self.project.version.clone(),
+                description: self.project.description.clone(),
+                authors: self.project.authors.clone(),
+                organization: self.project.organization.clone(),
+            },
+            namespaces,
+            types,
+            components,
+            concepts,
+            settings,
+        }
+    }
+}
+
+impl std::fmt::Display for ComponentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComponentType::String(ty) => f.write_str(ty),
+            ComponentType::ContainerType { type_, element_type: Some(element_type) } => write!(f, "{type_}<{element_type}>"),
+            ComponentType::ContainerType { type_, element_type: None } => f.write_str(type_),
+        }
+    }
+}
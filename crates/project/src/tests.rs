@@ -1,10 +1,10 @@
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{collections::HashMap, num::NonZeroUsize, path::PathBuf};
 
-use ambient_ecs::primitive_component_definitions;
+use ambient_ecs::{primitive_component_definitions, ConceptRequirements, PrimitiveComponentType, ValueRange};
 
 use crate::{
-    Build, BuildRust, Component, ComponentType, Concept, Identifier, IdentifierPathBuf, Manifest, Namespace, Project, Version,
-    VersionError, VersionSuffix,
+    schema::SCHEMA_VERSION, Build, BuildRust, Component, ComponentType, Concept, Font, Identifier, IdentifierPathBuf, Lua, Manifest,
+    Namespace, Project, Server, Setting, SettingType, Storage, UnicodeRange, Version, VersionError, VersionSuffix,
 };
 
 #[test]
@@ -36,14 +36,17 @@ fn can_parse_tictactoe_toml() {
                 authors: vec![],
                 organization: None
             },
-            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string(), "server".to_string()] } },
+            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string(), "server".to_string()] }, mirrors: vec![] },
             components: HashMap::from_iter([(
                 IdentifierPathBuf::new("cell").unwrap(),
                 Component {
                     name: "Cell".to_string(),
                     description: "The ID of the cell this player is in".to_string(),
                     type_: ComponentType::String("I32".to_string()),
-                    attributes: vec!["Store".to_string()]
+                    attributes: vec!["Store".to_string()],
+                    unit: None,
+                    min: None,
+                    max: None,
                 }
                 .into()
             )]),
@@ -57,6 +60,12 @@ fn can_parse_tictactoe_toml() {
                 }
                 .into()
             )]),
+            settings: HashMap::new(),
+            storage: Storage::default(),
+            server: Server::default(),
+            fonts: HashMap::new(),
+            types: HashMap::new(),
+            lua: Lua::default(),
         })
     )
 }
@@ -84,9 +93,15 @@ fn can_parse_rust_build_settings() {
                 authors: vec![],
                 organization: None
             },
-            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string()] } },
+            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string()] }, mirrors: vec![] },
             components: HashMap::new(),
             concepts: HashMap::new(),
+            settings: HashMap::new(),
+            storage: Storage::default(),
+            server: Server::default(),
+            fonts: HashMap::new(),
+            types: HashMap::new(),
+            lua: Lua::default(),
         })
     )
 }
@@ -117,7 +132,7 @@ fn can_parse_manifest_with_namespaces() {
                 authors: vec![],
                 organization: None
             },
-            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string(), "server".to_string()] } },
+            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string(), "server".to_string()] }, mirrors: vec![] },
             components: HashMap::from_iter([
                 (IdentifierPathBuf::new("core").unwrap(), Namespace { name: "Core".to_string(), description: String::new() }.into()),
                 (IdentifierPathBuf::new("core::app").unwrap(), Namespace { name: "App".to_string(), description: String::new() }.into()),
@@ -127,12 +142,21 @@ fn can_parse_manifest_with_namespaces() {
                         name: "Main Scene".to_string(),
                         description: "".to_string(),
                         type_: ComponentType::String("Empty".to_string()),
-                        attributes: vec![]
+                        attributes: vec![],
+                        unit: None,
+                        min: None,
+                        max: None,
                     }
                     .into()
                 )
             ]),
             concepts: HashMap::new(),
+            settings: HashMap::new(),
+            storage: Storage::default(),
+            server: Server::default(),
+            fonts: HashMap::new(),
+            types: HashMap::new(),
+            lua: Lua::default(),
         })
     )
 }
@@ -169,7 +193,7 @@ fn can_parse_concepts_with_documented_namespace_from_manifest() {
                 authors: vec![],
                 organization: None
             },
-            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string(), "server".to_string()] } },
+            build: Build { rust: BuildRust { feature_multibuild: vec!["client".to_string(), "server".to_string()] }, mirrors: vec![] },
             components: HashMap::from_iter([
                 (
                     IdentifierPathBuf::new("core::transform::rotation").unwrap(),
@@ -177,7 +201,10 @@ fn can_parse_concepts_with_documented_namespace_from_manifest() {
                         name: "Rotation".to_string(),
                         description: "".to_string(),
                         type_: ComponentType::String("Quat".to_string()),
-                        attributes: vec![]
+                        attributes: vec![],
+                        unit: None,
+                        min: None,
+                        max: None,
                     }
                     .into()
                 ),
@@ -187,7 +214,10 @@ fn can_parse_concepts_with_documented_namespace_from_manifest() {
                         name: "Scale".to_string(),
                         description: "".to_string(),
                         type_: ComponentType::String("Vec3".to_string()),
-                        attributes: vec![]
+                        attributes: vec![],
+                        unit: None,
+                        min: None,
+                        max: None,
                     }
                     .into()
                 ),
@@ -197,7 +227,10 @@ fn can_parse_concepts_with_documented_namespace_from_manifest() {
                         name: "Spherical billboard".to_string(),
                         description: "".to_string(),
                         type_: ComponentType::String("Empty".to_string()),
-                        attributes: vec![]
+                        attributes: vec![],
+                        unit: None,
+                        min: None,
+                        max: None,
                     }
                     .into()
                 ),
@@ -207,7 +240,10 @@ fn can_parse_concepts_with_documented_namespace_from_manifest() {
                         name: "Translation".to_string(),
                         description: "".to_string(),
                         type_: ComponentType::String("Vec3".to_string()),
-                        attributes: vec![]
+                        attributes: vec![],
+                        unit: None,
+                        min: None,
+                        max: None,
                     }
                     .into()
                 ),
@@ -241,10 +277,121 @@ fn can_parse_concepts_with_documented_namespace_from_manifest() {
                     .into()
                 )
             ]),
+            settings: HashMap::new(),
+            storage: Storage::default(),
+            server: Server::default(),
+            fonts: HashMap::new(),
+            types: HashMap::new(),
+            lua: Lua::default(),
         })
     )
 }
 
+#[test]
+fn can_parse_settings() {
+    const TOML: &str = r#"
+    [project]
+    id = "racer"
+    version = "0.0.1"
+
+    [settings]
+    volume = { name = "Volume", type = "F32", default = 0.8, min = 0.0, max = 1.0 }
+    show_minimap = { name = "Show minimap", description = "Shows the minimap in the corner", type = "Bool", default = true }
+    "#;
+
+    let manifest = Manifest::parse(TOML).unwrap();
+    assert_eq!(
+        manifest.settings.get(&Identifier::new("volume").unwrap()),
+        Some(&Setting {
+            name: "Volume".to_string(),
+            description: String::new(),
+            type_: SettingType::F32,
+            default: toml::Value::Float(0.8),
+            min: Some(0.),
+            max: Some(1.),
+        })
+    );
+    assert_eq!(manifest.settings[&Identifier::new("show_minimap").unwrap()].type_, SettingType::Bool);
+}
+
+#[test]
+fn can_parse_storage() {
+    const TOML: &str = r#"
+    [project]
+    id = "racer"
+    version = "0.0.1"
+
+    [storage]
+    enabled = true
+    autosave-interval = 30.0
+    "#;
+
+    assert_eq!(
+        Manifest::parse(TOML).unwrap().storage,
+        Storage { enabled: true, path: PathBuf::from("data/world.bin"), autosave_interval: 30., backups: 3 }
+    );
+}
+
+#[test]
+fn can_parse_server() {
+    const TOML: &str = r#"
+    [project]
+    id = "racer"
+    version = "0.0.1"
+
+    [server]
+    tick-rate = 30.0
+    replication-rate = 15.0
+    scenes = ["scenes/level.scene.toml"]
+    "#;
+
+    assert_eq!(
+        Manifest::parse(TOML).unwrap().server,
+        Server { tick_rate: 30., replication_rate: Some(15.), scenes: vec![PathBuf::from("scenes/level.scene.toml")] }
+    );
+}
+
+#[test]
+fn can_parse_lua_scripts() {
+    const TOML: &str = r#"
+    [project]
+    id = "jam"
+    version = "0.0.1"
+
+    [lua]
+    server = ["scripts/server.lua"]
+    "#;
+
+    assert_eq!(Manifest::parse(TOML).unwrap().lua, Lua { server: vec![PathBuf::from("scripts/server.lua")] });
+}
+
+#[test]
+fn can_parse_fonts() {
+    const TOML: &str = r#"
+    [project]
+    id = "chat"
+    version = "0.0.1"
+
+    [fonts.body]
+    path = "fonts/Inter.ttf"
+    ranges = ["U+0020-007E", "U+00E9"]
+
+    [fonts.emoji]
+    path = "fonts/Emoji.ttf"
+    preload = false
+    "#;
+
+    let fonts = Manifest::parse(TOML).unwrap().fonts;
+    assert_eq!(
+        fonts[&Identifier::new("body").unwrap()],
+        Font { path: PathBuf::from("fonts/Inter.ttf"), ranges: vec![UnicodeRange(0x20..=0x7e), UnicodeRange(0xe9..=0xe9)], preload: true }
+    );
+    assert_eq!(fonts[&Identifier::new("emoji").unwrap()], Font { path: PathBuf::from("fonts/Emoji.ttf"), ranges: vec![], preload: false });
+
+    assert!(UnicodeRange::try_from("0020-007E".to_string()).is_err());
+    assert!(UnicodeRange::try_from("U+007E-0020".to_string()).is_err());
+}
+
 #[test]
 fn can_validate_identifiers() {
     use Identifier as I;
@@ -367,3 +514,148 @@ fn can_sort_versions() {
         }
     }
 }
+
+#[test]
+fn can_list_concept_requirements() {
+    const TOML: &str = r#"
+    [project]
+    id = "tictactoe"
+    organization = "ambient"
+    version = "0.0.1"
+
+    [components]
+    cell = { type = "I32", name = "Cell", description = "The ID of the cell this player is in" }
+
+    [concepts.cell]
+    name = "Cell"
+    description = "A cell object"
+    extends = ["transformable"]
+    [concepts.cell.components]
+    cell = 0
+    "core::app::name" = "Cell"
+    "#;
+
+    let mut concepts = Manifest::parse(TOML).unwrap().all_defined_concepts(false);
+    concepts[0].components.sort();
+    assert_eq!(
+        concepts,
+        vec![ConceptRequirements {
+            id: "cell".to_string(),
+            extends: vec!["transformable".to_string()],
+            components: vec!["ambient::tictactoe::cell".to_string(), "core::app::name".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn can_propagate_component_metadata() {
+    const TOML: &str = r#"
+    [project]
+    id = "racing"
+    version = "0.0.1"
+
+    [components]
+    speed = { type = "F32", name = "Speed", description = "How fast the car goes", unit = "m/s", min = 0, max = 80.5 }
+    "#;
+
+    let components = Manifest::parse(TOML).unwrap().all_defined_components(false).unwrap();
+    assert_eq!(components[0].attributes.description.as_deref(), Some("How fast the car goes"));
+    assert_eq!(components[0].attributes.unit.as_deref(), Some("m/s"));
+    assert_eq!(components[0].attributes.range, Some(ValueRange { min: 0., max: 80.5 }));
+}
+
+#[test]
+fn can_expand_struct_components() {
+    const TOML: &str = r#"
+    [project]
+    id = "rpg"
+    version = "0.0.1"
+
+    [types.Stats]
+    name = "Stats"
+    description = "The stats of a character"
+    fields = { health = "F32", title = "String" }
+
+    [components]
+    stats = { type = "Stats", name = "Stats", description = "", attributes = ["Networked"] }
+    level = { type = "U32", name = "Level", description = "" }
+    "#;
+
+    let mut components = Manifest::parse(TOML).unwrap().all_defined_components(false).unwrap();
+    components.sort_by(|a, b| a.path.cmp(&b.path));
+    let components = components.iter().map(|c| (c.path.as_str(), c.ty, c.attributes.flags.networked)).collect::<Vec<_>>();
+    assert_eq!(
+        components,
+        vec![
+            ("rpg::level", PrimitiveComponentType::U32, false),
+            ("rpg::stats::health", PrimitiveComponentType::F32, true),
+            ("rpg::stats::title", PrimitiveComponentType::String, true),
+        ]
+    );
+}
+
+#[test]
+fn can_export_schema() {
+    const TOML: &str = r#"
+    [project]
+    id = "racing"
+    organization = "ambient"
+    version = "0.0.1"
+
+    [types.Stats]
+    name = "Stats"
+    description = "The stats of a car"
+    fields = { speed = "F32", boosts = { type = "Vec", element_type = "U32" } }
+
+    [components]
+    "car" = { name = "Car", description = "The components of cars" }
+    "car::stats" = { type = "Stats", name = "Stats", description = "", attributes = ["Networked"] }
+    "car::driver" = { type = "String", name = "Driver", description = "Who drives the car" }
+
+    [concepts.car]
+    name = "Car"
+    description = "A car"
+    [concepts.car.components]
+    "car::driver" = ""
+    "core::app::name" = "Car"
+    "#;
+
+    let schema = Manifest::parse(TOML).unwrap().schema(false);
+    assert_eq!(schema.schema_version, SCHEMA_VERSION);
+    assert_eq!(schema.namespaces.iter().map(|n| n.path.as_str()).collect::<Vec<_>>(), vec!["ambient::racing::car"]);
+    assert_eq!(
+        schema.components.iter().map(|c| (c.path.as_str(), c.type_.as_str())).collect::<Vec<_>>(),
+        vec![("ambient::racing::car::driver", "String"), ("ambient::racing::car::stats", "Stats")]
+    );
+    assert_eq!(schema.types[0].fields.values().collect::<Vec<_>>(), vec!["Vec<U32>", "F32"]);
+    assert_eq!(schema.concepts[0].components.keys().collect::<Vec<_>>(), vec!["ambient::racing::car::driver", "core::app::name"]);
+
+    let json = serde_json::to_value(&schema).unwrap();
+    assert_eq!(json["components"][0]["type"], "String");
+    assert_eq!(json["project"]["version"], "0.0.1");
+}
+
+#[test]
+fn can_generate_typescript() {
+    const TOML: &str = r#"
+    [project]
+    id = "racing"
+    version = "0.0.1"
+
+    [types.Stats]
+    name = "Stats"
+    description = "The stats of a car"
+    fields = { speed = "F32" }
+
+    [components]
+    stats = { type = "Stats", name = "Stats", description = "" }
+    driver = { type = "String", name = "Driver", description = "Who drives the car" }
+    checkpoints = { type = { type = "Vec", element_type = "Vec3" }, name = "Checkpoints", description = "" }
+    "#;
+
+    let typescript = Manifest::parse(TOML).unwrap().schema(false).typescript();
+    assert!(typescript.contains(r#""racing::driver": string;"#));
+    assert!(typescript.contains(r#""racing::checkpoints": Vec3[];"#));
+    assert!(typescript.contains(r#""racing::stats::speed": number;"#));
+    assert!(typescript.contains("export class AmbientClient"));
+}
@@ -0,0 +1,111 @@
+//! Generates a TypeScript module from a [Schema], so that pages embedding the web client can read and write the components
+//! of the project with their types checked
+use std::fmt::Write;
+
+use crate::schema::{ComponentSchema, Schema};
+
+/// The primitive types, as their JSON form
+const PRIMITIVE_TYPES: &[(&str, &str)] = &[
+    ("Empty", "null"),
+    ("Bool", "boolean"),
+    ("EntityId", "EntityId"),
+    ("F32", "number"),
+    ("F64", "number"),
+    ("Mat4", "Mat4"),
+    ("I32", "number"),
+    ("Quat", "Quat"),
+    ("String", "string"),
+    ("U32", "number"),
+    ("U64", "number"),
+    ("Vec2", "Vec2"),
+    ("Vec3", "Vec3"),
+    ("Vec4", "Vec4"),
+    ("Uvec2", "Vec2"),
+    ("Uvec3", "Vec3"),
+    ("Uvec4", "Vec4"),
+];
+
+const PRELUDE: &str = r#"/** The base64 id of an entity */
+export type EntityId = string;
+export type Vec2 = [number, number];
+export type Vec3 = [number, number, number];
+export type Vec4 = [number, number, number, number];
+/** `[x, y, z, w]` */
+export type Quat = [number, number, number, number];
+/** Column-major */
+export type Mat4 = [number, number, number, number, number, number, number, number, number, number, number, number, number, number, number, number];
+"#;
+
+impl Schema {
+    /// A TypeScript module with the value type of each component, the defaults of the concepts, and an `AmbientClient`
+    /// class which reads and writes components through the messages of the web client's embedding API
+    pub fn typescript(&self) -> String {
+        let mut out = String::new();
+        let project = &self.project;
+        writeln!(out, "// Generated by `ambient schema --typescript` from {} {}. Don't edit it by hand.", project.id, project.version)
+            .unwrap();
+        writeln!(out).unwrap();
+        out.push_str(PRELUDE);
+
+        writeln!(out).unwrap();
+        writeln!(out, "/** The value of each component, by its path */").unwrap();
+        writeln!(out, "export interface Components {{").unwrap();
+        for component in &self.components {
+            match self.types.iter().find(|ty| ty.id == component.type_) {
+                // Each field is its own component
+                Some(ty) => {
+                    for (field, field_type) in &ty.fields {
+                        let doc = format!("The `{field}` of {}: {}", component.name, ty.description);
+                        write_component(&mut out, &format!("{}::{field}", component.path), &doc, field_type);
+                    }
+                }
+                None => write_component(&mut out, &component.path, &component_doc(component), &component.type_),
+            }
+        }
+        writeln!(out, "}}").unwrap();
+
+        writeln!(out).unwrap();
+        writeln!(out, "/** The default value of the components of each concept, by the path of the concept */").unwrap();
+        writeln!(out, "export const concepts: Record<string, {{ extends: string[]; components: Record<string, unknown> }}> = {{").unwrap();
+        for concept in &self.concepts {
+            writeln!(out, "  /** {} */", doc_comment(&format!("{}: {}", concept.name, concept.description))).unwrap();
+            let extends = serde_json::to_string(&concept.extends).unwrap();
+            let components = serde_json::to_string(&concept.components).unwrap();
+            writeln!(out, "  {:?}: {{ extends: {extends}, components: {components} }},", concept.path).unwrap();
+        }
+        writeln!(out, "}};").unwrap();
+
+        writeln!(out).unwrap();
+        out.push_str(include_str!("typescript_bridge.ts"));
+        out
+    }
+}
+
+fn component_doc(component: &ComponentSchema) -> String {
+    let mut doc = format!("{}: {}", component.name, component.description);
+    if let Some(unit) = &component.unit {
+        write!(doc, " ({unit})").unwrap();
+    }
+    doc
+}
+
+fn write_component(out: &mut String, path: &str, doc: &str, type_: &str) {
+    writeln!(out, "  /** {} */", doc_comment(doc)).unwrap();
+    writeln!(out, "  {path:?}: {};", typescript_type(type_)).unwrap();
+}
+
+/// Converts a component type, as written in the manifest, to the TypeScript type of its JSON form
+fn typescript_type(type_: &str) -> String {
+    if let Some(element) = type_.strip_prefix("Vec<").and_then(|ty| ty.strip_suffix('>')) {
+        format!("{}[]", typescript_type(element))
+    } else if let Some(element) = type_.strip_prefix("Option<").and_then(|ty| ty.strip_suffix('>')) {
+        format!("{} | null", typescript_type(element))
+    } else {
+        PRIMITIVE_TYPES.iter().find(|(name, _)| *name == type_).map_or("unknown", |(_, ty)| ty).to_string()
+    }
+}
+
+/// Keeps `doc` on one line, and from ending the comment early
+fn doc_comment(doc: &str) -> String {
+    doc.replace("*/", "* /").replace('\n', " ")
+}
@@ -0,0 +1,107 @@
+/** Talks to an Ambient web client embedded in an iframe, with the messages it understands */
+export class AmbientClient {
+  private listeners = new Map<string, Set<(data: unknown) => void>>();
+  private errorListeners = new Set<(message: string) => void>();
+  private progressListeners = new Set<(loading: number, loaded: number) => void>();
+  private pendingComponents = new Map<string, ((value: unknown) => void)[]>();
+  private readyListeners: (() => void)[] = [];
+  private isReady = false;
+
+  /** `origin` is the origin of the client's page; messages from other origins are ignored */
+  constructor(private frame: HTMLIFrameElement, private origin: string = "*") {
+    window.addEventListener("message", this.onMessage);
+  }
+
+  /** Stops listening to the client */
+  dispose() {
+    window.removeEventListener("message", this.onMessage);
+  }
+
+  /** Resolves once the client has started */
+  ready(): Promise<void> {
+    return this.isReady ? Promise.resolve() : new Promise((resolve) => this.readyListeners.push(resolve));
+  }
+
+  /** Sends a message to the client, where it's received as an `EMBED_HOST_MESSAGE` event */
+  send(name: string, data: unknown = null) {
+    this.post({ type: "message", name, data });
+  }
+
+  /** Calls `listener` for each message of this name the client sends. Returns a function which stops listening */
+  on(name: string, listener: (data: unknown) => void): () => void {
+    if (!this.listeners.has(name)) {
+      this.listeners.set(name, new Set());
+    }
+    this.listeners.get(name)!.add(listener);
+    return () => this.listeners.get(name)?.delete(listener);
+  }
+
+  onError(listener: (message: string) => void): () => void {
+    this.errorListeners.add(listener);
+    return () => this.errorListeners.delete(listener);
+  }
+
+  onProgress(listener: (loading: number, loaded: number) => void): () => void {
+    this.progressListeners.add(listener);
+    return () => this.progressListeners.delete(listener);
+  }
+
+  /** Resolves with the value of the component, or `null` if the entity doesn't have it */
+  getComponent<P extends keyof Components>(entity: EntityId, component: P): Promise<Components[P] | null> {
+    const key = `${entity}/${String(component)}`;
+    return new Promise((resolve) => {
+      const pending = this.pendingComponents.get(key) ?? [];
+      pending.push(resolve as (value: unknown) => void);
+      this.pendingComponents.set(key, pending);
+      this.post({ type: "get_component", entity, component });
+    });
+  }
+
+  /** Adds the component to the entity, or replaces its value */
+  setComponent<P extends keyof Components>(entity: EntityId, component: P, value: Components[P]) {
+    this.post({ type: "set_component", entity, component, value });
+  }
+
+  resize(width: number, height: number) {
+    this.post({ type: "resize", width, height });
+  }
+
+  setVisible(visible: boolean) {
+    this.post({ type: "visibility", visible });
+  }
+
+  queryProgress() {
+    this.post({ type: "query_progress" });
+  }
+
+  private post(message: object) {
+    this.frame.contentWindow?.postMessage(message, this.origin);
+  }
+
+  private onMessage = (event: MessageEvent) => {
+    if (event.source !== this.frame.contentWindow || (this.origin !== "*" && event.origin !== this.origin)) {
+      return;
+    }
+    const message = event.data;
+    switch (message?.type) {
+      case "ready":
+        this.isReady = true;
+        this.readyListeners.splice(0).forEach((resolve) => resolve());
+        break;
+      case "client_message":
+        this.listeners.get(message.name)?.forEach((listener) => listener(message.data));
+        break;
+      case "component": {
+        const key = `${message.entity}/${message.component}`;
+        this.pendingComponents.get(key)?.shift()?.(message.value);
+        break;
+      }
+      case "progress":
+        this.progressListeners.forEach((listener) => listener(message.loading, message.loaded));
+        break;
+      case "error":
+        this.errorListeners.forEach((listener) => listener(message.message));
+        break;
+    }
+  };
+}
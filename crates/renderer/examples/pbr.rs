@@ -30,6 +30,7 @@ async fn init(app: &mut App) {
                         color: uvec4((255. * x as f32 / size as f32) as u32, (255. * y as f32 / size as f32) as u32, 0, 0),
                     }
                     .get(&assets),
+                    emissive: PixelTextureViewKey::white().get(&assets),
                     transparent: None,
                     double_sided: None,
                     depth_write_enabled: None,
@@ -34,6 +34,7 @@ async fn init(app: &mut App) {
             base_color: texture,
             normalmap: DefaultNormalMapViewKey.get(&assets),
             metallic_roughness: PixelTextureViewKey::white().get(&assets),
+            emissive: PixelTextureViewKey::white().get(&assets),
             transparent: None,
             double_sided: None,
             depth_write_enabled: None,
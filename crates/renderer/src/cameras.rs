@@ -0,0 +1,224 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ambient_core::{
+    asset_cache,
+    camera::{camera_texture_size, camera_viewport, is_camera_of_user},
+    gpu, gpu_components,
+    gpu_ecs::{GpuComponentFormat, GpuWorldSyncEvent, MappedComponentToGpuSystem},
+    player::{local_players, local_user_id},
+};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Store, SystemGroup, World};
+use ambient_gpu::{
+    blit::{Blitter, BlitterKey},
+    std_assets::{DefaultNormalMapViewKey, PixelTextureViewKey},
+};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    cb,
+    color::Color,
+};
+use glam::{uvec2, vec4, UVec2, Vec2, Vec4Swizzles};
+
+use crate::{
+    material,
+    materials::pbr_material::{get_pbr_shader_unlit, PbrMaterial, PbrMaterialConfig, PbrMaterialParams},
+    renderer_shader, RenderTarget, Renderer, RendererConfig, RendererTarget, SharedMaterial,
+};
+
+components!("rendering", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Render target from camera"],
+        Description["Displays the texture rendered by this camera on this entity, e.g. on a UI image or a mesh. The camera needs a `camera_texture_size`.\nUseful for minimaps, mirrors and security monitors."]
+    ]
+    render_target_from_camera: EntityId,
+    @[
+        Debuggable, Networked, Store,
+        Name["Render layers"],
+        Description["A mask of the rendering layers this entity is on; it's only rendered by the cameras whose `camera_layers` intersect it. Defaults to the first layer (1)."]
+    ]
+    render_layers: u32,
+    /// The texture a camera with a `camera_texture_size` renders into
+    camera_render_target: Arc<RenderTarget>,
+    /// The texture currently displayed by an entity with a `render_target_from_camera`
+    displayed_render_target: Arc<RenderTarget>,
+});
+
+gpu_components! {
+    // [layers as bits, 0, 0, 0]
+    render_layers() => render_layers: GpuComponentFormat::Vec4,
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "cameras",
+        vec![query(render_target_from_camera()).to_system(|q, world, qs, _| {
+            for (id, camera) in q.collect_cloned(world, qs) {
+                let Ok(target) = world.get_cloned(camera, camera_render_target()) else { continue };
+                if world.get_ref(id, displayed_render_target()).map_or(false, |displayed| Arc::ptr_eq(displayed, &target)) {
+                    continue;
+                }
+                let assets = world.resource(asset_cache()).clone();
+                let mat = PbrMaterial::new(
+                    assets.clone(),
+                    PbrMaterialConfig {
+                        source: "Camera".to_string(),
+                        name: "Camera".to_string(),
+                        params: PbrMaterialParams::default(),
+                        base_color: Arc::new(target.color_buffer.create_view(&Default::default())),
+                        normalmap: DefaultNormalMapViewKey.get(&assets),
+                        metallic_roughness: PixelTextureViewKey::white().get(&assets),
+                        emissive: PixelTextureViewKey::white().get(&assets),
+                        transparent: None,
+                        double_sided: None,
+                        depth_write_enabled: None,
+                    },
+                );
+                if !world.has_component(id, renderer_shader()) {
+                    world.add_component(id, renderer_shader(), cb(get_pbr_shader_unlit)).unwrap();
+                }
+                world.add_component(id, material(), SharedMaterial::new(mat)).unwrap();
+                world.add_component(id, displayed_render_target(), target).unwrap();
+            }
+        })],
+    )
+}
+
+pub fn gpu_world_systems() -> SystemGroup<GpuWorldSyncEvent> {
+    SystemGroup::new(
+        "cameras/gpu_world",
+        vec![Box::new(MappedComponentToGpuSystem::new(
+            GpuComponentFormat::Vec4,
+            render_layers(),
+            gpu_components::render_layers(),
+            Box::new(|_, _, layers| vec4(f32::from_bits(*layers), 0.0, 0.0, 0.0)),
+        ))],
+    )
+}
+
+/// Renders the cameras of a scene which have a `camera_texture_size` or a `camera_viewport`, each with its own [Renderer].
+/// The active camera of the scene is rendered by the main renderer, as usual.
+pub struct CameraRenderers {
+    assets: AssetCache,
+    config: RendererConfig,
+    renderers: HashMap<EntityId, CameraRenderer>,
+    blit: Arc<Blitter>,
+}
+
+struct CameraRenderer {
+    renderer: Renderer,
+    /// Where the camera is rendered before being drawn into its viewport
+    viewport_target: Option<RenderTarget>,
+}
+
+impl CameraRenderers {
+    pub fn new(assets: &AssetCache, config: RendererConfig) -> Self {
+        let gpu = ambient_gpu::gpu::GpuKey.get(assets);
+        Self {
+            assets: assets.clone(),
+            config,
+            renderers: HashMap::new(),
+            blit: BlitterKey { format: gpu.swapchain_format().into(), linear: false }.get(assets),
+        }
+    }
+
+    /// Renders the cameras with a `camera_texture_size` into their `camera_render_target`
+    pub fn render_textures(
+        &mut self,
+        world: &mut World,
+        encoder: &mut wgpu::CommandEncoder,
+        post_submit: &mut Vec<Box<dyn FnOnce() + Send + Send>>,
+    ) {
+        self.remove_unused(world);
+        let cameras =
+            self.cameras(world, query((self.config.scene, camera_texture_size())).iter(world, None).map(|(id, (_, size))| (id, *size)));
+        for (id, size) in cameras {
+            let size = size.max(UVec2::ONE);
+            let target = match world.get_cloned(id, camera_render_target()) {
+                Ok(target) if target.color_buffer.size.width == size.x && target.color_buffer.size.height == size.y => target,
+                _ => {
+                    let target = Arc::new(RenderTarget::new(world.resource(gpu()).clone(), size, None));
+                    world.add_component(id, camera_render_target(), target.clone()).unwrap();
+                    target
+                }
+            };
+            let renderer = self.get_renderer(world, id);
+            renderer.renderer.render(world, encoder, post_submit, RendererTarget::Target(&target), Some(Color::rgba(0., 0., 0., 1.)));
+        }
+    }
+
+    /// Renders the cameras with a `camera_viewport` on top of `target`
+    pub fn render_viewports(
+        &mut self,
+        world: &mut World,
+        encoder: &mut wgpu::CommandEncoder,
+        post_submit: &mut Vec<Box<dyn FnOnce() + Send + Send>>,
+        target: &RenderTarget,
+    ) {
+        self.remove_unused(world);
+        let cameras = self.cameras(
+            world,
+            query((self.config.scene, camera_viewport()))
+                .excl(camera_texture_size())
+                .iter(world, None)
+                .map(|(id, (_, viewport))| (id, *viewport)),
+        );
+        let target_size = uvec2(target.color_buffer.size.width, target.color_buffer.size.height).as_vec2();
+        let blit = self.blit.clone();
+        for (id, viewport) in cameras {
+            let offset = (viewport.xy() * target_size).floor().clamp(Vec2::ZERO, target_size - 1.);
+            let size = (viewport.zw() * target_size).floor().clamp(Vec2::ONE, target_size - offset);
+
+            let gpu_handle = world.resource(gpu()).clone();
+            let renderer = self.get_renderer(world, id);
+            if renderer.viewport_target.as_ref().map(|target| (target.color_buffer.size.width, target.color_buffer.size.height))
+                != Some((size.x as u32, size.y as u32))
+            {
+                renderer.viewport_target = Some(RenderTarget::new(gpu_handle, size.as_uvec2(), None));
+            }
+            let viewport_target = renderer.viewport_target.as_ref().unwrap();
+            renderer.renderer.render(
+                world,
+                encoder,
+                post_submit,
+                RendererTarget::Target(viewport_target),
+                Some(Color::rgba(0., 0., 0., 1.)),
+            );
+            blit.run_to_viewport(
+                encoder,
+                &viewport_target.color_buffer_view,
+                &target.color_buffer_view,
+                offset.extend(size.x).extend(size.y),
+            );
+        }
+    }
+
+    /// The cameras which belong to the local user, or to the other players playing on this client
+    fn cameras<T>(&self, world: &World, cameras: impl Iterator<Item = (EntityId, T)>) -> Vec<(EntityId, T)> {
+        let user_id = world.resource_opt(local_user_id());
+        let local_players = world.resource_opt(local_players()).map(|players| players.as_slice()).unwrap_or_default();
+        cameras
+            .filter(|(id, _)| {
+                is_camera_of_user(world, *id, user_id) || local_players.iter().any(|player| is_camera_of_user(world, *id, Some(player)))
+            })
+            .collect()
+    }
+
+    fn get_renderer(&mut self, world: &mut World, camera: EntityId) -> &mut CameraRenderer {
+        self.renderers.entry(camera).or_insert_with(|| {
+            let mut renderer = Renderer::new(world, self.assets.clone(), self.config.clone());
+            renderer.camera = Some(camera);
+            CameraRenderer { renderer, viewport_target: None }
+        })
+    }
+
+    fn remove_unused(&mut self, world: &World) {
+        self.renderers.retain(|id, _| world.has_component(*id, camera_texture_size()) || world.has_component(*id, camera_viewport()));
+    }
+}
+
+impl std::fmt::Debug for CameraRenderers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CameraRenderers").field("cameras", &self.renderers.keys().collect::<Vec<_>>()).finish()
+    }
+}
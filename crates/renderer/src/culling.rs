@@ -2,12 +2,11 @@ use std::{collections::HashMap, f32::INFINITY};
 
 use ambient_core::{
     bounding::world_bounding_sphere,
-    camera::{shadow_cameras_from_world, Camera},
+    camera::{camera_layers, Camera},
     gpu_components,
     gpu_ecs::{GpuComponentFormat, GpuWorldUpdater},
-    player::local_user_id,
 };
-use ambient_ecs::{ArchetypeFilter, World};
+use ambient_ecs::{ArchetypeFilter, EntityId, World};
 use ambient_gpu::{
     gpu::GpuKey,
     shader_module::{BindGroupDesc, ShaderModule, ShaderModuleIdentifier},
@@ -21,7 +20,11 @@ use ambient_std::{
 use glam::{Mat4, UVec3, Vec2, Vec3, Vec3Swizzles, Vec4};
 use wgpu::{BindGroupLayoutEntry, BindingType, BufferBindingType, ShaderStages};
 
-use crate::{get_sun_light_direction, RendererConfig};
+use crate::{
+    get_sun_light_direction,
+    lights::{get_shadowed_lights, MAX_SHADOWED_LIGHTS},
+    RendererConfig,
+};
 
 gpu_components! {
     world_bounding_sphere() => renderer_cameras_visible: GpuComponentFormat::Mat4,
@@ -65,8 +68,11 @@ pub const MAX_SHADOW_CASCADES: u32 = 6;
 struct CullingParams {
     pub main_camera: CullCamera,
     pub shadow_cameras: [CullCamera; MAX_SHADOW_CASCADES as usize],
+    pub light_cameras: [CullCamera; MAX_SHADOWED_LIGHTS as usize],
     pub lod_cutoff_scaling: f32,
-    pub _padding: UVec3,
+    pub light_camera_count: u32,
+    pub main_camera_layers: u32,
+    pub _padding: u32,
 }
 
 pub struct Culling {
@@ -93,6 +99,7 @@ impl Culling {
                 }),
                 ShaderModuleIdentifier::constant("SHADOW_CASCADES", config.shadow_cascades),
                 ShaderModuleIdentifier::constant("MAX_SHADOW_CASCADES", MAX_SHADOW_CASCADES),
+                ShaderModuleIdentifier::constant("MAX_SHADOWED_LIGHTS", MAX_SHADOWED_LIGHTS),
             ],
         );
 
@@ -120,29 +127,35 @@ impl Culling {
         &mut self,
         encoder: &'a mut wgpu::CommandEncoder,
         world: &World,
+        camera: Option<EntityId>,
         binding_context: &HashMap<String, &'a wgpu::BindGroup>,
     ) {
-        let main_camera = if let Some(camera) = Camera::get_active(world, self.config.scene, world.resource_opt(local_user_id())) {
+        let (camera, main_camera) = if let Some(camera) = camera.and_then(|id| Some((id, Camera::from_world(world, id)?))) {
             camera
         } else {
             // log::warn!("No valid camera");
             return;
         };
 
-        let mut params =
-            CullingParams { lod_cutoff_scaling: self.config.lod_cutoff_scaling, main_camera: main_camera.into(), ..Default::default() };
+        let mut params = CullingParams {
+            lod_cutoff_scaling: self.config.lod_cutoff_scaling,
+            main_camera: main_camera.clone().into(),
+            main_camera_layers: world.get(camera, camera_layers()).unwrap_or(u32::MAX),
+            ..Default::default()
+        };
         if self.config.shadow_cascades > 0 {
-            let shadow_cameras = shadow_cameras_from_world(
-                world,
-                self.config.shadow_cascades,
-                self.config.shadow_map_resolution,
-                get_sun_light_direction(world, self.config.scene),
-                self.config.scene,
-                world.resource_opt(local_user_id()),
-            );
-            #[allow(clippy::needless_range_loop)]
-            for i in 0..(self.config.shadow_cascades as usize) {
-                params.shadow_cameras[i] = shadow_cameras[i].clone().into();
+            let sun_direction = get_sun_light_direction(world, self.config.scene);
+            for i in 0..self.config.shadow_cascades {
+                params.shadow_cameras[i as usize] = main_camera
+                    .create_snapping_shadow_camera(sun_direction, i, self.config.shadow_cascades, self.config.shadow_map_resolution)
+                    .into();
+            }
+        }
+        if self.config.shadows {
+            let lights = get_shadowed_lights(world, self.config.scene, main_camera.position());
+            for (i, (_, camera)) in lights.into_iter().enumerate() {
+                params.light_cameras[i] = camera.into();
+                params.light_camera_count += 1;
             }
         }
 
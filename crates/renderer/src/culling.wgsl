@@ -22,7 +22,10 @@ struct Camera {
 struct Params {
     main_camera: Camera,
     shadow_cameras: array<Camera, #MAX_SHADOW_CASCADES>,
+    light_cameras: array<Camera, #MAX_SHADOWED_LIGHTS>,
     lod_cutoff_scaling: f32,
+    light_camera_count: u32,
+    main_camera_layers: u32,
 };
 
 @group(#LODDING_BIND_GROUP)
@@ -86,7 +89,12 @@ fn update(entity_loc: vec2<u32>) {
     }
     var cameras: mat4x4<f32>;
     let bounding_sphere = get_entity_world_bounding_sphere(entity_loc);
-    cameras[0][0] = f32(cull_camera(params.main_camera, bounding_sphere).inside);
+    // Entities are on the first layer by default
+    var layers = 1u;
+    if (has_entity_render_layers(entity_loc)) {
+        layers = bitcast<u32>(get_entity_render_layers(entity_loc).x);
+    }
+    cameras[0][0] = f32(cull_camera(params.main_camera, bounding_sphere).inside && (layers & params.main_camera_layers) != 0u);
 
     for (var i=1u; i <= #SHADOW_CASCADESu; i = i + 1u) {
         let a = i >> 2u;
@@ -113,5 +121,10 @@ fn update(entity_loc: vec2<u32>) {
             break;
         }
     }
+    // The shadow cameras of spot lights come after all the possible shadow cascades
+    for (var i=0u; i < params.light_camera_count; i = i + 1u) {
+        let camera = 1u + #MAX_SHADOW_CASCADESu + i;
+        cameras[camera >> 2u][camera & 3u] = f32(cull_camera(params.light_cameras[i], bounding_sphere).inside);
+    }
     set_entity_renderer_cameras_visible(entity_loc, cameras);
 }
@@ -0,0 +1,21 @@
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(i32(vertex_index) / 2) * 2.0;
+    let y = f32(i32(vertex_index) & 1) * 2.0;
+    return vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+}
+
+@group(0)
+@binding(0)
+var depth_ms: texture_depth_multisampled_2d;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @builtin(frag_depth) f32 {
+    let coord = vec2<i32>(position.xy);
+    var depth = 0.0;
+    // Reverse-z, so the closest sample has the largest depth
+    for (var i = 0; i < textureNumSamples(depth_ms); i++) {
+        depth = max(depth, textureLoad(depth_ms, coord, i));
+    }
+    return depth;
+}
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ambient_core::{asset_cache, gpu};
+use ambient_ecs::{components, query, Debuggable, Description, Name, Resource, SystemGroup, World};
+use ambient_gpu::{
+    std_assets::{DefaultNormalMapViewKey, PixelTextureViewKey},
+    texture::Texture,
+};
+use ambient_std::{asset_cache::SyncAssetKeyExt, cb};
+use glam::UVec2;
+
+use crate::{
+    material,
+    materials::pbr_material::{get_pbr_shader, PbrMaterial, PbrMaterialConfig, PbrMaterialParams},
+    renderer_shader, SharedMaterial,
+};
+
+components!("rendering", {
+    @[
+        Debuggable,
+        Name["Texture handle"],
+        Description["Displays the dynamic texture with this handle, which was created at runtime on this client, on this entity, e.g. on a mesh.\nUseful for minimaps, procedural skins and video-like surfaces."]
+    ]
+    texture_handle: u64,
+    @[Resource]
+    dynamic_textures: DynamicTextures,
+    /// The texture currently displayed by an entity with a `texture_handle`
+    displayed_dynamic_texture: Arc<Texture>,
+});
+
+/// The textures created at runtime, e.g. by modules, by handle
+#[derive(Debug, Clone, Default)]
+pub struct DynamicTextures {
+    textures: HashMap<u64, Arc<Texture>>,
+    next_handle: u64,
+}
+
+const BYTES_PER_PIXEL: usize = 4;
+
+fn textures_mut(world: &mut World) -> &mut DynamicTextures {
+    if world.resource_opt(dynamic_textures()).is_none() {
+        world.add_resource(dynamic_textures(), DynamicTextures::default());
+    }
+    world.resource_mut(dynamic_textures())
+}
+
+fn check_pixels(size: UVec2, pixels: &[u8]) -> anyhow::Result<()> {
+    let expected = size.x as usize * size.y as usize * BYTES_PER_PIXEL;
+    if pixels.len() != expected {
+        anyhow::bail!("Expected {expected} bytes of RGBA pixels for {}x{} pixels, got {}", size.x, size.y, pixels.len());
+    }
+    Ok(())
+}
+
+/// Creates an sRGB RGBA8 texture of `size`, filled with `pixels` (or transparent black if empty), and returns the handle
+/// entities can display it with through `texture_handle`
+pub fn create_dynamic_texture(world: &mut World, size: UVec2, pixels: &[u8]) -> anyhow::Result<u64> {
+    let gpu = world.resource(gpu()).clone();
+    let max_size = gpu.device.limits().max_texture_dimension_2d;
+    if size.x == 0 || size.y == 0 || size.x > max_size || size.y > max_size {
+        anyhow::bail!("Textures must be between 1x1 and {max_size}x{max_size} pixels, got {}x{}", size.x, size.y);
+    }
+    let pixels = if pixels.is_empty() { vec![0; size.x as usize * size.y as usize * BYTES_PER_PIXEL] } else { pixels.to_vec() };
+    check_pixels(size, &pixels)?;
+    let texture = Texture::new_with_data(
+        gpu,
+        &wgpu::TextureDescriptor {
+            label: Some("Dynamic texture"),
+            size: wgpu::Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        },
+        &pixels,
+    );
+    let textures = textures_mut(world);
+    let handle = textures.next_handle;
+    textures.next_handle += 1;
+    textures.textures.insert(handle, Arc::new(texture));
+    Ok(handle)
+}
+
+/// Overwrites the region of the texture with `handle` at `origin` of `size` with `pixels`, rows of RGBA8 pixels
+pub fn update_dynamic_texture(world: &mut World, handle: u64, origin: UVec2, size: UVec2, pixels: &[u8]) -> anyhow::Result<()> {
+    let Some(texture) = textures_mut(world).textures.get(&handle) else { anyhow::bail!("There's no dynamic texture with handle {handle}") };
+    if origin.x as u64 + size.x as u64 > texture.size.width as u64 || origin.y as u64 + size.y as u64 > texture.size.height as u64 {
+        anyhow::bail!(
+            "The region at {origin} of size {size} is out of bounds of the {}x{} texture with handle {handle}",
+            texture.size.width,
+            texture.size.height
+        );
+    }
+    check_pixels(size, pixels)?;
+    if size.x > 0 && size.y > 0 {
+        texture.write_region(origin, size, pixels);
+    }
+    Ok(())
+}
+
+/// Forgets the texture with `handle`; it's freed once no entity displays it anymore
+pub fn destroy_dynamic_texture(world: &mut World, handle: u64) {
+    textures_mut(world).textures.remove(&handle);
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "dynamic_textures",
+        vec![query(texture_handle()).to_system(|q, world, qs, _| {
+            for (id, handle) in q.collect_cloned(world, qs) {
+                let Some(texture) = world.resource_opt(dynamic_textures()).and_then(|textures| textures.textures.get(&handle)).cloned()
+                else {
+                    continue;
+                };
+                if world.get_ref(id, displayed_dynamic_texture()).map_or(false, |displayed| Arc::ptr_eq(displayed, &texture)) {
+                    continue;
+                }
+                let assets = world.resource(asset_cache()).clone();
+                let mat = PbrMaterial::new(
+                    assets.clone(),
+                    PbrMaterialConfig {
+                        source: "Dynamic texture".to_string(),
+                        name: format!("Dynamic texture {handle}"),
+                        params: PbrMaterialParams::default(),
+                        base_color: Arc::new(texture.create_view(&Default::default())),
+                        normalmap: DefaultNormalMapViewKey.get(&assets),
+                        metallic_roughness: PixelTextureViewKey::white().get(&assets),
+                        emissive: PixelTextureViewKey::white().get(&assets),
+                        transparent: None,
+                        double_sided: None,
+                        depth_write_enabled: None,
+                    },
+                );
+                if !world.has_component(id, renderer_shader()) {
+                    world.add_component(id, renderer_shader(), cb(get_pbr_shader)).unwrap();
+                }
+                world.add_component(id, material(), SharedMaterial::new(mat)).unwrap();
+                world.add_component(id, displayed_dynamic_texture(), texture).unwrap();
+            }
+        })],
+    )
+}
@@ -0,0 +1,498 @@
+use std::sync::Arc;
+
+use ambient_core::{asset_cache, async_ecs::async_run, runtime, transform::translation};
+use ambient_ecs::{components, query, Debuggable, Description, Entity, EntityId, Name, Networked, Store, SystemGroup, World};
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    shader_module::{BindGroupDesc, Shader, ShaderModule},
+    texture::{Texture, TextureView},
+    texture_loaders::ImageFromUrl,
+};
+use ambient_std::{
+    asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKey, SyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
+    cb,
+    download_asset::AssetError,
+    friendly_id, include_file,
+};
+use async_trait::async_trait;
+use glam::{vec3, Vec4};
+use wgpu::util::DeviceExt;
+
+use crate::{get_overlay_modules, material, overlay, renderer_shader, Material, RendererShader, SharedMaterial, MATERIAL_BIND_GROUP};
+
+components!("rendering", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Environment map from URL"],
+        Description["Load an equirectangular HDR or EXR image from the URL and use it as the skybox and the image-based lighting of the scene.\nReplaces the ambient light of the `sun`."]
+    ]
+    environment_map_from_url: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Environment intensity"],
+        Description["Scales the light contributed by the `environment_map_from_url` of this entity. Defaults to 1."]
+    ]
+    environment_intensity: f32,
+    environment_map: Arc<EnvironmentMap>,
+});
+
+/// The size of a face of the specular cubemap, at its most detailed mip
+const SPECULAR_SIZE: u32 = 256;
+/// Each mip of the specular cubemap is prefiltered for a roughness, from 0 at mip 0 to 1 at the last mip
+pub const SPECULAR_MIP_LEVELS: u32 = 6;
+const IRRADIANCE_SIZE: u32 = 32;
+const SPECULAR_SAMPLE_COUNT: u32 = 512;
+const WORKGROUP_SIZE: u32 = 8;
+const CUBE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "environment",
+        vec![query(environment_map_from_url().changed()).to_system(|q, world, qs, _| {
+            for (id, url) in q.collect_cloned(world, qs) {
+                let url = match AbsAssetUrl::parse(url) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        log::warn!("Failed to parse environment_map_from_url url: {:?}", err);
+                        continue;
+                    }
+                };
+                let assets = world.resource(asset_cache()).clone();
+                let async_run = world.resource(async_run()).clone();
+                world.resource(runtime()).spawn(async move {
+                    match EnvironmentMapFromUrl(url).get(&assets).await {
+                        Err(err) => {
+                            log::warn!("Failed to load environment map from url: {:?}", err);
+                        }
+                        Ok(map) => {
+                            let skybox = SkyboxMaterial::new(&assets, &map);
+                            async_run.run(move |world| {
+                                world
+                                    .add_components(
+                                        id,
+                                        Entity::new()
+                                            .with(environment_map(), map)
+                                            .with(
+                                                renderer_shader(),
+                                                cb(|assets, config| {
+                                                    SkyboxShaderKey { shadow_cascades: config.shadow_cascades }.get(assets)
+                                                }),
+                                            )
+                                            .with(material(), SharedMaterial::new(skybox))
+                                            .with(overlay(), ())
+                                            .with(translation(), vec3(0.0, 0.0, -1.0)),
+                                    )
+                                    .ok();
+                            });
+                        }
+                    }
+                });
+            }
+        })],
+    )
+}
+
+/// Returns the environment map lighting the scene, if any
+pub fn get_active_environment(world: &World, scene: ambient_ecs::Component<()>) -> Option<(EntityId, Arc<EnvironmentMap>)> {
+    query((scene, environment_map())).iter(world, None).next().map(|(id, (_, map))| (id, map.clone()))
+}
+
+/// An environment, prefiltered for image-based lighting
+#[derive(Debug)]
+pub struct EnvironmentMap {
+    /// The cosine-weighted incoming light for each normal direction
+    pub irradiance: TextureView,
+    /// The incoming light for each reflection direction, blurred for increasing roughness in each mip
+    pub specular: TextureView,
+}
+
+impl EnvironmentMap {
+    /// Converts an equirectangular image (RGBA, in linear color) to cubemaps and prefilters them
+    pub fn from_equirect(assets: &AssetCache, width: u32, height: u32, data: &[f32]) -> Self {
+        let gpu = GpuKey.get(assets);
+        let pipelines = EnvironmentPipelinesKey.get(assets);
+
+        let equirect = Arc::new(Texture::new_with_data(
+            gpu.clone(),
+            &wgpu::TextureDescriptor {
+                label: Some("EnvironmentMap.equirect"),
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba32Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            },
+            bytemuck::cast_slice(data),
+        ));
+        let radiance = create_cube(gpu.clone(), "EnvironmentMap.radiance", SPECULAR_SIZE, 1);
+        let specular = create_cube(gpu.clone(), "EnvironmentMap.specular", SPECULAR_SIZE, SPECULAR_MIP_LEVELS);
+        let irradiance = create_cube(gpu.clone(), "EnvironmentMap.irradiance", IRRADIANCE_SIZE, 1);
+
+        let mut encoder = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("EnvironmentMap") });
+        pipelines.equirect_to_cube(&gpu, &mut encoder, &equirect.create_view(&Default::default()), &radiance, SPECULAR_SIZE);
+        prefilter_specular(assets, &mut encoder, &cube_view(&radiance, 0, 1), &specular, 0, SPECULAR_SIZE);
+        // The irradiance is integrated from a blurred mip, which undersamples the environment a lot less than the full resolution
+        let irradiance_source_mip = 3;
+        let irradiance_source = cube_view(&specular, irradiance_source_mip, 1);
+        pipelines.prefilter(
+            &gpu,
+            &mut encoder,
+            &pipelines.irradiance,
+            &irradiance_source,
+            &storage_view(&irradiance, 0),
+            IRRADIANCE_SIZE,
+            PrefilterParams { roughness: 1., sample_count: 0, _padding: Default::default() },
+        );
+        gpu.queue.submit(Some(encoder.finish()));
+
+        Self { irradiance: cube_view(&irradiance, 0, 1), specular: cube_view(&specular, 0, SPECULAR_MIP_LEVELS) }
+    }
+
+    pub fn size_in_bytes(&self) -> u64 {
+        self.irradiance.texture.size_in_bytes + self.specular.texture.size_in_bytes
+    }
+}
+
+/// Prefilters the radiance cubemap `source` into the [SPECULAR_MIP_LEVELS] mips of the six layers of `target` starting at
+/// `base_layer`, whose faces are `size` texels wide
+pub(crate) fn prefilter_specular(
+    assets: &AssetCache,
+    encoder: &mut wgpu::CommandEncoder,
+    source: &wgpu::TextureView,
+    target: &Arc<Texture>,
+    base_layer: u32,
+    size: u32,
+) {
+    let gpu = GpuKey.get(assets);
+    let pipelines = EnvironmentPipelinesKey.get(assets);
+    for mip in 0..SPECULAR_MIP_LEVELS {
+        let roughness = mip as f32 / (SPECULAR_MIP_LEVELS - 1) as f32;
+        let sample_count = if mip == 0 { 1 } else { SPECULAR_SAMPLE_COUNT };
+        let target = target.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            base_mip_level: mip,
+            mip_level_count: std::num::NonZeroU32::new(1),
+            base_array_layer: base_layer,
+            array_layer_count: std::num::NonZeroU32::new(6),
+            ..Default::default()
+        });
+        pipelines.prefilter(
+            &gpu,
+            encoder,
+            &pipelines.specular,
+            source,
+            &target,
+            size >> mip,
+            PrefilterParams { roughness, sample_count, _padding: Default::default() },
+        );
+    }
+}
+
+fn create_cube(gpu: Arc<Gpu>, label: &str, size: u32, mip_level_count: u32) -> Arc<Texture> {
+    Arc::new(Texture::new(
+        gpu,
+        &wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d { width: size, height: size, depth_or_array_layers: 6 },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CUBE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+        },
+    ))
+}
+
+fn cube_view(texture: &Arc<Texture>, base_mip_level: u32, mip_level_count: u32) -> TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::Cube),
+        base_mip_level,
+        mip_level_count: std::num::NonZeroU32::new(mip_level_count),
+        ..Default::default()
+    })
+}
+
+fn storage_view(texture: &Arc<Texture>, mip: u32) -> TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        base_mip_level: mip,
+        mip_level_count: std::num::NonZeroU32::new(1),
+        ..Default::default()
+    })
+}
+
+/// A black environment, bound when there is no environment map
+pub(crate) fn create_dummy_environment_view(gpu: Arc<Gpu>) -> TextureView {
+    cube_view(&create_cube(gpu, "EnvironmentMap.dummy", 1, 1), 0, 1)
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvironmentMapFromUrl(pub AbsAssetUrl);
+#[async_trait]
+impl AsyncAssetKey<Result<Arc<EnvironmentMap>, AssetError>> for EnvironmentMapFromUrl {
+    fn gpu_size(&self, asset: &Result<Arc<EnvironmentMap>, AssetError>) -> Option<u64> {
+        asset.as_ref().ok().map(|asset| asset.size_in_bytes())
+    }
+    async fn load(self, assets: AssetCache) -> Result<Arc<EnvironmentMap>, AssetError> {
+        let image = ImageFromUrl { url: self.0 }.get(&assets).await?;
+        let image = image.to_rgba32f();
+        Ok(Arc::new(EnvironmentMap::from_equirect(&assets, image.width(), image.height(), image.as_raw())))
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PrefilterParams {
+    roughness: f32,
+    sample_count: u32,
+    _padding: [f32; 2],
+}
+
+struct EnvironmentPipelines {
+    equirect: wgpu::ComputePipeline,
+    specular: wgpu::ComputePipeline,
+    irradiance: wgpu::ComputePipeline,
+    sampler: wgpu::Sampler,
+}
+
+impl EnvironmentPipelines {
+    fn new(gpu: &Gpu) -> Self {
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format: CUBE_FORMAT,
+                view_dimension: wgpu::TextureViewDimension::D2Array,
+            },
+            count: None,
+        };
+        let create_pipeline = |label: &str, source: String, entry_point: &str, entries: &[wgpu::BindGroupLayoutEntry]| {
+            let module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor { label: Some(label), source: wgpu::ShaderSource::Wgsl(source.into()) });
+            let layout = gpu.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor { label: Some(label), entries });
+            gpu.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&gpu.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some(label),
+                    bind_group_layouts: &[&layout],
+                    push_constant_ranges: &[],
+                })),
+                module: &module,
+                entry_point,
+            })
+        };
+
+        let common = include_file!("environment_common.wgsl");
+        let equirect = create_pipeline(
+            "EnvironmentPipelines.equirect",
+            format!("{common}\n{}", include_file!("environment_equirect.wgsl")),
+            "main",
+            &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                storage_entry(1),
+            ],
+        );
+        let prefilter_entries = [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            storage_entry(2),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer { ty: wgpu::BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                count: None,
+            },
+        ];
+        let prefilter = format!("{common}\n{}", include_file!("environment_prefilter.wgsl"));
+        let specular = create_pipeline("EnvironmentPipelines.specular", prefilter.clone(), "specular_main", &prefilter_entries);
+        let irradiance = create_pipeline("EnvironmentPipelines.irradiance", prefilter, "irradiance_main", &prefilter_entries);
+
+        let sampler = gpu.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("EnvironmentPipelines.sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self { equirect, specular, irradiance, sampler }
+    }
+
+    fn equirect_to_cube(
+        &self,
+        gpu: &Gpu,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        target: &Arc<Texture>,
+        size: u32,
+    ) {
+        let target = storage_view(target, 0);
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("EnvironmentPipelines.equirect"),
+            layout: &self.equirect.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&target) },
+            ],
+        });
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("EnvironmentPipelines.equirect") });
+        cpass.set_pipeline(&self.equirect);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(workgroups(size), workgroups(size), 6);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn prefilter(
+        &self,
+        gpu: &Gpu,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::ComputePipeline,
+        source: &wgpu::TextureView,
+        target: &wgpu::TextureView,
+        size: u32,
+        params: PrefilterParams,
+    ) {
+        let params = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("EnvironmentPipelines.params"),
+            contents: bytemuck::cast_slice(&[params]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("EnvironmentPipelines.prefilter"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(target) },
+                wgpu::BindGroupEntry { binding: 3, resource: params.as_entire_binding() },
+            ],
+        });
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("EnvironmentPipelines.prefilter") });
+        cpass.set_pipeline(pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(workgroups(size), workgroups(size), 6);
+    }
+}
+
+fn workgroups(size: u32) -> u32 {
+    (size + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE
+}
+
+#[derive(Debug, Clone)]
+struct EnvironmentPipelinesKey;
+impl SyncAssetKey<Arc<EnvironmentPipelines>> for EnvironmentPipelinesKey {
+    fn load(&self, assets: AssetCache) -> Arc<EnvironmentPipelines> {
+        Arc::new(EnvironmentPipelines::new(&GpuKey.get(&assets)))
+    }
+}
+
+/// Draws the environment map behind everything else
+#[derive(Debug)]
+pub struct SkyboxMaterial {
+    id: String,
+    bind_group: wgpu::BindGroup,
+}
+
+impl SkyboxMaterial {
+    pub fn new(assets: &AssetCache, map: &EnvironmentMap) -> Self {
+        let gpu = GpuKey.get(assets);
+        let shader = SkyboxShaderKey { shadow_cascades: 1 }.get(assets);
+        Self {
+            id: friendly_id(),
+            bind_group: gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: shader.material_layout(),
+                entries: &[wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&map.specular) }],
+                label: Some("SkyboxMaterial.bind_group"),
+            }),
+        }
+    }
+}
+
+impl Material for SkyboxMaterial {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn bind(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SkyboxShaderKey {
+    pub shadow_cascades: u32,
+}
+impl SyncAssetKey<Arc<RendererShader>> for SkyboxShaderKey {
+    fn load(&self, assets: AssetCache) -> Arc<RendererShader> {
+        let layout = BindGroupDesc {
+            entries: vec![wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+            label: MATERIAL_BIND_GROUP.into(),
+        };
+
+        let id = "skybox shader".to_string();
+        Arc::new(RendererShader {
+            shader: Shader::from_modules(
+                &assets,
+                id.clone(),
+                get_overlay_modules(&assets, self.shadow_cascades).iter().chain([&ShaderModule::new(
+                    "Skybox",
+                    include_file!("skybox.wgsl"),
+                    vec![layout.into()],
+                )]),
+            ),
+            id,
+            vs_main: "vs_main".to_string(),
+            fs_forward_main: "fs_forward_main".to_string(),
+            fs_shadow_main: "fs_forward_main".to_string(),
+            fs_outline_main: "fs_forward_main".to_string(),
+            fs_picking_main: "fs_picking_main".to_string(),
+            transparent: false,
+            double_sided: true,
+            depth_write_enabled: true,
+            transparency_group: 0,
+        })
+    }
+}
+
+/// The parameters of the environment in the globals, as `(intensity, specular mip levels, enabled, unused)`
+pub(crate) fn environment_params(intensity: Option<f32>) -> Vec4 {
+    match intensity {
+        Some(intensity) => Vec4::new(intensity, (SPECULAR_MIP_LEVELS - 1) as f32, 1., 0.),
+        None => Vec4::ZERO,
+    }
+}
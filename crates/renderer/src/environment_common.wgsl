@@ -0,0 +1,19 @@
+// Ambient is Z-up, while cubemaps are addressed Y-up
+fn cube_to_world(dir: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(dir.x, -dir.z, dir.y);
+}
+
+// The direction through the center of texel `id.xy` on the cube face `id.z`, in cube space
+fn cube_texel_direction(id: vec3<u32>, size: u32) -> vec3<f32> {
+    let uv = (vec2<f32>(id.xy) + 0.5) / f32(size) * 2.0 - 1.0;
+    var dir: vec3<f32>;
+    switch (id.z) {
+        case 0u: { dir = vec3<f32>(1.0, -uv.y, -uv.x); }
+        case 1u: { dir = vec3<f32>(-1.0, -uv.y, uv.x); }
+        case 2u: { dir = vec3<f32>(uv.x, 1.0, uv.y); }
+        case 3u: { dir = vec3<f32>(uv.x, -1.0, -uv.y); }
+        case 4u: { dir = vec3<f32>(uv.x, -uv.y, 1.0); }
+        default: { dir = vec3<f32>(-uv.x, -uv.y, -1.0); }
+    }
+    return normalize(dir);
+}
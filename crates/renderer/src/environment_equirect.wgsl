@@ -0,0 +1,40 @@
+@group(0)
+@binding(0)
+var equirect: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var output: texture_storage_2d_array<rgba16float, write>;
+
+// Wraps around horizontally and clamps vertically
+fn load_equirect(coord: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    let x = (coord.x % size.x + size.x) % size.x;
+    let y = clamp(coord.y, 0, size.y - 1);
+    return textureLoad(equirect, vec2<i32>(x, y), 0);
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let output_size = vec2<u32>(textureDimensions(output));
+    if (id.x >= output_size.x || id.y >= output_size.y) {
+        return;
+    }
+    let pi = 3.14159265;
+    let dir = cube_to_world(cube_texel_direction(id, output_size.x));
+    let uv = vec2<f32>(atan2(dir.y, dir.x) / (2.0 * pi) + 0.5, acos(clamp(dir.z, -1.0, 1.0)) / pi);
+
+    // The source is a float texture, which can't be filtered by a sampler on all platforms
+    let size = vec2<i32>(textureDimensions(equirect));
+    let p = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(p));
+    let t = fract(p);
+    let color = mix(
+        mix(load_equirect(base, size), load_equirect(base + vec2<i32>(1, 0), size), t.x),
+        mix(load_equirect(base + vec2<i32>(0, 1), size), load_equirect(base + vec2<i32>(1, 1), size), t.x),
+        t.y
+    );
+
+    // Very bright texels, such as the sun, would overflow the half floats of the cubemap
+    textureStore(output, vec2<i32>(id.xy), i32(id.z), vec4<f32>(min(color.rgb, vec3<f32>(65000.0)), 1.0));
+}
@@ -0,0 +1,109 @@
+struct PrefilterParams {
+    roughness: f32,
+    sample_count: u32,
+};
+
+@group(0)
+@binding(0)
+var source: texture_cube<f32>;
+
+@group(0)
+@binding(1)
+var source_sampler: sampler;
+
+@group(0)
+@binding(2)
+var output: texture_storage_2d_array<rgba16float, write>;
+
+@group(0)
+@binding(3)
+var<uniform> params: PrefilterParams;
+
+fn radical_inverse_vdc(input: u32) -> f32 {
+    var bits = input;
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return f32(bits) * 2.3283064365386963e-10;
+}
+
+fn hammersley(i: u32, n: u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(n), radical_inverse_vdc(i));
+}
+
+// Returns a halfway vector around `n`, distributed according to the GGX normal distribution
+fn importance_sample_ggx(xi: vec2<f32>, n: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let a = roughness * roughness;
+    let phi = 2.0 * 3.14159265 * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+
+    var up = vec3<f32>(1.0, 0.0, 0.0);
+    if (abs(n.z) < 0.999) {
+        up = vec3<f32>(0.0, 0.0, 1.0);
+    }
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+    return normalize(tangent * cos(phi) * sin_theta + bitangent * sin(phi) * sin_theta + n * cos_theta);
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn specular_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<u32>(textureDimensions(output));
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    // Assumes that the view direction is the same as the normal and the reflection, as in the split sum approximation
+    let n = cube_texel_direction(id, size.x);
+    if (params.sample_count <= 1u) {
+        textureStore(output, vec2<i32>(id.xy), i32(id.z), textureSampleLevel(source, source_sampler, n, 0.0));
+        return;
+    }
+
+    var color = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var i = 0u; i < params.sample_count; i = i + 1u) {
+        let h = importance_sample_ggx(hammersley(i, params.sample_count), n, params.roughness);
+        let l = normalize(2.0 * dot(n, h) * h - n);
+        let ndotl = dot(n, l);
+        if (ndotl > 0.0) {
+            color = color + textureSampleLevel(source, source_sampler, l, 0.0).rgb * ndotl;
+            total_weight = total_weight + ndotl;
+        }
+    }
+    textureStore(output, vec2<i32>(id.xy), i32(id.z), vec4<f32>(color / max(total_weight, 0.0001), 1.0));
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn irradiance_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<u32>(textureDimensions(output));
+    if (id.x >= size.x || id.y >= size.y) {
+        return;
+    }
+    let pi = 3.14159265;
+    let n = cube_texel_direction(id, size.x);
+
+    var up = vec3<f32>(0.0, 1.0, 0.0);
+    if (abs(n.y) > 0.999) {
+        up = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+
+    // Integrates the cosine-weighted incoming light over the hemisphere
+    let delta = 0.05;
+    var irradiance = vec3<f32>(0.0);
+    var count = 0.0;
+    for (var phi = 0.0; phi < 2.0 * pi; phi = phi + delta) {
+        for (var theta = 0.0; theta < 0.5 * pi; theta = theta + delta) {
+            let dir = (tangent * cos(phi) + bitangent * sin(phi)) * sin(theta) + n * cos(theta);
+            irradiance = irradiance + textureSampleLevel(source, source_sampler, dir, 0.0).rgb * cos(theta) * sin(theta);
+            count = count + 1.0;
+        }
+    }
+    textureStore(output, vec2<i32>(id.xy), i32(id.z), vec4<f32>(pi * irradiance / count, 1.0));
+}
@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
 use ambient_core::{
-    camera::{far, fog, get_active_camera, projection_view},
+    camera::{exposure, far, fog, get_active_camera, projection_view},
     player::local_user_id,
     transform::{get_world_position, get_world_rotation, local_to_world},
+    weather::weather,
 };
-use ambient_ecs::{Component, ECSError, World};
+use ambient_ecs::{Component, ECSError, EntityId, World};
 use ambient_gpu::{
     gpu::{Gpu, GpuKey},
     shader_module::BindGroupDesc,
@@ -16,8 +17,13 @@ use ambient_std::asset_cache::{AssetCache, SyncAssetKeyExt};
 use glam::{vec3, Mat4, UVec2, Vec3, Vec4};
 use wgpu::BindGroup;
 
-use super::{fog_color, get_active_sun, light_ambient, light_diffuse, RenderTarget, ShadowCameraData};
-use crate::{fog_density, fog_height_falloff};
+use super::{fog_color, get_active_sun, light_ambient, light_diffuse, sky_intensity, RenderTarget, ShadowCameraData};
+use crate::{
+    environment::{create_dummy_environment_view, environment_intensity, environment_params, get_active_environment, EnvironmentMap},
+    fog_density, fog_height_falloff,
+    lights::{create_dummy_light_buffers, light_intensity, ClusteredLights, MAX_SHADOWED_LIGHTS},
+    reflection_probes::{create_dummy_reflection_probes_view, GpuReflectionProbe, MAX_REFLECTION_PROBES},
+};
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -47,6 +53,18 @@ pub(crate) struct GlobalParams {
     pub fog_height_falloff: f32,
     pub fog_density: f32,
     pub debug_params: ShaderDebugParams,
+    pub environment_params: Vec4,
+    pub cluster_params: Vec4,
+    pub exposure: f32,
+    pub wetness: f32,
+    pub sky_intensity: f32,
+    pub _padding: f32,
+    /// (velocity, unused) of the wind
+    pub wind: Vec4,
+    pub reflection_probe_count: u32,
+    pub _reflection_probe_padding: [u32; 3],
+    /// The captured reflection probes, from the smallest to the largest volume
+    pub reflection_probes: [GpuReflectionProbe; MAX_REFLECTION_PROBES],
 }
 
 impl Default for GlobalParams {
@@ -67,6 +85,16 @@ impl Default for GlobalParams {
             fog_height_falloff: 0.5,
             fog_density: 0.5,
             debug_params: Default::default(),
+            environment_params: environment_params(None),
+            cluster_params: Vec4::ZERO,
+            exposure: 1.,
+            wetness: 0.,
+            sky_intensity: 20.,
+            _padding: Default::default(),
+            wind: Vec4::ZERO,
+            reflection_probe_count: 0,
+            _reflection_probe_padding: Default::default(),
+            reflection_probes: Default::default(),
         }
     }
 }
@@ -146,6 +174,56 @@ pub fn globals_layout() -> BindGroupDesc {
                 },
                 count: None,
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 8,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 9,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 10,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 11,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 12,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2Array,
+                    multisampled: false,
+                },
+                count: None,
+            },
         ],
         label: "GLOBALS_BIND_GROUP".into(),
     }
@@ -157,6 +235,9 @@ pub(crate) struct ForwardGlobals {
     shadow_cameras_buffer: wgpu::Buffer,
     shadow_sampler: wgpu::Sampler,
     dummy_shadow_texture: TextureView,
+    dummy_environment: TextureView,
+    environment: Option<Arc<EnvironmentMap>>,
+    dummy_reflection_probes: TextureView,
     pub(crate) params: GlobalParams,
     scene: Component<()>,
     start_time: ambient_sys::time::Instant,
@@ -175,7 +256,7 @@ impl ForwardGlobals {
         let shadow_cameras_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("ForwardGlobals.shadow_cameras_buffer"),
             usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            size: shadow_cascades as u64 * std::mem::size_of::<ShadowCameraData>() as u64,
+            size: (shadow_cascades + MAX_SHADOWED_LIGHTS) as u64 * std::mem::size_of::<ShadowCameraData>() as u64,
             mapped_at_creation: false,
         });
 
@@ -198,6 +279,9 @@ impl ForwardGlobals {
             shadow_cameras_buffer,
             shadow_sampler,
             dummy_shadow_texture: create_dummy_shadow_texture(gpu.clone()).create_view(&Default::default()),
+            dummy_environment: create_dummy_environment_view(gpu.clone()),
+            environment: None,
+            dummy_reflection_probes: create_dummy_reflection_probes_view(gpu.clone()),
             params,
             gpu,
             scene,
@@ -206,7 +290,14 @@ impl ForwardGlobals {
         }
     }
 
-    pub fn create_bind_group(&self, assets: AssetCache, shadow_texture: Option<&TextureView>, solids_frame: &RenderTarget) -> BindGroup {
+    pub fn create_bind_group(
+        &self,
+        assets: AssetCache,
+        shadow_texture: Option<&TextureView>,
+        solids_frame: &RenderTarget,
+        lights: &ClusteredLights,
+        reflection_probes: Option<&TextureView>,
+    ) -> BindGroup {
         self.gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &self.layout,
             entries: &[
@@ -224,13 +315,31 @@ impl ForwardGlobals {
                 wgpu::BindGroupEntry { binding: 5, resource: wgpu::BindingResource::TextureView(&solids_frame.color_buffer_view) },
                 wgpu::BindGroupEntry { binding: 6, resource: wgpu::BindingResource::TextureView(&solids_frame.depth_buffer_view) },
                 wgpu::BindGroupEntry { binding: 7, resource: wgpu::BindingResource::TextureView(&solids_frame.normals_quat_buffer_view) },
+                wgpu::BindGroupEntry {
+                    binding: 8,
+                    resource: wgpu::BindingResource::TextureView(
+                        self.environment.as_ref().map_or(&self.dummy_environment, |x| &x.irradiance),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 9,
+                    resource: wgpu::BindingResource::TextureView(
+                        self.environment.as_ref().map_or(&self.dummy_environment, |x| &x.specular),
+                    ),
+                },
+                wgpu::BindGroupEntry { binding: 10, resource: lights.lights_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 11, resource: lights.clusters_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry {
+                    binding: 12,
+                    resource: wgpu::BindingResource::TextureView(reflection_probes.unwrap_or(&self.dummy_reflection_probes)),
+                },
             ],
             label: Some("ForwardGlobals.bind_group"),
         })
     }
-    pub fn update(&mut self, world: &World, shadow_cameras: &[ShadowCameraData]) {
+    pub fn update(&mut self, world: &World, camera: Option<EntityId>, shadow_cameras: &[ShadowCameraData]) {
         let mut p = &mut self.params;
-        if let Some(id) = get_active_camera(world, self.scene, world.resource_opt(local_user_id())) {
+        if let Some(id) = camera {
             p.projection_view = world.get(id, projection_view()).unwrap_or_default();
             p.inv_projection_view = p.projection_view.inverse();
             p.camera_position = get_world_position(world, id).unwrap_or_default().extend(1.);
@@ -238,6 +347,7 @@ impl ForwardGlobals {
             p.camera_far = world.get(id, far()).unwrap_or(1e3);
             p.fog = world.has_component(id, fog()) as i32;
             p.forward_camera_position = p.camera_position;
+            p.exposure = world.get(id, exposure()).map_or(1., exposure_from_ev100);
         }
         if let Some(sun) = get_active_sun(world, self.scene) {
             fn update<T, U>(out: &mut T, input: Result<U, ECSError>, mapper: impl Fn(U) -> T) {
@@ -247,18 +357,34 @@ impl ForwardGlobals {
             }
 
             update(&mut p.sun_direction, get_world_rotation(world, sun), |v| v.mul_vec3(Vec3::X).extend(1.));
-            update(&mut p.sun_diffuse, world.get(sun, light_diffuse()), |v| v.extend(1.));
+            update(&mut p.sun_diffuse, world.get(sun, light_diffuse()), |v| {
+                (v * world.get(sun, light_intensity()).unwrap_or(1.)).extend(1.)
+            });
             update(&mut p.sun_ambient, world.get(sun, light_ambient()), |v| v.extend(1.));
             update(&mut p.fog_color, world.get(sun, fog_color()), |v| v.extend(1.));
             update(&mut p.fog_height_falloff, world.get(sun, fog_height_falloff()), |v| v);
             update(&mut p.fog_density, world.get(sun, fog_density()), |v| v);
+            update(&mut p.sky_intensity, world.get(sun, sky_intensity()), |v| v);
         }
+        let environment = get_active_environment(world, self.scene);
+        p.environment_params =
+            environment_params(environment.as_ref().map(|(id, _)| world.get(*id, environment_intensity()).unwrap_or(1.)));
+        self.environment = environment.map(|(_, map)| map);
+        let weather = world.resource_opt(weather()).copied().unwrap_or_default();
+        p.wetness = weather.wetness;
+        p.wind = weather.wind.extend(0.);
         self.params.time = ambient_sys::time::Instant::now().duration_since(self.start_time).as_secs_f32();
         self.gpu.queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.params]));
         self.gpu.queue.write_buffer(&self.shadow_cameras_buffer, 0, bytemuck::cast_slice(shadow_cameras));
     }
 }
 
+/// The factor which maps luminance to the `[0, 1]` range of the display at an exposure of `ev100`,
+/// i.e. the inverse of the maximum luminance a camera with that exposure records without saturating
+pub(crate) fn exposure_from_ev100(ev100: f32) -> f32 {
+    1. / (1.2 * 2f32.powf(ev100))
+}
+
 fn create_dummy_shadow_texture(gpu: Arc<Gpu>) -> Arc<Texture> {
     Arc::new(Texture::new(
         gpu,
@@ -308,6 +434,9 @@ impl ShadowAndUIGlobals {
         let shadow_texture = create_dummy_shadow_texture(gpu.clone());
         let dummy_prev_frame = RenderTarget::new(gpu.clone(), UVec2::ONE, None);
         let shadow_view = shadow_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let dummy_environment = create_dummy_environment_view(gpu.clone());
+        let (dummy_lights, dummy_clusters) = create_dummy_light_buffers(&gpu);
+        let dummy_reflection_probes = create_dummy_reflection_probes_view(gpu.clone());
         Self {
             bind_group: gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &layout,
@@ -326,6 +455,11 @@ impl ShadowAndUIGlobals {
                         binding: 7,
                         resource: wgpu::BindingResource::TextureView(&dummy_prev_frame.normals_quat_buffer_view),
                     },
+                    wgpu::BindGroupEntry { binding: 8, resource: wgpu::BindingResource::TextureView(&dummy_environment) },
+                    wgpu::BindGroupEntry { binding: 9, resource: wgpu::BindingResource::TextureView(&dummy_environment) },
+                    wgpu::BindGroupEntry { binding: 10, resource: dummy_lights.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 11, resource: dummy_clusters.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 12, resource: wgpu::BindingResource::TextureView(&dummy_reflection_probes) },
                 ],
                 label: Some("ShadowGlobals.bind_group"),
             }),
@@ -3,6 +3,15 @@
 @binding(0)
 var default_sampler: sampler;
 
+struct ReflectionProbe {
+    // (position, radius), with a radius of 0 for a box
+    position: vec4<f32>,
+    // (half the size of the box, index of the probe's faces)
+    box_extents: vec4<f32>,
+    // (intensity, unused, unused, unused)
+    params: vec4<f32>,
+};
+
 struct ForwardGlobalParams {
     projection_view: mat4x4<f32>,
     inv_projection_view: mat4x4<f32>,
@@ -22,6 +31,21 @@ struct ForwardGlobalParams {
     debug_metallic_roughness: f32,
     debug_normals: f32,
     debug_shading: f32,
+
+    // (intensity, specular mip levels, enabled, unused)
+    environment_params: vec4<f32>,
+    // (near, far, slice scale, slice bias) of the light clusters
+    cluster_params: vec4<f32>,
+    // Scales the luminance of the lit and emissive surfaces to the range of the display
+    exposure: f32,
+    // How wet the surfaces are, from 0 to 1
+    wetness: f32,
+    sky_intensity: f32,
+    // (velocity, unused) of the wind
+    wind: vec4<f32>,
+    reflection_probe_count: u32,
+    // The captured reflection probes, from the smallest to the largest volume
+    reflection_probes: array<ReflectionProbe, #MAX_REFLECTION_PROBES>,
 };
 
 struct ShadowCamera {
@@ -61,6 +85,27 @@ var solids_screen_depth: texture_depth_2d;
 @binding(7)
 var solids_screen_normal_quat: texture_2d<f32>;
 
+@group(#GLOBALS_BIND_GROUP)
+@binding(8)
+var environment_irradiance: texture_cube<f32>;
+
+@group(#GLOBALS_BIND_GROUP)
+@binding(9)
+var environment_specular: texture_cube<f32>;
+
+@group(#GLOBALS_BIND_GROUP)
+@binding(10)
+var<storage> scene_lights: array<Light>;
+
+@group(#GLOBALS_BIND_GROUP)
+@binding(11)
+var<storage> light_clusters: array<LightCluster>;
+
+// The faces of the probes, prefiltered for roughness in each mip like the specular environment map
+@group(#GLOBALS_BIND_GROUP)
+@binding(12)
+var reflection_probe_faces: texture_2d_array<f32>;
+
 fn inside(v: vec3<f32>) -> bool {
     return v.x > -1. && v.x < 1. && v.y > -1. && v.y < 1. && v.z > 0. && v.z < 1.;
 }
@@ -228,6 +273,198 @@ fn geometry_smith(normal: vec3<f32>, v: vec3<f32>, l: vec3<f32>, roughness: f32)
         * geometry_schlick_ggx(ndotl, k);
 }
 
+// Ambient is Z-up, while cubemaps are addressed Y-up
+fn environment_direction(dir: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(dir.x, dir.z, -dir.y);
+}
+
+fn environment_intensity() -> f32 {
+    return select(1.0, global_params.environment_params.x, global_params.environment_params.z > 0.0);
+}
+
+fn fresnel_roughness(cos_theta: f32, f0: vec3<f32>, roughness: f32) -> vec3<f32> {
+    return f0 + (max(vec3<f32>(1.0 - roughness), f0) - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Analytic approximation of the split sum environment BRDF, from "Physically Based Shading on Mobile" (Karis 2014)
+fn environment_brdf(f0: vec3<f32>, roughness: f32, ndotv: f32) -> vec3<f32> {
+    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
+    let c1 = vec4<f32>(1.0, 0.0425, 1.04, -0.04);
+    let r = roughness * c0 + c1;
+    let a004 = min(r.x * r.x, exp2(-9.28 * ndotv)) * r.x + r.y;
+    let ab = vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+    return f0 * ab.x + ab.y;
+}
+
+// Image-based lighting from the prefiltered environment map
+fn environment_lighting(albedo: vec3<f32>, metallic: f32, roughness: f32, normal: vec3<f32>, v: vec3<f32>, f0: vec3<f32>) -> vec3<f32> {
+    let ndotv = max(dot(normal, v), 0.0);
+    let ks = fresnel_roughness(ndotv, f0, roughness);
+    let kd = (vec3<f32>(1.0) - ks) * (1.0 - metallic);
+
+    let irradiance = textureSampleLevel(environment_irradiance, default_sampler, environment_direction(normal), 0.0).rgb;
+    let r = reflect(-v, normal);
+    let specular_lod = roughness * global_params.environment_params.y;
+    let prefiltered = textureSampleLevel(environment_specular, default_sampler, environment_direction(r), specular_lod).rgb;
+
+    return (kd * albedo * irradiance + prefiltered * environment_brdf(f0, roughness, ndotv)) * global_params.environment_params.x;
+}
+
+// The face of a cubemap a direction in cube space points at, and the uv of the direction on it, inverting `cube_texel_direction`
+fn cube_face_uv(dir: vec3<f32>) -> vec3<f32> {
+    let a = abs(dir);
+    var face = 0;
+    var uv: vec2<f32>;
+    if (a.x >= a.y && a.x >= a.z) {
+        face = select(1, 0, dir.x > 0.0);
+        uv = vec2<f32>(select(dir.z, -dir.z, dir.x > 0.0), -dir.y) / a.x;
+    } else if (a.y >= a.z) {
+        face = select(3, 2, dir.y > 0.0);
+        uv = vec2<f32>(dir.x, select(-dir.z, dir.z, dir.y > 0.0)) / a.y;
+    } else {
+        face = select(5, 4, dir.z > 0.0);
+        uv = vec2<f32>(select(-dir.x, dir.x, dir.z > 0.0), -dir.y) / a.z;
+    }
+    return vec3<f32>(uv * 0.5 + 0.5, f32(face));
+}
+
+// How much a probe covers a position: 1 well inside its volume, fading out to 0 at its border
+fn reflection_probe_weight(probe: ReflectionProbe, position: vec3<f32>) -> f32 {
+    let offset = position - probe.position.xyz;
+    if (probe.position.w > 0.0) {
+        return 1.0 - smoothstep(probe.position.w * (1.0 - REFLECTION_PROBE_FADE), probe.position.w, length(offset));
+    }
+    let extents = probe.box_extents.xyz;
+    let distance = extents - abs(offset);
+    return clamp(min(distance.x, min(distance.y, distance.z)) / (REFLECTION_PROBE_FADE * min(extents.x, min(extents.y, extents.z))), 0.0, 1.0);
+}
+
+// Parallax correction: the direction from the probe to where the reflection ray from `position` leaves the probe's volume,
+// so that the reflections of the surroundings line up with them wherever the surface is
+fn reflection_probe_direction(probe: ReflectionProbe, position: vec3<f32>, r: vec3<f32>) -> vec3<f32> {
+    let offset = position - probe.position.xyz;
+    var t: f32;
+    if (probe.position.w > 0.0) {
+        let b = dot(offset, r);
+        let c = dot(offset, offset) - probe.position.w * probe.position.w;
+        t = -b + sqrt(max(b * b - c, 0.0));
+    } else {
+        let inv_r = 1.0 / r;
+        let far_planes = max((probe.box_extents.xyz - offset) * inv_r, (-probe.box_extents.xyz - offset) * inv_r);
+        t = min(far_planes.x, min(far_planes.y, far_planes.z));
+    }
+    return offset + r * max(t, 0.0);
+}
+
+// The specular radiance the probes around `position` reflect along `r`, and how much they cover it; smaller probes take
+// precedence over the larger ones they're in
+fn reflection_probe_radiance(position: vec3<f32>, r: vec3<f32>, roughness: f32) -> vec4<f32> {
+    let size = f32(textureDimensions(reflection_probe_faces).x);
+    let lod = roughness * global_params.environment_params.y;
+    var radiance = vec3<f32>(0.0);
+    var remaining = 1.0;
+    for (var i = 0u; i < global_params.reflection_probe_count; i = i + 1u) {
+        let probe = global_params.reflection_probes[i];
+        let weight = reflection_probe_weight(probe, position) * remaining;
+        if (weight <= 0.0) {
+            continue;
+        }
+        let face = cube_face_uv(environment_direction(reflection_probe_direction(probe, position, r)));
+        // The faces aren't filtered with each other, so the uv is kept half a texel inside of the face
+        let half_texel = 0.5 / max(size / exp2(floor(lod)), 1.0);
+        let uv = clamp(face.xy, vec2<f32>(half_texel), vec2<f32>(1.0 - half_texel));
+        let layer = i32(probe.box_extents.w) * 6 + i32(face.z);
+        radiance = radiance + textureSampleLevel(reflection_probe_faces, default_sampler, uv, layer, lod).rgb * probe.params.x * weight;
+        remaining = remaining - weight;
+    }
+    return vec4<f32>(radiance, 1.0 - remaining);
+}
+
+// Replaces the specular reflections of the environment, if any, with the ones of the reflection probes around `position`
+fn reflection_probe_lighting(roughness: f32, normal: vec3<f32>, v: vec3<f32>, f0: vec3<f32>, position: vec3<f32>) -> vec3<f32> {
+    if (global_params.reflection_probe_count == 0u) {
+        return vec3<f32>(0.0);
+    }
+    let r = reflect(-v, normal);
+    let probe = reflection_probe_radiance(position, r, roughness);
+    if (probe.a <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+    var environment = vec3<f32>(0.0);
+    if (global_params.environment_params.z > 0.0) {
+        let lod = roughness * global_params.environment_params.y;
+        environment = textureSampleLevel(environment_specular, default_sampler, environment_direction(r), lod).rgb * global_params.environment_params.x * probe.a;
+    }
+    let ndotv = max(dot(normal, v), 0.0);
+    return (probe.rgb - environment) * environment_brdf(f0, roughness, ndotv);
+}
+
+fn fetch_light_shadow(shadow_index: i32, world_position: vec4<f32>) -> f32 {
+    let cam = shadow_cameras.cameras[shadow_index].viewproj * world_position;
+    let p = cam.xyz / cam.w;
+    if (inside(p)) {
+        return fetch_shadow_cascade(shadow_index, p);
+    }
+    return 1.;
+}
+
+// The index of the light cluster containing `world_position`, or -1 if it's outside of the clusters
+fn get_light_cluster(world_position: vec4<f32>) -> i32 {
+    if (global_params.cluster_params.z <= 0.0) {
+        return -1;
+    }
+    let clip = global_params.projection_view * world_position;
+    let ndc = clip.xy / clip.w;
+    let tile = vec2<i32>(floor((ndc * 0.5 + 0.5) * vec2<f32>(f32(#CLUSTER_TILES_X), f32(#CLUSTER_TILES_Y))));
+    let depth = dot(world_position.xyz - global_params.camera_position.xyz, global_params.camera_forward);
+    let slice = i32(floor(log(max(depth, global_params.cluster_params.x)) * global_params.cluster_params.z - global_params.cluster_params.w));
+    if (tile.x < 0 || tile.y < 0 || tile.x >= #CLUSTER_TILES_X || tile.y >= #CLUSTER_TILES_Y || slice < 0 || slice >= #CLUSTER_SLICES) {
+        return -1;
+    }
+    return (slice * #CLUSTER_TILES_Y + tile.y) * #CLUSTER_TILES_X + tile.x;
+}
+
+// Cook-torrance specular and lambertian diffuse reflection of the light coming from `l`
+fn light_brdf(albedo: vec3<f32>, metallic: f32, roughness: f32, normal: vec3<f32>, v: vec3<f32>, l: vec3<f32>, f0: vec3<f32>) -> vec3<f32> {
+    let h = normalize(v + l);
+    let f = fresnel(max(dot(h, v), 0.0), f0);
+    let ndf = distribution_ggx(normal, h, roughness);
+    let g = geometry_smith(normal, v, l, roughness);
+    let denom = max(4.0 * max(dot(normal, v), 0.0) * max(dot(normal, l), 0.0), 0.001);
+    let kd = (vec3<f32>(1.0) - f) * (1.0 - metallic);
+    return kd * albedo / PI + f * ndf * g / denom;
+}
+
+// The light from the point and spot lights of the cluster of `world_position`
+fn local_lighting(albedo: vec3<f32>, metallic: f32, roughness: f32, normal: vec3<f32>, v: vec3<f32>, f0: vec3<f32>, world_position: vec4<f32>) -> vec3<f32> {
+    var lum = vec3<f32>(0.0);
+    let cluster = get_light_cluster(world_position);
+    if (cluster < 0) {
+        return lum;
+    }
+    let count = light_clusters[cluster].count;
+    for (var i = 0u; i < count; i = i + 1u) {
+        let light = scene_lights[light_clusters[cluster].lights[i]];
+        let to_light = light.position - world_position.xyz;
+        let distance = length(to_light);
+        let l = to_light / max(distance, 0.0001);
+        let ndotl = max(dot(normal, l), 0.0);
+        if (ndotl <= 0.0) {
+            continue;
+        }
+
+        // Inverse square fall-off, windowed to reach zero at the range of the light
+        let window = clamp(1.0 - pow(distance / light.range, 4.0), 0.0, 1.0);
+        var attenuation = window * window / max(distance * distance, 0.0001);
+        attenuation = attenuation * smoothstep(light.spot_cos_outer, light.spot_cos_inner, dot(-l, light.direction));
+        if (light.shadow_index >= 0) {
+            attenuation = attenuation * fetch_light_shadow(light.shadow_index, world_position);
+        }
+        lum = lum + light_brdf(albedo, metallic, roughness, normal, v, l, f0) * light.color * ndotl * attenuation;
+    }
+    return lum;
+}
+
 fn shading(material: MaterialOutput, world_position: vec4<f32>) -> vec4<f32> {
     if (global_params.debug_shading > 0.0) {
       return vec4(material.base_color.rgb, material.opacity);
@@ -238,11 +475,14 @@ fn shading(material: MaterialOutput, world_position: vec4<f32>) -> vec4<f32> {
     let l = normalize(global_params.sun_direction.xyz);
     let h = normalize(v + l);
 
-    let albedo = material.base_color.rgb;
+    let normal = material.normal;
+
+    // Wet surfaces are darker and glossier, the more so the more they face the sky
+    let wet = global_params.wetness * clamp(normal.z * 0.5 + 0.5, 0.0, 1.0);
+    let albedo = material.base_color.rgb * (1.0 - 0.4 * wet);
 
     let metallic = material.metallic;
-    let roughness = material.roughness;
-    let normal = material.normal;
+    let roughness = mix(material.roughness, 0.1, wet);
 
     // Interpolate the normal incidence.
     //
@@ -301,11 +541,17 @@ fn shading(material: MaterialOutput, world_position: vec4<f32>) -> vec4<f32> {
 
     let direct = (lambert + specular) * radiance * ndotl * in_shadow;
 
-    let indirect = albedo * global_params.sun_ambient.rgb;
+    var indirect = albedo * global_params.sun_ambient.rgb;
+    if (global_params.environment_params.z > 0.0) {
+        indirect = environment_lighting(albedo, metallic, roughness, normal, v, f0);
+    }
+    indirect = indirect + reflection_probe_lighting(roughness, normal, v, f0, world_position.xyz);
+
+    let local = local_lighting(albedo, metallic, roughness, normal, v, f0, world_position);
 
-    let lum = direct + indirect;
+    let lum = direct + local + indirect;
 
-    var color = mix(material.base_color.rgb, lum, material.shading) + material.emissive_factor;
+    var color = mix(material.base_color.rgb, lum * global_params.exposure, material.shading) + material.emissive_factor * global_params.exposure;
 
     color = mix(color, vec3(metallic, roughness, 0.0), global_params.debug_metallic_roughness);
     color = mix(color, normal, global_params.debug_normals);
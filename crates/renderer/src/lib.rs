@@ -4,7 +4,7 @@ use ambient_core::{
     asset_cache,
     async_ecs::async_run,
     gpu_components,
-    gpu_ecs::{ComponentToGpuSystem, GpuComponentFormat, GpuWorldShaderModuleKey, GpuWorldSyncEvent},
+    gpu_ecs::{ComponentToGpuSystem, GpuComponentFormat, GpuWorldShaderModuleKey, GpuWorldSyncEvent, MappedComponentToGpuSystem},
     mesh, runtime,
     transform::get_world_rotation,
 };
@@ -16,26 +16,37 @@ use ambient_gpu::{
     shader_module::{BindGroupDesc, Shader, ShaderModule, ShaderModuleIdentifier},
     wgsl_utils::wgsl_interpolate,
 };
-use ambient_std::{asset_cache::*, asset_url::AbsAssetUrl, cb, include_file, Cb};
+use ambient_std::{asset_cache::*, asset_url::AbsAssetUrl, cb, download_asset::AssetError, include_file, Cb};
 use derive_more::*;
 use downcast_rs::{impl_downcast, DowncastSync};
-use glam::{uvec4, UVec2, UVec4, Vec3, Vec4};
+use glam::{uvec4, vec4, UVec2, UVec4, Vec3, Vec4};
 use serde::{Deserialize, Serialize};
 
+pub mod cameras;
 mod collect;
 mod culling;
+pub mod dynamic_textures;
+pub mod environment;
 mod globals;
+pub mod lights;
 pub mod lod;
 pub mod materials;
+pub mod msaa;
 mod outlines;
 mod overlay_renderer;
+mod picking;
+pub mod reflection_probes;
+mod render_graph;
 mod renderer;
 mod shaders;
 mod shadow_renderer;
 pub mod skinning;
 mod target;
+mod timing;
 mod transparent_renderer;
 mod tree_renderer;
+#[cfg(feature = "volumetric-fog")]
+mod volumetric_fog;
 use ambient_ecs::{query, Component};
 pub use collect::*;
 pub use culling::*;
@@ -44,10 +55,13 @@ use materials::pbr_material::PbrMaterialFromUrl;
 pub use materials::*;
 use ordered_float::OrderedFloat;
 pub use outlines::*;
+pub use picking::*;
+pub use render_graph::*;
 pub use renderer::*;
 pub use shaders::*;
 pub use shadow_renderer::*;
 pub use target::*;
+pub use timing::*;
 pub use transparent_renderer::*;
 pub use tree_renderer::*;
 
@@ -100,7 +114,7 @@ components!("rendering", {
     @[
         Debuggable, Networked, Store,
         Name["Light diffuse"],
-        Description["The diffuse light color of the `sun`."]
+        Description["The diffuse light color of the `sun`, or of a `point_light` or `spot_light`."]
     ]
     light_diffuse: Vec3,
     @[
@@ -127,25 +141,63 @@ components!("rendering", {
         Description["The density of the fog for this `sun`."]
     ]
     fog_density: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Sky intensity"],
+        Description["The brightness of the `sky` lit by this `sun`, which fades at night. Defaults to 20."]
+    ]
+    sky_intensity: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Volumetric fog density"],
+        Description["If above 0, the scene of this `sun` is filled with fog which scatters its light, with shafts of light through the gaps between the shadows. This is the density of the fog at height 0.\nOnly rendered with the renderer's `volumetric-fog` feature."]
+    ]
+    volumetric_fog_density: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Volumetric fog height fall-off"],
+        Description["How fast the `volumetric_fog_density` of this `sun` falls off with the height, per metre. Defaults to 0.1."]
+    ]
+    volumetric_fog_height_falloff: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Volumetric fog anisotropy"],
+        Description["How much of the light of this `sun` the volumetric fog scatters forward, from -1 (all back towards the sun) to 1 (all away from it), which brightens the fog around the sun. Defaults to 0.6."]
+    ]
+    volumetric_fog_anisotropy: f32,
     @[
         Debuggable, Networked, Store,
         Name["Transparency group"],
         Description["Controls when this transparent object will be rendered. Transparent objects are sorted by `(transparency_group, z-depth)`."]
     ]
     transparency_group: i32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Wind sway"],
+        Description["If attached, this entity's mesh sways in the `wind`, the more the higher above its origin, e.g. for trees and grass. 1 is a typical amount."]
+    ]
+    wind_sway: f32,
 });
 gpu_components! {
     color() => color: GpuComponentFormat::Vec4,
     primitives() => primitives: GpuComponentFormat::UVec4Array20,
+    // [sway, 0, 0, 0]
+    wind_sway() => wind_sway: GpuComponentFormat::Vec4,
 }
 pub fn init_all_components() {
     init_components();
     init_gpu_components();
+    cameras::init_components();
+    cameras::init_gpu_components();
     outlines::init_components();
     outlines::init_gpu_components();
     culling::init_gpu_components();
+    dynamic_textures::init_components();
+    environment::init_components();
+    lights::init_components();
     lod::init_components();
     lod::init_gpu_components();
+    reflection_probes::init_components();
     skinning::init_components();
     skinning::init_gpu_components();
 }
@@ -166,26 +218,39 @@ pub fn systems() -> SystemGroup {
                     let assets = world.resource(asset_cache()).clone();
                     let async_run = world.resource(async_run()).clone();
                     world.resource(runtime()).spawn(async move {
-                        match PbrMaterialFromUrl(url).get(&assets).await {
+                        let key = PbrMaterialFromUrl(url);
+                        match key.get(&assets).await {
                             Err(err) => {
                                 log::warn!("Failed to load pbr material from url: {:?}", err);
                             }
                             Ok(mat) => {
                                 async_run.run(move |world| {
-                                    world
+                                    let added = world
                                         .add_components(
                                             id,
                                             Entity::new()
                                                 .with(renderer_shader(), cb(pbr_material::get_pbr_shader))
-                                                .with(material(), mat.into()),
+                                                .with(material(), mat.clone().into()),
                                         )
-                                        .ok();
+                                        .is_ok();
+                                    // The material's textures are referenced along with it
+                                    if added {
+                                        let referrer = material_referrer(id);
+                                        assets.graph.lock().release(&referrer);
+                                        assets.reference(referrer, &key, &Ok::<_, AssetError>(mat));
+                                    }
                                 });
                             }
                         }
                     });
                 }
             }),
+            query(()).incl(pbr_material_from_url()).despawned().to_system(|q, world, qs, _| {
+                let assets = world.resource(asset_cache()).clone();
+                for (id, _) in q.collect_cloned(world, qs) {
+                    assets.graph.lock().release(&material_referrer(id));
+                }
+            }),
             query_mut((primitives(),), (renderer_shader().changed(), material().changed(), mesh().changed())).to_system(
                 |q, world, qs, _| {
                     for (_, (primitives,), (shader, material, mesh)) in q.iter(world, qs) {
@@ -206,6 +271,9 @@ pub fn systems() -> SystemGroup {
                 }
             }),
             Box::new(outlines::systems()),
+            Box::new(environment::systems()),
+            Box::new(cameras::systems()),
+            Box::new(dynamic_textures::systems()),
         ],
     )
 }
@@ -215,14 +283,26 @@ pub fn gpu_world_systems() -> SystemGroup<GpuWorldSyncEvent> {
         "renderer/gpu_world_update",
         vec![
             Box::new(outlines::gpu_world_systems()),
+            Box::new(cameras::gpu_world_systems()),
             Box::new(ComponentToGpuSystem::new(GpuComponentFormat::Vec4, color(), gpu_components::color())),
             Box::new(ComponentToGpuSystem::new(GpuComponentFormat::UVec4Array20, gpu_primitives(), gpu_components::primitives())),
+            Box::new(MappedComponentToGpuSystem::new(
+                GpuComponentFormat::Vec4,
+                wind_sway(),
+                gpu_components::wind_sway(),
+                Box::new(|_, _, sway| vec4(*sway, 0., 0., 0.)),
+            )),
             Box::new(lod::gpu_world_system()),
             Box::new(skinning::gpu_world_systems()),
         ],
     )
 }
 
+/// Entities reference the material loaded from their `pbr_material_from_url` separately, so that it can be released when the url changes
+fn material_referrer(id: EntityId) -> String {
+    format!("{id}/material")
+}
+
 pub fn get_active_sun(world: &World, scene: Component<()>) -> Option<EntityId> {
     query((scene, sun())).iter(world, None).max_by_key(|(_, (_, x))| OrderedFloat(**x)).map(|(id, _)| id)
 }
@@ -339,11 +419,10 @@ pub fn get_common_module(_: &AssetCache) -> ShaderModule {
 
 /// Contains scene globals and shadow maps
 pub fn get_globals_module(_assets: &AssetCache, shadow_cascades: u32) -> ShaderModule {
-    ShaderModule::new(
-        "Globals",
-        include_file!("globals.wgsl"),
-        vec![ShaderModuleIdentifier::constant("SHADOW_CASCADES", shadow_cascades), globals_layout().into()],
-    )
+    let mut idents = vec![ShaderModuleIdentifier::constant("SHADOW_CASCADES", shadow_cascades), globals_layout().into()];
+    idents.extend(lights::cluster_constants());
+    idents.extend(reflection_probes::reflection_probe_constants());
+    ShaderModule::new("Globals", format!("{}\n{}", include_file!("lights.wgsl"), include_file!("globals.wgsl")), idents)
 }
 
 pub fn get_forward_modules(assets: &AssetCache, shadow_cascades: u32) -> [ShaderModule; 7] {
@@ -409,6 +488,7 @@ pub enum FSMain {
     Forward,
     Shadow,
     Outline,
+    Picking,
 }
 
 pub struct RendererShader {
@@ -418,6 +498,8 @@ pub struct RendererShader {
     pub fs_shadow_main: String,
     pub fs_forward_main: String,
     pub fs_outline_main: String,
+    /// Outputs the id of the entity, as written by `get_picking_id` in `renderer_common.wgsl`, for the picking pass
+    pub fs_picking_main: String,
     pub transparent: bool,
     pub double_sided: bool,
     /// TODO: Apply to tree renderer too (only applies to transparent now)
@@ -433,6 +515,7 @@ impl RendererShader {
             FSMain::Forward => &self.fs_forward_main,
             FSMain::Shadow => &self.fs_shadow_main,
             FSMain::Outline => &self.fs_outline_main,
+            FSMain::Picking => &self.fs_picking_main,
         }
     }
 }
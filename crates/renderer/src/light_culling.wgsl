@@ -0,0 +1,67 @@
+struct ClusterParams {
+    inv_projection: mat4x4<f32>,
+    view: mat4x4<f32>,
+    near: f32,
+    far: f32,
+    light_count: u32,
+};
+
+@group(#LIGHT_CULLING_BIND_GROUP)
+@binding(0)
+var<uniform> params: ClusterParams;
+
+@group(#LIGHT_CULLING_BIND_GROUP)
+@binding(1)
+var<storage> lights: array<Light>;
+
+@group(#LIGHT_CULLING_BIND_GROUP)
+@binding(2)
+var<storage, read_write> clusters: array<LightCluster>;
+
+// The view space position at `depth` along the ray through `ndc`
+fn view_position(ndc: vec2<f32>, depth: f32) -> vec3<f32> {
+    // Reverse-z; 1 is the near plane
+    let p = params.inv_projection * vec4<f32>(ndc, 1.0, 1.0);
+    let near_position = p.xyz / p.w;
+    return near_position * (depth / near_position.z);
+}
+
+@compute
+@workgroup_size(4, 4, 4)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= #CLUSTER_TILES_Xu || id.y >= #CLUSTER_TILES_Yu || id.z >= #CLUSTER_SLICESu) {
+        return;
+    }
+    let index = (id.z * #CLUSTER_TILES_Yu + id.y) * #CLUSTER_TILES_Xu + id.x;
+    if (params.light_count == 0u) {
+        clusters[index].count = 0u;
+        return;
+    }
+
+    let tiles = vec2<f32>(f32(#CLUSTER_TILES_X), f32(#CLUSTER_TILES_Y));
+    let ndc_min = vec2<f32>(id.xy) / tiles * 2.0 - 1.0;
+    let ndc_max = vec2<f32>(id.xy + 1u) / tiles * 2.0 - 1.0;
+    let ratio = params.far / params.near;
+    let depth_min = params.near * pow(ratio, f32(id.z) / f32(#CLUSTER_SLICES));
+    let depth_max = params.near * pow(ratio, f32(id.z + 1u) / f32(#CLUSTER_SLICES));
+
+    let a = view_position(ndc_min, depth_min);
+    let b = view_position(ndc_max, depth_min);
+    let c = view_position(ndc_min, depth_max);
+    let d = view_position(ndc_max, depth_max);
+    let aabb_min = min(min(a, b), min(c, d));
+    let aabb_max = max(max(a, b), max(c, d));
+
+    var count = 0u;
+    for (var i = 0u; i < params.light_count && count < #MAX_CLUSTER_LIGHTSu; i = i + 1u) {
+        let light = lights[i];
+        let center = (params.view * vec4<f32>(light.position, 1.0)).xyz;
+        let closest = clamp(center, aabb_min, aabb_max);
+        let delta = closest - center;
+        if (dot(delta, delta) <= light.range * light.range) {
+            clusters[index].lights[count] = i;
+            count = count + 1u;
+        }
+    }
+    clusters[index].count = count;
+}
@@ -0,0 +1,306 @@
+use std::{f32::consts::PI, sync::Arc};
+
+use ambient_core::{
+    camera::{Camera, Projection},
+    transform::{get_world_position, get_world_rotation},
+};
+use ambient_ecs::{components, query, Component, Debuggable, Description, EntityId, Name, Networked, Store, World};
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    shader_module::{BindGroupDesc, ComputePipeline, Shader, ShaderModule, ShaderModuleIdentifier},
+};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    include_file,
+};
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use ordered_float::OrderedFloat;
+use wgpu::util::DeviceExt;
+
+use crate::light_diffuse;
+
+components!("rendering", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Point light"],
+        Description["Marks this entity as a point light, which emits light in all directions from its position.\nThe light is configured with `light_diffuse`, `light_intensity` and `light_range`."]
+    ]
+    point_light: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Spot light"],
+        Description["Marks this entity as a spot light, which emits a cone of light along its local Z axis.\nThe value is the inner and outer half-angle of the cone, in radians; the light fades out between the two.\nThe light is configured with `light_diffuse`, `light_intensity` and `light_range`."]
+    ]
+    spot_light: Vec2,
+    @[
+        Debuggable, Networked, Store,
+        Name["Light intensity"],
+        Description["The intensity of a light, which scales its `light_diffuse` color. Defaults to 1.\nIn physical light units, this is the luminous intensity in candela for a `point_light` or `spot_light`, and the illuminance in lux for the `sun`."]
+    ]
+    light_intensity: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Light range"],
+        Description["The distance at which the light of a `point_light` or `spot_light` has faded out completely. Defaults to 10."]
+    ]
+    light_range: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Luminous power"],
+        Description["The luminous power of a `point_light` or `spot_light`, in lumens. If attached, it is used instead of `light_intensity`.\nA 60 W incandescent bulb is around 800 lumens."]
+    ]
+    luminous_power: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Light shadows"],
+        Description["If attached to a `spot_light`, it will cast shadows.\nOnly a limited number of spot lights, the ones closest to the camera, get shadows at a time."]
+    ]
+    light_shadows: (),
+});
+
+/// The maximum number of point and spot lights in a scene; the rest are ignored
+pub const MAX_LIGHTS: u32 = 1024;
+/// The maximum number of spot lights which get shadow maps
+pub const MAX_SHADOWED_LIGHTS: u32 = 4;
+pub const CLUSTER_TILES_X: u32 = 16;
+pub const CLUSTER_TILES_Y: u32 = 9;
+pub const CLUSTER_SLICES: u32 = 24;
+/// The maximum number of lights affecting a single cluster
+pub const MAX_CLUSTER_LIGHTS: u32 = 64;
+/// Lights further away from the camera than this are not shaded
+const MAX_CLUSTER_DEPTH: f32 = 1000.;
+const CLUSTER_COUNT: u64 = (CLUSTER_TILES_X * CLUSTER_TILES_Y * CLUSTER_SLICES) as u64;
+const CLUSTER_SIZE: u64 = (1 + MAX_CLUSTER_LIGHTS as u64) * std::mem::size_of::<u32>() as u64;
+const LIGHT_CULLING_BIND_GROUP: &str = "LIGHT_CULLING_BIND_GROUP";
+
+const DEFAULT_RANGE: f32 = 10.;
+const SHADOW_NEAR: f32 = 0.05;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GpuLight {
+    pub position: Vec3,
+    pub range: f32,
+    pub color: Vec3,
+    /// The index of the shadow map of this light in the shadow cameras and texture, or -1
+    pub shadow_index: i32,
+    pub direction: Vec3,
+    pub spot_cos_outer: f32,
+    pub spot_cos_inner: f32,
+    pub _padding: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct ClusterParams {
+    inv_projection: Mat4,
+    view: Mat4,
+    near: f32,
+    far: f32,
+    light_count: u32,
+    _padding: u32,
+}
+
+/// Returns the spot lights which get shadow maps this frame, closest to `camera_position` first
+pub fn get_shadowed_lights(world: &World, scene: Component<()>, camera_position: Vec3) -> Vec<(EntityId, Camera)> {
+    let mut lights = query((scene, spot_light(), light_shadows()))
+        .iter(world, None)
+        .filter_map(|(id, (_, cone, _))| {
+            let position = get_world_position(world, id).ok()?;
+            let direction = get_world_rotation(world, id).ok()?.mul_vec3(Vec3::Z);
+            let range = world.get(id, light_range()).unwrap_or(DEFAULT_RANGE);
+            let up = if direction.abs().z > 0.999 { Vec3::X } else { Vec3::Z };
+            let camera = Camera {
+                view: Mat4::look_at_lh(position, position + direction, up),
+                projection: Projection::Perspective { fovy: (cone.y * 2.).min(3.), aspect_ratio: 1., near: SHADOW_NEAR, far: range },
+                shadows_far: range,
+            };
+            Some((id, camera, position.distance_squared(camera_position)))
+        })
+        .collect::<Vec<_>>();
+    lights.sort_by_key(|(id, _, distance)| (OrderedFloat(*distance), *id));
+    lights.into_iter().take(MAX_SHADOWED_LIGHTS as usize).map(|(id, camera, _)| (id, camera)).collect()
+}
+
+/// Collects the point and spot lights of the scene. The shadows of the `shadowed` lights are expected at `shadow_offset` and after
+fn get_lights(world: &World, scene: Component<()>, shadowed: &[EntityId], shadow_offset: u32) -> Vec<GpuLight> {
+    let light = |id: EntityId, direction: Vec3, cone: Option<Vec2>| {
+        let position = get_world_position(world, id).ok()?;
+        let intensity = match world.get(id, luminous_power()) {
+            // Point lights spread their power over the whole sphere, spot lights over their cone (ignoring its angle keeps
+            // the brightness independent of the cone, as in Filament)
+            Ok(lumens) => lumens / if cone.is_some() { PI } else { 4. * PI },
+            Err(_) => world.get(id, light_intensity()).unwrap_or(1.),
+        };
+        let color = world.get(id, light_diffuse()).unwrap_or(Vec3::ONE) * intensity;
+        // Point lights are spot lights whose cone covers everything
+        let (spot_cos_inner, spot_cos_outer) = cone.map_or((-1., -2.), |cone| (cone.x.cos(), cone.y.cos()));
+        Some(GpuLight {
+            position,
+            range: world.get(id, light_range()).unwrap_or(DEFAULT_RANGE),
+            color,
+            shadow_index: shadowed.iter().position(|x| *x == id).map_or(-1, |index| (shadow_offset + index as u32) as i32),
+            direction,
+            spot_cos_outer,
+            spot_cos_inner,
+            _padding: Default::default(),
+        })
+    };
+    query((scene, point_light()))
+        .iter(world, None)
+        .filter_map(|(id, _)| light(id, Vec3::Z, None))
+        .chain(query((scene, spot_light())).iter(world, None).filter_map(|(id, (_, cone))| {
+            let direction = get_world_rotation(world, id).ok()?.mul_vec3(Vec3::Z);
+            light(id, direction, Some(*cone))
+        }))
+        .take(MAX_LIGHTS as usize)
+        .collect()
+}
+
+/// Clustered forward shading: the view frustum is split into a grid of clusters, and a compute pass
+/// finds which lights affect each cluster, so that each fragment only has to shade the lights of its cluster
+pub struct ClusteredLights {
+    gpu: Arc<Gpu>,
+    scene: Component<()>,
+    pub(crate) lights_buffer: wgpu::Buffer,
+    pub(crate) clusters_buffer: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    pipeline: ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    /// `(near, far, slice scale, slice bias)` of the clusters, for the globals
+    pub(crate) cluster_params: Vec4,
+}
+
+impl ClusteredLights {
+    pub fn new(assets: &AssetCache, scene: Component<()>) -> Self {
+        let gpu = GpuKey.get(assets);
+        let layout = BindGroupDesc {
+            entries: vec![
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: LIGHT_CULLING_BIND_GROUP.into(),
+        };
+        let mut idents = cluster_constants();
+        idents.push(layout.into());
+        let module =
+            ShaderModule::new("LightCulling", format!("{}\n{}", include_file!("lights.wgsl"), include_file!("light_culling.wgsl")), idents);
+        let pipeline = Shader::from_modules(assets, "LightCulling", [&module]).to_compute_pipeline(&gpu, "main");
+
+        let lights_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ClusteredLights.lights"),
+            size: MAX_LIGHTS as u64 * std::mem::size_of::<GpuLight>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let clusters_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ClusteredLights.clusters"),
+            size: CLUSTER_COUNT * CLUSTER_SIZE,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let params_buffer = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ClusteredLights.params"),
+            contents: bytemuck::cast_slice(&[ClusterParams::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ClusteredLights.bind_group"),
+            layout: pipeline.shader().get_bind_group_layout_by_name(LIGHT_CULLING_BIND_GROUP).unwrap(),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: lights_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: clusters_buffer.as_entire_binding() },
+            ],
+        });
+
+        Self { gpu, scene, lights_buffer, clusters_buffer, params_buffer, pipeline, bind_group, cluster_params: Vec4::ZERO }
+    }
+
+    /// Uploads the lights of the scene and assigns them to the clusters of `camera`.
+    /// `shadow_offset` is the index of the first light shadow map, or `None` if shadows are disabled
+    #[profiling::function]
+    pub fn run(&mut self, world: &World, encoder: &mut wgpu::CommandEncoder, camera: &Camera, shadow_offset: Option<u32>) {
+        let shadowed = match shadow_offset {
+            Some(_) => get_shadowed_lights(world, self.scene, camera.position()).into_iter().map(|(id, _)| id).collect(),
+            None => Vec::new(),
+        };
+        let lights = get_lights(world, self.scene, &shadowed, shadow_offset.unwrap_or_default());
+        if !lights.is_empty() {
+            self.gpu.queue.write_buffer(&self.lights_buffer, 0, bytemuck::cast_slice(&lights));
+        }
+
+        // Clusters are sliced exponentially along the view depth, which requires a perspective projection
+        let (near, far) = match camera.projection {
+            Projection::Perspective { near, .. } | Projection::PerspectiveInfiniteReverse { near, .. } if near > 0. => {
+                (near, camera.projection.far().unwrap_or(MAX_CLUSTER_DEPTH).min(MAX_CLUSTER_DEPTH).max(near * 2.))
+            }
+            _ => (0., 0.),
+        };
+        let light_count = if far > 0. { lights.len() as u32 } else { 0 };
+        let params =
+            ClusterParams { inv_projection: camera.projection.matrix().inverse(), view: camera.view, near, far, light_count, _padding: 0 };
+        self.gpu.queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+        self.cluster_params = if light_count > 0 {
+            let scale = CLUSTER_SLICES as f32 / (far / near).ln();
+            Vec4::new(near, far, scale, near.ln() * scale)
+        } else {
+            Vec4::ZERO
+        };
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("Light culling") });
+        cpass.set_pipeline(self.pipeline.pipeline());
+        self.pipeline.bind(&mut cpass, LIGHT_CULLING_BIND_GROUP, &self.bind_group);
+        cpass.dispatch_workgroups(CLUSTER_TILES_X / 4, (CLUSTER_TILES_Y + 3) / 4, CLUSTER_SLICES / 4);
+    }
+}
+
+pub(crate) fn cluster_constants() -> Vec<ShaderModuleIdentifier> {
+    vec![
+        ShaderModuleIdentifier::constant("CLUSTER_TILES_X", CLUSTER_TILES_X),
+        ShaderModuleIdentifier::constant("CLUSTER_TILES_Y", CLUSTER_TILES_Y),
+        ShaderModuleIdentifier::constant("CLUSTER_SLICES", CLUSTER_SLICES),
+        ShaderModuleIdentifier::constant("MAX_CLUSTER_LIGHTS", MAX_CLUSTER_LIGHTS),
+    ]
+}
+
+/// Empty lights and clusters, bound when there is no light culling
+pub(crate) fn create_dummy_light_buffers(gpu: &Gpu) -> (wgpu::Buffer, wgpu::Buffer) {
+    let create = |label, size| {
+        gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+    };
+    (create("DummyLights.lights", std::mem::size_of::<GpuLight>() as u64), create("DummyLights.clusters", CLUSTER_SIZE))
+}
@@ -0,0 +1,14 @@
+struct Light {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    shadow_index: i32,
+    direction: vec3<f32>,
+    spot_cos_outer: f32,
+    spot_cos_inner: f32,
+};
+
+struct LightCluster {
+    count: u32,
+    lights: array<u32, #MAX_CLUSTER_LIGHTS>,
+};
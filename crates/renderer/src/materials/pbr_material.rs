@@ -78,6 +78,16 @@ impl SyncAssetKey<Arc<MaterialShader>> for PbrMaterialShaderKey {
                             },
                             count: None,
                         },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 5,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
                     ],
                     label: MATERIAL_BIND_GROUP.into(),
                 }
@@ -127,6 +137,8 @@ pub struct PbrMaterialConfig {
     /// r: Metallic
     /// g: Roughness
     pub metallic_roughness: Arc<TextureView>,
+    /// Multiplied with `params.emissive_factor`
+    pub emissive: Arc<TextureView>,
     pub transparent: Option<bool>,
     pub double_sided: Option<bool>,
     pub depth_write_enabled: Option<bool>,
@@ -159,6 +171,7 @@ impl PbrMaterial {
                     wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&config.base_color.handle) },
                     wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&config.normalmap.handle) },
                     wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::TextureView(&config.metallic_roughness.handle) },
+                    wgpu::BindGroupEntry { binding: 5, resource: wgpu::BindingResource::TextureView(&config.emissive.handle) },
                 ],
                 label: Some("PbrMaterial.bind_group"),
             }),
@@ -181,6 +194,7 @@ impl PbrMaterial {
                 base_color: texture,
                 normalmap: DefaultNormalMapViewKey.get(assets),
                 metallic_roughness: PixelTextureViewKey::white().get(assets),
+                emissive: PixelTextureViewKey::white().get(assets),
                 transparent: None,
                 double_sided: None,
                 depth_write_enabled: None,
@@ -194,6 +208,7 @@ impl PbrMaterial {
         self.config.base_color.texture.size_in_bytes
             + self.config.normalmap.texture.size_in_bytes
             + self.config.metallic_roughness.texture.size_in_bytes
+            + self.config.emissive.texture.size_in_bytes
     }
 }
 impl std::fmt::Debug for PbrMaterial {
@@ -257,6 +272,8 @@ pub struct PbrMaterialDesc {
     pub opacity: Option<AssetUrl>,
     pub normalmap: Option<AssetUrl>,
     pub metallic_roughness: Option<AssetUrl>,
+    #[serde(default)]
+    pub emissive: Option<AssetUrl>,
 
     pub base_color_factor: Option<Vec4>,
     pub emissive_factor: Option<Vec4>,
@@ -278,6 +295,7 @@ impl PbrMaterialDesc {
             opacity: if let Some(x) = &self.opacity { Some(x.resolve(base_url)?.into()) } else { None },
             normalmap: if let Some(x) = &self.normalmap { Some(x.resolve(base_url)?.into()) } else { None },
             metallic_roughness: if let Some(x) = &self.metallic_roughness { Some(x.resolve(base_url)?.into()) } else { None },
+            emissive: if let Some(x) = &self.emissive { Some(x.resolve(base_url)?.into()) } else { None },
 
             base_color_factor: self.base_color_factor,
             emissive_factor: self.emissive_factor,
@@ -297,6 +315,7 @@ impl PbrMaterialDesc {
             opacity: self.opacity.as_ref().map(|x| base_url.relative_path(x.path()).into()),
             normalmap: self.normalmap.as_ref().map(|x| base_url.relative_path(x.path()).into()),
             metallic_roughness: self.metallic_roughness.as_ref().map(|x| base_url.relative_path(x.path()).into()),
+            emissive: self.emissive.as_ref().map(|x| base_url.relative_path(x.path()).into()),
 
             base_color_factor: self.base_color_factor,
             emissive_factor: self.emissive_factor,
@@ -353,9 +372,21 @@ impl AsyncAssetKey<Result<Arc<PbrMaterial>, AssetError>> for PbrMaterialDesc {
             PixelTextureViewKey::white().get(&assets)
         };
 
+        let emissive = if let Some(emissive) = &self.emissive {
+            Arc::new(
+                TextureFromUrl { url: emissive.clone().unwrap_abs(), format: wgpu::TextureFormat::Rgba8UnormSrgb }
+                    .get(&assets)
+                    .await?
+                    .create_view(&Default::default()),
+            )
+        } else {
+            PixelTextureViewKey::white().get(&assets)
+        };
+
         let params = PbrMaterialParams {
             base_color_factor: self.base_color_factor.unwrap_or(Vec4::ONE),
-            emissive_factor: self.emissive_factor.unwrap_or(Vec4::ZERO),
+            // An emissive map without a factor emits as is
+            emissive_factor: self.emissive_factor.unwrap_or(if self.emissive.is_some() { Vec4::ONE } else { Vec4::ZERO }),
             alpha_cutoff: self.alpha_cutoff.unwrap_or(0.01),
             metallic: self.metallic,
             roughness: self.roughness,
@@ -372,6 +403,7 @@ impl AsyncAssetKey<Result<Arc<PbrMaterial>, AssetError>> for PbrMaterialDesc {
                 base_color: color_view.clone(),
                 normalmap,
                 metallic_roughness,
+                emissive,
                 transparent: self.transparent,
                 double_sided: self.double_sided,
                 depth_write_enabled: None,
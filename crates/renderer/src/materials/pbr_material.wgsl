@@ -26,6 +26,10 @@ var normal_texture: texture_2d<f32>;
 @binding(4)
 var metallic_roughness: texture_2d<f32>;
 
+@group(#MATERIAL_BIND_GROUP)
+@binding(5)
+var emissive_texture: texture_2d<f32>;
+
 fn get_material(in: MaterialInput) -> MaterialOutput {
     var out: MaterialOutput;
     let base_color_texture_sample = textureSample(base_color_texture, base_color_sampler, in.texcoord);
@@ -37,7 +41,7 @@ fn get_material(in: MaterialInput) -> MaterialOutput {
 
     out.alpha_cutoff = pbr_params.alpha_cutoff;
     out.base_color = color.rgb;
-    out.emissive_factor = pbr_params.emissive_factor.rgb;
+    out.emissive_factor = textureSample(emissive_texture, base_color_sampler, in.texcoord).rgb * pbr_params.emissive_factor.rgb;
     out.shading = 1.;
     let normal = textureSample(normal_texture, base_color_sampler, in.texcoord).xyz * 2. - 1.;
     out.normal = in.normal_matrix * normal;
@@ -0,0 +1,147 @@
+use std::{borrow::Cow, sync::Arc};
+
+use ambient_gpu::{
+    gpu::Gpu,
+    texture::{Texture, TextureView},
+};
+use glam::UVec2;
+use wgpu::{BindGroupLayoutDescriptor, BindGroupLayoutEntry, PipelineLayoutDescriptor, ShaderStages, TextureSampleType};
+
+use crate::{to_linear_format, RenderContext, RenderHook, RenderPassDesc, RenderResource, SubRenderer};
+
+/// Multisampled buffers which the forward pass renders into when MSAA is enabled.
+///
+/// The color and normals are resolved into the [crate::RenderTarget] by the forward pass itself, while the depth is
+/// resolved with a [DepthResolver], so that all later passes can run single-sampled.
+#[derive(Debug)]
+pub struct MsaaRenderTarget {
+    pub sample_count: u32,
+    pub size: wgpu::Extent3d,
+    pub color_buffer_view: TextureView,
+    pub normals_quat_buffer_view: TextureView,
+    pub depth_buffer_view: TextureView,
+}
+impl MsaaRenderTarget {
+    pub fn new(gpu: Arc<Gpu>, size: UVec2, sample_count: u32) -> Self {
+        let size = wgpu::Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 };
+        let create = |label, format, usage| {
+            Arc::new(Texture::new(
+                gpu.clone(),
+                &wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size,
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                },
+            ))
+            .create_view(&Default::default())
+        };
+        let format = gpu.swapchain_format();
+        Self {
+            sample_count,
+            size,
+            color_buffer_view: create("MsaaRenderTarget.color_buffer", format, wgpu::TextureUsages::RENDER_ATTACHMENT),
+            normals_quat_buffer_view: create(
+                "MsaaRenderTarget.normals_quat_buffer",
+                to_linear_format(format),
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            ),
+            depth_buffer_view: create(
+                "MsaaRenderTarget.depth_buffer",
+                wgpu::TextureFormat::Depth32Float,
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            ),
+        }
+    }
+}
+
+/// Resolves a multisampled depth buffer into a single-sampled one, by keeping the closest sample of each pixel.
+pub struct DepthResolver {
+    pipeline: wgpu::RenderPipeline,
+    gpu: Arc<Gpu>,
+}
+impl DepthResolver {
+    pub fn new(gpu: Arc<Gpu>) -> Self {
+        let shader = gpu.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("DepthResolver.shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("depth_resolve.wgsl"))),
+        });
+
+        let bind_group_layout = gpu.device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("DepthResolver.bind_group_layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: true,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = gpu.device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("DepthResolver.layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = gpu.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("DepthResolver.pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState { module: &shader, entry_point: "vs_main", buffers: &[] },
+            fragment: Some(wgpu::FragmentState { module: &shader, entry_point: "fs_main", targets: &[] }),
+            primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, ..Default::default() },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self { pipeline, gpu }
+    }
+    pub fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("msaa_resolve", RenderHook::PostForward).writes(RenderResource::Depth)
+    }
+    pub fn run(&self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::TextureView, target: &wgpu::TextureView) {
+        let bind_group = self.gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(source) }],
+            label: Some("DepthResolver.bind_group"),
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth resolve"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: target,
+                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Load, store: true }),
+                stencil_ops: None,
+            }),
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..4, 0..1);
+    }
+}
+impl std::fmt::Debug for DepthResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DepthResolver").finish()
+    }
+}
+impl SubRenderer for DepthResolver {
+    fn render(&mut self, context: &mut RenderContext) {
+        if let Some(msaa) = context.msaa {
+            self.run(context.encoder, &msaa.depth_buffer_view, context.target.depth());
+        }
+    }
+}
@@ -1,14 +1,19 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use ambient_core::{
     gpu_components,
     gpu_ecs::{ComponentToGpuSystem, GpuComponentFormat, GpuWorldSyncEvent},
     hierarchy::children,
 };
-use ambient_ecs::{components, query, ArchetypeFilter, Component, Debuggable, Description, Name, Networked, Store, SystemGroup, World};
+use ambient_ecs::{
+    components, query, ArchetypeFilter, Component, Debuggable, Description, Entity, EntityId, FnSystem, Name, Networked, Resource, Store,
+    SystemGroup, World,
+};
 use ambient_gpu::{
     gpu::{Gpu, GpuKey},
-    mesh_buffer::MeshBuffer,
     shader_module::{BindGroupDesc, GraphicsPipeline, GraphicsPipelineInfo, Shader},
     texture::Texture,
 };
@@ -16,11 +21,11 @@ use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKeyExt},
     include_file,
 };
-use glam::Vec4;
-use wgpu::{BindGroup, BindGroupLayoutEntry, BindingType, PrimitiveTopology, ShaderStages};
+use glam::{vec4, Vec4};
+use wgpu::{BindGroupLayoutEntry, BindingType, PrimitiveTopology, ShaderStages};
 
-use super::{FSMain, RendererCollectState, RendererResources, RendererTarget, ShaderModule, TreeRenderer, TreeRendererConfig};
-use crate::RendererConfig;
+use super::{FSMain, RendererCollectState, RendererResources, ShaderModule, TreeRenderer, TreeRendererConfig};
+use crate::{RenderContext, RenderHook, RenderPassDesc, RenderResource, RendererConfig, SubRenderer};
 
 components!("rendering", {
     @[
@@ -35,11 +40,92 @@ components!("rendering", {
         Description["If attached, this entity and all of its children will be rendered with an outline with the color specified.\nYou do not need to attach `outline` if you have attached `outline_recursive`."]
     ]
     outline_recursive: Vec4,
+    @[
+        Networked, Store, Debuggable,
+        Name["Outline color"],
+        Description["If attached, this entity and all of its descendants will be rendered with an outline with the color specified, e.g. to highlight enemies.\nDescendants which have an outline color or group of their own use theirs."]
+    ]
+    outline_color: Vec4,
+    @[
+        Networked, Store, Debuggable,
+        Name["Outline group"],
+        Description["If attached, this entity and all of its descendants will be rendered with an outline with the color of this selection group, as set by `outline_group_colors`.\n`outline_color` takes precedence over this."]
+    ]
+    outline_group: u32,
+    @[
+        Networked, Resource, Debuggable,
+        Name["Outline group colors"],
+        Description["The outline color of each selection group of `outline_group`. Groups past the end of the list wrap around."]
+    ]
+    outline_group_colors: Vec<Vec4>,
+    /// Marks the entities whose `outline` was set from the hierarchy they're in, so that it's removed when they leave it
+    @[Debuggable]
+    outline_from_hierarchy: (),
 });
 gpu_components! {
     outline() => outline: GpuComponentFormat::Vec4,
 }
 
+/// The outline colors of the selection groups when `outline_group_colors` hasn't been set
+const DEFAULT_OUTLINE_GROUP_COLORS: [Vec4; 4] =
+    [vec4(1., 1., 1., 1.), vec4(1., 0.6, 0., 1.), vec4(0.2, 0.6, 1., 1.), vec4(1., 0.2, 0.2, 1.)];
+
+/// The outline color of a selection group of [outline_group]
+pub fn outline_group_color(world: &World, group: u32) -> Vec4 {
+    match world.resource_opt(outline_group_colors()) {
+        Some(colors) if !colors.is_empty() => colors[group as usize % colors.len()],
+        _ => DEFAULT_OUTLINE_GROUP_COLORS[group as usize % DEFAULT_OUTLINE_GROUP_COLORS.len()],
+    }
+}
+
+/// The outline color `id` gives to its hierarchy, if it's the root of an outlined hierarchy
+fn hierarchy_outline_color(world: &World, id: EntityId) -> Option<Vec4> {
+    world
+        .get(id, outline_color())
+        .or_else(|_| world.get(id, outline_recursive()))
+        .ok()
+        .or_else(|| world.get(id, outline_group()).ok().map(|group| outline_group_color(world, group)))
+}
+
+/// Sets the `outline` of every entity in an outlined hierarchy to the color of its nearest outlined ancestor (or itself),
+/// and removes it from the entities which have left one. Entities with an `outline` of their own are left as they are
+fn update_hierarchy_outlines(world: &mut World) {
+    let roots = query(outline_color())
+        .iter(world, None)
+        .map(|(id, _)| id)
+        .chain(query(outline_recursive()).iter(world, None).map(|(id, _)| id))
+        .chain(query(outline_group()).iter(world, None).map(|(id, _)| id))
+        .collect::<HashSet<_>>();
+
+    let mut outlines = HashMap::new();
+    for &root in &roots {
+        let Some(color) = hierarchy_outline_color(world, root) else { continue };
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            outlines.insert(id, color);
+            if let Ok(childs) = world.get_ref(id, children()) {
+                // Nested roots outline their own hierarchy
+                stack.extend(childs.iter().filter(|child| !roots.contains(*child)));
+            }
+        }
+    }
+
+    for (id, _) in query(outline_from_hierarchy()).collect_cloned(world, None) {
+        if !outlines.contains_key(&id) {
+            world.remove_components(id, vec![outline().desc(), outline_from_hierarchy().desc()]).ok();
+        }
+    }
+    for (id, color) in outlines {
+        if !world.exists(id) || (world.has_component(id, outline()) && !world.has_component(id, outline_from_hierarchy())) {
+            continue;
+        }
+        // Only written when it changes, as it's synced to the gpu
+        if world.get(id, outline()).ok() != Some(color) {
+            world.add_components(id, Entity::new().with(outline(), color).with(outline_from_hierarchy(), ())).ok();
+        }
+    }
+}
+
 pub struct OutlinesConfig {
     pub scene: Component<()>,
     pub renderer_resources: RendererResources,
@@ -109,6 +195,7 @@ impl Outlines {
                 depth_stencil: false,
                 cull_mode: Some(wgpu::Face::Back),
                 depth_bias: Default::default(),
+                sample_count: 1,
             }),
             _config: config,
             gpu,
@@ -132,15 +219,24 @@ impl Outlines {
         ))
     }
 
-    pub fn render(
-        &mut self,
-        world: &mut World,
-        encoder: &mut wgpu::CommandEncoder,
-        post_submit: &mut Vec<Box<dyn FnOnce() + Send + Send>>,
-        target: &RendererTarget,
-        binds: &[(&str, &BindGroup)],
-        mesh_buffer: &MeshBuffer,
-    ) {
+    pub fn dump(&self, f: &mut dyn std::io::Write) {
+        self.renderer.dump(f);
+    }
+    pub fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("outlines", RenderHook::PostTransparent).writes(RenderResource::Color)
+    }
+}
+impl std::fmt::Debug for Outlines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outlines").finish()
+    }
+}
+impl SubRenderer for Outlines {
+    fn render(&mut self, context: &mut RenderContext) {
+        let Some(scene) = context.scene else { return };
+        let binds = scene.entity_binds();
+        let (world, encoder, post_submit, target, mesh_buffer) =
+            (&mut *context.world, &mut *context.encoder, &mut *context.post_submit, &context.target, scene.mesh_buffer);
         let bind_group_layout = self.pipeline.pipeline().get_bind_group_layout(0);
 
         if self.outlines.size != target.size() {
@@ -170,7 +266,7 @@ impl Outlines {
             //     self.pipeline.bind(&mut render_pass, name, *group);
             // }
 
-            self.renderer.render(&mut render_pass, &self.collect_state, binds);
+            self.renderer.render(&mut render_pass, &self.collect_state, &binds);
             {
                 profiling::scope!("Drop render pass");
                 drop(render_pass);
@@ -197,41 +293,10 @@ impl Outlines {
         rpass.set_bind_group(0, &bind_group, &[]);
         rpass.draw(0..4, 0..1);
     }
-    pub fn dump(&self, f: &mut dyn std::io::Write) {
-        self.renderer.dump(f);
-    }
 }
 
 pub fn systems() -> SystemGroup {
-    SystemGroup::new(
-        "outlines",
-        vec![
-            query((outline_recursive().changed(),)).to_system(|q, world, qs, _| {
-                for (id, (val,)) in q.collect_cloned(world, qs) {
-                    world.add_component(id, outline(), val).ok();
-                }
-            }),
-            query((outline_recursive(),)).despawned().to_system(|q, world, qs, _| {
-                for (id, _) in q.collect_cloned(world, qs) {
-                    world.remove_component(id, outline()).ok();
-                }
-            }),
-            query((outline_recursive(), children().changed())).to_system(|q, world, qs, _| {
-                for (_, (val, childs)) in q.collect_cloned(world, qs) {
-                    for c in childs {
-                        world.add_component(c, outline_recursive(), val).ok();
-                    }
-                }
-            }),
-            query((outline_recursive(), children())).despawned().to_system(|q, world, qs, _| {
-                for (_, (_, childs)) in q.collect_cloned(world, qs) {
-                    for c in childs {
-                        world.remove_component(c, outline_recursive()).ok();
-                    }
-                }
-            }),
-        ],
-    )
+    SystemGroup::new("outlines", vec![Box::new(FnSystem::new(|world, _| update_hierarchy_outlines(world)))])
 }
 
 pub fn gpu_world_systems() -> SystemGroup<GpuWorldSyncEvent> {
@@ -0,0 +1,216 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use ambient_core::window::{cursor_position, window_physical_size};
+use ambient_ecs::{ArchetypeFilter, Component, World};
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    texture::Texture,
+};
+use ambient_input::picking::{gpu_picker_intersecting, PickerIntersection};
+use ambient_std::asset_cache::{AssetCache, SyncAssetKeyExt};
+use glam::{UVec4, Vec2};
+use parking_lot::Mutex;
+
+use super::{FSMain, RendererCollectState, RendererResources, TreeRenderer, TreeRendererConfig};
+use crate::{RenderContext, RenderHook, RenderPassDesc, RendererConfig, SubRenderer};
+
+const ID_SIZE: u64 = std::mem::size_of::<UVec4>() as u64;
+
+pub struct GpuPickingConfig {
+    pub scene: Component<()>,
+    pub renderer_resources: RendererResources,
+}
+
+/// Finds the entity under the cursor by rendering the ids of the entities of the scene, and reading back the pixel
+/// under the cursor.
+///
+/// Unlike ray casts against bounding boxes, this is pixel-accurate: skinned meshes are picked in their current pose,
+/// decals where they're projected, and alpha-tested materials (e.g. foliage) only where they're opaque. Only the pixel
+/// under the cursor is shaded, and it's read back asynchronously, so the result lags a frame or two behind.
+pub struct GpuPicking {
+    ids: Arc<Texture>,
+    depth: Arc<Texture>,
+    readback: Arc<wgpu::Buffer>,
+    readback_pending: Arc<AtomicBool>,
+    /// The id under the cursor, as written by `get_picking_id`, once it's been read back
+    picked: Arc<Mutex<Option<UVec4>>>,
+    renderer: TreeRenderer,
+    collect_state: RendererCollectState,
+    gpu: Arc<Gpu>,
+}
+impl GpuPicking {
+    pub fn new(assets: &AssetCache, config: GpuPickingConfig, renderer_config: RendererConfig) -> Self {
+        let gpu = GpuKey.get(assets);
+        let size = wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 };
+        Self {
+            ids: Self::create_texture(gpu.clone(), size, Self::FORMAT),
+            depth: Self::create_texture(gpu.clone(), size, wgpu::TextureFormat::Depth32Float),
+            readback: Arc::new(gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("GpuPicking.readback"),
+                size: ID_SIZE,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })),
+            readback_pending: Default::default(),
+            picked: Default::default(),
+            renderer: TreeRenderer::new(TreeRendererConfig {
+                gpu: gpu.clone(),
+                assets: assets.clone(),
+                renderer_config,
+                targets: vec![Some(wgpu::ColorTargetState { format: Self::FORMAT, blend: None, write_mask: wgpu::ColorWrites::all() })],
+                filter: ArchetypeFilter::new().incl(config.scene),
+                renderer_resources: config.renderer_resources,
+                fs_main: FSMain::Picking,
+                opaque_only: false,
+                depth_stencil: true,
+                cull_mode: Some(wgpu::Face::Back),
+                depth_bias: Default::default(),
+                sample_count: 1,
+            }),
+            collect_state: RendererCollectState::new(assets),
+            gpu,
+        }
+    }
+
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Uint;
+
+    fn create_texture(gpu: Arc<Gpu>, size: wgpu::Extent3d, format: wgpu::TextureFormat) -> Arc<Texture> {
+        Arc::new(Texture::new(
+            gpu,
+            &wgpu::TextureDescriptor {
+                label: Some("GpuPicking"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            },
+        ))
+    }
+
+    pub fn dump(&self, f: &mut dyn std::io::Write) {
+        self.renderer.dump(f);
+    }
+    pub fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("picking", RenderHook::PostOutlines)
+    }
+}
+impl std::fmt::Debug for GpuPicking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuPicking").finish()
+    }
+}
+impl SubRenderer for GpuPicking {
+    /// Updates [gpu_picker_intersecting] with the most recently read back pick, and picks the entity under the cursor
+    fn render(&mut self, context: &mut RenderContext) {
+        let Some(scene) = context.scene else { return };
+        let binds = scene.entity_binds();
+        let (world, encoder, post_submit, mesh_buffer) =
+            (&mut *context.world, &mut *context.encoder, &mut *context.post_submit, scene.mesh_buffer);
+        let size = context.target.size();
+        // The cursor is in pixels of the window, which the (possibly scaled) target covers
+        let cursor = world.resource_opt(cursor_position()).zip(world.resource_opt(window_physical_size())).and_then(|(cursor, window)| {
+            let cursor = *cursor / window.as_vec2().max(Vec2::ONE) * Vec2::new(size.width as f32, size.height as f32);
+            (cursor.min_element() >= 0.).then(|| cursor.as_uvec2())
+        });
+
+        if let Some(id) = self.picked.lock().take() {
+            let intersection = resolve_id(world, id);
+            world.add_resource(gpu_picker_intersecting(), intersection);
+        }
+
+        let Some(cursor) = cursor.filter(|cursor| cursor.x < size.width && cursor.y < size.height) else {
+            world.add_resource(gpu_picker_intersecting(), None);
+            return;
+        };
+        if self.readback_pending.load(Ordering::SeqCst) {
+            return;
+        }
+        if self.ids.size != size {
+            self.ids = Self::create_texture(self.gpu.clone(), size, Self::FORMAT);
+            self.depth = Self::create_texture(self.gpu.clone(), size, wgpu::TextureFormat::Depth32Float);
+        }
+        let ids = self.ids.create_view(&Default::default());
+        let depth = self.depth.create_view(&Default::default());
+
+        self.collect_state.set_camera(0);
+        self.renderer.update(world);
+        self.renderer.run_collect(encoder, post_submit, binds[0].1, binds[1].1, &mut self.collect_state);
+
+        {
+            profiling::scope!("Picking");
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Picking"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &ids,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: true },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth,
+                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(0.0), store: true }),
+                    stencil_ops: None,
+                }),
+            });
+            render_pass.set_index_buffer(mesh_buffer.index_buffer.buffer().slice(..), wgpu::IndexFormat::Uint32);
+            // Only the pixel under the cursor is shaded
+            render_pass.set_scissor_rect(cursor.x, cursor.y, 1, 1);
+
+            self.renderer.render(&mut render_pass, &self.collect_state, &binds);
+            {
+                profiling::scope!("Drop render pass");
+                drop(render_pass);
+            }
+        }
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.ids.handle,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: cursor.x, y: cursor.y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: None, rows_per_image: None },
+            },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+
+        self.readback_pending.store(true, Ordering::SeqCst);
+        let readback = self.readback.clone();
+        let readback_pending = self.readback_pending.clone();
+        let picked = self.picked.clone();
+        post_submit.push(Box::new(move || {
+            let buffer = readback.clone();
+            readback.slice(..).map_async(wgpu::MapMode::Read, move |res| {
+                if res.is_ok() {
+                    {
+                        let data = buffer.slice(..).get_mapped_range();
+                        *picked.lock() = Some(*bytemuck::from_bytes::<UVec4>(&data));
+                    }
+                    buffer.unmap();
+                }
+                readback_pending.store(false, Ordering::SeqCst);
+            });
+        }));
+    }
+}
+
+/// The entity of an id written by `get_picking_id`, which is `(archetype, index, hit, distance bits)`. The entity may have
+/// moved in the world since it was rendered, in which case this finds whichever entity is at its old location
+fn resolve_id(world: &World, id: UVec4) -> Option<PickerIntersection> {
+    if id.z == 0 {
+        return None;
+    }
+    let archetype = world.archetypes().get(id.x as usize)?;
+    let index = id.y as usize;
+    if index >= archetype.entity_count() {
+        return None;
+    }
+    Some(PickerIntersection { entity: archetype.get_entity_id_from_index(index), distance: f32::from_bits(id.w) })
+}
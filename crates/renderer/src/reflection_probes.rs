@@ -0,0 +1,328 @@
+//! Reflection probes capture their surroundings into cubemaps, which replace the specular reflections of the environment map
+//! for the surfaces inside their volume, e.g. so that a room reflects its walls rather than the sky.
+//!
+//! A probe is captured when it's spawned and whenever its [reflection_probe_version] changes, by rendering the scene from
+//! its position with a renderer of its own, one cube face per frame so that captures don't stall. The faces are prefiltered
+//! for roughness like the environment map, into the layers of an array texture shared by all the probes, and the reflections
+//! are parallax-corrected against the volume of the probe.
+use std::{collections::HashMap, f32::consts::FRAC_PI_2, sync::Arc};
+
+use ambient_core::{
+    camera::{exposure, far, fovy, get_active_camera, near, projection_view},
+    player::local_user_id,
+    transform::{get_world_position, inv_local_to_world, local_to_world},
+};
+use ambient_ecs::{components, query, Debuggable, Description, Entity, EntityId, Name, Networked, Store, World};
+use ambient_gpu::{
+    blit::{Blitter, BlitterKey},
+    gpu::{Gpu, GpuKey},
+    shader_module::ShaderModuleIdentifier,
+    texture::{Texture, TextureView},
+};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    color::Color,
+};
+use glam::{uvec2, vec3, Mat4, Vec3, Vec4};
+
+use crate::{
+    environment::{prefilter_specular, SPECULAR_MIP_LEVELS},
+    globals::exposure_from_ev100,
+    RenderContext, RenderHook, RenderPassDesc, RenderTarget, Renderer, RendererConfig, RendererTarget, SubRenderer,
+};
+
+components!("rendering", {
+    @[
+        Debuggable, Networked, Store,
+        Name["Reflection probe"],
+        Description["Captures the surroundings of this entity into a cubemap, which the surfaces inside the probe's volume reflect instead of the environment.\nThe volume is a box of `reflection_probe_box_extents`, or else a sphere of `reflection_probe_radius`. Smaller probes take precedence over the larger ones they're in."]
+    ]
+    reflection_probe: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Reflection probe box extents"],
+        Description["Makes the volume of this `reflection_probe` an axis-aligned box around it, with half of its size along each axis."]
+    ]
+    reflection_probe_box_extents: Vec3,
+    @[
+        Debuggable, Networked, Store,
+        Name["Reflection probe radius"],
+        Description["The radius of the sphere which is the volume of this `reflection_probe`, unless it has `reflection_probe_box_extents`. Defaults to 10."]
+    ]
+    reflection_probe_radius: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Reflection probe version"],
+        Description["Change this to capture this `reflection_probe` again, e.g. once the level has loaded or after its surroundings have changed. Probes are otherwise only captured when they're spawned."]
+    ]
+    reflection_probe_version: u32,
+});
+
+/// How many probes can be captured at a time; the other probes are captured once some of these are despawned
+pub const MAX_REFLECTION_PROBES: usize = 8;
+/// The size of the faces of the captured cubemaps
+const FACE_SIZE: u32 = 128;
+/// The probes fade out over this fraction of their size, at the border of their volume
+const FADE: f32 = 0.2;
+const DEFAULT_RADIUS: f32 = 10.;
+const CAPTURE_NEAR: f32 = 0.05;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// The world space orientation of the camera capturing each face, as (right, up, forward), so that the pixels of the
+/// captured image line up with the texels of the face
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (vec3(0., 1., 0.), vec3(0., 0., 1.), vec3(1., 0., 0.)),
+    (vec3(0., -1., 0.), vec3(0., 0., 1.), vec3(-1., 0., 0.)),
+    (vec3(1., 0., 0.), vec3(0., 1., 0.), vec3(0., 0., 1.)),
+    (vec3(1., 0., 0.), vec3(0., -1., 0.), vec3(0., 0., -1.)),
+    (vec3(1., 0., 0.), vec3(0., 0., 1.), vec3(0., -1., 0.)),
+    (vec3(-1., 0., 0.), vec3(0., 0., 1.), vec3(0., 1., 0.)),
+];
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct GpuReflectionProbe {
+    /// (position, radius), with a radius of 0 for a box
+    position: Vec4,
+    /// (half the size of the box, index of the probe's faces)
+    box_extents: Vec4,
+    /// (intensity, unused, unused, unused)
+    params: Vec4,
+}
+
+pub(crate) fn reflection_probe_constants() -> Vec<ShaderModuleIdentifier> {
+    vec![
+        ShaderModuleIdentifier::constant("MAX_REFLECTION_PROBES", MAX_REFLECTION_PROBES as u32),
+        ShaderModuleIdentifier::constant("REFLECTION_PROBE_FADE", FADE),
+    ]
+}
+
+/// An empty array of faces, bound when there are no reflection probes
+pub(crate) fn create_dummy_reflection_probes_view(gpu: Arc<Gpu>) -> TextureView {
+    Arc::new(Texture::new(
+        gpu,
+        &wgpu::TextureDescriptor {
+            label: Some("ReflectionProbes.dummy"),
+            size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        },
+    ))
+    .create_view(&wgpu::TextureViewDescriptor { dimension: Some(wgpu::TextureViewDimension::D2Array), ..Default::default() })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CapturedProbe {
+    slot: u32,
+    version: u32,
+    /// Converts the captured colors, which were exposed by the camera at the time, back to luminance
+    intensity: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Capture {
+    id: EntityId,
+    slot: u32,
+    version: u32,
+    position: Vec3,
+    ev100: Option<f32>,
+    face: usize,
+}
+
+/// Captures the reflection probes of a scene, for the renderer of its active camera
+pub(crate) struct ReflectionProbes {
+    assets: AssetCache,
+    config: RendererConfig,
+    /// Created when the first probe is captured
+    renderer: Option<Box<Renderer>>,
+    camera: Option<EntityId>,
+    target: RenderTarget,
+    /// The faces being captured, before they're prefiltered
+    radiance: Arc<Texture>,
+    blit: Arc<Blitter>,
+    /// The prefiltered faces of all of the probes, six layers per probe
+    faces: Arc<Texture>,
+    pub(crate) faces_view: TextureView,
+    probes: HashMap<EntityId, CapturedProbe>,
+    capture: Option<Capture>,
+    warned_full: bool,
+}
+impl ReflectionProbes {
+    pub fn new(assets: &AssetCache, config: &RendererConfig) -> Self {
+        let gpu = GpuKey.get(assets);
+        let texture = |label, mip_level_count, layers, usage| {
+            Arc::new(Texture::new(
+                gpu.clone(),
+                &wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d { width: FACE_SIZE, height: FACE_SIZE, depth_or_array_layers: layers },
+                    mip_level_count,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: FORMAT,
+                    usage,
+                },
+            ))
+        };
+        let radiance =
+            texture("ReflectionProbes.radiance", 1, 6, wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING);
+        let faces = texture(
+            "ReflectionProbes.faces",
+            SPECULAR_MIP_LEVELS,
+            MAX_REFLECTION_PROBES as u32 * 6,
+            wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        Self {
+            assets: assets.clone(),
+            // The captures are rendered without the probes, and without anything which only makes sense on screen
+            config: RendererConfig {
+                msaa_samples: 1,
+                resolution_scale: 1.,
+                gpu_picking: false,
+                reflection_probes: false,
+                ..config.clone()
+            },
+            renderer: None,
+            camera: None,
+            target: RenderTarget::new(gpu.clone(), uvec2(FACE_SIZE, FACE_SIZE), None),
+            radiance,
+            blit: BlitterKey { format: FORMAT.into(), linear: true }.get(assets),
+            faces_view: faces
+                .create_view(&wgpu::TextureViewDescriptor { dimension: Some(wgpu::TextureViewDimension::D2Array), ..Default::default() }),
+            faces,
+            probes: HashMap::new(),
+            capture: None,
+            warned_full: false,
+        }
+    }
+
+    /// The probes are captured with a renderer of their own, so this runs before the mesh buffer is locked
+    pub fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("reflection_probes", RenderHook::PreRender)
+    }
+
+    /// Captures the next face of the probe being captured, if any
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        encoder: &mut wgpu::CommandEncoder,
+        post_submit: &mut Vec<Box<dyn FnOnce() + Send + Send>>,
+    ) {
+        let scene = self.config.scene;
+        let current = query(reflection_probe())
+            .incl(scene)
+            .iter(world, None)
+            .map(|(id, _)| (id, world.get(id, reflection_probe_version()).unwrap_or_default()))
+            .collect::<HashMap<_, _>>();
+        self.probes.retain(|id, _| current.contains_key(id));
+        if self.capture.map_or(false, |capture| !current.contains_key(&capture.id)) {
+            self.capture = None;
+        }
+
+        if self.capture.is_none() {
+            let outdated = current.iter().find(|(id, version)| self.probes.get(*id).map_or(true, |probe| probe.version != **version));
+            if let Some((&id, &version)) = outdated {
+                let slot = match self.probes.get(&id) {
+                    Some(probe) => Some(probe.slot),
+                    None => (0..MAX_REFLECTION_PROBES as u32).find(|slot| self.probes.values().all(|probe| probe.slot != *slot)),
+                };
+                match slot {
+                    Some(slot) => {
+                        let ev100 = get_active_camera(world, scene, world.resource_opt(local_user_id()))
+                            .and_then(|camera| world.get(camera, exposure()).ok());
+                        let position = get_world_position(world, id).unwrap_or_default();
+                        self.capture = Some(Capture { id, slot, version, position, ev100, face: 0 });
+                    }
+                    None if !self.warned_full => {
+                        log::warn!("Only {MAX_REFLECTION_PROBES} reflection probes can be captured at a time");
+                        self.warned_full = true;
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        if let Some(capture) = &mut self.capture {
+            let renderer = self.renderer.get_or_insert_with(|| Box::new(Renderer::new(world, self.assets.clone(), self.config.clone())));
+            let camera = *self.camera.get_or_insert_with(|| Entity::new().spawn(world));
+            renderer.camera = Some(camera);
+
+            let (right, up, forward) = FACES[capture.face];
+            let transform = Mat4::from_cols(right.extend(0.), up.extend(0.), forward.extend(0.), capture.position.extend(1.));
+            let view = transform.inverse();
+            let mut data = Entity::new()
+                .with(local_to_world(), transform)
+                .with(inv_local_to_world(), view)
+                .with(projection_view(), Mat4::perspective_infinite_reverse_lh(FRAC_PI_2, 1., CAPTURE_NEAR) * view)
+                .with(fovy(), FRAC_PI_2)
+                .with(ambient_core::camera::aspect_ratio(), 1.)
+                .with(near(), CAPTURE_NEAR)
+                .with(far(), 1e3);
+            if let Some(ev100) = capture.ev100 {
+                data.set(exposure(), ev100);
+            }
+            world.add_components(camera, data).ok();
+
+            renderer.render(world, encoder, post_submit, RendererTarget::Target(&self.target), Some(Color::rgba(0., 0., 0., 1.)));
+            let face = self.radiance.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: capture.face as u32,
+                array_layer_count: std::num::NonZeroU32::new(1),
+                ..Default::default()
+            });
+            self.blit.run(encoder, &self.target.color_buffer_view, &face);
+
+            capture.face += 1;
+            if capture.face == FACES.len() {
+                let radiance = self
+                    .radiance
+                    .create_view(&wgpu::TextureViewDescriptor { dimension: Some(wgpu::TextureViewDimension::Cube), ..Default::default() });
+                prefilter_specular(&self.assets, encoder, &radiance, &self.faces, capture.slot * 6, FACE_SIZE);
+                let intensity = 1. / capture.ev100.map_or(1., exposure_from_ev100);
+                self.probes.insert(capture.id, CapturedProbe { slot: capture.slot, version: capture.version, intensity });
+                self.capture = None;
+            }
+        }
+    }
+
+    /// The captured probes, from the smallest to the largest volume, for the globals
+    pub fn gpu_probes(&self, world: &World) -> Vec<GpuReflectionProbe> {
+        let mut probes = self
+            .probes
+            .iter()
+            .filter_map(|(&id, probe)| {
+                let position = get_world_position(world, id).ok()?;
+                let (radius, extents, volume) = match world.get(id, reflection_probe_box_extents()) {
+                    Ok(extents) => (0., extents.max(Vec3::splat(0.01)), extents.x * extents.y * extents.z * 8.),
+                    Err(_) => {
+                        let radius = world.get(id, reflection_probe_radius()).unwrap_or(DEFAULT_RADIUS).max(0.01);
+                        (radius, Vec3::ZERO, radius.powi(3) * 4.19)
+                    }
+                };
+                let probe = GpuReflectionProbe {
+                    position: position.extend(radius),
+                    box_extents: extents.extend(probe.slot as f32),
+                    params: Vec4::new(probe.intensity, 0., 0., 0.),
+                };
+                Some((volume, probe))
+            })
+            .collect::<Vec<_>>();
+        probes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        probes.into_iter().map(|(_, probe)| probe).collect()
+    }
+}
+
+impl SubRenderer for ReflectionProbes {
+    fn render(&mut self, context: &mut RenderContext) {
+        self.update(context.world, context.encoder, context.post_submit);
+    }
+}
+
+impl std::fmt::Debug for ReflectionProbes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReflectionProbes").field("probes", &self.probes).field("capture", &self.capture).finish()
+    }
+}
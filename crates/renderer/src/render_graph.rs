@@ -0,0 +1,300 @@
+use std::fmt::Debug;
+
+use ambient_core::gpu_ecs::ENTITIES_BIND_GROUP;
+use ambient_ecs::World;
+use ambient_gpu::mesh_buffer::MeshBuffer;
+use itertools::Itertools;
+use wgpu::BindGroup;
+
+use crate::{msaa::MsaaRenderTarget, GpuTimer, RendererTarget, SubRenderer, GLOBALS_BIND_GROUP, RESOURCES_BIND_GROUP};
+
+/// Render targets that a pass can declare as read from or written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RenderResource {
+    Color,
+    Depth,
+    Normals,
+    /// A copy of the color/depth/normals of the opaque scene, made after the overlays are drawn.
+    /// Used by transparent materials (e.g. water) to sample what is behind them.
+    SolidsFrame,
+    /// The target the renderer outputs to, which the scene is upscaled into if it's rendered at a lower resolution
+    Output,
+}
+
+/// Well-defined points in the renderer's frame where passes can be inserted.
+///
+/// The hooks run in this order, around the passes which are built into the renderer:
+/// `[PreRender] -> Forward -> [PostForward] -> Overlays -> [PostOverlays] -> SolidsFrame copy -> Transparent -> [PostTransparent] -> [PostOutlines] -> [Final]`
+///
+/// The optional built-in passes are passes of the graph too: the reflection probes are captured at `PreRender`, the MSAA
+/// depth is resolved and the volumetric fog is applied at `PostForward`, the outlines are drawn at `PostTransparent`, the
+/// picking is rendered at `PostOutlines`, and the scene is upscaled into the output at `Final`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RenderHook {
+    /// Before the scene is rendered. The mesh buffer isn't locked yet, so passes here can render with renderers of their own,
+    /// but they have no [RenderScene]
+    PreRender,
+    PostForward,
+    PostOverlays,
+    PostTransparent,
+    PostOutlines,
+    /// After the scene is rendered, when it can be written to the [RenderResource::Output]
+    Final,
+}
+impl RenderHook {
+    pub const ALL: [RenderHook; 6] = [
+        RenderHook::PreRender,
+        RenderHook::PostForward,
+        RenderHook::PostOverlays,
+        RenderHook::PostTransparent,
+        RenderHook::PostOutlines,
+        RenderHook::Final,
+    ];
+
+    /// Whether the given resource holds valid data for passes running at this hook
+    pub fn can_read(&self, resource: RenderResource) -> bool {
+        match resource {
+            RenderResource::SolidsFrame => matches!(self, RenderHook::PostTransparent | RenderHook::PostOutlines | RenderHook::Final),
+            RenderResource::Output => matches!(self, RenderHook::Final),
+            _ => !matches!(self, RenderHook::PreRender),
+        }
+    }
+}
+
+/// The mesh buffer and the bind groups the scene is rendered with
+#[derive(Clone, Copy)]
+pub struct RenderScene<'a> {
+    pub mesh_buffer: &'a MeshBuffer,
+    pub resources: &'a BindGroup,
+    pub entities: &'a BindGroup,
+    pub globals: &'a BindGroup,
+}
+impl<'a> RenderScene<'a> {
+    /// The bind groups of the resources and the globals, for passes which draw their own meshes
+    pub fn binds(&self) -> [(&'static str, &'a BindGroup); 2] {
+        [(RESOURCES_BIND_GROUP, self.resources), (GLOBALS_BIND_GROUP, self.globals)]
+    }
+    /// The bind groups of the resources, the entities and the globals, for passes which draw the entities of the scene
+    pub fn entity_binds(&self) -> [(&'static str, &'a BindGroup); 3] {
+        [(RESOURCES_BIND_GROUP, self.resources), (ENTITIES_BIND_GROUP, self.entities), (GLOBALS_BIND_GROUP, self.globals)]
+    }
+}
+
+/// What the passes of a [RenderGraph] render with
+pub struct RenderContext<'a> {
+    pub world: &'a mut World,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub post_submit: &'a mut Vec<Box<dyn FnOnce() + Send + Send>>,
+    /// The target the scene is rendered to, which is smaller than the output if the renderer's `resolution_scale` is below 1
+    pub target: RendererTarget<'a>,
+    /// The target the renderer outputs to
+    pub output: RendererTarget<'a>,
+    /// The multisampled frame the forward pass renders to, if MSAA is enabled
+    pub msaa: Option<&'a MsaaRenderTarget>,
+    /// None at [RenderHook::PreRender], before the scene is set up
+    pub scene: Option<RenderScene<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderPassDesc {
+    pub name: String,
+    pub hook: RenderHook,
+    pub reads: Vec<RenderResource>,
+    pub writes: Vec<RenderResource>,
+}
+impl RenderPassDesc {
+    pub fn new(name: impl Into<String>, hook: RenderHook) -> Self {
+        Self { name: name.into(), hook, reads: Vec::new(), writes: Vec::new() }
+    }
+    pub fn reads(mut self, resource: RenderResource) -> Self {
+        self.reads.push(resource);
+        self
+    }
+    pub fn writes(mut self, resource: RenderResource) -> Self {
+        self.writes.push(resource);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RenderGraphError {
+    #[error("A render pass named {0:?} already exists")]
+    DuplicateName(String),
+    #[error("Render pass {name:?} reads {resource:?}, which is not available at {hook:?}")]
+    ResourceUnavailable { name: String, hook: RenderHook, resource: RenderResource },
+    #[error("Render pass {0:?} can't write to the solids frame")]
+    WritesSolidsFrame(String),
+    #[error("Render passes {0:?} depend on each other")]
+    Cycle(Vec<String>),
+}
+
+struct RenderGraphNode {
+    desc: RenderPassDesc,
+    renderer: Box<dyn SubRenderer>,
+}
+
+/// A small render graph of passes, attached to the [RenderHook]s of a [crate::Renderer].
+///
+/// Within a hook, passes run in insertion order, except that a pass that writes a resource is always run before a pass
+/// (inserted earlier) which only reads it. Passes which both read and write a resource modify it in insertion order.
+#[derive(Default)]
+pub struct RenderGraph {
+    nodes: Vec<RenderGraphNode>,
+    order: Vec<usize>,
+}
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add_pass(&mut self, desc: RenderPassDesc, renderer: Box<dyn SubRenderer>) -> Result<(), RenderGraphError> {
+        if self.nodes.iter().any(|n| n.desc.name == desc.name) {
+            return Err(RenderGraphError::DuplicateName(desc.name));
+        }
+        if let Some(&resource) = desc.reads.iter().find(|r| !desc.hook.can_read(**r)) {
+            return Err(RenderGraphError::ResourceUnavailable { name: desc.name, hook: desc.hook, resource });
+        }
+        if desc.writes.contains(&RenderResource::SolidsFrame) {
+            return Err(RenderGraphError::WritesSolidsFrame(desc.name));
+        }
+        self.nodes.push(RenderGraphNode { desc, renderer });
+        if let Err(err) = self.update_order() {
+            self.nodes.pop();
+            return Err(err);
+        }
+        Ok(())
+    }
+    pub fn remove_pass(&mut self, name: &str) -> Option<Box<dyn SubRenderer>> {
+        let index = self.nodes.iter().position(|n| n.desc.name == name)?;
+        let node = self.nodes.remove(index);
+        // Removing a pass can't introduce a cycle
+        self.update_order().unwrap();
+        Some(node.renderer)
+    }
+    pub fn contains(&self, name: &str) -> bool {
+        self.nodes.iter().any(|n| n.desc.name == name)
+    }
+    /// The renderer of the pass `name`, if it's a `T`
+    pub fn get<T: SubRenderer>(&self, name: &str) -> Option<&T> {
+        self.nodes.iter().find(|n| n.desc.name == name)?.renderer.downcast_ref::<T>()
+    }
+    pub fn passes(&self) -> impl Iterator<Item = &RenderPassDesc> + '_ {
+        self.order.iter().map(|&i| &self.nodes[i].desc)
+    }
+
+    fn update_order(&mut self) -> Result<(), RenderGraphError> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        for hook in RenderHook::ALL {
+            let mut pending = (0..self.nodes.len()).filter(|&i| self.nodes[i].desc.hook == hook).collect_vec();
+            while !pending.is_empty() {
+                // Pick the first pass that doesn't only read something another pending pass writes
+                let pick = pending.iter().position(|&i| {
+                    let desc = &self.nodes[i].desc;
+                    let waits_for = |resource: &RenderResource| desc.reads.contains(resource) && !desc.writes.contains(resource);
+                    !pending.iter().any(|&j| j != i && self.nodes[j].desc.writes.iter().any(waits_for))
+                });
+                let Some(pick) = pick else {
+                    return Err(RenderGraphError::Cycle(pending.iter().map(|&i| self.nodes[i].desc.name.clone()).collect()));
+                };
+                order.push(pending.remove(pick));
+            }
+        }
+        self.order = order;
+        Ok(())
+    }
+
+    pub(crate) fn render(&mut self, hook: RenderHook, context: &mut RenderContext, timer: &mut GpuTimer) {
+        for i in self.order.clone() {
+            let node = &mut self.nodes[i];
+            if node.desc.hook != hook {
+                continue;
+            }
+            profiling::scope!("RenderGraph pass", &node.desc.name);
+            let timer_scope = timer.begin(context.encoder, &node.desc.name);
+            node.renderer.render(context);
+            timer.end(context.encoder, timer_scope);
+        }
+    }
+
+    pub fn dump(&self, f: &mut dyn std::io::Write) {
+        for desc in self.passes() {
+            writeln!(f, "    {:?} {:?} reads: {:?} writes: {:?}", desc.hook, desc.name, desc.reads, desc.writes).unwrap();
+        }
+    }
+}
+impl Debug for RenderGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.passes().map(|p| &p.name)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Pass;
+    impl SubRenderer for Pass {
+        fn render(&mut self, _: &mut RenderContext) {}
+    }
+
+    fn order(graph: &RenderGraph) -> Vec<&str> {
+        graph.passes().map(|desc| desc.name.as_str()).collect()
+    }
+
+    #[test]
+    fn writers_run_before_readers() {
+        let mut graph = RenderGraph::new();
+        graph.add_pass(RenderPassDesc::new("final", RenderHook::Final), Box::new(Pass)).unwrap();
+        graph.add_pass(RenderPassDesc::new("fog", RenderHook::PostForward).reads(RenderResource::Depth), Box::new(Pass)).unwrap();
+        graph.add_pass(RenderPassDesc::new("resolve", RenderHook::PostForward).writes(RenderResource::Depth), Box::new(Pass)).unwrap();
+        graph.add_pass(RenderPassDesc::new("probes", RenderHook::PreRender), Box::new(Pass)).unwrap();
+        assert_eq!(order(&graph), ["probes", "resolve", "fog", "final"]);
+
+        graph.remove_pass("resolve").unwrap();
+        assert_eq!(order(&graph), ["probes", "fog", "final"]);
+    }
+
+    #[test]
+    fn modifiers_run_in_insertion_order() {
+        let mut graph = RenderGraph::new();
+        for name in ["a", "b", "c"] {
+            let desc = RenderPassDesc::new(name, RenderHook::PostTransparent).reads(RenderResource::Color).writes(RenderResource::Color);
+            graph.add_pass(desc, Box::new(Pass)).unwrap();
+        }
+        assert_eq!(order(&graph), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let mut graph = RenderGraph::new();
+        let a = RenderPassDesc::new("a", RenderHook::PostOverlays).reads(RenderResource::Color).writes(RenderResource::Normals);
+        let b = RenderPassDesc::new("b", RenderHook::PostOverlays).reads(RenderResource::Normals).writes(RenderResource::Color);
+        graph.add_pass(a, Box::new(Pass)).unwrap();
+        assert_eq!(graph.add_pass(b, Box::new(Pass)), Err(RenderGraphError::Cycle(vec!["a".to_string(), "b".to_string()])));
+        assert!(!graph.contains("b"));
+        assert_eq!(order(&graph), ["a"]);
+
+        // The same dependencies at different hooks are fine, as the hooks run in order
+        let b = RenderPassDesc::new("b", RenderHook::PostTransparent).reads(RenderResource::Normals).writes(RenderResource::Color);
+        graph.add_pass(b, Box::new(Pass)).unwrap();
+        assert_eq!(order(&graph), ["a", "b"]);
+    }
+
+    #[test]
+    fn invalid_passes_are_rejected() {
+        let mut graph = RenderGraph::new();
+        graph.add_pass(RenderPassDesc::new("a", RenderHook::PostForward), Box::new(Pass)).unwrap();
+        assert_eq!(
+            graph.add_pass(RenderPassDesc::new("a", RenderHook::PostForward), Box::new(Pass)),
+            Err(RenderGraphError::DuplicateName("a".to_string()))
+        );
+        assert!(matches!(
+            graph.add_pass(RenderPassDesc::new("b", RenderHook::PostForward).reads(RenderResource::SolidsFrame), Box::new(Pass)),
+            Err(RenderGraphError::ResourceUnavailable { .. })
+        ));
+        assert!(matches!(
+            graph.add_pass(RenderPassDesc::new("b", RenderHook::PostOutlines).reads(RenderResource::Output), Box::new(Pass)),
+            Err(RenderGraphError::ResourceUnavailable { .. })
+        ));
+    }
+}
@@ -8,16 +8,19 @@ use ambient_core::{
     player::local_user_id,
     ui_scene,
 };
-use ambient_ecs::{ArchetypeFilter, Component, World};
+use ambient_ecs::{ArchetypeFilter, Component, EntityId, World};
 use ambient_gpu::{
+    blit::{Blitter, BlitterKey},
     gpu::{Gpu, GpuKey},
     mesh_buffer::MeshBuffer,
     shader_module::BindGroupDesc,
 };
+use ambient_settings::RenderSettings;
 use ambient_std::{
     asset_cache::{AssetCache, SyncAssetKey, SyncAssetKeyExt},
     color::Color,
 };
+use downcast_rs::{impl_downcast, DowncastSync};
 use glam::uvec2;
 use wgpu::{BindGroupLayout, TextureView};
 
@@ -25,10 +28,18 @@ use super::{
     get_common_module, get_globals_module, get_resources_module,
     overlay_renderer::{OverlayConfig, OverlayRenderer},
     shadow_renderer::ShadowsRenderer,
-    Culling, FSMain, ForwardGlobals, Outlines, OutlinesConfig, RenderTarget, RendererCollect, RendererCollectState, TransparentRenderer,
+    Culling, FSMain, ForwardGlobals, GpuPicking, GpuPickingConfig, Outlines, OutlinesConfig, RenderContext, RenderGraph, RenderHook,
+    RenderPassDesc, RenderResource, RenderScene, RenderTarget, RendererCollect, RendererCollectState, TransparentRenderer,
     TransparentRendererConfig, TreeRenderer, TreeRendererConfig,
 };
-use crate::{skinning::SkinsBufferKey, to_linear_format, ShaderDebugParams};
+use crate::timing::{GpuTimer, GpuTimings};
+use crate::{
+    lights::ClusteredLights,
+    msaa::{DepthResolver, MsaaRenderTarget},
+    reflection_probes::{ReflectionProbes, MAX_REFLECTION_PROBES},
+    skinning::SkinsBufferKey,
+    to_linear_format, ShaderDebugParams,
+};
 pub const GLOBALS_BIND_GROUP: &str = "GLOBALS_BIND_GROUP";
 pub const MATERIAL_BIND_GROUP: &str = "MATERIAL_BIND_GROUP";
 pub const RESOURCES_BIND_GROUP: &str = "RESOURCES_BIND_GROUP";
@@ -78,14 +89,43 @@ pub struct RendererConfig {
     pub shadow_map_resolution: u32,
     pub shadow_cascades: u32,
     pub lod_cutoff_scaling: f32,
+    /// MSAA sample count for the forward pass. Transparent objects, overlays, outlines and the render graph passes
+    /// are rendered single-sampled on top of the resolved result. Only use this for renderers which clear the target.
+    pub msaa_samples: u32,
+    /// The scene is rendered at this fraction of the target's resolution, and then upscaled into the target.
+    /// Only applies to [RendererTarget::Target].
+    pub resolution_scale: f32,
+    /// Picks the entity under the cursor pixel-accurately on the GPU, see [GpuPicking]. The result is written to
+    /// `ambient_input::picking::gpu_picker_intersecting`
+    pub gpu_picking: bool,
+    /// Captures the `reflection_probe`s of the scene, and reflects them on the surfaces inside their volumes. Only enable this
+    /// for the renderer of the active camera, as each renderer captures the probes on its own
+    pub reflection_probes: bool,
+}
+
+impl RendererConfig {
+    pub fn with_render_settings(self, settings: &RenderSettings) -> Self {
+        Self { msaa_samples: settings.msaa_samples(), resolution_scale: settings.resolution_scale(), ..self }
+    }
 }
 
 impl Default for RendererConfig {
     fn default() -> Self {
-        Self { scene: ui_scene(), shadows: true, shadow_map_resolution: 1024, shadow_cascades: 5, lod_cutoff_scaling: 1. }
+        Self {
+            scene: ui_scene(),
+            shadows: true,
+            shadow_map_resolution: 1024,
+            shadow_cascades: 5,
+            lod_cutoff_scaling: 1.,
+            msaa_samples: 1,
+            resolution_scale: 1.,
+            gpu_picking: false,
+            reflection_probes: false,
+        }
     }
 }
 
+#[derive(Clone, Copy)]
 pub enum RendererTarget<'a> {
     Target(&'a RenderTarget),
     Direct { color: &'a TextureView, depth: &'a TextureView, normals: &'a TextureView, size: wgpu::Extent3d },
@@ -117,16 +157,51 @@ impl<'a> RendererTarget<'a> {
     }
 }
 
-pub trait SubRenderer: std::fmt::Debug + Send + Sync {
-    fn render<'a>(
-        &'a mut self,
-        world: &World,
-        mesh_buffer: &MeshBuffer,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &RendererTarget,
-        binds: &[(&str, &'a wgpu::BindGroup)],
-    );
+/// A pass of a [RenderGraph]
+pub trait SubRenderer: std::fmt::Debug + DowncastSync {
+    fn render(&mut self, context: &mut RenderContext);
 }
+impl_downcast!(sync SubRenderer);
+
+/// Upscales the scene into the output, when it's rendered at a lower resolution
+struct Upscale(Arc<Blitter>);
+impl Upscale {
+    fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("upscale", RenderHook::Final).reads(RenderResource::Color).writes(RenderResource::Output)
+    }
+}
+impl std::fmt::Debug for Upscale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Upscale").finish()
+    }
+}
+impl SubRenderer for Upscale {
+    fn render(&mut self, context: &mut RenderContext) {
+        let (RendererTarget::Target(output), RendererTarget::Target(scaled)) = (context.output, context.target) else { return };
+        if std::ptr::eq(output, scaled) {
+            return;
+        }
+        self.0.run(context.encoder, &scaled.color_buffer_view, &output.color_buffer_view);
+        // The scene's depth and normals can't be upscaled meaningfully, so they're reset for the passes drawn on top (e.g. the UI)
+        context.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Upscale clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &output.normals_quat_buffer_view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Clear(Color::BLACK.into()), store: true },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &output.depth_buffer_view,
+                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(0.0), store: true }),
+                stencil_ops: None,
+            }),
+        });
+    }
+}
+
+const REFLECTION_PROBES_PASS: &str = "reflection_probes";
+const OUTLINES_PASS: &str = "outlines";
+const PICKING_PASS: &str = "picking";
 
 pub struct Renderer {
     gpu: Arc<Gpu>,
@@ -136,14 +211,19 @@ pub struct Renderer {
     culling: Culling,
     pub shadows: Option<ShadowsRenderer>,
     forward_globals: ForwardGlobals,
+    lights: ClusteredLights,
     forward_collect_state: RendererCollectState,
     forward: TreeRenderer,
     overlays: OverlayRenderer,
     transparent: TransparentRenderer,
     solids_frame: RenderTarget,
-    outlines: Outlines,
-    pub post_forward: Option<Box<dyn SubRenderer>>,
-    pub post_transparent: Option<Box<dyn SubRenderer>>,
+    scaled_frame: Option<RenderTarget>,
+    msaa_frame: Option<MsaaRenderTarget>,
+    /// The passes which are run at the [RenderHook]s of this renderer; the built-in ones, and custom ones
+    pub graph: RenderGraph,
+    /// The camera to render from. Defaults to the active camera of the scene
+    pub camera: Option<EntityId>,
+    timer: GpuTimer,
 }
 
 impl Renderer {
@@ -160,10 +240,41 @@ impl Renderer {
 
         let normals_format = to_linear_format(gpu.swapchain_format()).into();
 
+        let mut graph = RenderGraph::new();
+        if config.reflection_probes {
+            graph.add_pass(ReflectionProbes::render_pass_desc(), Box::new(ReflectionProbes::new(&assets, &config))).unwrap();
+        }
+        if config.msaa_samples > 1 {
+            graph.add_pass(DepthResolver::render_pass_desc(), Box::new(DepthResolver::new(gpu.clone()))).unwrap();
+        }
+        #[cfg(feature = "volumetric-fog")]
+        graph
+            .add_pass(
+                crate::volumetric_fog::VolumetricFog::render_pass_desc(),
+                Box::new(crate::volumetric_fog::VolumetricFog::new(&assets, config.scene, shadow_cascades)),
+            )
+            .unwrap();
+        let outlines =
+            Outlines::new(&assets, OutlinesConfig { scene: config.scene, renderer_resources: renderer_resources.clone() }, config.clone());
+        graph.add_pass(Outlines::render_pass_desc(), Box::new(outlines)).unwrap();
+        if config.gpu_picking {
+            let picking = GpuPicking::new(
+                &assets,
+                GpuPickingConfig { scene: config.scene, renderer_resources: renderer_resources.clone() },
+                config.clone(),
+            );
+            graph.add_pass(GpuPicking::render_pass_desc(), Box::new(picking)).unwrap();
+        }
+        if config.resolution_scale != 1. {
+            let upscale = Upscale(BlitterKey { format: gpu.swapchain_format().into(), linear: true }.get(&assets));
+            graph.add_pass(Upscale::render_pass_desc(), Box::new(upscale)).unwrap();
+        }
+
         Self {
             culling: Culling::new(&assets, config.clone()),
             forward_globals: ForwardGlobals::new(gpu.clone(), renderer_resources.globals_layout.clone(), shadow_cascades, config.scene),
             forward_collect_state: RendererCollectState::new(&assets),
+            lights: ClusteredLights::new(&assets, config.scene),
             shadows,
             overlays: OverlayRenderer::new(
                 assets.clone(),
@@ -187,6 +298,7 @@ impl Renderer {
                 depth_stencil: true,
                 cull_mode: Some(wgpu::Face::Back),
                 depth_bias: Default::default(),
+                sample_count: config.msaa_samples,
             }),
             transparent: TransparentRenderer::new(TransparentRendererConfig {
                 gpu: gpu.clone(),
@@ -207,17 +319,15 @@ impl Renderer {
                 uvec2(1, 1),
                 Some(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST),
             ),
-            outlines: Outlines::new(
-                &assets,
-                OutlinesConfig { scene: config.scene, renderer_resources: renderer_resources.clone() },
-                config.clone(),
-            ),
+            scaled_frame: None,
+            msaa_frame: None,
             resources_layout: renderer_resources.resources_layout,
             config,
             shader_debug_params: Default::default(),
+            timer: GpuTimer::new(gpu.clone()),
             gpu,
-            post_forward: Default::default(),
-            post_transparent: Default::default(),
+            graph,
+            camera: None,
         }
     }
 
@@ -230,6 +340,30 @@ impl Renderer {
         clear: Option<Color>,
     ) {
         profiling::scope!("Renderer.render");
+        self.timer.begin_frame();
+
+        let output = target;
+        let target = match output {
+            RendererTarget::Target(output) if self.config.resolution_scale != 1. => {
+                let size = (uvec2(output.color_buffer.size.width, output.color_buffer.size.height).as_vec2()
+                    * self.config.resolution_scale)
+                    .as_uvec2()
+                    .max(uvec2(1, 1));
+                if self.scaled_frame.as_ref().map(|frame| (frame.color_buffer.size.width, frame.color_buffer.size.height))
+                    != Some((size.x, size.y))
+                {
+                    self.scaled_frame = Some(RenderTarget::new(self.gpu.clone(), size, None));
+                }
+                RendererTarget::Target(self.scaled_frame.as_ref().unwrap())
+            }
+            _ => output,
+        };
+
+        if self.config.msaa_samples > 1 && self.msaa_frame.as_ref().map(|frame| frame.size) != Some(target.size()) {
+            let size = target.size();
+            self.msaa_frame = Some(MsaaRenderTarget::new(self.gpu.clone(), uvec2(size.width, size.height), self.config.msaa_samples));
+        }
+        let msaa = self.msaa_frame.as_ref();
 
         if let RendererTarget::Target(target) = &target {
             if self.solids_frame.color_buffer.size != target.color_buffer.size {
@@ -241,6 +375,10 @@ impl Renderer {
             }
         }
 
+        let mut context =
+            RenderContext { world: &mut *world, encoder: &mut *encoder, post_submit: &mut *post_submit, target, output, msaa, scene: None };
+        self.graph.render(RenderHook::PreRender, &mut context, &mut self.timer);
+
         let mesh_buffer_h = MeshBufferKey.get(world.resource(asset_cache()));
         let mesh_buffer = mesh_buffer_h.lock();
 
@@ -251,28 +389,43 @@ impl Renderer {
             gpu_world.create_bind_group(true)
         };
 
-        let main_camera = Camera::get_active(world, self.config.scene, world.resource_opt(local_user_id())).unwrap_or_default();
+        let camera = self.camera.or_else(|| get_active_camera(world, self.config.scene, world.resource_opt(local_user_id())));
+        let main_camera = camera.and_then(|camera| Camera::from_world(world, camera)).unwrap_or_default();
+        let timer_scope = self.timer.begin(encoder, "Update");
         {
             profiling::scope!("Update");
-            self.culling.run(encoder, world, &Default::default());
+            self.culling.run(encoder, world, camera, &Default::default());
 
             self.forward_collect_state.set_camera(0);
             self.forward.update(world);
             self.overlays.update(world);
             self.forward.run_collect(encoder, post_submit, &resources_bind_group, &entities_bind_group, &mut self.forward_collect_state);
             self.transparent.update(world, &mesh_buffer, main_camera.projection_view());
+            self.lights.run(world, encoder, &main_camera, self.shadows.as_ref().map(|_| self.config.shadow_cascades));
         }
+        self.timer.end(encoder, timer_scope);
 
         if let Some(shadows) = &mut self.shadows {
-            shadows.run(world, encoder, post_submit, &resources_bind_group, &entities_bind_group, &mesh_buffer);
+            let timer_scope = self.timer.begin(encoder, "Shadows");
+            shadows.run(world, encoder, post_submit, &resources_bind_group, &entities_bind_group, &mesh_buffer, &main_camera);
+            self.timer.end(encoder, timer_scope);
         }
 
         self.forward_globals.params.debug_params = self.shader_debug_params;
-        self.forward_globals.update(world, &self.shadows.as_ref().map(|x| x.get_cameras()).unwrap_or_default());
+        self.forward_globals.params.cluster_params = self.lights.cluster_params;
+        let reflection_probes = self.graph.get::<ReflectionProbes>(REFLECTION_PROBES_PASS);
+        let gpu_probes = reflection_probes.map(|probes| probes.gpu_probes(world)).unwrap_or_default();
+        self.forward_globals.params.reflection_probe_count = gpu_probes.len().min(MAX_REFLECTION_PROBES) as u32;
+        for (i, probe) in gpu_probes.into_iter().take(MAX_REFLECTION_PROBES).enumerate() {
+            self.forward_globals.params.reflection_probes[i] = probe;
+        }
+        self.forward_globals.update(world, camera, &self.shadows.as_ref().map(|x| x.get_cameras()).unwrap_or_default());
         let forward_globals_bind_group = self.forward_globals.create_bind_group(
             world.resource(asset_cache()).clone(),
             self.shadows.as_ref().map(|x| &x.shadow_view),
             &self.solids_frame,
+            &self.lights,
+            reflection_probes.map(|probes| &probes.faces_view),
         );
 
         let binds = [
@@ -281,22 +434,23 @@ impl Renderer {
             (GLOBALS_BIND_GROUP, &forward_globals_bind_group),
         ];
 
+        let timer_scope = self.timer.begin(encoder, "Forward");
         {
             profiling::scope!("Forward");
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Forward"),
                 color_attachments: &[
                     Some(wgpu::RenderPassColorAttachment {
-                        view: target.color(),
-                        resolve_target: None,
+                        view: msaa.map(|msaa| &*msaa.color_buffer_view).unwrap_or(target.color()),
+                        resolve_target: msaa.map(|_| target.color()),
                         ops: wgpu::Operations {
                             load: if let Some(clear) = clear { wgpu::LoadOp::Clear(clear.into()) } else { wgpu::LoadOp::Load },
                             store: true,
                         },
                     }),
                     Some(wgpu::RenderPassColorAttachment {
-                        view: target.normals(),
-                        resolve_target: None,
+                        view: msaa.map(|msaa| &*msaa.normals_quat_buffer_view).unwrap_or(target.normals()),
+                        resolve_target: msaa.map(|_| target.normals()),
                         ops: wgpu::Operations {
                             /// clear color is ignored as the normal buffer should always be initialized with black
                             load: if clear.is_some() { wgpu::LoadOp::Clear(Color::BLACK.into()) } else { wgpu::LoadOp::Load },
@@ -305,7 +459,7 @@ impl Renderer {
                     }),
                 ],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: target.depth(),
+                    view: msaa.map(|msaa| &*msaa.depth_buffer_view).unwrap_or(target.depth()),
                     depth_ops: Some(wgpu::Operations {
                         load: if clear.is_some() { wgpu::LoadOp::Clear(0.0) } else { wgpu::LoadOp::Load },
                         store: true,
@@ -321,22 +475,34 @@ impl Renderer {
                 drop(render_pass);
             }
         }
-
-        if let Some(post_forward) = &mut self.post_forward {
-            post_forward.render(
-                world,
-                &mesh_buffer,
-                encoder,
-                &target,
-                &[(RESOURCES_BIND_GROUP, &resources_bind_group), (GLOBALS_BIND_GROUP, &forward_globals_bind_group)],
-            );
-        }
+        self.timer.end(encoder, timer_scope);
+
+        let mut context = RenderContext {
+            world: &mut *world,
+            encoder: &mut *encoder,
+            post_submit: &mut *post_submit,
+            target,
+            output,
+            msaa,
+            scene: Some(RenderScene {
+                mesh_buffer: &mesh_buffer,
+                resources: &resources_bind_group,
+                entities: &entities_bind_group,
+                globals: &forward_globals_bind_group,
+            }),
+        };
+        self.graph.render(RenderHook::PostForward, &mut context, &mut self.timer);
+        let encoder = &mut *context.encoder;
 
         {
+            let timer_scope = self.timer.begin(encoder, "Overlays");
             let binds = [(GLOBALS_BIND_GROUP, &forward_globals_bind_group)];
 
             self.overlays.render(encoder, &target, &binds, &mesh_buffer);
+            self.timer.end(encoder, timer_scope);
         }
+        self.graph.render(RenderHook::PostOverlays, &mut context, &mut self.timer);
+        let encoder = &mut *context.encoder;
 
         if let RendererTarget::Target(target) = &target {
             encoder.copy_texture_to_texture(
@@ -355,6 +521,7 @@ impl Renderer {
                 target.normals_quat_buffer.size,
             );
         }
+        let timer_scope = self.timer.begin(encoder, "Transparent");
         {
             profiling::scope!("Transparent");
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -380,18 +547,18 @@ impl Renderer {
                 drop(render_pass);
             }
         }
+        self.timer.end(encoder, timer_scope);
 
-        if let Some(post_transparent) = &mut self.post_transparent {
-            post_transparent.render(
-                world,
-                &mesh_buffer,
-                encoder,
-                &target,
-                &[(RESOURCES_BIND_GROUP, &resources_bind_group), (GLOBALS_BIND_GROUP, &forward_globals_bind_group)],
-            );
+        for hook in [RenderHook::PostTransparent, RenderHook::PostOutlines, RenderHook::Final] {
+            self.graph.render(hook, &mut context, &mut self.timer);
         }
 
-        self.outlines.render(world, encoder, post_submit, &target, &binds, &mesh_buffer);
+        self.timer.end_frame(context.encoder, context.post_submit);
+    }
+
+    /// The GPU time spent in each pass, if the adapter supports timestamp queries
+    pub fn gpu_timings(&self) -> Option<GpuTimings> {
+        self.timer.timings()
     }
 
     pub fn dump_to_tmp_file(&self) {
@@ -428,8 +595,16 @@ impl Renderer {
         self.forward.dump(f);
         writeln!(f, "  transparent").unwrap();
         self.transparent.dump(f);
-        writeln!(f, "  outlines").unwrap();
-        self.outlines.dump(f);
+        if let Some(outlines) = self.graph.get::<Outlines>(OUTLINES_PASS) {
+            writeln!(f, "  outlines").unwrap();
+            outlines.dump(f);
+        }
+        if let Some(picking) = self.graph.get::<GpuPicking>(PICKING_PASS) {
+            writeln!(f, "  picking").unwrap();
+            picking.dump(f);
+        }
+        writeln!(f, "  graph").unwrap();
+        self.graph.dump(f);
     }
 }
 
@@ -54,8 +54,29 @@ fn model_to_world(loc: vec2<u32>, mesh_index: u32, vertex_index: u32) -> ModelTo
         total_tangent = total_tangent + (ltw_z * tangent) * mesh_weight.z;
         total_tangent = total_tangent + (ltw_w * tangent) * mesh_weight.w;
 
-        return ModelToWorld(total_pos, model * total_pos, normalize((model * total_norm).xyz), normalize((model * normalize(tangent)).xyz));
+        return ModelToWorld(total_pos, apply_wind_sway(loc, model, model * total_pos), normalize((model * total_norm).xyz), normalize((model * normalize(tangent)).xyz));
     } else {
-        return ModelToWorld(pos, model * pos, normalize((model * normal).xyz), normalize((model * tangent).xyz));
+        return ModelToWorld(pos, apply_wind_sway(loc, model, model * pos), normalize((model * normal).xyz), normalize((model * tangent).xyz));
     }
 }
+
+/// Sways the vertices of an entity with a `wind_sway` in the wind, the more the higher they are above its origin
+fn apply_wind_sway(loc: vec2<u32>, model: mat4x4<f32>, world_pos: vec4<f32>) -> vec4<f32> {
+    if (!has_entity_wind_sway(loc)) {
+        return world_pos;
+    }
+    let origin = model * vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    let height = max(world_pos.z - origin.z, 0.0);
+    // Gusts, out of phase between neighbouring entities
+    let gust = 0.75 + 0.25 * sin(global_params.time * 1.7 + origin.x * 0.3 + origin.y * 0.2);
+    let offset = global_params.wind.xy * get_entity_wind_sway(loc).x * 0.02 * height * gust;
+    return world_pos + vec4<f32>(offset, 0.0, 0.0);
+}
+
+/// The id written by the picking pass: the entity's location in the gpu world, 1 to tell it apart from
+/// the cleared background, and the distance from the camera as f32 bits
+fn get_picking_id(instance_index: u32, world_position: vec3<f32>) -> vec4<u32> {
+    let entity_loc = primitives.data[instance_index].xy;
+    let distance = length(world_position - global_params.camera_position.xyz);
+    return vec4<u32>(entity_loc, 1u, bitcast<u32>(distance));
+}
@@ -36,6 +36,7 @@ impl SyncAssetKey<Arc<RendererShader>> for StandardShaderKey {
             fs_shadow_main: "fs_shadow_main".to_string(),
             fs_forward_main: if self.lit { "fs_forward_lit_main".to_string() } else { "fs_forward_unlit_main".to_string() },
             fs_outline_main: "fs_outlines_main".to_string(),
+            fs_picking_main: "fs_picking_main".to_string(),
             transparent: false,
             double_sided: false,
             depth_write_enabled: true,
@@ -112,3 +112,13 @@ fn fs_outlines_main(in: VertexOutput, @builtin(front_facing) is_front: bool) ->
     }
     return get_outline(in.instance_index);
 }
+
+@fragment
+fn fs_picking_main(in: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<u32> {
+    var material = get_material(get_material_in(in, is_front));
+
+    if (material.opacity < material.alpha_cutoff) {
+        discard;
+    }
+    return get_picking_id(in.instance_index, in.world_position.xyz);
+}
@@ -1,6 +1,6 @@
 use std::{num::NonZeroU32, sync::Arc};
 
-use ambient_core::{camera::Camera, gpu_ecs::ENTITIES_BIND_GROUP, main_scene, player::local_user_id, transform::*};
+use ambient_core::{camera::Camera, gpu_ecs::ENTITIES_BIND_GROUP, main_scene, transform::*};
 use ambient_ecs::{ArchetypeFilter, World};
 use ambient_gpu::{
     gpu::GpuKey,
@@ -18,11 +18,17 @@ use super::{
     cast_shadows, get_active_sun, FSMain, RendererCollectState, RendererResources, ShadowAndUIGlobals, TreeRenderer, TreeRendererConfig,
     GLOBALS_BIND_GROUP, MAX_SHADOW_CASCADES, RESOURCES_BIND_GROUP,
 };
-use crate::{default_sun_direction, RendererConfig};
+use crate::{
+    default_sun_direction,
+    lights::{get_shadowed_lights, MAX_SHADOWED_LIGHTS},
+    RendererConfig,
+};
 
 pub struct ShadowsRenderer {
     renderer: TreeRenderer,
     cascades: Vec<ShadowCascade>,
+    /// The shadow maps of spot lights, stored after the cascades in the shadow texture
+    lights: Vec<ShadowCascade>,
     pub shadow_texture: Arc<Texture>,
     config: RendererConfig,
     pub shadow_view: TextureView,
@@ -39,7 +45,7 @@ impl ShadowsRenderer {
                 size: wgpu::Extent3d {
                     width: config.shadow_map_resolution,
                     height: config.shadow_map_resolution,
-                    depth_or_array_layers: config.shadow_cascades,
+                    depth_or_array_layers: config.shadow_cascades + MAX_SHADOWED_LIGHTS,
                 },
                 mip_level_count: 1,
                 sample_count: 1,
@@ -66,22 +72,20 @@ impl ShadowsRenderer {
                 depth_stencil: true,
                 cull_mode: Some(wgpu::Face::Front),
                 depth_bias: DepthBiasState { constant: -2, slope_scale: -1.5, clamp: 0.0 },
+                sample_count: 1,
             }),
             cascades: (0..config.shadow_cascades)
-                .map(|i| ShadowCascade {
-                    dynamic_target: shadow_texture.create_view(&wgpu::TextureViewDescriptor {
-                        label: Some("Renderer.shadow_target_views"),
-                        format: None,
-                        dimension: Some(wgpu::TextureViewDimension::D2),
-                        aspect: wgpu::TextureAspect::All,
-                        base_mip_level: 0,
-                        mip_level_count: None,
-                        base_array_layer: i,
-                        array_layer_count: NonZeroU32::new(1),
-                    }),
-                    globals: ShadowAndUIGlobals::new(assets.clone(), renderer_resources.globals_layout.clone()),
-                    camera: Camera::default(),
-                    collect_state: RendererCollectState::new(&assets),
+                .map(|i| ShadowCascade::new(&assets, &renderer_resources, &shadow_texture, i, i + 1))
+                .collect_vec(),
+            lights: (0..MAX_SHADOWED_LIGHTS)
+                .map(|i| {
+                    ShadowCascade::new(
+                        &assets,
+                        &renderer_resources,
+                        &shadow_texture,
+                        config.shadow_cascades + i,
+                        1 + MAX_SHADOW_CASCADES + i,
+                    )
                 })
                 .collect_vec(),
             shadow_texture,
@@ -89,8 +93,8 @@ impl ShadowsRenderer {
             config,
         }
     }
-    pub fn get_cameras(&self) -> SmallVec<[ShadowCameraData; MAX_SHADOW_CASCADES as usize]> {
-        self.cascades.iter().map(|v| (&v.camera).into()).collect()
+    pub fn get_cameras(&self) -> SmallVec<[ShadowCameraData; (MAX_SHADOW_CASCADES + MAX_SHADOWED_LIGHTS) as usize]> {
+        self.cascades.iter().chain(self.lights.iter()).map(|v| (&v.camera).into()).collect()
     }
     pub fn n_cascades(&self) -> usize {
         self.cascades.len()
@@ -104,9 +108,8 @@ impl ShadowsRenderer {
         resources_bind_group: &wgpu::BindGroup,
         entities_bind_group: &wgpu::BindGroup,
         mesh_buffer: &MeshBuffer,
+        main_camera: &Camera,
     ) {
-        let main_camera = Camera::get_active(world, main_scene(), world.resource_opt(local_user_id())).unwrap_or_default();
-
         let sun_direction = if let Some(sun) = get_active_sun(world, main_scene()) {
             get_world_rotation(world, sun).unwrap().mul_vec3(Vec3::X)
         } else {
@@ -125,13 +128,21 @@ impl ShadowsRenderer {
             );
             cascade.globals.update(world, main_scene(), new_camera.projection_view());
             cascade.camera = new_camera;
-            cascade.collect_state.set_camera(i as u32 + 1);
         }
 
-        for (i, cascade) in self.cascades.iter_mut().enumerate() {
+        let shadowed_lights = get_shadowed_lights(world, main_scene(), main_camera.position());
+        for (light, (_, camera)) in self.lights.iter_mut().zip(shadowed_lights.iter()) {
+            profiling::scope!("Light shadow update");
+            light.globals.update(world, main_scene(), camera.projection_view());
+            light.camera = camera.clone();
+        }
+
+        let n_cascades = self.cascades.len();
+        let targets = self.cascades.iter_mut().chain(self.lights.iter_mut().take(shadowed_lights.len()));
+        for (i, cascade) in targets.enumerate() {
             profiling::scope!("Shadow dynamic render");
             self.renderer.run_collect(encoder, post_submit, resources_bind_group, entities_bind_group, &mut cascade.collect_state);
-            let label = format!("Shadow cascade {i}");
+            let label = if i < n_cascades { format!("Shadow cascade {i}") } else { format!("Light shadow {}", i - n_cascades) };
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some(&label),
                 color_attachments: &[],
@@ -175,6 +186,28 @@ struct ShadowCascade {
     camera: Camera,
     collect_state: RendererCollectState,
 }
+impl ShadowCascade {
+    /// Renders into `layer` of the shadow texture, with the entities culled for `culling_camera`
+    fn new(assets: &AssetCache, renderer_resources: &RendererResources, shadow_texture: &Texture, layer: u32, culling_camera: u32) -> Self {
+        let collect_state = RendererCollectState::new(assets);
+        collect_state.set_camera(culling_camera);
+        Self {
+            dynamic_target: shadow_texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Renderer.shadow_target_views"),
+                format: None,
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                aspect: wgpu::TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: None,
+                base_array_layer: layer,
+                array_layer_count: NonZeroU32::new(1),
+            }),
+            globals: ShadowAndUIGlobals::new(assets.clone(), renderer_resources.globals_layout.clone()),
+            camera: Camera::default(),
+            collect_state,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
 #[repr(C)]
@@ -0,0 +1,42 @@
+@group(#MATERIAL_BIND_GROUP)
+@binding(0)
+var environment: texture_cube<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(instance_index) instance_index: u32, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = i32(vertex_index) / 2;
+    let y = i32(vertex_index) & 1;
+    let tc = vec2<f32>(
+        f32(x) * 2.0,
+        f32(y) * 2.0
+    );
+    // Placed at the far plane, so that it's only drawn where nothing else was
+    out.position = vec4<f32>(
+        tc.x * 2.0 - 1.0,
+        1.0 - tc.y * 2.0,
+        0.000001, 1.0
+    );
+    out.world_position = global_params.inv_projection_view * out.position;
+    out.world_position = out.world_position / out.world_position.w;
+    return out;
+}
+
+@fragment
+fn fs_forward_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dir = normalize(in.world_position.xyz - global_params.camera_position.xyz);
+    let color = textureSampleLevel(environment, default_sampler, environment_direction(dir), 0.0).rgb;
+    return vec4<f32>(color * environment_intensity() * global_params.exposure, 1.0);
+}
+
+// The sky isn't pickable
+@fragment
+fn fs_picking_main(in: VertexOutput) -> @location(0) vec4<u32> {
+    discard;
+    return vec4<u32>(0u);
+}
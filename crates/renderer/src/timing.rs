@@ -0,0 +1,138 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use ambient_gpu::gpu::Gpu;
+use parking_lot::Mutex;
+
+/// The maximum number of passes that can be timed per frame
+const MAX_SCOPES: u32 = 32;
+const TIMESTAMP_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+/// The GPU time spent in each pass of the most recently measured frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GpuTimings {
+    pub passes: Vec<(String, f32)>,
+}
+impl GpuTimings {
+    /// The total time of all passes, in milliseconds
+    pub fn total_ms(&self) -> f32 {
+        self.passes.iter().map(|(_, ms)| ms).sum()
+    }
+}
+
+/// A pass which is being timed, see [GpuTimer::begin]
+#[must_use]
+pub struct GpuTimerScope(Option<u32>);
+
+/// Measures how long the GPU spends on each pass, using timestamp queries.
+///
+/// This does nothing on adapters that don't support `TIMESTAMP_QUERY`. The results are read back
+/// asynchronously; frames which are rendered while a read back is in progress aren't measured.
+pub struct GpuTimer {
+    gpu: Arc<Gpu>,
+    queries: Option<(wgpu::QuerySet, wgpu::Buffer, Arc<wgpu::Buffer>)>,
+    scopes: Vec<String>,
+    recording: bool,
+    readback_pending: Arc<AtomicBool>,
+    timings: Arc<Mutex<Option<GpuTimings>>>,
+}
+impl GpuTimer {
+    pub fn new(gpu: Arc<Gpu>) -> Self {
+        let queries = gpu.device.features().contains(wgpu::Features::TIMESTAMP_QUERY).then(|| {
+            let size = MAX_SCOPES as u64 * 2 * TIMESTAMP_SIZE;
+            (
+                gpu.device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("GpuTimer.queries"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: MAX_SCOPES * 2,
+                }),
+                gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("GpuTimer.resolve"),
+                    size,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }),
+                Arc::new(gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("GpuTimer.readback"),
+                    size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })),
+            )
+        });
+        Self { gpu, queries, scopes: Vec::new(), recording: false, readback_pending: Default::default(), timings: Default::default() }
+    }
+    pub fn is_supported(&self) -> bool {
+        self.queries.is_some()
+    }
+    /// The timings of the most recently measured frame, or None if no frame has been measured yet
+    pub fn timings(&self) -> Option<GpuTimings> {
+        self.timings.lock().clone()
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.scopes.clear();
+        self.recording = self.queries.is_some() && !self.readback_pending.load(Ordering::SeqCst);
+    }
+    pub fn begin(&mut self, encoder: &mut wgpu::CommandEncoder, name: &str) -> GpuTimerScope {
+        let Some((query_set, _, _)) = &self.queries else { return GpuTimerScope(None) };
+        if !self.recording || self.scopes.len() as u32 >= MAX_SCOPES {
+            return GpuTimerScope(None);
+        }
+        let index = self.scopes.len() as u32;
+        self.scopes.push(name.to_string());
+        encoder.write_timestamp(query_set, index * 2);
+        GpuTimerScope(Some(index))
+    }
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder, scope: GpuTimerScope) {
+        if let (Some((query_set, _, _)), GpuTimerScope(Some(index))) = (&self.queries, scope) {
+            encoder.write_timestamp(query_set, index * 2 + 1);
+        }
+    }
+    /// Resolves the timestamps of this frame, and reads them back once the commands have been submitted
+    pub fn end_frame(&mut self, encoder: &mut wgpu::CommandEncoder, post_submit: &mut Vec<Box<dyn FnOnce() + Send + Send>>) {
+        let Some((query_set, resolve_buffer, readback_buffer)) = &self.queries else { return };
+        if !self.recording || self.scopes.is_empty() {
+            return;
+        }
+        self.recording = false;
+        let count = self.scopes.len() as u32 * 2;
+        let size = count as u64 * TIMESTAMP_SIZE;
+        encoder.resolve_query_set(query_set, 0..count, resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(resolve_buffer, 0, readback_buffer, 0, size);
+
+        self.readback_pending.store(true, Ordering::SeqCst);
+        let names = std::mem::take(&mut self.scopes);
+        let period_ms = self.gpu.queue.get_timestamp_period() as f64 / 1_000_000.;
+        let readback_buffer = readback_buffer.clone();
+        let readback_pending = self.readback_pending.clone();
+        let timings = self.timings.clone();
+        post_submit.push(Box::new(move || {
+            let buffer = readback_buffer.clone();
+            readback_buffer.slice(0..size).map_async(wgpu::MapMode::Read, move |res| {
+                if res.is_ok() {
+                    {
+                        let data = buffer.slice(0..size).get_mapped_range();
+                        let ticks: &[u64] = bytemuck::cast_slice(&data);
+                        let passes = names
+                            .into_iter()
+                            .zip(ticks.chunks_exact(2))
+                            .map(|(name, ticks)| (name, (ticks[1].saturating_sub(ticks[0]) as f64 * period_ms) as f32))
+                            .collect();
+                        *timings.lock() = Some(GpuTimings { passes });
+                    }
+                    buffer.unmap();
+                }
+                readback_pending.store(false, Ordering::SeqCst);
+            });
+        }));
+    }
+}
+
+impl std::fmt::Debug for GpuTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuTimer").field("supported", &self.is_supported()).finish()
+    }
+}
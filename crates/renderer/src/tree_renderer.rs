@@ -3,6 +3,7 @@ use std::{
     sync::Arc,
 };
 
+use ambient_core::ui_culled;
 use ambient_ecs::{query, ArchetypeFilter, EntityId, FramedEventsReader, QueryState, World};
 use ambient_gpu::{
     gpu::Gpu,
@@ -32,6 +33,7 @@ pub struct TreeRendererConfig {
     pub depth_stencil: bool,
     pub cull_mode: Option<wgpu::Face>,
     pub depth_bias: DepthBiasState,
+    pub sample_count: u32,
 }
 
 pub struct TreeRenderer {
@@ -83,6 +85,7 @@ impl TreeRenderer {
         let mut despawn_qs = std::mem::replace(&mut self.despawn_qs, QueryState::new());
         for (id, (primitives,)) in query((primitives().changed(),))
             .optional_changed(cpu_lod_visible())
+            .optional_changed(ui_culled())
             .filter(&self.config.filter)
             .iter(world, Some(&mut spawn_qs))
         {
@@ -198,6 +201,7 @@ impl TreeRenderer {
     ) -> Option<(String, String)> {
         if (!material.transparent().unwrap_or(shader.transparent) || !self.config.opaque_only)
             && world.get(id, cpu_lod_visible()).unwrap_or(true)
+            && !world.get(id, ui_culled()).unwrap_or(false)
         {
             let config = &self.config;
             let double_sided = world.get(id, double_sided()).unwrap_or(material.double_sided().unwrap_or(shader.double_sided));
@@ -329,6 +333,7 @@ impl ShaderNode {
             fs_main: shader.get_fs_main_name(config.fs_main),
             targets: &config.targets,
             cull_mode: config.cull_mode.and_then(|f| if double_sided { None } else { Some(f) }),
+            sample_count: config.sample_count,
             ..Default::default()
         };
         if config.depth_stencil {
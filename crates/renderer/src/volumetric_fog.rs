@@ -0,0 +1,267 @@
+//! Froxel-based volumetric fog: the view frustum is split into a grid of froxels, sliced exponentially along the view, and
+//! the light the fog scatters towards the camera is gathered per froxel, through the cascaded shadow maps so that the gaps
+//! between the shadows become shafts of light. The froxels are then integrated front to back, and the scene is blended with
+//! them by the distance of each pixel.
+//!
+//! As the globals can't be bound to compute shaders, the passes are fragment passes, with the slices of the froxel grid laid
+//! out as the tiles of a 2D atlas.
+use std::sync::Arc;
+
+use ambient_ecs::{Component, World};
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    shader_module::{BindGroupDesc, GraphicsPipeline, GraphicsPipelineInfo, Shader, ShaderModule},
+    texture::{Texture, TextureView},
+};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    include_file,
+};
+use wgpu::{util::DeviceExt, BindGroup, PrimitiveTopology};
+
+use crate::{
+    get_active_sun, get_overlay_modules, volumetric_fog_anisotropy, volumetric_fog_density, volumetric_fog_height_falloff, RenderContext,
+    RenderHook, RenderPassDesc, RenderResource, RendererTarget, SubRenderer, GLOBALS_BIND_GROUP,
+};
+
+const VOLUMETRIC_FOG_BIND_GROUP: &str = "VOLUMETRIC_FOG_BIND_GROUP";
+
+const FROXELS_X: u32 = 160;
+const FROXELS_Y: u32 = 90;
+const SLICES: u32 = 64;
+/// The slices are laid out in this many columns of the atlas
+const ATLAS_COLUMNS: u32 = 8;
+/// The distances from the camera the slices span
+const NEAR: f32 = 0.5;
+const FAR: f32 = 200.;
+const DEFAULT_HEIGHT_FALLOFF: f32 = 0.1;
+const DEFAULT_ANISOTROPY: f32 = 0.6;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct VolumetricFogParams {
+    density: f32,
+    height_falloff: f32,
+    anisotropy: f32,
+    near: f32,
+    far: f32,
+    froxels_x: u32,
+    froxels_y: u32,
+    slices: u32,
+}
+
+pub struct VolumetricFog {
+    gpu: Arc<Gpu>,
+    scene: Component<()>,
+    params_buffer: wgpu::Buffer,
+    /// The light scattered by each froxel, and its density
+    scattering: TextureView,
+    /// The light scattered from the camera to the end of each froxel, and the transmittance
+    integrated: TextureView,
+    scatter_pipeline: GraphicsPipeline,
+    integrate_pipeline: GraphicsPipeline,
+    apply_pipeline: GraphicsPipeline,
+}
+impl VolumetricFog {
+    pub fn new(assets: &AssetCache, scene: Component<()>, shadow_cascades: u32) -> Self {
+        let gpu = GpuKey.get(assets);
+        let layout = BindGroupDesc {
+            entries: vec![
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+            label: VOLUMETRIC_FOG_BIND_GROUP.into(),
+        };
+        let shader = Shader::from_modules(
+            assets,
+            "VolumetricFog",
+            get_overlay_modules(assets, shadow_cascades).iter().chain([&ShaderModule::new(
+                "VolumetricFog",
+                include_file!("volumetric_fog.wgsl"),
+                vec![layout.into()],
+            )]),
+        );
+
+        let atlas_target = [Some(wgpu::ColorTargetState { format: FORMAT, blend: None, write_mask: wgpu::ColorWrites::ALL })];
+        let pipeline = |fs_main: &str, targets: &[Option<wgpu::ColorTargetState>]| {
+            shader.to_pipeline(
+                &gpu,
+                GraphicsPipelineInfo {
+                    vs_main: "vs_main",
+                    fs_main,
+                    targets,
+                    topology: PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+            )
+        };
+        let scatter_pipeline = pipeline("fs_scatter", &atlas_target);
+        let integrate_pipeline = pipeline("fs_integrate", &atlas_target);
+        // The scene is dimmed by the transmittance, and the scattered light is added to it
+        let apply_pipeline = pipeline(
+            "fs_apply",
+            &[Some(wgpu::ColorTargetState {
+                format: gpu.swapchain_format(),
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::SrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Zero,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        );
+
+        let params_buffer = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("VolumetricFog.params"),
+            contents: bytemuck::cast_slice(&[VolumetricFogParams::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            scattering: Self::create_atlas(gpu.clone(), "VolumetricFog.scattering"),
+            integrated: Self::create_atlas(gpu.clone(), "VolumetricFog.integrated"),
+            gpu,
+            scene,
+            params_buffer,
+            scatter_pipeline,
+            integrate_pipeline,
+            apply_pipeline,
+        }
+    }
+
+    fn create_atlas(gpu: Arc<Gpu>, label: &str) -> TextureView {
+        let texture = Arc::new(Texture::new(
+            gpu,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: FROXELS_X * ATLAS_COLUMNS,
+                    height: FROXELS_Y * ((SLICES + ATLAS_COLUMNS - 1) / ATLAS_COLUMNS),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            },
+        ));
+        texture.create_view(&Default::default())
+    }
+
+    /// The settings of the fog, from the active sun of the scene, if it has any
+    fn params(&self, world: &World) -> Option<VolumetricFogParams> {
+        let sun = get_active_sun(world, self.scene)?;
+        let density = world.get(sun, volumetric_fog_density()).ok().filter(|density| *density > 0.)?;
+        Some(VolumetricFogParams {
+            density,
+            height_falloff: world.get(sun, volumetric_fog_height_falloff()).unwrap_or(DEFAULT_HEIGHT_FALLOFF),
+            anisotropy: world.get(sun, volumetric_fog_anisotropy()).unwrap_or(DEFAULT_ANISOTROPY).clamp(-0.99, 0.99),
+            near: NEAR,
+            far: FAR,
+            froxels_x: FROXELS_X,
+            froxels_y: FROXELS_Y,
+            slices: SLICES,
+        })
+    }
+
+    fn create_bind_group(&self, pipeline: &GraphicsPipeline, volume: &wgpu::TextureView, depth: &wgpu::TextureView) -> BindGroup {
+        self.gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("VolumetricFog.bind_group"),
+            layout: pipeline.shader().get_bind_group_layout_by_name(VOLUMETRIC_FOG_BIND_GROUP).unwrap(),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: self.params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(volume) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(depth) },
+            ],
+        })
+    }
+
+    fn run_pass(
+        encoder: &mut wgpu::CommandEncoder,
+        label: &str,
+        pipeline: &GraphicsPipeline,
+        output: &wgpu::TextureView,
+        globals: &BindGroup,
+        bind_group: &BindGroup,
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(pipeline.pipeline());
+        pipeline.bind(&mut rpass, GLOBALS_BIND_GROUP, globals);
+        pipeline.bind(&mut rpass, VOLUMETRIC_FOG_BIND_GROUP, bind_group);
+        rpass.draw(0..4, 0..1);
+    }
+
+    pub fn render_pass_desc() -> RenderPassDesc {
+        RenderPassDesc::new("volumetric_fog", RenderHook::PostForward).reads(RenderResource::Depth).writes(RenderResource::Color)
+    }
+}
+impl std::fmt::Debug for VolumetricFog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VolumetricFog").finish()
+    }
+}
+impl SubRenderer for VolumetricFog {
+    /// Fogs the color of the target, once the depth of the scene is rendered to it
+    #[profiling::function]
+    fn render(&mut self, context: &mut RenderContext) {
+        let (Some(scene), RendererTarget::Target(_)) = (context.scene, context.target) else { return };
+        let Some(params) = self.params(context.world) else { return };
+        let (encoder, target, globals) = (&mut *context.encoder, &context.target, scene.globals);
+        self.gpu.queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+
+        let depth = target.depth();
+        // The scattering pass doesn't read the volume, but the layout requires one
+        let scatter = self.create_bind_group(&self.scatter_pipeline, &self.integrated, depth);
+        let integrate = self.create_bind_group(&self.integrate_pipeline, &self.scattering, depth);
+        let apply = self.create_bind_group(&self.apply_pipeline, &self.integrated, depth);
+
+        Self::run_pass(encoder, "Volumetric fog scattering", &self.scatter_pipeline, &self.scattering, globals, &scatter);
+        Self::run_pass(encoder, "Volumetric fog integration", &self.integrate_pipeline, &self.integrated, globals, &integrate);
+        Self::run_pass(encoder, "Volumetric fog", &self.apply_pipeline, target.color(), globals, &apply);
+    }
+}
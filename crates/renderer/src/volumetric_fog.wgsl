@@ -0,0 +1,148 @@
+struct VolumetricFogParams {
+    density: f32,
+    height_falloff: f32,
+    anisotropy: f32,
+    // The distances from the camera the froxel slices span, spaced exponentially between them
+    near: f32,
+    far: f32,
+    froxels_x: u32,
+    froxels_y: u32,
+    slices: u32,
+};
+
+@group(#VOLUMETRIC_FOG_BIND_GROUP)
+@binding(0)
+var<uniform> fog_params: VolumetricFogParams;
+
+// The froxels, with their slices laid out as tiles of an atlas
+@group(#VOLUMETRIC_FOG_BIND_GROUP)
+@binding(1)
+var fog_volume: texture_2d<f32>;
+
+@group(#VOLUMETRIC_FOG_BIND_GROUP)
+@binding(2)
+var fog_depth: texture_depth_2d;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = i32(vertex_index) / 2;
+    let y = i32(vertex_index) & 1;
+    let tc = vec2<f32>(
+        f32(x) * 2.0,
+        f32(y) * 2.0
+    );
+    out.position = vec4<f32>(
+        tc.x * 2.0 - 1.0,
+        1.0 - tc.y * 2.0,
+        0.0, 1.0
+    );
+    out.tex_coords = tc;
+    return out;
+}
+
+fn froxel_size() -> vec2<i32> {
+    return vec2<i32>(i32(fog_params.froxels_x), i32(fog_params.froxels_y));
+}
+
+fn atlas_tiles_x() -> i32 {
+    return i32(textureDimensions(fog_volume).x) / i32(fog_params.froxels_x);
+}
+
+// The atlas texel of the froxel at `froxel` in `slice`
+fn froxel_texel(froxel: vec2<i32>, slice: i32) -> vec2<i32> {
+    let tiles_x = atlas_tiles_x();
+    return froxel + vec2<i32>(slice % tiles_x, slice / tiles_x) * froxel_size();
+}
+
+// The froxel, and its slice, of an atlas texel
+fn texel_froxel(texel: vec2<i32>) -> vec3<i32> {
+    let tile = texel / froxel_size();
+    return vec3<i32>(texel % froxel_size(), tile.y * atlas_tiles_x() + tile.x);
+}
+
+// The distance from the camera at which `slice` starts
+fn slice_distance(slice: f32) -> f32 {
+    return fog_params.near * pow(fog_params.far / fog_params.near, slice / f32(fog_params.slices));
+}
+
+fn distance_slice(distance: f32) -> f32 {
+    return log(max(distance, fog_params.near) / fog_params.near) / log(fog_params.far / fog_params.near) * f32(fog_params.slices);
+}
+
+fn view_direction(uv: vec2<f32>) -> vec3<f32> {
+    let p = project_point(global_params.inv_projection_view, vec3<f32>(screen_uv_to_ndc(uv).xy, 0.5));
+    return normalize(p - global_params.camera_position.xyz);
+}
+
+// Henyey-Greenstein
+fn phase(cos_theta: f32, g: f32) -> f32 {
+    let gg = g * g;
+    return (1. - gg) / (4. * PI * pow(1. + gg - 2. * g * cos_theta, 1.5));
+}
+
+// The light scattered towards the camera by each froxel, and its density
+@fragment
+fn fs_scatter(in: VertexOutput) -> @location(0) vec4<f32> {
+    let froxel = texel_froxel(vec2<i32>(in.position.xy));
+    let uv = (vec2<f32>(froxel.xy) + 0.5) / vec2<f32>(froxel_size());
+    let dir = view_direction(uv);
+    let position = global_params.camera_position.xyz + dir * slice_distance(f32(froxel.z) + 0.5);
+
+    let density = fog_params.density * exp(-fog_params.height_falloff * position.z);
+    // The shadow maps cut the sunlight into shafts
+    let sun = global_params.sun_diffuse.rgb
+        * phase(dot(dir, global_params.sun_direction.xyz), fog_params.anisotropy)
+        * fetch_shadow(1., vec4<f32>(position, 1.));
+    let light = sun + global_params.sun_ambient.rgb;
+    return vec4<f32>(light * density, density);
+}
+
+// The light scattered towards the camera from the camera to the end of each froxel, and how much of what's behind it is seen
+@fragment
+fn fs_integrate(in: VertexOutput) -> @location(0) vec4<f32> {
+    let froxel = texel_froxel(vec2<i32>(in.position.xy));
+    var light = vec3<f32>(0.);
+    var transmittance = 1.;
+    for (var slice = 0; slice <= froxel.z; slice = slice + 1) {
+        let scattering = textureLoad(fog_volume, froxel_texel(froxel.xy, slice), 0);
+        let thickness = slice_distance(f32(slice + 1)) - slice_distance(f32(slice));
+        let slice_transmittance = exp(-scattering.a * thickness);
+        // Integrated over the thickness of the slice, rather than taken at its middle, so that dense slices don't get brighter
+        light = light + transmittance * scattering.rgb * (1. - slice_transmittance) / max(scattering.a, 0.00001);
+        transmittance = transmittance * slice_transmittance;
+    }
+    return vec4<f32>(light, transmittance);
+}
+
+fn sample_integrated(uv: vec2<f32>, slice: i32) -> vec4<f32> {
+    if (slice < 0) {
+        return vec4<f32>(0., 0., 0., 1.);
+    }
+    let tile = froxel_texel(vec2<i32>(0), min(slice, i32(fog_params.slices) - 1));
+    // Kept half a texel inside the tile, so that it isn't filtered with the neighbouring slices
+    let froxel = clamp(uv * vec2<f32>(froxel_size()), vec2<f32>(0.5), vec2<f32>(froxel_size()) - 0.5);
+    return textureSampleLevel(fog_volume, default_sampler, (vec2<f32>(tile) + froxel) / vec2<f32>(textureDimensions(fog_volume)), 0.);
+}
+
+// Blended as `color * transmittance + light`
+@fragment
+fn fs_apply(in: VertexOutput) -> @location(0) vec4<f32> {
+    let depth = textureLoad(fog_depth, vec2<i32>(in.position.xy), 0);
+    var distance = fog_params.far;
+    // The sky, at a depth of 0, is behind all of the froxels
+    if (depth > 0.) {
+        let position = project_point(global_params.inv_projection_view, vec3<f32>(screen_uv_to_ndc(in.tex_coords).xy, depth));
+        distance = length(position - global_params.camera_position.xyz);
+    }
+    // The integrated froxels of a slice reach to the start of the next one
+    let slice = distance_slice(distance) - 1.;
+    let base = floor(slice);
+    let fog = mix(sample_integrated(in.tex_coords, i32(base)), sample_integrated(in.tex_coords, i32(base) + 1), slice - base);
+    return vec4<f32>(fog.rgb * global_params.exposure, fog.a);
+}
@@ -91,6 +91,16 @@ impl<Args: Send + 'static> RpcRegistry<Args> {
         bincode::deserialize(resp)
     }
 }
+/// The name requests to `func` are sent with
+pub fn rpc_name<F: 'static>(_func: F) -> String {
+    std::any::type_name::<F>().to_string()
+}
+/// The name of the function a serialized request is for
+pub fn req_name(req: &[u8]) -> Option<&str> {
+    let end = req.iter().position(|&byte| byte == b'\n')?;
+    std::str::from_utf8(&req[..end]).ok()
+}
+
 impl<T> std::fmt::Debug for RpcRegistry<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RpcRegistry").finish()
@@ -11,9 +11,41 @@ use itertools::Itertools;
 
 const MINIMUM_RUST_VERSION: Version = Version((1, 65, 0));
 
+/// Which parts of the toolchain Rust modules are built with are installed, for diagnostics
+#[derive(Debug, Clone)]
+pub struct InstallationStatus {
+    pub rustup_version: Option<String>,
+    pub rustc_version: Option<String>,
+    /// Whether `rustc` is installed and at least [InstallationStatus::minimum_rustc_version]
+    pub rustc_supported: bool,
+    pub minimum_rustc_version: String,
+    /// Whether the `wasm32-wasi` target is installed for the stable toolchain
+    pub has_wasm32_wasi: bool,
+}
+
 #[derive(Clone)]
 pub struct Rust(Installation);
 impl Rust {
+    /// Checks every part of the installation, unlike [Rust::get_system_installation], which stops at the first one missing
+    pub fn get_installation_status() -> InstallationStatus {
+        let installation = Installation;
+        let rustc_version = installation.get_installed_rustc_version().ok();
+        InstallationStatus {
+            rustup_version: installation
+                .get_installed_rustup_version()
+                .ok()
+                .map(|v| v.to_string()),
+            rustc_supported: rustc_version
+                .as_ref()
+                .map_or(false, |v| *v >= MINIMUM_RUST_VERSION),
+            rustc_version: rustc_version.map(|v| v.to_string()),
+            minimum_rustc_version: MINIMUM_RUST_VERSION.to_string(),
+            has_wasm32_wasi: installation
+                .get_installed_targets()
+                .map_or(false, |targets| targets.iter().any(|s| s == "wasm32-wasi")),
+        }
+    }
+
     pub async fn get_system_installation() -> anyhow::Result<Self> {
         let installation = Installation;
         if installation.get_installed_rustup_version().is_err() {
@@ -165,7 +197,10 @@ fn parse_command_result_for_filenames(
     if success {
         let Some(last_compiler_artifact) = messages
             .iter()
-            .rfind(|v| v.get("reason").and_then(|v| v.as_str()) == Some("compiler-artifact")) else { return Ok(vec![]) };
+            .rfind(|v| v.get("reason").and_then(|v| v.as_str()) == Some("compiler-artifact"))
+        else {
+            return Ok(vec![]);
+        };
 
         let filenames = last_compiler_artifact
             .get("filenames")
@@ -0,0 +1,431 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use ambient_ecs::{components, world_events, Debuggable, Description, Entity, Name, Networked, Resource, World};
+use ambient_event_types::SETTINGS_CHANGED;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+mod schema;
+pub use schema::*;
+
+components!("settings", {
+    @[Debuggable, Resource]
+    settings: Settings,
+    /// The settings declared by the packages running on the server. This is a synced resource
+    @[Debuggable, Networked]
+    package_settings_schema: Vec<PackageSettingsSchema>,
+
+    @[
+        Debuggable,
+        Name["Setting package"],
+        Description["The id of the package whose setting changed, in a `core/settings_changed` event.\nNot set for the built-in settings."]
+    ]
+    setting_package: String,
+    @[
+        Debuggable,
+        Name["Setting key"],
+        Description["The key of the setting that changed, in a `core/settings_changed` event.\nFor the built-in settings, this is their section, like `render`."]
+    ]
+    setting_key: String,
+    @[
+        Debuggable,
+        Name["Setting value"],
+        Description["The new value of the package setting that changed, in a `core/settings_changed` event, as text: `true`, `42`, `0.5` or the string itself.\nNot set if the setting was reset to its default."]
+    ]
+    setting_value: String,
+});
+
+/// Replaces the settings of the app, and fires a [SETTINGS_CHANGED] event for each setting that changed so that the changes can
+/// be applied without a restart
+pub fn update_settings(world: &mut World, settings: Settings) {
+    let current = world.resource_opt(self::settings()).cloned().unwrap_or_default();
+    let changes = changed_settings(&current, &settings);
+    world.add_resource(self::settings(), settings);
+    let events = world.resource_mut(world_events());
+    for change in changes {
+        events.add_event((SETTINGS_CHANGED.to_string(), change));
+    }
+}
+
+/// The events for the differences between two settings: one per changed section of the built-in settings, with its
+/// `setting_key`, and one per changed package setting, with its `setting_package`, `setting_key` and `setting_value`
+fn changed_settings(old: &Settings, new: &Settings) -> Vec<Entity> {
+    let sections = [
+        ("render", old.render != new.render),
+        ("gpu", old.gpu != new.gpu),
+        ("crash_reports", old.crash_reports != new.crash_reports),
+        ("logging", old.logging != new.logging),
+        ("ui", old.ui != new.ui),
+    ];
+    let mut changes = sections
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(section, _)| Entity::new().with(setting_key(), section.to_string()))
+        .collect::<Vec<_>>();
+
+    for package in old.packages.keys().chain(new.packages.keys()).collect::<BTreeSet<_>>() {
+        let (old_values, new_values) = (old.packages.get(package), new.packages.get(package));
+        for key in old_values.into_iter().chain(new_values).flat_map(|values| values.keys()).collect::<BTreeSet<_>>() {
+            let value = new_values.and_then(|values| values.get(key));
+            if old_values.and_then(|values| values.get(key)) == value {
+                continue;
+            }
+            let mut change = Entity::new().with(setting_package(), package.clone()).with(setting_key(), key.clone());
+            if let Some(value) = value {
+                change.set(setting_value(), schema::toml_text(value));
+            }
+            changes.push(change);
+        }
+    }
+    changes
+}
+
+/// User settings for the Ambient client. These are stored in `settings.toml` in the user's config directory, or in IndexedDB on the web.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub render: RenderSettings,
+    pub gpu: GpuSettings,
+    pub crash_reports: CrashReportSettings,
+    pub logging: LoggingSettings,
+    pub ui: UiSettings,
+    /// The values of the settings declared by packages, by package id. Settings which aren't set here use their default
+    pub packages: BTreeMap<String, toml::Table>,
+}
+impl Settings {
+    pub const FILE_NAME: &str = "settings.toml";
+
+    #[cfg(not(target_os = "unknown"))]
+    pub fn path() -> Option<PathBuf> {
+        directories_next::ProjectDirs::from("com", "Ambient", "Ambient").map(|dirs| dirs.config_dir().join(Self::FILE_NAME))
+    }
+    /// On the web, the settings are stored in IndexedDB by [ambient_sys::fs], where paths are only keys
+    #[cfg(target_os = "unknown")]
+    pub fn path() -> Option<PathBuf> {
+        Some(PathBuf::from("config").join(Self::FILE_NAME))
+    }
+    pub fn parse(settings: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(settings)?)
+    }
+    /// Loads the settings from disk. Returns the default settings if there is no settings file.
+    #[cfg(not(target_os = "unknown"))]
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = Self::path() else { return Ok(Self::default()) };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path).with_context(|| format!("Failed to read settings from {path:?}"))?;
+        Self::parse(&contents).with_context(|| format!("Failed to parse settings from {path:?}"))
+    }
+    /// The browser only lets the settings be read asynchronously, so this returns the settings last loaded with
+    /// [Settings::load_async] or saved, or the default settings.
+    #[cfg(target_os = "unknown")]
+    pub fn load() -> anyhow::Result<Self> {
+        Ok(web::LOADED.lock().clone().unwrap_or_default())
+    }
+    /// Loads the settings without blocking. Returns the default settings if there is no settings file.
+    pub async fn load_async() -> anyhow::Result<Self> {
+        let Some(path) = Self::path() else { return Ok(Self::default()) };
+        let settings = match ambient_sys::fs::read_to_string(&path).await {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("Failed to parse settings from {path:?}"))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(err) => return Err(err).with_context(|| format!("Failed to read settings from {path:?}")),
+        };
+        #[cfg(target_os = "unknown")]
+        {
+            *web::LOADED.lock() = Some(settings.clone());
+        }
+        Ok(settings)
+    }
+    pub fn load_or_default() -> Self {
+        match Self::load() {
+            Ok(settings) => settings,
+            Err(err) => {
+                log::warn!("{err:?}; using default settings");
+                Self::default()
+            }
+        }
+    }
+    #[cfg(not(target_os = "unknown"))]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().context("No config directory available")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(self)?).with_context(|| format!("Failed to write settings to {path:?}"))
+    }
+    /// Saves the settings in the background, as the browser only lets them be written asynchronously
+    #[cfg(target_os = "unknown")]
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().context("No config directory available")?;
+        let contents = toml::to_string_pretty(self)?;
+        *web::LOADED.lock() = Some(self.clone());
+        ambient_sys::task::spawn(async move {
+            if let Err(err) = ambient_sys::fs::write(&path, contents).await {
+                log::warn!("Failed to write settings to {path:?}: {err:?}");
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "unknown")]
+mod web {
+    use parking_lot::{const_mutex, Mutex};
+
+    use super::Settings;
+
+    /// The settings as they are in IndexedDB, for the synchronous [Settings::load]
+    pub(super) static LOADED: Mutex<Option<Settings>> = const_mutex(None);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    /// The number of samples used for multisample anti-aliasing. Only 1 (off) and 4 are supported on all platforms.
+    pub msaa_samples: u32,
+    /// Scales the resolution the 3D scene is rendered at, relative to the window. The UI is always rendered at full resolution.
+    pub resolution_scale: f32,
+    /// Picks the entity under the cursor by rendering entity ids on the GPU, which is pixel-accurate for skinned meshes,
+    /// decals and alpha-tested materials, instead of by their bounding boxes.
+    pub gpu_picking: bool,
+}
+impl RenderSettings {
+    pub const SUPPORTED_MSAA_SAMPLES: [u32; 2] = [1, 4];
+    pub const RESOLUTION_SCALE_RANGE: (f32, f32) = (0.25, 2.0);
+
+    /// The MSAA sample count, falling back to no MSAA if the value in the settings is unsupported
+    pub fn msaa_samples(&self) -> u32 {
+        if Self::SUPPORTED_MSAA_SAMPLES.contains(&self.msaa_samples) {
+            self.msaa_samples
+        } else {
+            log::warn!("Unsupported MSAA sample count {}; expected one of {:?}", self.msaa_samples, Self::SUPPORTED_MSAA_SAMPLES);
+            1
+        }
+    }
+    pub fn resolution_scale(&self) -> f32 {
+        let (min, max) = Self::RESOLUTION_SCALE_RANGE;
+        if self.resolution_scale.is_finite() {
+            self.resolution_scale.clamp(min, max)
+        } else {
+            1.
+        }
+    }
+}
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self { msaa_samples: 1, resolution_scale: 1., gpu_picking: false }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuSettings {
+    pub backend: GpuBackend,
+    pub power_preference: GpuPowerPreference,
+    /// If set, the first adapter whose name contains this (case-insensitive) is used, if there is one
+    pub adapter_name: Option<String>,
+}
+impl GpuSettings {
+    /// Applies the value of the `--gpu` command line flag, which is either a power preference or (part of) an adapter name
+    pub fn apply_cli(&mut self, gpu: &str) {
+        match gpu.parse::<GpuPowerPreference>() {
+            Ok(power_preference) => self.power_preference = power_preference,
+            Err(_) => self.adapter_name = Some(gpu.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuBackend {
+    /// Use the best backend for the platform
+    #[default]
+    Auto,
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+}
+impl FromStr for GpuBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "auto" => Self::Auto,
+            "vulkan" => Self::Vulkan,
+            "metal" => Self::Metal,
+            "dx12" => Self::Dx12,
+            "gl" | "opengl" => Self::Gl,
+            _ => anyhow::bail!("Unknown GPU backend {s:?}; expected one of auto, vulkan, metal, dx12 or gl"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuPowerPreference {
+    /// Prefer a discrete GPU
+    #[default]
+    Discrete,
+    /// Prefer an integrated GPU, which uses less power
+    Integrated,
+}
+impl FromStr for GpuPowerPreference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "discrete" => Self::Discrete,
+            "integrated" => Self::Integrated,
+            _ => anyhow::bail!("Unknown GPU power preference {s:?}; expected discrete or integrated"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    /// A `RUST_LOG`-style filter, like `info,ambient_gpu=debug`. It's applied on top of the defaults, and `RUST_LOG` is
+    /// applied on top of it. Can be changed at runtime from the log console
+    pub filter: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiSettings {
+    /// Where the UI panels are, by panel id. Saved when a panel is moved, resized, docked or undocked
+    pub panels: BTreeMap<String, PanelLayout>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub dock: PanelDock,
+    /// The position of the top-left corner of the panel when it's floating, in logical pixels
+    pub x: f32,
+    pub y: f32,
+    /// The size of the panel when it's floating. When it's docked, only its size across the dock is used
+    pub width: f32,
+    pub height: f32,
+}
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self { dock: PanelDock::Floating, x: 50., y: 50., width: 400., height: 300. }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelDock {
+    #[default]
+    Floating,
+    Left,
+    Right,
+    Bottom,
+}
+
+/// Crash reporting is opt-in; nothing is written or uploaded unless `enabled` is set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrashReportSettings {
+    /// Write a crash report to disk when Ambient panics or crashes
+    pub enabled: bool,
+    /// Where crash reports are uploaded to. If not set, reports are only kept on disk
+    pub upload_endpoint: Option<String>,
+    /// Upload pending reports on launch without asking first
+    pub auto_upload: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_partial_settings() {
+        let settings = Settings::parse("[render]\nmsaa_samples = 4\n").unwrap();
+        assert_eq!(settings.render, RenderSettings { msaa_samples: 4, resolution_scale: 1. });
+        assert_eq!(Settings::parse("").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn sanitize_render_settings() {
+        let render = RenderSettings { msaa_samples: 3, resolution_scale: 10. };
+        assert_eq!(render.msaa_samples(), 1);
+        assert_eq!(render.resolution_scale(), 2.);
+    }
+
+    #[test]
+    fn gpu_cli_flag() {
+        let mut gpu = GpuSettings::default();
+        gpu.apply_cli("Integrated");
+        assert_eq!(gpu.power_preference, GpuPowerPreference::Integrated);
+        gpu.apply_cli("RTX 3080");
+        assert_eq!(gpu.adapter_name.as_deref(), Some("RTX 3080"));
+    }
+
+    #[test]
+    fn package_values() {
+        let setting = PackageSetting {
+            key: "volume".to_string(),
+            name: "Volume".to_string(),
+            description: String::new(),
+            default: SettingValue::F32(0.8),
+            min: Some(0.),
+            max: Some(1.),
+        };
+        let mut settings = Settings::parse("[packages.racer]\nvolume = \"loud\"\n").unwrap();
+        assert_eq!(settings.package_value("racer", &setting), SettingValue::F32(0.8));
+        settings.set_package_value("racer", "volume", &SettingValue::F32(2.));
+        assert_eq!(settings.package_value("racer", &setting), SettingValue::F32(1.));
+        let schemas = [PackageSettingsSchema { package: "racer".to_string(), name: "Racer".to_string(), settings: vec![setting] }];
+        assert_eq!(settings.package_value_text(&schemas, "racer", "volume").as_deref(), Some("1"));
+        assert_eq!(settings.package_value_text(&schemas, "racer", "laps"), None);
+        assert_eq!(Settings::parse(&toml::to_string(&settings).unwrap()).unwrap(), settings);
+    }
+
+    #[test]
+    fn changed_settings_events() {
+        init_components();
+        let old = Settings::parse("[packages.racer]\nvolume = 0.5\nname = \"Bob\"\n").unwrap();
+        let mut new = old.clone();
+        new.render.msaa_samples = 4;
+        new.set_package_value("racer", "name", &SettingValue::String("Alice".to_string()));
+        new.set_package_value("racer", "laps", &SettingValue::I32(3));
+        new.packages.get_mut("racer").unwrap().remove("volume");
+
+        let changes = changed_settings(&old, &new)
+            .into_iter()
+            .map(|change| {
+                (
+                    change.get_ref(setting_package()).cloned(),
+                    change.get_ref(setting_key()).cloned(),
+                    change.get_ref(setting_value()).cloned(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let racer = || Some("racer".to_string());
+        assert_eq!(
+            changes,
+            vec![
+                (None, Some("render".to_string()), None),
+                (racer(), Some("laps".to_string()), Some("3".to_string())),
+                (racer(), Some("name".to_string()), Some("Alice".to_string())),
+                (racer(), Some("volume".to_string()), None),
+            ]
+        );
+        assert!(changed_settings(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn panel_layouts() {
+        let settings = Settings::parse("[ui.panels.log_console]\ndock = \"bottom\"\nheight = 200.0\n").unwrap();
+        let layout = settings.ui.panels["log_console"];
+        assert_eq!(layout, PanelLayout { dock: PanelDock::Bottom, height: 200., ..Default::default() });
+        assert_eq!(Settings::parse(&toml::to_string(&settings).unwrap()).unwrap(), settings);
+    }
+}
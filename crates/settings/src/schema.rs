@@ -0,0 +1,137 @@
+use ambient_project::{Manifest, SettingType};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::Settings;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SettingValue {
+    Bool(bool),
+    I32(i32),
+    F32(f32),
+    String(String),
+}
+impl SettingValue {
+    /// Converts a value from a manifest or settings file; returns None if it isn't of the given type
+    pub fn from_toml(value: &toml::Value, ty: SettingType) -> Option<Self> {
+        Some(match (ty, value) {
+            (SettingType::Bool, toml::Value::Boolean(value)) => Self::Bool(*value),
+            (SettingType::I32, toml::Value::Integer(value)) => Self::I32((*value).try_into().ok()?),
+            (SettingType::F32, toml::Value::Float(value)) => Self::F32(*value as f32),
+            (SettingType::F32, toml::Value::Integer(value)) => Self::F32(*value as f32),
+            (SettingType::String, toml::Value::String(value)) => Self::String(value.clone()),
+            _ => return None,
+        })
+    }
+    pub fn to_toml(&self) -> toml::Value {
+        match self {
+            Self::Bool(value) => toml::Value::Boolean(*value),
+            Self::I32(value) => toml::Value::Integer(*value as i64),
+            Self::F32(value) => toml::Value::Float(*value as f64),
+            Self::String(value) => toml::Value::String(value.clone()),
+        }
+    }
+}
+/// Formats the value the way the guest API returns it: `true`, `42`, `0.5`, or the string itself
+impl std::fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::I32(value) => write!(f, "{value}"),
+            Self::F32(value) => write!(f, "{value}"),
+            Self::String(value) => f.write_str(value),
+        }
+    }
+}
+
+/// Formats a value from a settings file like [SettingValue]'s [Display](std::fmt::Display)
+pub(crate) fn toml_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// A setting declared by a package; see [ambient_project::Setting]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageSetting {
+    pub key: String,
+    pub name: String,
+    pub description: String,
+    pub default: SettingValue,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+impl PackageSetting {
+    /// Keeps numeric values within the declared range
+    pub fn clamp(&self, value: SettingValue) -> SettingValue {
+        let (min, max) = (self.min.unwrap_or(f64::MIN), self.max.unwrap_or(f64::MAX));
+        match value {
+            SettingValue::I32(value) => SettingValue::I32((value as f64).clamp(min, max) as i32),
+            SettingValue::F32(value) => SettingValue::F32((value as f64).clamp(min, max) as f32),
+            value => value,
+        }
+    }
+}
+
+/// The settings a package declares in its manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageSettingsSchema {
+    /// The id of the package, which is also the name of its section in the settings file
+    pub package: String,
+    pub name: String,
+    pub settings: Vec<PackageSetting>,
+}
+impl PackageSettingsSchema {
+    pub fn from_manifest(manifest: &Manifest) -> anyhow::Result<Self> {
+        let mut settings = manifest
+            .settings
+            .iter()
+            .map(|(key, setting)| {
+                let default = SettingValue::from_toml(&setting.default, setting.type_)
+                    .with_context(|| format!("The default of setting {key} is not a {:?}", setting.type_))?;
+                anyhow::Ok(PackageSetting {
+                    key: key.to_string(),
+                    name: setting.name.clone(),
+                    description: setting.description.clone(),
+                    default,
+                    min: setting.min,
+                    max: setting.max,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        settings.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(Self {
+            package: manifest.project.id.to_string(),
+            name: manifest.project.name.clone().unwrap_or_else(|| manifest.project.id.to_string()),
+            settings,
+        })
+    }
+}
+
+impl Settings {
+    /// The user's value for a package setting, or its default if the user hasn't set it or the stored value is invalid
+    pub fn package_value(&self, package: &str, setting: &PackageSetting) -> SettingValue {
+        let ty = match setting.default {
+            SettingValue::Bool(_) => SettingType::Bool,
+            SettingValue::I32(_) => SettingType::I32,
+            SettingValue::F32(_) => SettingType::F32,
+            SettingValue::String(_) => SettingType::String,
+        };
+        self.packages
+            .get(package)
+            .and_then(|values| values.get(&setting.key))
+            .and_then(|value| SettingValue::from_toml(value, ty))
+            .map(|value| setting.clamp(value))
+            .unwrap_or_else(|| setting.default.clone())
+    }
+    /// The value of a package setting as text, like [Settings::package_value], or None if the package doesn't declare it
+    pub fn package_value_text(&self, schemas: &[PackageSettingsSchema], package: &str, key: &str) -> Option<String> {
+        let schema = schemas.iter().find(|schema| schema.package == package)?;
+        let setting = schema.settings.iter().find(|setting| setting.key == key)?;
+        Some(self.package_value(package, setting).to_string())
+    }
+    pub fn set_package_value(&mut self, package: &str, key: &str, value: &SettingValue) {
+        self.packages.entry(package.to_string()).or_default().insert(key.to_string(), value.to_toml());
+    }
+}
@@ -162,7 +162,7 @@ fn scattering(depth: f32, pos: vec3<f32>, dir: vec3<f32>) -> vec3<f32> {
     (
           total_ray * beta_ray * phase_ray
         + total_mie * beta_mie * phase_mie
-    ) * 20.0;
+    ) * global_params.sky_intensity;
 
     return result;
 }
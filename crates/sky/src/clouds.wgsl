@@ -149,3 +149,10 @@ fn fs_forward_main(in: VertexOutput) -> @location(0) vec4<f32> {
 
     return vec4<f32>(apply_fog(color, global_params.camera_position.xyz, in.world_position.xyz), 1.0);
 }
+
+// The sky isn't pickable
+@fragment
+fn fs_picking_main(in: VertexOutput) -> @location(0) vec4<u32> {
+    discard;
+    return vec4<u32>(0u);
+}
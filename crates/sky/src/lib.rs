@@ -20,12 +20,33 @@ use wgpu::{BindGroup, BufferUsages};
 
 use self::tree::*;
 
+pub mod time_of_day;
 mod tree;
 
 components!("rendering", {
     cloud_state: CloudState,
     @[Debuggable, Networked, Store, Name["Sky"], Description["Add a realistic skybox to the scene."]]
     sky: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Time of day"],
+        Description["The time of day, in hours from 0 to 24: the sun rises at 6 and sets at 18.\nSet on the synchronized resources, it lights the scene through the suns with a `day_night_cycle`. Defaults to 12."]
+    ]
+    time_of_day: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Day length"],
+        Description["How long a whole day lasts, in seconds. If set on the synchronized resources, the server advances the `time_of_day` by itself."]
+    ]
+    day_length: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Day night cycle"],
+        Description["If attached to a `sun`, its direction and its light, the fog, the brightness of the `sky` and the image-based lighting follow the `time_of_day`.\nAt night, the light comes from the moon."]
+    ]
+    day_night_cycle: (),
+    /// The time of day the environment map of a sun with a `day_night_cycle` was captured at
+    day_night_environment_time: f32,
 });
 
 #[derive(Debug, Clone)]
@@ -48,10 +69,15 @@ impl CloudState {
     }
 }
 
+pub fn server_systems() -> SystemGroup {
+    time_of_day::server_systems()
+}
+
 pub fn systems() -> SystemGroup {
     SystemGroup::new(
         "sky",
         vec![
+            Box::new(time_of_day::systems()),
             query(sky()).excl(renderer_shader()).to_system(|q, world, qs, _| {
                 let assets = world.resource(asset_cache()).clone();
                 for (id, _) in q.collect_cloned(world, qs) {
@@ -202,6 +228,7 @@ impl SyncAssetKey<Arc<RendererShader>> for CloudShaderKey {
             fs_forward_main: "fs_forward_main".to_string(),
             fs_shadow_main: "fs_shadow_main".to_string(),
             fs_outline_main: "fs_outlines_main".to_string(),
+            fs_picking_main: "fs_picking_main".to_string(),
             transparent: true,
             double_sided: false,
             depth_write_enabled: true,
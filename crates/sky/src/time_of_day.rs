@@ -0,0 +1,174 @@
+//! The day/night cycle: the server advances the [time_of_day] on the synchronized resources by the [day_length], and clients
+//! light the scene for it through the suns with a [day_night_cycle]. The sun rises in the east (+X) at 6, is highest at 12 and
+//! sets at 18; at night, the light comes from the moon on the opposite side of the sky.
+//!
+//! The image-based lighting follows the sky, by capturing a new environment map every [ENVIRONMENT_INTERVAL] hours.
+use std::{
+    f32::consts::{PI, TAU},
+    sync::Arc,
+};
+
+use ambient_core::{asset_cache, dtime, transform::rotation};
+use ambient_ecs::{query, Entity, FnSystem, SystemGroup, World};
+use ambient_network::ServerWorldExt;
+use ambient_renderer::{
+    environment::{environment_map, EnvironmentMap},
+    fog_color, light_ambient, light_diffuse, sky_intensity,
+};
+use glam::{vec3, Quat, Vec3};
+
+use crate::{day_length, day_night_cycle, day_night_environment_time, time_of_day};
+
+/// The time of day when none is set on the synchronized resources
+const DEFAULT_TIME: f32 = 12.;
+/// How far the path of the sun is tilted from the zenith towards the south
+const TILT: f32 = 0.4;
+/// How many hours of game time pass between two captures of the environment lighting
+pub const ENVIRONMENT_INTERVAL: f32 = 0.25;
+/// The size of the equirectangular image the environment map is captured to
+const ENVIRONMENT_WIDTH: u32 = 64;
+const ENVIRONMENT_HEIGHT: u32 = 32;
+/// The brightness of the `sky` by day
+const SKY_INTENSITY: f32 = 20.;
+
+const NOON_SUNLIGHT: Vec3 = vec3(1., 0.96, 0.9);
+const HORIZON_SUNLIGHT: Vec3 = vec3(1., 0.5, 0.25);
+const MOONLIGHT: Vec3 = vec3(0.04, 0.05, 0.08);
+const DAY_AMBIENT: Vec3 = vec3(0.12, 0.14, 0.18);
+const NIGHT_AMBIENT: Vec3 = vec3(0.01, 0.012, 0.02);
+const DAY_ZENITH: Vec3 = vec3(0.1, 0.2, 0.45);
+const NIGHT_ZENITH: Vec3 = vec3(0.002, 0.003, 0.008);
+const DAY_HORIZON: Vec3 = vec3(0.35, 0.45, 0.55);
+const SUNSET_HORIZON: Vec3 = vec3(0.6, 0.35, 0.2);
+const NIGHT_HORIZON: Vec3 = vec3(0.01, 0.012, 0.02);
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+/// The direction towards the sun at `time`, in hours
+pub fn sun_direction(time: f32) -> Vec3 {
+    let angle = (time - 6.) / 24. * TAU;
+    vec3(angle.cos(), -angle.sin() * TILT.sin(), angle.sin() * TILT.cos())
+}
+
+/// The lighting of the scene at a time of day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayLight {
+    /// The direction the light comes from: the sun's by day, and the moon's by night
+    pub direction: Vec3,
+    pub diffuse: Vec3,
+    pub ambient: Vec3,
+    pub sky_intensity: f32,
+    /// The color of the sky straight up
+    pub zenith: Vec3,
+    /// The color of the sky at the horizon, which is also the color of the fog
+    pub horizon: Vec3,
+}
+impl DayLight {
+    pub fn at(time: f32) -> Self {
+        let sun = sun_direction(time);
+        let day = smoothstep(-0.1, 0.1, sun.z);
+        // The sunlight reddens as it crosses more of the atmosphere
+        let dusk = 1. - smoothstep(0., 0.4, sun.z);
+        // The sun and the moon hand over when both are below the horizon, so that the light never jumps
+        let (direction, diffuse) = if sun.z >= -0.05 {
+            (sun, NOON_SUNLIGHT.lerp(HORIZON_SUNLIGHT, dusk) * smoothstep(-0.05, 0.1, sun.z))
+        } else {
+            (-sun, MOONLIGHT * (1. - smoothstep(-0.2, -0.05, sun.z)))
+        };
+        Self {
+            direction,
+            diffuse,
+            ambient: NIGHT_AMBIENT.lerp(DAY_AMBIENT, day),
+            sky_intensity: SKY_INTENSITY * smoothstep(-0.3, 0.1, sun.z),
+            zenith: NIGHT_ZENITH.lerp(DAY_ZENITH, day),
+            horizon: NIGHT_HORIZON.lerp(DAY_HORIZON.lerp(SUNSET_HORIZON, dusk), day),
+        }
+    }
+
+    /// The radiance of the sky in every direction, as an equirectangular RGBA image. The sun itself is left out, as its light is
+    /// already direct
+    fn environment(&self) -> Vec<f32> {
+        let mut data = Vec::with_capacity((ENVIRONMENT_WIDTH * ENVIRONMENT_HEIGHT * 4) as usize);
+        for y in 0..ENVIRONMENT_HEIGHT {
+            for x in 0..ENVIRONMENT_WIDTH {
+                let phi = ((x as f32 + 0.5) / ENVIRONMENT_WIDTH as f32 - 0.5) * TAU;
+                let theta = (y as f32 + 0.5) / ENVIRONMENT_HEIGHT as f32 * PI;
+                let dir = vec3(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos());
+                let color = if dir.z >= 0. {
+                    let glow = self.diffuse * dir.dot(self.direction).max(0.).powi(8) * 0.5;
+                    self.horizon.lerp(self.zenith, dir.z.sqrt()) + glow
+                } else {
+                    // The ground reflects some of the sky
+                    self.horizon * 0.3
+                };
+                data.extend(color.extend(1.).to_array());
+            }
+        }
+        data
+    }
+}
+
+fn hours_between(a: f32, b: f32) -> f32 {
+    let difference = (a - b).rem_euclid(24.);
+    difference.min(24. - difference)
+}
+
+fn update_suns(world: &mut World) {
+    let time = world.synced_resource(time_of_day()).copied().unwrap_or(DEFAULT_TIME);
+    let light = DayLight::at(time);
+    for id in query(()).incl(day_night_cycle()).collect_ids(world, None) {
+        let sun_rotation = Quat::from_rotation_arc(Vec3::X, light.direction);
+        // The components are all added together, the first time
+        if world.has_component(id, sky_intensity()) {
+            world.set(id, rotation(), sun_rotation).ok();
+            world.set(id, light_diffuse(), light.diffuse).ok();
+            world.set(id, light_ambient(), light.ambient).ok();
+            world.set(id, fog_color(), light.horizon).ok();
+            world.set(id, sky_intensity(), light.sky_intensity).ok();
+        } else {
+            let data = Entity::new()
+                .with(rotation(), sun_rotation)
+                .with(light_diffuse(), light.diffuse)
+                .with(light_ambient(), light.ambient)
+                .with(fog_color(), light.horizon)
+                .with(sky_intensity(), light.sky_intensity);
+            world.add_components(id, data).ok();
+        }
+
+        let captured = world.get(id, day_night_environment_time()).ok();
+        if captured.map_or(true, |captured| hours_between(time, captured) >= ENVIRONMENT_INTERVAL) {
+            let assets = world.resource(asset_cache()).clone();
+            let environment = EnvironmentMap::from_equirect(&assets, ENVIRONMENT_WIDTH, ENVIRONMENT_HEIGHT, &light.environment());
+            world
+                .add_components(id, Entity::new().with(environment_map(), Arc::new(environment)).with(day_night_environment_time(), time))
+                .ok();
+        }
+    }
+}
+
+/// Advances the time of day
+pub fn server_systems() -> SystemGroup {
+    SystemGroup::new(
+        "sky/time_of_day",
+        vec![Box::new(FnSystem::new(|world, _| {
+            let Some(&length) = world.synced_resource(day_length()) else { return };
+            if length <= 0. {
+                return;
+            }
+            let Some(id) = world.synced_resource_entity() else { return };
+            let dt = *world.resource(dtime());
+            match world.get_mut(id, time_of_day()) {
+                Ok(time) => *time = (*time + dt / length * 24.).rem_euclid(24.),
+                Err(_) => world.add_component(id, time_of_day(), DEFAULT_TIME).unwrap(),
+            }
+        }))],
+    )
+}
+
+/// Lights the scene for the time of day
+pub fn systems() -> SystemGroup {
+    SystemGroup::new("sky/day_night_cycle", vec![Box::new(FnSystem::new(|world, _| update_suns(world)))])
+}
@@ -1,11 +1,13 @@
 use std::{
+    collections::HashSet,
     marker::PhantomData,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
 use anyhow::Context;
 use convert_case::{Case, Casing};
+use parking_lot::RwLock;
 use percent_encoding::percent_decode_str;
 use rand::seq::SliceRandom;
 use relative_path::{RelativePath, RelativePathBuf};
@@ -29,6 +31,89 @@ impl SyncAssetKey<AbsAssetUrl> for ServerBaseUrlKey {
     }
 }
 
+/// The directory on disk that the urls under [ServerBaseUrlKey] are served from, if this process is running the server
+#[derive(Debug, Clone)]
+pub struct ServerContentDirKey;
+impl SyncAssetKey<Option<PathBuf>> for ServerContentDirKey {
+    fn load(&self, _assets: AssetCache) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Mirrors that serve the same content as a base url, so that downloads can fall back to another mirror when one fails.
+/// Filled in from the build metadata by [crate::download_asset::load_build_metadata].
+#[derive(Debug, Clone)]
+pub struct AssetMirrorsKey;
+impl SyncAssetKey<Arc<AssetMirrors>> for AssetMirrorsKey {
+    fn load(&self, _assets: AssetCache) -> Arc<AssetMirrors> {
+        Default::default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AssetMirrors(RwLock<Vec<MirrorSet>>);
+#[derive(Debug)]
+struct MirrorSet {
+    origin: AbsAssetUrl,
+    mirrors: Vec<AbsAssetUrl>,
+    /// The mirror that last served a download successfully
+    healthy: Option<usize>,
+    /// The mirrors that failed since they last served a download successfully
+    failed: HashSet<usize>,
+}
+impl MirrorSet {
+    /// The mirror (or `None` for the origin) that `url` was resolved to, and the path relative to it
+    fn find<'a>(&self, url: &'a str) -> Option<(Option<usize>, &'a str)> {
+        if let Some(path) = url.strip_prefix(self.origin.0.as_str()) {
+            return Some((None, path));
+        }
+        self.mirrors.iter().enumerate().find_map(|(i, mirror)| url.strip_prefix(mirror.0.as_str()).map(|path| (Some(i), path)))
+    }
+}
+impl AssetMirrors {
+    /// Registers `mirrors` as serving the same content as `origin`
+    pub fn add(&self, origin: AbsAssetUrl, mirrors: Vec<AbsAssetUrl>) {
+        let origin = origin.as_directory();
+        let mirrors = mirrors.into_iter().map(|mirror| mirror.as_directory()).collect();
+        let mut sets = self.0.write();
+        sets.retain(|set| set.origin != origin);
+        sets.push(MirrorSet { origin, mirrors, healthy: None, failed: HashSet::new() });
+    }
+    /// The urls to try to download `url` from, in order: the mirror that last worked, the other mirrors that haven't failed,
+    /// the origin, and finally the mirrors that have failed. Just `url` if it isn't served by any mirror.
+    pub fn candidates(&self, url: &AbsAssetUrl) -> Vec<AbsAssetUrl> {
+        let sets = self.0.read();
+        let Some((set, path)) = sets.iter().find_map(|set| set.find(url.0.as_str()).map(|(_, path)| (set, path))) else {
+            return vec![url.clone()];
+        };
+        let (failed, ok): (Vec<_>, Vec<_>) = (0..set.mirrors.len()).partition(|i| set.failed.contains(i));
+        let ok = set.healthy.into_iter().chain(ok.into_iter().filter(|&i| Some(i) != set.healthy));
+        ok.map(|i| &set.mirrors[i])
+            .chain(std::iter::once(&set.origin))
+            .chain(failed.iter().map(|&i| &set.mirrors[i]))
+            .filter_map(|base| base.join(path).ok())
+            .collect()
+    }
+    /// Remembers whether downloading from `url` worked, so that the next downloads start from a mirror that works
+    pub fn report(&self, url: &AbsAssetUrl, success: bool) {
+        let mut sets = self.0.write();
+        let Some((set, Some(mirror))) =
+            sets.iter_mut().find_map(|set| set.find(url.0.as_str()).map(|(mirror, _)| mirror).map(|mirror| (set, mirror)))
+        else {
+            return;
+        };
+        if success {
+            set.healthy = Some(mirror);
+            set.failed.remove(&mirror);
+        } else {
+            if set.healthy == Some(mirror) {
+                set.healthy = None;
+            }
+            set.failed.insert(mirror);
+        }
+    }
+}
+
 /// This is a thin wrapper around Url, which is guaranteed to always
 /// be an absolute url (including when pointing to a local file).
 ///
@@ -108,6 +193,33 @@ impl AbsAssetUrl {
         Self(url)
     }
 
+    /// Returns the path in the content directory of this server which this url points to, if it's served by this server.
+    /// Urls whose path would leave the content directory, such as ones with `..` in them, are rejected
+    pub fn to_local_path(&self, assets: &AssetCache) -> anyhow::Result<Option<PathBuf>> {
+        let Some(content_dir) = ServerContentDirKey.get(assets) else { return Ok(None) };
+        let base_url = ServerBaseUrlKey.get(assets).to_string();
+        let Some(path) = self.0.as_str().strip_prefix(&base_url) else { return Ok(None) };
+        let path = percent_decode_str(path).decode_utf8_lossy();
+        let path = Path::new(path.trim_start_matches('/'));
+        anyhow::ensure!(
+            path.components().all(|component| matches!(component, Component::Normal(_))),
+            "Invalid path {path:?} in the content url {self}"
+        );
+        Ok(Some(content_dir.join(path)))
+    }
+    /// Like [Self::to_local_path], but also checks that the path stays in the content directory once symlinks are
+    /// resolved, so that it's safe to write to
+    #[cfg(not(target_os = "unknown"))]
+    pub fn to_writable_local_path(&self, assets: &AssetCache) -> anyhow::Result<Option<PathBuf>> {
+        let Some(path) = self.to_local_path(assets)? else { return Ok(None) };
+        let content_dir = ServerContentDirKey.get(assets).unwrap().canonicalize().context("Failed to resolve the content directory")?;
+        // The file, and the directories it goes in, may not exist yet
+        let existing = path.ancestors().find(|ancestor| ancestor.exists()).context("No part of the path exists")?;
+        let existing = existing.canonicalize().with_context(|| format!("Failed to resolve {existing:?}"))?;
+        anyhow::ensure!(existing.starts_with(&content_dir), "{path:?} is outside of the content directory");
+        Ok(Some(path))
+    }
+
     #[cfg(target_os = "unknown")]
     pub fn to_file_path(&self) -> anyhow::Result<Option<PathBuf>> {
         Ok(None)
@@ -473,6 +585,9 @@ pub enum AssetType {
     /// Represents a vorbis backed file
     VorbisTrack,
     SoundGraph,
+
+    /// The index of a world split into cells, which are streamed in around players
+    StreamingWorld,
 }
 
 impl AssetType {
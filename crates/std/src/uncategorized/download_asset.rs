@@ -1,16 +1,17 @@
-use std::{marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
+use std::{collections::HashMap, marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
 
 use ambient_sys::task::wasm_nonsend;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use futures::Future;
+use parking_lot::RwLock;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::Semaphore;
 
 use crate::{
     asset_cache::{AssetCache, AssetKeepalive, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKey, SyncAssetKeyExt},
-    asset_url::AbsAssetUrl,
+    asset_url::{AbsAssetUrl, AssetMirrorsKey},
     mesh::Mesh,
 };
 
@@ -47,9 +48,15 @@ impl From<anyhow::Error> for AssertErrorString {
 #[derive(Clone, Debug)]
 pub struct AssetsCacheDir;
 impl SyncAssetKey<PathBuf> for AssetsCacheDir {
+    #[cfg(not(target_os = "unknown"))]
     fn load(&self, _assets: AssetCache) -> PathBuf {
         std::env::current_dir().unwrap().join("tmp")
     }
+    /// On the web, the cache is stored in IndexedDB, where paths are only keys
+    #[cfg(target_os = "unknown")]
+    fn load(&self, _assets: AssetCache) -> PathBuf {
+        PathBuf::from("cache")
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -68,11 +75,116 @@ impl SyncAssetKey<reqwest::Client> for ReqwestClientKey {
     }
 }
 
+/// The maximum number of downloads that can run at the same time. Defaults to 5; insert another value before the first download to change it.
+#[derive(Clone, Debug)]
+pub struct MaxConcurrentDownloadsKey;
+impl SyncAssetKey<usize> for MaxConcurrentDownloadsKey {
+    fn load(&self, _assets: AssetCache) -> usize {
+        5
+    }
+}
+
+/// The SHA-256 hashes that downloaded assets are expected to have, by their url. Filled in by [load_build_metadata].
+#[derive(Clone, Debug)]
+pub struct AssetHashesKey;
+impl SyncAssetKey<Arc<RwLock<HashMap<String, String>>>> for AssetHashesKey {
+    fn load(&self, _assets: AssetCache) -> Arc<RwLock<HashMap<String, String>>> {
+        Default::default()
+    }
+}
+
+/// Metadata about a built project, written to `build/metadata.json` by the build
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildMetadata {
+    /// The SHA-256 hashes of the built files, in lowercase hex, by their path relative to the build directory
+    #[serde(default)]
+    pub asset_hashes: HashMap<String, String>,
+    /// Other base urls the build directory is served from, e.g. by a CDN
+    #[serde(default)]
+    pub mirrors: Vec<AbsAssetUrl>,
+}
+impl BuildMetadata {
+    pub const FILE_NAME: &'static str = "metadata.json";
+}
+
+/// Downloads the [BuildMetadata] of the project served at `base_url`, so that the assets downloaded from it are verified against their
+/// hashes, and fall back to its mirrors when they fail to download. The mirrors are raced to find one that responds.
+pub async fn load_build_metadata(assets: &AssetCache, base_url: &AbsAssetUrl) -> anyhow::Result<()> {
+    let url = base_url.as_directory().join(BuildMetadata::FILE_NAME)?;
+    let metadata: BuildMetadata = if let Some(path) = url.to_file_path()? {
+        serde_json::from_slice(&ambient_sys::fs::read(path).await?)?
+    } else {
+        download(assets, url.0.clone(), |resp| async { Ok(resp.json().await?) }).await?
+    };
+    {
+        let hashes = AssetHashesKey.get(assets);
+        let mut hashes = hashes.write();
+        for (path, hash) in metadata.asset_hashes {
+            hashes.insert(base_url.as_directory().join(path)?.to_string(), hash);
+        }
+    }
+
+    if !metadata.mirrors.is_empty() {
+        let mirrors = AssetMirrorsKey.get(assets);
+        mirrors.add(base_url.clone(), metadata.mirrors.clone());
+        if let Some(fastest) = race_mirrors(assets, &metadata.mirrors).await {
+            log::info!("Downloading assets from mirror {fastest}");
+            mirrors.report(&fastest.as_directory().join(BuildMetadata::FILE_NAME)?, true);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the first of `mirrors` to respond successfully to a request for its build metadata
+async fn race_mirrors(assets: &AssetCache, mirrors: &[AbsAssetUrl]) -> Option<AbsAssetUrl> {
+    let assets = assets.clone();
+    let mirrors = mirrors.to_vec();
+    wasm_nonsend(move || async move {
+        let client = ReqwestClientKey.get(&assets);
+        let requests = mirrors.into_iter().map(|mirror| {
+            let client = client.clone();
+            Box::pin(async move {
+                let url = mirror.as_directory().join(BuildMetadata::FILE_NAME)?;
+                client.head(url.0).send().await?.error_for_status()?;
+                anyhow::Ok(mirror)
+            })
+        });
+        futures::future::select_ok(requests).await.ok().map(|(mirror, _)| mirror)
+    })
+    .await
+}
+
+/// Checks that `digest` matches the hash listed for `url` in the build metadata, if there is one
+fn verify_hash(assets: &AssetCache, url: &str, digest: ring::digest::Digest) -> anyhow::Result<()> {
+    let Some(expected) = AssetHashesKey.get(assets).read().get(url).cloned() else { return Ok(()) };
+    let actual = data_encoding::HEXLOWER.encode(digest.as_ref());
+    if actual != expected {
+        return Err(anyhow!("Integrity check failed for {url}: expected SHA-256 {expected}, got {actual}"));
+    }
+    Ok(())
+}
+
+const MAX_RETRIES: u32 = 8;
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
 /// Download with retries and a global rate limiting sempahore
 pub(crate) async fn download<T: 'static + Send, F: Future<Output = anyhow::Result<T>>>(
     assets: &AssetCache,
     url: impl reqwest::IntoUrl,
     map: impl 'static + Send + Fn(reqwest::Response) -> F,
+) -> anyhow::Result<T> {
+    download_with(assets, url, |req| req, map).await
+}
+
+/// Like [download], but `request` can change the request before each attempt is sent (e.g. to resume a partial download).
+///
+/// If the url is served by mirrors (see [crate::asset_url::AssetMirrors]), each of them is tried in turn. Failed requests, server errors and errors
+/// from `map` are retried with exponential backoff; other bad status codes are only retried on the other mirrors.
+pub(crate) async fn download_with<T: 'static + Send, F: Future<Output = anyhow::Result<T>>>(
+    assets: &AssetCache,
+    url: impl reqwest::IntoUrl,
+    request: impl 'static + Send + Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
+    map: impl 'static + Send + Fn(reqwest::Response) -> F,
 ) -> anyhow::Result<T> {
     let url_str = url.as_str().to_string();
     let url = url.into_url()?;
@@ -82,31 +194,60 @@ pub(crate) async fn download<T: 'static + Send, F: Future<Output = anyhow::Resul
     wasm_nonsend(move || async move {
         let client = ReqwestClientKey.get(&assets);
         let url_short = if url_str.len() > 200 { format!("{}...", &url_str[..200]) } else { url_str.to_string() };
+        let mirrors = AssetMirrorsKey.get(&assets);
+        let candidates = mirrors.candidates(&AbsAssetUrl(url));
+        let attempts = MAX_RETRIES.max(candidates.len() as u32);
+
+        let mut last_err = None;
+        for i in 0..attempts {
+            // The other mirrors are tried right away; the backoff starts once they have all failed
+            if i as usize >= candidates.len() {
+                let backoff = Duration::from_millis(100 * 2u64.pow(i - candidates.len() as u32)).min(MAX_BACKOFF);
+                log::warn!("Retrying download of {url_str} in {backoff:?} ({i}/{attempts})");
+                ambient_sys::time::sleep(backoff).await;
+            }
+            let candidate = &candidates[i as usize % candidates.len()];
 
-        let max_retries = 12;
-        for i in 0..max_retries {
             let semaphore = DownloadSemaphore.get(&assets);
             log::info!("download [pending ] {}", url_short);
             let _permit = semaphore.acquire().await.unwrap();
             log::info!("download [download] {}", url_short);
-            let resp = client.get(url.clone()).send().await.with_context(|| format!("Failed to download {url_str}"))?;
-            if !resp.status().is_success() {
-                log::warn!("Request for {} failed: {:?}", url_str, resp.status());
-                return Err(anyhow!("Downloading {url_str} failed, bad status code: {:?}", resp.status()));
+            let resp = match request(client.get(candidate.0.clone())).send().await {
+                Ok(resp) => resp,
+                Err(err) => {
+                    log::warn!("Request for {candidate} failed: {err:?}");
+                    mirrors.report(candidate, false);
+                    last_err = Some(anyhow::Error::new(err).context(format!("Failed to download {candidate}")));
+                    continue;
+                }
+            };
+            let status = resp.status();
+            if !status.is_success() {
+                log::warn!("Request for {} failed: {:?}", candidate, status);
+                mirrors.report(candidate, false);
+                let err = anyhow!("Downloading {candidate} failed, bad status code: {:?}", status);
+                let untried_mirrors = (i as usize + 1) < candidates.len();
+                if untried_mirrors || status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    last_err = Some(err);
+                    continue;
+                }
+                return Err(err);
             }
             match map(resp).await {
                 Ok(res) => {
                     log::info!("download [complete] {}", url_short);
+                    mirrors.report(candidate, true);
                     return Ok(res);
                 }
                 Err(err) => {
-                    log::warn!("Failed to read body of {url_str}, retrying ({i}/{max_retries}): {:?}", err);
-                    ambient_sys::time::sleep(Duration::from_millis(2u64.pow(i))).await;
+                    log::warn!("Failed to read body of {candidate}: {:?}", err);
+                    mirrors.report(candidate, false);
+                    last_err = Some(err);
                 }
             }
         }
 
-        Err(anyhow::anyhow!("Failed to download body of {}", url_str))
+        Err(last_err.unwrap_or_else(|| anyhow!("Failed to download {url_str}")).context(format!("Gave up after {attempts} attempts")))
     })
     .await
 }
@@ -140,7 +281,21 @@ impl AsyncAssetKey<AssetResult<Arc<Vec<u8>>>> for BytesFromUrl {
             return Ok(Arc::new(ambient_sys::fs::read(path).await.context(format!("Failed to read file at: {:}", self.url.0))?));
         }
 
-        let body = download(&assets, self.url.0.clone(), |resp| async { Ok(resp.bytes().await?) }).await?.to_vec();
+        let body = download(&assets, self.url.0.clone(), {
+            let assets = assets.clone();
+            let url = self.url.to_string();
+            move |resp| {
+                let assets = assets.clone();
+                let url = url.clone();
+                async move {
+                    let body = resp.bytes().await?;
+                    verify_hash(&assets, &url, ring::digest::digest(&ring::digest::SHA256, &body))?;
+                    Ok(body)
+                }
+            }
+        })
+        .await?
+        .to_vec();
         assert!(!body.is_empty());
         Ok(Arc::new(body))
     }
@@ -169,8 +324,21 @@ impl AsyncAssetKey<AssetResult<Arc<PathBuf>>> for BytesFromUrlCachedPath {
         AssetKeepalive::Forever
     }
 
-    async fn load(self, _: AssetCache) -> AssetResult<Arc<PathBuf>> {
-        return Err(anyhow::anyhow!("Asset caching is not supported on wasm").into());
+    async fn load(self, assets: AssetCache) -> AssetResult<Arc<PathBuf>> {
+        let mut path = self.url.absolute_cache_path(&assets);
+        // The cache persists across reloads of the page, so assets which changed since, e.g. in a new build of the
+        // project, are cached separately
+        if let Some(hash) = AssetHashesKey.get(&assets).read().get(&self.url.to_string()) {
+            path = PathBuf::from(format!("{}.{hash}", path.display()));
+        }
+
+        let cached = ambient_sys::fs::try_exists(&path).await.context(format!("Failed to look up cached asset: {path:?}"))?;
+        if !cached {
+            let body = BytesFromUrl::new(self.url.clone(), false).get(&assets).await?;
+            ambient_sys::fs::write(&path, &*body).await.context(format!("Failed to cache asset at {path:?}"))?;
+            log::info!("Cached asset at {:?}", path);
+        }
+        Ok(Arc::new(path))
     }
 }
 
@@ -192,17 +360,45 @@ impl AsyncAssetKey<AssetResult<Arc<PathBuf>>> for BytesFromUrlCachedPath {
             dir.pop();
             std::fs::create_dir_all(&dir).context(format!("Failed to create asset dir: {dir:?}"))?;
             let tmp_path = path.with_extension(".downloading");
-            download(&assets, self.url.0.clone(), {
+            // A partial download left by a failed attempt (or an earlier run) is resumed with a range request
+            let resume = {
                 let tmp_path = tmp_path.clone();
+                move |req: reqwest::RequestBuilder| match std::fs::metadata(&tmp_path) {
+                    Ok(metadata) if metadata.len() > 0 => req.header(reqwest::header::RANGE, format!("bytes={}-", metadata.len())),
+                    _ => req,
+                }
+            };
+            download_with(&assets, self.url.0.clone(), resume, {
+                let tmp_path = tmp_path.clone();
+                let assets = assets.clone();
+                let url = self.url.to_string();
                 move |mut resp| {
                     let tmp_path = tmp_path.clone();
+                    let assets = assets.clone();
+                    let url = url.clone();
                     async move {
-                        let mut file = tokio::fs::File::create(&tmp_path).await.context(format!("Failed to create file: {tmp_path:?}"))?;
+                        let resumed = resp.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+                        let mut file = tokio::fs::OpenOptions::new()
+                            .create(true)
+                            .write(true)
+                            .append(resumed)
+                            .truncate(!resumed)
+                            .open(&tmp_path)
+                            .await
+                            .context(format!("Failed to create file: {tmp_path:?}"))?;
                         use std::borrow::BorrowMut;
                         while let Some(mut item) = resp.chunk().await.context("Failed to download chunk")? {
                             file.write_all_buf(item.borrow_mut()).await.context("Failed to write to tmp file")?;
                         }
                         file.flush().await.context("Failed to flush tmp file")?;
+                        drop(file);
+
+                        let digest = hash_file(&tmp_path).await?;
+                        if let Err(err) = verify_hash(&assets, &url, digest) {
+                            // Don't resume from corrupted data on the next attempt
+                            tokio::fs::remove_file(&tmp_path).await.ok();
+                            return Err(err);
+                        }
                         Ok(())
                     }
                 }
@@ -216,6 +412,22 @@ impl AsyncAssetKey<AssetResult<Arc<PathBuf>>> for BytesFromUrlCachedPath {
     }
 }
 
+#[cfg(not(target_os = "unknown"))]
+async fn hash_file(path: &std::path::Path) -> anyhow::Result<ring::digest::Digest> {
+    use tokio::io::AsyncReadExt;
+    let mut file = tokio::fs::File::open(path).await.context(format!("Failed to open file: {path:?}"))?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await.context(format!("Failed to read file: {path:?}"))?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buf[..read]);
+    }
+    Ok(context.finish())
+}
+
 /// Limit the number of concurent file reads to 10
 #[derive(Debug)]
 struct FileReadSemaphore;
@@ -225,12 +437,12 @@ impl SyncAssetKey<Arc<Semaphore>> for FileReadSemaphore {
     }
 }
 
-/// Limit the number of concurent downloads to 5
+/// Limit the number of concurent downloads to [MaxConcurrentDownloadsKey]
 #[derive(Debug)]
 struct DownloadSemaphore;
 impl SyncAssetKey<Arc<Semaphore>> for DownloadSemaphore {
-    fn load(&self, _assets: AssetCache) -> Arc<Semaphore> {
-        Arc::new(Semaphore::new(5))
+    fn load(&self, assets: AssetCache) -> Arc<Semaphore> {
+        Arc::new(Semaphore::new(MaxConcurrentDownloadsKey.get(&assets)))
     }
 }
 
@@ -0,0 +1,21 @@
+//! Native and browser dialogs for picking files.
+
+/// A file the user picked.
+#[derive(Debug, Clone)]
+pub struct PickedFile {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Asks the user to pick a file, and reads it.
+///
+/// `filters` are pairs of a description and the extensions it allows, like `("Images", ["png", "jpg"])`. Any file can be
+/// picked if there are none. Returns None if the dialog was cancelled.
+pub async fn open_file(filters: &[(String, Vec<String>)]) -> Option<PickedFile> {
+    let mut dialog = rfd::AsyncFileDialog::new();
+    for (name, extensions) in filters {
+        dialog = dialog.add_filter(name, extensions);
+    }
+    let file = dialog.pick_file().await?;
+    Some(PickedFile { name: file.file_name(), bytes: file.read().await })
+}
@@ -1,4 +1,5 @@
 pub mod control;
+pub mod file_dialog;
 mod missed_tick;
 pub mod task;
 pub mod time;
@@ -29,3 +30,13 @@ pub(crate) mod platform;
 ///
 /// **Note**: wasm file io always return Err, but do *not* panic.
 pub use platform::fs;
+
+/// Reading and writing text in the system clipboard.
+///
+/// **Note**: in browsers, only [clipboard::read_text] can see what was copied outside of the application.
+pub use platform::clipboard;
+
+/// Saving files the application created, like screenshots.
+///
+/// **Note**: on native, the files are written to disk, and in browsers they are downloaded by the user.
+pub use platform::export;
@@ -0,0 +1,18 @@
+/// Returns the text in the clipboard, if any.
+pub fn get_text() -> Option<String> {
+    arboard::Clipboard::new().ok()?.get_text().ok()
+}
+
+/// Returns the text in the clipboard, if any.
+///
+/// On native, this is the same as [get_text].
+pub async fn read_text() -> Option<String> {
+    get_text()
+}
+
+/// Replaces the contents of the clipboard with `text`.
+pub fn set_text(text: &str) {
+    if let Err(err) = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text.to_string())) {
+        tracing::warn!("Failed to set the clipboard: {err}");
+    }
+}
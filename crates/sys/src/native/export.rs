@@ -0,0 +1,9 @@
+use std::{io, path::Path};
+
+/// Writes `bytes` to the file at `path`, creating its parent directories if needed.
+pub async fn export_file(path: &Path, bytes: Vec<u8>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, bytes).await
+}
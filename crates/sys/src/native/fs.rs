@@ -1 +1 @@
-pub use tokio::fs::{read, read_to_string, write};
+pub use tokio::fs::{read, read_to_string, try_exists, write};
@@ -1,3 +1,5 @@
+pub mod clipboard;
+pub mod export;
 pub mod fs;
 pub mod task;
 pub mod time;
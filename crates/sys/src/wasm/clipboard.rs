@@ -0,0 +1,37 @@
+use parking_lot::{const_mutex, Mutex};
+use wasm_bindgen_futures::JsFuture;
+
+/// The browser only lets the clipboard be read asynchronously, and with the user's permission, so the synchronous
+/// functions use the last text set from here instead.
+static LAST_SET: Mutex<Option<String>> = const_mutex(None);
+
+fn clipboard() -> Option<web_sys::Clipboard> {
+    web_sys::window()?.navigator().clipboard()
+}
+
+/// Returns the last text set with [set_text].
+///
+/// Use [read_text] to read what was copied from outside of the application.
+pub fn get_text() -> Option<String> {
+    LAST_SET.lock().clone()
+}
+
+/// Returns the text in the clipboard, if any.
+///
+/// The browser may ask the user for permission first.
+pub async fn read_text() -> Option<String> {
+    let text = JsFuture::from(clipboard()?.read_text()).await.ok()?;
+    text.as_string()
+}
+
+/// Replaces the contents of the clipboard with `text`.
+pub fn set_text(text: &str) {
+    *LAST_SET.lock() = Some(text.to_string());
+    let Some(clipboard) = clipboard() else { return };
+    let promise = clipboard.write_text(text);
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(err) = JsFuture::from(promise).await {
+            tracing::warn!("Failed to set the clipboard: {err:?}");
+        }
+    });
+}
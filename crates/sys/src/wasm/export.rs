@@ -0,0 +1,29 @@
+use std::{io, path::Path};
+
+use wasm_bindgen::{JsCast, JsValue};
+
+fn js_error(err: JsValue) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{err:?}"))
+}
+
+/// Has the browser download `bytes` as a file named after the last component of `path`.
+pub async fn export_file(path: &Path, bytes: Vec<u8>) -> io::Result<()> {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let document = web_sys::window().and_then(|window| window.document()).ok_or_else(|| js_error("No document".into()))?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&bytes[..]));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(js_error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+    let anchor = document
+        .create_element("a")
+        .map_err(js_error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(JsValue::from)
+        .map_err(js_error)?;
+    anchor.set_href(&url);
+    anchor.set_download(&name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url).map_err(js_error)
+}
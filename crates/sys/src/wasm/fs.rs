@@ -1,13 +1,118 @@
-use std::{io, path::Path};
+//! The browser has no file system, so files are stored in IndexedDB by their path instead, which persists them across
+//! reloads of the page. Directories don't exist; a path is only a key.
+use std::{cell::RefCell, io, path::Path};
 
-pub async fn read(_path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
-    Err(io::Error::new(io::ErrorKind::Unsupported, "File IO on wasm it not supported"))
+use futures::channel::oneshot;
+use js_sys::Uint8Array;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+use crate::task::wasm_nonsend;
+
+const DATABASE: &str = "ambient";
+const DATABASE_VERSION: u32 = 1;
+const STORE: &str = "files";
+
+thread_local! {
+    static OPEN_DATABASE: RefCell<Option<IdbDatabase>> = RefCell::new(None);
+}
+
+fn js_error(err: JsValue) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{err:?}"))
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Waits for `request` to complete, and returns its result
+async fn complete(request: &IdbRequest) -> io::Result<JsValue> {
+    let (tx, rx) = oneshot::channel();
+    let mut tx = Some(tx);
+    let done = Closure::<dyn FnMut()>::new(move || {
+        if let Some(tx) = tx.take() {
+            tx.send(()).ok();
+        }
+    });
+    request.set_onsuccess(Some(done.as_ref().unchecked_ref()));
+    request.set_onerror(Some(done.as_ref().unchecked_ref()));
+    rx.await.map_err(|_| io::Error::new(io::ErrorKind::Interrupted, "The IndexedDB request was dropped"))?;
+
+    if let Ok(Some(err)) = request.error() {
+        return Err(js_error(err.into()));
+    }
+    request.result().map_err(js_error)
+}
+
+async fn database() -> io::Result<IdbDatabase> {
+    if let Some(database) = OPEN_DATABASE.with(|database| database.borrow().clone()) {
+        return Ok(database);
+    }
+
+    let factory = web_sys::window()
+        .and_then(|window| window.indexed_db().ok().flatten())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "IndexedDB is not available"))?;
+    let request = factory.open_with_u32(DATABASE, DATABASE_VERSION).map_err(js_error)?;
+    // Runs before the request succeeds when the database is created
+    let upgrade = Closure::<dyn FnMut(web_sys::Event)>::new(|event: web_sys::Event| {
+        let database = event
+            .target()
+            .and_then(|target| target.dyn_into::<IdbOpenDbRequest>().ok())
+            .and_then(|request| request.result().ok())
+            .and_then(|database| database.dyn_into::<IdbDatabase>().ok());
+        if let Some(database) = database {
+            if let Err(err) = database.create_object_store(STORE) {
+                tracing::error!("Failed to create the {STORE} object store: {err:?}");
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+    let database = complete(&request).await?.dyn_into::<IdbDatabase>().map_err(js_error)?;
+
+    OPEN_DATABASE.with(|open| *open.borrow_mut() = Some(database.clone()));
+    Ok(database)
+}
+
+async fn store(mode: IdbTransactionMode) -> io::Result<IdbObjectStore> {
+    let transaction = database().await?.transaction_with_str_and_mode(STORE, mode).map_err(js_error)?;
+    transaction.object_store(STORE).map_err(js_error)
+}
+
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let key = key(path.as_ref());
+    // IndexedDB objects can't leave the thread they were created on
+    wasm_nonsend(move || async move {
+        let store = store(IdbTransactionMode::Readonly).await?;
+        let value = complete(&store.get(&JsValue::from_str(&key)).map_err(js_error)?).await?;
+        if value.is_undefined() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("No such file: {key}")));
+        }
+        Ok(value.dyn_into::<Uint8Array>().map_err(js_error)?.to_vec())
+    })
+    .await
+}
+
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    String::from_utf8(read(path).await?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
 }
 
-pub async fn read_to_string(_path: impl AsRef<Path>) -> io::Result<String> {
-    Err(io::Error::new(io::ErrorKind::Unsupported, "File IO on wasm it not supported"))
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let key = key(path.as_ref());
+    let contents = contents.as_ref().to_vec();
+    wasm_nonsend(move || async move {
+        let store = store(IdbTransactionMode::Readwrite).await?;
+        complete(&store.put_with_key(&Uint8Array::from(&contents[..]), &JsValue::from_str(&key)).map_err(js_error)?).await?;
+        Ok(())
+    })
+    .await
 }
 
-pub async fn write(_path: impl AsRef<Path>, _contents: impl AsRef<[u8]>) -> io::Result<()> {
-    Err(io::Error::new(io::ErrorKind::Unsupported, "File IO on wasm it not supported"))
+pub async fn try_exists(path: impl AsRef<Path>) -> io::Result<bool> {
+    let key = key(path.as_ref());
+    wasm_nonsend(move || async move {
+        let store = store(IdbTransactionMode::Readonly).await?;
+        let count = complete(&store.count_with_key(&JsValue::from_str(&key)).map_err(js_error)?).await?;
+        Ok(count.as_f64().unwrap_or_default() > 0.)
+    })
+    .await
 }
@@ -5,7 +5,7 @@ use ambient_core::{asset_cache, frame_index, map_seed};
 use ambient_ecs::{EntityId, World};
 use ambient_gpu::{gpu::GpuKey, std_assets::PixelTextureViewKey, texture::Texture};
 use ambient_network::ServerWorldExt;
-use ambient_std::asset_cache::{AssetCache, AsyncAssetKey, SyncAssetKeyExt};
+use ambient_std::asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKeyExt};
 use glam::{ivec2, IVec2, UVec2, Vec2, Vec3, Vec3Swizzles};
 use serde::{Deserialize, Serialize};
 
@@ -17,6 +17,7 @@ mod hydraulic_erosion;
 mod init;
 mod normalmap;
 mod raise;
+mod stamp;
 mod thermal_erosion;
 mod water_sim;
 
@@ -26,6 +27,7 @@ pub use hydraulic_erosion::*;
 pub use init::*;
 pub use normalmap::*;
 pub use raise::*;
+pub use stamp::*;
 pub use thermal_erosion::*;
 pub use water_sim::*;
 
@@ -38,6 +40,7 @@ pub enum Brush {
     Erode,
     Erode2,
     Thermal,
+    Stamp,
 }
 unsafe impl bytemuck::Pod for Brush {}
 unsafe impl bytemuck::Zeroable for Brush {}
@@ -106,11 +109,17 @@ pub struct TerrainBrushStroke {
     pub brush_smoothness: BrushSmoothness,
     pub start_position: Vec3,
     pub erosion: HydraulicErosionConfig,
+    /// The heightmap applied by [Brush::Stamp]
+    #[serde(default)]
+    pub stamp: Option<TerrainStamp>,
 }
 impl TerrainBrushStroke {
     fn get_brush_cells(&self) -> (IVec2, IVec2) {
         let terrain = TerrainSize::new();
-        let radius = self.brush_size.radius() * 1.2;
+        let radius = match &self.stamp {
+            Some(stamp) if self.brush == Brush::Stamp => self.brush_size.radius() * stamp.scale.max_element() * std::f32::consts::SQRT_2,
+            _ => self.brush_size.radius() * 1.2,
+        };
         let top_left_cell = ((self.center - radius) / terrain.size_in_meters()).floor().as_ivec2();
         let bottom_right_cell = ((self.center + radius) / terrain.size_in_meters()).ceil().as_ivec2();
         (top_left_cell, bottom_right_cell)
@@ -151,6 +160,7 @@ impl TerrainBrushStroke {
             brush_smoothness: BrushSmoothness(1.),
             start_position: Default::default(),
             erosion: Default::default(),
+            stamp: None,
         }
     }
 }
@@ -219,8 +229,18 @@ impl TerrainBrush {
         let seed = world.get(map_globals, map_seed()).unwrap();
 
         let (top_left_cell, bottom_right_cell) = stroke.get_brush_cells();
-        let TerrainBrushStroke { center, layer, brush, brush_size, brush_strength, brush_smoothness, brush_shape, start_position, erosion } =
-            stroke;
+        let TerrainBrushStroke {
+            center,
+            layer,
+            brush,
+            brush_size,
+            brush_strength,
+            brush_smoothness,
+            brush_shape,
+            start_position,
+            erosion,
+            stamp,
+        } = stroke;
         let gpu = world.resource(gpu()).clone();
         let mut encoder = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
         let terrain = TerrainSize::new();
@@ -338,6 +358,38 @@ impl TerrainBrush {
                 };
                 brush.run(&gpu, &mut encoder, &self.intermediate_heightmap.create_view(&Default::default()), texture_size, &config);
             }
+            Brush::Stamp => {
+                let texture = stamp.as_ref().and_then(|stamp| stamp.texture_key()).and_then(|key| key.peek(world.resource(asset_cache())));
+                match (stamp, texture) {
+                    (Some(stamp), Some(Ok(texture))) => {
+                        let params = StampBrushParams {
+                            brush: BrushWGSL {
+                                center,
+                                radius: brush_size.radius(),
+                                shape: brush_shape,
+                                amplitude: brush_strength.strength(),
+                                smoothness: brush_smoothness.0,
+                                _padding: Default::default(),
+                            },
+                            heightmap_world_position: top_left_cell.as_vec2() * terrain.size_in_meters(),
+                            heightmap_world_texel_size,
+                            layer: layer as i32,
+                            stamp_scale: stamp.scale,
+                            stamp_rotation: stamp.rotation,
+                            replace: stamp.replace as i32,
+                        };
+                        StampBrush::new(&gpu).run(
+                            &gpu,
+                            &mut encoder,
+                            &self.intermediate_heightmap.create_view(&Default::default()),
+                            &texture,
+                            texture_size,
+                            &params,
+                        );
+                    }
+                    (stamp, _) => tracing::warn!("Skipping a terrain stamp stroke, as its heightmap isn't loaded: {stamp:?}"),
+                }
+            }
         }
         self.normals.run(
             &gpu,
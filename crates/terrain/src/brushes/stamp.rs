@@ -0,0 +1,196 @@
+use std::{borrow::Cow, sync::Arc};
+
+use ambient_gpu::{
+    gpu::{Gpu, GpuKey},
+    texture::{Texture, TextureView},
+    texture_loaders::ImageFromUrl,
+    wgsl_utils::wgsl_interpolate,
+};
+use ambient_std::{
+    asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt, SyncAssetKeyExt},
+    asset_url::AbsAssetUrl,
+    download_asset::AssetError,
+    include_file,
+};
+use async_trait::async_trait;
+use glam::{UVec2, Vec2};
+use serde::{Deserialize, Serialize};
+use wgpu::{
+    util::DeviceExt, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType, ShaderStages, TextureFormat,
+    TextureViewDimension,
+};
+
+use super::BrushWGSL;
+use crate::{heightmap::Heightmap, wgsl_terrain_preprocess};
+
+/// The heightmap image applied by [super::Brush::Stamp]. The image covers the square of the brush, which the stamp is
+/// stretched and rotated within, and its values are multiplied by the brush strength: EXRs exported from the terrain are in
+/// meters, and PNGs from 0 to 1
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TerrainStamp {
+    /// The url of the heightmap image
+    pub url: String,
+    /// The rotation around the brush center, in radians
+    pub rotation: f32,
+    /// The size of the stamp relative to the brush, on each axis
+    pub scale: Vec2,
+    /// Whether the stamp sets the height of the terrain instead of adding to it
+    pub replace: bool,
+}
+impl TerrainStamp {
+    pub fn new(url: String) -> Self {
+        Self { url, rotation: 0., scale: Vec2::ONE, replace: false }
+    }
+
+    pub fn texture_key(&self) -> Option<StampTextureKey> {
+        match AbsAssetUrl::parse(&self.url) {
+            Ok(url) => Some(StampTextureKey { url }),
+            Err(err) => {
+                tracing::warn!("Invalid terrain stamp url {:?}: {err:?}", self.url);
+                None
+            }
+        }
+    }
+}
+
+/// Loads a heightmap image to an `R32Float` texture, for [StampBrush]
+#[derive(Clone, Debug)]
+pub struct StampTextureKey {
+    pub url: AbsAssetUrl,
+}
+#[async_trait]
+impl AsyncAssetKey<Result<Arc<Texture>, AssetError>> for StampTextureKey {
+    fn gpu_size(&self, asset: &Result<Arc<Texture>, AssetError>) -> Option<u64> {
+        asset.as_ref().ok().map(|asset| asset.size_in_bytes)
+    }
+    async fn load(self, assets: AssetCache) -> Result<Arc<Texture>, AssetError> {
+        let image = ImageFromUrl { url: self.url.clone() }.get(&assets).await?;
+        let heightmap = Heightmap::from_image(&image);
+        Ok(Arc::new(Texture::new_with_data(
+            GpuKey.get(&assets),
+            &wgpu::TextureDescriptor {
+                label: Some(&format!("Terrain stamp {}", self.url)),
+                size: wgpu::Extent3d { width: heightmap.width, height: heightmap.height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R32Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            },
+            bytemuck::cast_slice(&heightmap.heights),
+        )))
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct StampBrushParams {
+    pub brush: BrushWGSL,
+    pub heightmap_world_position: Vec2,
+    pub heightmap_world_texel_size: f32,
+    pub layer: i32,
+    pub stamp_scale: Vec2,
+    pub stamp_rotation: f32,
+    pub replace: i32,
+}
+impl Default for StampBrushParams {
+    fn default() -> Self {
+        Self {
+            brush: Default::default(),
+            heightmap_world_position: Vec2::ZERO,
+            heightmap_world_texel_size: 0.,
+            layer: 0,
+            stamp_scale: Vec2::ONE,
+            stamp_rotation: 0.,
+            replace: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StampBrush {
+    pipeline: wgpu::ComputePipeline,
+}
+impl StampBrush {
+    pub fn new(gpu: &Gpu) -> Self {
+        let shader =
+            [&wgsl_interpolate() as &str, &include_file!("brush.wgsl"), &wgsl_terrain_preprocess(include_file!("stamp.wgsl"))].join("\n");
+        let shader = gpu.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("StampBrush.shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(&shader)),
+        });
+
+        let pipeline = gpu.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: Some(&gpu.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&gpu.device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: &[
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::StorageTexture {
+                                access: wgpu::StorageTextureAccess::ReadWrite,
+                                format: TextureFormat::R32Float,
+                                view_dimension: TextureViewDimension::D2Array,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Buffer { ty: BufferBindingType::Uniform, has_dynamic_offset: false, min_binding_size: None },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 2,
+                            visibility: ShaderStages::COMPUTE,
+                            ty: BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                                view_dimension: TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                })],
+                push_constant_ranges: &[],
+            })),
+            module: &shader,
+            entry_point: "main",
+        });
+        Self { pipeline }
+    }
+    pub fn run(
+        &self,
+        gpu: &Gpu,
+        encoder: &mut wgpu::CommandEncoder,
+        heightmap: &TextureView,
+        stamp: &Arc<Texture>,
+        size: UVec2,
+        params: &StampBrushParams,
+    ) {
+        let param_buffer = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Stamp Parameter Buffer"),
+            contents: bytemuck::cast_slice(&[*params]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = self.pipeline.get_bind_group_layout(0);
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(heightmap) },
+                wgpu::BindGroupEntry { binding: 1, resource: param_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&stamp.create_view(&Default::default())) },
+            ],
+        });
+
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(size.x, size.y, 1);
+    }
+}
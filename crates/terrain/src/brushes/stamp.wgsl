@@ -0,0 +1,74 @@
+
+struct Params {
+    brush: Brush,
+    heightmap_world_position: vec2<f32>,
+    heightmap_world_texel_size: f32,
+    layer: i32,
+    stamp_scale: vec2<f32>,
+    stamp_rotation: f32,
+    replace: i32,
+};
+@group(0)
+@binding(0)
+var heightmap: texture_storage_2d_array<r32float, read_write>;
+
+@group(0)
+@binding(1)
+var<uniform> params: Params;
+
+@group(0)
+@binding(2)
+var stamp: texture_2d<f32>;
+
+// Bilinear, as r32float textures can't be filtered
+fn sample_stamp(uv: vec2<f32>) -> f32 {
+    let size = vec2<f32>(textureDimensions(stamp));
+    let texel = clamp(uv * size - 0.5, vec2<f32>(0.), size - 1.);
+    let t = fract(texel);
+    let a = vec2<i32>(floor(texel));
+    let b = min(a + 1, vec2<i32>(size) - 1);
+    let h00 = textureLoad(stamp, a, 0).r;
+    let h10 = textureLoad(stamp, vec2<i32>(b.x, a.y), 0).r;
+    let h01 = textureLoad(stamp, vec2<i32>(a.x, b.y), 0).r;
+    let h11 = textureLoad(stamp, b, 0).r;
+    return mix(mix(h00, h10, t.x), mix(h01, h11, t.x), t.y);
+}
+
+@compute
+@workgroup_size(1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let p = vec2<f32>(global_id.xy) * params.heightmap_world_texel_size + params.heightmap_world_position;
+
+    // The position in the stamp, from -1 to 1 on each axis
+    let c = cos(-params.stamp_rotation);
+    let s = sin(-params.stamp_rotation);
+    let d = p - params.brush.center;
+    let local = vec2<f32>(c * d.x - s * d.y, s * d.x + c * d.y) / (params.brush.radius * params.stamp_scale);
+    if (any(abs(local) > vec2<f32>(1.))) {
+        return;
+    }
+
+    var edge: f32;
+    if (params.brush.shape == 0) { // circle
+        edge = min(length(local), 1.);
+    } else { // rectangle
+        edge = max(abs(local.x), abs(local.y));
+    }
+    let fade = smoothstep_power(1., 0., edge, params.brush.smoothness);
+
+    // The rows of the image go from north to south
+    let uv = vec2<f32>(local.x, -local.y) * 0.5 + 0.5;
+    let stamp_height = sample_stamp(uv) * params.brush.amplitude;
+
+    let texel = vec2<i32>(global_id.xy);
+    var height: f32 = textureLoad(heightmap, texel, params.layer).x;
+    if (params.replace != 0) {
+        let total = textureLoad(heightmap, texel, #ROCK_LAYER).x + textureLoad(heightmap, texel, #SOIL_LAYER).x;
+        height = height + (stamp_height - total) * fade;
+    } else {
+        height = height + stamp_height * fade;
+    }
+    height = max(height, 0.);
+
+    textureStore(heightmap, texel, params.layer, vec4<f32>(height, 0., 0., 0.));
+}
@@ -0,0 +1,92 @@
+//! Heightmaps exchange terrain with external tools: a region of the terrain can be exported to a PNG or an EXR, and an
+//! image can be applied to the terrain with the [Brush::Stamp](crate::brushes::Brush::Stamp) brush.
+//!
+//! The rows of an image go from north (+y) to south, with one pixel per terrain texel. EXRs hold the heights in meters,
+//! while 16-bit PNGs hold them normalized from the lowest to the highest point of the region.
+use std::path::Path;
+
+use ambient_core::transform::translation;
+use ambient_ecs::{query, World};
+use anyhow::Context;
+use glam::{vec2, Vec2, Vec3Swizzles};
+use image::{DynamicImage, ImageBuffer, Luma, Rgb};
+
+use crate::{find_terrain_cell, terrain_state_cpu, TerrainSize};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    pub width: u32,
+    pub height: u32,
+    /// The heights, row by row from the north-west corner
+    pub heights: Vec<f32>,
+}
+impl Heightmap {
+    /// Samples the terrain from `min` to `max`, at the center of each texel. Points without terrain are at 0
+    pub fn from_terrain(world: &World, min: Vec2, max: Vec2) -> Self {
+        let texel_size = texel_size();
+        let size = ((max - min) / texel_size).ceil().max(Vec2::ONE).as_uvec2();
+        let heights = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let point = vec2(min.x + (x as f32 + 0.5) * texel_size, max.y - (y as f32 + 0.5) * texel_size);
+                find_terrain_cell(world, point).map_or(0., |(_, position, state)| state.get_height_at_world_offset(point - position.xy()))
+            })
+            .collect();
+        Self { width: size.x, height: size.y, heights }
+    }
+
+    /// Reads the heights from the luminance of `image`
+    pub fn from_image(image: &DynamicImage) -> Self {
+        let image = image.to_luma32f();
+        Self { width: image.width(), height: image.height(), heights: image.into_raw() }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self::from_image(&image::open(path).with_context(|| format!("Failed to open the heightmap {path:?}"))?))
+    }
+
+    /// Writes the heightmap to a `.png`, normalized to 16 bits, or to an `.exr`, in meters
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("exr") => {
+                let pixels = self.heights.iter().flat_map(|&height| [height; 3]).collect();
+                let image =
+                    ImageBuffer::<Rgb<f32>, Vec<f32>>::from_raw(self.width, self.height, pixels).context("Invalid heightmap size")?;
+                DynamicImage::ImageRgb32F(image).save(path)?;
+            }
+            Some("png") => {
+                let (min, max) = self.range();
+                let pixels =
+                    self.heights.iter().map(|&height| ((height - min) / (max - min).max(f32::EPSILON) * u16::MAX as f32) as u16).collect();
+                let image =
+                    ImageBuffer::<Luma<u16>, Vec<u16>>::from_raw(self.width, self.height, pixels).context("Invalid heightmap size")?;
+                image.save(path)?;
+            }
+            _ => anyhow::bail!("Heightmaps can only be saved as .png or .exr, not {path:?}"),
+        }
+        Ok(())
+    }
+
+    /// The lowest and the highest height
+    pub fn range(&self) -> (f32, f32) {
+        self.heights.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &height| (min.min(height), max.max(height)))
+    }
+}
+
+/// The size of a terrain texel, in meters
+pub fn texel_size() -> f32 {
+    let size = TerrainSize::new();
+    size.size_in_meters() / (size.texture_size() - 1) as f32
+}
+
+/// The region covered by the terrain cells of `world`, as its minimum and maximum corners
+pub fn terrain_bounds(world: &World) -> Option<(Vec2, Vec2)> {
+    let size = TerrainSize::new().size_in_meters();
+    query(translation()).incl(terrain_state_cpu()).iter(world, None).map(|(_, position)| position.xy()).fold(None, |bounds, position| {
+        let (min, max) = bounds.unwrap_or((position, position + size));
+        Some((min.min(position), max.max(position + size)))
+    })
+}
@@ -0,0 +1,137 @@
+//! Holes let meshes such as caves and overhangs punch through the terrain. A cell can have a [terrain_hole_mask], with
+//! one bit per quad of its heightfield, and the quads which are holes are left out of the cell's meshes and of its
+//! collider, so that raycasts and characters pass through them.
+use std::sync::Arc;
+
+use ambient_core::transform::translation;
+use ambient_ecs::{components, query, Debuggable, Networked, Store, World};
+use ambient_gpu::mesh_buffer::GpuMesh;
+use ambient_meshes::{GridMesh, GridMeshKey};
+use ambient_std::{
+    asset_cache::{AssetCache, SyncAssetKeyExt},
+    mesh::Mesh,
+};
+use glam::{uvec2, vec2, UVec2, Vec2, Vec3Swizzles};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{terrain_world_cell, TerrainSize};
+
+components!("terrain", {
+    /// The quads of a terrain cell which are holes, see [TerrainHoleMask]
+    @[Debuggable, Networked, Store]
+    terrain_hole_mask: TerrainHoleMask,
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerrainHoleMask {
+    /// The number of quads along each side of the cell
+    pub size: u32,
+    /// One bit per quad, row by row from the south-west corner of the cell
+    pub bits: Vec<u64>,
+}
+impl Default for TerrainHoleMask {
+    fn default() -> Self {
+        Self::new(TerrainSize::new().polygon_size() as u32)
+    }
+}
+impl TerrainHoleMask {
+    pub fn new(size: u32) -> Self {
+        Self { size, bits: vec![0; ((size * size) as usize + 63) / 64] }
+    }
+
+    pub fn is_hole(&self, quad: UVec2) -> bool {
+        if quad.x >= self.size || quad.y >= self.size {
+            return false;
+        }
+        let index = (quad.x + quad.y * self.size) as usize;
+        self.bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn set_hole(&mut self, quad: UVec2, hole: bool) {
+        if quad.x >= self.size || quad.y >= self.size {
+            return;
+        }
+        let index = (quad.x + quad.y * self.size) as usize;
+        if hole {
+            self.bits[index / 64] |= 1 << (index % 64);
+        } else {
+            self.bits[index / 64] &= !(1 << (index % 64));
+        }
+    }
+
+    /// The size of a quad, in meters
+    pub fn quad_size(&self) -> f32 {
+        TerrainSize::new().size_in_meters() / self.size as f32
+    }
+
+    /// Whether the quad at `offset` from the south-west corner of the cell is a hole
+    pub fn is_hole_at_world_offset(&self, offset: Vec2) -> bool {
+        offset.min_element() >= 0. && self.is_hole((offset / self.quad_size()).as_uvec2())
+    }
+
+    /// Whether all the quads from `min` to `max`, excluded, are holes. The quads of the lower LODs span several quads of
+    /// the mask, and they're only left out when they're entirely holes
+    pub fn is_area_hole(&self, min: UVec2, max: UVec2) -> bool {
+        (min.y..max.y).cartesian_product(min.x..max.x).all(|(y, x)| self.is_hole(uvec2(x, y)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&bits| bits == 0)
+    }
+}
+
+/// Makes the terrain quads whose centers are between `min` and `max` holes, or fills them back in if `hole` is false
+pub fn set_terrain_holes(world: &mut World, min: Vec2, max: Vec2, hole: bool) {
+    let cell_size = TerrainSize::new().size_in_meters();
+    let cells = query(translation())
+        .incl(terrain_world_cell())
+        .iter(world, None)
+        .map(|(id, position)| (id, position.xy()))
+        .filter(|(_, position)| {
+            position.x < max.x && position.y < max.y && position.x + cell_size > min.x && position.y + cell_size > min.y
+        })
+        .collect_vec();
+    for (id, position) in cells {
+        let mut mask = world.get_cloned(id, terrain_hole_mask()).unwrap_or_default();
+        let quad_size = mask.quad_size();
+        for (y, x) in (0..mask.size).cartesian_product(0..mask.size) {
+            let center = position + (vec2(x as f32, y as f32) + 0.5) * quad_size;
+            if center.cmpge(min).all() && center.cmple(max).all() {
+                mask.set_hole(uvec2(x, y), hole);
+            }
+        }
+        if world.get_ref(id, terrain_hole_mask()).map_or(true, |old| *old != mask) {
+            world.add_component(id, terrain_hole_mask(), mask).ok();
+        }
+    }
+}
+
+/// The mesh of a terrain cell at `lod`, without the quads which are holes
+pub fn terrain_lod_mesh(assets: &AssetCache, size: &TerrainSize, lod: usize, holes: Option<&TerrainHoleMask>) -> Arc<GpuMesh> {
+    let quads = size.polygons_at_lod(lod);
+    let grid = GridMesh {
+        n_vertices_height: quads + 1,
+        n_vertices_width: quads + 1,
+        size: vec2(size.size_in_meters(), size.size_in_meters()),
+        ..GridMesh::default()
+    };
+    let Some(holes) = holes.filter(|holes| !holes.is_empty()) else { return GridMeshKey(grid).get(assets) };
+
+    let quads = quads as u32;
+    let step = (holes.size / quads).max(1);
+    let mut mesh = Mesh::from(&grid);
+    if let Some(indices) = &mut mesh.indices {
+        // The grid has two triangles per quad, row by row
+        *indices = indices
+            .chunks(6)
+            .enumerate()
+            .filter(|(i, _)| {
+                let quad = uvec2(*i as u32 % quads, *i as u32 / quads) * step;
+                !holes.is_area_hole(quad, quad + step)
+            })
+            .flat_map(|(_, triangles)| triangles.iter().copied())
+            .collect();
+    }
+    GpuMesh::from_mesh(assets.clone(), &mesh)
+}
@@ -45,6 +45,12 @@ pub fn terrain_intent_client_system() -> SystemGroup {
                     let assets = world.resource(asset_cache()).clone();
                     world.resource(runtime()).spawn(async move {
                         let brush = TerrainBrushKey.get(&assets).await;
+                        // Stamps are applied from the asset cache, so their heightmaps have to be loaded first
+                        for key in strokes.iter().filter_map(|(_, (stroke, _))| stroke.stamp.as_ref()?.texture_key()) {
+                            if let Err(err) = key.get(&assets).await {
+                                tracing::warn!("Failed to load the terrain stamp {}: {err:?}", key.url);
+                            }
+                        }
                         async_run.run(move |world| {
                             for (_, (stroke, _)) in strokes {
                                 brush.apply(world, stroke);
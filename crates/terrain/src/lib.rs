@@ -24,7 +24,6 @@ use ambient_gpu::{
     texture::{Texture, TextureReader},
     texture_loaders::TextureFromUrl,
 };
-use ambient_meshes::{GridMesh, GridMeshKey};
 use ambient_physics::{
     collider::{collider_type, ColliderType},
     main_physics_scene,
@@ -38,7 +37,7 @@ use ambient_std::{
     cb, friendly_id, log_result,
     shapes::{Sphere, AABB},
 };
-use glam::{vec2, vec3, vec4, IVec2, Mat4, Quat, UVec2, Vec2, Vec3, Vec3Swizzles, Vec4};
+use glam::{uvec2, vec2, vec3, vec4, IVec2, Mat4, Quat, UVec2, Vec2, Vec3, Vec3Swizzles, Vec4};
 use itertools::Itertools;
 use ndarray::{s, Array3, ArrayView3, Axis};
 use physxx::{
@@ -53,11 +52,15 @@ use crate::terrain_shader::{TerrainMaterial, TerrainMaterialParams};
 
 pub mod brushes;
 mod gather_spread;
+pub mod heightmap;
+mod holes;
 pub mod intents;
+pub mod splines;
 mod terrain_shader;
 use ambient_network::ServerWorldExt;
 use ambient_sys::time::Instant;
 pub use gather_spread::*;
+pub use holes::*;
 pub use intents::*;
 
 components!("terrain", {
@@ -81,6 +84,7 @@ components!("terrain", {
 });
 pub fn init_all_components() {
     init_components();
+    holes::init_components();
     intents::init_components();
 }
 
@@ -107,11 +111,17 @@ pub fn spawn_terrain(world: &mut World, terrain_compressed: Arc<TerrainStateCpu>
         .spawn(world)
 }
 
-fn create_terrain_physics(world: &World, terrain_state: Arc<TerrainStateCpu>, position: Vec3, _cell: IVec2) -> PxRigidStaticRef {
+fn create_terrain_physics(
+    world: &World,
+    terrain_state: Arc<TerrainStateCpu>,
+    holes: Option<&TerrainHoleMask>,
+    position: Vec3,
+    _cell: IVec2,
+) -> PxRigidStaticRef {
     let scene = world.resource(main_physics_scene());
     let physics = world.resource(physics());
     let physics_material = PxTerrainMaterialKey.get(world.resource(asset_cache()));
-    let actor = px_rigid_static_from_heightmap(physics, physics_material, &terrain_state.heightmap);
+    let actor = px_rigid_static_from_heightmap(physics, physics_material, &terrain_state.heightmap, holes);
     actor.as_actor().set_user_data(PxActorUserData { serialize: false });
     actor.set_actor_flag(PxActorFlag::VISUALIZATION, false);
     actor.get_shapes()[0].set_flag(PxShapeFlag::VISUALIZATION, false);
@@ -185,7 +195,8 @@ pub fn server_systems() -> SystemGroup {
                                 actor.release();
                             }
                         }
-                        let body = create_terrain_physics(world, state.clone(), position, cell);
+                        let holes = world.get_ref(id, terrain_hole_mask()).ok();
+                        let body = create_terrain_physics(world, state.clone(), holes, position, cell);
                         body.get_shapes()[0].set_user_data(PxShapeUserData { entity: id, ..Default::default() });
 
                         world
@@ -200,6 +211,13 @@ pub fn server_systems() -> SystemGroup {
                     }
                 },
             ),
+            Box::new(splines::systems()),
+            query(terrain_hole_mask().changed()).incl(physics_shape()).to_system_with_name("terrain_holes", |q, world, qs, _| {
+                // The collider is rebuilt with the new holes along with the missing ones, above
+                for (id, _) in q.collect_cloned(world, qs) {
+                    world.remove_component(id, physics_shape()).ok();
+                }
+            }),
             query((translation(), character_controller())).to_system_with_name("character", |q, world, qs, _| {
                 for (_id, (pos, controller)) in q.iter(world, qs) {
                     if let Some(height) = get_terrain_height(world, pos.xy()) {
@@ -214,8 +232,11 @@ pub fn server_systems() -> SystemGroup {
 
                 for (cell, (state, cell_pos)) in q.iter(world, qs) {
                     let _span = info_span!("raise_objects", ?cell, ?cell_pos).entered();
+                    let holes = world.get_ref(cell, terrain_hole_mask()).ok();
                     for (id, (pos, rot, scale, offset)) in query((translation(), rotation(), scale(), snap_to_ground())).iter(world, None) {
-                        if state.contains(cell_pos.xy(), pos.xy()) {
+                        // Entities above holes are in caves, below the terrain
+                        let in_hole = holes.map_or(false, |holes| holes.is_hole_at_world_offset(pos.xy() - cell_pos.xy()));
+                        if state.contains(cell_pos.xy(), pos.xy()) && !in_hole {
                             // Move the entity or attachment position up to the new position
                             if let Some(height) = state.get_height(pos.xy() - cell_pos.xy()) {
                                 let new_pos = vec3(pos.x, pos.y, height + cell_pos.z + offset);
@@ -251,16 +272,16 @@ pub fn client_systems() -> SystemGroup {
                     world.add_component(id, terrain_cell_version(), 0).ok();
                 }
             }),
+            Box::new(splines::systems()),
             Box::new(terrain_gpu_to_cpu_system()),
             query((terrain_state().changed(), translation())).to_system(|q, world, qs, _| {
-                for (id, (state, pos)) in q.collect_cloned(world, qs) {
-                    render_parented_with_component(
-                        world,
-                        id,
-                        element_tree(),
-                        Group(vec![Terrain { state: state.clone(), heightmap_position: pos.xy() }.el().set_default(local_to_parent())])
-                            .el(),
-                    );
+                for (id, _) in q.collect_cloned(world, qs) {
+                    render_terrain_cell(world, id);
+                }
+            }),
+            query(terrain_hole_mask().changed()).incl(terrain_state()).to_system(|q, world, qs, _| {
+                for (id, _) in q.collect_cloned(world, qs) {
+                    render_terrain_cell(world, id);
                 }
             }),
             query((terrain_lods(), cpu_lod(), local_to_world(), terrain_cell_bounding(), terrain_lod_factor(), terrain_cell_diagonal()))
@@ -294,6 +315,17 @@ pub fn client_systems() -> SystemGroup {
     )
 }
 
+fn render_terrain_cell(world: &mut World, id: EntityId) {
+    let (Ok(state), Ok(pos)) = (world.get_cloned(id, terrain_state()), world.get(id, translation())) else { return };
+    let holes = world.get_cloned(id, terrain_hole_mask()).ok();
+    render_parented_with_component(
+        world,
+        id,
+        element_tree(),
+        Group(vec![Terrain { state, heightmap_position: pos.xy(), holes }.el().set_default(local_to_parent())]).el(),
+    );
+}
+
 pub fn ray_terrain_intersection(world: &World, origin: Vec3, dir: Vec3) -> Option<f32> {
     for (_, (rigid_static,)) in query((rigid_static(),)).incl(terrain_state_cpu()).iter(world, None) {
         let shapes = rigid_static.get_shapes();
@@ -663,6 +695,7 @@ pub fn use_async_asset<T: Asset + Clone + Sync + Send + std::fmt::Debug + 'stati
 pub struct Terrain {
     pub state: TerrainState,
     pub heightmap_position: Vec2,
+    pub holes: Option<TerrainHoleMask>,
 }
 impl ElementComponent for Terrain {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
@@ -742,17 +775,9 @@ impl ElementComponent for Terrain {
             },
         );
 
-        let lod_meshes = (0..self.state.size.lods)
-            .map(|lod| {
-                GridMeshKey(GridMesh {
-                    n_vertices_height: self.state.size.polygons_at_lod(lod) + 1,
-                    n_vertices_width: self.state.size.polygons_at_lod(lod) + 1,
-                    size: glam::vec2(self.state.size.size_in_meters(), self.state.size.size_in_meters()),
-                    ..GridMesh::default()
-                })
-                .get(&assets)
-            })
-            .collect_vec();
+        let lod_meshes = hooks.use_memo_with((self.state.size.clone(), self.holes.clone()), |_, (size, holes)| {
+            (0..size.lods).map(|lod| terrain_lod_mesh(&assets, size, lod, holes.as_ref())).collect_vec()
+        });
 
         let (height_min, height_max) = (0., 500.);
         let aabb = AABB { min: vec3(0., 0., height_min), max: vec3(size_in_meters, size_in_meters, height_max) };
@@ -782,7 +807,12 @@ impl ElementComponent for Terrain {
     }
 }
 
-pub fn px_rigid_static_from_heightmap(physics: &Physics, physics_material: PxMaterial, heightmap: &Array3<f32>) -> PxRigidStaticRef {
+pub fn px_rigid_static_from_heightmap(
+    physics: &Physics,
+    physics_material: PxMaterial,
+    heightmap: &Array3<f32>,
+    holes: Option<&TerrainHoleMask>,
+) -> PxRigidStaticRef {
     let heightmap = heightmap.slice(s![0..2, .., ..]).sum_axis(Axis(0));
     let texture_size = heightmap.shape()[0];
     let total_size = texture_size - 1;
@@ -793,6 +823,14 @@ pub fn px_rigid_static_from_heightmap(physics: &Physics, physics_material: PxMat
     for i in 0..quantized.samples.len() {
         quantized.samples[i].set_tesselation(true);
     }
+    if let Some(holes) = holes {
+        // The samples are transposed and flipped along y, and each one holds the material of the quad it starts
+        for (y, x) in (0..total_size).cartesian_product(0..total_size) {
+            if holes.is_hole(uvec2(x as u32, y as u32)) {
+                quantized.samples[x * texture_size + (total_size - 1 - y)].set_hole();
+            }
+        }
+    }
     let hfd = PxHeightFieldDesc::new(texture_size as u32, texture_size as u32, &quantized.samples);
 
     let xy_scale = 1.;
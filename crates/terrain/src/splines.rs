@@ -0,0 +1,140 @@
+//! Splines with [spline_deform_terrain] shape the terrain under them: it's flattened to a road, and carved out below the
+//! surface of a river. Like a brush stroke, the edit stays in the terrain; it's applied again whenever the spline's points
+//! change.
+use std::sync::Arc;
+
+use ambient_core::transform::translation;
+use ambient_ecs::{query, SystemGroup, World};
+use ambient_primitives::spline::{spline_deform_terrain, spline_kind, spline_points, spline_width, Spline, SplineKind, SplineSample};
+use glam::{vec2, vec3, Vec2, Vec3, Vec3Swizzles};
+use itertools::Itertools;
+
+use crate::{heightmap::texel_size, terrain_state, terrain_state_cpu, TerrainLayers, TerrainSize, TerrainStateCpu};
+
+/// How far beyond the edges of a spline the terrain blends back to its own height, relative to the spline's width
+const FALLOFF: f32 = 0.5;
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+fn height_at(state: &TerrainStateCpu, x: usize, y: usize) -> f32 {
+    state.heightmap[[TerrainLayers::Rock as usize, y, x]] + state.heightmap[[TerrainLayers::Soil as usize, y, x]]
+}
+
+/// Shapes `state`, the cell at `cell_position`, to the spline with `samples`; returns whether anything changed
+pub fn deform_terrain_along_spline(
+    state: &mut TerrainStateCpu,
+    cell_position: Vec3,
+    samples: &[SplineSample],
+    width: f32,
+    kind: SplineKind,
+) -> bool {
+    let half_width = width / 2.;
+    let reach = half_width + width * FALLOFF;
+    let cell_size = state.size.size_in_meters();
+    let samples = samples
+        .iter()
+        .filter(|sample| {
+            let offset = sample.position.xy() - cell_position.xy();
+            offset.min_element() > -reach && offset.max_element() < cell_size + reach
+        })
+        .collect_vec();
+    if samples.is_empty() {
+        return false;
+    }
+
+    let texel_size = texel_size();
+    let texture_size = state.size.texture_size();
+    let depth = kind.depth(width);
+    let mut changed = false;
+    for (y, x) in (0..texture_size).cartesian_product(0..texture_size) {
+        let point = cell_position.xy() + vec2(x as f32, y as f32) * texel_size;
+        let Some((distance, sample)) =
+            samples.iter().map(|sample| (sample.position.xy().distance(point), sample)).min_by(|a, b| a.0.total_cmp(&b.0))
+        else {
+            continue;
+        };
+        if distance >= reach {
+            continue;
+        }
+        // The terrain follows the spline across its width, and blends back to its own height beyond it
+        let weight = 1. - smoothstep(half_width, reach, distance);
+        // River beds are deepest in the middle
+        let bed = depth * (1. - (distance / half_width).min(1.).powi(2));
+        let target = sample.position.z - bed - cell_position.z;
+
+        let rock = state.heightmap[[TerrainLayers::Rock as usize, y, x]];
+        let height = height_at(state, x, y);
+        let new_height = height + (target - height) * weight;
+        if (new_height - height).abs() < 0.001 {
+            continue;
+        }
+        // Soil is added or removed first, and the rock is only cut into below it
+        let (rock, soil) = if new_height >= rock { (rock, new_height - rock) } else { (new_height.max(0.), 0.) };
+        state.heightmap[[TerrainLayers::Rock as usize, y, x]] = rock;
+        state.heightmap[[TerrainLayers::Soil as usize, y, x]] = soil;
+        changed = true;
+    }
+    if changed {
+        update_normals(state);
+    }
+    changed
+}
+
+/// Recomputes the normals from the heights, the same way as the normal map brush
+fn update_normals(state: &mut TerrainStateCpu) {
+    let last = state.size.texture_size() - 1;
+    for (y, x) in (0..=last).cartesian_product(0..=last) {
+        let left = height_at(state, x.saturating_sub(1), y);
+        let right = height_at(state, (x + 1).min(last), y);
+        let up = height_at(state, x, y.saturating_sub(1));
+        let down = height_at(state, x, (y + 1).min(last));
+        let normal = vec3(left - right, up - down, 2.).normalize();
+        for (i, value) in normal.to_array().into_iter().enumerate() {
+            state.normalmap[[y, x, i]] = value;
+        }
+    }
+}
+
+fn deform_terrain(world: &mut World, spline: &Spline, width: f32, kind: SplineKind) {
+    let samples = spline.sample(texel_size() / 2.);
+    let reach = width / 2. + width * FALLOFF;
+    let (min, max) = samples.iter().fold((Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)), |(min, max), sample| {
+        (min.min(sample.position.xy()), max.max(sample.position.xy()))
+    });
+    let cell_size = TerrainSize::new().size_in_meters();
+    let cells = query((translation(), terrain_state_cpu()))
+        .iter(world, None)
+        .filter(|(_, (position, _))| {
+            position.x < max.x + reach
+                && position.y < max.y + reach
+                && position.x + cell_size > min.x - reach
+                && position.y + cell_size > min.y - reach
+        })
+        .map(|(id, (position, state))| (id, *position, state.clone()))
+        .collect_vec();
+    for (id, position, state) in cells {
+        let mut state = (*state).clone();
+        if deform_terrain_along_spline(&mut state, position, &samples, width, kind) {
+            world.set(id, terrain_state_cpu(), Arc::new(state)).ok();
+            // Uploaded again to the GPU on clients
+            world.remove_component(id, terrain_state()).ok();
+        }
+    }
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "dims/terrain/splines",
+        vec![query(spline_points().changed()).incl(spline_deform_terrain()).to_system(|q, world, qs, _| {
+            for (id, _) in q.collect_cloned(world, qs) {
+                let Some(spline) = Spline::from_world(world, id) else { continue };
+                let width = world.get(id, spline_width()).unwrap_or(4.);
+                let kind = world.get_ref(id, spline_kind()).map(|kind| SplineKind::parse(kind)).unwrap_or_default();
+                deform_terrain(world, &spline, width, kind);
+            }
+        })],
+    )
+}
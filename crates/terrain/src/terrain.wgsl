@@ -285,3 +285,8 @@ fn fs_outlines_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let entity_loc = primitives.data[in.instance_index].xy;
     return get_entity_outline_or(entity_loc, vec4<f32>(0., 0., 0., 0.));
 }
+
+@fragment
+fn fs_picking_main(in: VertexOutput) -> @location(0) vec4<u32> {
+    return get_picking_id(in.instance_index, in.world_position.xyz);
+}
@@ -0,0 +1,192 @@
+//! Glyph atlases, which are shared by all the text entities using the same font and style. The entities of an atlas
+//! render from one texture with one material, so they're drawn together
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{Hash, Hasher},
+    num::NonZeroU32,
+    sync::Arc,
+};
+
+use ambient_ecs::EntityId;
+use ambient_gpu::{gpu::Gpu, texture::Texture};
+use ambient_renderer::SharedMaterial;
+use ambient_std::asset_cache::AssetCache;
+use glyph_brush::{
+    ab_glyph::{FontArc, PxScale},
+    BrushAction, BrushError, GlyphBrush, GlyphBrushBuilder, Rectangle, Section,
+};
+
+use crate::{text_material::TextMaterial, GlyphVertex};
+
+const INITIAL_ATLAS_SIZE: u32 = 256;
+
+/// Fonts are assets, so there's one of each font and style, and an atlas per font
+#[derive(Clone)]
+struct FontKey(Arc<FontArc>);
+impl PartialEq for FontKey {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for FontKey {}
+impl Hash for FontKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+struct AtlasTexture {
+    texture: Arc<Texture>,
+    material: SharedMaterial,
+}
+impl AtlasTexture {
+    fn new(gpu: Arc<Gpu>, assets: &AssetCache, width: u32, height: u32) -> Self {
+        let texture = Arc::new(Texture::new(
+            gpu,
+            &wgpu::TextureDescriptor {
+                size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                label: Some("Text.atlas"),
+            },
+        ));
+        let view = Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        Self { texture, material: SharedMaterial::new(TextMaterial::new(assets.clone(), view)) }
+    }
+    fn write(&self, rect: Rectangle<u32>, data: &[u8]) {
+        self.texture.gpu.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture.handle,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: rect.min[0], y: rect.min[1], z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(rect.width()),
+                rows_per_image: NonZeroU32::new(rect.height()),
+            },
+            wgpu::Extent3d { width: rect.width(), height: rect.height(), depth_or_array_layers: 1 },
+        );
+    }
+}
+
+struct AtlasText {
+    text: String,
+    scale: PxScale,
+    /// `None` until the text has been laid out with the current texture
+    glyphs: Option<Vec<GlyphVertex>>,
+}
+
+struct FontAtlas {
+    /// Each vertex is tagged with the index of its text, as all the texts are laid out together
+    brush: GlyphBrush<(usize, GlyphVertex)>,
+    /// Only when rendering, e.g. not on the server
+    texture: Option<AtlasTexture>,
+    texts: BTreeMap<EntityId, AtlasText>,
+    dirty: bool,
+}
+impl FontAtlas {
+    fn new(font: &FontArc, gpu: Option<(Arc<Gpu>, &AssetCache)>) -> Self {
+        Self {
+            brush: GlyphBrushBuilder::using_font(font.clone()).initial_cache_size((INITIAL_ATLAS_SIZE, INITIAL_ATLAS_SIZE)).build(),
+            texture: gpu.map(|(gpu, assets)| AtlasTexture::new(gpu, assets, INITIAL_ATLAS_SIZE, INITIAL_ATLAS_SIZE)),
+            texts: BTreeMap::new(),
+            dirty: false,
+        }
+    }
+
+    /// Lays out all the texts, updating the texture as needed, and returns the entities whose glyphs changed.
+    ///
+    /// Every text is queued, as the glyphs of texts which aren't may be evicted from the texture to make room.
+    fn process(&mut self, assets: &AssetCache) -> Vec<EntityId> {
+        self.dirty = false;
+        loop {
+            for (i, text) in self.texts.values().enumerate() {
+                self.brush.queue(Section::default().add_text(glyph_brush::Text::new(&text.text).with_scale(text.scale).with_z(i as f32)));
+            }
+            let texture = &self.texture;
+            let result = self.brush.process_queued(
+                |rect, data| {
+                    if let Some(texture) = texture {
+                        texture.write(rect, data);
+                    }
+                },
+                |vertex| (vertex.extra.z as usize, GlyphVertex { tex_coords: vertex.tex_coords, pixel_coords: vertex.pixel_coords }),
+            );
+            match result {
+                Ok(BrushAction::Draw(vertices)) => {
+                    let mut glyphs = vec![Vec::new(); self.texts.len()];
+                    for (i, vertex) in vertices {
+                        glyphs[i].push(vertex);
+                    }
+                    return self
+                        .texts
+                        .iter_mut()
+                        .zip(glyphs)
+                        .filter(|((_, text), glyphs)| text.glyphs.as_ref() != Some(glyphs))
+                        .map(|((&id, text), glyphs)| {
+                            text.glyphs = Some(glyphs);
+                            id
+                        })
+                        .collect();
+                }
+                Ok(BrushAction::ReDraw) => return Vec::new(),
+                Err(BrushError::TextureTooSmall { suggested: (width, height) }) => {
+                    self.brush.resize_texture(width, height);
+                    self.texture = self.texture.take().map(|texture| AtlasTexture::new(texture.texture.gpu.clone(), assets, width, height));
+                    // The glyphs are laid out again in the new texture, and the entities need its material
+                    for text in self.texts.values_mut() {
+                        text.glyphs = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The glyph atlases of the texts of a world, by font
+#[derive(Default)]
+pub(crate) struct FontAtlases {
+    atlases: HashMap<FontKey, FontAtlas>,
+    entities: HashMap<EntityId, FontKey>,
+}
+impl FontAtlases {
+    /// Sets the text of `id`, moving it to the atlas of `font` if it was in another one
+    pub fn set_text(&mut self, id: EntityId, font: &Arc<FontArc>, text: String, scale: PxScale, gpu: Option<(Arc<Gpu>, &AssetCache)>) {
+        let key = FontKey(font.clone());
+        if self.entities.get(&id) != Some(&key) {
+            self.remove(id);
+            self.entities.insert(id, key.clone());
+        }
+        let atlas = self.atlases.entry(key).or_insert_with(|| FontAtlas::new(font, gpu));
+        let entry = atlas.texts.entry(id).or_insert_with(|| AtlasText { text: String::new(), scale, glyphs: None });
+        entry.text = text;
+        entry.scale = scale;
+        atlas.dirty = true;
+    }
+
+    pub fn remove(&mut self, id: EntityId) {
+        if let Some(key) = self.entities.remove(&id) {
+            if let Some(atlas) = self.atlases.get_mut(&key) {
+                atlas.texts.remove(&id);
+            }
+        }
+    }
+
+    /// Lays out the atlases whose texts changed, and returns the entities whose glyphs changed
+    pub fn process(&mut self, assets: &AssetCache) -> Vec<EntityId> {
+        self.atlases.values_mut().filter(|atlas| atlas.dirty).flat_map(|atlas| atlas.process(assets)).collect()
+    }
+
+    /// The glyphs of `id`, and the material of its atlas when rendering
+    pub fn glyphs(&self, id: EntityId) -> Option<(&[GlyphVertex], Option<SharedMaterial>)> {
+        let atlas = self.atlases.get(self.entities.get(&id)?)?;
+        let glyphs = atlas.texts.get(&id)?.glyphs.as_deref().unwrap_or_default();
+        Some((glyphs, atlas.texture.as_ref().map(|texture| texture.material.clone())))
+    }
+}
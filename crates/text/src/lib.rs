@@ -1,10 +1,10 @@
-use std::{num::NonZeroU32, ops::Deref, str::FromStr, sync::Arc};
+use std::{collections::HashSet, ops::Deref, str::FromStr, sync::Arc};
 
 use ambient_core::{asset_cache, async_ecs::async_run, gpu, mesh, runtime, transform::*, window::window_scale_factor};
-use ambient_ecs::{components, query, Debuggable, Description, Entity, Name, Networked, Store, SystemGroup};
-use ambient_gpu::{mesh_buffer::GpuMesh, texture::Texture};
+use ambient_ecs::{components, query, Debuggable, Description, Entity, EntityId, Name, Networked, Store, SystemGroup};
+use ambient_gpu::mesh_buffer::GpuMesh;
 use ambient_layout::{height, min_height, min_width, width};
-use ambient_renderer::{gpu_primitives, material, primitives, renderer_shader, SharedMaterial};
+use ambient_renderer::{gpu_primitives, material, primitives, renderer_shader};
 use ambient_std::{
     asset_cache::{AssetCache, AsyncAssetKey, AsyncAssetKeyExt},
     asset_url::AbsAssetUrl,
@@ -17,16 +17,14 @@ use ambient_std::{
 use anyhow::Context;
 use async_trait::async_trait;
 use glam::*;
-use glyph_brush::{
-    ab_glyph::{Font, FontArc, PxScale, Rect},
-    BrushAction, BrushError, GlyphBrush, GlyphBrushBuilder, Section,
-};
+use glyph_brush::ab_glyph::{Font, FontArc, PxScale, Rect};
 use log::info;
 use parking_lot::Mutex;
 
-use crate::text_material::{get_text_shader, TextMaterial};
+use crate::{atlas::FontAtlases, text_material::get_text_shader};
 use strum::EnumString;
 
+mod atlas;
 mod text_material;
 
 components!("text", {
@@ -41,9 +39,10 @@ components!("text", {
     @[Debuggable, Networked, Store, Name["Font family"], Description["Font family to be used. Can either be 'Default', 'FontAwesome', 'FontAwesomeSolid', 'Code' or a url to a font."]]
     font_family: String,
     font_arc: Arc<FontArc>,
-
-    glyph_brush: Arc<Mutex<GlyphBrush<GlyphVertex>>>,
-    text_texture: Arc<Texture>,
+    @[Debuggable, Networked, Name["Font preloads"], Description["The urls of the fonts of the project, which are loaded before any text uses them."]]
+    font_preloads: Vec<String>,
+    /// Keeps the fonts of `font_preloads` loaded
+    preloaded_fonts: Vec<Arc<FontArc>>,
 });
 
 #[derive(Debug, Clone, Copy)]
@@ -179,6 +178,7 @@ impl AsyncAssetKey<Arc<FontArc>> for FontDef {
 }
 
 pub fn systems(use_gpu: bool) -> SystemGroup {
+    let atlases = Arc::new(Mutex::new(FontAtlases::default()));
     SystemGroup::new(
         "ui/text",
         vec![
@@ -201,29 +201,13 @@ pub fn systems(use_gpu: bool) -> SystemGroup {
                 if !use_gpu {
                     return;
                 }
-                let assets = world.resource(asset_cache()).clone();
-                let gpu = world.resource(gpu()).clone();
                 for (id, _) in q.collect_cloned(world, qs) {
-                    let texture = Arc::new(Texture::new(
-                        gpu.clone(),
-                        &wgpu::TextureDescriptor {
-                            size: wgpu::Extent3d { width: 256, height: 256, depth_or_array_layers: 1 },
-                            mip_level_count: 1,
-                            sample_count: 1,
-                            dimension: wgpu::TextureDimension::D2,
-                            format: wgpu::TextureFormat::R8Unorm,
-                            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                            label: Some("Text.texture"),
-                        },
-                    ));
-                    let texture_view = Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+                    // The material is the one of the atlas of its font, once that's loaded
                     world
                         .add_components(
                             id,
                             Entity::new()
-                                .with(text_texture(), texture)
                                 .with(renderer_shader(), cb(get_text_shader))
-                                .with(material(), SharedMaterial::new(TextMaterial::new(assets.clone(), texture_view)))
                                 .with(primitives(), vec![])
                                 .with_default(gpu_primitives()),
                         )
@@ -235,22 +219,52 @@ pub fn systems(use_gpu: bool) -> SystemGroup {
                     let async_run = world.resource(async_run()).clone();
                     let assets = world.resource(asset_cache()).clone();
                     world.resource(runtime()).spawn(async move {
-                        let font = FontDef(
+                        let key = FontDef(
                             unwrap_log_warn!(FontFamily::from_str(&font_family)),
                             unwrap_log_warn!(FontStyle::from_str(&font_style)),
-                        )
-                        .get(&assets)
-                        .await;
+                        );
+                        let font = key.get(&assets).await;
                         async_run.run(move |world| {
-                            world.add_component(id, font_arc(), font).ok();
+                            if world.add_component(id, font_arc(), font.clone()).is_ok() {
+                                let referrer = font_referrer(id);
+                                assets.graph.lock().release(&referrer);
+                                assets.reference(referrer, &key, &font);
+                            }
                         });
                     });
                 }
             }),
-            query(font_arc().changed()).to_system(|q, world, qs, _| {
-                for (id, font) in q.collect_cloned(world, qs) {
-                    let brush = Arc::new(Mutex::new(GlyphBrushBuilder::using_font(font.deref().clone()).build()));
-                    world.add_component(id, glyph_brush(), brush).unwrap();
+            query(font_preloads().changed()).to_system(|q, world, qs, _| {
+                for (id, urls) in q.collect_cloned(world, qs) {
+                    let async_run = world.resource(async_run()).clone();
+                    let assets = world.resource(asset_cache()).clone();
+                    world.resource(runtime()).spawn(async move {
+                        let mut fonts = Vec::new();
+                        for url in urls {
+                            let font = match AbsAssetUrl::parse(&url) {
+                                Ok(url) => FontFromUrl(url).get(&assets).await.map_err(anyhow::Error::from),
+                                Err(err) => Err(err),
+                            };
+                            match font {
+                                Ok(font) => fonts.push(font),
+                                Err(err) => log::warn!("Failed to preload the font {url}: {err:?}"),
+                            }
+                        }
+                        async_run.run(move |world| {
+                            world.add_component(id, preloaded_fonts(), fonts).ok();
+                        });
+                    });
+                }
+            }),
+            query(()).incl(font_arc()).despawned().to_system({
+                let atlases = atlases.clone();
+                move |q, world, qs, _| {
+                    let assets = world.resource(asset_cache()).clone();
+                    let mut atlases = atlases.lock();
+                    for (id, _) in q.collect_cloned(world, qs) {
+                        assets.graph.lock().release(&font_referrer(id));
+                        atlases.remove(id);
+                    }
                 }
             }),
             query(()).incl(mesh_to_local()).incl(text()).to_system(|q, world, qs, _| {
@@ -259,113 +273,53 @@ pub fn systems(use_gpu: bool) -> SystemGroup {
                     world.set_if_changed(id, mesh_to_local(), Mat4::from_scale(Vec3::ONE / scale_factor)).unwrap();
                 }
             }),
-            {
-                let q = query((glyph_brush().changed(), text().changed(), font_size().changed(), font_arc()));
-                if use_gpu {
-                    q.incl(text_texture())
-                } else {
-                    q
-                }
-            }
-            .optional_changed(text_case())
-            .optional_changed(min_width())
-            .to_system(move |q, world, qs, _| {
-                let scale_factor = world.resource_opt(window_scale_factor()).cloned().unwrap_or(1.) as f32;
-                for (id, (glyph_brush, text, font_size, font)) in q.collect_cloned(world, qs) {
+            query((text().changed(), font_size().changed(), font_arc().changed()))
+                .optional_changed(text_case())
+                .optional_changed(min_width())
+                .to_system(move |q, world, qs, _| {
+                    let scale_factor = world.resource_opt(window_scale_factor()).cloned().unwrap_or(1.) as f32;
                     let assets = world.resource(asset_cache()).clone();
-                    let text = world.get(id, text_case()).unwrap_or_default().format(text);
-                    let min_width = world.get(id, min_width()).unwrap_or(0.);
-                    let min_height = world.get(id, min_height()).unwrap_or(0.);
+                    let gpu = if use_gpu { Some(world.resource(gpu()).clone()) } else { None };
+                    let mut atlases = atlases.lock();
 
-                    loop {
-                        let process_result = {
-                            let mut brush = glyph_brush.lock();
-                            brush.queue(Section::default().add_text(glyph_brush::Text::new(&text).with_scale(pt_size_to_px_scale(
-                                &*font,
-                                font_size,
-                                scale_factor,
-                            ))));
-                            brush.process_queued(
-                                |rect, tex_data| {
-                                    if !use_gpu {
-                                        return;
-                                    }
-                                    let gpu = world.resource(gpu());
+                    let mut updated = HashSet::new();
+                    for (id, (text, font_size, font)) in q.collect_cloned(world, qs) {
+                        let text = world.get(id, text_case()).unwrap_or_default().format(text);
+                        let scale = pt_size_to_px_scale(&*font, font_size, scale_factor);
+                        atlases.set_text(id, &font, text, scale, gpu.clone().map(|gpu| (gpu, &assets)));
+                        updated.insert(id);
+                    }
+                    // Texts whose glyphs moved in their atlas need new meshes too, even if they didn't change themselves
+                    updated.extend(atlases.process(&assets));
 
-                                    gpu.queue.write_texture(
-                                        wgpu::ImageCopyTexture {
-                                            texture: &world.get_ref(id, text_texture()).unwrap().handle,
-                                            mip_level: 0,
-                                            origin: wgpu::Origin3d { x: rect.min[0], y: rect.min[1], z: 0 },
-                                            aspect: wgpu::TextureAspect::All,
-                                        },
-                                        tex_data,
-                                        wgpu::ImageDataLayout {
-                                            offset: 0,
-                                            bytes_per_row: NonZeroU32::new(rect.width()),
-                                            rows_per_image: NonZeroU32::new(rect.height()),
-                                        },
-                                        wgpu::Extent3d { width: rect.width(), height: rect.height(), depth_or_array_layers: 1 },
-                                    );
-                                },
-                                |vertex_data| GlyphVertex { tex_coords: vertex_data.tex_coords, pixel_coords: vertex_data.pixel_coords },
-                            )
-                        };
-                        match process_result {
-                            Ok(BrushAction::Draw(vertices)) => {
-                                let has_verts = !vertices.is_empty();
-                                let cpu_mesh = mesh_from_glyph_vertices(vertices);
-                                let bounding = if has_verts { cpu_mesh.aabb().unwrap() } else { AABB::new(Vec3::ZERO, Vec3::ZERO) };
-                                let mut data = Entity::new()
-                                    .with(width(), (bounding.max.x / scale_factor).max(min_width))
-                                    .with(height(), (bounding.max.y / scale_factor).max(min_height));
-                                if use_gpu {
-                                    data.set(mesh(), GpuMesh::from_mesh(assets.clone(), &cpu_mesh));
-                                }
-                                world.add_components(id, data).unwrap();
-                                break;
-                            }
-                            Ok(BrushAction::ReDraw) => {
-                                break;
-                            }
-                            Err(BrushError::TextureTooSmall { suggested }) => {
-                                if !use_gpu {
-                                    return;
-                                }
-                                let size = wgpu::Extent3d { width: suggested.0, height: suggested.1, depth_or_array_layers: 1 };
-                                let gpu = world.resource(gpu()).clone();
-                                let texture = Arc::new(Texture::new(
-                                    gpu,
-                                    &wgpu::TextureDescriptor {
-                                        size,
-                                        mip_level_count: 1,
-                                        sample_count: 1,
-                                        dimension: wgpu::TextureDimension::D2,
-                                        format: wgpu::TextureFormat::R8Unorm,
-                                        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                                        label: Some("Text.texture"),
-                                    },
-                                ));
-                                glyph_brush.lock().resize_texture(suggested.0, suggested.1);
-                                let view = Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()));
-                                world
-                                    .add_components(
-                                        id,
-                                        Entity::new()
-                                            .with(material(), SharedMaterial::new(TextMaterial::new(assets.clone(), view.clone())))
-                                            .with(text_texture(), texture),
-                                    )
-                                    .unwrap();
-                            }
+                    for id in updated {
+                        let Some((glyphs, atlas_material)) = atlases.glyphs(id) else { continue };
+                        let min_width = world.get(id, min_width()).unwrap_or(0.);
+                        let min_height = world.get(id, min_height()).unwrap_or(0.);
+                        let cpu_mesh = mesh_from_glyph_vertices(glyphs);
+                        let bounding = if glyphs.is_empty() { AABB::new(Vec3::ZERO, Vec3::ZERO) } else { cpu_mesh.aabb().unwrap() };
+                        let mut data = Entity::new()
+                            .with(width(), (bounding.max.x / scale_factor).max(min_width))
+                            .with(height(), (bounding.max.y / scale_factor).max(min_height));
+                        if use_gpu {
+                            data.set(mesh(), GpuMesh::from_mesh(assets.clone(), &cpu_mesh));
                         }
+                        if let Some(atlas_material) = atlas_material {
+                            data.set(material(), atlas_material);
+                        }
+                        world.add_components(id, data).ok();
                     }
-                }
-            }),
+                }),
         ],
     )
 }
 
 // From: https://docs.rs/glyph_brush/latest/glyph_brush/ab_glyph/trait.Font.html#units
+/// Text entities reference their font separately from any other asset they use, so that it can be released when the font changes
+fn font_referrer(id: EntityId) -> String {
+    format!("{id}/font")
+}
+
 fn pt_size_to_px_scale<F: Font>(font: &F, pt_size: f32, screen_scale_factor: f32) -> PxScale {
     let px_per_em = pt_size * screen_scale_factor; // * (96.0 / 72.0); // this part is used in the example but seems to make the scale wrong, hence disabled
     let units_per_em = font.units_per_em().unwrap();
@@ -373,18 +327,18 @@ fn pt_size_to_px_scale<F: Font>(font: &F, pt_size: f32, screen_scale_factor: f32
     PxScale::from(px_per_em * height / units_per_em)
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct GlyphVertex {
     pub tex_coords: Rect,
     pub pixel_coords: Rect,
 }
 
-fn mesh_from_glyph_vertices(vertices: Vec<GlyphVertex>) -> Mesh {
+fn mesh_from_glyph_vertices(vertices: &[GlyphVertex]) -> Mesh {
     let mut positions = Vec::new();
     let mut texcoords = Vec::new();
     let mut normals = Vec::new();
     let mut indices = Vec::new();
-    for vert in vertices.into_iter() {
+    for vert in vertices {
         let offset = positions.len() as u32;
         positions.push(vec3(vert.pixel_coords.min.x, vert.pixel_coords.min.y, 0.));
         positions.push(vec3(vert.pixel_coords.max.x, vert.pixel_coords.min.y, 0.));
@@ -0,0 +1,310 @@
+use ambient_core::{hierarchy::children, transform::local_to_world};
+use ambient_ecs::{query, world_events, EntityId, FnSystem, SystemGroup, World, WorldEventReader};
+use ambient_event_types::{WINDOW_KEYBOARD_INPUT, WINDOW_MOUSE_INPUT};
+use ambient_input::{
+    accessibility::{accessibility_label, accessibility_role, activate_ui, focus_order, get_ui_focus, set_ui_focus},
+    event_keyboard_input, event_mouse_input, keyboard_modifiers, keycode,
+    picking::mouse_over,
+};
+use ambient_layout::{height, width};
+use ambient_text::text;
+use ambient_window_types::{ModifiersState, VirtualKeyCode};
+use glam::Vec2;
+use itertools::Itertools;
+use ordered_float::OrderedFloat;
+
+/// The focusable UI entities, in the order Tab moves through them
+pub fn focus_order_entities(world: &World) -> Vec<EntityId> {
+    query((focus_order(), local_to_world()))
+        .iter(world, None)
+        .map(|(id, (order, transform))| {
+            let position = transform.to_scale_rotation_translation().2;
+            (id, (*order, OrderedFloat(position.y), OrderedFloat(position.x)))
+        })
+        .sorted_by_key(|(_, key)| *key)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Moves the keyboard focus to the next focusable UI entity, or the previous one if `backwards`
+pub fn move_ui_focus(world: &mut World, backwards: bool) {
+    let entities = focus_order_entities(world);
+    if entities.is_empty() {
+        return;
+    }
+    let current = get_ui_focus(world).and_then(|focus| entities.iter().position(|id| *id == focus));
+    let next = match (current, backwards) {
+        (None, false) => 0,
+        (None, true) => entities.len() - 1,
+        (Some(index), false) => (index + 1) % entities.len(),
+        (Some(index), true) => (index + entities.len() - 1) % entities.len(),
+    };
+    set_ui_focus(world, entities[next]);
+}
+
+/// Gives the keyboard focus to the focusable UI entity under the mouse, or clears it if there's none, like when clicking
+pub fn focus_hovered(world: &mut World) {
+    let hovered = query((focus_order(), mouse_over())).iter(world, None).find(|(_, (_, over))| **over > 0).map(|(id, _)| id);
+    set_ui_focus(world, hovered.unwrap_or_else(EntityId::null));
+}
+
+/// Tab and Shift-Tab move the keyboard focus between the UI entities with a `focus_order`, and Enter or Space activates
+/// the focused entity. Clicking focuses the focusable entity under the mouse, or clears the focus
+pub fn focus_systems() -> SystemGroup {
+    let mut reader = WorldEventReader::new();
+    SystemGroup::new(
+        "ui/focus",
+        vec![Box::new(FnSystem::new(move |world, _| {
+            let mut clicked = false;
+            let events = reader
+                .iter(world.resource(world_events()))
+                .filter_map(|(_, (name, event))| {
+                    if name == WINDOW_MOUSE_INPUT {
+                        clicked |= event.get(event_mouse_input()) == Some(true);
+                        return None;
+                    }
+                    if name != WINDOW_KEYBOARD_INPUT || event.get(event_keyboard_input()) != Some(true) {
+                        return None;
+                    }
+                    let keycode = event.get_ref(keycode())?.parse::<VirtualKeyCode>().ok()?;
+                    let modifiers = ModifiersState::from_bits_truncate(event.get(keyboard_modifiers()).unwrap_or_default());
+                    Some((keycode, modifiers))
+                })
+                .collect_vec();
+
+            if let Some(focus) = get_ui_focus(world) {
+                if !world.exists(focus) {
+                    set_ui_focus(world, EntityId::null());
+                }
+            }
+            if clicked {
+                focus_hovered(world);
+            }
+            for (keycode, modifiers) in events {
+                match keycode {
+                    VirtualKeyCode::Tab => move_ui_focus(world, modifiers.shift()),
+                    VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter | VirtualKeyCode::Space => {
+                        let Some(focus) = get_ui_focus(world) else { continue };
+                        // Text inputs use these keys themselves
+                        if world.get_ref(focus, accessibility_role()).map(|role| role.as_str() != "text_input").unwrap_or(true) {
+                            activate_ui(world, focus);
+                        }
+                    }
+                    VirtualKeyCode::Escape => set_ui_focus(world, EntityId::null()),
+                    _ => {}
+                }
+            }
+        }))],
+    )
+}
+
+/// The label of `id`, or the text of its first descendant with text
+fn label(world: &World, id: EntityId) -> Option<String> {
+    if let Ok(label) = world.get_ref(id, accessibility_label()) {
+        return Some(label.clone());
+    }
+    if let Ok(text) = world.get_ref(id, text()) {
+        return Some(text.clone());
+    }
+    world.get_ref(id, children()).ok()?.iter().find_map(|child| label(world, *child))
+}
+
+/// The screen-space rectangle of `id`
+fn bounds(world: &World, id: EntityId) -> (Vec2, Vec2) {
+    let position =
+        world.get(id, local_to_world()).map(|transform| transform.to_scale_rotation_translation().2.truncate()).unwrap_or_default();
+    let size = Vec2::new(world.get(id, width()).unwrap_or_default(), world.get(id, height()).unwrap_or_default());
+    (position, position + size)
+}
+
+#[cfg(not(target_os = "unknown"))]
+pub use native::Accessibility;
+
+#[cfg(not(target_os = "unknown"))]
+mod native {
+    use std::num::NonZeroU128;
+
+    use accesskit::{
+        Action, ActionHandler, ActionRequest, DefaultActionVerb, NodeBuilder, NodeClassSet, NodeId, Rect, Role, Tree, TreeUpdate,
+    };
+    use ambient_ecs::{query, EntityId, World};
+    use ambient_input::accessibility::{accessibility_role, activate_ui, focus_order, get_ui_focus, set_ui_focus};
+    use winit::{event::WindowEvent, window::Window};
+
+    use super::{bounds, label};
+
+    const ROOT_ID: NodeId = NodeId(match NonZeroU128::new(1) {
+        Some(id) => id,
+        None => unreachable!(),
+    });
+
+    fn node_id(id: EntityId) -> NodeId {
+        NodeId(NonZeroU128::new(id.0).unwrap_or(ROOT_ID.0))
+    }
+
+    fn role(role: &str) -> Role {
+        match role {
+            "button" => Role::Button,
+            "checkbox" => Role::CheckBox,
+            "text_input" => Role::TextField,
+            "slider" => Role::Slider,
+            "label" => Role::StaticText,
+            "image" => Role::Image,
+            "list" => Role::List,
+            "list_item" => Role::ListItem,
+            _ => Role::Group,
+        }
+    }
+
+    struct Actions(flume::Sender<ActionRequest>);
+    impl ActionHandler for Actions {
+        fn do_action(&self, request: ActionRequest) {
+            self.0.send(request).ok();
+        }
+    }
+
+    /// Exposes the UI entities with an `accessibility_role` to screen readers and other assistive technologies,
+    /// and lets them focus and activate those entities.
+    pub struct Accessibility {
+        adapter: accesskit_winit::Adapter,
+        actions: flume::Receiver<ActionRequest>,
+        classes: NodeClassSet,
+    }
+    impl std::fmt::Debug for Accessibility {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("Accessibility").finish_non_exhaustive()
+        }
+    }
+    impl Accessibility {
+        /// Must be created before the window is shown
+        pub fn new(window: &Window) -> Self {
+            let (actions_tx, actions) = flume::unbounded();
+            let adapter = accesskit_winit::Adapter::with_action_handler(
+                window,
+                || {
+                    let mut classes = NodeClassSet::new();
+                    TreeUpdate {
+                        nodes: vec![(ROOT_ID, NodeBuilder::new(Role::Window).build(&mut classes))],
+                        tree: Some(Tree::new(ROOT_ID)),
+                        focus: None,
+                    }
+                },
+                Box::new(Actions(actions_tx)),
+            );
+            Self { adapter, actions, classes: NodeClassSet::new() }
+        }
+        pub fn on_window_event(&self, window: &Window, event: &WindowEvent) {
+            self.adapter.on_event(window, event);
+        }
+        /// Handles the requests from assistive technologies, and updates the tree they see if any are active
+        pub fn update(&mut self, world: &mut World) {
+            for request in self.actions.try_iter().collect::<Vec<_>>() {
+                let Some(id) = query(accessibility_role()).iter(world, None).map(|(id, _)| id).find(|id| node_id(*id) == request.target)
+                else {
+                    continue;
+                };
+                match request.action {
+                    Action::Focus => set_ui_focus(world, id),
+                    Action::Default => activate_ui(world, id),
+                    _ => {}
+                }
+            }
+            let classes = &mut self.classes;
+            self.adapter.update_if_active(|| build_tree(world, classes));
+        }
+    }
+
+    fn build_tree(world: &World, classes: &mut NodeClassSet) -> TreeUpdate {
+        let mut nodes = Vec::new();
+        let mut root_children = Vec::new();
+        for (id, role_name) in query(accessibility_role()).iter(world, None) {
+            let mut node = NodeBuilder::new(role(role_name));
+            if let Some(label) = label(world, id) {
+                node.set_name(label);
+            }
+            let (min, max) = bounds(world, id);
+            node.set_bounds(Rect { x0: min.x as f64, y0: min.y as f64, x1: max.x as f64, y1: max.y as f64 });
+            if world.has_component(id, focus_order()) {
+                node.add_action(Action::Focus);
+                node.add_action(Action::Default);
+                node.set_default_action_verb(DefaultActionVerb::Click);
+            }
+            root_children.push(node_id(id));
+            nodes.push((node_id(id), node.build(classes)));
+        }
+        let mut root = NodeBuilder::new(Role::Window);
+        root.set_children(root_children);
+        nodes.push((ROOT_ID, root.build(classes)));
+        TreeUpdate { nodes, tree: Some(Tree::new(ROOT_ID)), focus: Some(get_ui_focus(world).map(node_id).unwrap_or(ROOT_ID)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{vec3, Mat4};
+
+    use super::*;
+
+    fn world() -> World {
+        ambient_ecs::init_components();
+        ambient_core::init_all_components();
+        ambient_input::init_all_components();
+        let mut world = World::new("move_ui_focus");
+        world.add_resource(world_events(), Default::default());
+        world
+    }
+
+    fn focusable(world: &mut World, order: i32, x: f32, y: f32) -> EntityId {
+        ambient_ecs::Entity::new().with(focus_order(), order).with(local_to_world(), Mat4::from_translation(vec3(x, y, 0.))).spawn(world)
+    }
+
+    #[test]
+    fn tab_moves_by_order_then_position() {
+        let mut world = world();
+        let bottom = focusable(&mut world, 0, 0., 100.);
+        let top_right = focusable(&mut world, 0, 50., 0.);
+        let top_left = focusable(&mut world, 0, 0., 0.);
+        let last = focusable(&mut world, 1, 0., 0.);
+        assert_eq!(focus_order_entities(&world), vec![top_left, top_right, bottom, last]);
+
+        for expected in [top_left, top_right, bottom, last, top_left] {
+            move_ui_focus(&mut world, false);
+            assert_eq!(get_ui_focus(&world), Some(expected));
+        }
+        move_ui_focus(&mut world, true);
+        assert_eq!(get_ui_focus(&world), Some(last));
+    }
+
+    #[test]
+    fn shift_tab_without_focus_starts_from_the_end() {
+        let mut world = world();
+        let first = focusable(&mut world, 0, 0., 0.);
+        let second = focusable(&mut world, 0, 0., 10.);
+        move_ui_focus(&mut world, true);
+        assert_eq!(get_ui_focus(&world), Some(second));
+        move_ui_focus(&mut world, true);
+        assert_eq!(get_ui_focus(&world), Some(first));
+    }
+
+    #[test]
+    fn clicking_focuses_the_hovered_entity() {
+        let mut world = world();
+        let first = focusable(&mut world, 0, 0., 0.);
+        let second = focusable(&mut world, 0, 0., 10.);
+        world.add_component(second, mouse_over(), 1).unwrap();
+        focus_hovered(&mut world);
+        assert_eq!(get_ui_focus(&world), Some(second));
+
+        world.set(second, mouse_over(), 0).unwrap();
+        world.add_component(first, mouse_over(), 0).unwrap();
+        focus_hovered(&mut world);
+        assert_eq!(get_ui_focus(&world), None);
+    }
+
+    #[test]
+    fn no_focusable_entities() {
+        let mut world = world();
+        move_ui_focus(&mut world, false);
+        assert_eq!(get_ui_focus(&world), None);
+    }
+}
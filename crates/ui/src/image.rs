@@ -1,8 +1,10 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
 use ambient_core::{asset_cache, mesh, transform::*, ui_scene};
+use ambient_ecs::{components, query, Debuggable, Description, Name, SystemGroup};
 use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
 use ambient_gpu::{
+    mesh_buffer::GpuMesh,
     std_assets::{DefaultNormalMapViewKey, PixelTextureViewKey},
     texture::TextureView,
     texture_loaders::{TextureFromBytes, TextureFromUrl},
@@ -16,20 +18,88 @@ use ambient_renderer::{
 use ambient_std::{
     asset_cache::{AsyncAssetKeyExt, SyncAssetKeyExt},
     asset_url::AbsAssetUrl,
-    cb, CowStr,
+    cb,
+    download_asset::{AssetError, JsonFromUrl},
+    mesh::Mesh,
+    CowStr,
 };
+use anyhow::Context;
 use glam::*;
+use serde::{Deserialize, Serialize};
 
 use super::UIBase;
 use crate::layout::*;
 
+components!("ui", {
+    @[
+        Debuggable,
+        Name["Image nine-slice"],
+        Description["The insets `(top, right, bottom, left)`, in pixels, of the corners and edges of this image, which keep their size when the image is resized while its center stretches."]
+    ]
+    image_nine_slice: Vec4,
+    @[
+        Debuggable,
+        Name["Image region"],
+        Description["The region of its texture this image shows, in texture coordinates `(min x, min y, max x, max y)`, e.g. a sprite in an atlas."]
+    ]
+    image_region: Vec4,
+    @[Debuggable, Name["Image texture size"], Description["The size of the texture of this image in pixels, which its nine-slice insets are in."]]
+    image_texture_size: Vec2,
+});
+
+/// A rectangle of an image, in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageRegion {
+    pub position: UVec2,
+    pub size: UVec2,
+}
+
+/// The insets, in pixels, of the corners and edges of an image, which keep their size when the image is resized while its
+/// center stretches; typically used for skinned buttons and panels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NineSlice {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+impl NineSlice {
+    pub fn even(inset: f32) -> Self {
+        Self { top: inset, right: inset, bottom: inset, left: inset }
+    }
+}
+
+/// The metadata of an image atlas: the image, relative to the metadata file, and its named sprites
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImageAtlas {
+    pub image: String,
+    pub sprites: HashMap<String, AtlasSprite>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtlasSprite {
+    #[serde(flatten)]
+    pub region: ImageRegion,
+    #[serde(default)]
+    pub nine_slice: Option<NineSlice>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Image {
     pub texture: Option<Arc<TextureView>>,
+    /// The region of the texture to show; the whole texture if `None`
+    pub region: Option<ImageRegion>,
+    pub nine_slice: Option<NineSlice>,
+}
+impl Image {
+    pub fn new(texture: Option<Arc<TextureView>>) -> Self {
+        Self { texture, region: None, nine_slice: None }
+    }
 }
 impl ElementComponent for Image {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let Image { texture } = *self;
+        let Image { texture, region, nine_slice } = *self;
+        let texture_size = texture.as_ref().map(|x| vec2(x.texture.size.width as f32, x.texture.size.height as f32));
         let assets = hooks.world.resource(asset_cache()).clone();
         let texture_id = texture.as_ref().map(|x| x.texture.id);
         let mat = hooks.use_memo_with(texture_id, move |_, _| {
@@ -43,6 +113,7 @@ impl ElementComponent for Image {
                         base_color: texture,
                         normalmap: DefaultNormalMapViewKey.get(&assets),
                         metallic_roughness: PixelTextureViewKey::white().get(&assets),
+                        emissive: PixelTextureViewKey::white().get(&assets),
                         transparent: None,
                         double_sided: None,
                         depth_write_enabled: None,
@@ -63,6 +134,19 @@ impl ElementComponent for Image {
             .init_default(gpu_primitives())
             .init(ui_scene(), ())
             .init(color(), Vec4::ONE);
+        // Sliced images get a mesh of their size in pixels from `systems`, rebuilt when they're resized
+        let el = match texture_size {
+            Some(texture_size) if region.is_some() || nine_slice.is_some() => {
+                let region = region.unwrap_or(ImageRegion { position: UVec2::ZERO, size: texture_size.as_uvec2() });
+                let min = region.position.as_vec2() / texture_size;
+                let max = (region.position + region.size).as_vec2() / texture_size;
+                let NineSlice { top, right, bottom, left } = nine_slice.unwrap_or_default();
+                el.set(image_region(), vec4(min.x, min.y, max.x, max.y))
+                    .set(image_nine_slice(), vec4(top, right, bottom, left))
+                    .set(image_texture_size(), texture_size)
+            }
+            _ => el,
+        };
 
         if let Some(mat) = mat {
             el.set(material(), mat)
@@ -72,6 +156,55 @@ impl ElementComponent for Image {
     }
 }
 
+/// A grid of 3x3 quads of `size`, whose corners keep the size of the `nine_slice` insets while the rest stretches
+fn sliced_mesh(size: Vec2, region: Vec4, nine_slice: Vec4, texture_size: Vec2) -> Mesh {
+    let [top, right, bottom, left] = nine_slice.to_array();
+    // The insets shrink if they don't fit
+    let fit = (size.x / (left + right).max(f32::EPSILON)).min(size.y / (top + bottom).max(f32::EPSILON)).min(1.);
+    let texture_size = texture_size.max(Vec2::ONE);
+    let xs = [0., left * fit, size.x - right * fit, size.x];
+    let ys = [0., top * fit, size.y - bottom * fit, size.y];
+    let us = [region.x, region.x + left / texture_size.x, region.z - right / texture_size.x, region.z];
+    let vs = [region.y, region.y + top / texture_size.y, region.w - bottom / texture_size.y, region.w];
+    let mut mesh = Mesh {
+        name: "ui_sliced_image".to_string(),
+        positions: Some(ys.iter().flat_map(|&y| xs.map(|x| vec3(x, y, 0.))).collect()),
+        normals: Some(vec![Vec3::Z; 16]),
+        texcoords: vec![vs.iter().flat_map(|&v| us.map(|u| vec2(u, v))).collect()],
+        indices: Some(
+            (0..3)
+                .flat_map(|row| {
+                    (0..3).flat_map(move |column| {
+                        let corner = row * 4 + column;
+                        [corner, corner + 1, corner + 4, corner + 1, corner + 5, corner + 4]
+                    })
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    mesh.create_tangents();
+    mesh
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "ui/image",
+        vec![query((width().changed(), height().changed(), image_region().changed(), image_nine_slice().changed(), image_texture_size()))
+            .to_system(|q, world, qs, _| {
+                for (id, (width, height, region, nine_slice, texture_size)) in q.collect_cloned(world, qs) {
+                    let mesh = sliced_mesh(vec2(width, height), region, nine_slice, texture_size);
+                    let gpu_mesh = GpuMesh::from_mesh(world.resource(asset_cache()).clone(), &mesh);
+                    world.set(id, self::mesh(), gpu_mesh).ok();
+                    if world.has_component(id, mesh_to_local_from_size()) {
+                        world.remove_component(id, mesh_to_local_from_size()).ok();
+                        world.set(id, mesh_to_local(), Mat4::IDENTITY).ok();
+                    }
+                }
+            })],
+    )
+}
+
 #[derive(Clone, Debug)]
 pub struct ImageFromBytes {
     pub bytes: Cow<'static, [u8]>,
@@ -92,18 +225,19 @@ impl ElementComponent for ImageFromBytes {
                 })
                 .and_then(Result::ok);
 
-        Image { texture }.el()
+        Image::new(texture).el()
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct ImageFromUrl {
     pub url: String,
+    pub nine_slice: Option<NineSlice>,
 }
 
 impl ElementComponent for ImageFromUrl {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let ImageFromUrl { url } = *self;
+        let ImageFromUrl { url, nine_slice } = *self;
 
         let texture = hooks
             .use_async(|w| {
@@ -117,6 +251,55 @@ impl ElementComponent for ImageFromUrl {
             })
             .and_then(Result::ok);
 
-        Image { texture }.el()
+        Image { texture, region: None, nine_slice }.el()
+    }
+}
+
+/// A sprite of an image atlas, whose region and nine-slice insets are read from the atlas metadata: a JSON [ImageAtlas] at `url`
+#[derive(Clone, Debug)]
+pub struct ImageFromAtlas {
+    pub url: String,
+    pub sprite: String,
+}
+
+impl ElementComponent for ImageFromAtlas {
+    fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
+        let ImageFromAtlas { url, sprite } = *self;
+
+        let image = hooks
+            .use_async(|w| {
+                let assets = w.resource(asset_cache()).clone();
+                async move {
+                    let url = AbsAssetUrl::parse(url)?;
+                    let atlas = JsonFromUrl::<ImageAtlas>::new(url.clone(), true).get(&assets).await?;
+                    let sprite = *atlas.sprites.get(&sprite).with_context(|| format!("The atlas {url} has no sprite {sprite}"))?;
+                    let image_url = url.resolve(&atlas.image).with_context(|| format!("Invalid image url in the atlas {url}"))?;
+                    let texture = TextureFromUrl { url: image_url, format: wgpu::TextureFormat::Rgba8UnormSrgb }.get(&assets).await?;
+                    Ok::<_, AssetError>((Arc::new(texture.create_view(&Default::default())), sprite))
+                }
+            })
+            .and_then(Result::ok);
+
+        match image {
+            Some((texture, sprite)) => Image { texture: Some(texture), region: Some(sprite.region), nine_slice: sprite.nine_slice }.el(),
+            None => Image::new(None).el(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nine_slice_corners_keep_their_size() {
+        let mesh = sliced_mesh(vec2(200., 50.), vec4(0., 0., 1., 1.), vec4(8., 4., 8., 4.), vec2(32., 32.));
+        let positions = mesh.positions.unwrap();
+        assert_eq!(positions[5], vec3(4., 8., 0.));
+        assert_eq!(positions[10], vec3(196., 42., 0.));
+        let texcoords = &mesh.texcoords[0];
+        assert_eq!(texcoords[5], vec2(4. / 32., 8. / 32.));
+        assert_eq!(texcoords[15], vec2(1., 1.));
+        assert_eq!(mesh.indices.unwrap().len(), 9 * 6);
     }
 }
@@ -20,9 +20,14 @@ use winit::window::CursorGrabMode;
 
 // mod asset_url;
 
+pub mod accessibility;
 mod component_editor;
 pub mod graph;
 mod image;
+pub mod minimap;
+pub mod panels;
+pub mod plot;
+mod transitions;
 
 pub use ambient_layout as layout;
 pub use ambient_rect as rect;
@@ -32,20 +37,25 @@ pub use ambient_text::*;
 pub use ambient_ui_components::clickarea::*;
 pub use ambient_ui_components::default_theme as style_constants;
 pub use ambient_ui_components::*;
-pub use ambient_ui_components::{button, dropdown, prompt, select, tabs, throbber};
-pub use ambient_ui_components::{editor::*, layout::*, scroll_area::*, text::*};
+pub use ambient_ui_components::{button, dropdown, prompt, select, table, tabs, throbber, transition};
+pub use ambient_ui_components::{editor::*, layout::*, scroll_area::*, text::*, touch::*};
 // pub use asset_url::*;
 pub use button::*;
 pub use component_editor::*;
 pub use dropdown::*;
 pub use editor::*;
 pub use layout::*;
+pub use panels::*;
+pub use plot::*;
 pub use prompt::*;
 pub use screens::*;
 pub use select::*;
 pub use style_constants::*;
+pub use table::*;
 pub use tabs::*;
+pub use theme::*;
 pub use throbber::*;
+pub use transition::*;
 
 pub use self::image::*;
 use ambient_event_types::{WINDOW_FOCUSED, WINDOW_MOUSE_MOTION};
@@ -56,10 +66,25 @@ pub fn init_all_components() {
     layout::init_gpu_components();
     rect::init_components();
     text::init_components();
+    transitions::init_components();
+    self::image::init_components();
+    minimap::init_components();
 }
 
 pub fn systems() -> SystemGroup {
-    SystemGroup::new("ui", vec![Box::new(rect::systems()), Box::new(text::systems(true)), Box::new(layout::layout_systems())])
+    SystemGroup::new(
+        "ui",
+        vec![
+            // First, so that the icons it spawns are drawn the same frame
+            Box::new(minimap::systems()),
+            Box::new(rect::systems()),
+            Box::new(text::systems(true)),
+            Box::new(layout::layout_systems()),
+            Box::new(transitions::systems()),
+            Box::new(self::image::systems()),
+            Box::new(accessibility::focus_systems()),
+        ],
+    )
 }
 
 impl Default for HighjackMouse {
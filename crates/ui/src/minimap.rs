@@ -0,0 +1,131 @@
+//! Shows the entities with a `minimap_icon` on the UI elements which are a `minimap`, projected from above
+use std::collections::BTreeMap;
+
+use ambient_core::{
+    hierarchy::{children, parent},
+    minimap::{minimap, minimap_center, minimap_icon, minimap_icon_size, minimap_rotation, minimap_zoom},
+    transform::{local_to_parent, local_to_world, mesh_to_local, mesh_to_world, scale, translation},
+    ui_scene,
+};
+use ambient_ecs::{components, query, Entity, EntityId, FnSystem, SystemGroup, World};
+use ambient_element::element_unmanaged_children;
+use ambient_layout::{gpu_ui_size, height, mesh_to_local_from_size, width};
+use ambient_rect::{background_color, border_radius, rect};
+use glam::{vec2, vec3, Mat4, Vec2, Vec3, Vec4};
+
+components!("ui", {
+    /// The icon of each entity shown on a minimap, by entity
+    minimap_icons: BTreeMap<EntityId, EntityId>,
+});
+
+const DEFAULT_ICON_SIZE: f32 = 8.;
+
+/// Where `position` is on a minimap of `size` pixels, which shows `center` at its center at `zoom` pixels per world unit,
+/// with the direction `rotation` radians counterclockwise from the world's y axis up. The world's z axis is ignored
+pub fn project_to_minimap(position: Vec3, center: Vec2, zoom: f32, rotation: f32, size: Vec2) -> Vec2 {
+    let offset = Vec2::from_angle(-rotation).rotate(position.truncate() - center) * zoom;
+    size / 2. + vec2(offset.x, -offset.y)
+}
+
+fn new_icon(canvas: EntityId, color: Vec4, size: f32, position: Vec2) -> Entity {
+    Entity::new()
+        .with(rect(), ())
+        .with(ui_scene(), ())
+        .with(gpu_ui_size(), Vec4::ZERO)
+        .with(mesh_to_local(), Mat4::IDENTITY)
+        .with(mesh_to_local_from_size(), ())
+        .with(scale(), Vec3::ONE)
+        .with_default(local_to_world())
+        .with_default(local_to_parent())
+        .with_default(mesh_to_world())
+        .with(translation(), icon_translation(position, size))
+        .with(width(), size)
+        .with(height(), size)
+        .with(border_radius(), Vec4::splat(size / 2.))
+        .with(background_color(), color)
+        .with(parent(), canvas)
+}
+
+/// Centers the icon on its position, in front of the minimap
+fn icon_translation(position: Vec2, size: f32) -> Vec3 {
+    vec3(position.x - size / 2., position.y - size / 2., -0.001)
+}
+
+fn update_minimaps(world: &mut World) {
+    let sources = query((minimap_icon(), translation()))
+        .iter(world, None)
+        .map(|(id, (&color, &position))| (id, color, position, world.get(id, minimap_icon_size()).unwrap_or(DEFAULT_ICON_SIZE)))
+        .collect::<Vec<_>>();
+    let canvases = query((minimap(), width(), height()))
+        .iter(world, None)
+        .map(|(id, (_, &width, &height))| (id, vec2(width, height)))
+        .collect::<Vec<_>>();
+
+    for (canvas, canvas_size) in canvases {
+        let center = world.get(canvas, minimap_center()).unwrap_or_default();
+        let zoom = world.get(canvas, minimap_zoom()).unwrap_or(1.);
+        let rotation = world.get(canvas, minimap_rotation()).unwrap_or_default();
+        let old_icons = world.get_ref(canvas, minimap_icons()).cloned().unwrap_or_default();
+
+        let mut icons = BTreeMap::new();
+        for &(source, color, position, size) in &sources {
+            let position = project_to_minimap(position, center, zoom, rotation, canvas_size);
+            // Icons outside of the minimap aren't shown
+            if position.cmplt(Vec2::ZERO).any() || position.cmpgt(canvas_size).any() {
+                continue;
+            }
+            let icon = match old_icons.get(&source) {
+                Some(&icon) if world.exists(icon) => {
+                    world.set_if_changed(icon, translation(), icon_translation(position, size)).ok();
+                    world.set_if_changed(icon, background_color(), color).ok();
+                    world.set_if_changed(icon, width(), size).ok();
+                    world.set_if_changed(icon, height(), size).ok();
+                    world.set_if_changed(icon, border_radius(), Vec4::splat(size / 2.)).ok();
+                    icon
+                }
+                _ => new_icon(canvas, color, size, position).spawn(world),
+            };
+            icons.insert(source, icon);
+        }
+        for (source, icon) in &old_icons {
+            if icons.get(source) != Some(icon) {
+                world.despawn(*icon);
+            }
+        }
+
+        // The icons are the only children of the minimap, so the element tree mustn't replace them with its own
+        let icon_ids = icons.values().copied().collect::<Vec<_>>();
+        if world.get_ref(canvas, children()).map_or(true, |childs| *childs != icon_ids) {
+            world.add_component(canvas, children(), icon_ids).ok();
+        }
+        if !world.has_component(canvas, element_unmanaged_children()) {
+            world.add_component(canvas, element_unmanaged_children(), ()).ok();
+        }
+        if old_icons != icons {
+            world.add_component(canvas, minimap_icons(), icons).ok();
+        }
+    }
+}
+
+pub(crate) fn systems() -> SystemGroup {
+    SystemGroup::new("ui/minimap", vec![Box::new(FnSystem::new(|world, _| update_minimaps(world)))])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn projection() {
+        let size = vec2(200., 100.);
+        assert_eq!(project_to_minimap(Vec3::ZERO, Vec2::ZERO, 1., 0., size), vec2(100., 50.));
+        // Up the map is +y in the world
+        assert_eq!(project_to_minimap(vec3(10., 20., 5.), Vec2::ZERO, 2., 0., size), vec2(120., 10.));
+        assert_eq!(project_to_minimap(vec3(10., 20., 0.), vec2(10., 20.), 2., 0., size), vec2(100., 50.));
+        // With -x up, +y is to the right
+        let rotated = project_to_minimap(vec3(0., 10., 0.), Vec2::ZERO, 1., FRAC_PI_2, size);
+        assert!((rotated - vec2(110., 50.)).length() < 1e-4);
+    }
+}
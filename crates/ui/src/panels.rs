@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ambient_core::{transform::translation, window::cursor_position};
+use ambient_ecs::World;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_event_types::WINDOW_MOUSE_INPUT;
+use ambient_input::event_mouse_input;
+use ambient_layout::{docking, height, width, Docking};
+use ambient_settings::{settings, update_settings, PanelDock, PanelLayout, Settings};
+use ambient_ui_components::{
+    button::{Button, ButtonStyle},
+    default_theme::StylesExt,
+    layout::{Dock, FlowColumn, FlowRow, WindowSized},
+    text::Text,
+    theme::{tokens, ThemeExt},
+    UIBase, UIExt,
+};
+use glam::{vec3, Vec2};
+
+const MIN_PANEL_SIZE: f32 = 100.;
+const RESIZE_HANDLE_SIZE: f32 = 6.;
+
+/// A panel of a [PanelManager]
+#[derive(Debug, Clone)]
+pub struct Panel {
+    /// Identifies the panel in the saved layouts, so it must be stable
+    pub id: String,
+    pub title: String,
+    pub content: Element,
+    /// Where the panel is until the user moves it
+    pub default_layout: PanelLayout,
+}
+impl Panel {
+    pub fn new(id: impl Into<String>, title: impl Into<String>, content: Element) -> Self {
+        Self { id: id.into(), title: title.into(), content, default_layout: PanelLayout::default() }
+    }
+    pub fn default_layout(mut self, layout: PanelLayout) -> Self {
+        self.default_layout = layout;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DragKind {
+    Move,
+    Resize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Drag {
+    panel: String,
+    kind: DragKind,
+    start_cursor: Vec2,
+    start_layout: PanelLayout,
+}
+impl Drag {
+    fn apply(&self, cursor: Vec2) -> PanelLayout {
+        let delta = cursor - self.start_cursor;
+        let mut layout = self.start_layout;
+        match (self.kind, layout.dock) {
+            (DragKind::Move, _) => {
+                layout.x += delta.x;
+                layout.y += delta.y;
+            }
+            (DragKind::Resize, PanelDock::Floating) => {
+                layout.width += delta.x;
+                layout.height += delta.y;
+            }
+            (DragKind::Resize, PanelDock::Left) => layout.width += delta.x,
+            (DragKind::Resize, PanelDock::Right) => layout.width -= delta.x,
+            (DragKind::Resize, PanelDock::Bottom) => layout.height -= delta.y,
+        }
+        layout.width = layout.width.max(MIN_PANEL_SIZE);
+        layout.height = layout.height.max(MIN_PANEL_SIZE);
+        layout
+    }
+}
+
+/// Saves the layouts of the panels in the settings of the world, and in the settings file
+fn save_layouts(world: &mut World, layouts: &BTreeMap<String, PanelLayout>) {
+    if let Some(current) = world.resource_opt(settings()) {
+        let mut current = current.clone();
+        current.ui.panels = layouts.clone();
+        update_settings(world, current);
+    }
+    // Only the layouts are saved; the other settings in the world may have been overridden from the command line
+    let mut saved = Settings::load_or_default();
+    saved.ui.panels = layouts.clone();
+    if let Err(err) = saved.save() {
+        log::warn!("Failed to save the layout of the panels: {err:?}");
+    }
+}
+
+/// Lays out `panels` around `content`, which fills the rest of the window.
+///
+/// Panels float over the content, or are docked to the left, right or bottom of the window, where the panels docked to the
+/// same side are tabs. Floating panels are moved by their title bar, and panels are resized by their edge or corner. Their
+/// layouts are saved in the settings, by panel id.
+#[element_component]
+pub fn PanelManager(hooks: &mut Hooks, panels: Vec<Panel>, content: Element) -> Element {
+    let (layouts, set_layouts) =
+        hooks.use_state_with(|world| world.resource_opt(settings()).map(|settings| settings.ui.panels.clone()).unwrap_or_default());
+    let (active_tabs, set_active_tabs) = hooks.use_state(HashMap::<PanelDock, String>::new());
+    let (drag, set_drag) = hooks.use_state(None::<Drag>);
+
+    hooks.use_frame({
+        let drag = drag.clone();
+        let layouts = layouts.clone();
+        let set_layouts = set_layouts.clone();
+        move |world| {
+            let Some(drag) = &drag else { return };
+            let layout = drag.apply(*world.resource(cursor_position()));
+            if layouts.get(&drag.panel) != Some(&layout) {
+                let mut layouts = layouts.clone();
+                layouts.insert(drag.panel.clone(), layout);
+                set_layouts(layouts);
+            }
+        }
+    });
+    hooks.use_event(WINDOW_MOUSE_INPUT, {
+        let layouts = layouts.clone();
+        let set_drag = set_drag.clone();
+        move |world, event| {
+            if drag.is_some() && event.get(event_mouse_input()) == Some(false) {
+                set_drag(None);
+                save_layouts(world, &layouts);
+            }
+        }
+    });
+
+    let layout_of = |panel: &Panel| layouts.get(&panel.id).copied().unwrap_or(panel.default_layout);
+    let start_drag = |panel: &Panel, kind: DragKind| {
+        let set_drag = set_drag.clone();
+        let id = panel.id.clone();
+        let start_layout = layout_of(panel);
+        move |world: &mut World, _, _| {
+            let start_cursor = *world.resource(cursor_position());
+            set_drag(Some(Drag { panel: id.clone(), kind, start_cursor, start_layout }));
+        }
+    };
+    let dock_buttons = |panel: &Panel| {
+        let layout = layout_of(panel);
+        [
+            ("\u{f137}", "Dock left", PanelDock::Left),
+            ("\u{f138}", "Dock right", PanelDock::Right),
+            ("\u{f13a}", "Dock bottom", PanelDock::Bottom),
+            ("\u{f2d2}", "Float", PanelDock::Floating),
+        ]
+        .into_iter()
+        .map(|(icon, tooltip, dock)| {
+            let layouts = layouts.clone();
+            let set_layouts = set_layouts.clone();
+            let id = panel.id.clone();
+            Button::new(icon, move |world| {
+                let mut layouts = layouts.clone();
+                layouts.insert(id.clone(), PanelLayout { dock, ..layout });
+                save_layouts(world, &layouts);
+                set_layouts(layouts);
+            })
+            .toggled(layout.dock == dock)
+            .style(ButtonStyle::Flat)
+            .tooltip(tooltip)
+            .el()
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let mut docked = Vec::new();
+    for dock in [PanelDock::Left, PanelDock::Right, PanelDock::Bottom] {
+        let tabs = panels.iter().filter(|panel| layout_of(panel).dock == dock).collect::<Vec<_>>();
+        let Some(&first) = tabs.first() else { continue };
+        let active = tabs.iter().copied().find(|panel| active_tabs.get(&dock) == Some(&panel.id)).unwrap_or(first);
+        let layout = layout_of(active);
+        let tab_bar = FlowRow(
+            tabs.iter()
+                .map(|panel| {
+                    let active_tabs = active_tabs.clone();
+                    let set_active_tabs = set_active_tabs.clone();
+                    let id = panel.id.clone();
+                    Button::new(panel.title.clone(), move |_| {
+                        let mut active_tabs = active_tabs.clone();
+                        active_tabs.insert(dock, id.clone());
+                        set_active_tabs(active_tabs);
+                    })
+                    .toggled(panel.id == active.id)
+                    .style(ButtonStyle::Flat)
+                    .el()
+                })
+                .chain(dock_buttons(active))
+                .collect(),
+        )
+        .el();
+        let (docking_side, handle_side, handle) = match dock {
+            PanelDock::Left => (Docking::Left, Docking::Right, UIBase.el().set(width(), RESIZE_HANDLE_SIZE)),
+            PanelDock::Right => (Docking::Right, Docking::Left, UIBase.el().set(width(), RESIZE_HANDLE_SIZE)),
+            _ => (Docking::Bottom, Docking::Top, UIBase.el().set(height(), RESIZE_HANDLE_SIZE)),
+        };
+        let area = Dock(vec![
+            handle.set(docking(), handle_side).with_clickarea().on_mouse_down(start_drag(active, DragKind::Resize)).el(),
+            FlowColumn::el([tab_bar, active.content.clone()]).set(docking(), Docking::Fill),
+        ])
+        .el()
+        .set(docking(), docking_side)
+        .floating_panel();
+        docked.push(if dock == PanelDock::Bottom { area.set(height(), layout.height) } else { area.set(width(), layout.width) });
+    }
+    docked.push(content.set(docking(), Docking::Fill));
+
+    let floating = panels
+        .iter()
+        .filter(|panel| layout_of(panel).dock == PanelDock::Floating)
+        .enumerate()
+        .flat_map(|(i, panel)| {
+            let layout = layout_of(panel);
+            let z = -0.01 * (i + 1) as f32;
+            let title_bar = FlowRow(std::iter::once(Text::el(panel.title.clone()).section_style()).chain(dock_buttons(panel)).collect())
+                .el()
+                .with_clickarea()
+                .on_mouse_down(start_drag(panel, DragKind::Move))
+                .el()
+                .set(docking(), Docking::Top);
+            let window = Dock(vec![title_bar, panel.content.clone().set(docking(), Docking::Fill)])
+                .el()
+                .floating_panel()
+                .set(width(), layout.width)
+                .set(height(), layout.height)
+                .set(translation(), vec3(layout.x, layout.y, z));
+            let resize_handle = UIBase
+                .el()
+                .set(width(), RESIZE_HANDLE_SIZE * 2.)
+                .set(height(), RESIZE_HANDLE_SIZE * 2.)
+                .theme_background(tokens::TEXT_SECONDARY)
+                .set(
+                    translation(),
+                    vec3(layout.x + layout.width - RESIZE_HANDLE_SIZE * 2., layout.y + layout.height - RESIZE_HANDLE_SIZE * 2., z - 0.001),
+                )
+                .with_clickarea()
+                .on_mouse_down(start_drag(panel, DragKind::Resize))
+                .el();
+            [window, resize_handle]
+        })
+        .collect();
+
+    UIBase.el().children(vec![WindowSized(docked).el(), UIBase.el().set(translation(), vec3(0., 0., -0.1)).children(floating)])
+}
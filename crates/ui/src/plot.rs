@@ -0,0 +1,174 @@
+//! Line plots of series of points, e.g. frame times or network stats over time
+use ambient_core::{transform::translation, ui_scene};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_layout::{docking, height, space_between_items, width, Docking};
+use ambient_primitives::procedural::{create_procedural_mesh, destroy_procedural_mesh, procedural_mesh, update_procedural_mesh};
+use ambient_renderer::{color, flat_material::get_flat_shader_unlit, renderer_shader};
+use ambient_std::{cb, mesh::Mesh};
+use ambient_ui_components::{
+    default_theme::{StylesExt, STREET},
+    layout::{Dock, FlowColumn, FlowRow},
+    text::Text,
+    theme::{tokens, ThemeExt},
+    UIBase, UIExt,
+};
+use glam::{vec2, vec3, Vec2, Vec3, Vec4};
+use itertools::Itertools;
+
+const LINE_THICKNESS: f32 = 1.5;
+const AXIS_LABELS_WIDTH: f32 = 50.;
+const X_LABELS_HEIGHT: f32 = 14.;
+const LEGEND_SWATCH_SIZE: f32 = 8.;
+
+/// A series of a [Plot], e.g. a value over time
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotSeries {
+    pub name: String,
+    pub color: Vec4,
+    /// The `(x, y)` points of the line, in the order they're joined
+    pub points: Vec<Vec2>,
+}
+impl PlotSeries {
+    pub fn new(name: impl Into<String>, color: Vec4, points: Vec<Vec2>) -> Self {
+        Self { name: name.into(), color, points }
+    }
+    /// A series of `values` at x = 0, 1, 2, ..., e.g. a history of samples
+    pub fn from_values(name: impl Into<String>, color: Vec4, values: impl IntoIterator<Item = f32>) -> Self {
+        Self::new(name, color, values.into_iter().enumerate().map(|(i, value)| vec2(i as f32, value)).collect())
+    }
+}
+
+/// Rounds `value` up to 1, 2 or 5 times a power of ten, so that the axes have readable bounds
+fn nice_ceil(value: f32) -> f32 {
+    if value <= 0. {
+        return value;
+    }
+    let magnitude = 10f32.powf(value.log10().floor());
+    [1., 2., 5., 10.].into_iter().map(|step| step * magnitude).find(|&bound| bound >= value).unwrap_or(value)
+}
+
+fn format_bound(value: f32) -> String {
+    if value.abs() >= 100. || value == value.round() {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+/// Plots `series` as lines in an area of `size`, with their names in a legend below.
+///
+/// The x axis spans the points unless `x_range` is set, and the y axis spans from 0 (or the lowest negative point) to the
+/// highest point, rounded up, unless `y_range` is set. Points outside of the ranges are clamped to them.
+#[element_component]
+pub fn Plot(_hooks: &mut Hooks, series: Vec<PlotSeries>, size: Vec2, x_range: Option<(f32, f32)>, y_range: Option<(f32, f32)>) -> Element {
+    let points = series.iter().flat_map(|series| series.points.iter().copied());
+    let (x_min, x_max) = x_range.or_else(|| points.clone().map(|point| point.x).minmax().into_option()).unwrap_or((0., 1.));
+    let (y_min, y_max) = y_range.unwrap_or_else(|| {
+        let (min, max) = points.map(|point| point.y).fold((0f32, 0f32), |(min, max), y| (min.min(y), max.max(y)));
+        (if min < 0. { -nice_ceil(-min) } else { 0. }, nice_ceil(max))
+    });
+    let span = vec2(x_max - x_min, y_max - y_min).max(Vec2::splat(f32::EPSILON));
+    let to_area = |point: Vec2| {
+        let normalized = ((point - vec2(x_min, y_min)) / span).clamp(Vec2::ZERO, Vec2::ONE);
+        vec2(normalized.x, 1. - normalized.y) * size
+    };
+
+    let axes = [
+        UIBase.el().set(width(), 1.).set(height(), size.y),
+        UIBase.el().set(width(), size.x).set(height(), 1.).set(translation(), vec3(0., size.y, -0.001)),
+    ]
+    .into_iter()
+    .map(|axis| axis.theme_background(tokens::TEXT_SECONDARY));
+    let lines = series
+        .iter()
+        .map(|series| PlotLine { points: series.points.iter().map(|&point| to_area(point)).collect(), line_color: series.color }.el());
+    let area = UIBase.el().set(width(), size.x).set(height(), size.y).children(axes.chain(lines).collect());
+    let y_labels = Dock(vec![
+        Text::el(format_bound(y_max)).small_style().set(docking(), Docking::Top),
+        Text::el(format_bound(y_min)).small_style().set(docking(), Docking::Bottom),
+    ])
+    .el()
+    .set(width(), AXIS_LABELS_WIDTH)
+    .set(height(), size.y);
+    let x_labels = FlowRow::el([
+        UIBase.el().set(width(), AXIS_LABELS_WIDTH),
+        Dock(vec![
+            Text::el(format_bound(x_min)).small_style().set(docking(), Docking::Left),
+            Text::el(format_bound(x_max)).small_style().set(docking(), Docking::Right),
+        ])
+        .el()
+        .set(width(), size.x)
+        .set(height(), X_LABELS_HEIGHT),
+    ]);
+    let legend = FlowRow(
+        series
+            .iter()
+            .map(|series| {
+                FlowRow::el([
+                    UIBase.el().set(width(), LEGEND_SWATCH_SIZE).set(height(), LEGEND_SWATCH_SIZE).with_background(series.color),
+                    Text::el(series.name.clone()).small_style(),
+                ])
+                .set(space_between_items(), 4.)
+            })
+            .collect(),
+    )
+    .el()
+    .set(space_between_items(), STREET);
+
+    FlowColumn::el([FlowRow::el([y_labels, area]), x_labels, legend])
+}
+
+/// A line through `points`, in pixels, as a procedural mesh which is updated in place when they change
+#[element_component]
+fn PlotLine(hooks: &mut Hooks, points: Vec<Vec2>, line_color: Vec4) -> Element {
+    let handle = *hooks.use_ref_with(|world| create_procedural_mesh(world, line_mesh(&[]))).lock();
+    hooks.use_spawn(move |_| Box::new(move |world| destroy_procedural_mesh(world, handle)));
+    hooks.use_effect(points, move |world, points| {
+        update_procedural_mesh(world, handle, line_mesh(points)).ok();
+        Box::new(|_| {})
+    });
+    UIBase
+        .el()
+        .init(ui_scene(), ())
+        .init(renderer_shader(), cb(get_flat_shader_unlit))
+        .set(procedural_mesh(), handle)
+        .set(color(), line_color)
+}
+
+/// A quad of [LINE_THICKNESS] per segment, facing both ways as segments can go in any direction
+fn line_mesh(points: &[Vec2]) -> Mesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for (a, b) in points.iter().tuple_windows() {
+        let offset = (*b - *a).normalize_or_zero().perp() * LINE_THICKNESS / 2.;
+        let first = positions.len() as u32;
+        positions.extend([*a - offset, *b - offset, *a + offset, *b + offset].map(|corner| corner.extend(0.)));
+        indices.extend([0, 1, 2, 1, 3, 2, 0, 2, 1, 1, 2, 3].map(|index| first + index));
+    }
+    // Meshes can't be empty
+    if positions.is_empty() {
+        positions = vec![Vec3::ZERO; 3];
+        indices = vec![0, 1, 2];
+    }
+    Mesh {
+        name: "Plot line".to_string(),
+        normals: Some(vec![Vec3::Z; positions.len()]),
+        positions: Some(positions),
+        indices: Some(indices),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nice_bounds() {
+        assert_eq!(nice_ceil(0.), 0.);
+        assert_eq!(nice_ceil(7.), 10.);
+        assert_eq!(nice_ceil(13.), 20.);
+        assert!((nice_ceil(0.3) - 0.5).abs() < 1e-6);
+        assert_eq!(nice_ceil(400.), 500.);
+    }
+}
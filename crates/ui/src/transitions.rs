@@ -0,0 +1,145 @@
+//! Animates the UI elements which have a `transition_duration` from the values they had to the values they're set to,
+//! whether by their element, their theme or the layout
+use std::ops::{Add, Mul, Sub};
+
+use ambient_core::{
+    transform::translation,
+    transition::{transition_curve, transition_duration},
+    unscaled_dtime,
+};
+use ambient_ecs::{components, query, Component, ComponentValue, EntityId, SystemGroup, World};
+use ambient_layout::{height, width};
+use ambient_rect::background_color;
+use ambient_renderer::color;
+use glam::{Vec3, Vec4};
+
+components!("ui", {
+    /// What the transitions of an element animate between
+    transition_state: TransitionState,
+});
+
+/// The curve of the transitions of elements without a `transition_curve`
+const LINEAR: Vec4 = Vec4::new(0., 0., 1., 1.);
+const BISECTION_STEPS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tween<T> {
+    from: T,
+    to: T,
+    shown: T,
+    elapsed: f32,
+}
+impl<T: Copy + PartialEq + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>> Tween<T> {
+    fn new(value: T) -> Self {
+        Self { from: value, to: value, shown: value, elapsed: 0. }
+    }
+    /// Starts animating towards `value` if it's new, and returns the value to show this frame
+    fn update(&mut self, value: T, dtime: f32, duration: f32, curve: Vec4) -> T {
+        // The layout keeps setting its target while the animation runs, which mustn't restart it
+        if value != self.shown && value != self.to {
+            self.from = self.shown;
+            self.to = value;
+            self.elapsed = 0.;
+        }
+        self.elapsed += dtime;
+        let progress = if duration > 0. { self.elapsed / duration } else { 1. };
+        self.shown = if progress >= 1. { self.to } else { self.from + (self.to - self.from) * ease(curve, progress) };
+        self.shown
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransitionState {
+    translation: Option<Tween<Vec3>>,
+    width: Option<Tween<f32>>,
+    height: Option<Tween<f32>>,
+    background_color: Option<Tween<Vec4>>,
+    color: Option<Tween<Vec4>>,
+}
+
+/// Evaluates the easing `curve`, the control points of a cubic Bézier curve from (0, 0) to (1, 1), at `x`
+fn ease(curve: Vec4, x: f32) -> f32 {
+    if x <= 0. {
+        return 0.;
+    } else if x >= 1. {
+        return 1.;
+    }
+    let bezier = |p1: f32, p2: f32, s: f32| 3. * (1. - s).powi(2) * s * p1 + 3. * (1. - s) * s.powi(2) * p2 + s.powi(3);
+    // With the x of the control points in [0, 1], x increases along the curve, so the point at `x` is found by bisection
+    let (x1, x2) = (curve.x.clamp(0., 1.), curve.z.clamp(0., 1.));
+    let (mut low, mut high) = (0., 1.);
+    let mut s = x;
+    for _ in 0..BISECTION_STEPS {
+        if bezier(x1, x2, s) < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.;
+    }
+    bezier(curve.y, curve.w, s)
+}
+
+fn animate<T: ComponentValue + Copy + PartialEq + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>>(
+    world: &mut World,
+    id: EntityId,
+    component: Component<T>,
+    tween: &mut Option<Tween<T>>,
+    (dtime, duration, curve): (f32, f32, Vec4),
+) {
+    let Ok(value) = world.get(id, component) else {
+        *tween = None;
+        return;
+    };
+    let shown = tween.get_or_insert_with(|| Tween::new(value)).update(value, dtime, duration, curve);
+    if shown != value {
+        world.set(id, component, shown).ok();
+    }
+}
+
+/// Runs after the layout, so that the layout positions and sizes the elements at their targets, which are then animated to
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "transitions",
+        vec![query(transition_duration()).to_system(|q, world, qs, _| {
+            let dtime = *world.resource(unscaled_dtime());
+            for (id, duration) in q.collect_cloned(world, qs) {
+                let timing = (dtime, duration, world.get(id, transition_curve()).unwrap_or(LINEAR));
+                let mut state = world.get_cloned(id, transition_state()).unwrap_or_default();
+                animate(world, id, translation(), &mut state.translation, timing);
+                animate(world, id, width(), &mut state.width, timing);
+                animate(world, id, height(), &mut state.height, timing);
+                animate(world, id, background_color(), &mut state.background_color, timing);
+                animate(world, id, color(), &mut state.color, timing);
+                world.add_component(id, transition_state(), state).ok();
+            }
+        })],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ease_curves() {
+        for x in [0., 0.25, 0.5, 0.75, 1.] {
+            assert!((ease(LINEAR, x) - x).abs() < 1e-4);
+        }
+        let ease_in_out = Vec4::new(0.42, 0., 0.58, 1.);
+        assert!((ease(ease_in_out, 0.5) - 0.5).abs() < 1e-4);
+        assert!(ease(ease_in_out, 0.2) < 0.2);
+        assert!(ease(ease_in_out, 0.8) > 0.8);
+    }
+
+    #[test]
+    fn retargeting_to_the_same_value_continues() {
+        let mut tween = Tween::new(0.);
+        assert!((tween.update(10., 0.5, 1., LINEAR) - 5.).abs() < 1e-3);
+        // The target is set again, e.g. by the layout
+        assert!((tween.update(10., 0.25, 1., LINEAR) - 7.5).abs() < 1e-3);
+        assert_eq!(tween.update(10., 0.5, 1., LINEAR), 10.);
+        // A new target animates from the shown value
+        assert!((tween.update(0., 0.5, 1., LINEAR) - 5.).abs() < 1e-3);
+    }
+}
@@ -13,8 +13,8 @@ use glam::*;
 use parking_lot::Mutex;
 
 use crate::{
-    default_theme::{cutout_color, primary_color, secondary_color},
     dropdown::Tooltip,
+    theme::{tokens, use_theme, Theme},
     UIExt,
 };
 use crate::{layout::FlowColumn, layout::FlowRow, text::Text, UIBase, UIElement};
@@ -22,7 +22,9 @@ use ambient_cb::{cb, Callback, Cb};
 use ambient_color::Color;
 use ambient_guest_bridge::{
     components::{
-        input::{event_focus_change, event_keyboard_input, event_mouse_input, keyboard_modifiers, keycode},
+        input::{
+            accessibility_role, event_focus_change, event_keyboard_input, event_mouse_input, focus_order, keyboard_modifiers, keycode,
+        },
         layout::{
             align_vertical_center, fit_horizontal_parent, height, margin_top, min_height, padding_bottom, padding_left, padding_right,
             padding_top, space_between_items,
@@ -74,6 +76,7 @@ impl ButtonStyle {
     #[allow(clippy::too_many_arguments)]
     fn create_container(
         &self,
+        theme: &Theme,
         is_pressed: bool,
         is_working: bool,
         disabled: bool,
@@ -84,29 +87,32 @@ impl ButtonStyle {
         tooltip: Option<Element>,
         content: Element,
     ) -> Element {
+        let cutout_color = theme.color(tokens::CUTOUT);
+        let primary_color = theme.color(tokens::PRIMARY);
+        let secondary_color = theme.color(tokens::SECONDARY);
         let background = match self {
             ButtonStyle::Regular | ButtonStyle::Card => {
                 if is_pressed {
-                    cutout_color().lighten(0.1)
+                    cutout_color.lighten(0.1)
                 } else if disabled || is_working {
-                    cutout_color()
+                    cutout_color
                 } else if toggled {
-                    primary_color()
+                    primary_color
                 } else if hover && *self == ButtonStyle::Card {
-                    cutout_color().lighten(0.05)
+                    cutout_color.lighten(0.05)
                 } else {
-                    cutout_color()
+                    cutout_color
                 }
             }
             ButtonStyle::Primary => {
                 if is_pressed {
-                    secondary_color().lighten(0.2)
+                    secondary_color.lighten(0.2)
                 } else if disabled || is_working {
-                    secondary_color().desaturate(-1.)
+                    secondary_color.desaturate(-1.)
                 } else if hover || disabled || is_working {
-                    secondary_color().lighten(0.1)
+                    secondary_color.lighten(0.1)
                 } else {
-                    secondary_color()
+                    secondary_color
                 }
             }
             ButtonStyle::Flat | ButtonStyle::Inline => Color::rgba(1., 1., 1., 0.0),
@@ -115,19 +121,19 @@ impl ButtonStyle {
             Self::Regular | Self::Flat | ButtonStyle::Inline => content.set(
                 color(),
                 if is_pressed {
-                    Color::rgba(1., 1., 1., 1.)
+                    theme.color(tokens::BUTTON_TEXT_ACTIVE)
                 } else if disabled || is_working {
                     Color::rgba(0.3, 0.3, 0.3, 1.)
                 } else if toggled {
                     if *self == Self::Flat || *self == Self::Inline {
-                        primary_color()
+                        primary_color
                     } else {
-                        Color::rgba(1., 1., 1., 1.)
+                        theme.color(tokens::BUTTON_TEXT_ACTIVE)
                     }
                 } else if hover {
-                    Color::rgba(0.8, 0.8, 0.8, 1.)
+                    theme.color(tokens::BUTTON_TEXT_HOVER)
                 } else {
-                    Color::hex("B3B3B3").unwrap()
+                    theme.color(tokens::BUTTON_TEXT)
                 }
                 .into(),
             ),
@@ -213,6 +219,7 @@ pub fn Button(
 ) -> Element {
     let (is_pressed, set_is_pressed) = hooks.use_state(false);
     let (hover, set_hover) = hooks.use_state(false);
+    let (focused, set_focused) = hooks.use_state(false);
     let (is_working, set_is_working) = hooks.use_state(false);
     let (is_pressed_immediate, _) = hooks.use_state_with(|_| Arc::new(AtomicBool::new(false)));
 
@@ -244,9 +251,35 @@ pub fn Button(
         }
     });
 
-    let content = style
-        .create_container(is_pressed, is_working, disabled, toggled, hover, hotkey, hotkey_modifier, tooltip, content)
+    let theme = use_theme(hooks);
+    let mut container = style.create_container(
+        &theme,
+        is_pressed,
+        is_working,
+        disabled,
+        toggled,
+        hover || focused,
+        hotkey,
+        hotkey_modifier,
+        tooltip,
+        content,
+    );
+    container = container.set(accessibility_role(), "button".to_string());
+    if !disabled {
+        container = container.init(focus_order(), 0);
+    }
+    let content = container
         .with_clickarea()
+        .on_focus_change(move |_, _, focused| set_focused(focused))
+        .on_activate({
+            let on_invoked = on_invoked.clone();
+            let set_is_working = set_is_working.clone();
+            move |world, _| {
+                if !disabled {
+                    on_invoked.invoke(world, set_is_working.clone());
+                }
+            }
+        })
         .on_mouse_enter({
             let set_hover = set_hover.clone();
             move |world, _| {
@@ -1,9 +1,10 @@
 use ambient_cb::{cb, Cb};
 use ambient_element::{Element, ElementComponent, Hooks};
-use ambient_event_types::{WINDOW_MOUSE_INPUT, WINDOW_MOUSE_WHEEL};
+use ambient_event_types::{UI_ACTIVATED, UI_FOCUS_CHANGED, WINDOW_MOUSE_INPUT, WINDOW_MOUSE_WHEEL};
 use ambient_guest_bridge::{
     components::input::{
-        event_mouse_input, event_mouse_wheel, event_mouse_wheel_pixels, mouse_button, mouse_over, mouse_pickable_max, mouse_pickable_min,
+        event_mouse_input, event_mouse_wheel, event_mouse_wheel_pixels, event_ui_activate, event_ui_focus, mouse_button, mouse_over,
+        mouse_pickable_max, mouse_pickable_min,
     },
     ecs::{EntityId, World},
 };
@@ -33,6 +34,8 @@ pub struct ClickArea {
     pub on_mouse_hover: Vec<Cb<dyn Fn(&mut World, EntityId) + Sync + Send>>,
     pub on_mouse_input: Vec<Cb<dyn Fn(&mut World, EntityId, MouseInput, MouseButton) + Sync + Send>>,
     pub on_mouse_wheel: Vec<Cb<dyn Fn(&mut World, EntityId, Vec2, bool) + Sync + Send>>,
+    pub on_focus_change: Vec<Cb<dyn Fn(&mut World, EntityId, bool) + Sync + Send>>,
+    pub on_activate: Vec<Cb<dyn Fn(&mut World, EntityId) + Sync + Send>>,
 }
 impl ClickArea {
     pub fn new(inner: Element) -> Self {
@@ -43,6 +46,8 @@ impl ClickArea {
             on_mouse_hover: Vec::new(),
             on_mouse_input: Vec::new(),
             on_mouse_wheel: Vec::new(),
+            on_focus_change: Vec::new(),
+            on_activate: Vec::new(),
         }
     }
     pub fn on_mouse_hover<F: Fn(&mut World, EntityId) + Sync + Send + 'static>(mut self, handle: F) -> Self {
@@ -65,6 +70,16 @@ impl ClickArea {
         self.on_mouse_wheel.push(cb(handle));
         self
     }
+    /// Called when this gains or loses keyboard focus. Set `focus_order` on the inner element to make it focusable.
+    pub fn on_focus_change<F: Fn(&mut World, EntityId, bool) + Sync + Send + 'static>(mut self, handle: F) -> Self {
+        self.on_focus_change.push(cb(handle));
+        self
+    }
+    /// Called when this is activated from the keyboard while focused, or by assistive technologies
+    pub fn on_activate<F: Fn(&mut World, EntityId) + Sync + Send + 'static>(mut self, handle: F) -> Self {
+        self.on_activate.push(cb(handle));
+        self
+    }
 
     pub fn on_mouse_down<F: Fn(&mut World, EntityId, MouseButton) + Sync + Send + 'static>(self, handle: F) -> Self {
         self.on_mouse_input(move |world, id, state, button| {
@@ -83,7 +98,8 @@ impl ClickArea {
 }
 impl ElementComponent for ClickArea {
     fn render(self: Box<Self>, hooks: &mut Hooks) -> Element {
-        let Self { inner, on_mouse_enter, on_mouse_leave, on_mouse_hover, on_mouse_input, on_mouse_wheel } = *self;
+        let Self { inner, on_mouse_enter, on_mouse_leave, on_mouse_hover, on_mouse_input, on_mouse_wheel, on_focus_change, on_activate } =
+            *self;
         let id = hooks.use_ref_with(|_| None);
         let mouse_over_count = hooks.use_ref_with(|_| 0);
         hooks.use_frame({
@@ -133,6 +149,26 @@ impl ElementComponent for ClickArea {
                 }
             }
         });
+        let focused = hooks.use_ref_with(|_| false);
+        hooks.use_multi_event(&[UI_FOCUS_CHANGED, UI_ACTIVATED], {
+            let id = id.clone();
+            move |world, event| {
+                let Some(id) = *id.lock() else { return };
+                if let Some(focus) = event.get(event_ui_focus()) {
+                    let mut focused = focused.lock();
+                    if *focused != (focus == id) {
+                        *focused = focus == id;
+                        for handler in &on_focus_change {
+                            handler(world, id, *focused);
+                        }
+                    }
+                } else if event.get(event_ui_activate()) == Some(id) {
+                    for handler in &on_activate {
+                        handler(world, id);
+                    }
+                }
+            }
+        });
         inner.init(mouse_pickable_min(), Vec3::ZERO).init(mouse_pickable_max(), Vec3::ZERO).on_spawned(move |_, new_id, _| {
             *id.lock() = Some(new_id);
         })
@@ -1,6 +1,6 @@
-use glam::{vec4, Vec4};
+use glam::Vec4;
 
-use crate::UIExt;
+use crate::theme::{tokens, ThemeExt};
 use ambient_color::Color;
 use ambient_element::Element;
 use ambient_guest_bridge::components::{
@@ -10,6 +10,8 @@ use ambient_guest_bridge::components::{
     text::font_size,
 };
 
+// These are the colors of the dark theme; use `Theme` to follow the active theme instead
+
 pub fn primary_color() -> Color {
     Color::hex("DE0B5D").unwrap()
 }
@@ -47,28 +49,27 @@ pub trait StylesExt {
 }
 impl StylesExt for Element {
     fn section_style(self) -> Self {
-        self.set(font_size(), 16.).set(color(), vec4(0.9, 0.9, 0.9, 1.))
+        self.theme_size(font_size(), tokens::FONT_SIZE_SECTION).theme_color(color(), tokens::TEXT)
     }
     fn header_style(self) -> Self {
-        self.set(font_size(), 25.).set(color(), vec4(0.9, 0.9, 0.9, 1.))
+        self.theme_size(font_size(), tokens::FONT_SIZE_HEADER).theme_color(color(), tokens::TEXT)
     }
     fn small_style(self) -> Self {
-        self.set(font_size(), 10.).set(color(), vec4(0.5, 0.5, 0.5, 1.))
+        self.theme_size(font_size(), tokens::FONT_SIZE_SMALL).theme_color(color(), tokens::TEXT_SECONDARY)
     }
     fn error_text_style(self) -> Self {
-        self.set(color(), vec4(1., 0.5, 0.5, 1.))
+        self.theme_color(color(), tokens::ERROR_TEXT)
     }
-    #[allow(clippy::clone_on_copy)]
     fn floating_panel(self) -> Self {
-        self.with_background(Color::hex("1D1C22").unwrap().set_a(0.9).clone().into())
-            .set(border_radius(), Vec4::ONE * 5.)
-            .with_padding_even(STREET)
+        self.theme_background(tokens::FLOATING_PANEL_BACKGROUND)
+            .themed(border_radius(), |theme| Vec4::ONE * theme.size(tokens::PANEL_ROUNDING))
+            .theme_padding_even(tokens::STREET)
     }
     fn panel(self) -> Self {
-        self.with_background(Color::rgba(1., 1., 1., 0.01).into()).set(border_radius(), Vec4::ONE * 5.)
+        self.theme_background(tokens::PANEL_BACKGROUND).themed(border_radius(), |theme| Vec4::ONE * theme.size(tokens::PANEL_ROUNDING))
     }
     fn keyboard(self) -> Self {
-        self.set(space_between_items(), STREET).with_padding_even(STREET).set_default(align_vertical_center())
+        self.theme_size(space_between_items(), tokens::STREET).theme_padding_even(tokens::STREET).set_default(align_vertical_center())
     }
 }
 
@@ -3,7 +3,8 @@ use glam::{vec3, Vec4};
 
 use crate::{layout::FlowColumn, UIBase};
 
-use crate::default_theme::{tooltip_background_color, SMALL_ROUNDING, STREET};
+use crate::default_theme::{SMALL_ROUNDING, STREET};
+use crate::theme::{tokens, ThemeExt};
 use crate::UIExt;
 use ambient_guest_bridge::components::transform::translation;
 use ambient_guest_bridge::components::{layout::margin_top, rect::border_radius};
@@ -28,7 +29,7 @@ pub fn Tooltip(hooks: &mut Hooks, inner: Element, tooltip: Element) -> Element {
         dropdown: FlowColumn(vec![tooltip])
             .el()
             .with_padding_even(STREET)
-            .with_background(tooltip_background_color().into())
+            .theme_background(tokens::TOOLTIP_BACKGROUND)
             .set(border_radius(), Vec4::ONE * SMALL_ROUNDING)
             .set(margin_top(), STREET),
         show: hover,
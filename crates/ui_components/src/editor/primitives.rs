@@ -3,16 +3,22 @@ use std::{fmt::Debug, time::Duration};
 
 use ambient_cb::{cb, Cb};
 use ambient_element::{define_el_function_for_vec_element_newtype, Element, ElementComponent, ElementComponentExt, Hooks};
-use ambient_guest_bridge::components::layout::margin_right;
+use ambient_guest_bridge::components::{
+    layout::{height, margin_right, width},
+    rect::border_radius,
+    transform::translation,
+};
 use convert_case::{Case, Casing};
-use glam::{Vec2, Vec3, Vec4};
+use glam::{vec3, EulerRot, Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 use itertools::Itertools;
 
 use crate::button::{Button, ButtonStyle};
 use crate::default_theme::STREET;
 use crate::layout::{FlowColumn, FlowRow};
 use crate::text::{FontAwesomeIcon, Text};
-use crate::use_focus_for_instance_id;
+use crate::theme::{tokens, ThemeExt};
+use crate::transition::{curves, TransitionExt, DEFAULT_TRANSITION_DURATION};
+use crate::{use_focus_for_instance_id, UIBase, UIExt};
 
 use super::{ChangeCb, Editor, EditorOpts, TextEditor};
 
@@ -86,6 +92,7 @@ impl<T: Debug + Clone + Sync + Send + 'static> ElementComponent for CustomParseI
 }
 
 pub type F32Input = ParseableInput<f32>;
+pub type F64Input = ParseableInput<f64>;
 pub type I32Input = ParseableInput<i32>;
 pub type U32Input = ParseableInput<u32>;
 pub type U64Input = ParseableInput<u64>;
@@ -119,6 +126,15 @@ impl Editor for f32 {
         Text::el(format!("{self}"))
     }
 }
+impl Editor for f64 {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        F64Input { value: self, on_change }.el()
+    }
+
+    fn view(self, _: EditorOpts) -> Element {
+        Text::el(format!("{self}"))
+    }
+}
 impl Editor for i32 {
     fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
         I32Input { value: self, on_change }.el()
@@ -156,6 +172,17 @@ impl Editor for usize {
     }
 }
 
+#[cfg(feature = "native")]
+impl Editor for ambient_guest_bridge::ecs::EntityId {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        ParseableInput { value: self, on_change }.el()
+    }
+
+    fn view(self, _: EditorOpts) -> Element {
+        Text::el(format!("{self}"))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Checkbox {
     pub value: bool,
@@ -175,6 +202,48 @@ impl ElementComponent for Checkbox {
     }
 }
 
+const TOGGLE_WIDTH: f32 = 28.;
+const TOGGLE_HEIGHT: f32 = 16.;
+const TOGGLE_KNOB_MARGIN: f32 = 2.;
+
+/// A switch whose knob slides to the right when it's on
+#[derive(Clone, Debug)]
+pub struct Toggle {
+    pub value: bool,
+    pub on_change: Cb<dyn Fn(bool) + Sync + Send>,
+}
+impl Toggle {
+    pub fn new(value: bool, on_change: impl Fn(bool) + Sync + Send + 'static) -> Self {
+        Self { value, on_change: cb(on_change) }
+    }
+}
+impl ElementComponent for Toggle {
+    fn render(self: Box<Self>, _: &mut Hooks) -> Element {
+        let Toggle { value, on_change } = *self;
+        let knob_size = TOGGLE_HEIGHT - TOGGLE_KNOB_MARGIN * 2.;
+        let knob_x = if value { TOGGLE_WIDTH - TOGGLE_KNOB_MARGIN - knob_size } else { TOGGLE_KNOB_MARGIN };
+        let knob = UIBase
+            .el()
+            .set(width(), knob_size)
+            .set(height(), knob_size)
+            .theme_background(tokens::TEXT)
+            .set(border_radius(), Vec4::ONE * knob_size / 2.)
+            .set(translation(), vec3(knob_x, TOGGLE_KNOB_MARGIN, -0.01))
+            .with_transition(DEFAULT_TRANSITION_DURATION, curves::EASE_OUT);
+        UIBase
+            .el()
+            .set(width(), TOGGLE_WIDTH)
+            .set(height(), TOGGLE_HEIGHT)
+            .theme_background(if value { tokens::PRIMARY } else { tokens::CUTOUT })
+            .set(border_radius(), Vec4::ONE * TOGGLE_HEIGHT / 2.)
+            .with_transition(DEFAULT_TRANSITION_DURATION, curves::EASE_OUT)
+            .children(vec![knob])
+            .with_clickarea()
+            .on_mouse_up(move |_, _, _| on_change.0(!value))
+            .el()
+    }
+}
+
 impl Editor for bool {
     fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
         Checkbox { value: self, on_change }.el()
@@ -235,6 +304,52 @@ impl Editor for Vec4 {
     }
 }
 
+impl Editor for UVec2 {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        ArrayEditor { value: self.to_array(), on_change: cb(move |v| (on_change)(Self::from(v))), field_names: Some(&["X", "Y"]) }.el()
+    }
+}
+
+impl Editor for UVec3 {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        ArrayEditor { value: self.to_array(), on_change: cb(move |v| (on_change)(Self::from(v))), field_names: Some(&["X", "Y", "Z"]) }.el()
+    }
+}
+
+impl Editor for UVec4 {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        ArrayEditor { value: self.to_array(), on_change: cb(move |v| (on_change)(Self::from(v))), field_names: Some(&["X", "Y", "Z", "W"]) }
+            .el()
+    }
+}
+
+/// Quaternions are edited as euler angles (in degrees, applied in XYZ order)
+impl Editor for Quat {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        let (x, y, z) = self.to_euler(EulerRot::XYZ);
+        ArrayEditor {
+            value: [x.to_degrees(), y.to_degrees(), z.to_degrees()],
+            on_change: cb(move |[x, y, z]: [f32; 3]| {
+                (on_change)(Quat::from_euler(EulerRot::XYZ, x.to_radians(), y.to_radians(), z.to_radians()))
+            }),
+            field_names: Some(&["X", "Y", "Z"]),
+        }
+        .el()
+    }
+}
+
+/// Matrices are edited column by column
+impl Editor for Mat4 {
+    fn editor(self, on_change: ChangeCb<Self>, _: EditorOpts) -> Element {
+        ArrayEditor {
+            value: [self.x_axis, self.y_axis, self.z_axis, self.w_axis],
+            on_change: cb(move |[x, y, z, w]: [Vec4; 4]| (on_change)(Mat4::from_cols(x, y, z, w))),
+            field_names: Some(&["X axis", "Y axis", "Z axis", "W axis"]),
+        }
+        .el()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ArrayEditor<const C: usize, T> {
     pub value: [T; C],
@@ -20,10 +20,11 @@ use glam::{vec3, Vec4};
 
 use super::Editor;
 use crate::{
-    default_theme::{primary_color, STREET},
+    default_theme::STREET,
     editor::EditorOpts,
     layout::FlowRow,
     text::Text,
+    theme::{tokens, ThemeExt},
     Rectangle, UIBase, UIExt,
 };
 
@@ -114,7 +115,7 @@ impl ElementComponent for Slider {
             .set(width(), slider_width)
             .set(height(), 2.)
             .set(translation(), vec3(0., (SLIDER_HEIGHT - 2.) / 2., 0.))
-            .set(background_color(), primary_color().into())
+            .theme_color(background_color(), tokens::PRIMARY)
             .on_spawned(move |_, id, _| *block_id.lock() = id);
 
         let thumb = {
@@ -122,7 +123,7 @@ impl ElementComponent for Slider {
                 .el()
                 .set(width(), THUMB_WIDTH)
                 .set(height(), SLIDER_HEIGHT)
-                .with_background(primary_color().into())
+                .theme_background(tokens::PRIMARY)
                 .set(border_radius(), Vec4::ONE * THUMB_WIDTH / 2.)
                 .set(translation(), vec3(block_left_offset, 0., -0.01))
                 .with_clickarea()
@@ -9,7 +9,7 @@ use ambient_cb::{cb, Cb};
 use ambient_event_types::{WINDOW_KEYBOARD_INPUT, WINDOW_RECEIVED_CHARACTER};
 use ambient_guest_bridge::{
     components::{
-        input::{event_keyboard_input, event_received_character, keycode},
+        input::{accessibility_role, event_keyboard_input, event_received_character, focus_order, keycode},
         layout::{align_horizontal_end, fit_horizontal_none, fit_vertical_none, height, layout_flow, min_height, min_width, width},
         rendering::color,
         text::text,
@@ -101,10 +101,16 @@ pub fn TextEditor(
     .set_default(fit_vertical_none())
     .set(min_width(), 3.)
     .set(min_height(), 13.)
+    .set(accessibility_role(), "text_input".to_string())
+    .init(focus_order(), 0)
     .with_clickarea()
-    .on_mouse_up(move |_, _, _| {
-        set_focused(true);
+    .on_mouse_up({
+        let set_focused = set_focused.clone();
+        move |_, _, _| {
+            set_focused(true);
+        }
     })
+    .on_focus_change(move |_, _, focused| set_focused(focused))
     .on_mouse_enter(|world, _| {
         set_cursor(world, CursorIcon::Text);
     })
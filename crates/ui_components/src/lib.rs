@@ -26,9 +26,13 @@ pub mod prompt;
 pub mod screens;
 pub mod scroll_area;
 pub mod select;
+pub mod table;
 pub mod tabs;
 pub mod text;
+pub mod theme;
 pub mod throbber;
+pub mod touch;
+pub mod transition;
 
 #[element_component]
 pub fn UIBase(_: &mut Hooks) -> Element {
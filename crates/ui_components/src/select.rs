@@ -2,10 +2,11 @@ use ambient_element::{Element, ElementComponent, ElementComponentExt, Hooks};
 
 use crate::{
     button::{Button, ButtonStyle},
-    default_theme::{tooltip_background_color, SMALL_ROUNDING, STREET},
+    default_theme::{SMALL_ROUNDING, STREET},
     dropdown::Dropdown,
     layout::{FlowColumn, FlowRow},
     text::Text,
+    theme::{tokens, ThemeExt},
     UIExt,
 };
 use ambient_cb::Cb;
@@ -79,7 +80,7 @@ impl ElementComponent for DropdownSelect {
             .el()
             .with_padding_even(STREET)
             .set(border_radius(), Vec4::ONE * SMALL_ROUNDING)
-            .with_background(tooltip_background_color().into()),
+            .theme_background(tokens::TOOLTIP_BACKGROUND),
             show,
         }
         .el()
@@ -0,0 +1,201 @@
+//! Lists and tables which only instantiate the rows they show, so that they can display thousands of rows
+use std::str::FromStr;
+
+use ambient_cb::{cb, Cb};
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_event_types::WINDOW_KEYBOARD_INPUT;
+use ambient_guest_bridge::components::{
+    input::{accessibility_role, event_keyboard_input, focus_order, keycode},
+    layout::{align_vertical_center, fit_horizontal_none, fit_horizontal_parent, fit_vertical_none, height, width},
+    rect::background_color,
+    rendering::color,
+};
+use ambient_window_types::VirtualKeyCode;
+
+use crate::{
+    button::{Button, ButtonStyle},
+    layout::{FlowColumn, FlowRow},
+    text::Text,
+    theme::{tokens, ThemeExt},
+    with_rect, UIExt,
+};
+
+/// Scrolled by the mouse wheel, in rows per line
+const ROWS_PER_WHEEL_LINE: f32 = 3.;
+
+/// A vertical list of `row_count` rows of `row_height`, of which only those that fit in `visible_height` are rendered.
+///
+/// `render_row` provides the element of a row from its index. The list scrolls with the mouse wheel, and when focused the
+/// arrow keys, Page Up/Down, Home and End move the selection, which is reported to `on_select`.
+#[element_component]
+pub fn ListView(
+    hooks: &mut Hooks,
+    row_count: usize,
+    row_height: f32,
+    visible_height: f32,
+    render_row: Cb<dyn Fn(usize) -> Element + Sync + Send>,
+    selected: Option<usize>,
+    on_select: Option<Cb<dyn Fn(usize) + Sync + Send>>,
+) -> Element {
+    let (first_row, set_first_row) = hooks.use_state(0);
+    let (focused, set_focused) = hooks.use_state(false);
+    let visible_rows = ((visible_height / row_height).floor() as usize).max(1);
+    let first_row = first_row.min(row_count.saturating_sub(visible_rows));
+
+    // Moves the selection, and scrolls just enough to show it
+    let select = cb({
+        let set_first_row = set_first_row.clone();
+        move |row: usize| {
+            if row_count == 0 {
+                return;
+            }
+            let row = row.min(row_count - 1);
+            if row < first_row {
+                set_first_row(row);
+            } else if row >= first_row + visible_rows {
+                set_first_row(row + 1 - visible_rows);
+            }
+            if let Some(on_select) = &on_select {
+                on_select(row);
+            }
+        }
+    });
+    hooks.use_event(WINDOW_KEYBOARD_INPUT, {
+        let select = select.clone();
+        move |_world, event| {
+            if !focused || event.get(event_keyboard_input()) != Some(true) {
+                return;
+            }
+            let Some(key) = event.get_ref(keycode()).clone() else { return };
+            let current = selected.unwrap_or(first_row);
+            match VirtualKeyCode::from_str(&key) {
+                Ok(VirtualKeyCode::Up) => select(current.saturating_sub(1)),
+                Ok(VirtualKeyCode::Down) => select(if selected.is_some() { current + 1 } else { current }),
+                Ok(VirtualKeyCode::PageUp) => select(current.saturating_sub(visible_rows)),
+                Ok(VirtualKeyCode::PageDown) => select(current + visible_rows),
+                Ok(VirtualKeyCode::Home) => select(0),
+                Ok(VirtualKeyCode::End) => select(row_count.saturating_sub(1)),
+                _ => {}
+            }
+        }
+    });
+
+    let rows = (first_row..(first_row + visible_rows).min(row_count))
+        .map(|row| {
+            let el = FlowRow(vec![render_row(row)])
+                .el()
+                .set_default(fit_horizontal_parent())
+                .set_default(fit_vertical_none())
+                .set(height(), row_height)
+                .set_default(align_vertical_center());
+            let el = if selected == Some(row) {
+                with_rect(el).themed(background_color(), |theme| (*theme.color(tokens::PRIMARY).set_a(0.5)).into())
+            } else {
+                el
+            };
+            let select = select.clone();
+            el.with_clickarea().on_mouse_up(move |_, _, _| select(row)).el()
+        })
+        .collect();
+    FlowColumn(rows)
+        .el()
+        .set_default(fit_vertical_none())
+        .set(height(), visible_height)
+        .set(accessibility_role(), "list".to_string())
+        .init(focus_order(), 0)
+        .with_clickarea()
+        .on_focus_change(move |_, _, focused| set_focused(focused))
+        .on_mouse_wheel(move |_, _, delta, pixels| {
+            let rows = if pixels { delta.y / row_height } else { delta.y * ROWS_PER_WHEEL_LINE };
+            // Scrolling up is a positive delta
+            let rows = rows.abs().ceil() as usize;
+            set_first_row(if delta.y > 0. { first_row.saturating_sub(rows) } else { first_row + rows });
+        })
+        .el()
+}
+
+fn table_cell(cell_width: f32, content: Element) -> Element {
+    FlowRow(vec![content]).el().set_default(fit_horizontal_none()).set(width(), cell_width)
+}
+
+/// How a [Table] is sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableSort {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+/// A column of a [Table]
+#[derive(Debug, Clone)]
+pub struct TableColumn {
+    pub title: String,
+    pub width: f32,
+    /// Whether clicking the header of the column sorts the table by it
+    pub sortable: bool,
+}
+impl TableColumn {
+    pub fn new(title: impl Into<String>, width: f32) -> Self {
+        Self { title: title.into(), width, sortable: false }
+    }
+    pub fn sortable(mut self) -> Self {
+        self.sortable = true;
+        self
+    }
+}
+
+/// A [ListView] whose rows are made of the cells of `columns`, under a row of headers.
+///
+/// `render_cell` provides the element of a cell from its row and column. The table doesn't sort its rows itself: clicking
+/// the header of a sortable column reports the new `sort` to `on_sort`, and the rows are expected to be provided in that order.
+#[element_component]
+pub fn Table(
+    _hooks: &mut Hooks,
+    columns: Vec<TableColumn>,
+    row_count: usize,
+    row_height: f32,
+    visible_height: f32,
+    render_cell: Cb<dyn Fn(usize, usize) -> Element + Sync + Send>,
+    sort: Option<TableSort>,
+    on_sort: Option<Cb<dyn Fn(TableSort) + Sync + Send>>,
+    selected: Option<usize>,
+    on_select: Option<Cb<dyn Fn(usize) + Sync + Send>>,
+) -> Element {
+    let headers = columns
+        .iter()
+        .enumerate()
+        .map(|(column, TableColumn { title, width, sortable })| {
+            let title = match sort {
+                Some(sort) if sort.column == column => format!("{title} {}", if sort.ascending { "\u{25B2}" } else { "\u{25BC}" }),
+                _ => title.clone(),
+            };
+            let header = match (&on_sort, sortable) {
+                (Some(on_sort), true) => {
+                    let on_sort = on_sort.clone();
+                    Button::new(title, move |_| {
+                        let ascending = !matches!(sort, Some(sort) if sort.column == column && sort.ascending);
+                        on_sort(TableSort { column, ascending });
+                    })
+                    .style(ButtonStyle::Flat)
+                    .el()
+                }
+                _ => Text::el(title).theme_color(color(), tokens::TEXT_SECONDARY),
+            };
+            table_cell(*width, header)
+        })
+        .collect();
+    let widths = columns.iter().map(|column| column.width).collect::<Vec<_>>();
+    FlowColumn::el([
+        FlowRow(headers).el().set(height(), row_height).set_default(fit_vertical_none()).set_default(align_vertical_center()),
+        ListView {
+            row_count,
+            row_height,
+            visible_height: (visible_height - row_height).max(row_height),
+            render_row: cb(move |row| {
+                FlowRow(widths.iter().enumerate().map(|(column, &width)| table_cell(width, render_cell(row, column))).collect()).el()
+            }),
+            selected,
+            on_select,
+        }
+        .el(),
+    ])
+}
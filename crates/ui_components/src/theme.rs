@@ -0,0 +1,211 @@
+//! Named style tokens (colors and sizes) stored as resources, so that the UI can be restyled at runtime.
+//!
+//! Elements refer to tokens with [ThemeExt], which resolves them each time they're rendered, and [ThemeProvider] re-renders
+//! its content when the theme is switched with [Theme::apply].
+use std::collections::BTreeMap;
+
+use ambient_color::Color;
+use ambient_element::{element_component, Element, Hooks};
+use ambient_guest_bridge::{
+    components::{
+        layout::{padding_bottom, padding_left, padding_right, padding_top},
+        rect::background_color,
+        ui::{theme_color_tokens, theme_color_values, theme_name, theme_size_tokens, theme_size_values},
+    },
+    ecs::{Component, ComponentValue, World},
+};
+use glam::Vec4;
+
+use crate::with_rect;
+
+/// The tokens used by the built-in components
+pub mod tokens {
+    pub const PRIMARY: &str = "primary";
+    pub const SECONDARY: &str = "secondary";
+    pub const APP_BACKGROUND: &str = "app_background";
+    pub const ERROR: &str = "error";
+    /// A color slightly darker than the app background
+    pub const CUTOUT: &str = "cutout";
+    pub const TOOLTIP_BACKGROUND: &str = "tooltip_background";
+    pub const FLOATING_PANEL_BACKGROUND: &str = "floating_panel_background";
+    pub const PANEL_BACKGROUND: &str = "panel_background";
+    pub const TEXT: &str = "text";
+    pub const TEXT_SECONDARY: &str = "text_secondary";
+    pub const ERROR_TEXT: &str = "error_text";
+    pub const BUTTON_TEXT: &str = "button_text";
+    pub const BUTTON_TEXT_HOVER: &str = "button_text_hover";
+    pub const BUTTON_TEXT_ACTIVE: &str = "button_text_active";
+
+    pub const FONT_SIZE_HEADER: &str = "font_size_header";
+    pub const FONT_SIZE_SECTION: &str = "font_size_section";
+    pub const FONT_SIZE_SMALL: &str = "font_size_small";
+    /// Default margin/padding
+    pub const STREET: &str = "street";
+    /// Default rounding of corners
+    pub const SMALL_ROUNDING: &str = "small_rounding";
+    pub const PANEL_ROUNDING: &str = "panel_rounding";
+}
+
+/// The name of the theme the UI has when no theme has been applied
+const DEFAULT_THEME: &str = "dark";
+
+/// A named set of style tokens. Tokens missing from a theme fall back to their value in [Theme::dark]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub colors: BTreeMap<String, Color>,
+    pub sizes: BTreeMap<String, f32>,
+}
+impl Theme {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), colors: BTreeMap::new(), sizes: BTreeMap::new() }
+    }
+    pub fn with_color(mut self, token: impl Into<String>, color: Color) -> Self {
+        self.colors.insert(token.into(), color);
+        self
+    }
+    pub fn with_size(mut self, token: impl Into<String>, size: f32) -> Self {
+        self.sizes.insert(token.into(), size);
+        self
+    }
+
+    /// The default theme, which the UI has when no theme has been applied
+    pub fn dark() -> Self {
+        Self::new(DEFAULT_THEME)
+            .with_color(tokens::PRIMARY, Color::hex("DE0B5D").unwrap())
+            .with_color(tokens::SECONDARY, Color::hex("ffac04").unwrap())
+            .with_color(tokens::APP_BACKGROUND, Color::hex("1B1B1B").unwrap())
+            .with_color(tokens::ERROR, Color::hex("750631").unwrap())
+            .with_color(tokens::CUTOUT, Color::hex("151515").unwrap())
+            .with_color(tokens::TOOLTIP_BACKGROUND, Color::rgba(0., 0., 0., 0.9))
+            .with_color(tokens::FLOATING_PANEL_BACKGROUND, *Color::hex("1D1C22").unwrap().set_a(0.9))
+            .with_color(tokens::PANEL_BACKGROUND, Color::rgba(1., 1., 1., 0.01))
+            .with_color(tokens::TEXT, Color::rgba(0.9, 0.9, 0.9, 1.))
+            .with_color(tokens::TEXT_SECONDARY, Color::rgba(0.5, 0.5, 0.5, 1.))
+            .with_color(tokens::ERROR_TEXT, Color::rgba(1., 0.5, 0.5, 1.))
+            .with_color(tokens::BUTTON_TEXT, Color::hex("B3B3B3").unwrap())
+            .with_color(tokens::BUTTON_TEXT_HOVER, Color::rgba(0.8, 0.8, 0.8, 1.))
+            .with_color(tokens::BUTTON_TEXT_ACTIVE, Color::WHITE)
+            .with_size(tokens::FONT_SIZE_HEADER, 25.)
+            .with_size(tokens::FONT_SIZE_SECTION, 16.)
+            .with_size(tokens::FONT_SIZE_SMALL, 10.)
+            .with_size(tokens::STREET, 10.)
+            .with_size(tokens::SMALL_ROUNDING, 3.)
+            .with_size(tokens::PANEL_ROUNDING, 5.)
+    }
+    pub fn light() -> Self {
+        Self { name: "light".to_string(), ..Self::dark() }
+            .with_color(tokens::SECONDARY, Color::hex("E09600").unwrap())
+            .with_color(tokens::APP_BACKGROUND, Color::hex("F2F2F2").unwrap())
+            .with_color(tokens::ERROR, Color::hex("F5B8C8").unwrap())
+            .with_color(tokens::CUTOUT, Color::hex("E4E4E4").unwrap())
+            .with_color(tokens::TOOLTIP_BACKGROUND, Color::rgba(0.2, 0.2, 0.2, 0.95))
+            .with_color(tokens::FLOATING_PANEL_BACKGROUND, Color::rgba(0.97, 0.97, 0.98, 0.95))
+            .with_color(tokens::PANEL_BACKGROUND, Color::rgba(0., 0., 0., 0.03))
+            .with_color(tokens::TEXT, Color::rgba(0.1, 0.1, 0.1, 1.))
+            .with_color(tokens::TEXT_SECONDARY, Color::rgba(0.4, 0.4, 0.4, 1.))
+            .with_color(tokens::ERROR_TEXT, Color::rgba(0.7, 0.1, 0.1, 1.))
+            .with_color(tokens::BUTTON_TEXT, Color::hex("4D4D4D").unwrap())
+            .with_color(tokens::BUTTON_TEXT_HOVER, Color::rgba(0.2, 0.2, 0.2, 1.))
+            .with_color(tokens::BUTTON_TEXT_ACTIVE, Color::BLACK)
+    }
+
+    pub fn color(&self, token: &str) -> Color {
+        match self.colors.get(token) {
+            Some(color) => *color,
+            None => Self::dark().colors.get(token).copied().unwrap_or(Color::WHITE),
+        }
+    }
+    pub fn size(&self, token: &str) -> f32 {
+        match self.sizes.get(token) {
+            Some(size) => *size,
+            None => Self::dark().sizes.get(token).copied().unwrap_or(0.),
+        }
+    }
+
+    /// The active theme, or the dark theme if none has been applied
+    // `resource_opt` returns a ref on host and a value on guest
+    #[allow(clippy::map_clone)]
+    pub fn from_world(world: &World) -> Self {
+        let Some(name) = world.resource_opt(theme_name()).map(|name| name.clone()) else { return Self::dark() };
+        let color_tokens = world.resource_opt(theme_color_tokens()).map(|tokens| tokens.clone()).unwrap_or_default();
+        let color_values = world.resource_opt(theme_color_values()).map(|values| values.clone()).unwrap_or_default();
+        let size_tokens = world.resource_opt(theme_size_tokens()).map(|tokens| tokens.clone()).unwrap_or_default();
+        let size_values = world.resource_opt(theme_size_values()).map(|values| values.clone()).unwrap_or_default();
+        Self {
+            name,
+            colors: color_tokens.into_iter().zip(color_values.into_iter().map(Color::from)).collect(),
+            sizes: size_tokens.into_iter().zip(size_values).collect(),
+        }
+    }
+    /// Makes this the active theme, which restyles the content of every [ThemeProvider]
+    pub fn apply(&self, world: &mut World) {
+        world.add_resource(theme_color_tokens(), self.colors.keys().cloned().collect());
+        world.add_resource(theme_color_values(), self.colors.values().map(|&color| color.into()).collect());
+        world.add_resource(theme_size_tokens(), self.sizes.keys().cloned().collect());
+        world.add_resource(theme_size_values(), self.sizes.values().copied().collect());
+        // Set last, as this is what the UI watches
+        world.add_resource(theme_name(), self.name.clone());
+    }
+}
+
+/// The active theme, which re-renders the component when another theme is applied. Themes are told apart by name
+pub fn use_theme(hooks: &mut Hooks) -> Theme {
+    let (theme, set_theme) = hooks.use_state_with(|world| Theme::from_world(world));
+    hooks.use_frame({
+        let name = theme.name.clone();
+        move |world| {
+            // `resource_opt` returns a ref on host and a value on guest
+            #[allow(clippy::map_clone)]
+            let current = world.resource_opt(theme_name()).map(|name| name.clone());
+            if current.as_ref().map_or(name != DEFAULT_THEME, |current| *current != name) {
+                set_theme(Theme::from_world(world));
+            }
+        }
+    });
+    theme
+}
+
+/// Re-renders `content` when another theme is applied, so that the tokens of its elements are resolved again
+#[element_component]
+pub fn ThemeProvider(hooks: &mut Hooks, content: Element) -> Element {
+    use_theme(hooks);
+    content
+}
+
+/// Element modifiers which resolve theme tokens each time the element is rendered
+pub trait ThemeExt {
+    /// Sets `component` to the value computed from the active theme
+    fn themed<T: ComponentValue + Sync + Send + 'static>(
+        self,
+        component: Component<T>,
+        value: impl Fn(&Theme) -> T + Sync + Send + 'static,
+    ) -> Self;
+    fn theme_color(self, component: Component<Vec4>, token: &'static str) -> Self;
+    fn theme_size(self, component: Component<f32>, token: &'static str) -> Self;
+    fn theme_background(self, token: &'static str) -> Self;
+    fn theme_padding_even(self, token: &'static str) -> Self;
+}
+impl ThemeExt for Element {
+    fn themed<T: ComponentValue + Sync + Send + 'static>(
+        self,
+        component: Component<T>,
+        value: impl Fn(&Theme) -> T + Sync + Send + 'static,
+    ) -> Self {
+        self.set_with(component, move |world| value(&Theme::from_world(world)))
+    }
+    fn theme_color(self, component: Component<Vec4>, token: &'static str) -> Self {
+        self.themed(component, move |theme| theme.color(token).into())
+    }
+    fn theme_size(self, component: Component<f32>, token: &'static str) -> Self {
+        self.themed(component, move |theme| theme.size(token))
+    }
+    fn theme_background(self, token: &'static str) -> Self {
+        with_rect(self).theme_color(background_color(), token)
+    }
+    fn theme_padding_even(self, token: &'static str) -> Self {
+        [padding_left(), padding_right(), padding_top(), padding_bottom()]
+            .into_iter()
+            .fold(self, |el, padding| el.theme_size(padding, token))
+    }
+}
@@ -0,0 +1,131 @@
+//! On-screen controls for touch screens.
+//!
+//! Each control follows the finger that started touching within it. The controls set `touch_capture`, so that those fingers
+//! don't also act as the mouse.
+
+use ambient_cb::Cb;
+use ambient_element::{element_component, Element, ElementComponentExt, Hooks};
+use ambient_event_types::WINDOW_TOUCH;
+use ambient_guest_bridge::{
+    components::{
+        input::{event_touch, touch_capture, touch_id, touch_phase},
+        layout::{height, width},
+        rect::border_radius,
+        text::font_size,
+        transform::{local_to_world, translation},
+    },
+    ecs::EntityId,
+};
+use glam::{vec2, vec3, vec4, Vec2, Vec4};
+
+use crate::{text::Text, use_window_physical_resolution, UIBase, UIExt};
+
+const BASE_COLOR: Vec4 = vec4(1., 1., 1., 0.15);
+const ACTIVE_COLOR: Vec4 = vec4(1., 1., 1., 0.4);
+
+/// Tracks the finger that started touching within the circle of `radius` at the top left corner of `base_id`.
+/// `on_touch` is called with its offset from the center of the circle, or None when it's lifted.
+fn use_touch_circle(hooks: &mut Hooks, radius: f32, on_touch: impl Fn(Option<Vec2>) + Sync + Send + 'static) -> Element {
+    let base_id = hooks.use_ref_with(|_| EntityId::null());
+    let touch = hooks.use_ref_with(|_| None::<u64>);
+    hooks.use_event(WINDOW_TOUCH, {
+        let base_id = base_id.clone();
+        move |world, event| {
+            let (Some(position), Some(id), Some(phase)) = (event.get(event_touch()), event.get(touch_id()), event.get_ref(touch_phase()))
+            else {
+                return;
+            };
+            let Ok(transform) = world.get(*base_id.lock(), local_to_world()) else { return };
+            let center = transform.to_scale_rotation_translation().2.truncate() + Vec2::splat(radius);
+            let mut touch = touch.lock();
+            match phase.as_str() {
+                "started" if touch.is_none() && position.distance(center) <= radius => {
+                    *touch = Some(id);
+                    on_touch(Some(position - center));
+                }
+                "moved" if *touch == Some(id) => on_touch(Some(position - center)),
+                "ended" | "cancelled" if *touch == Some(id) => {
+                    *touch = None;
+                    on_touch(None);
+                }
+                _ => {}
+            }
+        }
+    });
+    UIBase
+        .el()
+        .set(width(), radius * 2.)
+        .set(height(), radius * 2.)
+        .set(border_radius(), Vec4::ONE * radius)
+        .set(touch_capture(), Vec2::splat(radius * 2.))
+        .on_spawned(move |_, id, _| *base_id.lock() = id)
+}
+
+/// An on-screen joystick.
+///
+/// `on_change` receives the position of the stick, from -1 to 1 on each axis with up being positive. It's reset to zero when the
+/// finger is lifted.
+#[element_component]
+pub fn VirtualJoystick(hooks: &mut Hooks, radius: f32, on_change: Cb<dyn Fn(Vec2) + Sync + Send>) -> Element {
+    let (offset, set_offset) = hooks.use_state(None::<Vec2>);
+    let base = use_touch_circle(hooks, radius, move |offset| {
+        let offset = offset.map(|offset| offset.clamp_length_max(radius));
+        set_offset(offset);
+        let offset = offset.unwrap_or_default();
+        on_change(vec2(offset.x, -offset.y) / radius);
+    });
+
+    let knob_radius = radius * 0.4;
+    let knob_offset = offset.unwrap_or_default();
+    base.with_background(BASE_COLOR).children(vec![UIBase
+        .el()
+        .set(width(), knob_radius * 2.)
+        .set(height(), knob_radius * 2.)
+        .with_background(if offset.is_some() { ACTIVE_COLOR } else { BASE_COLOR })
+        .set(border_radius(), Vec4::ONE * knob_radius)
+        .set(translation(), vec3(radius - knob_radius + knob_offset.x, radius - knob_radius + knob_offset.y, -0.01))])
+}
+
+/// An on-screen button. `on_change` is called with true when it's pressed, and false when it's released.
+#[element_component]
+pub fn VirtualButton(hooks: &mut Hooks, label: String, radius: f32, on_change: Cb<dyn Fn(bool) + Sync + Send>) -> Element {
+    let (pressed, set_pressed) = hooks.use_state(false);
+    let base = use_touch_circle(hooks, radius, move |offset| {
+        set_pressed(offset.is_some());
+        on_change(offset.is_some());
+    });
+
+    let label_size = radius * 0.6;
+    base.with_background(if pressed { ACTIVE_COLOR } else { BASE_COLOR }).children(vec![Text::el(label)
+        .set(font_size(), label_size)
+        .set(translation(), vec3(radius - label_size * 0.3, radius - label_size * 0.6, -0.01))])
+}
+
+/// A joystick in the bottom left corner of the window, and a row of buttons in the bottom right corner.
+///
+/// `on_button` receives the index of the button in `buttons` and whether it's pressed.
+#[element_component]
+pub fn TouchControls(
+    hooks: &mut Hooks,
+    joystick_radius: f32,
+    button_radius: f32,
+    buttons: Vec<String>,
+    on_joystick: Cb<dyn Fn(Vec2) + Sync + Send>,
+    on_button: Cb<dyn Fn(usize, bool) + Sync + Send>,
+) -> Element {
+    let size = use_window_physical_resolution(hooks).as_vec2();
+    let margin = joystick_radius * 0.5;
+    let mut children =
+        vec![VirtualJoystick::el(joystick_radius, on_joystick)
+            .set(translation(), vec3(margin, size.y - margin - joystick_radius * 2., -0.1))];
+    // The first button is the rightmost one
+    for (index, label) in buttons.into_iter().enumerate() {
+        let on_button = on_button.clone();
+        let from_right = margin + index as f32 * (button_radius * 2. + margin / 2.) + button_radius * 2.;
+        children.push(
+            VirtualButton::el(label, button_radius, ambient_cb::cb(move |pressed| on_button(index, pressed)))
+                .set(translation(), vec3(size.x - from_right, size.y - margin - button_radius * 2., -0.1)),
+        );
+    }
+    UIBase.el().children(children)
+}
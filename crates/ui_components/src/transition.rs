@@ -0,0 +1,30 @@
+//! Declarative animations of UI elements: an element with a transition animates to the values it's set to, instead of
+//! jumping to them, so that e.g. menus and toggles animate when their state changes without timers of their own.
+use ambient_element::Element;
+use ambient_guest_bridge::components::ui::{transition_curve, transition_duration};
+use glam::Vec4;
+
+/// Easing curves for [TransitionExt::with_transition], as the control points of cubic Bézier curves, like CSS's
+pub mod curves {
+    use glam::Vec4;
+
+    pub const LINEAR: Vec4 = Vec4::new(0., 0., 1., 1.);
+    pub const EASE: Vec4 = Vec4::new(0.25, 0.1, 0.25, 1.);
+    pub const EASE_IN: Vec4 = Vec4::new(0.42, 0., 1., 1.);
+    pub const EASE_OUT: Vec4 = Vec4::new(0., 0., 0.58, 1.);
+    pub const EASE_IN_OUT: Vec4 = Vec4::new(0.42, 0., 0.58, 1.);
+}
+
+/// How long the transitions of the built-in components last, in seconds
+pub const DEFAULT_TRANSITION_DURATION: f32 = 0.15;
+
+pub trait TransitionExt {
+    /// Animates the `translation`, `width`, `height`, `background_color` and `color` of the element to the values they're set
+    /// to, by the element, its theme or the layout, over `duration` seconds following `curve`, e.g. [curves::EASE_OUT]
+    fn with_transition(self, duration: f32, curve: Vec4) -> Self;
+}
+impl TransitionExt for Element {
+    fn with_transition(self, duration: f32, curve: Vec4) -> Self {
+        self.set(transition_duration(), duration).set(transition_curve(), curve)
+    }
+}
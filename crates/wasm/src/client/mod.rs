@@ -1,10 +1,32 @@
-use crate::shared::{self, client_bytecode_from_url, module_bytecode, wit, ModuleBytecode};
-use ambient_core::{asset_cache, async_ecs::async_run, runtime};
+use crate::shared::{
+    self, client_bytecode_from_url,
+    conversion::{FromBindgen, IntoBindgen},
+    implementation::unsupported,
+    module_bytecode, wit, ModuleBytecode,
+};
+use ambient_core::{
+    asset_cache,
+    async_ecs::async_run,
+    loading::{module_load_stage, ModuleLoadStage},
+    runtime,
+};
 use ambient_ecs::{query, EntityId, SystemGroup, World};
+use ambient_gizmos::{gizmos, Cuboid, GizmoPrimitive, DEFAULT_WIDTH};
+use ambient_input::files::files;
+use ambient_network::{
+    capture::{capture, RecordingSettings},
+    ServerWorldExt,
+};
+use ambient_prefab::prefetch;
+use ambient_primitives::procedural;
+use ambient_renderer::dynamic_textures;
+use ambient_settings::{package_settings_schema, settings};
 use ambient_std::{
     asset_cache::AsyncAssetKeyExt, asset_url::AbsAssetUrl, download_asset::BytesFromUrl,
 };
-use std::sync::Arc;
+use anyhow::Context;
+use glam::uvec2;
+use std::{sync::Arc, time::Duration};
 
 pub fn initialize(
     world: &mut World,
@@ -34,12 +56,24 @@ pub fn systems() -> SystemGroup {
                             continue;
                         }
                     };
+                    world
+                        .add_component(id, module_load_stage(), ModuleLoadStage::Downloading)
+                        .ok();
                     let assets = world.resource(asset_cache()).clone();
                     let async_run = world.resource(async_run()).clone();
                     world.resource(runtime()).spawn(async move {
                         match BytesFromUrl::new(url, true).get(&assets).await {
                             Err(err) => {
                                 log::warn!("Failed to load client bytecode from url: {:?}", err);
+                                async_run.run(move |world| {
+                                    world
+                                        .add_component(
+                                            id,
+                                            module_load_stage(),
+                                            ModuleLoadStage::Failed(format!("{err:#}")),
+                                        )
+                                        .ok();
+                                });
                             }
                             Ok(bytecode) => {
                                 async_run.run(move |world| {
@@ -50,6 +84,13 @@ pub fn systems() -> SystemGroup {
                                             ModuleBytecode(bytecode.to_vec()),
                                         )
                                         .ok();
+                                    world
+                                        .add_component(
+                                            id,
+                                            module_load_stage(),
+                                            ModuleLoadStage::Compiling,
+                                        )
+                                        .ok();
                                 });
                             }
                         }
@@ -242,6 +283,35 @@ impl wit::component::Host for Bindings {
             query_index,
         )
     }
+
+    fn query_subscribe(&mut self, query_index: u64) -> anyhow::Result<()> {
+        shared::implementation::component::query_subscribe(
+            &self.base.query_states,
+            &mut self.base.query_subscriptions,
+            query_index,
+        )
+    }
+
+    fn query_unsubscribe(&mut self, query_index: u64) -> anyhow::Result<()> {
+        shared::implementation::component::query_unsubscribe(
+            &mut self.base.query_subscriptions,
+            query_index,
+        )
+    }
+
+    fn query_take_delta(
+        &mut self,
+        query_index: u64,
+        kind: wit::component::QueryDeltaKind,
+    ) -> anyhow::Result<Vec<(wit::types::EntityId, Vec<wit::component::ValueResult>)>> {
+        shared::implementation::component::query_take_delta(
+            unsafe { self.world_ref.world() },
+            &self.base.query_states,
+            &mut self.base.query_subscriptions,
+            query_index,
+            kind,
+        )
+    }
 }
 impl wit::event::Host for Bindings {
     fn subscribe(&mut self, name: String) -> anyhow::Result<()> {
@@ -257,8 +327,255 @@ impl wit::event::Host for Bindings {
     }
 }
 
-fn unsupported<T>() -> anyhow::Result<T> {
-    anyhow::bail!("This function is not supported on this side of the API. Please report this if you were able to access this function.")
+impl wit::client_window::Host for Bindings {
+    fn get_clipboard(&mut self) -> anyhow::Result<Option<String>> {
+        Ok(ambient_sys::clipboard::get_text())
+    }
+
+    fn set_clipboard(&mut self, text: String) -> anyhow::Result<()> {
+        ambient_sys::clipboard::set_text(&text);
+        Ok(())
+    }
+
+    fn open_file_dialog(&mut self, extensions: Vec<String>) -> anyhow::Result<()> {
+        let filters = if extensions.is_empty() {
+            vec![]
+        } else {
+            vec![("Supported files".to_string(), extensions)]
+        };
+        ambient_input::files::open_file_dialog(self.world(), filters);
+        Ok(())
+    }
+
+    fn read_file(&mut self, handle: u64) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self
+            .world()
+            .resource_opt(files())
+            .and_then(|files| files.get(handle))
+            .map(|bytes| bytes.to_vec()))
+    }
+
+    fn screenshot(&mut self, name: Option<String>) -> anyhow::Result<()> {
+        self.world().resource(capture()).screenshot(name)
+    }
+
+    fn start_recording(
+        &mut self,
+        name: Option<String>,
+        fps: f32,
+        seconds: Option<f32>,
+    ) -> anyhow::Result<()> {
+        self.world()
+            .resource(capture())
+            .start_recording(RecordingSettings {
+                name,
+                fps,
+                duration: seconds.map(Duration::from_secs_f32),
+            })
+    }
+
+    fn stop_recording(&mut self) -> anyhow::Result<()> {
+        self.world().resource(capture()).stop_recording();
+        Ok(())
+    }
+
+    fn get_setting(&mut self, package: String, key: String) -> anyhow::Result<Option<String>> {
+        let world = self.world();
+        let schemas = world
+            .synced_resource(package_settings_schema())
+            .cloned()
+            .unwrap_or_default();
+        let settings = world.resource_opt(settings()).cloned().unwrap_or_default();
+        Ok(settings.package_value_text(&schemas, &package, &key))
+    }
+}
+
+impl wit::client_mesh::Host for Bindings {
+    fn create(
+        &mut self,
+        positions: Vec<wit::types::Vec3>,
+        normals: Vec<wit::types::Vec3>,
+        texcoords: Vec<wit::types::Vec2>,
+        indices: Vec<u32>,
+    ) -> anyhow::Result<u64> {
+        let mesh = procedural::mesh_from_attributes(
+            positions.from_bindgen(),
+            normals.from_bindgen(),
+            texcoords.from_bindgen(),
+            indices,
+        )?;
+        Ok(procedural::create_procedural_mesh(self.world_mut(), mesh))
+    }
+
+    fn update(
+        &mut self,
+        handle: u64,
+        positions: Vec<wit::types::Vec3>,
+        normals: Vec<wit::types::Vec3>,
+        texcoords: Vec<wit::types::Vec2>,
+        indices: Vec<u32>,
+    ) -> anyhow::Result<()> {
+        let mesh = procedural::mesh_from_attributes(
+            positions.from_bindgen(),
+            normals.from_bindgen(),
+            texcoords.from_bindgen(),
+            indices,
+        )?;
+        procedural::update_procedural_mesh(self.world_mut(), handle, mesh)
+    }
+
+    fn update_vertices(
+        &mut self,
+        handle: u64,
+        offset: u32,
+        positions: Vec<wit::types::Vec3>,
+        normals: Vec<wit::types::Vec3>,
+        texcoords: Vec<wit::types::Vec2>,
+    ) -> anyhow::Result<()> {
+        procedural::update_procedural_mesh_vertices(
+            self.world_mut(),
+            handle,
+            offset,
+            &positions.from_bindgen(),
+            &normals.from_bindgen(),
+            &texcoords.from_bindgen(),
+        )
+    }
+
+    fn destroy(&mut self, handle: u64) -> anyhow::Result<()> {
+        procedural::destroy_procedural_mesh(self.world_mut(), handle);
+        Ok(())
+    }
+}
+
+impl wit::client_texture::Host for Bindings {
+    fn create(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> anyhow::Result<u64> {
+        dynamic_textures::create_dynamic_texture(self.world_mut(), uvec2(width, height), &pixels)
+    }
+
+    fn update(
+        &mut self,
+        handle: u64,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        dynamic_textures::update_dynamic_texture(
+            self.world_mut(),
+            handle,
+            uvec2(x, y),
+            uvec2(width, height),
+            &pixels,
+        )
+    }
+
+    fn destroy(&mut self, handle: u64) -> anyhow::Result<()> {
+        dynamic_textures::destroy_dynamic_texture(self.world_mut(), handle);
+        Ok(())
+    }
+}
+
+impl wit::client_gizmos::Host for Bindings {
+    fn line(
+        &mut self,
+        start: wit::types::Vec3,
+        end: wit::types::Vec3,
+        color: wit::types::Vec3,
+        duration: f32,
+        category: String,
+    ) -> anyhow::Result<()> {
+        let line = GizmoPrimitive::line(start.from_bindgen(), end.from_bindgen(), DEFAULT_WIDTH)
+            .with_color(color.from_bindgen());
+        self.world()
+            .resource(gizmos())
+            .draw_for(&category, gizmo_duration(duration)?, line);
+        Ok(())
+    }
+
+    fn cuboid(
+        &mut self,
+        center: wit::types::Vec3,
+        half_extents: wit::types::Vec3,
+        color: wit::types::Vec3,
+        duration: f32,
+        category: String,
+    ) -> anyhow::Result<()> {
+        let cuboid = Cuboid::new(
+            center.from_bindgen(),
+            half_extents.from_bindgen(),
+            color.from_bindgen(),
+            DEFAULT_WIDTH,
+        );
+        self.world()
+            .resource(gizmos())
+            .draw_for(&category, gizmo_duration(duration)?, cuboid);
+        Ok(())
+    }
+
+    fn sphere(
+        &mut self,
+        center: wit::types::Vec3,
+        radius: f32,
+        color: wit::types::Vec3,
+        duration: f32,
+        category: String,
+    ) -> anyhow::Result<()> {
+        let sphere = GizmoPrimitive::torus(center.from_bindgen(), radius, DEFAULT_WIDTH)
+            .with_color(color.from_bindgen());
+        self.world()
+            .resource(gizmos())
+            .draw_for(&category, gizmo_duration(duration)?, sphere);
+        Ok(())
+    }
+
+    fn text(
+        &mut self,
+        position: wit::types::Vec3,
+        text: String,
+        color: wit::types::Vec3,
+        duration: f32,
+        category: String,
+    ) -> anyhow::Result<()> {
+        self.world().resource(gizmos()).text_for(
+            &category,
+            gizmo_duration(duration)?,
+            position.from_bindgen(),
+            text,
+            color.from_bindgen(),
+        );
+        Ok(())
+    }
+}
+
+impl wit::client_asset::Host for Bindings {
+    fn prefetch(
+        &mut self,
+        url: String,
+        priority: wit::client_asset::PrefetchPriority,
+    ) -> anyhow::Result<u64> {
+        let url = AbsAssetUrl::parse(url)?;
+        Ok(prefetch::prefetch_asset(
+            self.world_mut(),
+            url,
+            priority.from_bindgen(),
+        ))
+    }
+
+    fn cancel_prefetch(&mut self, handle: u64) -> anyhow::Result<()> {
+        prefetch::cancel_prefetch(self.world_mut(), handle);
+        Ok(())
+    }
+
+    fn status(&mut self, handle: u64) -> anyhow::Result<Option<wit::client_asset::PrefetchStatus>> {
+        Ok(prefetch::prefetch_status(self.world(), handle).into_bindgen())
+    }
+}
+
+fn gizmo_duration(seconds: f32) -> anyhow::Result<Duration> {
+    Duration::try_from_secs_f32(seconds.max(0.))
+        .with_context(|| format!("Invalid gizmo duration: {seconds}"))
 }
 
 impl wit::server_player::Host for Bindings {
@@ -275,6 +592,24 @@ impl wit::server_player::Host for Bindings {
     ) -> anyhow::Result<Option<wit::server_player::RawInput>> {
         unsupported()
     }
+
+    fn get_gamepads(
+        &mut self,
+        _player: wit::types::EntityId,
+    ) -> anyhow::Result<Vec<wit::server_player::Gamepad>> {
+        unsupported()
+    }
+
+    fn rumble(
+        &mut self,
+        _player: wit::types::EntityId,
+        _gamepad: Option<u32>,
+        _strong: f32,
+        _weak: f32,
+        _duration: f32,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
 }
 
 impl wit::server_physics::Host for Bindings {
@@ -348,3 +683,57 @@ impl wit::server_asset::Host for Bindings {
         unsupported()
     }
 }
+
+impl wit::server_instance::Host for Bindings {
+    fn current(&mut self) -> anyhow::Result<String> {
+        unsupported()
+    }
+
+    fn list(&mut self) -> anyhow::Result<Vec<String>> {
+        unsupported()
+    }
+
+    fn create(
+        &mut self,
+        _id: String,
+        _source: Option<String>,
+        _tick_rate: Option<f32>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn destroy(&mut self, _id: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn transfer_player(&mut self, _user_id: String, _instance: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn send(
+        &mut self,
+        _instance: String,
+        _name: String,
+        _data: wit::entity::EntityData,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::server_validation::Host for Bindings {
+    fn read_only(&mut self, _component: u32) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn max_speed(&mut self, _component: u32, _max_speed: f32) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn limit_input_rate(&mut self, _max_per_second: f32) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn kick_after(&mut self, _violations: Option<u32>) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
@@ -387,3 +387,50 @@ impl IntoBindgen for ambient_window_types::MouseButton {
         }
     }
 }
+
+impl IntoBindgen for (u32, ambient_input::gamepad::GamepadState) {
+    type Item = wit::server_player::Gamepad;
+
+    fn into_bindgen(self) -> Self::Item {
+        let (id, gamepad) = self;
+        Self::Item {
+            id,
+            name: gamepad.name,
+            buttons: gamepad
+                .buttons
+                .into_iter()
+                .map(|b| b.into_bindgen())
+                .collect(),
+            left_stick: gamepad.left_stick.into_bindgen(),
+            right_stick: gamepad.right_stick.into_bindgen(),
+            left_trigger: gamepad.left_trigger,
+            right_trigger: gamepad.right_trigger,
+        }
+    }
+}
+
+impl IntoBindgen for ambient_input::gamepad::GamepadButton {
+    type Item = wit::server_player::GamepadButton;
+
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Self::South => Self::Item::South,
+            Self::East => Self::Item::East,
+            Self::North => Self::Item::North,
+            Self::West => Self::Item::West,
+            Self::LeftBumper => Self::Item::LeftBumper,
+            Self::RightBumper => Self::Item::RightBumper,
+            Self::LeftTrigger => Self::Item::LeftTrigger,
+            Self::RightTrigger => Self::Item::RightTrigger,
+            Self::Select => Self::Item::Select,
+            Self::Start => Self::Item::Start,
+            Self::Mode => Self::Item::Mode,
+            Self::LeftStick => Self::Item::LeftStick,
+            Self::RightStick => Self::Item::RightStick,
+            Self::DPadUp => Self::Item::DpadUp,
+            Self::DPadDown => Self::Item::DpadDown,
+            Self::DPadLeft => Self::Item::DpadLeft,
+            Self::DPadRight => Self::Item::DpadRight,
+        }
+    }
+}
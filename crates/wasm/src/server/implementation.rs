@@ -1,5 +1,20 @@
 use ambient_core::asset_cache;
-use ambient_input::{player_prev_raw_input, player_raw_input};
+use ambient_ecs::{with_component_registry, Component};
+use ambient_input::{
+    gamepad::{gamepad_rumble, GamepadRumble},
+    player_prev_raw_input, player_raw_input, PLAYER_INPUT_DATAGRAM_ID,
+};
+use ambient_network::{
+    instances::{
+        get_instance_id, instance_requests, request_instance_change, send_instance_message,
+        InstanceRequest,
+    },
+    server::MAIN_INSTANCE_ID,
+    validation::{
+        client_validators, max_speed_validator, rate_limit_validator, read_only_validator,
+        ClientMessage, ClientValidators,
+    },
+};
 use ambient_physics::{helpers::PhysicsObjectCollection, physx::character_controller};
 use ambient_std::{
     asset_cache::SyncAssetKeyExt,
@@ -7,12 +22,15 @@ use ambient_std::{
     shapes::Ray,
 };
 use anyhow::Context;
+use glam::Vec3;
 use itertools::Itertools;
 use physxx::{PxControllerCollisionFlag, PxControllerFilters};
 
 use super::Bindings;
 use crate::shared::{
+    self,
     conversion::{FromBindgen, IntoBindgen},
+    implementation::unsupported,
     wit,
 };
 
@@ -38,6 +56,52 @@ impl wit::server_player::Host for Bindings {
             .ok()
             .into_bindgen())
     }
+
+    fn get_gamepads(
+        &mut self,
+        player: wit::types::EntityId,
+    ) -> anyhow::Result<Vec<wit::server_player::Gamepad>> {
+        Ok(self
+            .world()
+            .get_ref(player.from_bindgen(), player_raw_input())
+            .map(|input| {
+                input
+                    .gamepads
+                    .iter()
+                    .map(|(id, gamepad)| (*id, gamepad.clone()).into_bindgen())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn rumble(
+        &mut self,
+        player: wit::types::EntityId,
+        gamepad: Option<u32>,
+        strong: f32,
+        weak: f32,
+        duration: f32,
+    ) -> anyhow::Result<()> {
+        let player = player.from_bindgen();
+        let world = self.world_mut();
+        // The player's client plays the rumble when it sees a new sequence number
+        let sequence = world
+            .get_ref(player, gamepad_rumble())
+            .map(|rumble| rumble.sequence + 1)
+            .unwrap_or(1);
+        world.add_component(
+            player,
+            gamepad_rumble(),
+            GamepadRumble {
+                sequence,
+                gamepad,
+                strong,
+                weak,
+                duration,
+            },
+        )?;
+        Ok(())
+    }
 }
 
 impl wit::server_physics::Host for Bindings {
@@ -187,3 +251,262 @@ impl wit::server_asset::Host for Bindings {
         Ok(Some(AssetUrl::parse(path)?.resolve(&base_url)?.to_string()))
     }
 }
+
+impl wit::server_instance::Host for Bindings {
+    fn current(&mut self) -> anyhow::Result<String> {
+        Ok(get_instance_id(self.world()))
+    }
+
+    fn list(&mut self) -> anyhow::Result<Vec<String>> {
+        Ok(self.world().resource(instance_requests()).ids())
+    }
+
+    fn create(
+        &mut self,
+        id: String,
+        source: Option<String>,
+        tick_rate: Option<f32>,
+    ) -> anyhow::Result<()> {
+        let source = source.unwrap_or_else(|| MAIN_INSTANCE_ID.to_string());
+        request_instance_change(
+            self.world(),
+            InstanceRequest::Create {
+                id,
+                source,
+                tick_rate,
+            },
+        )
+    }
+
+    fn destroy(&mut self, id: String) -> anyhow::Result<()> {
+        request_instance_change(self.world(), InstanceRequest::Destroy { id })
+    }
+
+    fn transfer_player(&mut self, user_id: String, instance: String) -> anyhow::Result<()> {
+        request_instance_change(
+            self.world(),
+            InstanceRequest::TransferPlayer { user_id, instance },
+        )
+    }
+
+    fn send(
+        &mut self,
+        instance: String,
+        name: String,
+        data: wit::entity::EntityData,
+    ) -> anyhow::Result<()> {
+        send_instance_message(
+            self.world(),
+            instance,
+            name,
+            shared::implementation::component::convert_components_to_entity_data(data),
+        )
+    }
+}
+
+impl Bindings {
+    fn validators(&self) -> anyhow::Result<ClientValidators> {
+        self.world()
+            .resource_opt(client_validators())
+            .cloned()
+            .context("This world does not validate what clients send")
+    }
+}
+impl wit::server_validation::Host for Bindings {
+    fn read_only(&mut self, component: u32) -> anyhow::Result<()> {
+        let desc =
+            with_component_registry(|r| r.get_by_index(component)).context("No such component")?;
+        self.validators()?
+            .add_component_validator(desc, read_only_validator());
+        Ok(())
+    }
+
+    fn max_speed(&mut self, component: u32, max_speed: f32) -> anyhow::Result<()> {
+        let desc =
+            with_component_registry(|r| r.get_by_index(component)).context("No such component")?;
+        anyhow::ensure!(desc.is::<Vec3>(), "{} is not a vec3 component", desc.path());
+        let component = Component::<Vec3>::new(desc);
+        self.validators()?
+            .add_component_validator(component, max_speed_validator(component, max_speed));
+        Ok(())
+    }
+
+    fn limit_input_rate(&mut self, max_per_second: f32) -> anyhow::Result<()> {
+        self.validators()?.add_message_validator(
+            ClientMessage::Datagram(PLAYER_INPUT_DATAGRAM_ID),
+            rate_limit_validator(max_per_second),
+        );
+        Ok(())
+    }
+
+    fn kick_after(&mut self, violations: Option<u32>) -> anyhow::Result<()> {
+        self.validators()?.set_kick_after(violations);
+        Ok(())
+    }
+}
+
+impl wit::client_window::Host for Bindings {
+    fn get_clipboard(&mut self) -> anyhow::Result<Option<String>> {
+        unsupported()
+    }
+
+    fn set_clipboard(&mut self, _text: String) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn open_file_dialog(&mut self, _extensions: Vec<String>) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn read_file(&mut self, _handle: u64) -> anyhow::Result<Option<Vec<u8>>> {
+        unsupported()
+    }
+
+    fn screenshot(&mut self, _name: Option<String>) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn start_recording(
+        &mut self,
+        _name: Option<String>,
+        _fps: f32,
+        _seconds: Option<f32>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn stop_recording(&mut self) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn get_setting(&mut self, _package: String, _key: String) -> anyhow::Result<Option<String>> {
+        unsupported()
+    }
+}
+
+impl wit::client_mesh::Host for Bindings {
+    fn create(
+        &mut self,
+        _positions: Vec<wit::types::Vec3>,
+        _normals: Vec<wit::types::Vec3>,
+        _texcoords: Vec<wit::types::Vec2>,
+        _indices: Vec<u32>,
+    ) -> anyhow::Result<u64> {
+        unsupported()
+    }
+
+    fn update(
+        &mut self,
+        _handle: u64,
+        _positions: Vec<wit::types::Vec3>,
+        _normals: Vec<wit::types::Vec3>,
+        _texcoords: Vec<wit::types::Vec2>,
+        _indices: Vec<u32>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn update_vertices(
+        &mut self,
+        _handle: u64,
+        _offset: u32,
+        _positions: Vec<wit::types::Vec3>,
+        _normals: Vec<wit::types::Vec3>,
+        _texcoords: Vec<wit::types::Vec2>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn destroy(&mut self, _handle: u64) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_texture::Host for Bindings {
+    fn create(&mut self, _width: u32, _height: u32, _pixels: Vec<u8>) -> anyhow::Result<u64> {
+        unsupported()
+    }
+
+    fn update(
+        &mut self,
+        _handle: u64,
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+        _pixels: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn destroy(&mut self, _handle: u64) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_gizmos::Host for Bindings {
+    fn line(
+        &mut self,
+        _start: wit::types::Vec3,
+        _end: wit::types::Vec3,
+        _color: wit::types::Vec3,
+        _duration: f32,
+        _category: String,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn cuboid(
+        &mut self,
+        _center: wit::types::Vec3,
+        _half_extents: wit::types::Vec3,
+        _color: wit::types::Vec3,
+        _duration: f32,
+        _category: String,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn sphere(
+        &mut self,
+        _center: wit::types::Vec3,
+        _radius: f32,
+        _color: wit::types::Vec3,
+        _duration: f32,
+        _category: String,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn text(
+        &mut self,
+        _position: wit::types::Vec3,
+        _text: String,
+        _color: wit::types::Vec3,
+        _duration: f32,
+        _category: String,
+    ) -> anyhow::Result<()> {
+        unsupported()
+    }
+}
+
+impl wit::client_asset::Host for Bindings {
+    fn prefetch(
+        &mut self,
+        _url: String,
+        _priority: wit::client_asset::PrefetchPriority,
+    ) -> anyhow::Result<u64> {
+        unsupported()
+    }
+
+    fn cancel_prefetch(&mut self, _handle: u64) -> anyhow::Result<()> {
+        unsupported()
+    }
+
+    fn status(
+        &mut self,
+        _handle: u64,
+    ) -> anyhow::Result<Option<wit::client_asset::PrefetchStatus>> {
+        unsupported()
+    }
+}
@@ -230,6 +230,35 @@ impl wit::component::Host for Bindings {
             query_index,
         )
     }
+
+    fn query_subscribe(&mut self, query_index: u64) -> anyhow::Result<()> {
+        shared::implementation::component::query_subscribe(
+            &self.base.query_states,
+            &mut self.base.query_subscriptions,
+            query_index,
+        )
+    }
+
+    fn query_unsubscribe(&mut self, query_index: u64) -> anyhow::Result<()> {
+        shared::implementation::component::query_unsubscribe(
+            &mut self.base.query_subscriptions,
+            query_index,
+        )
+    }
+
+    fn query_take_delta(
+        &mut self,
+        query_index: u64,
+        kind: wit::component::QueryDeltaKind,
+    ) -> anyhow::Result<Vec<(wit::types::EntityId, Vec<wit::component::ValueResult>)>> {
+        shared::implementation::component::query_take_delta(
+            unsafe { self.world_ref.world() },
+            &self.base.query_states,
+            &mut self.base.query_subscriptions,
+            query_index,
+            kind,
+        )
+    }
 }
 impl wit::event::Host for Bindings {
     fn subscribe(&mut self, name: String) -> anyhow::Result<()> {
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use ambient_ecs::{EntityId, PrimitiveComponent, Query, QueryState, World};
 
@@ -12,6 +12,20 @@ pub struct BindingsBase {
     pub spawned_entities: HashSet<EntityId>,
     pub subscribed_events: HashSet<String>,
     pub query_states: QueryStateMap,
+    /// The queries whose changes are sent to the module, by the id of the query
+    pub query_subscriptions: HashMap<u64, QuerySubscription>,
+}
+
+/// Finds the changes to the results of a query each frame, and keeps them until the module takes them
+#[derive(Clone)]
+pub struct QuerySubscription {
+    pub spawn_query: (Query, QueryState),
+    pub despawn_query: (Query, QueryState),
+    /// Only if the query has components whose changes are tracked
+    pub change_query: Option<(Query, QueryState)>,
+    pub added: Vec<EntityId>,
+    pub changed: Vec<EntityId>,
+    pub removed: Vec<EntityId>,
 }
 
 pub trait BindingsBound:
@@ -19,9 +33,16 @@ pub trait BindingsBound:
     + wit::component::Host
     + wit::entity::Host
     + wit::event::Host
+    + wit::client_window::Host
+    + wit::client_mesh::Host
+    + wit::client_texture::Host
+    + wit::client_gizmos::Host
+    + wit::client_asset::Host
     + wit::server_player::Host
     + wit::server_physics::Host
     + wit::server_asset::Host
+    + wit::server_instance::Host
+    + wit::server_validation::Host
     + Clone
     + Sync
     + Send
@@ -2,6 +2,7 @@ use ambient_sys::time::SystemTime;
 
 use ambient_animation as animation;
 use ambient_ecs::EntityId;
+use ambient_prefab::prefetch::{PrefetchPriority, PrefetchStatus};
 use ambient_std::asset_url::TypedAssetUrl;
 use glam::{Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 
@@ -273,6 +274,7 @@ impl FromBindgen for wit::entity::AnimationAction {
             },
             looping: self.looping,
             weight: self.weight,
+            root_motion: true,
         }
     }
 }
@@ -286,3 +288,26 @@ impl FromBindgen for wit::entity::AnimationController {
         }
     }
 }
+
+impl FromBindgen for wit::client_asset::PrefetchPriority {
+    type Item = PrefetchPriority;
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            Self::High => PrefetchPriority::High,
+            Self::Normal => PrefetchPriority::Normal,
+            Self::Low => PrefetchPriority::Low,
+        }
+    }
+}
+
+impl IntoBindgen for PrefetchStatus {
+    type Item = wit::client_asset::PrefetchStatus;
+    fn into_bindgen(self) -> Self::Item {
+        match self {
+            Self::Queued => Self::Item::Queued,
+            Self::Loading => Self::Item::Loading,
+            Self::Loaded => Self::Item::Loaded,
+            Self::Failed => Self::Item::Failed,
+        }
+    }
+}
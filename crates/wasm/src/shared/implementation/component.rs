@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+
 use ambient_ecs::{
     primitive_component_definitions, with_component_registry, Component, ComponentDesc,
-    ComponentEntry, ComponentSet, ComponentValue, Entity, EntityId, PrimitiveComponentType as PCT,
-    QueryEvent, QueryState, World,
+    ComponentEntry, ComponentSet, ComponentValue, Entity, EntityAccessor, EntityId,
+    PrimitiveComponentType as PCT, Query, QueryEvent, QueryState, World,
 };
 use anyhow::Context;
 use glam::{Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
@@ -9,7 +11,7 @@ use itertools::Itertools;
 use paste::paste;
 use slotmap::Key;
 
-use crate::shared::bindings::QueryStateMap;
+use crate::shared::bindings::{QueryStateMap, QuerySubscription};
 
 use super::super::{
     conversion::{FromBindgen, IntoBindgen},
@@ -378,3 +380,276 @@ pub fn query_eval(
 
     Ok(result)
 }
+
+pub fn query_subscribe(
+    query_states: &QueryStateMap,
+    query_subscriptions: &mut HashMap<u64, QuerySubscription>,
+    query_index: u64,
+) -> anyhow::Result<()> {
+    let key = slotmap::DefaultKey::from(slotmap::KeyData::from_ffi(query_index));
+    let (query, _, _) = query_states.get(key).context("no query state for key")?;
+
+    let with_event = |event| Query {
+        filter: query.filter.clone(),
+        event,
+    };
+    let change_query = match &query.event {
+        QueryEvent::Changed { components } => Some((
+            with_event(QueryEvent::Changed {
+                components: components.clone(),
+            }),
+            QueryState::new(),
+        )),
+        _ => None,
+    };
+    query_subscriptions.insert(
+        query_index,
+        QuerySubscription {
+            spawn_query: (with_event(QueryEvent::Spawned), QueryState::new()),
+            despawn_query: (with_event(QueryEvent::Despawned), QueryState::new()),
+            change_query,
+            added: vec![],
+            changed: vec![],
+            removed: vec![],
+        },
+    );
+
+    Ok(())
+}
+
+pub fn query_unsubscribe(
+    query_subscriptions: &mut HashMap<u64, QuerySubscription>,
+    query_index: u64,
+) -> anyhow::Result<()> {
+    query_subscriptions.remove(&query_index);
+    Ok(())
+}
+
+/// Adds the changes since the last update to the subscriptions, and returns the ids of the
+/// queries that have changes the module hasn't taken yet
+pub fn update_query_subscriptions(
+    world: &World,
+    query_subscriptions: &mut HashMap<u64, QuerySubscription>,
+) -> Vec<u64> {
+    fn run(world: &World, (query, state): &mut (Query, QueryState)) -> Vec<EntityId> {
+        query.iter(world, Some(state)).map(|ea| ea.id()).collect()
+    }
+
+    let mut changed_queries = vec![];
+    for (query_index, subscription) in query_subscriptions.iter_mut() {
+        let added = run(world, &mut subscription.spawn_query);
+        let removed = run(world, &mut subscription.despawn_query);
+        // A change query also returns the entities that were just added
+        let changed = match &mut subscription.change_query {
+            Some(change_query) => run(world, change_query)
+                .into_iter()
+                .filter(|id| !added.contains(id))
+                .collect(),
+            None => vec![],
+        };
+
+        subscription.removed.extend(removed);
+        subscription.added.extend(added);
+        subscription.changed.extend(changed);
+        if !(subscription.added.is_empty()
+            && subscription.changed.is_empty()
+            && subscription.removed.is_empty())
+        {
+            changed_queries.push(*query_index);
+        }
+    }
+    changed_queries
+}
+
+pub fn query_take_delta(
+    world: &World,
+    query_states: &QueryStateMap,
+    query_subscriptions: &mut HashMap<u64, QuerySubscription>,
+    query_index: u64,
+    kind: wit::component::QueryDeltaKind,
+) -> anyhow::Result<Vec<(wit::types::EntityId, Vec<wit::component::ValueResult>)>> {
+    use wit::component::QueryDeltaKind;
+
+    let key = slotmap::DefaultKey::from(slotmap::KeyData::from_ffi(query_index));
+    let (_, _, primitive_components) = query_states.get(key).context("no query state for key")?;
+    let subscription = query_subscriptions
+        .get_mut(&query_index)
+        .context("the query isn't subscribed to")?;
+
+    let ids = match kind {
+        QueryDeltaKind::Added => std::mem::take(&mut subscription.added),
+        QueryDeltaKind::Changed => std::mem::take(&mut subscription.changed),
+        QueryDeltaKind::Removed => {
+            return Ok(std::mem::take(&mut subscription.removed)
+                .into_iter()
+                .map(|id| (id.into_bindgen(), vec![]))
+                .collect())
+        }
+    };
+
+    Ok(ids
+        .into_iter()
+        .unique()
+        // The entity may have been despawned since it was added or changed
+        .filter(|id| world.exists(*id))
+        .filter_map(|id| {
+            let ea = EntityAccessor::World { id };
+            let values = primitive_components
+                .iter()
+                .map(|pc| read_primitive_component_from_entity_accessor(world, &ea, pc.clone()))
+                .collect::<Option<Vec<_>>>()?;
+            Some((id.into_bindgen(), values))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use ambient_ecs::components;
+    use wit::component::{QueryBuild, QueryDeltaKind};
+
+    use super::*;
+
+    components!("test", {
+        query_test_value: f32,
+        query_test_tag: (),
+    });
+
+    fn init() {
+        ambient_ecs::init_components();
+        init_components();
+    }
+
+    /// A subscription to the entities with a `query_test_value`, whose changes are tracked
+    struct Subscription {
+        query_states: QueryStateMap,
+        query_subscriptions: HashMap<u64, QuerySubscription>,
+        query_index: u64,
+    }
+    impl Subscription {
+        fn new() -> Self {
+            let mut query_states = QueryStateMap::default();
+            let mut query_subscriptions = HashMap::new();
+            let query_index = query(
+                &mut query_states,
+                QueryBuild {
+                    components: vec![query_test_value().index() as u32],
+                    include: vec![],
+                    exclude: vec![],
+                    changed: vec![query_test_value().index() as u32],
+                },
+                wit::component::QueryEvent::Frame,
+            )
+            .unwrap();
+            query_subscribe(&query_states, &mut query_subscriptions, query_index).unwrap();
+            Self {
+                query_states,
+                query_subscriptions,
+                query_index,
+            }
+        }
+        /// Whether the query has changes that weren't taken
+        fn update(&mut self, world: &World) -> bool {
+            let changed_queries = update_query_subscriptions(world, &mut self.query_subscriptions);
+            changed_queries == vec![self.query_index]
+        }
+        /// The ids of the entities, and how many values they have
+        fn take(&mut self, world: &World, kind: QueryDeltaKind) -> Vec<(EntityId, usize)> {
+            query_take_delta(
+                world,
+                &self.query_states,
+                &mut self.query_subscriptions,
+                self.query_index,
+                kind,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(id, values)| (id.from_bindgen(), values.len()))
+            .collect()
+        }
+    }
+
+    #[test]
+    fn subscriptions_track_added_changed_and_removed() {
+        init();
+        let mut world = World::new("test");
+        let a = Entity::new()
+            .with(query_test_value(), 1.)
+            .with(query_test_tag(), ())
+            .spawn(&mut world);
+        let mut subscription = Subscription::new();
+
+        // The entities that already match are added
+        assert!(subscription.update(&world));
+        assert_eq!(
+            subscription.take(&world, QueryDeltaKind::Added),
+            vec![(a, 1)]
+        );
+        assert_eq!(subscription.take(&world, QueryDeltaKind::Added), vec![]);
+        assert_eq!(subscription.take(&world, QueryDeltaKind::Changed), vec![]);
+        assert_eq!(subscription.take(&world, QueryDeltaKind::Removed), vec![]);
+        assert!(!subscription.update(&world));
+
+        // Entities that were just added aren't also reported as changed
+        world.set(a, query_test_value(), 2.).unwrap();
+        let b = Entity::new().with(query_test_value(), 1.).spawn(&mut world);
+        assert!(subscription.update(&world));
+        assert_eq!(
+            subscription.take(&world, QueryDeltaKind::Added),
+            vec![(b, 1)]
+        );
+        assert_eq!(
+            subscription.take(&world, QueryDeltaKind::Changed),
+            vec![(a, 1)]
+        );
+
+        // Entities that stop matching are removed, without values
+        world.remove_component(b, query_test_value()).unwrap();
+        assert!(subscription.update(&world));
+        assert_eq!(
+            subscription.take(&world, QueryDeltaKind::Removed),
+            vec![(b, 0)]
+        );
+        assert_eq!(subscription.take(&world, QueryDeltaKind::Added), vec![]);
+        assert_eq!(subscription.take(&world, QueryDeltaKind::Changed), vec![]);
+        assert!(!subscription.update(&world));
+    }
+
+    #[test]
+    fn despawned_entities_are_not_taken() {
+        init();
+        let mut world = World::new("test");
+        let mut subscription = Subscription::new();
+        assert!(!subscription.update(&world));
+
+        let a = Entity::new().with(query_test_value(), 1.).spawn(&mut world);
+        let b = Entity::new().with(query_test_value(), 1.).spawn(&mut world);
+        subscription.update(&world);
+        // Changes are queued until they're taken, so an entity may be queued several times
+        world.set(b, query_test_value(), 2.).unwrap();
+        subscription.update(&world);
+        world.set(b, query_test_value(), 3.).unwrap();
+        subscription.update(&world);
+
+        world.despawn(a);
+        assert_eq!(
+            subscription.take(&world, QueryDeltaKind::Added),
+            vec![(b, 1)]
+        );
+        assert_eq!(
+            subscription.take(&world, QueryDeltaKind::Changed),
+            vec![(b, 1)]
+        );
+
+        world.set(b, query_test_value(), 4.).unwrap();
+        subscription.update(&world);
+        world.despawn(b);
+        assert_eq!(subscription.take(&world, QueryDeltaKind::Changed), vec![]);
+
+        // Their removal is reported by the next updates
+        subscription.update(&world);
+        let removed = subscription.take(&world, QueryDeltaKind::Removed);
+        assert_eq!(removed.len(), 2);
+        assert!(removed.contains(&(a, 0)) && removed.contains(&(b, 0)));
+    }
+}
@@ -9,6 +9,7 @@ pub mod wit;
 
 use std::sync::Arc;
 
+use ambient_core::loading::{module_load_stage, ModuleLoadStage};
 use ambient_ecs::{
     components, dont_despawn_on_unload, query, world_events, ComponentEntry, Debuggable,
     Description, Entity, EntityId, FnSystem, Networked, Resource, Store, SystemGroup, World,
@@ -32,6 +33,8 @@ components!("wasm::shared", {
     module_enabled: bool,
     @[Networked, Store, Debuggable]
     module_errors: ModuleErrors,
+    @[Debuggable, Description["The id of the package this module was loaded from."]]
+    module_package: String,
 
     @[Resource, Description["Used to signal messages from the WASM host/runtime."]]
     messenger: Arc<dyn Fn(&World, EntityId, MessageType, &str) + Send + Sync>,
@@ -101,6 +104,22 @@ pub fn systems() -> SystemGroup {
                     run_all(world, &RunContext::new(world, &name, data));
                 }
             })),
+            Box::new(FnSystem::new(move |world, _| {
+                profiling::scope!("WASM module query deltas");
+                // Only the modules whose subscribed queries changed are run
+                let mut errors = vec![];
+                for (id, mut state) in query(module_state()).collect_cloned(world, None) {
+                    for query_index in state.update_query_subscriptions(world) {
+                        let context = RunContext::new(
+                            world,
+                            &format!("core/query_delta/{query_index}"),
+                            Entity::new(),
+                        );
+                        errors.extend(run(world, id, state.clone(), &context));
+                    }
+                }
+                update_errors(world, &errors);
+            })),
             Box::new(FnSystem::new(move |world, _| {
                 profiling::scope!("WASM module frame event");
                 // trigger frame event
@@ -230,15 +249,32 @@ fn load(
         })
     });
 
+    // Only client-side modules report their progress
+    if world.has_component(module_id, module_load_stage()) {
+        let stage = match &result {
+            Ok(_) => ModuleLoadStage::Loaded,
+            Err(err) => ModuleLoadStage::Failed(err.clone()),
+        };
+        world
+            .add_component(module_id, module_load_stage(), stage)
+            .ok();
+    }
+
     match result {
         Ok(sms) => {
             // Run the initial startup event.
-            errors.extend(run(
+            let error = run(
                 world,
                 module_id,
                 sms.clone(),
                 &RunContext::new(world, "core/module_load", Entity::new()),
-            ));
+            );
+            if error.is_none() {
+                // Tools like `campfire test examples` wait for this to know the module started
+                let messenger = world.resource(self::messenger()).clone();
+                messenger(world, module_id, MessageType::Info, "Loaded");
+            }
+            errors.extend(error);
 
             world.add_component(module_id, module_state(), sms).unwrap();
         }
@@ -251,7 +287,9 @@ pub(crate) fn unload(
     module_id: EntityId,
     reason: &str,
 ) -> Vec<(EntityId, String)> {
-    let Ok(sms) = world.get_cloned(module_id, module_state()) else { return vec![]; };
+    let Ok(sms) = world.get_cloned(module_id, module_state()) else {
+        return vec![];
+    };
 
     let errors = run(
         world,
@@ -352,6 +390,13 @@ pub fn spawn_module(
     Ok(ed.spawn(world))
 }
 
+/// Unloads the module, and then despawns it
+pub fn despawn_module(world: &mut World, id: EntityId) {
+    let errors = unload(world, id, "despawning");
+    update_errors(world, &errors);
+    world.despawn(id);
+}
+
 pub fn get_module_name(world: &World, id: EntityId) -> Identifier {
     Identifier::new(world.get_cloned(id, ambient_core::name()).unwrap()).unwrap()
 }
@@ -76,6 +76,9 @@ pub trait ModuleStateBehavior: Sync + Send {
     fn run(&mut self, world: &mut World, context: &RunContext) -> anyhow::Result<()>;
     fn drain_spawned_entities(&mut self) -> HashSet<EntityId>;
     fn supports_event(&self, event_name: &str) -> bool;
+    /// Finds the changes to the queries the module subscribed to, and returns the ids of those
+    /// with changes it hasn't taken yet
+    fn update_query_subscriptions(&mut self, world: &World) -> Vec<u64>;
 }
 
 pub type Messenger = Box<dyn Fn(&World, &str) + Sync + Send>;
@@ -131,6 +134,10 @@ impl ModuleStateBehavior for ModuleState {
     fn supports_event(&self, event_name: &str) -> bool {
         self.inner.read().supports_event(event_name)
     }
+
+    fn update_query_subscriptions(&mut self, world: &World) -> Vec<u64> {
+        self.inner.write().update_query_subscriptions(world)
+    }
 }
 
 struct ModuleStateInnerImpl<Bindings: BindingsBound> {
@@ -237,6 +244,11 @@ impl<Bindings: BindingsBound> ModuleStateBehavior for ModuleStateInnerImpl<Bindi
             .subscribed_events
             .contains(event_name)
     }
+
+    fn update_query_subscriptions(&mut self, world: &World) -> Vec<u64> {
+        let base = self.store.data_mut().bindings.base_mut();
+        component::update_query_subscriptions(world, &mut base.query_subscriptions)
+    }
 }
 
 struct WasiOutputStream(flume::Sender<String>);
@@ -0,0 +1,125 @@
+//! The ambience sounds set on the synchronized resources loop for as long as they're set, and are faded in and out with the
+//! weather: the rain with the `rain`, the wind with its speed, and the calm ambience when neither is noticeable.
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use ambient_audio::{AudioFromUrl, Frame, SampleRate, Source};
+use ambient_core::{
+    asset_cache, runtime,
+    weather::{clear_ambience, rain_ambience, weather, wind_ambience},
+};
+use ambient_ecs::{components, Component, FnSystem, Resource, SystemGroup, World};
+use ambient_network::ServerWorldExt;
+use ambient_std::{asset_cache::AsyncAssetKeyExt, asset_url::AbsAssetUrl};
+use ambient_world_audio::audio_mixer;
+use parking_lot::Mutex;
+
+components!("weather", {
+    @[Resource]
+    ambience: Ambience,
+});
+
+/// The wind is at its loudest at this speed, in metres per second
+const LOUDEST_WIND: f32 = 20.;
+
+#[derive(Debug, Clone, Default)]
+pub struct Ambience {
+    clear: AmbienceLayer,
+    rain: AmbienceLayer,
+    wind: AmbienceLayer,
+}
+
+/// A looping sound, and its volume
+#[derive(Debug, Clone, Default)]
+struct AmbienceLayer {
+    url: String,
+    gain: Arc<Mutex<f32>>,
+    stopped: Arc<AtomicBool>,
+}
+impl AmbienceLayer {
+    /// Loads the sound at `url`, and loops it from silence once it's loaded
+    fn play(world: &World, url: String) -> Self {
+        let layer = Self { url, ..Default::default() };
+        if layer.url.is_empty() {
+            return layer;
+        }
+        let url = match AbsAssetUrl::parse(&layer.url) {
+            Ok(url) => url,
+            Err(err) => {
+                log::warn!("Failed to parse the ambience url {}: {err:?}", layer.url);
+                return layer;
+            }
+        };
+        let assets = world.resource(asset_cache()).clone();
+        let mixer = world.resource(audio_mixer()).clone();
+        let (gain, stopped) = (layer.gain.clone(), layer.stopped.clone());
+        world.resource(runtime()).spawn(async move {
+            match (AudioFromUrl { url }).get(&assets).await {
+                Ok(track) => {
+                    mixer.play(Stoppable { source: track.decode().repeat().dynamic_gain(gain), stopped });
+                }
+                Err(err) => log::warn!("Failed to load the ambience: {err:?}"),
+            }
+        });
+        layer
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Plays `source` until `stopped` is set, as the mixer can't stop sounds
+struct Stoppable<S> {
+    source: S,
+    stopped: Arc<AtomicBool>,
+}
+impl<S: Source> Source for Stoppable<S> {
+    fn next_sample(&mut self) -> Option<Frame> {
+        if self.stopped.load(Ordering::Relaxed) {
+            None
+        } else {
+            self.source.next_sample()
+        }
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn sample_count(&self) -> Option<u64> {
+        self.source.sample_count()
+    }
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "weather/ambience",
+        vec![Box::new(FnSystem::new(|world, _| {
+            if world.resource_opt(audio_mixer()).is_none() {
+                return;
+            }
+            let weather = world.resource_opt(weather()).copied().unwrap_or_default();
+            let wind = (weather.wind.length() / LOUDEST_WIND).clamp(0., 1.);
+            let clear = 1. - weather.rain.max(weather.snow).max(wind);
+
+            let mut state = world.resource_opt(ambience()).cloned().unwrap_or_default();
+            let layers: [(&mut AmbienceLayer, Component<String>, f32); 3] = [
+                (&mut state.clear, clear_ambience(), clear),
+                (&mut state.rain, rain_ambience(), weather.rain),
+                (&mut state.wind, wind_ambience(), wind),
+            ];
+            for (layer, url, gain) in layers {
+                let url = world.synced_resource(url).cloned().unwrap_or_default();
+                if url != layer.url {
+                    layer.stop();
+                    *layer = AmbienceLayer::play(world, url);
+                }
+                *layer.gain.lock() = gain;
+            }
+            world.add_resource(ambience(), state);
+        }))],
+    )
+}
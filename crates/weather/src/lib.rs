@@ -0,0 +1,57 @@
+//! The weather of the world, as set by the server on the synchronized resources: rain and snow fall around the camera, surfaces
+//! get wet in the rain, the wind blows the precipitation and sways the vegetation, and ambience sounds follow it all.
+//!
+//! Clients ease their own [weather] towards the one set by the server, so that changes are gradual.
+use ambient_core::{
+    dtime,
+    weather::{rain, snow, weather, wind, Weather},
+};
+use ambient_ecs::{FnSystem, SystemGroup};
+use ambient_network::ServerWorldExt;
+use glam::Vec3;
+
+mod ambience;
+mod precipitation;
+
+/// How fast the weather of the client follows the one set by the server, per second
+const EASING: f32 = 0.5;
+/// How fast surfaces get wet in a downpour, per second
+const WETTING_RATE: f32 = 0.1;
+/// How fast surfaces dry when it doesn't rain, per second
+const DRYING_RATE: f32 = 0.02;
+
+pub fn init_components() {
+    precipitation::init_components();
+    ambience::init_components();
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "weather",
+        vec![
+            Box::new(FnSystem::new(|world, _| {
+                let dt = *world.resource(dtime());
+                let target = Weather {
+                    rain: world.synced_resource(rain()).copied().unwrap_or_default().clamp(0., 1.),
+                    snow: world.synced_resource(snow()).copied().unwrap_or_default().clamp(0., 1.),
+                    wind: world.synced_resource(wind()).copied().unwrap_or(Vec3::ZERO),
+                    wetness: 0.,
+                };
+                let mut current = world.resource_opt(weather()).copied().unwrap_or_default();
+                let t = 1. - (-EASING * dt).exp();
+                current.rain += (target.rain - current.rain) * t;
+                current.snow += (target.snow - current.snow) * t;
+                current.wind += (target.wind - current.wind) * t;
+                current.wetness = if current.rain > 0.01 {
+                    current.wetness + current.rain * WETTING_RATE * dt
+                } else {
+                    current.wetness - DRYING_RATE * dt
+                }
+                .clamp(0., 1.);
+                world.add_resource(weather(), current);
+            })),
+            Box::new(precipitation::systems()),
+            Box::new(ambience::systems()),
+        ],
+    )
+}
@@ -0,0 +1,194 @@
+//! Rain and snow are particles falling in a box around the camera, which wrap around it as it moves. Each kind is rendered by an
+//! emitter entity with a procedural mesh of one quad per particle, whose vertices are rewritten every frame; particles beyond
+//! the intensity of the weather are collapsed, so the mesh never has to be reallocated.
+use ambient_core::{
+    camera::get_active_camera,
+    dtime, main_scene,
+    player::local_user_id,
+    transform::get_world_position,
+    weather::{weather, Weather},
+};
+use ambient_ecs::{components, query, Entity, FnSystem, SystemGroup, World};
+use ambient_primitives::procedural::{create_procedural_mesh, mesh_from_attributes, procedural_mesh, update_procedural_mesh_vertices};
+use ambient_renderer::{color, double_sided};
+use glam::{vec2, vec3, vec4, Vec2, Vec3, Vec3Swizzles, Vec4};
+
+components!("weather", {
+    precipitation_emitter: PrecipitationEmitter,
+});
+
+/// How far from the camera particles fall, horizontally
+const RADIUS: f32 = 20.;
+/// Particles fall from this high above the camera ...
+const TOP: f32 = 15.;
+/// ... to this far below it
+const BOTTOM: f32 = 5.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrecipitationKind {
+    Rain,
+    Snow,
+}
+impl PrecipitationKind {
+    const ALL: [Self; 2] = [Self::Rain, Self::Snow];
+
+    fn max_particles(&self) -> usize {
+        match self {
+            Self::Rain => 4000,
+            Self::Snow => 3000,
+        }
+    }
+
+    /// The velocity of the particles in still air, in metres per second
+    fn fall_velocity(&self) -> Vec3 {
+        match self {
+            Self::Rain => vec3(0., 0., -9.),
+            Self::Snow => vec3(0., 0., -1.2),
+        }
+    }
+
+    /// How much the particles are carried by the wind
+    fn drift(&self) -> f32 {
+        match self {
+            Self::Rain => 0.6,
+            Self::Snow => 1.,
+        }
+    }
+
+    /// The width and length of the particles; raindrops are streaks along their velocity
+    fn size(&self) -> Vec2 {
+        match self {
+            Self::Rain => vec2(0.01, 0.4),
+            Self::Snow => vec2(0.05, 0.05),
+        }
+    }
+
+    fn color(&self) -> Vec4 {
+        match self {
+            Self::Rain => vec4(0.7, 0.75, 0.8, 1.),
+            Self::Snow => Vec4::ONE,
+        }
+    }
+
+    fn intensity(&self, weather: &Weather) -> f32 {
+        match self {
+            Self::Rain => weather.rain,
+            Self::Snow => weather.snow,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrecipitationEmitter {
+    kind: PrecipitationKind,
+    /// The procedural mesh the particles are rendered with
+    mesh: u64,
+    /// The positions of the particles, in world space; only the first `active` ones are rendered
+    particles: Vec<Vec3>,
+    active: usize,
+}
+impl PrecipitationEmitter {
+    fn new(kind: PrecipitationKind, mesh: u64, camera: Vec3) -> Self {
+        let particles = (0..kind.max_particles())
+            .map(|_| camera + vec3(random_range(-RADIUS, RADIUS), random_range(-RADIUS, RADIUS), random_range(-BOTTOM, TOP)))
+            .collect();
+        Self { kind, mesh, particles, active: 0 }
+    }
+
+    fn velocity(&self, wind: Vec3) -> Vec3 {
+        self.kind.fall_velocity() + wind * self.kind.drift()
+    }
+
+    fn simulate(&mut self, camera: Vec3, weather: &Weather, dt: f32) {
+        self.active = (self.kind.intensity(weather) * self.particles.len() as f32).round() as usize;
+        let velocity = self.velocity(weather.wind);
+        for particle in &mut self.particles[..self.active] {
+            *particle += velocity * dt;
+            // Snowflakes flutter as they fall
+            if self.kind == PrecipitationKind::Snow {
+                *particle += vec3(random_range(-1., 1.), random_range(-1., 1.), 0.) * 0.3 * dt;
+            }
+            if particle.z < camera.z - BOTTOM {
+                *particle = camera + vec3(random_range(-RADIUS, RADIUS), random_range(-RADIUS, RADIUS), TOP);
+            }
+            // Keeps the particles around the camera as it moves
+            let offset = particle.xy() - camera.xy();
+            let wrapped = vec2((offset.x + RADIUS).rem_euclid(2. * RADIUS), (offset.y + RADIUS).rem_euclid(2. * RADIUS)) - RADIUS;
+            *particle += (wrapped - offset).extend(0.);
+            if particle.z > camera.z + TOP {
+                particle.z -= TOP + BOTTOM;
+            }
+        }
+    }
+
+    /// The four corners of the quad of each particle, facing the camera
+    fn vertices(&self, camera: Vec3, wind: Vec3) -> Vec<Vec3> {
+        let size = self.kind.size();
+        let fall = self.velocity(wind).normalize_or_zero();
+        let mut vertices = Vec::with_capacity(self.particles.len() * 4);
+        for particle in &self.particles[..self.active] {
+            let to_camera = (camera - *particle).normalize_or_zero();
+            let axis = match self.kind {
+                PrecipitationKind::Rain => fall,
+                PrecipitationKind::Snow => to_camera.cross(Vec3::Z).cross(to_camera).normalize_or_zero(),
+            };
+            let side = axis.cross(to_camera).normalize_or_zero() * size.x / 2.;
+            let top = *particle - axis * size.y;
+            vertices.extend([*particle - side, *particle + side, top - side, top + side]);
+        }
+        vertices.resize(self.particles.len() * 4, camera);
+        vertices
+    }
+}
+
+fn random_range(min: f32, max: f32) -> f32 {
+    min + rand::random::<f32>() * (max - min)
+}
+
+fn spawn_emitter(world: &mut World, kind: PrecipitationKind, camera: Vec3) {
+    let vertex_count = kind.max_particles() * 4;
+    let indices = (0..kind.max_particles() as u32).flat_map(|i| [0, 1, 2, 1, 3, 2].map(|vertex| i * 4 + vertex)).collect();
+    let mesh = match mesh_from_attributes(vec![camera; vertex_count], vec![Vec3::Z; vertex_count], vec![], indices) {
+        Ok(mesh) => mesh,
+        Err(err) => {
+            log::warn!("Failed to build the {kind:?} mesh: {err:?}");
+            return;
+        }
+    };
+    let handle = create_procedural_mesh(world, mesh);
+    Entity::new()
+        .with(precipitation_emitter(), PrecipitationEmitter::new(kind, handle, camera))
+        .with(procedural_mesh(), handle)
+        .with(color(), kind.color())
+        .with(double_sided(), true)
+        .spawn(world);
+}
+
+pub fn systems() -> SystemGroup {
+    SystemGroup::new(
+        "weather/precipitation",
+        vec![Box::new(FnSystem::new(|world, _| {
+            let Some(camera) = get_active_camera(world, main_scene(), world.resource_opt(local_user_id())) else { return };
+            let camera = get_world_position(world, camera).unwrap_or_default();
+            let weather = world.resource_opt(weather()).copied().unwrap_or_default();
+            let dt = *world.resource(dtime());
+
+            let spawned = query(precipitation_emitter()).iter(world, None).map(|(_, emitter)| emitter.kind).collect::<Vec<_>>();
+            for kind in PrecipitationKind::ALL {
+                // Emitters are only spawned the first time it rains or snows
+                if kind.intensity(&weather) > 0. && !spawned.contains(&kind) {
+                    spawn_emitter(world, kind, camera);
+                }
+            }
+            for id in query(precipitation_emitter()).collect_ids(world, None) {
+                let Ok(emitter) = world.get_mut(id, precipitation_emitter()) else { continue };
+                if emitter.active == 0 && emitter.kind.intensity(&weather) == 0. {
+                    continue;
+                }
+                emitter.simulate(camera, &weather, dt);
+                let (handle, vertices) = (emitter.mesh, emitter.vertices(camera, weather.wind));
+                update_procedural_mesh_vertices(world, handle, 0, &vertices, &[], &[]).ok();
+            }
+        }))],
+    )
+}
@@ -29,6 +29,7 @@ fn spawn_emitters(world: &mut World) {
             amplitude: 5.0,
             attenuation: Attenuation::InversePoly { quad: 0.1, lin: 0.0, constant: 1.0 },
             pos,
+            ..Default::default()
         }));
 
         let id = Cube
@@ -1,10 +1,10 @@
 use ambient_core::asset_cache;
 use ambient_ecs::{EntityId, World};
+use ambient_network::events::{broadcast_event, ServerEventRegistry};
 
-use crate::{play_sound_on_entity, AudioNode, AudioSeed};
+use crate::{audio_mixer, play_sound_on_entity, AudioNode, AudioSeed};
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-/// Plays a sound on an entity. Requires that the entity has an AudioEmitterDef on the server.
-/// Otherwise, the audio is played on a temporary emitter
+/// Plays a sound on an entity. Requires that the entity has a [crate::sound_emitter].
 pub struct PlayLocalSound {
     pub id: EntityId,
     pub source: AudioNode,
@@ -13,7 +13,21 @@ pub struct PlayLocalSound {
     pub seed: AudioSeed,
 }
 
+/// Plays `source` on the [crate::sound_emitter] entity `id` for all the players. Call this on the server.
+pub fn broadcast_sound(world: &World, id: EntityId, source: AudioNode, label: impl Into<String>) {
+    broadcast_event(world, PlayLocalSound { id, source, label: label.into(), seed: AudioSeed::new() });
+}
+
+/// Lets the client play the sounds sent with [broadcast_sound]
+pub fn register_client_events(registry: &ServerEventRegistry) {
+    registry.register(play_local_sound);
+}
+
 pub fn play_local_sound(world: &mut World, event: PlayLocalSound) -> anyhow::Result<()> {
+    // Clients without an audio device don't play anything
+    if world.resource_opt(audio_mixer()).is_none() {
+        return Ok(());
+    }
     let assets = world.resource(asset_cache());
     let source = match event.source.try_build(assets, event.seed).transpose() {
         Some(source) => source?,
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use ambient_audio::{hrtf::HrtfLib, Attenuation, AudioEmitter, AudioListener, AudioMixer, Sound, Source};
-use ambient_ecs::{components, query, EntityId, Resource, World};
+use ambient_ecs::{components, query, Debuggable, Description, EntityId, Name, Networked, Resource, Store, World};
 use ambient_element::ElementComponentExt;
 use ambient_std::{cb, Cb};
 use ambient_ui::{
@@ -23,6 +23,37 @@ components!("audio", {
 
     @[Resource]
     audio_mixer: AudioMixer,
+
+    @[
+        Debuggable, Networked, Store,
+        Name["Sound emitter"],
+        Description["If attached, the sounds played on this entity are positioned in 3D, and the client attenuates and pans them for its listener.\nThe other `sound_*` components configure it."]
+    ]
+    sound_emitter: (),
+    @[
+        Debuggable, Networked, Store,
+        Name["Sound min distance"],
+        Description["Within this distance, the sound is heard at full volume. Defaults to 1."]
+    ]
+    sound_min_distance: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Sound max distance"],
+        Description["Beyond this distance, the sound can't be heard. Defaults to no limit."]
+    ]
+    sound_max_distance: f32,
+    @[
+        Debuggable, Networked, Store,
+        Name["Sound rolloff"],
+        Description["How the volume falls off beyond the min distance. One of Inverse (halved at twice the min distance), Linear (silent at the max distance) or Exponential. Defaults to Inverse."]
+    ]
+    sound_rolloff: String,
+    @[
+        Debuggable, Networked, Store,
+        Name["Sound doppler"],
+        Description["Whether the pitch of the sound changes as it moves towards or away from the listener. Defaults to true."]
+    ]
+    sound_doppler: bool,
 });
 
 /// TODO: hook this into the Attenuation inside ambient_audio
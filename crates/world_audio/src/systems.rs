@@ -1,11 +1,15 @@
 use std::{io::Cursor, sync::Arc};
 
-use ambient_audio::{hrtf::HrtfLib, AudioMixer};
-use ambient_core::transform::local_to_world;
-use ambient_ecs::{query, SystemGroup, World};
-use glam::{vec4, Mat4};
+use ambient_audio::{hrtf::HrtfLib, Attenuation, AudioEmitter, AudioListener, AudioMixer};
+use ambient_core::{camera::get_active_camera, main_scene, player::local_user_id, transform::local_to_world};
+use ambient_ecs::{query, EntityId, FnSystem, SystemGroup, World};
+use glam::{vec4, Mat4, Vec3};
+use parking_lot::Mutex;
 
-use crate::{audio_emitter, audio_listener, audio_mixer, hrtf_lib};
+use crate::{
+    audio_emitter, audio_listener, audio_mixer, hrtf_lib, sound_doppler, sound_emitter, sound_max_distance, sound_min_distance,
+    sound_rolloff,
+};
 
 /// Initializes the HRTF sphere and adds the appropriate resources
 ///
@@ -28,6 +32,18 @@ pub fn spatial_audio_systems() -> SystemGroup {
     SystemGroup::new(
         "spatial_audio",
         vec![
+            // Gives the entities the server made emitters one the client can play sounds from
+            query(sound_emitter()).excl(audio_emitter()).to_system(|q, world, qs, _| {
+                for id in q.collect_ids(world, qs) {
+                    world.add_component(id, audio_emitter(), Arc::new(Mutex::new(AudioEmitter::default()))).ok();
+                }
+            }),
+            // Applies the parameters the server gave the emitters
+            query((audio_emitter(), sound_emitter())).to_system(|q, world, qs, _| {
+                for (id, (emitter, _)) in q.iter(world, qs) {
+                    update_sound_emitter(world, id, &mut emitter.lock());
+                }
+            }),
             // Updates the volume of audio emitters in the world
             query((audio_emitter(), local_to_world())).to_system(|q, world, qs, _| {
                 for (_, (emitter, ltw)) in q.iter(world, qs) {
@@ -36,6 +52,26 @@ pub fn spatial_audio_systems() -> SystemGroup {
                     emitter.pos = pos;
                 }
             }),
+            // Keeps the listener on the camera the local player is looking through
+            Box::new(FnSystem::new(|world, _| {
+                if world.resource_opt(audio_mixer()).is_none() {
+                    return;
+                }
+                let Some(camera) = get_active_camera(world, main_scene(), world.resource_opt(local_user_id())) else { return };
+                if world.has_component(camera, audio_listener()) {
+                    return;
+                }
+                // The sounds that are playing keep the listener they were started with, so it's moved rather than replaced
+                let listeners = query(audio_listener()).collect_cloned(world, None);
+                let listener = match listeners.first() {
+                    Some((_, listener)) => listener.clone(),
+                    None => Arc::new(Mutex::new(AudioListener::new(Mat4::IDENTITY, Vec3::X * 0.2))),
+                };
+                for (id, _) in listeners {
+                    world.remove_component(id, audio_listener()).ok();
+                }
+                world.add_component(camera, audio_listener(), listener).ok();
+            })),
             query((audio_listener(), local_to_world())).to_system_with_name("update_audio_listener", |q, world, qs, _| {
                 for (_, (listener, &ltw)) in q.iter(world, qs) {
                     let mut listener = listener.lock();
@@ -46,6 +82,20 @@ pub fn spatial_audio_systems() -> SystemGroup {
     )
 }
 
+fn update_sound_emitter(world: &World, id: EntityId, emitter: &mut AudioEmitter) {
+    emitter.min_distance = world.get(id, sound_min_distance()).unwrap_or(1.0);
+    emitter.max_distance = world.get(id, sound_max_distance()).unwrap_or(f32::INFINITY);
+    emitter.doppler = world.get(id, sound_doppler()).unwrap_or(true);
+
+    // The curves are relative to the min distance
+    let reference = emitter.min_distance.max(1.0);
+    emitter.attenuation = match world.get_ref(id, sound_rolloff()).map(|rolloff| rolloff.as_str()) {
+        Ok("Linear") => Attenuation::Linear(1.0 / (emitter.max_distance - emitter.min_distance).max(f32::EPSILON)),
+        Ok("Exponential") => Attenuation::Exponential(std::f32::consts::LN_2 / reference),
+        _ => Attenuation::InversePoly { quad: 0.0, lin: 1.0 / reference, constant: 1.0 },
+    };
+}
+
 pub fn client_systems() -> SystemGroup {
     SystemGroup::new("Spatial audio", vec![Box::new(spatial_audio_systems())])
 }
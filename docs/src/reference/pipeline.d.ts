@@ -59,6 +59,10 @@ export type Pipeline = {
       "TriggerArea" | 
       /// This object should only be present in the picking scene.
       "Picking",
+    /// If specified, the model is pre-fractured: the children of the node with this name are split off into chunks with
+    /// colliders of their own, which the prefab breaks into when it's destroyed. The children of a chunk are the smaller
+    /// chunks it breaks into.
+    destructible_chunks?: string,
     /// Whether or not this mesh should have its texture sizes capped.
     cap_texture_sizes?: 
       /// Cap this model's textures to 128x128.
@@ -76,6 +80,33 @@ export type Pipeline = {
       /// Cap this model's textures to SIZE x SIZE.
       /// It is strongly recommended that this is a power of two.
       {"Custom": u32},
+    /// If specified, the meshes are optimized for rendering, and can have levels of detail generated for them.
+    optimize_meshes?: {
+      /// Reorder triangles so that the GPU's vertex cache is used better. On by default.
+      vertex_cache?: boolean,
+      /// Reorder triangles to reduce overdraw, allowing the vertex cache efficiency to get worse by at most this factor
+      /// (i.e. 1.05 allows it to be 5% worse). Defaults to 1.05; set it to `null` to turn it off.
+      overdraw_threshold?: f32 | null,
+      /// Quantize the vertex attributes (positions to 16 bits within the mesh's bounds, normals, tangents and texture coordinates to
+      /// half precision), and merge the vertices that become identical. Off by default.
+      quantize?: boolean,
+      /// If specified, generate simplified versions of the meshes, which are switched to as they get smaller on screen.
+      lods?: {
+        /// The smallest size on screen (the radius of the mesh's bounding sphere, relative to the screen) at which the original mesh is
+        /// used; below it, the first level is used.
+        min_screen_size?: f32,
+        /// The levels, from the most detailed to the least.
+        levels?: {
+          /// The fraction of the original mesh's triangles to aim for.
+          ratio: f32,
+          /// The largest error the simplification is allowed to make, relative to the size of the mesh. Simplification stops before
+          /// reaching `ratio` if it would exceed this.
+          max_error: f32,
+          /// The smallest size on screen at which this level is used; below it, the next level is, or the mesh is hidden if this is the last one.
+          min_screen_size?: f32,
+        }[],
+      },
+    },
     /// Treats all assets in the pipeline as variations, and outputs a single asset which is a collection of all assets.
     /// Most useful for grass and other entities whose individual identity is not important.
     collection_of_variants?: boolean,
@@ -239,6 +270,10 @@ export type Pipeline = {
     /// The audio asset pipeline.
     /// Will import supported audio file formats and produce Ogg Vorbis files to be used by the runtime.
     type: "Audio",
+  } | {
+    /// The world streaming pipeline.
+    /// Will split the entities of `.world.json` files by their `streaming_cell` into cells, which are loaded around players.
+    type: "Streaming",
   },
   /// Filter the sources used to feed this pipeline.
   /// This is a list of glob patterns for accepted files.
@@ -248,4 +283,15 @@ export type Pipeline = {
   tags?: string[],
   /// Categories to apply to the output resources.
   categories?: string[][],
+  /// If specified, the textures output by this pipeline are transcoded to BasisU textures in KTX2 containers.
+  /// These are much smaller to download and to keep in GPU memory, and are transcoded to a format the GPU supports when loaded.
+  texture_compression?: {
+    /// The UASTC quality level, from 0 (fastest) to 4 (best quality). Defaults to 2.
+    quality?: u32,
+    /// If set, rate-distortion optimization is used to make the textures compress better, at some cost of quality.
+    /// Lower values give better quality; 1.0 is a good starting point.
+    rdo_quality?: f32,
+    /// The Zstandard compression level used for the texture data. Defaults to 18.
+    zstd_level?: u32,
+  },
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use super::{
     identifier::{IdentifierPath, IdentifierPathBuf},
-    tree::{Tree, TreeNode, TreeNodeInner},
+    tree::{Tree, TreeNode, TreeNodeInner, TreeNodeNamespace},
 };
 use anyhow::Context;
 use quote::quote;
@@ -110,11 +110,19 @@ fn to_token_stream(
                         #[doc = #doc_comment]
                     }
                 });
+                let struct_accessors =
+                    match ns.namespace.as_ref().and_then(|n| n.struct_type.as_ref()) {
+                        Some(struct_type) => {
+                            Some(generate_struct_accessors(node, ns, api_name, struct_type)?)
+                        }
+                        None => None,
+                    };
                 quote! {
                     #doc_comment_fragment
                     pub mod #name_ident {
                         #prelude
                         #(#children)*
+                        #struct_accessors
                     }
                 }
             })
@@ -155,6 +163,48 @@ fn to_token_stream(
     }
 }
 
+/// Generates the functions to get and set a component of a struct type, which is stored as one component per field
+fn generate_struct_accessors(
+    node: &TreeNode<Component>,
+    ns: &TreeNodeNamespace<Component>,
+    api_name: &syn::Path,
+    struct_type: &str,
+) -> anyhow::Result<proc_macro2::TokenStream> {
+    let supers = (0..=node.path.len()).map(|_| quote! { super });
+    let struct_ident = quote::format_ident!("{}", struct_type);
+    let struct_path = quote! { #(#supers::)* types::#struct_ident };
+    let fields = ns.children.keys().collect::<Vec<_>>();
+
+    let get_comment =
+        format!("Gets the *{struct_type}* of the entity, if it has all of its fields.");
+    let set_comment = format!(
+        "Sets the *{struct_type}* of the entity, adding the components of the fields it lacks."
+    );
+    let to_entity_comment =
+        format!("The components of a *{struct_type}*, to spawn or to merge into an entity.");
+    Ok(quote! {
+        #[doc = #get_comment]
+        pub fn get(id: #api_name::global::EntityId) -> Option<#struct_path> {
+            Some(#struct_path {
+                #(#fields: #api_name::entity::get_component(id, #fields())?),*
+            })
+        }
+        #[doc = #set_comment]
+        pub fn set(id: #api_name::global::EntityId, value: #struct_path) {
+            #(#api_name::entity::add_component(id, #fields(), value.#fields);)*
+        }
+        #[doc = #to_entity_comment]
+        pub fn to_entity(value: #struct_path) -> #api_name::ecs::Entity {
+            #api_name::ecs::Entity::new()
+                #(.with(#fields(), value.#fields))*
+        }
+    })
+}
+
+pub fn is_primitive_type(ty: &str) -> bool {
+    convert_primitive_type_to_rust_type(ty, &syn::parse_quote!(ambient_api), false).is_some()
+}
+
 fn convert_primitive_type_to_rust_type(
     ty: &str,
     api_name: &syn::Path,
@@ -7,7 +7,7 @@ use anyhow::Context;
 use proc_macro2::TokenStream;
 use quote::quote;
 use serde::Deserialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Concept {
@@ -16,6 +16,9 @@ pub struct Concept {
     #[serde(default)]
     pub extends: Vec<IdentifierPathBuf>,
     pub components: BTreeMap<IdentifierPathBuf, toml::Value>,
+    /// Components that the concept doesn't require, with their suggested values
+    #[serde(default)]
+    pub optional: BTreeMap<IdentifierPathBuf, toml::Value>,
 }
 
 pub fn tree_to_token_stream(
@@ -76,9 +79,13 @@ fn to_token_stream(
             let make_concept =
                 generate_make(concept_tree, components_tree, api_name, name, concept)?;
             let is_concept = generate_is(concept_tree, components_tree, api_name, name, concept)?;
+            let missing_concept = generate_missing(name, concept)?;
+            let builder = generate_builder(concept_tree, components_tree, api_name, name, concept)?;
             Ok(quote! {
                 #make_concept
                 #is_concept
+                #missing_concept
+                #builder
             })
         }
     }
@@ -98,6 +105,8 @@ fn generate_make(
         generate_component_list_doc_comment(concept_tree, component_tree, api_name, concept)?
     );
     let make_ident = quote::format_ident!("make_{}", name);
+    // The concepts it extends are merged in order, so this is only to reject ambiguous inheritance
+    resolve_values(concept_tree, concept, |c| &c.components)?;
 
     let extends: Vec<_> = concept
         .extends
@@ -190,6 +199,200 @@ fn generate_is(
     })
 }
 
+fn generate_missing(name: &str, concept: &Concept) -> anyhow::Result<TokenStream> {
+    let missing_comment = format!(
+        "The components of a *{}* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *{}*.",
+        concept.name, concept.name
+    );
+    let missing_ident = quote::format_ident!("missing_{}", name);
+
+    let extends: Vec<_> = concept
+        .extends
+        .iter()
+        .map(|i| {
+            let (last, namespaces) = i.split_last().unwrap();
+            let extend_ident = quote::format_ident!("missing_{}", last.as_ref());
+            let supers = namespaces.iter().map(|_| quote! { super });
+            quote! {
+                missing.extend(#(#supers::)* #(#namespaces::)* #extend_ident(id));
+            }
+        })
+        .collect();
+
+    let components_prefix = Identifier::new("components").map_err(anyhow::Error::msg)?;
+    let components: Vec<_> = concept
+        .components
+        .keys()
+        .map(|path| {
+            let full_path = build_component_path(&components_prefix, path.as_path());
+            let path = path.to_string();
+            quote! {
+                if !entity::has_component(id, #full_path()) {
+                    missing.push(#path);
+                }
+            }
+        })
+        .collect();
+
+    let body = if extends.is_empty() && components.is_empty() {
+        quote! { Vec::new() }
+    } else {
+        quote! {
+            let mut missing = Vec::new();
+            #(#extends)*
+            #(#components)*
+            missing
+        }
+    };
+
+    Ok(quote! {
+        #[doc = #missing_comment]
+        pub fn #missing_ident(id: EntityId) -> Vec<&'static str> {
+            #body
+        }
+    })
+}
+
+/// The values of the components of `concept` that `values` selects, including those inherited from the concepts it extends.
+///
+/// The values of the concept override those it inherits. Two of the concepts it extends can't disagree on the value of
+/// a component, unless the concept overrides it.
+fn resolve_values(
+    concept_tree: &Tree<Concept>,
+    concept: &Concept,
+    values: fn(&Concept) -> &BTreeMap<IdentifierPathBuf, toml::Value>,
+) -> anyhow::Result<BTreeMap<IdentifierPathBuf, toml::Value>> {
+    let mut inherited = BTreeMap::<IdentifierPathBuf, (toml::Value, &IdentifierPathBuf)>::new();
+    for parent_path in &concept.extends {
+        let parent = concept_tree
+            .get(parent_path.as_path())
+            .with_context(|| format!("no definition found for {parent_path}"))?;
+        for (component, value) in resolve_values(concept_tree, parent, values)? {
+            if let Some((other_value, other_parent)) = inherited.get(&component) {
+                if *other_value != value && !values(concept).contains_key(&component) {
+                    anyhow::bail!(
+                        "*{}* inherits `{component}` from both `{other_parent}` and `{parent_path}` with different values, so it must set it itself",
+                        concept.name
+                    );
+                }
+            }
+            inherited.insert(component, (value, parent_path));
+        }
+    }
+
+    let mut resolved: BTreeMap<_, _> = inherited
+        .into_iter()
+        .map(|(component, (value, _))| (component, value))
+        .collect();
+    resolved.extend(
+        values(concept)
+            .iter()
+            .map(|(component, value)| (component.clone(), value.clone())),
+    );
+    Ok(resolved)
+}
+
+/// Generates a builder for the concepts with optional components (including inherited ones), with a method per optional
+/// component.
+fn generate_builder(
+    concept_tree: &Tree<Concept>,
+    component_tree: &Tree<Component>,
+    api_name: &syn::Path,
+    name: &str,
+    concept: &Concept,
+) -> anyhow::Result<Option<TokenStream>> {
+    let required = resolve_values(concept_tree, concept, |c| &c.components)?;
+    let optional: Vec<_> = resolve_values(concept_tree, concept, |c| &c.optional)?
+        .into_iter()
+        .filter(|(path, _)| !required.contains_key(path))
+        .collect();
+    if optional.is_empty() {
+        return Ok(None);
+    }
+
+    let pascal_name: String = name
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    let builder_ident = quote::format_ident!("{}Builder", pascal_name);
+    let build_ident = quote::format_ident!("build_{}", name);
+    let make_ident = quote::format_ident!("make_{}", name);
+
+    let components_prefix = Identifier::new("components").map_err(anyhow::Error::msg)?;
+    let mut method_names = HashSet::new();
+    let mut methods = Vec::new();
+    let mut suggestions = Vec::new();
+    for (path, value) in &optional {
+        let component = component_tree
+            .get(path.as_path())
+            .with_context(|| format!("there is no component defined at `{path}`"))?;
+        let ty = component.type_.to_token_stream(api_name, false)?;
+        let suggested = toml_value_to_tokens(path.as_path(), &component.type_, value)?;
+        let full_path = build_component_path(&components_prefix, path.as_path());
+
+        let method_name = path.last().context("empty component path")?;
+        if ["build", "suggested"].contains(&method_name.as_ref())
+            || !method_names.insert(method_name.clone())
+        {
+            anyhow::bail!(
+                "*{}* has several optional components named `{method_name}`, or one named like a method of its builder",
+                concept.name
+            );
+        }
+        let method_comment = format!(
+            "Sets the optional `{path}`. Its suggested value is `{}`.",
+            SemiprettyTokenStream(suggested.clone())
+        );
+        methods.push(quote! {
+            #[doc = #method_comment]
+            pub fn #method_name(mut self, value: #ty) -> Self {
+                self.0.set(#full_path(), value);
+                self
+            }
+        });
+        suggestions.push(quote! {
+            if self.0.get(#full_path()).is_none() {
+                self.0.set(#full_path(), #suggested);
+            }
+        });
+    }
+
+    let builder_comment = format!(
+        "Builds a *{}* with some of its optional components. See [{build_ident}].",
+        concept.name
+    );
+    let build_comment = format!(
+        "Starts building a *{}*, from its required components.",
+        concept.name
+    );
+    Ok(Some(quote! {
+        #[doc = #builder_comment]
+        pub struct #builder_ident(Entity);
+        impl #builder_ident {
+            #(#methods)*
+            /// Sets the optional components which haven't been set to their suggested values.
+            #[allow(clippy::approx_constant)]
+            pub fn suggested(mut self) -> Self {
+                #(#suggestions)*
+                self
+            }
+            pub fn build(self) -> Entity {
+                self.0
+            }
+        }
+        #[doc = #build_comment]
+        pub fn #build_ident() -> #builder_ident {
+            #builder_ident(#make_ident())
+        }
+    }))
+}
+
 fn build_component_path(prefix: &Identifier, path: IdentifierPath) -> IdentifierPathBuf {
     IdentifierPathBuf::from_iter(std::iter::once(prefix).chain(path.iter()).cloned())
 }
@@ -2,6 +2,7 @@ use super::{
     component::Component,
     concept::Concept,
     identifier::{Identifier, IdentifierPathBuf},
+    struct_type::StructType,
 };
 use serde::Deserialize;
 use std::collections::BTreeMap;
@@ -13,6 +14,8 @@ pub struct Manifest {
     pub components: BTreeMap<IdentifierPathBuf, NamespaceOr<Component>>,
     #[serde(default)]
     pub concepts: BTreeMap<IdentifierPathBuf, NamespaceOr<Concept>>,
+    #[serde(default)]
+    pub types: BTreeMap<String, StructType>,
 }
 impl Manifest {
     pub fn project_path(&self) -> IdentifierPathBuf {
@@ -35,6 +38,9 @@ pub struct Project {
 pub struct Namespace {
     pub name: String,
     pub description: String,
+    /// The struct type of the component this namespace holds the fields of, if it does
+    #[serde(skip)]
+    pub struct_type: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
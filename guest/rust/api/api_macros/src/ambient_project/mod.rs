@@ -12,6 +12,7 @@ mod component;
 mod concept;
 mod identifier;
 mod manifest;
+mod struct_type;
 mod tree;
 
 pub fn read_file(file_path: String) -> anyhow::Result<(Option<String>, String)> {
@@ -37,13 +38,26 @@ pub fn implementation(
         IdentifierPathBuf::empty()
     };
 
-    let component_tree = Tree::new(&manifest.components, validate_namespaces_documented)?;
+    let components = struct_type::expand_components(&manifest.components, &manifest.types)?;
+    let component_tree = Tree::new(&components, validate_namespaces_documented)?;
     let concept_tree = Tree::new(&manifest.concepts, validate_namespaces_documented)?;
 
     let components_tokens =
         component::tree_to_token_stream(&component_tree, &api_name, project_path.as_path())?;
     let concept_tokens = concept::tree_to_token_stream(&concept_tree, &component_tree, &api_name)?;
 
+    let types = if manifest.types.is_empty() {
+        None
+    } else {
+        let types_tokens = struct_type::types_to_token_stream(&manifest.types, &api_name)?;
+        Some(quote! {
+            /// Auto-generated type definitions. These are the struct types of components, which are stored as one component per field.
+            pub mod types {
+                #types_tokens
+            }
+        })
+    };
+
     let manifest = file_path.map(
         |file_path| quote! { const _PROJECT_MANIFEST: &'static str = include_str!(#file_path); },
     );
@@ -59,5 +73,6 @@ pub fn implementation(
         pub mod concepts {
             #concept_tokens
         }
+        #types
     ))
 }
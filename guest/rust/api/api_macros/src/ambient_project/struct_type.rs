@@ -0,0 +1,126 @@
+use super::{
+    component::{is_primitive_type, Component, ComponentType},
+    identifier::{Identifier, IdentifierPathBuf},
+    manifest::{Namespace, NamespaceOr},
+};
+use anyhow::Context;
+use quote::quote;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A struct type that components can be of. As the runtime only stores primitive values, a component of a struct type
+/// is a namespace of one component per field.
+#[derive(Deserialize, Debug, Clone)]
+pub struct StructType {
+    pub name: String,
+    pub description: String,
+    pub fields: BTreeMap<Identifier, ComponentType>,
+}
+
+/// Validates that the name of a type can be used as a Rust type name and isn't that of a primitive type.
+pub fn validate_type_name(name: &str) -> anyhow::Result<()> {
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        anyhow::bail!("type `{name}` must start with an uppercase ASCII character");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        anyhow::bail!("type `{name}` must be PascalCase ASCII");
+    }
+    if is_primitive_type(name) {
+        anyhow::bail!("type `{name}` has the name of a primitive type");
+    }
+    Ok(())
+}
+
+/// Replaces the components of a struct type by a namespace of one component per field.
+pub fn expand_components(
+    components: &BTreeMap<IdentifierPathBuf, NamespaceOr<Component>>,
+    types: &BTreeMap<String, StructType>,
+) -> anyhow::Result<BTreeMap<IdentifierPathBuf, NamespaceOr<Component>>> {
+    let mut expanded = BTreeMap::new();
+    for (path, component) in components {
+        let (component, type_name, struct_type) = match component {
+            NamespaceOr::Other(
+                component @ Component {
+                    type_: ComponentType::String(ty),
+                    ..
+                },
+            ) => match types.get(ty) {
+                Some(struct_type) => (component, ty, struct_type),
+                None => {
+                    expanded.insert(path.clone(), NamespaceOr::Other(component.clone()));
+                    continue;
+                }
+            },
+            other => {
+                expanded.insert(path.clone(), other.clone());
+                continue;
+            }
+        };
+        if component.default.is_some() {
+            anyhow::bail!("component `{path}` is of the struct type `{type_name}`, so it can't have a default");
+        }
+
+        expanded.insert(
+            path.clone(),
+            NamespaceOr::Namespace(Namespace {
+                name: component.name.clone(),
+                description: component.description.clone(),
+                struct_type: Some(type_name.clone()),
+            }),
+        );
+        for (field, ty) in &struct_type.fields {
+            let field_path =
+                IdentifierPathBuf::from_iter(path.iter().chain(std::iter::once(field)).cloned());
+            expanded.insert(
+                field_path,
+                NamespaceOr::Other(Component {
+                    name: format!("{}: {field}", component.name),
+                    description: format!("The `{field}` of the *{}*.", struct_type.name),
+                    type_: ty.clone(),
+                    attributes: component.attributes.clone(),
+                    default: None,
+                }),
+            );
+        }
+    }
+    Ok(expanded)
+}
+
+pub fn types_to_token_stream(
+    types: &BTreeMap<String, StructType>,
+    api_name: &syn::Path,
+) -> anyhow::Result<proc_macro2::TokenStream> {
+    let types = types
+        .iter()
+        .map(|(type_name, struct_type)| {
+            validate_type_name(type_name)?;
+            let type_ident = quote::format_ident!("{}", type_name);
+
+            let mut doc_comment = format!("**{}**", struct_type.name);
+            if !struct_type.description.is_empty() {
+                doc_comment += &format!(": {}", struct_type.description.replace('\n', "\n\n"));
+            }
+
+            let fields = struct_type
+                .fields
+                .iter()
+                .map(|(field, ty)| {
+                    let ty = ty.to_token_stream(api_name, true).with_context(|| {
+                        format!("invalid type for the field `{field}` of `{type_name}`")
+                    })?;
+                    Ok(quote! { pub #field: #ty })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            Ok(quote! {
+                #[doc = #doc_comment]
+                #[derive(Debug, Clone, PartialEq)]
+                pub struct #type_ident {
+                    #(#fields),*
+                }
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(quote! { #(#types)* })
+}
@@ -510,6 +510,62 @@ fn can_generate_concepts_with_all_supported_types() {
                     ]
                 )
             }
+            #[doc = "The components of a *Everything* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *Everything*."]
+            pub fn missing_everything(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                if !entity::has_component(id, components::bool()) {
+                    missing.push("bool");
+                }
+                if !entity::has_component(id, components::empty()) {
+                    missing.push("empty");
+                }
+                if !entity::has_component(id, components::entity_id()) {
+                    missing.push("entity_id");
+                }
+                if !entity::has_component(id, components::f32()) {
+                    missing.push("f32");
+                }
+                if !entity::has_component(id, components::f64()) {
+                    missing.push("f64");
+                }
+                if !entity::has_component(id, components::i32()) {
+                    missing.push("i32");
+                }
+                if !entity::has_component(id, components::mat4()) {
+                    missing.push("mat4");
+                }
+                if !entity::has_component(id, components::option_string1()) {
+                    missing.push("option_string1");
+                }
+                if !entity::has_component(id, components::option_string2()) {
+                    missing.push("option_string2");
+                }
+                if !entity::has_component(id, components::quat()) {
+                    missing.push("quat");
+                }
+                if !entity::has_component(id, components::string()) {
+                    missing.push("string");
+                }
+                if !entity::has_component(id, components::u32()) {
+                    missing.push("u32");
+                }
+                if !entity::has_component(id, components::u64()) {
+                    missing.push("u64");
+                }
+                if !entity::has_component(id, components::vec2()) {
+                    missing.push("vec2");
+                }
+                if !entity::has_component(id, components::vec3()) {
+                    missing.push("vec3");
+                }
+                if !entity::has_component(id, components::vec4()) {
+                    missing.push("vec4");
+                }
+                if !entity::has_component(id, components::vec_vec2()) {
+                    missing.push("vec_vec2");
+                }
+                missing
+            }
         }
     };
 
@@ -585,6 +641,14 @@ fn can_extend_with_multiple_concepts() {
             pub fn is_concept1(id: EntityId) -> bool {
                 entity::has_components(id, &[&components::f32()])
             }
+            #[doc = "The components of a *C1* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *C1*."]
+            pub fn missing_concept1(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                if !entity::has_component(id, components::f32()) {
+                    missing.push("f32");
+                }
+                missing
+            }
 
             #[allow(clippy::approx_constant)]
             #[doc = "Makes a *C2*.\n\n\n\n*Components*:\n\n- `f64: f64 = 8f64`\n"]
@@ -595,6 +659,14 @@ fn can_extend_with_multiple_concepts() {
             pub fn is_concept2(id: EntityId) -> bool {
                 entity::has_components(id, &[&components::f64()])
             }
+            #[doc = "The components of a *C2* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *C2*."]
+            pub fn missing_concept2(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                if !entity::has_component(id, components::f64()) {
+                    missing.push("f64");
+                }
+                missing
+            }
 
             #[allow(clippy::approx_constant)]
             #[doc = "Makes a *C3*.\n\n\n\n*Components*:\n\n- `i32: i32 = 16i32`\n- **`concept1`**:\n  - `f32: f32 = 4f32`\n- **`concept2`**:\n  - `f64: f64 = 8f64`\n"]
@@ -608,6 +680,16 @@ fn can_extend_with_multiple_concepts() {
             pub fn is_concept3(id: EntityId) -> bool {
                 is_concept1(id) && is_concept2(id) && entity::has_components(id, &[&components::i32()])
             }
+            #[doc = "The components of a *C3* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *C3*."]
+            pub fn missing_concept3(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                missing.extend(missing_concept1(id));
+                missing.extend(missing_concept2(id));
+                if !entity::has_component(id, components::i32()) {
+                    missing.push("i32");
+                }
+                missing
+            }
         }
     };
 
@@ -729,6 +811,15 @@ fn can_generate_concepts() {
                     &components::core::rendering::color()
                 ])
             }
+            #[doc = "The components of a *Colored Sphere* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *Colored Sphere*."]
+            pub fn missing_colored_sphere(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                missing.extend(missing_sphere(id));
+                if !entity::has_component(id, components::core::rendering::color()) {
+                    missing.push("core::rendering::color");
+                }
+                missing
+            }
 
             #[allow(clippy::approx_constant)]
             #[doc = "Makes a *Sphere*.\n\nA primitive sphere.\n\n*Components*:\n\n- `core::primitives::sphere: () = ()`\n- `core::primitives::sphere_radius: f32 = 0.5f32`\n- `core::primitives::sphere_sectors: u32 = 36u32`\n- `core::primitives::sphere_stacks: u32 = 18u32`\n- **`transformable`**:\n  - `core::transform::rotation: Quat = Quat::from_xyzw(0f32, 0f32, 0f32, 1f32)`\n  - `core::transform::scale: Vec3 = Vec3::new(1f32, 1f32, 1f32)`\n  - `core::transform::translation: Vec3 = Vec3::new(0f32, 0f32, 0f32)`\n"]
@@ -750,6 +841,24 @@ fn can_generate_concepts() {
                     &components::core::primitives::sphere_stacks()
                 ])
             }
+            #[doc = "The components of a *Sphere* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *Sphere*."]
+            pub fn missing_sphere(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                missing.extend(missing_transformable(id));
+                if !entity::has_component(id, components::core::primitives::sphere()) {
+                    missing.push("core::primitives::sphere");
+                }
+                if !entity::has_component(id, components::core::primitives::sphere_radius()) {
+                    missing.push("core::primitives::sphere_radius");
+                }
+                if !entity::has_component(id, components::core::primitives::sphere_sectors()) {
+                    missing.push("core::primitives::sphere_sectors");
+                }
+                if !entity::has_component(id, components::core::primitives::sphere_stacks()) {
+                    missing.push("core::primitives::sphere_stacks");
+                }
+                missing
+            }
 
             #[allow(clippy::approx_constant)]
             #[doc = "Makes a *Transformable*.\n\nCan be translated, rotated and scaled.\n\n*Components*:\n\n- `core::transform::rotation: Quat = Quat::from_xyzw(0f32, 0f32, 0f32, 1f32)`\n- `core::transform::scale: Vec3 = Vec3::new(1f32, 1f32, 1f32)`\n- `core::transform::translation: Vec3 = Vec3::new(0f32, 0f32, 0f32)`\n"]
@@ -768,6 +877,20 @@ fn can_generate_concepts() {
                     &components::core::transform::translation()
                 ])
             }
+            #[doc = "The components of a *Transformable* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *Transformable*."]
+            pub fn missing_transformable(id: EntityId) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                if !entity::has_component(id, components::core::transform::rotation()) {
+                    missing.push("core::transform::rotation");
+                }
+                if !entity::has_component(id, components::core::transform::scale()) {
+                    missing.push("core::transform::scale");
+                }
+                if !entity::has_component(id, components::core::transform::translation()) {
+                    missing.push("core::transform::translation");
+                }
+                missing
+            }
         }
     };
 
@@ -845,6 +968,12 @@ fn can_generate_concepts_with_documented_namespace_from_manifest() {
                 pub fn is_concept2(id: EntityId) -> bool {
                     super::ns::is_transformable(id) && entity::has_components(id, &[])
                 }
+                #[doc = "The components of a *Concept 2* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *Concept 2*."]
+                pub fn missing_concept2(id: EntityId) -> Vec<&'static str> {
+                    let mut missing = Vec::new();
+                    missing.extend(super::ns::missing_transformable(id));
+                    missing
+                }
 
                 #[allow(clippy::approx_constant)]
                 #[doc = "Makes a *Transformable*.\n\nCan be translated, rotated and scaled.\n\n*Components*:\n\n- `core::transform::rotation: Quat = Quat::from_xyzw(0f32, 0f32, 0f32, 1f32)`\n- `core::transform::scale: Vec3 = Vec3::new(1f32, 1f32, 1f32)`\n- `core::transform::translation: Vec3 = Vec3::new(0f32, 0f32, 0f32)`\n"]
@@ -863,6 +992,20 @@ fn can_generate_concepts_with_documented_namespace_from_manifest() {
                         &components::core::transform::translation()
                     ])
                 }
+                #[doc = "The components of a *Transformable* that the entity lacks, including those of the concepts it extends. Empty if the entity is a complete *Transformable*."]
+                pub fn missing_transformable(id: EntityId) -> Vec<&'static str> {
+                    let mut missing = Vec::new();
+                    if !entity::has_component(id, components::core::transform::rotation()) {
+                        missing.push("core::transform::rotation");
+                    }
+                    if !entity::has_component(id, components::core::transform::scale()) {
+                        missing.push("core::transform::scale");
+                    }
+                    if !entity::has_component(id, components::core::transform::translation()) {
+                        missing.push("core::transform::translation");
+                    }
+                    missing
+                }
             }
         }
     };
@@ -999,3 +1142,174 @@ fn can_generate_nested_doc_comment_for_concepts() {
     "#}
     );
 }
+
+#[test]
+fn can_generate_struct_components() {
+    let manifest = indoc::indoc! {r#"
+        [project]
+        id = "my_project"
+        name = "My Project"
+
+        [types.Stats]
+        name = "Stats"
+        description = "The stats of a character"
+        fields = { health = "F32", title = "String" }
+
+        [components]
+        stats = { name = "Stats", description = "", type = "Stats" }
+        "#};
+
+    let expected_output = quote::quote! {
+        #[doc = r" Auto-generated component definitions. These come from `ambient.toml` in the root of the project."]
+        pub mod components {
+            use ambient_api2::{once_cell::sync::Lazy, ecs::{Component, __internal_get_component}};
+            #[doc = "**Stats**"]
+            pub mod stats {
+                use ambient_api2::{once_cell::sync::Lazy, ecs::{Component, __internal_get_component}};
+                static HEALTH: Lazy< Component<f32> > = Lazy::new(|| __internal_get_component("my_project::stats::health"));
+                #[doc = "**Stats: health**: The `health` of the *Stats*."]
+                pub fn health() -> Component<f32> { *HEALTH }
+                static TITLE: Lazy< Component<String> > = Lazy::new(|| __internal_get_component("my_project::stats::title"));
+                #[doc = "**Stats: title**: The `title` of the *Stats*."]
+                pub fn title() -> Component<String> { *TITLE }
+                #[doc = "Gets the *Stats* of the entity, if it has all of its fields."]
+                pub fn get(id: ambient_api2::global::EntityId) -> Option<super::super::types::Stats> {
+                    Some(super::super::types::Stats {
+                        health: ambient_api2::entity::get_component(id, health())?,
+                        title: ambient_api2::entity::get_component(id, title())?
+                    })
+                }
+                #[doc = "Sets the *Stats* of the entity, adding the components of the fields it lacks."]
+                pub fn set(id: ambient_api2::global::EntityId, value: super::super::types::Stats) {
+                    ambient_api2::entity::add_component(id, health(), value.health);
+                    ambient_api2::entity::add_component(id, title(), value.title);
+                }
+                #[doc = "The components of a *Stats*, to spawn or to merge into an entity."]
+                pub fn to_entity(value: super::super::types::Stats) -> ambient_api2::ecs::Entity {
+                    ambient_api2::ecs::Entity::new()
+                        .with(health(), value.health)
+                        .with(title(), value.title)
+                }
+            }
+        }
+        #[doc = r" Auto-generated concept definitions. Concepts are collections of components that describe some form of gameplay concept."]
+        #[doc = r""]
+        #[doc = r" They do not have any runtime representation outside of the components that compose them."]
+        pub mod concepts {
+            use super::components;
+            use ambient_api2::prelude::*;
+        }
+        #[doc = r" Auto-generated type definitions. These are the struct types of components, which are stored as one component per field."]
+        pub mod types {
+            #[doc = "**Stats**: The stats of a character"]
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct Stats {
+                pub health: f32,
+                pub title: String
+            }
+        }
+    };
+
+    let result = implementation((None, manifest.to_string()), api_name(), false, true).unwrap();
+
+    assert_eq!(result.to_string(), expected_output.to_string());
+}
+
+#[test]
+fn can_generate_builders_for_optional_components() {
+    let manifest = indoc::indoc! {r#"
+        [project]
+        id = "my_project"
+        name = "My Project"
+
+        [components]
+        f32 = { type = "F32", name = "F32", description = "" }
+        f64 = { type = "F64", name = "F64", description = "" }
+        i32 = { type = "I32", name = "I32", description = "" }
+
+        [concepts.concept1]
+        name = "C1"
+        description = ""
+        components = { f32 = 4.0 }
+        optional = { f64 = 8.0, i32 = 1 }
+
+        [concepts.concept2]
+        name = "C2"
+        description = ""
+        extends = ["concept1"]
+        components = { i32 = 16 }
+        "#};
+
+    let expected_builder = quote::quote! {
+        #[doc = "Builds a *C2* with some of its optional components. See [build_concept2]."]
+        pub struct Concept2Builder(Entity);
+        impl Concept2Builder {
+            #[doc = "Sets the optional `f64`. Its suggested value is `8f64`."]
+            pub fn f64(mut self, value: f64) -> Self {
+                self.0.set(components::f64(), value);
+                self
+            }
+            #[doc = r" Sets the optional components which haven't been set to their suggested values."]
+            #[allow(clippy::approx_constant)]
+            pub fn suggested(mut self) -> Self {
+                if self.0.get(components::f64()).is_none() {
+                    self.0.set(components::f64(), 8f64);
+                }
+                self
+            }
+            pub fn build(self) -> Entity {
+                self.0
+            }
+        }
+        #[doc = "Starts building a *C2*, from its required components."]
+        pub fn build_concept2() -> Concept2Builder {
+            Concept2Builder(make_concept2())
+        }
+    };
+
+    let result = implementation((None, manifest.to_string()), api_name(), false, false).unwrap();
+
+    // The required `i32` of `concept2` isn't optional anymore
+    assert!(result.to_string().contains(&expected_builder.to_string()));
+    assert!(result.to_string().contains("pub struct Concept1Builder"));
+}
+
+#[test]
+fn will_error_on_ambiguous_inheritance() {
+    let manifest = indoc::indoc! {r#"
+        [project]
+        id = "my_project"
+        name = "My Project"
+
+        [components]
+        f32 = { type = "F32", name = "F32", description = "" }
+
+        [concepts.concept1]
+        name = "C1"
+        description = ""
+        components = { f32 = 4.0 }
+
+        [concepts.concept2]
+        name = "C2"
+        description = ""
+        components = { f32 = 8.0 }
+
+        [concepts.concept3]
+        name = "C3"
+        description = ""
+        extends = ["concept1", "concept2"]
+        components = {}
+
+        [concepts.concept4]
+        name = "C4"
+        description = ""
+        extends = ["concept1", "concept2"]
+        components = { f32 = 16.0 }
+        "#};
+
+    let err = implementation((None, manifest.to_string()), api_name(), false, false).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "*C3* inherits `f32` from both `concept1` and `concept2` with different values, so it must set it itself"
+    );
+}
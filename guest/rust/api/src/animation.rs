@@ -0,0 +1,359 @@
+use std::fmt::Write;
+
+use crate::{
+    components::core::animation::{
+        animation_graph, animation_graph_parameter_names, animation_graph_parameter_values,
+        animation_graph_state,
+    },
+    entity,
+    global::{EntityId, Vec2},
+};
+
+/// A state machine of animations: each of its states plays a clip or blends several depending
+/// on parameters, and its transitions crossfade between the states when their conditions are met.
+///
+/// Set it on an entity with [set_graph], and drive it with [set_parameter].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnimationGraph {
+    /// The states of the graph.
+    pub states: Vec<AnimationState>,
+    /// The transitions between the states. The first one whose conditions are met is taken.
+    pub transitions: Vec<AnimationTransition>,
+    /// The state the graph starts in. Defaults to the first state.
+    pub initial_state: Option<String>,
+}
+impl AnimationGraph {
+    /// Creates a graph with these `states` and no transitions.
+    pub fn new(states: impl IntoIterator<Item = AnimationState>) -> Self {
+        Self {
+            states: states.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+    /// Adds a transition to the graph.
+    pub fn with_transition(mut self, transition: AnimationTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+    /// Sets the state the graph starts in.
+    pub fn with_initial_state(mut self, name: impl Into<String>) -> Self {
+        self.initial_state = Some(name.into());
+        self
+    }
+
+    /// The graph as the JSON expected by the `animation_graph` component.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"states\":[");
+        for (i, state) in self.states.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            state.write_json(&mut json);
+        }
+        json.push_str("],\"transitions\":[");
+        for (i, transition) in self.transitions.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            transition.write_json(&mut json);
+        }
+        json.push(']');
+        if let Some(initial_state) = &self.initial_state {
+            write!(json, ",\"initial_state\":{}", quote(initial_state)).unwrap();
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// A state of an [AnimationGraph].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationState {
+    /// The name transitions refer to the state by.
+    pub name: String,
+    /// What the state plays.
+    pub motion: AnimationMotion,
+    /// How fast the state plays. Defaults to 1.
+    pub speed: f32,
+    /// Whether the state loops. Defaults to true.
+    pub looping: bool,
+    /// Whether the root motion of the clips is taken out of them, if the entity has the
+    /// `root_motion` component. Defaults to true.
+    pub root_motion: bool,
+}
+impl AnimationState {
+    /// Creates a looping state named `name` which plays `motion`.
+    pub fn new(name: impl Into<String>, motion: AnimationMotion) -> Self {
+        Self {
+            name: name.into(),
+            motion,
+            speed: 1.,
+            looping: true,
+            root_motion: true,
+        }
+    }
+    /// Sets how fast the state plays.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+    /// Sets whether the state loops.
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+    /// Sets whether the root motion of the clips is taken out of them.
+    pub fn with_root_motion(mut self, root_motion: bool) -> Self {
+        self.root_motion = root_motion;
+        self
+    }
+
+    fn write_json(&self, json: &mut String) {
+        write!(
+            json,
+            "{{\"name\":{},\"speed\":{},\"looping\":{},\"root_motion\":{},\"motion\":",
+            quote(&self.name),
+            number(self.speed),
+            self.looping,
+            self.root_motion
+        )
+        .unwrap();
+        match &self.motion {
+            AnimationMotion::Clip(clip) => {
+                write!(json, "{{\"type\":\"clip\",\"clip\":{}}}", quote(clip)).unwrap()
+            }
+            AnimationMotion::Blend1d { parameter, points } => {
+                write!(
+                    json,
+                    "{{\"type\":\"blend1d\",\"parameter\":{},\"points\":[",
+                    quote(parameter)
+                )
+                .unwrap();
+                for (i, (value, clip)) in points.iter().enumerate() {
+                    let separator = if i > 0 { "," } else { "" };
+                    write!(
+                        json,
+                        "{separator}{{\"value\":{},\"clip\":{}}}",
+                        number(*value),
+                        quote(clip)
+                    )
+                    .unwrap();
+                }
+                json.push_str("]}");
+            }
+            AnimationMotion::Blend2d { x, y, points } => {
+                write!(
+                    json,
+                    "{{\"type\":\"blend2d\",\"x\":{},\"y\":{},\"points\":[",
+                    quote(x),
+                    quote(y)
+                )
+                .unwrap();
+                for (i, (position, clip)) in points.iter().enumerate() {
+                    let separator = if i > 0 { "," } else { "" };
+                    write!(
+                        json,
+                        "{separator}{{\"position\":[{},{}],\"clip\":{}}}",
+                        number(position.x),
+                        number(position.y),
+                        quote(clip)
+                    )
+                    .unwrap();
+                }
+                json.push_str("]}");
+            }
+        }
+        json.push('}');
+    }
+}
+
+/// What an [AnimationState] plays. The clips are URLs, like those returned by [asset::url](crate::asset::url).
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationMotion {
+    /// Plays a single clip.
+    Clip(String),
+    /// Blends the two clips whose values surround the value of `parameter`, e.g. idle, walk and run
+    /// clips by speed.
+    Blend1d {
+        /// The parameter the clips are blended by.
+        parameter: String,
+        /// The clips, and the values of the parameter at which they play alone.
+        points: Vec<(f32, String)>,
+    },
+    /// Blends the clips by how close their positions are to the values of the `x` and `y`
+    /// parameters, e.g. strafing clips by the direction of movement.
+    Blend2d {
+        /// The parameter of the first coordinate.
+        x: String,
+        /// The parameter of the second coordinate.
+        y: String,
+        /// The clips, and the positions at which they play alone.
+        points: Vec<(Vec2, String)>,
+    },
+}
+
+/// A transition between two states of an [AnimationGraph].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationTransition {
+    /// The state the transition leaves, or any state if `None`.
+    pub from: Option<String>,
+    /// The state the transition goes to.
+    pub to: String,
+    /// The conditions which must all be met for the transition to happen.
+    pub conditions: Vec<AnimationCondition>,
+    /// How long the states are crossfaded, in seconds.
+    pub duration: f32,
+    /// If set, the transition only happens once the state it leaves has played this many times its
+    /// length, e.g. 1 at the end of its first loop.
+    pub exit_time: Option<f32>,
+}
+impl AnimationTransition {
+    /// Creates a transition from `from` (or any state if `None`) to `to`, without conditions or
+    /// crossfade.
+    pub fn new(from: Option<&str>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.map(|from| from.to_string()),
+            to: to.into(),
+            conditions: vec![],
+            duration: 0.,
+            exit_time: None,
+        }
+    }
+    /// Adds a condition on the value of `parameter`.
+    pub fn when(
+        mut self,
+        parameter: impl Into<String>,
+        comparison: AnimationComparison,
+        value: f32,
+    ) -> Self {
+        self.conditions.push(AnimationCondition {
+            parameter: parameter.into(),
+            comparison,
+            value,
+        });
+        self
+    }
+    /// Sets how long the states are crossfaded, in seconds.
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+    /// Sets how many times its length the state must have played before the transition happens.
+    pub fn with_exit_time(mut self, exit_time: f32) -> Self {
+        self.exit_time = Some(exit_time);
+        self
+    }
+
+    fn write_json(&self, json: &mut String) {
+        json.push('{');
+        if let Some(from) = &self.from {
+            write!(json, "\"from\":{},", quote(from)).unwrap();
+        }
+        write!(
+            json,
+            "\"to\":{},\"duration\":{},",
+            quote(&self.to),
+            number(self.duration)
+        )
+        .unwrap();
+        if let Some(exit_time) = self.exit_time {
+            write!(json, "\"exit_time\":{},", number(exit_time)).unwrap();
+        }
+        json.push_str("\"conditions\":[");
+        for (i, condition) in self.conditions.iter().enumerate() {
+            let separator = if i > 0 { "," } else { "" };
+            let comparison = match condition.comparison {
+                AnimationComparison::Greater => "greater",
+                AnimationComparison::Less => "less",
+                AnimationComparison::Equal => "equal",
+                AnimationComparison::NotEqual => "not_equal",
+            };
+            write!(
+                json,
+                "{separator}{{\"parameter\":{},\"comparison\":\"{comparison}\",\"value\":{}}}",
+                quote(&condition.parameter),
+                number(condition.value)
+            )
+            .unwrap();
+        }
+        json.push_str("]}");
+    }
+}
+
+/// A condition of an [AnimationTransition] on the value of a parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCondition {
+    /// The name of the parameter. Parameters which haven't been set are 0.
+    pub parameter: String,
+    /// How the value of the parameter is compared to `value`.
+    pub comparison: AnimationComparison,
+    /// The value the parameter is compared to.
+    pub value: f32,
+}
+
+/// How an [AnimationCondition] compares its parameter to its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationComparison {
+    /// The parameter is greater than the value.
+    Greater,
+    /// The parameter is less than the value.
+    Less,
+    /// The parameter is equal to the value.
+    Equal,
+    /// The parameter is not equal to the value.
+    NotEqual,
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// JSON has no infinities or NaN
+fn number(value: f32) -> f32 {
+    if value.is_finite() {
+        value
+    } else {
+        0.
+    }
+}
+
+/// Plays `graph` on `entity`, replacing its animation controller.
+pub fn set_graph(entity: EntityId, graph: &AnimationGraph) {
+    entity::add_component(entity, animation_graph(), graph.to_json());
+}
+
+/// Sets the parameter `name` of the animation graph of `entity` to `value`. Booleans are 0 or 1.
+pub fn set_parameter(entity: EntityId, name: &str, value: f32) {
+    let mut names =
+        entity::get_component(entity, animation_graph_parameter_names()).unwrap_or_default();
+    let mut values =
+        entity::get_component(entity, animation_graph_parameter_values()).unwrap_or_default();
+    values.resize(names.len(), 0.);
+    match names.iter().position(|parameter| parameter == name) {
+        Some(index) => values[index] = value,
+        None => {
+            names.push(name.to_string());
+            values.push(value);
+            entity::add_component(entity, animation_graph_parameter_names(), names);
+        }
+    }
+    entity::add_component(entity, animation_graph_parameter_values(), values);
+}
+
+/// The state the animation graph of `entity` is in, or is transitioning to.
+///
+/// The graph is evaluated where it's rendered, so this is only available on the client.
+pub fn get_state(entity: EntityId) -> Option<String> {
+    entity::get_component(entity, animation_graph_state())
+}
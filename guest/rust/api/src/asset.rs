@@ -1,8 +1,55 @@
-#[cfg(feature = "server")]
+#[cfg(any(feature = "client", feature = "server"))]
 use crate::internal::wit;
+#[cfg(feature = "client")]
+use crate::prelude::block_until;
+
+#[cfg(feature = "client")]
+pub use wit::client_asset::{PrefetchPriority, PrefetchStatus};
 
 /// Resolves a asset path for an Ambient asset in this project to an absolute URL.
 #[cfg(feature = "server")]
 pub fn url(path: impl AsRef<str>) -> Option<String> {
     wit::server_asset::url(path.as_ref())
 }
+
+/// Starts loading the asset at `url` ahead of its use, e.g. the level behind a teleport or the
+/// props of a cutscene, so that using it later doesn't hitch. The asset is downloaded, decoded and
+/// uploaded to the GPU; prefabs are loaded with their models, and a URL to a model file like
+/// `Cube.glb` is the prefab built from it.
+///
+/// High priority prefetches start immediately; the others start in order of priority, a few at a
+/// time. The asset stays loaded until the prefetch is cancelled, or for a minute after it has loaded.
+#[cfg(feature = "client")]
+pub fn prefetch(url: impl AsRef<str>, priority: PrefetchPriority) -> Prefetch {
+    Prefetch(wit::client_asset::prefetch(url.as_ref(), priority))
+}
+
+/// A prefetch started with [prefetch].
+#[cfg(feature = "client")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Prefetch(u64);
+#[cfg(feature = "client")]
+impl Prefetch {
+    /// The status of the prefetch, or `None` if it was cancelled or has expired.
+    pub fn status(self) -> Option<PrefetchStatus> {
+        wit::client_asset::status(self.0)
+    }
+
+    /// Stops the prefetch if it's still loading, and releases its asset. Entities already using
+    /// the asset keep it loaded.
+    pub fn cancel(self) {
+        wit::client_asset::cancel_prefetch(self.0)
+    }
+
+    /// Waits until the prefetch has finished, and returns whether the asset was loaded.
+    pub async fn loaded(self) -> bool {
+        block_until(move || {
+            !matches!(
+                self.status(),
+                Some(PrefetchStatus::Queued | PrefetchStatus::Loading)
+            )
+        })
+        .await;
+        self.status() == Some(PrefetchStatus::Loaded)
+    }
+}
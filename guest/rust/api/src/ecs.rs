@@ -1,7 +1,7 @@
 pub use crate::internal::component::{
     query::{
         change_query, despawn_query, query, spawn_query, ChangeQuery, EventQuery, GeneralQuery,
-        GeneralQueryBuilder, QueryEvent,
+        GeneralQueryBuilder, QueryDelta, QueryDeltaHandle, QueryEvent,
     },
     Component, ComponentsTuple, Entity, SupportedValueGet, SupportedValueSet, UntypedComponent,
     __internal_get_component,
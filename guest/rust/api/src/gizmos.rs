@@ -0,0 +1,52 @@
+use crate::{
+    global::Vec3,
+    internal::{conversion::IntoBindgen, wit},
+};
+
+/// Draws a line from `start` to `end` in `color` for `duration` seconds, or for a single frame if
+/// it's 0, e.g. to visualize a raycast.
+///
+/// The draws accumulate, and they stay while the game is paused. Each of them belongs to a
+/// `category`, which can be hidden from the debugger's gizmo categories.
+pub fn line(start: Vec3, end: Vec3, color: Vec3, duration: f32, category: &str) {
+    wit::client_gizmos::line(
+        start.into_bindgen(),
+        end.into_bindgen(),
+        color.into_bindgen(),
+        duration,
+        category,
+    )
+}
+
+/// Draws the outline of a box around `center` with `half_extents`, as in [line].
+pub fn cuboid(center: Vec3, half_extents: Vec3, color: Vec3, duration: f32, category: &str) {
+    wit::client_gizmos::cuboid(
+        center.into_bindgen(),
+        half_extents.into_bindgen(),
+        color.into_bindgen(),
+        duration,
+        category,
+    )
+}
+
+/// Draws the outline of a sphere around `center` with `radius`, as in [line].
+pub fn sphere(center: Vec3, radius: f32, color: Vec3, duration: f32, category: &str) {
+    wit::client_gizmos::sphere(
+        center.into_bindgen(),
+        radius,
+        color.into_bindgen(),
+        duration,
+        category,
+    )
+}
+
+/// Draws `text` at `position`, facing the camera at a constant size on screen, as in [line].
+pub fn text(position: Vec3, text: &str, color: Vec3, duration: f32, category: &str) {
+    wit::client_gizmos::text(
+        position.into_bindgen(),
+        text,
+        color.into_bindgen(),
+        duration,
+        category,
+    )
+}
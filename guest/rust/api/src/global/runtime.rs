@@ -1,4 +1,4 @@
-use std::{cell::RefCell, future::Future, rc::Rc, task::Poll};
+use std::{cell::RefCell, future::Future, rc::Rc, task::Poll, time::Duration};
 
 use crate::{
     components, entity,
@@ -104,7 +104,73 @@ pub fn once_async<R: Future<Output = EventResult> + 'static>(
 /// });
 /// ```
 pub fn run_async(future: impl Future<Output = EventResult> + 'static) {
-    EXECUTOR.spawn(Box::pin(future));
+    run_async_with_priority(TaskPriority::Normal, future);
+}
+
+/// How soon a task started with [run_async_with_priority] is run, relative to the other tasks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskPriority {
+    /// Run before the other tasks, even if the [frame budget](set_frame_budget) has been spent.
+    High,
+    /// The priority of [run_async] and of the `async` event handlers.
+    #[default]
+    Normal,
+    /// Run after the other tasks.
+    Low,
+}
+
+/// Like [run_async], but the `future` is polled before the tasks of lower priority
+/// and after those of higher priority.
+pub fn run_async_with_priority(
+    priority: TaskPriority,
+    future: impl Future<Output = EventResult> + 'static,
+) {
+    EXECUTOR.spawn(priority, Box::pin(future));
+}
+
+/// Limits how long the tasks of this module run for each frame. Once they've run for `budget`
+/// in a frame, the tasks that haven't been polled yet wait for the next frame, except for those
+/// of [TaskPriority::High]. `None`, the default, lets them all run every frame.
+///
+/// The budget is only checked between polls, so long-running tasks should [yield_now] regularly.
+///
+/// # Examples
+/// ```
+/// set_frame_budget(Some(Duration::from_millis(2)));
+/// run_async_with_priority(TaskPriority::Low, async {
+///     for chunk in 0..1000 {
+///         generate_chunk(chunk);
+///         if frame_budget_exceeded() {
+///             yield_now().await;
+///         }
+///     }
+///     EventOk
+/// });
+/// ```
+pub fn set_frame_budget(budget: Option<Duration>) {
+    EXECUTOR.set_frame_budget(budget);
+}
+
+/// Whether the tasks of this module have run for longer than the [frame budget](set_frame_budget)
+/// in this frame. Always false if there's no budget.
+pub fn frame_budget_exceeded() -> bool {
+    EXECUTOR.frame_budget_exceeded()
+}
+
+/// Gives the other tasks a chance to run, continuing on the next event this module receives.
+///
+/// This must be used with `.await` in either an `async fn` or an `async` block.
+pub async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(move |_cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            Poll::Pending
+        }
+    })
+    .await
 }
 
 /// Stops execution of this function until the provided `condition` is true.
@@ -126,8 +192,15 @@ pub async fn block_until(condition: impl Fn() -> bool) {
 ///
 /// This must be used with `.await` in either an `async fn` or an `async` block.
 pub async fn sleep(seconds: f32) {
-    let target_time = time() + seconds;
-    block_until(|| time() > target_time).await
+    sleep_until(time() + seconds).await
+}
+
+/// Stops execution of this function until [time] reaches `game_time`.
+/// Returns immediately if it's already past.
+///
+/// This must be used with `.await` in either an `async fn` or an `async` block.
+pub async fn sleep_until(game_time: f32) {
+    block_until(|| time() > game_time).await
 }
 
 /// Stops execution of this function until `event` occurs with the specified `condition`.
@@ -0,0 +1,38 @@
+use crate::{ecs::Entity, internal::wit};
+
+/// The id of the world instance this module is running in. The instance every player starts in is `main`.
+pub fn current() -> String {
+    wit::server_instance::current()
+}
+
+/// The ids of the world instances, as of the end of the last server tick.
+pub fn list() -> Vec<String> {
+    wit::server_instance::list()
+}
+
+/// Creates the `id` instance from a copy of the `source` instance (or the main instance, if `None`), without its players.
+///
+/// The instance is stepped `tick_rate` times per second, or as often as the server if `None`; it can't be stepped more often
+/// than the server. It stays around until it's [destroy]ed, even when it has no players.
+///
+/// Instances are created at the end of the current server tick.
+pub fn create(id: impl AsRef<str>, source: Option<&str>, tick_rate: Option<f32>) {
+    wit::server_instance::create(id.as_ref(), source, tick_rate)
+}
+
+/// Moves the players of the `id` instance to the main instance, and removes it at the end of the current server tick.
+pub fn destroy(id: impl AsRef<str>) {
+    wit::server_instance::destroy(id.as_ref())
+}
+
+/// Moves the player with `user_id` to the `instance` instance at the end of the current server tick.
+pub fn transfer_player(user_id: impl AsRef<str>, instance: impl AsRef<str>) {
+    wit::server_instance::transfer_player(user_id.as_ref(), instance.as_ref())
+}
+
+/// Sends the `name` event with `data` to the modules of the `instance` instance, at the end of the current server tick.
+///
+/// The event data they receive also has a `source_instance` component with the id of this module's instance.
+pub fn send(instance: impl AsRef<str>, name: impl AsRef<str>, data: Entity) {
+    data.call_with(|data| wit::server_instance::send(instance.as_ref(), name.as_ref(), data))
+}
@@ -148,6 +148,32 @@ pub mod component{
       }
     }
   }
+  /// Which part of the changes to the results of a subscribed query to take.
+  #[repr(u8)]
+  #[derive(Clone, Copy, PartialEq, Eq)]
+  pub enum QueryDeltaKind {
+    /// The entities that started matching the query.
+    Added,
+    /// The entities whose `changed` components changed, and which still match the query.
+    Changed,
+    /// The entities that stopped matching the query, or were despawned. Their values are empty.
+    Removed,
+  }
+  impl core::fmt::Debug for QueryDeltaKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+      match self {
+        QueryDeltaKind::Added => {
+          f.debug_tuple("QueryDeltaKind::Added").finish()
+        }
+        QueryDeltaKind::Changed => {
+          f.debug_tuple("QueryDeltaKind::Changed").finish()
+        }
+        QueryDeltaKind::Removed => {
+          f.debug_tuple("QueryDeltaKind::Removed").finish()
+        }
+      }
+    }
+  }
   #[derive(Clone)]
   pub struct QueryBuild<'a,> {
     pub components: &'a [u32],
@@ -4654,240 +4680,801 @@ pub mod component{
                           result22
                         }
                       }
-                      
-                    }
-                    
-                    
-                    #[allow(clippy::all)]
-                    pub mod entity{
-                      pub type EntityId = super::types::EntityId;
-                      pub type Vec3 = super::types::Vec3;
-                      pub type EntityData<'a,> = super::component::EntityParam<'a,>;
-                      /// An action in an animation.
-                      #[derive(Clone)]
-                      pub struct AnimationAction<'a,> {
-                        /// The animation clip URL to play.
-                        pub clip_url: &'a str,
-                        /// Whether or not this action should loop
-                        pub looping: bool,
-                        /// How strongly this action applies to the final blend [0-1]
-                        pub weight: f32,
-                      }
-                      impl<'a,> core::fmt::Debug for AnimationAction<'a,> {
-                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                          f.debug_struct("AnimationAction").field("clip-url", &self.clip_url).field("looping", &self.looping).field("weight", &self.weight).finish()
+                      #[allow(clippy::all)]
+                      /// Subscribes to the changes in the results of the query `q`, which is a `frame` query. Each frame they change, the module receives a `core/query_delta/{q}` event before `core/frame`, during which the changes can be taken with `query-take-delta`.
+                      pub fn query_subscribe(q: u64,){
+                        
+                        #[allow(unused_imports)]
+                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                        unsafe {
+                          
+                          #[link(wasm_import_module = "component")]
+                          extern "C" {
+                            #[cfg_attr(target_arch = "wasm32", link_name = "query-subscribe")]
+                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "component_query-subscribe")]
+                            fn wit_import(
+                            _: i64, );
+                          }
+                          wit_import(wit_bindgen::rt::as_i64(q));
                         }
                       }
-                      /// Controls the animations for an entity.
-                      #[derive(Clone)]
-                      pub struct AnimationController<'a,> {
-                        /// All of the actions that contribute to this animation.
-                        /// Will be blended together.
-                        pub actions: &'a [AnimationAction<'a,>],
-                        /// Whether or not the first action's pose should be used as a base pose.
-                        pub apply_base_pose: bool,
-                      }
-                      impl<'a,> core::fmt::Debug for AnimationController<'a,> {
-                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                          f.debug_struct("AnimationController").field("actions", &self.actions).field("apply-base-pose", &self.apply_base_pose).finish()
+                      #[allow(clippy::all)]
+                      pub fn query_unsubscribe(q: u64,){
+                        
+                        #[allow(unused_imports)]
+                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                        unsafe {
+                          
+                          #[link(wasm_import_module = "component")]
+                          extern "C" {
+                            #[cfg_attr(target_arch = "wasm32", link_name = "query-unsubscribe")]
+                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "component_query-unsubscribe")]
+                            fn wit_import(
+                            _: i64, );
+                          }
+                          wit_import(wit_bindgen::rt::as_i64(q));
                         }
                       }
                       #[allow(clippy::all)]
-                      pub fn spawn(data: EntityData<'_,>,) -> EntityId{
+                      /// Takes the changes of `kind` since the last `core/query_delta/{q}` event.
+                      pub fn query_take_delta(q: u64,kind: QueryDeltaKind,) -> wit_bindgen::rt::vec::Vec::<(EntityId,wit_bindgen::rt::vec::Vec::<ValueResult>,)>{
                         
                         #[allow(unused_imports)]
                         use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
                         unsafe {
-                          let mut cleanup_list = Vec::new();
                           
-                          #[repr(align(8))]
-                          struct RetArea([u8; 16]);
+                          #[repr(align(4))]
+                          struct RetArea([u8; 8]);
                           let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
-                          let vec49 = data;
-                          let len49 = vec49.len() as i32;
-                          let layout49 = alloc::Layout::from_size_align_unchecked(vec49.len() * 96, 8);
-                          let result49 = if layout49.size() != 0
-                          {
-                            let ptr = alloc::alloc(layout49);
-                            if ptr.is_null()
-                            {
-                              alloc::handle_alloc_error(layout49);
-                            }
-                            ptr
-                          }else {
-                            core::ptr::null_mut()
-                          };
-                          for (i, e) in vec49.into_iter().enumerate() {
-                            let base = result49 as i32 + (i as i32) * 96;
-                            {
-                              let (t0_0, t0_1, ) = e;
-                              *((base + 0) as *mut i32) = wit_bindgen::rt::as_i32(t0_0);
-                              match t0_1 {
-                                super::component::ValueParam::TypeEmpty(e) => {
-                                  *((base + 8) as *mut u8) = (0i32) as u8;
-                                  let () = e;
-                                  
-                                },
-                                super::component::ValueParam::TypeBool(e) => {
-                                  *((base + 8) as *mut u8) = (1i32) as u8;
-                                  *((base + 16) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
-                                  
-                                },
-                                super::component::ValueParam::TypeEntityId(e) => {
-                                  *((base + 8) as *mut u8) = (2i32) as u8;
-                                  let super::types::EntityId{ id0:id02, id1:id12, } = e;
-                                  *((base + 16) as *mut i64) = wit_bindgen::rt::as_i64(id02);
-                                  *((base + 24) as *mut i64) = wit_bindgen::rt::as_i64(id12);
-                                  
-                                },
-                                super::component::ValueParam::TypeF32(e) => {
-                                  *((base + 8) as *mut u8) = (3i32) as u8;
-                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(e);
-                                  
-                                },
-                                super::component::ValueParam::TypeF64(e) => {
-                                  *((base + 8) as *mut u8) = (4i32) as u8;
-                                  *((base + 16) as *mut f64) = wit_bindgen::rt::as_f64(e);
-                                  
-                                },
-                                super::component::ValueParam::TypeMat4(e) => {
-                                  *((base + 8) as *mut u8) = (5i32) as u8;
-                                  let super::types::Mat4{ x:x3, y:y3, z:z3, w:w3, } = e;
-                                  let super::types::Vec4{ x:x4, y:y4, z:z4, w:w4, } = x3;
-                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x4);
-                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y4);
-                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z4);
-                                  *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w4);
-                                  let super::types::Vec4{ x:x5, y:y5, z:z5, w:w5, } = y3;
-                                  *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(x5);
-                                  *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(y5);
-                                  *((base + 40) as *mut f32) = wit_bindgen::rt::as_f32(z5);
-                                  *((base + 44) as *mut f32) = wit_bindgen::rt::as_f32(w5);
-                                  let super::types::Vec4{ x:x6, y:y6, z:z6, w:w6, } = z3;
-                                  *((base + 48) as *mut f32) = wit_bindgen::rt::as_f32(x6);
-                                  *((base + 52) as *mut f32) = wit_bindgen::rt::as_f32(y6);
-                                  *((base + 56) as *mut f32) = wit_bindgen::rt::as_f32(z6);
-                                  *((base + 60) as *mut f32) = wit_bindgen::rt::as_f32(w6);
-                                  let super::types::Vec4{ x:x7, y:y7, z:z7, w:w7, } = w3;
-                                  *((base + 64) as *mut f32) = wit_bindgen::rt::as_f32(x7);
-                                  *((base + 68) as *mut f32) = wit_bindgen::rt::as_f32(y7);
-                                  *((base + 72) as *mut f32) = wit_bindgen::rt::as_f32(z7);
-                                  *((base + 76) as *mut f32) = wit_bindgen::rt::as_f32(w7);
-                                  
-                                },
-                                super::component::ValueParam::TypeI32(e) => {
-                                  *((base + 8) as *mut u8) = (6i32) as u8;
-                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(e);
-                                  
-                                },
-                                super::component::ValueParam::TypeQuat(e) => {
-                                  *((base + 8) as *mut u8) = (7i32) as u8;
-                                  let super::types::Quat{ x:x8, y:y8, z:z8, w:w8, } = e;
-                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x8);
-                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y8);
-                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z8);
-                                  *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w8);
-                                  
-                                },
-                                super::component::ValueParam::TypeString(e) => {
-                                  *((base + 8) as *mut u8) = (8i32) as u8;
-                                  let vec9 = e;
-                                  let ptr9 = vec9.as_ptr() as i32;
-                                  let len9 = vec9.len() as i32;
-                                  *((base + 20) as *mut i32) = len9;
-                                  *((base + 16) as *mut i32) = ptr9;
-                                  
-                                },
-                                super::component::ValueParam::TypeU32(e) => {
-                                  *((base + 8) as *mut u8) = (9i32) as u8;
-                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(e);
-                                  
-                                },
-                                super::component::ValueParam::TypeU64(e) => {
-                                  *((base + 8) as *mut u8) = (10i32) as u8;
-                                  *((base + 16) as *mut i64) = wit_bindgen::rt::as_i64(e);
-                                  
-                                },
-                                super::component::ValueParam::TypeVec2(e) => {
-                                  *((base + 8) as *mut u8) = (11i32) as u8;
-                                  let super::types::Vec2{ x:x10, y:y10, } = e;
-                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x10);
-                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y10);
-                                  
-                                },
-                                super::component::ValueParam::TypeVec3(e) => {
-                                  *((base + 8) as *mut u8) = (12i32) as u8;
-                                  let super::types::Vec3{ x:x11, y:y11, z:z11, } = e;
-                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x11);
-                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y11);
-                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z11);
-                                  
-                                },
-                                super::component::ValueParam::TypeVec4(e) => {
-                                  *((base + 8) as *mut u8) = (13i32) as u8;
-                                  let super::types::Vec4{ x:x12, y:y12, z:z12, w:w12, } = e;
-                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x12);
-                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y12);
-                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z12);
-                                  *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w12);
-                                  
-                                },
-                                super::component::ValueParam::TypeUvec2(e) => {
-                                  *((base + 8) as *mut u8) = (14i32) as u8;
-                                  let super::types::Uvec2{ x:x13, y:y13, } = e;
-                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x13);
-                                  *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y13);
-                                  
-                                },
-                                super::component::ValueParam::TypeUvec3(e) => {
-                                  *((base + 8) as *mut u8) = (15i32) as u8;
-                                  let super::types::Uvec3{ x:x14, y:y14, z:z14, } = e;
-                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x14);
-                                  *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y14);
-                                  *((base + 24) as *mut i32) = wit_bindgen::rt::as_i32(z14);
-                                  
-                                },
-                                super::component::ValueParam::TypeUvec4(e) => {
-                                  *((base + 8) as *mut u8) = (16i32) as u8;
-                                  let super::types::Uvec4{ x:x15, y:y15, z:z15, w:w15, } = e;
-                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x15);
-                                  *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y15);
-                                  *((base + 24) as *mut i32) = wit_bindgen::rt::as_i32(z15);
-                                  *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(w15);
-                                  
-                                },
-                                super::component::ValueParam::TypeVec(e) => {
-                                  *((base + 8) as *mut u8) = (17i32) as u8;
-                                  match e {
-                                    super::component::VecValueParam::TypeEmpty(e) => {
-                                      *((base + 16) as *mut u8) = (0i32) as u8;
-                                      let vec16 = e;
-                                      let ptr16 = vec16.as_ptr() as i32;
-                                      let len16 = vec16.len() as i32;
-                                      *((base + 24) as *mut i32) = len16;
-                                      *((base + 20) as *mut i32) = ptr16;
-                                      
-                                    },
-                                    super::component::VecValueParam::TypeBool(e) => {
-                                      *((base + 16) as *mut u8) = (1i32) as u8;
-                                      let vec17 = e;
-                                      let len17 = vec17.len() as i32;
-                                      let layout17 = alloc::Layout::from_size_align_unchecked(vec17.len() * 1, 1);
-                                      let result17 = if layout17.size() != 0
-                                      {
-                                        let ptr = alloc::alloc(layout17);
-                                        if ptr.is_null()
-                                        {
-                                          alloc::handle_alloc_error(layout17);
-                                        }
-                                        ptr
-                                      }else {
-                                        core::ptr::null_mut()
-                                      };
-                                      for (i, e) in vec17.into_iter().enumerate() {
-                                        let base = result17 as i32 + (i as i32) * 1;
-                                        {
-                                          *((base + 0) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
+                          let ptr0 = ret_area.as_mut_ptr() as i32;
+                          #[link(wasm_import_module = "component")]
+                          extern "C" {
+                            #[cfg_attr(target_arch = "wasm32", link_name = "query-take-delta")]
+                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "component_query-take-delta")]
+                            fn wit_import(
+                            _: i64, _: i32, _: i32, );
+                          }
+                          wit_import(wit_bindgen::rt::as_i64(q), match kind {
+                            QueryDeltaKind::Added => 0,
+                            QueryDeltaKind::Changed => 1,
+                            QueryDeltaKind::Removed => 2,
+                          }, ptr0);
+                          let base22 = *((ptr0 + 0) as *const i32);
+                          let len22 = *((ptr0 + 4) as *const i32);
+                          let mut result22 = Vec::with_capacity(len22 as usize);
+                          for i in 0..len22 {
+                            let base = base22 + i *24;
+                            result22.push({
+                              let base21 = *((base + 16) as *const i32);
+                              let len21 = *((base + 20) as *const i32);
+                              let mut result21 = Vec::with_capacity(len21 as usize);
+                              for i in 0..len21 {
+                                let base = base21 + i *88;
+                                result21.push({{match i32::from(*((base + 0) as *const u8)) {
+                                  0 => ValueResult::TypeEmpty(()),
+                                  1 => ValueResult::TypeBool({
+                                    #[cfg(not(debug_assertions))]
+                                    { core::mem::transmute::<u8, bool>(i32::from(*((base + 8) as *const u8)) as u8) }
+                                    #[cfg(debug_assertions)]
+                                    {
+                                      match i32::from(*((base + 8) as *const u8)) {
+                                        0 => false,
+                                        1 => true,
+                                        _ => panic!("invalid bool discriminant"),
+                                      }
+                                    }
+                                  }),
+                                  2 => ValueResult::TypeEntityId(super::types::EntityId{id0:*((base + 8) as *const i64) as u64, id1:*((base + 16) as *const i64) as u64, }),
+                                  3 => ValueResult::TypeF32(*((base + 8) as *const f32)),
+                                  4 => ValueResult::TypeF64(*((base + 8) as *const f64)),
+                                  5 => ValueResult::TypeMat4(super::types::Mat4{x:super::types::Vec4{x:*((base + 8) as *const f32), y:*((base + 12) as *const f32), z:*((base + 16) as *const f32), w:*((base + 20) as *const f32), }, y:super::types::Vec4{x:*((base + 24) as *const f32), y:*((base + 28) as *const f32), z:*((base + 32) as *const f32), w:*((base + 36) as *const f32), }, z:super::types::Vec4{x:*((base + 40) as *const f32), y:*((base + 44) as *const f32), z:*((base + 48) as *const f32), w:*((base + 52) as *const f32), }, w:super::types::Vec4{x:*((base + 56) as *const f32), y:*((base + 60) as *const f32), z:*((base + 64) as *const f32), w:*((base + 68) as *const f32), }, }),
+                                  6 => ValueResult::TypeI32(*((base + 8) as *const i32)),
+                                  7 => ValueResult::TypeQuat(super::types::Quat{x:*((base + 8) as *const f32), y:*((base + 12) as *const f32), z:*((base + 16) as *const f32), w:*((base + 20) as *const f32), }),
+                                  8 => ValueResult::TypeString({
+                                    let len1 = *((base + 12) as *const i32) as usize;
+                                    
+                                    {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 8) as *const i32) as *mut _, len1, len1))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 8) as *const i32) as *mut _, len1, len1)).unwrap()}}
+                                  }),
+                                  9 => ValueResult::TypeU32(*((base + 8) as *const i32) as u32),
+                                  10 => ValueResult::TypeU64(*((base + 8) as *const i64) as u64),
+                                  11 => ValueResult::TypeVec2(super::types::Vec2{x:*((base + 8) as *const f32), y:*((base + 12) as *const f32), }),
+                                  12 => ValueResult::TypeVec3(super::types::Vec3{x:*((base + 8) as *const f32), y:*((base + 12) as *const f32), z:*((base + 16) as *const f32), }),
+                                  13 => ValueResult::TypeVec4(super::types::Vec4{x:*((base + 8) as *const f32), y:*((base + 12) as *const f32), z:*((base + 16) as *const f32), w:*((base + 20) as *const f32), }),
+                                  14 => ValueResult::TypeUvec2(super::types::Uvec2{x:*((base + 8) as *const i32) as u32, y:*((base + 12) as *const i32) as u32, }),
+                                  15 => ValueResult::TypeUvec3(super::types::Uvec3{x:*((base + 8) as *const i32) as u32, y:*((base + 12) as *const i32) as u32, z:*((base + 16) as *const i32) as u32, }),
+                                  16 => ValueResult::TypeUvec4(super::types::Uvec4{x:*((base + 8) as *const i32) as u32, y:*((base + 12) as *const i32) as u32, z:*((base + 16) as *const i32) as u32, w:*((base + 20) as *const i32) as u32, }),
+                                  17 => ValueResult::TypeVec({{match i32::from(*((base + 8) as *const u8)) {
+                                    0 => VecValueResult::TypeEmpty({
+                                      let len2 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len2, len2)
+                                    }),
+                                    1 => VecValueResult::TypeBool({
+                                      let base3 = *((base + 12) as *const i32);
+                                      let len3 = *((base + 16) as *const i32);
+                                      let mut result3 = Vec::with_capacity(len3 as usize);
+                                      for i in 0..len3 {
+                                        let base = base3 + i *1;
+                                        result3.push({
+                                          #[cfg(not(debug_assertions))]
+                                          { core::mem::transmute::<u8, bool>(i32::from(*((base + 0) as *const u8)) as u8) }
+                                          #[cfg(debug_assertions)]
+                                          {
+                                            match i32::from(*((base + 0) as *const u8)) {
+                                              0 => false,
+                                              1 => true,
+                                              _ => panic!("invalid bool discriminant"),
+                                            }
+                                          }
+                                        });
+                                      }
+                                      wit_bindgen::rt::dealloc(base3, (len3 as usize) * 1, 1);
+                                      
+                                      result3
+                                    }),
+                                    2 => VecValueResult::TypeEntityId({
+                                      let len4 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len4, len4)
+                                    }),
+                                    3 => VecValueResult::TypeF32({
+                                      let len5 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len5, len5)
+                                    }),
+                                    4 => VecValueResult::TypeF64({
+                                      let len6 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len6, len6)
+                                    }),
+                                    5 => VecValueResult::TypeMat4({
+                                      let len7 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len7, len7)
+                                    }),
+                                    6 => VecValueResult::TypeI32({
+                                      let len8 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len8, len8)
+                                    }),
+                                    7 => VecValueResult::TypeQuat({
+                                      let len9 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len9, len9)
+                                    }),
+                                    8 => VecValueResult::TypeString({
+                                      let base11 = *((base + 12) as *const i32);
+                                      let len11 = *((base + 16) as *const i32);
+                                      let mut result11 = Vec::with_capacity(len11 as usize);
+                                      for i in 0..len11 {
+                                        let base = base11 + i *8;
+                                        result11.push({
+                                          let len10 = *((base + 4) as *const i32) as usize;
+                                          
+                                          {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 0) as *const i32) as *mut _, len10, len10))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 0) as *const i32) as *mut _, len10, len10)).unwrap()}}
+                                        });
+                                      }
+                                      wit_bindgen::rt::dealloc(base11, (len11 as usize) * 8, 4);
+                                      
+                                      result11
+                                    }),
+                                    9 => VecValueResult::TypeU32({
+                                      let len12 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len12, len12)
+                                    }),
+                                    10 => VecValueResult::TypeU64({
+                                      let len13 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len13, len13)
+                                    }),
+                                    11 => VecValueResult::TypeVec2({
+                                      let len14 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len14, len14)
+                                    }),
+                                    12 => VecValueResult::TypeVec3({
+                                      let len15 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len15, len15)
+                                    }),
+                                    13 => VecValueResult::TypeVec4({
+                                      let len16 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len16, len16)
+                                    }),
+                                    14 => VecValueResult::TypeUvec2({
+                                      let len17 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len17, len17)
+                                    }),
+                                    15 => VecValueResult::TypeUvec3({
+                                      let len18 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len18, len18)
+                                    }),
+                                    #[cfg(debug_assertions)]16 => VecValueResult::TypeUvec4({
+                                      let len19 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len19, len19)
+                                    }),
+                                    #[cfg(not(debug_assertions))]_ => VecValueResult::TypeUvec4({
+                                      let len19 = *((base + 16) as *const i32) as usize;
+                                      
+                                      Vec::from_raw_parts(*((base + 12) as *const i32) as *mut _, len19, len19)
+                                    }),
+                                    #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
+                                  }}}),
+                                  #[cfg(debug_assertions)]18 => ValueResult::TypeOption({{match i32::from(*((base + 8) as *const u8)) {
+                                    0 => OptionValueResult::TypeEmpty(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(()),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    1 => OptionValueResult::TypeBool(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some({
+                                        #[cfg(not(debug_assertions))]
+                                        { core::mem::transmute::<u8, bool>(i32::from(*((base + 17) as *const u8)) as u8) }
+                                        #[cfg(debug_assertions)]
+                                        {
+                                          match i32::from(*((base + 17) as *const u8)) {
+                                            0 => false,
+                                            1 => true,
+                                            _ => panic!("invalid bool discriminant"),
+                                          }
+                                        }
+                                      }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    2 => OptionValueResult::TypeEntityId(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::EntityId{id0:*((base + 24) as *const i64) as u64, id1:*((base + 32) as *const i64) as u64, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    3 => OptionValueResult::TypeF32(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 20) as *const f32)),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    4 => OptionValueResult::TypeF64(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 24) as *const f64)),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    5 => OptionValueResult::TypeMat4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Mat4{x:super::types::Vec4{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }, y:super::types::Vec4{x:*((base + 36) as *const f32), y:*((base + 40) as *const f32), z:*((base + 44) as *const f32), w:*((base + 48) as *const f32), }, z:super::types::Vec4{x:*((base + 52) as *const f32), y:*((base + 56) as *const f32), z:*((base + 60) as *const f32), w:*((base + 64) as *const f32), }, w:super::types::Vec4{x:*((base + 68) as *const f32), y:*((base + 72) as *const f32), z:*((base + 76) as *const f32), w:*((base + 80) as *const f32), }, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    6 => OptionValueResult::TypeI32(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 20) as *const i32)),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    7 => OptionValueResult::TypeQuat(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Quat{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    8 => OptionValueResult::TypeString(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some({
+                                        let len20 = *((base + 24) as *const i32) as usize;
+                                        
+                                        {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 20) as *const i32) as *mut _, len20, len20))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 20) as *const i32) as *mut _, len20, len20)).unwrap()}}
+                                      }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    9 => OptionValueResult::TypeU32(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 20) as *const i32) as u32),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    10 => OptionValueResult::TypeU64(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 24) as *const i64) as u64),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    11 => OptionValueResult::TypeVec2(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Vec2{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    12 => OptionValueResult::TypeVec3(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Vec3{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    13 => OptionValueResult::TypeVec4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Vec4{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    14 => OptionValueResult::TypeUvec2(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec2{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    15 => OptionValueResult::TypeUvec3(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec3{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    #[cfg(debug_assertions)]16 => OptionValueResult::TypeUvec4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec4{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, w:*((base + 32) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    #[cfg(not(debug_assertions))]_ => OptionValueResult::TypeUvec4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec4{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, w:*((base + 32) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
+                                  }}}),
+                                  #[cfg(not(debug_assertions))]_ => ValueResult::TypeOption({{match i32::from(*((base + 8) as *const u8)) {
+                                    0 => OptionValueResult::TypeEmpty(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(()),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    1 => OptionValueResult::TypeBool(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some({
+                                        #[cfg(not(debug_assertions))]
+                                        { core::mem::transmute::<u8, bool>(i32::from(*((base + 17) as *const u8)) as u8) }
+                                        #[cfg(debug_assertions)]
+                                        {
+                                          match i32::from(*((base + 17) as *const u8)) {
+                                            0 => false,
+                                            1 => true,
+                                            _ => panic!("invalid bool discriminant"),
+                                          }
+                                        }
+                                      }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    2 => OptionValueResult::TypeEntityId(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::EntityId{id0:*((base + 24) as *const i64) as u64, id1:*((base + 32) as *const i64) as u64, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    3 => OptionValueResult::TypeF32(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 20) as *const f32)),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    4 => OptionValueResult::TypeF64(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 24) as *const f64)),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    5 => OptionValueResult::TypeMat4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Mat4{x:super::types::Vec4{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }, y:super::types::Vec4{x:*((base + 36) as *const f32), y:*((base + 40) as *const f32), z:*((base + 44) as *const f32), w:*((base + 48) as *const f32), }, z:super::types::Vec4{x:*((base + 52) as *const f32), y:*((base + 56) as *const f32), z:*((base + 60) as *const f32), w:*((base + 64) as *const f32), }, w:super::types::Vec4{x:*((base + 68) as *const f32), y:*((base + 72) as *const f32), z:*((base + 76) as *const f32), w:*((base + 80) as *const f32), }, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    6 => OptionValueResult::TypeI32(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 20) as *const i32)),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    7 => OptionValueResult::TypeQuat(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Quat{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    8 => OptionValueResult::TypeString(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some({
+                                        let len20 = *((base + 24) as *const i32) as usize;
+                                        
+                                        {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 20) as *const i32) as *mut _, len20, len20))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 20) as *const i32) as *mut _, len20, len20)).unwrap()}}
+                                      }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    9 => OptionValueResult::TypeU32(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 20) as *const i32) as u32),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    10 => OptionValueResult::TypeU64(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(*((base + 24) as *const i64) as u64),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    11 => OptionValueResult::TypeVec2(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Vec2{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    12 => OptionValueResult::TypeVec3(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Vec3{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    13 => OptionValueResult::TypeVec4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Vec4{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), z:*((base + 28) as *const f32), w:*((base + 32) as *const f32), }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    14 => OptionValueResult::TypeUvec2(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec2{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    15 => OptionValueResult::TypeUvec3(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec3{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    #[cfg(debug_assertions)]16 => OptionValueResult::TypeUvec4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec4{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, w:*((base + 32) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    #[cfg(not(debug_assertions))]_ => OptionValueResult::TypeUvec4(match i32::from(*((base + 16) as *const u8)) {
+                                      0 => None,
+                                      1 => Some(super::types::Uvec4{x:*((base + 20) as *const i32) as u32, y:*((base + 24) as *const i32) as u32, z:*((base + 28) as *const i32) as u32, w:*((base + 32) as *const i32) as u32, }),
+                                      #[cfg(not(debug_assertions))]
+                                      _ => core::hint::unreachable_unchecked(),
+                                      #[cfg(debug_assertions)]
+                                      _ => panic!("invalid enum discriminant"),
+                                    }),
+                                    #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
+                                  }}}),
+                                  #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
+                                }}});
+                              }
+                              wit_bindgen::rt::dealloc(base21, (len21 as usize) * 88, 8);
+                              
+                              (super::types::EntityId{id0:*((base + 0) as *const i64) as u64, id1:*((base + 8) as *const i64) as u64, }, result21)
+                            });
+                          }
+                          wit_bindgen::rt::dealloc(base22, (len22 as usize) * 24, 8);
+                          result22
+                        }
+                      }
+                      
+                    }
+                    
+                    
+                    #[allow(clippy::all)]
+                    pub mod entity{
+                      pub type EntityId = super::types::EntityId;
+                      pub type Vec3 = super::types::Vec3;
+                      pub type EntityData<'a,> = super::component::EntityParam<'a,>;
+                      /// An action in an animation.
+                      #[derive(Clone)]
+                      pub struct AnimationAction<'a,> {
+                        /// The animation clip URL to play.
+                        pub clip_url: &'a str,
+                        /// Whether or not this action should loop
+                        pub looping: bool,
+                        /// How strongly this action applies to the final blend [0-1]
+                        pub weight: f32,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationAction<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationAction").field("clip-url", &self.clip_url).field("looping", &self.looping).field("weight", &self.weight).finish()
+                        }
+                      }
+                      /// Controls the animations for an entity.
+                      #[derive(Clone)]
+                      pub struct AnimationController<'a,> {
+                        /// All of the actions that contribute to this animation.
+                        /// Will be blended together.
+                        pub actions: &'a [AnimationAction<'a,>],
+                        /// Whether or not the first action's pose should be used as a base pose.
+                        pub apply_base_pose: bool,
+                      }
+                      impl<'a,> core::fmt::Debug for AnimationController<'a,> {
+                        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                          f.debug_struct("AnimationController").field("actions", &self.actions).field("apply-base-pose", &self.apply_base_pose).finish()
+                        }
+                      }
+                      #[allow(clippy::all)]
+                      pub fn spawn(data: EntityData<'_,>,) -> EntityId{
+                        
+                        #[allow(unused_imports)]
+                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                        unsafe {
+                          let mut cleanup_list = Vec::new();
+                          
+                          #[repr(align(8))]
+                          struct RetArea([u8; 16]);
+                          let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                          let vec49 = data;
+                          let len49 = vec49.len() as i32;
+                          let layout49 = alloc::Layout::from_size_align_unchecked(vec49.len() * 96, 8);
+                          let result49 = if layout49.size() != 0
+                          {
+                            let ptr = alloc::alloc(layout49);
+                            if ptr.is_null()
+                            {
+                              alloc::handle_alloc_error(layout49);
+                            }
+                            ptr
+                          }else {
+                            core::ptr::null_mut()
+                          };
+                          for (i, e) in vec49.into_iter().enumerate() {
+                            let base = result49 as i32 + (i as i32) * 96;
+                            {
+                              let (t0_0, t0_1, ) = e;
+                              *((base + 0) as *mut i32) = wit_bindgen::rt::as_i32(t0_0);
+                              match t0_1 {
+                                super::component::ValueParam::TypeEmpty(e) => {
+                                  *((base + 8) as *mut u8) = (0i32) as u8;
+                                  let () = e;
+                                  
+                                },
+                                super::component::ValueParam::TypeBool(e) => {
+                                  *((base + 8) as *mut u8) = (1i32) as u8;
+                                  *((base + 16) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
+                                  
+                                },
+                                super::component::ValueParam::TypeEntityId(e) => {
+                                  *((base + 8) as *mut u8) = (2i32) as u8;
+                                  let super::types::EntityId{ id0:id02, id1:id12, } = e;
+                                  *((base + 16) as *mut i64) = wit_bindgen::rt::as_i64(id02);
+                                  *((base + 24) as *mut i64) = wit_bindgen::rt::as_i64(id12);
+                                  
+                                },
+                                super::component::ValueParam::TypeF32(e) => {
+                                  *((base + 8) as *mut u8) = (3i32) as u8;
+                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(e);
+                                  
+                                },
+                                super::component::ValueParam::TypeF64(e) => {
+                                  *((base + 8) as *mut u8) = (4i32) as u8;
+                                  *((base + 16) as *mut f64) = wit_bindgen::rt::as_f64(e);
+                                  
+                                },
+                                super::component::ValueParam::TypeMat4(e) => {
+                                  *((base + 8) as *mut u8) = (5i32) as u8;
+                                  let super::types::Mat4{ x:x3, y:y3, z:z3, w:w3, } = e;
+                                  let super::types::Vec4{ x:x4, y:y4, z:z4, w:w4, } = x3;
+                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x4);
+                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y4);
+                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z4);
+                                  *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w4);
+                                  let super::types::Vec4{ x:x5, y:y5, z:z5, w:w5, } = y3;
+                                  *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(x5);
+                                  *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(y5);
+                                  *((base + 40) as *mut f32) = wit_bindgen::rt::as_f32(z5);
+                                  *((base + 44) as *mut f32) = wit_bindgen::rt::as_f32(w5);
+                                  let super::types::Vec4{ x:x6, y:y6, z:z6, w:w6, } = z3;
+                                  *((base + 48) as *mut f32) = wit_bindgen::rt::as_f32(x6);
+                                  *((base + 52) as *mut f32) = wit_bindgen::rt::as_f32(y6);
+                                  *((base + 56) as *mut f32) = wit_bindgen::rt::as_f32(z6);
+                                  *((base + 60) as *mut f32) = wit_bindgen::rt::as_f32(w6);
+                                  let super::types::Vec4{ x:x7, y:y7, z:z7, w:w7, } = w3;
+                                  *((base + 64) as *mut f32) = wit_bindgen::rt::as_f32(x7);
+                                  *((base + 68) as *mut f32) = wit_bindgen::rt::as_f32(y7);
+                                  *((base + 72) as *mut f32) = wit_bindgen::rt::as_f32(z7);
+                                  *((base + 76) as *mut f32) = wit_bindgen::rt::as_f32(w7);
+                                  
+                                },
+                                super::component::ValueParam::TypeI32(e) => {
+                                  *((base + 8) as *mut u8) = (6i32) as u8;
+                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(e);
+                                  
+                                },
+                                super::component::ValueParam::TypeQuat(e) => {
+                                  *((base + 8) as *mut u8) = (7i32) as u8;
+                                  let super::types::Quat{ x:x8, y:y8, z:z8, w:w8, } = e;
+                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x8);
+                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y8);
+                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z8);
+                                  *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w8);
+                                  
+                                },
+                                super::component::ValueParam::TypeString(e) => {
+                                  *((base + 8) as *mut u8) = (8i32) as u8;
+                                  let vec9 = e;
+                                  let ptr9 = vec9.as_ptr() as i32;
+                                  let len9 = vec9.len() as i32;
+                                  *((base + 20) as *mut i32) = len9;
+                                  *((base + 16) as *mut i32) = ptr9;
+                                  
+                                },
+                                super::component::ValueParam::TypeU32(e) => {
+                                  *((base + 8) as *mut u8) = (9i32) as u8;
+                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(e);
+                                  
+                                },
+                                super::component::ValueParam::TypeU64(e) => {
+                                  *((base + 8) as *mut u8) = (10i32) as u8;
+                                  *((base + 16) as *mut i64) = wit_bindgen::rt::as_i64(e);
+                                  
+                                },
+                                super::component::ValueParam::TypeVec2(e) => {
+                                  *((base + 8) as *mut u8) = (11i32) as u8;
+                                  let super::types::Vec2{ x:x10, y:y10, } = e;
+                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x10);
+                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y10);
+                                  
+                                },
+                                super::component::ValueParam::TypeVec3(e) => {
+                                  *((base + 8) as *mut u8) = (12i32) as u8;
+                                  let super::types::Vec3{ x:x11, y:y11, z:z11, } = e;
+                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x11);
+                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y11);
+                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z11);
+                                  
+                                },
+                                super::component::ValueParam::TypeVec4(e) => {
+                                  *((base + 8) as *mut u8) = (13i32) as u8;
+                                  let super::types::Vec4{ x:x12, y:y12, z:z12, w:w12, } = e;
+                                  *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x12);
+                                  *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y12);
+                                  *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z12);
+                                  *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w12);
+                                  
+                                },
+                                super::component::ValueParam::TypeUvec2(e) => {
+                                  *((base + 8) as *mut u8) = (14i32) as u8;
+                                  let super::types::Uvec2{ x:x13, y:y13, } = e;
+                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x13);
+                                  *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y13);
+                                  
+                                },
+                                super::component::ValueParam::TypeUvec3(e) => {
+                                  *((base + 8) as *mut u8) = (15i32) as u8;
+                                  let super::types::Uvec3{ x:x14, y:y14, z:z14, } = e;
+                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x14);
+                                  *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y14);
+                                  *((base + 24) as *mut i32) = wit_bindgen::rt::as_i32(z14);
+                                  
+                                },
+                                super::component::ValueParam::TypeUvec4(e) => {
+                                  *((base + 8) as *mut u8) = (16i32) as u8;
+                                  let super::types::Uvec4{ x:x15, y:y15, z:z15, w:w15, } = e;
+                                  *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x15);
+                                  *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y15);
+                                  *((base + 24) as *mut i32) = wit_bindgen::rt::as_i32(z15);
+                                  *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(w15);
+                                  
+                                },
+                                super::component::ValueParam::TypeVec(e) => {
+                                  *((base + 8) as *mut u8) = (17i32) as u8;
+                                  match e {
+                                    super::component::VecValueParam::TypeEmpty(e) => {
+                                      *((base + 16) as *mut u8) = (0i32) as u8;
+                                      let vec16 = e;
+                                      let ptr16 = vec16.as_ptr() as i32;
+                                      let len16 = vec16.len() as i32;
+                                      *((base + 24) as *mut i32) = len16;
+                                      *((base + 20) as *mut i32) = ptr16;
+                                      
+                                    },
+                                    super::component::VecValueParam::TypeBool(e) => {
+                                      *((base + 16) as *mut u8) = (1i32) as u8;
+                                      let vec17 = e;
+                                      let len17 = vec17.len() as i32;
+                                      let layout17 = alloc::Layout::from_size_align_unchecked(vec17.len() * 1, 1);
+                                      let result17 = if layout17.size() != 0
+                                      {
+                                        let ptr = alloc::alloc(layout17);
+                                        if ptr.is_null()
+                                        {
+                                          alloc::handle_alloc_error(layout17);
+                                        }
+                                        ptr
+                                      }else {
+                                        core::ptr::null_mut()
+                                      };
+                                      for (i, e) in vec17.into_iter().enumerate() {
+                                        let base = result17 as i32 + (i as i32) * 1;
+                                        {
+                                          *((base + 0) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
                                           
                                         }}
                                         *((base + 24) as *mut i32) = len17;
@@ -6349,32 +6936,726 @@ pub mod component{
                                           
                                         }}
                                         
-                                        #[link(wasm_import_module = "event")]
+                                        #[link(wasm_import_module = "event")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "send")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "event_send")]
+                                          fn wit_import(
+                                          _: i32, _: i32, _: i32, _: i32, );
+                                        }
+                                        wit_import(ptr0, len0, result50 as i32, len50);
+                                        if layout50.size() != 0 {
+                                          alloc::dealloc(result50, layout50);
+                                        }
+                                        for (ptr, layout) in cleanup_list {
+                                          
+                                          if layout.size() != 0 {
+                                            
+                                            alloc::dealloc(ptr, layout);
+                                            
+                                          }
+                                          
+                                        }
+                                      }
+                                    }
+                                    
+                                  }
+                                  
+                                  
+                                  #[allow(clippy::all)]
+                                  pub mod client_window{
+                                    #[allow(clippy::all)]
+                                    pub fn get_clipboard() -> Option<wit_bindgen::rt::string::String>{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[repr(align(4))]
+                                        struct RetArea([u8; 12]);
+                                        let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                        let ptr0 = ret_area.as_mut_ptr() as i32;
+                                        #[link(wasm_import_module = "client-window")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "get-clipboard")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_get-clipboard")]
+                                          fn wit_import(
+                                          _: i32, );
+                                        }
+                                        wit_import(ptr0);
+                                        match i32::from(*((ptr0 + 0) as *const u8)) {
+                                          0 => None,
+                                          1 => Some({
+                                            let len1 = *((ptr0 + 8) as *const i32) as usize;
+                                            
+                                            {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((ptr0 + 4) as *const i32) as *mut _, len1, len1))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((ptr0 + 4) as *const i32) as *mut _, len1, len1)).unwrap()}}
+                                          }),
+                                          #[cfg(not(debug_assertions))]
+                                          _ => core::hint::unreachable_unchecked(),
+                                          #[cfg(debug_assertions)]
+                                          _ => panic!("invalid enum discriminant"),
+                                        }
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn set_clipboard(text: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = text;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-window")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "set-clipboard")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_set-clipboard")]
+                                          fn wit_import(
+                                          _: i32, _: i32, );
+                                        }
+                                        wit_import(ptr0, len0);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Shows a dialog for the user to pick a file with one of `extensions`, or any file if empty.
+                                    /// Emits `core/file_opened` with the file if they do.
+                                    pub fn open_file_dialog(extensions: &[&str],){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec1 = extensions;
+                                        let len1 = vec1.len() as i32;
+                                        let layout1 = alloc::Layout::from_size_align_unchecked(vec1.len() * 8, 4);
+                                        let result1 = if layout1.size() != 0
+                                        {
+                                          let ptr = alloc::alloc(layout1);
+                                          if ptr.is_null()
+                                          {
+                                            alloc::handle_alloc_error(layout1);
+                                          }
+                                          ptr
+                                        }else {
+                                          core::ptr::null_mut()
+                                        };
+                                        for (i, e) in vec1.into_iter().enumerate() {
+                                          let base = result1 as i32 + (i as i32) * 8;
+                                          {
+                                            let vec0 = e;
+                                            let ptr0 = vec0.as_ptr() as i32;
+                                            let len0 = vec0.len() as i32;
+                                            *((base + 4) as *mut i32) = len0;
+                                            *((base + 0) as *mut i32) = ptr0;
+                                            
+                                          }}
+                                          
+                                          #[link(wasm_import_module = "client-window")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "open-file-dialog")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_open-file-dialog")]
+                                            fn wit_import(
+                                            _: i32, _: i32, );
+                                          }
+                                          wit_import(result1 as i32, len1);
+                                          if layout1.size() != 0 {
+                                            alloc::dealloc(result1, layout1);
+                                          }
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      /// Returns the contents of a file that was dropped into the window or opened with the file dialog.
+                                      pub fn read_file(handle: u64,) -> Option<wit_bindgen::rt::vec::Vec::<u8>>{
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                        unsafe {
+                                          
+                                          #[repr(align(4))]
+                                          struct RetArea([u8; 12]);
+                                          let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                          let ptr0 = ret_area.as_mut_ptr() as i32;
+                                          #[link(wasm_import_module = "client-window")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "read-file")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_read-file")]
+                                            fn wit_import(
+                                            _: i64, _: i32, );
+                                          }
+                                          wit_import(wit_bindgen::rt::as_i64(handle), ptr0);
+                                          match i32::from(*((ptr0 + 0) as *const u8)) {
+                                            0 => None,
+                                            1 => Some({
+                                              let len1 = *((ptr0 + 8) as *const i32) as usize;
+                                              
+                                              Vec::from_raw_parts(*((ptr0 + 4) as *const i32) as *mut _, len1, len1)
+                                            }),
+                                            #[cfg(not(debug_assertions))]
+                                            _ => core::hint::unreachable_unchecked(),
+                                            #[cfg(debug_assertions)]
+                                            _ => panic!("invalid enum discriminant"),
+                                          }
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      /// Saves the next frame as a PNG named `name`, or after the current time. In browsers, it's downloaded instead.
+                                      pub fn screenshot(name: Option<&str>,){
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                        unsafe {
+                                          let (result1_0,result1_1,result1_2,) = match name {
+                                            Some(e) => {
+                                              let vec0 = e;
+                                              let ptr0 = vec0.as_ptr() as i32;
+                                              let len0 = vec0.len() as i32;
+                                              
+                                              (1i32, ptr0, len0)
+                                            },
+                                            None => {
+                                              (0i32, 0i32, 0i32)
+                                            },
+                                          };
+                                          #[link(wasm_import_module = "client-window")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "screenshot")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_screenshot")]
+                                            fn wit_import(
+                                            _: i32, _: i32, _: i32, );
+                                          }
+                                          wit_import(result1_0, result1_1, result1_2);
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      /// Records the frames at `fps` into a video named `name`, or after the current time, for `seconds`, or until `stop-recording` is called.
+                                      pub fn start_recording(name: Option<&str>,fps: f32,seconds: Option<f32>,){
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                        unsafe {
+                                          let (result1_0,result1_1,result1_2,) = match name {
+                                            Some(e) => {
+                                              let vec0 = e;
+                                              let ptr0 = vec0.as_ptr() as i32;
+                                              let len0 = vec0.len() as i32;
+                                              
+                                              (1i32, ptr0, len0)
+                                            },
+                                            None => {
+                                              (0i32, 0i32, 0i32)
+                                            },
+                                          };
+                                          let (result2_0,result2_1,) = match seconds {
+                                            Some(e) => (1i32, wit_bindgen::rt::as_f32(e)),
+                                            None => {
+                                              (0i32, 0.0f32)
+                                            },
+                                          };
+                                          #[link(wasm_import_module = "client-window")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "start-recording")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_start-recording")]
+                                            fn wit_import(
+                                            _: i32, _: i32, _: i32, _: f32, _: i32, _: f32, );
+                                          }
+                                          wit_import(result1_0, result1_1, result1_2, wit_bindgen::rt::as_f32(fps), result2_0, result2_1);
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      pub fn stop_recording(){
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                        unsafe {
+                                          
+                                          #[link(wasm_import_module = "client-window")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "stop-recording")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_stop-recording")]
+                                            fn wit_import(
+                                            );
+                                          }
+                                          wit_import();
+                                        }
+                                      }
+                                      #[allow(clippy::all)]
+                                      /// Returns the user's value of the setting `key` declared by `package`, or its default, as text: `true`, `42`, `0.5` or the string itself.
+                                      /// Returns none if the package doesn't declare the setting.
+                                      pub fn get_setting(package: &str,key: &str,) -> Option<wit_bindgen::rt::string::String>{
+                                        
+                                        #[allow(unused_imports)]
+                                        use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                        unsafe {
+                                          
+                                          #[repr(align(4))]
+                                          struct RetArea([u8; 12]);
+                                          let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                          let vec0 = package;
+                                          let ptr0 = vec0.as_ptr() as i32;
+                                          let len0 = vec0.len() as i32;
+                                          let vec1 = key;
+                                          let ptr1 = vec1.as_ptr() as i32;
+                                          let len1 = vec1.len() as i32;
+                                          let ptr2 = ret_area.as_mut_ptr() as i32;
+                                          #[link(wasm_import_module = "client-window")]
+                                          extern "C" {
+                                            #[cfg_attr(target_arch = "wasm32", link_name = "get-setting")]
+                                            #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-window_get-setting")]
+                                            fn wit_import(
+                                            _: i32, _: i32, _: i32, _: i32, _: i32, );
+                                          }
+                                          wit_import(ptr0, len0, ptr1, len1, ptr2);
+                                          match i32::from(*((ptr2 + 0) as *const u8)) {
+                                            0 => None,
+                                            1 => Some({
+                                              let len3 = *((ptr2 + 8) as *const i32) as usize;
+                                              
+                                              {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((ptr2 + 4) as *const i32) as *mut _, len3, len3))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((ptr2 + 4) as *const i32) as *mut _, len3, len3)).unwrap()}}
+                                            }),
+                                            #[cfg(not(debug_assertions))]
+                                            _ => core::hint::unreachable_unchecked(),
+                                            #[cfg(debug_assertions)]
+                                            _ => panic!("invalid enum discriminant"),
+                                          }
+                                        }
+                                      }
+                                      
+                                    }
+                                  
+                                  
+                                  #[allow(clippy::all)]
+                                  pub mod client_mesh{
+                                    pub type Vec2 = super::types::Vec2;
+                                    pub type Vec3 = super::types::Vec3;
+                                    #[allow(clippy::all)]
+                                    /// Creates a mesh from its vertices and triangles, and returns its handle, which entities are rendered with by setting their `procedural_mesh` component to it.
+                                    /// The normals are computed from the triangles if `normals` is empty.
+                                    pub fn create(positions: &[Vec3],normals: &[Vec3],texcoords: &[Vec2],indices: &[u32],) -> u64{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = positions;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        let vec1 = normals;
+                                        let ptr1 = vec1.as_ptr() as i32;
+                                        let len1 = vec1.len() as i32;
+                                        let vec2 = texcoords;
+                                        let ptr2 = vec2.as_ptr() as i32;
+                                        let len2 = vec2.len() as i32;
+                                        let vec3 = indices;
+                                        let ptr3 = vec3.as_ptr() as i32;
+                                        let len3 = vec3.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-mesh")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "create")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-mesh_create")]
+                                          fn wit_import(
+                                          _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, ) -> i64;
+                                        }
+                                        let ret = wit_import(ptr0, len0, ptr1, len1, ptr2, len2, ptr3, len3);
+                                        ret as u64
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Replaces the vertices and triangles of the mesh with `handle`.
+                                    pub fn update(handle: u64,positions: &[Vec3],normals: &[Vec3],texcoords: &[Vec2],indices: &[u32],){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = positions;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        let vec1 = normals;
+                                        let ptr1 = vec1.as_ptr() as i32;
+                                        let len1 = vec1.len() as i32;
+                                        let vec2 = texcoords;
+                                        let ptr2 = vec2.as_ptr() as i32;
+                                        let len2 = vec2.len() as i32;
+                                        let vec3 = indices;
+                                        let ptr3 = vec3.as_ptr() as i32;
+                                        let len3 = vec3.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-mesh")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "update")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-mesh_update")]
+                                          fn wit_import(
+                                          _: i64, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(handle), ptr0, len0, ptr1, len1, ptr2, len2, ptr3, len3);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Overwrites the vertices of the mesh with `handle` from `offset`, without changing how many there are. Empty lists are left as they are.
+                                    pub fn update_vertices(handle: u64,offset: u32,positions: &[Vec3],normals: &[Vec3],texcoords: &[Vec2],){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = positions;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        let vec1 = normals;
+                                        let ptr1 = vec1.as_ptr() as i32;
+                                        let len1 = vec1.len() as i32;
+                                        let vec2 = texcoords;
+                                        let ptr2 = vec2.as_ptr() as i32;
+                                        let len2 = vec2.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-mesh")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "update-vertices")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-mesh_update-vertices")]
+                                          fn wit_import(
+                                          _: i64, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(handle), wit_bindgen::rt::as_i32(offset), ptr0, len0, ptr1, len1, ptr2, len2);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Destroys the mesh with `handle`. The entities which render it keep doing so until their `procedural_mesh` changes.
+                                    pub fn destroy(handle: u64,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[link(wasm_import_module = "client-mesh")]
                                         extern "C" {
-                                          #[cfg_attr(target_arch = "wasm32", link_name = "send")]
-                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "event_send")]
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "destroy")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-mesh_destroy")]
                                           fn wit_import(
-                                          _: i32, _: i32, _: i32, _: i32, );
+                                          _: i64, );
                                         }
-                                        wit_import(ptr0, len0, result50 as i32, len50);
-                                        if layout50.size() != 0 {
-                                          alloc::dealloc(result50, layout50);
+                                        wit_import(wit_bindgen::rt::as_i64(handle));
+                                      }
+                                    }
+                                    
+                                  }
+                                  
+                                  
+                                  #[allow(clippy::all)]
+                                  pub mod client_texture{
+                                    #[allow(clippy::all)]
+                                    /// Creates an RGBA8 texture of `width` by `height` pixels filled with `pixels`, or transparent black if `pixels` is empty, and returns its handle, which entities display it with by setting their `texture_handle` component to it.
+                                    pub fn create(width: u32,height: u32,pixels: &[u8],) -> u64{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = pixels;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-texture")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "create")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-texture_create")]
+                                          fn wit_import(
+                                          _: i32, _: i32, _: i32, _: i32, ) -> i64;
                                         }
-                                        for (ptr, layout) in cleanup_list {
-                                          
-                                          if layout.size() != 0 {
-                                            
-                                            alloc::dealloc(ptr, layout);
-                                            
-                                          }
-                                          
+                                        let ret = wit_import(wit_bindgen::rt::as_i32(width), wit_bindgen::rt::as_i32(height), ptr0, len0);
+                                        ret as u64
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Overwrites the region of `width` by `height` pixels at `x`, `y` of the texture with `handle` with `pixels`, rows of RGBA8 pixels.
+                                    pub fn update(handle: u64,x: u32,y: u32,width: u32,height: u32,pixels: &[u8],){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = pixels;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-texture")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "update")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-texture_update")]
+                                          fn wit_import(
+                                          _: i64, _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(handle), wit_bindgen::rt::as_i32(x), wit_bindgen::rt::as_i32(y), wit_bindgen::rt::as_i32(width), wit_bindgen::rt::as_i32(height), ptr0, len0);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Destroys the texture with `handle`. The entities which display it keep doing so until their `texture_handle` changes.
+                                    pub fn destroy(handle: u64,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        
+                                        #[link(wasm_import_module = "client-texture")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "destroy")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-texture_destroy")]
+                                          fn wit_import(
+                                          _: i64, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(handle));
+                                      }
+                                    }
+                                    
+                                  }
+                                  
+                                  
+                                  #[allow(clippy::all)]
+                                  pub mod client_gizmos{
+                                    pub type Vec3 = super::types::Vec3;
+                                    #[allow(clippy::all)]
+                                    /// Draws a line from `start` to `end` for `duration` seconds, or for a single frame if it's 0, in `category`, which can be hidden from the debugger.
+                                    pub fn line(start: Vec3,end: Vec3,color: Vec3,duration: f32,category: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let super::types::Vec3{ x:x0, y:y0, z:z0, } = start;
+                                        let super::types::Vec3{ x:x1, y:y1, z:z1, } = end;
+                                        let super::types::Vec3{ x:x2, y:y2, z:z2, } = color;
+                                        let vec3 = category;
+                                        let ptr3 = vec3.as_ptr() as i32;
+                                        let len3 = vec3.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-gizmos")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "line")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-gizmos_line")]
+                                          fn wit_import(
+                                          _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_f32(x0), wit_bindgen::rt::as_f32(y0), wit_bindgen::rt::as_f32(z0), wit_bindgen::rt::as_f32(x1), wit_bindgen::rt::as_f32(y1), wit_bindgen::rt::as_f32(z1), wit_bindgen::rt::as_f32(x2), wit_bindgen::rt::as_f32(y2), wit_bindgen::rt::as_f32(z2), wit_bindgen::rt::as_f32(duration), ptr3, len3);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Draws the outline of a box around `center`, with `half-extents`, like `line`.
+                                    pub fn cuboid(center: Vec3,half_extents: Vec3,color: Vec3,duration: f32,category: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let super::types::Vec3{ x:x0, y:y0, z:z0, } = center;
+                                        let super::types::Vec3{ x:x1, y:y1, z:z1, } = half_extents;
+                                        let super::types::Vec3{ x:x2, y:y2, z:z2, } = color;
+                                        let vec3 = category;
+                                        let ptr3 = vec3.as_ptr() as i32;
+                                        let len3 = vec3.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-gizmos")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "cuboid")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-gizmos_cuboid")]
+                                          fn wit_import(
+                                          _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_f32(x0), wit_bindgen::rt::as_f32(y0), wit_bindgen::rt::as_f32(z0), wit_bindgen::rt::as_f32(x1), wit_bindgen::rt::as_f32(y1), wit_bindgen::rt::as_f32(z1), wit_bindgen::rt::as_f32(x2), wit_bindgen::rt::as_f32(y2), wit_bindgen::rt::as_f32(z2), wit_bindgen::rt::as_f32(duration), ptr3, len3);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Draws the outline of a sphere around `center`, with `radius`, like `line`.
+                                    pub fn sphere(center: Vec3,radius: f32,color: Vec3,duration: f32,category: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let super::types::Vec3{ x:x0, y:y0, z:z0, } = center;
+                                        let super::types::Vec3{ x:x1, y:y1, z:z1, } = color;
+                                        let vec2 = category;
+                                        let ptr2 = vec2.as_ptr() as i32;
+                                        let len2 = vec2.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-gizmos")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "sphere")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-gizmos_sphere")]
+                                          fn wit_import(
+                                          _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_f32(x0), wit_bindgen::rt::as_f32(y0), wit_bindgen::rt::as_f32(z0), wit_bindgen::rt::as_f32(radius), wit_bindgen::rt::as_f32(x1), wit_bindgen::rt::as_f32(y1), wit_bindgen::rt::as_f32(z1), wit_bindgen::rt::as_f32(duration), ptr2, len2);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Draws `text` at `position`, facing the camera, like `line`.
+                                    pub fn text(position: Vec3,text: &str,color: Vec3,duration: f32,category: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let super::types::Vec3{ x:x0, y:y0, z:z0, } = position;
+                                        let vec1 = text;
+                                        let ptr1 = vec1.as_ptr() as i32;
+                                        let len1 = vec1.len() as i32;
+                                        let super::types::Vec3{ x:x2, y:y2, z:z2, } = color;
+                                        let vec3 = category;
+                                        let ptr3 = vec3.as_ptr() as i32;
+                                        let len3 = vec3.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-gizmos")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "text")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-gizmos_text")]
+                                          fn wit_import(
+                                          _: f32, _: f32, _: f32, _: i32, _: i32, _: f32, _: f32, _: f32, _: f32, _: i32, _: i32, );
                                         }
+                                        wit_import(wit_bindgen::rt::as_f32(x0), wit_bindgen::rt::as_f32(y0), wit_bindgen::rt::as_f32(z0), ptr1, len1, wit_bindgen::rt::as_f32(x2), wit_bindgen::rt::as_f32(y2), wit_bindgen::rt::as_f32(z2), wit_bindgen::rt::as_f32(duration), ptr3, len3);
                                       }
                                     }
                                     
                                   }
                                   
                                   
+                                  #[allow(clippy::all)]
+                                  pub mod client_asset{
+                                    /// The order in which prefetches start. `high` prefetches start immediately; the others wait until fewer prefetches are loading.
+                                    #[repr(u8)]
+                                    #[derive(Clone, Copy, PartialEq, Eq)]
+                                    pub enum PrefetchPriority {
+                                      High,
+                                      Normal,
+                                      Low,
+                                    }
+                                    impl core::fmt::Debug for PrefetchPriority {
+                                      fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                        match self {
+                                          PrefetchPriority::High => {
+                                            f.debug_tuple("PrefetchPriority::High").finish()
+                                          }
+                                          PrefetchPriority::Normal => {
+                                            f.debug_tuple("PrefetchPriority::Normal").finish()
+                                          }
+                                          PrefetchPriority::Low => {
+                                            f.debug_tuple("PrefetchPriority::Low").finish()
+                                          }
+                                        }
+                                      }
+                                    }
+                                    #[repr(u8)]
+                                    #[derive(Clone, Copy, PartialEq, Eq)]
+                                    pub enum PrefetchStatus {
+                                      Queued,
+                                      Loading,
+                                      Loaded,
+                                      Failed,
+                                    }
+                                    impl core::fmt::Debug for PrefetchStatus {
+                                      fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                        match self {
+                                          PrefetchStatus::Queued => {
+                                            f.debug_tuple("PrefetchStatus::Queued").finish()
+                                          }
+                                          PrefetchStatus::Loading => {
+                                            f.debug_tuple("PrefetchStatus::Loading").finish()
+                                          }
+                                          PrefetchStatus::Loaded => {
+                                            f.debug_tuple("PrefetchStatus::Loaded").finish()
+                                          }
+                                          PrefetchStatus::Failed => {
+                                            f.debug_tuple("PrefetchStatus::Failed").finish()
+                                          }
+                                        }
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Starts loading the asset at `url` into the asset cache, including decoding it and uploading it to the GPU, so that using it later doesn't stall. Prefabs are loaded with their models.
+                                    /// Returns the handle of the prefetch. The asset is kept loaded until the prefetch is cancelled, or for a minute after it has loaded.
+                                    pub fn prefetch(url: &str,priority: PrefetchPriority,) -> u64{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = url;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "client-asset")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "prefetch")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-asset_prefetch")]
+                                          fn wit_import(
+                                          _: i32, _: i32, _: i32, ) -> i64;
+                                        }
+                                        let ret = wit_import(ptr0, len0, match priority {
+                                          PrefetchPriority::High => 0,
+                                          PrefetchPriority::Normal => 1,
+                                          PrefetchPriority::Low => 2,
+                                        });
+                                        ret as u64
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Stops the prefetch with `handle` if it's still loading, and releases its asset.
+                                    pub fn cancel_prefetch(handle: u64,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        
+                                        #[link(wasm_import_module = "client-asset")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "cancel-prefetch")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-asset_cancel-prefetch")]
+                                          fn wit_import(
+                                          _: i64, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(handle));
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    /// Returns the status of the prefetch with `handle`, or none if it was cancelled or has expired.
+                                    pub fn status(handle: u64,) -> Option<PrefetchStatus>{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[repr(align(1))]
+                                        struct RetArea([u8; 2]);
+                                        let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                        let ptr0 = ret_area.as_mut_ptr() as i32;
+                                        #[link(wasm_import_module = "client-asset")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "status")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "client-asset_status")]
+                                          fn wit_import(
+                                          _: i64, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(handle), ptr0);
+                                        match i32::from(*((ptr0 + 0) as *const u8)) {
+                                          0 => None,
+                                          1 => Some({
+                                            #[cfg(not(debug_assertions))]
+                                            {
+                                              core::mem::transmute::<_, PrefetchStatus>(i32::from(*((ptr0 + 1) as *const u8)) as u8)
+                                            }
+                                            #[cfg(debug_assertions)]
+                                            {
+                                              match i32::from(*((ptr0 + 1) as *const u8)) {
+                                                0 => PrefetchStatus::Queued,
+                                                1 => PrefetchStatus::Loading,
+                                                2 => PrefetchStatus::Loaded,
+                                                3 => PrefetchStatus::Failed,
+                                                _ => panic!("invalid enum discriminant"),
+                                              }
+                                            }
+                                          }),
+                                          #[cfg(not(debug_assertions))]
+                                          _ => core::hint::unreachable_unchecked(),
+                                          #[cfg(debug_assertions)]
+                                          _ => panic!("invalid enum discriminant"),
+                                        }
+                                      }
+                                    }
+
+                                  }
+                                  
+                                  
                                   #[allow(clippy::all)]
                                   pub mod server_player{
                                     pub type Vec2 = super::types::Vec2;
@@ -7098,6 +8379,99 @@ pub mod component{
                                         f.debug_struct("RawInput").field("keys", &self.keys).field("mouse-position", &self.mouse_position).field("cursor-position", &self.cursor_position).field("mouse-wheel", &self.mouse_wheel).field("mouse-buttons", &self.mouse_buttons).finish()
                                       }
                                     }
+                                    #[repr(u8)]
+                                    #[derive(Clone, Copy, PartialEq, Eq)]
+                                    pub enum GamepadButton {
+                                      South,
+                                      East,
+                                      North,
+                                      West,
+                                      LeftBumper,
+                                      RightBumper,
+                                      LeftTrigger,
+                                      RightTrigger,
+                                      Select,
+                                      Start,
+                                      Mode,
+                                      LeftStick,
+                                      RightStick,
+                                      DpadUp,
+                                      DpadDown,
+                                      DpadLeft,
+                                      DpadRight,
+                                    }
+                                    impl core::fmt::Debug for GamepadButton {
+                                      fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                        match self {
+                                          GamepadButton::South => {
+                                            f.debug_tuple("GamepadButton::South").finish()
+                                          }
+                                          GamepadButton::East => {
+                                            f.debug_tuple("GamepadButton::East").finish()
+                                          }
+                                          GamepadButton::North => {
+                                            f.debug_tuple("GamepadButton::North").finish()
+                                          }
+                                          GamepadButton::West => {
+                                            f.debug_tuple("GamepadButton::West").finish()
+                                          }
+                                          GamepadButton::LeftBumper => {
+                                            f.debug_tuple("GamepadButton::LeftBumper").finish()
+                                          }
+                                          GamepadButton::RightBumper => {
+                                            f.debug_tuple("GamepadButton::RightBumper").finish()
+                                          }
+                                          GamepadButton::LeftTrigger => {
+                                            f.debug_tuple("GamepadButton::LeftTrigger").finish()
+                                          }
+                                          GamepadButton::RightTrigger => {
+                                            f.debug_tuple("GamepadButton::RightTrigger").finish()
+                                          }
+                                          GamepadButton::Select => {
+                                            f.debug_tuple("GamepadButton::Select").finish()
+                                          }
+                                          GamepadButton::Start => {
+                                            f.debug_tuple("GamepadButton::Start").finish()
+                                          }
+                                          GamepadButton::Mode => {
+                                            f.debug_tuple("GamepadButton::Mode").finish()
+                                          }
+                                          GamepadButton::LeftStick => {
+                                            f.debug_tuple("GamepadButton::LeftStick").finish()
+                                          }
+                                          GamepadButton::RightStick => {
+                                            f.debug_tuple("GamepadButton::RightStick").finish()
+                                          }
+                                          GamepadButton::DpadUp => {
+                                            f.debug_tuple("GamepadButton::DpadUp").finish()
+                                          }
+                                          GamepadButton::DpadDown => {
+                                            f.debug_tuple("GamepadButton::DpadDown").finish()
+                                          }
+                                          GamepadButton::DpadLeft => {
+                                            f.debug_tuple("GamepadButton::DpadLeft").finish()
+                                          }
+                                          GamepadButton::DpadRight => {
+                                            f.debug_tuple("GamepadButton::DpadRight").finish()
+                                          }
+                                        }
+                                      }
+                                    }
+                                    #[derive(Clone)]
+                                    pub struct Gamepad {
+                                      pub id: u32,
+                                      pub name: wit_bindgen::rt::string::String,
+                                      pub buttons: wit_bindgen::rt::vec::Vec::<GamepadButton>,
+                                      pub left_stick: Vec2,
+                                      pub right_stick: Vec2,
+                                      pub left_trigger: f32,
+                                      pub right_trigger: f32,
+                                    }
+                                    impl core::fmt::Debug for Gamepad {
+                                      fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                                        f.debug_struct("Gamepad").field("id", &self.id).field("name", &self.name).field("buttons", &self.buttons).field("left-stick", &self.left_stick).field("right-stick", &self.right_stick).field("left-trigger", &self.left_trigger).field("right-trigger", &self.right_trigger).finish()
+                                      }
+                                    }
                                     #[allow(clippy::all)]
                                     pub fn get_raw_input(player: EntityId,) -> Option<RawInput>{
                                       
@@ -7529,15 +8903,100 @@ pub mod component{
                                                 #[cfg(debug_assertions)]_ => panic!("invalid enum discriminant"),
                                               }}});
                                             }
-                                            wit_bindgen::rt::dealloc(base3, (len3 as usize) * 4, 2);
+                                            wit_bindgen::rt::dealloc(base3, (len3 as usize) * 4, 2);
+                                            
+                                            RawInput{keys:result2, mouse_position:super::types::Vec2{x:*((ptr1 + 12) as *const f32), y:*((ptr1 + 16) as *const f32), }, cursor_position:super::types::Vec2{x:*((ptr1 + 20) as *const f32), y:*((ptr1 + 24) as *const f32), }, mouse_wheel:*((ptr1 + 28) as *const f32), mouse_buttons:result3, }
+                                          }),
+                                          #[cfg(not(debug_assertions))]
+                                          _ => core::hint::unreachable_unchecked(),
+                                          #[cfg(debug_assertions)]
+                                          _ => panic!("invalid enum discriminant"),
+                                        }
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn get_gamepads(player: EntityId,) -> wit_bindgen::rt::vec::Vec::<Gamepad>{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[repr(align(4))]
+                                        struct RetArea([u8; 8]);
+                                        let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                        let super::types::EntityId{ id0:id00, id1:id10, } = player;
+                                        let ptr1 = ret_area.as_mut_ptr() as i32;
+                                        #[link(wasm_import_module = "server-player")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "get-gamepads")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-player_get-gamepads")]
+                                          fn wit_import(
+                                          _: i64, _: i64, _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), ptr1);
+                                        let base4 = *((ptr1 + 0) as *const i32);
+                                        let len4 = *((ptr1 + 4) as *const i32);
+                                        let mut result4 = Vec::with_capacity(len4 as usize);
+                                        for i in 0..len4 {
+                                          let base = base4 + i *44;
+                                          result4.push({
+                                            let len2 = *((base + 8) as *const i32) as usize;
+                                            let base3 = *((base + 12) as *const i32);
+                                            let len3 = *((base + 16) as *const i32);
+                                            let mut result3 = Vec::with_capacity(len3 as usize);
+                                            for i in 0..len3 {
+                                              let base = base3 + i *1;
+                                              result3.push({#[cfg(debug_assertions)]{match i32::from(*((base + 0) as *const u8)) {
+                                                  0 => GamepadButton::South,
+                                                  1 => GamepadButton::East,
+                                                  2 => GamepadButton::North,
+                                                  3 => GamepadButton::West,
+                                                  4 => GamepadButton::LeftBumper,
+                                                  5 => GamepadButton::RightBumper,
+                                                  6 => GamepadButton::LeftTrigger,
+                                                  7 => GamepadButton::RightTrigger,
+                                                  8 => GamepadButton::Select,
+                                                  9 => GamepadButton::Start,
+                                                  10 => GamepadButton::Mode,
+                                                  11 => GamepadButton::LeftStick,
+                                                  12 => GamepadButton::RightStick,
+                                                  13 => GamepadButton::DpadUp,
+                                                  14 => GamepadButton::DpadDown,
+                                                  15 => GamepadButton::DpadLeft,
+                                                  16 => GamepadButton::DpadRight,
+                                                  _ => panic!("invalid enum discriminant"),
+                                                }}#[cfg(not(debug_assertions))]{core::mem::transmute::<_, GamepadButton>(i32::from(*((base + 0) as *const u8)) as u8)}});
+                                            }
+                                            wit_bindgen::rt::dealloc(base3, (len3 as usize) * 1, 1);
                                             
-                                            RawInput{keys:result2, mouse_position:super::types::Vec2{x:*((ptr1 + 12) as *const f32), y:*((ptr1 + 16) as *const f32), }, cursor_position:super::types::Vec2{x:*((ptr1 + 20) as *const f32), y:*((ptr1 + 24) as *const f32), }, mouse_wheel:*((ptr1 + 28) as *const f32), mouse_buttons:result3, }
-                                          }),
-                                          #[cfg(not(debug_assertions))]
-                                          _ => core::hint::unreachable_unchecked(),
-                                          #[cfg(debug_assertions)]
-                                          _ => panic!("invalid enum discriminant"),
+                                            Gamepad{id:*((base + 0) as *const i32) as u32, name:{#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 4) as *const i32) as *mut _, len2, len2))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 4) as *const i32) as *mut _, len2, len2)).unwrap()}}, buttons:result3, left_stick:super::types::Vec2{x:*((base + 20) as *const f32), y:*((base + 24) as *const f32), }, right_stick:super::types::Vec2{x:*((base + 28) as *const f32), y:*((base + 32) as *const f32), }, left_trigger:*((base + 36) as *const f32), right_trigger:*((base + 40) as *const f32), }
+                                          });
+                                        }
+                                        wit_bindgen::rt::dealloc(base4, (len4 as usize) * 44, 4);
+                                        result4
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn rumble(player: EntityId,gamepad: Option<u32>,strong: f32,weak: f32,duration: f32,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let super::types::EntityId{ id0:id00, id1:id10, } = player;
+                                        let (result1_0,result1_1,) = match gamepad {
+                                          Some(e) => (1i32, wit_bindgen::rt::as_i32(e)),
+                                          None => {
+                                            (0i32, 0i32)
+                                          },
+                                        };
+                                        #[link(wasm_import_module = "server-player")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "rumble")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-player_rumble")]
+                                          fn wit_import(
+                                          _: i64, _: i64, _: i32, _: i32, _: f32, _: f32, _: f32, );
                                         }
+                                        wit_import(wit_bindgen::rt::as_i64(id00), wit_bindgen::rt::as_i64(id10), result1_0, result1_1, wit_bindgen::rt::as_f32(strong), wit_bindgen::rt::as_f32(weak), wit_bindgen::rt::as_f32(duration));
                                       }
                                     }
                                     
@@ -7853,6 +9312,946 @@ pub mod component{
                                   }
                                   
                                   
+                                  #[allow(clippy::all)]
+                                  pub mod server_instance{
+                                    pub type Entity<'a,> = super::component::EntityParam<'a,>;
+                                    #[allow(clippy::all)]
+                                    pub fn current() -> wit_bindgen::rt::string::String{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[repr(align(4))]
+                                        struct RetArea([u8; 8]);
+                                        let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                        let ptr0 = ret_area.as_mut_ptr() as i32;
+                                        #[link(wasm_import_module = "server-instance")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "current")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-instance_current")]
+                                          fn wit_import(
+                                          _: i32, );
+                                        }
+                                        wit_import(ptr0);
+                                        let len1 = *((ptr0 + 4) as *const i32) as usize;
+                                        {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((ptr0 + 0) as *const i32) as *mut _, len1, len1))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((ptr0 + 0) as *const i32) as *mut _, len1, len1)).unwrap()}}
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn list() -> wit_bindgen::rt::vec::Vec::<wit_bindgen::rt::string::String>{
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[repr(align(4))]
+                                        struct RetArea([u8; 8]);
+                                        let mut ret_area = core::mem::MaybeUninit::<RetArea>::uninit();
+                                        let ptr0 = ret_area.as_mut_ptr() as i32;
+                                        #[link(wasm_import_module = "server-instance")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "list")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-instance_list")]
+                                          fn wit_import(
+                                          _: i32, );
+                                        }
+                                        wit_import(ptr0);
+                                        let base2 = *((ptr0 + 0) as *const i32);
+                                        let len2 = *((ptr0 + 4) as *const i32);
+                                        let mut result2 = Vec::with_capacity(len2 as usize);
+                                        for i in 0..len2 {
+                                          let base = base2 + i *8;
+                                          result2.push({
+                                            let len1 = *((base + 4) as *const i32) as usize;
+                                            
+                                            {#[cfg(not(debug_assertions))]{String::from_utf8_unchecked(Vec::from_raw_parts(*((base + 0) as *const i32) as *mut _, len1, len1))}#[cfg(debug_assertions)]{String::from_utf8(Vec::from_raw_parts(*((base + 0) as *const i32) as *mut _, len1, len1)).unwrap()}}
+                                          });
+                                        }
+                                        wit_bindgen::rt::dealloc(base2, (len2 as usize) * 8, 4);
+                                        result2
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn create(id: &str,source: Option<&str>,tick_rate: Option<f32>,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = id;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        let (result2_0,result2_1,result2_2,) = match source {
+                                          Some(e) => {
+                                            let vec1 = e;
+                                            let ptr1 = vec1.as_ptr() as i32;
+                                            let len1 = vec1.len() as i32;
+                                            
+                                            (1i32, ptr1, len1)
+                                          },
+                                          None => {
+                                            (0i32, 0i32, 0i32)
+                                          },
+                                        };
+                                        let (result3_0,result3_1,) = match tick_rate {
+                                          Some(e) => (1i32, wit_bindgen::rt::as_f32(e)),
+                                          None => {
+                                            (0i32, 0.0f32)
+                                          },
+                                        };
+                                        #[link(wasm_import_module = "server-instance")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "create")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-instance_create")]
+                                          fn wit_import(
+                                          _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, _: f32, );
+                                        }
+                                        wit_import(ptr0, len0, result2_0, result2_1, result2_2, result3_0, result3_1);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn destroy(id: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = id;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "server-instance")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "destroy")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-instance_destroy")]
+                                          fn wit_import(
+                                          _: i32, _: i32, );
+                                        }
+                                        wit_import(ptr0, len0);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn transfer_player(user_id: &str,instance: &str,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let vec0 = user_id;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        let vec1 = instance;
+                                        let ptr1 = vec1.as_ptr() as i32;
+                                        let len1 = vec1.len() as i32;
+                                        
+                                        #[link(wasm_import_module = "server-instance")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "transfer-player")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-instance_transfer-player")]
+                                          fn wit_import(
+                                          _: i32, _: i32, _: i32, _: i32, );
+                                        }
+                                        wit_import(ptr0, len0, ptr1, len1);
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn send(instance: &str,name: &str,data: Entity<'_,>,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let mut cleanup_list = Vec::new();
+                                        let vec0 = name;
+                                        let ptr0 = vec0.as_ptr() as i32;
+                                        let len0 = vec0.len() as i32;
+                                        let vec51 = instance;
+                                        let ptr51 = vec51.as_ptr() as i32;
+                                        let len51 = vec51.len() as i32;
+                                        let vec50 = data;
+                                        let len50 = vec50.len() as i32;
+                                        let layout50 = alloc::Layout::from_size_align_unchecked(vec50.len() * 96, 8);
+                                        let result50 = if layout50.size() != 0
+                                        {
+                                          let ptr = alloc::alloc(layout50);
+                                          if ptr.is_null()
+                                          {
+                                            alloc::handle_alloc_error(layout50);
+                                          }
+                                          ptr
+                                        }else {
+                                          core::ptr::null_mut()
+                                        };
+                                        for (i, e) in vec50.into_iter().enumerate() {
+                                          let base = result50 as i32 + (i as i32) * 96;
+                                          {
+                                            let (t1_0, t1_1, ) = e;
+                                            *((base + 0) as *mut i32) = wit_bindgen::rt::as_i32(t1_0);
+                                            match t1_1 {
+                                              super::component::ValueParam::TypeEmpty(e) => {
+                                                *((base + 8) as *mut u8) = (0i32) as u8;
+                                                let () = e;
+                                                
+                                              },
+                                              super::component::ValueParam::TypeBool(e) => {
+                                                *((base + 8) as *mut u8) = (1i32) as u8;
+                                                *((base + 16) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
+                                                
+                                              },
+                                              super::component::ValueParam::TypeEntityId(e) => {
+                                                *((base + 8) as *mut u8) = (2i32) as u8;
+                                                let super::types::EntityId{ id0:id03, id1:id13, } = e;
+                                                *((base + 16) as *mut i64) = wit_bindgen::rt::as_i64(id03);
+                                                *((base + 24) as *mut i64) = wit_bindgen::rt::as_i64(id13);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeF32(e) => {
+                                                *((base + 8) as *mut u8) = (3i32) as u8;
+                                                *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(e);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeF64(e) => {
+                                                *((base + 8) as *mut u8) = (4i32) as u8;
+                                                *((base + 16) as *mut f64) = wit_bindgen::rt::as_f64(e);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeMat4(e) => {
+                                                *((base + 8) as *mut u8) = (5i32) as u8;
+                                                let super::types::Mat4{ x:x4, y:y4, z:z4, w:w4, } = e;
+                                                let super::types::Vec4{ x:x5, y:y5, z:z5, w:w5, } = x4;
+                                                *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x5);
+                                                *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y5);
+                                                *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z5);
+                                                *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w5);
+                                                let super::types::Vec4{ x:x6, y:y6, z:z6, w:w6, } = y4;
+                                                *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(x6);
+                                                *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(y6);
+                                                *((base + 40) as *mut f32) = wit_bindgen::rt::as_f32(z6);
+                                                *((base + 44) as *mut f32) = wit_bindgen::rt::as_f32(w6);
+                                                let super::types::Vec4{ x:x7, y:y7, z:z7, w:w7, } = z4;
+                                                *((base + 48) as *mut f32) = wit_bindgen::rt::as_f32(x7);
+                                                *((base + 52) as *mut f32) = wit_bindgen::rt::as_f32(y7);
+                                                *((base + 56) as *mut f32) = wit_bindgen::rt::as_f32(z7);
+                                                *((base + 60) as *mut f32) = wit_bindgen::rt::as_f32(w7);
+                                                let super::types::Vec4{ x:x8, y:y8, z:z8, w:w8, } = w4;
+                                                *((base + 64) as *mut f32) = wit_bindgen::rt::as_f32(x8);
+                                                *((base + 68) as *mut f32) = wit_bindgen::rt::as_f32(y8);
+                                                *((base + 72) as *mut f32) = wit_bindgen::rt::as_f32(z8);
+                                                *((base + 76) as *mut f32) = wit_bindgen::rt::as_f32(w8);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeI32(e) => {
+                                                *((base + 8) as *mut u8) = (6i32) as u8;
+                                                *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(e);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeQuat(e) => {
+                                                *((base + 8) as *mut u8) = (7i32) as u8;
+                                                let super::types::Quat{ x:x9, y:y9, z:z9, w:w9, } = e;
+                                                *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x9);
+                                                *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y9);
+                                                *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z9);
+                                                *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w9);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeString(e) => {
+                                                *((base + 8) as *mut u8) = (8i32) as u8;
+                                                let vec10 = e;
+                                                let ptr10 = vec10.as_ptr() as i32;
+                                                let len10 = vec10.len() as i32;
+                                                *((base + 20) as *mut i32) = len10;
+                                                *((base + 16) as *mut i32) = ptr10;
+                                                
+                                              },
+                                              super::component::ValueParam::TypeU32(e) => {
+                                                *((base + 8) as *mut u8) = (9i32) as u8;
+                                                *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(e);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeU64(e) => {
+                                                *((base + 8) as *mut u8) = (10i32) as u8;
+                                                *((base + 16) as *mut i64) = wit_bindgen::rt::as_i64(e);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeVec2(e) => {
+                                                *((base + 8) as *mut u8) = (11i32) as u8;
+                                                let super::types::Vec2{ x:x11, y:y11, } = e;
+                                                *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x11);
+                                                *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y11);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeVec3(e) => {
+                                                *((base + 8) as *mut u8) = (12i32) as u8;
+                                                let super::types::Vec3{ x:x12, y:y12, z:z12, } = e;
+                                                *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x12);
+                                                *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y12);
+                                                *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z12);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeVec4(e) => {
+                                                *((base + 8) as *mut u8) = (13i32) as u8;
+                                                let super::types::Vec4{ x:x13, y:y13, z:z13, w:w13, } = e;
+                                                *((base + 16) as *mut f32) = wit_bindgen::rt::as_f32(x13);
+                                                *((base + 20) as *mut f32) = wit_bindgen::rt::as_f32(y13);
+                                                *((base + 24) as *mut f32) = wit_bindgen::rt::as_f32(z13);
+                                                *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(w13);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeUvec2(e) => {
+                                                *((base + 8) as *mut u8) = (14i32) as u8;
+                                                let super::types::Uvec2{ x:x14, y:y14, } = e;
+                                                *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x14);
+                                                *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y14);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeUvec3(e) => {
+                                                *((base + 8) as *mut u8) = (15i32) as u8;
+                                                let super::types::Uvec3{ x:x15, y:y15, z:z15, } = e;
+                                                *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x15);
+                                                *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y15);
+                                                *((base + 24) as *mut i32) = wit_bindgen::rt::as_i32(z15);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeUvec4(e) => {
+                                                *((base + 8) as *mut u8) = (16i32) as u8;
+                                                let super::types::Uvec4{ x:x16, y:y16, z:z16, w:w16, } = e;
+                                                *((base + 16) as *mut i32) = wit_bindgen::rt::as_i32(x16);
+                                                *((base + 20) as *mut i32) = wit_bindgen::rt::as_i32(y16);
+                                                *((base + 24) as *mut i32) = wit_bindgen::rt::as_i32(z16);
+                                                *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(w16);
+                                                
+                                              },
+                                              super::component::ValueParam::TypeVec(e) => {
+                                                *((base + 8) as *mut u8) = (17i32) as u8;
+                                                match e {
+                                                  super::component::VecValueParam::TypeEmpty(e) => {
+                                                    *((base + 16) as *mut u8) = (0i32) as u8;
+                                                    let vec17 = e;
+                                                    let ptr17 = vec17.as_ptr() as i32;
+                                                    let len17 = vec17.len() as i32;
+                                                    *((base + 24) as *mut i32) = len17;
+                                                    *((base + 20) as *mut i32) = ptr17;
+                                                    
+                                                  },
+                                                  super::component::VecValueParam::TypeBool(e) => {
+                                                    *((base + 16) as *mut u8) = (1i32) as u8;
+                                                    let vec18 = e;
+                                                    let len18 = vec18.len() as i32;
+                                                    let layout18 = alloc::Layout::from_size_align_unchecked(vec18.len() * 1, 1);
+                                                    let result18 = if layout18.size() != 0
+                                                    {
+                                                      let ptr = alloc::alloc(layout18);
+                                                      if ptr.is_null()
+                                                      {
+                                                        alloc::handle_alloc_error(layout18);
+                                                      }
+                                                      ptr
+                                                    }else {
+                                                      core::ptr::null_mut()
+                                                    };
+                                                    for (i, e) in vec18.into_iter().enumerate() {
+                                                      let base = result18 as i32 + (i as i32) * 1;
+                                                      {
+                                                        *((base + 0) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
+                                                        
+                                                      }}
+                                                      *((base + 24) as *mut i32) = len18;
+                                                      *((base + 20) as *mut i32) = result18 as i32;
+                                                      cleanup_list.extend_from_slice(&[(result18, layout18),]);
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeEntityId(e) => {
+                                                      *((base + 16) as *mut u8) = (2i32) as u8;
+                                                      let vec19 = e;
+                                                      let ptr19 = vec19.as_ptr() as i32;
+                                                      let len19 = vec19.len() as i32;
+                                                      *((base + 24) as *mut i32) = len19;
+                                                      *((base + 20) as *mut i32) = ptr19;
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeF32(e) => {
+                                                      *((base + 16) as *mut u8) = (3i32) as u8;
+                                                      let vec20 = e;
+                                                      let ptr20 = vec20.as_ptr() as i32;
+                                                      let len20 = vec20.len() as i32;
+                                                      *((base + 24) as *mut i32) = len20;
+                                                      *((base + 20) as *mut i32) = ptr20;
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeF64(e) => {
+                                                      *((base + 16) as *mut u8) = (4i32) as u8;
+                                                      let vec21 = e;
+                                                      let ptr21 = vec21.as_ptr() as i32;
+                                                      let len21 = vec21.len() as i32;
+                                                      *((base + 24) as *mut i32) = len21;
+                                                      *((base + 20) as *mut i32) = ptr21;
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeMat4(e) => {
+                                                      *((base + 16) as *mut u8) = (5i32) as u8;
+                                                      let vec22 = e;
+                                                      let ptr22 = vec22.as_ptr() as i32;
+                                                      let len22 = vec22.len() as i32;
+                                                      *((base + 24) as *mut i32) = len22;
+                                                      *((base + 20) as *mut i32) = ptr22;
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeI32(e) => {
+                                                      *((base + 16) as *mut u8) = (6i32) as u8;
+                                                      let vec23 = e;
+                                                      let ptr23 = vec23.as_ptr() as i32;
+                                                      let len23 = vec23.len() as i32;
+                                                      *((base + 24) as *mut i32) = len23;
+                                                      *((base + 20) as *mut i32) = ptr23;
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeQuat(e) => {
+                                                      *((base + 16) as *mut u8) = (7i32) as u8;
+                                                      let vec24 = e;
+                                                      let ptr24 = vec24.as_ptr() as i32;
+                                                      let len24 = vec24.len() as i32;
+                                                      *((base + 24) as *mut i32) = len24;
+                                                      *((base + 20) as *mut i32) = ptr24;
+                                                      
+                                                    },
+                                                    super::component::VecValueParam::TypeString(e) => {
+                                                      *((base + 16) as *mut u8) = (8i32) as u8;
+                                                      let vec26 = e;
+                                                      let len26 = vec26.len() as i32;
+                                                      let layout26 = alloc::Layout::from_size_align_unchecked(vec26.len() * 8, 4);
+                                                      let result26 = if layout26.size() != 0
+                                                      {
+                                                        let ptr = alloc::alloc(layout26);
+                                                        if ptr.is_null()
+                                                        {
+                                                          alloc::handle_alloc_error(layout26);
+                                                        }
+                                                        ptr
+                                                      }else {
+                                                        core::ptr::null_mut()
+                                                      };
+                                                      for (i, e) in vec26.into_iter().enumerate() {
+                                                        let base = result26 as i32 + (i as i32) * 8;
+                                                        {
+                                                          let vec25 = e;
+                                                          let ptr25 = vec25.as_ptr() as i32;
+                                                          let len25 = vec25.len() as i32;
+                                                          *((base + 4) as *mut i32) = len25;
+                                                          *((base + 0) as *mut i32) = ptr25;
+                                                          
+                                                        }}
+                                                        *((base + 24) as *mut i32) = len26;
+                                                        *((base + 20) as *mut i32) = result26 as i32;
+                                                        cleanup_list.extend_from_slice(&[(result26, layout26),]);
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeU32(e) => {
+                                                        *((base + 16) as *mut u8) = (9i32) as u8;
+                                                        let vec27 = e;
+                                                        let ptr27 = vec27.as_ptr() as i32;
+                                                        let len27 = vec27.len() as i32;
+                                                        *((base + 24) as *mut i32) = len27;
+                                                        *((base + 20) as *mut i32) = ptr27;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeU64(e) => {
+                                                        *((base + 16) as *mut u8) = (10i32) as u8;
+                                                        let vec28 = e;
+                                                        let ptr28 = vec28.as_ptr() as i32;
+                                                        let len28 = vec28.len() as i32;
+                                                        *((base + 24) as *mut i32) = len28;
+                                                        *((base + 20) as *mut i32) = ptr28;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeVec2(e) => {
+                                                        *((base + 16) as *mut u8) = (11i32) as u8;
+                                                        let vec29 = e;
+                                                        let ptr29 = vec29.as_ptr() as i32;
+                                                        let len29 = vec29.len() as i32;
+                                                        *((base + 24) as *mut i32) = len29;
+                                                        *((base + 20) as *mut i32) = ptr29;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeVec3(e) => {
+                                                        *((base + 16) as *mut u8) = (12i32) as u8;
+                                                        let vec30 = e;
+                                                        let ptr30 = vec30.as_ptr() as i32;
+                                                        let len30 = vec30.len() as i32;
+                                                        *((base + 24) as *mut i32) = len30;
+                                                        *((base + 20) as *mut i32) = ptr30;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeVec4(e) => {
+                                                        *((base + 16) as *mut u8) = (13i32) as u8;
+                                                        let vec31 = e;
+                                                        let ptr31 = vec31.as_ptr() as i32;
+                                                        let len31 = vec31.len() as i32;
+                                                        *((base + 24) as *mut i32) = len31;
+                                                        *((base + 20) as *mut i32) = ptr31;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeUvec2(e) => {
+                                                        *((base + 16) as *mut u8) = (14i32) as u8;
+                                                        let vec32 = e;
+                                                        let ptr32 = vec32.as_ptr() as i32;
+                                                        let len32 = vec32.len() as i32;
+                                                        *((base + 24) as *mut i32) = len32;
+                                                        *((base + 20) as *mut i32) = ptr32;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeUvec3(e) => {
+                                                        *((base + 16) as *mut u8) = (15i32) as u8;
+                                                        let vec33 = e;
+                                                        let ptr33 = vec33.as_ptr() as i32;
+                                                        let len33 = vec33.len() as i32;
+                                                        *((base + 24) as *mut i32) = len33;
+                                                        *((base + 20) as *mut i32) = ptr33;
+                                                        
+                                                      },
+                                                      super::component::VecValueParam::TypeUvec4(e) => {
+                                                        *((base + 16) as *mut u8) = (16i32) as u8;
+                                                        let vec34 = e;
+                                                        let ptr34 = vec34.as_ptr() as i32;
+                                                        let len34 = vec34.len() as i32;
+                                                        *((base + 24) as *mut i32) = len34;
+                                                        *((base + 20) as *mut i32) = ptr34;
+                                                        
+                                                      },
+                                                    };
+                                                    
+                                                  },
+                                                  super::component::ValueParam::TypeOption(e) => {
+                                                    *((base + 8) as *mut u8) = (18i32) as u8;
+                                                    match e {
+                                                      super::component::OptionValueParam::TypeEmpty(e) => {
+                                                        *((base + 16) as *mut u8) = (0i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let () = e;
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeBool(e) => {
+                                                        *((base + 16) as *mut u8) = (1i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            *((base + 25) as *mut u8) = (match e { true => 1, false => 0 }) as u8;
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeEntityId(e) => {
+                                                        *((base + 16) as *mut u8) = (2i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::EntityId{ id0:id036, id1:id136, } = e;
+                                                            *((base + 32) as *mut i64) = wit_bindgen::rt::as_i64(id036);
+                                                            *((base + 40) as *mut i64) = wit_bindgen::rt::as_i64(id136);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeF32(e) => {
+                                                        *((base + 16) as *mut u8) = (3i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(e);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeF64(e) => {
+                                                        *((base + 16) as *mut u8) = (4i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            *((base + 32) as *mut f64) = wit_bindgen::rt::as_f64(e);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeMat4(e) => {
+                                                        *((base + 16) as *mut u8) = (5i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Mat4{ x:x37, y:y37, z:z37, w:w37, } = e;
+                                                            let super::types::Vec4{ x:x38, y:y38, z:z38, w:w38, } = x37;
+                                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(x38);
+                                                            *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(y38);
+                                                            *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(z38);
+                                                            *((base + 40) as *mut f32) = wit_bindgen::rt::as_f32(w38);
+                                                            let super::types::Vec4{ x:x39, y:y39, z:z39, w:w39, } = y37;
+                                                            *((base + 44) as *mut f32) = wit_bindgen::rt::as_f32(x39);
+                                                            *((base + 48) as *mut f32) = wit_bindgen::rt::as_f32(y39);
+                                                            *((base + 52) as *mut f32) = wit_bindgen::rt::as_f32(z39);
+                                                            *((base + 56) as *mut f32) = wit_bindgen::rt::as_f32(w39);
+                                                            let super::types::Vec4{ x:x40, y:y40, z:z40, w:w40, } = z37;
+                                                            *((base + 60) as *mut f32) = wit_bindgen::rt::as_f32(x40);
+                                                            *((base + 64) as *mut f32) = wit_bindgen::rt::as_f32(y40);
+                                                            *((base + 68) as *mut f32) = wit_bindgen::rt::as_f32(z40);
+                                                            *((base + 72) as *mut f32) = wit_bindgen::rt::as_f32(w40);
+                                                            let super::types::Vec4{ x:x41, y:y41, z:z41, w:w41, } = w37;
+                                                            *((base + 76) as *mut f32) = wit_bindgen::rt::as_f32(x41);
+                                                            *((base + 80) as *mut f32) = wit_bindgen::rt::as_f32(y41);
+                                                            *((base + 84) as *mut f32) = wit_bindgen::rt::as_f32(z41);
+                                                            *((base + 88) as *mut f32) = wit_bindgen::rt::as_f32(w41);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeI32(e) => {
+                                                        *((base + 16) as *mut u8) = (6i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(e);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeQuat(e) => {
+                                                        *((base + 16) as *mut u8) = (7i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Quat{ x:x42, y:y42, z:z42, w:w42, } = e;
+                                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(x42);
+                                                            *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(y42);
+                                                            *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(z42);
+                                                            *((base + 40) as *mut f32) = wit_bindgen::rt::as_f32(w42);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeString(e) => {
+                                                        *((base + 16) as *mut u8) = (8i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let vec43 = e;
+                                                            let ptr43 = vec43.as_ptr() as i32;
+                                                            let len43 = vec43.len() as i32;
+                                                            *((base + 32) as *mut i32) = len43;
+                                                            *((base + 28) as *mut i32) = ptr43;
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeU32(e) => {
+                                                        *((base + 16) as *mut u8) = (9i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(e);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeU64(e) => {
+                                                        *((base + 16) as *mut u8) = (10i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            *((base + 32) as *mut i64) = wit_bindgen::rt::as_i64(e);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeVec2(e) => {
+                                                        *((base + 16) as *mut u8) = (11i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Vec2{ x:x44, y:y44, } = e;
+                                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(x44);
+                                                            *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(y44);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeVec3(e) => {
+                                                        *((base + 16) as *mut u8) = (12i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Vec3{ x:x45, y:y45, z:z45, } = e;
+                                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(x45);
+                                                            *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(y45);
+                                                            *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(z45);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeVec4(e) => {
+                                                        *((base + 16) as *mut u8) = (13i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Vec4{ x:x46, y:y46, z:z46, w:w46, } = e;
+                                                            *((base + 28) as *mut f32) = wit_bindgen::rt::as_f32(x46);
+                                                            *((base + 32) as *mut f32) = wit_bindgen::rt::as_f32(y46);
+                                                            *((base + 36) as *mut f32) = wit_bindgen::rt::as_f32(z46);
+                                                            *((base + 40) as *mut f32) = wit_bindgen::rt::as_f32(w46);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeUvec2(e) => {
+                                                        *((base + 16) as *mut u8) = (14i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Uvec2{ x:x47, y:y47, } = e;
+                                                            *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(x47);
+                                                            *((base + 32) as *mut i32) = wit_bindgen::rt::as_i32(y47);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeUvec3(e) => {
+                                                        *((base + 16) as *mut u8) = (15i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Uvec3{ x:x48, y:y48, z:z48, } = e;
+                                                            *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(x48);
+                                                            *((base + 32) as *mut i32) = wit_bindgen::rt::as_i32(y48);
+                                                            *((base + 36) as *mut i32) = wit_bindgen::rt::as_i32(z48);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                      super::component::OptionValueParam::TypeUvec4(e) => {
+                                                        *((base + 16) as *mut u8) = (16i32) as u8;
+                                                        match e {
+                                                          Some(e) => {
+                                                            *((base + 24) as *mut u8) = (1i32) as u8;
+                                                            let super::types::Uvec4{ x:x49, y:y49, z:z49, w:w49, } = e;
+                                                            *((base + 28) as *mut i32) = wit_bindgen::rt::as_i32(x49);
+                                                            *((base + 32) as *mut i32) = wit_bindgen::rt::as_i32(y49);
+                                                            *((base + 36) as *mut i32) = wit_bindgen::rt::as_i32(z49);
+                                                            *((base + 40) as *mut i32) = wit_bindgen::rt::as_i32(w49);
+                                                            
+                                                          },
+                                                          None => {
+                                                            {
+                                                              *((base + 24) as *mut u8) = (0i32) as u8;
+                                                              
+                                                            }
+                                                          },
+                                                        };
+                                                      },
+                                                    };
+                                                    
+                                                  },
+                                                };
+                                                
+                                              }}
+                                              
+                                              #[link(wasm_import_module = "server-instance")]
+                                              extern "C" {
+                                                #[cfg_attr(target_arch = "wasm32", link_name = "send")]
+                                                #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-instance_send")]
+                                                fn wit_import(
+                                                _: i32, _: i32, _: i32, _: i32, _: i32, _: i32, );
+                                              }
+                                              wit_import(ptr51, len51, ptr0, len0, result50 as i32, len50);
+                                              if layout50.size() != 0 {
+                                                alloc::dealloc(result50, layout50);
+                                              }
+                                              for (ptr, layout) in cleanup_list {
+                                                
+                                                if layout.size() != 0 {
+                                                  
+                                                  alloc::dealloc(ptr, layout);
+                                                  
+                                                }
+                                                
+                                              }
+                                            }
+                                          }
+                                    
+                                  }
+                                  
+                                  
+                                  #[allow(clippy::all)]
+                                  pub mod server_validation{
+                                    #[allow(clippy::all)]
+                                    pub fn read_only(component: u32,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[link(wasm_import_module = "server-validation")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "read-only")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-validation_read-only")]
+                                          fn wit_import(
+                                          _: i32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i32(component));
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn max_speed(component: u32,max_speed: f32,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[link(wasm_import_module = "server-validation")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "max-speed")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-validation_max-speed")]
+                                          fn wit_import(
+                                          _: i32, _: f32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_i32(component), wit_bindgen::rt::as_f32(max_speed));
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn limit_input_rate(max_per_second: f32,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        
+                                        #[link(wasm_import_module = "server-validation")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "limit-input-rate")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-validation_limit-input-rate")]
+                                          fn wit_import(
+                                          _: f32, );
+                                        }
+                                        wit_import(wit_bindgen::rt::as_f32(max_per_second));
+                                      }
+                                    }
+                                    #[allow(clippy::all)]
+                                    pub fn kick_after(violations: Option<u32>,){
+                                      
+                                      #[allow(unused_imports)]
+                                      use wit_bindgen::rt::{{alloc, vec::Vec, string::String}};
+                                      unsafe {
+                                        let (result0_0,result0_1,) = match violations {
+                                          Some(e) => (1i32, wit_bindgen::rt::as_i32(e)),
+                                          None => {
+                                            (0i32, 0i32)
+                                          },
+                                        };
+                                        #[link(wasm_import_module = "server-validation")]
+                                        extern "C" {
+                                          #[cfg_attr(target_arch = "wasm32", link_name = "kick-after")]
+                                          #[cfg_attr(not(target_arch = "wasm32"), link_name = "server-validation_kick-after")]
+                                          fn wit_import(
+                                          _: i32, _: i32, );
+                                        }
+                                        wit_import(result0_0, result0_1);
+                                      }
+                                    }
+                                    
+                                  }
+                                  
+                                  
                                   #[allow(clippy::all)]
                                   pub mod guest{
                                     pub type Entity = super::component::EntityResult;
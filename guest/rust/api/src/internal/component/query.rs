@@ -164,6 +164,24 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> ChangeQuery<Component
         self.build().bind_async(callback)
     }
 
+    /// Each frame the results of the query change, the `callback` (`fn`) is called with only
+    /// the entities that were added, changed or removed since the last call.
+    ///
+    /// Unlike [bind](Self::bind), the host keeps track of the changes, so the module isn't run
+    /// at all on frames where nothing changed. Entities are only reported as changed when
+    /// components marked by [track_change](Self::track_change) change; without any, only
+    /// additions and removals are reported.
+    pub fn bind_delta(
+        self,
+        callback: impl Fn(QueryDelta<Components::Data>) + 'static,
+    ) -> QueryDeltaHandle {
+        QueryImpl::<Components>::new(
+            self.0
+                .build_impl(&self.1, wit::component::QueryEvent::Frame),
+        )
+        .subscribe(callback)
+    }
+
     fn build(self) -> QueryImpl<Components> {
         assert!(
             !self.1.is_empty(),
@@ -226,6 +244,26 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> EventQuery<Components
     }
 }
 
+/// The changes to the results of a query since they were last reported by [ChangeQuery::bind_delta].
+pub struct QueryDelta<Data> {
+    /// The entities that started matching the query, with their values.
+    pub added: Vec<(EntityId, Data)>,
+    /// The entities whose tracked components changed, with their new values.
+    pub changed: Vec<(EntityId, Data)>,
+    /// The entities that stopped matching the query, or were despawned.
+    pub removed: Vec<EntityId>,
+}
+
+/// Handle to a [ChangeQuery::bind_delta] subscription, which can be canceled by calling `.stop`
+pub struct QueryDeltaHandle(u64, OnHandle);
+impl QueryDeltaHandle {
+    /// Stops listening, and stops the host from tracking the changes
+    pub fn stop(self) {
+        wit::component::query_unsubscribe(self.0);
+        self.1.stop();
+    }
+}
+
 #[derive(Clone, Copy)]
 struct QueryImpl<Components: ComponentsTuple + Copy + Clone + 'static>(
     u64,
@@ -237,7 +275,13 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> QueryImpl<Components>
     }
 
     fn evaluate(&self) -> Vec<(EntityId, Components::Data)> {
-        wit::component::query_eval(self.0)
+        Self::convert(wit::component::query_eval(self.0))
+    }
+
+    fn convert(
+        results: Vec<(wit::types::EntityId, Vec<wit::component::ValueResult>)>,
+    ) -> Vec<(EntityId, Components::Data)> {
+        results
             .into_iter()
             .map(|(id, components)| {
                 (
@@ -249,6 +293,28 @@ impl<Components: ComponentsTuple + Copy + Clone + 'static> QueryImpl<Components>
             .collect()
     }
 
+    fn subscribe(
+        self,
+        callback: impl Fn(QueryDelta<Components::Data>) + 'static,
+    ) -> QueryDeltaHandle {
+        use wit::component::QueryDeltaKind;
+
+        wit::component::query_subscribe(self.0);
+        let handle = on(&format!("core/query_delta/{}", self.0), move |_| {
+            let take = |kind| wit::component::query_take_delta(self.0, kind);
+            callback(QueryDelta {
+                added: Self::convert(take(QueryDeltaKind::Added)),
+                changed: Self::convert(take(QueryDeltaKind::Changed)),
+                removed: take(QueryDeltaKind::Removed)
+                    .into_iter()
+                    .map(|(id, _)| id.from_bindgen())
+                    .collect(),
+            });
+            EventOk
+        });
+        QueryDeltaHandle(self.0, handle)
+    }
+
     fn bind(self, callback: impl Fn(Vec<(EntityId, Components::Data)>) + 'static) -> OnHandle {
         on(event::FRAME, move |_| {
             let results = self.evaluate();
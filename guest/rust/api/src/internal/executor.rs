@@ -4,11 +4,16 @@ use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    time::{Duration, Instant},
 };
 
 use once_cell::sync::Lazy;
 
-use crate::{global::EventResult, internal::component::Entity};
+use crate::{
+    event,
+    global::{EventResult, TaskPriority},
+    internal::component::Entity,
+};
 use rand::random;
 
 type EventFuture = Pin<Box<dyn Future<Output = EventResult>>>;
@@ -25,13 +30,19 @@ static RAW_WAKER: RawWakerVTable = RawWakerVTable::new(
     |_| {},
 );
 
+struct Task {
+    priority: TaskPriority,
+    future: EventFuture,
+}
+
 pub(crate) struct Executor {
     waker: Waker,
-    current: RefCell<Vec<EventFuture>>,
-    incoming: RefCell<Vec<Pin<Box<dyn Future<Output = EventResult>>>>>,
+    current: RefCell<Vec<Task>>,
+    incoming: RefCell<Vec<Task>>,
     current_callbacks: RefCell<Callbacks>,
     incoming_callbacks: RefCell<Callbacks>,
     frame_state: RefCell<FrameState>,
+    budget: RefCell<FrameBudget>,
 }
 // WebAssembly, at time of writing, is single-threaded. This is a convenient little lie
 // to make it easy to use this in a global context.
@@ -47,11 +58,15 @@ impl Executor {
             current_callbacks: RefCell::new(Default::default()),
             incoming_callbacks: RefCell::new(Default::default()),
             frame_state: RefCell::new(Default::default()),
+            budget: RefCell::new(Default::default()),
         }
     }
 
     pub fn execute(&self, frame_state: FrameState, event_name: &str, components: &Entity) {
         *self.frame_state.borrow_mut() = frame_state;
+        if event_name == event::FRAME {
+            self.budget.borrow_mut().spent = Duration::ZERO;
+        }
 
         // Load all pending callbacks.
         {
@@ -89,7 +104,12 @@ impl Executor {
             }
 
             // This must be done as a separate step as `callback` could mutate `self.incoming`.
-            self.incoming.borrow_mut().append(&mut new_futures);
+            self.incoming
+                .borrow_mut()
+                .extend(new_futures.into_iter().map(|future| Task {
+                    priority: TaskPriority::Normal,
+                    future,
+                }));
         }
 
         // Load all pending futures into current.
@@ -99,22 +119,41 @@ impl Executor {
             current.append(&mut incoming);
         }
 
-        // Run all current futures.
+        // Run the current futures, most important first, until the budget of the frame is spent.
         // These are extracted to ensure that a panic will not result in the same
         // tasks being executed forever.
         {
-            let mut futures = std::mem::take(&mut *self.current.borrow_mut());
-            futures.retain_mut(
-                |f| match f.as_mut().poll(&mut Context::from_waker(&self.waker)) {
-                    Poll::Ready(Ok(_)) => false,
-                    Poll::Ready(Err(e)) => {
-                        eprintln!("Error while handling future: {e:?}");
-                        false
-                    }
-                    Poll::Pending => true,
-                },
-            );
-            *self.current.borrow_mut() = futures;
+            let mut tasks = std::mem::take(&mut *self.current.borrow_mut());
+            // The sort is stable, so tasks that were skipped stay ahead of those that were polled.
+            tasks.sort_by_key(|task| task.priority);
+
+            let limit = self.budget.borrow().limit;
+            let mut spent = self.budget.borrow().spent;
+            let mut skipped = vec![];
+            let mut polled = vec![];
+            for mut task in tasks {
+                let over_budget = limit.map_or(false, |limit| spent >= limit);
+                if over_budget && task.priority != TaskPriority::High {
+                    skipped.push(task);
+                    continue;
+                }
+
+                let start = Instant::now();
+                let result = task
+                    .future
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&self.waker));
+                spent += start.elapsed();
+                match result {
+                    Poll::Ready(Ok(_)) => {}
+                    Poll::Ready(Err(e)) => eprintln!("Error while handling future: {e:?}"),
+                    Poll::Pending => polled.push(task),
+                }
+            }
+            self.budget.borrow_mut().spent = spent;
+
+            skipped.append(&mut polled);
+            *self.current.borrow_mut() = skipped;
         }
     }
 
@@ -165,11 +204,28 @@ impl Executor {
         }
     }
 
-    pub fn spawn(&self, fut: EventFuture) {
-        self.incoming.borrow_mut().push(fut);
+    pub fn spawn(&self, priority: TaskPriority, future: EventFuture) {
+        self.incoming.borrow_mut().push(Task { priority, future });
+    }
+
+    pub fn set_frame_budget(&self, limit: Option<Duration>) {
+        self.budget.borrow_mut().limit = limit;
+    }
+
+    pub fn frame_budget_exceeded(&self) -> bool {
+        let budget = self.budget.borrow();
+        budget.limit.map_or(false, |limit| budget.spent >= limit)
     }
 }
 
+/// How long the tasks may be polled for each frame
+#[derive(Default)]
+struct FrameBudget {
+    limit: Option<Duration>,
+    /// The time spent polling tasks since the start of the frame
+    spent: Duration,
+}
+
 #[derive(Default)]
 pub struct FrameState {
     time: f32,
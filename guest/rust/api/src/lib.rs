@@ -8,7 +8,9 @@
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
-/// Asset-related functionality, including retrieval of assets and where to find them.
+/// Animation-related functionality, including animation graphs and their parameters.
+pub mod animation;
+/// Asset-related functionality, including retrieval of assets, where to find them, and prefetching them.
 pub mod asset;
 /// ECS-related functionality not directly related to entities.
 pub mod ecs;
@@ -20,13 +22,33 @@ pub mod event;
 pub mod global;
 /// Player-related functionality.
 pub mod player;
+/// Splines through control points, e.g. for AI agents following the roads and rivers generated along them.
+pub mod spline;
 
+/// World instance functionality, including creating instances, moving players between them, and messaging them.
+#[cfg(feature = "server")]
+pub mod instance;
 /// Physics-related functionality, including applying forces, changing physical properties, and more.
 #[cfg(feature = "server")]
 pub mod physics;
+/// Validation of what clients send, applied before it reaches the world; for example, limiting how fast players can move.
+#[cfg(feature = "server")]
+pub mod validation;
 
+/// Debug drawing of lines, boxes, spheres and text that last for a duration, in categories that can be toggled from the debugger.
+#[cfg(feature = "client")]
+pub mod gizmos;
+/// Procedural meshes, created and updated at runtime, e.g. for voxel terrain, trails and debug geometry.
+#[cfg(feature = "client")]
+pub mod mesh;
 /// Helpful imports that almost all Ambient projects will use.
 pub mod prelude;
+/// Dynamic textures, created and updated at runtime, e.g. for minimaps, procedural skins and video-like surfaces.
+#[cfg(feature = "client")]
+pub mod texture;
+/// Window-related functionality, including the clipboard, files dropped into the window or opened with a dialog, screenshots and recordings, and the settings declared by packages.
+#[cfg(feature = "client")]
+pub mod window;
 
 /// Internal implementation details.
 mod internal;
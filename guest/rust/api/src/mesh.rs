@@ -0,0 +1,66 @@
+use crate::{
+    global::{Vec2, Vec3},
+    internal::{conversion::IntoBindgen, wit},
+};
+
+fn convert<T: IntoBindgen + Copy>(values: &[T]) -> Vec<T::Item> {
+    values.iter().map(|value| value.into_bindgen()).collect()
+}
+
+/// Creates a mesh from its vertices and triangles, e.g. for voxel terrain or trails, and returns
+/// its handle. Entities are rendered with it by setting their
+/// [procedural_mesh](crate::components::core::primitives::procedural_mesh) component to the handle.
+///
+/// `indices` lists the vertices of each triangle. `normals` and `texcoords` have one value per
+/// position, or are empty; the normals are then computed from the triangles.
+pub fn create(positions: &[Vec3], normals: &[Vec3], texcoords: &[Vec2], indices: &[u32]) -> u64 {
+    wit::client_mesh::create(
+        &convert(positions),
+        &convert(normals),
+        &convert(texcoords),
+        indices,
+    )
+}
+
+/// Replaces the vertices and triangles of the mesh with `handle`, as in [create].
+pub fn update(
+    handle: u64,
+    positions: &[Vec3],
+    normals: &[Vec3],
+    texcoords: &[Vec2],
+    indices: &[u32],
+) {
+    wit::client_mesh::update(
+        handle,
+        &convert(positions),
+        &convert(normals),
+        &convert(texcoords),
+        indices,
+    )
+}
+
+/// Overwrites the vertices of the mesh with `handle` from `offset`, which is cheaper than [update]
+/// for meshes which change every frame.
+///
+/// The number of vertices can't change: the lists must fit in the mesh. Empty lists are left as they are.
+pub fn update_vertices(
+    handle: u64,
+    offset: u32,
+    positions: &[Vec3],
+    normals: &[Vec3],
+    texcoords: &[Vec2],
+) {
+    wit::client_mesh::update_vertices(
+        handle,
+        offset,
+        &convert(positions),
+        &convert(normals),
+        &convert(texcoords),
+    )
+}
+
+/// Destroys the mesh with `handle`. The entities which render it keep doing so until their
+/// `procedural_mesh` changes.
+pub fn destroy(handle: u64) {
+    wit::client_mesh::destroy(handle)
+}
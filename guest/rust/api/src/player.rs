@@ -496,3 +496,117 @@ pub fn get_raw_input_delta(player_id: EntityId) -> Option<(RawInputDelta, RawInp
     let (p, c) = get_prev_and_current_raw_input(player_id)?;
     Some((c.delta(&p), c))
 }
+
+/// A button on a gamepad, named after its position on a standard layout.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GamepadButton {
+    /// The bottom face button; A on Xbox controllers, Cross on PlayStation controllers.
+    South,
+    /// The right face button.
+    East,
+    /// The top face button.
+    North,
+    /// The left face button.
+    West,
+    /// The left shoulder button.
+    LeftBumper,
+    /// The right shoulder button.
+    RightBumper,
+    /// The left trigger, when pressed far enough. See [Gamepad::left_trigger] for how far it's pressed.
+    LeftTrigger,
+    /// The right trigger, when pressed far enough. See [Gamepad::right_trigger] for how far it's pressed.
+    RightTrigger,
+    /// The select or back button.
+    Select,
+    /// The start button.
+    Start,
+    /// The button in the middle, like the Xbox or PS button.
+    Mode,
+    /// Pressing down the left stick.
+    LeftStick,
+    /// Pressing down the right stick.
+    RightStick,
+    /// Up on the directional pad.
+    DPadUp,
+    /// Down on the directional pad.
+    DPadDown,
+    /// Left on the directional pad.
+    DPadLeft,
+    /// Right on the directional pad.
+    DPadRight,
+}
+impl FromBindgen for wit::server_player::GamepadButton {
+    type Item = GamepadButton;
+
+    fn from_bindgen(self) -> Self::Item {
+        match self {
+            Self::South => Self::Item::South,
+            Self::East => Self::Item::East,
+            Self::North => Self::Item::North,
+            Self::West => Self::Item::West,
+            Self::LeftBumper => Self::Item::LeftBumper,
+            Self::RightBumper => Self::Item::RightBumper,
+            Self::LeftTrigger => Self::Item::LeftTrigger,
+            Self::RightTrigger => Self::Item::RightTrigger,
+            Self::Select => Self::Item::Select,
+            Self::Start => Self::Item::Start,
+            Self::Mode => Self::Item::Mode,
+            Self::LeftStick => Self::Item::LeftStick,
+            Self::RightStick => Self::Item::RightStick,
+            Self::DpadUp => Self::Item::DPadUp,
+            Self::DpadDown => Self::Item::DPadDown,
+            Self::DpadLeft => Self::Item::DPadLeft,
+            Self::DpadRight => Self::Item::DPadRight,
+        }
+    }
+}
+
+/// The state of one of a player's gamepads. Get these with [get_gamepads].
+///
+/// To react to changes as they happen on the client, listen to the `GAMEPAD_*` events instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gamepad {
+    /// Identifies the gamepad among the player's gamepads. IDs may be reused after a gamepad is disconnected.
+    pub id: u32,
+    /// The name of the gamepad, as reported by the player's platform.
+    pub name: String,
+    /// All of the buttons being pressed this frame.
+    pub buttons: HashSet<GamepadButton>,
+    /// The position of the left stick, from -1 to 1 on each axis. Up is positive.
+    pub left_stick: Vec2,
+    /// The position of the right stick, from -1 to 1 on each axis. Up is positive.
+    pub right_stick: Vec2,
+    /// How far the left trigger is pressed, from 0 to 1.
+    pub left_trigger: f32,
+    /// How far the right trigger is pressed, from 0 to 1.
+    pub right_trigger: f32,
+}
+impl FromBindgen for wit::server_player::Gamepad {
+    type Item = Gamepad;
+    fn from_bindgen(self) -> Self::Item {
+        Self::Item {
+            id: self.id,
+            name: self.name,
+            buttons: self.buttons.into_iter().map(|b| b.from_bindgen()).collect(),
+            left_stick: self.left_stick.from_bindgen(),
+            right_stick: self.right_stick.from_bindgen(),
+            left_trigger: self.left_trigger,
+            right_trigger: self.right_trigger,
+        }
+    }
+}
+
+/// Gets the gamepads `player_id` has connected, as of their most recent input.
+#[cfg(feature = "server")]
+pub fn get_gamepads(player_id: EntityId) -> Vec<Gamepad> {
+    wit::server_player::get_gamepads(player_id.into_bindgen()).from_bindgen()
+}
+
+/// Rumbles `player_id`'s `gamepad`, or all of their gamepads if `None`, for `duration` seconds.
+///
+/// `strong` and `weak` are the strengths of the low- and high-frequency motors, from 0 to 1.
+/// Gamepads that can't rumble are ignored, and browsers only support a single strength.
+#[cfg(feature = "server")]
+pub fn rumble(player_id: EntityId, gamepad: Option<u32>, strong: f32, weak: f32, duration: f32) {
+    wit::server_player::rumble(player_id.into_bindgen(), gamepad, strong, weak, duration)
+}
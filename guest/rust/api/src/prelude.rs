@@ -1,5 +1,5 @@
 pub use crate::{
-    asset,
+    animation, asset,
     ecs::{change_query, despawn_query, query, spawn_query, Component, Entity, QueryEvent},
     entity, event,
     global::*,
@@ -10,4 +10,6 @@ pub use glam;
 pub use rand::prelude::*;
 
 #[cfg(feature = "server")]
-pub use crate::physics;
+pub use crate::{instance, physics};
+#[cfg(feature = "client")]
+pub use crate::{gizmos, mesh, texture, window};
@@ -0,0 +1,167 @@
+use crate::{
+    components::core::primitives::{spline_closed, spline_points},
+    entity,
+    global::{EntityId, Vec3},
+};
+
+/// A Catmull-Rom spline, which goes through all of its points, evaluated the same way as the roads
+/// and rivers the host generates along [spline_points].
+///
+/// It is parametrized by `t`, which goes from 0 at the first point to [Spline::segments] at the last one.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Spline {
+    /// The control points of the spline.
+    pub points: Vec<Vec3>,
+    /// Whether the spline loops from its last point back to its first.
+    pub closed: bool,
+}
+impl Spline {
+    /// Creates a spline through `points`.
+    pub fn new(points: Vec<Vec3>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    /// Reads the spline of `entity` from its [spline_points] and [spline_closed], if it has one.
+    pub fn from_entity(entity: EntityId) -> Option<Self> {
+        let points = entity::get_component(entity, spline_points())?;
+        let closed = entity::get_component(entity, spline_closed()).unwrap_or(false);
+        Some(Self::new(points, closed))
+    }
+
+    /// The number of curves between the points, which is the largest value of `t`.
+    pub fn segments(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n - 1,
+        }
+    }
+
+    fn point(&self, index: isize) -> Vec3 {
+        let n = self.points.len() as isize;
+        if self.closed {
+            self.points[index.rem_euclid(n) as usize]
+        } else {
+            self.points[index.clamp(0, n - 1) as usize]
+        }
+    }
+
+    fn segment(&self, t: f32) -> (isize, f32) {
+        let segments = self.segments();
+        let t = if self.closed {
+            t.rem_euclid(segments as f32)
+        } else {
+            t.clamp(0., segments as f32)
+        };
+        let index = (t.floor() as usize).min(segments - 1);
+        (index as isize, t - index as f32)
+    }
+
+    fn coefficients(&self, index: isize) -> [Vec3; 4] {
+        let [p0, p1, p2, p3] = [index - 1, index, index + 1, index + 2].map(|i| self.point(i));
+        [
+            2. * p1,
+            p2 - p0,
+            2. * p0 - 5. * p1 + 4. * p2 - p3,
+            -p0 + 3. * p1 - 3. * p2 + p3,
+        ]
+    }
+
+    /// The point of the spline at `t`.
+    pub fn evaluate(&self, t: f32) -> Vec3 {
+        match self.points.len() {
+            0 => Vec3::ZERO,
+            1 => self.points[0],
+            _ => {
+                let (index, u) = self.segment(t);
+                let [a, b, c, d] = self.coefficients(index);
+                0.5 * (a + b * u + c * u * u + d * u * u * u)
+            }
+        }
+    }
+
+    /// The normalized direction of the spline at `t`.
+    pub fn tangent(&self, t: f32) -> Vec3 {
+        if self.points.len() < 2 {
+            return Vec3::X;
+        }
+        let (index, u) = self.segment(t);
+        let [_, b, c, d] = self.coefficients(index);
+        let tangent = 0.5 * (b + 2. * c * u + 3. * d * u * u);
+        tangent
+            .try_normalize()
+            .unwrap_or_else(|| (self.point(index + 1) - self.point(index)).normalize_or_zero())
+    }
+
+    /// The approximate length of the spline, in metres.
+    pub fn length(&self) -> f32 {
+        self.distances()
+            .last()
+            .map_or(0., |(_, distance)| *distance)
+    }
+
+    /// The value of `t` about `distance` metres along the spline from its start, e.g. to move an
+    /// agent along it at a constant speed.
+    pub fn t_at_distance(&self, distance: f32) -> f32 {
+        let distances = self.distances();
+        let distance = match (self.closed, distances.last()) {
+            (true, Some((_, length))) if *length > 0. => distance.rem_euclid(*length),
+            _ => distance,
+        };
+        for ((t0, d0), (t1, d1)) in distances.iter().zip(distances.iter().skip(1)) {
+            if distance <= *d1 {
+                return t0 + (t1 - t0) * ((distance - d0) / (d1 - d0).max(f32::EPSILON)).max(0.);
+            }
+        }
+        self.segments() as f32
+    }
+
+    /// The parameter `t` of the point of the spline closest to `point`, and that point; for
+    /// example, to find where an agent joins a road.
+    pub fn closest(&self, point: Vec3) -> (f32, Vec3) {
+        if self.points.len() < 2 {
+            return (0., self.evaluate(0.));
+        }
+        const STEPS: usize = 16;
+        let distance = |t: f32| self.evaluate(t).distance_squared(point);
+        let coarse = (0..=self.segments() * STEPS)
+            .map(|i| i as f32 / STEPS as f32)
+            .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+            .unwrap_or_default();
+        // Refine it with a ternary search around the closest sample
+        let (mut min, mut max) = (coarse - 1. / STEPS as f32, coarse + 1. / STEPS as f32);
+        if !self.closed {
+            (min, max) = (min.max(0.), max.min(self.segments() as f32));
+        }
+        for _ in 0..20 {
+            let (a, b) = (min + (max - min) / 3., max - (max - min) / 3.);
+            if distance(a) < distance(b) {
+                max = b;
+            } else {
+                min = a;
+            }
+        }
+        let t = (min + max) / 2.;
+        let t = if self.closed {
+            t.rem_euclid(self.segments() as f32)
+        } else {
+            t
+        };
+        (t, self.evaluate(t))
+    }
+
+    /// The distance along the spline at regular values of `t`.
+    fn distances(&self) -> Vec<(f32, f32)> {
+        const STEPS: usize = 16;
+        let mut distances = vec![(0., 0.)];
+        let mut last = self.evaluate(0.);
+        for i in 1..=self.segments() * STEPS {
+            let t = i as f32 / STEPS as f32;
+            let position = self.evaluate(t);
+            let distance = distances.last().unwrap().1 + position.distance(last);
+            distances.push((t, distance));
+            last = position;
+        }
+        distances
+    }
+}
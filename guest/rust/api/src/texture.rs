@@ -0,0 +1,24 @@
+use crate::{global::UVec2, internal::wit};
+
+/// Creates a texture of `size` pixels, e.g. for a minimap or a procedural skin, and returns its
+/// handle. Entities display it by setting their
+/// [texture_handle](crate::components::core::rendering::texture_handle) component to the handle.
+///
+/// `pixels` are rows of RGBA pixels in sRGB, with 4 bytes per pixel, or empty for a transparent
+/// black texture.
+pub fn create(size: UVec2, pixels: &[u8]) -> u64 {
+    wit::client_texture::create(size.x, size.y, pixels)
+}
+
+/// Overwrites the region of `size` pixels at `origin` of the texture with `handle` with `pixels`,
+/// laid out as in [create]. Only the region is uploaded, so this is cheap enough to call every frame
+/// for small regions, e.g. for video-like surfaces.
+pub fn update(handle: u64, origin: UVec2, size: UVec2, pixels: &[u8]) {
+    wit::client_texture::update(handle, origin.x, origin.y, size.x, size.y, pixels)
+}
+
+/// Destroys the texture with `handle`. The entities which display it keep doing so until their
+/// `texture_handle` changes.
+pub fn destroy(handle: u64) {
+    wit::client_texture::destroy(handle)
+}
@@ -0,0 +1,27 @@
+use crate::{
+    global::Vec3,
+    internal::{
+        component::{Component, UntypedComponent},
+        wit,
+    },
+};
+
+/// Rejects all changes clients make to `component`. Changes from this module and the server are unaffected.
+pub fn read_only(component: impl UntypedComponent) {
+    wit::server_validation::read_only(component.index())
+}
+
+/// Rejects changes clients make to `component` that move it faster than `max_speed` units per second.
+pub fn max_speed(component: Component<Vec3>, max_speed: f32) {
+    wit::server_validation::max_speed(component.index(), max_speed)
+}
+
+/// Rejects the input of players that send it more than `max_per_second` times per second, allowing bursts of up to a second's worth.
+pub fn limit_input_rate(max_per_second: f32) {
+    wit::server_validation::limit_input_rate(max_per_second)
+}
+
+/// Kicks players once they've made `violations` violations. If `None`, which is the default, violations are only logged.
+pub fn kick_after(violations: Option<u32>) {
+    wit::server_validation::kick_after(violations)
+}
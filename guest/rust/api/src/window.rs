@@ -0,0 +1,58 @@
+use std::str::FromStr;
+
+use crate::internal::wit;
+
+/// Returns the text in the clipboard, if any.
+pub fn get_clipboard() -> Option<String> {
+    wit::client_window::get_clipboard()
+}
+
+/// Replaces the contents of the clipboard with `text`.
+pub fn set_clipboard(text: impl AsRef<str>) {
+    wit::client_window::set_clipboard(text.as_ref())
+}
+
+/// Shows a dialog for the user to pick a file with one of `extensions` (like `"png"`), or any file if it's empty.
+///
+/// If they pick one, a [FILE_OPENED](crate::event::FILE_OPENED) event is sent with it, which can be read with [read_file].
+pub fn open_file_dialog(extensions: &[&str]) {
+    wit::client_window::open_file_dialog(extensions)
+}
+
+/// Returns the contents of the file with `handle`, from a [FILE_OPENED](crate::event::FILE_OPENED)
+/// or [WINDOW_FILE_DROPPED](crate::event::WINDOW_FILE_DROPPED) event.
+///
+/// Only the most recent files are kept, so this should be called when the event is received.
+pub fn read_file(handle: u64) -> Option<Vec<u8>> {
+    wit::client_window::read_file(handle)
+}
+
+/// Saves the next frame as a PNG named `name`, or after the current time if it's `None`.
+///
+/// On native, it's saved in the `screenshots` directory; in browsers, it's downloaded instead.
+pub fn screenshot(name: Option<&str>) {
+    wit::client_window::screenshot(name)
+}
+
+/// Starts recording the frames at `fps` into a video named `name`, or after the current time if it's `None`.
+///
+/// The recording stops after `seconds`, or when [stop_recording] is called if it's `None`. It's saved in the `recordings`
+/// directory, as an mp4 if `ffmpeg` is installed and as a sequence of PNGs otherwise.
+pub fn start_recording(name: Option<&str>, fps: f32, seconds: Option<f32>) {
+    wit::client_window::start_recording(name, fps, seconds)
+}
+
+/// Stops the recording started with [start_recording].
+pub fn stop_recording() {
+    wit::client_window::stop_recording()
+}
+
+/// Returns the user's value of the setting `key` declared in the manifest of `package`, or its default if they haven't changed it.
+///
+/// Returns `None` if the package doesn't declare the setting, or if it isn't a `T`. A [SETTINGS_CHANGED](crate::event::SETTINGS_CHANGED)
+/// event is sent when the user changes it.
+pub fn get_setting<T: FromStr>(package: impl AsRef<str>, key: impl AsRef<str>) -> Option<T> {
+    wit::client_window::get_setting(package.as_ref(), key.as_ref())?
+        .parse()
+        .ok()
+}
@@ -3,6 +3,10 @@ use ambient_api::prelude::*;
 #[main]
 pub async fn main() -> EventResult {
     use ambient_api::{
+        animation::{
+            AnimationComparison, AnimationGraph, AnimationMotion, AnimationState,
+            AnimationTransition,
+        },
         components::core::{
             app::main_scene,
             camera::aspect_ratio_from_window,
@@ -13,7 +17,6 @@ pub async fn main() -> EventResult {
             transform::{lookat_center, scale, translation},
         },
         concepts::{make_perspective_infinite_reverse_camera, make_transformable},
-        entity::{AnimationAction, AnimationController},
         player::KeyCode,
     };
 
@@ -40,17 +43,33 @@ pub async fn main() -> EventResult {
         )
         .spawn();
 
-    entity::set_animation_controller(
+    let capoeira = asset::url("assets/Capoeira.fbx/animations/mixamo.com.anim").unwrap();
+    let robot = asset::url("assets/Robot Hip Hop Dance.fbx/animations/mixamo.com.anim").unwrap();
+    // Keys 1, 2 and 3 set the `dance` parameter, which the transitions crossfade between the states by
+    let dance_transition = |to: &str, dance: f32| {
+        AnimationTransition::new(None, to)
+            .when("dance", AnimationComparison::Equal, dance)
+            .with_duration(0.5)
+    };
+    animation::set_graph(
         unit_id,
-        AnimationController {
-            actions: &[AnimationAction {
-                clip_url: &asset::url("assets/Capoeira.fbx/animations/mixamo.com.anim").unwrap(),
-                looping: true,
-                weight: 1.,
-            }],
-            apply_base_pose: false,
-        },
+        &AnimationGraph::new([
+            AnimationState::new("capoeira", AnimationMotion::Clip(capoeira.clone())),
+            AnimationState::new("robot", AnimationMotion::Clip(robot.clone())),
+            AnimationState::new(
+                "blend",
+                AnimationMotion::Blend1d {
+                    parameter: "blend".to_string(),
+                    points: vec![(0., robot), (1., capoeira)],
+                },
+            ),
+        ])
+        .with_transition(dance_transition("robot", 1.))
+        .with_transition(dance_transition("capoeira", 2.))
+        .with_transition(dance_transition("blend", 3.)),
     );
+    animation::set_parameter(unit_id, "dance", 2.);
+    animation::set_parameter(unit_id, "blend", 0.5);
 
     query(player()).build().each_frame(move |players| {
         for (player, _) in players {
@@ -61,63 +80,14 @@ pub async fn main() -> EventResult {
 
             entity::set_component(camera_id, lookat_center(), camera_position);
 
-            if delta.keys.contains(&KeyCode::Key1) {
-                entity::set_animation_controller(
-                    unit_id,
-                    AnimationController {
-                        actions: &[AnimationAction {
-                            clip_url: &asset::url(
-                                "assets/Robot Hip Hop Dance.fbx/animations/mixamo.com.anim",
-                            )
-                            .unwrap(),
-                            looping: true,
-                            weight: 1.,
-                        }],
-                        apply_base_pose: false,
-                    },
-                );
-            }
-
-            if delta.keys.contains(&KeyCode::Key2) {
-                entity::set_animation_controller(
-                    unit_id,
-                    AnimationController {
-                        actions: &[AnimationAction {
-                            clip_url: &asset::url("assets/Capoeira.fbx/animations/mixamo.com.anim")
-                                .unwrap(),
-                            looping: true,
-                            weight: 1.,
-                        }],
-                        apply_base_pose: false,
-                    },
-                );
-            }
-
-            if delta.keys.contains(&KeyCode::Key3) {
-                entity::set_animation_controller(
-                    unit_id,
-                    AnimationController {
-                        actions: &[
-                            AnimationAction {
-                                clip_url: &asset::url(
-                                    "assets/Robot Hip Hop Dance.fbx/animations/mixamo.com.anim",
-                                )
-                                .unwrap(),
-                                looping: true,
-                                weight: 0.5,
-                            },
-                            AnimationAction {
-                                clip_url: &asset::url(
-                                    "assets/Capoeira.fbx/animations/mixamo.com.anim",
-                                )
-                                .unwrap(),
-                                looping: true,
-                                weight: 0.5,
-                            },
-                        ],
-                        apply_base_pose: false,
-                    },
-                );
+            for (key, dance) in [
+                (KeyCode::Key1, 1.),
+                (KeyCode::Key2, 2.),
+                (KeyCode::Key3, 3.),
+            ] {
+                if delta.keys.contains(&key) {
+                    animation::set_parameter(unit_id, "dance", dance);
+                }
             }
         }
     });
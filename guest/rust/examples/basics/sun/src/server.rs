@@ -7,8 +7,10 @@ pub async fn main() -> EventResult {
             app::main_scene,
             camera::aspect_ratio_from_window,
             primitives::{quad, sphere_radius},
-            rendering::{cast_shadows, color, fog_density, light_diffuse, sky, sun, water},
-            transform::{lookat_center, rotation, scale, translation},
+            rendering::{
+                cast_shadows, color, day_length, day_night_cycle, fog_density, sky, sun, water,
+            },
+            transform::{lookat_center, scale, translation},
         },
         concepts::{make_perspective_infinite_reverse_camera, make_sphere, make_transformable},
     };
@@ -48,20 +50,15 @@ pub async fn main() -> EventResult {
         .with(color(), vec4(1., 1., 1., 1.))
         .spawn();
 
-    let sun = Entity::new()
+    // The sun's direction and light follow the time of day, which goes round in a minute
+    Entity::new()
         .with_merge(make_transformable())
         .with_default(sun())
-        .with_default(rotation())
+        .with_default(day_night_cycle())
         .with_default(main_scene())
-        .with(light_diffuse(), Vec3::ONE)
         .with(fog_density(), 0.)
         .spawn();
-
-    on(event::FRAME, move |_| {
-        let rot = entity::get_component(sun, rotation()).unwrap();
-        entity::set_component(sun, rotation(), rot * Quat::from_rotation_y(0.01));
-        EventOk
-    });
+    entity::add_component(entity::synchronized_resources(), day_length(), 60.);
 
     EventOk
 }
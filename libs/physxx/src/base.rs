@@ -1,5 +1,5 @@
 use crate::{
-    AsPxJoint, AsPxRigidActor, AsPxRigidBody, PxAggregateRef, PxArticulationLinkRef, PxConstraintRef, PxConvexMesh, PxFixedJointRef, PxHeightField, PxJointRef, PxMaterial, PxRevoluteJointRef, PxRigidActorRef, PxRigidBodyRef, PxRigidDynamicRef, PxRigidStaticRef, PxShape
+    AsPxJoint, AsPxRigidActor, AsPxRigidBody, PxAggregateRef, PxArticulationLinkRef, PxConstraintRef, PxConvexMesh, PxFixedJointRef, PxHeightField, PxJointRef, PxMaterial, PxRevoluteJointRef, PxRigidActorRef, PxRigidBodyRef, PxRigidDynamicRef, PxRigidStaticRef, PxShape, PxSphericalJointRef
 };
 
 pub trait AsPxBase: Sync + Send {
@@ -15,6 +15,7 @@ pub trait PxBase: Sync + Send + as_any::AsAny {
     fn to_joint(&self) -> Option<PxJointRef>;
     fn to_fixed_joint(&self) -> Option<PxFixedJointRef>;
     fn to_revolute_joint(&self) -> Option<PxRevoluteJointRef>;
+    fn to_spherical_joint(&self) -> Option<PxSphericalJointRef>;
 }
 impl<T: AsPxBase + 'static> PxBase for T {
     fn get_concrete_type(&self) -> u16 {
@@ -53,6 +54,7 @@ impl<T: AsPxBase + 'static> PxBase for T {
         match self.as_px_any() {
             PxAny::PxFixedJoint(o) => Some(o.as_joint()),
             PxAny::PxRevoluteJoint(o) => Some(o.as_joint()),
+            PxAny::PxSphericalJoint(o) => Some(o.as_joint()),
             _ => None,
         }
     }
@@ -68,6 +70,12 @@ impl<T: AsPxBase + 'static> PxBase for T {
             _ => None,
         }
     }
+    fn to_spherical_joint(&self) -> Option<PxSphericalJointRef> {
+        match self.as_px_any() {
+            PxAny::PxSphericalJoint(o) => Some(o),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -91,6 +99,7 @@ pub enum PxAny {
     PxShape(PxShape),
     PxFixedJoint(PxFixedJointRef),
     PxRevoluteJoint(PxRevoluteJointRef),
+    PxSphericalJoint(PxSphericalJointRef),
     PxConstraint(PxConstraintRef),
     PxArticulationLink(PxArticulationLinkRef),
 }
@@ -126,7 +135,7 @@ impl PxAny {
                 physx_sys::PxJointConcreteType::eLast => panic!("PhysX object type is not supported"),
                 physx_sys::PxJointConcreteType::ePRISMATIC => panic!("PhysX object type is not supported"),
                 physx_sys::PxJointConcreteType::eREVOLUTE => PxAny::PxRevoluteJoint(PxRevoluteJointRef(obj as _)),
-                physx_sys::PxJointConcreteType::eSPHERICAL => panic!("PhysX object type is not supported"),
+                physx_sys::PxJointConcreteType::eSPHERICAL => PxAny::PxSphericalJoint(PxSphericalJointRef(obj as _)),
 
                 _ => panic!("Unknown type"),
             }
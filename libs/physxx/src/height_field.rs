@@ -38,6 +38,10 @@ impl Drop for PxHeightField {
 }
 
 const PX_MIN_HEIGHTFIELD_Y_SCALE: f32 = 0.0001 / (0xFFFF as f32);
+/// The material index which makes a triangle of a height field a hole
+pub const PX_HEIGHT_FIELD_MATERIAL_HOLE: u8 = 127;
+/// The tesselation flag is stored in the highest bit of the first material index
+const PX_HEIGHT_FIELD_TESS_FLAG: u8 = 0x80;
 
 pub struct PxHeightFieldSample(physx_sys::PxHeightFieldSample);
 impl PxHeightFieldSample {
@@ -55,6 +59,15 @@ impl PxHeightFieldSample {
             unsafe { physx_sys::PxHeightFieldSample_clearTessFlag_mut(&mut self.0 as *mut physx_sys::PxHeightFieldSample) }
         }
     }
+    /// Sets the material of both triangles of the cell starting at this sample, keeping the tesselation flag
+    pub fn set_material_indices(&mut self, index0: u8, index1: u8) {
+        self.0.materialIndex0.mData = (self.0.materialIndex0.mData & PX_HEIGHT_FIELD_TESS_FLAG) | (index0 & !PX_HEIGHT_FIELD_TESS_FLAG);
+        self.0.materialIndex1.mData = index1 & !PX_HEIGHT_FIELD_TESS_FLAG;
+    }
+    /// Makes the cell starting at this sample a hole, which nothing collides with
+    pub fn set_hole(&mut self) {
+        self.set_material_indices(PX_HEIGHT_FIELD_MATERIAL_HOLE, PX_HEIGHT_FIELD_MATERIAL_HOLE);
+    }
 }
 
 // From: https://gameworksdocs.nvidia.com/PhysX/4.1/documentation/physxguide/Manual/BestPractices.html#quantizing-heightfield-samples
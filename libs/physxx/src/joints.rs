@@ -282,3 +282,92 @@ impl PxJointAngularLimitPair {
         }
     }
 }
+
+bitflags! {
+    #[derive(Serialize, Deserialize)]
+    pub struct PxSphericalJointFlag: u32 {
+        const LIMIT_ENABLED = physx_sys::PxSphericalJointFlag::eLIMIT_ENABLED;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PxSphericalJointRef(pub(crate) *mut physx_sys::PxSphericalJoint);
+impl PxSphericalJointRef {
+    pub fn new(
+        physics: PxPhysicsRef,
+        actor0: Option<PxRigidActorRef>,
+        local_frame_0: &PxTransform,
+        actor1: Option<PxRigidActorRef>,
+        local_frame_1: &PxTransform,
+    ) -> Self {
+        Self(unsafe {
+            physx_sys::phys_PxSphericalJointCreate(
+                physics.0,
+                actor0.map_or(null_mut(), |v| v.0),
+                &local_frame_0.0,
+                actor1.map_or(null_mut(), |v| v.0),
+                &local_frame_1.0,
+            )
+        })
+    }
+    pub fn set_spherical_joint_flag(&self, flag: PxSphericalJointFlag, value: bool) {
+        unsafe { physx_sys::PxSphericalJoint_setSphericalJointFlag_mut(self.0, flag.bits() as _, value) }
+    }
+    pub fn get_limit_cone(&self) -> PxJointLimitCone {
+        PxJointLimitCone::from_physx(unsafe { physx_sys::PxSphericalJoint_getLimitCone(self.0) })
+    }
+    pub fn set_limit_cone(&self, limit: &PxJointLimitCone) {
+        unsafe { physx_sys::PxSphericalJoint_setLimitCone_mut(self.0, &limit.to_physx() as _) }
+    }
+}
+impl AsPxBase for PxSphericalJointRef {
+    fn as_base(&self) -> PxBaseRef {
+        PxBaseRef(self.0 as _)
+    }
+}
+impl AsPxJoint for PxSphericalJointRef {
+    fn as_joint(&self) -> PxJointRef {
+        PxJointRef(self.0 as _)
+    }
+}
+unsafe impl Sync for PxSphericalJointRef {}
+unsafe impl Send for PxSphericalJointRef {}
+
+/// Limits the rotation of a joint to an elliptical cone around its X axis
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PxJointLimitCone {
+    pub restitution: f32,
+    pub bounce_threshold: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub contact_distance: f32,
+    pub y_angle: f32,
+    pub z_angle: f32,
+}
+impl PxJointLimitCone {
+    pub fn new(y_limit_angle: f32, z_limit_angle: f32, contact_dist: f32) -> Self {
+        Self::from_physx(unsafe { physx_sys::PxJointLimitCone_new(y_limit_angle, z_limit_angle, contact_dist) })
+    }
+    fn from_physx(limit: physx_sys::PxJointLimitCone) -> Self {
+        Self {
+            restitution: limit.restitution,
+            bounce_threshold: limit.bounceThreshold,
+            stiffness: limit.stiffness,
+            damping: limit.damping,
+            contact_distance: limit.contactDistance,
+            y_angle: limit.yAngle,
+            z_angle: limit.zAngle,
+        }
+    }
+    fn to_physx(&self) -> physx_sys::PxJointLimitCone {
+        physx_sys::PxJointLimitCone {
+            restitution: self.restitution,
+            bounceThreshold: self.bounce_threshold,
+            stiffness: self.stiffness,
+            damping: self.damping,
+            contactDistance: self.contact_distance,
+            yAngle: self.y_angle,
+            zAngle: self.z_angle,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Tasks for working on Ambient itself; run it from the root of the repository with `cargo run -p campfire -- <task>`
+use clap::Parser;
+
+mod test;
+
+#[derive(Parser, Clone)]
+#[command(author, version, about, long_about = None)]
+enum Cli {
+    /// Run the tests which need more than `cargo test`
+    #[command(subcommand)]
+    Test(test::Test),
+}
+
+fn main() -> anyhow::Result<()> {
+    match Cli::parse() {
+        Cli::Test(test) => test::run(test),
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use clap::Subcommand;
+
+const EXAMPLES_DIR: &str = "guest/rust/examples";
+/// Logged by the app for each module whose `core/module_load` event ran without errors
+const MODULE_LOADED: &str = "] info: Loaded";
+/// Logged by the app for each error reported by a module
+const MODULE_ERROR: &str = "] error: ";
+
+#[derive(Subcommand, Clone)]
+pub enum Test {
+    /// Build every guest example, run each of them headlessly, and report which of them built and loaded their modules
+    Examples {
+        /// Only test the examples whose path contains this
+        filter: Option<String>,
+        /// How long to wait for the modules of an example to load, in seconds
+        #[arg(long, default_value_t = 120)]
+        timeout: u64,
+        /// Build Ambient and the examples in release mode
+        #[arg(long)]
+        release: bool,
+    },
+}
+
+pub fn run(test: Test) -> anyhow::Result<()> {
+    match test {
+        Test::Examples { filter, timeout, release } => test_examples(filter.as_deref(), Duration::from_secs(timeout), release),
+    }
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Passed,
+    /// The example has no modules, so there's nothing to wait for
+    NoModules,
+    BuildFailed,
+    /// Not all the modules loaded before the timeout
+    TimedOut {
+        loaded: usize,
+        expected: usize,
+    },
+    /// Ambient exited before all the modules loaded
+    Exited {
+        loaded: usize,
+        expected: usize,
+    },
+    ModuleError(String),
+}
+impl Outcome {
+    fn is_failure(&self) -> bool {
+        !matches!(self, Self::Passed | Self::NoModules)
+    }
+}
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passed => write!(f, "passed"),
+            Self::NoModules => write!(f, "passed (no modules)"),
+            Self::BuildFailed => write!(f, "FAILED to build"),
+            Self::TimedOut { loaded, expected } => write!(f, "FAILED: timed out with {loaded}/{expected} modules loaded"),
+            Self::Exited { loaded, expected } => write!(f, "FAILED: exited with {loaded}/{expected} modules loaded"),
+            Self::ModuleError(err) => write!(f, "FAILED: {err}"),
+        }
+    }
+}
+
+fn test_examples(filter: Option<&str>, timeout: Duration, release: bool) -> anyhow::Result<()> {
+    let examples_dir = Path::new(EXAMPLES_DIR);
+    anyhow::ensure!(examples_dir.is_dir(), "{EXAMPLES_DIR} not found; campfire must be run from the root of the repository");
+
+    let mut examples = Vec::new();
+    for category in std::fs::read_dir(examples_dir)? {
+        let category = category?.path();
+        if !category.is_dir() {
+            continue;
+        }
+        for example in std::fs::read_dir(&category)? {
+            let example = example?.path();
+            if example.join("ambient.toml").exists() {
+                examples.push(example);
+            }
+        }
+    }
+    examples.retain(|example| filter.map_or(true, |filter| example.to_string_lossy().contains(filter)));
+    examples.sort();
+    anyhow::ensure!(!examples.is_empty(), "No examples to test");
+
+    eprintln!("Building Ambient");
+    let mut cargo = Command::new("cargo");
+    cargo.args(["build", "-p", "ambient"]);
+    if release {
+        cargo.arg("--release");
+    }
+    anyhow::ensure!(cargo.status().context("Failed to run cargo")?.success(), "Failed to build Ambient");
+    let ambient =
+        Path::new("target").join(if release { "release" } else { "debug" }).join(format!("ambient{}", std::env::consts::EXE_SUFFIX));
+
+    let mut results = Vec::new();
+    for example in examples {
+        eprintln!("Testing {}", example.display());
+        let outcome = test_example(&ambient, &example, timeout, release)?;
+        eprintln!("{}: {outcome}", example.display());
+        results.push((example, outcome));
+    }
+
+    let width = results.iter().map(|(example, _)| example.to_string_lossy().len()).max().unwrap_or_default();
+    println!();
+    for (example, outcome) in &results {
+        println!("{:width$}  {outcome}", example.to_string_lossy());
+    }
+    let failures = results.iter().filter(|(_, outcome)| outcome.is_failure()).count();
+    println!();
+    println!("{} passed, {failures} failed", results.len() - failures);
+    anyhow::ensure!(failures == 0, "{failures} examples failed");
+    Ok(())
+}
+
+fn test_example(ambient: &Path, example: &Path, timeout: Duration, release: bool) -> anyhow::Result<Outcome> {
+    let mut build = Command::new(ambient);
+    build.arg("build").arg(example);
+    if release {
+        build.arg("--release");
+    }
+    if !build.status().with_context(|| format!("Failed to run {ambient:?}"))?.success() {
+        return Ok(Outcome::BuildFailed);
+    }
+
+    let expected = ["client", "server"].iter().map(|target| count_modules(&example.join("build").join(target))).sum::<usize>();
+    if expected == 0 {
+        return Ok(Outcome::NoModules);
+    }
+
+    // Running headlessly runs both a server and a client, so both the client and the server modules are loaded
+    let mut run = Command::new(ambient);
+    run.arg("run").arg(example).arg("--headless");
+    if release {
+        run.arg("--release");
+    }
+    let mut child = run.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().with_context(|| format!("Failed to run {ambient:?}"))?;
+    let lines = read_lines(&mut child);
+
+    let started = Instant::now();
+    let mut loaded = 0;
+    let outcome = loop {
+        let Some(remaining) = timeout.checked_sub(started.elapsed()) else { break Outcome::TimedOut { loaded, expected } };
+        match lines.recv_timeout(remaining) {
+            Ok(line) if line.contains(MODULE_ERROR) => break Outcome::ModuleError(line),
+            Ok(line) if line.contains(MODULE_LOADED) => {
+                loaded += 1;
+                if loaded >= expected {
+                    break Outcome::Passed;
+                }
+            }
+            Ok(_) => {}
+            Err(mpsc::RecvTimeoutError::Timeout) => break Outcome::TimedOut { loaded, expected },
+            Err(mpsc::RecvTimeoutError::Disconnected) => break Outcome::Exited { loaded, expected },
+        }
+    };
+    child.kill().ok();
+    child.wait().ok();
+    Ok(outcome)
+}
+
+fn count_modules(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "wasm"))
+                .count()
+        })
+        .unwrap_or_default()
+}
+
+/// Sends the lines of the output of `child` to the receiver, which disconnects once both its stdout and stderr are closed
+fn read_lines(child: &mut Child) -> mpsc::Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    let outputs: [Option<Box<dyn Read + Send>>; 2] =
+        [child.stdout.take().map(|out| Box::new(out) as _), child.stderr.take().map(|err| Box::new(err) as _)];
+    for output in outputs.into_iter().flatten() {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    receiver
+}
@@ -0,0 +1,220 @@
+//! Lets the page embedding the web client control it with `postMessage`. The page sends these to the client's window:
+//!
+//! - `{ type: "message", name, data }` sends a message to the client, as an `EMBED_HOST_MESSAGE` event
+//! - `{ type: "resize", width, height }` resizes the client, in CSS pixels
+//! - `{ type: "visibility", visible }` shows or hides the client
+//! - `{ type: "query_progress" }` is answered with a `progress` message
+//! - `{ type: "get_component", entity, component }` is answered with a `component` message, whose `value` is `null` if the
+//!   entity doesn't have the component
+//! - `{ type: "set_component", entity, component, value }` adds the component to the entity, or replaces its value
+//!
+//! Entities are identified by their base64 id, and components by their full path. Component values are in the JSON form
+//! they're serialized to, e.g. `[x, y, z]` for a `Vec3`. `ambient schema --typescript` generates a typed wrapper for these
+//! messages from the components of a project.
+//!
+//! Only messages from the parent window are handled. The page serving the client can restrict which origins may embed it
+//! with a `<meta name="ambient-embed-origins" content="https://a.example https://b.example">` tag; see [EmbedOptions].
+//!
+//! The client posts these to its parent window, for the origin of the last message it received:
+//!
+//! - `{ type: "ready" }` once it has started, to each allowed origin
+//! - `{ type: "client_message", name, data }` for each `EMBED_CLIENT_MESSAGE` event
+//! - `{ type: "progress", loading, loaded }` with the number of assets which are loading and have loaded
+//! - `{ type: "component", entity, component, value }`
+//! - `{ type: "error", message }`
+use std::collections::VecDeque;
+
+use ambient_core::{
+    asset_cache,
+    embed::{embed_message_data, embed_message_name},
+    window::{window_ctl, WindowCtl},
+};
+use ambient_ecs::{
+    world_events, ComponentDesc, ComponentRegistry, DynSystem, Entity, EntityId, FnSystem, FramedEventsReader, Serializable, World,
+};
+use ambient_event_types::{EMBED_CLIENT_MESSAGE, EMBED_HOST_MESSAGE};
+use anyhow::{anyhow, ensure, Context};
+use glam::uvec2;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum HostMessage {
+    Message {
+        name: String,
+        #[serde(default)]
+        data: serde_json::Value,
+    },
+    Resize {
+        width: u32,
+        height: u32,
+    },
+    Visibility {
+        visible: bool,
+    },
+    QueryProgress,
+    GetComponent {
+        entity: String,
+        component: String,
+    },
+    SetComponent {
+        entity: String,
+        component: String,
+        value: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Ready,
+    ClientMessage { name: String, data: serde_json::Value },
+    Progress { loading: usize, loaded: usize },
+    Component { entity: String, component: String, value: serde_json::Value },
+    Error { message: String },
+}
+
+/// How many messages are kept for the embedding page until it sends one, after which the oldest are dropped
+const MAX_PENDING_MESSAGES: usize = 256;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EmbedOptions {
+    /// The origins of the pages which may control the client, or any origin if `None`
+    pub allowed_origins: Option<Vec<String>>,
+}
+impl EmbedOptions {
+    /// Reads the options from the `ambient-embed-origins` meta tag of the client's document, which lists the allowed
+    /// origins separated by whitespace
+    pub fn from_document() -> Self {
+        let origins = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.query_selector("meta[name=ambient-embed-origins]").ok().flatten())
+            .and_then(|meta| meta.get_attribute("content"));
+        Self { allowed_origins: origins.map(|origins| origins.split_whitespace().map(|origin| origin.to_string()).collect()) }
+    }
+    fn is_allowed(&self, origin: &str) -> bool {
+        self.allowed_origins.as_ref().map_or(true, |origins| origins.iter().any(|allowed| allowed == origin))
+    }
+}
+
+fn queue(pending: &mut VecDeque<ClientMessage>, message: ClientMessage) {
+    if pending.len() == MAX_PENDING_MESSAGES {
+        pending.pop_front();
+    }
+    pending.push_back(message);
+}
+
+fn post(message: &ClientMessage, origin: &str) {
+    let Some(parent) = web_sys::window().and_then(|window| window.parent().ok().flatten()) else { return };
+    let value = serde_json::to_string(message).ok().and_then(|json| js_sys::JSON::parse(&json).ok());
+    if let Some(value) = value {
+        if let Err(err) = parent.post_message(&value, origin) {
+            tracing::warn!("Failed to post {message:?} to the embedding page: {err:?}");
+        }
+    }
+}
+
+/// Listens for the messages of the embedding page, and returns the system which handles them
+pub(crate) fn listen(options: EmbedOptions) -> anyhow::Result<DynSystem> {
+    let window = web_sys::window().context("No window")?;
+    let (tx, rx) = flume::unbounded();
+    let ready_origins = options.allowed_origins.clone().unwrap_or_else(|| vec!["*".to_string()]);
+    let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(move |event: web_sys::MessageEvent| {
+        // Other messages to the window, including the client's own when it isn't in a frame, are ignored
+        let Some(window) = web_sys::window() else { return };
+        let Some(parent) = window.parent().ok().flatten() else { return };
+        let from_parent = event.source().map_or(false, |source| js_sys::Object::is(&source, &parent));
+        if !from_parent || js_sys::Object::is(&parent, &window) || !options.is_allowed(&event.origin()) {
+            return;
+        }
+        let json = js_sys::JSON::stringify(&event.data()).ok().and_then(|json| json.as_string());
+        if let Some(message) = json.and_then(|json| serde_json::from_str::<HostMessage>(&json).ok()) {
+            tx.send((message, event.origin())).ok();
+        }
+    });
+    window.add_event_listener_with_callback("message", on_message.as_ref().unchecked_ref()).map_err(|err| anyhow!("{err:?}"))?;
+    // The client listens for as long as the page is open
+    on_message.forget();
+    for origin in &ready_origins {
+        post(&ClientMessage::Ready, origin);
+    }
+
+    let mut host_origin = None;
+    let mut pending = VecDeque::new();
+    let mut reader = FramedEventsReader::new();
+    Ok(Box::new(FnSystem::new(move |world, _| {
+        for (message, origin) in rx.try_iter() {
+            host_origin = Some(origin);
+            match message {
+                HostMessage::Message { name, data } => {
+                    let event = Entity::new().with(embed_message_name(), name).with(embed_message_data(), data.to_string());
+                    world.resource_mut(world_events()).add_event((EMBED_HOST_MESSAGE.to_string(), event));
+                }
+                HostMessage::Resize { width, height } => {
+                    world.resource(window_ctl()).send(WindowCtl::SetSize(uvec2(width, height))).ok();
+                }
+                HostMessage::Visibility { visible } => {
+                    world.resource(window_ctl()).send(WindowCtl::SetVisible(visible)).ok();
+                }
+                HostMessage::QueryProgress => {
+                    let timeline = world.resource(asset_cache()).timeline.lock();
+                    let loading = timeline.n_loading();
+                    queue(&mut pending, ClientMessage::Progress { loading, loaded: timeline.assets.len() - loading });
+                }
+                HostMessage::GetComponent { entity, component } => {
+                    let message = match get_component(world, &entity, &component) {
+                        Ok(value) => ClientMessage::Component { entity, component, value },
+                        Err(err) => ClientMessage::Error { message: format!("{err:#}") },
+                    };
+                    queue(&mut pending, message);
+                }
+                HostMessage::SetComponent { entity, component, value } => {
+                    if let Err(err) = set_component(world, &entity, &component, &value) {
+                        queue(&mut pending, ClientMessage::Error { message: format!("{err:#}") });
+                    }
+                }
+            }
+        }
+
+        for (_, (name, event)) in reader.iter(world.resource(world_events())) {
+            if name == EMBED_CLIENT_MESSAGE {
+                let data = event.get_ref(embed_message_data()).and_then(|data| serde_json::from_str(data).ok()).unwrap_or_default();
+                queue(
+                    &mut pending,
+                    ClientMessage::ClientMessage { name: event.get_cloned(embed_message_name()).unwrap_or_default(), data },
+                );
+            }
+        }
+
+        // Messages are only posted once the page has sent one, so that they're only seen by that origin
+        if let Some(origin) = &host_origin {
+            for message in pending.drain(..) {
+                post(&message, origin);
+            }
+        }
+    })))
+}
+
+fn component_desc(entity: &str, component: &str) -> anyhow::Result<(EntityId, ComponentDesc)> {
+    let id = entity.parse::<EntityId>().map_err(|_| anyhow!("Invalid entity id {entity:?}"))?;
+    let desc = ComponentRegistry::get().get_by_path(component).with_context(|| format!("Unknown component {component:?}"))?;
+    ensure!(desc.has_attribute::<Serializable>(), "The component {component:?} can't be serialized");
+    Ok((id, desc))
+}
+
+fn get_component(world: &World, entity: &str, component: &str) -> anyhow::Result<serde_json::Value> {
+    let (id, desc) = component_desc(entity, component)?;
+    ensure!(world.exists(id), "The entity {entity} doesn't exist");
+    match world.get_entry(id, desc) {
+        Ok(entry) => Ok(serde_json::from_str(&desc.to_json(&entry)?)?),
+        Err(_) => Ok(serde_json::Value::Null),
+    }
+}
+
+fn set_component(world: &mut World, entity: &str, component: &str, value: &serde_json::Value) -> anyhow::Result<()> {
+    let (id, desc) = component_desc(entity, component)?;
+    let entry = desc.from_json(&value.to_string()).with_context(|| format!("Invalid value for {component:?}"))?;
+    world.add_entry(id, entry).with_context(|| format!("Failed to set {component:?} on {entity}"))?;
+    Ok(())
+}
@@ -2,6 +2,9 @@ use tracing_subscriber::{filter::LevelFilter, fmt::time::UtcTime, prelude::*, re
 use tracing_web::MakeConsoleWriter;
 use wasm_bindgen::prelude::wasm_bindgen;
 
+#[cfg(target_os = "unknown")]
+mod embed;
+
 #[wasm_bindgen(start)]
 async fn start() {
     let fmt_layer = tracing_subscriber::fmt::layer()
@@ -29,12 +32,64 @@ async fn run() -> anyhow::Result<()> {
     use ambient_sys::timer::TimerWheel;
     ambient_sys::task::spawn(TimerWheel::new().start());
 
+    use ambient_settings::Settings;
     use anyhow::Context;
-    App::builder().build().await.context("Failed to build app")?.spawn();
+    // The settings are stored in IndexedDB, which can't be read synchronously when the app is built
+    let settings = Settings::load_async().await.unwrap_or_else(|err| {
+        tracing::warn!("{err:?}; using default settings");
+        Settings::default()
+    });
+    let mut app = App::builder().with_settings(settings).build().await.context("Failed to build app")?;
+
+    let has_touch_screen = web_sys::window().map(|window| window.navigator().max_touch_points() > 0).unwrap_or_default();
+    if has_touch_screen {
+        spawn_touch_controls(&mut app.world);
+    }
+
+    match embed::listen(embed::EmbedOptions::from_document()) {
+        Ok(system) => {
+            app.systems.add(system);
+        }
+        Err(err) => tracing::warn!("Failed to listen for messages from the embedding page: {err:?}"),
+    }
+
+    app.spawn();
 
     Ok(())
 }
 
+/// Shows on-screen controls which act as a gamepad, so that games which support gamepads can be played on touch screens
+#[cfg(target_os = "unknown")]
+fn spawn_touch_controls(world: &mut ambient_ecs::World) {
+    use ambient_element::ElementComponentExt;
+    use ambient_input::gamepad::{gamepads, GamepadAxis, GamepadButton, GamepadEvent, VIRTUAL_GAMEPAD_ID};
+    use ambient_std::cb;
+    use ambient_ui::TouchControls;
+
+    const BUTTONS: [(&str, GamepadButton); 4] =
+        [("A", GamepadButton::South), ("B", GamepadButton::East), ("X", GamepadButton::West), ("Y", GamepadButton::North)];
+
+    let Some(gamepads) = world.resource_opt(gamepads()).cloned() else { return };
+    gamepads.push_event(GamepadEvent::Connected { id: VIRTUAL_GAMEPAD_ID, name: "Touch controls".to_string() });
+
+    TouchControls::el(
+        80.,
+        40.,
+        BUTTONS.iter().map(|(label, _)| label.to_string()).collect(),
+        cb({
+            let gamepads = gamepads.clone();
+            move |position| {
+                gamepads.push_event(GamepadEvent::Axis { id: VIRTUAL_GAMEPAD_ID, axis: GamepadAxis::LeftStickX, value: position.x });
+                gamepads.push_event(GamepadEvent::Axis { id: VIRTUAL_GAMEPAD_ID, axis: GamepadAxis::LeftStickY, value: position.y });
+            }
+        }),
+        cb(move |index, pressed| {
+            gamepads.push_event(GamepadEvent::Button { id: VIRTUAL_GAMEPAD_ID, button: BUTTONS[index].1, pressed });
+        }),
+    )
+    .spawn_interactive(world);
+}
+
 #[cfg(not(target_os = "unknown"))]
 async fn run() -> anyhow::Result<()> {
     unimplemented!("This only builds on the web")